@@ -0,0 +1,244 @@
+package graphics.pathfinder;
+
+/**
+ * A 2D drawing surface with the API of an HTML canvas. Show it with
+ * `PathfinderView.setCanvas()`.
+ *
+ * Colors are Android color ints. Call `destroy()` when done with the canvas.
+ */
+public class PathfinderCanvas {
+    public static final int TEXT_ALIGN_LEFT = 0;
+    public static final int TEXT_ALIGN_RIGHT = 1;
+    public static final int TEXT_ALIGN_CENTER = 2;
+
+    public static final int LINE_CAP_BUTT = 0;
+    public static final int LINE_CAP_ROUND = 1;
+    public static final int LINE_CAP_SQUARE = 2;
+
+    public static final int LINE_JOIN_MITER = 0;
+    public static final int LINE_JOIN_BEVEL = 1;
+    public static final int LINE_JOIN_ROUND = 2;
+
+    private long mNativeCanvas;
+
+    private static native long nativeCreate(float width, float height);
+
+    private static native void nativeDestroy(long canvas);
+
+    private static native void nativeClear(long canvas);
+
+    private static native void nativeFillRect(long canvas, float x, float y, float width,
+                                              float height);
+
+    private static native void nativeStrokeRect(long canvas, float x, float y, float width,
+                                                float height);
+
+    private static native void nativeClearRect(long canvas, float x, float y, float width,
+                                               float height);
+
+    private static native void nativeFillText(long canvas, String text, float x, float y);
+
+    private static native void nativeStrokeText(long canvas, String text, float x, float y);
+
+    private static native float nativeMeasureText(long canvas, String text);
+
+    private static native void nativeSetFont(long canvas, String font);
+
+    private static native void nativeSetTextAlign(long canvas, int textAlign);
+
+    private static native void nativeSetLineWidth(long canvas, float lineWidth);
+
+    private static native void nativeSetLineCap(long canvas, int lineCap);
+
+    private static native void nativeSetLineJoin(long canvas, int lineJoin);
+
+    private static native void nativeSetFillColor(long canvas, int color);
+
+    private static native void nativeSetStrokeColor(long canvas, int color);
+
+    private static native void nativeSetGlobalAlpha(long canvas, float globalAlpha);
+
+    private static native void nativeBeginPath(long canvas);
+
+    private static native void nativeClosePath(long canvas);
+
+    private static native void nativeMoveTo(long canvas, float x, float y);
+
+    private static native void nativeLineTo(long canvas, float x, float y);
+
+    private static native void nativeQuadraticCurveTo(long canvas, float cpx, float cpy, float x,
+                                                      float y);
+
+    private static native void nativeBezierCurveTo(long canvas, float cp1x, float cp1y,
+                                                   float cp2x, float cp2y, float x, float y);
+
+    private static native void nativeArc(long canvas, float x, float y, float radius,
+                                         float startAngle, float endAngle,
+                                         boolean anticlockwise);
+
+    private static native void nativeRect(long canvas, float x, float y, float width,
+                                          float height);
+
+    private static native void nativeFill(long canvas, boolean evenOdd);
+
+    private static native void nativeStroke(long canvas);
+
+    private static native void nativeClip(long canvas, boolean evenOdd);
+
+    private static native void nativeTranslate(long canvas, float x, float y);
+
+    private static native void nativeScale(long canvas, float x, float y);
+
+    private static native void nativeRotate(long canvas, float angle);
+
+    private static native void nativeSave(long canvas);
+
+    private static native void nativeRestore(long canvas);
+
+    static {
+        System.loadLibrary("pathfinder_android");
+    }
+
+    public PathfinderCanvas(float width, float height) {
+        mNativeCanvas = nativeCreate(width, height);
+    }
+
+    public void destroy() {
+        if (mNativeCanvas != 0) {
+            nativeDestroy(mNativeCanvas);
+            mNativeCanvas = 0;
+        }
+    }
+
+    long getNativeCanvas() {
+        return mNativeCanvas;
+    }
+
+    /** Erases everything drawn so far, keeping the drawing state. */
+    public void clear() {
+        nativeClear(mNativeCanvas);
+    }
+
+    public void fillRect(float x, float y, float width, float height) {
+        nativeFillRect(mNativeCanvas, x, y, width, height);
+    }
+
+    public void strokeRect(float x, float y, float width, float height) {
+        nativeStrokeRect(mNativeCanvas, x, y, width, height);
+    }
+
+    public void clearRect(float x, float y, float width, float height) {
+        nativeClearRect(mNativeCanvas, x, y, width, height);
+    }
+
+    public void fillText(String text, float x, float y) {
+        nativeFillText(mNativeCanvas, text, x, y);
+    }
+
+    public void strokeText(String text, float x, float y) {
+        nativeStrokeText(mNativeCanvas, text, x, y);
+    }
+
+    /** Returns the width of the text in the current font. */
+    public float measureText(String text) {
+        return nativeMeasureText(mNativeCanvas, text);
+    }
+
+    /** Takes a CSS `font` shorthand like `"bold 14px sans-serif"`. */
+    public void setFont(String font) {
+        nativeSetFont(mNativeCanvas, font);
+    }
+
+    public void setTextAlign(int textAlign) {
+        nativeSetTextAlign(mNativeCanvas, textAlign);
+    }
+
+    public void setLineWidth(float lineWidth) {
+        nativeSetLineWidth(mNativeCanvas, lineWidth);
+    }
+
+    public void setLineCap(int lineCap) {
+        nativeSetLineCap(mNativeCanvas, lineCap);
+    }
+
+    public void setLineJoin(int lineJoin) {
+        nativeSetLineJoin(mNativeCanvas, lineJoin);
+    }
+
+    public void setFillColor(int color) {
+        nativeSetFillColor(mNativeCanvas, color);
+    }
+
+    public void setStrokeColor(int color) {
+        nativeSetStrokeColor(mNativeCanvas, color);
+    }
+
+    public void setGlobalAlpha(float globalAlpha) {
+        nativeSetGlobalAlpha(mNativeCanvas, globalAlpha);
+    }
+
+    public void beginPath() {
+        nativeBeginPath(mNativeCanvas);
+    }
+
+    public void closePath() {
+        nativeClosePath(mNativeCanvas);
+    }
+
+    public void moveTo(float x, float y) {
+        nativeMoveTo(mNativeCanvas, x, y);
+    }
+
+    public void lineTo(float x, float y) {
+        nativeLineTo(mNativeCanvas, x, y);
+    }
+
+    public void quadraticCurveTo(float cpx, float cpy, float x, float y) {
+        nativeQuadraticCurveTo(mNativeCanvas, cpx, cpy, x, y);
+    }
+
+    public void bezierCurveTo(float cp1x, float cp1y, float cp2x, float cp2y, float x, float y) {
+        nativeBezierCurveTo(mNativeCanvas, cp1x, cp1y, cp2x, cp2y, x, y);
+    }
+
+    public void arc(float x, float y, float radius, float startAngle, float endAngle,
+                    boolean anticlockwise) {
+        nativeArc(mNativeCanvas, x, y, radius, startAngle, endAngle, anticlockwise);
+    }
+
+    public void rect(float x, float y, float width, float height) {
+        nativeRect(mNativeCanvas, x, y, width, height);
+    }
+
+    public void fill(boolean evenOdd) {
+        nativeFill(mNativeCanvas, evenOdd);
+    }
+
+    public void stroke() {
+        nativeStroke(mNativeCanvas);
+    }
+
+    public void clip(boolean evenOdd) {
+        nativeClip(mNativeCanvas, evenOdd);
+    }
+
+    public void translate(float x, float y) {
+        nativeTranslate(mNativeCanvas, x, y);
+    }
+
+    public void scale(float x, float y) {
+        nativeScale(mNativeCanvas, x, y);
+    }
+
+    public void rotate(float angle) {
+        nativeRotate(mNativeCanvas, angle);
+    }
+
+    public void save() {
+        nativeSave(mNativeCanvas);
+    }
+
+    public void restore() {
+        nativeRestore(mNativeCanvas);
+    }
+}
@@ -0,0 +1,107 @@
+package graphics.pathfinder;
+
+import android.content.Context;
+import android.opengl.GLSurfaceView;
+import android.util.AttributeSet;
+
+import javax.microedition.khronos.egl.EGLConfig;
+import javax.microedition.khronos.opengles.GL10;
+
+/**
+ * A view that displays a Pathfinder canvas drawing or SVG file, scaled to fit.
+ *
+ * The view renders with OpenGL ES 3.0 on its own thread, and only redraws when its contents
+ * change. If the context is lost, for example because the app was paused, the renderer is
+ * recreated and the contents are drawn again.
+ */
+public class PathfinderView extends GLSurfaceView {
+    private long mNativeView;
+
+    private static native long nativeCreate();
+
+    private static native void nativeDestroy(long view);
+
+    private static native void nativeSurfaceCreated(long view);
+
+    private static native void nativeSurfaceChanged(long view, int width, int height);
+
+    private static native void nativeDrawFrame(long view);
+
+    private static native void nativeSetCanvas(long view, long canvas);
+
+    private static native boolean nativeLoadSVG(long view, byte[] data);
+
+    private static native void nativeSetBackgroundColor(long view, int color);
+
+    static {
+        System.loadLibrary("pathfinder_android");
+    }
+
+    public PathfinderView(Context context) {
+        super(context);
+        init();
+    }
+
+    public PathfinderView(Context context, AttributeSet attrs) {
+        super(context, attrs);
+        init();
+    }
+
+    private void init() {
+        mNativeView = nativeCreate();
+        setEGLContextClientVersion(3);
+        setPreserveEGLContextOnPause(true);
+        setRenderer(new Renderer());
+        setRenderMode(RENDERMODE_WHEN_DIRTY);
+    }
+
+    /** Displays a copy of everything drawn on the canvas so far. */
+    public void setCanvas(PathfinderCanvas canvas) {
+        nativeSetCanvas(mNativeView, canvas.getNativeCanvas());
+        requestRender();
+    }
+
+    /**
+     * Displays an SVG file. Returns false, leaving the current contents, if the data isn't valid
+     * SVG.
+     */
+    public boolean loadSVG(byte[] data) {
+        if (!nativeLoadSVG(mNativeView, data))
+            return false;
+        requestRender();
+        return true;
+    }
+
+    @Override
+    public void setBackgroundColor(int color) {
+        nativeSetBackgroundColor(mNativeView, color);
+        requestRender();
+    }
+
+    @Override
+    protected void onDetachedFromWindow() {
+        // This stops the renderer thread and destroys the context first.
+        super.onDetachedFromWindow();
+        if (mNativeView != 0) {
+            nativeDestroy(mNativeView);
+            mNativeView = 0;
+        }
+    }
+
+    private class Renderer implements GLSurfaceView.Renderer {
+        @Override
+        public void onSurfaceCreated(GL10 gl, EGLConfig config) {
+            nativeSurfaceCreated(mNativeView);
+        }
+
+        @Override
+        public void onSurfaceChanged(GL10 gl, int width, int height) {
+            nativeSurfaceChanged(mNativeView, width, height);
+        }
+
+        @Override
+        public void onDrawFrame(GL10 gl) {
+            nativeDrawFrame(mNativeView);
+        }
+    }
+}
@@ -0,0 +1,692 @@
+// pathfinder/android/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Android integration: JNI entry points behind the `graphics.pathfinder.PathfinderView` and
+//! `graphics.pathfinder.PathfinderCanvas` Java classes in `java/`.
+//!
+//! `PathfinderView` is a `GLSurfaceView` that owns the OpenGL ES context. Its renderer thread
+//! calls `nativeSurfaceCreated()`, `nativeSurfaceChanged()`, and `nativeDrawFrame()`, while the
+//! UI thread replaces the displayed scene with a canvas drawing or a parsed SVG.
+
+use jni::objects::{JClass, JString};
+use jni::sys::{jboolean, jbyteArray, jfloat, jint, jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+use pathfinder_canvas::{ArcDirection, Canvas, CanvasFontContext, CanvasRenderingContext2D};
+use pathfinder_canvas::{FillRule, LineCap, LineJoin, Path2D, TextAlign};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use pathfinder_svg::SVGScene;
+use std::cell::RefCell;
+use std::mem;
+use std::os::raw::c_void;
+use std::sync::Mutex;
+use usvg::Options;
+
+// The native side of a `PathfinderView`.
+//
+// The scene is shared between the UI thread, which replaces it, and the renderer thread, which
+// draws it. Everything else belongs to the renderer thread.
+struct AndroidView {
+    scene: Mutex<SceneState>,
+    gl: RefCell<Option<GLState>>,
+}
+
+struct SceneState {
+    scene: Scene,
+    background_color: ColorF,
+}
+
+// The objects that live in the view's OpenGL ES context.
+struct GLState {
+    renderer: Renderer<GLDevice>,
+    size: Vector2I,
+}
+
+// The native side of a `PathfinderCanvas`.
+struct AndroidCanvas {
+    context: CanvasRenderingContext2D,
+    path: Path2D,
+}
+
+// Views
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeCreate(
+    _: JNIEnv,
+    _: JClass,
+) -> jlong {
+    Box::into_raw(Box::new(AndroidView {
+        scene: Mutex::new(SceneState {
+            scene: Scene::new(),
+            background_color: ColorF::white(),
+        }),
+        gl: RefCell::new(None),
+    })) as jlong
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeDestroy(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+) {
+    let view = *Box::from_raw(view as *mut AndroidView);
+
+    // By the time the view is destroyed, `GLSurfaceView` has torn down the context, and the
+    // objects in it with it.
+    mem::forget(view.gl.into_inner());
+}
+
+/// Called on the renderer thread whenever `GLSurfaceView` creates an OpenGL ES context: once at
+/// startup, and again after the context was lost, for example while the app was paused.
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeSurfaceCreated(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+) {
+    let view = &*(view as *const AndroidView);
+    gl::load_with(|name| egl::get_proc_address(name) as *const c_void);
+
+    // A renderer left over from a lost context refers to textures, buffers, and programs that
+    // died with it. Dropping it would delete whatever objects happen to have the same names in
+    // the new context, so leak it instead. A new renderer is created once the size is known.
+    if let Some(old_gl_state) = view.gl.borrow_mut().take() {
+        mem::forget(old_gl_state);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeSurfaceChanged(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+    width: jint,
+    height: jint,
+) {
+    let view = &*(view as *const AndroidView);
+    let size = vec2i(width, height);
+
+    let mut gl_state = view.gl.borrow_mut();
+    match *gl_state {
+        Some(ref mut gl_state) => {
+            gl_state.size = size;
+            gl_state.renderer.options_mut().dest = DestFramebuffer::full_window(size);
+            gl_state.renderer.dest_framebuffer_size_changed();
+        }
+        None => {
+            let device = GLDevice::new(GLVersion::GLES3, 0);
+            let mode = RendererMode::default_for_device(&device);
+            let options = RendererOptions {
+                dest: DestFramebuffer::full_window(size),
+                ..RendererOptions::default()
+            };
+            let renderer = Renderer::new(device, &EmbeddedResourceLoader::new(), mode, options);
+            *gl_state = Some(GLState { renderer, size });
+        }
+    }
+}
+
+/// Draws the scene, scaled to fit the surface and centered in it.
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeDrawFrame(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+) {
+    let view = &*(view as *const AndroidView);
+    let mut gl_state = view.gl.borrow_mut();
+    let gl_state = match *gl_state {
+        Some(ref mut gl_state) => gl_state,
+        None => return,
+    };
+
+    let mut scene_state = view.scene.lock().unwrap();
+    let SceneState {
+        ref mut scene,
+        background_color,
+    } = *scene_state;
+    gl_state.renderer.options_mut().background_color = Some(background_color);
+
+    // The view box is in device pixels once the transform is applied, so swap in the surface
+    // bounds while building.
+    let view_box = scene.view_box();
+    let surface_rect = RectF::new(Vector2F::zero(), gl_state.size.to_f32());
+    let build_options = BuildOptions {
+        transform: RenderTransform::Transform2D(fit_transform(view_box, surface_rect.size())),
+        ..BuildOptions::default()
+    };
+    scene.set_view_box(surface_rect);
+    scene.build_and_render(&mut gl_state.renderer, build_options, SequentialExecutor);
+    scene.set_view_box(view_box);
+}
+
+/// Displays a copy of everything drawn on the canvas so far.
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeSetCanvas(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+    canvas: jlong,
+) {
+    let view = &*(view as *const AndroidView);
+    let canvas = &*(canvas as *const AndroidCanvas);
+    view.scene.lock().unwrap().scene = canvas.context.canvas().scene().clone();
+}
+
+/// Parses and displays an SVG file. Returns false, leaving the current scene, if the data isn't
+/// valid SVG.
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeLoadSVG(
+    env: JNIEnv,
+    _: JClass,
+    view: jlong,
+    data: jbyteArray,
+) -> jboolean {
+    let view = &*(view as *const AndroidView);
+    let data = env.convert_byte_array(data).unwrap();
+    match SVGScene::from_data(&data, &Options::default().to_ref()) {
+        Ok(svg) => {
+            view.scene.lock().unwrap().scene = svg.scene;
+            JNI_TRUE
+        }
+        Err(_) => JNI_FALSE,
+    }
+}
+
+/// Takes an Android color int (`0xAARRGGBB`).
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderView_nativeSetBackgroundColor(
+    _: JNIEnv,
+    _: JClass,
+    view: jlong,
+    color: jint,
+) {
+    let view = &*(view as *const AndroidView);
+    view.scene.lock().unwrap().background_color = to_rust_color(color).to_f32();
+}
+
+// Canvases
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeCreate(
+    _: JNIEnv,
+    _: JClass,
+    width: jfloat,
+    height: jfloat,
+) -> jlong {
+    let font_context = CanvasFontContext::from_system_source();
+    let context = Canvas::new(vec2f(width, height)).get_context_2d(font_context);
+    Box::into_raw(Box::new(AndroidCanvas {
+        context,
+        path: Path2D::new(),
+    })) as jlong
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeDestroy(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    drop(Box::from_raw(canvas as *mut AndroidCanvas));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeClear(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    (*(canvas as *mut AndroidCanvas)).context.clear();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeFillRect(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+    width: jfloat,
+    height: jfloat,
+) {
+    let rect = RectF::new(vec2f(x, y), vec2f(width, height));
+    (*(canvas as *mut AndroidCanvas)).context.fill_rect(rect);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeStrokeRect(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+    width: jfloat,
+    height: jfloat,
+) {
+    let rect = RectF::new(vec2f(x, y), vec2f(width, height));
+    (*(canvas as *mut AndroidCanvas)).context.stroke_rect(rect);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeClearRect(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+    width: jfloat,
+    height: jfloat,
+) {
+    let rect = RectF::new(vec2f(x, y), vec2f(width, height));
+    (*(canvas as *mut AndroidCanvas)).context.clear_rect(rect);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeFillText(
+    env: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    text: JString,
+    x: jfloat,
+    y: jfloat,
+) {
+    let text: String = env.get_string(text).unwrap().into();
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .fill_text(&text, vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeStrokeText(
+    env: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    text: JString,
+    x: jfloat,
+    y: jfloat,
+) {
+    let text: String = env.get_string(text).unwrap().into();
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .stroke_text(&text, vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeMeasureText(
+    env: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    text: JString,
+) -> jfloat {
+    let text: String = env.get_string(text).unwrap().into();
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .measure_text(&text)
+        .width()
+}
+
+/// Takes a CSS `font` shorthand like `"bold 14px sans-serif"`.
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetFont(
+    env: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    font: JString,
+) {
+    let font: String = env.get_string(font).unwrap().into();
+    (*(canvas as *mut AndroidCanvas)).context.set_font(&font);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetTextAlign(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    new_text_align: jint,
+) {
+    let new_text_align = match new_text_align {
+        0 => TextAlign::Left,
+        1 => TextAlign::Right,
+        2 => TextAlign::Center,
+        _ => panic!("Invalid Pathfinder text alignment style!"),
+    };
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_text_align(new_text_align);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetLineWidth(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    new_line_width: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_line_width(new_line_width);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetLineCap(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    new_line_cap: jint,
+) {
+    let new_line_cap = match new_line_cap {
+        0 => LineCap::Butt,
+        1 => LineCap::Round,
+        2 => LineCap::Square,
+        _ => panic!("Invalid Pathfinder line cap style!"),
+    };
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_line_cap(new_line_cap);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetLineJoin(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    new_line_join: jint,
+) {
+    let new_line_join = match new_line_join {
+        0 => LineJoin::Miter,
+        1 => LineJoin::Bevel,
+        2 => LineJoin::Round,
+        _ => panic!("Invalid Pathfinder line join style!"),
+    };
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_line_join(new_line_join);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetFillColor(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    color: jint,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_fill_style(to_rust_color(color));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetStrokeColor(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    color: jint,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_stroke_style(to_rust_color(color));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSetGlobalAlpha(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    new_global_alpha: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .set_global_alpha(new_global_alpha);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeBeginPath(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    (*(canvas as *mut AndroidCanvas)).path = Path2D::new();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeClosePath(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    (*(canvas as *mut AndroidCanvas)).path.close_path();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeMoveTo(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas)).path.move_to(vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeLineTo(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas)).path.line_to(vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeQuadraticCurveTo(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    cpx: jfloat,
+    cpy: jfloat,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .path
+        .quadratic_curve_to(vec2f(cpx, cpy), vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeBezierCurveTo(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    cp1x: jfloat,
+    cp1y: jfloat,
+    cp2x: jfloat,
+    cp2y: jfloat,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas)).path.bezier_curve_to(
+        vec2f(cp1x, cp1y),
+        vec2f(cp2x, cp2y),
+        vec2f(x, y),
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeArc(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+    radius: jfloat,
+    start_angle: jfloat,
+    end_angle: jfloat,
+    anticlockwise: jboolean,
+) {
+    let direction = if anticlockwise == JNI_TRUE {
+        ArcDirection::CCW
+    } else {
+        ArcDirection::CW
+    };
+    (*(canvas as *mut AndroidCanvas)).path.arc(
+        vec2f(x, y),
+        radius,
+        start_angle,
+        end_angle,
+        direction,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeRect(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+    width: jfloat,
+    height: jfloat,
+) {
+    let rect = RectF::new(vec2f(x, y), vec2f(width, height));
+    (*(canvas as *mut AndroidCanvas)).path.rect(rect);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeFill(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    even_odd: jboolean,
+) {
+    let canvas = &mut *(canvas as *mut AndroidCanvas);
+    let fill_rule = if even_odd == JNI_TRUE {
+        FillRule::EvenOdd
+    } else {
+        FillRule::Winding
+    };
+    canvas.context.fill_path(canvas.path.clone(), fill_rule);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeStroke(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    let canvas = &mut *(canvas as *mut AndroidCanvas);
+    canvas.context.stroke_path(canvas.path.clone());
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeClip(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    even_odd: jboolean,
+) {
+    let canvas = &mut *(canvas as *mut AndroidCanvas);
+    let fill_rule = if even_odd == JNI_TRUE {
+        FillRule::EvenOdd
+    } else {
+        FillRule::Winding
+    };
+    canvas.context.clip_path(canvas.path.clone(), fill_rule);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeTranslate(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas))
+        .context
+        .translate(vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeScale(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    x: jfloat,
+    y: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas)).context.scale(vec2f(x, y));
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeRotate(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+    angle: jfloat,
+) {
+    (*(canvas as *mut AndroidCanvas)).context.rotate(angle);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeSave(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    (*(canvas as *mut AndroidCanvas)).context.save();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_graphics_pathfinder_PathfinderCanvas_nativeRestore(
+    _: JNIEnv,
+    _: JClass,
+    canvas: jlong,
+) {
+    (*(canvas as *mut AndroidCanvas)).context.restore();
+}
+
+// Helpers
+
+// Scales the view box to fit inside a surface of the given size, centered, keeping its aspect
+// ratio.
+fn fit_transform(view_box: RectF, surface_size: Vector2F) -> Transform2F {
+    if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+        return Transform2F::default();
+    }
+    let scale = surface_size / view_box.size();
+    let scale = f32::min(scale.x(), scale.y());
+    let offset = (surface_size - view_box.size() * scale) * 0.5;
+    Transform2F::from_translation(offset)
+        * Transform2F::from_scale(scale)
+        * Transform2F::from_translation(-view_box.origin())
+}
+
+fn to_rust_color(color: jint) -> ColorU {
+    let color = color as u32;
+    ColorU::new(
+        (color >> 16) as u8,
+        (color >> 8) as u8,
+        color as u8,
+        (color >> 24) as u8,
+    )
+}
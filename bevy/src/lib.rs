@@ -0,0 +1,304 @@
+// pathfinder/bevy/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A Bevy plugin that renders Pathfinder scenes and SVGs into textures.
+//!
+//! Add `PathfinderPlugin` to the app, then give an entity a `VectorImage` component. Whenever the
+//! component changes, or the SVG it shows finishes loading or is reloaded, the plugin renders it
+//! into the component's target image. Use that image like any other: as a sprite or UI image
+//! texture, or in a material for world-space vector art. Since the source is vector data, you can
+//! raise the image size to re-render it sharply at any resolution.
+//!
+//! `.svg` files load as `SvgAsset`s through the asset server.
+//!
+//! Rendering happens on the main thread with a wgpu device that the plugin creates for itself, so
+//! the rendered pixels are copied into Bevy's image assets.
+
+use bevy::app::{App, Plugin, PostUpdate};
+use bevy::asset::io::Reader;
+use bevy::asset::{Asset, AssetApp, AssetEvent, AssetId, AssetLoader, Assets, AsyncReadExt};
+use bevy::asset::{Handle, LoadContext};
+use bevy::ecs::prelude::ResMut;
+use bevy::ecs::prelude::{Component, Entity, EventReader, Local, NonSendMut, Query, Ref, Res};
+use bevy::log::error;
+use bevy::math::UVec2;
+use bevy::reflect::TypePath;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::Image;
+use bevy::utils::{BoxedFuture, HashSet};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::{RenderError, Renderer};
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use pathfinder_svg::SVGScene;
+use pathfinder_wgpu::WgpuDevice;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use usvg::Options;
+
+/// Renders `VectorImage` components into their target images and loads `.svg` files.
+pub struct PathfinderPlugin;
+
+impl Plugin for PathfinderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SvgAsset>()
+            .register_asset_loader(SvgLoader)
+            .insert_non_send_resource(PathfinderContext::new())
+            .add_systems(PostUpdate, render_vector_images);
+    }
+}
+
+/// An SVG file, converted to a Pathfinder scene at its intrinsic size.
+#[derive(Asset, TypePath)]
+pub struct SvgAsset {
+    pub scene: Scene,
+}
+
+/// Vector art that the plugin renders into `target`.
+#[derive(Component)]
+pub struct VectorImage {
+    /// What to draw. The view box of the scene is stretched to fill the image.
+    pub source: VectorSource,
+    /// The size of the image to render, in pixels.
+    pub size: UVec2,
+    /// The image that the art is rendered into. It's replaced each time the art is rendered.
+    pub target: Handle<Image>,
+}
+
+/// The contents of a `VectorImage`.
+pub enum VectorSource {
+    Scene(Scene),
+    Svg(Handle<SvgAsset>),
+}
+
+impl VectorImage {
+    /// Creates a vector image with a new, transparent target image of the given size.
+    ///
+    /// The art is rendered into the target after the next update.
+    pub fn new(source: VectorSource, size: UVec2, images: &mut Assets<Image>) -> VectorImage {
+        let pixels = vec![0; size.x as usize * size.y as usize * 4];
+        let target = images.add(new_image(size, pixels));
+        VectorImage {
+            source,
+            size,
+            target,
+        }
+    }
+}
+
+// The renderer is created once, since creating it compiles its shaders. The wgpu device isn't
+// `Send`, so this is a non-send resource.
+struct PathfinderContext {
+    renderer: Renderer<WgpuDevice>,
+}
+
+impl PathfinderContext {
+    fn new() -> PathfinderContext {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))
+        .expect("No wgpu adapter is available!");
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("pathfinder"),
+                required_features: adapter.features() & WgpuDevice::optional_features(),
+                required_limits: adapter.limits(),
+            },
+            None,
+        ))
+        .expect("Failed to create a wgpu device!");
+
+        // Everything is rendered off-screen, so the main texture is only a placeholder.
+        let main_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("pathfinder main"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let device = WgpuDevice::new(&adapter, device, queue, main_texture);
+        let mode = RendererMode::default_for_device(&device);
+        let options = RendererOptions {
+            dest: DestFramebuffer::full_window(vec2i(1, 1)),
+            ..RendererOptions::default()
+        };
+        let renderer = Renderer::new(device, &EmbeddedResourceLoader::new(), mode, options);
+        PathfinderContext { renderer }
+    }
+
+    fn render(&mut self, mut scene: Scene, size: UVec2) -> Result<Image, RenderError> {
+        let pixel_size = vec2i(size.x as i32, size.y as i32).max(vec2i(1, 1));
+        let view_box = scene.view_box();
+        let transform = Transform2F::from_scale(pixel_size.to_f32() / view_box.size())
+            * Transform2F::from_translation(-view_box.origin());
+        let build_options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+
+        let image = self.renderer.render_to_image(
+            &mut scene,
+            pixel_size,
+            build_options,
+            SequentialExecutor,
+        )?;
+        let pixels = image
+            .pixels()
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect();
+        Ok(new_image(to_uvec2(pixel_size), pixels))
+    }
+}
+
+fn render_vector_images(
+    mut context: NonSendMut<PathfinderContext>,
+    mut pending: Local<HashSet<Entity>>,
+    mut svg_events: EventReader<AssetEvent<SvgAsset>>,
+    vector_images: Query<(Entity, Ref<VectorImage>)>,
+    svgs: Res<Assets<SvgAsset>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let reloaded_svgs: HashSet<AssetId<SvgAsset>> = svg_events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::Modified { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, vector_image) in vector_images.iter() {
+        let needs_render = vector_image.is_changed()
+            || pending.contains(&entity)
+            || match vector_image.source {
+                VectorSource::Svg(ref handle) => reloaded_svgs.contains(&handle.id()),
+                VectorSource::Scene(_) => false,
+            };
+        if !needs_render {
+            continue;
+        }
+
+        let scene = match vector_image.source {
+            VectorSource::Scene(ref scene) => scene.clone(),
+            VectorSource::Svg(ref handle) => match svgs.get(handle) {
+                Some(svg) => svg.scene.clone(),
+                None => {
+                    // Try again once the SVG has loaded.
+                    pending.insert(entity);
+                    continue;
+                }
+            },
+        };
+        pending.remove(&entity);
+
+        match context.render(scene, vector_image.size) {
+            Ok(image) => {
+                images.insert(&vector_image.target, image);
+            }
+            Err(error) => error!("failed to render vector image: {}", error),
+        }
+    }
+
+    // Forget entities that were despawned while waiting for their SVGs.
+    pending.retain(|&entity| vector_images.contains(entity));
+}
+
+fn new_image(size: UVec2, pixels: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn to_uvec2(size: Vector2I) -> UVec2 {
+    UVec2::new(size.x() as u32, size.y() as u32)
+}
+
+/// Loads `.svg` files as `SvgAsset`s.
+#[derive(Default)]
+pub struct SvgLoader;
+
+impl AssetLoader for SvgLoader {
+    type Asset = SvgAsset;
+    type Settings = ();
+    type Error = SvgLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SvgAsset, SvgLoaderError>> {
+        Box::pin(async move {
+            let mut data = vec![];
+            reader.read_to_end(&mut data).await?;
+            let svg = SVGScene::from_data(&data, &Options::default().to_ref())?;
+            Ok(SvgAsset { scene: svg.scene })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["svg"]
+    }
+}
+
+/// An error that occurred while loading an SVG.
+#[derive(Debug)]
+pub enum SvgLoaderError {
+    Io(io::Error),
+    Svg(usvg::Error),
+}
+
+impl From<io::Error> for SvgLoaderError {
+    fn from(error: io::Error) -> SvgLoaderError {
+        SvgLoaderError::Io(error)
+    }
+}
+
+impl From<usvg::Error> for SvgLoaderError {
+    fn from(error: usvg::Error) -> SvgLoaderError {
+        SvgLoaderError::Svg(error)
+    }
+}
+
+impl Display for SvgLoaderError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            SvgLoaderError::Io(ref error) => write!(formatter, "I/O error: {}", error),
+            SvgLoaderError::Svg(ref error) => write!(formatter, "SVG error: {}", error),
+        }
+    }
+}
+
+impl Error for SvgLoaderError {}
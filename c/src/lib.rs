@@ -12,12 +12,17 @@
 
 use font_kit::handle::Handle;
 use gl;
-use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, FillStyle, LineJoin};
-use pathfinder_canvas::{Path2D, TextAlign, TextBaseline, TextMetrics};
-use pathfinder_color::{ColorF, ColorU};
+use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, CompositeOperation};
+use pathfinder_canvas::{CornerRadii, FillStyle, ImageData, ImageSmoothingQuality, LineJoin};
+use pathfinder_canvas::{Path2D, PatternRepetition, TextAlign, TextBaseline};
+use pathfinder_canvas::{TextDirection, TextMetrics};
+use pathfinder_color::{color_slice_to_u8_slice, u8_slice_to_color_slice, ColorF, ColorU};
 use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::Gradient;
 use pathfinder_content::outline::ArcDirection;
+use pathfinder_content::pattern::{Image, Pattern};
 use pathfinder_content::stroke::LineCap;
+use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::transform3d::{Perspective, Transform4F};
@@ -26,15 +31,16 @@ use pathfinder_gl::{GLDevice, GLVersion};
 use pathfinder_gpu::Device;
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererLevel};
-use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::options::{DestFramebuffer, FillMode, RendererLevel};
+use pathfinder_renderer::gpu::options::{MemoryBudget, RendererMode, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
 use pathfinder_renderer::options::{BuildOptions, RenderTransform};
-use pathfinder_renderer::scene::Scene;
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, DrawPathId, Scene};
 use pathfinder_resources::embedded::EmbeddedResourceLoader;
 use pathfinder_resources::fs::FilesystemResourceLoader;
 use pathfinder_resources::ResourceLoader;
-use pathfinder_simd::default::F32x4;
+use pathfinder_simd::default::{F32x2, F32x4};
 use pathfinder_svg::SVGScene;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_void};
@@ -42,14 +48,19 @@ use std::path::PathBuf;
 use std::ptr;
 use std::slice;
 use std::str;
+use std::sync::Arc;
 use usvg::{Options, Tree};
 
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 use io_surface::IOSurfaceRef;
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
-use metal::{self, CoreAnimationDrawableRef, DeviceRef as NativeMetalDeviceRef};
+use metal::DeviceRef as NativeMetalDeviceRef;
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+use metal::{self, CoreAnimationDrawableRef, CoreAnimationLayerRef};
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 use pathfinder_metal::MetalDevice;
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+use pathfinder_metal_layer::MetalLayerRenderer;
 
 // Constants
 
@@ -66,6 +77,8 @@ pub const PF_LINE_JOIN_ROUND: u8 = 2;
 pub const PF_TEXT_ALIGN_LEFT: u8 = 0;
 pub const PF_TEXT_ALIGN_CENTER: u8 = 1;
 pub const PF_TEXT_ALIGN_RIGHT: u8 = 2;
+pub const PF_TEXT_ALIGN_START: u8 = 3;
+pub const PF_TEXT_ALIGN_END: u8 = 4;
 
 pub const PF_TEXT_BASELINE_ALPHABETIC: u8 = 0;
 pub const PF_TEXT_BASELINE_TOP: u8 = 1;
@@ -74,25 +87,75 @@ pub const PF_TEXT_BASELINE_MIDDLE: u8 = 3;
 pub const PF_TEXT_BASELINE_IDEOGRAPHIC: u8 = 4;
 pub const PF_TEXT_BASELINE_BOTTOM: u8 = 5;
 
+pub const PF_TEXT_DIRECTION_LTR: u8 = 0;
+pub const PF_TEXT_DIRECTION_RTL: u8 = 1;
+
+pub const PF_PATTERN_REPETITION_REPEAT: u8 = 0;
+pub const PF_PATTERN_REPETITION_REPEAT_X: u8 = 1;
+pub const PF_PATTERN_REPETITION_REPEAT_Y: u8 = 2;
+pub const PF_PATTERN_REPETITION_NO_REPEAT: u8 = 3;
+
+pub const PF_IMAGE_SMOOTHING_QUALITY_LOW: u8 = 0;
+pub const PF_IMAGE_SMOOTHING_QUALITY_MEDIUM: u8 = 1;
+pub const PF_IMAGE_SMOOTHING_QUALITY_HIGH: u8 = 2;
+
+pub const PF_COMPOSITE_OPERATION_SOURCE_OVER: u8 = 0;
+pub const PF_COMPOSITE_OPERATION_SOURCE_IN: u8 = 1;
+pub const PF_COMPOSITE_OPERATION_SOURCE_OUT: u8 = 2;
+pub const PF_COMPOSITE_OPERATION_SOURCE_ATOP: u8 = 3;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_OVER: u8 = 4;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_IN: u8 = 5;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_OUT: u8 = 6;
+pub const PF_COMPOSITE_OPERATION_DESTINATION_ATOP: u8 = 7;
+pub const PF_COMPOSITE_OPERATION_LIGHTER: u8 = 8;
+pub const PF_COMPOSITE_OPERATION_COPY: u8 = 9;
+pub const PF_COMPOSITE_OPERATION_XOR: u8 = 10;
+pub const PF_COMPOSITE_OPERATION_MULTIPLY: u8 = 11;
+pub const PF_COMPOSITE_OPERATION_SCREEN: u8 = 12;
+pub const PF_COMPOSITE_OPERATION_OVERLAY: u8 = 13;
+pub const PF_COMPOSITE_OPERATION_DARKEN: u8 = 14;
+pub const PF_COMPOSITE_OPERATION_LIGHTEN: u8 = 15;
+pub const PF_COMPOSITE_OPERATION_COLOR_DODGE: u8 = 16;
+pub const PF_COMPOSITE_OPERATION_COLOR_BURN: u8 = 17;
+pub const PF_COMPOSITE_OPERATION_HARD_LIGHT: u8 = 18;
+pub const PF_COMPOSITE_OPERATION_SOFT_LIGHT: u8 = 19;
+pub const PF_COMPOSITE_OPERATION_DIFFERENCE: u8 = 20;
+pub const PF_COMPOSITE_OPERATION_EXCLUSION: u8 = 21;
+pub const PF_COMPOSITE_OPERATION_HUE: u8 = 22;
+pub const PF_COMPOSITE_OPERATION_SATURATION: u8 = 23;
+pub const PF_COMPOSITE_OPERATION_COLOR: u8 = 24;
+pub const PF_COMPOSITE_OPERATION_LUMINOSITY: u8 = 25;
+
 // `content`
 
 pub const PF_ARC_DIRECTION_CW: u8 = 0;
 pub const PF_ARC_DIRECTION_CCW: u8 = 1;
 
+pub const PF_FILL_RULE_WINDING: u8 = 0;
+pub const PF_FILL_RULE_EVEN_ODD: u8 = 1;
+
 // `gl`
 
 pub const PF_GL_VERSION_GL3: u8 = 0;
 pub const PF_GL_VERSION_GLES3: u8 = 1;
 pub const PF_GL_VERSION_GL4: u8 = 2;
+pub const PF_GL_VERSION_GLES2: u8 = 3;
 
 // `renderer`
 
 pub const PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR: u8 = 0x1;
 pub const PF_RENDERER_OPTIONS_FLAGS_SHOW_DEBUG_UI: u8 = 0x2;
+pub const PF_RENDERER_OPTIONS_FLAGS_SUPERSAMPLE_FILLS: u8 = 0x4;
+pub const PF_RENDERER_OPTIONS_FLAGS_DITHER: u8 = 0x8;
 
 pub const PF_RENDERER_LEVEL_D3D9: u8 = 0x1;
 pub const PF_RENDERER_LEVEL_D3D11: u8 = 0x2;
 
+/// Returned instead of a draw path ID when the draw path can't be added to the scene.
+pub const PF_INVALID_DRAW_PATH_ID: u32 = 0xffff_ffff;
+/// Returned instead of a clip path ID when the clip path can't be added to the scene.
+pub const PF_INVALID_CLIP_PATH_ID: u32 = 0xffff_ffff;
+
 // Types
 
 // External: `font-kit`
@@ -108,12 +171,31 @@ pub type PFLineJoin = u8;
 pub type PFArcDirection = u8;
 pub type PFTextAlign = u8;
 pub type PFTextBaseline = u8;
+pub type PFTextDirection = u8;
+pub type PFPatternRepetition = u8;
+pub type PFImageSmoothingQuality = u8;
+pub type PFCompositeOperation = u8;
 #[repr(C)]
 pub struct PFTextMetrics {
     pub width: f32,
+    pub actual_bounding_box_left: f32,
+    pub actual_bounding_box_right: f32,
+    pub font_bounding_box_ascent: f32,
+    pub font_bounding_box_descent: f32,
+    pub actual_bounding_box_ascent: f32,
+    pub actual_bounding_box_descent: f32,
+    pub em_height_ascent: f32,
+    pub em_height_descent: f32,
+    pub hanging_baseline: f32,
+    pub alphabetic_baseline: f32,
+    pub ideographic_baseline: f32,
 }
 
 // `content`
+pub type PFGradientRef = *mut Gradient;
+pub type PFPatternRef = *mut Pattern;
+pub type PFImageRef = *mut Image;
+pub type PFFillRule = u8;
 #[repr(C)]
 pub struct PFColorF {
     pub r: f32,
@@ -211,9 +293,22 @@ pub struct ResourceLoaderWrapper(Box<dyn ResourceLoader>);
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 pub type PFMetalDeviceRef = *mut MetalDevice;
 
+// `metal_layer`
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+pub type PFMetalLayerRendererRef = *mut MetalLayerRenderer;
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+pub type PFMetalLayerRenderCallback =
+    extern "C" fn(renderer: PFMetalRendererRef, userdata: *mut c_void);
+
 // `renderer`
 pub type PFSceneRef = *mut Scene;
 pub type PFSceneProxyRef = *mut SceneProxy;
+pub type PFPaintRef = *mut Paint;
+pub type PFDrawPathRef = *mut DrawPath;
+pub type PFClipPathRef = *mut ClipPath;
+pub type PFPaintId = u16;
+pub type PFDrawPathId = u32;
+pub type PFClipPathId = u32;
 #[repr(C)]
 pub struct PFRendererMode {
     pub level: PFRendererLevel,
@@ -229,6 +324,12 @@ pub type PFRendererOptionsFlags = u8;
 pub type PFBuildOptionsRef = *mut BuildOptions;
 pub type PFRenderTransformRef = *mut RenderTransform;
 pub type PFRendererLevel = u8;
+/// A limit of zero means no limit.
+#[repr(C)]
+pub struct PFMemoryBudget {
+    pub max_mask_framebuffer_bytes: u64,
+    pub max_tile_vertex_buffer_bytes: u64,
+}
 
 // `svg`
 pub type PFSVGSceneRef = *mut SVGScene;
@@ -291,6 +392,13 @@ pub unsafe extern "C" fn PFCanvasCreateScene(canvas: PFCanvasRef) -> PFSceneRef
     Box::into_raw(Box::new(Box::from_raw(canvas).into_canvas().into_scene()))
 }
 
+/// Returns everything drawn so far as a new scene and leaves the canvas blank, keeping the drawing
+/// state. Unlike `PFCanvasCreateScene()`, the canvas remains valid.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasTransferToScene(canvas: PFCanvasRef) -> PFSceneRef {
+    Box::into_raw(Box::new((*canvas).transfer_to_scene()))
+}
+
 // Drawing rectangles
 
 #[no_mangle]
@@ -303,6 +411,11 @@ pub unsafe extern "C" fn PFCanvasStrokeRect(canvas: PFCanvasRef, rect: *const PF
     (*canvas).stroke_rect((*rect).to_rust())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasClearRect(canvas: PFCanvasRef, rect: *const PFRectF) {
+    (*canvas).clear_rect((*rect).to_rust())
+}
+
 // Drawing text
 
 #[no_mangle]
@@ -390,6 +503,30 @@ pub unsafe extern "C" fn PFCanvasResetTransform(canvas: PFCanvasRef) {
     (*canvas).reset_transform();
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasGetTransform(
+    canvas: PFCanvasRef,
+    out_transform: *mut PFTransform2F,
+) {
+    debug_assert!(!out_transform.is_null());
+    *out_transform = (*canvas).transform().to_c()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasRotate(canvas: PFCanvasRef, angle: f32) {
+    (*canvas).rotate(angle)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasScale(canvas: PFCanvasRef, scale: *const PFVector2F) {
+    (*canvas).scale((*scale).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasTranslate(canvas: PFCanvasRef, offset: *const PFVector2F) {
+    (*canvas).translate((*offset).to_rust())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSave(canvas: PFCanvasRef) {
     (*canvas).save();
@@ -414,6 +551,17 @@ pub unsafe extern "C" fn PFCanvasSetFontByPostScriptName(
     (*canvas).set_font(to_rust_string(&postscript_name, postscript_name_len))
 }
 
+/// Sets the font from a CSS `font` shorthand like `bold 14px 'Fira Sans', serif`, which also sets
+/// the font size. Strings that aren't valid shorthands are taken to be PostScript names.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetFont(
+    canvas: PFCanvasRef,
+    font: *const c_char,
+    font_len: usize,
+) {
+    (*canvas).set_font(to_rust_string(&font, font_len))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetFontSize(canvas: PFCanvasRef, new_font_size: f32) {
     (*canvas).set_font_size(new_font_size)
@@ -424,6 +572,8 @@ pub unsafe extern "C" fn PFCanvasSetTextAlign(canvas: PFCanvasRef, new_text_alig
     (*canvas).set_text_align(match new_text_align {
         PF_TEXT_ALIGN_CENTER => TextAlign::Center,
         PF_TEXT_ALIGN_RIGHT => TextAlign::Right,
+        PF_TEXT_ALIGN_START => TextAlign::Start,
+        PF_TEXT_ALIGN_END => TextAlign::End,
         _ => TextAlign::Left,
     });
 }
@@ -443,6 +593,24 @@ pub unsafe extern "C" fn PFCanvasSetTextBaseline(
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetDirection(canvas: PFCanvasRef, new_direction: PFTextDirection) {
+    (*canvas).set_direction(match new_direction {
+        PF_TEXT_DIRECTION_RTL => TextDirection::Rtl,
+        _ => TextDirection::Ltr,
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetLetterSpacing(canvas: PFCanvasRef, new_letter_spacing: f32) {
+    (*canvas).set_letter_spacing(new_letter_spacing)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetWordSpacing(canvas: PFCanvasRef, new_word_spacing: f32) {
+    (*canvas).set_word_spacing(new_word_spacing)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasSetFillStyle(canvas: PFCanvasRef, fill_style: PFFillStyleRef) {
     // FIXME(pcwalton): Avoid the copy?
@@ -455,14 +623,110 @@ pub unsafe extern "C" fn PFCanvasSetStrokeStyle(canvas: PFCanvasRef, stroke_styl
     (*canvas).set_stroke_style((*stroke_style).clone())
 }
 
+/// Creates a pattern that paints the image, repeated as `repetition` specifies. This function does
+/// not take ownership of the image.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasCreatePattern(
+    canvas: PFCanvasRef,
+    image: PFImageRef,
+    repetition: PFPatternRepetition,
+) -> PFPatternRef {
+    let repetition = match repetition {
+        PF_PATTERN_REPETITION_REPEAT => PatternRepetition::Repeat,
+        PF_PATTERN_REPETITION_REPEAT_X => PatternRepetition::RepeatX,
+        PF_PATTERN_REPETITION_REPEAT_Y => PatternRepetition::RepeatY,
+        PF_PATTERN_REPETITION_NO_REPEAT => PatternRepetition::NoRepeat,
+        _ => panic!("Invalid Pathfinder pattern repetition!"),
+    };
+    Box::into_raw(Box::new(
+        (*canvas).create_pattern((*image).clone(), repetition),
+    ))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetGlobalAlpha(canvas: PFCanvasRef, new_global_alpha: f32) {
+    (*canvas).set_global_alpha(new_global_alpha)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetGlobalCompositeOperation(
+    canvas: PFCanvasRef,
+    new_composite_operation: PFCompositeOperation,
+) {
+    (*canvas).set_global_composite_operation(to_rust_composite_operation(new_composite_operation))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowBlur(canvas: PFCanvasRef, new_shadow_blur: f32) {
+    (*canvas).set_shadow_blur(new_shadow_blur)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowColor(
+    canvas: PFCanvasRef,
+    new_shadow_color: *const PFColorU,
+) {
+    (*canvas).set_shadow_color((*new_shadow_color).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetShadowOffset(
+    canvas: PFCanvasRef,
+    new_shadow_offset: *const PFVector2F,
+) {
+    (*canvas).set_shadow_offset((*new_shadow_offset).to_rust())
+}
+
+/// Sets the CSS filter functions that paths and images are drawn through, such as
+/// `blur(4px) brightness(50%)`. Values that can't be parsed are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetFilter(
+    canvas: PFCanvasRef,
+    new_filter: *const c_char,
+    new_filter_len: usize,
+) {
+    (*canvas).set_filter(to_rust_string(&new_filter, new_filter_len))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetImageSmoothingEnabled(canvas: PFCanvasRef, enabled: bool) {
+    (*canvas).set_image_smoothing_enabled(enabled)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasSetImageSmoothingQuality(
+    canvas: PFCanvasRef,
+    new_quality: PFImageSmoothingQuality,
+) {
+    (*canvas).set_image_smoothing_quality(match new_quality {
+        PF_IMAGE_SMOOTHING_QUALITY_LOW => ImageSmoothingQuality::Low,
+        PF_IMAGE_SMOOTHING_QUALITY_MEDIUM => ImageSmoothingQuality::Medium,
+        PF_IMAGE_SMOOTHING_QUALITY_HIGH => ImageSmoothingQuality::High,
+        _ => panic!("Invalid Pathfinder image smoothing quality!"),
+    });
+}
+
 /// This function automatically destroys the path. If you wish to use the path again, clone it
 /// first.
+///
+/// The path is filled with the nonzero winding rule. To use the even-odd rule, call
+/// `PFCanvasFillPathWithFillRule()`.
 #[no_mangle]
 pub unsafe extern "C" fn PFCanvasFillPath(canvas: PFCanvasRef, path: PFPathRef) {
-    // TODO(pcwalton): Expose fill rules to the C API.
     (*canvas).fill_path(*Box::from_raw(path), FillRule::Winding)
 }
 
+/// This function automatically destroys the path. If you wish to use the path again, clone it
+/// first.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasFillPathWithFillRule(
+    canvas: PFCanvasRef,
+    path: PFPathRef,
+    fill_rule: PFFillRule,
+) {
+    (*canvas).fill_path(*Box::from_raw(path), to_rust_fill_rule(fill_rule))
+}
+
 /// This function automatically destroys the path. If you wish to use the path again, clone it
 /// first.
 #[no_mangle]
@@ -470,6 +734,108 @@ pub unsafe extern "C" fn PFCanvasStrokePath(canvas: PFCanvasRef, path: PFPathRef
     (*canvas).stroke_path(*Box::from_raw(path))
 }
 
+/// Intersects the current clip with the path. This function automatically destroys the path. If
+/// you wish to use the path again, clone it first.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasClipPath(
+    canvas: PFCanvasRef,
+    path: PFPathRef,
+    fill_rule: PFFillRule,
+) {
+    (*canvas).clip_path(*Box::from_raw(path), to_rust_fill_rule(fill_rule))
+}
+
+/// This function does not take ownership of the path.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasIsPointInPath(
+    canvas: PFCanvasRef,
+    path: PFPathRef,
+    point: *const PFVector2F,
+    fill_rule: PFFillRule,
+) -> bool {
+    (*canvas).is_point_in_path(&*path, (*point).to_rust(), to_rust_fill_rule(fill_rule))
+}
+
+/// This function does not take ownership of the path.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasIsPointInStroke(
+    canvas: PFCanvasRef,
+    path: PFPathRef,
+    point: *const PFVector2F,
+) -> bool {
+    (*canvas).is_point_in_stroke(&*path, (*point).to_rust())
+}
+
+// Drawing images
+
+/// Draws the whole image into the destination rectangle, scaling it to fit. This function does not
+/// take ownership of the image.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasDrawImage(
+    canvas: PFCanvasRef,
+    image: PFImageRef,
+    dest_rect: *const PFRectF,
+) {
+    (*canvas).draw_image((*image).clone(), (*dest_rect).to_rust())
+}
+
+/// Draws the part of the image in `src_rect` into the destination rectangle, scaling it to fit.
+/// This function does not take ownership of the image.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasDrawSubimage(
+    canvas: PFCanvasRef,
+    image: PFImageRef,
+    src_rect: *const PFRectF,
+    dest_rect: *const PFRectF,
+) {
+    (*canvas).draw_subimage(
+        (*image).clone(),
+        (*src_rect).to_rust(),
+        (*dest_rect).to_rust(),
+    )
+}
+
+// Pixel manipulation
+
+/// Renders everything drawn so far on the CPU and copies the pixels in the rectangle to
+/// `out_pixels` as unpremultiplied RGBA, 4 bytes per pixel and row by row, with no padding.
+/// `out_pixels` must have room for all of them.
+///
+/// Returns false, leaving `out_pixels` untouched, if the canvas uses blurs or other filters that
+/// can't be rendered on the CPU.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasGetImageData(
+    canvas: PFCanvasRef,
+    rect: *const PFRectI,
+    out_pixels: *mut u8,
+) -> bool {
+    let image_data = match (*canvas).get_image_data((*rect).to_rust()) {
+        Ok(image_data) => image_data,
+        Err(_) => return false,
+    };
+    let bytes = color_slice_to_u8_slice(&image_data.data);
+    ptr::copy_nonoverlapping(bytes.as_ptr(), out_pixels, bytes.len());
+    true
+}
+
+/// Replaces the pixels at `dest_origin` with the given RGBA pixels, laid out as
+/// `PFCanvasGetImageData()` returns them. This function does not take ownership of the pixels.
+#[no_mangle]
+pub unsafe extern "C" fn PFCanvasPutImageData(
+    canvas: PFCanvasRef,
+    pixels: *const u8,
+    size: *const PFVector2I,
+    dest_origin: *const PFVector2F,
+) {
+    let size = (*size).to_rust();
+    let bytes = slice::from_raw_parts(pixels, size.area() as usize * 4);
+    let image_data = ImageData {
+        data: u8_slice_to_color_slice(bytes).to_vec(),
+        size,
+    };
+    (*canvas).put_image_data(image_data, (*dest_origin).to_rust())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFPathCreate() -> PFPathRef {
     Box::into_raw(Box::new(Path2D::new()))
@@ -485,6 +851,29 @@ pub unsafe extern "C" fn PFPathClone(path: PFPathRef) -> PFPathRef {
     Box::into_raw(Box::new((*path).clone()))
 }
 
+/// Creates a path from SVG path data like `M 0 0 L 10 10 Z`. Parsing stops at the first error,
+/// keeping the commands before it.
+#[no_mangle]
+pub unsafe extern "C" fn PFPathCreateWithSVGPathData(
+    data: *const c_char,
+    data_len: usize,
+) -> PFPathRef {
+    Box::into_raw(Box::new(Path2D::from_svg_path_data(to_rust_string(
+        &data, data_len,
+    ))))
+}
+
+/// Appends the subpaths of `other`, transformed by `transform`. This function does not take
+/// ownership of `other`.
+#[no_mangle]
+pub unsafe extern "C" fn PFPathAddPath(
+    path: PFPathRef,
+    other: PFPathRef,
+    transform: *const PFTransform2F,
+) {
+    (*path).add_path(&*other, (*transform).to_rust())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFPathMoveTo(path: PFPathRef, to: *const PFVector2F) {
     (*path).move_to((*to).to_rust())
@@ -552,6 +941,24 @@ pub unsafe extern "C" fn PFPathRect(path: PFPathRef, rect: *const PFRectF) {
     (*path).rect((*rect).to_rust())
 }
 
+/// Adds a rounded rectangle. As in CSS, `radii` lists one to four corner radii, which may be
+/// elliptical; nothing is added for any other count.
+#[no_mangle]
+pub unsafe extern "C" fn PFPathRoundRect(
+    path: PFPathRef,
+    rect: *const PFRectF,
+    radii: *const PFVector2F,
+    radius_count: usize,
+) {
+    let radii: Vec<_> = slice::from_raw_parts(radii, radius_count)
+        .iter()
+        .map(|radius| radius.to_rust())
+        .collect();
+    if let Some(radii) = CornerRadii::from_list(&radii) {
+        (*path).round_rect((*rect).to_rust(), radii)
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFPathEllipse(
     path: PFPathRef,
@@ -560,13 +967,20 @@ pub unsafe extern "C" fn PFPathEllipse(
     rotation: f32,
     start_angle: f32,
     end_angle: f32,
+    direction: PFArcDirection,
 ) {
+    let direction = match direction {
+        PF_ARC_DIRECTION_CW => ArcDirection::CW,
+        PF_ARC_DIRECTION_CCW => ArcDirection::CCW,
+        _ => panic!("Invalid Pathfinder arc direction!"),
+    };
     (*path).ellipse(
         (*center).to_rust(),
         (*axes).to_rust(),
         rotation,
         start_angle,
         end_angle,
+        direction,
     )
 }
 
@@ -580,11 +994,109 @@ pub unsafe extern "C" fn PFFillStyleCreateColor(color: *const PFColorU) -> PFFil
     Box::into_raw(Box::new(FillStyle::Color((*color).to_rust())))
 }
 
+/// This function takes ownership of the gradient and will automatically destroy it when the fill
+/// style is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn PFFillStyleCreateGradient(gradient: PFGradientRef) -> PFFillStyleRef {
+    Box::into_raw(Box::new(FillStyle::Gradient(*Box::from_raw(gradient))))
+}
+
+/// This function takes ownership of the pattern and will automatically destroy it when the fill
+/// style is destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn PFFillStyleCreatePattern(pattern: PFPatternRef) -> PFFillStyleRef {
+    Box::into_raw(Box::new(FillStyle::Pattern(*Box::from_raw(pattern))))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFFillStyleDestroy(fill_style: PFFillStyleRef) {
     drop(Box::from_raw(fill_style))
 }
 
+// `content`
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientCreateLinear(
+    from: *const PFVector2F,
+    to: *const PFVector2F,
+) -> PFGradientRef {
+    Box::into_raw(Box::new(Gradient::linear_from_points(
+        (*from).to_rust(),
+        (*to).to_rust(),
+    )))
+}
+
+/// Creates a radial gradient between the circle at `from` with radius `from_radius` and the
+/// circle at `to` with radius `to_radius`.
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientCreateRadial(
+    from: *const PFVector2F,
+    from_radius: f32,
+    to: *const PFVector2F,
+    to_radius: f32,
+) -> PFGradientRef {
+    let line = LineSegment2F::new((*from).to_rust(), (*to).to_rust());
+    Box::into_raw(Box::new(Gradient::radial(
+        line,
+        F32x2::new(from_radius, to_radius),
+    )))
+}
+
+/// Creates a gradient that sweeps around `center`, starting at `start_angle` radians clockwise
+/// from the positive x axis.
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientCreateConic(
+    center: *const PFVector2F,
+    start_angle: f32,
+) -> PFGradientRef {
+    Box::into_raw(Box::new(Gradient::conic((*center).to_rust(), start_angle)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientAddColorStop(
+    gradient: PFGradientRef,
+    color: *const PFColorU,
+    offset: f32,
+) {
+    (*gradient).add_color_stop((*color).to_rust(), offset)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGradientDestroy(gradient: PFGradientRef) {
+    drop(Box::from_raw(gradient))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFPatternSetTransform(
+    pattern: PFPatternRef,
+    transform: *const PFTransform2F,
+) {
+    (*pattern).set_transform((*transform).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFPatternDestroy(pattern: PFPatternRef) {
+    drop(Box::from_raw(pattern))
+}
+
+/// Creates an image from unpremultiplied RGBA pixels, 4 bytes per pixel and row by row, with no
+/// padding. The pixels are copied, so you may free them afterward.
+#[no_mangle]
+pub unsafe extern "C" fn PFImageCreateWithRGBA8Pixels(
+    size: *const PFVector2I,
+    pixels: *const u8,
+) -> PFImageRef {
+    let size = (*size).to_rust();
+    let bytes = slice::from_raw_parts(pixels, size.area() as usize * 4);
+    let pixels = u8_slice_to_color_slice(bytes).to_vec();
+    Box::into_raw(Box::new(Image::new(size, Arc::new(pixels))))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFImageDestroy(image: PFImageRef) {
+    drop(Box::from_raw(image))
+}
+
 // `resources`
 
 #[no_mangle]
@@ -630,6 +1142,14 @@ pub unsafe extern "C" fn PFGLLoadWith(loader: PFGLFunctionLoader, userdata: *mut
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFGLLoadGLES2With(loader: PFGLFunctionLoader, userdata: *mut c_void) {
+    pathfinder_gl::load_gles2_with(|name| {
+        let name = CString::new(name).unwrap();
+        loader(name.as_ptr(), userdata)
+    });
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFGLDeviceCreate(
     version: PFGLVersion,
@@ -639,6 +1159,7 @@ pub unsafe extern "C" fn PFGLDeviceCreate(
         PF_GL_VERSION_GL3 => GLVersion::GL3,
         PF_GL_VERSION_GLES3 => GLVersion::GLES3,
         PF_GL_VERSION_GL4 => GLVersion::GL4,
+        PF_GL_VERSION_GLES2 => GLVersion::GLES2,
         _ => panic!("Invalid Pathfinder OpenGL version!"),
     };
     Box::into_raw(Box::new(GLDevice::new(version, default_framebuffer)))
@@ -693,6 +1214,39 @@ pub unsafe extern "C" fn PFGLRendererGetDevice(renderer: PFGLRendererRef) -> PFG
     (*renderer).device_mut()
 }
 
+/// This function takes ownership of and automatically takes responsibility for destroying
+/// `dest_framebuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn PFGLRendererSetDestFramebuffer(
+    renderer: PFGLRendererRef,
+    dest_framebuffer: PFGLDestFramebufferRef,
+) {
+    (*renderer).options_mut().dest = *Box::from_raw(dest_framebuffer);
+    (*renderer).dest_framebuffer_size_changed();
+}
+
+/// Pass `NULL` to render over transparent black.
+#[no_mangle]
+pub unsafe extern "C" fn PFGLRendererSetBackgroundColor(
+    renderer: PFGLRendererRef,
+    background_color: *const PFColorF,
+) {
+    (*renderer).options_mut().background_color = background_color.as_ref().map(PFColorF::to_rust)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGLRendererSetMemoryBudget(
+    renderer: PFGLRendererRef,
+    memory_budget: *const PFMemoryBudget,
+) {
+    (*renderer).options_mut().memory_budget = (*memory_budget).to_rust()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFGLRendererSetDither(renderer: PFGLRendererRef, dither: bool) {
+    (*renderer).options_mut().dither = dither
+}
+
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFMetalDestFramebufferCreateFullWindow(
@@ -747,6 +1301,43 @@ pub unsafe extern "C" fn PFMetalRendererGetDevice(
     (*renderer).device_mut()
 }
 
+/// This function takes ownership of and automatically takes responsibility for destroying
+/// `dest_framebuffer`.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalRendererSetDestFramebuffer(
+    renderer: PFMetalRendererRef,
+    dest_framebuffer: PFMetalDestFramebufferRef,
+) {
+    (*renderer).options_mut().dest = *Box::from_raw(dest_framebuffer);
+    (*renderer).dest_framebuffer_size_changed();
+}
+
+/// Pass `NULL` to render over transparent black.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalRendererSetBackgroundColor(
+    renderer: PFMetalRendererRef,
+    background_color: *const PFColorF,
+) {
+    (*renderer).options_mut().background_color = background_color.as_ref().map(PFColorF::to_rust)
+}
+
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalRendererSetMemoryBudget(
+    renderer: PFMetalRendererRef,
+    memory_budget: *const PFMemoryBudget,
+) {
+    (*renderer).options_mut().memory_budget = (*memory_budget).to_rust()
+}
+
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalRendererSetDither(renderer: PFMetalRendererRef, dither: bool) {
+    (*renderer).options_mut().dither = dither
+}
+
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
 #[no_mangle]
@@ -823,6 +1414,88 @@ pub unsafe extern "C" fn PFMetalDeviceDestroy(device: PFMetalDeviceRef) {
     drop(Box::from_raw(device))
 }
 
+// `metal_layer`
+
+/// Sets up a `CAMetalLayer` for Pathfinder and creates a renderer that draws into it. The layer
+/// is given the system default Metal device if it doesn't have one yet, and its drawables are
+/// sized to `drawable_size`, in device pixels. Pass `NULL` for `background_color` to render over
+/// transparent black.
+///
+/// This function does not take ownership of `resources`; therefore, if you created the resource
+/// loader, you must destroy it yourself to avoid a memory leak.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererCreate(
+    layer: &CoreAnimationLayerRef,
+    drawable_size: *const PFVector2I,
+    resources: PFResourceLoaderRef,
+    background_color: *const PFColorF,
+) -> PFMetalLayerRendererRef {
+    let options = RendererOptions {
+        background_color: background_color.as_ref().map(PFColorF::to_rust),
+        ..RendererOptions::default()
+    };
+    Box::into_raw(Box::new(MetalLayerRenderer::new(
+        layer,
+        (*drawable_size).to_rust(),
+        &*((*resources).0),
+        options,
+    )))
+}
+
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererDestroy(renderer: PFMetalLayerRendererRef) {
+    drop(Box::from_raw(renderer))
+}
+
+/// Call this when the view is resized or moves to a screen with a different scale factor.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererResize(
+    renderer: PFMetalLayerRendererRef,
+    new_drawable_size: *const PFVector2I,
+) {
+    (*renderer).resize((*new_drawable_size).to_rust())
+}
+
+/// Returns a reference to the renderer that draws into the layer, for example to change its
+/// options. Don't destroy it; it belongs to the layer renderer.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererGetRenderer(
+    renderer: PFMetalLayerRendererRef,
+) -> PFMetalRendererRef {
+    (*renderer).renderer_mut()
+}
+
+/// Acquires a drawable, calls `callback` with the renderer to draw into it, and presents it.
+/// Returns false, without calling `callback`, if the layer has no drawable available.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererRenderFrame(
+    renderer: PFMetalLayerRendererRef,
+    callback: PFMetalLayerRenderCallback,
+    userdata: *mut c_void,
+) -> bool {
+    (*renderer).render_frame(|renderer| callback(renderer, userdata))
+}
+
+/// Builds and renders the scene in the proxy into the next drawable, and presents it. Returns
+/// false if the layer has no drawable available.
+///
+/// This function does not take ownership of `scene_proxy` or `build_options`. Therefore, if you
+/// created them, you must destroy them yourself to avoid a leak.
+#[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
+#[no_mangle]
+pub unsafe extern "C" fn PFMetalLayerRendererRenderScene(
+    renderer: PFMetalLayerRendererRef,
+    scene_proxy: PFSceneProxyRef,
+    build_options: PFBuildOptionsRef,
+) -> bool {
+    (*renderer).render_scene(&mut *scene_proxy, (*build_options).clone())
+}
+
 // `renderer`
 
 #[no_mangle]
@@ -883,11 +1556,263 @@ pub unsafe extern "C" fn PFBuildOptionsSetSubpixelAAEnabled(
     (*options).subpixel_aa_enabled = subpixel_aa_enabled
 }
 
+/// Creates an empty scene. Set its view box with `PFSceneSetViewBox()` before rendering it.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneCreate() -> PFSceneRef {
+    Box::into_raw(Box::new(Scene::new()))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneDestroy(scene: PFSceneRef) {
     drop(Box::from_raw(scene))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneSetViewBox(scene: PFSceneRef, new_view_box: *const PFRectF) {
+    (*scene).set_view_box((*new_view_box).to_rust())
+}
+
+/// Adds the paint to the scene and returns its ID, which is the same as that of any identical
+/// paint already in the scene. This function does not take ownership of the paint.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushPaint(scene: PFSceneRef, paint: PFPaintRef) -> PFPaintId {
+    (*scene).push_paint(&*paint).0
+}
+
+/// Returns the ID of the new path, which stays valid for as long as the scene does, or
+/// `PF_INVALID_DRAW_PATH_ID` if the path's paint or clip path isn't in the scene. This function
+/// takes ownership of the draw path, and destroys it on failure.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushDrawPath(
+    scene: PFSceneRef,
+    draw_path: PFDrawPathRef,
+) -> PFDrawPathId {
+    let draw_path = *Box::from_raw(draw_path);
+    if !draw_path_is_valid(&*scene, &draw_path) {
+        return PF_INVALID_DRAW_PATH_ID;
+    }
+    (*scene).push_draw_path(draw_path).0
+}
+
+/// Returns the ID of the new clip path, for use with `PFDrawPathSetClipPath()`, or
+/// `PF_INVALID_CLIP_PATH_ID` if the clip path is clipped by one that isn't in the scene. This
+/// function takes ownership of the clip path, and destroys it on failure.
+#[no_mangle]
+pub unsafe extern "C" fn PFScenePushClipPath(
+    scene: PFSceneRef,
+    clip_path: PFClipPathRef,
+) -> PFClipPathId {
+    let clip_path = *Box::from_raw(clip_path);
+    if !clip_path_id_is_valid(&*scene, clip_path.clip_path()) {
+        return PF_INVALID_CLIP_PATH_ID;
+    }
+    (*scene).push_clip_path(clip_path).0
+}
+
+/// Replaces the draw path with the given ID, keeping its place in the scene. This function takes
+/// ownership of the new draw path and destroys the old one.
+///
+/// Returns false, destroying the new draw path and leaving the scene untouched, if there's no
+/// draw path with that ID or the new path's paint or clip path isn't in the scene.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneReplaceDrawPath(
+    scene: PFSceneRef,
+    draw_path_id: PFDrawPathId,
+    new_draw_path: PFDrawPathRef,
+) -> bool {
+    let new_draw_path = *Box::from_raw(new_draw_path);
+    if draw_path_id >= (*scene).draw_path_count() || !draw_path_is_valid(&*scene, &new_draw_path) {
+        return false;
+    }
+    drop((*scene).replace_draw_path(DrawPathId(draw_path_id), new_draw_path));
+    true
+}
+
+/// Stops the draw path with the given ID from drawing anything. The IDs of other paths are
+/// unaffected. Returns false if there's no draw path with that ID.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneRemoveDrawPath(
+    scene: PFSceneRef,
+    draw_path_id: PFDrawPathId,
+) -> bool {
+    if draw_path_id >= (*scene).draw_path_count() {
+        return false;
+    }
+    drop((*scene).remove_draw_path(DrawPathId(draw_path_id)));
+    true
+}
+
+/// Returns false if there's no draw path with that ID.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneSetDrawPathTransform(
+    scene: PFSceneRef,
+    draw_path_id: PFDrawPathId,
+    new_transform: *const PFTransform2F,
+) -> bool {
+    if draw_path_id >= (*scene).draw_path_count() {
+        return false;
+    }
+    (*scene).set_draw_path_transform(DrawPathId(draw_path_id), (*new_transform).to_rust());
+    true
+}
+
+/// Returns false if there's no draw path with that ID.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneSetDrawPathDepth(
+    scene: PFSceneRef,
+    draw_path_id: PFDrawPathId,
+    new_depth: f32,
+) -> bool {
+    if draw_path_id >= (*scene).draw_path_count() {
+        return false;
+    }
+    (*scene).set_draw_path_depth(DrawPathId(draw_path_id), new_depth);
+    true
+}
+
+/// The paint must have been added to this scene with `PFScenePushPaint()`. Returns false if
+/// there's no draw path or paint with those IDs.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneSetDrawPathPaint(
+    scene: PFSceneRef,
+    draw_path_id: PFDrawPathId,
+    new_paint_id: PFPaintId,
+) -> bool {
+    if draw_path_id >= (*scene).draw_path_count() || new_paint_id >= (*scene).paint_count() {
+        return false;
+    }
+    (*scene).set_paint(DrawPathId(draw_path_id), PaintId(new_paint_id));
+    true
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneGetDrawPathCount(scene: PFSceneRef) -> u32 {
+    (*scene).draw_path_count()
+}
+
+/// Adds everything in `other` to `scene`. This function takes ownership of `other`.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneAppendScene(scene: PFSceneRef, other: PFSceneRef) {
+    (*scene).append_scene(*Box::from_raw(other))
+}
+
+/// Adds everything in `other` to `scene`, transformed by `transform`. This function does not take
+/// ownership of `other`, so it can be placed many times.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneAppendSceneTransformed(
+    scene: PFSceneRef,
+    other: PFSceneRef,
+    transform: *const PFTransform2F,
+) {
+    (*scene).append_scene_transformed(&*other, (*transform).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFPaintCreateColor(color: *const PFColorU) -> PFPaintRef {
+    Box::into_raw(Box::new(Paint::from_color((*color).to_rust())))
+}
+
+/// This function takes ownership of the gradient.
+#[no_mangle]
+pub unsafe extern "C" fn PFPaintCreateGradient(gradient: PFGradientRef) -> PFPaintRef {
+    Box::into_raw(Box::new(Paint::from_gradient(*Box::from_raw(gradient))))
+}
+
+/// This function takes ownership of the pattern.
+#[no_mangle]
+pub unsafe extern "C" fn PFPaintCreatePattern(pattern: PFPatternRef) -> PFPaintRef {
+    Box::into_raw(Box::new(Paint::from_pattern(*Box::from_raw(pattern))))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFPaintDestroy(paint: PFPaintRef) {
+    drop(Box::from_raw(paint))
+}
+
+/// Creates a draw path that fills the path with the paint, which must have been added to the scene
+/// with `PFScenePushPaint()`. This function takes ownership of the path.
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathCreate(path: PFPathRef, paint_id: PFPaintId) -> PFDrawPathRef {
+    let outline = Box::from_raw(path).into_outline();
+    Box::into_raw(Box::new(DrawPath::new(outline, PaintId(paint_id))))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathDestroy(draw_path: PFDrawPathRef) {
+    drop(Box::from_raw(draw_path))
+}
+
+/// The clip path must have been added to the scene that the draw path is added to.
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathSetClipPath(
+    draw_path: PFDrawPathRef,
+    clip_path_id: PFClipPathId,
+) {
+    (*draw_path).set_clip_path(Some(ClipPathId(clip_path_id)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathSetFillRule(draw_path: PFDrawPathRef, fill_rule: PFFillRule) {
+    (*draw_path).set_fill_rule(to_rust_fill_rule(fill_rule))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathSetTransform(
+    draw_path: PFDrawPathRef,
+    transform: *const PFTransform2F,
+) {
+    (*draw_path).set_transform((*transform).to_rust())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathSetDepth(draw_path: PFDrawPathRef, depth: f32) {
+    (*draw_path).set_depth(depth)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFDrawPathSetName(
+    draw_path: PFDrawPathRef,
+    name: *const c_char,
+    name_len: usize,
+) {
+    (*draw_path).set_name(to_rust_string(&name, name_len).to_owned())
+}
+
+/// This function takes ownership of the path.
+#[no_mangle]
+pub unsafe extern "C" fn PFClipPathCreate(path: PFPathRef) -> PFClipPathRef {
+    let outline = Box::from_raw(path).into_outline();
+    Box::into_raw(Box::new(ClipPath::new(outline)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFClipPathDestroy(clip_path: PFClipPathRef) {
+    drop(Box::from_raw(clip_path))
+}
+
+/// Clips this clip path by another one, which must have been added to the same scene.
+#[no_mangle]
+pub unsafe extern "C" fn PFClipPathSetClipPath(
+    clip_path: PFClipPathRef,
+    clip_path_id: PFClipPathId,
+) {
+    (*clip_path).set_clip_path(Some(ClipPathId(clip_path_id)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFClipPathSetFillRule(clip_path: PFClipPathRef, fill_rule: PFFillRule) {
+    (*clip_path).set_fill_rule(to_rust_fill_rule(fill_rule))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFClipPathSetName(
+    clip_path: PFClipPathRef,
+    name: *const c_char,
+    name_len: usize,
+) {
+    (*clip_path).set_name(to_rust_string(&name, name_len).to_owned())
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyCreateFromSceneAndRayonExecutor(
     scene: PFSceneRef,
@@ -905,6 +1830,23 @@ pub unsafe extern "C" fn PFSceneProxyDestroy(scene_proxy: PFSceneProxyRef) {
     drop(Box::from_raw(scene_proxy))
 }
 
+/// Replaces the scene that the proxy builds. This function takes ownership of the new scene.
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneProxyReplaceScene(
+    scene_proxy: PFSceneProxyRef,
+    new_scene: PFSceneRef,
+) {
+    (*scene_proxy).replace_scene(*Box::from_raw(new_scene))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn PFSceneProxySetViewBox(
+    scene_proxy: PFSceneProxyRef,
+    new_view_box: *const PFRectF,
+) {
+    (*scene_proxy).set_view_box((*new_view_box).to_rust())
+}
+
 // `svg`
 
 /// Returns `NULL` on failure.
@@ -953,6 +1895,38 @@ unsafe fn to_rust_string(ptr: &*const c_char, mut len: usize) -> &str {
     str::from_utf8(slice::from_raw_parts(*ptr as *const u8, len)).unwrap()
 }
 
+fn to_rust_composite_operation(operation: PFCompositeOperation) -> CompositeOperation {
+    match operation {
+        PF_COMPOSITE_OPERATION_SOURCE_OVER => CompositeOperation::SourceOver,
+        PF_COMPOSITE_OPERATION_SOURCE_IN => CompositeOperation::SourceIn,
+        PF_COMPOSITE_OPERATION_SOURCE_OUT => CompositeOperation::SourceOut,
+        PF_COMPOSITE_OPERATION_SOURCE_ATOP => CompositeOperation::SourceAtop,
+        PF_COMPOSITE_OPERATION_DESTINATION_OVER => CompositeOperation::DestinationOver,
+        PF_COMPOSITE_OPERATION_DESTINATION_IN => CompositeOperation::DestinationIn,
+        PF_COMPOSITE_OPERATION_DESTINATION_OUT => CompositeOperation::DestinationOut,
+        PF_COMPOSITE_OPERATION_DESTINATION_ATOP => CompositeOperation::DestinationAtop,
+        PF_COMPOSITE_OPERATION_LIGHTER => CompositeOperation::Lighter,
+        PF_COMPOSITE_OPERATION_COPY => CompositeOperation::Copy,
+        PF_COMPOSITE_OPERATION_XOR => CompositeOperation::Xor,
+        PF_COMPOSITE_OPERATION_MULTIPLY => CompositeOperation::Multiply,
+        PF_COMPOSITE_OPERATION_SCREEN => CompositeOperation::Screen,
+        PF_COMPOSITE_OPERATION_OVERLAY => CompositeOperation::Overlay,
+        PF_COMPOSITE_OPERATION_DARKEN => CompositeOperation::Darken,
+        PF_COMPOSITE_OPERATION_LIGHTEN => CompositeOperation::Lighten,
+        PF_COMPOSITE_OPERATION_COLOR_DODGE => CompositeOperation::ColorDodge,
+        PF_COMPOSITE_OPERATION_COLOR_BURN => CompositeOperation::ColorBurn,
+        PF_COMPOSITE_OPERATION_HARD_LIGHT => CompositeOperation::HardLight,
+        PF_COMPOSITE_OPERATION_SOFT_LIGHT => CompositeOperation::SoftLight,
+        PF_COMPOSITE_OPERATION_DIFFERENCE => CompositeOperation::Difference,
+        PF_COMPOSITE_OPERATION_EXCLUSION => CompositeOperation::Exclusion,
+        PF_COMPOSITE_OPERATION_HUE => CompositeOperation::Hue,
+        PF_COMPOSITE_OPERATION_SATURATION => CompositeOperation::Saturation,
+        PF_COMPOSITE_OPERATION_COLOR => CompositeOperation::Color,
+        PF_COMPOSITE_OPERATION_LUMINOSITY => CompositeOperation::Luminosity,
+        _ => panic!("Invalid Pathfinder composite operation!"),
+    }
+}
+
 trait TextMetricsExt {
     fn to_c(&self) -> PFTextMetrics;
 }
@@ -961,12 +1935,31 @@ impl TextMetricsExt for TextMetrics {
     fn to_c(&self) -> PFTextMetrics {
         PFTextMetrics {
             width: self.width(),
+            actual_bounding_box_left: self.actual_bounding_box_left(),
+            actual_bounding_box_right: self.actual_bounding_box_right(),
+            font_bounding_box_ascent: self.font_bounding_box_ascent(),
+            font_bounding_box_descent: self.font_bounding_box_descent(),
+            actual_bounding_box_ascent: self.actual_bounding_box_ascent(),
+            actual_bounding_box_descent: self.actual_bounding_box_descent(),
+            em_height_ascent: self.em_height_ascent(),
+            em_height_descent: self.em_height_descent(),
+            hanging_baseline: self.hanging_baseline(),
+            alphabetic_baseline: self.alphabetic_baseline(),
+            ideographic_baseline: self.ideographic_baseline(),
         }
     }
 }
 
 // Helpers for `content`
 
+fn to_rust_fill_rule(fill_rule: PFFillRule) -> FillRule {
+    match fill_rule {
+        PF_FILL_RULE_WINDING => FillRule::Winding,
+        PF_FILL_RULE_EVEN_ODD => FillRule::EvenOdd,
+        _ => panic!("Invalid Pathfinder fill rule!"),
+    }
+}
+
 impl PFColorF {
     #[inline]
     pub fn to_rust(&self) -> ColorF {
@@ -1033,6 +2026,27 @@ impl PFTransform2F {
     }
 }
 
+trait Transform2FExt {
+    fn to_c(&self) -> PFTransform2F;
+}
+
+impl Transform2FExt for Transform2F {
+    fn to_c(&self) -> PFTransform2F {
+        PFTransform2F {
+            matrix: PFMatrix2x2F {
+                m00: self.matrix.m11(),
+                m01: self.matrix.m12(),
+                m10: self.matrix.m21(),
+                m11: self.matrix.m22(),
+            },
+            vector: PFVector2F {
+                x: self.vector.x(),
+                y: self.vector.y(),
+            },
+        }
+    }
+}
+
 impl PFTransform4F {
     #[inline]
     pub fn to_rust(&self) -> Transform4F {
@@ -1070,6 +2084,11 @@ impl PFRendererOptions {
     {
         let has_background_color = self.flags & PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR;
         let show_debug_ui = (self.flags & PF_RENDERER_OPTIONS_FLAGS_SHOW_DEBUG_UI) != 0;
+        let fill_mode = if (self.flags & PF_RENDERER_OPTIONS_FLAGS_SUPERSAMPLE_FILLS) != 0 {
+            FillMode::Supersample
+        } else {
+            FillMode::Analytic
+        };
         unsafe {
             RendererOptions {
                 background_color: if has_background_color != 0 {
@@ -1079,11 +2098,25 @@ impl PFRendererOptions {
                 },
                 dest: *Box::from_raw(self.dest as *mut DestFramebuffer<D>),
                 show_debug_ui,
+                fill_mode,
+                memory_budget: MemoryBudget::default(),
+                dither: (self.flags & PF_RENDERER_OPTIONS_FLAGS_DITHER) != 0,
+                post_process: vec![],
             }
         }
     }
 }
 
+impl PFMemoryBudget {
+    pub fn to_rust(&self) -> MemoryBudget {
+        let to_limit = |bytes: u64| if bytes == 0 { None } else { Some(bytes) };
+        MemoryBudget {
+            max_mask_framebuffer_bytes: to_limit(self.max_mask_framebuffer_bytes),
+            max_tile_vertex_buffer_bytes: to_limit(self.max_tile_vertex_buffer_bytes),
+        }
+    }
+}
+
 fn to_rust_renderer_level(level: PFRendererLevel) -> RendererLevel {
     match level {
         PF_RENDERER_LEVEL_D3D9 => RendererLevel::D3D9,
@@ -1091,3 +2124,14 @@ fn to_rust_renderer_level(level: PFRendererLevel) -> RendererLevel {
         _ => panic!("Invalid Pathfinder renderer level!"),
     }
 }
+
+// Whether the paint and clip path that the draw path refers to are in the scene.
+fn draw_path_is_valid(scene: &Scene, draw_path: &DrawPath) -> bool {
+    draw_path.paint().0 < scene.paint_count() && clip_path_id_is_valid(scene, draw_path.clip_path())
+}
+
+fn clip_path_id_is_valid(scene: &Scene, clip_path_id: Option<ClipPathId>) -> bool {
+    clip_path_id.map_or(true, |clip_path_id| {
+        clip_path_id.0 < scene.clip_path_count()
+    })
+}
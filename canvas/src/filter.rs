@@ -0,0 +1,504 @@
+// pathfinder/canvas/src/filter.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `filter` property of the canvas, which runs CSS filter functions over each shape drawn.
+//!
+//! Like SVG filter effects, every intermediate result is a render target the size of the canvas,
+//! so results can be sampled without any transform. Filters are evaluated in sRGB.
+
+use crate::CanvasRenderingContext2D;
+use pathfinder_color::matrix::ColorMatrix;
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::{BlendMode, BlurDirection, PatternFilter};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::Pattern;
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::{ClipPathId, DrawPath, RenderTarget};
+use std::f32::consts::PI;
+
+/// One function of a parsed `filter` value.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum FilterFunction {
+    /// A Gaussian blur with the given standard deviation.
+    Blur(f32),
+    /// A blurred, offset, and recolored copy of the input, drawn underneath it.
+    DropShadow {
+        offset: Vector2F,
+        sigma: f32,
+        color: ColorU,
+    },
+    /// `brightness()`, `contrast()`, `saturate()`, or `hue-rotate()`.
+    ColorMatrix(ColorMatrix),
+}
+
+/// Parses a CSS `filter` value, returning `None` if it's invalid.
+///
+/// Lengths may be in absolute units only, and colors may be hex, `rgb()`, `rgba()`, `transparent`,
+/// or one of the 16 basic color keywords.
+pub(crate) fn parse_filter(value: &str) -> Option<Vec<FilterFunction>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") {
+        return Some(vec![]);
+    }
+
+    let mut functions = vec![];
+    let mut rest = value;
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let name = rest[..open].to_ascii_lowercase();
+        let close = open + matching_paren(&rest[open..])?;
+        let args = split_args(&rest[(open + 1)..close])?;
+        functions.push(parse_function(&name, &args)?);
+        rest = rest[(close + 1)..].trim_start();
+    }
+
+    if functions.is_empty() {
+        None
+    } else {
+        Some(functions)
+    }
+}
+
+fn parse_function(name: &str, args: &[&str]) -> Option<FilterFunction> {
+    match name {
+        "blur" => {
+            let radius = match *args {
+                [] => 0.0,
+                [radius] => parse_length(radius)?,
+                _ => return None,
+            };
+            if radius < 0.0 {
+                return None;
+            }
+            Some(FilterFunction::Blur(radius * 0.5))
+        }
+        "drop-shadow" => parse_drop_shadow(args),
+        "brightness" => {
+            let amount = parse_amount(args)?;
+            Some(FilterFunction::ColorMatrix(ColorMatrix::from_rows([
+                [amount, 0.0, 0.0, 0.0, 0.0],
+                [0.0, amount, 0.0, 0.0, 0.0],
+                [0.0, 0.0, amount, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0, 0.0],
+            ])))
+        }
+        "contrast" => {
+            // The intercept goes in the alpha column, since render targets are premultiplied.
+            let amount = parse_amount(args)?;
+            let intercept = 0.5 - 0.5 * amount;
+            Some(FilterFunction::ColorMatrix(ColorMatrix::from_rows([
+                [amount, 0.0, 0.0, intercept, 0.0],
+                [0.0, amount, 0.0, intercept, 0.0],
+                [0.0, 0.0, amount, intercept, 0.0],
+                [0.0, 0.0, 0.0, 1.0, 0.0],
+            ])))
+        }
+        "saturate" => Some(FilterFunction::ColorMatrix(ColorMatrix::saturate(
+            parse_amount(args)?,
+        ))),
+        "hue-rotate" => {
+            let angle = match *args {
+                [] => 0.0,
+                [angle] => parse_angle(angle)?,
+                _ => return None,
+            };
+            Some(FilterFunction::ColorMatrix(ColorMatrix::hue_rotate(angle)))
+        }
+        _ => None,
+    }
+}
+
+// The color may come before or after the lengths, and defaults to black.
+fn parse_drop_shadow(args: &[&str]) -> Option<FilterFunction> {
+    let (color, lengths) = match args.first().and_then(|arg| parse_color(arg)) {
+        Some(color) => (color, &args[1..]),
+        None => match args.last().and_then(|arg| parse_color(arg)) {
+            Some(color) => (color, &args[..(args.len() - 1)]),
+            None => (ColorU::black(), args),
+        },
+    };
+
+    let lengths = lengths
+        .iter()
+        .map(|length| parse_length(length))
+        .collect::<Option<Vec<f32>>>()?;
+    let (offset, radius) = match *lengths {
+        [x, y] => (vec2f(x, y), 0.0),
+        [x, y, radius] if radius >= 0.0 => (vec2f(x, y), radius),
+        _ => return None,
+    };
+    Some(FilterFunction::DropShadow {
+        offset,
+        sigma: radius * 0.5,
+        color,
+    })
+}
+
+// A number or percentage that defaults to 1 and can't be negative.
+fn parse_amount(args: &[&str]) -> Option<f32> {
+    let amount = match *args {
+        [] => return Some(1.0),
+        [amount] if amount.ends_with('%') => {
+            amount[..(amount.len() - 1)].parse::<f32>().ok()? / 100.0
+        }
+        [amount] => amount.parse().ok()?,
+        _ => return None,
+    };
+    if amount >= 0.0 {
+        Some(amount)
+    } else {
+        None
+    }
+}
+
+// Returns a length in CSS pixels, which are canvas units.
+pub(crate) fn parse_length(length: &str) -> Option<f32> {
+    let length = length.to_ascii_lowercase();
+    let split = length
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(length.len());
+    let value: f32 = length[..split].parse().ok()?;
+    let scale = match &length[split..] {
+        "" if value == 0.0 => 1.0,
+        "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        _ => return None,
+    };
+    Some(value * scale)
+}
+
+// Returns an angle in radians.
+fn parse_angle(angle: &str) -> Option<f32> {
+    let angle = angle.to_ascii_lowercase();
+    let split = angle
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(angle.len());
+    let value: f32 = angle[..split].parse().ok()?;
+    let scale = match &angle[split..] {
+        "" if value == 0.0 => 1.0,
+        "deg" => PI / 180.0,
+        "rad" => 1.0,
+        "grad" => PI / 200.0,
+        "turn" => 2.0 * PI,
+        _ => return None,
+    };
+    Some(value * scale)
+}
+
+fn parse_color(color: &str) -> Option<ColorU> {
+    let color = color.to_ascii_lowercase();
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|digit| digit.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<u8>>>()?;
+        return match *digits {
+            [r, g, b] => Some(ColorU::new(r * 17, g * 17, b * 17, 255)),
+            [r, g, b, a] => Some(ColorU::new(r * 17, g * 17, b * 17, a * 17)),
+            [r0, r1, g0, g1, b0, b1] => {
+                Some(ColorU::new(r0 * 16 + r1, g0 * 16 + g1, b0 * 16 + b1, 255))
+            }
+            [r0, r1, g0, g1, b0, b1, a0, a1] => Some(ColorU::new(
+                r0 * 16 + r1,
+                g0 * 16 + g1,
+                b0 * 16 + b1,
+                a0 * 16 + a1,
+            )),
+            _ => None,
+        };
+    }
+
+    if color.starts_with("rgb(") || color.starts_with("rgba(") {
+        let open = color.find('(')?;
+        let close = open + matching_paren(&color[open..])?;
+        if close + 1 != color.len() {
+            return None;
+        }
+        let components = color[(open + 1)..close]
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|component| !component.is_empty())
+            .collect::<Vec<_>>();
+        let channel = |component: &str| -> Option<f32> {
+            match component.strip_suffix('%') {
+                Some(percent) => Some(percent.parse::<f32>().ok()? / 100.0),
+                None => Some(component.parse::<f32>().ok()? / 255.0),
+            }
+        };
+        let alpha = |component: &str| -> Option<f32> {
+            match component.strip_suffix('%') {
+                Some(percent) => Some(percent.parse::<f32>().ok()? / 100.0),
+                None => component.parse().ok(),
+            }
+        };
+        let (r, g, b, a) = match *components {
+            [r, g, b] => (channel(r)?, channel(g)?, channel(b)?, 1.0),
+            [r, g, b, a] => (channel(r)?, channel(g)?, channel(b)?, alpha(a)?),
+            _ => return None,
+        };
+        return Some(ColorF::new(r, g, b, a).to_u8());
+    }
+
+    let rgb = match &*color {
+        "transparent" => return Some(ColorU::transparent_black()),
+        "black" => 0x000000,
+        "silver" => 0xc0c0c0,
+        "gray" | "grey" => 0x808080,
+        "white" => 0xffffff,
+        "maroon" => 0x800000,
+        "red" => 0xff0000,
+        "purple" => 0x800080,
+        "fuchsia" | "magenta" => 0xff00ff,
+        "green" => 0x008000,
+        "lime" => 0x00ff00,
+        "olive" => 0x808000,
+        "yellow" => 0xffff00,
+        "navy" => 0x000080,
+        "blue" => 0x0000ff,
+        "teal" => 0x008080,
+        "aqua" | "cyan" => 0x00ffff,
+        _ => return None,
+    };
+    Some(ColorU::new(
+        (rgb >> 16) as u8,
+        (rgb >> 8) as u8,
+        rgb as u8,
+        255,
+    ))
+}
+
+// Returns the index of the parenthesis that closes the one at the start of the string.
+fn matching_paren(string: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in string.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits function arguments on whitespace outside of nested parentheses.
+fn split_args(args: &str) -> Option<Vec<&str>> {
+    let mut result = vec![];
+    let (mut depth, mut start) = (0, None);
+    for (index, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    result.push(&args[start..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(start) = start {
+        result.push(&args[start..]);
+    }
+    if depth == 0 {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+impl CanvasRenderingContext2D {
+    /// Draws a path through the current filter, followed by its shadow, if any.
+    ///
+    /// Per the canvas drawing model, the shadow is cast by the filtered image rather than by the
+    /// path itself, and the result is composited onto the canvas as one image. With no filter,
+    /// this implements composite operations that replace the canvas outside the path.
+    pub(crate) fn push_filtered_path(
+        &mut self,
+        outline: Outline,
+        paint_id: PaintId,
+        fill_rule: FillRule,
+        clip_path: Option<ClipPathId>,
+        blend_mode: BlendMode,
+    ) {
+        let canvas_rect = RectF::new(Vector2F::zero(), self.canvas.size().to_f32());
+        let mut region = outline
+            .bounds()
+            .round_out()
+            .intersection(canvas_rect)
+            .unwrap_or_default();
+
+        let source = self.push_filter_render_target();
+        let mut path = DrawPath::new(outline, paint_id);
+        path.set_fill_rule(fill_rule);
+        self.canvas.scene.push_draw_path(path);
+        self.canvas.scene.pop_render_target();
+
+        let mut result = source;
+        let functions = self.current_state.filter_functions.clone();
+        for function in functions {
+            region = filter_region(&function, region)
+                .round_out()
+                .intersection(canvas_rect)
+                .unwrap_or_default();
+            result = self.apply_filter_function(&function, result, region);
+        }
+
+        if self.current_state.casts_shadow() {
+            let shadow = FilterFunction::DropShadow {
+                offset: self.current_state.shadow_offset,
+                sigma: self.current_state.shadow_blur * 0.5,
+                color: self.current_state.shadow_color,
+            };
+            region = filter_region(&shadow, region)
+                .round_out()
+                .intersection(canvas_rect)
+                .unwrap_or_default();
+            result = self.apply_filter_function(&shadow, result, region);
+        }
+
+        self.composite_filter_render_target(result, region, clip_path, blend_mode);
+    }
+
+    /// Composites a filter render target, drawn to within `region`, onto the canvas.
+    ///
+    /// Outside that region the image is transparent, which composite operations that replace the
+    /// canvas must see, so those cover the whole canvas.
+    pub(crate) fn composite_filter_render_target(
+        &mut self,
+        render_target_id: RenderTargetId,
+        mut region: RectF,
+        clip_path: Option<ClipPathId>,
+        blend_mode: BlendMode,
+    ) {
+        if blend_mode.is_destructive() {
+            region = RectF::new(Vector2F::zero(), self.canvas.size().to_f32());
+        }
+
+        let paint = self.render_target_paint(render_target_id, Vector2F::zero(), None);
+        let paint_id = self.canvas.scene.push_paint(&paint);
+        let mut path = DrawPath::new(Outline::from_rect(region), paint_id);
+        path.set_clip_path(clip_path);
+        path.set_blend_mode(blend_mode);
+        self.canvas.scene.push_draw_path(path);
+    }
+
+    fn apply_filter_function(
+        &mut self,
+        function: &FilterFunction,
+        input: RenderTargetId,
+        region: RectF,
+    ) -> RenderTargetId {
+        match *function {
+            FilterFunction::Blur(sigma) => self.blur(input, sigma, region),
+            FilterFunction::DropShadow {
+                offset,
+                sigma,
+                color,
+            } => {
+                let blurred = self.blur(input, sigma, region);
+                let shadow_filter = PatternFilter::ColorMatrix(shadow_matrix(color.to_f32()));
+                let shadow_paint = self.render_target_paint(blurred, offset, Some(shadow_filter));
+                let input_paint = self.render_target_paint(input, Vector2F::zero(), None);
+                self.draw_filter_layers(&[shadow_paint, input_paint], region)
+            }
+            FilterFunction::ColorMatrix(matrix) => {
+                let filter = PatternFilter::ColorMatrix(matrix);
+                let paint = self.render_target_paint(input, Vector2F::zero(), Some(filter));
+                self.draw_filter_layers(&[paint], region)
+            }
+        }
+    }
+
+    /// Performs a two-pass Gaussian blur, the same way shadows are blurred.
+    fn blur(&mut self, input: RenderTargetId, sigma: f32, region: RectF) -> RenderTargetId {
+        if sigma <= 0.0 {
+            return input;
+        }
+        let mut result = input;
+        for &direction in &[BlurDirection::X, BlurDirection::Y] {
+            let filter = PatternFilter::Blur { direction, sigma };
+            let paint = self.render_target_paint(result, Vector2F::zero(), Some(filter));
+            result = self.draw_filter_layers(&[paint], region);
+        }
+        result
+    }
+
+    /// Draws the given paints, in order, over the filter region of a new render target.
+    fn draw_filter_layers(&mut self, paints: &[Paint], region: RectF) -> RenderTargetId {
+        let render_target_id = self.push_filter_render_target();
+        for paint in paints {
+            let paint_id = self.canvas.scene.push_paint(paint);
+            let path = DrawPath::new(Outline::from_rect(region), paint_id);
+            self.canvas.scene.push_draw_path(path);
+        }
+        self.canvas.scene.pop_render_target();
+        render_target_id
+    }
+
+    pub(crate) fn push_filter_render_target(&mut self) -> RenderTargetId {
+        let render_target = RenderTarget::new(self.canvas.size(), String::new());
+        self.canvas.scene.push_render_target(render_target)
+    }
+
+    pub(crate) fn render_target_paint(
+        &self,
+        render_target_id: RenderTargetId,
+        offset: Vector2F,
+        filter: Option<PatternFilter>,
+    ) -> Paint {
+        let mut pattern = Pattern::from_render_target(render_target_id, self.canvas.size());
+        pattern.apply_transform(Transform2F::from_translation(offset));
+        pattern.set_filter(filter);
+        Paint::from_pattern(pattern)
+    }
+}
+
+// Returns the region that the function can draw to, given the region its input covers.
+fn filter_region(function: &FilterFunction, region: RectF) -> RectF {
+    match *function {
+        FilterFunction::Blur(sigma) => region.dilate(sigma * 3.0),
+        FilterFunction::DropShadow { offset, sigma, .. } => {
+            region.union_rect((region + offset).dilate(sigma * 3.0))
+        }
+        FilterFunction::ColorMatrix(_) => region,
+    }
+}
+
+// Replaces the color with `color`, scaling by the source alpha.
+//
+// Render targets hold premultiplied colors, so the color channels come from the alpha column
+// rather than the constant one.
+fn shadow_matrix(color: ColorF) -> ColorMatrix {
+    let a = color.a();
+    ColorMatrix::from_rows([
+        [0.0, 0.0, 0.0, color.r() * a, 0.0],
+        [0.0, 0.0, 0.0, color.g() * a, 0.0],
+        [0.0, 0.0, 0.0, color.b() * a, 0.0],
+        [0.0, 0.0, 0.0, a, 0.0],
+    ])
+}
@@ -0,0 +1,178 @@
+// pathfinder/canvas/src/font.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of the CSS `font` shorthand, as accepted by the `font` property of the canvas.
+
+use crate::filter::parse_length;
+use crate::DEFAULT_FONT_SIZE;
+use font_kit::family_name::FamilyName;
+use font_kit::properties::{Properties, Stretch, Style, Weight};
+
+/// A parsed CSS `font` value.
+///
+/// The line height is parsed but discarded, since the canvas always uses `normal`.
+#[derive(Clone, Debug)]
+pub(crate) struct FontShorthand {
+    pub(crate) properties: Properties,
+    /// The font size in CSS pixels.
+    pub(crate) size: f32,
+    /// The families, in order of preference.
+    pub(crate) families: Vec<FamilyName>,
+}
+
+impl FontShorthand {
+    /// Parses a value like `italic bold 14px/1.2 'Fira Sans', sans-serif`, returning `None` if
+    /// it's invalid.
+    ///
+    /// Relative sizes are relative to the default canvas font size of 10px. System font keywords
+    /// like `caption` aren't supported.
+    pub(crate) fn parse(value: &str) -> Option<FontShorthand> {
+        let mut properties = Properties::new();
+        let mut rest = value.trim_start();
+
+        // Style, variant, weight, and stretch may come in any order before the size.
+        let size = loop {
+            let word = take_word(&mut rest).to_ascii_lowercase();
+            match &*word {
+                "" => return None,
+                "normal" | "small-caps" => {}
+                "italic" => properties.style = Style::Italic,
+                "oblique" => properties.style = Style::Oblique,
+                "bold" | "bolder" => properties.weight = Weight::BOLD,
+                "lighter" => properties.weight = Weight::THIN,
+                _ => match (parse_stretch(&word), word.parse::<f32>()) {
+                    (Some(stretch), _) => properties.stretch = stretch,
+                    (None, Ok(weight)) if (1.0..=1000.0).contains(&weight) => {
+                        properties.weight = Weight(weight)
+                    }
+                    _ => break word,
+                },
+            }
+        };
+
+        // The size may be followed by a slash and a line height, with or without whitespace.
+        let (size, line_height_follows) = match size.find('/') {
+            Some(slash) => (&size[..slash], slash + 1 == size.len()),
+            None => match rest.strip_prefix('/') {
+                Some(line_height) => {
+                    rest = line_height.trim_start();
+                    (&*size, true)
+                }
+                None => (&*size, false),
+            },
+        };
+        if line_height_follows {
+            take_word(&mut rest);
+        }
+        let size = parse_font_size(size)?;
+
+        let families = parse_families(rest)?;
+        Some(FontShorthand {
+            properties,
+            size,
+            families,
+        })
+    }
+}
+
+// Removes the next whitespace-separated word from the front of the string and returns it.
+fn take_word<'a>(rest: &mut &'a str) -> &'a str {
+    let end = rest
+        .find(|c: char| c.is_ascii_whitespace())
+        .unwrap_or(rest.len());
+    let word = &rest[..end];
+    *rest = rest[end..].trim_start();
+    word
+}
+
+fn parse_stretch(word: &str) -> Option<Stretch> {
+    match word {
+        "ultra-condensed" => Some(Stretch::ULTRA_CONDENSED),
+        "extra-condensed" => Some(Stretch::EXTRA_CONDENSED),
+        "condensed" => Some(Stretch::CONDENSED),
+        "semi-condensed" => Some(Stretch::SEMI_CONDENSED),
+        "semi-expanded" => Some(Stretch::SEMI_EXPANDED),
+        "expanded" => Some(Stretch::EXPANDED),
+        "extra-expanded" => Some(Stretch::EXTRA_EXPANDED),
+        "ultra-expanded" => Some(Stretch::ULTRA_EXPANDED),
+        _ => None,
+    }
+}
+
+fn parse_font_size(size: &str) -> Option<f32> {
+    let keyword_scale = match size {
+        "xx-small" => 0.6,
+        "x-small" => 0.75,
+        "small" => 8.0 / 9.0,
+        "medium" => 1.0,
+        "large" => 1.2,
+        "x-large" => 1.5,
+        "xx-large" => 2.0,
+        "xxx-large" => 3.0,
+        "smaller" => return Some(DEFAULT_FONT_SIZE / 1.2),
+        "larger" => return Some(DEFAULT_FONT_SIZE * 1.2),
+        _ => {
+            let relative_size = if let Some(percentage) = size.strip_suffix('%') {
+                percentage
+                    .parse::<f32>()
+                    .ok()
+                    .map(|percentage| percentage / 100.0)
+            } else if let Some(ems) = size.strip_suffix("rem").or_else(|| size.strip_suffix("em")) {
+                ems.parse::<f32>().ok()
+            } else {
+                None
+            };
+            let size = match relative_size {
+                Some(relative_size) => relative_size * DEFAULT_FONT_SIZE,
+                None => parse_length(size)?,
+            };
+            return if size >= 0.0 { Some(size) } else { None };
+        }
+    };
+    // Keywords are relative to the browser's default font size of 16px.
+    Some(16.0 * keyword_scale)
+}
+
+// Parses a comma-separated list of quoted family names, unquoted family names, and generic
+// families.
+fn parse_families(value: &str) -> Option<Vec<FamilyName>> {
+    let mut families = vec![];
+    let mut rest = value.trim();
+    loop {
+        let family;
+        if let Some(quote) = rest.chars().next().filter(|&c| c == '"' || c == '\'') {
+            let end = rest[1..].find(quote)? + 1;
+            family = FamilyName::Title(rest[1..end].to_owned());
+            rest = rest[(end + 1)..].trim_start();
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let words: Vec<&str> = rest[..end].split_ascii_whitespace().collect();
+            family = match &*words {
+                [] => return None,
+                [word] => match &*word.to_ascii_lowercase() {
+                    "serif" => FamilyName::Serif,
+                    "sans-serif" => FamilyName::SansSerif,
+                    "monospace" => FamilyName::Monospace,
+                    "cursive" => FamilyName::Cursive,
+                    "fantasy" => FamilyName::Fantasy,
+                    _ => FamilyName::Title((*word).to_owned()),
+                },
+                _ => FamilyName::Title(words.join(" ")),
+            };
+            rest = &rest[end..];
+        }
+        families.push(family);
+
+        if rest.is_empty() {
+            return Some(families);
+        }
+        rest = rest.strip_prefix(',')?.trim_start();
+    }
+}
@@ -23,23 +23,29 @@ use pathfinder_content::dash::OutlineDash;
 use pathfinder_content::effects::{BlendMode, BlurDirection, PatternFilter};
 use pathfinder_content::gradient::Gradient;
 use pathfinder_content::outline::{Contour, Outline};
-use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_content::pattern::{Image, Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_content::stroke::LineJoin as StrokeLineJoin;
 use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
 use pathfinder_geometry::line_segment::LineSegment2F;
-use pathfinder_renderer::paint::{Paint, PaintCompositeOp};
+use pathfinder_rasterize::RasterizeOptions;
+use pathfinder_renderer::paint::{Paint, PaintCompositeOp, PaintId};
 use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, RenderTarget, Scene};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::default::Default;
+use std::error::Error;
 use std::f32;
 use std::f32::consts::PI;
-use std::fmt::{Debug, Error as FmtError, Formatter};
+use std::fmt::{Debug, Display, Error as FmtError, Formatter};
+use std::iter;
 use std::mem;
 use std::sync::Arc;
 
 pub use text::CanvasFontContext;
 
+use crate::filter::FilterFunction;
+
 #[cfg(not(feature = "pf-text"))]
 use crate::text::FontCollection;
 #[cfg(feature = "pf-text")]
@@ -50,6 +56,14 @@ pub use text::TextMetrics;
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 const DEFAULT_FONT_SIZE: f32 = 10.0;
+const EPSILON: f32 = 0.0001;
+
+mod filter;
+#[cfg(feature = "pf-text")]
+mod font;
+mod path_data;
+#[cfg(feature = "pf-image")]
+mod raster;
 
 #[cfg(feature = "pf-text")]
 mod text;
@@ -151,7 +165,8 @@ impl Canvas {
 pub struct CanvasRenderingContext2D {
     canvas: Canvas,
     current_state: State,
-    saved_states: Vec<State>,
+    // Each saved state, with the layer that `save_layer()` began along with it, if any.
+    saved_states: Vec<(State, Option<Layer>)>,
     #[allow(dead_code)]
     canvas_font_context: CanvasFontContext,
 }
@@ -169,16 +184,58 @@ impl CanvasRenderingContext2D {
         &mut self.canvas
     }
 
-    #[inline]
-    pub fn into_canvas(self) -> Canvas {
+    /// Returns the canvas, first compositing any layers that haven't been restored.
+    pub fn into_canvas(mut self) -> Canvas {
+        while !self.saved_states.is_empty() {
+            self.restore();
+        }
         self.canvas
     }
 
+    /// Returns everything drawn so far as a scene and leaves the canvas blank, like
+    /// `transferToImageBitmap()` on an HTML `OffscreenCanvas`.
+    ///
+    /// The drawing state, including the clip, carries over to the blank canvas. Since drawing
+    /// needs no GPU device, a context created on a worker thread can draw frame after frame and
+    /// send each scene to the thread that renders it. Layers begun with `save_layer()` must be
+    /// restored first.
+    pub fn transfer_to_scene(&mut self) -> Scene {
+        assert!(
+            self.saved_states.iter().all(|(_, layer)| layer.is_none()),
+            "Restore all layers before transferring the scene!"
+        );
+        self.take_scene()
+    }
+
+    // Replaces the scene with a blank one, copying the clip paths that the saved and current
+    // states refer to into it.
+    fn take_scene(&mut self) -> Scene {
+        let old_scene = self.canvas.take_scene();
+        let new_scene = &mut self.canvas.scene;
+        let mut new_clip_path_ids = HashMap::new();
+        let states = self
+            .saved_states
+            .iter_mut()
+            .map(|(state, _)| state)
+            .chain(iter::once(&mut self.current_state));
+        for state in states {
+            if let Some(clip_path_id) = state.clip_path {
+                state.clip_path = Some(copy_clip_path(
+                    &old_scene,
+                    new_scene,
+                    clip_path_id,
+                    &mut new_clip_path_ids,
+                ));
+            }
+        }
+        old_scene
+    }
+
     // Extensions
 
     /// Clears the current canvas.
     pub fn clear(&mut self) {
-        drop(self.canvas.take_scene())
+        drop(self.take_scene())
     }
 
     // Drawing rectangles
@@ -300,6 +357,35 @@ impl CanvasRenderingContext2D {
         self.current_state.stroke_paint = new_stroke_style.into().into_paint();
     }
 
+    /// Creates a gradient that sweeps around `center`, starting at `start_angle` radians
+    /// clockwise from the positive x axis.
+    ///
+    /// Add color stops to it, then pass it to `set_fill_style` or `set_stroke_style`.
+    #[inline]
+    pub fn create_conic_gradient(&self, start_angle: f32, center: Vector2F) -> Gradient {
+        Gradient::conic(center, start_angle)
+    }
+
+    /// Creates a pattern that paints the image, repeated as `repetition` specifies.
+    ///
+    /// The areas that the image doesn't cover are transparent. Place the pattern with
+    /// `Pattern::set_transform`, then pass it to `set_fill_style` or `set_stroke_style`.
+    pub fn create_pattern<I>(&mut self, image: I, repetition: PatternRepetition) -> Pattern
+    where
+        I: CanvasImageSource,
+    {
+        let mut pattern = image.to_pattern(self, Transform2F::default());
+        let (repeat_x, repeat_y) = match repetition {
+            PatternRepetition::Repeat => (true, true),
+            PatternRepetition::RepeatX => (true, false),
+            PatternRepetition::RepeatY => (false, true),
+            PatternRepetition::NoRepeat => (false, false),
+        };
+        pattern.set_repeat_x(repeat_x);
+        pattern.set_repeat_y(repeat_y);
+        pattern
+    }
+
     // Shadows
 
     #[inline]
@@ -332,6 +418,26 @@ impl CanvasRenderingContext2D {
         self.current_state.shadow_offset = new_shadow_offset;
     }
 
+    // Filters
+
+    #[inline]
+    pub fn filter(&self) -> &str {
+        &self.current_state.filter
+    }
+
+    /// Sets the CSS filter functions that paths and images are drawn through, such as
+    /// `"blur(4px) brightness(50%)"`, or `"none"`.
+    ///
+    /// `blur()`, `drop-shadow()`, `brightness()`, `contrast()`, `saturate()`, and `hue-rotate()`
+    /// are supported. As in HTML canvas, values that can't be parsed are ignored. Text isn't
+    /// filtered.
+    pub fn set_filter(&mut self, new_filter: &str) {
+        if let Some(functions) = filter::parse_filter(new_filter) {
+            self.current_state.filter = new_filter.to_owned();
+            self.current_state.filter_functions = functions;
+        }
+    }
+
     // Drawing paths
 
     #[inline]
@@ -341,6 +447,16 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     pub fn stroke_path(&mut self, path: Path2D) {
+        let (outline, stroke_style) = self.dash_path_for_stroking(path);
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+        stroke_to_fill.offset();
+        let outline = stroke_to_fill.into_outline();
+
+        self.push_path(outline, PathOp::Stroke, FillRule::Winding);
+    }
+
+    // Applies the line dash to the path and returns it, along with the style to stroke it with.
+    fn dash_path_for_stroking(&self, path: Path2D) -> (Outline, StrokeStyle) {
         let mut stroke_style = self.current_state.resolve_stroke_style();
 
         // The smaller scale is relevant here, as we multiply by it and want to ensure it is always
@@ -364,13 +480,47 @@ impl CanvasRenderingContext2D {
             outline = dash.into_outline();
         }
 
-        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
-        stroke_to_fill.offset();
-        outline = stroke_to_fill.into_outline();
+        (outline, stroke_style)
+    }
 
-        self.push_path(outline, PathOp::Stroke, FillRule::Winding);
+    // Hit testing
+
+    /// Returns true if the given point, in canvas coordinates, would be painted by filling the
+    /// path with the given fill rule under the current transform.
+    pub fn is_point_in_path(&self, path: &Path2D, point: Vector2F, fill_rule: FillRule) -> bool {
+        match self.point_in_path_space(point) {
+            None => false,
+            Some(point) => path.clone().into_outline().contains_point(point, fill_rule),
+        }
+    }
+
+    /// Returns true if the given point, in canvas coordinates, would be painted by stroking the
+    /// path with the current line style and transform.
+    pub fn is_point_in_stroke(&self, path: &Path2D, point: Vector2F) -> bool {
+        match self.point_in_path_space(point) {
+            None => false,
+            Some(point) => {
+                let (outline, stroke_style) = self.dash_path_for_stroking(path.clone());
+                outline.stroke_contains_point(point, stroke_style)
+            }
+        }
+    }
+
+    // Maps a point from canvas coordinates back through the current transform, or returns `None`
+    // if the transform collapses paths to nothing.
+    fn point_in_path_space(&self, point: Vector2F) -> Option<Vector2F> {
+        let transform = self.current_state.transform;
+        if transform.matrix.det() == 0.0 {
+            return None;
+        }
+        Some(transform.inverse() * point)
     }
 
+    /// Intersects the clip with the given path, filled with the given fill rule, under the
+    /// current transform.
+    ///
+    /// As in HTML canvas, successive clips narrow the clip instead of replacing it, and
+    /// `restore()` brings back the clip that was in effect at the matching `save()`.
     pub fn clip_path(&mut self, path: Path2D, fill_rule: FillRule) {
         let mut outline = path.into_outline();
         outline.transform(&self.current_state.transform);
@@ -401,122 +551,34 @@ impl CanvasRenderingContext2D {
 
         outline.transform(&transform);
 
-        if !self.current_state.shadow_color.is_fully_transparent() {
-            let mut outline = outline.clone();
-            outline.transform(&Transform2F::from_translation(
-                self.current_state.shadow_offset,
-            ));
-
-            let shadow_blur_info = push_shadow_blur_render_targets_if_needed(
-                &mut self.canvas.scene,
-                &self.current_state,
-                outline.bounds(),
-            );
-
-            if let Some(ref shadow_blur_info) = shadow_blur_info {
-                outline.transform(&Transform2F::from_translation(
-                    -shadow_blur_info.bounds.origin().to_f32(),
-                ));
-            }
-
-            // Per spec the shadow must respect the alpha of the shadowed path, but otherwise have
-            // the color of the shadow paint.
-            let mut shadow_paint = (*paint).clone();
-            let shadow_base_alpha = shadow_paint.base_color().a;
-            let mut shadow_color = self.current_state.shadow_color.to_f32();
-            shadow_color.set_a(shadow_color.a() * shadow_base_alpha as f32 / 255.0);
-            shadow_paint.set_base_color(shadow_color.to_u8());
-            if let &mut Some(ref mut shadow_paint_overlay) = shadow_paint.overlay_mut() {
-                shadow_paint_overlay.set_composite_op(PaintCompositeOp::DestIn);
-            }
-            let shadow_paint_id = self.canvas.scene.push_paint(&shadow_paint);
+        // Composite operations like `source-in` affect the whole canvas, not just the shape, so
+        // they need a layer too.
+        if !self.current_state.filter_functions.is_empty() || blend_mode.is_destructive() {
+            self.push_filtered_path(outline, paint_id, fill_rule, clip_path, blend_mode);
+            return;
+        }
 
-            let mut path = DrawPath::new(outline, shadow_paint_id);
-            if shadow_blur_info.is_none() {
+        push_shadow(
+            &mut self.canvas.scene,
+            &self.current_state,
+            &paint,
+            outline.bounds(),
+            |scene, shadow_transform, shadow_paint_id, clip_path| {
+                let mut outline = outline.clone();
+                outline.transform(&shadow_transform);
+                let mut path = DrawPath::new(outline, shadow_paint_id);
                 path.set_clip_path(clip_path);
-            }
-            path.set_fill_rule(fill_rule);
-            path.set_blend_mode(blend_mode);
-            self.canvas.scene.push_draw_path(path);
-
-            composite_shadow_blur_render_targets_if_needed(
-                &mut self.canvas.scene,
-                shadow_blur_info,
-                clip_path,
-            );
-        }
+                path.set_fill_rule(fill_rule);
+                path.set_blend_mode(blend_mode);
+                scene.push_draw_path(path);
+            },
+        );
 
         let mut path = DrawPath::new(outline, paint_id);
         path.set_clip_path(clip_path);
         path.set_fill_rule(fill_rule);
         path.set_blend_mode(blend_mode);
         self.canvas.scene.push_draw_path(path);
-
-        fn push_shadow_blur_render_targets_if_needed(
-            scene: &mut Scene,
-            current_state: &State,
-            outline_bounds: RectF,
-        ) -> Option<ShadowBlurRenderTargetInfo> {
-            if current_state.shadow_blur == 0.0 {
-                return None;
-            }
-
-            let sigma = current_state.shadow_blur * 0.5;
-            let bounds = outline_bounds.dilate(sigma * 3.0).round_out().to_i32();
-
-            let render_target_y = RenderTarget::new(bounds.size(), String::new());
-            let render_target_id_y = scene.push_render_target(render_target_y);
-            let render_target_x = RenderTarget::new(bounds.size(), String::new());
-            let render_target_id_x = scene.push_render_target(render_target_x);
-
-            Some(ShadowBlurRenderTargetInfo {
-                id_x: render_target_id_x,
-                id_y: render_target_id_y,
-                bounds,
-                sigma,
-            })
-        }
-
-        fn composite_shadow_blur_render_targets_if_needed(
-            scene: &mut Scene,
-            info: Option<ShadowBlurRenderTargetInfo>,
-            clip_path: Option<ClipPathId>,
-        ) {
-            let info = match info {
-                None => return,
-                Some(info) => info,
-            };
-
-            let mut paint_x = Pattern::from_render_target(info.id_x, info.bounds.size());
-            let mut paint_y = Pattern::from_render_target(info.id_y, info.bounds.size());
-            paint_y.apply_transform(Transform2F::from_translation(info.bounds.origin().to_f32()));
-
-            let sigma = info.sigma;
-            paint_x.set_filter(Some(PatternFilter::Blur {
-                direction: BlurDirection::X,
-                sigma,
-            }));
-            paint_y.set_filter(Some(PatternFilter::Blur {
-                direction: BlurDirection::Y,
-                sigma,
-            }));
-
-            let paint_id_x = scene.push_paint(&Paint::from_pattern(paint_x));
-            let paint_id_y = scene.push_paint(&Paint::from_pattern(paint_y));
-
-            // TODO(pcwalton): Apply clip as necessary.
-            let outline_x =
-                Outline::from_rect(RectF::new(vec2f(0.0, 0.0), info.bounds.size().to_f32()));
-            let path_x = DrawPath::new(outline_x, paint_id_x);
-            let outline_y = Outline::from_rect(info.bounds.to_f32());
-            let mut path_y = DrawPath::new(outline_y, paint_id_y);
-            path_y.set_clip_path(clip_path);
-
-            scene.pop_render_target();
-            scene.push_draw_path(path_x);
-            scene.pop_render_target();
-            scene.push_draw_path(path_y);
-        }
     }
 
     // Transformations
@@ -571,6 +633,10 @@ impl CanvasRenderingContext2D {
         self.current_state.global_composite_operation
     }
 
+    /// Sets the composite operation used to draw shapes and images.
+    ///
+    /// As in HTML canvas, operations like `SourceIn` and `Copy` clear the parts of the canvas
+    /// outside the shape or text, within the clip.
     #[inline]
     pub fn set_global_composite_operation(&mut self, new_composite_operation: CompositeOperation) {
         self.current_state.global_composite_operation = new_composite_operation;
@@ -589,34 +655,120 @@ impl CanvasRenderingContext2D {
         self.draw_subimage(pattern, src_rect, dest_location)
     }
 
+    /// Draws the part of the image in `src_location` into the destination, scaling it to fit.
+    ///
+    /// As in HTML canvas, the source rectangle is clipped to the image, and the destination shrinks
+    /// in proportion. Smoothing never blends in pixels from outside the source rectangle, so
+    /// sprites can be drawn from a sprite sheet.
     pub fn draw_subimage<I, L>(&mut self, image: I, src_location: RectF, dest_location: L)
     where
         I: CanvasImageSource,
         L: CanvasImageDestLocation,
     {
+        let mut pattern = image.to_pattern(self, Transform2F::default());
         let dest_size = dest_location.size().unwrap_or(src_location.size());
         let scale = dest_size / src_location.size();
-        let offset = dest_location.origin() - src_location.origin() * scale;
-        let transform = Transform2F::from_scale(scale).translate(offset);
 
-        let pattern = image.to_pattern(self, transform);
+        // A pattern with its own transform has no fixed bounds in source space.
+        let untransformed = pattern.transform().is_identity();
+        let src_rect = if untransformed {
+            let image_rect = RectF::new(Vector2F::zero(), pattern.size().to_f32());
+            match src_location.intersection(image_rect) {
+                Some(src_rect) => src_rect,
+                None => return,
+            }
+        } else {
+            src_location
+        };
+        let dest_origin =
+            dest_location.origin() + (src_rect.origin() - src_location.origin()) * scale;
+        let dest_rect = RectF::new(dest_origin, src_rect.size() * scale);
+
+        // Crop images to the pixels that the source rectangle touches, so that the sampler can't
+        // reach their neighbors.
+        let mut src_origin = Vector2F::zero();
+        if let PatternSource::Image(ref image) = *pattern.source() {
+            let crop_rect = src_rect.round_out().to_i32();
+            if untransformed && crop_rect.size() != image.size() {
+                let mut cropped = Pattern::from_image(crop_image(image, crop_rect));
+                cropped.set_filter(pattern.filter());
+                pattern = cropped;
+                src_origin = crop_rect.origin().to_f32() - vec2f(1.0, 1.0);
+            }
+        }
+
+        let offset = dest_rect.origin() - (src_rect.origin() - src_origin) * scale;
+        pattern.apply_transform(Transform2F::from_scale(scale).translate(offset));
         let old_fill_paint = self.current_state.fill_paint.clone();
         self.set_fill_style(pattern);
-        self.fill_rect(RectF::new(dest_location.origin(), dest_size));
+        self.fill_rect(dest_rect);
         self.current_state.fill_paint = old_fill_paint;
     }
 
     // Pixel manipulation
 
+    /// Renders everything drawn so far and returns the pixels in the given rectangle of the
+    /// canvas. The rectangle is in canvas pixels, unaffected by the current transform.
+    ///
+    /// The scene is rendered on the CPU, so this is much slower than drawing. Pixels outside the
+    /// canvas are transparent black.
+    ///
+    /// The CPU renderer can't apply blurs (from `shadow_blur` or a `blur()` filter) or text
+    /// filters, so this fails rather than returning pixels that differ from what the GPU draws.
+    pub fn get_image_data(&self, rect: RectI) -> Result<ImageData, ImageDataError> {
+        if let Some(filter) = pathfinder_rasterize::unsupported_filter(&self.canvas.scene) {
+            return Err(ImageDataError::UnsupportedFilter(filter));
+        }
+
+        // The whole canvas is rendered untransformed, since a transform would also move the
+        // contents of render targets that shadows and filters sample in canvas space.
+        let canvas_size = self.canvas.size();
+        let options = RasterizeOptions::new(canvas_size);
+        let image = pathfinder_rasterize::rasterize(&self.canvas.scene, &options);
+
+        let canvas_rect = RectI::new(Vector2I::zero(), canvas_size);
+        let mut data = Vec::with_capacity(rect.size().area() as usize);
+        for y in rect.min_y()..rect.max_y() {
+            for x in rect.min_x()..rect.max_x() {
+                data.push(if canvas_rect.contains_point(vec2i(x, y)) {
+                    image.pixels()[(y * canvas_size.x() + x) as usize]
+                } else {
+                    ColorU::transparent_black()
+                });
+            }
+        }
+        Ok(ImageData {
+            data,
+            size: rect.size(),
+        })
+    }
+
+    /// Replaces the pixels in the destination rectangle with the image data, which is stretched
+    /// to fill it if the sizes differ.
+    ///
+    /// As in HTML canvas, the current transform, clip, global alpha, shadow, and composite
+    /// operation are ignored.
     pub fn put_image_data<L>(&mut self, image_data: ImageData, dest_location: L)
     where
         L: CanvasImageDestLocation,
     {
         let origin = dest_location.origin();
         let size = dest_location.size().unwrap_or(image_data.size.to_f32());
-        let pattern = Pattern::from_image(image_data.into_image());
+        let dest_rect = RectF::new(origin, size);
+
+        // Erase what's underneath first, so that transparent image data replaces it rather than
+        // being composited over it.
+        let eraser_id = self.canvas.scene.push_paint(&Paint::black());
+        let mut eraser = DrawPath::new(Outline::from_rect(dest_rect), eraser_id);
+        eraser.set_blend_mode(BlendMode::DestOut);
+        self.canvas.scene.push_draw_path(eraser);
+
+        let scale = size / image_data.size.to_f32();
+        let mut pattern = Pattern::from_image(image_data.into_image());
+        pattern.apply_transform(Transform2F::from_scale(scale).translate(origin));
+        pattern.set_smoothing_enabled(false);
         let paint_id = self.canvas.scene.push_paint(&Paint::from_pattern(pattern));
-        let draw_path = DrawPath::new(Outline::from_rect(RectF::new(origin, size)), paint_id);
+        let draw_path = DrawPath::new(Outline::from_rect(dest_rect), paint_id);
         self.canvas.scene.push_draw_path(draw_path);
     }
 
@@ -627,6 +779,8 @@ impl CanvasRenderingContext2D {
         self.current_state.image_smoothing_enabled
     }
 
+    /// Sets whether scaled images and patterns are drawn with bilinear filtering or, if false,
+    /// with nearest-neighbor filtering, which keeps pixel art crisp.
     #[inline]
     pub fn set_image_smoothing_enabled(&mut self, enabled: bool) {
         self.current_state.image_smoothing_enabled = enabled
@@ -637,6 +791,10 @@ impl CanvasRenderingContext2D {
         self.current_state.image_smoothing_quality
     }
 
+    /// Sets the preferred quality of image smoothing.
+    ///
+    /// As HTML canvas allows, this is only a hint: every quality currently uses bilinear
+    /// filtering.
     #[inline]
     pub fn set_image_smoothing_quality(&mut self, new_quality: ImageSmoothingQuality) {
         self.current_state.image_smoothing_quality = new_quality
@@ -646,16 +804,79 @@ impl CanvasRenderingContext2D {
 
     #[inline]
     pub fn save(&mut self) {
-        self.saved_states.push(self.current_state.clone());
+        self.saved_states.push((self.current_state.clone(), None));
     }
 
-    #[inline]
+    /// Restores the most recently saved state. If it was saved with `save_layer()`, the layer is
+    /// composited onto the canvas.
     pub fn restore(&mut self) {
-        if let Some(state) = self.saved_states.pop() {
+        if let Some((state, layer)) = self.saved_states.pop() {
             self.current_state = state;
+            if let Some(layer) = layer {
+                self.composite_layer(layer);
+            }
         }
     }
 
+    /// Saves the state, like `save()`, and begins a layer that everything drawn until the
+    /// matching `restore()` goes to. On restore, the layer is composited onto the canvas as a
+    /// single image with the given alpha and composite operation, clipped to the clip in effect
+    /// now.
+    ///
+    /// This is how to give a group of overlapping shapes a collective opacity. If `bounds` is
+    /// given, in the current coordinate system, the layer only covers that rectangle, and
+    /// anything drawn outside it is discarded. The layer starts out transparent, and the state
+    /// inside it, including the global alpha and composite operation, is unchanged.
+    pub fn save_layer(
+        &mut self,
+        bounds: Option<RectF>,
+        alpha: f32,
+        composite_operation: CompositeOperation,
+    ) {
+        let canvas_rect = RectF::new(Vector2F::zero(), self.canvas.size().to_f32());
+        let region = match bounds {
+            Some(bounds) => (self.current_state.transform * bounds)
+                .round_out()
+                .intersection(canvas_rect)
+                .unwrap_or_default(),
+            None => canvas_rect,
+        };
+        let layer = Layer {
+            render_target_id: self.push_filter_render_target(),
+            region,
+            alpha,
+            blend_mode: composite_operation.to_blend_mode(),
+        };
+        self.saved_states
+            .push((self.current_state.clone(), Some(layer)));
+
+        if let Some(bounds) = bounds {
+            let mut path = Path2D::new();
+            path.rect(bounds);
+            self.clip_path(path, FillRule::Winding);
+        }
+    }
+
+    fn composite_layer(&mut self, layer: Layer) {
+        self.canvas.scene.pop_render_target();
+
+        // Composite operations like `source-in` clear the canvas outside the layer too.
+        let mut region = layer.region;
+        if layer.blend_mode.is_destructive() {
+            region = RectF::new(Vector2F::zero(), self.canvas.size().to_f32());
+        }
+
+        let mut paint = self.render_target_paint(layer.render_target_id, Vector2F::zero(), None);
+        let alpha = (layer.alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+        paint.set_base_color(ColorU::new(255, 255, 255, alpha));
+        let paint_id = self.canvas.scene.push_paint(&paint);
+
+        let mut path = DrawPath::new(Outline::from_rect(region), paint_id);
+        path.set_clip_path(self.current_state.clip_path);
+        path.set_blend_mode(layer.blend_mode);
+        self.canvas.scene.push_draw_path(path);
+    }
+
     // Extensions
 
     pub fn create_pattern_from_canvas(
@@ -692,8 +913,14 @@ struct State {
     shadow_color: ColorU,
     shadow_blur: f32,
     shadow_offset: Vector2F,
+    filter: String,
+    filter_functions: Vec<FilterFunction>,
     text_align: TextAlign,
     text_baseline: TextBaseline,
+    direction: TextDirection,
+    letter_spacing: f32,
+    word_spacing: f32,
+    text_rendering: TextRendering,
     image_smoothing_enabled: bool,
     image_smoothing_quality: ImageSmoothingQuality,
     global_alpha: f32,
@@ -718,8 +945,14 @@ impl State {
             shadow_color: ColorU::transparent_black(),
             shadow_blur: 0.0,
             shadow_offset: Vector2F::zero(),
-            text_align: TextAlign::Left,
+            filter: "none".to_owned(),
+            filter_functions: vec![],
+            text_align: TextAlign::Start,
             text_baseline: TextBaseline::Alphabetic,
+            direction: TextDirection::Ltr,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            text_rendering: TextRendering::Auto,
             image_smoothing_enabled: true,
             image_smoothing_quality: ImageSmoothingQuality::Low,
             global_alpha: 1.0,
@@ -753,6 +986,12 @@ impl State {
         Cow::Owned(paint)
     }
 
+    // Per spec, shadows are drawn only if they're visible and either blurred or offset.
+    fn casts_shadow(&self) -> bool {
+        !self.shadow_color.is_fully_transparent()
+            && (self.shadow_blur != 0.0 || self.shadow_offset != Vector2F::zero())
+    }
+
     fn resolve_stroke_style(&self) -> StrokeStyle {
         StrokeStyle {
             line_width: self.line_width,
@@ -789,6 +1028,16 @@ impl Path2D {
         }
     }
 
+    /// Creates a path from SVG path data, like the `d` attribute of a `<path>` element.
+    ///
+    /// As in HTML canvas and SVG, if the data contains an error, the path consists of the commands
+    /// before the error.
+    pub fn from_svg_path_data(data: &str) -> Path2D {
+        let mut path = Path2D::new();
+        path_data::parse_path_data(data, &mut path);
+        path
+    }
+
     #[inline]
     pub fn close_path(&mut self) {
         self.current_contour.close();
@@ -824,27 +1073,56 @@ impl Path2D {
         end_angle: f32,
         direction: ArcDirection,
     ) {
-        let transform = Transform2F::from_scale(radius).translate(center);
-        self.current_contour
-            .push_arc(&transform, start_angle, end_angle, direction);
+        self.ellipse(center, radius, 0.0, start_angle, end_angle, direction);
     }
 
-    #[inline]
+    /// Adds an arc of the given radius that is tangent to the line from the current point to
+    /// `ctrl` and to the line from `ctrl` to `to`, connected to the current point by a straight
+    /// line.
+    ///
+    /// As in HTML canvas, if the points are collinear or the radius is zero, this adds a straight
+    /// line to `ctrl` instead. Negative radii are ignored.
     pub fn arc_to(&mut self, ctrl: Vector2F, to: Vector2F, radius: f32) {
-        // FIXME(pcwalton): What should we do if there's no initial point?
-        let from = self.current_contour.last_position().unwrap_or_default();
+        if radius < 0.0 {
+            return;
+        }
+        let from = match self.current_contour.last_position() {
+            Some(from) => from,
+            None => {
+                self.move_to(ctrl);
+                return;
+            }
+        };
+
         let (v0, v1) = (from - ctrl, to - ctrl);
+        if radius == 0.0 || v0.is_zero() || v1.is_zero() {
+            self.line_to(ctrl);
+            return;
+        }
         let (vu0, vu1) = (v0.normalize(), v1.normalize());
-        let hypot = radius / f32::sqrt(0.5 * (1.0 - vu0.dot(vu1)));
-        let bisector = vu0 + vu1;
-        let center = ctrl + bisector * (hypot / bisector.length());
+        let (cos, sin) = (vu0.dot(vu1), vu0.det(vu1));
+        if sin.abs() < EPSILON {
+            self.line_to(ctrl);
+            return;
+        }
 
+        // The arc's center lies on the bisector of the corner, and it touches both lines at the
+        // same distance from the corner.
+        let tangent_distance = radius * f32::sqrt((1.0 + cos) / (1.0 - cos));
+        let center_distance = radius / f32::sqrt(0.5 * (1.0 - cos));
+        let center = ctrl + (vu0 + vu1).normalize() * center_distance;
+        let start = ctrl + vu0 * tangent_distance;
+        let end = ctrl + vu1 * tangent_distance;
+
+        let direction = if sin < 0.0 {
+            ArcDirection::CW
+        } else {
+            ArcDirection::CCW
+        };
         let transform = Transform2F::from_scale(radius).translate(center);
-        let chord = LineSegment2F::new(vu0.yx() * vec2f(-1.0, 1.0), vu1.yx() * vec2f(1.0, -1.0));
-
-        // FIXME(pcwalton): Is clockwise direction correct?
+        let chord = LineSegment2F::new((start - center) / radius, (end - center) / radius);
         self.current_contour
-            .push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
+            .push_arc_from_unit_chord(&transform, chord, direction);
     }
 
     pub fn rect(&mut self, rect: RectF) {
@@ -856,6 +1134,71 @@ impl Path2D {
         self.current_contour.close();
     }
 
+    /// Adds a closed rectangle with rounded corners as a new subpath.
+    ///
+    /// As in HTML canvas, radii that don't fit are scaled down together, and the path goes
+    /// clockwise from the top left corner, even if the rectangle has a negative size. Negative
+    /// radii are ignored.
+    pub fn round_rect<R>(&mut self, rect: RectF, radii: R)
+    where
+        R: Into<CornerRadii>,
+    {
+        let CornerRadii {
+            mut upper_left,
+            mut upper_right,
+            mut lower_right,
+            mut lower_left,
+        } = radii.into();
+        let all_radii = [upper_left, upper_right, lower_right, lower_left];
+        if all_radii
+            .iter()
+            .any(|radii| radii.x() < 0.0 || radii.y() < 0.0)
+        {
+            return;
+        }
+
+        if rect.width() < 0.0 {
+            mem::swap(&mut upper_left, &mut upper_right);
+            mem::swap(&mut lower_left, &mut lower_right);
+        }
+        if rect.height() < 0.0 {
+            mem::swap(&mut upper_left, &mut lower_left);
+            mem::swap(&mut upper_right, &mut lower_right);
+        }
+        let (corner, opposite_corner) = (rect.origin(), rect.lower_right());
+        let rect = RectF::from_points(corner.min(opposite_corner), corner.max(opposite_corner));
+
+        let scale = [
+            rect.width() / (upper_left.x() + upper_right.x()),
+            rect.height() / (upper_right.y() + lower_right.y()),
+            rect.width() / (lower_right.x() + lower_left.x()),
+            rect.height() / (upper_left.y() + lower_left.y()),
+        ]
+        .iter()
+        .fold(1.0, |scale: f32, &side_scale| scale.min(side_scale));
+        let (upper_left, upper_right) = (upper_left * scale, upper_right * scale);
+        let (lower_right, lower_left) = (lower_right * scale, lower_left * scale);
+
+        self.move_to(rect.origin() + vec2f(upper_left.x(), 0.0));
+        self.push_corner(
+            rect.upper_right() + upper_right * vec2f(-1.0, 1.0),
+            upper_right,
+            -0.5 * PI,
+        );
+        self.push_corner(rect.lower_right() - lower_right, lower_right, 0.0);
+        self.push_corner(
+            rect.lower_left() + lower_left * vec2f(1.0, -1.0),
+            lower_left,
+            0.5 * PI,
+        );
+        self.push_corner(rect.origin() + upper_left, upper_left, PI);
+        self.close_path();
+    }
+
+    /// Adds an arc of an ellipse, connected to the current point by a straight line.
+    ///
+    /// As in HTML canvas, if the arc sweeps all the way around in the given direction, it becomes
+    /// a full ellipse beginning and ending at the start angle. Negative radii are ignored.
     pub fn ellipse<A>(
         &mut self,
         center: Vector2F,
@@ -863,27 +1206,43 @@ impl Path2D {
         rotation: f32,
         start_angle: f32,
         end_angle: f32,
+        direction: ArcDirection,
     ) where
         A: IntoVector2F,
     {
-        self.flush_current_contour();
+        let axes = axes.into_vector_2f();
+        if axes.x() < 0.0 || axes.y() < 0.0 {
+            return;
+        }
 
-        let transform = Transform2F::from_scale(axes)
+        let mut transform = Transform2F::from_scale(axes)
             .rotate(rotation)
-            .translate(center);
-        self.current_contour
-            .push_arc(&transform, start_angle, end_angle, ArcDirection::CW);
-
-        if end_angle - start_angle >= 2.0 * PI {
-            self.current_contour.close();
+            .translate(center)
+            * Transform2F::from_rotation(start_angle);
+        let sweep = match direction {
+            ArcDirection::CW => end_angle - start_angle,
+            ArcDirection::CCW => start_angle - end_angle,
+        };
+        if sweep >= 2.0 * PI {
+            if direction == ArcDirection::CCW {
+                transform *= Transform2F::from_scale(vec2f(1.0, -1.0));
+            }
+            self.current_contour.push_ellipse(&transform);
+        } else {
+            let end_angle = end_angle - start_angle;
+            let chord =
+                LineSegment2F::new(vec2f(1.0, 0.0), vec2f(end_angle.cos(), end_angle.sin()));
+            self.current_contour
+                .push_arc_from_unit_chord(&transform, chord, direction);
         }
     }
 
     // https://html.spec.whatwg.org/multipage/canvas.html#dom-path2d-addpath
-    pub fn add_path(&mut self, mut path: Path2D, transform: &Transform2F) {
+    pub fn add_path(&mut self, path: &Path2D, transform: Transform2F) {
         self.flush_current_contour();
+        let mut path = path.clone();
         path.flush_current_contour();
-        path.outline.transform(transform);
+        path.outline.transform(&transform);
         let last_contour = path.outline.pop_contour();
         for contour in path.outline.into_contours() {
             self.outline.push_contour(contour);
@@ -901,6 +1260,17 @@ impl Path2D {
         self.outline
     }
 
+    // Adds a clockwise quarter of the ellipse with the given center and radii, or a line to the
+    // center if either radius is zero.
+    fn push_corner(&mut self, center: Vector2F, radii: Vector2F, start_angle: f32) {
+        if radii.x() == 0.0 || radii.y() == 0.0 {
+            self.line_to(center);
+        } else {
+            let end_angle = start_angle + 0.5 * PI;
+            self.ellipse(center, radii, 0.0, start_angle, end_angle, ArcDirection::CW);
+        }
+    }
+
     fn flush_current_contour(&mut self) {
         if !self.current_contour.is_empty() {
             self.outline
@@ -909,6 +1279,57 @@ impl Path2D {
     }
 }
 
+/// The radii of the corners of a rounded rectangle, which may be elliptical.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CornerRadii {
+    pub upper_left: Vector2F,
+    pub upper_right: Vector2F,
+    pub lower_right: Vector2F,
+    pub lower_left: Vector2F,
+}
+
+impl CornerRadii {
+    /// Creates radii from the values of a CSS-style list of one to four radii.
+    ///
+    /// One radius applies to every corner. With two, the first applies to the upper left and
+    /// lower right corners. With three, the second applies to the upper right and lower left
+    /// corners. Four go clockwise from the upper left. Returns `None` for any other length.
+    pub fn from_list(radii: &[Vector2F]) -> Option<CornerRadii> {
+        let (upper_left, upper_right, lower_right, lower_left) = match *radii {
+            [radius] => (radius, radius, radius, radius),
+            [first, second] => (first, second, first, second),
+            [first, second, third] => (first, second, third, second),
+            [first, second, third, fourth] => (first, second, third, fourth),
+            _ => return None,
+        };
+        Some(CornerRadii {
+            upper_left,
+            upper_right,
+            lower_right,
+            lower_left,
+        })
+    }
+}
+
+impl From<f32> for CornerRadii {
+    #[inline]
+    fn from(radius: f32) -> CornerRadii {
+        CornerRadii::from(Vector2F::splat(radius))
+    }
+}
+
+impl From<Vector2F> for CornerRadii {
+    #[inline]
+    fn from(radii: Vector2F) -> CornerRadii {
+        CornerRadii {
+            upper_left: radii,
+            upper_right: radii,
+            lower_right: radii,
+            lower_left: radii,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum FillStyle {
     Color(ColorU),
@@ -931,6 +1352,10 @@ pub enum TextAlign {
     Left,
     Right,
     Center,
+    /// The left for left-to-right text, or the right for right-to-left text.
+    Start,
+    /// The right for left-to-right text, or the left for right-to-left text.
+    End,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -943,6 +1368,20 @@ pub enum TextBaseline {
     Bottom,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextRendering {
+    Auto,
+    OptimizeSpeed,
+    OptimizeLegibility,
+    GeometricPrecision,
+}
+
 // We duplicate `pathfinder_content::stroke::LineJoin` here because the HTML canvas API treats the
 // miter limit as part of the canvas state, while the native Pathfinder API treats the miter limit
 // as part of the line join. Pathfinder's choice is more logical, because the miter limit is
@@ -1025,6 +1464,14 @@ pub enum ImageSmoothingQuality {
     High,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternRepetition {
+    Repeat,
+    RepeatX,
+    RepeatY,
+    NoRepeat,
+}
+
 pub trait CanvasImageSource {
     fn to_pattern(
         self,
@@ -1046,6 +1493,15 @@ impl CanvasImageSource for Pattern {
     }
 }
 
+impl CanvasImageSource for Image {
+    #[inline]
+    fn to_pattern(self, _: &mut CanvasRenderingContext2D, transform: Transform2F) -> Pattern {
+        let mut pattern = Pattern::from_image(self);
+        pattern.apply_transform(transform);
+        pattern
+    }
+}
+
 impl CanvasImageSource for Canvas {
     #[inline]
     fn to_pattern(
@@ -1099,6 +1555,31 @@ impl ImageData {
     }
 }
 
+/// Why pixels couldn't be read back from a canvas.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageDataError {
+    /// The canvas draws a pattern with a filter that the CPU renderer can't apply.
+    UnsupportedFilter(PatternFilter),
+}
+
+impl Display for ImageDataError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            ImageDataError::UnsupportedFilter(PatternFilter::Blur { .. }) => {
+                write!(formatter, "can't read back pixels drawn with a blur")
+            }
+            ImageDataError::UnsupportedFilter(_) => {
+                write!(
+                    formatter,
+                    "can't read back pixels drawn with a pattern filter"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ImageDataError {}
+
 impl Debug for Path2D {
     fn fmt(&self, formatter: &mut Formatter) -> Result<(), FmtError> {
         self.clone().into_outline().fmt(formatter)
@@ -1126,6 +1607,169 @@ impl From<Pattern> for FillStyle {
     }
 }
 
+// Copies the pixels in the rectangle, with a one-pixel border that repeats its edges so that
+// smoothing clamps to them.
+fn crop_image(image: &Image, rect: RectI) -> Image {
+    let (width, pixels) = (image.size().x(), image.pixels());
+    let mut cropped = Vec::with_capacity(((rect.width() + 2) * (rect.height() + 2)) as usize);
+    for y in (rect.min_y() - 1)..(rect.max_y() + 1) {
+        let y = y.max(rect.min_y()).min(rect.max_y() - 1);
+        for x in (rect.min_x() - 1)..(rect.max_x() + 1) {
+            let x = x.max(rect.min_x()).min(rect.max_x() - 1);
+            cropped.push(pixels[(y * width + x) as usize]);
+        }
+    }
+    Image::new(rect.size() + vec2i(2, 2), Arc::new(cropped))
+}
+
+// Copies a clip path, along with the clip paths that clip it, from one scene to another, and
+// returns its ID in the new scene. Clip paths that have already been copied are reused.
+fn copy_clip_path(
+    old_scene: &Scene,
+    new_scene: &mut Scene,
+    clip_path_id: ClipPathId,
+    new_clip_path_ids: &mut HashMap<ClipPathId, ClipPathId>,
+) -> ClipPathId {
+    if let Some(&new_clip_path_id) = new_clip_path_ids.get(&clip_path_id) {
+        return new_clip_path_id;
+    }
+    let mut clip_path = old_scene.get_clip_path(clip_path_id).clone();
+    clip_path.clip_path = clip_path
+        .clip_path
+        .map(|subclip_id| copy_clip_path(old_scene, new_scene, subclip_id, new_clip_path_ids));
+    let new_clip_path_id = new_scene.push_clip_path(clip_path);
+    new_clip_path_ids.insert(clip_path_id, new_clip_path_id);
+    new_clip_path_id
+}
+
+/// Draws the shadow of a shape with the given paint and bounds in canvas space, if the state
+/// casts one.
+///
+/// `push_shape` draws the shape into the scene with the supplied transform, which moves it
+/// into place, the shadow paint, and the clip path.
+pub(crate) fn push_shadow<F>(
+    scene: &mut Scene,
+    state: &State,
+    paint: &Paint,
+    bounds: RectF,
+    push_shape: F,
+) where
+    F: FnOnce(&mut Scene, Transform2F, PaintId, Option<ClipPathId>),
+{
+    if !state.casts_shadow() {
+        return;
+    }
+
+    let clip_path = state.clip_path;
+    let mut shadow_transform = Transform2F::from_translation(state.shadow_offset);
+    let shadow_blur_info =
+        push_shadow_blur_render_targets_if_needed(scene, state, shadow_transform * bounds);
+
+    if let Some(ref shadow_blur_info) = shadow_blur_info {
+        shadow_transform =
+            Transform2F::from_translation(-shadow_blur_info.bounds.origin().to_f32())
+                * shadow_transform;
+    }
+
+    // Per spec the shadow must respect the alpha of the shadowed shape, but otherwise have
+    // the color of the shadow paint. The paint moves along with the shape so that patterns and
+    // gradients mask the shadow where they are drawn.
+    let mut shadow_paint = (*paint).clone();
+    shadow_paint.apply_transform(&shadow_transform);
+    let shadow_base_alpha = shadow_paint.base_color().a;
+    let mut shadow_color = state.shadow_color.to_f32();
+    shadow_color.set_a(shadow_color.a() * shadow_base_alpha as f32 / 255.0);
+    shadow_paint.set_base_color(shadow_color.to_u8());
+    if let &mut Some(ref mut shadow_paint_overlay) = shadow_paint.overlay_mut() {
+        shadow_paint_overlay.set_composite_op(PaintCompositeOp::DestIn);
+    }
+    let shadow_paint_id = scene.push_paint(&shadow_paint);
+
+    // A blurred shadow is clipped when it's composited instead.
+    let shape_clip_path = match shadow_blur_info {
+        None => clip_path,
+        Some(_) => None,
+    };
+    push_shape(scene, shadow_transform, shadow_paint_id, shape_clip_path);
+
+    composite_shadow_blur_render_targets_if_needed(scene, shadow_blur_info, clip_path);
+
+    fn push_shadow_blur_render_targets_if_needed(
+        scene: &mut Scene,
+        current_state: &State,
+        outline_bounds: RectF,
+    ) -> Option<ShadowBlurRenderTargetInfo> {
+        if current_state.shadow_blur == 0.0 {
+            return None;
+        }
+
+        let sigma = current_state.shadow_blur * 0.5;
+        let bounds = outline_bounds.dilate(sigma * 3.0).round_out().to_i32();
+
+        let render_target_y = RenderTarget::new(bounds.size(), String::new());
+        let render_target_id_y = scene.push_render_target(render_target_y);
+        let render_target_x = RenderTarget::new(bounds.size(), String::new());
+        let render_target_id_x = scene.push_render_target(render_target_x);
+
+        Some(ShadowBlurRenderTargetInfo {
+            id_x: render_target_id_x,
+            id_y: render_target_id_y,
+            bounds,
+            sigma,
+        })
+    }
+
+    fn composite_shadow_blur_render_targets_if_needed(
+        scene: &mut Scene,
+        info: Option<ShadowBlurRenderTargetInfo>,
+        clip_path: Option<ClipPathId>,
+    ) {
+        let info = match info {
+            None => return,
+            Some(info) => info,
+        };
+
+        let mut paint_x = Pattern::from_render_target(info.id_x, info.bounds.size());
+        let mut paint_y = Pattern::from_render_target(info.id_y, info.bounds.size());
+        paint_y.apply_transform(Transform2F::from_translation(info.bounds.origin().to_f32()));
+
+        let sigma = info.sigma;
+        paint_x.set_filter(Some(PatternFilter::Blur {
+            direction: BlurDirection::X,
+            sigma,
+        }));
+        paint_y.set_filter(Some(PatternFilter::Blur {
+            direction: BlurDirection::Y,
+            sigma,
+        }));
+
+        let paint_id_x = scene.push_paint(&Paint::from_pattern(paint_x));
+        let paint_id_y = scene.push_paint(&Paint::from_pattern(paint_y));
+
+        // TODO(pcwalton): Apply clip as necessary.
+        let outline_x =
+            Outline::from_rect(RectF::new(vec2f(0.0, 0.0), info.bounds.size().to_f32()));
+        let path_x = DrawPath::new(outline_x, paint_id_x);
+        let outline_y = Outline::from_rect(info.bounds.to_f32());
+        let mut path_y = DrawPath::new(outline_y, paint_id_y);
+        path_y.set_clip_path(clip_path);
+
+        scene.pop_render_target();
+        scene.push_draw_path(path_x);
+        scene.pop_render_target();
+        scene.push_draw_path(path_y);
+    }
+}
+
+// A layer begun by `save_layer()`.
+struct Layer {
+    render_target_id: RenderTargetId,
+    // The part of the canvas that the layer's bounds cover.
+    region: RectF,
+    alpha: f32,
+    blend_mode: BlendMode,
+}
+
 struct ShadowBlurRenderTargetInfo {
     id_x: RenderTargetId,
     id_y: RenderTargetId,
@@ -0,0 +1,286 @@
+// pathfinder/canvas/src/path_data.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of SVG path data, the syntax of the `d` attribute, into `Path2D`s.
+
+use crate::Path2D;
+use pathfinder_content::outline::ArcDirection;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+
+/// Appends the commands in the SVG path data to the path.
+///
+/// As the SVG specification requires, parsing stops at the first error, and everything before
+/// the erroneous command is kept.
+pub(crate) fn parse_path_data(data: &str, path: &mut Path2D) {
+    let mut parser = PathDataParser {
+        data: data.as_bytes(),
+        position: 0,
+    };
+    let mut state = PathDataState::default();
+    let mut last_command = None;
+
+    loop {
+        parser.skip_whitespace();
+        let next = match parser.peek() {
+            None => return,
+            Some(next) => next,
+        };
+
+        let command = if next.is_ascii_alphabetic() {
+            parser.position += 1;
+            next
+        } else {
+            // Repeated parameters repeat the previous command, except that the parameters after
+            // a moveto are linetos.
+            match last_command {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(b'Z') | Some(b'z') | None => return,
+                Some(command) => command,
+            }
+        };
+
+        // Path data must begin with a moveto.
+        if last_command.is_none() && command != b'M' && command != b'm' {
+            return;
+        }
+
+        if state.execute(command, &mut parser, path).is_none() {
+            return;
+        }
+        last_command = Some(command);
+    }
+}
+
+#[derive(Default)]
+struct PathDataState {
+    current_point: Vector2F,
+    subpath_start: Vector2F,
+    // The second control point of the previous command, if it was a cubic curve.
+    last_cubic_ctrl: Option<Vector2F>,
+    // The control point of the previous command, if it was a quadratic curve.
+    last_quadratic_ctrl: Option<Vector2F>,
+    // Whether the last command closed the subpath, so that drawing must start a new one.
+    closed: bool,
+}
+
+impl PathDataState {
+    // Parses the parameters of one command and applies it, all or nothing.
+    fn execute(
+        &mut self,
+        command: u8,
+        parser: &mut PathDataParser,
+        path: &mut Path2D,
+    ) -> Option<()> {
+        let origin = if command.is_ascii_lowercase() {
+            self.current_point
+        } else {
+            Vector2F::zero()
+        };
+        let (last_cubic_ctrl, last_quadratic_ctrl) =
+            (self.last_cubic_ctrl.take(), self.last_quadratic_ctrl.take());
+
+        let command = command.to_ascii_uppercase();
+        match command {
+            b'M' => {
+                let to = origin + parser.point()?;
+                path.move_to(to);
+                self.subpath_start = to;
+                self.current_point = to;
+                self.closed = false;
+                return Some(());
+            }
+            b'Z' => {
+                path.close_path();
+                self.current_point = self.subpath_start;
+                self.closed = true;
+                return Some(());
+            }
+            _ => {}
+        }
+
+        let to = match command {
+            b'L' => origin + parser.point()?,
+            b'H' => vec2f(origin.x() + parser.number()?, self.current_point.y()),
+            b'V' => vec2f(self.current_point.x(), origin.y() + parser.number()?),
+            b'C' => {
+                let ctrl0 = origin + parser.point()?;
+                let ctrl1 = origin + parser.point()?;
+                let to = origin + parser.point()?;
+                self.start_drawing(path);
+                path.bezier_curve_to(ctrl0, ctrl1, to);
+                self.last_cubic_ctrl = Some(ctrl1);
+                to
+            }
+            b'S' => {
+                let ctrl1 = origin + parser.point()?;
+                let to = origin + parser.point()?;
+                let ctrl0 = reflect(last_cubic_ctrl, self.current_point);
+                self.start_drawing(path);
+                path.bezier_curve_to(ctrl0, ctrl1, to);
+                self.last_cubic_ctrl = Some(ctrl1);
+                to
+            }
+            b'Q' => {
+                let ctrl = origin + parser.point()?;
+                let to = origin + parser.point()?;
+                self.start_drawing(path);
+                path.quadratic_curve_to(ctrl, to);
+                self.last_quadratic_ctrl = Some(ctrl);
+                to
+            }
+            b'T' => {
+                let to = origin + parser.point()?;
+                let ctrl = reflect(last_quadratic_ctrl, self.current_point);
+                self.start_drawing(path);
+                path.quadratic_curve_to(ctrl, to);
+                self.last_quadratic_ctrl = Some(ctrl);
+                to
+            }
+            b'A' => {
+                let radius = vec2f(parser.number()?, parser.number()?);
+                let x_axis_rotation = parser.number()?.to_radians();
+                let large_arc = parser.flag()?;
+                let direction = if parser.flag()? {
+                    ArcDirection::CW
+                } else {
+                    ArcDirection::CCW
+                };
+                let to = origin + parser.point()?;
+                self.start_drawing(path);
+                // Per spec, arcs to the current point are omitted, and arcs with a zero radius
+                // are straight lines.
+                if to == self.current_point {
+                    return Some(());
+                }
+                if radius.x() == 0.0 || radius.y() == 0.0 {
+                    path.line_to(to);
+                } else {
+                    path.current_contour.push_svg_arc(
+                        radius,
+                        x_axis_rotation,
+                        large_arc,
+                        direction,
+                        to,
+                    );
+                }
+                to
+            }
+            _ => return None,
+        };
+
+        if command == b'L' || command == b'H' || command == b'V' {
+            self.start_drawing(path);
+            path.line_to(to);
+        }
+        self.current_point = to;
+        Some(())
+    }
+
+    // After a closepath, the next segment starts a new subpath at the start of the closed one.
+    fn start_drawing(&mut self, path: &mut Path2D) {
+        if self.closed {
+            path.move_to(self.subpath_start);
+            self.closed = false;
+        }
+    }
+}
+
+// Reflects the previous control point about the current point, or uses the current point if the
+// previous command wasn't a curve of the same kind.
+fn reflect(last_ctrl: Option<Vector2F>, current_point: Vector2F) -> Vector2F {
+    match last_ctrl {
+        None => current_point,
+        Some(last_ctrl) => current_point * 2.0 - last_ctrl,
+    }
+}
+
+struct PathDataParser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b'\x0c') = self.peek()
+        {
+            self.position += 1;
+        }
+    }
+
+    // Skips whitespace, then an optional comma and more whitespace.
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn point(&mut self) -> Option<Vector2F> {
+        Some(vec2f(self.number()?, self.number()?))
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separator();
+        let start = self.position;
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.position += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.position = start;
+            return None;
+        }
+
+        // Only consume an exponent if it's complete, so that the "e" can't be misread.
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.position += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.position = mantissa_end;
+            }
+        }
+
+        let number = std::str::from_utf8(&self.data[start..self.position]).ok()?;
+        number.parse().ok()
+    }
+
+    // Arc flags are single digits that needn't be separated from what follows.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separator();
+        let flag = match self.peek()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+}
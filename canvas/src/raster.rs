@@ -0,0 +1,77 @@
+// pathfinder/canvas/src/raster.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders a canvas to an image in one call, on either the CPU or the GPU.
+
+use crate::CanvasRenderingContext2D;
+use image::RgbaImage;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::Device;
+use pathfinder_rasterize::RasterizeOptions;
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::{RenderError, Renderer};
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_resources::ResourceLoader;
+
+impl CanvasRenderingContext2D {
+    /// Renders the canvas on the CPU and returns the pixels, stretching the canvas to fill an
+    /// image of the given size.
+    ///
+    /// This needs no GPU, so it works anywhere, but it's much slower than
+    /// `into_image_with_device()`. Pixels that nothing was drawn to are transparent.
+    pub fn into_image(self, size: Vector2I) -> RgbaImage {
+        let transform = self.image_transform(size);
+        let scene = self.into_canvas().into_scene();
+
+        let mut options = RasterizeOptions::new(size);
+        options.transform = transform;
+        pathfinder_rasterize::rasterize(&scene, &options).to_image_buffer()
+    }
+
+    /// Renders the canvas with a new GPU renderer on the given device and returns the pixels,
+    /// stretching the canvas to fill an image of the given size.
+    ///
+    /// The canvas is rendered off-screen, so the device needs no window; a headless context
+    /// works. Pixels that nothing was drawn to are transparent. To render many canvases, create
+    /// a `Renderer` once and call its `render_to_image()` method instead, since creating a
+    /// renderer compiles its shaders.
+    pub fn into_image_with_device<D>(
+        self,
+        size: Vector2I,
+        device: D,
+        resource_loader: &dyn ResourceLoader,
+    ) -> Result<RgbaImage, RenderError>
+    where
+        D: Device,
+    {
+        let transform = self.image_transform(size);
+        let mut scene = self.into_canvas().into_scene();
+
+        let mode = RendererMode::default_for_device(&device);
+        let options = RendererOptions {
+            dest: DestFramebuffer::full_window(size),
+            ..RendererOptions::default()
+        };
+        let mut renderer = Renderer::try_new(device, resource_loader, mode, options)?;
+        let build_options = BuildOptions {
+            transform: RenderTransform::Transform2D(transform),
+            ..BuildOptions::default()
+        };
+        let image =
+            renderer.render_to_image(&mut scene, size, build_options, SequentialExecutor)?;
+        Ok(image.to_image_buffer())
+    }
+
+    fn image_transform(&self, size: Vector2I) -> Transform2F {
+        Transform2F::from_scale(size.to_f32() / self.canvas.size().to_f32())
+    }
+}
@@ -3,8 +3,18 @@
 // For this file only, any copyright is dedicated to the Public Domain.
 // https://creativecommons.org/publicdomain/zero/1.0/
 
-use super::Path2D;
-use pathfinder_geometry::vector::{vec2f, Vector2F};
+use super::{ArcDirection, Canvas, CanvasFontContext, CompositeOperation, CornerRadii, ImageData};
+use super::{ImageDataError, Path2D, PatternRepetition};
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::PatternFilter;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::thread;
 
 #[test]
 pub fn test_path2d_formatting() {
@@ -17,3 +27,541 @@ pub fn test_path2d_formatting() {
     path.close_path();
     assert_eq!(format!("{:?}", path), "M 0 1 L 2 3 L 4 5 z");
 }
+
+#[test]
+pub fn test_is_point_in_path_and_stroke() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(100.0, 100.0)).get_context_2d(font_context);
+    context.set_transform(&Transform2F::from_scale(2.0));
+    context.set_line_width(2.0);
+
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(10.0, 10.0), vec2f(10.0, 10.0)));
+
+    // The path covers 20..40 in canvas coordinates, and its stroke is 4 wide there.
+    assert!(context.is_point_in_path(&path, vec2f(30.0, 30.0), FillRule::Winding));
+    assert!(!context.is_point_in_path(&path, vec2f(15.0, 30.0), FillRule::Winding));
+    assert!(context.is_point_in_stroke(&path, vec2f(21.0, 30.0)));
+    assert!(!context.is_point_in_stroke(&path, vec2f(30.0, 30.0)));
+    assert!(!context.is_point_in_stroke(&path, vec2f(17.0, 30.0)));
+}
+
+#[test]
+pub fn test_get_and_put_image_data() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    context.set_fill_style(ColorU::new(255, 0, 0, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 8.0)));
+
+    // The last column is outside the canvas.
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(2, 0), vec2i(7, 1)))
+        .unwrap();
+    let red = ColorU::new(255, 0, 0, 255);
+    let transparent = ColorU::transparent_black();
+    assert_eq!(
+        image_data.data,
+        [
+            red,
+            red,
+            transparent,
+            transparent,
+            transparent,
+            transparent,
+            transparent
+        ]
+    );
+
+    // Putting transparent pixels replaces what's underneath.
+    context.put_image_data(ImageData::new(vec2i(2, 2)), vec2f(0.0, 0.0));
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 0), vec2i(3, 1)))
+        .unwrap();
+    assert_eq!(image_data.data, [transparent, transparent, red]);
+}
+
+#[test]
+pub fn test_conic_gradient() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    let mut gradient = context.create_conic_gradient(0.0, vec2f(4.0, 4.0));
+    gradient.add_color_stop(ColorU::new(255, 0, 0, 255), 0.0);
+    gradient.add_color_stop(ColorU::new(0, 0, 255, 255), 1.0);
+    context.set_fill_style(gradient);
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+
+    // Just below the start angle, a quarter turn around, and just above the start angle.
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(7, 4), vec2i(1, 1)))
+        .unwrap();
+    assert!(image_data.data[0].r > 200 && image_data.data[0].b < 80);
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(4, 7), vec2i(1, 1)))
+        .unwrap();
+    assert!(image_data.data[0].r > 150 && image_data.data[0].b > 40);
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(7, 3), vec2i(1, 1)))
+        .unwrap();
+    assert!(image_data.data[0].b > 200 && image_data.data[0].r < 80);
+}
+
+#[test]
+pub fn test_filter() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    assert_eq!(context.filter(), "none");
+
+    context.set_filter("blur(2px) drop-shadow(rgba(0, 0, 0, 0.5) 1px 1px 3px) hue-rotate(90deg)");
+    assert_eq!(
+        context.filter(),
+        "blur(2px) drop-shadow(rgba(0, 0, 0, 0.5) 1px 1px 3px) hue-rotate(90deg)"
+    );
+
+    // Invalid values are ignored.
+    context.set_filter("blur(-1px)");
+    context.set_filter("brightness(50%) sepia(1)");
+    assert_eq!(
+        context.filter(),
+        "blur(2px) drop-shadow(rgba(0, 0, 0, 0.5) 1px 1px 3px) hue-rotate(90deg)"
+    );
+
+    context.set_filter("brightness(50%)");
+    context.set_fill_style(ColorU::new(200, 100, 0, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(4, 4), vec2i(1, 1)))
+        .unwrap();
+    assert_eq!(image_data.data, [ColorU::new(100, 50, 0, 255)]);
+}
+
+#[test]
+pub fn test_shadow() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    let red = ColorU::new(255, 0, 0, 255);
+    let blue = ColorU::new(0, 0, 255, 255);
+    let transparent = ColorU::transparent_black();
+
+    // A shadow that's neither offset nor blurred isn't drawn.
+    context.set_shadow_color(blue);
+    context.set_fill_style(ColorU::new(255, 0, 0, 128));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 0), vec2i(1, 1)))
+        .unwrap();
+    assert_eq!(image_data.data[0].b, 0);
+
+    // The shadow of an image is masked by the image where the shadow lands.
+    let pixels = vec![red, transparent, red, transparent];
+    let image = Image::new(vec2i(2, 2), Arc::new(pixels));
+    context.set_image_smoothing_enabled(false);
+    context.set_shadow_offset(vec2f(4.0, 4.0));
+    context.draw_subimage(
+        Pattern::from_image(image),
+        RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)),
+        RectF::new(vec2f(0.0, 2.0), vec2f(4.0, 4.0)),
+    );
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 7), vec2i(8, 1)))
+        .unwrap();
+    assert_eq!(
+        image_data.data,
+        [
+            transparent,
+            transparent,
+            transparent,
+            transparent,
+            blue,
+            blue,
+            transparent,
+            transparent
+        ]
+    );
+}
+
+#[test]
+pub fn test_get_image_data_rejects_blurs() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    context.set_shadow_color(ColorU::new(0, 0, 255, 255));
+    context.set_shadow_blur(2.0);
+    context.fill_rect(RectF::new(vec2f(2.0, 2.0), vec2f(4.0, 4.0)));
+    match context.get_image_data(RectI::new(vec2i(0, 0), vec2i(8, 8))) {
+        Err(ImageDataError::UnsupportedFilter(PatternFilter::Blur { .. })) => {}
+        other => panic!(
+            "expected a blur to be rejected, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+pub fn test_path2d_from_svg_path_data() {
+    let path = Path2D::from_svg_path_data("M10-5 20,0h5v5H10zl1 1");
+    assert_eq!(
+        format!("{:?}", path),
+        "M 10 -5 L 20 0 L 25 0 L 25 5 L 10 5 z M 10 -5 L 11 -4"
+    );
+
+    // Smooth curves reflect the previous control point.
+    let path = Path2D::from_svg_path_data("M0 0C0 1 2 1 2 0s2-1 2 0Q5 1 6 0t2 0");
+    assert_eq!(
+        format!("{:?}", path),
+        "M 0 0 C 0 1 2 1 2 0 C 2 -1 4 -1 4 0 Q 5 1 6 0 Q 7 -1 8 0"
+    );
+
+    // Everything before an error is kept.
+    let path = Path2D::from_svg_path_data("M 1 2 L 3 4 L 5 L 7 8");
+    assert_eq!(format!("{:?}", path), "M 1 2 L 3 4");
+    let path = Path2D::from_svg_path_data("L 1 2");
+    assert_eq!(format!("{:?}", path), "");
+
+    let mut composite = Path2D::from_svg_path_data("M 0 0 L 1 0");
+    composite.add_path(&path, Transform2F::default());
+    composite.add_path(
+        &Path2D::from_svg_path_data("M 0 0 L 1 1"),
+        Transform2F::from_translation(vec2f(2.0, 0.0)),
+    );
+    assert_eq!(format!("{:?}", composite), "M 0 0 L 1 0 M 2 0 L 3 1");
+}
+
+#[test]
+pub fn test_composite_operation() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    context.set_fill_style(ColorU::new(255, 0, 0, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+
+    // `source-in` clears the canvas outside the shape, but only within the clip.
+    let mut clip = Path2D::new();
+    clip.rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 4.0)));
+    context.clip_path(clip, FillRule::Winding);
+    context.set_global_composite_operation(CompositeOperation::SourceIn);
+    context.set_fill_style(ColorU::new(0, 0, 255, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 8.0)));
+
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 0), vec2i(8, 1)))
+        .unwrap();
+    let (blue, clear) = (ColorU::new(0, 0, 255, 255), ColorU::transparent_black());
+    assert_eq!(
+        image_data.data,
+        [blue, blue, blue, blue, clear, clear, clear, clear]
+    );
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 7), vec2i(8, 1)))
+        .unwrap();
+    assert_eq!(image_data.data, [ColorU::new(255, 0, 0, 255); 8]);
+}
+
+#[cfg(feature = "pf-text")]
+#[test]
+pub fn test_composite_operation_with_text() {
+    use font_kit::loaders::default::Font;
+
+    let font_data = include_bytes!("../../resources/fonts/Roboto-Regular.ttf").to_vec();
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(32.0, 16.0)).get_context_2d(font_context);
+    context.set_fill_style(ColorU::new(255, 0, 0, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(32.0, 16.0)));
+
+    // Text clears the canvas outside the glyphs, like shapes do.
+    context.set_font(font);
+    context.set_font_size(16.0);
+    context.set_global_composite_operation(CompositeOperation::SourceIn);
+    context.set_fill_style(ColorU::new(0, 0, 255, 255));
+    context.fill_text("I", vec2f(4.0, 14.0));
+
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 8), vec2i(32, 1)))
+        .unwrap();
+    assert!(image_data
+        .data
+        .iter()
+        .any(|pixel| pixel.r == 0 && pixel.b > 0));
+    assert_eq!(image_data.data[24..], [ColorU::transparent_black(); 8]);
+}
+
+#[test]
+pub fn test_pattern_repetition() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    context.set_image_smoothing_enabled(false);
+    let red = ColorU::new(255, 0, 0, 255);
+    let image = Image::new(vec2i(2, 2), Arc::new(vec![red; 4]));
+    let (row, column) = (
+        RectI::new(vec2i(0, 5), vec2i(8, 1)),
+        RectI::new(vec2i(5, 0), vec2i(1, 8)),
+    );
+
+    let mut pattern =
+        context.create_pattern(Pattern::from_image(image), PatternRepetition::RepeatX);
+    pattern.set_transform(Transform2F::from_translation(vec2f(4.0, 4.0)));
+    context.set_fill_style(pattern.clone());
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+    assert_eq!(context.get_image_data(row).unwrap().data, [red; 8]);
+    let image_data = context.get_image_data(column).unwrap();
+    assert!(image_data.data[..4]
+        .iter()
+        .all(|color| color.is_fully_transparent()));
+    assert!(image_data.data[6..]
+        .iter()
+        .all(|color| color.is_fully_transparent()));
+
+    // Without repetition, only the image itself is painted.
+    context.clear_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+    pattern.set_repeat_x(false);
+    context.set_fill_style(pattern);
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+    let image_data = context.get_image_data(row).unwrap();
+    assert_eq!(image_data.data[4..6], [red; 2]);
+    assert!(image_data.data[..4]
+        .iter()
+        .all(|color| color.is_fully_transparent()));
+    assert!(image_data.data[6..]
+        .iter()
+        .all(|color| color.is_fully_transparent()));
+}
+
+#[test]
+pub fn test_draw_subimage() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    let (red, blue) = (ColorU::new(255, 0, 0, 255), ColorU::new(0, 0, 255, 255));
+    let sprite_sheet = Image::new(
+        vec2i(4, 2),
+        Arc::new(vec![red, red, blue, blue, red, red, blue, blue]),
+    );
+
+    // Smoothing doesn't blend in the neighboring sprite, even when scaling up.
+    context.draw_subimage(
+        sprite_sheet.clone(),
+        RectF::new(vec2f(2.0, 0.0), vec2f(2.0, 2.0)),
+        RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 4.0)),
+    );
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 0), vec2i(8, 1)))
+        .unwrap();
+    assert_eq!(image_data.data, [blue; 8]);
+
+    // The source rectangle is clipped to the image, shrinking the destination to match.
+    context.set_image_smoothing_enabled(false);
+    context.draw_subimage(
+        sprite_sheet,
+        RectF::new(vec2f(-2.0, 0.0), vec2f(4.0, 2.0)),
+        RectF::new(vec2f(0.0, 4.0), vec2f(8.0, 4.0)),
+    );
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 6), vec2i(8, 1)))
+        .unwrap();
+    let clear = ColorU::transparent_black();
+    assert_eq!(
+        image_data.data,
+        [clear, clear, clear, clear, red, red, red, red]
+    );
+}
+
+#[test]
+pub fn test_path2d_arcs() {
+    let font_context = CanvasFontContext::from_system_source();
+    let context = Canvas::new(vec2f(100.0, 100.0)).get_context_2d(font_context);
+
+    // Collinear points and a zero radius make `arc_to` draw a line to the corner.
+    let mut path = Path2D::new();
+    path.move_to(vec2f(0.0, 0.0));
+    path.arc_to(vec2f(10.0, 0.0), vec2f(20.0, 0.0), 5.0);
+    path.arc_to(vec2f(10.0, 10.0), vec2f(0.0, 10.0), 0.0);
+    assert_eq!(format!("{:?}", path), "M 0 0 L 10 0 L 10 10");
+
+    // The arc is tangent to both lines, turning whichever way they do.
+    let mut path = Path2D::new();
+    path.move_to(vec2f(0.0, 0.0));
+    path.arc_to(vec2f(20.0, 0.0), vec2f(20.0, 20.0), 10.0);
+    path.line_to(vec2f(0.0, 20.0));
+    assert!(context.is_point_in_path(&path, vec2f(10.0, 1.0), FillRule::Winding));
+    assert!(context.is_point_in_path(&path, vec2f(16.0, 4.0), FillRule::Winding));
+    assert!(!context.is_point_in_path(&path, vec2f(19.0, 1.0), FillRule::Winding));
+
+    // A counterclockwise sweep covers the rest of the circle.
+    let mut path = Path2D::new();
+    path.move_to(vec2f(50.0, 50.0));
+    path.ellipse(
+        vec2f(50.0, 50.0),
+        10.0,
+        0.0,
+        0.0,
+        0.5 * PI,
+        ArcDirection::CCW,
+    );
+    assert!(context.is_point_in_path(&path, vec2f(45.0, 45.0), FillRule::Winding));
+    assert!(!context.is_point_in_path(&path, vec2f(55.0, 55.0), FillRule::Winding));
+
+    // Radii that don't fit are scaled down, so this rounded rectangle is a circle.
+    let mut path = Path2D::new();
+    path.round_rect(RectF::new(vec2f(20.0, 20.0), vec2f(-20.0, -20.0)), 100.0);
+    assert!(context.is_point_in_path(&path, vec2f(10.0, 1.0), FillRule::Winding));
+    assert!(!context.is_point_in_path(&path, vec2f(2.0, 2.0), FillRule::Winding));
+
+    let radii = CornerRadii::from_list(&[vec2f(10.0, 10.0), Vector2F::zero()]).unwrap();
+    let mut path = Path2D::new();
+    path.round_rect(RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 20.0)), radii);
+    assert!(!context.is_point_in_path(&path, vec2f(1.0, 1.0), FillRule::Winding));
+    assert!(context.is_point_in_path(&path, vec2f(19.0, 1.0), FillRule::Winding));
+    assert!(!context.is_point_in_path(&path, vec2f(19.0, 19.0), FillRule::Winding));
+    assert!(context.is_point_in_path(&path, vec2f(1.0, 19.0), FillRule::Winding));
+}
+
+#[cfg(feature = "pf-text")]
+#[test]
+pub fn test_font_shorthand() {
+    use super::font::FontShorthand;
+    use font_kit::family_name::FamilyName;
+    use font_kit::properties::{Style, Weight};
+
+    let font = FontShorthand::parse("italic bold 14px/1.2 'Fira Sans', sans-serif").unwrap();
+    assert_eq!(font.properties.style, Style::Italic);
+    assert_eq!(font.properties.weight, Weight::BOLD);
+    assert_eq!(font.size, 14.0);
+    assert_eq!(
+        font.families,
+        [
+            FamilyName::Title("Fira Sans".to_owned()),
+            FamilyName::SansSerif
+        ]
+    );
+
+    let font = FontShorthand::parse("300 2em / 1 Open Sans").unwrap();
+    assert_eq!(font.properties.weight, Weight(300.0));
+    assert_eq!(font.size, 20.0);
+    assert_eq!(font.families, [FamilyName::Title("Open Sans".to_owned())]);
+
+    // PostScript names aren't shorthands.
+    assert!(FontShorthand::parse("Roboto-Regular").is_none());
+    assert!(FontShorthand::parse("12px Arial,").is_none());
+}
+
+#[test]
+pub fn test_clip_intersection() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+
+    // The second clip, which has a hole in it, narrows the first.
+    context.save();
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(2.0, 0.0), vec2f(4.0, 8.0)));
+    context.clip_path(path, FillRule::Winding);
+    let mut path = Path2D::new();
+    path.rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+    path.rect(RectF::new(vec2f(3.0, 0.0), vec2f(2.0, 8.0)));
+    context.clip_path(path, FillRule::EvenOdd);
+    context.set_fill_style(ColorU::new(255, 0, 0, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 8.0)));
+
+    // Restoring removes both clips.
+    context.restore();
+    context.set_fill_style(ColorU::new(0, 0, 255, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 6.0), vec2f(8.0, 2.0)));
+
+    let red = ColorU::new(255, 0, 0, 255);
+    let clear = ColorU::transparent_black();
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 2), vec2i(8, 1)))
+        .unwrap();
+    assert_eq!(
+        image_data.data,
+        vec![clear, clear, red, clear, clear, red, clear, clear]
+    );
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(0, 7), vec2i(8, 1)))
+        .unwrap();
+    assert!(image_data
+        .data
+        .iter()
+        .all(|&color| color == ColorU::new(0, 0, 255, 255)));
+}
+
+#[test]
+pub fn test_transfer_to_scene() {
+    // Draw two frames on another thread, clipped to the left half of the canvas.
+    let canvas = Canvas::new(vec2f(4.0, 4.0));
+    let (first_scene, second_scene, blank_pixel) = thread::spawn(move || {
+        let font_context = CanvasFontContext::from_system_source();
+        let mut context = canvas.get_context_2d(font_context);
+        let mut path = Path2D::new();
+        path.rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 4.0)));
+        context.clip_path(path, FillRule::Winding);
+        context.set_fill_style(ColorU::new(255, 0, 0, 255));
+        context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let first_scene = context.transfer_to_scene();
+
+        let blank_pixel = context
+            .get_image_data(RectI::new(vec2i(0, 0), vec2i(1, 1)))
+            .unwrap()
+            .data[0];
+        context.set_fill_style(ColorU::new(0, 0, 255, 255));
+        context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        (first_scene, context.transfer_to_scene(), blank_pixel)
+    })
+    .join()
+    .unwrap();
+    assert_eq!(blank_pixel, ColorU::transparent_black());
+
+    // The clip carries over to the second frame.
+    let rect = RectI::new(vec2i(0, 1), vec2i(4, 1));
+    let font_context = CanvasFontContext::from_system_source();
+    let context = Canvas::from_scene(first_scene).get_context_2d(font_context.clone());
+    let red = ColorU::new(255, 0, 0, 255);
+    let clear = ColorU::transparent_black();
+    assert_eq!(
+        context.get_image_data(rect).unwrap().data,
+        vec![red, red, clear, clear]
+    );
+    let context = Canvas::from_scene(second_scene).get_context_2d(font_context);
+    let blue = ColorU::new(0, 0, 255, 255);
+    assert_eq!(
+        context.get_image_data(rect).unwrap().data,
+        vec![blue, blue, clear, clear]
+    );
+}
+
+#[cfg(feature = "pf-image")]
+#[test]
+pub fn test_into_image() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(4.0, 4.0)).get_context_2d(font_context);
+    context.set_fill_style(ColorU::new(0, 0, 255, 255));
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 4.0)));
+
+    // The canvas is stretched to fill the image.
+    let image = context.into_image(vec2i(8, 2));
+    assert_eq!(image.dimensions(), (8, 2));
+    assert_eq!(image.get_pixel(1, 1).0, [0, 0, 255, 255]);
+    assert_eq!(image.get_pixel(6, 0).0, [0, 0, 0, 0]);
+}
+
+#[test]
+pub fn test_save_layer() {
+    let font_context = CanvasFontContext::from_system_source();
+    let mut context = Canvas::new(vec2f(8.0, 8.0)).get_context_2d(font_context);
+    context.set_fill_style(ColorU::new(255, 0, 0, 255));
+
+    // Overlapping shapes in a layer share one opacity, and nothing outside the bounds is drawn.
+    context.save_layer(
+        Some(RectF::new(vec2f(0.0, 0.0), vec2f(6.0, 8.0))),
+        0.5,
+        CompositeOperation::SourceOver,
+    );
+    context.fill_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 8.0)));
+    context.fill_rect(RectF::new(vec2f(2.0, 0.0), vec2f(6.0, 8.0)));
+    context.restore();
+
+    let image_data = context
+        .get_image_data(RectI::new(vec2i(1, 4), vec2i(7, 1)))
+        .unwrap();
+    let half_red = image_data.data[0];
+    assert!((half_red.a as i32 - 128).abs() <= 1);
+    assert_eq!(image_data.data[2], half_red);
+    assert_eq!(image_data.data[4], half_red);
+    assert_eq!(image_data.data[6], ColorU::transparent_black());
+}
@@ -8,8 +8,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{CanvasRenderingContext2D, State, TextAlign, TextBaseline};
-use font_kit::canvas::RasterizationOptions;
+use crate::font::FontShorthand;
+use crate::{push_shadow, CanvasRenderingContext2D, State, TextAlign, TextBaseline};
+use crate::{TextDirection, TextRendering};
 use font_kit::family_name::FamilyName;
 use font_kit::handle::Handle;
 use font_kit::hinting::HintingOptions;
@@ -17,16 +18,22 @@ use font_kit::loaders::default::Font;
 use font_kit::properties::Properties;
 use font_kit::source::{Source, SystemSource};
 use font_kit::sources::mem::MemSource;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::stroke::LineJoin;
+use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util;
 use pathfinder_geometry::vector::{vec2f, Vector2F};
-use pathfinder_renderer::paint::PaintId;
-use pathfinder_text::{FontContext, FontRenderOptions, TextRenderMode};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_text::{Baselines, FontContext, FontRenderOptions, TextRenderMode};
 use skribo::{FontCollection, FontFamily, FontRef, Layout as SkriboLayout, TextStyle};
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::iter;
 use std::rc::Rc;
 use std::sync::Arc;
+use unicode_bidi::{BidiInfo, Level};
 
 impl CanvasRenderingContext2D {
     /// Fills the given text using the current style.
@@ -39,11 +46,7 @@ impl CanvasRenderingContext2D {
     where
         T: ToTextLayout + ?Sized,
     {
-        let paint = self
-            .current_state
-            .resolve_paint(&self.current_state.fill_paint);
-        let paint_id = self.canvas.scene.push_paint(&paint);
-        self.fill_or_stroke_text(text, position, paint_id, TextRenderMode::Fill);
+        self.fill_or_stroke_text(text, position, TextRenderMode::Fill);
     }
 
     /// Strokes the given text using the current style.
@@ -56,12 +59,8 @@ impl CanvasRenderingContext2D {
     where
         T: ToTextLayout + ?Sized,
     {
-        let paint = self
-            .current_state
-            .resolve_paint(&self.current_state.stroke_paint);
-        let paint_id = self.canvas.scene.push_paint(&paint);
         let render_mode = TextRenderMode::Stroke(self.current_state.resolve_stroke_style());
-        self.fill_or_stroke_text(text, position, paint_id, render_mode);
+        self.fill_or_stroke_text(text, position, render_mode);
     }
 
     /// Returns metrics of the given text using the current style.
@@ -80,44 +79,102 @@ impl CanvasRenderingContext2D {
         &mut self,
         text: &T,
         mut position: Vector2F,
-        paint_id: PaintId,
         render_mode: TextRenderMode,
     ) where
         T: ToTextLayout + ?Sized,
     {
-        let layout = text.layout(CanvasState(&self.current_state));
-
         let clip_path = self.current_state.clip_path;
         let blend_mode = self
             .current_state
             .global_composite_operation
             .to_blend_mode();
 
+        // Composite operations like `source-in` affect the whole canvas, so the text and its
+        // shadow go to a layer that's composited like a filtered path.
+        let layer = if blend_mode.is_destructive() {
+            Some(self.push_filter_render_target())
+        } else {
+            None
+        };
+        let glyph_blend_mode = match layer {
+            Some(_) => BlendMode::SrcOver,
+            None => blend_mode,
+        };
+
+        let layout = text.layout(CanvasState(&self.current_state));
+        let paint = self.current_state.resolve_paint(match render_mode {
+            TextRenderMode::Fill => &self.current_state.fill_paint,
+            TextRenderMode::Stroke(_) => &self.current_state.stroke_paint,
+        });
+
         position += layout.text_origin();
         let transform = self.current_state.transform * Transform2F::from_translation(position);
+        let hinting_options = match self.current_state.text_rendering {
+            TextRendering::OptimizeLegibility => HintingOptions::Vertical(layout.font_size),
+            TextRendering::Auto
+            | TextRendering::OptimizeSpeed
+            | TextRendering::GeometricPrecision => HintingOptions::None,
+        };
 
-        // TODO(pcwalton): Report errors.
-        drop(
-            self.canvas_font_context
-                .0
-                .borrow_mut()
-                .font_context
-                .push_layout(
-                    &mut self.canvas.scene,
-                    &layout.skribo_layout,
-                    &TextStyle {
-                        size: layout.font_size,
-                    },
+        // The glyph bounds point up, so flip them into canvas space for the shadow.
+        let glyph_bounds = layout.glyph_bounds();
+        let mut bounds = RectF::from_points(
+            vec2f(glyph_bounds.min_x(), -glyph_bounds.max_y()),
+            vec2f(glyph_bounds.max_x(), -glyph_bounds.min_y()),
+        );
+        if let TextRenderMode::Stroke(ref stroke_style) = render_mode {
+            let miter_limit = match stroke_style.line_join {
+                LineJoin::Miter(miter_limit) => miter_limit.max(1.0),
+                LineJoin::Bevel | LineJoin::Round => 1.0,
+            };
+            bounds = bounds.dilate(stroke_style.line_width * 0.5 * miter_limit);
+        }
+
+        let canvas_font_context = &self.canvas_font_context;
+        push_shadow(
+            &mut self.canvas.scene,
+            &self.current_state,
+            &paint,
+            transform * bounds,
+            |scene, shadow_transform, shadow_paint_id, shadow_clip_path| {
+                push_text_layout(
+                    canvas_font_context,
+                    scene,
+                    &layout,
                     &FontRenderOptions {
-                        transform,
+                        transform: shadow_transform * transform,
                         render_mode,
-                        hinting_options: HintingOptions::None,
-                        clip_path,
-                        blend_mode,
-                        paint_id,
+                        hinting_options,
+                        clip_path: shadow_clip_path,
+                        blend_mode: glyph_blend_mode,
+                        paint_id: shadow_paint_id,
+                        subpixel_coverage: false,
                     },
-                ),
+                );
+            },
+        );
+
+        let paint_id = self.canvas.scene.push_paint(&paint);
+        push_text_layout(
+            &self.canvas_font_context,
+            &mut self.canvas.scene,
+            &layout,
+            &FontRenderOptions {
+                transform,
+                render_mode,
+                hinting_options,
+                clip_path,
+                blend_mode: glyph_blend_mode,
+                paint_id,
+                subpixel_coverage: false,
+            },
         );
+
+        if let Some(layer) = layer {
+            self.canvas.scene.pop_render_target();
+            let region = transform * bounds;
+            self.composite_filter_render_target(layer, region, clip_path, blend_mode);
+        }
     }
 
     // Text styles
@@ -127,11 +184,20 @@ impl CanvasRenderingContext2D {
         self.current_state.font_collection.clone()
     }
 
+    /// Sets the font used to draw text.
+    ///
+    /// As in HTML canvas, this accepts a CSS `font` shorthand like `bold 14px 'Fira Sans', serif`,
+    /// which also sets the font size. The first installed family is used, then each later one for
+    /// any glyphs that it lacks, and finally the default sans-serif font. Strings that aren't valid
+    /// shorthands are taken to be PostScript names.
     #[inline]
     pub fn set_font<FC>(&mut self, font_collection: FC)
     where
         FC: IntoFontCollection,
     {
+        if let Some(font_size) = font_collection.font_size() {
+            self.current_state.font_size = font_size;
+        }
         let font_collection = font_collection.into_font_collection(&self.canvas_font_context);
         self.current_state.font_collection = font_collection;
     }
@@ -165,6 +231,56 @@ impl CanvasRenderingContext2D {
     pub fn set_text_baseline(&mut self, new_text_baseline: TextBaseline) {
         self.current_state.text_baseline = new_text_baseline;
     }
+
+    #[inline]
+    pub fn direction(&self) -> TextDirection {
+        self.current_state.direction
+    }
+
+    /// Sets the base direction of text, which decides which side `TextAlign::Start` and
+    /// `TextAlign::End` refer to and how runs of mixed-direction text are ordered.
+    #[inline]
+    pub fn set_direction(&mut self, new_direction: TextDirection) {
+        self.current_state.direction = new_direction;
+    }
+
+    #[inline]
+    pub fn letter_spacing(&self) -> f32 {
+        self.current_state.letter_spacing
+    }
+
+    /// Sets the space in pixels added after each character, which may be negative to tighten the
+    /// text.
+    #[inline]
+    pub fn set_letter_spacing(&mut self, new_letter_spacing: f32) {
+        self.current_state.letter_spacing = new_letter_spacing;
+    }
+
+    #[inline]
+    pub fn word_spacing(&self) -> f32 {
+        self.current_state.word_spacing
+    }
+
+    /// Sets the space in pixels added after each space character, on top of the letter spacing.
+    #[inline]
+    pub fn set_word_spacing(&mut self, new_word_spacing: f32) {
+        self.current_state.word_spacing = new_word_spacing;
+    }
+
+    #[inline]
+    pub fn text_rendering(&self) -> TextRendering {
+        self.current_state.text_rendering
+    }
+
+    /// Sets how text is rendered.
+    ///
+    /// `OptimizeLegibility` hints glyph outlines so that their horizontal stems and edges are
+    /// aligned with pixels at the font size. Every other value draws the outlines unhinted, as
+    /// before.
+    #[inline]
+    pub fn set_text_rendering(&mut self, new_text_rendering: TextRendering) {
+        self.current_state.text_rendering = new_text_rendering;
+    }
 }
 
 // Avoids leaking `State` to the outside.
@@ -180,17 +296,16 @@ pub trait ToTextLayout {
 
 impl ToTextLayout for str {
     fn layout(&self, state: CanvasState) -> Cow<TextMetrics> {
-        let skribo_layout = Rc::new(skribo::layout(
-            &TextStyle {
-                size: state.0.font_size,
-            },
-            &state.0.font_collection,
-            self,
-        ));
+        let mut skribo_layout = layout_bidi_text(self, state.0);
+        pathfinder_text::apply_spacing(
+            &mut skribo_layout,
+            state.0.letter_spacing,
+            state.0.word_spacing,
+        );
         Cow::Owned(TextMetrics::new(
-            skribo_layout,
+            Rc::new(skribo_layout),
             state.0.font_size,
-            state.0.text_align,
+            state.0.resolved_text_align(),
             state.0.text_baseline,
         ))
     }
@@ -208,7 +323,7 @@ impl ToTextLayout for Rc<SkriboLayout> {
         Cow::Owned(TextMetrics::new(
             (*self).clone(),
             state.0.font_size,
-            state.0.text_align,
+            state.0.resolved_text_align(),
             state.0.text_baseline,
         ))
     }
@@ -220,6 +335,81 @@ impl ToTextLayout for TextMetrics {
     }
 }
 
+impl State {
+    // Resolves `Start` and `End` to a side, according to the direction.
+    fn resolved_text_align(&self) -> TextAlign {
+        match (self.text_align, self.direction) {
+            (TextAlign::Start, TextDirection::Ltr) | (TextAlign::End, TextDirection::Rtl) => {
+                TextAlign::Left
+            }
+            (TextAlign::Start, TextDirection::Rtl) | (TextAlign::End, TextDirection::Ltr) => {
+                TextAlign::Right
+            }
+            (text_align, _) => text_align,
+        }
+    }
+}
+
+// Lays out the text in visual order, as the Unicode bidirectional algorithm arranges it for the
+// direction.
+//
+// skribo only lays out text left to right, so each run is laid out on its own and the glyphs of
+// right-to-left runs are mirrored within the run.
+fn layout_bidi_text(text: &str, state: &State) -> SkriboLayout {
+    let style = TextStyle {
+        size: state.font_size,
+    };
+    let base_level = match state.direction {
+        TextDirection::Ltr => Level::ltr(),
+        TextDirection::Rtl => Level::rtl(),
+    };
+    let bidi_info = BidiInfo::new(text, Some(base_level));
+    if bidi_info.levels.iter().all(|level| level.is_ltr()) {
+        return skribo::layout(&style, &state.font_collection, text);
+    }
+
+    let mut layout = skribo::layout(&style, &state.font_collection, "");
+    let mut run_origin = 0.0;
+    for paragraph in &bidi_info.paragraphs {
+        let (levels, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let is_rtl = levels[run.start].is_rtl();
+            let run_layout = skribo::layout(&style, &state.font_collection, &text[run]);
+            let run_width = layout_width(&run_layout);
+            let mut glyphs = run_layout.glyphs;
+            if is_rtl {
+                glyphs.reverse();
+            }
+            for mut glyph in glyphs {
+                if is_rtl {
+                    let advance = glyph_advance(&glyph.font, glyph.glyph_id, style.size);
+                    glyph.offset.set_x(run_width - glyph.offset.x() - advance);
+                }
+                glyph.offset += vec2f(run_origin, 0.0);
+                layout.glyphs.push(glyph);
+            }
+            run_origin += run_width;
+        }
+    }
+    layout
+}
+
+// Returns the advance width of the laid-out text, in pixels.
+fn layout_width(skribo_layout: &SkriboLayout) -> f32 {
+    match skribo_layout.glyphs.last() {
+        None => 0.0,
+        Some(last_glyph) => {
+            let advance = glyph_advance(&last_glyph.font, last_glyph.glyph_id, skribo_layout.size);
+            last_glyph.offset.x() + advance
+        }
+    }
+}
+
+fn glyph_advance(font: &FontRef, glyph_id: u32, font_size: f32) -> f32 {
+    let scale_factor = font_size / font.font.metrics().units_per_em as f32;
+    font.font.advance(glyph_id).unwrap_or_default().x() * scale_factor
+}
+
 #[cfg(feature = "pf-text")]
 #[derive(Clone)]
 pub struct CanvasFontContext(pub(crate) Rc<RefCell<CanvasFontContextData>>);
@@ -230,6 +420,8 @@ pub(super) struct CanvasFontContextData {
     pub(super) font_source: Arc<dyn Source>,
     #[allow(dead_code)]
     pub(super) default_font_collection: Arc<FontCollection>,
+    // Font collections for CSS `font` values, since selecting fonts by family is slow.
+    pub(super) font_collections_by_shorthand: HashMap<String, Arc<FontCollection>>,
 }
 
 impl CanvasFontContext {
@@ -247,6 +439,7 @@ impl CanvasFontContext {
             font_source,
             default_font_collection: Arc::new(default_font_collection),
             font_context: FontContext::new(),
+            font_collections_by_shorthand: HashMap::new(),
         })))
     }
 
@@ -275,6 +468,40 @@ impl CanvasFontContext {
             .load()
             .expect("Failed to load the font!")
     }
+
+    fn get_font_collection_by_shorthand(&self, shorthand: &str) -> Option<Arc<FontCollection>> {
+        if let Some(font_collection) = self.0.borrow().font_collections_by_shorthand.get(shorthand)
+        {
+            return Some(font_collection.clone());
+        }
+
+        let font = FontShorthand::parse(shorthand)?;
+        let mut font_collection = FontCollection::new();
+        {
+            let this = self.0.borrow();
+            let fallback = FamilyName::SansSerif;
+            for family_name in font.families.iter().chain(iter::once(&fallback)) {
+                let family_names = [family_name.clone()];
+                let handle = match this
+                    .font_source
+                    .select_best_match(&family_names, &font.properties)
+                {
+                    Ok(handle) => handle,
+                    Err(_) => continue,
+                };
+                if let Ok(font) = handle.load() {
+                    font_collection.add_family(FontFamily::new_from_font(font));
+                }
+            }
+        }
+
+        let font_collection = Arc::new(font_collection);
+        self.0
+            .borrow_mut()
+            .font_collections_by_shorthand
+            .insert(shorthand.to_owned(), font_collection.clone());
+        Some(font_collection)
+    }
 }
 
 // Text layout utilities
@@ -283,6 +510,11 @@ impl CanvasFontContext {
 /// passed into `fill_text()` and/or `stroke_text()` to draw the text without having to lay it out
 /// again.
 ///
+/// The metrics follow the HTML `TextMetrics` interface. Horizontal distances are measured from
+/// the point given by the `text_align` state, and vertical distances from the line given by the
+/// `text_baseline` state. Ascents and baselines are positive above that line, descents are
+/// positive below it, and the left and right extents are positive in their own direction.
+///
 /// Internally, this structure caches most of its layout queries.
 #[derive(Clone)]
 pub struct TextMetrics {
@@ -295,42 +527,25 @@ pub struct TextMetrics {
     vertical_metrics: Cell<Option<VerticalMetrics>>,
     // The calculated width of a segment of inline text in pixels.
     width: Cell<Option<f32>>,
-    // The distance from the typographic left side of the text to the left side of the bounding
-    // rectangle of the given text, in pixels. The distance is measured parallel to the baseline.
-    actual_left_extent: Cell<Option<f32>>,
-    // The distance from the typographic right side of the text to the right side of the bounding
-    // rectangle of the given text, in pixels. The distance is measured parallel to the baseline.
-    actual_right_extent: Cell<Option<f32>>,
+    // The union of the bounding boxes of the glyphs, in pixels, with the origin at the start of
+    // the alphabetic baseline and y pointing up.
+    glyph_bounds: Cell<Option<RectF>>,
 }
 
+// These are all measured from the alphabetic baseline, in pixels, with y pointing up.
 #[derive(Clone, Copy)]
 struct VerticalMetrics {
-    // The distance from the horizontal line indicated by the `text_baseline` state to the top of
-    // the highest bounding rectangle of all the fonts used to render the text, in pixels.
+    // The top of the highest bounding rectangle of all the fonts used to render the text.
     font_bounding_box_ascent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the bottom
-    // of the highest bounding rectangle of all the fonts used to render the text, in pixels.
+    // The bottom of the lowest bounding rectangle of all the fonts used to render the text.
     font_bounding_box_descent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the top of
-    // the bounding rectangle used to render the text, in pixels.
-    actual_bounding_box_ascent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the bottom
-    // of the bounding rectangle used to render the text, in pixels.
-    actual_bounding_box_descent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the top of
-    // the em square in the line box, in pixels.
+    // The top of the highest em square of all the fonts used to render the text.
     em_height_ascent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the bottom
-    // of the em square in the line box, in pixels.
+    // The bottom of the lowest em square of all the fonts used to render the text.
     em_height_descent: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the hanging
-    // baseline of the line box, in pixels.
+    // The hanging baseline.
     hanging_baseline: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the
-    // alphabetic baseline of the line box, in pixels.
-    alphabetic_baseline: f32,
-    // The distance from the horizontal line indicated by the `text_baseline` state to the
-    // ideographic baseline of the line box, in pixels.
+    // The ideographic baseline.
     ideographic_baseline: f32,
 }
 
@@ -350,16 +565,15 @@ impl TextMetrics {
             text_y_offset: Cell::new(None),
             vertical_metrics: Cell::new(None),
             width: Cell::new(None),
-            actual_left_extent: Cell::new(None),
-            actual_right_extent: Cell::new(None),
+            glyph_bounds: Cell::new(None),
         }
     }
 
     pub fn text_x_offset(&self) -> f32 {
         if self.text_x_offset.get().is_none() {
             self.text_x_offset.set(Some(match self.align {
-                TextAlign::Left => 0.0,
-                TextAlign::Right => -self.width(),
+                TextAlign::Left | TextAlign::Start => 0.0,
+                TextAlign::Right | TextAlign::End => -self.width(),
                 TextAlign::Center => -0.5 * self.width(),
             }));
         }
@@ -367,9 +581,8 @@ impl TextMetrics {
     }
 
     pub fn text_y_offset(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
         if self.text_y_offset.get().is_none() {
-            let vertical_metrics = self.vertical_metrics.get().unwrap();
+            let vertical_metrics = self.vertical_metrics();
             self.text_y_offset.set(Some(match self.baseline {
                 TextBaseline::Alphabetic => 0.0,
                 TextBaseline::Top => vertical_metrics.em_height_ascent,
@@ -390,146 +603,87 @@ impl TextMetrics {
         vec2f(self.text_x_offset(), self.text_y_offset())
     }
 
+    /// The advance width of the text, in pixels.
     pub fn width(&self) -> f32 {
         if self.width.get().is_none() {
-            match self.skribo_layout.glyphs.last() {
-                None => self.width.set(Some(0.0)),
-                Some(last_glyph) => {
-                    let glyph_id = last_glyph.glyph_id;
-                    let font_metrics = last_glyph.font.font.metrics();
-                    let scale_factor = self.skribo_layout.size / font_metrics.units_per_em as f32;
-                    let glyph_rect = last_glyph.font.font.typographic_bounds(glyph_id).unwrap();
-                    self.width.set(Some(
-                        last_glyph.offset.x() + glyph_rect.max_x() * scale_factor,
-                    ));
-                }
-            }
+            self.width.set(Some(layout_width(&self.skribo_layout)));
         }
         self.width.get().unwrap()
     }
 
-    fn populate_vertical_metrics_if_necessary(&self) {
+    fn vertical_metrics(&self) -> VerticalMetrics {
         if self.vertical_metrics.get().is_none() {
             self.vertical_metrics
                 .set(Some(VerticalMetrics::measure(&self.skribo_layout)));
         }
+        self.vertical_metrics.get().unwrap()
+    }
+
+    fn glyph_bounds(&self) -> RectF {
+        if self.glyph_bounds.get().is_none() {
+            self.glyph_bounds
+                .set(Some(measure_glyph_bounds(&self.skribo_layout)));
+        }
+        self.glyph_bounds.get().unwrap()
     }
 
+    /// The distance from the `text_baseline` line to the top of the highest bounding box of the
+    /// fonts used, in pixels.
     pub fn font_bounding_box_ascent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics
-            .get()
-            .unwrap()
-            .font_bounding_box_ascent
-            - self.text_y_offset()
+        self.vertical_metrics().font_bounding_box_ascent - self.text_y_offset()
     }
 
+    /// The distance from the `text_baseline` line to the bottom of the lowest bounding box of the
+    /// fonts used, in pixels.
     pub fn font_bounding_box_descent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics
-            .get()
-            .unwrap()
-            .font_bounding_box_descent
-            - self.text_y_offset()
+        self.text_y_offset() - self.vertical_metrics().font_bounding_box_descent
     }
 
+    /// The distance from the `text_baseline` line to the top of the glyphs, in pixels.
     pub fn actual_bounding_box_ascent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics
-            .get()
-            .unwrap()
-            .actual_bounding_box_ascent
-            - self.text_y_offset()
+        self.glyph_bounds().max_y() - self.text_y_offset()
     }
 
+    /// The distance from the `text_baseline` line to the bottom of the glyphs, in pixels.
     pub fn actual_bounding_box_descent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics
-            .get()
-            .unwrap()
-            .actual_bounding_box_descent
-            - self.text_y_offset()
+        self.text_y_offset() - self.glyph_bounds().min_y()
     }
 
+    /// The distance from the `text_baseline` line to the top of the em squares of the fonts
+    /// used, in pixels.
     pub fn em_height_ascent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics.get().unwrap().em_height_ascent - self.text_y_offset()
+        self.vertical_metrics().em_height_ascent - self.text_y_offset()
     }
 
+    /// The distance from the `text_baseline` line to the bottom of the em squares of the fonts
+    /// used, in pixels.
     pub fn em_height_descent(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics.get().unwrap().em_height_descent - self.text_y_offset()
+        self.text_y_offset() - self.vertical_metrics().em_height_descent
     }
 
+    /// The distance from the `text_align` point to the left side of the glyphs, in pixels.
     pub fn actual_bounding_box_left(&self) -> f32 {
-        if self.actual_left_extent.get().is_none() {
-            match self.skribo_layout.glyphs.get(0) {
-                None => self.actual_left_extent.set(Some(0.0)),
-                Some(first_glyph) => {
-                    let glyph_id = first_glyph.glyph_id;
-                    let font_metrics = first_glyph.font.font.metrics();
-                    let scale_factor = self.skribo_layout.size / font_metrics.units_per_em as f32;
-                    let glyph_rect = first_glyph
-                        .font
-                        .font
-                        .raster_bounds(
-                            glyph_id,
-                            font_metrics.units_per_em as f32,
-                            Transform2F::default(),
-                            HintingOptions::None,
-                            RasterizationOptions::GrayscaleAa,
-                        )
-                        .unwrap();
-                    self.actual_left_extent.set(Some(
-                        first_glyph.offset.x() + glyph_rect.min_x() as f32 * scale_factor,
-                    ));
-                }
-            }
-        }
-        self.actual_left_extent.get().unwrap() + self.text_x_offset()
+        -self.text_x_offset() - self.glyph_bounds().min_x()
     }
 
+    /// The distance from the `text_align` point to the right side of the glyphs, in pixels.
     pub fn actual_bounding_box_right(&self) -> f32 {
-        if self.actual_right_extent.get().is_none() {
-            match self.skribo_layout.glyphs.last() {
-                None => self.actual_right_extent.set(Some(0.0)),
-                Some(last_glyph) => {
-                    let glyph_id = last_glyph.glyph_id;
-                    let font_metrics = last_glyph.font.font.metrics();
-                    let scale_factor = self.skribo_layout.size / font_metrics.units_per_em as f32;
-                    let glyph_rect = last_glyph
-                        .font
-                        .font
-                        .raster_bounds(
-                            glyph_id,
-                            font_metrics.units_per_em as f32,
-                            Transform2F::default(),
-                            HintingOptions::None,
-                            RasterizationOptions::GrayscaleAa,
-                        )
-                        .unwrap();
-                    self.actual_right_extent.set(Some(
-                        last_glyph.offset.x() + glyph_rect.max_x() as f32 * scale_factor,
-                    ));
-                }
-            }
-        }
-        self.actual_right_extent.get().unwrap() + self.text_x_offset()
+        self.glyph_bounds().max_x() + self.text_x_offset()
     }
 
+    /// The distance from the `text_baseline` line up to the hanging baseline, in pixels.
     pub fn hanging_baseline(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics.get().unwrap().hanging_baseline - self.text_y_offset()
+        self.vertical_metrics().hanging_baseline - self.text_y_offset()
     }
 
+    /// The distance from the `text_baseline` line up to the alphabetic baseline, in pixels.
     pub fn alphabetic_baseline(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics.get().unwrap().alphabetic_baseline - self.text_y_offset()
+        -self.text_y_offset()
     }
 
+    /// The distance from the `text_baseline` line up to the ideographic baseline, in pixels.
     pub fn ideographic_baseline(&self) -> f32 {
-        self.populate_vertical_metrics_if_necessary();
-        self.vertical_metrics.get().unwrap().ideographic_baseline - self.text_y_offset()
+        self.vertical_metrics().ideographic_baseline - self.text_y_offset()
     }
 }
 
@@ -538,63 +692,116 @@ impl VerticalMetrics {
         let mut vertical_metrics = VerticalMetrics {
             font_bounding_box_ascent: 0.0,
             font_bounding_box_descent: 0.0,
-            actual_bounding_box_ascent: 0.0,
-            actual_bounding_box_descent: 0.0,
             em_height_ascent: 0.0,
             em_height_descent: 0.0,
             hanging_baseline: 0.0,
-            alphabetic_baseline: 0.0,
             ideographic_baseline: 0.0,
         };
 
         let mut last_font: Option<Arc<Font>> = None;
         for glyph in &skribo_layout.glyphs {
             match last_font {
-                Some(ref last_font) if Arc::ptr_eq(&last_font, &glyph.font.font) => {}
-                _ => {
-                    let font = glyph.font.font.clone();
-
-                    let font_metrics = font.metrics();
-                    let scale_factor = skribo_layout.size / font_metrics.units_per_em as f32;
-                    vertical_metrics.em_height_ascent =
-                        (font_metrics.ascent * scale_factor).max(vertical_metrics.em_height_ascent);
-                    vertical_metrics.em_height_descent = (font_metrics.descent * scale_factor)
-                        .min(vertical_metrics.em_height_descent);
-                    vertical_metrics.font_bounding_box_ascent = (font_metrics.bounding_box.max_y()
-                        * scale_factor)
-                        .max(vertical_metrics.font_bounding_box_ascent);
-                    vertical_metrics.font_bounding_box_descent =
-                        (font_metrics.bounding_box.min_y() * scale_factor)
-                            .min(vertical_metrics.font_bounding_box_descent);
-
-                    last_font = Some(font);
-                }
+                Some(ref last_font) if Arc::ptr_eq(&last_font, &glyph.font.font) => continue,
+                _ => {}
             }
-
-            let font = last_font.as_ref().unwrap();
-            let glyph_rect = font
-                .raster_bounds(
-                    glyph.glyph_id,
-                    skribo_layout.size,
-                    Transform2F::default(),
-                    HintingOptions::None,
-                    RasterizationOptions::GrayscaleAa,
-                )
-                .unwrap();
-            vertical_metrics.actual_bounding_box_ascent =
-                (glyph_rect.max_y() as f32).max(vertical_metrics.actual_bounding_box_ascent);
-            vertical_metrics.actual_bounding_box_descent =
-                (glyph_rect.min_y() as f32).min(vertical_metrics.actual_bounding_box_descent);
+            let font = glyph.font.font.clone();
+
+            let font_metrics = font.metrics();
+            let scale_factor = skribo_layout.size / font_metrics.units_per_em as f32;
+            let ascent = font_metrics.ascent * scale_factor;
+            let descent = font_metrics.descent * scale_factor;
+
+            // The em square is placed so that it splits the font's ascent and descent in
+            // proportion, as browsers do.
+            let (em_height_ascent, em_height_descent) = if ascent > descent {
+                let em_height_ascent = skribo_layout.size * ascent / (ascent - descent);
+                (em_height_ascent, em_height_ascent - skribo_layout.size)
+            } else {
+                (ascent, descent)
+            };
+
+            vertical_metrics.em_height_ascent =
+                em_height_ascent.max(vertical_metrics.em_height_ascent);
+            vertical_metrics.em_height_descent =
+                em_height_descent.min(vertical_metrics.em_height_descent);
+            vertical_metrics.font_bounding_box_ascent = (font_metrics.bounding_box.max_y()
+                * scale_factor)
+                .max(vertical_metrics.font_bounding_box_ascent);
+            vertical_metrics.font_bounding_box_descent = (font_metrics.bounding_box.min_y()
+                * scale_factor)
+                .min(vertical_metrics.font_bounding_box_descent);
+
+            // Use the hanging and ideographic baselines from the font's `BASE` table. Most fonts
+            // don't have one, so fall back to the same approximations as browsers: 80% of the
+            // ascent, and the descent.
+            let baselines = Baselines::from_font(&*font);
+            let hanging_baseline = baselines
+                .hanging
+                .map_or(ascent * 0.8, |hanging| hanging * scale_factor);
+            let ideographic_baseline = baselines
+                .ideographic
+                .map_or(descent, |ideographic| ideographic * scale_factor);
+            vertical_metrics.hanging_baseline =
+                hanging_baseline.max(vertical_metrics.hanging_baseline);
+            vertical_metrics.ideographic_baseline =
+                ideographic_baseline.min(vertical_metrics.ideographic_baseline);
+
+            last_font = Some(font);
         }
 
         vertical_metrics
     }
 }
 
+fn push_text_layout(
+    canvas_font_context: &CanvasFontContext,
+    scene: &mut Scene,
+    layout: &TextMetrics,
+    render_options: &FontRenderOptions,
+) {
+    // TODO(pcwalton): Report errors.
+    drop(canvas_font_context.0.borrow_mut().font_context.push_layout(
+        scene,
+        &layout.skribo_layout,
+        &TextStyle {
+            size: layout.font_size,
+        },
+        render_options,
+    ));
+}
+
+// Returns the union of the bounding boxes of the glyphs in the layout, in pixels, with y pointing
+// up.
+fn measure_glyph_bounds(skribo_layout: &SkriboLayout) -> RectF {
+    let mut bounds: Option<RectF> = None;
+    for glyph in &skribo_layout.glyphs {
+        let font = &glyph.font.font;
+        let glyph_rect = match font.typographic_bounds(glyph.glyph_id) {
+            Ok(glyph_rect) if glyph_rect.width() > 0.0 && glyph_rect.height() > 0.0 => glyph_rect,
+            // Blank glyphs, such as spaces, don't contribute to the bounds.
+            _ => continue,
+        };
+        let scale_factor = skribo_layout.size / font.metrics().units_per_em as f32;
+        // Glyph offsets point down, like the canvas.
+        let glyph_rect = glyph_rect * scale_factor + glyph.offset * vec2f(1.0, -1.0);
+        bounds = Some(match bounds {
+            None => glyph_rect,
+            Some(bounds) => bounds.union_rect(glyph_rect),
+        });
+    }
+    bounds.unwrap_or_default()
+}
+
 /// Various things that can be conveniently converted into font collections for use with
 /// `CanvasRenderingContext2D::set_font()`.
 pub trait IntoFontCollection {
     fn into_font_collection(self, font_context: &CanvasFontContext) -> Arc<FontCollection>;
+
+    /// Returns the font size that this value specifies along with the font, if any.
+    #[inline]
+    fn font_size(&self) -> Option<f32> {
+        None
+    }
 }
 
 impl IntoFontCollection for Arc<FontCollection> {
@@ -645,9 +852,17 @@ impl<'a> IntoFontCollection for &'a [Font] {
 impl<'a> IntoFontCollection for &'a str {
     #[inline]
     fn into_font_collection(self, context: &CanvasFontContext) -> Arc<FontCollection> {
-        context
-            .get_font_by_postscript_name(self)
-            .into_font_collection(context)
+        match context.get_font_collection_by_shorthand(self) {
+            Some(font_collection) => font_collection,
+            None => context
+                .get_font_by_postscript_name(self)
+                .into_font_collection(context),
+        }
+    }
+
+    #[inline]
+    fn font_size(&self) -> Option<f32> {
+        FontShorthand::parse(self).map(|font| font.size)
     }
 }
 
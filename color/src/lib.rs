@@ -8,10 +8,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! Colors and color conversions.
+//!
+//! With the default `std` feature disabled, this crate is `no_std` and needs only `alloc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::fmt::{self, Debug, Formatter};
+use core::slice;
 use pathfinder_simd::default::F32x4;
-use std::f32::consts::PI;
-use std::fmt::{self, Debug, Formatter};
-use std::slice;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 pub mod matrix;
 
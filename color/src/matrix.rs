@@ -8,8 +8,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use core::ops::{Add, Deref, Mul};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Deref, Mul};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// ColorMatrix filter/transformation
 ///
@@ -14,14 +14,16 @@
 
 use crate::outline::{Contour, ContourIterFlags, PointFlags, PushSegmentFlags};
 use crate::segment::{CubicSegment, Segment};
+use alloc::vec;
+use alloc::vec::Vec;
 use arrayvec::ArrayVec;
+use core::fmt::Debug;
+use core::mem;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::util::lerp;
 use pathfinder_geometry::vector::{vec2f, Vector2F, Vector4F};
 use smallvec::SmallVec;
-use std::fmt::Debug;
-use std::mem;
 
 #[derive(Clone, Copy, Debug)]
 struct Edge(LineSegment2F);
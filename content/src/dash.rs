@@ -11,7 +11,7 @@
 //! Transforms a stroke into a dashed stroke.
 
 use crate::outline::{Contour, ContourIterFlags, Outline, PushSegmentFlags};
-use std::mem;
+use core::mem;
 
 const EPSILON: f32 = 0.0001;
 
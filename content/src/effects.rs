@@ -10,6 +10,8 @@
 
 //! Special effects that can be applied to layers.
 
+use crate::noise::Noise;
+use crate::paint_effect::PaintEffect;
 use pathfinder_color::{matrix::ColorMatrix, ColorF};
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::vector::Vector2F;
@@ -55,6 +57,27 @@ pub enum Filter {
         uv_origin: Vector2F,
     },
 
+    /// Converts a linear gradient to a conic one.
+    ConicGradient {
+        /// The point that the gradient sweeps around.
+        center: Vector2F,
+        /// The angle at which the gradient starts, in radians.
+        angle: f32,
+        /// The origin of the linearized gradient in the texture.
+        uv_origin: Vector2F,
+    },
+
+    /// Evaluates procedural noise instead of sampling the color texture.
+    Noise(Noise),
+
+    /// Evaluates a stylized coverage pattern instead of sampling the color texture.
+    PaintEffect {
+        /// The pattern to draw.
+        effect: PaintEffect,
+        /// The size of a device pixel in effect space, for antialiasing.
+        pixel_size: f32,
+    },
+
     /// One of the `PatternFilter` filters.
     PatternFilter(PatternFilter),
 }
@@ -11,21 +11,25 @@
 //! Gradient effects that paths can be filled with.
 
 use crate::util;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert;
+use core::f32::consts::PI;
+use core::hash::{Hash, Hasher};
+use core::mem;
 use pathfinder_color::ColorU;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util as geometry_util;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
 use pathfinder_simd::default::F32x2;
-use std::cmp::Ordering;
-use std::convert;
-use std::hash::{Hash, Hasher};
-use std::mem;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
-/// A gradient, either linear or radial.
+/// A gradient, either linear, radial, or conic.
 #[derive(Clone, PartialEq, Debug)]
 pub struct Gradient {
-    /// Information specific to the type of gradient (linear or radial).
+    /// Information specific to the type of gradient (linear, radial, or conic).
     pub geometry: GradientGeometry,
     stops: Vec<ColorStop>,
     /// What should be rendered upon reaching the end of the color stops.
@@ -43,7 +47,7 @@ pub struct ColorStop {
     pub color: ColorU,
 }
 
-/// The type of gradient: linear or radial.
+/// The type of gradient: linear, radial, or conic.
 #[derive(Clone, PartialEq, Debug)]
 pub enum GradientGeometry {
     /// A linear gradient that follows a line.
@@ -68,6 +72,18 @@ pub enum GradientGeometry {
         /// transform.
         transform: Transform2F,
     },
+    /// A conic gradient that sweeps once around a center point, like the hand of a clock.
+    Conic {
+        /// The point that the gradient sweeps around.
+        ///
+        /// This is in scene coordinates, not relative to the bounding box of the path.
+        center: Vector2F,
+        /// The angle at which the gradient starts, in radians, measured from the +x axis toward
+        /// the +y axis. The gradient sweeps in the same direction.
+        angle: f32,
+        /// Transform from conic gradient space into screen space.
+        transform: Transform2F,
+    },
 }
 
 /// What should be rendered outside the color stops.
@@ -108,6 +124,22 @@ impl Hash for Gradient {
                 util::hash_f32(transform.m22(), state);
                 util::hash_f32(transform.m23(), state);
             }
+            GradientGeometry::Conic {
+                center,
+                angle,
+                transform,
+            } => {
+                (2).hash(state);
+                util::hash_f32(center.x(), state);
+                util::hash_f32(center.y(), state);
+                util::hash_f32(angle, state);
+                util::hash_f32(transform.m11(), state);
+                util::hash_f32(transform.m12(), state);
+                util::hash_f32(transform.m13(), state);
+                util::hash_f32(transform.m21(), state);
+                util::hash_f32(transform.m22(), state);
+                util::hash_f32(transform.m23(), state);
+            }
         }
         self.stops.hash(state);
     }
@@ -171,6 +203,24 @@ impl Gradient {
         }
     }
 
+    /// Creates a new conic gradient that sweeps around the given center point, starting at the
+    /// given angle in radians.
+    ///
+    /// The angle is measured from the +x axis toward the +y axis, which is clockwise when y
+    /// points down, as it does in HTML canvas.
+    #[inline]
+    pub fn conic(center: Vector2F, angle: f32) -> Gradient {
+        Gradient {
+            geometry: GradientGeometry::Conic {
+                center,
+                angle,
+                transform: Transform2F::default(),
+            },
+            stops: Vec::new(),
+            wrap: GradientWrap::Clamp,
+        }
+    }
+
     /// Adds a new color stop to the radial gradient.
     #[inline]
     pub fn add(&mut self, stop: ColorStop) {
@@ -267,6 +317,10 @@ impl Gradient {
 
     /// Applies the given affine transform to this gradient.
     ///
+    /// Lines of constant color in a linear gradient stay perpendicular to its line only under
+    /// transforms that preserve angles, so for others the line is rotated to keep every point on
+    /// the color it had before the transform.
+    ///
     /// FIXME(pcwalton): This isn't correct for radial gradients, as transforms can transform the
     /// circles into ellipses…
     pub fn apply_transform(&mut self, new_transform: Transform2F) {
@@ -275,14 +329,52 @@ impl Gradient {
         }
 
         match self.geometry {
-            GradientGeometry::Linear(ref mut line) => *line = new_transform * *line,
+            GradientGeometry::Linear(ref mut line) => {
+                // A point `p` samples the gradient at `dot(p - from, d) / |d|^2`, where `d` is the
+                // line vector. After the transform, the point `M p` must sample it at the same
+                // place, which makes the new line vector parallel to `M^-T d`.
+                let vector = line.vector();
+                let inverse = new_transform.matrix.inverse();
+                let normal = vec2f(
+                    inverse.m11() * vector.x() + inverse.m21() * vector.y(),
+                    inverse.m12() * vector.x() + inverse.m22() * vector.y(),
+                );
+                let from = new_transform * line.from();
+                let normal_square_length = normal.square_length();
+                *line = if normal_square_length.is_finite() && normal_square_length > 0.0 {
+                    let new_vector = normal * (vector.square_length() / normal_square_length);
+                    LineSegment2F::new(from, from + new_vector)
+                } else {
+                    new_transform * *line
+                };
+            }
             GradientGeometry::Radial {
                 ref mut transform, ..
+            }
+            | GradientGeometry::Conic {
+                ref mut transform, ..
             } => *transform = new_transform * *transform,
         }
     }
 }
 
+impl GradientGeometry {
+    /// Returns the offset into the color stops of a conic gradient at the given point, between
+    /// 0.0 and 1.0, in gradient space.
+    ///
+    /// Returns 0.0 for linear and radial gradients.
+    pub fn conic_offset(&self, point: Vector2F) -> f32 {
+        match *self {
+            GradientGeometry::Conic { center, angle, .. } => {
+                let vector = point - center;
+                let turns = (vector.y().atan2(vector.x()) - angle) / (2.0 * PI);
+                turns - turns.floor()
+            }
+            GradientGeometry::Linear(_) | GradientGeometry::Radial { .. } => 0.0,
+        }
+    }
+}
+
 impl ColorStop {
     /// Creates a new color stop from a color and offset between 0.0 and 1.0 inclusive.
     #[inline]
@@ -313,9 +405,11 @@ impl RadialGradientLine for Vector2F {
 
 #[cfg(test)]
 mod test {
-    use crate::gradient::Gradient;
+    use crate::gradient::{Gradient, GradientGeometry};
     use pathfinder_color::ColorU;
-    use pathfinder_geometry::vector::Vector2F;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, Vector2F};
+    use std::f32::consts::FRAC_PI_2;
 
     #[test]
     fn stable_order() {
@@ -331,6 +425,46 @@ mod test {
             .all(|w| { w[0].offset < w[1].offset || w[0].color.r < w[1].color.r }));
     }
 
+    #[test]
+    fn linear_transform_preserves_colors() {
+        let mut grad = Gradient::linear_from_points(vec2f(0.0, 0.0), vec2f(1.0, 1.0));
+        let transform = Transform2F::from_translation(vec2f(3.0, 1.0))
+            * Transform2F::from_scale(vec2f(2.0, 1.0));
+        grad.apply_transform(transform);
+
+        let line = match grad.geometry {
+            GradientGeometry::Linear(line) => line,
+            _ => unreachable!(),
+        };
+        for &point in &[
+            vec2f(1.0, 0.0),
+            vec2f(0.0, 1.0),
+            vec2f(0.25, 0.75),
+            vec2f(2.0, -1.0),
+        ] {
+            let expected_t = point.dot(vec2f(1.0, 1.0)) / 2.0;
+            let offset = transform * point - line.from();
+            let t = offset.dot(line.vector()) / line.vector().square_length();
+            assert!(
+                (t - expected_t).abs() < 0.0001,
+                "{:?}: {} != {}",
+                point,
+                t,
+                expected_t
+            );
+        }
+    }
+
+    #[test]
+    fn conic_offsets_sweep_from_the_start_angle() {
+        let grad = Gradient::conic(vec2f(1.0, 1.0), FRAC_PI_2);
+        let offset = |point| grad.geometry.conic_offset(point);
+        assert!(offset(vec2f(1.0, 2.0)).abs() < 0.0001);
+        assert!((offset(vec2f(0.0, 1.0)) - 0.25).abs() < 0.0001);
+        assert!((offset(vec2f(1.0, 0.0)) - 0.5).abs() < 0.0001);
+        assert!((offset(vec2f(2.0, 1.0)) - 0.75).abs() < 0.0001);
+    }
+
     #[test]
     fn never_sample_zero_width() {
         let mut grad = Gradient::linear_from_points(Vector2F::default(), Vector2F::default());
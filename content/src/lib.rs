@@ -9,9 +9,15 @@
 // except according to those terms.
 
 //! Components of a vector scene, and various path utilities.
+//!
+//! With the default `std` feature disabled, this crate is `no_std` and needs only `alloc`, so the
+//! path, stroking, and dashing code can feed a rasterizer on embedded targets. Image support
+//! (`pf-image`) requires `std`.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
@@ -22,8 +28,10 @@ pub mod dash;
 pub mod effects;
 pub mod fill;
 pub mod gradient;
+pub mod noise;
 pub mod orientation;
 pub mod outline;
+pub mod paint_effect;
 pub mod pattern;
 pub mod render_target;
 pub mod segment;
@@ -0,0 +1,152 @@
+// pathfinder/content/src/noise.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Procedural noise that paths can be filled with.
+
+use crate::util;
+use core::hash::{Hash, Hasher};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
+
+/// Tileable Perlin noise, evaluated on the GPU.
+///
+/// The parameters follow the `feTurbulence` filter primitive in SVG, so that paper textures and
+/// turbulence filters can be rendered without uploading large raster patterns.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Noise {
+    /// Whether to produce fractal noise or turbulence.
+    pub kind: NoiseKind,
+    /// The base frequency of the noise function, in cycles per unit, along each axis.
+    pub base_frequency: Vector2F,
+    /// The number of octaves of noise to sum. Each octave doubles the frequency and halves the
+    /// amplitude of the previous one.
+    pub num_octaves: u32,
+    /// The seed for the pseudorandom gradients.
+    ///
+    /// Only the low 11 bits are significant, as the seed travels to the GPU as a half float.
+    pub seed: i32,
+    /// If present, the noise is adjusted to repeat seamlessly across this rectangle, like
+    /// `stitchTiles="stitch"` in SVG.
+    pub stitch_rect: Option<RectF>,
+    /// Transform from noise space into scene space.
+    pub transform: Transform2F,
+}
+
+/// The flavor of noise to generate. See the `type` attribute of `feTurbulence`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum NoiseKind {
+    /// Sums signed noise, producing soft clouds centered around 50% intensity.
+    FractalNoise,
+    /// Sums the absolute value of noise, producing sharp creases.
+    Turbulence,
+}
+
+impl Eq for Noise {}
+
+impl Hash for Noise {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.kind.hash(state);
+        util::hash_f32x2(self.base_frequency.0, state);
+        self.num_octaves.hash(state);
+        self.seed.hash(state);
+        match self.stitch_rect {
+            None => (0).hash(state),
+            Some(rect) => {
+                (1).hash(state);
+                util::hash_f32x4(rect.0, state);
+            }
+        }
+        util::hash_transform2f(self.transform, state);
+    }
+}
+
+impl Noise {
+    /// Creates a new single-octave noise function with the given kind and base frequency.
+    #[inline]
+    pub fn new(kind: NoiseKind, base_frequency: Vector2F) -> Noise {
+        Noise {
+            kind,
+            base_frequency,
+            num_octaves: 1,
+            seed: 0,
+            stitch_rect: None,
+            transform: Transform2F::default(),
+        }
+    }
+
+    /// Applies the given affine transform to this noise function.
+    #[inline]
+    pub fn apply_transform(&mut self, transform: Transform2F) {
+        self.transform = transform * self.transform;
+    }
+
+    /// Returns the base frequency, adjusted so that a whole number of lattice cells fits inside
+    /// the stitch rectangle, if there is one.
+    ///
+    /// This follows the algorithm in the SVG specification: each axis is rounded to whichever of
+    /// the neighboring frequencies is closer by ratio.
+    pub fn stitched_base_frequency(&self) -> Vector2F {
+        let rect = match self.stitch_rect {
+            None => return self.base_frequency,
+            Some(rect) => rect,
+        };
+        vec2f(
+            stitch_frequency(self.base_frequency.x(), rect.width()),
+            stitch_frequency(self.base_frequency.y(), rect.height()),
+        )
+    }
+
+    /// Returns the number of lattice cells that the first octave repeats after along each axis,
+    /// or zero if the noise is not stitched.
+    pub fn stitch_period(&self) -> Vector2F {
+        match self.stitch_rect {
+            None => Vector2F::zero(),
+            Some(rect) => (self.stitched_base_frequency() * rect.size()).round(),
+        }
+    }
+}
+
+fn stitch_frequency(frequency: f32, length: f32) -> f32 {
+    if frequency == 0.0 || length <= 0.0 {
+        return frequency;
+    }
+    let lo = f32::floor(length * frequency) / length;
+    let hi = f32::ceil(length * frequency) / length;
+    if lo == 0.0 || frequency / lo >= hi / frequency {
+        hi
+    } else {
+        lo
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Noise, NoiseKind};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn stitching_fits_whole_cells() {
+        let mut noise = Noise::new(NoiseKind::Turbulence, vec2f(0.05, 0.013));
+        assert_eq!(noise.stitch_period(), vec2f(0.0, 0.0));
+
+        noise.stitch_rect = Some(RectF::new(vec2f(10.0, 10.0), vec2f(110.0, 200.0)));
+        let frequency = noise.stitched_base_frequency();
+        assert_eq!(noise.stitch_period(), vec2f(6.0, 3.0));
+        assert!((frequency.x() * 110.0 - 6.0).abs() < 0.0001);
+        assert!((frequency.y() * 200.0 - 3.0).abs() < 0.0001);
+    }
+}
@@ -12,18 +12,24 @@
 
 use crate::clip::{self, ContourPolygonClipper};
 use crate::dilation::ContourDilator;
+use crate::fill::FillRule;
 use crate::orientation::Orientation;
 use crate::segment::{Segment, SegmentFlags, SegmentKind};
+use crate::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
 use crate::util::safe_sqrt;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::{PI, SQRT_2};
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::unit_vector::UnitVector;
 use pathfinder_geometry::vector::{vec2f, Vector2F};
-use std::f32::consts::PI;
-use std::fmt::{self, Debug, Formatter};
-use std::mem;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// A vector path to be filled. Outlines (a.k.a. paths) consist of *contours* (a.k.a. subpaths),
 /// which can be filled according to a fill rule.
@@ -251,6 +257,104 @@ impl Outline {
         self.bounds = self.bounds.dilate(amount);
     }
 
+    /// Returns a copy of this outline with every cubic Bézier curve approximated by quadratic
+    /// Bézier curves that deviate from the original by no more than `tolerance`.
+    ///
+    /// Lines and quadratic curves are copied unchanged.
+    pub fn to_quadratics(&self, tolerance: f32) -> Outline {
+        let mut outline = Outline::with_capacity(self.contours.len());
+        for contour in &self.contours {
+            outline.push_contour(contour.to_quadratics(tolerance));
+        }
+        outline
+    }
+
+    /// Reverses contours as necessary so that this outline fills correctly under the nonzero
+    /// winding rule regardless of how its contours were originally wound.
+    ///
+    /// With `FillRule::EvenOdd`, contours are treated as nested shapes and holes, as the even-odd
+    /// rule would fill them: outermost contours and contours nested an even number of levels deep
+    /// are wound clockwise, and the rest are wound counterclockwise. With `FillRule::Winding`,
+    /// every contour is wound clockwise, so that every contour adds coverage.
+    ///
+    /// Nesting is determined by testing the first point of each contour against the others, so
+    /// the results are only meaningful for contours that don't intersect one another.
+    pub fn normalize_orientations(&mut self, fill_rule: FillRule) {
+        let mut clockwise = vec![true; self.contours.len()];
+        if fill_rule == FillRule::EvenOdd {
+            for (contour_index, contour) in self.contours.iter().enumerate() {
+                let point = match contour.first_position() {
+                    None => continue,
+                    Some(point) => point,
+                };
+                let depth = self
+                    .contours
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other_index, other)| {
+                        other_index != contour_index
+                            && other.bounds.contains_point(point)
+                            && other.winding_number(point) != 0
+                    })
+                    .count();
+                clockwise[contour_index] = depth % 2 == 0;
+            }
+        }
+
+        for (contour, clockwise) in self.contours.iter_mut().zip(clockwise) {
+            let area = contour.signed_area();
+            if area != 0.0 && (area > 0.0) != clockwise {
+                contour.reverse();
+            }
+        }
+    }
+
+    /// Returns true if the given point lies inside this outline when it's filled with the given
+    /// fill rule.
+    ///
+    /// Every contour is treated as closed, as it is when filling.
+    pub fn contains_point(&self, point: Vector2F, fill_rule: FillRule) -> bool {
+        if !self.bounds.contains_point(point) {
+            return false;
+        }
+        let winding: i32 = self
+            .contours
+            .iter()
+            .filter(|contour| contour.bounds.contains_point(point))
+            .map(|contour| contour.winding_number(point))
+            .sum();
+        match fill_rule {
+            FillRule::Winding => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    /// Returns true if the given point lies inside the stroke of this outline with the given
+    /// style.
+    ///
+    /// The stroke is the one that `OutlineStrokeToFill` produces, filled with the nonzero winding
+    /// rule, so this agrees with how strokes are rendered.
+    pub fn stroke_contains_point(&self, point: Vector2F, style: StrokeStyle) -> bool {
+        // The stroke reaches at most this far outside the outline's bounds.
+        let half_width = style.line_width * 0.5;
+        let mut extent = half_width;
+        if style.line_cap == LineCap::Square {
+            extent *= SQRT_2;
+        }
+        if let LineJoin::Miter(miter_limit) = style.line_join {
+            extent = extent.max(half_width * miter_limit);
+        }
+        if !self.bounds.dilate(extent).contains_point(point) {
+            return false;
+        }
+
+        let mut stroke_to_fill = OutlineStrokeToFill::new(self, style);
+        stroke_to_fill.offset();
+        stroke_to_fill
+            .into_outline()
+            .contains_point(point, FillRule::Winding)
+    }
+
     /// Returns true if this outline is obviously completely outside the closed polygon with the
     /// given vertices, via a quick check.
     ///
@@ -363,7 +467,7 @@ impl Contour {
     /// Creates a closed subpath representing the given axis-aligned rounded rectangle.
     #[inline]
     pub fn from_rect_rounded(rect: RectF, radius: Vector2F) -> Contour {
-        use std::f32::consts::SQRT_2;
+        use core::f32::consts::SQRT_2;
         const QUARTER_ARC_CP_FROM_OUTSIDE: f32 = (3.0 - 4.0 * (SQRT_2 - 1.0)) / 3.0;
 
         if radius.is_zero() {
@@ -900,6 +1004,84 @@ impl Contour {
         }
     }
 
+    /// Returns the signed area enclosed by this contour, computed exactly for curves.
+    ///
+    /// The area is positive if the contour is wound clockwise and negative if it is wound
+    /// counterclockwise, with Y down. Open contours are treated as though they were closed.
+    pub fn signed_area(&self) -> f32 {
+        let (first_position, last_position) = match (self.first_position(), self.last_position()) {
+            (Some(first_position), Some(last_position)) => (first_position, last_position),
+            _ => return 0.0,
+        };
+
+        let mut area = last_position.det(first_position) * 0.5;
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let (p0, p3) = (segment.baseline.from(), segment.baseline.to());
+            area += match segment.kind {
+                SegmentKind::None => 0.0,
+                SegmentKind::Line => p0.det(p3) * 0.5,
+                SegmentKind::Quadratic => {
+                    let p1 = segment.ctrl.from();
+                    (2.0 * p0.det(p1) + p0.det(p3) + 2.0 * p1.det(p3)) / 6.0
+                }
+                SegmentKind::Cubic => {
+                    let (p1, p2) = (segment.ctrl.from(), segment.ctrl.to());
+                    (6.0 * p0.det(p1)
+                        + 3.0 * p0.det(p2)
+                        + p0.det(p3)
+                        + 3.0 * p1.det(p2)
+                        + 3.0 * p1.det(p3)
+                        + 6.0 * p2.det(p3))
+                        / 20.0
+                }
+            };
+        }
+        area
+    }
+
+    /// Returns true if this contour is wound clockwise, with Y down.
+    #[inline]
+    pub fn is_clockwise(&self) -> bool {
+        self.signed_area() > 0.0
+    }
+
+    /// Reverses the direction of this contour in place.
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+        self.flags.reverse();
+
+        // The control points of each cubic curve have swapped places, so swap their flags back.
+        let mut point_index = 0;
+        while point_index < self.flags.len() {
+            if self.flags[point_index] == PointFlags::CONTROL_POINT_1 {
+                self.flags[point_index] = PointFlags::CONTROL_POINT_0;
+                if let Some(flags) = self.flags.get_mut(point_index + 1) {
+                    *flags = PointFlags::CONTROL_POINT_1;
+                }
+                point_index += 1;
+            }
+            point_index += 1;
+        }
+    }
+
+    // Returns the winding number of this contour around the given point, treating the contour as
+    // closed.
+    pub(crate) fn winding_number(&self, point: Vector2F) -> i32 {
+        let (first_position, last_position) = match (self.first_position(), self.last_position()) {
+            (Some(first_position), Some(last_position)) => (first_position, last_position),
+            _ => return 0,
+        };
+
+        let mut winding = 0;
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            if !segment.is_none() {
+                winding += segment_winding_number(&segment.to_line_or_cubic(), point, 0);
+            }
+        }
+        let close_segment = Segment::line(LineSegment2F::new(last_position, first_position));
+        winding + segment_winding_number(&close_segment, point, 0)
+    }
+
     /// Applies the given affine transform to this subpath.
     pub fn transform(&mut self, transform: &Transform2F) {
         if transform.is_identity() {
@@ -929,6 +1111,33 @@ impl Contour {
         }
     }
 
+    /// Returns a copy of this contour with every cubic Bézier curve approximated by quadratic
+    /// Bézier curves that deviate from the original by no more than `tolerance`.
+    pub fn to_quadratics(&self, tolerance: f32) -> Contour {
+        let mut contour = Contour::with_capacity(self.points.len());
+        if let Some(first_position) = self.first_position() {
+            contour.push_endpoint(first_position);
+        }
+
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => contour.push_endpoint(segment.baseline.to()),
+                SegmentKind::Quadratic => {
+                    contour.push_quadratic(segment.ctrl.from(), segment.baseline.to())
+                }
+                SegmentKind::Cubic => {
+                    for quadratic in segment.as_cubic_segment().to_quadratics(tolerance) {
+                        contour.push_quadratic(quadratic.ctrl.from(), quadratic.baseline.to());
+                    }
+                }
+            }
+        }
+
+        contour.closed = self.closed;
+        contour
+    }
+
     /// Thickens the outline by the given amount. The `orientation` parameter specifies the winding
     /// of the path (clockwise or counterclockwise) and is necessary to avoid flipped normals.
     pub fn dilate(&mut self, amount: Vector2F, orientation: Orientation) {
@@ -1108,6 +1317,38 @@ bitflags! {
     }
 }
 
+// Counts the signed crossings of the segment, which must be a line or a cubic Bézier curve, with
+// the ray pointing in the +x direction from `point`.
+fn segment_winding_number(segment: &Segment, point: Vector2F, depth: u32) -> i32 {
+    const MAX_DEPTH: u32 = 16;
+
+    let bounds = segment.hull_bounds();
+    if bounds.max_x() < point.x() || bounds.min_y() > point.y() || bounds.max_y() <= point.y() {
+        return 0;
+    }
+
+    let (from, to) = (segment.baseline.from(), segment.baseline.to());
+    let direction = if from.y() <= point.y() && to.y() > point.y() {
+        1
+    } else if to.y() <= point.y() && from.y() > point.y() {
+        -1
+    } else {
+        0
+    };
+
+    // If the curve lies entirely to the right of the point, only its endpoints matter.
+    if segment.is_line() || bounds.min_x() > point.x() || depth >= MAX_DEPTH {
+        if direction != 0 && segment.baseline.solve_x_for_y(point.y()) < point.x() {
+            return 0;
+        }
+        return direction;
+    }
+
+    let (prev, next) = segment.as_cubic_segment().split(0.5);
+    segment_winding_number(&prev, point, depth + 1)
+        + segment_winding_number(&next, point, depth + 1)
+}
+
 #[inline]
 pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
     if first {
@@ -1116,3 +1357,132 @@ pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
         *bounds = bounds.union_point(new_point)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{Contour, ContourIterFlags, Outline};
+    use crate::stroke::{LineCap, LineJoin, StrokeStyle};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn signed_area_of_circle() {
+        let mut contour = Contour::new();
+        contour.push_ellipse(&Transform2F::from_scale(10.0));
+        contour.close();
+        let area = contour.signed_area();
+        assert!(f32::abs(area - 100.0 * std::f32::consts::PI) < 0.1);
+        assert!(contour.is_clockwise());
+
+        contour.reverse();
+        assert!(f32::abs(contour.signed_area() + area) < 0.001);
+        assert!(!contour.is_clockwise());
+    }
+
+    #[test]
+    fn normalize_orientations_punches_holes() {
+        let mut outer = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)));
+        let hole = Contour::from_rect(RectF::new(vec2f(25.0, 25.0), vec2f(50.0, 50.0)));
+        outer.reverse();
+        assert!(!outer.is_clockwise() && hole.is_clockwise());
+
+        let mut outline = Outline::new();
+        outline.push_contour(outer);
+        outline.push_contour(hole);
+        outline.normalize_orientations(FillRule::EvenOdd);
+        assert!(outline.contours()[0].is_clockwise());
+        assert!(!outline.contours()[1].is_clockwise());
+
+        outline.normalize_orientations(FillRule::Winding);
+        assert!(outline.contours().iter().all(Contour::is_clockwise));
+    }
+
+    #[test]
+    fn to_quadratics_stays_within_tolerance() {
+        let (from, ctrl0, ctrl1, to) = (
+            vec2f(0.0, 0.0),
+            vec2f(10.0, 100.0),
+            vec2f(90.0, -100.0),
+            vec2f(100.0, 0.0),
+        );
+        let mut contour = Contour::new();
+        contour.push_endpoint(from);
+        contour.push_cubic(ctrl0, ctrl1, to);
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour.clone());
+
+        let tolerance = 0.25;
+        let quadratic_outline = outline.to_quadratics(tolerance);
+        let quadratic_contour = &quadratic_outline.contours()[0];
+        assert!(quadratic_contour.is_closed());
+        assert_eq!(quadratic_contour.first_position(), Some(from));
+        assert_eq!(quadratic_contour.last_position(), Some(to));
+
+        let cubic = contour
+            .iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+            .next()
+            .unwrap();
+        let quadratics: Vec<_> = quadratic_contour
+            .iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+            .collect();
+        assert!(quadratics.len() > 1);
+        assert!(quadratics.iter().all(|segment| segment.is_quadratic()));
+
+        // Each quadratic covers an equal parametric slice of the original cubic.
+        let count = quadratics.len();
+        for (index, quadratic) in quadratics.iter().enumerate() {
+            for step in 0..=8 {
+                let t = step as f32 / 8.0;
+                let cubic_t = (index as f32 + t) / count as f32;
+                let distance = (quadratic.sample(t) - cubic.sample(cubic_t)).length();
+                assert!(
+                    distance <= tolerance,
+                    "distance {} > {}",
+                    distance,
+                    tolerance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn contains_point_with_fill_rules() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        outline.push_outline(Outline::from_rect(RectF::new(
+            vec2f(2.0, 2.0),
+            vec2f(6.0, 6.0),
+        )));
+        let hole = vec2f(5.0, 5.0);
+        assert!(outline.contains_point(hole, FillRule::Winding));
+        assert!(!outline.contains_point(hole, FillRule::EvenOdd));
+        assert!(outline.contains_point(vec2f(1.0, 1.0), FillRule::EvenOdd));
+        assert!(!outline.contains_point(vec2f(11.0, 5.0), FillRule::Winding));
+    }
+
+    #[test]
+    fn stroke_contains_point_near_the_path() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle {
+            line_width: 2.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter(10.0),
+        };
+        assert!(outline.stroke_contains_point(vec2f(5.0, 0.5), style));
+        assert!(!outline.stroke_contains_point(vec2f(5.0, 1.5), style));
+        assert!(!outline.stroke_contains_point(vec2f(10.5, 0.0), style));
+
+        let style = StrokeStyle {
+            line_cap: LineCap::Square,
+            ..style
+        };
+        assert!(outline.stroke_contains_point(vec2f(10.5, 0.0), style));
+    }
+}
@@ -0,0 +1,114 @@
+// pathfinder/content/src/paint_effect.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Stylized fills, such as hatching and halftone screens, that are evaluated in the shader.
+
+use crate::util;
+use core::hash::{Hash, Hasher};
+use pathfinder_geometry::transform2d::Transform2F;
+
+/// A stylized fill, evaluated on the GPU for every pixel of a path.
+///
+/// An effect computes a coverage pattern that masks the base color of the paint, so stylized
+/// rendering doesn't need a raster pattern or a custom shader. The pattern is antialiased.
+///
+/// Effects are evaluated only inside the path they fill. Effects that spill outside the path, such
+/// as glows, are better built by blurring a render target with `PatternFilter::Blur`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PaintEffect {
+    /// The pattern to draw.
+    pub kind: PaintEffectKind,
+    /// Transform from effect space into scene space.
+    ///
+    /// Hatching lines run along the X axis of effect space, so rotate this to change their angle.
+    pub transform: Transform2F,
+}
+
+/// The patterns that a `PaintEffect` can draw. All distances are in effect space.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PaintEffectKind {
+    /// Parallel lines.
+    Hatching {
+        /// The distance between the centers of neighboring lines.
+        spacing: f32,
+        /// The thickness of each line.
+        line_width: f32,
+    },
+    /// Two sets of parallel lines, at right angles to one another.
+    CrossHatching {
+        /// The distance between the centers of neighboring lines.
+        spacing: f32,
+        /// The thickness of each line.
+        line_width: f32,
+    },
+    /// A square grid of round dots, like a printer's halftone screen.
+    Halftone {
+        /// The distance between the centers of neighboring dots.
+        cell_size: f32,
+        /// The fraction of each grid cell that its dot covers, from 0.0 to 1.0.
+        ///
+        /// Dots start to touch their neighbors above π/4.
+        coverage: f32,
+    },
+}
+
+impl Eq for PaintEffect {}
+
+impl Hash for PaintEffect {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        match self.kind {
+            PaintEffectKind::Hatching {
+                spacing,
+                line_width,
+            } => {
+                (0).hash(state);
+                util::hash_f32(spacing, state);
+                util::hash_f32(line_width, state);
+            }
+            PaintEffectKind::CrossHatching {
+                spacing,
+                line_width,
+            } => {
+                (1).hash(state);
+                util::hash_f32(spacing, state);
+                util::hash_f32(line_width, state);
+            }
+            PaintEffectKind::Halftone {
+                cell_size,
+                coverage,
+            } => {
+                (2).hash(state);
+                util::hash_f32(cell_size, state);
+                util::hash_f32(coverage, state);
+            }
+        }
+        util::hash_transform2f(self.transform, state);
+    }
+}
+
+impl PaintEffect {
+    /// Creates a new effect of the given kind, in scene space.
+    #[inline]
+    pub fn new(kind: PaintEffectKind) -> PaintEffect {
+        PaintEffect {
+            kind,
+            transform: Transform2F::default(),
+        }
+    }
+
+    /// Applies the given affine transform to this effect.
+    #[inline]
+    pub fn apply_transform(&mut self, transform: Transform2F) {
+        self.transform = transform * self.transform;
+    }
+}
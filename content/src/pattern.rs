@@ -13,13 +13,17 @@
 use crate::effects::PatternFilter;
 use crate::render_target::RenderTargetId;
 use crate::util;
+#[cfg(not(feature = "std"))]
+use crate::util::Fnv1aHasher as DefaultHasher;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::hash::{Hash, Hasher};
 use pathfinder_color::{self as color, ColorU};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{vec2i, Vector2I};
+#[cfg(feature = "std")]
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::{self, Debug, Formatter};
-use std::hash::{Hash, Hasher};
-use std::sync::Arc;
 
 #[cfg(feature = "pf-image")]
 use image::RgbaImage;
@@ -47,8 +51,22 @@ pub enum PatternSource {
         /// The device pixel size of the render target.
         size: Vector2I,
     },
+    /// A GPU texture created outside Pathfinder, such as a video frame or a camera feed.
+    ///
+    /// The texture must be registered with the renderer, which samples it directly instead of
+    /// copying it. Pixels outside the texture repeat its edge unless the pattern repeats.
+    ExternalTexture {
+        /// The ID that the renderer assigned to the texture when it was registered.
+        id: ExternalTextureId,
+        /// The pixel size of the texture.
+        size: Vector2I,
+    },
 }
 
+/// Identifies a GPU texture that was created outside Pathfinder and registered with the renderer.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ExternalTextureId(pub u32);
+
 /// A raster image, in 32-bit RGBA (8 bits per channel), non-premultiplied form.
 // FIXME(pcwalton): Hash the pixel contents so that we don't have to compare every pixel!
 // TODO(pcwalton): Should the pixels be premultiplied?
@@ -106,6 +124,14 @@ impl Pattern {
         Pattern::from_source(PatternSource::RenderTarget { id, size })
     }
 
+    /// Creates a new pattern from a GPU texture registered with the renderer.
+    ///
+    /// The transform is initialized to the identity transform. There is no filter.
+    #[inline]
+    pub fn from_external_texture(id: ExternalTextureId, size: Vector2I) -> Pattern {
+        Pattern::from_source(PatternSource::ExternalTexture { id, size })
+    }
+
     /// Returns the affine transform applied to this pattern.
     #[inline]
     pub fn transform(&self) -> Transform2F {
@@ -120,12 +146,19 @@ impl Pattern {
         self.transform = transform * self.transform;
     }
 
+    /// Replaces the affine transform applied to this pattern.
+    #[inline]
+    pub fn set_transform(&mut self, transform: Transform2F) {
+        self.transform = transform;
+    }
+
     /// Returns the underlying pixel size of this pattern, not taking transforms into account.
     #[inline]
     pub fn size(&self) -> Vector2I {
         match self.source {
             PatternSource::Image(ref image) => image.size(),
-            PatternSource::RenderTarget { size, .. } => size,
+            PatternSource::RenderTarget { size, .. }
+            | PatternSource::ExternalTexture { size, .. } => size,
         }
     }
 
@@ -229,6 +262,13 @@ impl Image {
         Image::new(vec2i(width as i32, height as i32), Arc::new(pixels))
     }
 
+    /// A convenience function to copy this image into an image buffer from the `image` crate.
+    #[cfg(feature = "pf-image")]
+    pub fn to_image_buffer(&self) -> RgbaImage {
+        let pixels = color::color_slice_to_u8_slice(&self.pixels).to_vec();
+        RgbaImage::from_raw(self.size.x() as u32, self.size.y() as u32, pixels).unwrap()
+    }
+
     /// Returns the device pixel size of the image.
     #[inline]
     pub fn size(&self) -> Vector2I {
@@ -269,7 +309,7 @@ impl PatternSource {
     pub fn is_opaque(&self) -> bool {
         match *self {
             PatternSource::Image(ref image) => image.is_opaque(),
-            PatternSource::RenderTarget { .. } => {
+            PatternSource::RenderTarget { .. } | PatternSource::ExternalTexture { .. } => {
                 // TODO(pcwalton): Maybe do something smarter here?
                 false
             }
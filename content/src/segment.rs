@@ -10,12 +10,17 @@
 
 //! Single line or Bézier curve segments, optimized with SIMD.
 
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::SQRT_2;
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::util::EPSILON;
+use pathfinder_geometry::util::{self, EPSILON};
 use pathfinder_geometry::vector::{vec2f, Vector2F};
 use pathfinder_simd::default::F32x4;
-use std::f32::consts::SQRT_2;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// A single line or Bézier curve segment, with explicit start and end points.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -248,6 +253,68 @@ impl Segment {
         }
     }
 
+    /// Returns every point at which this segment intersects `other`, as pairs of parametric t
+    /// values: the first value of each pair is the t value along this segment, and the second is
+    /// the t value along `other`.
+    ///
+    /// Pairs are sorted by their t value along this segment. Two lines are intersected exactly;
+    /// curves are recursively subdivided until their pieces are flat, and the pieces are then
+    /// intersected as lines. Portions where the segments overlap, such as identical curves or
+    /// collinear lines, are not reported, and neither are points where they meet at the ends of
+    /// such portions.
+    pub fn intersections(&self, other: &Segment) -> Vec<(f32, f32)> {
+        let mut intersections = Intersections {
+            points: vec![],
+            overlaps: vec![],
+        };
+        if self.is_none() || other.is_none() {
+            return intersections.points;
+        }
+
+        let (this, other) = (self.to_line_or_cubic(), other.to_line_or_cubic());
+        intersect_segments(&this, (0.0, 1.0), &other, (0.0, 1.0), 0, &mut intersections);
+
+        let Intersections {
+            mut points,
+            overlaps,
+        } = intersections;
+        let in_range = |value: f32, (start, end): (f32, f32)| {
+            value >= start - EPSILON && value <= end + EPSILON
+        };
+        points.retain(|&(t, u)| {
+            !overlaps
+                .iter()
+                .any(|&(a_t, b_t)| in_range(t, a_t) && in_range(u, b_t))
+        });
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points
+    }
+
+    /// Returns the bounding box of the endpoints and control points of this segment.
+    ///
+    /// This is guaranteed to contain the segment, but it may be larger than the tight bounds.
+    #[inline]
+    pub fn hull_bounds(&self) -> RectF {
+        let mut bounds = RectF::from_points(self.baseline.from(), self.baseline.from())
+            .union_point(self.baseline.to());
+        if !self.is_line() {
+            bounds = bounds.union_point(self.ctrl.from());
+            if !self.is_quadratic() {
+                bounds = bounds.union_point(self.ctrl.to());
+            }
+        }
+        bounds
+    }
+
+    #[inline]
+    pub(crate) fn to_line_or_cubic(self) -> Segment {
+        if self.is_quadratic() {
+            self.to_cubic()
+        } else {
+            self
+        }
+    }
+
     pub(crate) fn arc_length(&self) -> f32 {
         // FIXME(pcwalton)
         self.baseline.vector().length()
@@ -259,6 +326,109 @@ impl Segment {
     }
 }
 
+// The intersections found so far between two segments.
+struct Intersections {
+    // Pairs of t values at which the segments cross.
+    points: Vec<(f32, f32)>,
+    // Pairs of parametric ranges along which the segments overlap.
+    overlaps: Vec<((f32, f32), (f32, f32))>,
+}
+
+// Intersects two segments, each of which must be a line or a cubic Bézier curve. `a_t` and `b_t`
+// are the parametric ranges of the original segments that these pieces cover.
+fn intersect_segments(
+    a: &Segment,
+    a_t: (f32, f32),
+    b: &Segment,
+    b_t: (f32, f32),
+    depth: u32,
+    intersections: &mut Intersections,
+) {
+    const TOLERANCE: f32 = 0.0005;
+    const MAX_DEPTH: u32 = 48;
+
+    let a_bounds = a.hull_bounds().dilate(TOLERANCE);
+    let b_bounds = b.hull_bounds().dilate(TOLERANCE);
+    if !a_bounds.intersects(b_bounds) {
+        return;
+    }
+
+    let a_flat = a.is_line() || depth >= MAX_DEPTH || a.as_cubic_segment().is_flat(TOLERANCE);
+    let b_flat = b.is_line() || depth >= MAX_DEPTH || b.as_cubic_segment().is_flat(TOLERANCE);
+    if a_flat && b_flat {
+        // Flat pieces are within `TOLERANCE` of their chords, so pieces of overlapping curves have
+        // chords within twice that of each other.
+        if line_segments_are_collinear(a.baseline, b.baseline, TOLERANCE * 2.0) {
+            intersections.overlaps.push((a_t, b_t));
+            return;
+        }
+
+        if let Some((t, u)) = intersect_line_segments(a.baseline, b.baseline) {
+            let t = util::lerp(a_t.0, a_t.1, t);
+            let u = util::lerp(b_t.0, b_t.1, u);
+
+            // Adjacent pieces share endpoints, so the same intersection can be found twice.
+            let duplicate = intersections.points.iter().any(|&(prev_t, prev_u)| {
+                f32::abs(prev_t - t) < EPSILON && f32::abs(prev_u - u) < EPSILON
+            });
+            if !duplicate {
+                intersections.points.push((t, u));
+            }
+        }
+        return;
+    }
+
+    // Subdivide whichever curve is larger.
+    let a_area = a_bounds.width() * a_bounds.height();
+    let b_area = b_bounds.width() * b_bounds.height();
+    if !a_flat && (b_flat || a_area >= b_area) {
+        let (a_0, a_1) = a.as_cubic_segment().split(0.5);
+        let a_mid = util::lerp(a_t.0, a_t.1, 0.5);
+        intersect_segments(&a_0, (a_t.0, a_mid), b, b_t, depth + 1, intersections);
+        intersect_segments(&a_1, (a_mid, a_t.1), b, b_t, depth + 1, intersections);
+    } else {
+        let (b_0, b_1) = b.as_cubic_segment().split(0.5);
+        let b_mid = util::lerp(b_t.0, b_t.1, 0.5);
+        intersect_segments(a, a_t, &b_0, (b_t.0, b_mid), depth + 1, intersections);
+        intersect_segments(a, a_t, &b_1, (b_mid, b_t.1), depth + 1, intersections);
+    }
+}
+
+// Returns true if the endpoints of each line segment lie within `tolerance` of the line through
+// the other. Degenerate segments only have the other segment's endpoints checked against them.
+fn line_segments_are_collinear(a: LineSegment2F, b: LineSegment2F, tolerance: f32) -> bool {
+    let endpoints_near_line = |line: LineSegment2F, other: LineSegment2F| {
+        let length = line.vector().length();
+        if length < EPSILON {
+            return true;
+        }
+        [other.from(), other.to()]
+            .iter()
+            .all(|&point| f32::abs(line.vector().det(point - line.from())) <= tolerance * length)
+    };
+    endpoints_near_line(a, b) && endpoints_near_line(b, a)
+}
+
+// Returns the t values along `a` and `b` at which the two line segments cross, if they do.
+fn intersect_line_segments(a: LineSegment2F, b: LineSegment2F) -> Option<(f32, f32)> {
+    const SLOP: f32 = 0.00001;
+
+    let (a_vector, b_vector) = (a.vector(), b.vector());
+    let denominator = a_vector.det(b_vector);
+    if f32::abs(denominator) < SLOP {
+        return None;
+    }
+
+    let offset = b.from() - a.from();
+    let t = offset.det(b_vector) / denominator;
+    let u = offset.det(a_vector) / denominator;
+    let range = -SLOP..=(1.0 + SLOP);
+    if !range.contains(&t) || !range.contains(&u) {
+        return None;
+    }
+    Some((util::clamp(t, 0.0, 1.0), util::clamp(u, 0.0, 1.0)))
+}
+
 /// The type of segment this is.
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(u8)]
@@ -363,6 +533,40 @@ impl<'s> CubicSegment<'s> {
         )
     }
 
+    /// Approximates this cubic Bézier curve with a series of quadratic Bézier curves, none of which
+    /// deviates from the original curve by more than `tolerance`.
+    ///
+    /// The curve is divided into pieces of equal parametric length, and each piece is replaced
+    /// with the quadratic that shares its endpoints and the midpoint of its tangent lines. The
+    /// number of pieces is chosen from the error bound given in Timothée Groleau, "Approximating
+    /// Cubic Bezier Curves in Flash MX", 2002.
+    pub fn to_quadratics(self, tolerance: f32) -> Vec<Segment> {
+        const MIN_TOLERANCE: f32 = 0.0001;
+
+        let (p0, p3) = (self.0.baseline.from(), self.0.baseline.to());
+        let (p1, p2) = (self.0.ctrl.from(), self.0.ctrl.to());
+        let third_difference = (p3 - p2 * 3.0 + p1 * 3.0 - p0).length();
+        let error = f32::sqrt(3.0) / 36.0 * third_difference;
+        let count = f32::cbrt(error / f32::max(tolerance, MIN_TOLERANCE))
+            .ceil()
+            .max(1.0) as u32;
+
+        let mut quadratics = Vec::with_capacity(count as usize);
+        let mut remaining = *self.0;
+        for index in 0..count {
+            let (piece, rest) = remaining
+                .as_cubic_segment()
+                .split(1.0 / (count - index) as f32);
+            let ctrl = ((piece.ctrl.from() + piece.ctrl.to()) * 3.0
+                - piece.baseline.from()
+                - piece.baseline.to())
+                * 0.25;
+            quadratics.push(Segment::quadratic(piece.baseline, ctrl));
+            remaining = rest;
+        }
+        quadratics
+    }
+
     /// A convenience method equivalent to `segment.split(t).0`.
     #[inline]
     pub fn split_before(self, t: f32) -> Segment {
@@ -405,3 +609,87 @@ impl<'s> CubicSegment<'s> {
         f32::max(self.0.baseline.max_y(), self.0.ctrl.max_y())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::segment::Segment;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::vector::vec2f;
+
+    fn assert_intersections_coincide(a: &Segment, b: &Segment, expected_count: usize) {
+        let intersections = a.intersections(b);
+        assert_eq!(intersections.len(), expected_count, "{:?}", intersections);
+        for (t, u) in intersections {
+            assert!((a.sample(t) - b.sample(u)).length() < 0.01);
+        }
+    }
+
+    #[test]
+    fn line_line_intersections() {
+        let a = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let b = Segment::line(LineSegment2F::new(vec2f(0.0, 10.0), vec2f(10.0, 0.0)));
+        assert_eq!(a.intersections(&b), vec![(0.5, 0.5)]);
+
+        let parallel = Segment::line(LineSegment2F::new(vec2f(1.0, 0.0), vec2f(11.0, 10.0)));
+        assert!(a.intersections(&parallel).is_empty());
+    }
+
+    #[test]
+    fn curve_intersections() {
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 5.0), vec2f(100.0, 5.0)));
+        let cubic = Segment::cubic(
+            LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)),
+            LineSegment2F::new(vec2f(30.0, 100.0), vec2f(70.0, -100.0)),
+        );
+        assert_intersections_coincide(&line, &cubic, 2);
+
+        let quadratic = Segment::quadratic(
+            LineSegment2F::new(vec2f(0.0, 50.0), vec2f(100.0, 50.0)),
+            vec2f(50.0, -50.0),
+        );
+        assert_intersections_coincide(&quadratic, &cubic, 2);
+    }
+
+    #[test]
+    fn overlapping_intersections() {
+        let cubic = Segment::cubic(
+            LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)),
+            LineSegment2F::new(vec2f(30.0, 100.0), vec2f(70.0, -100.0)),
+        );
+        assert!(cubic.intersections(&cubic).is_empty());
+
+        let a = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let b = Segment::line(LineSegment2F::new(vec2f(5.0, 5.0), vec2f(15.0, 15.0)));
+        assert!(a.intersections(&b).is_empty());
+
+        // Part of a curve overlaps the whole curve.
+        let (first_half, _) = cubic.as_cubic_segment().split(0.5);
+        assert!(cubic.intersections(&first_half).is_empty());
+
+        // A straight curve that lies along half of a line.
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)));
+        let quadratic = Segment::quadratic(
+            LineSegment2F::new(vec2f(50.0, 0.0), vec2f(150.0, 0.0)),
+            vec2f(100.0, 0.0),
+        );
+        assert!(line.intersections(&quadratic).is_empty());
+
+        // Overlaps don't hide crossings elsewhere.
+        let crossing = Segment::line(LineSegment2F::new(vec2f(0.0, 5.0), vec2f(100.0, 5.0)));
+        assert_intersections_coincide(&cubic, &crossing, 2);
+    }
+
+    #[test]
+    fn non_finite_intersections() {
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 5.0), vec2f(100.0, 5.0)));
+        for &value in &[f32::NAN, f32::INFINITY] {
+            let cubic = Segment::cubic(
+                LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)),
+                LineSegment2F::new(vec2f(30.0, value), vec2f(70.0, -100.0)),
+            );
+            for (t, u) in line.intersections(&cubic) {
+                assert!(t.is_finite() && u.is_finite());
+            }
+        }
+    }
+}
@@ -12,12 +12,14 @@
 
 use crate::outline::{ArcDirection, Contour, ContourIterFlags, Outline, PushSegmentFlags};
 use crate::segment::Segment;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util::EPSILON;
 use pathfinder_geometry::vector::{vec2f, Vector2F};
-use std::f32;
 
 const TOLERANCE: f32 = 0.01;
 
@@ -10,11 +10,13 @@
 
 //! Miscellaneous utilities.
 
+use core::hash::{Hash, Hasher};
+use core::mem;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_simd::default::{F32x2, F32x4};
-use std::hash::{Hash, Hasher};
-use std::mem;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 pub(crate) fn hash_line_segment<H>(line_segment: LineSegment2F, state: &mut H)
 where
@@ -69,3 +71,31 @@ pub(crate) fn safe_sqrt(x: f32) -> f32 {
         x.sqrt()
     }
 }
+
+/// A 64-bit FNV-1a hasher, which stands in for the standard library's `DefaultHasher` when `std`
+/// isn't available.
+#[cfg(not(feature = "std"))]
+pub(crate) struct Fnv1aHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl Fnv1aHasher {
+    #[inline]
+    pub(crate) fn new() -> Fnv1aHasher {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for Fnv1aHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
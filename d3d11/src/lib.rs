@@ -0,0 +1,2225 @@
+// pathfinder/d3d11/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A Direct3D 11 implementation of the device abstraction, for Windows.
+//!
+//! This lets applications that already own a D3D11 device and swap chain embed Pathfinder without
+//! going through ANGLE or WGL. Shaders are loaded as HLSL from `shaders/hlsl` and compiled at
+//! runtime with `D3DCompile`.
+
+use half::f16;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceCapabilities};
+use pathfinder_gpu::{DeviceError, VertexAttrType};
+use pathfinder_gpu::{FeatureLevel, ImageBinding, Primitive, ProgramKind, RenderOptions};
+use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, TextureBinding};
+use pathfinder_gpu::{TextureData, TextureDataRef, TextureFormat, TextureSamplingFlags};
+use pathfinder_gpu::{UniformBinding, UniformData, VertexAttrClass, VertexAttrDescriptor};
+use pathfinder_resources::ResourceLoader;
+use pathfinder_simd::default::{F32x2, F32x4, I32x2};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::ops::Range;
+use std::os::raw::c_void;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+use std::time::Duration;
+use winapi::shared::dxgi::{IDXGIAdapter, IDXGIDevice, DXGI_ADAPTER_DESC};
+use winapi::shared::dxgiformat::*;
+use winapi::shared::dxgitype::DXGI_SAMPLE_DESC;
+use winapi::shared::minwindef::{BOOL, UINT};
+use winapi::shared::winerror::{HRESULT, SUCCEEDED, S_OK};
+use winapi::um::d3d11::*;
+use winapi::um::d3d11_1::ID3DUserDefinedAnnotation;
+use winapi::um::d3d11shader::D3D11_SHADER_VARIABLE_DESC;
+use winapi::um::d3d11shader::{ID3D11ShaderReflection, D3D11_SHADER_BUFFER_DESC};
+use winapi::um::d3d11shader::{D3D11_SHADER_DESC, D3D11_SHADER_INPUT_BIND_DESC};
+use winapi::um::d3dcommon::*;
+use winapi::um::d3dcompiler::{D3DCompile, D3DReflect, D3DCOMPILE_OPTIMIZATION_LEVEL3};
+use winapi::Interface;
+use wio::com::ComPtr;
+
+const DEPTH_STENCIL_FORMAT: DXGI_FORMAT = DXGI_FORMAT_D24_UNORM_S8_UINT;
+
+pub struct D3D11Device {
+    device: ComPtr<ID3D11Device>,
+    context: ComPtr<ID3D11DeviceContext>,
+    main_color_texture: D3D11Texture,
+    main_depth_stencil_view: ComPtr<ID3D11DepthStencilView>,
+    samplers: Vec<ComPtr<ID3D11SamplerState>>,
+    rasterizer_state: ComPtr<ID3D11RasterizerState>,
+    blend_states: RefCell<HashMap<BlendStateKey, ComPtr<ID3D11BlendState>>>,
+    depth_stencil_states: RefCell<HashMap<DepthStencilStateKey, ComPtr<ID3D11DepthStencilState>>>,
+    annotation: Option<ComPtr<ID3DUserDefinedAnnotation>>,
+}
+
+pub struct D3D11Program {
+    shaders: ProgramKind<D3D11Shader>,
+}
+
+pub struct D3D11Shader {
+    bytecode: Vec<u8>,
+    object: D3D11ShaderObject,
+    reflection: ShaderReflection,
+}
+
+enum D3D11ShaderObject {
+    Vertex(ComPtr<ID3D11VertexShader>),
+    Pixel(ComPtr<ID3D11PixelShader>),
+    Compute(ComPtr<ID3D11ComputeShader>),
+}
+
+pub struct D3D11Texture {
+    texture: ComPtr<ID3D11Texture2D>,
+    dxgi_format: DXGI_FORMAT,
+    size: Vector2I,
+    sampling_flags: Cell<TextureSamplingFlags>,
+    render_target_view: ComPtr<ID3D11RenderTargetView>,
+    shader_resource_view: Option<ComPtr<ID3D11ShaderResourceView>>,
+    unordered_access_view: Option<ComPtr<ID3D11UnorderedAccessView>>,
+}
+
+pub struct D3D11Framebuffer(D3D11Texture);
+
+#[derive(Clone)]
+pub struct D3D11Buffer {
+    allocation: Rc<RefCell<Option<D3D11BufferAllocation>>>,
+}
+
+#[derive(Clone)]
+struct D3D11BufferAllocation {
+    buffer: ComPtr<ID3D11Buffer>,
+    shader_resource_view: Option<ComPtr<ID3D11ShaderResourceView>>,
+    unordered_access_view: Option<ComPtr<ID3D11UnorderedAccessView>>,
+}
+
+pub struct D3D11VertexArray {
+    vertex_buffers: RefCell<Vec<D3D11Buffer>>,
+    index_buffer: RefCell<Option<D3D11Buffer>>,
+    attributes: RefCell<Vec<D3D11VertexAttrInfo>>,
+    input_layout: RefCell<Option<ComPtr<ID3D11InputLayout>>>,
+}
+
+pub struct D3D11VertexAttr {
+    semantic_index: u32,
+}
+
+pub struct D3D11Uniform {
+    name: String,
+}
+
+pub struct D3D11TextureParameter {
+    name: String,
+}
+
+pub struct D3D11ImageParameter {
+    name: String,
+}
+
+pub struct D3D11StorageBuffer {
+    binding: u32,
+}
+
+pub struct D3D11Fence(ComPtr<ID3D11Query>);
+
+pub struct D3D11TimerQuery {
+    disjoint: ComPtr<ID3D11Query>,
+    start: ComPtr<ID3D11Query>,
+    end: ComPtr<ID3D11Query>,
+}
+
+pub struct D3D11TextureDataReceiver {
+    staging_texture: ComPtr<ID3D11Texture2D>,
+    query: ComPtr<ID3D11Query>,
+    format: TextureFormat,
+    swizzle: bool,
+    size: Vector2I,
+    data: RefCell<Option<TextureData>>,
+}
+
+pub struct D3D11BufferDataReceiver {
+    staging_buffer: ComPtr<ID3D11Buffer>,
+    query: ComPtr<ID3D11Query>,
+    size: usize,
+    data: RefCell<Option<Vec<u8>>>,
+}
+
+struct D3D11VertexAttrInfo {
+    semantic_index: u32,
+    format: DXGI_FORMAT,
+    buffer_index: u32,
+    offset: u32,
+    stride: u32,
+    divisor: u32,
+}
+
+#[derive(Default)]
+struct ShaderReflection {
+    constant_buffers: Vec<ConstantBufferInfo>,
+    textures: HashMap<String, u32>,
+    samplers: HashMap<String, u32>,
+    images: HashMap<String, u32>,
+    storage_buffer_srvs: HashMap<u32, u32>,
+    storage_buffer_uavs: HashMap<u32, u32>,
+    vertex_inputs: HashMap<String, u32>,
+}
+
+struct ConstantBufferInfo {
+    slot: u32,
+    size: usize,
+    offsets: HashMap<String, usize>,
+}
+
+// The resources bound to one shader stage, indexed by slot.
+#[derive(Default)]
+struct StageBindings {
+    constant_buffers: Vec<ComPtr<ID3D11Buffer>>,
+    constant_buffer_slots: Vec<*mut ID3D11Buffer>,
+    shader_resource_views: Vec<*mut ID3D11ShaderResourceView>,
+    samplers: Vec<*mut ID3D11SamplerState>,
+    unordered_access_views: Vec<*mut ID3D11UnorderedAccessView>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct BlendStateKey {
+    blend: Option<(
+        D3D11_BLEND,
+        D3D11_BLEND,
+        D3D11_BLEND,
+        D3D11_BLEND,
+        D3D11_BLEND_OP,
+    )>,
+    color_mask: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct DepthStencilStateKey {
+    depth: Option<(D3D11_COMPARISON_FUNC, bool)>,
+    stencil: Option<(D3D11_COMPARISON_FUNC, u32, bool)>,
+}
+
+impl D3D11Device {
+    /// Creates a device that renders to the given texture by default, typically the back buffer of
+    /// a swap chain.
+    pub fn new(
+        device: ComPtr<ID3D11Device>,
+        context: ComPtr<ID3D11DeviceContext>,
+        default_texture: ComPtr<ID3D11Texture2D>,
+    ) -> D3D11Device {
+        unsafe {
+            let main_color_texture = D3D11Texture::from_texture(&device, default_texture);
+            let main_depth_stencil_view =
+                create_depth_stencil_view(&device, main_color_texture.size);
+
+            let samplers = (0..16)
+                .map(|sampling_flags_value| {
+                    let sampling_flags =
+                        TextureSamplingFlags::from_bits(sampling_flags_value).unwrap();
+                    let filter = match (
+                        sampling_flags.contains(TextureSamplingFlags::NEAREST_MIN),
+                        sampling_flags.contains(TextureSamplingFlags::NEAREST_MAG),
+                    ) {
+                        (true, true) => D3D11_FILTER_MIN_MAG_MIP_POINT,
+                        (true, false) => D3D11_FILTER_MIN_POINT_MAG_LINEAR_MIP_POINT,
+                        (false, true) => D3D11_FILTER_MIN_LINEAR_MAG_MIP_POINT,
+                        (false, false) => D3D11_FILTER_MIN_MAG_LINEAR_MIP_POINT,
+                    };
+                    let address_mode = |repeat| {
+                        if repeat {
+                            D3D11_TEXTURE_ADDRESS_WRAP
+                        } else {
+                            D3D11_TEXTURE_ADDRESS_CLAMP
+                        }
+                    };
+                    let sampler_desc = D3D11_SAMPLER_DESC {
+                        Filter: filter,
+                        AddressU: address_mode(
+                            sampling_flags.contains(TextureSamplingFlags::REPEAT_U),
+                        ),
+                        AddressV: address_mode(
+                            sampling_flags.contains(TextureSamplingFlags::REPEAT_V),
+                        ),
+                        AddressW: D3D11_TEXTURE_ADDRESS_CLAMP,
+                        MipLODBias: 0.0,
+                        MaxAnisotropy: 1,
+                        ComparisonFunc: D3D11_COMPARISON_NEVER,
+                        BorderColor: [0.0; 4],
+                        MinLOD: 0.0,
+                        MaxLOD: D3D11_FLOAT32_MAX,
+                    };
+                    create_com_object(|sampler| device.CreateSamplerState(&sampler_desc, sampler))
+                })
+                .collect();
+
+            let rasterizer_desc = D3D11_RASTERIZER_DESC {
+                FillMode: D3D11_FILL_SOLID,
+                CullMode: D3D11_CULL_NONE,
+                FrontCounterClockwise: 0,
+                DepthBias: 0,
+                DepthBiasClamp: 0.0,
+                SlopeScaledDepthBias: 0.0,
+                DepthClipEnable: 1,
+                ScissorEnable: 0,
+                MultisampleEnable: 0,
+                AntialiasedLineEnable: 0,
+            };
+            let rasterizer_state = create_com_object(|rasterizer_state| {
+                device.CreateRasterizerState(&rasterizer_desc, rasterizer_state)
+            });
+
+            // Debug events need the D3D11.1 runtime.
+            let annotation = context.cast::<ID3DUserDefinedAnnotation>().ok();
+
+            D3D11Device {
+                device,
+                context,
+                main_color_texture,
+                main_depth_stencil_view,
+                samplers,
+                rasterizer_state,
+                blend_states: RefCell::new(HashMap::new()),
+                depth_stencil_states: RefCell::new(HashMap::new()),
+                annotation,
+            }
+        }
+    }
+
+    /// Replaces the texture that the device renders to by default, returning the old one.
+    ///
+    /// Call this after resizing the swap chain.
+    pub fn swap_texture(
+        &mut self,
+        new_texture: ComPtr<ID3D11Texture2D>,
+    ) -> ComPtr<ID3D11Texture2D> {
+        unsafe {
+            let new_texture = D3D11Texture::from_texture(&self.device, new_texture);
+            if new_texture.size != self.main_color_texture.size {
+                self.main_depth_stencil_view =
+                    create_depth_stencil_view(&self.device, new_texture.size);
+            }
+            mem::replace(&mut self.main_color_texture, new_texture).texture
+        }
+    }
+
+    #[inline]
+    pub fn d3d11_device(&self) -> ComPtr<ID3D11Device> {
+        self.device.clone()
+    }
+
+    #[inline]
+    pub fn d3d11_device_context(&self) -> ComPtr<ID3D11DeviceContext> {
+        self.context.clone()
+    }
+
+    fn render_target_color_texture<'a>(
+        &'a self,
+        target: &RenderTarget<'a, D3D11Device>,
+    ) -> &'a D3D11Texture {
+        match *target {
+            RenderTarget::Default => &self.main_color_texture,
+            RenderTarget::Framebuffer(framebuffer) => &framebuffer.0,
+        }
+    }
+
+    unsafe fn create_query(&self, query_type: D3D11_QUERY) -> ComPtr<ID3D11Query> {
+        let query_desc = D3D11_QUERY_DESC {
+            Query: query_type,
+            MiscFlags: 0,
+        };
+        create_com_object(|query| self.device.CreateQuery(&query_desc, query))
+    }
+
+    // Returns true if the query has finished, without blocking.
+    unsafe fn query_finished(&self, query: &ComPtr<ID3D11Query>) -> bool {
+        self.context.GetData(
+            query.as_raw() as *mut ID3D11Asynchronous,
+            ptr::null_mut(),
+            0,
+            D3D11_ASYNC_GETDATA_DONOTFLUSH,
+        ) == S_OK
+    }
+
+    unsafe fn query_timestamp(&self, query: &ComPtr<ID3D11Query>) -> Option<u64> {
+        let mut timestamp = 0u64;
+        if self.context.GetData(
+            query.as_raw() as *mut ID3D11Asynchronous,
+            &mut timestamp as *mut u64 as *mut c_void,
+            mem::size_of::<u64>() as UINT,
+            D3D11_ASYNC_GETDATA_DONOTFLUSH,
+        ) == S_OK
+        {
+            Some(timestamp)
+        } else {
+            None
+        }
+    }
+
+    unsafe fn wait_for_query(&self, query: &ComPtr<ID3D11Query>) {
+        self.context.Flush();
+        while !self.query_finished(query) {}
+    }
+
+    unsafe fn blend_state(&self, options: &RenderOptions) -> ComPtr<ID3D11BlendState> {
+        let key = BlendStateKey {
+            blend: options.blend.map(|blend| {
+                (
+                    blend.src_rgb_factor.to_d3d11_blend(),
+                    blend.dest_rgb_factor.to_d3d11_blend(),
+                    blend.src_alpha_factor.to_d3d11_alpha_blend(),
+                    blend.dest_alpha_factor.to_d3d11_alpha_blend(),
+                    blend.op.to_d3d11_blend_op(),
+                )
+            }),
+            color_mask: options.color_mask,
+        };
+        if let Some(blend_state) = self.blend_states.borrow().get(&key) {
+            return blend_state.clone();
+        }
+
+        let mut render_target_blend_desc = D3D11_RENDER_TARGET_BLEND_DESC {
+            BlendEnable: 0,
+            SrcBlend: D3D11_BLEND_ONE,
+            DestBlend: D3D11_BLEND_ZERO,
+            BlendOp: D3D11_BLEND_OP_ADD,
+            SrcBlendAlpha: D3D11_BLEND_ONE,
+            DestBlendAlpha: D3D11_BLEND_ZERO,
+            BlendOpAlpha: D3D11_BLEND_OP_ADD,
+            RenderTargetWriteMask: if key.color_mask {
+                D3D11_COLOR_WRITE_ENABLE_ALL as u8
+            } else {
+                0
+            },
+        };
+        if let Some((src_rgb, dest_rgb, src_alpha, dest_alpha, op)) = key.blend {
+            render_target_blend_desc.BlendEnable = 1;
+            render_target_blend_desc.SrcBlend = src_rgb;
+            render_target_blend_desc.DestBlend = dest_rgb;
+            render_target_blend_desc.BlendOp = op;
+            render_target_blend_desc.SrcBlendAlpha = src_alpha;
+            render_target_blend_desc.DestBlendAlpha = dest_alpha;
+            render_target_blend_desc.BlendOpAlpha = op;
+        }
+        let blend_desc = D3D11_BLEND_DESC {
+            AlphaToCoverageEnable: 0,
+            IndependentBlendEnable: 0,
+            RenderTarget: [render_target_blend_desc; 8],
+        };
+
+        let blend_state =
+            create_com_object(|blend_state| self.device.CreateBlendState(&blend_desc, blend_state));
+        self.blend_states
+            .borrow_mut()
+            .insert(key, blend_state.clone());
+        blend_state
+    }
+
+    unsafe fn depth_stencil_state(
+        &self,
+        options: &RenderOptions,
+    ) -> ComPtr<ID3D11DepthStencilState> {
+        let key = DepthStencilStateKey {
+            depth: options
+                .depth
+                .map(|depth| (depth.func.to_d3d11_comparison_func(), depth.write)),
+            stencil: options.stencil.map(|stencil| {
+                let func = stencil.func.to_d3d11_comparison_func();
+                (func, stencil.mask, stencil.write)
+            }),
+        };
+        if let Some(depth_stencil_state) = self.depth_stencil_states.borrow().get(&key) {
+            return depth_stencil_state.clone();
+        }
+
+        let (depth_func, depth_write) = key.depth.unwrap_or((D3D11_COMPARISON_ALWAYS, false));
+        let (stencil_func, stencil_mask, stencil_write) =
+            key.stencil.unwrap_or((D3D11_COMPARISON_ALWAYS, 0, false));
+        let stencil_op_desc = D3D11_DEPTH_STENCILOP_DESC {
+            StencilFailOp: D3D11_STENCIL_OP_KEEP,
+            StencilDepthFailOp: D3D11_STENCIL_OP_KEEP,
+            StencilPassOp: if stencil_write {
+                D3D11_STENCIL_OP_REPLACE
+            } else {
+                D3D11_STENCIL_OP_KEEP
+            },
+            StencilFunc: stencil_func,
+        };
+        let depth_stencil_desc = D3D11_DEPTH_STENCIL_DESC {
+            DepthEnable: key.depth.is_some() as BOOL,
+            DepthWriteMask: if depth_write {
+                D3D11_DEPTH_WRITE_MASK_ALL
+            } else {
+                D3D11_DEPTH_WRITE_MASK_ZERO
+            },
+            DepthFunc: depth_func,
+            StencilEnable: key.stencil.is_some() as BOOL,
+            StencilReadMask: stencil_mask as u8,
+            StencilWriteMask: if stencil_write { stencil_mask as u8 } else { 0 },
+            FrontFace: stencil_op_desc,
+            BackFace: stencil_op_desc,
+        };
+
+        let depth_stencil_state = create_com_object(|depth_stencil_state| {
+            self.device
+                .CreateDepthStencilState(&depth_stencil_desc, depth_stencil_state)
+        });
+        self.depth_stencil_states
+            .borrow_mut()
+            .insert(key, depth_stencil_state.clone());
+        depth_stencil_state
+    }
+
+    unsafe fn input_layout(
+        &self,
+        vertex_array: &D3D11VertexArray,
+        vertex_shader: &D3D11Shader,
+    ) -> Option<ComPtr<ID3D11InputLayout>> {
+        if let Some(ref input_layout) = *vertex_array.input_layout.borrow() {
+            return Some(input_layout.clone());
+        }
+
+        let attributes = vertex_array.attributes.borrow();
+        if attributes.is_empty() {
+            return None;
+        }
+
+        let semantic_name = b"TEXCOORD\0";
+        let input_element_descs: Vec<_> = attributes
+            .iter()
+            .map(|attribute| D3D11_INPUT_ELEMENT_DESC {
+                SemanticName: semantic_name.as_ptr() as *const i8,
+                SemanticIndex: attribute.semantic_index,
+                Format: attribute.format,
+                InputSlot: attribute.buffer_index,
+                AlignedByteOffset: attribute.offset,
+                InputSlotClass: if attribute.divisor == 0 {
+                    D3D11_INPUT_PER_VERTEX_DATA
+                } else {
+                    D3D11_INPUT_PER_INSTANCE_DATA
+                },
+                InstanceDataStepRate: attribute.divisor,
+            })
+            .collect();
+
+        let input_layout = create_com_object(|input_layout| {
+            self.device.CreateInputLayout(
+                input_element_descs.as_ptr(),
+                input_element_descs.len() as UINT,
+                vertex_shader.bytecode.as_ptr() as *const c_void,
+                vertex_shader.bytecode.len(),
+                input_layout,
+            )
+        });
+        *vertex_array.input_layout.borrow_mut() = Some(input_layout.clone());
+        Some(input_layout)
+    }
+
+    unsafe fn create_stage_bindings(
+        &self,
+        shader: &D3D11Shader,
+        uniforms: &[UniformBinding<D3D11Uniform>],
+        textures: &[TextureBinding<D3D11TextureParameter, D3D11Texture>],
+        images: &[ImageBinding<D3D11ImageParameter, D3D11Texture>],
+        storage_buffers: &[(&D3D11StorageBuffer, &D3D11Buffer)],
+    ) -> StageBindings {
+        let reflection = &shader.reflection;
+        let mut bindings = StageBindings::default();
+
+        for constant_buffer_info in &reflection.constant_buffers {
+            let constant_buffer = self.create_constant_buffer(constant_buffer_info, uniforms);
+            set_slot(
+                &mut bindings.constant_buffer_slots,
+                constant_buffer_info.slot,
+                constant_buffer.as_raw(),
+            );
+            bindings.constant_buffers.push(constant_buffer);
+        }
+
+        for &(texture_parameter, texture) in textures {
+            let slot = match reflection.textures.get(&texture_parameter.name) {
+                None => continue,
+                Some(&slot) => slot,
+            };
+            let shader_resource_view = texture
+                .shader_resource_view
+                .as_ref()
+                .expect("Texture can't be sampled!");
+            set_slot(
+                &mut bindings.shader_resource_views,
+                slot,
+                shader_resource_view.as_raw(),
+            );
+            if let Some(&sampler_slot) = reflection.samplers.get(&texture_parameter.name) {
+                let sampler = &self.samplers[texture.sampling_flags.get().bits() as usize];
+                set_slot(&mut bindings.samplers, sampler_slot, sampler.as_raw());
+            }
+        }
+
+        for &(image_parameter, texture, _) in images {
+            let slot = match reflection.images.get(&image_parameter.name) {
+                None => continue,
+                Some(&slot) => slot,
+            };
+            let unordered_access_view = texture
+                .unordered_access_view
+                .as_ref()
+                .expect("Texture can't be used as an image!");
+            set_slot(
+                &mut bindings.unordered_access_views,
+                slot,
+                unordered_access_view.as_raw(),
+            );
+        }
+
+        for &(storage_buffer, buffer) in storage_buffers {
+            let allocation = buffer.allocation.borrow();
+            let allocation = allocation.as_ref().expect("Buffer hasn't been allocated!");
+            if let Some(&slot) = reflection.storage_buffer_srvs.get(&storage_buffer.binding) {
+                let shader_resource_view = allocation
+                    .shader_resource_view
+                    .as_ref()
+                    .expect("Buffer isn't a storage buffer!");
+                set_slot(
+                    &mut bindings.shader_resource_views,
+                    slot,
+                    shader_resource_view.as_raw(),
+                );
+            }
+            if let Some(&slot) = reflection.storage_buffer_uavs.get(&storage_buffer.binding) {
+                let unordered_access_view = allocation
+                    .unordered_access_view
+                    .as_ref()
+                    .expect("Buffer isn't a storage buffer!");
+                set_slot(
+                    &mut bindings.unordered_access_views,
+                    slot,
+                    unordered_access_view.as_raw(),
+                );
+            }
+        }
+
+        bindings
+    }
+
+    unsafe fn create_constant_buffer(
+        &self,
+        constant_buffer_info: &ConstantBufferInfo,
+        uniforms: &[UniformBinding<D3D11Uniform>],
+    ) -> ComPtr<ID3D11Buffer> {
+        // Constant buffers must be a multiple of 16 bytes long.
+        let mut data = vec![0u8; (constant_buffer_info.size + 15) & !15];
+        for (uniform, uniform_data) in uniforms {
+            let offset = match constant_buffer_info.offsets.get(&uniform.name) {
+                None => continue,
+                Some(&offset) => offset,
+            };
+            let bytes = uniform_data.as_bytes();
+            match *uniform_data {
+                UniformData::Mat2(_) => {
+                    // Each column of a matrix starts a new 16-byte register.
+                    data[offset..(offset + 8)].copy_from_slice(&bytes[0..8]);
+                    data[(offset + 16)..(offset + 24)].copy_from_slice(&bytes[8..16]);
+                }
+                _ => data[offset..(offset + bytes.len())].copy_from_slice(bytes),
+            }
+        }
+
+        let buffer_desc = D3D11_BUFFER_DESC {
+            ByteWidth: data.len() as UINT,
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_CONSTANT_BUFFER,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+            StructureByteStride: 0,
+        };
+        let initial_data = D3D11_SUBRESOURCE_DATA {
+            pSysMem: data.as_ptr() as *const c_void,
+            SysMemPitch: 0,
+            SysMemSlicePitch: 0,
+        };
+        create_com_object(|buffer| {
+            self.device
+                .CreateBuffer(&buffer_desc, &initial_data, buffer)
+        })
+    }
+
+    unsafe fn set_stage_bindings(&self, kind: ShaderKind, bindings: &StageBindings) {
+        let context = &self.context;
+        let constant_buffers = &bindings.constant_buffer_slots;
+        let shader_resource_views = &bindings.shader_resource_views;
+        let samplers = &bindings.samplers;
+        match kind {
+            ShaderKind::Vertex => {
+                context.VSSetConstantBuffers(
+                    0,
+                    constant_buffers.len() as UINT,
+                    constant_buffers.as_ptr(),
+                );
+                context.VSSetShaderResources(
+                    0,
+                    shader_resource_views.len() as UINT,
+                    shader_resource_views.as_ptr(),
+                );
+                context.VSSetSamplers(0, samplers.len() as UINT, samplers.as_ptr());
+            }
+            ShaderKind::Fragment => {
+                context.PSSetConstantBuffers(
+                    0,
+                    constant_buffers.len() as UINT,
+                    constant_buffers.as_ptr(),
+                );
+                context.PSSetShaderResources(
+                    0,
+                    shader_resource_views.len() as UINT,
+                    shader_resource_views.as_ptr(),
+                );
+                context.PSSetSamplers(0, samplers.len() as UINT, samplers.as_ptr());
+            }
+            ShaderKind::Compute => {
+                context.CSSetConstantBuffers(
+                    0,
+                    constant_buffers.len() as UINT,
+                    constant_buffers.as_ptr(),
+                );
+                context.CSSetShaderResources(
+                    0,
+                    shader_resource_views.len() as UINT,
+                    shader_resource_views.as_ptr(),
+                );
+                context.CSSetSamplers(0, samplers.len() as UINT, samplers.as_ptr());
+                context.CSSetUnorderedAccessViews(
+                    0,
+                    bindings.unordered_access_views.len() as UINT,
+                    bindings.unordered_access_views.as_ptr(),
+                    ptr::null(),
+                );
+            }
+        }
+    }
+
+    // Unbinds views after use so that the same resources can be bound for output next time.
+    unsafe fn clear_stage_bindings(&self, kind: ShaderKind, bindings: &StageBindings) {
+        let null_shader_resource_views =
+            vec![ptr::null_mut(); bindings.shader_resource_views.len()];
+        let null_unordered_access_views =
+            vec![ptr::null_mut(); bindings.unordered_access_views.len()];
+        match kind {
+            ShaderKind::Vertex => self.context.VSSetShaderResources(
+                0,
+                null_shader_resource_views.len() as UINT,
+                null_shader_resource_views.as_ptr(),
+            ),
+            ShaderKind::Fragment => self.context.PSSetShaderResources(
+                0,
+                null_shader_resource_views.len() as UINT,
+                null_shader_resource_views.as_ptr(),
+            ),
+            ShaderKind::Compute => {
+                self.context.CSSetShaderResources(
+                    0,
+                    null_shader_resource_views.len() as UINT,
+                    null_shader_resource_views.as_ptr(),
+                );
+                self.context.CSSetUnorderedAccessViews(
+                    0,
+                    null_unordered_access_views.len() as UINT,
+                    null_unordered_access_views.as_ptr(),
+                    ptr::null(),
+                );
+            }
+        }
+    }
+
+    unsafe fn set_render_target(&self, render_state: &RenderState<D3D11Device>) {
+        let color_texture = self.render_target_color_texture(render_state.target);
+        let render_target_view = color_texture.render_target_view.as_raw();
+        let depth_stencil_view = match *render_state.target {
+            RenderTarget::Default => self.main_depth_stencil_view.as_raw(),
+            RenderTarget::Framebuffer(_) => ptr::null_mut(),
+        };
+        self.context
+            .OMSetRenderTargets(1, &render_target_view, depth_stencil_view);
+
+        let clear_ops = &render_state.options.clear_ops;
+        if let Some(color) = clear_ops.color {
+            let color = [color.r(), color.g(), color.b(), color.a()];
+            self.context
+                .ClearRenderTargetView(render_target_view, &color);
+        }
+        if !depth_stencil_view.is_null() {
+            let mut clear_flags = 0;
+            if clear_ops.depth.is_some() {
+                clear_flags |= D3D11_CLEAR_DEPTH;
+            }
+            if clear_ops.stencil.is_some() {
+                clear_flags |= D3D11_CLEAR_STENCIL;
+            }
+            if clear_flags != 0 {
+                self.context.ClearDepthStencilView(
+                    depth_stencil_view,
+                    clear_flags,
+                    clear_ops.depth.unwrap_or(1.0),
+                    clear_ops.stencil.unwrap_or(0),
+                );
+            }
+        }
+
+        let viewport = render_state.viewport;
+        let d3d11_viewport = D3D11_VIEWPORT {
+            TopLeftX: viewport.origin().x() as f32,
+            TopLeftY: viewport.origin().y() as f32,
+            Width: viewport.width() as f32,
+            Height: viewport.height() as f32,
+            MinDepth: 0.0,
+            MaxDepth: 1.0,
+        };
+        self.context.RSSetViewports(1, &d3d11_viewport);
+    }
+
+    fn draw<F>(&self, render_state: &RenderState<D3D11Device>, draw: F)
+    where
+        F: FnOnce(&ID3D11DeviceContext),
+    {
+        let (vertex_shader, fragment_shader) = match render_state.program.shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => (vertex, fragment),
+            ProgramKind::Compute(_) => panic!("Can't draw with a compute program!"),
+        };
+
+        unsafe {
+            self.set_render_target(render_state);
+
+            let options = &render_state.options;
+            self.context.RSSetState(self.rasterizer_state.as_raw());
+            self.context
+                .OMSetBlendState(self.blend_state(options).as_raw(), &[1.0; 4], !0);
+            let stencil_reference = options.stencil.map_or(0, |stencil| stencil.reference);
+            self.context.OMSetDepthStencilState(
+                self.depth_stencil_state(options).as_raw(),
+                stencil_reference,
+            );
+
+            // Set up the input assembler.
+            let vertex_array = render_state.vertex_array;
+            let input_layout = self.input_layout(vertex_array, vertex_shader);
+            self.context.IASetInputLayout(
+                input_layout
+                    .as_ref()
+                    .map_or(ptr::null_mut(), |input_layout| input_layout.as_raw()),
+            );
+            let vertex_buffers: Vec<_> = vertex_array
+                .vertex_buffers
+                .borrow()
+                .iter()
+                .map(|buffer| buffer.allocated().buffer)
+                .collect();
+            let vertex_buffer_ptrs: Vec<_> = vertex_buffers
+                .iter()
+                .map(|buffer| buffer.as_raw())
+                .collect();
+            let strides = vertex_array.strides();
+            let offsets = vec![0; vertex_buffers.len()];
+            self.context.IASetVertexBuffers(
+                0,
+                vertex_buffer_ptrs.len() as UINT,
+                vertex_buffer_ptrs.as_ptr(),
+                strides.as_ptr(),
+                offsets.as_ptr(),
+            );
+            if let Some(ref index_buffer) = *vertex_array.index_buffer.borrow() {
+                self.context.IASetIndexBuffer(
+                    index_buffer.allocated().buffer.as_raw(),
+                    DXGI_FORMAT_R32_UINT,
+                    0,
+                );
+            }
+            self.context
+                .IASetPrimitiveTopology(render_state.primitive.to_d3d11_primitive_topology());
+
+            // Set up the shaders.
+            if let D3D11ShaderObject::Vertex(ref object) = vertex_shader.object {
+                self.context.VSSetShader(object.as_raw(), ptr::null(), 0);
+            }
+            if let D3D11ShaderObject::Pixel(ref object) = fragment_shader.object {
+                self.context.PSSetShader(object.as_raw(), ptr::null(), 0);
+            }
+            let vertex_bindings = self.create_stage_bindings(
+                vertex_shader,
+                render_state.uniforms,
+                render_state.textures,
+                render_state.images,
+                render_state.storage_buffers,
+            );
+            let fragment_bindings = self.create_stage_bindings(
+                fragment_shader,
+                render_state.uniforms,
+                render_state.textures,
+                render_state.images,
+                render_state.storage_buffers,
+            );
+            self.set_stage_bindings(ShaderKind::Vertex, &vertex_bindings);
+            self.set_stage_bindings(ShaderKind::Fragment, &fragment_bindings);
+
+            draw(&self.context);
+
+            self.clear_stage_bindings(ShaderKind::Vertex, &vertex_bindings);
+            self.clear_stage_bindings(ShaderKind::Fragment, &fragment_bindings);
+        }
+    }
+}
+
+impl Device for D3D11Device {
+    type Buffer = D3D11Buffer;
+    type BufferDataReceiver = D3D11BufferDataReceiver;
+    type Fence = D3D11Fence;
+    type Framebuffer = D3D11Framebuffer;
+    type ImageParameter = D3D11ImageParameter;
+    type Program = D3D11Program;
+    type Shader = D3D11Shader;
+    type StorageBuffer = D3D11StorageBuffer;
+    type Texture = D3D11Texture;
+    type TextureDataReceiver = D3D11TextureDataReceiver;
+    type TextureParameter = D3D11TextureParameter;
+    type TimerQuery = D3D11TimerQuery;
+    type Uniform = D3D11Uniform;
+    type VertexArray = D3D11VertexArray;
+    type VertexAttr = D3D11VertexAttr;
+
+    #[inline]
+    fn backend_name(&self) -> &'static str {
+        "Direct3D 11"
+    }
+
+    fn device_name(&self) -> String {
+        unsafe {
+            let dxgi_device = match self.device.cast::<IDXGIDevice>() {
+                Ok(dxgi_device) => dxgi_device,
+                Err(_) => return String::new(),
+            };
+            let adapter = create_com_object(|adapter: *mut *mut IDXGIAdapter| {
+                dxgi_device.GetAdapter(adapter)
+            });
+            let mut adapter_desc: DXGI_ADAPTER_DESC = mem::zeroed();
+            check(adapter.GetDesc(&mut adapter_desc));
+            let description = &adapter_desc.Description;
+            let length = description
+                .iter()
+                .position(|&character| character == 0)
+                .unwrap_or(description.len());
+            String::from_utf16_lossy(&description[0..length])
+        }
+    }
+
+    fn feature_level(&self) -> FeatureLevel {
+        unsafe {
+            if self.device.GetFeatureLevel() >= D3D_FEATURE_LEVEL_11_0 {
+                FeatureLevel::D3D11
+            } else {
+                FeatureLevel::D3D10
+            }
+        }
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        unsafe {
+            let feature_level_11 = self.device.GetFeatureLevel() >= D3D_FEATURE_LEVEL_11_0;
+            let max_msaa_samples = [8, 4, 2]
+                .iter()
+                .cloned()
+                .find(|&sample_count| {
+                    let mut quality_levels = 0;
+                    let result = self.device.CheckMultisampleQualityLevels(
+                        DXGI_FORMAT_R8G8B8A8_UNORM,
+                        sample_count,
+                        &mut quality_levels,
+                    );
+                    SUCCEEDED(result) && quality_levels > 0
+                })
+                .unwrap_or(1);
+            DeviceCapabilities {
+                max_texture_size: if feature_level_11 {
+                    D3D11_REQ_TEXTURE2D_U_OR_V_DIMENSION
+                } else {
+                    // The Direct3D 10 limit.
+                    8192
+                },
+                compute_shaders: feature_level_11,
+                dual_source_blending: true,
+                max_msaa_samples,
+            }
+        }
+    }
+
+    fn push_debug_group(&self, name: &str) {
+        if let Some(ref annotation) = self.annotation {
+            let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+            unsafe {
+                annotation.BeginEvent(name.as_ptr());
+            }
+        }
+    }
+
+    fn pop_debug_group(&self) {
+        if let Some(ref annotation) = self.annotation {
+            unsafe {
+                annotation.EndEvent();
+            }
+        }
+    }
+
+    fn set_texture_label(&self, texture: &D3D11Texture, label: &str) {
+        unsafe {
+            set_debug_object_name(&texture.texture, label);
+        }
+    }
+
+    fn set_buffer_label(&self, buffer: &D3D11Buffer, label: &str) {
+        if let Some(ref allocation) = *buffer.allocation.borrow() {
+            unsafe {
+                set_debug_object_name(&allocation.buffer, label);
+            }
+        }
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> D3D11Texture {
+        let mut bind_flags = D3D11_BIND_SHADER_RESOURCE | D3D11_BIND_RENDER_TARGET;
+        match format {
+            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => {
+                bind_flags |= D3D11_BIND_UNORDERED_ACCESS
+            }
+            TextureFormat::R8 | TextureFormat::R16F => {}
+        }
+
+        let texture_desc = D3D11_TEXTURE2D_DESC {
+            Width: size.x() as UINT,
+            Height: size.y() as UINT,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: format.to_dxgi_format(),
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: bind_flags,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        unsafe {
+            let texture = create_com_object(|texture| {
+                self.device
+                    .CreateTexture2D(&texture_desc, ptr::null(), texture)
+            });
+            D3D11Texture::from_texture(&self.device, texture)
+        }
+    }
+
+    fn create_texture_from_data(
+        &self,
+        format: TextureFormat,
+        size: Vector2I,
+        data: TextureDataRef,
+    ) -> D3D11Texture {
+        let texture = self.create_texture(format, size);
+        self.upload_to_texture(&texture, RectI::new(Vector2I::default(), size), data);
+        texture
+    }
+
+    fn create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> D3D11Shader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<D3D11Shader, DeviceError> {
+        let suffix = match kind {
+            ShaderKind::Vertex => 'v',
+            ShaderKind::Fragment => 'f',
+            ShaderKind::Compute => 'c',
+        };
+        let path = format!("shaders/hlsl/{}.{}s.hlsl", name, suffix);
+        match resources.slurp(&path) {
+            Ok(source) => Ok(self.create_shader_from_source(name, &source, kind)),
+            Err(error) => Err(DeviceError::ResourceLoad { path, error }),
+        }
+    }
+
+    fn create_shader_from_source(
+        &self,
+        name: &str,
+        source: &[u8],
+        kind: ShaderKind,
+    ) -> D3D11Shader {
+        let target = match kind {
+            ShaderKind::Vertex => "vs_5_0\0",
+            ShaderKind::Fragment => "ps_5_0\0",
+            ShaderKind::Compute => "cs_5_0\0",
+        };
+        let source_name = CString::new(name).unwrap();
+
+        unsafe {
+            let (mut code, mut errors) = (ptr::null_mut(), ptr::null_mut());
+            let result = D3DCompile(
+                source.as_ptr() as *const c_void,
+                source.len(),
+                source_name.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                b"main\0".as_ptr() as *const i8,
+                target.as_ptr() as *const i8,
+                D3DCOMPILE_OPTIMIZATION_LEVEL3,
+                0,
+                &mut code,
+                &mut errors,
+            );
+            if !SUCCEEDED(result) {
+                let message = if errors.is_null() {
+                    String::new()
+                } else {
+                    let errors = ComPtr::<ID3DBlob>::from_raw(errors);
+                    let errors = slice::from_raw_parts(
+                        errors.GetBufferPointer() as *const u8,
+                        errors.GetBufferSize(),
+                    );
+                    String::from_utf8_lossy(errors).into_owned()
+                };
+                panic!(
+                    "{:?} shader '{}' compilation failed:\n{}",
+                    kind, name, message
+                );
+            }
+            if !errors.is_null() {
+                ComPtr::<ID3DBlob>::from_raw(errors);
+            }
+
+            let code = ComPtr::<ID3DBlob>::from_raw(code);
+            let bytecode =
+                slice::from_raw_parts(code.GetBufferPointer() as *const u8, code.GetBufferSize())
+                    .to_vec();
+
+            let bytecode_ptr = bytecode.as_ptr() as *const c_void;
+            let object = match kind {
+                ShaderKind::Vertex => D3D11ShaderObject::Vertex(create_com_object(|object| {
+                    self.device.CreateVertexShader(
+                        bytecode_ptr,
+                        bytecode.len(),
+                        ptr::null_mut(),
+                        object,
+                    )
+                })),
+                ShaderKind::Fragment => D3D11ShaderObject::Pixel(create_com_object(|object| {
+                    self.device.CreatePixelShader(
+                        bytecode_ptr,
+                        bytecode.len(),
+                        ptr::null_mut(),
+                        object,
+                    )
+                })),
+                ShaderKind::Compute => D3D11ShaderObject::Compute(create_com_object(|object| {
+                    self.device.CreateComputeShader(
+                        bytecode_ptr,
+                        bytecode.len(),
+                        ptr::null_mut(),
+                        object,
+                    )
+                })),
+            };
+
+            let mut reflection = reflect_shader(&bytecode);
+            if kind == ShaderKind::Vertex {
+                reflection.vertex_inputs = parse_vertex_inputs(&String::from_utf8_lossy(source));
+            }
+
+            D3D11Shader {
+                bytecode,
+                object,
+                reflection,
+            }
+        }
+    }
+
+    fn create_vertex_array(&self) -> D3D11VertexArray {
+        D3D11VertexArray {
+            vertex_buffers: RefCell::new(vec![]),
+            index_buffer: RefCell::new(None),
+            attributes: RefCell::new(vec![]),
+            input_layout: RefCell::new(None),
+        }
+    }
+
+    fn bind_buffer(
+        &self,
+        vertex_array: &D3D11VertexArray,
+        buffer: &D3D11Buffer,
+        target: BufferTarget,
+    ) {
+        match target {
+            BufferTarget::Vertex => vertex_array
+                .vertex_buffers
+                .borrow_mut()
+                .push((*buffer).clone()),
+            BufferTarget::Index => {
+                *vertex_array.index_buffer.borrow_mut() = Some((*buffer).clone())
+            }
+            _ => panic!("Buffers bound to vertex arrays must be vertex or index buffers!"),
+        }
+    }
+
+    fn create_program_from_shaders(
+        &self,
+        _: &dyn ResourceLoader,
+        _: &str,
+        shaders: ProgramKind<D3D11Shader>,
+    ) -> D3D11Program {
+        D3D11Program { shaders }
+    }
+
+    // The local size is baked into the shader, so there's nothing to do here.
+    fn set_compute_program_local_size(&self, _: &mut D3D11Program, _: ComputeDimensions) {}
+
+    fn get_vertex_attr(&self, program: &D3D11Program, name: &str) -> Option<D3D11VertexAttr> {
+        let vertex_shader = match program.shaders {
+            ProgramKind::Raster { ref vertex, .. } => vertex,
+            ProgramKind::Compute(_) => unreachable!(),
+        };
+        vertex_shader
+            .reflection
+            .vertex_inputs
+            .get(&format!("a{}", name))
+            .map(|&semantic_index| D3D11VertexAttr { semantic_index })
+    }
+
+    fn get_uniform(&self, _: &D3D11Program, name: &str) -> D3D11Uniform {
+        D3D11Uniform {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_texture_parameter(&self, _: &D3D11Program, name: &str) -> D3D11TextureParameter {
+        D3D11TextureParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_image_parameter(&self, _: &D3D11Program, name: &str) -> D3D11ImageParameter {
+        D3D11ImageParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_storage_buffer(&self, _: &D3D11Program, _: &str, binding: u32) -> D3D11StorageBuffer {
+        D3D11StorageBuffer { binding }
+    }
+
+    fn configure_vertex_attr(
+        &self,
+        vertex_array: &D3D11VertexArray,
+        attr: &D3D11VertexAttr,
+        descriptor: &VertexAttrDescriptor,
+    ) {
+        debug_assert_ne!(descriptor.stride, 0);
+
+        let format = match (descriptor.class, descriptor.attr_type, descriptor.size) {
+            (VertexAttrClass::Int, VertexAttrType::I8, 2) => DXGI_FORMAT_R8G8_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I8, 4) => DXGI_FORMAT_R8G8B8A8_SINT,
+            (VertexAttrClass::Int, VertexAttrType::U8, 1) => DXGI_FORMAT_R8_UINT,
+            (VertexAttrClass::Int, VertexAttrType::U8, 2) => DXGI_FORMAT_R8G8_UINT,
+            (VertexAttrClass::Int, VertexAttrType::U8, 4) => DXGI_FORMAT_R8G8B8A8_UINT,
+            (VertexAttrClass::Int, VertexAttrType::I16, 1) => DXGI_FORMAT_R16_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I16, 2) => DXGI_FORMAT_R16G16_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I16, 4) => DXGI_FORMAT_R16G16B16A16_SINT,
+            (VertexAttrClass::Int, VertexAttrType::U16, 1) => DXGI_FORMAT_R16_UINT,
+            (VertexAttrClass::Int, VertexAttrType::U16, 2) => DXGI_FORMAT_R16G16_UINT,
+            (VertexAttrClass::Int, VertexAttrType::U16, 4) => DXGI_FORMAT_R16G16B16A16_UINT,
+            (VertexAttrClass::Int, VertexAttrType::I32, 1) => DXGI_FORMAT_R32_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I32, 2) => DXGI_FORMAT_R32G32_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I32, 3) => DXGI_FORMAT_R32G32B32_SINT,
+            (VertexAttrClass::Int, VertexAttrType::I32, 4) => DXGI_FORMAT_R32G32B32A32_SINT,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 2) => DXGI_FORMAT_R8G8_UNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 4) => DXGI_FORMAT_R8G8B8A8_UNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 2) => DXGI_FORMAT_R8G8_SNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 4) => DXGI_FORMAT_R8G8B8A8_SNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 2) => DXGI_FORMAT_R16G16_UNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 4) => DXGI_FORMAT_R16G16B16A16_UNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 2) => DXGI_FORMAT_R16G16_SNORM,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 4) => DXGI_FORMAT_R16G16B16A16_SNORM,
+            (VertexAttrClass::Float, VertexAttrType::F32, 1) => DXGI_FORMAT_R32_FLOAT,
+            (VertexAttrClass::Float, VertexAttrType::F32, 2) => DXGI_FORMAT_R32G32_FLOAT,
+            (VertexAttrClass::Float, VertexAttrType::F32, 3) => DXGI_FORMAT_R32G32B32_FLOAT,
+            (VertexAttrClass::Float, VertexAttrType::F32, 4) => DXGI_FORMAT_R32G32B32A32_FLOAT,
+            _ => panic!("Unsupported vertex class/type/size combination!"),
+        };
+
+        let mut attributes = vertex_array.attributes.borrow_mut();
+        attributes.retain(|info| info.semantic_index != attr.semantic_index);
+        attributes.push(D3D11VertexAttrInfo {
+            semantic_index: attr.semantic_index,
+            format,
+            buffer_index: descriptor.buffer_index,
+            offset: descriptor.offset as u32,
+            stride: descriptor.stride as u32,
+            divisor: descriptor.divisor,
+        });
+        *vertex_array.input_layout.borrow_mut() = None;
+    }
+
+    fn create_framebuffer(&self, texture: D3D11Texture) -> D3D11Framebuffer {
+        D3D11Framebuffer(texture)
+    }
+
+    fn create_buffer(&self, _: BufferUploadMode) -> D3D11Buffer {
+        D3D11Buffer {
+            allocation: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn allocate_buffer<T>(&self, buffer: &D3D11Buffer, data: BufferData<T>, target: BufferTarget) {
+        let byte_size = match data {
+            BufferData::Uninitialized(size) => size * mem::size_of::<T>(),
+            BufferData::Memory(slice) => slice.len() * mem::size_of::<T>(),
+        };
+        // Raw views address the buffer in 4-byte words.
+        let byte_size = ((byte_size.max(1) + 3) & !3) as UINT;
+
+        let (bind_flags, misc_flags) = match target {
+            BufferTarget::Vertex => (D3D11_BIND_VERTEX_BUFFER, 0),
+            BufferTarget::Index => (D3D11_BIND_INDEX_BUFFER, 0),
+            BufferTarget::Storage => (
+                D3D11_BIND_SHADER_RESOURCE | D3D11_BIND_UNORDERED_ACCESS,
+                D3D11_RESOURCE_MISC_BUFFER_ALLOW_RAW_VIEWS,
+            ),
+        };
+        let buffer_desc = D3D11_BUFFER_DESC {
+            ByteWidth: byte_size,
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: bind_flags,
+            CPUAccessFlags: 0,
+            MiscFlags: misc_flags,
+            StructureByteStride: 0,
+        };
+
+        unsafe {
+            let d3d11_buffer = create_com_object(|d3d11_buffer| {
+                self.device
+                    .CreateBuffer(&buffer_desc, ptr::null(), d3d11_buffer)
+            });
+
+            let (mut shader_resource_view, mut unordered_access_view) = (None, None);
+            if let BufferTarget::Storage = target {
+                let element_count = byte_size / 4;
+
+                let mut shader_resource_view_desc: D3D11_SHADER_RESOURCE_VIEW_DESC = mem::zeroed();
+                shader_resource_view_desc.Format = DXGI_FORMAT_R32_TYPELESS;
+                shader_resource_view_desc.ViewDimension = D3D11_SRV_DIMENSION_BUFFEREX;
+                *shader_resource_view_desc.u.BufferEx_mut() = D3D11_BUFFEREX_SRV {
+                    FirstElement: 0,
+                    NumElements: element_count,
+                    Flags: D3D11_BUFFEREX_SRV_FLAG_RAW,
+                };
+                shader_resource_view = Some(create_com_object(|view| {
+                    self.device.CreateShaderResourceView(
+                        d3d11_buffer.as_raw() as *mut ID3D11Resource,
+                        &shader_resource_view_desc,
+                        view,
+                    )
+                }));
+
+                let mut unordered_access_view_desc: D3D11_UNORDERED_ACCESS_VIEW_DESC =
+                    mem::zeroed();
+                unordered_access_view_desc.Format = DXGI_FORMAT_R32_TYPELESS;
+                unordered_access_view_desc.ViewDimension = D3D11_UAV_DIMENSION_BUFFER;
+                *unordered_access_view_desc.u.Buffer_mut() = D3D11_BUFFER_UAV {
+                    FirstElement: 0,
+                    NumElements: element_count,
+                    Flags: D3D11_BUFFER_UAV_FLAG_RAW,
+                };
+                unordered_access_view = Some(create_com_object(|view| {
+                    self.device.CreateUnorderedAccessView(
+                        d3d11_buffer.as_raw() as *mut ID3D11Resource,
+                        &unordered_access_view_desc,
+                        view,
+                    )
+                }));
+            }
+
+            *buffer.allocation.borrow_mut() = Some(D3D11BufferAllocation {
+                buffer: d3d11_buffer,
+                shader_resource_view,
+                unordered_access_view,
+            });
+        }
+
+        if let BufferData::Memory(slice) = data {
+            self.upload_to_buffer(buffer, 0, slice, target);
+        }
+    }
+
+    fn upload_to_buffer<T>(
+        &self,
+        buffer: &D3D11Buffer,
+        position: usize,
+        data: &[T],
+        _: BufferTarget,
+    ) {
+        let byte_size = data.len() * mem::size_of::<T>();
+        if byte_size == 0 {
+            return;
+        }
+
+        let dest_box = D3D11_BOX {
+            left: position as UINT,
+            top: 0,
+            front: 0,
+            right: (position + byte_size) as UINT,
+            bottom: 1,
+            back: 1,
+        };
+        unsafe {
+            self.context.UpdateSubresource(
+                buffer.allocated().buffer.as_raw() as *mut ID3D11Resource,
+                0,
+                &dest_box,
+                data.as_ptr() as *const c_void,
+                0,
+                0,
+            );
+        }
+    }
+
+    #[inline]
+    fn framebuffer_texture<'f>(&self, framebuffer: &'f D3D11Framebuffer) -> &'f D3D11Texture {
+        &framebuffer.0
+    }
+
+    #[inline]
+    fn destroy_framebuffer(&self, framebuffer: D3D11Framebuffer) -> D3D11Texture {
+        framebuffer.0
+    }
+
+    fn texture_format(&self, texture: &D3D11Texture) -> TextureFormat {
+        TextureFormat::from_dxgi_format(texture.dxgi_format).expect("Unexpected texture format!")
+    }
+
+    #[inline]
+    fn texture_size(&self, texture: &D3D11Texture) -> Vector2I {
+        texture.size
+    }
+
+    #[inline]
+    fn set_texture_sampling_mode(&self, texture: &D3D11Texture, flags: TextureSamplingFlags) {
+        texture.sampling_flags.set(flags);
+    }
+
+    fn upload_to_texture(&self, texture: &D3D11Texture, rect: RectI, data: TextureDataRef) {
+        let format = self.texture_format(texture);
+        let data_ptr = data.check_and_extract_data_ptr(rect.size(), format);
+        let dest_box = D3D11_BOX {
+            left: rect.min_x() as UINT,
+            top: rect.min_y() as UINT,
+            front: 0,
+            right: rect.max_x() as UINT,
+            bottom: rect.max_y() as UINT,
+            back: 1,
+        };
+        unsafe {
+            self.context.UpdateSubresource(
+                texture.texture.as_raw() as *mut ID3D11Resource,
+                0,
+                &dest_box,
+                data_ptr,
+                (rect.width() as usize * format.bytes_per_pixel()) as UINT,
+                0,
+            );
+        }
+    }
+
+    fn read_pixels(
+        &self,
+        target: &RenderTarget<D3D11Device>,
+        viewport: RectI,
+    ) -> D3D11TextureDataReceiver {
+        let texture = self.render_target_color_texture(target);
+        let format = TextureFormat::from_dxgi_format(texture.dxgi_format)
+            .expect("Unexpected texture format!");
+
+        let staging_texture_desc = D3D11_TEXTURE2D_DESC {
+            Width: viewport.width() as UINT,
+            Height: viewport.height() as UINT,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: texture.dxgi_format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_STAGING,
+            BindFlags: 0,
+            CPUAccessFlags: D3D11_CPU_ACCESS_READ,
+            MiscFlags: 0,
+        };
+        let source_box = D3D11_BOX {
+            left: viewport.min_x() as UINT,
+            top: viewport.min_y() as UINT,
+            front: 0,
+            right: viewport.max_x() as UINT,
+            bottom: viewport.max_y() as UINT,
+            back: 1,
+        };
+
+        unsafe {
+            let staging_texture = create_com_object(|staging_texture| {
+                self.device
+                    .CreateTexture2D(&staging_texture_desc, ptr::null(), staging_texture)
+            });
+            self.context.CopySubresourceRegion(
+                staging_texture.as_raw() as *mut ID3D11Resource,
+                0,
+                0,
+                0,
+                0,
+                texture.texture.as_raw() as *mut ID3D11Resource,
+                0,
+                &source_box,
+            );
+
+            let query = self.create_query(D3D11_QUERY_EVENT);
+            self.context.End(query.as_raw() as *mut ID3D11Asynchronous);
+            self.context.Flush();
+
+            D3D11TextureDataReceiver {
+                staging_texture,
+                query,
+                format,
+                swizzle: match texture.dxgi_format {
+                    DXGI_FORMAT_B8G8R8A8_UNORM | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => true,
+                    _ => false,
+                },
+                size: viewport.size(),
+                data: RefCell::new(None),
+            }
+        }
+    }
+
+    fn read_buffer(
+        &self,
+        buffer: &D3D11Buffer,
+        _: BufferTarget,
+        range: Range<usize>,
+    ) -> D3D11BufferDataReceiver {
+        let size = range.end - range.start;
+        let staging_buffer_desc = D3D11_BUFFER_DESC {
+            ByteWidth: size.max(1) as UINT,
+            Usage: D3D11_USAGE_STAGING,
+            BindFlags: 0,
+            CPUAccessFlags: D3D11_CPU_ACCESS_READ,
+            MiscFlags: 0,
+            StructureByteStride: 0,
+        };
+        let source_box = D3D11_BOX {
+            left: range.start as UINT,
+            top: 0,
+            front: 0,
+            right: range.end as UINT,
+            bottom: 1,
+            back: 1,
+        };
+
+        unsafe {
+            let staging_buffer = create_com_object(|staging_buffer| {
+                self.device
+                    .CreateBuffer(&staging_buffer_desc, ptr::null(), staging_buffer)
+            });
+            self.context.CopySubresourceRegion(
+                staging_buffer.as_raw() as *mut ID3D11Resource,
+                0,
+                0,
+                0,
+                0,
+                buffer.allocated().buffer.as_raw() as *mut ID3D11Resource,
+                0,
+                &source_box,
+            );
+
+            let query = self.create_query(D3D11_QUERY_EVENT);
+            self.context.End(query.as_raw() as *mut ID3D11Asynchronous);
+            self.context.Flush();
+
+            D3D11BufferDataReceiver {
+                staging_buffer,
+                query,
+                size,
+                data: RefCell::new(None),
+            }
+        }
+    }
+
+    #[inline]
+    fn begin_commands(&self) {
+        // The immediate context records commands as they're issued.
+    }
+
+    fn end_commands(&self) {
+        unsafe {
+            self.context.Flush();
+        }
+    }
+
+    fn draw_arrays(&self, index_count: u32, render_state: &RenderState<D3D11Device>) {
+        self.draw(render_state, |context| unsafe {
+            context.Draw(index_count, 0)
+        });
+    }
+
+    fn draw_elements(&self, index_count: u32, render_state: &RenderState<D3D11Device>) {
+        self.draw(render_state, |context| unsafe {
+            context.DrawIndexed(index_count, 0, 0)
+        });
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        render_state: &RenderState<D3D11Device>,
+    ) {
+        self.draw(render_state, |context| unsafe {
+            context.DrawIndexedInstanced(index_count, instance_count, 0, 0, 0)
+        });
+    }
+
+    fn dispatch_compute(
+        &self,
+        dimensions: ComputeDimensions,
+        compute_state: &ComputeState<D3D11Device>,
+    ) {
+        let compute_shader = match compute_state.program.shaders {
+            ProgramKind::Compute(ref compute_shader) => compute_shader,
+            ProgramKind::Raster { .. } => panic!("Can't dispatch a raster program!"),
+        };
+
+        unsafe {
+            if let D3D11ShaderObject::Compute(ref object) = compute_shader.object {
+                self.context.CSSetShader(object.as_raw(), ptr::null(), 0);
+            }
+            let bindings = self.create_stage_bindings(
+                compute_shader,
+                compute_state.uniforms,
+                compute_state.textures,
+                compute_state.images,
+                compute_state.storage_buffers,
+            );
+            self.set_stage_bindings(ShaderKind::Compute, &bindings);
+            self.context
+                .Dispatch(dimensions.x, dimensions.y, dimensions.z);
+            self.clear_stage_bindings(ShaderKind::Compute, &bindings);
+        }
+    }
+
+    fn add_fence(&self) -> D3D11Fence {
+        unsafe {
+            let query = self.create_query(D3D11_QUERY_EVENT);
+            self.context.End(query.as_raw() as *mut ID3D11Asynchronous);
+            D3D11Fence(query)
+        }
+    }
+
+    fn wait_for_fence(&self, fence: &D3D11Fence) {
+        unsafe { self.wait_for_query(&fence.0) }
+    }
+
+    fn create_timer_query(&self) -> D3D11TimerQuery {
+        unsafe {
+            D3D11TimerQuery {
+                disjoint: self.create_query(D3D11_QUERY_TIMESTAMP_DISJOINT),
+                start: self.create_query(D3D11_QUERY_TIMESTAMP),
+                end: self.create_query(D3D11_QUERY_TIMESTAMP),
+            }
+        }
+    }
+
+    fn begin_timer_query(&self, query: &D3D11TimerQuery) {
+        unsafe {
+            self.context
+                .Begin(query.disjoint.as_raw() as *mut ID3D11Asynchronous);
+            self.context
+                .End(query.start.as_raw() as *mut ID3D11Asynchronous);
+        }
+    }
+
+    fn end_timer_query(&self, query: &D3D11TimerQuery) {
+        unsafe {
+            self.context
+                .End(query.end.as_raw() as *mut ID3D11Asynchronous);
+            self.context
+                .End(query.disjoint.as_raw() as *mut ID3D11Asynchronous);
+        }
+    }
+
+    fn try_recv_timer_query(&self, query: &D3D11TimerQuery) -> Option<Duration> {
+        unsafe {
+            let mut disjoint_data: D3D11_QUERY_DATA_TIMESTAMP_DISJOINT = mem::zeroed();
+            if self.context.GetData(
+                query.disjoint.as_raw() as *mut ID3D11Asynchronous,
+                &mut disjoint_data as *mut D3D11_QUERY_DATA_TIMESTAMP_DISJOINT as *mut c_void,
+                mem::size_of::<D3D11_QUERY_DATA_TIMESTAMP_DISJOINT>() as UINT,
+                D3D11_ASYNC_GETDATA_DONOTFLUSH,
+            ) != S_OK
+            {
+                return None;
+            }
+
+            let start_time = self.query_timestamp(&query.start)?;
+            let end_time = self.query_timestamp(&query.end)?;
+
+            // The timestamps are meaningless if the GPU clock changed frequency mid-query.
+            if disjoint_data.Disjoint != 0 || end_time < start_time {
+                return Some(Duration::default());
+            }
+            let nanos =
+                (end_time - start_time) as u128 * 1_000_000_000 / disjoint_data.Frequency as u128;
+            Some(Duration::from_nanos(nanos as u64))
+        }
+    }
+
+    fn recv_timer_query(&self, query: &D3D11TimerQuery) -> Duration {
+        unsafe {
+            self.wait_for_query(&query.disjoint);
+        }
+        loop {
+            if let Some(duration) = self.try_recv_timer_query(query) {
+                return duration;
+            }
+        }
+    }
+
+    fn try_recv_texture_data(&self, receiver: &D3D11TextureDataReceiver) -> Option<TextureData> {
+        unsafe {
+            if receiver.data.borrow().is_none() && !self.query_finished(&receiver.query) {
+                return None;
+            }
+            Some(self.download_texture_data(receiver))
+        }
+    }
+
+    fn recv_texture_data(&self, receiver: &D3D11TextureDataReceiver) -> TextureData {
+        unsafe {
+            self.wait_for_query(&receiver.query);
+            self.download_texture_data(receiver)
+        }
+    }
+
+    fn try_recv_buffer(&self, receiver: &D3D11BufferDataReceiver) -> Option<Vec<u8>> {
+        unsafe {
+            if receiver.data.borrow().is_none() && !self.query_finished(&receiver.query) {
+                return None;
+            }
+            Some(self.download_buffer_data(receiver))
+        }
+    }
+
+    fn recv_buffer(&self, receiver: &D3D11BufferDataReceiver) -> Vec<u8> {
+        unsafe {
+            self.wait_for_query(&receiver.query);
+            self.download_buffer_data(receiver)
+        }
+    }
+}
+
+impl D3D11Device {
+    unsafe fn download_texture_data(&self, receiver: &D3D11TextureDataReceiver) -> TextureData {
+        if let Some(ref data) = *receiver.data.borrow() {
+            return (*data).clone();
+        }
+
+        let resource = receiver.staging_texture.as_raw() as *mut ID3D11Resource;
+        let mut mapped_subresource: D3D11_MAPPED_SUBRESOURCE = mem::zeroed();
+        check(
+            self.context
+                .Map(resource, 0, D3D11_MAP_READ, 0, &mut mapped_subresource),
+        );
+
+        let row_size = receiver.size.x() as usize * receiver.format.bytes_per_pixel();
+        let mut bytes = Vec::with_capacity(row_size * receiver.size.y() as usize);
+        for row in 0..(receiver.size.y() as usize) {
+            let row_start = (mapped_subresource.pData as *const u8)
+                .add(row * mapped_subresource.RowPitch as usize);
+            bytes.extend_from_slice(slice::from_raw_parts(row_start, row_size));
+        }
+        self.context.Unmap(resource, 0);
+
+        if receiver.swizzle {
+            for pixel in bytes.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let data = match receiver.format {
+            TextureFormat::R8 | TextureFormat::RGBA8 => TextureData::U8(bytes),
+            TextureFormat::R16F | TextureFormat::RGBA16F => TextureData::F16(
+                bytes
+                    .chunks(2)
+                    .map(|half| f16::from_bits(u16::from_ne_bytes([half[0], half[1]])))
+                    .collect(),
+            ),
+            TextureFormat::RGBA32F => TextureData::F32(
+                bytes
+                    .chunks(4)
+                    .map(|float| f32::from_ne_bytes([float[0], float[1], float[2], float[3]]))
+                    .collect(),
+            ),
+        };
+        *receiver.data.borrow_mut() = Some(data.clone());
+        data
+    }
+
+    unsafe fn download_buffer_data(&self, receiver: &D3D11BufferDataReceiver) -> Vec<u8> {
+        if let Some(ref data) = *receiver.data.borrow() {
+            return (*data).clone();
+        }
+
+        let resource = receiver.staging_buffer.as_raw() as *mut ID3D11Resource;
+        let mut mapped_subresource: D3D11_MAPPED_SUBRESOURCE = mem::zeroed();
+        check(
+            self.context
+                .Map(resource, 0, D3D11_MAP_READ, 0, &mut mapped_subresource),
+        );
+        let data =
+            slice::from_raw_parts(mapped_subresource.pData as *const u8, receiver.size).to_vec();
+        self.context.Unmap(resource, 0);
+
+        *receiver.data.borrow_mut() = Some(data.clone());
+        data
+    }
+}
+
+impl D3D11Texture {
+    unsafe fn from_texture(
+        device: &ComPtr<ID3D11Device>,
+        texture: ComPtr<ID3D11Texture2D>,
+    ) -> D3D11Texture {
+        let mut texture_desc: D3D11_TEXTURE2D_DESC = mem::zeroed();
+        texture.GetDesc(&mut texture_desc);
+
+        let resource = texture.as_raw() as *mut ID3D11Resource;
+        let render_target_view =
+            create_com_object(|view| device.CreateRenderTargetView(resource, ptr::null(), view));
+        let shader_resource_view = if texture_desc.BindFlags & D3D11_BIND_SHADER_RESOURCE != 0 {
+            Some(create_com_object(|view| {
+                device.CreateShaderResourceView(resource, ptr::null(), view)
+            }))
+        } else {
+            None
+        };
+        let unordered_access_view = if texture_desc.BindFlags & D3D11_BIND_UNORDERED_ACCESS != 0 {
+            Some(create_com_object(|view| {
+                device.CreateUnorderedAccessView(resource, ptr::null(), view)
+            }))
+        } else {
+            None
+        };
+
+        D3D11Texture {
+            texture,
+            dxgi_format: texture_desc.Format,
+            size: vec2i(texture_desc.Width as i32, texture_desc.Height as i32),
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+            render_target_view,
+            shader_resource_view,
+            unordered_access_view,
+        }
+    }
+
+    #[inline]
+    pub fn d3d11_texture(&self) -> ComPtr<ID3D11Texture2D> {
+        self.texture.clone()
+    }
+}
+
+impl D3D11Buffer {
+    fn allocated(&self) -> D3D11BufferAllocation {
+        (*self.allocation.borrow())
+            .clone()
+            .expect("Buffer hasn't been allocated!")
+    }
+}
+
+impl D3D11VertexArray {
+    // The stride of each bound vertex buffer.
+    fn strides(&self) -> Vec<UINT> {
+        let attributes = self.attributes.borrow();
+        (0..self.vertex_buffers.borrow().len() as u32)
+            .map(|buffer_index| {
+                attributes
+                    .iter()
+                    .find(|info| info.buffer_index == buffer_index)
+                    .map_or(0, |info| info.stride)
+            })
+            .collect()
+    }
+}
+
+unsafe fn create_depth_stencil_view(
+    device: &ComPtr<ID3D11Device>,
+    size: Vector2I,
+) -> ComPtr<ID3D11DepthStencilView> {
+    let texture_desc = D3D11_TEXTURE2D_DESC {
+        Width: size.x() as UINT,
+        Height: size.y() as UINT,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DEPTH_STENCIL_FORMAT,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D11_USAGE_DEFAULT,
+        BindFlags: D3D11_BIND_DEPTH_STENCIL,
+        CPUAccessFlags: 0,
+        MiscFlags: 0,
+    };
+    let texture: ComPtr<ID3D11Texture2D> =
+        create_com_object(|texture| device.CreateTexture2D(&texture_desc, ptr::null(), texture));
+    create_com_object(|view| {
+        device.CreateDepthStencilView(texture.as_raw() as *mut ID3D11Resource, ptr::null(), view)
+    })
+}
+
+// Reflects the constant buffers and resources of a compiled shader.
+//
+// SPIRV-Cross prefixes the members of the flattened uniform block with `_<id>_` and names the
+// sampler for a texture `_<texture>_sampler`; both are stripped off here.
+unsafe fn reflect_shader(bytecode: &[u8]) -> ShaderReflection {
+    let mut shader_reflection = ptr::null_mut();
+    check(D3DReflect(
+        bytecode.as_ptr() as *const c_void,
+        bytecode.len(),
+        &ID3D11ShaderReflection::uuidof(),
+        &mut shader_reflection,
+    ));
+    let shader_reflection = ComPtr::<ID3D11ShaderReflection>::from_raw(shader_reflection as *mut _);
+
+    let mut shader_desc: D3D11_SHADER_DESC = mem::zeroed();
+    check(shader_reflection.GetDesc(&mut shader_desc));
+
+    let mut reflection = ShaderReflection::default();
+    for resource_index in 0..shader_desc.BoundResources {
+        let mut bind_desc: D3D11_SHADER_INPUT_BIND_DESC = mem::zeroed();
+        check(shader_reflection.GetResourceBindingDesc(resource_index, &mut bind_desc));
+        let name = CStr::from_ptr(bind_desc.Name)
+            .to_string_lossy()
+            .into_owned();
+        let slot = bind_desc.BindPoint;
+
+        match bind_desc.Type {
+            D3D_SIT_CBUFFER => {
+                let constant_buffer = shader_reflection.GetConstantBufferByName(bind_desc.Name);
+                let mut buffer_desc: D3D11_SHADER_BUFFER_DESC = mem::zeroed();
+                check((*constant_buffer).GetDesc(&mut buffer_desc));
+
+                let mut offsets = HashMap::new();
+                for variable_index in 0..buffer_desc.Variables {
+                    let variable = (*constant_buffer).GetVariableByIndex(variable_index);
+                    let mut variable_desc: D3D11_SHADER_VARIABLE_DESC = mem::zeroed();
+                    check((*variable).GetDesc(&mut variable_desc));
+                    let variable_name = CStr::from_ptr(variable_desc.Name).to_string_lossy();
+                    offsets.insert(
+                        strip_spirv_cross_prefix(&variable_name).to_owned(),
+                        variable_desc.StartOffset as usize,
+                    );
+                }
+
+                reflection.constant_buffers.push(ConstantBufferInfo {
+                    slot,
+                    size: buffer_desc.Size as usize,
+                    offsets,
+                });
+            }
+            D3D_SIT_TEXTURE => {
+                reflection.textures.insert(name, slot);
+            }
+            D3D_SIT_SAMPLER => {
+                let texture_name = name
+                    .strip_prefix('_')
+                    .and_then(|name| name.strip_suffix("_sampler"))
+                    .unwrap_or(&name)
+                    .to_owned();
+                reflection.samplers.insert(texture_name, slot);
+            }
+            D3D_SIT_UAV_RWTYPED => {
+                reflection.images.insert(name, slot);
+            }
+            // Storage buffers keep the register number of their GLSL binding, so they're matched
+            // up by that instead of by name.
+            D3D_SIT_BYTEADDRESS => {
+                reflection.storage_buffer_srvs.insert(slot, slot);
+            }
+            D3D_SIT_UAV_RWBYTEADDRESS => {
+                reflection.storage_buffer_uavs.insert(slot, slot);
+            }
+            _ => panic!("Unsupported shader resource `{}`!", name),
+        }
+    }
+
+    reflection
+}
+
+// Reads the semantic of each vertex input from the `SPIRV_Cross_Input` struct.
+//
+// The input signature in the bytecode only records semantics, so the names have to come from the
+// source.
+fn parse_vertex_inputs(source: &str) -> HashMap<String, u32> {
+    let mut vertex_inputs = HashMap::new();
+    let mut lines = source
+        .lines()
+        .skip_while(|line| line.trim() != "struct SPIRV_Cross_Input");
+    for line in lines.by_ref().skip(1) {
+        let line = line.trim();
+        if line.starts_with('}') {
+            break;
+        }
+        let mut parts = line.trim_end_matches(';').split(':');
+        let (declaration, semantic) = match (parts.next(), parts.next()) {
+            (Some(declaration), Some(semantic)) => (declaration, semantic.trim()),
+            _ => continue,
+        };
+        let name = match declaration.split_whitespace().last() {
+            Some(name) => name,
+            None => continue,
+        };
+        if let Some(Ok(semantic_index)) =
+            semantic.strip_prefix("TEXCOORD").map(|index| index.parse())
+        {
+            vertex_inputs.insert(name.to_owned(), semantic_index);
+        }
+    }
+    vertex_inputs
+}
+
+fn strip_spirv_cross_prefix(name: &str) -> &str {
+    if let Some(rest) = name.strip_prefix('_') {
+        if let Some(index) = rest.find('_') {
+            if index > 0 && rest[0..index].bytes().all(|byte| byte.is_ascii_digit()) {
+                return &rest[(index + 1)..];
+            }
+        }
+    }
+    name
+}
+
+fn set_slot<T>(slots: &mut Vec<*mut T>, slot: u32, value: *mut T) {
+    let slot = slot as usize;
+    if slots.len() <= slot {
+        slots.resize(slot + 1, ptr::null_mut());
+    }
+    slots[slot] = value;
+}
+
+unsafe fn create_com_object<T, F>(create: F) -> ComPtr<T>
+where
+    T: Interface,
+    F: FnOnce(*mut *mut T) -> HRESULT,
+{
+    let mut object = ptr::null_mut();
+    check(create(&mut object));
+    ComPtr::from_raw(object)
+}
+
+unsafe fn set_debug_object_name(object: &ID3D11DeviceChild, name: &str) {
+    object.SetPrivateData(
+        &WKPDID_D3DDebugObjectName,
+        name.len() as UINT,
+        name.as_ptr() as *const c_void,
+    );
+}
+
+#[inline]
+fn check(result: HRESULT) {
+    if !SUCCEEDED(result) {
+        panic!("Direct3D call failed: 0x{:08x}", result as u32);
+    }
+}
+
+// Extension traits
+
+trait BlendFactorExt {
+    fn to_d3d11_blend(self) -> D3D11_BLEND;
+    fn to_d3d11_alpha_blend(self) -> D3D11_BLEND;
+}
+
+impl BlendFactorExt for BlendFactor {
+    #[inline]
+    fn to_d3d11_blend(self) -> D3D11_BLEND {
+        match self {
+            BlendFactor::Zero => D3D11_BLEND_ZERO,
+            BlendFactor::One => D3D11_BLEND_ONE,
+            BlendFactor::SrcAlpha => D3D11_BLEND_SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => D3D11_BLEND_INV_SRC_ALPHA,
+            BlendFactor::DestAlpha => D3D11_BLEND_DEST_ALPHA,
+            BlendFactor::OneMinusDestAlpha => D3D11_BLEND_INV_DEST_ALPHA,
+            BlendFactor::DestColor => D3D11_BLEND_DEST_COLOR,
+            BlendFactor::OneMinusSrcColor => D3D11_BLEND_INV_SRC_COLOR,
+            BlendFactor::OneMinusSrc1Color => D3D11_BLEND_INV_SRC1_COLOR,
+        }
+    }
+
+    // Direct3D doesn't allow color factors in the alpha blend, so use their alpha equivalents.
+    #[inline]
+    fn to_d3d11_alpha_blend(self) -> D3D11_BLEND {
+        match self {
+            BlendFactor::DestColor => D3D11_BLEND_DEST_ALPHA,
+            BlendFactor::OneMinusSrcColor => D3D11_BLEND_INV_SRC_ALPHA,
+            BlendFactor::OneMinusSrc1Color => D3D11_BLEND_INV_SRC1_ALPHA,
+            _ => self.to_d3d11_blend(),
+        }
+    }
+}
+
+trait BlendOpExt {
+    fn to_d3d11_blend_op(self) -> D3D11_BLEND_OP;
+}
+
+impl BlendOpExt for BlendOp {
+    #[inline]
+    fn to_d3d11_blend_op(self) -> D3D11_BLEND_OP {
+        match self {
+            BlendOp::Add => D3D11_BLEND_OP_ADD,
+            BlendOp::Subtract => D3D11_BLEND_OP_SUBTRACT,
+            BlendOp::ReverseSubtract => D3D11_BLEND_OP_REV_SUBTRACT,
+            BlendOp::Min => D3D11_BLEND_OP_MIN,
+            BlendOp::Max => D3D11_BLEND_OP_MAX,
+        }
+    }
+}
+
+trait ComparisonFuncExt {
+    fn to_d3d11_comparison_func(self) -> D3D11_COMPARISON_FUNC;
+}
+
+impl ComparisonFuncExt for DepthFunc {
+    #[inline]
+    fn to_d3d11_comparison_func(self) -> D3D11_COMPARISON_FUNC {
+        match self {
+            DepthFunc::Less => D3D11_COMPARISON_LESS,
+            DepthFunc::LessEqual => D3D11_COMPARISON_LESS_EQUAL,
+            DepthFunc::Always => D3D11_COMPARISON_ALWAYS,
+        }
+    }
+}
+
+impl ComparisonFuncExt for StencilFunc {
+    #[inline]
+    fn to_d3d11_comparison_func(self) -> D3D11_COMPARISON_FUNC {
+        match self {
+            StencilFunc::Always => D3D11_COMPARISON_ALWAYS,
+            StencilFunc::Equal => D3D11_COMPARISON_EQUAL,
+        }
+    }
+}
+
+trait PrimitiveExt {
+    fn to_d3d11_primitive_topology(self) -> D3D11_PRIMITIVE_TOPOLOGY;
+}
+
+impl PrimitiveExt for Primitive {
+    #[inline]
+    fn to_d3d11_primitive_topology(self) -> D3D11_PRIMITIVE_TOPOLOGY {
+        match self {
+            Primitive::Triangles => D3D11_PRIMITIVE_TOPOLOGY_TRIANGLELIST,
+            Primitive::Lines => D3D11_PRIMITIVE_TOPOLOGY_LINELIST,
+        }
+    }
+}
+
+trait TextureFormatExt: Sized {
+    fn to_dxgi_format(self) -> DXGI_FORMAT;
+    fn from_dxgi_format(dxgi_format: DXGI_FORMAT) -> Option<Self>;
+}
+
+impl TextureFormatExt for TextureFormat {
+    #[inline]
+    fn to_dxgi_format(self) -> DXGI_FORMAT {
+        match self {
+            TextureFormat::R8 => DXGI_FORMAT_R8_UNORM,
+            TextureFormat::R16F => DXGI_FORMAT_R16_FLOAT,
+            TextureFormat::RGBA8 => DXGI_FORMAT_R8G8B8A8_UNORM,
+            TextureFormat::RGBA16F => DXGI_FORMAT_R16G16B16A16_FLOAT,
+            TextureFormat::RGBA32F => DXGI_FORMAT_R32G32B32A32_FLOAT,
+        }
+    }
+
+    fn from_dxgi_format(dxgi_format: DXGI_FORMAT) -> Option<TextureFormat> {
+        match dxgi_format {
+            DXGI_FORMAT_R8_UNORM => Some(TextureFormat::R8),
+            DXGI_FORMAT_R16_FLOAT => Some(TextureFormat::R16F),
+            DXGI_FORMAT_R8G8B8A8_UNORM
+            | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+            | DXGI_FORMAT_B8G8R8A8_UNORM
+            | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => Some(TextureFormat::RGBA8),
+            DXGI_FORMAT_R16G16B16A16_FLOAT => Some(TextureFormat::RGBA16F),
+            DXGI_FORMAT_R32G32B32A32_FLOAT => Some(TextureFormat::RGBA32F),
+            _ => None,
+        }
+    }
+}
+
+trait UniformDataExt {
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl UniformDataExt for UniformData {
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            match *self {
+                UniformData::Float(ref data) => {
+                    slice::from_raw_parts(data as *const f32 as *const u8, 4 * 1)
+                }
+                UniformData::IVec2(ref data) => {
+                    slice::from_raw_parts(data as *const I32x2 as *const u8, 4 * 2)
+                }
+                UniformData::IVec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Int(ref data) => {
+                    slice::from_raw_parts(data as *const i32 as *const u8, 4 * 1)
+                }
+                UniformData::Mat2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+                UniformData::Mat4(ref data) => {
+                    slice::from_raw_parts(&data[0] as *const F32x4 as *const u8, 4 * 16)
+                }
+                UniformData::Vec2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x2 as *const u8, 4 * 2)
+                }
+                UniformData::Vec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Vec4(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+            }
+        }
+    }
+}
@@ -33,25 +33,25 @@ use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I, Vector4F};
-use pathfinder_gpu::Device;
+use pathfinder_gpu::{Device, TextureFormat};
+use pathfinder_pdf::{PDFDocument, PDFScene};
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
-use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererLevel};
-use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
-use pathfinder_renderer::gpu::renderer::{DebugUIPresenterInfo, Renderer};
+use pathfinder_renderer::gpu::options::{DestFramebuffer, FillMode, RendererLevel};
+use pathfinder_renderer::gpu::options::{MemoryBudget, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::{DebugUIPresenterInfo, FrameReadback, Renderer};
 use pathfinder_renderer::options::{BuildOptions, RenderTransform};
 use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::{DrawPath, RenderTarget, Scene};
 use pathfinder_resources::ResourceLoader;
+use pathfinder_svg::text as svg_text;
 use pathfinder_svg::SVGScene;
 use pathfinder_ui::{MousePosition, UIEvent};
-use pdf::file::File as PdfFile;
-use pdf_render::Cache as PdfRenderCache;
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
-use usvg::{Options as UsvgOptions, Tree as SvgTree};
+use usvg::Tree as SvgTree;
 
 #[cfg(any(not(target_os = "macos"), feature = "pf-gl"))]
 use pathfinder_gl::GLDevice as DeviceImpl;
@@ -86,9 +86,8 @@ mod ui;
 enum Content {
     Svg(SvgTree),
     Pdf {
-        file: PdfFile<Vec<u8>>,
-        cache: PdfRenderCache,
-        page_nr: u32,
+        document: PDFDocument,
+        page_nr: usize,
     },
 }
 
@@ -109,6 +108,7 @@ where
     camera: Camera,
     frame_counter: u32,
     pending_screenshot_info: Option<ScreenshotInfo>,
+    pending_raster_screenshot: Option<(PathBuf, FrameReadback<DeviceImpl>)>,
     mouselook_enabled: bool,
     pub dirty: bool,
     expire_message_event_id: u32,
@@ -161,12 +161,17 @@ where
         let dest_framebuffer = DestFramebuffer::Default {
             viewport,
             window_size: window_size.device_size(),
+            format: TextureFormat::RGBA8,
         };
         let render_mode = RendererMode { level };
         let render_options = RendererOptions {
             dest: dest_framebuffer,
             background_color: None,
             show_debug_ui: true,
+            fill_mode: FillMode::default(),
+            memory_budget: MemoryBudget::default(),
+            dither: false,
+            post_process: vec![],
         };
 
         let filter = build_filter(&ui_model);
@@ -215,6 +220,7 @@ where
             camera,
             frame_counter: 0,
             pending_screenshot_info: None,
+            pending_raster_screenshot: None,
             mouselook_enabled: false,
             dirty: true,
             expire_message_event_id,
@@ -284,6 +290,7 @@ where
                 Vector2F::zero()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            dirty_rect: None,
         };
 
         self.scene_proxy.build(build_options);
@@ -502,6 +509,7 @@ where
     }
 
     pub fn finish_drawing_frame(&mut self) {
+        self.maybe_save_raster_screenshot();
         self.maybe_take_screenshot();
 
         let frame = self.current_frame.take().unwrap();
@@ -813,15 +821,12 @@ impl Content {
                 (built_svg.scene, message)
             }
             Content::Pdf {
-                ref file,
-                ref mut cache,
+                ref document,
                 page_nr,
             } => {
-                let page = file.get_page(page_nr).expect("no such page");
-                let (scene, _) = cache
-                    .render_page(file, &page, Transform2F::default())
-                    .unwrap();
-                (scene, String::new())
+                let built_pdf = document.render_page(page_nr).expect("no such page");
+                let message = get_pdf_building_message(&built_pdf);
+                (built_pdf.scene, message)
             }
         }
     }
@@ -834,12 +839,11 @@ fn load_scene(resource_loader: &dyn ResourceLoader, input_path: &DataPath) -> Co
         DataPath::Path(ref path) => std::fs::read(path).unwrap().into(),
     };
 
-    if let Ok(tree) = SvgTree::from_data(&data, &UsvgOptions::default().to_ref()) {
+    if let Ok(tree) = SvgTree::from_data(&data, &svg_text::options_with_system_fonts().to_ref()) {
         Content::Svg(tree)
-    } else if let Ok(file) = PdfFile::from_data(data) {
+    } else if let Ok(document) = PDFDocument::from_data(&data) {
         Content::Pdf {
-            file,
-            cache: PdfRenderCache::new(),
+            document,
             page_nr: 0,
         }
     } else {
@@ -915,6 +919,16 @@ fn get_svg_building_message(built_svg: &SVGScene) -> String {
     )
 }
 
+fn get_pdf_building_message(built_pdf: &PDFScene) -> String {
+    if built_pdf.result_flags.is_empty() {
+        return String::new();
+    }
+    format!(
+        "Warning: These features in the PDF are unsupported: {}.",
+        built_pdf.result_flags
+    )
+}
+
 fn emit_message<W>(
     ui_model: &mut DemoUIModel,
     message_epoch: &mut u32,
@@ -15,11 +15,10 @@ use crate::window::{View, Window};
 use crate::{BackgroundColor, DemoApp, UIVisibility};
 use image::ColorType;
 use pathfinder_color::{ColorF, ColorU};
-use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::transform3d::Transform4F;
-use pathfinder_geometry::vector::{Vector2I, Vector4F};
+use pathfinder_geometry::vector::Vector4F;
 use pathfinder_gpu::{ClearOps, DepthFunc, DepthState, Device, Primitive, RenderOptions};
-use pathfinder_gpu::{RenderState, RenderTarget, TextureData, TextureFormat, UniformData};
+use pathfinder_gpu::{RenderState, TextureFormat, UniformData};
 use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererOptions};
 use pathfinder_renderer::options::RenderTransform;
 use std::mem;
@@ -82,6 +81,10 @@ where
                     dest: DestFramebuffer::Other(self.scene_framebuffer.take().unwrap()),
                     background_color: clear_color,
                     show_debug_ui: self.options.ui != UIVisibility::None,
+                    fill_mode: self.renderer.options().fill_mode,
+                    memory_budget: self.renderer.options().memory_budget,
+                    dither: self.renderer.options().dither,
+                    post_process: mem::take(&mut self.renderer.options_mut().post_process),
                 };
                 2
             }
@@ -90,9 +93,14 @@ where
                     dest: DestFramebuffer::Default {
                         viewport: self.window.viewport(View::Mono),
                         window_size,
+                        format: TextureFormat::RGBA8,
                     },
                     background_color: clear_color,
                     show_debug_ui: self.options.ui != UIVisibility::None,
+                    fill_mode: self.renderer.options().fill_mode,
+                    memory_budget: self.renderer.options().memory_budget,
+                    dither: self.renderer.options().dither,
+                    post_process: mem::take(&mut self.renderer.options_mut().post_process),
                 };
                 1
             }
@@ -121,6 +129,7 @@ where
                 dest: DestFramebuffer::Default {
                     viewport: self.window.viewport(View::Mono),
                     window_size: self.window_size.device_size(),
+                    format: TextureFormat::RGBA8,
                 },
                 ..*self.renderer.options()
             };
@@ -164,6 +173,7 @@ where
         self.renderer.options_mut().dest = DestFramebuffer::Default {
             viewport,
             window_size: self.window_size.device_size(),
+            format: TextureFormat::RGBA8,
         };
 
         self.draw_environment(render_scene_index);
@@ -294,26 +304,26 @@ where
         self.scene_proxy.render(&mut self.renderer);
     }
 
+    // Screenshots are saved on a later frame, so that reading back pixels doesn't stall.
     pub fn take_raster_screenshot(&mut self, path: PathBuf) {
-        let drawable_size = self.window_size.device_size();
-        let viewport = RectI::new(Vector2I::default(), drawable_size);
-        let texture_data_receiver = self
-            .renderer
-            .device()
-            .read_pixels(&RenderTarget::Default, viewport);
-        let pixels = match self
-            .renderer
-            .device()
-            .recv_texture_data(&texture_data_receiver)
-        {
-            TextureData::U8(pixels) => pixels,
-            _ => panic!("Unexpected pixel format for default framebuffer!"),
+        let readback = self.renderer.read_frame_async();
+        self.pending_raster_screenshot = Some((path, readback));
+    }
+
+    pub fn maybe_save_raster_screenshot(&mut self) {
+        let frame = match self.pending_raster_screenshot {
+            None => return,
+            Some((_, ref readback)) => match self.renderer.try_recv_frame_image(readback) {
+                None => return,
+                Some(frame) => frame,
+            },
         };
+        let (path, _) = self.pending_raster_screenshot.take().unwrap();
         image::save_buffer(
             path,
-            &pixels,
-            drawable_size.x() as u32,
-            drawable_size.y() as u32,
+            &frame.pixels,
+            frame.size.x() as u32,
+            frame.size.y() as u32,
             ColorType::Rgba8,
         )
         .unwrap();
@@ -38,7 +38,7 @@ use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gl::GLDevice;
 use pathfinder_gl::GLVersion;
 use pathfinder_gpu::ClearParams;
-use pathfinder_gpu::Device;
+use pathfinder_gpu::{Device, TextureFormat};
 use pathfinder_renderer::concurrent::executor::SequentialExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::renderer::DestFramebuffer;
@@ -245,6 +245,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(
                 let dest_framebuffer = DestFramebuffer::Default {
                     viewport,
                     window_size: size,
+                    format: TextureFormat::RGBA8,
                 };
                 Renderer::new(device, resources, dest_framebuffer)
             });
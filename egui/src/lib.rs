@@ -0,0 +1,138 @@
+// pathfinder/egui/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Draws Pathfinder scenes inside `egui` user interfaces that are painted with `egui_glow`.
+//!
+//! `EguiRenderer` shares the OpenGL context that `egui` paints with. Its `paint()` method adds a
+//! paint callback that renders a scene into a rectangle of the UI, in the middle of `egui`'s own
+//! drawing, so plots and diagrams can be drawn as vector graphics inside panels and windows.
+
+use egui::{PaintCallback, PaintCallbackInfo, Rect, Ui};
+use egui_glow::{CallbackFn, Painter};
+use gl::types::{GLint, GLuint};
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::vec2i;
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::TextureFormat;
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::ResourceLoader;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex};
+
+/// A Pathfinder renderer that draws into `egui`'s OpenGL context.
+#[derive(Clone)]
+pub struct EguiRenderer {
+    renderer: Arc<Mutex<Renderer<GLDevice>>>,
+}
+
+impl EguiRenderer {
+    /// Loads OpenGL functions with `loader` and creates a renderer.
+    ///
+    /// The OpenGL context that `egui` paints with must be current, and `loader` must look up
+    /// functions in it; with `glutin`, for example, pass the display's `get_proc_address()`. The
+    /// context must support OpenGL 3.0 or later.
+    pub fn new<F>(loader: F, resource_loader: &dyn ResourceLoader) -> EguiRenderer
+    where
+        F: FnMut(&'static str) -> *const c_void,
+    {
+        gl::load_with(loader);
+
+        let device = GLDevice::new(GLVersion::GL3, current_framebuffer());
+        let mode = RendererMode::default_for_device(&device);
+        // Clearing would erase the rest of the UI, so scenes have to paint their own background.
+        let options = RendererOptions {
+            background_color: None,
+            ..RendererOptions::default()
+        };
+        let renderer = Renderer::new(device, resource_loader, mode, options);
+        EguiRenderer {
+            renderer: Arc::new(Mutex::new(renderer)),
+        }
+    }
+
+    /// Returns a paint callback that renders the scene into `rect`, in points.
+    ///
+    /// The view box of the scene is stretched to fill the rectangle. The scene is kept between
+    /// frames, so return the same callback each frame to let Pathfinder reuse built tiles.
+    pub fn callback(&self, rect: Rect, scene: Arc<Mutex<Scene>>) -> PaintCallback {
+        let renderer = self.renderer.clone();
+        let callback = CallbackFn::new(move |info: PaintCallbackInfo, _: &Painter| {
+            let mut renderer = renderer.lock().unwrap();
+            let mut scene = scene.lock().unwrap();
+            render(&mut renderer, &mut scene, &info);
+        });
+        PaintCallback {
+            rect,
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// Adds a paint callback to the UI that renders the scene into `rect`, in points.
+    pub fn paint(&self, ui: &mut Ui, rect: Rect, scene: Arc<Mutex<Scene>>) {
+        ui.painter().add(self.callback(rect, scene));
+    }
+
+    /// Runs `f` with the renderer, for example to change its options.
+    pub fn with_renderer<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Renderer<GLDevice>) -> R,
+    {
+        f(&mut self.renderer.lock().unwrap())
+    }
+}
+
+fn render(renderer: &mut Renderer<GLDevice>, scene: &mut Scene, info: &PaintCallbackInfo) {
+    // `egui_glow` may paint into an intermediate framebuffer rather than the window's.
+    renderer
+        .device_mut()
+        .set_default_framebuffer(current_framebuffer());
+
+    // OpenGL viewports are measured from the bottom of the framebuffer.
+    let viewport = info.viewport_in_pixels();
+    let viewport = RectI::new(
+        vec2i(viewport.left_px, viewport.from_bottom_px),
+        vec2i(viewport.width_px, viewport.height_px),
+    );
+    if viewport.width() <= 0 || viewport.height() <= 0 {
+        return;
+    }
+    let window_size = vec2i(info.screen_size_px[0] as i32, info.screen_size_px[1] as i32);
+    let old_window_size = renderer.options().dest.window_size(renderer.device());
+    renderer.options_mut().dest = DestFramebuffer::Default {
+        viewport,
+        window_size,
+        format: TextureFormat::RGBA8,
+    };
+    if window_size != old_window_size {
+        renderer.dest_framebuffer_size_changed();
+    }
+
+    let view_box = scene.view_box();
+    let transform = Transform2F::from_scale(viewport.size().to_f32() / view_box.size())
+        * Transform2F::from_translation(-view_box.origin());
+    let build_options = BuildOptions {
+        transform: RenderTransform::Transform2D(transform),
+        ..BuildOptions::default()
+    };
+    scene.build_and_render(renderer, build_options, SequentialExecutor);
+}
+
+fn current_framebuffer() -> GLuint {
+    let mut framebuffer: GLint = 0;
+    unsafe {
+        gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut framebuffer);
+    }
+    framebuffer as GLuint
+}
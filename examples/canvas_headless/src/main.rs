@@ -0,0 +1,56 @@
+// pathfinder/examples/canvas_headless/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders a canvas to a PNG file without opening a window.
+//!
+//! Usage: `canvas_headless [OUTPUT.png]`. This runs in containers without a display server; set
+//! `LIBGL_ALWAYS_SOFTWARE=1` to force Mesa's software rasterizer if no GPU is available.
+
+use pathfinder_canvas::{Canvas, CanvasFontContext, Path2D};
+use pathfinder_color::ColorU;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F};
+use pathfinder_gl::headless::GLHeadlessContext;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use std::env;
+
+fn main() {
+    let output_path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "canvas_headless.png".to_owned());
+
+    // Create an offscreen OpenGL context. This also loads the OpenGL functions.
+    let framebuffer_size = vec2i(320, 320);
+    let context = GLHeadlessContext::new(framebuffer_size).unwrap();
+
+    // Make a canvas. We're going to draw a house on a white background.
+    let font_context = CanvasFontContext::from_system_source();
+    let mut canvas = Canvas::new(framebuffer_size.to_f32()).get_context_2d(font_context);
+    canvas.set_fill_style(ColorU::white());
+    canvas.fill_rect(RectF::new(Vector2F::zero(), framebuffer_size.to_f32()));
+    canvas.set_fill_style(ColorU::black());
+    canvas.set_line_width(10.0);
+    canvas.stroke_rect(RectF::new(vec2f(75.0, 140.0), vec2f(150.0, 110.0)));
+    canvas.fill_rect(RectF::new(vec2f(130.0, 190.0), vec2f(40.0, 60.0)));
+    let mut path = Path2D::new();
+    path.move_to(vec2f(50.0, 140.0));
+    path.line_to(vec2f(150.0, 60.0));
+    path.line_to(vec2f(250.0, 140.0));
+    path.close_path();
+    canvas.stroke_path(path);
+
+    // Render the canvas on the GPU and save it.
+    let device = context.create_device();
+    let resource_loader = EmbeddedResourceLoader::new();
+    let image = canvas
+        .into_image_with_device(framebuffer_size, device, &resource_loader)
+        .unwrap();
+    image.save(&output_path).unwrap();
+}
@@ -254,6 +254,7 @@ fn draw_eyes(
         0.0,
         0.0,
         PI_2,
+        ArcDirection::CW,
     );
     path.ellipse(
         eyes_right_position + vec2f(3.0, 16.0),
@@ -261,6 +262,7 @@ fn draw_eyes(
         0.0,
         0.0,
         PI_2,
+        ArcDirection::CW,
     );
     context.set_fill_style(gradient);
     context.fill_path(path, FillRule::Winding);
@@ -274,8 +276,22 @@ fn draw_eyes(
     gradient.add_color_stop(rgbu(220, 220, 220), 0.0);
     gradient.add_color_stop(rgbu(128, 128, 128), 1.0);
     let mut path = Path2D::new();
-    path.ellipse(eyes_left_position, eyes_radii, 0.0, 0.0, PI_2);
-    path.ellipse(eyes_right_position, eyes_radii, 0.0, 0.0, PI_2);
+    path.ellipse(
+        eyes_left_position,
+        eyes_radii,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
+    path.ellipse(
+        eyes_right_position,
+        eyes_radii,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.set_fill_style(gradient);
     context.fill_path(path, FillRule::Winding);
 
@@ -292,6 +308,7 @@ fn draw_eyes(
         0.0,
         0.0,
         PI_2,
+        ArcDirection::CW,
     );
     path.ellipse(
         eyes_right_position + delta + vec2f(0.0, eyes_radii.y() * 0.25 * (1.0 - blink)),
@@ -299,6 +316,7 @@ fn draw_eyes(
         0.0,
         0.0,
         PI_2,
+        ArcDirection::CW,
     );
     context.set_fill_style(rgbu(32, 32, 32));
     context.fill_path(path, FillRule::Winding);
@@ -310,7 +328,14 @@ fn draw_eyes(
     gloss.add_color_stop(rgbau(255, 255, 255, 0), 1.0);
     context.set_fill_style(gloss);
     let mut path = Path2D::new();
-    path.ellipse(eyes_left_position, eyes_radii, 0.0, 0.0, PI_2);
+    path.ellipse(
+        eyes_left_position,
+        eyes_radii,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.fill_path(path, FillRule::Winding);
 
     let gloss_position = eyes_right_position - eyes_radii * vec2f(0.25, 0.5);
@@ -319,7 +344,14 @@ fn draw_eyes(
     gloss.add_color_stop(rgbau(255, 255, 255, 0), 1.0);
     context.set_fill_style(gloss);
     let mut path = Path2D::new();
-    path.ellipse(eyes_right_position, eyes_radii, 0.0, 0.0, PI_2);
+    path.ellipse(
+        eyes_right_position,
+        eyes_radii,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.fill_path(path, FillRule::Winding);
 }
 
@@ -376,12 +408,12 @@ fn draw_paragraph(
 
         let gutter_text_bounds = RectF::from_points(
             vec2f(
-                gutter_text_metrics.actual_bounding_box_left(),
+                -gutter_text_metrics.actual_bounding_box_left(),
                 -gutter_text_metrics.font_bounding_box_ascent(),
             ),
             vec2f(
                 gutter_text_metrics.actual_bounding_box_right(),
-                -gutter_text_metrics.font_bounding_box_descent(),
+                gutter_text_metrics.font_bounding_box_descent(),
             ),
         );
         let gutter_path_bounds = gutter_text_bounds.dilate(vec2f(4.0, 2.0));
@@ -474,7 +506,7 @@ impl MultilineTextBox {
         let a_b_measure = context.measure_text("A B");
         let space_width = a_b_measure.width() - context.measure_text("AB").width();
         let line_height =
-            a_b_measure.em_height_ascent() - a_b_measure.em_height_descent() + LINE_SPACING;
+            a_b_measure.em_height_ascent() + a_b_measure.em_height_descent() + LINE_SPACING;
 
         let mut text: VecDeque<VecDeque<_>> = text
             .split('\n')
@@ -574,7 +606,7 @@ impl Line {
             }
 
             self.ascent = self.ascent.max(word_metrics.em_height_ascent());
-            self.descent = self.descent.min(word_metrics.em_height_descent());
+            self.descent = self.descent.max(word_metrics.em_height_descent());
             self.words.push(Word {
                 metrics: word_metrics,
                 string: word,
@@ -602,7 +634,7 @@ impl Line {
     fn bounds(&self) -> RectF {
         RectF::new(
             self.origin - vec2f(0.0, self.ascent),
-            vec2f(self.width, self.ascent - self.descent),
+            vec2f(self.width, self.ascent + self.descent),
         )
     }
 
@@ -728,13 +760,27 @@ fn draw_graph(context: &mut CanvasRenderingContext2D, rect: RectF, time: f32) {
     context.set_fill_style(rgbu(0, 160, 192));
     let mut path = Path2D::new();
     for &sample_point in &sample_points {
-        path.ellipse(sample_point, vec2f(4.0, 4.0), 0.0, 0.0, PI_2);
+        path.ellipse(
+            sample_point,
+            vec2f(4.0, 4.0),
+            0.0,
+            0.0,
+            PI_2,
+            ArcDirection::CW,
+        );
     }
     context.fill_path(path, FillRule::Winding);
     context.set_fill_style(rgbu(220, 220, 220));
     let mut path = Path2D::new();
     for &sample_point in &sample_points {
-        path.ellipse(sample_point, vec2f(2.0, 2.0), 0.0, 0.0, PI_2);
+        path.ellipse(
+            sample_point,
+            vec2f(2.0, 2.0),
+            0.0,
+            0.0,
+            PI_2,
+            ArcDirection::CW,
+        );
     }
     context.fill_path(path, FillRule::Winding);
 
@@ -797,8 +843,9 @@ fn draw_color_wheel(
     context.set_stroke_style(rgbau(0, 0, 0, 64));
     context.set_line_width(1.0);
     let mut path = Path2D::new();
-    path.ellipse(center, inner_radius - 0.5, 0.0, 0.0, PI_2);
-    path.ellipse(center, outer_radius + 0.5, 0.0, 0.0, PI_2);
+    path.ellipse(center, inner_radius - 0.5, 0.0, 0.0, PI_2, ArcDirection::CW);
+    path.move_to(center + vec2f(outer_radius + 0.5, 0.0));
+    path.ellipse(center, outer_radius + 0.5, 0.0, 0.0, PI_2, ArcDirection::CW);
     context.stroke_path(path);
 
     // Prepare to draw the selector.
@@ -850,7 +897,14 @@ fn draw_color_wheel(
     context.set_stroke_style(rgbau(255, 255, 255, 192));
     context.set_line_width(2.0);
     let mut path = Path2D::new();
-    path.ellipse(selection_circle_center, vec2f(5.0, 5.0), 0.0, 0.0, PI_2);
+    path.ellipse(
+        selection_circle_center,
+        vec2f(5.0, 5.0),
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.stroke_path(path);
 
     // Fill the selection circle.
@@ -863,7 +917,14 @@ fn draw_color_wheel(
         selection_circle_center - vec2f(20.0, 20.0),
         vec2f(40.0, 40.0),
     ));
-    path.ellipse(selection_circle_center, vec2f(7.0, 7.0), 0.0, 0.0, PI_2);
+    path.ellipse(
+        selection_circle_center,
+        vec2f(7.0, 7.0),
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.fill_path(path, FillRule::EvenOdd);
 
     context.restore();
@@ -1361,7 +1422,14 @@ fn draw_slider(context: &mut CanvasRenderingContext2D, value: f32, rect: RectF,
     background_gradient.add_color_stop(rgbau(255, 255, 255, 16), 0.0);
     background_gradient.add_color_stop(rgbau(0, 0, 0, 16), 1.0);
     let mut path = Path2D::new();
-    path.ellipse(knob_position, knob_radius - 1.0, 0.0, 0.0, PI_2);
+    path.ellipse(
+        knob_position,
+        knob_radius - 1.0,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.set_fill_style(rgbu(40, 43, 48));
     context.set_shadow_blur(6.0 * hidpi_factor);
     context.set_shadow_color(rgbau(0, 0, 0, 128));
@@ -1373,7 +1441,14 @@ fn draw_slider(context: &mut CanvasRenderingContext2D, value: f32, rect: RectF,
 
     // Outline knob.
     let mut path = Path2D::new();
-    path.ellipse(knob_position, knob_radius - 0.5, 0.0, 0.0, PI_2);
+    path.ellipse(
+        knob_position,
+        knob_radius - 0.5,
+        0.0,
+        0.0,
+        PI_2,
+        ArcDirection::CW,
+    );
     context.set_stroke_style(rgbau(0, 0, 0, 92));
     context.stroke_path(path);
 
@@ -8,16 +8,28 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Experimental example for reading Lottie animations. This is very incomplete.
+//! Renders one frame of a Lottie animation to a PNG file on the CPU.
+//!
+//! Usage: `lottie_basic ANIMATION.json [OUTPUT.png] [TIME_IN_SECONDS]`.
 
+use pathfinder_geometry::vector::vec2f;
 use pathfinder_lottie::Lottie;
+use pathfinder_rasterize::RasterizeOptions;
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
 
 fn main() {
-    let path = env::args().skip(1).next().unwrap();
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap();
+    let output_path = args.next().unwrap_or_else(|| "lottie_basic.png".to_owned());
+    let time: f32 = args.next().map_or(0.0, |time| time.parse().unwrap());
+
     let file = BufReader::new(File::open(path).unwrap());
     let lottie = Lottie::from_reader(file).unwrap();
-    println!("{:#?}", lottie);
+    let scene = lottie.build_scene(lottie.frame_at_time(time));
+
+    let size = vec2f(lottie.width, lottie.height).ceil().to_i32();
+    let image = pathfinder_rasterize::rasterize(&scene, &RasterizeOptions::new(size));
+    image.to_image_buffer().save(&output_path).unwrap();
 }
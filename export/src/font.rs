@@ -0,0 +1,526 @@
+// pathfinder/export/src/font.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reads and subsets TrueType and OpenType fonts, for embedding in documents.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+// Tables that TrueType subsets keep, besides `glyf` and `loca`, which are rebuilt.
+const TRUETYPE_TABLES: &[&[u8; 4]] = &[
+    b"head", b"hhea", b"hmtx", b"maxp", b"cvt ", b"fpgm", b"prep",
+];
+
+// Flags of composite glyph components.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// A font within a TrueType or OpenType file, or a collection of them.
+pub(crate) struct Font {
+    data: Arc<Vec<u8>>,
+    tables: Vec<([u8; 4], Range<usize>)>,
+}
+
+impl Font {
+    /// Reads the table directory of the font, returning `None` if the font is malformed or lacks
+    /// tables that embedding needs.
+    ///
+    /// CID-keyed CFF fonts are rejected too: PDF addresses their glyphs by CID, which their
+    /// charset maps to glyph IDs, but text is written with glyph IDs.
+    pub(crate) fn parse(data: Arc<Vec<u8>>, font_index: u32) -> Option<Font> {
+        let mut offset = 0;
+        if data.get(0..4)? == b"ttcf" {
+            if font_index >= read_u32(&data, 8)? {
+                return None;
+            }
+            offset = read_u32(&data, 12 + 4 * font_index as usize)? as usize;
+        }
+
+        let table_count = read_u16(&data, offset + 4)? as usize;
+        let mut tables = Vec::with_capacity(table_count);
+        for table_index in 0..table_count {
+            let record = offset + 12 + 16 * table_index;
+            let tag = data.get(record..record + 4)?;
+            let start = read_u32(&data, record + 8)? as usize;
+            let end = start.checked_add(read_u32(&data, record + 12)? as usize)?;
+            data.get(start..end)?;
+            tables.push(([tag[0], tag[1], tag[2], tag[3]], start..end));
+        }
+
+        let font = Font { data, tables };
+        for tag in &[b"head", b"hhea", b"hmtx", b"maxp"] {
+            font.table(tag)?;
+        }
+        if font.table(b"head")?.len() < 54
+            || font.table(b"hhea")?.len() < 36
+            || font.table(b"maxp")?.len() < 6
+            || font.units_per_em() == 0
+        {
+            return None;
+        }
+        if !font.is_cff() && (font.table(b"glyf").is_none() || font.table(b"loca").is_none()) {
+            return None;
+        }
+        if font.is_cff() && cff_is_cid_keyed(font.table(b"CFF ")?)? {
+            return None;
+        }
+        Some(font)
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> Option<&[u8]> {
+        self.tables
+            .iter()
+            .find(|(table_tag, _)| table_tag == tag)
+            .map(|(_, range)| &self.data[range.clone()])
+    }
+
+    /// Whether the glyphs are PostScript outlines in a `CFF ` table, rather than TrueType ones.
+    pub(crate) fn is_cff(&self) -> bool {
+        self.table(b"CFF ").is_some()
+    }
+
+    pub(crate) fn glyph_count(&self) -> u16 {
+        self.table(b"maxp")
+            .and_then(|maxp| read_u16(maxp, 4))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn units_per_em(&self) -> u16 {
+        self.table(b"head")
+            .and_then(|head| read_u16(head, 18))
+            .unwrap_or(0)
+    }
+
+    /// Returns the bounding box of all glyphs, as `[x_min, y_min, x_max, y_max]` in font units.
+    pub(crate) fn bounding_box(&self) -> [i16; 4] {
+        let mut bounding_box = [0; 4];
+        if let Some(head) = self.table(b"head") {
+            for (index, value) in bounding_box.iter_mut().enumerate() {
+                *value = read_i16(head, 36 + 2 * index).unwrap_or(0);
+            }
+        }
+        bounding_box
+    }
+
+    pub(crate) fn ascent(&self) -> i16 {
+        self.table(b"hhea")
+            .and_then(|hhea| read_i16(hhea, 4))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn descent(&self) -> i16 {
+        self.table(b"hhea")
+            .and_then(|hhea| read_i16(hhea, 6))
+            .unwrap_or(0)
+    }
+
+    /// Returns the height of capital letters, or the ascent if the font doesn't say.
+    pub(crate) fn cap_height(&self) -> i16 {
+        self.table(b"OS/2")
+            .filter(|os2| read_u16(os2, 0).unwrap_or(0) >= 2)
+            .and_then(|os2| read_i16(os2, 88))
+            .unwrap_or_else(|| self.ascent())
+    }
+
+    /// Returns the slant of the font in degrees, counterclockwise from vertical.
+    pub(crate) fn italic_angle(&self) -> f32 {
+        self.table(b"post")
+            .and_then(|post| read_u32(post, 4))
+            .map_or(0.0, |angle| angle as i32 as f32 / 65536.0)
+    }
+
+    pub(crate) fn advance_width(&self, glyph_id: u16) -> u16 {
+        let metric_count = self
+            .table(b"hhea")
+            .and_then(|hhea| read_u16(hhea, 34))
+            .unwrap_or(0);
+        if metric_count == 0 {
+            return 0;
+        }
+        let index = glyph_id.min(metric_count - 1) as usize;
+        self.table(b"hmtx")
+            .and_then(|hmtx| read_u16(hmtx, 4 * index))
+            .unwrap_or(0)
+    }
+
+    /// Returns the PostScript name of the font, if it has one.
+    pub(crate) fn postscript_name(&self) -> Option<String> {
+        let name = self.table(b"name")?;
+        let string_offset = read_u16(name, 4)? as usize;
+        for record_index in 0..read_u16(name, 2)? as usize {
+            let record = 6 + 12 * record_index;
+            if read_u16(name, record + 6)? != 6 {
+                continue;
+            }
+            let start = string_offset + read_u16(name, record + 10)? as usize;
+            let bytes = name.get(start..start + read_u16(name, record + 8)? as usize)?;
+            let string = match read_u16(name, record)? {
+                1 => bytes.iter().map(|&byte| byte as char).collect(),
+                0 | 3 => {
+                    let units: Vec<u16> = bytes
+                        .chunks(2)
+                        .filter_map(|unit| read_u16(unit, 0))
+                        .collect();
+                    String::from_utf16_lossy(&units)
+                }
+                _ => continue,
+            };
+            if !string.is_empty() {
+                return Some(string);
+            }
+        }
+        None
+    }
+
+    /// Returns the character that each glyph maps to, according to the font's Unicode `cmap`
+    /// subtables. Glyphs mapped from several characters map to the lowest.
+    pub(crate) fn glyph_characters(&self) -> HashMap<u16, char> {
+        let mut characters = HashMap::new();
+        let cmap = match self.table(b"cmap") {
+            Some(cmap) => cmap,
+            None => return characters,
+        };
+        let mut mappings = vec![];
+        let subtable_count = read_u16(cmap, 2).unwrap_or(0) as usize;
+        for subtable_index in 0..subtable_count {
+            let record = 4 + 8 * subtable_index;
+            let (platform, encoding) = match (read_u16(cmap, record), read_u16(cmap, record + 2)) {
+                (Some(platform), Some(encoding)) => (platform, encoding),
+                _ => break,
+            };
+            if platform != 0 && !(platform == 3 && (encoding == 1 || encoding == 10)) {
+                continue;
+            }
+            let subtable =
+                match read_u32(cmap, record + 4).and_then(|offset| cmap.get(offset as usize..)) {
+                    Some(subtable) => subtable,
+                    None => continue,
+                };
+            match read_u16(subtable, 0) {
+                Some(4) => read_cmap_format_4(subtable, &mut mappings),
+                Some(12) => read_cmap_format_12(subtable, &mut mappings),
+                _ => {}
+            }
+        }
+
+        mappings.sort_unstable();
+        for (character, glyph_id) in mappings {
+            if glyph_id != 0 {
+                characters.entry(glyph_id).or_insert(character);
+            }
+        }
+        characters
+    }
+
+    /// Returns a standalone font file containing the given glyphs.
+    ///
+    /// Glyph IDs are kept, so the other glyphs are left empty rather than removed. Components of
+    /// composite glyphs are kept too. Fonts with CFF outlines aren't subset, but they're still
+    /// extracted from any collection they're in.
+    // TODO: Subset CFF fonts.
+    pub(crate) fn subset(&self, glyph_ids: &BTreeSet<u16>) -> Vec<u8> {
+        if self.is_cff() {
+            let tables = self
+                .tables
+                .iter()
+                .map(|&(tag, ref range)| (tag, self.data[range.clone()].to_vec()))
+                .collect();
+            return write_font(*b"OTTO", tables);
+        }
+
+        let glyph_count = self.glyph_count();
+        let mut kept_glyph_ids = BTreeSet::new();
+        let mut pending_glyph_ids: Vec<u16> = glyph_ids.iter().cloned().collect();
+        // The `.notdef` glyph must always be present.
+        pending_glyph_ids.push(0);
+        while let Some(glyph_id) = pending_glyph_ids.pop() {
+            if glyph_id < glyph_count && kept_glyph_ids.insert(glyph_id) {
+                pending_glyph_ids.extend(composite_components(self.glyph_data(glyph_id)));
+            }
+        }
+
+        let mut glyf = vec![];
+        let mut loca = Vec::with_capacity(4 * (glyph_count as usize + 1));
+        for glyph_id in 0..glyph_count {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            if kept_glyph_ids.contains(&glyph_id) {
+                glyf.extend_from_slice(self.glyph_data(glyph_id));
+                glyf.resize(glyf.len().div_ceil(4) * 4, 0);
+            }
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let mut tables = vec![(*b"glyf", glyf), (*b"loca", loca)];
+        for &tag in TRUETYPE_TABLES {
+            if let Some(table) = self.table(tag) {
+                let mut table = table.to_vec();
+                if tag == b"head" {
+                    // The rebuilt `loca` table always has 32-bit offsets.
+                    table[50..52].copy_from_slice(&1u16.to_be_bytes());
+                }
+                tables.push((*tag, table));
+            }
+        }
+        write_font([0, 1, 0, 0], tables)
+    }
+
+    // Returns the outline data of a TrueType glyph, which is empty for glyphs with no outline.
+    fn glyph_data(&self, glyph_id: u16) -> &[u8] {
+        let (head, loca, glyf) = match (
+            self.table(b"head"),
+            self.table(b"loca"),
+            self.table(b"glyf"),
+        ) {
+            (Some(head), Some(loca), Some(glyf)) => (head, loca, glyf),
+            _ => return &[],
+        };
+        let glyph_id = glyph_id as usize;
+        let (start, end) = if read_i16(head, 50) == Some(0) {
+            (
+                read_u16(loca, 2 * glyph_id).map(|offset| offset as usize * 2),
+                read_u16(loca, 2 * glyph_id + 2).map(|offset| offset as usize * 2),
+            )
+        } else {
+            (
+                read_u32(loca, 4 * glyph_id).map(|offset| offset as usize),
+                read_u32(loca, 4 * glyph_id + 4).map(|offset| offset as usize),
+            )
+        };
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => glyf.get(start..end).unwrap_or(&[]),
+            _ => &[],
+        }
+    }
+}
+
+// Returns the IDs of the glyphs that a composite glyph is made of, or nothing if it's simple.
+fn composite_components(glyph: &[u8]) -> Vec<u16> {
+    let mut components = vec![];
+    match read_i16(glyph, 0) {
+        Some(contour_count) if contour_count < 0 => {}
+        _ => return components,
+    }
+    let mut offset = 10;
+    while let (Some(flags), Some(glyph_id)) = (read_u16(glyph, offset), read_u16(glyph, offset + 2))
+    {
+        components.push(glyph_id);
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            8
+        } else {
+            6
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    components
+}
+
+// Whether the CFF table has a CID-keyed font, whose top DICT has the `ROS` operator. Returns
+// `None` if the table is malformed.
+fn cff_is_cid_keyed(cff: &[u8]) -> Option<bool> {
+    let names = *cff.get(2)? as usize;
+    let top_dicts = skip_cff_index(cff, names)?;
+    if read_u16(cff, top_dicts)? == 0 {
+        return None;
+    }
+    let offset_size = *cff.get(top_dicts + 2)? as usize;
+    let read_offset = |index: usize| {
+        let start = top_dicts + 3 + index * offset_size;
+        let bytes = cff.get(start..start + offset_size)?;
+        Some(
+            bytes
+                .iter()
+                .fold(0, |offset, &byte| offset << 8 | byte as usize),
+        )
+    };
+    let data = top_dicts + 2 + (read_u16(cff, top_dicts)? as usize + 1) * offset_size;
+    let top_dict = cff.get(data + read_offset(0)?..data + read_offset(1)?)?;
+
+    let mut position = 0;
+    while let Some(&byte) = top_dict.get(position) {
+        position += match byte {
+            12 if *top_dict.get(position + 1)? == 30 => return Some(true),
+            12 => 2,
+            0..=21 => 1,
+            28 => 3,
+            29 => 5,
+            30 => {
+                // Real numbers are packed in nibbles, ending with 0xf.
+                let end = top_dict[position + 1..]
+                    .iter()
+                    .position(|&byte| byte & 0xf0 == 0xf0 || byte & 0x0f == 0x0f)?;
+                end + 2
+            }
+            32..=246 => 1,
+            247..=254 => 2,
+            _ => return None,
+        };
+    }
+    Some(false)
+}
+
+// Returns the offset of the end of the CFF INDEX at the given offset.
+fn skip_cff_index(cff: &[u8], index: usize) -> Option<usize> {
+    let count = read_u16(cff, index)? as usize;
+    if count == 0 {
+        return Some(index + 2);
+    }
+    let offset_size = *cff.get(index + 2)? as usize;
+    let last = index + 3 + count * offset_size;
+    let bytes = cff.get(last..last + offset_size)?;
+    let end = bytes
+        .iter()
+        .fold(0, |offset, &byte| offset << 8 | byte as usize);
+    Some(index + 2 + (count + 1) * offset_size + end)
+}
+
+fn read_cmap_format_4(subtable: &[u8], mappings: &mut Vec<(char, u16)>) {
+    let segment_count = read_u16(subtable, 6).unwrap_or(0) as usize / 2;
+    let end_codes = 14;
+    let start_codes = end_codes + 2 * segment_count + 2;
+    let id_deltas = start_codes + 2 * segment_count;
+    let id_range_offsets = id_deltas + 2 * segment_count;
+    for segment in 0..segment_count {
+        let (start, end, delta, range_offset) = match (
+            read_u16(subtable, start_codes + 2 * segment),
+            read_u16(subtable, end_codes + 2 * segment),
+            read_u16(subtable, id_deltas + 2 * segment),
+            read_u16(subtable, id_range_offsets + 2 * segment),
+        ) {
+            (Some(start), Some(end), Some(delta), Some(range_offset)) => {
+                (start, end, delta, range_offset)
+            }
+            _ => return,
+        };
+        for code in start..=end.min(0xfffe) {
+            let glyph_id = if range_offset == 0 {
+                code.wrapping_add(delta)
+            } else {
+                let offset = id_range_offsets
+                    + 2 * segment
+                    + range_offset as usize
+                    + 2 * (code - start) as usize;
+                match read_u16(subtable, offset) {
+                    Some(0) | None => continue,
+                    Some(glyph_id) => glyph_id.wrapping_add(delta),
+                }
+            };
+            if let Some(character) = std::char::from_u32(code as u32) {
+                mappings.push((character, glyph_id));
+            }
+        }
+    }
+}
+
+fn read_cmap_format_12(subtable: &[u8], mappings: &mut Vec<(char, u16)>) {
+    let group_count = read_u32(subtable, 12).unwrap_or(0) as usize;
+    for group in 0..group_count {
+        let offset = 16 + 12 * group;
+        let (start, end, start_glyph_id) = match (
+            read_u32(subtable, offset),
+            read_u32(subtable, offset + 4),
+            read_u32(subtable, offset + 8),
+        ) {
+            (Some(start), Some(end), Some(start_glyph_id)) => (start, end, start_glyph_id),
+            _ => return,
+        };
+        for code in start..=end.min(0x10ffff) {
+            let glyph_id = start_glyph_id + (code - start);
+            if glyph_id > 0xffff {
+                break;
+            }
+            if let Some(character) = std::char::from_u32(code) {
+                mappings.push((character, glyph_id as u16));
+            }
+        }
+    }
+}
+
+// Assembles tables into a font file, sorting them by tag as the format requires.
+fn write_font(version: [u8; 4], mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|&(tag, _)| tag);
+    let table_count = tables.len() as u16;
+    let mut search_range = 1;
+    let mut entry_selector = 0;
+    while search_range * 2 <= table_count {
+        search_range *= 2;
+        entry_selector += 1;
+    }
+
+    let mut font = version.to_vec();
+    for &value in &[
+        table_count,
+        search_range * 16,
+        entry_selector,
+        table_count * 16 - search_range * 16,
+    ] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut offset = 12 + 16 * tables.len();
+    for &(tag, ref table) in &tables {
+        font.extend_from_slice(&tag);
+        font.extend_from_slice(&checksum(table).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().div_ceil(4) * 4;
+    }
+
+    let mut head_offset = None;
+    for &(tag, ref table) in &tables {
+        if &tag == b"head" {
+            head_offset = Some(font.len());
+        }
+        font.extend_from_slice(table);
+        font.resize(font.len().div_ceil(4) * 4, 0);
+    }
+
+    // The checksum adjustment makes the checksum of the whole file a fixed value.
+    if let Some(head_offset) = head_offset {
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&[0; 4]);
+        let adjustment = 0xb1b0_afbau32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0, |sum: u32, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
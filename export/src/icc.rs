@@ -0,0 +1,118 @@
+// pathfinder/export/src/icc.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Builds the ICC color profile that archival PDFs declare their colors to be in.
+
+// The number of entries in the tone curve of each channel.
+const CURVE_SIZE: usize = 1024;
+
+// The D50 white point that profile connection space is relative to.
+const D50: [f32; 3] = [0.9642, 1.0, 0.8249];
+
+// The sRGB primaries, adapted to D50 with the Bradford transform.
+const RED: [f32; 3] = [0.436_074_7, 0.222_504_5, 0.013_932_2];
+const GREEN: [f32; 3] = [0.385_064_9, 0.716_878_6, 0.097_104_5];
+const BLUE: [f32; 3] = [0.143_080_4, 0.060_616_9, 0.714_173_3];
+
+/// Returns a version 2 ICC profile for the sRGB color space
+pub(crate) fn srgb_profile() -> Vec<u8> {
+    let curve = curve_tag();
+    let tags: [(&[u8; 4], Vec<u8>); 9] = [
+        (b"desc", description_tag("sRGB IEC61966-2.1")),
+        (b"cprt", text_tag("No copyright, use freely")),
+        (b"wtpt", xyz_tag(D50)),
+        (b"rXYZ", xyz_tag(RED)),
+        (b"gXYZ", xyz_tag(GREEN)),
+        (b"bXYZ", xyz_tag(BLUE)),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve),
+    ];
+
+    // Tag data follows the header and the tag table, with each tag aligned to 4 bytes.
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = vec![];
+    let data_offset = 128 + 4 + tags.len() * 12;
+    for (signature, tag) in &tags {
+        table.extend_from_slice(&signature[..]);
+        table.extend_from_slice(&((data_offset + data.len()) as u32).to_be_bytes());
+        table.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+        data.extend_from_slice(tag);
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+    }
+
+    let size = data_offset + data.len();
+    let mut profile = Vec::with_capacity(size);
+    profile.extend_from_slice(&(size as u32).to_be_bytes());
+    profile.extend_from_slice(&[0; 4]); // preferred CMM
+    profile.extend_from_slice(&[2, 0x10, 0, 0]); // version 2.1
+    profile.extend_from_slice(b"mntrRGB XYZ ");
+    for &date_field in &[2020u16, 1, 1, 0, 0, 0] {
+        profile.extend_from_slice(&date_field.to_be_bytes());
+    }
+    profile.extend_from_slice(b"acsp");
+    profile.extend_from_slice(&[0; 24]); // platform, flags, device, and attributes
+    profile.extend_from_slice(&[0; 4]); // perceptual rendering intent
+    for &component in &D50 {
+        profile.extend_from_slice(&s15_fixed16(component));
+    }
+    profile.resize(128, 0);
+    profile.extend_from_slice(&table);
+    profile.extend_from_slice(&data);
+    profile
+}
+
+fn description_tag(description: &str) -> Vec<u8> {
+    let mut tag = b"desc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+    tag.extend_from_slice(description.as_bytes());
+    tag.push(0);
+    // Empty Unicode and ScriptCode descriptions, the latter padded to a fixed size.
+    tag.extend_from_slice(&[0; 8]);
+    tag.extend_from_slice(&[0; 3 + 67]);
+    tag
+}
+
+fn text_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"text\0\0\0\0".to_vec();
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+fn xyz_tag(xyz: [f32; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    for &component in &xyz {
+        tag.extend_from_slice(&s15_fixed16(component));
+    }
+    tag
+}
+
+// The sRGB transfer function, sampled evenly.
+fn curve_tag() -> Vec<u8> {
+    let mut tag = b"curv\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(CURVE_SIZE as u32).to_be_bytes());
+    for index in 0..CURVE_SIZE {
+        let value = index as f32 / (CURVE_SIZE - 1) as f32;
+        let linear = if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        };
+        tag.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+    tag
+}
+
+fn s15_fixed16(value: f32) -> [u8; 4] {
+    ((value * 65536.0).round() as i32).to_be_bytes()
+}
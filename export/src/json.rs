@@ -0,0 +1,536 @@
+// pathfinder/export/src/json.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Dumps scenes as human-readable JSON, for inspecting and diffing them.
+//!
+//! The dump lists the display list, then every paint, clip path, and draw path that it uses, in
+//! ID order, with outlines spelled out segment by segment. The output is deterministic, so dumps of
+//! the same scene compare equal. Image pixels aren't included, only their sizes.
+
+use pathfinder_color::ColorU;
+use pathfinder_content::gradient::{Gradient, GradientGeometry};
+use pathfinder_content::outline::{ContourIterFlags, Outline};
+use pathfinder_content::pattern::{Pattern, PatternSource};
+use pathfinder_content::segment::SegmentKind;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DisplayItem, DrawPath, DrawPathId, Scene};
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+const INDENT: &str = "  ";
+
+pub(crate) fn export_json<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
+    let mut paint_ids = BTreeSet::new();
+    let mut clip_path_ids = BTreeSet::new();
+    for draw_path_index in 0..scene.draw_path_count() {
+        let draw_path = scene.get_draw_path(DrawPathId(draw_path_index));
+        paint_ids.insert(draw_path.paint.0);
+        let mut clip_path_id = draw_path.clip_path;
+        while let Some(current_clip_path_id) = clip_path_id {
+            if !clip_path_ids.insert(current_clip_path_id.0) {
+                break;
+            }
+            clip_path_id = scene.get_clip_path(current_clip_path_id).clip_path;
+        }
+    }
+
+    let display_list = scene
+        .display_list()
+        .iter()
+        .map(|display_item| match *display_item {
+            DisplayItem::DrawPaths(ref range) => Value::Object(vec![(
+                "draw_paths",
+                Value::Array(vec![range.start.0.into(), range.end.0.into()]),
+            )]),
+            DisplayItem::PushRenderTarget(render_target_id) => {
+                let render_target = scene.get_render_target(render_target_id);
+                Value::Object(vec![
+                    ("push_render_target", render_target_id.render_target.into()),
+                    ("size", render_target.size().into()),
+                ])
+            }
+            DisplayItem::PopRenderTarget => Value::String("pop_render_target".to_owned()),
+        })
+        .collect();
+
+    let paints = paint_ids
+        .into_iter()
+        .map(|paint_id| paint_value(paint_id, scene.get_paint(PaintId(paint_id))))
+        .collect();
+    let clip_paths = clip_path_ids
+        .into_iter()
+        .map(|clip_path_id| {
+            let clip_path = scene.get_clip_path(ClipPathId(clip_path_id));
+            clip_path_value(clip_path_id, clip_path)
+        })
+        .collect();
+    let draw_paths = (0..scene.draw_path_count())
+        .map(|draw_path_index| {
+            let draw_path = scene.get_draw_path(DrawPathId(draw_path_index));
+            draw_path_value(draw_path_index, draw_path)
+        })
+        .collect();
+
+    let dump = Value::Object(vec![
+        ("view_box", scene.view_box().into()),
+        ("display_list", Value::Array(display_list)),
+        ("paints", Value::Array(paints)),
+        ("clip_paths", Value::Array(clip_paths)),
+        ("draw_paths", Value::Array(draw_paths)),
+    ]);
+    let mut output = String::new();
+    dump.write(0, &mut output);
+    output.push('\n');
+    writer.write_all(output.as_bytes())
+}
+
+fn paint_value(paint_id: u16, paint: &Paint) -> Value {
+    let mut fields = vec![
+        ("id", paint_id.into()),
+        ("color", paint.base_color().into()),
+    ];
+    if let Some(gradient) = paint.gradient() {
+        fields.push(("gradient", gradient_value(gradient)));
+    }
+    if let Some(pattern) = paint.pattern() {
+        fields.push(("pattern", pattern_value(pattern)));
+    }
+    if let Some(noise) = paint.noise() {
+        fields.push(("noise", Value::String(format!("{:?}", noise))));
+    }
+    if let Some(effect) = paint.effect() {
+        fields.push(("effect", Value::String(format!("{:?}", effect))));
+    }
+    Value::Object(fields)
+}
+
+fn gradient_value(gradient: &Gradient) -> Value {
+    let mut fields = match gradient.geometry {
+        GradientGeometry::Linear(line) => vec![
+            ("kind", "linear".into()),
+            ("from", line.from().into()),
+            ("to", line.to().into()),
+        ],
+        GradientGeometry::Radial {
+            line,
+            radii,
+            transform,
+        } => vec![
+            ("kind", "radial".into()),
+            ("from", line.from().into()),
+            ("to", line.to().into()),
+            (
+                "radii",
+                Value::Array(vec![radii.x().into(), radii.y().into()]),
+            ),
+            ("transform", transform.into()),
+        ],
+        GradientGeometry::Conic {
+            center,
+            angle,
+            transform,
+        } => vec![
+            ("kind", "conic".into()),
+            ("center", center.into()),
+            ("angle", angle.into()),
+            ("transform", transform.into()),
+        ],
+    };
+    let stops = gradient
+        .stops()
+        .iter()
+        .map(|stop| {
+            Value::Object(vec![
+                ("offset", stop.offset.into()),
+                ("color", stop.color.into()),
+            ])
+        })
+        .collect();
+    fields.push(("stops", Value::Array(stops)));
+    fields.push(("wrap", format!("{:?}", gradient.wrap).into()));
+    Value::Object(fields)
+}
+
+fn pattern_value(pattern: &Pattern) -> Value {
+    let source = match *pattern.source() {
+        PatternSource::Image(ref image) => Value::Object(vec![
+            ("kind", "image".into()),
+            ("size", image.size().into()),
+        ]),
+        PatternSource::RenderTarget { id, size } => Value::Object(vec![
+            ("kind", "render_target".into()),
+            ("render_target", id.render_target.into()),
+            ("size", size.into()),
+        ]),
+        PatternSource::ExternalTexture { id, size } => Value::Object(vec![
+            ("kind", "external_texture".into()),
+            ("external_texture", id.0.into()),
+            ("size", size.into()),
+        ]),
+    };
+    Value::Object(vec![
+        ("source", source),
+        ("transform", pattern.transform().into()),
+        ("repeat_x", pattern.repeat_x().into()),
+        ("repeat_y", pattern.repeat_y().into()),
+        ("smoothing_enabled", pattern.smoothing_enabled().into()),
+    ])
+}
+
+fn clip_path_value(clip_path_id: u32, clip_path: &ClipPath) -> Value {
+    Value::Object(vec![
+        ("id", clip_path_id.into()),
+        ("name", clip_path.name.as_str().into()),
+        (
+            "clip_path",
+            clip_path
+                .clip_path
+                .map(|clip_path_id| clip_path_id.0)
+                .into(),
+        ),
+        ("fill_rule", format!("{:?}", clip_path.fill_rule).into()),
+        ("outline", outline_value(&clip_path.outline)),
+    ])
+}
+
+fn draw_path_value(draw_path_id: u32, draw_path: &DrawPath) -> Value {
+    Value::Object(vec![
+        ("id", draw_path_id.into()),
+        ("name", draw_path.name.as_str().into()),
+        ("paint", draw_path.paint.0.into()),
+        (
+            "clip_path",
+            draw_path
+                .clip_path
+                .map(|clip_path_id| clip_path_id.0)
+                .into(),
+        ),
+        ("fill_rule", format!("{:?}", draw_path.fill_rule).into()),
+        ("blend_mode", format!("{:?}", draw_path.blend_mode).into()),
+        ("transform", draw_path.transform.into()),
+        ("outline", outline_value(&draw_path.outline)),
+    ])
+}
+
+fn outline_value(outline: &Outline) -> Value {
+    let contours = outline
+        .contours()
+        .iter()
+        .map(|contour| {
+            let segments = contour
+                .iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                .map(|segment| {
+                    let mut fields = vec![];
+                    match segment.kind {
+                        SegmentKind::None => fields.push(("kind", "none".into())),
+                        SegmentKind::Line => fields.push(("kind", "line".into())),
+                        SegmentKind::Quadratic => {
+                            fields.push(("kind", "quadratic".into()));
+                            fields.push(("ctrl", segment.ctrl.from().into()));
+                        }
+                        SegmentKind::Cubic => {
+                            fields.push(("kind", "cubic".into()));
+                            fields.push((
+                                "ctrl",
+                                Value::Array(vec![
+                                    segment.ctrl.from().into(),
+                                    segment.ctrl.to().into(),
+                                ]),
+                            ));
+                        }
+                    }
+                    fields.push(("from", segment.baseline.from().into()));
+                    fields.push(("to", segment.baseline.to().into()));
+                    Value::Object(fields)
+                })
+                .collect();
+            Value::Object(vec![
+                ("closed", contour.is_closed().into()),
+                ("segments", Value::Array(segments)),
+            ])
+        })
+        .collect();
+    Value::Array(contours)
+}
+
+// A JSON value, built up before it's written so that it can be laid out readably.
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+}
+
+impl Value {
+    fn is_scalar(&self) -> bool {
+        match *self {
+            Value::Array(_) | Value::Object(_) => false,
+            Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => true,
+        }
+    }
+
+    // Writes the value, indenting nested lines to the given depth. Arrays of scalars, such as
+    // points and transforms, are kept on one line.
+    fn write(&self, depth: usize, output: &mut String) {
+        match *self {
+            Value::Null => output.push_str("null"),
+            Value::Bool(value) => output.push_str(if value { "true" } else { "false" }),
+            // JSON has no infinities or NaNs.
+            Value::Number(value) if !value.is_finite() => output.push_str("null"),
+            Value::Number(value) => output.push_str(&value.to_string()),
+            Value::String(ref value) => write_string(value, output),
+            Value::Array(ref values) if values.is_empty() => output.push_str("[]"),
+            Value::Array(ref values) if values.iter().all(Value::is_scalar) => {
+                output.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        output.push_str(", ");
+                    }
+                    value.write(depth, output);
+                }
+                output.push(']');
+            }
+            Value::Array(ref values) => {
+                output.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    new_line(depth + 1, output);
+                    value.write(depth + 1, output);
+                }
+                new_line(depth, output);
+                output.push(']');
+            }
+            Value::Object(ref fields) if fields.is_empty() => output.push_str("{}"),
+            Value::Object(ref fields) => {
+                output.push('{');
+                for (index, &(name, ref value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    new_line(depth + 1, output);
+                    write_string(name, output);
+                    output.push_str(": ");
+                    value.write(depth + 1, output);
+                }
+                new_line(depth, output);
+                output.push('}');
+            }
+        }
+    }
+}
+
+fn new_line(depth: usize, output: &mut String) {
+    output.push('\n');
+    for _ in 0..depth {
+        output.push_str(INDENT);
+    }
+}
+
+fn write_string(value: &str, output: &mut String) {
+    output.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                output.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl From<u16> for Value {
+    fn from(value: u16) -> Value {
+        Value::Number(value.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Value {
+        Value::Number(value.into())
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Value {
+        Value::Number(value.into())
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Value {
+        // Go through the shortest decimal representation of the `f32`, so that values like 0.1
+        // aren't written with the noise of their conversion to `f64`.
+        Value::Number(value.to_string().parse().unwrap_or(f64::NAN))
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Value {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::String(value)
+    }
+}
+
+impl<T> From<Option<T>> for Value
+where
+    T: Into<Value>,
+{
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl From<Vector2F> for Value {
+    fn from(vector: Vector2F) -> Value {
+        Value::Array(vec![vector.x().into(), vector.y().into()])
+    }
+}
+
+impl From<Vector2I> for Value {
+    fn from(vector: Vector2I) -> Value {
+        Value::Array(vec![vector.x().into(), vector.y().into()])
+    }
+}
+
+impl From<RectF> for Value {
+    // Rects are written as `[x, y, width, height]`.
+    fn from(rect: RectF) -> Value {
+        Value::Array(vec![
+            rect.origin_x().into(),
+            rect.origin_y().into(),
+            rect.width().into(),
+            rect.height().into(),
+        ])
+    }
+}
+
+impl From<Transform2F> for Value {
+    // Transforms are written as `[a, b, c, d, e, f]`, as in SVG and CSS.
+    fn from(transform: Transform2F) -> Value {
+        Value::Array(vec![
+            transform.m11().into(),
+            transform.m21().into(),
+            transform.m12().into(),
+            transform.m22().into(),
+            transform.m13().into(),
+            transform.m23().into(),
+        ])
+    }
+}
+
+impl From<ColorU> for Value {
+    // Colors are written as `#rrggbbaa`.
+    fn from(color: ColorU) -> Value {
+        Value::String(format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r, color.g, color.b, color.a
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::export_json;
+    use crate::test::one_path_scene;
+    use pathfinder_renderer::scene::{DrawPathId, Scene};
+
+    fn export(scene: &Scene) -> String {
+        let mut json = vec![];
+        export_json(scene, &mut json).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    #[test]
+    fn json_export_of_one_path() {
+        let expected = r##"{
+  "view_box": [0, 0, 100, 50],
+  "display_list": [
+    {
+      "draw_paths": [0, 1]
+    }
+  ],
+  "paints": [
+    {
+      "id": 0,
+      "color": "#ff0000ff"
+    }
+  ],
+  "clip_paths": [],
+  "draw_paths": [
+    {
+      "id": 0,
+      "name": "",
+      "paint": 0,
+      "clip_path": null,
+      "fill_rule": "Winding",
+      "blend_mode": "SrcOver",
+      "transform": [1, 0, 0, 1, 0, 0],
+      "outline": [
+        {
+          "closed": true,
+          "segments": [
+            {
+              "kind": "line",
+              "from": [10, 10],
+              "to": [30, 10]
+            },
+            {
+              "kind": "line",
+              "from": [30, 10],
+              "to": [30, 20]
+            },
+            {
+              "kind": "line",
+              "from": [30, 20],
+              "to": [10, 20]
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+"##;
+        assert_eq!(export(&one_path_scene()), expected);
+    }
+
+    #[test]
+    fn json_export_escapes_names() {
+        let mut scene = one_path_scene();
+        let mut draw_path = scene.get_draw_path(DrawPathId(0)).clone();
+        draw_path.set_name("a \"quoted\"\\name\n\u{1}".to_owned());
+        scene.push_draw_path(draw_path);
+        assert!(export(&scene).contains(r#""name": "a \"quoted\"\\name\n\u0001","#));
+    }
+}
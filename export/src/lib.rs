@@ -8,26 +8,70 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use pathfinder_content::outline::ContourIterFlags;
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::GradientGeometry;
+use pathfinder_content::outline::{ContourIterFlags, Outline};
+use pathfinder_content::pattern::PatternSource;
 use pathfinder_content::segment::SegmentKind;
-use pathfinder_geometry::vector::{vec2f, Vector2F};
-use pathfinder_renderer::scene::{DrawPathId, Scene};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F, Vector2I};
+use pathfinder_renderer::scene::{DrawPathId, GlyphRun, RunGlyph, Scene};
+use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, Write};
+use std::iter;
 
+mod font;
+mod icc;
+mod json;
+mod page;
 mod pdf;
+mod png;
+mod svg;
+use json::export_json;
+use page::Decimal;
+pub use page::{Margins, Orientation, PageFit, PageOptions, PageSize};
 use pdf::Pdf;
+pub use pdf::{PdfConformance, PdfGradientError};
+use png::export_png;
+use svg::export_svg;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum FileFormat {
     /// Scalable Vector Graphics
     SVG,
 
     /// Portable Document Format
-    PDF,
+    PDF {
+        /// Whether to compress the document's streams. Turning this off makes the document much
+        /// larger, but readable in a text editor, which can help with debugging.
+        compressed: bool,
+        /// How the scene is placed on the page.
+        page: PageOptions,
+        /// The standard that the document conforms to.
+        conformance: PdfConformance,
+    },
 
     /// PostScript
-    PS,
+    PS {
+        /// How the scene is placed on the page.
+        page: PageOptions,
+    },
+
+    /// Portable Network Graphics, rendered on the CPU
+    PNG {
+        /// The size of the image in pixels. The view box is scaled to fill it.
+        size: Vector2I,
+        /// The color behind the scene, or `None` for a transparent background.
+        background: Option<ColorU>,
+    },
+
+    /// A human-readable JSON description of the scene's paths, paints, and clips, for debugging
+    /// and for comparing scenes
+    JSON,
 }
 
 pub trait Export {
@@ -38,168 +82,281 @@ impl Export for Scene {
     fn export<W: Write>(&self, writer: &mut W, format: FileFormat) -> io::Result<()> {
         match format {
             FileFormat::SVG => export_svg(self, writer),
-            FileFormat::PDF => export_pdf(self, writer),
-            FileFormat::PS => export_ps(self, writer),
+            FileFormat::PDF {
+                compressed,
+                page,
+                conformance,
+            } => export_pdf(self, writer, compressed, &page, conformance),
+            FileFormat::PS { page } => export_ps(self, writer, &page),
+            FileFormat::PNG { size, background } => export_png(self, writer, size, background),
+            FileFormat::JSON => export_json(self, writer),
         }
     }
 }
 
-fn export_svg<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
-    let view_box = scene.view_box();
-    writeln!(
-        writer,
-        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
-        view_box.origin().x(),
-        view_box.origin().y(),
-        view_box.size().x(),
-        view_box.size().y()
-    )?;
-    for draw_path_index in 0..scene.draw_path_count() {
-        let draw_path_id = DrawPathId(draw_path_index);
-        let draw_path = scene.get_draw_path(draw_path_id);
-        let paint = scene.get_paint(draw_path.paint);
-
-        write!(writer, "    <path")?;
-        if !draw_path.name.is_empty() {
-            write!(writer, " id=\"{}\"", draw_path.name)?;
-        }
-        writeln!(
-            writer,
-            " fill=\"{:?}\" d=\"{:?}\" />",
-            paint.base_color(),
-            draw_path.outline
-        )?;
+/// Writes the scenes as the pages of a single PDF document, in order.
+///
+/// Each scene is placed on its page according to the page options. The options have the same
+/// meaning as they do for `FileFormat::PDF`. Gradients that PDF can't draw fail the export
+/// with an `InvalidInput` error that wraps a `PdfGradientError`.
+pub fn export_pdf_pages<'a, W, I>(
+    scenes: I,
+    writer: &mut W,
+    compressed: bool,
+    page: &PageOptions,
+    conformance: PdfConformance,
+) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a Scene>,
+{
+    let mut pdf = Pdf::new();
+    pdf.set_compressed(compressed);
+    pdf.set_conformance(conformance);
+    pdf.set_precision(page.precision);
+    for scene in scenes {
+        add_pdf_page(&mut pdf, scene, page)?;
     }
-    writeln!(writer, "</svg>")?;
-    Ok(())
+    pdf.write_to(writer)
 }
 
-fn export_pdf<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
-    let mut pdf = Pdf::new();
-    let view_box = scene.view_box();
-    pdf.add_page(view_box.size());
+fn export_pdf<W: Write>(
+    scene: &Scene,
+    writer: &mut W,
+    compressed: bool,
+    page: &PageOptions,
+    conformance: PdfConformance,
+) -> io::Result<()> {
+    export_pdf_pages(iter::once(scene), writer, compressed, page, conformance)
+}
+
+fn add_pdf_page(pdf: &mut Pdf, scene: &Scene, page: &PageOptions) -> io::Result<()> {
+    let layout = page.layout(scene.view_box());
+    pdf.add_page(layout.size);
+
+    // PDF's y axis points up.
+    let flip_transform = Transform2F::from_translation(vec2f(0.0, layout.size.y()))
+        * Transform2F::from_scale(vec2f(1.0, -1.0));
+    let page_transform = flip_transform * layout.scene_transform;
+
+    // Keep content outside the view box out of the margins.
+    if layout.content_rect != RectF::new(Vector2F::zero(), layout.size) {
+        let content_outline = Outline::from_rect(layout.content_rect);
+        add_pdf_outline(pdf, &content_outline, flip_transform);
+        pdf.clip(FillRule::Winding);
+    }
 
-    let height = view_box.size().y();
-    let tr = |v: Vector2F| -> Vector2F {
-        let r = v - view_box.origin();
-        vec2f(r.x(), height - r.y())
-    };
+    // Glyphs that are filled with a color are written as text, so that it can be searched and
+    // copied.
+    let mut glyphs: HashMap<DrawPathId, (&GlyphRun, &RunGlyph)> = HashMap::new();
+    for glyph_run in scene.glyph_runs() {
+        for glyph in &glyph_run.glyphs {
+            glyphs.insert(glyph.draw_path, (glyph_run, glyph));
+        }
+    }
 
     for draw_path_index in 0..scene.draw_path_count() {
         let draw_path_id = DrawPathId(draw_path_index);
         let draw_path = scene.get_draw_path(draw_path_id);
 
-        // TODO(pcwalton): Gradients and patterns.
+        // Patterns drawn from render targets composite content that is also drawn directly, so
+        // they're skipped rather than painted over it.
         let paint = scene.get_paint(draw_path.paint);
-        if paint.is_color() {
-            pdf.set_fill_color(paint.base_color());
+        let image = paint.pattern().and_then(|pattern| match *pattern.source() {
+            PatternSource::Image(ref image) => Some((pattern, image)),
+            PatternSource::RenderTarget { .. } | PatternSource::ExternalTexture { .. } => None,
+        });
+        if paint.pattern().is_some() && image.is_none() {
+            continue;
         }
 
-        for contour in draw_path.outline.contours() {
-            for (segment_index, segment) in contour.iter(ContourIterFlags::empty()).enumerate() {
-                if segment_index == 0 {
-                    pdf.move_to(tr(segment.baseline.from()));
-                }
+        // Subpixel antialiased paths are always composited source-over.
+        let blend_mode = if draw_path.subpixel_coverage {
+            BlendMode::SrcOver
+        } else {
+            draw_path.blend_mode
+        };
 
-                match segment.kind {
-                    SegmentKind::None => {}
-                    SegmentKind::Line => pdf.line_to(tr(segment.baseline.to())),
-                    SegmentKind::Quadratic => {
-                        let current = segment.baseline.from();
-                        let c = segment.ctrl.from();
-                        let p = segment.baseline.to();
-                        let c1 = c * (2.0 / 3.0) + current * (1.0 / 3.0);
-                        let c2 = c * (2.0 / 3.0) + p * (1.0 / 3.0);
-                        pdf.cubic_to(c1, c2, p);
+        // Clips and blend modes are set in a saved graphics state, which is restored afterward,
+        // since clips can only be removed that way.
+        let saves_state = draw_path.clip_path.is_some() || blend_mode != BlendMode::SrcOver;
+        if saves_state {
+            pdf.save_state();
+        }
+        let mut clip_path_id = draw_path.clip_path;
+        while let Some(current_clip_path_id) = clip_path_id {
+            let clip_path = scene.get_clip_path(current_clip_path_id);
+            add_pdf_outline(pdf, &clip_path.outline, page_transform);
+            pdf.clip(clip_path.fill_rule);
+            clip_path_id = clip_path.clip_path;
+        }
+        pdf.set_blend_mode(blend_mode);
+
+        let shown_as_text = match glyphs.get(&draw_path_id) {
+            Some(&(glyph_run, glyph)) if paint.is_color() => {
+                match pdf.add_font(&glyph_run.font_data, glyph_run.font_index) {
+                    Some(font) => {
+                        let transform = page_transform
+                            * draw_path.transform
+                            * glyph_run.transform
+                            * Transform2F::from_translation(glyph.position)
+                            * Transform2F::from_scale(vec2f(
+                                glyph_run.font_size,
+                                -glyph_run.font_size,
+                            ));
+                        pdf.set_fill_alpha(paint.base_color().a);
+                        pdf.set_fill_color(paint.base_color());
+                        pdf.show_glyph(font, glyph.glyph_id, transform)
                     }
-                    SegmentKind::Cubic => pdf.cubic_to(
-                        tr(segment.ctrl.from()),
-                        tr(segment.ctrl.to()),
-                        tr(segment.baseline.to()),
-                    ),
+                    None => false,
                 }
             }
+            _ => false,
+        };
+
+        if !shown_as_text {
+            add_pdf_outline(
+                pdf,
+                &draw_path.outline,
+                page_transform * draw_path.transform,
+            );
 
-            if contour.is_closed() {
-                pdf.close();
+            // TODO: Noise and paint effects.
+            pdf.set_fill_alpha(paint.base_color().a);
+            if let Some(gradient) = paint.gradient() {
+                let shading = pdf
+                    .add_gradient_shading(gradient)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+                let gradient_transform = match gradient.geometry {
+                    GradientGeometry::Linear(_) => Transform2F::default(),
+                    GradientGeometry::Radial { transform, .. }
+                    | GradientGeometry::Conic { transform, .. } => transform,
+                };
+                pdf.fill_with_shading(
+                    draw_path.fill_rule,
+                    &shading,
+                    page_transform * gradient_transform,
+                );
+            } else if let Some((pattern, image)) = image {
+                // Image XObjects fill the unit square, with the first row at the top.
+                let size = image.size().to_f32();
+                let image_transform =
+                    Transform2F::row_major(size.x(), 0.0, 0.0, 0.0, -size.y(), size.y());
+                let image = pdf.add_image(image);
+                pdf.fill_with_image(
+                    draw_path.fill_rule,
+                    &image,
+                    page_transform * pattern.transform() * image_transform,
+                );
+            } else {
+                pdf.set_fill_color(paint.base_color());
+                pdf.fill(draw_path.fill_rule);
             }
         }
 
-        // closes implicitly
-        pdf.fill();
+        if saves_state {
+            pdf.restore_state();
+        }
     }
-    pdf.write_to(writer)
+    Ok(())
 }
 
-fn export_ps<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
-    struct P(Vector2F);
-    impl fmt::Display for P {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{} {}", self.0.x(), self.0.y())
+fn add_pdf_outline(pdf: &mut Pdf, outline: &Outline, transform: Transform2F) {
+    for contour in outline.contours() {
+        for (segment_index, segment) in contour.iter(ContourIterFlags::empty()).enumerate() {
+            if segment_index == 0 {
+                pdf.move_to(transform * segment.baseline.from());
+            }
+
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => pdf.line_to(transform * segment.baseline.to()),
+                SegmentKind::Quadratic => {
+                    let current = segment.baseline.from();
+                    let c = segment.ctrl.from();
+                    let p = segment.baseline.to();
+                    let c1 = c * (2.0 / 3.0) + current * (1.0 / 3.0);
+                    let c2 = c * (2.0 / 3.0) + p * (1.0 / 3.0);
+                    pdf.cubic_to(transform * c1, transform * c2, transform * p);
+                }
+                SegmentKind::Cubic => pdf.cubic_to(
+                    transform * segment.ctrl.from(),
+                    transform * segment.ctrl.to(),
+                    transform * segment.baseline.to(),
+                ),
+            }
+        }
+
+        if contour.is_closed() {
+            pdf.close();
         }
     }
+}
 
-    let view_box = scene.view_box();
+fn export_ps<W: Write>(scene: &Scene, writer: &mut W, page: &PageOptions) -> io::Result<()> {
+    let layout = page.layout(scene.view_box());
+    let precision = page.precision;
     writeln!(writer, "%!PS-Adobe-3.0 EPSF-3.0")?;
     writeln!(
         writer,
-        "%%BoundingBox: {:.0} {:.0}",
-        P(view_box.origin()),
-        P(view_box.size()),
+        "%%BoundingBox: 0 0 {:.0} {:.0}",
+        layout.size.x().ceil(),
+        layout.size.y().ceil()
     )?;
     writeln!(
         writer,
-        "%%HiResBoundingBox: {} {}",
-        P(view_box.origin()),
-        P(view_box.size()),
+        "%%HiResBoundingBox: 0 0 {} {}",
+        layout.size.x(),
+        layout.size.y()
     )?;
-    writeln!(writer, "0 {} translate", view_box.size().y())?;
+    writeln!(writer, "0 {} translate", layout.size.y())?;
     writeln!(writer, "1 -1 scale")?;
 
+    // Keep content outside the view box out of the margins.
+    if layout.content_rect != RectF::new(Vector2F::zero(), layout.size) {
+        writeln!(writer, "newpath")?;
+        let content_outline = Outline::from_rect(layout.content_rect);
+        write_ps_outline(writer, &content_outline, Transform2F::default(), precision)?;
+        writeln!(writer, "clip")?;
+    }
+
     for draw_path_index in 0..scene.draw_path_count() {
         let draw_path_id = DrawPathId(draw_path_index);
         let draw_path = scene.get_draw_path(draw_path_id);
+
+        // PostScript has no blend modes, but clips are set in a saved graphics state, which is
+        // restored afterward, since clips can only be removed that way.
+        if draw_path.clip_path.is_some() {
+            writeln!(writer, "gsave")?;
+        }
+        let mut clip_path_id = draw_path.clip_path;
+        while let Some(current_clip_path_id) = clip_path_id {
+            let clip_path = scene.get_clip_path(current_clip_path_id);
+            writeln!(writer, "newpath")?;
+            write_ps_outline(
+                writer,
+                &clip_path.outline,
+                layout.scene_transform,
+                precision,
+            )?;
+            match clip_path.fill_rule {
+                FillRule::Winding => writeln!(writer, "clip")?,
+                FillRule::EvenOdd => writeln!(writer, "eoclip")?,
+            }
+            clip_path_id = clip_path.clip_path;
+        }
+
         if !draw_path.name.is_empty() {
             writeln!(writer, "newpath % {}", draw_path.name)?;
         } else {
             writeln!(writer, "newpath")?;
         }
-
-        for contour in draw_path.outline.contours() {
-            for (segment_index, segment) in contour.iter(ContourIterFlags::empty()).enumerate() {
-                if segment_index == 0 {
-                    writeln!(writer, "{} moveto", P(segment.baseline.from()))?;
-                }
-
-                match segment.kind {
-                    SegmentKind::None => {}
-                    SegmentKind::Line => {
-                        writeln!(writer, "{} lineto", P(segment.baseline.to()))?;
-                    }
-                    SegmentKind::Quadratic => {
-                        let current = segment.baseline.from();
-                        let c = segment.ctrl.from();
-                        let p = segment.baseline.to();
-                        let c1 = c * (2.0 / 3.0) + current * (1.0 / 3.0);
-                        let c2 = c * (2.0 / 3.0) + p * (1.0 / 3.0);
-                        writeln!(writer, "{} {} {} curveto", P(c1), P(c2), P(p))?;
-                    }
-                    SegmentKind::Cubic => {
-                        writeln!(
-                            writer,
-                            "{} {} {} curveto",
-                            P(segment.ctrl.from()),
-                            P(segment.ctrl.to()),
-                            P(segment.baseline.to())
-                        )?;
-                    }
-                }
-            }
-
-            if contour.is_closed() {
-                writeln!(writer, "closepath")?;
-            }
-        }
+        write_ps_outline(
+            writer,
+            &draw_path.outline,
+            layout.scene_transform * draw_path.transform,
+            precision,
+        )?;
 
         // TODO(pcwalton): Gradients and patterns.
         let paint = scene.get_paint(draw_path.paint);
@@ -209,7 +366,265 @@ fn export_ps<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
         }
 
         writeln!(writer, "fill")?;
+        if draw_path.clip_path.is_some() {
+            writeln!(writer, "grestore")?;
+        }
     }
     writeln!(writer, "showpage")?;
     Ok(())
 }
+
+fn write_ps_outline<W: Write>(
+    writer: &mut W,
+    outline: &Outline,
+    transform: Transform2F,
+    precision: Option<u8>,
+) -> io::Result<()> {
+    let point = |point: Vector2F| P(transform * point, precision);
+    for contour in outline.contours() {
+        for (segment_index, segment) in contour.iter(ContourIterFlags::empty()).enumerate() {
+            if segment_index == 0 {
+                writeln!(writer, "{} moveto", point(segment.baseline.from()))?;
+            }
+
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => {
+                    writeln!(writer, "{} lineto", point(segment.baseline.to()))?;
+                }
+                SegmentKind::Quadratic => {
+                    let current = segment.baseline.from();
+                    let c = segment.ctrl.from();
+                    let p = segment.baseline.to();
+                    let c1 = c * (2.0 / 3.0) + current * (1.0 / 3.0);
+                    let c2 = c * (2.0 / 3.0) + p * (1.0 / 3.0);
+                    writeln!(writer, "{} {} {} curveto", point(c1), point(c2), point(p))?;
+                }
+                SegmentKind::Cubic => {
+                    writeln!(
+                        writer,
+                        "{} {} {} curveto",
+                        point(segment.ctrl.from()),
+                        point(segment.ctrl.to()),
+                        point(segment.baseline.to())
+                    )?;
+                }
+            }
+        }
+
+        if contour.is_closed() {
+            writeln!(writer, "closepath")?;
+        }
+    }
+    Ok(())
+}
+
+// Formats a point as PostScript operands, with at most the given number of decimal places.
+struct P(Vector2F, Option<u8>);
+
+impl fmt::Display for P {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            Decimal(self.0.x(), self.1),
+            Decimal(self.0.y(), self.1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{export_pdf_pages, Export, FileFormat, PageOptions, PdfConformance};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use pathfinder_renderer::paint::Paint;
+    use pathfinder_renderer::scene::{ClipPath, DrawPath, DrawPathId, Scene};
+
+    // A 100×50 scene holding one red rectangle from (10, 10) to (30, 20).
+    pub(crate) fn one_path_scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 50.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let outline = Outline::from_rect(RectF::new(vec2f(10.0, 10.0), vec2f(20.0, 10.0)));
+        scene.push_draw_path(DrawPath::new(outline, paint));
+        scene
+    }
+
+    fn export_uncompressed_pdf(scene: &Scene) -> Vec<u8> {
+        let mut pdf = vec![];
+        let format = FileFormat::PDF {
+            compressed: false,
+            page: PageOptions::default(),
+            conformance: PdfConformance::Standard,
+        };
+        scene.export(&mut pdf, format).unwrap();
+        pdf
+    }
+
+    // Returns the contents of the first stream in the document.
+    fn first_stream(pdf: &str) -> &str {
+        let start = pdf.find("stream\n").unwrap() + "stream\n".len();
+        let end = start + pdf[start..].find("endstream").unwrap();
+        pdf[start..end].trim_end()
+    }
+
+    #[test]
+    fn pdf_export_of_one_path() {
+        let pdf = export_uncompressed_pdf(&one_path_scene());
+        assert!(pdf.starts_with(b"%PDF-1.7\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // The page is the size of the view box, and y points up.
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/MediaBox [0 0 100 50]"));
+        let operators: Vec<&str> = first_stream(&pdf).lines().collect();
+        assert_eq!(
+            operators,
+            [
+                "/DeviceRGB cs /DeviceRGB CS",
+                "1 j 1 J",
+                "10 40 m",
+                "30 40 l",
+                "30 30 l",
+                "10 30 l",
+                "10 40 l",
+                "h",
+                "1 0 0 rg",
+                "f",
+            ]
+        );
+    }
+
+    #[test]
+    fn pdf_export_cross_reference_table_points_at_objects() {
+        let pdf = export_uncompressed_pdf(&one_path_scene());
+        let text = String::from_utf8_lossy(&pdf);
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        // The header's comment isn't UTF-8, so offsets have to index the bytes.
+        let xref = std::str::from_utf8(&pdf[startxref..]).unwrap();
+        assert!(xref.starts_with("xref\n"));
+
+        let mut lines = xref.lines().skip(1);
+        let count: usize = lines
+            .next()
+            .unwrap()
+            .split(' ')
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for object_number in 1..count {
+            let offset: usize = lines.next().unwrap()[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", object_number);
+            assert!(pdf[offset..].starts_with(header.as_bytes()));
+        }
+    }
+
+    // Clips the rectangle of `one_path_scene()` to its left half and multiplies it.
+    fn clipped_multiply_scene() -> Scene {
+        let mut scene = one_path_scene();
+        let clip_rect = RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 50.0));
+        let clip_path = scene.push_clip_path(ClipPath::new(Outline::from_rect(clip_rect)));
+        let mut draw_path = scene.get_draw_path(DrawPathId(0)).clone();
+        draw_path.set_clip_path(Some(clip_path));
+        draw_path.set_blend_mode(BlendMode::Multiply);
+        scene.push_draw_path(draw_path);
+        scene
+    }
+
+    fn export_to_string(scene: &Scene, format: FileFormat) -> String {
+        let mut output = vec![];
+        scene.export(&mut output, format).unwrap();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    #[test]
+    fn pdf_pages_are_the_sizes_of_their_scenes() {
+        let mut tall_scene = one_path_scene();
+        tall_scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(40.0, 80.0)));
+        let mut pdf = vec![];
+        export_pdf_pages(
+            vec![&one_path_scene(), &tall_scene],
+            &mut pdf,
+            false,
+            &PageOptions::default(),
+            PdfConformance::Standard,
+        )
+        .unwrap();
+
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/Count 2\n"));
+        let media_boxes: Vec<_> = pdf.matches("/MediaBox [0 0 ").collect();
+        assert_eq!(media_boxes.len(), 2);
+        assert!(pdf.contains("/MediaBox [0 0 100 50]"));
+        assert!(pdf.contains("/MediaBox [0 0 40 80]"));
+    }
+
+    #[test]
+    fn compressed_pdf_streams_are_flate_encoded() {
+        let format = FileFormat::PDF {
+            compressed: true,
+            page: PageOptions::default(),
+            conformance: PdfConformance::Standard,
+        };
+        let pdf = export_to_string(&one_path_scene(), format);
+        assert!(pdf.contains("/Filter [/FlateDecode]"));
+        assert!(!pdf.contains("10 40 m"));
+    }
+
+    #[test]
+    fn pdf_export_clips_and_blends_in_a_saved_state() {
+        let pdf = export_uncompressed_pdf(&clipped_multiply_scene());
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("<< /Type /ExtGState /ca 1 /BM /Multiply >>"));
+
+        let operators: Vec<&str> = first_stream(&pdf).lines().collect();
+        let clipped_path = operators.iter().rposition(|&op| op == "q").unwrap();
+        assert_eq!(
+            operators[clipped_path..],
+            [
+                "q", "0 50 m", "20 50 l", "20 0 l", "0 0 l", "0 50 l", "h", "W n", "/GS0 gs",
+                "10 40 m", "30 40 l", "30 30 l", "10 30 l", "10 40 l", "h", "f", "Q",
+            ]
+        );
+    }
+
+    #[test]
+    fn pdf_a_documents_declare_an_output_intent() {
+        let format = FileFormat::PDF {
+            compressed: false,
+            page: PageOptions::default(),
+            conformance: PdfConformance::PdfA2b,
+        };
+        let pdf = export_to_string(&one_path_scene(), format);
+        assert!(pdf.contains("/OutputIntents ["));
+        assert!(pdf.contains("/Metadata "));
+        let pdf = export_uncompressed_pdf(&one_path_scene());
+        assert!(!String::from_utf8_lossy(&pdf).contains("/OutputIntents"));
+    }
+
+    #[test]
+    fn ps_export_clips_in_a_saved_state() {
+        let format = FileFormat::PS {
+            page: PageOptions::default(),
+        };
+        let ps = export_to_string(&clipped_multiply_scene(), format);
+        assert!(ps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 100 50\n"));
+        assert!(ps.ends_with("fill\ngrestore\nshowpage\n"));
+        assert!(ps.contains(
+            "gsave\nnewpath\n0 0 moveto\n20 0 lineto\n20 50 lineto\n0 50 lineto\n\
+             0 0 lineto\nclosepath\nclip\nnewpath\n10 10 moveto\n"
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+// pathfinder/export/src/page.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Placement of scenes on the pages of PDF and PostScript documents.
+
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use std::fmt::{self, Display, Formatter};
+
+/// How a scene is placed on a page.
+///
+/// All lengths are in points, 1/72 of an inch.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PageOptions {
+    /// The size of the page.
+    pub size: PageSize,
+    /// Which way up fixed-size pages are.
+    pub orientation: Orientation,
+    /// The space between the edges of the page and the scene.
+    pub margins: Margins,
+    /// How the scene's view box is scaled into the space inside the margins.
+    pub fit: PageFit,
+    /// How many decimal places coordinates are written with, or `None` for as many as are needed
+    /// to represent them exactly. Fewer places make smaller files.
+    pub precision: Option<u8>,
+}
+
+/// The size of a page.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PageSize {
+    /// The size of the scaled view box, plus the margins.
+    ViewBox,
+    /// ISO A4, 210 × 297 mm.
+    A4,
+    /// US Letter, 8.5 × 11 inches.
+    Letter,
+    /// A custom size, in points, before the orientation is applied.
+    Custom(Vector2F),
+}
+
+/// Which way up a fixed-size page is.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Orientation {
+    /// The page is at least as tall as it is wide.
+    Portrait,
+    /// The page is at least as wide as it is tall.
+    Landscape,
+}
+
+/// The space between each edge of the page and the scene, in points.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+/// How a scene's view box is scaled onto a page.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PageFit {
+    /// Each scene unit is a pixel at the given resolution, in dots per inch, with the view box
+    /// placed at the top left of the space inside the margins. Content that doesn't fit is cut
+    /// off.
+    Resolution(f32),
+    /// The view box is scaled uniformly to the largest size that fits inside the margins, and
+    /// centered.
+    Contain,
+    /// The view box is stretched to fill the space inside the margins.
+    Stretch,
+}
+
+impl Default for PageOptions {
+    /// Returns options that make the page the size of the view box, with one scene unit per
+    /// point.
+    #[inline]
+    fn default() -> PageOptions {
+        PageOptions {
+            size: PageSize::ViewBox,
+            orientation: Orientation::Portrait,
+            margins: Margins::default(),
+            fit: PageFit::Resolution(72.0),
+            precision: None,
+        }
+    }
+}
+
+impl Margins {
+    /// Returns margins that are the same on every side.
+    #[inline]
+    pub fn uniform(margin: f32) -> Margins {
+        Margins {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+}
+
+impl PageOptions {
+    // Works out where the scene with the given view box goes on the page.
+    pub(crate) fn layout(&self, view_box: RectF) -> PageLayout {
+        let margins = &self.margins;
+        let margin_origin = vec2f(margins.left, margins.top);
+        let margin_size = margin_origin + vec2f(margins.right, margins.bottom);
+
+        let fixed_size = match self.size {
+            PageSize::ViewBox => None,
+            // 210 × 297 mm.
+            PageSize::A4 => Some(vec2f(595.2756, 841.8898)),
+            PageSize::Letter => Some(vec2f(612.0, 792.0)),
+            PageSize::Custom(size) => Some(size),
+        };
+        let fixed_size = fixed_size.map(|size| {
+            let landscape = self.orientation == Orientation::Landscape;
+            if landscape == (size.x() < size.y()) {
+                size.yx()
+            } else {
+                size
+            }
+        });
+
+        // Pages sized to the view box fit it exactly, unless it's drawn at a resolution.
+        let (size, scale) = match (fixed_size, self.fit) {
+            (None, PageFit::Resolution(dpi)) => {
+                let scale = 72.0 / dpi;
+                (
+                    view_box.size() * scale + margin_size,
+                    Vector2F::splat(scale),
+                )
+            }
+            (None, PageFit::Contain) | (None, PageFit::Stretch) => {
+                (view_box.size() + margin_size, Vector2F::splat(1.0))
+            }
+            (Some(size), PageFit::Resolution(dpi)) => (size, Vector2F::splat(72.0 / dpi)),
+            (Some(size), PageFit::Contain) => {
+                let scale = (size - margin_size).max(Vector2F::zero()) / view_box.size();
+                (size, Vector2F::splat(scale.x().min(scale.y())))
+            }
+            (Some(size), PageFit::Stretch) => {
+                let scale = (size - margin_size).max(Vector2F::zero()) / view_box.size();
+                (size, scale)
+            }
+        };
+
+        let area = RectF::new(margin_origin, (size - margin_size).max(Vector2F::zero()));
+        let origin = match self.fit {
+            PageFit::Resolution(_) | PageFit::Stretch => area.origin(),
+            PageFit::Contain => area.origin() + (area.size() - view_box.size() * scale) * 0.5,
+        };
+        let scene_transform = Transform2F::from_translation(origin)
+            * Transform2F::from_scale(scale)
+            * Transform2F::from_translation(-view_box.origin());
+        let content_rect = (scene_transform * view_box)
+            .intersection(area)
+            .unwrap_or_default();
+
+        PageLayout {
+            size,
+            scene_transform,
+            content_rect,
+        }
+    }
+}
+
+pub(crate) struct PageLayout {
+    /// The size of the page, in points.
+    pub(crate) size: Vector2F,
+    /// The transform from the scene to the page, with the origin at the top left and y pointing
+    /// down.
+    pub(crate) scene_transform: Transform2F,
+    /// The part of the page that the view box covers inside the margins, which content is clipped
+    /// to, in the same space as the page.
+    pub(crate) content_rect: RectF,
+}
+
+/// Formats a coordinate with at most the given number of decimal places.
+pub(crate) struct Decimal(pub(crate) f32, pub(crate) Option<u8>);
+
+impl Display for Decimal {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let places = match self.1 {
+            None => return write!(formatter, "{}", self.0),
+            Some(places) => places as usize,
+        };
+        let string = format!("{:.*}", places, self.0);
+        let string = if string.contains('.') {
+            string.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &string
+        };
+        match string {
+            "-0" => formatter.write_str("0"),
+            string => formatter.write_str(string),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Decimal, Margins, Orientation, PageFit, PageOptions, PageSize};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    fn view_box() -> RectF {
+        RectF::new(vec2f(10.0, 20.0), vec2f(200.0, 100.0))
+    }
+
+    #[test]
+    fn view_box_pages_scale_with_the_resolution() {
+        let options = PageOptions {
+            fit: PageFit::Resolution(144.0),
+            margins: Margins::uniform(5.0),
+            ..PageOptions::default()
+        };
+        let layout = options.layout(view_box());
+        assert_eq!(layout.size, vec2f(110.0, 60.0));
+        assert_eq!(layout.scene_transform * vec2f(10.0, 20.0), vec2f(5.0, 5.0));
+        assert_eq!(
+            layout.scene_transform * vec2f(210.0, 120.0),
+            vec2f(105.0, 55.0)
+        );
+        assert_eq!(
+            layout.content_rect,
+            RectF::new(vec2f(5.0, 5.0), vec2f(100.0, 50.0))
+        );
+    }
+
+    #[test]
+    fn fixed_size_pages_follow_the_orientation() {
+        let mut options = PageOptions {
+            size: PageSize::Letter,
+            orientation: Orientation::Landscape,
+            ..PageOptions::default()
+        };
+        assert_eq!(options.layout(view_box()).size, vec2f(792.0, 612.0));
+
+        // Custom sizes are turned to match the orientation too.
+        options.size = PageSize::Custom(vec2f(300.0, 200.0));
+        options.orientation = Orientation::Portrait;
+        assert_eq!(options.layout(view_box()).size, vec2f(200.0, 300.0));
+    }
+
+    #[test]
+    fn contained_scenes_are_centered() {
+        let options = PageOptions {
+            size: PageSize::Custom(vec2f(420.0, 420.0)),
+            margins: Margins::uniform(10.0),
+            fit: PageFit::Contain,
+            ..PageOptions::default()
+        };
+        let layout = options.layout(view_box());
+        assert_eq!(
+            layout.content_rect,
+            RectF::new(vec2f(10.0, 110.0), vec2f(400.0, 200.0))
+        );
+    }
+
+    #[test]
+    fn stretched_scenes_fill_the_margins() {
+        let options = PageOptions {
+            size: PageSize::Custom(vec2f(400.0, 400.0)),
+            fit: PageFit::Stretch,
+            ..PageOptions::default()
+        };
+        let layout = options.layout(view_box());
+        assert_eq!(
+            layout.scene_transform * vec2f(210.0, 120.0),
+            vec2f(400.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn decimals_are_trimmed() {
+        assert_eq!(Decimal(2.5, None).to_string(), "2.5");
+        assert_eq!(Decimal(2.5, Some(3)).to_string(), "2.5");
+        assert_eq!(Decimal(1.23456, Some(2)).to_string(), "1.23");
+        assert_eq!(Decimal(3.999, Some(2)).to_string(), "4");
+        assert_eq!(Decimal(-0.001, Some(2)).to_string(), "0");
+        assert_eq!(Decimal(120.0, Some(0)).to_string(), "120");
+    }
+}
@@ -11,10 +11,40 @@
 //! This is a heavily modified version of the pdfpdf crate by Benjamin Kimock <kimockb@gmail.com>
 //! (aka. saethlin)
 
+use crate::font::Font;
+use crate::icc;
+use crate::page::Decimal;
 use deflate::Compression;
 use pathfinder_color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientGeometry, GradientWrap};
+use pathfinder_content::pattern::Image;
+use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hasher;
 use std::io::{self, Write};
+use std::mem;
+use std::sync::Arc;
+
+// How many mappings a ToUnicode CMap may list in one `bfchar` block.
+const MAX_BFCHAR_ENTRIES: usize = 100;
+
+// The XMP metadata that identifies a document as PDF/A-2b.
+const PDF_A_METADATA: &str = "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">
+  <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">
+    <rdf:Description rdf:about=\"\" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">
+      <pdfaid:part>2</pdfaid:part>
+      <pdfaid:conformance>B</pdfaid:conformance>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end=\"w\"?>";
 
 struct Counter<T> {
     inner: T,
@@ -53,16 +83,102 @@ impl<W: Write> Write for Counter<W> {
 struct PdfObject {
     contents: Vec<u8>,
     is_page: bool,
-    is_xobject: bool,
     offset: Option<u64>,
 }
 
+/// A font embedded in the document
+struct PdfFont {
+    font: Font,
+    /// The font dictionary, which is written once all the glyphs used are known
+    object_id: usize,
+    glyph_ids: BTreeSet<u16>,
+}
+
+/// A standard that a PDF document can be written to conform to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PdfConformance {
+    /// Plain PDF 1.7.
+    Standard,
+    /// PDF/A-2b, for archiving. The document declares its colors to be sRGB and carries the XMP
+    /// metadata that identifies it as PDF/A.
+    PdfA2b,
+}
+
+impl Default for PdfConformance {
+    #[inline]
+    fn default() -> PdfConformance {
+        PdfConformance::Standard
+    }
+}
+
+/// The reasons why a gradient can't be exported to PDF.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PdfGradientError {
+    /// PDF has no conic shading.
+    Conic,
+    /// Shadings can only extend their end colors, not repeat.
+    Repeating,
+    /// A color stop is translucent, which would need a soft mask.
+    TranslucentColorStop,
+}
+
+impl Display for PdfGradientError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            PdfGradientError::Conic => write!(formatter, "PDF can't draw conic gradients"),
+            PdfGradientError::Repeating => {
+                write!(formatter, "PDF can't draw repeating gradients")
+            }
+            PdfGradientError::TranslucentColorStop => write!(
+                formatter,
+                "PDF can't draw gradients with translucent color stops"
+            ),
+        }
+    }
+}
+
+impl Error for PdfGradientError {}
+
 /// The top-level struct that represents a (partially) in-memory PDF file
 pub struct Pdf {
     page_buffer: Vec<u8>,
     objects: Vec<PdfObject>,
     page_size: Option<Vector2F>,
     compression: Option<Compression>,
+    precision: Option<u8>,
+    conformance: PdfConformance,
+    /// Resources used by the current page, as (name, object ID) pairs
+    page_shadings: Vec<(String, usize)>,
+    page_images: Vec<(String, usize)>,
+    page_graphics_states: Vec<(String, usize)>,
+    page_fonts: Vec<(String, usize)>,
+    fonts: Vec<PdfFont>,
+    /// Indices into `fonts`, by the address of the font data and the index of the font in it
+    font_indices: HashMap<(usize, u32), Option<usize>>,
+    /// Graphics state resources, by the fill alpha and blend mode that they set
+    graphics_state_names: HashMap<(u8, &'static str), String>,
+    state: GraphicsState,
+    saved_states: Vec<GraphicsState>,
+}
+
+/// The parts of the graphics state that are only written when they change
+#[derive(Clone, Copy)]
+struct GraphicsState {
+    /// The fill color, ignoring alpha
+    fill_color: ColorU,
+    fill_alpha: u8,
+    /// The name of the PDF blend mode
+    blend_mode: &'static str,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        GraphicsState {
+            fill_color: ColorU::black(),
+            fill_alpha: 255,
+            blend_mode: "Normal",
+        }
+    }
 }
 
 impl Default for Pdf {
@@ -81,34 +197,100 @@ impl Pdf {
                 PdfObject {
                     contents: Vec::new(),
                     is_page: false,
-                    is_xobject: false,
                     offset: None,
                 },
                 PdfObject {
                     contents: Vec::new(),
                     is_page: false,
-                    is_xobject: false,
                     offset: None,
                 },
             ],
             page_size: None,
-            compression: Some(Compression::Fast),
+            compression: Some(Compression::Default),
+            precision: None,
+            conformance: PdfConformance::Standard,
+            page_shadings: vec![],
+            page_images: vec![],
+            page_graphics_states: vec![],
+            page_fonts: vec![],
+            fonts: vec![],
+            font_indices: HashMap::new(),
+            graphics_state_names: HashMap::new(),
+            state: GraphicsState::default(),
+            saved_states: vec![],
         }
     }
 
-    fn add_object(&mut self, data: Vec<u8>, is_page: bool, is_xobject: bool) -> usize {
+    /// Set how many decimal places coordinates are written with, or `None` for full precision
+    pub fn set_precision(&mut self, precision: Option<u8>) {
+        self.precision = precision;
+    }
+
+    /// Set the standard that the document conforms to
+    pub fn set_conformance(&mut self, conformance: PdfConformance) {
+        self.conformance = conformance;
+    }
+
+    /// Set whether streams are compressed, which they are by default
+    ///
+    /// Uncompressed documents are much larger, but can be read in a text editor.
+    pub fn set_compressed(&mut self, compressed: bool) {
+        self.compression = if compressed {
+            Some(Compression::Default)
+        } else {
+            None
+        };
+    }
+
+    fn add_object(&mut self, data: Vec<u8>, is_page: bool) -> usize {
         self.objects.push(PdfObject {
             contents: data,
             is_page,
-            is_xobject,
             offset: None,
         });
         self.objects.len()
     }
 
+    /// Wraps data in a stream object, compressing it if enabled
+    fn add_stream_object(&mut self, dictionary: &str, data: &[u8]) -> usize {
+        let mut object = Vec::new();
+        let dictionary = if dictionary.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", dictionary)
+        };
+        if let Some(level) = self.compression {
+            let compressed = deflate::deflate_bytes_zlib_conf(data, level);
+            write!(
+                object,
+                "<< {}/Length {} /Filter [/FlateDecode] >>\nstream\n",
+                dictionary,
+                compressed.len()
+            )
+            .unwrap();
+            object.extend_from_slice(&compressed);
+        } else {
+            write!(
+                object,
+                "<< {}/Length {} >>\nstream\n",
+                dictionary,
+                data.len()
+            )
+            .unwrap();
+            object.extend_from_slice(data);
+        }
+        object.extend(b"\nendstream\n");
+        self.add_object(object, false)
+    }
+
     /// Set the color for all subsequent drawing operations
     #[inline]
     pub fn set_fill_color(&mut self, color: ColorU) {
+        let color = ColorU { a: 255, ..color };
+        if color == self.state.fill_color {
+            return;
+        }
+        self.state.fill_color = color;
         let norm = |color| f32::from(color) / 255.0;
         writeln!(
             self.page_buffer,
@@ -120,6 +302,202 @@ impl Pdf {
         .unwrap();
     }
 
+    /// Set the alpha for all subsequent fills, including shadings and images
+    pub fn set_fill_alpha(&mut self, alpha: u8) {
+        self.set_graphics_state(alpha, self.state.blend_mode);
+    }
+
+    /// Set the blend mode for all subsequent fills
+    ///
+    /// Porter-Duff modes other than source-over have no PDF equivalent and draw as source-over.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.set_graphics_state(self.state.fill_alpha, pdf_blend_mode(blend_mode));
+    }
+
+    fn set_graphics_state(&mut self, alpha: u8, blend_mode: &'static str) {
+        if alpha == self.state.fill_alpha && blend_mode == self.state.blend_mode {
+            return;
+        }
+        let name = match self.graphics_state_names.get(&(alpha, blend_mode)) {
+            Some(name) => name.clone(),
+            None => {
+                let dictionary = format!(
+                    "<< /Type /ExtGState /ca {} /BM /{} >>\n",
+                    f32::from(alpha) / 255.0,
+                    blend_mode
+                );
+                let object_id = self.add_object(dictionary.into_bytes(), false);
+                let name = format!("GS{}", self.page_graphics_states.len());
+                self.page_graphics_states.push((name.clone(), object_id));
+                self.graphics_state_names
+                    .insert((alpha, blend_mode), name.clone());
+                name
+            }
+        };
+        writeln!(self.page_buffer, "/{} gs", name).unwrap();
+        self.state.fill_alpha = alpha;
+        self.state.blend_mode = blend_mode;
+    }
+
+    /// Save the graphics state, to be restored by a matching `restore_state()`
+    pub fn save_state(&mut self) {
+        writeln!(self.page_buffer, "q").unwrap();
+        self.saved_states.push(self.state);
+    }
+
+    /// Restore the graphics state saved by the last `save_state()`, which removes any clips set
+    /// since
+    pub fn restore_state(&mut self) {
+        if let Some(state) = self.saved_states.pop() {
+            writeln!(self.page_buffer, "Q").unwrap();
+            self.state = state;
+        }
+    }
+
+    /// Intersect the clip with the current path, and end the path
+    pub fn clip(&mut self, fill_rule: FillRule) {
+        match fill_rule {
+            FillRule::Winding => writeln!(self.page_buffer, "W n").unwrap(),
+            FillRule::EvenOdd => writeln!(self.page_buffer, "W* n").unwrap(),
+        }
+    }
+
+    /// Add a shading for the gradient to the current page, returning its resource name
+    ///
+    /// The shading is in gradient space; for radial gradients, that's mapped to the scene by
+    /// the gradient's transform. Conic and repeating gradients can't be expressed as a shading,
+    /// and neither can translucent color stops without a soft mask, so those are rejected.
+    pub fn add_gradient_shading(
+        &mut self,
+        gradient: &Gradient,
+    ) -> Result<String, PdfGradientError> {
+        if gradient.wrap == GradientWrap::Repeat {
+            return Err(PdfGradientError::Repeating);
+        }
+        if !gradient.is_opaque() {
+            return Err(PdfGradientError::TranslucentColorStop);
+        }
+        let (shading_type, coords) = match gradient.geometry {
+            GradientGeometry::Linear(line) => (
+                2,
+                format!(
+                    "{} {} {} {}",
+                    line.from_x(),
+                    line.from_y(),
+                    line.to_x(),
+                    line.to_y()
+                ),
+            ),
+            GradientGeometry::Radial { line, radii, .. } => (
+                3,
+                format!(
+                    "{} {} {} {} {} {}",
+                    line.from_x(),
+                    line.from_y(),
+                    radii.x(),
+                    line.to_x(),
+                    line.to_y(),
+                    radii.y()
+                ),
+            ),
+            GradientGeometry::Conic { .. } => return Err(PdfGradientError::Conic),
+        };
+
+        let dictionary = format!(
+            "<< /ShadingType {} /ColorSpace /DeviceRGB /Coords [{}] /Function {} \
+             /Extend [true true] >>\n",
+            shading_type,
+            coords,
+            stitching_function(gradient.stops())
+        );
+        let object_id = self.add_object(dictionary.into_bytes(), false);
+        let name = format!("Sh{}", self.page_shadings.len());
+        self.page_shadings.push((name.clone(), object_id));
+        Ok(name)
+    }
+
+    /// Add an image to the current page, returning its resource name
+    pub fn add_image(&mut self, image: &Image) -> String {
+        let size = image.size();
+        let mut colors = Vec::with_capacity(image.pixels().len() * 3);
+        let mut alphas = Vec::with_capacity(image.pixels().len());
+        for pixel in image.pixels().iter() {
+            colors.extend_from_slice(&[pixel.r, pixel.g, pixel.b]);
+            alphas.push(pixel.a);
+        }
+
+        let mut dictionary = format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8",
+            size.x(),
+            size.y()
+        );
+        if !image.is_opaque() {
+            let mask_dictionary = format!("{} /ColorSpace /DeviceGray", dictionary);
+            let mask_id = self.add_stream_object(&mask_dictionary, &alphas);
+            dictionary.push_str(&format!(" /SMask {} 0 R", mask_id));
+        }
+        dictionary.push_str(" /ColorSpace /DeviceRGB");
+        let object_id = self.add_stream_object(&dictionary, &colors);
+
+        let name = format!("Im{}", self.page_images.len());
+        self.page_images.push((name.clone(), object_id));
+        name
+    }
+
+    /// Add a font to the document, returning its index, or `None` if it can't be embedded
+    pub fn add_font(&mut self, data: &Arc<Vec<u8>>, font_index: u32) -> Option<usize> {
+        let key = (&**data as *const Vec<u8> as usize, font_index);
+        if let Some(&index) = self.font_indices.get(&key) {
+            return index;
+        }
+        let index = Font::parse(data.clone(), font_index).map(|font| {
+            let object_id = self.add_object(vec![], false);
+            self.fonts.push(PdfFont {
+                font,
+                object_id,
+                glyph_ids: BTreeSet::new(),
+            });
+            self.fonts.len() - 1
+        });
+        self.font_indices.insert(key, index);
+        index
+    }
+
+    /// Draw a glyph of a font with the current fill color, returning false if the font doesn't
+    /// have the glyph
+    ///
+    /// The transform maps the glyph's em square, with the origin on the baseline and y pointing
+    /// up, to the page.
+    pub fn show_glyph(&mut self, font: usize, glyph_id: u32, transform: Transform2F) -> bool {
+        let pdf_font = &mut self.fonts[font];
+        if glyph_id >= u32::from(pdf_font.font.glyph_count()) {
+            return false;
+        }
+        // PDF/A forbids showing `.notdef`.
+        if glyph_id == 0 && self.conformance == PdfConformance::PdfA2b {
+            return false;
+        }
+        pdf_font.glyph_ids.insert(glyph_id as u16);
+
+        let name = format!("F{}", font);
+        if self
+            .page_fonts
+            .iter()
+            .all(|(page_font, _)| *page_font != name)
+        {
+            self.page_fonts.push((name.clone(), pdf_font.object_id));
+        }
+        writeln!(
+            self.page_buffer,
+            "BT /{} 1 Tf {} Tm <{:04x}> Tj ET",
+            name,
+            Matrix(transform, self.precision),
+            glyph_id
+        )
+        .unwrap();
+        true
+    }
+
     /// Move to a new page in the PDF document
     #[inline]
     pub fn add_page(&mut self, size: Vector2F) {
@@ -135,28 +513,64 @@ impl Pdf {
     }
 
     pub fn move_to(&mut self, p: Vector2F) {
-        writeln!(self.page_buffer, "{} {} m", p.x(), p.y()).unwrap();
+        let precision = self.precision;
+        writeln!(self.page_buffer, "{} m", Point(p, precision)).unwrap();
     }
 
     pub fn line_to(&mut self, p: Vector2F) {
-        writeln!(self.page_buffer, "{} {} l", p.x(), p.y()).unwrap();
+        let precision = self.precision;
+        writeln!(self.page_buffer, "{} l", Point(p, precision)).unwrap();
     }
 
     pub fn cubic_to(&mut self, c1: Vector2F, c2: Vector2F, p: Vector2F) {
+        let precision = self.precision;
         writeln!(
             self.page_buffer,
-            "{} {} {} {} {} {} c",
-            c1.x(),
-            c1.y(),
-            c2.x(),
-            c2.y(),
-            p.x(),
-            p.y()
+            "{} {} {} c",
+            Point(c1, precision),
+            Point(c2, precision),
+            Point(p, precision)
         )
         .unwrap();
     }
-    pub fn fill(&mut self) {
-        writeln!(self.page_buffer, "f").unwrap();
+    pub fn fill(&mut self, fill_rule: FillRule) {
+        match fill_rule {
+            FillRule::Winding => writeln!(self.page_buffer, "f").unwrap(),
+            FillRule::EvenOdd => writeln!(self.page_buffer, "f*").unwrap(),
+        }
+    }
+
+    /// Fill the current path with a shading, placed on the page by the given transform
+    pub fn fill_with_shading(
+        &mut self,
+        fill_rule: FillRule,
+        shading: &str,
+        transform: Transform2F,
+    ) {
+        self.clip_and_transform(fill_rule, transform);
+        writeln!(self.page_buffer, "/{} sh Q", shading).unwrap();
+    }
+
+    /// Fill the current path with an image, placed on the page by the given transform
+    ///
+    /// The transform maps the unit square to the image's place on the page.
+    pub fn fill_with_image(&mut self, fill_rule: FillRule, image: &str, transform: Transform2F) {
+        self.clip_and_transform(fill_rule, transform);
+        writeln!(self.page_buffer, "/{} Do Q", image).unwrap();
+    }
+
+    fn clip_and_transform(&mut self, fill_rule: FillRule, transform: Transform2F) {
+        let clip = match fill_rule {
+            FillRule::Winding => "W",
+            FillRule::EvenOdd => "W*",
+        };
+        writeln!(
+            self.page_buffer,
+            "q {} n {} cm",
+            clip,
+            Matrix(transform, self.precision)
+        )
+        .unwrap();
     }
 
     pub fn close(&mut self) {
@@ -168,26 +582,9 @@ impl Pdf {
             Some(size) => size,
             None => return, // no page started
         };
-        let page_stream = if let Some(level) = self.compression {
-            let compressed = deflate::deflate_bytes_zlib_conf(&self.page_buffer, level);
-            let mut page = format!(
-                "<< /Length {} /Filter [/FlateDecode] >>\nstream\n",
-                compressed.len()
-            )
-            .into_bytes();
-            page.extend_from_slice(&compressed);
-            page.extend(b"endstream\n");
-            page
-        } else {
-            let mut page = Vec::new();
-            page.extend(format!("<< /Length {} >>\nstream\n", self.page_buffer.len()).bytes());
-            page.extend(&self.page_buffer);
-            page.extend(b"endstream\n");
-            page
-        };
-
         // Create the stream object for this page
-        let stream_object_id = self.add_object(page_stream, false, false);
+        let page_buffer = mem::take(&mut self.page_buffer);
+        let stream_object_id = self.add_stream_object("", &page_buffer);
 
         // Create the page object, which describes settings for the whole page
         let mut page_object = b"<< /Type /Page\n \
@@ -195,19 +592,39 @@ impl Pdf {
             /Resources <<\n"
             .to_vec();
 
-        for (idx, _obj) in self
-            .objects
-            .iter()
-            .enumerate()
-            .filter(|&(_, o)| o.is_xobject)
-        {
-            write!(page_object, "/XObject {} 0 R ", idx + 1).unwrap();
+        let resources = [
+            ("Shading", &self.page_shadings),
+            ("XObject", &self.page_images),
+            ("ExtGState", &self.page_graphics_states),
+            ("Font", &self.page_fonts),
+        ];
+        for &(kind, entries) in &resources {
+            if entries.is_empty() {
+                continue;
+            }
+            write!(page_object, "/{} <<", kind).unwrap();
+            for (name, object_id) in entries {
+                write!(page_object, " /{} {} 0 R", name, object_id).unwrap();
+            }
+            writeln!(page_object, " >>").unwrap();
+        }
+
+        writeln!(page_object, " >>").unwrap();
+
+        // Archival documents spell out the color space that transparency is blended in.
+        let uses_transparency =
+            !self.page_graphics_states.is_empty() || !self.page_images.is_empty();
+        if self.conformance == PdfConformance::PdfA2b && uses_transparency {
+            writeln!(
+                page_object,
+                " /Group << /Type /Group /S /Transparency /CS /DeviceRGB >>"
+            )
+            .unwrap();
         }
 
         write!(
             page_object,
-            " >>\n \
-                /MediaBox [0 0 {} {}]\n \
+            " /MediaBox [0 0 {} {}]\n \
                 /Contents {} 0 R\n\
                 >>\n",
             size.x(),
@@ -215,7 +632,129 @@ impl Pdf {
             stream_object_id
         )
         .unwrap();
-        self.add_object(page_object, true, false);
+        self.add_object(page_object, true);
+
+        self.page_shadings.clear();
+        self.page_images.clear();
+        self.page_graphics_states.clear();
+        self.page_fonts.clear();
+        self.graphics_state_names.clear();
+        self.state = GraphicsState::default();
+        self.saved_states.clear();
+    }
+
+    /// Embed the fonts, subset to the glyphs that were shown
+    fn end_fonts(&mut self) {
+        for (font_index, pdf_font) in mem::take(&mut self.fonts).into_iter().enumerate() {
+            let font = &pdf_font.font;
+            let scale = |value: i32| value * 1000 / i32::from(font.units_per_em());
+
+            // Subsets are named with a tag of six capital letters.
+            let mut base_font: String = font
+                .postscript_name()
+                .unwrap_or_else(|| format!("Font{}", font_index))
+                .chars()
+                .filter(|&character| {
+                    character.is_ascii_graphic() && !"()<>[]{}/%#".contains(character)
+                })
+                .collect();
+            if !font.is_cff() {
+                let tag: String = (0..6)
+                    .map(|digit| (b'A' + (font_index / 26usize.pow(digit) % 26) as u8) as char)
+                    .collect();
+                base_font = format!("{}+{}", tag, base_font);
+            }
+
+            let font_data = font.subset(&pdf_font.glyph_ids);
+            let font_file = if font.is_cff() {
+                let object_id = self.add_stream_object("/Subtype /OpenType", &font_data);
+                format!("/FontFile3 {} 0 R", object_id)
+            } else {
+                let dictionary = format!("/Length1 {}", font_data.len());
+                let object_id = self.add_stream_object(&dictionary, &font_data);
+                format!("/FontFile2 {} 0 R", object_id)
+            };
+
+            let bounding_box = font.bounding_box();
+            let descriptor = format!(
+                "<< /Type /FontDescriptor /FontName /{} /Flags 4 /FontBBox [{} {} {} {}] \
+                 /ItalicAngle {} /Ascent {} /Descent {} /CapHeight {} /StemV 80 {} >>\n",
+                base_font,
+                scale(bounding_box[0].into()),
+                scale(bounding_box[1].into()),
+                scale(bounding_box[2].into()),
+                scale(bounding_box[3].into()),
+                font.italic_angle(),
+                scale(font.ascent().into()),
+                scale(font.descent().into()),
+                scale(font.cap_height().into()),
+                font_file
+            );
+            let descriptor_id = self.add_object(descriptor.into_bytes(), false);
+
+            let mut widths = String::new();
+            for &glyph_id in &pdf_font.glyph_ids {
+                let width = scale(font.advance_width(glyph_id).into());
+                widths.push_str(&format!("{} [{}] ", glyph_id, width));
+            }
+            // CFF fonts that aren't CID-keyed use glyph IDs as CIDs. `Font::parse()` rejects
+            // CID-keyed ones.
+            let (subtype, cid_to_gid_map) = if font.is_cff() {
+                ("CIDFontType0", "")
+            } else {
+                ("CIDFontType2", " /CIDToGIDMap /Identity")
+            };
+            let cid_font = format!(
+                "<< /Type /Font /Subtype /{} /BaseFont /{} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor {} 0 R /W [{}]{} >>\n",
+                subtype,
+                base_font,
+                descriptor_id,
+                widths.trim_end(),
+                cid_to_gid_map
+            );
+            let cid_font_id = self.add_object(cid_font.into_bytes(), false);
+
+            let to_unicode = to_unicode_cmap(font, &pdf_font.glyph_ids);
+            let to_unicode_id = self.add_stream_object("", to_unicode.as_bytes());
+
+            self.objects[pdf_font.object_id - 1].contents = format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+                 /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>\n",
+                base_font, cid_font_id, to_unicode_id
+            )
+            .into_bytes();
+        }
+    }
+
+    /// Add the output intent and metadata that PDF/A requires, returning the catalog entries
+    /// that refer to them
+    fn add_pdf_a_objects(&mut self) -> String {
+        let profile = icc::srgb_profile();
+        let profile_id = self.add_stream_object("/N 3", &profile);
+        let output_intent = format!(
+            "<< /Type /OutputIntent /S /GTS_PDFA1 \
+             /OutputConditionIdentifier (sRGB IEC61966-2.1) /Info (sRGB IEC61966-2.1) \
+             /DestOutputProfile {} 0 R >>\n",
+            profile_id
+        );
+        let output_intent_id = self.add_object(output_intent.into_bytes(), false);
+
+        // Metadata streams must not be compressed.
+        let mut metadata = format!(
+            "<< /Type /Metadata /Subtype /XML /Length {} >>\nstream\n",
+            PDF_A_METADATA.len()
+        )
+        .into_bytes();
+        metadata.extend_from_slice(PDF_A_METADATA.as_bytes());
+        metadata.extend(b"\nendstream\n");
+        let metadata_id = self.add_object(metadata, false);
+
+        format!(
+            "\n/OutputIntents [{} 0 R]\n/Metadata {} 0 R",
+            output_intent_id, metadata_id
+        )
     }
 
     /// Write the in-memory PDF representation to disk
@@ -229,6 +768,11 @@ impl Pdf {
         if !self.page_buffer.is_empty() {
             self.end_page();
         }
+        self.end_fonts();
+        let catalog_entries = match self.conformance {
+            PdfConformance::Standard => String::new(),
+            PdfConformance::PdfA2b => self.add_pdf_a_objects(),
+        };
 
         // Write out each object
         for (idx, obj) in self.objects.iter_mut().enumerate().skip(2) {
@@ -260,29 +804,205 @@ impl Pdf {
 
         // Write out the catalog dictionary object
         self.objects[0].offset = Some(out.pos());
-        out.write_all(b"1 0 obj\n<< /Type /Catalog\n/Pages 2 0 R >>\nendobj\n")?;
+        write!(
+            out,
+            "1 0 obj\n<< /Type /Catalog\n/Pages 2 0 R{} >>\nendobj\n",
+            catalog_entries
+        )?;
 
         // Write the cross-reference table
-        let startxref = out.pos() + 1; // NOTE: apparently there's some 1-based indexing??
+        let startxref = out.pos();
         out.write_all(b"xref\n")?;
         write!(out, "0 {}\n", self.objects.len() + 1)?;
         out.write_all(b"0000000000 65535 f \n")?;
 
         for obj in &self.objects {
-            write!(out, "{:010} 00000 f \n", obj.offset.unwrap())?;
+            writeln!(out, "{:010} 00000 n ", obj.offset.unwrap())?;
         }
 
-        // Write the document trailer
+        // Write the document trailer. The ID is derived from the contents, so that the same
+        // document always gets the same one.
+        let mut hasher = DefaultHasher::new();
+        for obj in &self.objects {
+            hasher.write(&obj.contents);
+        }
+        let hash = hasher.finish();
         out.write_all(b"trailer\n")?;
-        write!(out, "<< /Size {}\n", self.objects.len())?;
+        writeln!(out, "<< /Size {}", self.objects.len() + 1)?;
+        writeln!(
+            out,
+            "/ID [<{:016x}{:016x}> <{:016x}{:016x}>]",
+            hash, !hash, hash, !hash
+        )?;
         out.write_all(b"/Root 1 0 R >>\n")?;
 
         // Write the offset to the xref table
         write!(out, "startxref\n{}\n", startxref)?;
 
         // Write the PDF EOF
-        out.write_all(b"%%EOF")?;
+        out.write_all(b"%%EOF\n")?;
 
         Ok(())
     }
 }
+
+/// Formats a point as two operands
+struct Point(Vector2F, Option<u8>);
+
+impl Display for Point {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let Point(point, precision) = *self;
+        write!(
+            formatter,
+            "{} {}",
+            Decimal(point.x(), precision),
+            Decimal(point.y(), precision)
+        )
+    }
+}
+
+/// Formats a transform as the six operands of `cm` or `Tm`
+///
+/// Only the translation is rounded, since rounding the other entries could distort the shapes
+/// drawn.
+struct Matrix(Transform2F, Option<u8>);
+
+impl Display for Matrix {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let Matrix(transform, precision) = *self;
+        write!(
+            formatter,
+            "{} {} {} {} {}",
+            transform.m11(),
+            transform.m21(),
+            transform.m12(),
+            transform.m22(),
+            Point(transform.translation(), precision)
+        )
+    }
+}
+
+/// Returns the name of the PDF blend mode that matches the given one
+fn pdf_blend_mode(blend_mode: BlendMode) -> &'static str {
+    match blend_mode {
+        BlendMode::Multiply => "Multiply",
+        BlendMode::Screen => "Screen",
+        BlendMode::Overlay => "Overlay",
+        BlendMode::Darken => "Darken",
+        BlendMode::Lighten => "Lighten",
+        BlendMode::ColorDodge => "ColorDodge",
+        BlendMode::ColorBurn => "ColorBurn",
+        BlendMode::HardLight => "HardLight",
+        BlendMode::SoftLight => "SoftLight",
+        BlendMode::Difference => "Difference",
+        BlendMode::Exclusion => "Exclusion",
+        BlendMode::Hue => "Hue",
+        BlendMode::Saturation => "Saturation",
+        BlendMode::Color => "Color",
+        BlendMode::Luminosity => "Luminosity",
+        BlendMode::Clear
+        | BlendMode::Copy
+        | BlendMode::SrcIn
+        | BlendMode::SrcOut
+        | BlendMode::SrcOver
+        | BlendMode::SrcAtop
+        | BlendMode::DestIn
+        | BlendMode::DestOut
+        | BlendMode::DestOver
+        | BlendMode::DestAtop
+        | BlendMode::Xor
+        | BlendMode::Lighter => "Normal",
+    }
+}
+
+/// Returns a CMap that maps the glyphs back to the characters they show, so text can be copied
+fn to_unicode_cmap(font: &Font, glyph_ids: &BTreeSet<u16>) -> String {
+    let characters = font.glyph_characters();
+    let mappings: Vec<(u16, char)> = glyph_ids
+        .iter()
+        .filter_map(|glyph_id| Some((*glyph_id, *characters.get(glyph_id)?)))
+        .collect();
+
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n\
+         12 dict begin\n\
+         begincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n\
+         /CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    for block in mappings.chunks(MAX_BFCHAR_ENTRIES) {
+        cmap.push_str(&format!("{} beginbfchar\n", block.len()));
+        for &(glyph_id, character) in block {
+            cmap.push_str(&format!("<{:04X}> <", glyph_id));
+            let mut units = [0; 2];
+            for unit in character.encode_utf16(&mut units) {
+                cmap.push_str(&format!("{:04X}", unit));
+            }
+            cmap.push_str(">\n");
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str(
+        "endcmap\n\
+         CMapName currentdict /CMap defineresource pop\n\
+         end\n\
+         end\n",
+    );
+    cmap
+}
+
+/// Returns a function that interpolates between the color stops from 0 to 1
+fn stitching_function(stops: &[ColorStop]) -> String {
+    let color = |color: ColorU| {
+        format!(
+            "{} {} {}",
+            f32::from(color.r) / 255.0,
+            f32::from(color.g) / 255.0,
+            f32::from(color.b) / 255.0
+        )
+    };
+    let interpolate = |from: ColorU, to: ColorU| {
+        format!(
+            "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+            color(from),
+            color(to)
+        )
+    };
+
+    // Pad the stops out to cover the whole domain.
+    let mut stops = stops.to_vec();
+    match (stops.first().cloned(), stops.last().cloned()) {
+        (Some(first), Some(last)) => {
+            if first.offset > 0.0 {
+                stops.insert(0, ColorStop::new(first.color, 0.0));
+            }
+            if last.offset < 1.0 {
+                stops.push(ColorStop::new(last.color, 1.0));
+            }
+        }
+        _ => return interpolate(ColorU::transparent_black(), ColorU::transparent_black()),
+    }
+    if stops.len() == 1 {
+        return interpolate(stops[0].color, stops[0].color);
+    }
+
+    let mut functions = String::new();
+    let mut bounds = String::new();
+    let mut encode = String::new();
+    for (index, pair) in stops.windows(2).enumerate() {
+        functions.push_str(&interpolate(pair[0].color, pair[1].color));
+        functions.push(' ');
+        encode.push_str("0 1 ");
+        if index > 0 {
+            bounds.push_str(&format!("{} ", pair[0].offset));
+        }
+    }
+    format!(
+        "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+        functions.trim_end(),
+        bounds.trim_end(),
+        encode.trim_end()
+    )
+}
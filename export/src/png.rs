@@ -0,0 +1,84 @@
+// pathfinder/export/src/png.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rasterizes scenes to PNG images with the CPU rasterizer, so no GPU device is needed.
+
+use image::png::PngEncoder;
+use image::ColorType;
+use pathfinder_color::ColorU;
+use pathfinder_content::pattern::Image;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_rasterize::RasterizeOptions;
+use pathfinder_renderer::scene::Scene;
+use std::io::{self, Write};
+
+pub(crate) fn export_png<W: Write>(
+    scene: &Scene,
+    writer: &mut W,
+    size: Vector2I,
+    background: Option<ColorU>,
+) -> io::Result<()> {
+    // Stretch the view box to fill the image.
+    let view_box = scene.view_box();
+    let transform = Transform2F::from_scale(size.to_f32() / view_box.size())
+        * Transform2F::from_translation(-view_box.origin());
+
+    let mut options = RasterizeOptions::new(size);
+    options.transform = transform;
+    options.background_color = background.map(|background| background.to_f32());
+    let image = pathfinder_rasterize::rasterize(scene, &options);
+    write_png(&image, writer)
+}
+
+pub(crate) fn write_png<W: Write>(image: &Image, writer: &mut W) -> io::Result<()> {
+    let size = image.size();
+    let mut pixels = Vec::with_capacity(image.pixels().len() * 4);
+    for pixel in image.pixels().iter() {
+        pixels.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+    }
+
+    PngEncoder::new(writer)
+        .encode(&pixels, size.x() as u32, size.y() as u32, ColorType::Rgba8)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod test {
+    use super::export_png;
+    use crate::test::one_path_scene;
+    use image::RgbaImage;
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::vector::{vec2i, Vector2I};
+
+    fn export(size: Vector2I, background: Option<ColorU>) -> RgbaImage {
+        let mut png = vec![];
+        export_png(&one_path_scene(), &mut png, size, background).unwrap();
+        image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgba8()
+    }
+
+    #[test]
+    fn png_export_fills_the_image_with_the_view_box() {
+        let image = export(vec2i(200, 100), Some(ColorU::white()));
+        assert_eq!(image.dimensions(), (200, 100));
+        assert_eq!(image.get_pixel(40, 30).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(10, 10).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(70, 30).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn png_export_without_a_background_is_transparent() {
+        let image = export(vec2i(100, 50), None);
+        assert_eq!(image.get_pixel(20, 15).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(50, 40).0[3], 0);
+    }
+}
@@ -0,0 +1,515 @@
+// pathfinder/export/src/svg.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Serializes scenes to editable SVG documents.
+//!
+//! Gradients, patterns, and clip paths become `<defs>` entries that the paths refer to. Content
+//! drawn to render targets, such as the contents of groups, is kept as vector graphics inside the
+//! `<pattern>` that uses it. Noise and stylized paint effects have no SVG equivalent and are
+//! written as their base color.
+
+use crate::png::write_png;
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{Gradient, GradientGeometry, GradientWrap};
+use pathfinder_content::pattern::{Image, Pattern, PatternSource};
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_renderer::paint::PaintId;
+use pathfinder_renderer::scene::{ClipPathId, DisplayItem, DrawPathId, Scene};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+pub(crate) fn export_svg<W: Write>(scene: &Scene, writer: &mut W) -> io::Result<()> {
+    let mut exporter = SvgExporter {
+        scene,
+        defs: vec![],
+        paints: HashMap::new(),
+        clip_paths: HashSet::new(),
+        render_targets: HashMap::new(),
+        next_def_id: 0,
+    };
+    let body = exporter.export_display_list()?;
+
+    let view_box = scene.view_box();
+    writeln!(
+        writer,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+         xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+         width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">",
+        view_box.size().x(),
+        view_box.size().y(),
+        view_box.origin().x(),
+        view_box.origin().y(),
+        view_box.size().x(),
+        view_box.size().y()
+    )?;
+    if !exporter.defs.is_empty() {
+        writeln!(writer, "    <defs>")?;
+        writer.write_all(&exporter.defs)?;
+        writeln!(writer, "    </defs>")?;
+    }
+    writer.write_all(&body)?;
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+struct SvgExporter<'a> {
+    scene: &'a Scene,
+    defs: Vec<u8>,
+    // The `fill` attributes that each paint has been written as so far.
+    paints: HashMap<PaintId, String>,
+    clip_paths: HashSet<ClipPathId>,
+    // The vector contents of each render target that has been popped.
+    render_targets: HashMap<RenderTargetId, Vec<u8>>,
+    next_def_id: u32,
+}
+
+impl<'a> SvgExporter<'a> {
+    fn export_display_list(&mut self) -> io::Result<Vec<u8>> {
+        let mut body = vec![];
+        let mut render_target_stack: Vec<(RenderTargetId, Vec<u8>)> = vec![];
+        for display_item in self.scene.display_list() {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push((render_target_id, vec![]));
+                }
+                DisplayItem::PopRenderTarget => {
+                    if let Some((render_target_id, contents)) = render_target_stack.pop() {
+                        self.render_targets.insert(render_target_id, contents);
+                    }
+                }
+                DisplayItem::DrawPaths(ref draw_path_ids) => {
+                    for draw_path_index in draw_path_ids.start.0..draw_path_ids.end.0 {
+                        let output = match render_target_stack.last_mut() {
+                            Some(&mut (_, ref mut contents)) => contents,
+                            None => &mut body,
+                        };
+                        self.write_draw_path(DrawPathId(draw_path_index), output)?;
+                    }
+                }
+            }
+        }
+        Ok(body)
+    }
+
+    fn write_draw_path(
+        &mut self,
+        draw_path_id: DrawPathId,
+        output: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let draw_path = self.scene.get_draw_path(draw_path_id);
+        if draw_path.outline.is_empty() {
+            return Ok(());
+        }
+
+        let fill = self.paint(draw_path.paint)?;
+        let clip_path = match draw_path.clip_path {
+            Some(clip_path_id) => Some(self.clip_path(clip_path_id)?),
+            None => None,
+        };
+
+        write!(output, "    <path")?;
+        if !draw_path.name.is_empty() {
+            write!(output, " id=\"{}\"", escape(&draw_path.name))?;
+        }
+        write!(output, " {}", fill)?;
+        if draw_path.fill_rule == FillRule::EvenOdd {
+            write!(output, " fill-rule=\"evenodd\"")?;
+        }
+        if let Some(clip_path) = clip_path {
+            write!(output, " clip-path=\"url(#{})\"", clip_path)?;
+        }
+        if let Some(blend_mode) = css_blend_mode(draw_path.blend_mode) {
+            write!(output, " style=\"mix-blend-mode: {}\"", blend_mode)?;
+        }
+
+        // The draw path transform doesn't apply to the paint or the clip path, so it's baked into
+        // the outline rather than written as a `transform` attribute.
+        if draw_path.transform.is_identity() {
+            writeln!(output, " d=\"{:?}\" />", draw_path.outline)?;
+        } else {
+            let mut outline = (*draw_path.outline).clone();
+            outline.transform(&draw_path.transform);
+            writeln!(output, " d=\"{:?}\" />", outline)?;
+        }
+        Ok(())
+    }
+
+    // Returns the attributes that fill a path with the given paint, writing any definitions that
+    // they refer to.
+    fn paint(&mut self, paint_id: PaintId) -> io::Result<String> {
+        if let Some(fill) = self.paints.get(&paint_id) {
+            return Ok(fill.clone());
+        }
+
+        let paint = self.scene.get_paint(paint_id);
+        let base_color = paint.base_color();
+        let fill = if let Some(gradient) = paint.gradient() {
+            match self.gradient(gradient)? {
+                Some(id) => {
+                    let opacity = opacity_attribute("fill-opacity", base_color);
+                    format!("fill=\"url(#{})\"{}", id, opacity)
+                }
+                None => color_attributes("fill", base_color),
+            }
+        } else if let Some(pattern) = paint.pattern() {
+            match self.pattern(pattern)? {
+                Some(id) => {
+                    let opacity = opacity_attribute("fill-opacity", base_color);
+                    format!("fill=\"url(#{})\"{}", id, opacity)
+                }
+                None => color_attributes("fill", base_color),
+            }
+        } else {
+            color_attributes("fill", base_color)
+        };
+
+        self.paints.insert(paint_id, fill.clone());
+        Ok(fill)
+    }
+
+    // Writes a gradient definition, returning its ID, or returns `None` for conic gradients,
+    // which SVG has no equivalent of.
+    fn gradient(&mut self, gradient: &Gradient) -> io::Result<Option<String>> {
+        if let GradientGeometry::Conic { .. } = gradient.geometry {
+            return Ok(None);
+        }
+
+        let id = self.next_def_id("gradient");
+        match gradient.geometry {
+            GradientGeometry::Linear(line) => {
+                write!(
+                    self.defs,
+                    "        <linearGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" \
+                     x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"",
+                    id,
+                    line.from_x(),
+                    line.from_y(),
+                    line.to_x(),
+                    line.to_y()
+                )?;
+            }
+            GradientGeometry::Radial {
+                line,
+                radii,
+                transform,
+            } => {
+                write!(
+                    self.defs,
+                    "        <radialGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" \
+                     fx=\"{}\" fy=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"",
+                    id,
+                    line.from_x(),
+                    line.from_y(),
+                    line.to_x(),
+                    line.to_y(),
+                    radii.y()
+                )?;
+                if radii.x() != 0.0 {
+                    write!(self.defs, " fr=\"{}\"", radii.x())?;
+                }
+                if !transform.is_identity() {
+                    write!(self.defs, " gradientTransform=\"{}\"", matrix(&transform))?;
+                }
+            }
+            GradientGeometry::Conic { .. } => unreachable!(),
+        }
+        if gradient.wrap == GradientWrap::Repeat {
+            write!(self.defs, " spreadMethod=\"repeat\"")?;
+        }
+        writeln!(self.defs, ">")?;
+
+        for stop in gradient.stops() {
+            writeln!(
+                self.defs,
+                "            <stop offset=\"{}\" {} />",
+                stop.offset,
+                color_attributes("stop-color", stop.color)
+            )?;
+        }
+
+        match gradient.geometry {
+            GradientGeometry::Linear(_) => writeln!(self.defs, "        </linearGradient>")?,
+            GradientGeometry::Radial { .. } => writeln!(self.defs, "        </radialGradient>")?,
+            GradientGeometry::Conic { .. } => unreachable!(),
+        }
+        Ok(Some(id))
+    }
+
+    // Writes a pattern definition, returning its ID, or returns `None` if the pattern's contents
+    // can't be expressed in SVG.
+    fn pattern(&mut self, pattern: &Pattern) -> io::Result<Option<String>> {
+        let contents = match *pattern.source() {
+            PatternSource::Image(ref image) => {
+                let size = image.size();
+                let mut contents = vec![];
+                write!(
+                    contents,
+                    "            <image width=\"{}\" height=\"{}\"",
+                    size.x(),
+                    size.y()
+                )?;
+                if !pattern.smoothing_enabled() {
+                    write!(contents, " style=\"image-rendering: pixelated\"")?;
+                }
+                writeln!(contents, " xlink:href=\"{}\" />", png_data_url(image)?)?;
+                contents
+            }
+            PatternSource::RenderTarget { id, .. } => match self.render_targets.get(&id) {
+                // Indent the paths to sit inside the `<pattern>`.
+                Some(contents) => contents
+                    .split(|&byte| byte == b'\n')
+                    .filter(|line| !line.is_empty())
+                    .flat_map(|line| b"        ".iter().chain(line).chain(b"\n"))
+                    .cloned()
+                    .collect(),
+                None => return Ok(None),
+            },
+            // TODO: Read back external textures?
+            PatternSource::ExternalTexture { .. } => return Ok(None),
+        };
+
+        let id = self.next_def_id("pattern");
+        let size = pattern.size();
+        write!(
+            self.defs,
+            "        <pattern id=\"{}\" patternUnits=\"userSpaceOnUse\" width=\"{}\" \
+             height=\"{}\"",
+            id,
+            size.x(),
+            size.y()
+        )?;
+        if !pattern.transform().is_identity() {
+            write!(
+                self.defs,
+                " patternTransform=\"{}\"",
+                matrix(&pattern.transform())
+            )?;
+        }
+        writeln!(self.defs, ">")?;
+        self.defs.extend_from_slice(&contents);
+        writeln!(self.defs, "        </pattern>")?;
+        Ok(Some(id))
+    }
+
+    // Returns the ID of the `<clipPath>` for the given clip path, writing it and the clip paths it
+    // is nested in if they haven't been written yet.
+    fn clip_path(&mut self, clip_path_id: ClipPathId) -> io::Result<String> {
+        let id = format!("clip-path-{}", clip_path_id.0);
+        if !self.clip_paths.insert(clip_path_id) {
+            return Ok(id);
+        }
+
+        let clip_path = self.scene.get_clip_path(clip_path_id);
+        let parent = match clip_path.clip_path {
+            Some(parent_id) => Some(self.clip_path(parent_id)?),
+            None => None,
+        };
+
+        write!(
+            self.defs,
+            "        <clipPath id=\"{}\" clipPathUnits=\"userSpaceOnUse\"",
+            id
+        )?;
+        if let Some(parent) = parent {
+            write!(self.defs, " clip-path=\"url(#{})\"", parent)?;
+        }
+        writeln!(self.defs, ">")?;
+        write!(self.defs, "            <path")?;
+        if clip_path.fill_rule == FillRule::EvenOdd {
+            write!(self.defs, " clip-rule=\"evenodd\"")?;
+        }
+        writeln!(self.defs, " d=\"{:?}\" />", clip_path.outline)?;
+        writeln!(self.defs, "        </clipPath>")?;
+        Ok(id)
+    }
+
+    fn next_def_id(&mut self, kind: &str) -> String {
+        let id = format!("{}-{}", kind, self.next_def_id);
+        self.next_def_id += 1;
+        id
+    }
+}
+
+fn color_attributes(name: &str, color: ColorU) -> String {
+    format!(
+        "{}=\"rgb({}, {}, {})\"{}",
+        name,
+        color.r,
+        color.g,
+        color.b,
+        opacity_attribute(
+            &format!("{}-opacity", name.trim_end_matches("-color")),
+            color
+        )
+    )
+}
+
+fn opacity_attribute(name: &str, color: ColorU) -> String {
+    if color.a == 255 {
+        String::new()
+    } else {
+        format!(" {}=\"{}\"", name, color.a as f32 / 255.0)
+    }
+}
+
+fn matrix(transform: &Transform2F) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        transform.m11(),
+        transform.m21(),
+        transform.m12(),
+        transform.m22(),
+        transform.m13(),
+        transform.m23()
+    )
+}
+
+// Porter-Duff operators other than source-over have no CSS equivalent and are dropped.
+fn css_blend_mode(blend_mode: BlendMode) -> Option<&'static str> {
+    match blend_mode {
+        BlendMode::Darken => Some("darken"),
+        BlendMode::Lighten => Some("lighten"),
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::HardLight => Some("hard-light"),
+        BlendMode::Overlay => Some("overlay"),
+        BlendMode::ColorDodge => Some("color-dodge"),
+        BlendMode::ColorBurn => Some("color-burn"),
+        BlendMode::SoftLight => Some("soft-light"),
+        BlendMode::Difference => Some("difference"),
+        BlendMode::Exclusion => Some("exclusion"),
+        BlendMode::Hue => Some("hue"),
+        BlendMode::Saturation => Some("saturation"),
+        BlendMode::Color => Some("color"),
+        BlendMode::Luminosity => Some("luminosity"),
+        BlendMode::Lighter => Some("plus-lighter"),
+        BlendMode::Clear
+        | BlendMode::Copy
+        | BlendMode::SrcIn
+        | BlendMode::SrcOut
+        | BlendMode::SrcOver
+        | BlendMode::SrcAtop
+        | BlendMode::DestIn
+        | BlendMode::DestOut
+        | BlendMode::DestOver
+        | BlendMode::DestAtop
+        | BlendMode::Xor => None,
+    }
+}
+
+fn png_data_url(image: &Image) -> io::Result<String> {
+    let mut png = vec![];
+    write_png(image, &mut png)?;
+    Ok(format!("data:image/png;base64,{}", base64(&png)))
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(ALPHABET[(bits >> (18 - index * 6)) as usize & 63] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::export_svg;
+    use crate::test::one_path_scene;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use pathfinder_renderer::paint::Paint;
+    use pathfinder_renderer::scene::{ClipPath, DrawPath, Scene};
+
+    fn export(scene: &Scene) -> String {
+        let mut svg = vec![];
+        export_svg(scene, &mut svg).unwrap();
+        String::from_utf8(svg).unwrap()
+    }
+
+    #[test]
+    fn svg_export_of_one_path() {
+        assert_eq!(
+            export(&one_path_scene()),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">\n\
+             \x20   <path fill=\"rgb(255, 0, 0)\" d=\"M 10 10 L 30 10 L 30 20 L 10 20 z\" />\n\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn svg_export_refers_to_gradients_and_nested_clip_paths() {
+        let mut scene = one_path_scene();
+        let mut gradient = Gradient::linear_from_points(vec2f(0.0, 0.0), vec2f(100.0, 0.0));
+        gradient.add_color_stop(ColorU::new(0, 0, 255, 255), 0.0);
+        gradient.add_color_stop(ColorU::new(0, 255, 0, 128), 1.0);
+        let paint = scene.push_paint(&Paint::from_gradient(gradient));
+
+        let outer_rect = RectF::new(vec2f(0.0, 0.0), vec2f(50.0, 50.0));
+        let outer = scene.push_clip_path(ClipPath::new(Outline::from_rect(outer_rect)));
+        let mut inner = ClipPath::new(Outline::from_rect(RectF::new(
+            vec2f(20.0, 0.0),
+            vec2f(80.0, 50.0),
+        )));
+        inner.set_clip_path(Some(outer));
+        let inner = scene.push_clip_path(inner);
+
+        let mut draw_path = DrawPath::new(Outline::from_rect(outer_rect), paint);
+        draw_path.set_clip_path(Some(inner));
+        scene.push_draw_path(draw_path);
+
+        let svg = export(&scene);
+        assert!(svg.contains(
+            "        <linearGradient id=\"gradient-0\" gradientUnits=\"userSpaceOnUse\" \
+             x1=\"0\" y1=\"0\" x2=\"100\" y2=\"0\">\n\
+             \x20           <stop offset=\"0\" stop-color=\"rgb(0, 0, 255)\" />\n\
+             \x20           <stop offset=\"1\" stop-color=\"rgb(0, 255, 0)\" \
+             stop-opacity=\"0.5019608\" />\n\
+             \x20       </linearGradient>\n"
+        ));
+        assert!(svg.contains(
+            "        <clipPath id=\"clip-path-1\" clipPathUnits=\"userSpaceOnUse\" \
+             clip-path=\"url(#clip-path-0)\">\n"
+        ));
+        assert!(svg.contains("<clipPath id=\"clip-path-0\" clipPathUnits=\"userSpaceOnUse\">"));
+        assert!(svg.contains(
+            "    <path fill=\"url(#gradient-0)\" clip-path=\"url(#clip-path-1)\" \
+             d=\"M 0 0 L 50 0 L 50 50 L 0 50 z\" />\n"
+        ));
+    }
+}
@@ -10,7 +10,7 @@
 
 //! Angle utilities.
 
-use std::f32::consts::PI;
+use core::f32::consts::PI;
 
 #[inline]
 pub fn angle_from_degrees(degrees: f32) -> f32 {
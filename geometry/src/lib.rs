@@ -9,6 +9,10 @@
 // except according to those terms.
 
 //! Basic geometry and linear algebra primitives, optimized with SIMD.
+//!
+//! With the default `std` feature disabled, this crate is `no_std`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 pub mod angle;
 pub mod line_segment;
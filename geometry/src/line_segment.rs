@@ -13,8 +13,10 @@
 use crate::transform2d::Matrix2x2F;
 use crate::util;
 use crate::vector::{vec2f, Vector2F};
+use core::ops::{Add, Mul, MulAssign, Sub};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Sub};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct LineSegment2F(pub F32x4);
@@ -11,8 +11,8 @@
 //! 2D axis-aligned rectangles, optimized with SIMD.
 
 use crate::vector::{IntoVector2F, Vector2F, Vector2I};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 use pathfinder_simd::default::{F32x4, I32x4};
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct RectF(pub F32x4);
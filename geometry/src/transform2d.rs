@@ -15,8 +15,10 @@ use crate::rect::RectF;
 use crate::transform3d::Transform4F;
 use crate::unit_vector::UnitVector;
 use crate::vector::{vec2f, IntoVector2F, Vector2F};
+use core::ops::{Mul, MulAssign, Sub};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Mul, MulAssign, Sub};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// A 2x2 matrix, optimized with SIMD, in column-major order.
 #[derive(Clone, Copy, Debug, PartialEq)]
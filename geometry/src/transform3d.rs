@@ -13,8 +13,10 @@
 use crate::rect::RectF;
 use crate::transform2d::Matrix2x2F;
 use crate::vector::{Vector2F, Vector2I, Vector3F, Vector4F};
+use core::ops::{Add, Mul, MulAssign, Neg};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Neg};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// An transform, optimized with SIMD.
 ///
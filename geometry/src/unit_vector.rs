@@ -12,6 +12,8 @@
 
 use crate::vector::Vector2F;
 use pathfinder_simd::default::F32x2;
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 #[derive(Clone, Copy, Debug)]
 pub struct UnitVector(pub Vector2F);
@@ -10,9 +10,11 @@
 
 //! A SIMD-optimized point type.
 
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
-use std::hash::{Hash, Hasher};
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+#[cfg(not(feature = "std"))]
+use pathfinder_simd::float::F32Ext;
 
 /// 2D points with 32-bit floating point coordinates.
 #[derive(Clone, Copy, Debug, Default)]
@@ -0,0 +1,215 @@
+// pathfinder/gl/src/headless.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! OpenGL contexts that need no window system, for rendering on servers and in CI.
+//!
+//! The context is created through EGL, preferring Mesa's surfaceless platform so that neither X11
+//! nor Wayland is needed. `libEGL` is loaded at runtime, so binaries built with the `headless`
+//! feature still start on machines without it. In containers without a GPU, Mesa's software
+//! rasterizer (llvmpipe) provides the GL implementation.
+
+use crate::{GLDevice, GLVersion};
+use khronos_egl as egl;
+use pathfinder_geometry::vector::Vector2I;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+// From `EGL_MESA_platform_surfaceless`.
+const EGL_PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31dd;
+
+/// An OpenGL 3.3 core profile context that renders to an offscreen pbuffer.
+///
+/// The pbuffer acts as the default framebuffer, so a `GLDevice` created with
+/// `create_device()` can be used with the renderer's default `DestFramebuffer`. Read the
+/// rendered image back with `Renderer::read_frame_async()`.
+pub struct GLHeadlessContext {
+    egl: egl::DynamicInstance<egl::EGL1_4>,
+    display: egl::Display,
+    context: egl::Context,
+    surface: egl::Surface,
+    size: Vector2I,
+}
+
+/// The reasons why a headless context couldn't be created.
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// `libEGL` couldn't be found or is older than EGL 1.4.
+    Load(String),
+    /// No EGL display was available.
+    NoDisplay,
+    /// The display offers no configuration with 8-bit RGBA color that supports pbuffers and
+    /// desktop OpenGL.
+    NoConfig,
+    /// An EGL call failed.
+    Egl(egl::Error),
+}
+
+impl GLHeadlessContext {
+    /// Creates a context backed by an offscreen surface of the given size, makes it current on
+    /// this thread, and loads the OpenGL entry points.
+    pub fn new(size: Vector2I) -> Result<GLHeadlessContext, HeadlessError> {
+        unsafe {
+            let egl = egl::DynamicInstance::<egl::EGL1_4>::load_required()
+                .map_err(|err| HeadlessError::Load(err.to_string()))?;
+
+            // The surfaceless platform needs `eglGetPlatformDisplay()`, which is new in EGL 1.5.
+            let surfaceless_display = egl.upcast::<egl::EGL1_5>().and_then(|egl| {
+                egl.get_platform_display(
+                    EGL_PLATFORM_SURFACELESS_MESA,
+                    egl::DEFAULT_DISPLAY,
+                    &[egl::ATTRIB_NONE],
+                )
+                .ok()
+            });
+            let display = match surfaceless_display {
+                Some(display) => display,
+                None => egl
+                    .get_display(egl::DEFAULT_DISPLAY)
+                    .ok_or(HeadlessError::NoDisplay)?,
+            };
+            egl.initialize(display)?;
+            // Terminating the display also frees the context and surface, if they were created.
+            let display = DisplayGuard { egl: &egl, display };
+
+            let config_attributes = [
+                egl::SURFACE_TYPE,
+                egl::PBUFFER_BIT,
+                egl::RENDERABLE_TYPE,
+                egl::OPENGL_BIT,
+                egl::RED_SIZE,
+                8,
+                egl::GREEN_SIZE,
+                8,
+                egl::BLUE_SIZE,
+                8,
+                egl::ALPHA_SIZE,
+                8,
+                egl::DEPTH_SIZE,
+                24,
+                egl::STENCIL_SIZE,
+                8,
+                egl::NONE,
+            ];
+            let config = egl
+                .choose_first_config(display.display, &config_attributes)?
+                .ok_or(HeadlessError::NoConfig)?;
+
+            egl.bind_api(egl::OPENGL_API)?;
+            let context_attributes = [
+                egl::CONTEXT_MAJOR_VERSION,
+                3,
+                egl::CONTEXT_MINOR_VERSION,
+                3,
+                egl::CONTEXT_OPENGL_PROFILE_MASK,
+                egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                egl::NONE,
+            ];
+            let context = egl.create_context(display.display, config, None, &context_attributes)?;
+
+            let surface_attributes = [egl::WIDTH, size.x(), egl::HEIGHT, size.y(), egl::NONE];
+            let surface =
+                egl.create_pbuffer_surface(display.display, config, &surface_attributes)?;
+
+            // From here on, dropping the context cleans up.
+            let display = display.release();
+            let headless_context = GLHeadlessContext {
+                egl,
+                display,
+                context,
+                surface,
+                size,
+            };
+            headless_context.make_current()?;
+
+            gl::load_with(|name| match headless_context.egl.get_proc_address(name) {
+                Some(function) => function as *const _,
+                None => std::ptr::null(),
+            });
+
+            Ok(headless_context)
+        }
+    }
+
+    /// Makes this context current on the calling thread.
+    ///
+    /// `new()` already does this; call it again after using another context on this thread.
+    pub fn make_current(&self) -> Result<(), HeadlessError> {
+        self.egl.make_current(
+            self.display,
+            Some(self.surface),
+            Some(self.surface),
+            Some(self.context),
+        )?;
+        Ok(())
+    }
+
+    /// Creates a device that renders with this context.
+    #[inline]
+    pub fn create_device(&self) -> GLDevice {
+        GLDevice::new(GLVersion::GL3, 0)
+    }
+
+    /// Returns the size of the offscreen surface, in device pixels.
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+}
+
+impl Drop for GLHeadlessContext {
+    fn drop(&mut self) {
+        let _ = self.egl.make_current(self.display, None, None, None);
+        let _ = self.egl.destroy_surface(self.display, self.surface);
+        let _ = self.egl.destroy_context(self.display, self.context);
+        let _ = self.egl.terminate(self.display);
+    }
+}
+
+// Terminates an initialized display if context creation fails partway through.
+struct DisplayGuard<'a> {
+    egl: &'a egl::DynamicInstance<egl::EGL1_4>,
+    display: egl::Display,
+}
+
+impl<'a> DisplayGuard<'a> {
+    fn release(self) -> egl::Display {
+        let display = self.display;
+        mem::forget(self);
+        display
+    }
+}
+
+impl<'a> Drop for DisplayGuard<'a> {
+    fn drop(&mut self) {
+        let _ = self.egl.terminate(self.display);
+    }
+}
+
+impl From<egl::Error> for HeadlessError {
+    fn from(error: egl::Error) -> HeadlessError {
+        HeadlessError::Egl(error)
+    }
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeadlessError::Load(ref message) => {
+                write!(formatter, "failed to load EGL: {}", message)
+            }
+            HeadlessError::NoDisplay => write!(formatter, "no EGL display is available"),
+            HeadlessError::NoConfig => write!(formatter, "no suitable EGL configuration"),
+            HeadlessError::Egl(ref error) => write!(formatter, "EGL error: {}", error),
+        }
+    }
+}
+
+impl Error for HeadlessError {}
@@ -13,13 +13,17 @@
 #[macro_use]
 extern crate log;
 
+#[cfg(feature = "headless")]
+pub mod headless;
+
 use gl::types::{GLboolean, GLchar, GLenum, GLfloat, GLint, GLintptr, GLsizei, GLsizeiptr, GLsync};
 use gl::types::{GLuint, GLvoid};
 use half::f16;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{CompressedTextureFormat, ComputeDimensions, ComputeState, DepthFunc};
+use pathfinder_gpu::{Device, DeviceCapabilities, DeviceError, FeatureLevel};
 use pathfinder_gpu::{ImageAccess, ImageBinding, Primitive, ProgramKind, RenderOptions};
 use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, TextureBinding};
 use pathfinder_gpu::{
@@ -28,11 +32,13 @@ use pathfinder_gpu::{
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x4;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ffi::{CStr, CString};
+use std::fs;
 use std::mem;
 use std::ops::Range;
 use std::os::raw::c_char;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::rc::Rc;
 use std::str;
@@ -40,10 +46,20 @@ use std::time::Duration;
 
 const DUMMY_TEXTURE_LENGTH: i32 = 16;
 
+// OpenGL ES 2.0 enums that the core profile bindings don't include.
+const GL_LUMINANCE: GLenum = 0x1909;
+const GL_HALF_FLOAT_OES: GLenum = 0x8d61;
+
+// Compressed texture enums from extensions that the core profile bindings don't include.
+const GL_COMPRESSED_RGBA_S3TC_DXT1_EXT: GLenum = 0x83f1;
+const GL_COMPRESSED_RGBA_S3TC_DXT5_EXT: GLenum = 0x83f3;
+const GL_COMPRESSED_RGBA_ASTC_4X4_KHR: GLenum = 0x93b0;
+
 pub struct GLDevice {
     version: GLVersion,
     default_framebuffer: GLuint,
     dummy_texture: GLTexture,
+    program_cache: Option<GLProgramCache>,
 }
 
 impl GLDevice {
@@ -53,12 +69,14 @@ impl GLDevice {
             gl_texture: 0,
             size: Vector2I::zero(),
             format: TextureFormat::RGBA8,
+            owned: false,
         };
 
         let mut device = GLDevice {
             version,
             default_framebuffer,
             dummy_texture,
+            program_cache: None,
         };
         let dummy_texture_data =
             [0; DUMMY_TEXTURE_LENGTH as usize * DUMMY_TEXTURE_LENGTH as usize * 4];
@@ -74,6 +92,68 @@ impl GLDevice {
         self.default_framebuffer = framebuffer;
     }
 
+    /// Saves linked program binaries to `directory` and loads them from there when the same
+    /// programs are created again, skipping shader compilation.
+    ///
+    /// Binaries are keyed by the driver and the shader sources, so stale entries are never used,
+    /// but they're also never removed. Call this before creating the renderer. This does nothing
+    /// on OpenGL 3.0 and OpenGL ES 2.0, or if the driver doesn't support any binary formats.
+    pub fn set_program_cache_directory<P>(&mut self, directory: P)
+    where
+        P: Into<PathBuf>,
+    {
+        let supported = match self.version {
+            GLVersion::GL4 | GLVersion::GLES3 => unsafe {
+                let mut format_count = 0;
+                gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut format_count);
+                ck();
+                format_count > 0
+            },
+            GLVersion::GLES2 | GLVersion::GL3 => false,
+        };
+        if !supported {
+            warn!("Program binaries aren't supported; not caching programs.");
+            return;
+        }
+
+        self.program_cache = Some(GLProgramCache {
+            directory: directory.into(),
+            driver: format!("{}\n{}", gl_string(gl::RENDERER), gl_string(gl::VERSION)),
+        });
+    }
+
+    /// Wraps a texture created outside Pathfinder, such as a decoded video frame, so that it can
+    /// be used without a copy.
+    ///
+    /// The returned texture doesn't take ownership: dropping it leaves `gl_texture` alive, so the
+    /// caller must keep it alive for as long as Pathfinder uses it.
+    pub fn wrap_texture(
+        &self,
+        gl_texture: GLuint,
+        size: Vector2I,
+        format: TextureFormat,
+    ) -> GLTexture {
+        GLTexture {
+            gl_texture,
+            size,
+            format,
+            owned: false,
+        }
+    }
+
+    /// Wraps a framebuffer object created outside Pathfinder, so that Pathfinder can render into
+    /// it directly, for example as `DestFramebuffer::External`.
+    ///
+    /// `texture` describes the color attachment; wrap it with `wrap_texture()`. Neither the
+    /// framebuffer nor the texture is deleted when the returned framebuffer is dropped.
+    pub fn wrap_framebuffer(&self, gl_framebuffer: GLuint, texture: GLTexture) -> GLFramebuffer {
+        GLFramebuffer {
+            gl_framebuffer,
+            texture,
+            owned: false,
+        }
+    }
+
     fn set_render_state(&self, render_state: &RenderState<GLDevice>) {
         self.bind_render_target(render_state.target);
 
@@ -410,25 +490,49 @@ impl Device for GLDevice {
 
     #[inline]
     fn device_name(&self) -> String {
-        unsafe {
-            CStr::from_ptr(gl::GetString(gl::RENDERER) as *const c_char)
-                .to_string_lossy()
-                .to_string()
-        }
+        gl_string(gl::RENDERER)
     }
 
     fn feature_level(&self) -> FeatureLevel {
         match self.version {
+            GLVersion::GLES2 => FeatureLevel::D3D9,
             GLVersion::GL3 | GLVersion::GLES3 => FeatureLevel::D3D10,
             GLVersion::GL4 => FeatureLevel::D3D11,
         }
     }
 
+    fn capabilities(&self) -> DeviceCapabilities {
+        unsafe {
+            let mut max_texture_size = 0;
+            gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max_texture_size);
+            ck();
+
+            // OpenGL ES 2.0 has no multisampled renderbuffers without extensions.
+            let mut max_msaa_samples = 1;
+            if let GLVersion::GL3 | GLVersion::GLES3 | GLVersion::GL4 = self.version {
+                gl::GetIntegerv(gl::MAX_SAMPLES, &mut max_msaa_samples);
+                ck();
+            }
+
+            DeviceCapabilities {
+                max_texture_size: max_texture_size as u32,
+                compute_shaders: matches!(self.version, GLVersion::GL4),
+                // Core in OpenGL 3.3, but only an extension in OpenGL ES.
+                dual_source_blending: match self.version {
+                    GLVersion::GL3 | GLVersion::GL4 => true,
+                    GLVersion::GLES2 | GLVersion::GLES3 => false,
+                },
+                max_msaa_samples: i32::max(max_msaa_samples, 1) as u32,
+            }
+        }
+    }
+
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> GLTexture {
         let mut texture = GLTexture {
             gl_texture: 0,
             size,
             format,
+            owned: true,
         };
         unsafe {
             gl::GenTextures(1, &mut texture.gl_texture);
@@ -437,12 +541,12 @@ impl Device for GLDevice {
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                format.gl_internal_format(),
+                format.gl_internal_format(self.version),
                 size.x() as GLsizei,
                 size.y() as GLsizei,
                 0,
-                format.gl_format(),
-                format.gl_type(),
+                format.gl_format(self.version),
+                format.gl_type(self.version),
                 ptr::null(),
             );
             ck();
@@ -463,6 +567,7 @@ impl Device for GLDevice {
             gl_texture: 0,
             size,
             format: TextureFormat::R8,
+            owned: true,
         };
         unsafe {
             gl::GenTextures(1, &mut texture.gl_texture);
@@ -471,12 +576,12 @@ impl Device for GLDevice {
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                format.gl_internal_format(),
+                format.gl_internal_format(self.version),
                 size.x() as GLsizei,
                 size.y() as GLsizei,
                 0,
-                format.gl_format(),
-                format.gl_type(),
+                format.gl_format(self.version),
+                format.gl_type(self.version),
                 data_ptr,
             )
         }
@@ -485,114 +590,154 @@ impl Device for GLDevice {
         texture
     }
 
-    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> GLShader {
-        // FIXME(pcwalton): Do this once and cache it.
-        let glsl_version_spec = self.version.to_glsl_version_spec();
+    fn supports_compressed_texture_format(&self, format: CompressedTextureFormat) -> bool {
+        match format {
+            CompressedTextureFormat::BC1 | CompressedTextureFormat::BC3 => {
+                self.has_extension("GL_EXT_texture_compression_s3tc")
+            }
+            CompressedTextureFormat::BC7 => match self.version {
+                GLVersion::GL4 => true,
+                GLVersion::GL3 => self.has_extension("GL_ARB_texture_compression_bptc"),
+                GLVersion::GLES2 | GLVersion::GLES3 => {
+                    self.has_extension("GL_EXT_texture_compression_bptc")
+                }
+            },
+            CompressedTextureFormat::ETC2RGB8 | CompressedTextureFormat::ETC2RGBA8 => {
+                match self.version {
+                    GLVersion::GL4 | GLVersion::GLES3 => true,
+                    GLVersion::GL3 => self.has_extension("GL_ARB_ES3_compatibility"),
+                    GLVersion::GLES2 => false,
+                }
+            }
+            CompressedTextureFormat::ASTC4x4 => {
+                self.has_extension("GL_KHR_texture_compression_astc_ldr")
+            }
+        }
+    }
 
-        let mut output = vec![];
-        self.preprocess(&mut output, source, glsl_version_spec);
-        let source = output;
+    fn create_compressed_texture(
+        &self,
+        format: CompressedTextureFormat,
+        size: Vector2I,
+        data: &[u8],
+    ) -> Option<GLTexture> {
+        if !self.supports_compressed_texture_format(format) {
+            return None;
+        }
+        assert_eq!(data.len(), format.data_size(size));
 
-        let gl_shader_kind = match kind {
-            ShaderKind::Vertex => gl::VERTEX_SHADER,
-            ShaderKind::Fragment => gl::FRAGMENT_SHADER,
-            ShaderKind::Compute => gl::COMPUTE_SHADER,
+        // The format only describes how the texture samples.
+        let mut texture = GLTexture {
+            gl_texture: 0,
+            size,
+            format: TextureFormat::RGBA8,
+            owned: true,
         };
-
         unsafe {
-            let gl_shader = gl::CreateShader(gl_shader_kind);
+            gl::GenTextures(1, &mut texture.gl_texture);
             ck();
-            gl::ShaderSource(
-                gl_shader,
-                1,
-                [source.as_ptr() as *const GLchar].as_ptr(),
-                [source.len() as GLint].as_ptr(),
+            self.bind_texture(&texture, 0);
+            gl::CompressedTexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                format.gl_internal_format(),
+                size.x() as GLsizei,
+                size.y() as GLsizei,
+                0,
+                data.len() as GLsizei,
+                data.as_ptr() as *const GLvoid,
             );
             ck();
-            gl::CompileShader(gl_shader);
-            ck();
+        }
 
-            let mut compile_status = 0;
-            gl::GetShaderiv(gl_shader, gl::COMPILE_STATUS, &mut compile_status);
+        self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
+        Some(texture)
+    }
+
+    fn push_debug_group(&self, name: &str) {
+        // `KHR_debug` is core in GL 4.3 and GLES 3.2; older contexts leave the entry point null.
+        if !gl::PushDebugGroup::is_loaded() {
+            return;
+        }
+        unsafe {
+            gl::PushDebugGroup(
+                gl::DEBUG_SOURCE_APPLICATION,
+                0,
+                name.len() as GLsizei,
+                name.as_ptr() as *const GLchar,
+            );
             ck();
-            if compile_status != gl::TRUE as GLint {
-                let mut info_log_length = 0;
-                gl::GetShaderiv(gl_shader, gl::INFO_LOG_LENGTH, &mut info_log_length);
-                ck();
-                let mut info_log = vec![0; info_log_length as usize];
-                gl::GetShaderInfoLog(
-                    gl_shader,
-                    info_log.len() as GLint,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                ck();
-                error!("Shader info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("{:?} shader '{}' compilation failed", kind, name);
-            }
+        }
+    }
 
-            GLShader { gl_shader }
+    fn pop_debug_group(&self) {
+        if !gl::PopDebugGroup::is_loaded() {
+            return;
+        }
+        unsafe {
+            gl::PopDebugGroup();
+            ck();
         }
     }
 
+    fn set_texture_label(&self, texture: &GLTexture, label: &str) {
+        self.set_object_label(gl::TEXTURE, texture.gl_texture, label);
+    }
+
+    fn set_buffer_label(&self, buffer: &GLBuffer, label: &str) {
+        self.set_object_label(gl::BUFFER, buffer.object.gl_buffer, label);
+    }
+
+    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> GLShader {
+        self.compile_shader_from_source(name, source, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
     fn create_program_from_shaders(
         &self,
-        _resources: &dyn ResourceLoader,
+        resources: &dyn ResourceLoader,
         name: &str,
         shaders: ProgramKind<GLShader>,
     ) -> GLProgram {
-        let gl_program;
-        unsafe {
-            gl_program = gl::CreateProgram();
-            ck();
-            match shaders {
-                ProgramKind::Raster {
-                    vertex: ref vertex_shader,
-                    fragment: ref fragment_shader,
-                } => {
-                    gl::AttachShader(gl_program, vertex_shader.gl_shader);
-                    ck();
-                    gl::AttachShader(gl_program, fragment_shader.gl_shader);
-                    ck();
-                }
-                ProgramKind::Compute(ref compute_shader) => {
-                    gl::AttachShader(gl_program, compute_shader.gl_shader);
-                    ck();
-                }
-            }
-            gl::LinkProgram(gl_program);
-            ck();
+        self.try_create_program_from_shaders(resources, name, shaders)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-            let mut link_status = 0;
-            gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status);
-            ck();
-            if link_status != gl::TRUE as GLint {
-                let mut info_log_length = 0;
-                gl::GetProgramiv(gl_program, gl::INFO_LOG_LENGTH, &mut info_log_length);
-                ck();
-                let mut info_log = vec![0; info_log_length as usize];
-                gl::GetProgramInfoLog(
-                    gl_program,
-                    info_log.len() as GLint,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                ck();
-                eprintln!("Program info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("Program '{}' linking failed", name);
+    fn try_create_program_from_shaders(
+        &self,
+        _resources: &dyn ResourceLoader,
+        name: &str,
+        shaders: ProgramKind<GLShader>,
+    ) -> Result<GLProgram, DeviceError> {
+        let cache_path = self
+            .program_cache
+            .as_ref()
+            .map(|program_cache| program_cache.path_for(name, &shaders));
+
+        let gl_program = match cache_path
+            .as_ref()
+            .and_then(|path| load_program_binary(path))
+        {
+            Some(gl_program) => gl_program,
+            None => {
+                let gl_program = link_program(name, &shaders, cache_path.is_some())?;
+                if let Some(ref cache_path) = cache_path {
+                    save_program_binary(gl_program, cache_path);
+                }
+                gl_program
             }
-        }
+        };
 
         let parameters = GLProgramParameters {
             textures: vec![],
             images: vec![],
         };
 
-        GLProgram {
+        Ok(GLProgram {
             gl_program,
             shaders,
             parameters: RefCell::new(parameters),
-        }
+        })
     }
 
     #[inline]
@@ -680,6 +825,12 @@ impl Device for GLDevice {
     ) {
         debug_assert_ne!(descriptor.stride, 0);
 
+        // OpenGL ES 2.0 has no integer attributes.
+        let descriptor = match self.version {
+            GLVersion::GLES2 => descriptor.to_float_compatible(),
+            GLVersion::GL3 | GLVersion::GLES3 | GLVersion::GL4 => *descriptor,
+        };
+
         self.bind_vertex_array(vertex_array);
 
         unsafe {
@@ -747,6 +898,7 @@ impl Device for GLDevice {
         GLFramebuffer {
             gl_framebuffer,
             texture,
+            owned: true,
         }
     }
 
@@ -804,15 +956,18 @@ impl Device for GLDevice {
     }
 
     #[inline]
-    fn destroy_framebuffer(&self, mut framebuffer: Self::Framebuffer) -> Self::Texture {
+    fn destroy_framebuffer(&self, framebuffer: Self::Framebuffer) -> Self::Texture {
         let texture = GLTexture {
             gl_texture: framebuffer.texture.gl_texture,
             size: framebuffer.texture.size,
             format: framebuffer.texture.format,
+            owned: framebuffer.texture.owned,
         };
-        unsafe {
-            gl::DeleteFramebuffers(1, &mut framebuffer.gl_framebuffer);
-            ck();
+        if framebuffer.owned {
+            unsafe {
+                gl::DeleteFramebuffers(1, &framebuffer.gl_framebuffer);
+                ck();
+            }
         }
         mem::forget(framebuffer);
         texture
@@ -888,12 +1043,12 @@ impl Device for GLDevice {
                 gl::TexImage2D(
                     gl::TEXTURE_2D,
                     0,
-                    texture.format.gl_internal_format(),
+                    texture.format.gl_internal_format(self.version),
                     texture.size.x() as GLsizei,
                     texture.size.y() as GLsizei,
                     0,
-                    texture.format.gl_format(),
-                    texture.format.gl_type(),
+                    texture.format.gl_format(self.version),
+                    texture.format.gl_type(self.version),
                     data_ptr,
                 );
                 ck();
@@ -905,8 +1060,8 @@ impl Device for GLDevice {
                     rect.origin().y(),
                     rect.size().x() as GLsizei,
                     rect.size().y() as GLsizei,
-                    texture.format.gl_format(),
-                    texture.format.gl_type(),
+                    texture.format.gl_format(self.version),
+                    texture.format.gl_type(self.version),
                     data_ptr,
                 );
                 ck();
@@ -945,8 +1100,8 @@ impl Device for GLDevice {
                 origin.y(),
                 size.x() as GLsizei,
                 size.y() as GLsizei,
-                format.gl_format(),
-                format.gl_type(),
+                format.gl_format(self.version),
+                format.gl_type(self.version),
                 0 as *mut GLvoid,
             );
             ck();
@@ -1153,17 +1308,35 @@ impl Device for GLDevice {
         name: &str,
         kind: ShaderKind,
     ) -> Self::Shader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<GLShader, DeviceError> {
         match (self.version, kind) {
-            (GLVersion::GL3, ShaderKind::Compute) | (GLVersion::GLES3, ShaderKind::Compute) => {
-                panic!("Compute shaders are not supported on OpenGL versions prior to 4!")
+            (GLVersion::GLES2, ShaderKind::Compute)
+            | (GLVersion::GL3, ShaderKind::Compute)
+            | (GLVersion::GLES3, ShaderKind::Compute) => {
+                return Err(DeviceError::UnsupportedShader {
+                    name: name.to_owned(),
+                    kind,
+                });
             }
-            (GLVersion::GL3, ShaderKind::Vertex)
+            (GLVersion::GLES2, ShaderKind::Vertex)
+            | (GLVersion::GLES2, ShaderKind::Fragment)
+            | (GLVersion::GL3, ShaderKind::Vertex)
             | (GLVersion::GL3, ShaderKind::Fragment)
             | (GLVersion::GLES3, ShaderKind::Vertex)
             | (GLVersion::GLES3, ShaderKind::Fragment)
             | (GLVersion::GL4, _) => {}
         }
         let directory = match self.version {
+            GLVersion::GLES2 => "gles2",
             GLVersion::GL3 | GLVersion::GLES3 => "gl3",
             GLVersion::GL4 => "gl4",
         };
@@ -1173,7 +1346,11 @@ impl Device for GLDevice {
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/{}/{}.{}s.glsl", directory, name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = match resources.slurp(&path) {
+            Ok(source) => source,
+            Err(error) => return Err(DeviceError::ResourceLoad { path, error }),
+        };
+        self.compile_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> Self::Fence {
@@ -1193,6 +1370,40 @@ impl Device for GLDevice {
 }
 
 impl GLDevice {
+    fn set_object_label(&self, identifier: GLenum, name: GLuint, label: &str) {
+        if !gl::ObjectLabel::is_loaded() {
+            return;
+        }
+        unsafe {
+            gl::ObjectLabel(
+                identifier,
+                name,
+                label.len() as GLsizei,
+                label.as_ptr() as *const GLchar,
+            );
+            ck();
+        }
+    }
+
+    fn has_extension(&self, name: &str) -> bool {
+        unsafe {
+            match self.version {
+                GLVersion::GLES2 => gl_string(gl::EXTENSIONS)
+                    .split_whitespace()
+                    .any(|extension| extension == name),
+                GLVersion::GL3 | GLVersion::GLES3 | GLVersion::GL4 => {
+                    let mut extension_count = 0;
+                    gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut extension_count);
+                    ck();
+                    (0..extension_count as GLuint).any(|index| {
+                        let extension = gl::GetStringi(gl::EXTENSIONS, index);
+                        CStr::from_ptr(extension as *const c_char).to_bytes() == name.as_bytes()
+                    })
+                }
+            }
+        }
+    }
+
     fn bind_render_target(&self, attachment: &RenderTarget<GLDevice>) {
         match *attachment {
             RenderTarget::Default => self.bind_default_framebuffer(),
@@ -1241,7 +1452,7 @@ impl GLDevice {
                 gl::FALSE,
                 0,
                 binding.2.to_gl_access(),
-                binding.1.format.gl_internal_format() as GLenum,
+                binding.1.format.gl_internal_format(self.version) as GLenum,
             );
             ck();
         }
@@ -1282,6 +1493,33 @@ impl GLDevice {
         }
     }
 
+    fn compile_shader_from_source(
+        &self,
+        name: &str,
+        source: &[u8],
+        kind: ShaderKind,
+    ) -> Result<GLShader, DeviceError> {
+        // FIXME(pcwalton): Do this once and cache it.
+        let glsl_version_spec = self.version.to_glsl_version_spec();
+
+        let mut output = vec![];
+        self.preprocess(&mut output, source, glsl_version_spec);
+
+        let shader = GLShader {
+            gl_shader: Cell::new(0),
+            name: name.to_owned(),
+            kind,
+            source: output,
+        };
+
+        // With a program cache, compilation is put off until we know that the program isn't in
+        // the cache.
+        if self.program_cache.is_none() {
+            shader.compile()?;
+        }
+        Ok(shader)
+    }
+
     fn preprocess(&self, output: &mut Vec<u8>, source: &[u8], version: &str) {
         let mut index = 0;
         while index < source.len() {
@@ -1495,10 +1733,15 @@ impl Drop for GLFence {
 pub struct GLFramebuffer {
     pub gl_framebuffer: GLuint,
     pub texture: GLTexture,
+    // False for framebuffers wrapped with `GLDevice::wrap_framebuffer()`.
+    owned: bool,
 }
 
 impl Drop for GLFramebuffer {
     fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
         unsafe {
             gl::DeleteFramebuffers(1, &mut self.gl_framebuffer);
             ck();
@@ -1570,26 +1813,137 @@ pub struct GLProgramParameters {
 }
 
 pub struct GLShader {
-    gl_shader: GLuint,
+    // Zero until the shader is compiled.
+    gl_shader: Cell<GLuint>,
+    name: String,
+    kind: ShaderKind,
+    source: Vec<u8>,
+}
+
+impl GLShader {
+    fn compile(&self) -> Result<GLuint, DeviceError> {
+        if self.gl_shader.get() != 0 {
+            return Ok(self.gl_shader.get());
+        }
+
+        let gl_shader_kind = match self.kind {
+            ShaderKind::Vertex => gl::VERTEX_SHADER,
+            ShaderKind::Fragment => gl::FRAGMENT_SHADER,
+            ShaderKind::Compute => gl::COMPUTE_SHADER,
+        };
+
+        unsafe {
+            let gl_shader = gl::CreateShader(gl_shader_kind);
+            ck();
+            gl::ShaderSource(
+                gl_shader,
+                1,
+                [self.source.as_ptr() as *const GLchar].as_ptr(),
+                [self.source.len() as GLint].as_ptr(),
+            );
+            ck();
+            gl::CompileShader(gl_shader);
+            ck();
+
+            let mut compile_status = 0;
+            gl::GetShaderiv(gl_shader, gl::COMPILE_STATUS, &mut compile_status);
+            ck();
+            if compile_status != gl::TRUE as GLint {
+                let mut info_log_length = 0;
+                gl::GetShaderiv(gl_shader, gl::INFO_LOG_LENGTH, &mut info_log_length);
+                ck();
+                let mut info_log = vec![0; info_log_length as usize];
+                gl::GetShaderInfoLog(
+                    gl_shader,
+                    info_log.len() as GLint,
+                    ptr::null_mut(),
+                    info_log.as_mut_ptr() as *mut GLchar,
+                );
+                ck();
+                gl::DeleteShader(gl_shader);
+                ck();
+                return Err(DeviceError::ShaderCompile {
+                    name: self.name.clone(),
+                    kind: self.kind,
+                    log: info_log_to_string(&info_log),
+                });
+            }
+
+            self.gl_shader.set(gl_shader);
+            Ok(gl_shader)
+        }
+    }
 }
 
 impl Drop for GLShader {
     fn drop(&mut self) {
+        if self.gl_shader.get() == 0 {
+            return;
+        }
         unsafe {
-            gl::DeleteShader(self.gl_shader);
+            gl::DeleteShader(self.gl_shader.get());
             ck();
         }
     }
 }
 
+struct GLProgramCache {
+    directory: PathBuf,
+    // The renderer and version strings, since program binaries are only valid for the driver
+    // that produced them.
+    driver: String,
+}
+
+impl GLProgramCache {
+    fn path_for(&self, name: &str, shaders: &ProgramKind<GLShader>) -> PathBuf {
+        // The file name must stay the same across runs and Rust versions, so this uses FNV-1a
+        // rather than `DefaultHasher`, whose algorithm is unspecified.
+        let mut hasher = ProgramCacheHasher::new();
+        hasher.write(self.driver.as_bytes());
+        match *shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => {
+                hasher.write(&vertex.source);
+                hasher.write(&fragment.source);
+            }
+            ProgramKind::Compute(ref compute) => hasher.write(&compute.source),
+        }
+        let file_name = format!("{}-{:016x}.bin", name.replace('/', "-"), hasher.0);
+        self.directory.join(file_name)
+    }
+}
+
+// A 64-bit FNV-1a hash over length-prefixed byte strings, so that moving bytes from one string
+// to the next changes the hash.
+struct ProgramCacheHasher(u64);
+
+impl ProgramCacheHasher {
+    fn new() -> ProgramCacheHasher {
+        ProgramCacheHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
 pub struct GLTexture {
     gl_texture: GLuint,
     pub size: Vector2I,
     pub format: TextureFormat,
+    // False for textures wrapped with `GLDevice::wrap_texture()`, which we mustn't delete.
+    owned: bool,
 }
 
 impl Drop for GLTexture {
     fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
         unsafe {
             gl::DeleteTextures(1, &mut self.gl_texture);
             ck();
@@ -1626,6 +1980,8 @@ impl BlendFactorExt for BlendFactor {
             BlendFactor::DestAlpha => gl::DST_ALPHA,
             BlendFactor::OneMinusDestAlpha => gl::ONE_MINUS_DST_ALPHA,
             BlendFactor::DestColor => gl::DST_COLOR,
+            BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::OneMinusSrc1Color => gl::ONE_MINUS_SRC1_COLOR,
         }
     }
 }
@@ -1682,6 +2038,7 @@ impl DepthFuncExt for DepthFunc {
     fn to_gl_depth_func(self) -> GLenum {
         match self {
             DepthFunc::Less => gl::LESS,
+            DepthFunc::LessEqual => gl::LEQUAL,
             DepthFunc::Always => gl::ALWAYS,
         }
     }
@@ -1728,13 +2085,17 @@ impl StencilFuncExt for StencilFunc {
 }
 
 trait TextureFormatExt {
-    fn gl_internal_format(self) -> GLint;
-    fn gl_format(self) -> GLuint;
-    fn gl_type(self) -> GLuint;
+    fn gl_internal_format(self, version: GLVersion) -> GLint;
+    fn gl_format(self, version: GLVersion) -> GLuint;
+    fn gl_type(self, version: GLVersion) -> GLuint;
 }
 
 impl TextureFormatExt for TextureFormat {
-    fn gl_internal_format(self) -> GLint {
+    fn gl_internal_format(self, version: GLVersion) -> GLint {
+        // OpenGL ES 2.0 has no sized internal formats.
+        if let GLVersion::GLES2 = version {
+            return self.gl_format(version) as GLint;
+        }
         match self {
             TextureFormat::R8 => gl::R8 as GLint,
             TextureFormat::R16F => gl::R16F as GLint,
@@ -1744,22 +2105,45 @@ impl TextureFormatExt for TextureFormat {
         }
     }
 
-    fn gl_format(self) -> GLuint {
+    fn gl_format(self, version: GLVersion) -> GLuint {
         match self {
-            TextureFormat::R8 | TextureFormat::R16F => gl::RED,
+            TextureFormat::R8 | TextureFormat::R16F => match version {
+                GLVersion::GLES2 => GL_LUMINANCE,
+                GLVersion::GL3 | GLVersion::GLES3 | GLVersion::GL4 => gl::RED,
+            },
             TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => gl::RGBA,
         }
     }
 
-    fn gl_type(self) -> GLuint {
+    fn gl_type(self, version: GLVersion) -> GLuint {
         match self {
             TextureFormat::R8 | TextureFormat::RGBA8 => gl::UNSIGNED_BYTE,
-            TextureFormat::R16F | TextureFormat::RGBA16F => gl::HALF_FLOAT,
+            TextureFormat::R16F | TextureFormat::RGBA16F => match version {
+                GLVersion::GLES2 => GL_HALF_FLOAT_OES,
+                GLVersion::GL3 | GLVersion::GLES3 | GLVersion::GL4 => gl::HALF_FLOAT,
+            },
             TextureFormat::RGBA32F => gl::FLOAT,
         }
     }
 }
 
+trait CompressedTextureFormatExt {
+    fn gl_internal_format(self) -> GLenum;
+}
+
+impl CompressedTextureFormatExt for CompressedTextureFormat {
+    fn gl_internal_format(self) -> GLenum {
+        match self {
+            CompressedTextureFormat::BC1 => GL_COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            CompressedTextureFormat::BC3 => GL_COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            CompressedTextureFormat::BC7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            CompressedTextureFormat::ETC2RGB8 => gl::COMPRESSED_RGB8_ETC2,
+            CompressedTextureFormat::ETC2RGBA8 => gl::COMPRESSED_RGBA8_ETC2_EAC,
+            CompressedTextureFormat::ASTC4x4 => GL_COMPRESSED_RGBA_ASTC_4X4_KHR,
+        }
+    }
+}
+
 trait VertexAttrTypeExt {
     fn to_gl_type(self) -> GLuint;
 }
@@ -1820,6 +2204,13 @@ pub enum GLVersion {
     GLES3 = 1,
     /// OpenGL 4.3+, core profile.
     GL4 = 2,
+    /// OpenGL ES 2.0, with the `OES_vertex_array_object`, `ANGLE_instanced_arrays` (or
+    /// `EXT_instanced_arrays`), `OES_element_index_uint`, `OES_texture_half_float`, and
+    /// `EXT_color_buffer_half_float` extensions, and vertex texture fetch.
+    ///
+    /// Load function pointers with `load_gles2_with()` so that the extension entry points are
+    /// found. Reading pixels back isn't supported.
+    GLES2 = 3,
 }
 
 impl GLVersion {
@@ -1828,7 +2219,181 @@ impl GLVersion {
             GLVersion::GL3 => "330",
             GLVersion::GLES3 => "300 es",
             GLVersion::GL4 => "430",
+            GLVersion::GLES2 => "100",
+        }
+    }
+}
+
+/// Loads OpenGL function pointers for an OpenGL ES 2.0 context, like `gl::load_with()`.
+///
+/// OpenGL ES 2.0 only offers vertex array objects, instancing, and timer queries through
+/// extensions, whose entry points have suffixed names. Any function that the context doesn't
+/// export under its core name is looked up again with the `OES`, `EXT`, and `ANGLE` suffixes.
+pub fn load_gles2_with<F>(mut loader: F)
+where
+    F: FnMut(&str) -> *const GLvoid,
+{
+    gl::load_with(|name| {
+        let function = loader(name);
+        if !function.is_null() {
+            return function;
+        }
+        for suffix in &["OES", "EXT", "ANGLE"] {
+            let function = loader(&format!("{}{}", name, suffix));
+            if !function.is_null() {
+                return function;
+            }
         }
+        ptr::null()
+    });
+}
+
+fn gl_string(name: GLenum) -> String {
+    unsafe {
+        CStr::from_ptr(gl::GetString(name) as *const c_char)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+// Program binaries
+
+fn link_program(
+    name: &str,
+    shaders: &ProgramKind<GLShader>,
+    retrievable: bool,
+) -> Result<GLuint, DeviceError> {
+    // Compile first, so that a failure doesn't leak the program object.
+    let gl_shaders = match *shaders {
+        ProgramKind::Raster {
+            vertex: ref vertex_shader,
+            fragment: ref fragment_shader,
+        } => vec![vertex_shader.compile()?, fragment_shader.compile()?],
+        ProgramKind::Compute(ref compute_shader) => vec![compute_shader.compile()?],
+    };
+
+    unsafe {
+        let gl_program = gl::CreateProgram();
+        ck();
+        for gl_shader in gl_shaders {
+            gl::AttachShader(gl_program, gl_shader);
+            ck();
+        }
+        if retrievable {
+            gl::ProgramParameteri(
+                gl_program,
+                gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                gl::TRUE as GLint,
+            );
+            ck();
+        }
+        gl::LinkProgram(gl_program);
+        ck();
+
+        let mut link_status = 0;
+        gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status);
+        ck();
+        if link_status != gl::TRUE as GLint {
+            let mut info_log_length = 0;
+            gl::GetProgramiv(gl_program, gl::INFO_LOG_LENGTH, &mut info_log_length);
+            ck();
+            let mut info_log = vec![0; info_log_length as usize];
+            gl::GetProgramInfoLog(
+                gl_program,
+                info_log.len() as GLint,
+                ptr::null_mut(),
+                info_log.as_mut_ptr() as *mut GLchar,
+            );
+            ck();
+            gl::DeleteProgram(gl_program);
+            ck();
+            return Err(DeviceError::ProgramLink {
+                name: name.to_owned(),
+                log: info_log_to_string(&info_log),
+            });
+        }
+
+        Ok(gl_program)
+    }
+}
+
+// Info logs end with a NUL terminator.
+fn info_log_to_string(info_log: &[u8]) -> String {
+    let length = info_log
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(info_log.len());
+    String::from_utf8_lossy(&info_log[..length]).into_owned()
+}
+
+// The file holds the binary format as a little-endian `u32`, followed by the binary itself.
+fn load_program_binary(path: &Path) -> Option<GLuint> {
+    let data = fs::read(path).ok()?;
+    if data.len() < 4 {
+        return None;
+    }
+    let format = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let binary = &data[4..];
+
+    unsafe {
+        let gl_program = gl::CreateProgram();
+        ck();
+        gl::ProgramBinary(
+            gl_program,
+            format,
+            binary.as_ptr() as *const GLvoid,
+            binary.len() as GLsizei,
+        );
+        // A driver update can invalidate the format, which is reported as an error. Treat that as
+        // a cache miss instead of failing.
+        gl::GetError();
+
+        let mut link_status = 0;
+        gl::GetProgramiv(gl_program, gl::LINK_STATUS, &mut link_status);
+        ck();
+        if link_status != gl::TRUE as GLint {
+            gl::DeleteProgram(gl_program);
+            ck();
+            return None;
+        }
+        Some(gl_program)
+    }
+}
+
+fn save_program_binary(gl_program: GLuint, path: &Path) {
+    let mut data = vec![0; 4];
+    unsafe {
+        let mut length = 0;
+        gl::GetProgramiv(gl_program, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        ck();
+        if length == 0 {
+            return;
+        }
+
+        let mut format = 0;
+        data.resize(4 + length as usize, 0);
+        gl::GetProgramBinary(
+            gl_program,
+            length,
+            &mut length,
+            &mut format,
+            data[4..].as_mut_ptr() as *mut GLvoid,
+        );
+        ck();
+        data.truncate(4 + length as usize);
+        data[0..4].copy_from_slice(&format.to_le_bytes());
+    }
+
+    let result = match path.parent() {
+        Some(directory) => fs::create_dir_all(directory).and_then(|_| fs::write(path, &data)),
+        None => fs::write(path, &data),
+    };
+    if let Err(err) = result {
+        warn!(
+            "Failed to save program binary to {}: {}",
+            path.display(),
+            err
+        );
     }
 }
 
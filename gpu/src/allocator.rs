@@ -16,6 +16,8 @@ use instant::Instant;
 use pathfinder_geometry::vector::Vector2I;
 use std::collections::VecDeque;
 use std::default::Default;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::mem;
 
 // Everything above 16 MB is allocated exactly.
@@ -130,6 +132,34 @@ pub struct FramebufferID(pub u64);
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct BufferTag(pub &'static str);
 
+/// A snapshot of the GPU memory held by an allocator, broken down by resource type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GPUMemoryUsage {
+    /// Vertex and storage buffers in use.
+    pub general_buffers: ResourceUsage,
+    /// Index buffers in use.
+    pub index_buffers: ResourceUsage,
+    /// Textures in use.
+    pub textures: ResourceUsage,
+    /// Framebuffers in use, including their color textures.
+    pub framebuffers: ResourceUsage,
+    /// Objects that have been freed but are kept around for reuse until they're purged.
+    pub cached: ResourceUsage,
+    /// The resources in use, grouped by the tag they were allocated with and sorted by tag.
+    ///
+    /// A tag whose count keeps growing from scene to scene usually indicates a leak.
+    pub by_tag: Vec<(&'static str, ResourceUsage)>,
+}
+
+/// The number and total size of a group of GPU resources.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResourceUsage {
+    /// The number of resources.
+    pub count: usize,
+    /// The total size of the resources, in bytes.
+    pub bytes: u64,
+}
+
 // For debugging and profiling.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TextureTag(pub &'static str);
@@ -138,6 +168,18 @@ pub struct TextureTag(pub &'static str);
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct FramebufferTag(pub &'static str);
 
+/// Errors that the allocator reports instead of panicking.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AllocationError {
+    /// A texture or framebuffer is larger than the largest texture that the device supports.
+    TextureTooLarge {
+        /// The requested size, in texels.
+        size: Vector2I,
+        /// The largest width or height that the device supports, in texels.
+        max_size: u32,
+    },
+}
+
 impl<D> GPUMemoryAllocator<D>
 where
     D: Device,
@@ -190,6 +232,7 @@ where
             };
 
             allocation.tag = tag;
+            device.set_buffer_label(&allocation.buffer, tag.0);
             self.bytes_committed += allocation.size;
             self.general_buffers_in_use.insert(id, allocation);
             return id;
@@ -201,6 +244,7 @@ where
             BufferData::Uninitialized(byte_size as usize),
             BufferTarget::Vertex,
         );
+        device.set_buffer_label(&buffer, tag.0);
 
         let id = self.next_general_buffer_id;
         self.next_general_buffer_id.0 += 1;
@@ -260,6 +304,7 @@ where
             };
 
             allocation.tag = tag;
+            device.set_buffer_label(&allocation.buffer, tag.0);
             self.bytes_committed += allocation.size;
             self.index_buffers_in_use.insert(id, allocation);
             return id;
@@ -271,6 +316,7 @@ where
             BufferData::Uninitialized(byte_size as usize),
             BufferTarget::Index,
         );
+        device.set_buffer_label(&buffer, tag.0);
 
         let id = self.next_index_buffer_id;
         self.next_index_buffer_id.0 += 1;
@@ -305,6 +351,19 @@ where
         format: TextureFormat,
         tag: TextureTag,
     ) -> TextureID {
+        self.try_allocate_texture(device, size, format, tag)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `allocate_texture()`, but returns an error instead of panicking if the device can't
+    /// create a texture of the given size.
+    pub fn try_allocate_texture(
+        &mut self,
+        device: &D,
+        size: Vector2I,
+        format: TextureFormat,
+        tag: TextureTag,
+    ) -> Result<TextureID, AllocationError> {
         let descriptor = TextureDescriptor {
             width: size.x() as u32,
             height: size.y() as u32,
@@ -330,14 +389,18 @@ where
             };
 
             allocation.tag = tag;
+            device.set_texture_label(&allocation.texture, tag.0);
             self.bytes_committed += allocation.descriptor.byte_size();
             self.textures_in_use.insert(id, allocation);
-            return id;
+            return Ok(id);
         }
 
+        check_texture_size(device, size)?;
+
         debug!("mapping texture: {:?} {:?}", descriptor, tag);
 
         let texture = device.create_texture(format, size);
+        device.set_texture_label(&texture, tag.0);
         let id = self.next_texture_id;
         self.next_texture_id.0 += 1;
 
@@ -353,7 +416,7 @@ where
         self.bytes_allocated += byte_size;
         self.bytes_committed += byte_size;
 
-        id
+        Ok(id)
     }
 
     pub fn allocate_framebuffer(
@@ -363,6 +426,19 @@ where
         format: TextureFormat,
         tag: FramebufferTag,
     ) -> FramebufferID {
+        self.try_allocate_framebuffer(device, size, format, tag)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `allocate_framebuffer()`, but returns an error instead of panicking if the device
+    /// can't create a texture of the given size.
+    pub fn try_allocate_framebuffer(
+        &mut self,
+        device: &D,
+        size: Vector2I,
+        format: TextureFormat,
+        tag: FramebufferTag,
+    ) -> Result<FramebufferID, AllocationError> {
         let descriptor = TextureDescriptor {
             width: size.x() as u32,
             height: size.y() as u32,
@@ -386,14 +462,18 @@ where
             };
 
             allocation.tag = tag;
+            device.set_texture_label(device.framebuffer_texture(&allocation.framebuffer), tag.0);
             self.bytes_committed += allocation.descriptor.byte_size();
             self.framebuffers_in_use.insert(id, allocation);
-            return id;
+            return Ok(id);
         }
 
+        check_texture_size(device, size)?;
+
         debug!("mapping framebuffer: {:?} {:?}", descriptor, tag);
 
         let texture = device.create_texture(format, size);
+        device.set_texture_label(&texture, tag.0);
         let framebuffer = device.create_framebuffer(texture);
         let id = self.next_framebuffer_id;
         self.next_framebuffer_id.0 += 1;
@@ -410,7 +490,7 @@ where
         self.bytes_allocated += byte_size;
         self.bytes_committed += byte_size;
 
-        id
+        Ok(id)
     }
 
     pub fn purge_if_needed(&mut self) {
@@ -531,6 +611,50 @@ where
         self.bytes_committed
     }
 
+    /// Returns the number and size of the resources that this allocator holds, by type and by tag.
+    pub fn memory_usage(&self) -> GPUMemoryUsage {
+        let mut usage = GPUMemoryUsage::default();
+        let mut by_tag: FxHashMap<&'static str, ResourceUsage> = FxHashMap::default();
+        let mut add = |total: &mut ResourceUsage, tag: &'static str, bytes: u64| {
+            total.add(bytes);
+            by_tag.entry(tag).or_default().add(bytes);
+        };
+
+        for allocation in self.general_buffers_in_use.values() {
+            add(
+                &mut usage.general_buffers,
+                allocation.tag.0,
+                allocation.size,
+            );
+        }
+        for allocation in self.index_buffers_in_use.values() {
+            add(&mut usage.index_buffers, allocation.tag.0, allocation.size);
+        }
+        for allocation in self.textures_in_use.values() {
+            let bytes = allocation.descriptor.byte_size();
+            add(&mut usage.textures, allocation.tag.0, bytes);
+        }
+        for allocation in self.framebuffers_in_use.values() {
+            let bytes = allocation.descriptor.byte_size();
+            add(&mut usage.framebuffers, allocation.tag.0, bytes);
+        }
+
+        for free_object in &self.free_objects {
+            usage.cached.add(match free_object.kind {
+                FreeObjectKind::GeneralBuffer { ref allocation, .. }
+                | FreeObjectKind::IndexBuffer { ref allocation, .. } => allocation.size,
+                FreeObjectKind::Texture { ref allocation, .. } => allocation.descriptor.byte_size(),
+                FreeObjectKind::Framebuffer { ref allocation, .. } => {
+                    allocation.descriptor.byte_size()
+                }
+            });
+        }
+
+        usage.by_tag = by_tag.into_iter().collect();
+        usage.by_tag.sort_by_key(|&(tag, _)| tag);
+        usage
+    }
+
     #[allow(dead_code)]
     pub fn dump(&self) {
         println!("GPU memory dump");
@@ -592,8 +716,43 @@ where
     }
 }
 
+impl ResourceUsage {
+    #[inline]
+    fn add(&mut self, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+}
+
 impl TextureDescriptor {
     fn byte_size(&self) -> u64 {
         self.width as u64 * self.height as u64 * self.format.bytes_per_pixel() as u64
     }
 }
+
+impl Display for AllocationError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            AllocationError::TextureTooLarge { size, max_size } => write!(
+                formatter,
+                "texture size {}x{} exceeds the device limit of {}",
+                size.x(),
+                size.y(),
+                max_size
+            ),
+        }
+    }
+}
+
+impl Error for AllocationError {}
+
+fn check_texture_size<D>(device: &D, size: Vector2I) -> Result<(), AllocationError>
+where
+    D: Device,
+{
+    let max_size = device.capabilities().max_texture_size;
+    if size.x() as u32 > max_size || size.y() as u32 > max_size {
+        return Err(AllocationError::TextureTooLarge { size, max_size });
+    }
+    Ok(())
+}
@@ -18,15 +18,21 @@ extern crate log;
 pub mod allocator;
 
 use half::f16;
-use image::ImageFormat;
+use image::{DynamicImage, ImageError, ImageFormat};
 use pathfinder_color::ColorF;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{vec2i, Vector2I};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::future::Future;
+use std::io;
 use std::ops::Range;
 use std::os::raw::c_void;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 pub trait Device: Sized {
@@ -49,6 +55,11 @@ pub trait Device: Sized {
     fn backend_name(&self) -> &'static str;
     fn device_name(&self) -> String;
     fn feature_level(&self) -> FeatureLevel;
+    /// Returns the limits and optional features of this device.
+    ///
+    /// The renderer consults these to pick its rendering path. Applications can inspect them
+    /// before choosing renderer options.
+    fn capabilities(&self) -> DeviceCapabilities;
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> Self::Texture;
     fn create_texture_from_data(
         &self,
@@ -118,6 +129,8 @@ pub trait Device: Sized {
     fn texture_size(&self, texture: &Self::Texture) -> Vector2I;
     fn set_texture_sampling_mode(&self, texture: &Self::Texture, flags: TextureSamplingFlags);
     fn upload_to_texture(&self, texture: &Self::Texture, rect: RectI, data: TextureDataRef);
+    /// Starts reading back pixels from a render target, without waiting for the GPU. Collect the
+    /// result with `try_recv_texture_data()`, `recv_texture_data()`, or `texture_data_future()`.
     fn read_pixels(
         &self,
         target: &RenderTarget<Self>,
@@ -158,18 +171,28 @@ pub trait Device: Sized {
         name: &str,
         format: TextureFormat,
     ) -> Self::Texture {
-        let data = resources.slurp(&format!("textures/{}.png", name)).unwrap();
-        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+        self.try_create_texture_from_png(resources, name, format)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `create_texture_from_png()`, but returns an error if the image can't be loaded.
+    fn try_create_texture_from_png(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        format: TextureFormat,
+    ) -> Result<Self::Texture, DeviceError> {
+        let image = load_png(resources, name)?;
         match format {
             TextureFormat::R8 => {
                 let image = image.to_luma8();
                 let size = vec2i(image.width() as i32, image.height() as i32);
-                self.create_texture_from_data(format, size, TextureDataRef::U8(&image))
+                Ok(self.create_texture_from_data(format, size, TextureDataRef::U8(&image)))
             }
             TextureFormat::RGBA8 => {
                 let image = image.to_rgba8();
                 let size = vec2i(image.width() as i32, image.height() as i32);
-                self.create_texture_from_data(format, size, TextureDataRef::U8(&image))
+                Ok(self.create_texture_from_data(format, size, TextureDataRef::U8(&image)))
             }
             _ => unimplemented!(),
         }
@@ -182,8 +205,19 @@ pub trait Device: Sized {
         texture: &Self::Texture,
         format: TextureFormat,
     ) {
-        let data = resources.slurp(&format!("textures/{}.png", name)).unwrap();
-        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+        self.try_upload_png_to_texture(resources, name, texture, format)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `upload_png_to_texture()`, but returns an error if the image can't be loaded.
+    fn try_upload_png_to_texture(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        texture: &Self::Texture,
+        format: TextureFormat,
+    ) -> Result<(), DeviceError> {
+        let image = load_png(resources, name)?;
         match format {
             TextureFormat::R8 => {
                 let image = image.to_luma8();
@@ -199,6 +233,35 @@ pub trait Device: Sized {
             }
             _ => unimplemented!(),
         }
+        Ok(())
+    }
+
+    /// Like `create_shader()`, but returns an error instead of panicking if the shader can't be
+    /// loaded or compiled.
+    ///
+    /// Backends that defer compilation until link time report compile errors from
+    /// `try_create_program_from_shaders()` instead. The default implementation calls
+    /// `create_shader()`.
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<Self::Shader, DeviceError> {
+        Ok(self.create_shader(resources, name, kind))
+    }
+
+    /// Like `create_program_from_shaders()`, but returns an error instead of panicking if the
+    /// program can't be linked.
+    ///
+    /// The default implementation calls `create_program_from_shaders()`.
+    fn try_create_program_from_shaders(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        shaders: ProgramKind<Self::Shader>,
+    ) -> Result<Self::Program, DeviceError> {
+        Ok(self.create_program_from_shaders(resources, name, shaders))
     }
 
     fn create_program_from_shader_names(
@@ -207,40 +270,252 @@ pub trait Device: Sized {
         program_name: &str,
         shader_names: ProgramKind<&str>,
     ) -> Self::Program {
+        self.try_create_program_from_shader_names(resources, program_name, shader_names)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_program_from_shader_names(
+        &self,
+        resources: &dyn ResourceLoader,
+        program_name: &str,
+        shader_names: ProgramKind<&str>,
+    ) -> Result<Self::Program, DeviceError> {
         let shaders = match shader_names {
             ProgramKind::Raster { vertex, fragment } => ProgramKind::Raster {
-                vertex: self.create_shader(resources, vertex, ShaderKind::Vertex),
-                fragment: self.create_shader(resources, fragment, ShaderKind::Fragment),
+                vertex: self.try_create_shader(resources, vertex, ShaderKind::Vertex)?,
+                fragment: self.try_create_shader(resources, fragment, ShaderKind::Fragment)?,
             },
-            ProgramKind::Compute(compute) => {
-                ProgramKind::Compute(self.create_shader(resources, compute, ShaderKind::Compute))
-            }
+            ProgramKind::Compute(compute) => ProgramKind::Compute(self.try_create_shader(
+                resources,
+                compute,
+                ShaderKind::Compute,
+            )?),
         };
-        self.create_program_from_shaders(resources, program_name, shaders)
+        self.try_create_program_from_shaders(resources, program_name, shaders)
     }
 
     fn create_raster_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+        self.try_create_raster_program(resources, name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_raster_program(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+    ) -> Result<Self::Program, DeviceError> {
         let shaders = ProgramKind::Raster {
             vertex: name,
             fragment: name,
         };
-        self.create_program_from_shader_names(resources, name, shaders)
+        self.try_create_program_from_shader_names(resources, name, shaders)
     }
 
     fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+        self.try_create_compute_program(resources, name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_compute_program(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+    ) -> Result<Self::Program, DeviceError> {
         let shaders = ProgramKind::Compute(name);
-        self.create_program_from_shader_names(resources, name, shaders)
+        self.try_create_program_from_shader_names(resources, name, shaders)
     }
+
+    /// Creates a program for a full-frame post-processing pass from a fragment shader.
+    ///
+    /// The fragment shader is paired with the `blit` vertex shader: it receives the position
+    /// within the frame in `vTexCoord` and should sample the frame from a `uSrc` texture.
+    fn create_post_process_program(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        fragment: Self::Shader,
+    ) -> Self::Program {
+        let vertex = self.create_shader(resources, "blit", ShaderKind::Vertex);
+        self.create_program_from_shaders(resources, name, ProgramKind::Raster { vertex, fragment })
+    }
+
+    /// Returns true if `create_compressed_texture()` accepts the given format on this device.
+    fn supports_compressed_texture_format(&self, _format: CompressedTextureFormat) -> bool {
+        false
+    }
+
+    /// Creates a texture from block-compressed data, which stays compressed in GPU memory.
+    ///
+    /// `data` holds the blocks of the top mip level in row-major order and must be
+    /// `format.data_size(size)` bytes long. The texture samples as non-premultiplied RGBA, so it
+    /// can be painted by registering it with the renderer as an external texture. Returns `None`
+    /// if the device doesn't support the format.
+    fn create_compressed_texture(
+        &self,
+        _format: CompressedTextureFormat,
+        _size: Vector2I,
+        _data: &[u8],
+    ) -> Option<Self::Texture> {
+        None
+    }
+
+    /// Opens a named group of commands, which GPU debuggers such as RenderDoc and Xcode show as
+    /// a collapsible section of the frame. Groups nest and must be closed with
+    /// `pop_debug_group()`. Does nothing on devices without debug marker support.
+    fn push_debug_group(&self, _name: &str) {}
+
+    /// Closes the group most recently opened with `push_debug_group()`.
+    fn pop_debug_group(&self) {}
+
+    /// Attaches a name to a texture for display in GPU debuggers.
+    fn set_texture_label(&self, _texture: &Self::Texture, _label: &str) {}
+
+    /// Attaches a name to a buffer for display in GPU debuggers. Call this after
+    /// `allocate_buffer()`, since some backends replace the underlying storage on allocation.
+    fn set_buffer_label(&self, _buffer: &Self::Buffer, _label: &str) {}
+
+    /// Wraps the receiver returned by `read_pixels()` in a future that resolves to the pixels.
+    ///
+    /// Backends can't tell when the GPU finishes, so the future never wakes its task. Callers
+    /// must poll it again themselves, typically once per frame after `end_commands()`; an
+    /// executor that waits for a wakeup will wait forever.
+    fn texture_data_future(
+        &self,
+        receiver: Self::TextureDataReceiver,
+    ) -> TextureDataFuture<'_, Self> {
+        TextureDataFuture {
+            device: self,
+            receiver,
+        }
+    }
+}
+
+/// A future that resolves to the pixels read by `Device::read_pixels()`.
+///
+/// The future doesn't wake its task when the pixels are ready; see
+/// `Device::texture_data_future()`.
+pub struct TextureDataFuture<'a, D>
+where
+    D: Device,
+{
+    device: &'a D,
+    receiver: D::TextureDataReceiver,
+}
+
+impl<'a, D> Future for TextureDataFuture<'a, D>
+where
+    D: Device,
+{
+    type Output = TextureData;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context) -> Poll<TextureData> {
+        match self.device.try_recv_texture_data(&self.receiver) {
+            Some(texture_data) => Poll::Ready(texture_data),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Display for DeviceError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            DeviceError::ResourceLoad {
+                ref path,
+                ref error,
+            } => write!(formatter, "failed to load `{}`: {}", path, error),
+            DeviceError::ImageDecode {
+                ref path,
+                ref error,
+            } => write!(formatter, "failed to decode `{}`: {}", path, error),
+            DeviceError::UnsupportedShader { ref name, kind } => write!(
+                formatter,
+                "{:?} shader '{}' isn't supported by this device",
+                kind, name
+            ),
+            DeviceError::ShaderCompile {
+                ref name,
+                kind,
+                ref log,
+            } => write!(
+                formatter,
+                "{:?} shader '{}' compilation failed:\n{}",
+                kind, name, log
+            ),
+            DeviceError::ProgramLink { ref name, ref log } => {
+                write!(formatter, "program '{}' linking failed:\n{}", name, log)
+            }
+        }
+    }
+}
+
+impl Error for DeviceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            DeviceError::ResourceLoad { ref error, .. } => Some(error),
+            DeviceError::ImageDecode { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn load_png(resources: &dyn ResourceLoader, name: &str) -> Result<DynamicImage, DeviceError> {
+    let path = format!("textures/{}.png", name);
+    let data = match resources.slurp(&path) {
+        Ok(data) => data,
+        Err(error) => return Err(DeviceError::ResourceLoad { path, error }),
+    };
+    image::load_from_memory_with_format(&data, ImageFormat::Png)
+        .map_err(|error| DeviceError::ImageDecode { path, error })
 }
 
 /// These are rough analogues to D3D versions; don't expect them to represent exactly the feature
 /// set of the versions.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FeatureLevel {
+    /// OpenGL ES 2.0 and WebGL 1-class hardware. There are no integer vertex attributes, texture
+    /// arrays, or uniform buffers, and shaders can't do integer arithmetic.
+    ///
+    /// Integer vertex attributes are supplied to shaders as floats instead, as described in
+    /// `VertexAttrDescriptor::to_float_compatible()`.
+    D3D9,
     D3D10,
     D3D11,
 }
 
+/// The limits and optional features of a device, as returned by `Device::capabilities()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    /// The largest width or height of a 2D texture, in pixels.
+    pub max_texture_size: u32,
+    /// Whether compute shaders are available. The D3D11 renderer level requires them.
+    pub compute_shaders: bool,
+    /// Whether blend states may use the second fragment output (`Src1`) factors.
+    pub dual_source_blending: bool,
+    /// The largest number of samples per pixel that multisampled render targets may have. This
+    /// is 1 if multisampling isn't supported.
+    pub max_msaa_samples: u32,
+}
+
+/// Errors that can occur when creating shaders, programs, and textures from resources.
+#[derive(Debug)]
+pub enum DeviceError {
+    /// A resource couldn't be read from the resource loader.
+    ResourceLoad { path: String, error: io::Error },
+    /// A PNG resource couldn't be decoded.
+    ImageDecode { path: String, error: ImageError },
+    /// The device doesn't support shaders of this kind; for example, OpenGL 3 has no compute
+    /// shaders.
+    UnsupportedShader { name: String, kind: ShaderKind },
+    /// A shader failed to compile. `log` holds the driver's diagnostics.
+    ShaderCompile {
+        name: String,
+        kind: ShaderKind,
+        log: String,
+    },
+    /// A program failed to link. `log` holds the driver's diagnostics.
+    ProgramLink { name: String, log: String },
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TextureFormat {
     R8,
@@ -250,6 +525,23 @@ pub enum TextureFormat {
     RGBA32F,
 }
 
+/// A block-compressed texture format. Every format here uses 4×4 pixel blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressedTextureFormat {
+    /// BC1 (DXT1): RGB with 1-bit alpha, 8 bytes per block. Common on desktop GPUs.
+    BC1,
+    /// BC3 (DXT5): RGBA, 16 bytes per block. Common on desktop GPUs.
+    BC3,
+    /// BC7: high-quality RGBA, 16 bytes per block. Common on desktop GPUs.
+    BC7,
+    /// ETC2: RGB, 8 bytes per block. Common on mobile GPUs.
+    ETC2RGB8,
+    /// ETC2 with EAC alpha: RGBA, 16 bytes per block. Common on mobile GPUs.
+    ETC2RGBA8,
+    /// ASTC with 4×4 blocks, LDR profile: RGBA, 16 bytes per block. Common on mobile GPUs.
+    ASTC4x4,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum VertexAttrType {
     F32,
@@ -396,6 +688,9 @@ pub enum BlendFactor {
     DestAlpha,
     OneMinusDestAlpha,
     DestColor,
+    OneMinusSrcColor,
+    /// One minus the second color output of the fragment shader. Requires dual-source blending.
+    OneMinusSrc1Color,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -416,6 +711,7 @@ pub struct DepthState {
 #[derive(Clone, Copy, Debug)]
 pub enum DepthFunc {
     Less,
+    LessEqual,
     Always,
 }
 
@@ -540,6 +836,60 @@ impl TextureFormat {
     }
 }
 
+impl CompressedTextureFormat {
+    /// The size of a block, in pixels.
+    #[inline]
+    pub fn block_size(self) -> Vector2I {
+        vec2i(4, 4)
+    }
+
+    /// The size of a block, in bytes.
+    #[inline]
+    pub fn bytes_per_block(self) -> usize {
+        match self {
+            CompressedTextureFormat::BC1 | CompressedTextureFormat::ETC2RGB8 => 8,
+            CompressedTextureFormat::BC3
+            | CompressedTextureFormat::BC7
+            | CompressedTextureFormat::ETC2RGBA8
+            | CompressedTextureFormat::ASTC4x4 => 16,
+        }
+    }
+
+    /// Returns the number of bytes of compressed data for an image of the given size. Partial
+    /// blocks at the right and bottom edges count as whole blocks.
+    #[inline]
+    pub fn data_size(self, size: Vector2I) -> usize {
+        let block_size = self.block_size();
+        let blocks_x = (size.x() + block_size.x() - 1) / block_size.x();
+        let blocks_y = (size.y() + block_size.y() - 1) / block_size.y();
+        blocks_x as usize * blocks_y as usize * self.bytes_per_block()
+    }
+}
+
+impl VertexAttrDescriptor {
+    /// Returns the equivalent descriptor for devices without integer vertex attributes.
+    ///
+    /// Integer attributes become non-normalized floating-point attributes. 32-bit integers can't
+    /// all be represented exactly as floats, so each one is split into its low and high signed
+    /// 16-bit halves instead, and the shader reassembles the value as `high * 65536 + low`, after
+    /// adding 65536 to `low` if it's negative.
+    pub fn to_float_compatible(&self) -> VertexAttrDescriptor {
+        if self.class != VertexAttrClass::Int {
+            return *self;
+        }
+        let (size, attr_type) = match self.attr_type {
+            VertexAttrType::I32 => (self.size * 2, VertexAttrType::I16),
+            attr_type => (self.size, attr_type),
+        };
+        VertexAttrDescriptor {
+            size,
+            class: VertexAttrClass::Float,
+            attr_type,
+            ..*self
+        }
+    }
+}
+
 impl ClearOps {
     #[inline]
     pub fn has_ops(&self) -> bool {
@@ -8,191 +8,254 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Experimental support for Lottie. This is very incomplete.
+//! Loads Lottie (Bodymovin) animations exported from After Effects and builds Pathfinder scenes
+//! from them.
+//!
+//! Parse an animation with `Lottie::from_reader()`, then call `Lottie::build_scene()` with a frame
+//! number to get a scene showing the animation at that point in time. Shape, solid, null, and
+//! precomposition layers are supported, along with parenting, shape groups and transforms,
+//! rectangles, ellipses, paths, fills, strokes, and trim paths. Layer and group opacity are
+//! composited with scene groups. Expressions, masks, mattes, effects, images, and text are
+//! ignored.
 
 use serde::{Deserialize, Serialize};
 use serde_json::Error as JSONError;
 use std::io::Read;
 
+mod scene;
+mod trim;
+mod value;
+
+pub const LAYER_TYPE_PRECOMP: i64 = 0;
+pub const LAYER_TYPE_SOLID: i64 = 1;
+pub const LAYER_TYPE_IMAGE: i64 = 2;
+pub const LAYER_TYPE_NULL: i64 = 3;
+pub const LAYER_TYPE_SHAPE: i64 = 4;
+pub const LAYER_TYPE_TEXT: i64 = 5;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Lottie {
     #[serde(rename = "v")]
+    #[serde(default)]
     pub version: String,
     #[serde(rename = "fr")]
-    pub frame_rate: i64,
+    pub frame_rate: f32,
     #[serde(rename = "ip")]
-    pub in_point: i64,
+    pub in_point: f32,
     #[serde(rename = "op")]
-    pub out_point: i64,
+    pub out_point: f32,
     #[serde(rename = "w")]
-    pub width: f64,
+    pub width: f32,
     #[serde(rename = "h")]
-    pub height: f64,
+    pub height: f32,
     #[serde(rename = "ddd")]
+    #[serde(default)]
     pub three_d: i64,
+    #[serde(default)]
     pub assets: Vec<Asset>,
     pub layers: Vec<Layer>,
 }
 
+/// A reusable item. Only precompositions, which have layers, are supported.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Asset {}
+pub struct Asset {
+    pub id: String,
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+}
 
-// FIXME(pcwalton): Using an untagged enum is a botch here. There actually is a tag: it's just an
-// integer, which `serde_json` doesn't support natively.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum Layer {
-    Shape {
-        #[serde(rename = "ddd")]
-        three_d: i64,
-        #[serde(rename = "ind")]
-        index: i64,
-        #[serde(rename = "nm")]
-        name: String,
-        #[serde(rename = "ks")]
-        transform: Transform,
-        #[serde(rename = "ao")]
-        auto_orient: i64,
-        #[serde(rename = "ip")]
-        in_point: i64,
-        #[serde(rename = "op")]
-        out_point: i64,
-        #[serde(rename = "st")]
-        start_time: i64,
-        #[serde(rename = "bm")]
-        blend_mode: i64,
-        #[serde(rename = "sr")]
-        stretch: i64,
-        #[serde(rename = "ln")]
-        #[serde(default)]
-        layer_id: Option<String>,
-        shapes: Vec<Shape>,
-    },
+pub struct Layer {
+    /// One of the `LAYER_TYPE_` constants.
+    #[serde(rename = "ty")]
+    pub layer_type: i64,
+    #[serde(rename = "ddd")]
+    #[serde(default)]
+    pub three_d: i64,
+    #[serde(rename = "ind")]
+    #[serde(default)]
+    pub index: Option<i64>,
+    #[serde(rename = "parent")]
+    #[serde(default)]
+    pub parent: Option<i64>,
+    #[serde(rename = "nm")]
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "ks")]
+    pub transform: Transform,
+    #[serde(rename = "ao")]
+    #[serde(default)]
+    pub auto_orient: i64,
+    #[serde(rename = "ip")]
+    pub in_point: f32,
+    #[serde(rename = "op")]
+    pub out_point: f32,
+    #[serde(rename = "st")]
+    #[serde(default)]
+    pub start_time: f32,
+    #[serde(rename = "bm")]
+    #[serde(default)]
+    pub blend_mode: i64,
+    #[serde(rename = "sr")]
+    #[serde(default = "default_stretch")]
+    pub stretch: f32,
+    #[serde(rename = "hd")]
+    #[serde(default)]
+    pub hidden: bool,
+    /// True if this layer is only used as a track matte for the layer below it.
+    #[serde(rename = "td")]
+    #[serde(default)]
+    pub track_matte_source: i64,
+    #[serde(rename = "ln")]
+    #[serde(default)]
+    pub layer_id: Option<String>,
+    /// The shapes of a shape layer.
+    #[serde(default)]
+    pub shapes: Vec<Shape>,
+    /// The ID of the asset that a precomposition layer shows.
+    #[serde(rename = "refId")]
+    #[serde(default)]
+    pub ref_id: Option<String>,
+    /// The size of a precomposition layer, which clips its contents.
+    #[serde(rename = "w")]
+    #[serde(default)]
+    pub width: Option<f32>,
+    #[serde(rename = "h")]
+    #[serde(default)]
+    pub height: Option<f32>,
+    /// Maps the time of a precomposition layer to the time of its contents, in seconds.
+    #[serde(rename = "tm")]
+    #[serde(default)]
+    pub time_remap: Option<Property>,
+    /// The color of a solid layer, as `#rrggbb`.
+    #[serde(rename = "sc")]
+    #[serde(default)]
+    pub solid_color: Option<String>,
+    #[serde(rename = "sw")]
+    #[serde(default)]
+    pub solid_width: Option<f32>,
+    #[serde(rename = "sh")]
+    #[serde(default)]
+    pub solid_height: Option<f32>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+fn default_stretch() -> f32 {
+    1.0
+}
+
+/// The transform of a layer or a shape group. Missing properties have their identity values.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Transform {
-    #[serde(rename = "p")]
-    pub position: MultidimensionalPropertyValue,
     #[serde(rename = "a")]
-    pub anchor_point: MultidimensionalPropertyValue,
+    #[serde(default)]
+    pub anchor_point: Option<Property>,
+    #[serde(rename = "p")]
+    #[serde(default)]
+    pub position: Option<Position>,
+    /// The scale, in percent.
     #[serde(rename = "s")]
-    pub scale: MultidimensionalPropertyValue,
-    #[serde(rename = "r")]
-    pub rotation: PropertyValue,
-    #[serde(rename = "o")]
     #[serde(default)]
-    pub opacity: Option<PropertyValue>,
-    #[serde(rename = "px")]
+    pub scale: Option<Property>,
+    /// The rotation, in degrees clockwise.
+    #[serde(rename = "r")]
     #[serde(default)]
-    pub position_x: Option<PropertyValue>,
-    #[serde(rename = "py")]
+    pub rotation: Option<Property>,
+    /// The rotation of a 3D layer around the Z axis, which is used as its rotation.
+    #[serde(rename = "rz")]
     #[serde(default)]
-    pub position_y: Option<PropertyValue>,
-    #[serde(rename = "pz")]
+    pub rotation_z: Option<Property>,
+    /// The opacity, in percent.
+    #[serde(rename = "o")]
     #[serde(default)]
-    pub position_z: Option<PropertyValue>,
+    pub opacity: Option<Property>,
+    /// The skew angle, in degrees.
     #[serde(rename = "sk")]
     #[serde(default)]
-    pub skew: Option<PropertyValue>,
+    pub skew: Option<Property>,
+    /// The direction of the skew, in degrees.
     #[serde(rename = "sa")]
     #[serde(default)]
-    pub skew_axis: Option<PropertyValue>,
+    pub skew_axis: Option<Property>,
 }
 
+/// A position, which may be animated as a whole or one dimension at a time.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
-pub enum PropertyValue {
-    Value {
-        #[serde(rename = "k")]
-        value: f32,
-        #[serde(rename = "x")]
-        #[serde(default)]
-        expression: Option<String>,
-        #[serde(rename = "ix")]
-        #[serde(default)]
-        index: Option<i64>,
-    },
-    KeyframedValue {
-        #[serde(rename = "k")]
-        keyframes: Vec<KeyframeValue>,
-        #[serde(rename = "x")]
-        #[serde(default)]
-        expression: Option<String>,
-        #[serde(rename = "ix")]
-        #[serde(default)]
-        index: Option<i64>,
+pub enum Position {
+    Split {
+        #[serde(rename = "s")]
+        split: bool,
+        x: Property,
+        y: Property,
     },
+    Combined(Property),
 }
 
+/// A possibly-animated number or list of numbers.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct KeyframeValue {
-    #[serde(rename = "s")]
+pub struct Property {
+    #[serde(rename = "a")]
     #[serde(default)]
-    pub start: Option<Vec<f32>>,
-    #[serde(rename = "t")]
-    pub time: i64,
-    #[serde(rename = "i")]
+    pub animated: i64,
+    #[serde(rename = "k")]
+    pub value: PropertyValue,
+    #[serde(rename = "x")]
     #[serde(default)]
-    pub interpolation: Option<OffsetInterpolation>,
-}
-
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-pub struct Interpolation {
-    pub x: f32,
-    pub y: f32,
+    pub expression: Option<String>,
+    #[serde(rename = "ix")]
+    #[serde(default)]
+    pub index: Option<i64>,
 }
 
+// FIXME(pcwalton): Using an untagged enum is a botch here. Whether the value is keyframed is
+// given by the `a` field of the property, but serde can't use a sibling field as the tag.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct OffsetInterpolation {
-    pub x: Vec<f32>,
-    pub y: Vec<f32>,
+#[serde(untagged)]
+pub enum PropertyValue {
+    Scalar(f32),
+    Vector(Vec<f32>),
+    Keyframed(Vec<Keyframe>),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct OffsetKeyframe {
+pub struct Keyframe {
+    #[serde(rename = "t")]
+    pub time: f32,
     #[serde(rename = "s")]
     #[serde(default)]
-    pub start: Option<Vec<f32>>,
-    #[serde(rename = "t")]
-    pub time: i64,
+    pub start: Option<Values>,
+    /// The value at the next keyframe. Newer files omit this and use the next keyframe's start.
+    #[serde(rename = "e")]
+    #[serde(default)]
+    pub end: Option<Values>,
+    /// The easing into the next keyframe.
     #[serde(rename = "i")]
     #[serde(default)]
-    pub in_value: Option<OffsetInterpolation>,
+    pub in_value: Option<Easing>,
+    /// The easing out of this keyframe.
     #[serde(rename = "o")]
     #[serde(default)]
-    pub out_value: Option<OffsetInterpolation>,
+    pub out_value: Option<Easing>,
+    /// 1 if the value holds until the next keyframe instead of being interpolated.
+    #[serde(rename = "h")]
+    #[serde(default)]
+    pub hold: i64,
 }
 
+/// One or more numbers. Scalars are sometimes written as single-element arrays.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
-pub enum MultidimensionalPropertyValue {
-    Value {
-        #[serde(rename = "k")]
-        value: Vec<f32>,
-        #[serde(rename = "x")]
-        #[serde(default)]
-        expression: Option<String>,
-        #[serde(rename = "ix")]
-        #[serde(default)]
-        index: Option<i64>,
-    },
-    KeyframedValue {
-        #[serde(rename = "k")]
-        keyframes: Vec<OffsetKeyframe>,
-        #[serde(rename = "x")]
-        #[serde(default)]
-        expression: Option<String>,
-        #[serde(rename = "ix")]
-        #[serde(default)]
-        index: Option<i64>,
-        #[serde(rename = "ti")]
-        #[serde(default)]
-        in_tangent: Option<i64>,
-        #[serde(rename = "to")]
-        #[serde(default)]
-        out_tangent: Option<i64>,
-    },
+pub enum Values {
+    Scalar(f32),
+    Vector(Vec<f32>),
+}
+
+/// A control point of the cubic Bézier easing curve between two keyframes, either for all
+/// dimensions or one per dimension.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Easing {
+    pub x: Values,
+    pub y: Values,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -203,15 +266,55 @@ pub enum Shape {
         #[serde(rename = "it")]
         items: Vec<Shape>,
         #[serde(rename = "nm")]
+        #[serde(default)]
         name: String,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
     },
     #[serde(rename = "sh")]
     Shape {
         #[serde(rename = "ks")]
         vertices: ShapeVertices,
+        /// 3 if the path runs in reverse.
+        #[serde(rename = "d")]
+        #[serde(default)]
+        direction: Option<i64>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
+    },
+    #[serde(rename = "rc")]
+    Rectangle {
+        /// The center of the rectangle.
+        #[serde(rename = "p")]
+        position: Property,
+        #[serde(rename = "s")]
+        size: Property,
+        /// The corner radius.
+        #[serde(rename = "r")]
+        #[serde(default)]
+        roundness: Option<Property>,
+        #[serde(rename = "d")]
+        #[serde(default)]
+        direction: Option<i64>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
+    },
+    #[serde(rename = "el")]
+    Ellipse {
+        /// The center of the ellipse.
+        #[serde(rename = "p")]
+        position: Property,
+        #[serde(rename = "s")]
+        size: Property,
         #[serde(rename = "d")]
         #[serde(default)]
         direction: Option<i64>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
     },
     #[serde(rename = "fl")]
     Fill {
@@ -220,24 +323,66 @@ pub enum Shape {
         name: Option<String>,
         #[serde(rename = "o")]
         #[serde(default)]
-        opacity: Option<PropertyValue>,
+        opacity: Option<Property>,
+        /// The color, as RGB or RGBA components from 0 to 1.
+        #[serde(rename = "c")]
+        color: Property,
+        /// 1 for the nonzero fill rule, 2 for even-odd.
+        #[serde(rename = "r")]
+        #[serde(default)]
+        fill_rule: Option<i64>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
+    },
+    #[serde(rename = "st")]
+    Stroke {
+        #[serde(rename = "nm")]
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(rename = "o")]
+        #[serde(default)]
+        opacity: Option<Property>,
         #[serde(rename = "c")]
-        color: MultidimensionalPropertyValue,
+        color: Property,
+        #[serde(rename = "w")]
+        width: Property,
+        /// 1 for butt caps, 2 for round, 3 for square.
+        #[serde(rename = "lc")]
+        #[serde(default)]
+        line_cap: Option<i64>,
+        /// 1 for miter joins, 2 for round, 3 for bevel.
+        #[serde(rename = "lj")]
+        #[serde(default)]
+        line_join: Option<i64>,
+        #[serde(rename = "ml")]
+        #[serde(default)]
+        miter_limit: Option<f32>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
     },
     #[serde(rename = "tr")]
-    Transform {
-        #[serde(rename = "r")]
-        rotation: PropertyValue,
-        #[serde(rename = "sk")]
-        skew: PropertyValue,
-        #[serde(rename = "sa")]
-        skew_axis: PropertyValue,
-        #[serde(rename = "p")]
-        position: MultidimensionalPropertyValue,
-        #[serde(rename = "a")]
-        anchor_point: MultidimensionalPropertyValue,
+    Transform(Transform),
+    #[serde(rename = "tm")]
+    TrimPath {
+        /// The start of the visible part of the paths, in percent.
         #[serde(rename = "s")]
-        scale: MultidimensionalPropertyValue,
+        start: Property,
+        /// The end of the visible part of the paths, in percent.
+        #[serde(rename = "e")]
+        end: Property,
+        /// An offset added to the start and end, in degrees, where 360 is the whole length.
+        #[serde(rename = "o")]
+        #[serde(default)]
+        offset: Option<Property>,
+        /// 1 to trim each path separately, 2 to trim the paths as if they were one.
+        #[serde(rename = "m")]
+        #[serde(default)]
+        mode: Option<i64>,
+        #[serde(rename = "hd")]
+        #[serde(default)]
+        hidden: bool,
     },
     #[serde(other)]
     Unimplemented,
@@ -256,6 +401,7 @@ pub enum ShapeVertices {
         #[serde(default)]
         index: Option<i64>,
         #[serde(rename = "a")]
+        #[serde(default)]
         animated: i64,
     },
     ShapeKeyframed {
@@ -268,19 +414,16 @@ pub enum ShapeVertices {
         #[serde(default)]
         index: Option<i64>,
         #[serde(rename = "a")]
-        animated: i64,
-        #[serde(rename = "ti")]
-        #[serde(default)]
-        in_tangent: Vec<i64>,
-        #[serde(rename = "to")]
         #[serde(default)]
-        out_tangent: Vec<i64>,
+        animated: i64,
     },
 }
 
+/// A Bézier path. The tangents are relative to their vertices.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShapeProperty {
     #[serde(rename = "c")]
+    #[serde(default)]
     pub closed: bool,
     #[serde(rename = "i")]
     pub in_points: Vec<[f32; 2]>,
@@ -292,17 +435,23 @@ pub struct ShapeProperty {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShapeKeyframeProperty {
+    #[serde(rename = "t")]
+    pub time: f32,
     #[serde(rename = "s")]
     #[serde(default)]
-    pub start: Vec<Option<ShapeProperty>>,
-    #[serde(rename = "t")]
-    pub time: i64,
+    pub start: Option<Vec<ShapeProperty>>,
+    #[serde(rename = "e")]
+    #[serde(default)]
+    pub end: Option<Vec<ShapeProperty>>,
     #[serde(rename = "i")]
     #[serde(default)]
-    pub in_value: Option<OffsetInterpolation>,
+    pub in_value: Option<Easing>,
     #[serde(rename = "o")]
     #[serde(default)]
-    pub out_value: Option<OffsetInterpolation>,
+    pub out_value: Option<Easing>,
+    #[serde(rename = "h")]
+    #[serde(default)]
+    pub hold: i64,
 }
 
 impl Lottie {
@@ -313,4 +462,29 @@ impl Lottie {
     {
         serde_json::from_reader(reader)
     }
+
+    /// Returns the number of frames in the animation.
+    #[inline]
+    pub fn frame_count(&self) -> f32 {
+        self.out_point - self.in_point
+    }
+
+    /// Returns the length of the animation, in seconds.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.frame_count() / self.frame_rate
+    }
+
+    /// Returns the frame number at the given time in seconds from the start of the animation.
+    ///
+    /// Times past the end of the animation wrap around, so that it loops.
+    pub fn frame_at_time(&self, time: f32) -> f32 {
+        let frame = time * self.frame_rate;
+        let frame_count = self.frame_count();
+        if frame_count > 0.0 {
+            self.in_point + frame.rem_euclid(frame_count)
+        } else {
+            self.in_point
+        }
+    }
 }
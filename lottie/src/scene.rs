@@ -0,0 +1,579 @@
+// pathfinder/lottie/src/scene.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Builds Pathfinder scenes from Lottie animations.
+
+use crate::trim;
+use crate::{Layer, Lottie, Property, Shape, ShapeProperty, Transform};
+use crate::{LAYER_TYPE_PRECOMP, LAYER_TYPE_SHAPE, LAYER_TYPE_SOLID};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Group, Scene};
+
+// Guards against precompositions that contain themselves.
+const MAX_PRECOMP_DEPTH: u32 = 16;
+
+impl Lottie {
+    /// Builds a scene showing the animation at the given frame.
+    ///
+    /// Frames are numbered in the animation's own time, from `in_point` to `out_point`, and may be
+    /// fractional. The view box of the scene covers the animation's `width` and `height`. Use
+    /// `frame_at_time()` to find the frame to show at a point in time.
+    pub fn build_scene(&self, frame: f32) -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(self.width, self.height)));
+        let mut builder = SceneBuilder {
+            lottie: self,
+            scene,
+        };
+        builder.draw_layers(&self.layers, frame, Transform2F::default(), None, 0);
+        builder.scene
+    }
+}
+
+struct SceneBuilder<'a> {
+    lottie: &'a Lottie,
+    scene: Scene,
+}
+
+impl<'a> SceneBuilder<'a> {
+    fn draw_layers(
+        &mut self,
+        layers: &[Layer],
+        frame: f32,
+        transform: Transform2F,
+        clip_path: Option<ClipPathId>,
+        depth: u32,
+    ) {
+        // The first layer is the topmost one.
+        for layer in layers.iter().rev() {
+            if layer.hidden
+                || layer.track_matte_source != 0
+                || frame < layer.in_point
+                || frame >= layer.out_point
+            {
+                continue;
+            }
+
+            // Properties are animated relative to the start time of the layer.
+            let layer_frame = frame - layer.start_time;
+            let opacity = opacity_at(&layer.transform, layer_frame);
+            if opacity <= 0.0 {
+                continue;
+            }
+            let layer_transform = transform * layer_transform(layers, layer, frame, 0);
+
+            let mut group = Group::new();
+            group.set_opacity(opacity);
+            group.set_blend_mode(blend_mode(layer.blend_mode));
+            group.set_name(layer.name.clone());
+            self.scene.push_group(group);
+
+            match layer.layer_type {
+                LAYER_TYPE_SHAPE => {
+                    self.draw_shape_group(&layer.shapes, layer_frame, layer_transform, clip_path)
+                }
+                LAYER_TYPE_SOLID => self.draw_solid(layer, layer_transform, clip_path),
+                LAYER_TYPE_PRECOMP if depth < MAX_PRECOMP_DEPTH => {
+                    self.draw_precomp(layer, layer_frame, layer_transform, clip_path, depth)
+                }
+                _ => {}
+            }
+
+            self.scene.pop_group();
+        }
+    }
+
+    fn draw_precomp(
+        &mut self,
+        layer: &Layer,
+        layer_frame: f32,
+        transform: Transform2F,
+        clip_path: Option<ClipPathId>,
+        depth: u32,
+    ) {
+        let lottie = self.lottie;
+        let asset = match layer.ref_id {
+            Some(ref ref_id) => match lottie.assets.iter().find(|asset| asset.id == *ref_id) {
+                Some(asset) => asset,
+                None => return,
+            },
+            None => return,
+        };
+
+        let frame = match layer.time_remap {
+            Some(ref time_remap) => time_remap.scalar_at(layer_frame) * lottie.frame_rate,
+            None if layer.stretch != 0.0 => layer_frame / layer.stretch,
+            None => layer_frame,
+        };
+
+        // Precompositions are clipped to their size.
+        let clip_path = match (layer.width, layer.height) {
+            (Some(width), Some(height)) => {
+                let rect = RectF::new(Vector2F::zero(), vec2f(width, height));
+                let mut clip = ClipPath::new(Outline::from_rect(rect).transformed(&transform));
+                clip.set_clip_path(clip_path);
+                clip.set_name(format!("Clip({})", layer.name));
+                Some(self.scene.push_clip_path(clip))
+            }
+            _ => clip_path,
+        };
+
+        self.draw_layers(&asset.layers, frame, transform, clip_path, depth + 1);
+    }
+
+    fn draw_solid(&mut self, layer: &Layer, transform: Transform2F, clip_path: Option<ClipPathId>) {
+        let color = match layer
+            .solid_color
+            .as_ref()
+            .and_then(|color| parse_hex_color(color))
+        {
+            Some(color) => color,
+            None => return,
+        };
+        let size = vec2f(
+            layer.solid_width.unwrap_or(0.0),
+            layer.solid_height.unwrap_or(0.0),
+        );
+        let outline =
+            Outline::from_rect(RectF::new(Vector2F::zero(), size)).transformed(&transform);
+        self.push_draw_path(
+            outline,
+            color,
+            FillRule::Winding,
+            clip_path,
+            layer.name.clone(),
+        );
+    }
+
+    // Draws the items of a shape layer or group. Items are listed from top to bottom, and fills
+    // and strokes paint all of the paths listed before them, including those in nested groups.
+    fn draw_shape_group(
+        &mut self,
+        items: &[Shape],
+        frame: f32,
+        transform: Transform2F,
+        clip_path: Option<ClipPathId>,
+    ) {
+        let transform = transform * group_transform(items, frame);
+        for (index, item) in items.iter().enumerate().rev() {
+            match *item {
+                Shape::Group {
+                    items: ref group_items,
+                    ref name,
+                    hidden: false,
+                } => {
+                    let opacity = group_opacity(group_items, frame);
+                    if opacity <= 0.0 {
+                        continue;
+                    }
+                    let mut group = Group::new();
+                    group.set_opacity(opacity);
+                    group.set_name(name.clone());
+                    self.scene.push_group(group);
+                    self.draw_shape_group(group_items, frame, transform, clip_path);
+                    self.scene.pop_group();
+                }
+                Shape::Fill {
+                    ref name,
+                    ref opacity,
+                    ref color,
+                    fill_rule,
+                    hidden: false,
+                } => {
+                    let outline = merge_outlines(collect_paths(&items[..index], frame));
+                    let color = color_at(color, opacity, frame);
+                    let fill_rule = match fill_rule {
+                        Some(2) => FillRule::EvenOdd,
+                        _ => FillRule::Winding,
+                    };
+                    let name = name.clone().unwrap_or_else(|| "Fill".to_owned());
+                    self.push_draw_path(
+                        outline.transformed(&transform),
+                        color,
+                        fill_rule,
+                        clip_path,
+                        name,
+                    );
+                }
+                Shape::Stroke {
+                    ref name,
+                    ref opacity,
+                    ref color,
+                    ref width,
+                    line_cap,
+                    line_join,
+                    miter_limit,
+                    hidden: false,
+                } => {
+                    let outline = merge_outlines(collect_paths(&items[..index], frame));
+                    let line_width = width.scalar_at(frame);
+                    if outline.is_empty() || line_width <= 0.0 {
+                        continue;
+                    }
+                    let style = StrokeStyle {
+                        line_width,
+                        line_cap: match line_cap {
+                            Some(2) => LineCap::Round,
+                            Some(3) => LineCap::Square,
+                            _ => LineCap::Butt,
+                        },
+                        line_join: match line_join {
+                            Some(2) => LineJoin::Round,
+                            Some(3) => LineJoin::Bevel,
+                            _ => LineJoin::Miter(miter_limit.unwrap_or(4.0)),
+                        },
+                    };
+                    let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+                    stroke_to_fill.offset();
+                    let outline = stroke_to_fill.into_outline().transformed(&transform);
+
+                    let color = color_at(color, opacity, frame);
+                    let name = name.clone().unwrap_or_else(|| "Stroke".to_owned());
+                    self.push_draw_path(outline, color, FillRule::Winding, clip_path, name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_draw_path(
+        &mut self,
+        outline: Outline,
+        color: ColorU,
+        fill_rule: FillRule,
+        clip_path: Option<ClipPathId>,
+        name: String,
+    ) {
+        if outline.is_empty() || color.a == 0 {
+            return;
+        }
+        let paint_id = self.scene.push_paint(&Paint::from_color(color));
+        let mut draw_path = DrawPath::new(outline, paint_id);
+        draw_path.set_fill_rule(fill_rule);
+        draw_path.set_clip_path(clip_path);
+        draw_path.set_name(name);
+        self.scene.push_draw_path(draw_path);
+    }
+}
+
+// Returns the paths of the given shape items, in the coordinate space of the group containing
+// them, after applying any trim paths among them.
+fn collect_paths(items: &[Shape], frame: f32) -> Vec<Outline> {
+    let mut paths = vec![];
+    for item in items {
+        match *item {
+            Shape::Shape {
+                ref vertices,
+                direction,
+                hidden: false,
+            } => {
+                if let Some(path) = vertices.path_at(frame) {
+                    paths.push(directed(path_outline(&path), direction));
+                }
+            }
+            Shape::Rectangle {
+                ref position,
+                ref size,
+                ref roundness,
+                direction,
+                hidden: false,
+            } => {
+                let size = size.vector_at(frame);
+                let rect = RectF::new(position.vector_at(frame) - size * 0.5, size);
+                let radius = match *roundness {
+                    Some(ref roundness) => roundness.scalar_at(frame),
+                    None => 0.0,
+                };
+                let radius = radius.min(size.x() * 0.5).min(size.y() * 0.5);
+                let contour = if radius > 0.0 {
+                    Contour::from_rect_rounded(rect, Vector2F::splat(radius))
+                } else {
+                    Contour::from_rect(rect)
+                };
+                let mut outline = Outline::new();
+                outline.push_contour(contour);
+                paths.push(directed(outline, direction));
+            }
+            Shape::Ellipse {
+                ref position,
+                ref size,
+                direction,
+                hidden: false,
+            } => {
+                let transform = Transform2F::from_translation(position.vector_at(frame))
+                    * Transform2F::from_scale(size.vector_at(frame) * 0.5);
+                let mut contour = Contour::new();
+                contour.push_ellipse(&transform);
+                contour.close();
+                let mut outline = Outline::new();
+                outline.push_contour(contour);
+                paths.push(directed(outline, direction));
+            }
+            Shape::Group {
+                items: ref group_items,
+                hidden: false,
+                ..
+            } => {
+                let transform = group_transform(group_items, frame);
+                paths.extend(
+                    collect_paths(group_items, frame)
+                        .into_iter()
+                        .map(|outline| outline.transformed(&transform)),
+                );
+            }
+            Shape::TrimPath {
+                ref start,
+                ref end,
+                ref offset,
+                mode,
+                hidden: false,
+            } => {
+                let offset = match *offset {
+                    Some(ref offset) => offset.scalar_at(frame) / 360.0,
+                    None => 0.0,
+                };
+                let (start, end) = (start.scalar_at(frame) * 0.01, end.scalar_at(frame) * 0.01);
+                let (start, end) = (start.min(end) + offset, start.max(end) + offset);
+                paths = trim_paths(paths, start, end, mode == Some(2));
+            }
+            _ => {}
+        }
+    }
+    paths
+}
+
+// Trims the paths to the range from `start` to `end`, which are fractions of their length. If
+// `together` is true, the paths are trimmed as if they were one path; otherwise, each one is.
+fn trim_paths(paths: Vec<Outline>, start: f32, end: f32, together: bool) -> Vec<Outline> {
+    if end - start >= 1.0 {
+        return paths;
+    }
+    if end <= start {
+        return vec![];
+    }
+
+    let start = start.rem_euclid(1.0);
+    let mut end = start + (end - start);
+    if end > 1.0 {
+        end -= 1.0;
+    }
+
+    if together {
+        vec![trim::trim_outline(&merge_outlines(paths), start, end)]
+    } else {
+        paths
+            .iter()
+            .map(|outline| trim::trim_outline(outline, start, end))
+            .collect()
+    }
+}
+
+fn path_outline(path: &ShapeProperty) -> Outline {
+    let mut outline = Outline::new();
+    let vertices: Vec<Vector2F> = path.vertices.iter().map(|&[x, y]| vec2f(x, y)).collect();
+    let point_at = |points: &[[f32; 2]], index: usize| match points.get(index) {
+        Some(&[x, y]) => vec2f(x, y),
+        None => Vector2F::zero(),
+    };
+    if vertices.is_empty() {
+        return outline;
+    }
+
+    let mut contour = Contour::new();
+    contour.push_endpoint(vertices[0]);
+    let mut push_curve = |from_index: usize, to_index: usize| {
+        let out_tangent = point_at(&path.out_points, from_index);
+        let in_tangent = point_at(&path.in_points, to_index);
+        let to = vertices[to_index];
+        if out_tangent == Vector2F::zero() && in_tangent == Vector2F::zero() {
+            contour.push_endpoint(to);
+        } else {
+            contour.push_cubic(vertices[from_index] + out_tangent, to + in_tangent, to);
+        }
+    };
+    for index in 1..vertices.len() {
+        push_curve(index - 1, index);
+    }
+    if path.closed {
+        push_curve(vertices.len() - 1, 0);
+        contour.close();
+    }
+    outline.push_contour(contour);
+    outline
+}
+
+// Reverses the outline if the shape's direction is 3, which affects trimming and the nonzero
+// fill rule.
+fn directed(outline: Outline, direction: Option<i64>) -> Outline {
+    if direction != Some(3) {
+        return outline;
+    }
+    let mut reversed = Outline::new();
+    for mut contour in outline.into_contours() {
+        contour.reverse();
+        reversed.push_contour(contour);
+    }
+    reversed
+}
+
+fn merge_outlines(outlines: Vec<Outline>) -> Outline {
+    let mut merged = Outline::new();
+    for outline in outlines {
+        merged.push_outline(outline);
+    }
+    merged
+}
+
+// Returns the transform of the layer at the given frame of its composition, including the
+// transforms of its parents.
+fn layer_transform(layers: &[Layer], layer: &Layer, frame: f32, depth: usize) -> Transform2F {
+    let transform = transform_at(&layer.transform, frame - layer.start_time);
+    let parent = layer.parent.and_then(|parent_index| {
+        layers
+            .iter()
+            .find(|parent| parent.index == Some(parent_index))
+    });
+    match parent {
+        Some(parent) if depth < layers.len() => {
+            layer_transform(layers, parent, frame, depth + 1) * transform
+        }
+        _ => transform,
+    }
+}
+
+fn group_transform(items: &[Shape], frame: f32) -> Transform2F {
+    for item in items {
+        if let Shape::Transform(ref transform) = *item {
+            return transform_at(transform, frame);
+        }
+    }
+    Transform2F::default()
+}
+
+fn group_opacity(items: &[Shape], frame: f32) -> f32 {
+    for item in items {
+        if let Shape::Transform(ref transform) = *item {
+            return opacity_at(transform, frame);
+        }
+    }
+    1.0
+}
+
+fn transform_at(transform: &Transform, frame: f32) -> Transform2F {
+    let anchor_point = match transform.anchor_point {
+        Some(ref anchor_point) => anchor_point.vector_at(frame),
+        None => Vector2F::zero(),
+    };
+    let position = match transform.position {
+        Some(ref position) => position.vector_at(frame),
+        None => Vector2F::zero(),
+    };
+    let scale = match transform.scale {
+        Some(ref scale) => scale.vector_at(frame) * 0.01,
+        None => Vector2F::splat(1.0),
+    };
+    let rotation = match transform
+        .rotation
+        .as_ref()
+        .or(transform.rotation_z.as_ref())
+    {
+        Some(rotation) => rotation.scalar_at(frame).to_radians(),
+        None => 0.0,
+    };
+
+    let skew = match transform.skew {
+        Some(ref skew) => skew.scalar_at(frame).to_radians(),
+        None => 0.0,
+    };
+    let skew = if skew != 0.0 {
+        let skew_axis = match transform.skew_axis {
+            Some(ref skew_axis) => skew_axis.scalar_at(frame).to_radians(),
+            None => 0.0,
+        };
+        let shear = Transform2F::row_major(1.0, (-skew).tan(), 0.0, 0.0, 1.0, 0.0);
+        Transform2F::from_rotation(skew_axis) * shear * Transform2F::from_rotation(-skew_axis)
+    } else {
+        Transform2F::default()
+    };
+
+    Transform2F::from_translation(position)
+        * Transform2F::from_rotation(rotation)
+        * skew
+        * Transform2F::from_scale(scale)
+        * Transform2F::from_translation(-anchor_point)
+}
+
+fn opacity_at(transform: &Transform, frame: f32) -> f32 {
+    match transform.opacity {
+        Some(ref opacity) => (opacity.scalar_at(frame) * 0.01).max(0.0).min(1.0),
+        None => 1.0,
+    }
+}
+
+fn color_at(color: &Property, opacity: &Option<Property>, frame: f32) -> ColorU {
+    let components = color.values_at(frame);
+    let component = |index: usize| components.get(index).cloned().unwrap_or(0.0);
+    // Very old files give components from 0 to 255.
+    let scale = if components.iter().any(|&component| component > 1.0) {
+        1.0 / 255.0
+    } else {
+        1.0
+    };
+    let opacity = match *opacity {
+        Some(ref opacity) => opacity.scalar_at(frame) * 0.01,
+        None => 1.0,
+    };
+    let alpha = components.get(3).map_or(1.0, |&alpha| alpha * scale) * opacity;
+    ColorF::new(
+        component(0) * scale,
+        component(1) * scale,
+        component(2) * scale,
+        alpha.max(0.0).min(1.0),
+    )
+    .to_u8()
+}
+
+fn parse_hex_color(color: &str) -> Option<ColorU> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some(ColorU::from_u32((rgb << 8) | 0xff))
+}
+
+fn blend_mode(blend_mode: i64) -> BlendMode {
+    match blend_mode {
+        1 => BlendMode::Multiply,
+        2 => BlendMode::Screen,
+        3 => BlendMode::Overlay,
+        4 => BlendMode::Darken,
+        5 => BlendMode::Lighten,
+        6 => BlendMode::ColorDodge,
+        7 => BlendMode::ColorBurn,
+        8 => BlendMode::HardLight,
+        9 => BlendMode::SoftLight,
+        10 => BlendMode::Difference,
+        11 => BlendMode::Exclusion,
+        12 => BlendMode::Hue,
+        13 => BlendMode::Saturation,
+        14 => BlendMode::Color,
+        15 => BlendMode::Luminosity,
+        _ => BlendMode::SrcOver,
+    }
+}
@@ -0,0 +1,154 @@
+// pathfinder/lottie/src/trim.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Trims outlines to a fraction of their length, as the "Trim Paths" shape modifier does.
+
+use pathfinder_content::outline::{Contour, ContourIterFlags, Outline};
+use pathfinder_content::segment::Segment;
+
+// The number of pieces each curve is divided into to measure its length.
+const LENGTH_SAMPLES: usize = 16;
+
+/// Returns the part of the outline between `start` and `end`, which are fractions of its total
+/// length from 0 to 1. The range wraps around if `end` is less than `start`.
+pub(crate) fn trim_outline(outline: &Outline, start: f32, end: f32) -> Outline {
+    let segments: Vec<Vec<MeasuredSegment>> = outline
+        .contours()
+        .iter()
+        .map(|contour| {
+            contour
+                .iter(ContourIterFlags::empty())
+                .map(MeasuredSegment::new)
+                .collect()
+        })
+        .collect();
+    let total_length: f32 = segments
+        .iter()
+        .flat_map(|contour| contour.iter())
+        .map(|segment| segment.length())
+        .sum();
+
+    let mut output = Outline::new();
+    if total_length <= 0.0 {
+        return output;
+    }
+    if end < start {
+        push_range(&mut output, &segments, start * total_length, total_length);
+        push_range(&mut output, &segments, 0.0, end * total_length);
+    } else {
+        push_range(
+            &mut output,
+            &segments,
+            start * total_length,
+            end * total_length,
+        );
+    }
+    output
+}
+
+// Pushes the parts of the contours between the given distances along them onto the output.
+fn push_range(output: &mut Outline, contours: &[Vec<MeasuredSegment>], start: f32, end: f32) {
+    let mut distance = 0.0;
+    for contour in contours {
+        let mut trimmed = Contour::new();
+        for segment in contour {
+            let length = segment.length();
+            let (segment_start, segment_end) = (distance, distance + length);
+            distance = segment_end;
+            if segment_end <= start || segment_start >= end || length <= 0.0 {
+                continue;
+            }
+
+            let t0 = segment.time_at_distance(start - segment_start);
+            let t1 = segment.time_at_distance(end - segment_start);
+            push_segment(&mut trimmed, &sub_segment(&segment.segment, t0, t1));
+        }
+        if !trimmed.is_empty() {
+            output.push_contour(trimmed);
+        }
+    }
+}
+
+fn sub_segment(segment: &Segment, t0: f32, t1: f32) -> Segment {
+    let mut segment = *segment;
+    if t1 < 1.0 {
+        segment = segment.split(t1).0;
+    }
+    if t0 > 0.0 && t1 > 0.0 {
+        segment = segment.split(t0 / t1).1;
+    }
+    segment
+}
+
+fn push_segment(contour: &mut Contour, segment: &Segment) {
+    if contour.is_empty() {
+        contour.push_endpoint(segment.baseline.from());
+    }
+    if segment.is_line() {
+        contour.push_endpoint(segment.baseline.to());
+    } else if segment.is_quadratic() {
+        contour.push_quadratic(segment.ctrl.from(), segment.baseline.to());
+    } else if segment.is_cubic() {
+        contour.push_cubic(
+            segment.ctrl.from(),
+            segment.ctrl.to(),
+            segment.baseline.to(),
+        );
+    }
+}
+
+// A segment with the lengths along it at evenly-spaced parametric values.
+struct MeasuredSegment {
+    segment: Segment,
+    lengths: Vec<f32>,
+}
+
+impl MeasuredSegment {
+    fn new(segment: Segment) -> MeasuredSegment {
+        let sample_count = if segment.is_line() { 1 } else { LENGTH_SAMPLES };
+        let mut lengths = Vec::with_capacity(sample_count + 1);
+        let (mut length, mut last_point) = (0.0, segment.sample(0.0));
+        lengths.push(0.0);
+        for index in 1..=sample_count {
+            let point = segment.sample(index as f32 / sample_count as f32);
+            length += (point - last_point).length();
+            lengths.push(length);
+            last_point = point;
+        }
+        MeasuredSegment { segment, lengths }
+    }
+
+    fn length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    fn time_at_distance(&self, distance: f32) -> f32 {
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        if distance >= self.length() {
+            return 1.0;
+        }
+        let sample_count = self.lengths.len() - 1;
+        let index = self
+            .lengths
+            .iter()
+            .position(|&length| length >= distance)
+            .unwrap_or(sample_count)
+            .max(1);
+        let (prev_length, next_length) = (self.lengths[index - 1], self.lengths[index]);
+        let fraction = if next_length > prev_length {
+            (distance - prev_length) / (next_length - prev_length)
+        } else {
+            0.0
+        };
+        ((index - 1) as f32 + fraction) / sample_count as f32
+    }
+}
@@ -0,0 +1,320 @@
+// pathfinder/lottie/src/value.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Evaluates animated properties at a given frame.
+
+use crate::{Easing, Keyframe, Position, Property, PropertyValue, ShapeKeyframeProperty};
+use crate::{ShapeProperty, ShapeVertices, Values};
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+
+// The number of Newton-Raphson iterations used to invert easing curves.
+const EASING_ITERATIONS: usize = 8;
+
+impl Property {
+    /// Returns the value of the property at the given frame.
+    pub fn values_at(&self, frame: f32) -> Vec<f32> {
+        match self.value {
+            PropertyValue::Scalar(value) => vec![value],
+            PropertyValue::Vector(ref values) => values.clone(),
+            PropertyValue::Keyframed(ref keyframes) => keyframes_at(keyframes, frame),
+        }
+    }
+
+    /// Returns the first component of the value of the property at the given frame.
+    pub fn scalar_at(&self, frame: f32) -> f32 {
+        self.values_at(frame).first().cloned().unwrap_or(0.0)
+    }
+
+    /// Returns the first two components of the value of the property at the given frame.
+    pub fn vector_at(&self, frame: f32) -> Vector2F {
+        let values = self.values_at(frame);
+        match values[..] {
+            [] => Vector2F::zero(),
+            [value] => Vector2F::splat(value),
+            [x, y, ..] => vec2f(x, y),
+        }
+    }
+}
+
+impl Position {
+    /// Returns the position at the given frame.
+    pub fn vector_at(&self, frame: f32) -> Vector2F {
+        match *self {
+            Position::Split { ref x, ref y, .. } => vec2f(x.scalar_at(frame), y.scalar_at(frame)),
+            Position::Combined(ref property) => property.vector_at(frame),
+        }
+    }
+}
+
+impl ShapeVertices {
+    /// Returns the path at the given frame, or `None` if the shape has no keyframes.
+    pub fn path_at(&self, frame: f32) -> Option<ShapeProperty> {
+        match *self {
+            ShapeVertices::Shape { ref value, .. } => Some(value.clone()),
+            ShapeVertices::ShapeKeyframed { ref value, .. } => shape_keyframes_at(value, frame),
+        }
+    }
+}
+
+impl Values {
+    fn to_vec(&self) -> Vec<f32> {
+        match *self {
+            Values::Scalar(value) => vec![value],
+            Values::Vector(ref values) => values.clone(),
+        }
+    }
+
+    // Returns the component for the given dimension, or the last one if there are fewer.
+    fn component(&self, index: usize) -> f32 {
+        match *self {
+            Values::Scalar(value) => value,
+            Values::Vector(ref values) => match values.get(index).or(values.last()) {
+                Some(&value) => value,
+                None => 0.0,
+            },
+        }
+    }
+}
+
+fn keyframes_at(keyframes: &[Keyframe], frame: f32) -> Vec<f32> {
+    let (index, progress) = match find_keyframe(keyframes.iter().map(|key| key.time), frame) {
+        None => return vec![],
+        Some(found) => found,
+    };
+
+    let keyframe = &keyframes[index];
+    let next_keyframe = keyframes.get(index + 1);
+    let start = match (&keyframe.start, index.checked_sub(1)) {
+        (&Some(ref start), _) => start.to_vec(),
+        // The last keyframe of older files only has a time; it holds the previous end value.
+        (&None, Some(prev_index)) => match keyframes[prev_index].end {
+            Some(ref end) => end.to_vec(),
+            None => vec![],
+        },
+        (&None, None) => vec![],
+    };
+    if progress <= 0.0 || keyframe.hold != 0 {
+        return start;
+    }
+
+    let next_start = next_keyframe.and_then(|next| next.start.as_ref());
+    let end = match keyframe.end.as_ref().or(next_start) {
+        Some(end) => end.to_vec(),
+        None => return start,
+    };
+
+    start
+        .iter()
+        .zip(end.iter())
+        .enumerate()
+        .map(|(dimension, (&start, &end))| {
+            let t = ease(&keyframe.out_value, &keyframe.in_value, dimension, progress);
+            start + (end - start) * t
+        })
+        .collect()
+}
+
+fn shape_keyframes_at(keyframes: &[ShapeKeyframeProperty], frame: f32) -> Option<ShapeProperty> {
+    let (index, progress) = find_keyframe(keyframes.iter().map(|key| key.time), frame)?;
+
+    let keyframe = &keyframes[index];
+    let start = match (&keyframe.start, index.checked_sub(1)) {
+        (&Some(ref start), _) => start.first()?.clone(),
+        (&None, Some(prev_index)) => keyframes[prev_index].end.as_ref()?.first()?.clone(),
+        (&None, None) => return None,
+    };
+    if progress <= 0.0 || keyframe.hold != 0 {
+        return Some(start);
+    }
+
+    let next_start = keyframes
+        .get(index + 1)
+        .and_then(|next| next.start.as_ref());
+    let end = match keyframe
+        .end
+        .as_ref()
+        .or(next_start)
+        .and_then(|end| end.first())
+    {
+        Some(end) => end,
+        None => return Some(start),
+    };
+    if end.vertices.len() != start.vertices.len() {
+        return Some(start);
+    }
+
+    let t = ease(&keyframe.out_value, &keyframe.in_value, 0, progress);
+    let lerp_points = |start: &[[f32; 2]], end: &[[f32; 2]]| -> Vec<[f32; 2]> {
+        start
+            .iter()
+            .zip(end.iter())
+            .map(|(start, end)| {
+                [
+                    start[0] + (end[0] - start[0]) * t,
+                    start[1] + (end[1] - start[1]) * t,
+                ]
+            })
+            .collect()
+    };
+    Some(ShapeProperty {
+        closed: start.closed,
+        in_points: lerp_points(&start.in_points, &end.in_points),
+        out_points: lerp_points(&start.out_points, &end.out_points),
+        vertices: lerp_points(&start.vertices, &end.vertices),
+    })
+}
+
+// Returns the index of the keyframe that the frame falls after, and how far the frame is toward
+// the next keyframe, from 0 to 1.
+fn find_keyframe<I>(times: I, frame: f32) -> Option<(usize, f32)>
+where
+    I: Iterator<Item = f32>,
+{
+    let times: Vec<f32> = times.collect();
+    let last_index = times.len().checked_sub(1)?;
+    if frame <= times[0] {
+        return Some((0, 0.0));
+    }
+    for index in 0..last_index {
+        let (start_time, end_time) = (times[index], times[index + 1]);
+        if frame < end_time {
+            let progress = if end_time > start_time {
+                (frame - start_time) / (end_time - start_time)
+            } else {
+                1.0
+            };
+            return Some((index, progress));
+        }
+    }
+    // After the last keyframe, the value holds at it. Older files give the last keyframe's value
+    // as the end of the one before.
+    Some((last_index, 0.0))
+}
+
+// Applies the cubic Bézier easing curve running from (0, 0) through the out and in control points
+// to (1, 1). Without control points, the progress is linear.
+fn ease(out_value: &Option<Easing>, in_value: &Option<Easing>, dimension: usize, x: f32) -> f32 {
+    let (out_value, in_value) = match (out_value, in_value) {
+        (&Some(ref out_value), &Some(ref in_value)) => (out_value, in_value),
+        _ => return x,
+    };
+    let (x1, y1) = (
+        out_value.x.component(dimension),
+        out_value.y.component(dimension),
+    );
+    let (x2, y2) = (
+        in_value.x.component(dimension),
+        in_value.y.component(dimension),
+    );
+    let t = solve_bezier_for_x(x1, x2, x);
+    bezier(y1, y2, t)
+}
+
+fn bezier(p1: f32, p2: f32, t: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+}
+
+fn bezier_derivative(p1: f32, p2: f32, t: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * p1 + 6.0 * u * t * (p2 - p1) + 3.0 * t * t * (1.0 - p2)
+}
+
+fn solve_bezier_for_x(x1: f32, x2: f32, x: f32) -> f32 {
+    let x1 = x1.max(0.0).min(1.0);
+    let x2 = x2.max(0.0).min(1.0);
+
+    // Newton-Raphson usually converges quickly; fall back to bisection where the slope is flat.
+    let mut t = x;
+    for _ in 0..EASING_ITERATIONS {
+        let error = bezier(x1, x2, t) - x;
+        if error.abs() < 1e-5 {
+            return t;
+        }
+        let slope = bezier_derivative(x1, x2, t);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        t = (t - error / slope).max(0.0).min(1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    while high - low > 1e-5 {
+        if bezier(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) * 0.5;
+    }
+    t
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ease, keyframes_at};
+    use crate::{Easing, Keyframe, Values};
+
+    fn keyframe(time: f32, start: f32) -> Keyframe {
+        Keyframe {
+            time,
+            start: Some(Values::Vector(vec![start])),
+            end: None,
+            in_value: None,
+            out_value: None,
+            hold: 0,
+        }
+    }
+
+    #[test]
+    fn test_linear_keyframes() {
+        let keyframes = vec![keyframe(0.0, 0.0), keyframe(10.0, 100.0)];
+        assert_eq!(keyframes_at(&keyframes, -5.0), vec![0.0]);
+        assert_eq!(keyframes_at(&keyframes, 5.0), vec![50.0]);
+        assert_eq!(keyframes_at(&keyframes, 20.0), vec![100.0]);
+    }
+
+    #[test]
+    fn test_hold_keyframes() {
+        let mut keyframes = vec![keyframe(0.0, 0.0), keyframe(10.0, 100.0)];
+        keyframes[0].hold = 1;
+        assert_eq!(keyframes_at(&keyframes, 9.0), vec![0.0]);
+    }
+
+    #[test]
+    fn test_easing() {
+        let linear = (
+            Some(Easing {
+                x: Values::Scalar(0.25),
+                y: Values::Scalar(0.25),
+            }),
+            Some(Easing {
+                x: Values::Scalar(0.75),
+                y: Values::Scalar(0.75),
+            }),
+        );
+        assert!((ease(&linear.0, &linear.1, 0, 0.3) - 0.3).abs() < 0.001);
+
+        let ease_in = (
+            Some(Easing {
+                x: Values::Scalar(0.42),
+                y: Values::Scalar(0.0),
+            }),
+            Some(Easing {
+                x: Values::Scalar(1.0),
+                y: Values::Scalar(1.0),
+            }),
+        );
+        let value = ease(&ease_in.0, &ease_in.1, 0, 0.5);
+        assert!(value > 0.0 && value < 0.5);
+        assert!((ease(&ease_in.0, &ease_in.1, 0, 1.0) - 1.0).abs() < 0.001);
+    }
+}
@@ -20,7 +20,7 @@ extern crate objc;
 use block::{Block, ConcreteBlock, RcBlock};
 use byteorder::{NativeEndian, WriteBytesExt};
 use cocoa::base::{id, nil};
-use cocoa::foundation::{NSAutoreleasePool, NSUInteger};
+use cocoa::foundation::{NSAutoreleasePool, NSString, NSUInteger};
 use core_foundation::base::TCFType;
 use core_foundation::string::{CFString, CFStringRef};
 use dispatch::ffi::dispatch_queue_t;
@@ -51,11 +51,12 @@ use metal::{RenderPipelineDescriptor, RenderPipelineReflection, RenderPipelineRe
 use metal::{RenderPipelineState, SamplerDescriptor, SamplerState, StencilDescriptor};
 use metal::{StructMemberRef, StructType, StructTypeRef, Texture, TextureDescriptor, TextureRef};
 use metal::{VertexAttribute, VertexAttributeRef, VertexDescriptor, VertexDescriptorRef};
-use objc::runtime::{Class, Object};
+use objc::runtime::{Class, Object, BOOL, NO};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{vec2i, Vector2I};
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{CompressedTextureFormat, ComputeDimensions, ComputeState, DepthFunc};
+use pathfinder_gpu::{Device, DeviceCapabilities, DeviceError, FeatureLevel};
 use pathfinder_gpu::{ImageAccess, Primitive, ProgramKind, RenderState, RenderTarget, ShaderKind};
 use pathfinder_gpu::{StencilFunc, TextureData, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{TextureSamplingFlags, UniformData, VertexAttrClass};
@@ -64,8 +65,10 @@ use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::cell::{Cell, RefCell};
 use std::convert::TryInto;
+use std::fs;
 use std::mem;
 use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::rc::Rc;
 use std::slice;
@@ -74,6 +77,12 @@ use std::time::{Duration, Instant};
 
 const FIRST_VERTEX_BUFFER_INDEX: u64 = 16;
 
+// `MTLGPUFamily` values, which the `metal` crate doesn't define.
+const MTL_GPU_FAMILY_APPLE_1: i64 = 1001;
+const MTL_GPU_FAMILY_APPLE_2: i64 = 1002;
+const MTL_GPU_FAMILY_APPLE_3: i64 = 1003;
+const MTL_GPU_FAMILY_MAC_1: i64 = 2001;
+
 pub struct MetalDevice {
     device: NativeMetalDevice,
     main_color_texture: Texture,
@@ -90,6 +99,8 @@ pub struct MetalDevice {
     next_timer_query_event_value: Cell<u64>,
     next_buffer_upload_event_value: Cell<u64>,
     buffer_upload_event_data: Arc<BufferUploadEventData>,
+    pipeline_cache: Option<PipelineCache>,
+    debug_groups: RefCell<Vec<String>>,
 }
 
 pub enum MetalProgram {
@@ -117,6 +128,7 @@ struct BufferAllocations {
     private: Option<Buffer>,
     shared: Option<StagingBuffer>,
     byte_size: u64,
+    label: Option<String>,
 }
 
 struct StagingBuffer {
@@ -207,6 +219,8 @@ impl MetalDevice {
             next_timer_query_event_value: Cell::new(1),
             next_buffer_upload_event_value: Cell::new(1),
             buffer_upload_event_data,
+            pipeline_cache: None,
+            debug_groups: RefCell::new(vec![]),
         }
     }
 
@@ -217,15 +231,72 @@ impl MetalDevice {
     {
         unsafe {
             let new_texture = new_texture.into_texture(&self.device);
+            if new_texture.width() != self.main_color_texture.width()
+                || new_texture.height() != self.main_color_texture.height()
+            {
+                let size = vec2i(new_texture.width() as i32, new_texture.height() as i32);
+                self.main_depth_stencil_texture = self.device.create_depth_stencil_texture(size);
+            }
             mem::replace(&mut self.main_color_texture, new_texture)
         }
     }
 
+    /// Wraps a texture created outside Pathfinder, such as a video frame, so that it can be
+    /// sampled without a copy.
+    pub fn wrap_texture<T>(&self, texture: T) -> MetalTexture
+    where
+        T: IntoTexture,
+    {
+        unsafe {
+            MetalTexture {
+                private_texture: texture.into_texture(&self.device),
+                shared_buffer: RefCell::new(None),
+                sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+            }
+        }
+    }
+
     #[inline]
     pub fn metal_device(&self) -> NativeMetalDevice {
         self.device.clone()
     }
 
+    /// Loads compiled pipelines from a binary archive in `directory`, if there is one, and adds
+    /// the pipelines created from now on to it. Call `save_pipeline_cache()` to write the archive
+    /// back out, for example after the first frame.
+    ///
+    /// Binary archives require macOS 11 or iOS 14. An archive that can't be loaded, for example
+    /// because it came from a different GPU, is replaced with an empty one.
+    pub fn set_pipeline_cache_directory<P>(&mut self, directory: P) -> Result<(), String>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = directory.into().join("pipelines.metallib");
+        unsafe {
+            let selector = sel!(newBinaryArchiveWithDescriptor:error:);
+            let supported: BOOL = msg_send![self.device.as_ptr(), respondsToSelector:selector];
+            if supported == NO {
+                return Err("Binary archives aren't supported on this system".to_owned());
+            }
+            let archive = BinaryArchive::new(&self.device, &path)?;
+            self.pipeline_cache = Some(PipelineCache { archive, path });
+        }
+        Ok(())
+    }
+
+    /// Writes the pipeline cache set up with `set_pipeline_cache_directory()` to disk.
+    pub fn save_pipeline_cache(&self) -> Result<(), String> {
+        match self.pipeline_cache {
+            None => Ok(()),
+            Some(ref pipeline_cache) => {
+                if let Some(directory) = pipeline_cache.path.parent() {
+                    fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+                }
+                pipeline_cache.archive.serialize(&pipeline_cache.path)
+            }
+        }
+    }
+
     /// A convenience function to present a Core Animation drawable.
     pub fn present_drawable(&self, drawable: &CoreAnimationDrawableRef) {
         self.begin_commands();
@@ -399,6 +470,119 @@ impl Device for MetalDevice {
         FeatureLevel::D3D11
     }
 
+    fn capabilities(&self) -> DeviceCapabilities {
+        // Every Mac supports 16K textures, as do iOS GPUs from the A9 on.
+        let max_texture_size = if cfg!(target_os = "macos")
+            || self.supports_gpu_family(MTL_GPU_FAMILY_APPLE_3) == Some(true)
+        {
+            16384
+        } else {
+            8192
+        };
+        let max_msaa_samples = [8, 4, 2]
+            .iter()
+            .cloned()
+            .find(|&sample_count: &u64| unsafe {
+                let device = self.device.as_ptr();
+                let supported: BOOL = msg_send![device, supportsTextureSampleCount:sample_count];
+                supported != NO
+            })
+            .unwrap_or(1);
+        DeviceCapabilities {
+            max_texture_size,
+            compute_shaders: true,
+            dual_source_blending: true,
+            max_msaa_samples: max_msaa_samples as u32,
+        }
+    }
+
+    fn supports_compressed_texture_format(&self, format: CompressedTextureFormat) -> bool {
+        let family = match format {
+            CompressedTextureFormat::BC1
+            | CompressedTextureFormat::BC3
+            | CompressedTextureFormat::BC7 => MTL_GPU_FAMILY_MAC_1,
+            CompressedTextureFormat::ETC2RGB8 | CompressedTextureFormat::ETC2RGBA8 => {
+                MTL_GPU_FAMILY_APPLE_1
+            }
+            CompressedTextureFormat::ASTC4x4 => MTL_GPU_FAMILY_APPLE_2,
+        };
+        match self.supports_gpu_family(family) {
+            Some(supported) => supported,
+            // Before GPU families, Macs supported only the BC formats and iOS devices only the
+            // others.
+            None => cfg!(target_os = "macos") == (family == MTL_GPU_FAMILY_MAC_1),
+        }
+    }
+
+    fn create_compressed_texture(
+        &self,
+        format: CompressedTextureFormat,
+        size: Vector2I,
+        data: &[u8],
+    ) -> Option<MetalTexture> {
+        if !self.supports_compressed_texture_format(format) {
+            return None;
+        }
+        assert_eq!(data.len(), format.data_size(size));
+
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_texture_type(MTLTextureType::D2);
+        descriptor.set_pixel_format(format.metal_pixel_format());
+        descriptor.set_width(size.x() as u64);
+        descriptor.set_height(size.y() as u64);
+        descriptor.set_usage(MTLTextureUsage::ShaderRead);
+        // The texture is filled from the CPU once, so it needs CPU-visible storage.
+        if cfg!(target_os = "macos") {
+            descriptor.set_storage_mode(MTLStorageMode::Managed);
+        } else {
+            descriptor.set_storage_mode(MTLStorageMode::Shared);
+        }
+        let texture = self.device.new_texture(&descriptor);
+
+        let block_size = format.block_size();
+        let blocks_per_row = (size.x() + block_size.x() - 1) / block_size.x();
+        let region = MTLRegion {
+            origin: MTLOrigin { x: 0, y: 0, z: 0 },
+            size: MTLSize {
+                width: size.x() as u64,
+                height: size.y() as u64,
+                depth: 1,
+            },
+        };
+        let bytes_per_row = blocks_per_row as u64 * format.bytes_per_block() as u64;
+        texture.replace_region(region, 0, bytes_per_row, data.as_ptr() as *const _);
+
+        Some(MetalTexture {
+            private_texture: texture,
+            shared_buffer: RefCell::new(None),
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+        })
+    }
+
+    fn push_debug_group(&self, name: &str) {
+        self.debug_groups.borrow_mut().push(name.to_owned());
+    }
+
+    fn pop_debug_group(&self) {
+        self.debug_groups.borrow_mut().pop();
+    }
+
+    fn set_texture_label(&self, texture: &MetalTexture, label: &str) {
+        unsafe {
+            set_object_label(texture.private_texture.as_ptr() as *mut Object, label);
+        }
+    }
+
+    fn set_buffer_label(&self, buffer: &MetalBuffer, label: &str) {
+        let mut allocations = buffer.allocations.borrow_mut();
+        if let Some(ref private_buffer) = allocations.private {
+            unsafe {
+                set_object_label(private_buffer.as_ptr() as *mut Object, label);
+            }
+        }
+        allocations.label = Some(label.to_owned());
+    }
+
     // TODO: Add texture usage hint.
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
         let descriptor = create_texture_descriptor(format, size);
@@ -665,6 +849,7 @@ impl Device for MetalDevice {
                 private: None,
                 shared: None,
                 byte_size: 0,
+                label: None,
             })),
             mode,
         }
@@ -679,10 +864,19 @@ impl Device for MetalDevice {
         let byte_size = (length * mem::size_of::<T>()) as u64;
         let new_buffer = self.device.new_buffer(byte_size, options);
 
+        // Carry the label over to the new allocation.
+        let label = buffer.allocations.borrow_mut().label.take();
+        if let Some(ref label) = label {
+            unsafe {
+                set_object_label(new_buffer.as_ptr() as *mut Object, label);
+            }
+        }
+
         *buffer.allocations.borrow_mut() = BufferAllocations {
             private: Some(new_buffer),
             shared: None,
             byte_size,
+            label,
         };
 
         match data {
@@ -792,6 +986,13 @@ impl Device for MetalDevice {
             MTLPixelFormat::RGBA8Unorm => TextureFormat::RGBA8,
             MTLPixelFormat::RGBA16Float => TextureFormat::RGBA16F,
             MTLPixelFormat::RGBA32Float => TextureFormat::RGBA32F,
+            // Compressed textures sample as RGBA.
+            MTLPixelFormat::BC1_RGBA
+            | MTLPixelFormat::BC3_RGBA
+            | MTLPixelFormat::BC7_RGBAUnorm
+            | MTLPixelFormat::ETC2_RGB8
+            | MTLPixelFormat::EAC_RGBA8
+            | MTLPixelFormat::ASTC_4x4_LDR => TextureFormat::RGBA8,
             _ => panic!("Unexpected Metal texture format!"),
         }
     }
@@ -1060,6 +1261,7 @@ impl Device for MetalDevice {
         let command_buffer = &scopes.last().unwrap().command_buffer;
 
         let encoder = command_buffer.real_new_compute_command_encoder();
+        self.label_encoder(encoder.as_ptr() as *mut Object);
 
         let program = match compute_state.program {
             MetalProgram::Compute(ref compute_program) => compute_program,
@@ -1068,6 +1270,11 @@ impl Device for MetalDevice {
 
         let compute_pipeline_descriptor = ComputePipelineDescriptor::new();
         compute_pipeline_descriptor.set_compute_function(Some(&program.shader.function));
+        if let Some(ref pipeline_cache) = self.pipeline_cache {
+            pipeline_cache
+                .archive
+                .attach_to_descriptor(compute_pipeline_descriptor.as_ptr() as *mut Object);
+        }
 
         let compute_pipeline_state = unsafe {
             if program.shader.arguments.borrow().is_none() {
@@ -1086,6 +1293,11 @@ impl Device for MetalDevice {
                     ComputePipelineState::from_ptr(raw_compute_pipeline_state);
                 *program.shader.arguments.borrow_mut() =
                     Some(ArgumentArray::from_ptr(msg_send![reflection, arguments]));
+                if let Some(ref pipeline_cache) = self.pipeline_cache {
+                    pipeline_cache
+                        .archive
+                        .add_compute_pipeline(compute_pipeline_descriptor.as_ptr() as *mut Object);
+                }
                 compute_pipeline_state
             } else {
                 self.device
@@ -1218,13 +1430,26 @@ impl Device for MetalDevice {
         name: &str,
         kind: ShaderKind,
     ) -> Self::Shader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<Self::Shader, DeviceError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/metal/{}.{}s.metal", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        match resources.slurp(&path) {
+            Ok(source) => Ok(self.create_shader_from_source(name, &source, kind)),
+            Err(error) => Err(DeviceError::ResourceLoad { path, error }),
+        }
     }
 
     fn add_fence(&self) -> MetalFence {
@@ -1257,6 +1482,19 @@ impl Device for MetalDevice {
 }
 
 impl MetalDevice {
+    // Returns `None` if the system predates `-[MTLDevice supportsFamily:]`.
+    fn supports_gpu_family(&self, family: i64) -> Option<bool> {
+        unsafe {
+            let selector = sel!(supportsFamily:);
+            let responds: BOOL = msg_send![self.device.as_ptr(), respondsToSelector:selector];
+            if responds == NO {
+                return None;
+            }
+            let supported: BOOL = msg_send![self.device.as_ptr(), supportsFamily:family];
+            Some(supported != NO)
+        }
+    }
+
     fn get_uniform_index(&self, shader: &MetalShader, name: &str) -> Option<MetalUniformIndex> {
         let uniforms = shader.arguments.borrow();
         let arguments = match *uniforms {
@@ -1471,6 +1709,18 @@ impl MetalDevice {
         }
     }
 
+    // Metal has no debug groups that span encoders, and this backend starts a new encoder for
+    // every draw and dispatch, so the open debug groups are recorded as encoder labels instead.
+    fn label_encoder(&self, encoder: *mut Object) {
+        let debug_groups = self.debug_groups.borrow();
+        if debug_groups.is_empty() {
+            return;
+        }
+        unsafe {
+            set_object_label(encoder, &debug_groups.join(" / "));
+        }
+    }
+
     fn prepare_to_draw(&self, render_state: &RenderState<MetalDevice>) -> RenderCommandEncoder {
         let scopes = self.scopes.borrow();
         let command_buffer = &scopes.last().unwrap().command_buffer;
@@ -1478,6 +1728,7 @@ impl MetalDevice {
         let render_pass_descriptor = self.create_render_pass_descriptor(render_state);
 
         let encoder = command_buffer.new_render_command_encoder_retained(&render_pass_descriptor);
+        self.label_encoder(encoder.as_ptr() as *mut Object);
 
         // Wait on the previous compute command, if any.
         let compute_fence = self.compute_fence.borrow();
@@ -1511,6 +1762,12 @@ impl MetalDevice {
             render_pipeline_descriptor.set_stencil_attachment_pixel_format(depth_stencil_format);
         }
 
+        if let Some(ref pipeline_cache) = self.pipeline_cache {
+            pipeline_cache
+                .archive
+                .attach_to_descriptor(render_pipeline_descriptor.as_ptr() as *mut Object);
+        }
+
         let render_pipeline_state = if program.vertex_shader.arguments.borrow().is_none()
             || program.fragment_shader.arguments.borrow().is_none()
         {
@@ -1529,6 +1786,11 @@ impl MetalDevice {
             if fragment_arguments.is_none() {
                 *fragment_arguments = Some(reflection.real_fragment_arguments());
             }
+            if let Some(ref pipeline_cache) = self.pipeline_cache {
+                pipeline_cache
+                    .archive
+                    .add_render_pipeline(render_pipeline_descriptor.as_ptr() as *mut Object);
+            }
             render_pipeline_state
         } else {
             self.device
@@ -2241,6 +2503,8 @@ impl BlendFactorExt for BlendFactor {
             BlendFactor::DestAlpha => MTLBlendFactor::DestinationAlpha,
             BlendFactor::OneMinusDestAlpha => MTLBlendFactor::OneMinusDestinationAlpha,
             BlendFactor::DestColor => MTLBlendFactor::DestinationColor,
+            BlendFactor::OneMinusSrcColor => MTLBlendFactor::OneMinusSourceColor,
+            BlendFactor::OneMinusSrc1Color => MTLBlendFactor::OneMinusSource1Color,
         }
     }
 }
@@ -2301,6 +2565,7 @@ impl DepthFuncExt for DepthFunc {
     fn to_metal_compare_function(self) -> MTLCompareFunction {
         match self {
             DepthFunc::Less => MTLCompareFunction::Less,
+            DepthFunc::LessEqual => MTLCompareFunction::LessEqual,
             DepthFunc::Always => MTLCompareFunction::Always,
         }
     }
@@ -2386,6 +2651,23 @@ impl UniformDataExt for UniformData {
     }
 }
 
+trait CompressedTextureFormatExt {
+    fn metal_pixel_format(self) -> MTLPixelFormat;
+}
+
+impl CompressedTextureFormatExt for CompressedTextureFormat {
+    fn metal_pixel_format(self) -> MTLPixelFormat {
+        match self {
+            CompressedTextureFormat::BC1 => MTLPixelFormat::BC1_RGBA,
+            CompressedTextureFormat::BC3 => MTLPixelFormat::BC3_RGBA,
+            CompressedTextureFormat::BC7 => MTLPixelFormat::BC7_RGBAUnorm,
+            CompressedTextureFormat::ETC2RGB8 => MTLPixelFormat::ETC2_RGB8,
+            CompressedTextureFormat::ETC2RGBA8 => MTLPixelFormat::EAC_RGBA8,
+            CompressedTextureFormat::ASTC4x4 => MTLPixelFormat::ASTC_4x4_LDR,
+        }
+    }
+}
+
 trait TextureFormatExt: Sized {
     fn from_metal_pixel_format(metal_pixel_format: MTLPixelFormat) -> Option<Self>;
 }
@@ -2613,6 +2895,107 @@ impl Drop for Fence {
     }
 }
 
+struct PipelineCache {
+    archive: BinaryArchive,
+    path: PathBuf,
+}
+
+struct BinaryArchive(*mut Object);
+
+impl Drop for BinaryArchive {
+    fn drop(&mut self) {
+        unsafe { msg_send![self.0, release] }
+    }
+}
+
+impl BinaryArchive {
+    unsafe fn new(device: &DeviceRef, path: &Path) -> Result<BinaryArchive, String> {
+        let descriptor: *mut Object = msg_send![class!(MTLBinaryArchiveDescriptor), new];
+        let mut error: *mut Object = ptr::null_mut();
+        let mut archive: *mut Object = ptr::null_mut();
+        if path.exists() {
+            let () = msg_send![descriptor, setUrl:file_url(path)];
+            archive = msg_send![device.as_ptr(),
+                                newBinaryArchiveWithDescriptor:descriptor
+                                                         error:&mut error];
+            if archive.is_null() {
+                let () = msg_send![descriptor, setUrl:nil];
+            }
+        }
+        if archive.is_null() {
+            error = ptr::null_mut();
+            archive = msg_send![device.as_ptr(),
+                                newBinaryArchiveWithDescriptor:descriptor
+                                                         error:&mut error];
+        }
+        let () = msg_send![descriptor, release];
+
+        if archive.is_null() {
+            return Err(error_description(error));
+        }
+        Ok(BinaryArchive(archive))
+    }
+
+    fn attach_to_descriptor(&self, descriptor: *mut Object) {
+        unsafe {
+            let archives: id = msg_send![class!(NSArray), arrayWithObject:self.0];
+            let () = msg_send![descriptor, setBinaryArchives:archives];
+        }
+    }
+
+    // Failing to add a pipeline only means that it won't be cached, so errors are ignored.
+    fn add_render_pipeline(&self, descriptor: *mut Object) {
+        unsafe {
+            let mut error: *mut Object = ptr::null_mut();
+            let _: BOOL = msg_send![self.0,
+                                    addRenderPipelineFunctionsWithDescriptor:descriptor
+                                                                       error:&mut error];
+        }
+    }
+
+    fn add_compute_pipeline(&self, descriptor: *mut Object) {
+        unsafe {
+            let mut error: *mut Object = ptr::null_mut();
+            let _: BOOL = msg_send![self.0,
+                                    addComputePipelineFunctionsWithDescriptor:descriptor
+                                                                        error:&mut error];
+        }
+    }
+
+    fn serialize(&self, path: &Path) -> Result<(), String> {
+        unsafe {
+            let mut error: *mut Object = ptr::null_mut();
+            let succeeded: BOOL = msg_send![self.0, serializeToURL:file_url(path)
+                                                             error:&mut error];
+            if succeeded == NO {
+                return Err(error_description(error));
+            }
+        }
+        Ok(())
+    }
+}
+
+unsafe fn file_url(path: &Path) -> id {
+    let string = NSString::alloc(nil).init_str(&path.to_string_lossy());
+    let url: id = msg_send![class!(NSURL), fileURLWithPath:string];
+    let () = msg_send![string, release];
+    url
+}
+
+unsafe fn set_object_label(object: *mut Object, label: &str) {
+    let string = NSString::alloc(nil).init_str(label);
+    let () = msg_send![object, setLabel:string];
+    let () = msg_send![string, release];
+}
+
+unsafe fn error_description(error: *mut Object) -> String {
+    if error.is_null() {
+        return "Unknown error".to_owned();
+    }
+    let description: CFStringRef = msg_send![error, localizedDescription];
+    CFString::wrap_under_get_rule(description).to_string()
+}
+
 struct VertexAttributeArray(*mut Object);
 
 impl Drop for VertexAttributeArray {
@@ -0,0 +1,174 @@
+// pathfinder/metal_layer/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders Pathfinder scenes into a `CAMetalLayer` on macOS and iOS.
+//!
+//! `MetalLayerRenderer` sets the layer up, creates the Metal device and the renderer, and takes
+//! care of acquiring, sizing, and presenting a drawable each frame. An app only has to call
+//! `resize()` when its view changes size and `render_frame()` or `render_scene()` from its
+//! display link.
+
+#[macro_use]
+extern crate objc;
+
+use cocoa::base::nil;
+use cocoa::foundation::NSAutoreleasePool;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use metal::{CGSize, CoreAnimationDrawable, CoreAnimationLayer, CoreAnimationLayerRef, Device};
+use metal::{DeviceRef, MTLDevice, MTLPixelFormat};
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_metal::MetalDevice;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_resources::ResourceLoader;
+
+/// A renderer that draws into the drawables of a `CAMetalLayer`.
+pub struct MetalLayerRenderer {
+    layer: CoreAnimationLayer,
+    renderer: Renderer<MetalDevice>,
+    // A drawable acquired but not yet drawn to. The first one is needed to create the device.
+    next_drawable: Option<CoreAnimationDrawable>,
+}
+
+impl MetalLayerRenderer {
+    /// Prepares the layer for Pathfinder and creates a renderer that draws into it.
+    ///
+    /// If the layer has no Metal device yet, it's given the system default device. Its pixel
+    /// format is set to BGRA8, and its drawables are sized to `drawable_size` in device pixels.
+    /// The `dest` field of `options` is ignored; the renderer always draws to the whole layer.
+    ///
+    /// This function is unsafe because the layer must be a valid `CAMetalLayer`, and because the
+    /// renderer must only be used on one thread at a time, like the layer itself.
+    pub unsafe fn new(
+        layer: &CoreAnimationLayerRef,
+        drawable_size: Vector2I,
+        resource_loader: &dyn ResourceLoader,
+        options: RendererOptions<MetalDevice>,
+    ) -> MetalLayerRenderer {
+        let device: *mut MTLDevice = msg_send![layer.as_ptr(), device];
+        let device = if device.is_null() {
+            let device = Device::system_default().expect("No Metal device is available!");
+            layer.set_device(&device);
+            device
+        } else {
+            DeviceRef::from_ptr(device).to_owned()
+        };
+        layer.set_pixel_format(MTLPixelFormat::BGRA8Unorm);
+        layer.set_drawable_size(to_cg_size(drawable_size));
+
+        let pool = NSAutoreleasePool::new(nil);
+        let drawable = layer
+            .next_drawable()
+            .expect("No drawable is available!")
+            .to_owned();
+        pool.drain();
+
+        let device = MetalDevice::new(device, &*drawable);
+        let mode = RendererMode::default_for_device(&device);
+        let options = RendererOptions {
+            dest: DestFramebuffer::full_window(drawable_size),
+            ..options
+        };
+        let renderer = Renderer::new(device, resource_loader, mode, options);
+
+        MetalLayerRenderer {
+            layer: layer.to_owned(),
+            renderer,
+            next_drawable: Some(drawable),
+        }
+    }
+
+    /// Resizes the layer's drawables, in device pixels. Call this when the view is resized or
+    /// moves to a screen with a different scale factor.
+    pub fn resize(&mut self, new_drawable_size: Vector2I) {
+        if new_drawable_size == self.drawable_size() {
+            return;
+        }
+        self.layer.set_drawable_size(to_cg_size(new_drawable_size));
+        self.renderer.options_mut().dest = DestFramebuffer::full_window(new_drawable_size);
+        self.renderer.dest_framebuffer_size_changed();
+
+        // A drawable that was acquired already has the old size.
+        self.next_drawable = None;
+    }
+
+    /// Returns the size of the layer's drawables, in device pixels.
+    #[inline]
+    pub fn drawable_size(&self) -> Vector2I {
+        self.renderer
+            .options()
+            .dest
+            .window_size(self.renderer.device())
+    }
+
+    /// Acquires a drawable, calls `render` to draw into it with the renderer, and presents it.
+    ///
+    /// Returns false, without calling `render`, if the layer has no drawable available, which
+    /// can happen when the app is in the background.
+    pub fn render_frame<F>(&mut self, render: F) -> bool
+    where
+        F: FnOnce(&mut Renderer<MetalDevice>),
+    {
+        unsafe {
+            let pool = NSAutoreleasePool::new(nil);
+            let drawable = match self.next_drawable.take() {
+                Some(drawable) => Some(drawable),
+                None => self
+                    .layer
+                    .next_drawable()
+                    .map(|drawable| drawable.to_owned()),
+            };
+            let drawable = match drawable {
+                Some(drawable) => drawable,
+                None => {
+                    pool.drain();
+                    return false;
+                }
+            };
+
+            drop(self.renderer.device_mut().swap_texture(&*drawable));
+            render(&mut self.renderer);
+            self.renderer.device().present_drawable(&drawable);
+            pool.drain();
+            true
+        }
+    }
+
+    /// Builds and renders the scene in the proxy into the next drawable, and presents it.
+    ///
+    /// Returns false if the layer has no drawable available.
+    pub fn render_scene(&mut self, scene_proxy: &mut SceneProxy, options: BuildOptions) -> bool {
+        self.render_frame(|renderer| scene_proxy.build_and_render(renderer, options))
+    }
+
+    /// Returns the renderer, for example to change its options.
+    #[inline]
+    pub fn renderer(&self) -> &Renderer<MetalDevice> {
+        &self.renderer
+    }
+
+    #[inline]
+    pub fn renderer_mut(&mut self) -> &mut Renderer<MetalDevice> {
+        &mut self.renderer
+    }
+
+    /// Returns the layer that this renderer draws into.
+    #[inline]
+    pub fn layer(&self) -> &CoreAnimationLayerRef {
+        &self.layer
+    }
+}
+
+fn to_cg_size(size: Vector2I) -> CGSize {
+    let size = size.max(vec2i(1, 1));
+    CGSize::new(size.x() as f64, size.y() as f64)
+}
@@ -0,0 +1,895 @@
+// pathfinder/node/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Node.js bindings that expose the Pathfinder canvas with the API of an HTML canvas, so that it
+//! can stand in for `node-canvas` in server-side rendering.
+
+#[macro_use]
+extern crate napi_derive;
+
+use css_color_parser::Color;
+use image::png::PngEncoder;
+use image::{ColorType, RgbaImage};
+use napi::bindgen_prelude::{Buffer, ClassInstance, Either, Uint8ClampedArray};
+use napi::{Error, Result, Status};
+use pathfinder_canvas::{ArcDirection, CanvasFontContext, CompositeOperation, FillRule};
+use pathfinder_canvas::{Canvas as PFCanvas, CanvasRenderingContext2D as PFContext};
+use pathfinder_canvas::{FillStyle, ImageData as CanvasImageData, LineCap, LineJoin, Path2D};
+use pathfinder_canvas::{TextAlign, TextBaseline};
+use pathfinder_color::{self as color, ColorU};
+use pathfinder_content::gradient::Gradient;
+use pathfinder_export::{Export, FileFormat, PageOptions, PdfConformance};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2I};
+use pathfinder_gl::headless::GLHeadlessContext;
+use pathfinder_gl::GLDevice;
+use pathfinder_rasterize::RasterizeOptions;
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use pathfinder_simd::default::F32x2;
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+use std::str::FromStr;
+
+thread_local! {
+    static FONT_CONTEXT: CanvasFontContext = CanvasFontContext::from_system_source();
+    static GPU_RENDERER: RefCell<Option<GPURenderer>> = RefCell::new(None);
+}
+
+/// Where a canvas is rendered when it's converted to pixels.
+#[derive(Clone, Copy, PartialEq)]
+enum Backend {
+    /// The software rasterizer, which works anywhere.
+    CPU,
+    /// A headless OpenGL context, shared by all canvases on the thread.
+    GPU,
+}
+
+// The state that a canvas shares with every context object it has handed out.
+struct CanvasState {
+    context: PFContext,
+    current_path: Path2D,
+    backend: Backend,
+}
+
+type SharedCanvasState = Rc<RefCell<CanvasState>>;
+
+/// A canvas, like the `Canvas` class of `node-canvas`.
+#[napi]
+pub struct Canvas {
+    state: SharedCanvasState,
+}
+
+#[napi]
+impl Canvas {
+    /// Creates a transparent canvas. The backend is `"cpu"` (the default) or `"gpu"`.
+    #[napi(constructor)]
+    pub fn new(width: u32, height: u32, backend: Option<String>) -> Result<Canvas> {
+        let backend = match backend.as_deref() {
+            None | Some("cpu") => Backend::CPU,
+            Some("gpu") => Backend::GPU,
+            Some(backend) => return Err(invalid_value("backend", backend)),
+        };
+        let size = vec2f(width as f32, height as f32);
+        let font_context = FONT_CONTEXT.with(|font_context| font_context.clone());
+        let context = PFCanvas::new(size).get_context_2d(font_context);
+        Ok(Canvas {
+            state: Rc::new(RefCell::new(CanvasState {
+                context,
+                current_path: Path2D::new(),
+                backend,
+            })),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.state.borrow().context.canvas().size().x() as u32
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> u32 {
+        self.state.borrow().context.canvas().size().y() as u32
+    }
+
+    /// Returns the 2D context of the canvas. Only `"2d"` is supported.
+    #[napi]
+    pub fn get_context(&self, context_type: String) -> Result<CanvasRenderingContext2D> {
+        if context_type != "2d" {
+            return Err(invalid_value("context type", &context_type));
+        }
+        Ok(CanvasRenderingContext2D {
+            state: self.state.clone(),
+        })
+    }
+
+    /// Encodes everything drawn so far as `image/png` (the default), `application/pdf`, or
+    /// `image/svg+xml`. PNG images are rendered with the canvas's backend.
+    #[napi]
+    pub fn to_buffer(&self, mime_type: Option<String>) -> Result<Buffer> {
+        let state = self.state.borrow();
+        let scene = state.context.canvas().scene();
+        let bytes = match mime_type.as_deref().unwrap_or("image/png") {
+            "image/png" => {
+                let image = render(scene, state.context.canvas().size(), state.backend)?;
+                encode_png(&image)?
+            }
+            "application/pdf" => export_scene(
+                scene,
+                FileFormat::PDF {
+                    compressed: true,
+                    page: PageOptions::default(),
+                    conformance: PdfConformance::default(),
+                },
+            )?,
+            "image/svg+xml" => export_scene(scene, FileFormat::SVG)?,
+            mime_type => return Err(invalid_value("MIME type", mime_type)),
+        };
+        Ok(bytes.into())
+    }
+}
+
+/// Creates a canvas, like `createCanvas()` in `node-canvas`. The backend is `"cpu"` (the default)
+/// or `"gpu"`.
+#[napi]
+pub fn create_canvas(width: u32, height: u32, backend: Option<String>) -> Result<Canvas> {
+    Canvas::new(width, height, backend)
+}
+
+/// A 2D drawing context with the API of the one of an HTML canvas.
+#[napi]
+pub struct CanvasRenderingContext2D {
+    state: SharedCanvasState,
+}
+
+#[napi]
+impl CanvasRenderingContext2D {
+    #[napi(getter)]
+    pub fn canvas(&self) -> Canvas {
+        Canvas {
+            state: self.state.clone(),
+        }
+    }
+
+    // Drawing rectangles
+
+    #[napi]
+    pub fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.context_mut().clear_rect(rect(x, y, width, height));
+    }
+
+    #[napi]
+    pub fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.context_mut().fill_rect(rect(x, y, width, height));
+    }
+
+    #[napi]
+    pub fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.context_mut().stroke_rect(rect(x, y, width, height));
+    }
+
+    // Drawing text
+
+    #[napi]
+    pub fn fill_text(&self, text: String, x: f64, y: f64) {
+        self.context_mut()
+            .fill_text(&text, vec2f(x as f32, y as f32));
+    }
+
+    #[napi]
+    pub fn stroke_text(&self, text: String, x: f64, y: f64) {
+        self.context_mut()
+            .stroke_text(&text, vec2f(x as f32, y as f32));
+    }
+
+    #[napi]
+    pub fn measure_text(&self, text: String) -> TextMetrics {
+        let metrics = self.state.borrow().context.measure_text(&text);
+        TextMetrics {
+            width: metrics.width() as f64,
+            actual_bounding_box_left: metrics.actual_bounding_box_left() as f64,
+            actual_bounding_box_right: metrics.actual_bounding_box_right() as f64,
+            actual_bounding_box_ascent: metrics.actual_bounding_box_ascent() as f64,
+            actual_bounding_box_descent: metrics.actual_bounding_box_descent() as f64,
+            font_bounding_box_ascent: metrics.font_bounding_box_ascent() as f64,
+            font_bounding_box_descent: metrics.font_bounding_box_descent() as f64,
+        }
+    }
+
+    // Line styles
+
+    #[napi(getter)]
+    pub fn line_width(&self) -> f64 {
+        self.state.borrow().context.line_width() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_line_width(&self, new_line_width: f64) {
+        self.context_mut().set_line_width(new_line_width as f32);
+    }
+
+    #[napi(getter)]
+    pub fn line_cap(&self) -> &'static str {
+        match self.state.borrow().context.line_cap() {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+
+    #[napi(setter)]
+    pub fn set_line_cap(&self, new_line_cap: String) -> Result<()> {
+        let new_line_cap = match &*new_line_cap {
+            "butt" => LineCap::Butt,
+            "round" => LineCap::Round,
+            "square" => LineCap::Square,
+            _ => return Err(invalid_value("line cap", &new_line_cap)),
+        };
+        self.context_mut().set_line_cap(new_line_cap);
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn line_join(&self) -> &'static str {
+        match self.state.borrow().context.line_join() {
+            LineJoin::Miter => "miter",
+            LineJoin::Bevel => "bevel",
+            LineJoin::Round => "round",
+        }
+    }
+
+    #[napi(setter)]
+    pub fn set_line_join(&self, new_line_join: String) -> Result<()> {
+        let new_line_join = match &*new_line_join {
+            "miter" => LineJoin::Miter,
+            "bevel" => LineJoin::Bevel,
+            "round" => LineJoin::Round,
+            _ => return Err(invalid_value("line join", &new_line_join)),
+        };
+        self.context_mut().set_line_join(new_line_join);
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn miter_limit(&self) -> f64 {
+        self.state.borrow().context.miter_limit() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_miter_limit(&self, new_miter_limit: f64) {
+        self.context_mut().set_miter_limit(new_miter_limit as f32);
+    }
+
+    #[napi]
+    pub fn get_line_dash(&self) -> Vec<f64> {
+        let mut state = self.state.borrow_mut();
+        state
+            .context
+            .line_dash()
+            .iter()
+            .map(|&segment| segment as f64)
+            .collect()
+    }
+
+    #[napi]
+    pub fn set_line_dash(&self, segments: Vec<f64>) {
+        let segments = segments.into_iter().map(|segment| segment as f32).collect();
+        self.context_mut().set_line_dash(segments);
+    }
+
+    #[napi(getter)]
+    pub fn line_dash_offset(&self) -> f64 {
+        self.state.borrow().context.line_dash_offset() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_line_dash_offset(&self, new_line_dash_offset: f64) {
+        self.context_mut()
+            .set_line_dash_offset(new_line_dash_offset as f32);
+    }
+
+    // Text styles
+
+    /// Sets the font from a CSS `font` shorthand like `"bold 14px 'Fira Sans', serif"`.
+    #[napi(setter)]
+    pub fn set_font(&self, new_font: String) {
+        self.context_mut().set_font(&new_font);
+    }
+
+    #[napi(setter)]
+    pub fn set_text_align(&self, new_text_align: String) -> Result<()> {
+        let new_text_align = match &*new_text_align {
+            "left" => TextAlign::Left,
+            "right" => TextAlign::Right,
+            "center" => TextAlign::Center,
+            "start" => TextAlign::Start,
+            "end" => TextAlign::End,
+            _ => return Err(invalid_value("text alignment", &new_text_align)),
+        };
+        self.context_mut().set_text_align(new_text_align);
+        Ok(())
+    }
+
+    #[napi(setter)]
+    pub fn set_text_baseline(&self, new_text_baseline: String) -> Result<()> {
+        let new_text_baseline = match &*new_text_baseline {
+            "alphabetic" => TextBaseline::Alphabetic,
+            "top" => TextBaseline::Top,
+            "hanging" => TextBaseline::Hanging,
+            "middle" => TextBaseline::Middle,
+            "ideographic" => TextBaseline::Ideographic,
+            "bottom" => TextBaseline::Bottom,
+            _ => return Err(invalid_value("text baseline", &new_text_baseline)),
+        };
+        self.context_mut().set_text_baseline(new_text_baseline);
+        Ok(())
+    }
+
+    // Fill and stroke styles
+
+    /// Sets the fill style to a CSS color string or a `CanvasGradient`.
+    #[napi(setter)]
+    pub fn set_fill_style(
+        &self,
+        new_fill_style: Either<String, ClassInstance<CanvasGradient>>,
+    ) -> Result<()> {
+        let new_fill_style = to_fill_style(new_fill_style)?;
+        self.context_mut().set_fill_style(new_fill_style);
+        Ok(())
+    }
+
+    /// Sets the stroke style to a CSS color string or a `CanvasGradient`.
+    #[napi(setter)]
+    pub fn set_stroke_style(
+        &self,
+        new_stroke_style: Either<String, ClassInstance<CanvasGradient>>,
+    ) -> Result<()> {
+        let new_stroke_style = to_fill_style(new_stroke_style)?;
+        self.context_mut().set_stroke_style(new_stroke_style);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> CanvasGradient {
+        let (from, to) = (vec2f(x0 as f32, y0 as f32), vec2f(x1 as f32, y1 as f32));
+        CanvasGradient {
+            gradient: Gradient::linear_from_points(from, to),
+        }
+    }
+
+    #[napi]
+    pub fn create_radial_gradient(
+        &self,
+        x0: f64,
+        y0: f64,
+        r0: f64,
+        x1: f64,
+        y1: f64,
+        r1: f64,
+    ) -> CanvasGradient {
+        let line = LineSegment2F::new(vec2f(x0 as f32, y0 as f32), vec2f(x1 as f32, y1 as f32));
+        CanvasGradient {
+            gradient: Gradient::radial(line, F32x2::new(r0 as f32, r1 as f32)),
+        }
+    }
+
+    #[napi]
+    pub fn create_conic_gradient(&self, start_angle: f64, x: f64, y: f64) -> CanvasGradient {
+        let center = vec2f(x as f32, y as f32);
+        let state = self.state.borrow();
+        CanvasGradient {
+            gradient: state
+                .context
+                .create_conic_gradient(start_angle as f32, center),
+        }
+    }
+
+    // Shadows
+
+    #[napi(getter)]
+    pub fn shadow_blur(&self) -> f64 {
+        self.state.borrow().context.shadow_blur() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_shadow_blur(&self, new_shadow_blur: f64) {
+        self.context_mut().set_shadow_blur(new_shadow_blur as f32);
+    }
+
+    #[napi(setter)]
+    pub fn set_shadow_color(&self, new_shadow_color: String) -> Result<()> {
+        let new_shadow_color = parse_color(&new_shadow_color)?;
+        self.context_mut().set_shadow_color(new_shadow_color);
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn shadow_offset_x(&self) -> f64 {
+        self.state.borrow().context.shadow_offset().x() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_shadow_offset_x(&self, new_shadow_offset_x: f64) {
+        let mut state = self.state.borrow_mut();
+        let offset = state.context.shadow_offset();
+        let new_offset = vec2f(new_shadow_offset_x as f32, offset.y());
+        state.context.set_shadow_offset(new_offset);
+    }
+
+    #[napi(getter)]
+    pub fn shadow_offset_y(&self) -> f64 {
+        self.state.borrow().context.shadow_offset().y() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_shadow_offset_y(&self, new_shadow_offset_y: f64) {
+        let mut state = self.state.borrow_mut();
+        let offset = state.context.shadow_offset();
+        let new_offset = vec2f(offset.x(), new_shadow_offset_y as f32);
+        state.context.set_shadow_offset(new_offset);
+    }
+
+    // Compositing
+
+    #[napi(getter)]
+    pub fn global_alpha(&self) -> f64 {
+        self.state.borrow().context.global_alpha() as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_global_alpha(&self, new_global_alpha: f64) {
+        self.context_mut().set_global_alpha(new_global_alpha as f32);
+    }
+
+    #[napi(setter)]
+    pub fn set_global_composite_operation(&self, new_composite_operation: String) -> Result<()> {
+        let new_composite_operation = parse_composite_operation(&new_composite_operation)?;
+        self.context_mut()
+            .set_global_composite_operation(new_composite_operation);
+        Ok(())
+    }
+
+    // Paths
+
+    #[napi]
+    pub fn begin_path(&self) {
+        self.state.borrow_mut().current_path = Path2D::new();
+    }
+
+    #[napi]
+    pub fn close_path(&self) {
+        self.state.borrow_mut().current_path.close_path();
+    }
+
+    #[napi]
+    pub fn move_to(&self, x: f64, y: f64) {
+        let to = vec2f(x as f32, y as f32);
+        self.state.borrow_mut().current_path.move_to(to);
+    }
+
+    #[napi]
+    pub fn line_to(&self, x: f64, y: f64) {
+        let to = vec2f(x as f32, y as f32);
+        self.state.borrow_mut().current_path.line_to(to);
+    }
+
+    #[napi]
+    pub fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        let (ctrl, to) = (vec2f(cpx as f32, cpy as f32), vec2f(x as f32, y as f32));
+        self.state
+            .borrow_mut()
+            .current_path
+            .quadratic_curve_to(ctrl, to);
+    }
+
+    #[napi]
+    pub fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        let ctrl0 = vec2f(cp1x as f32, cp1y as f32);
+        let ctrl1 = vec2f(cp2x as f32, cp2y as f32);
+        let to = vec2f(x as f32, y as f32);
+        self.state
+            .borrow_mut()
+            .current_path
+            .bezier_curve_to(ctrl0, ctrl1, to);
+    }
+
+    #[napi]
+    pub fn arc(
+        &self,
+        x: f64,
+        y: f64,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        anticlockwise: Option<bool>,
+    ) {
+        self.state.borrow_mut().current_path.arc(
+            vec2f(x as f32, y as f32),
+            radius as f32,
+            start_angle as f32,
+            end_angle as f32,
+            arc_direction(anticlockwise),
+        );
+    }
+
+    #[napi]
+    pub fn arc_to(&self, x1: f64, y1: f64, x2: f64, y2: f64, radius: f64) {
+        let (ctrl, to) = (vec2f(x1 as f32, y1 as f32), vec2f(x2 as f32, y2 as f32));
+        self.state
+            .borrow_mut()
+            .current_path
+            .arc_to(ctrl, to, radius as f32);
+    }
+
+    #[napi]
+    pub fn ellipse(
+        &self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start_angle: f64,
+        end_angle: f64,
+        anticlockwise: Option<bool>,
+    ) {
+        self.state.borrow_mut().current_path.ellipse(
+            vec2f(x as f32, y as f32),
+            vec2f(radius_x as f32, radius_y as f32),
+            rotation as f32,
+            start_angle as f32,
+            end_angle as f32,
+            arc_direction(anticlockwise),
+        );
+    }
+
+    #[napi]
+    pub fn rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.state
+            .borrow_mut()
+            .current_path
+            .rect(rect(x, y, width, height));
+    }
+
+    #[napi]
+    pub fn round_rect(&self, x: f64, y: f64, width: f64, height: f64, radius: Option<f64>) {
+        let radius = radius.unwrap_or(0.0) as f32;
+        self.state
+            .borrow_mut()
+            .current_path
+            .round_rect(rect(x, y, width, height), radius);
+    }
+
+    /// Fills the current path with the `"nonzero"` (the default) or `"evenodd"` fill rule.
+    #[napi]
+    pub fn fill(&self, fill_rule: Option<String>) -> Result<()> {
+        let fill_rule = parse_fill_rule(fill_rule)?;
+        let mut state = self.state.borrow_mut();
+        let path = state.current_path.clone();
+        state.context.fill_path(path, fill_rule);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn stroke(&self) {
+        let mut state = self.state.borrow_mut();
+        let path = state.current_path.clone();
+        state.context.stroke_path(path);
+    }
+
+    /// Intersects the clip with the current path, using the `"nonzero"` (the default) or
+    /// `"evenodd"` fill rule.
+    #[napi]
+    pub fn clip(&self, fill_rule: Option<String>) -> Result<()> {
+        let fill_rule = parse_fill_rule(fill_rule)?;
+        let mut state = self.state.borrow_mut();
+        let path = state.current_path.clone();
+        state.context.clip_path(path, fill_rule);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn is_point_in_path(&self, x: f64, y: f64, fill_rule: Option<String>) -> Result<bool> {
+        let fill_rule = parse_fill_rule(fill_rule)?;
+        let state = self.state.borrow();
+        let point = vec2f(x as f32, y as f32);
+        Ok(state
+            .context
+            .is_point_in_path(&state.current_path, point, fill_rule))
+    }
+
+    #[napi]
+    pub fn is_point_in_stroke(&self, x: f64, y: f64) -> bool {
+        let state = self.state.borrow();
+        let point = vec2f(x as f32, y as f32);
+        state.context.is_point_in_stroke(&state.current_path, point)
+    }
+
+    // Transformations
+
+    #[napi]
+    pub fn translate(&self, x: f64, y: f64) {
+        self.context_mut().translate(vec2f(x as f32, y as f32));
+    }
+
+    #[napi]
+    pub fn scale(&self, x: f64, y: f64) {
+        self.context_mut().scale(vec2f(x as f32, y as f32));
+    }
+
+    #[napi]
+    pub fn rotate(&self, angle: f64) {
+        self.context_mut().rotate(angle as f32);
+    }
+
+    #[napi]
+    pub fn transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        let mut state = self.state.borrow_mut();
+        let new_transform = state.context.transform() * row_major(a, b, c, d, e, f);
+        state.context.set_transform(&new_transform);
+    }
+
+    #[napi]
+    pub fn set_transform(&self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+        self.context_mut()
+            .set_transform(&row_major(a, b, c, d, e, f));
+    }
+
+    #[napi]
+    pub fn reset_transform(&self) {
+        self.context_mut().reset_transform();
+    }
+
+    // Pixel manipulation
+
+    /// Renders everything drawn so far on the CPU and returns the pixels in the given rectangle.
+    #[napi]
+    pub fn get_image_data(&self, x: i32, y: i32, width: i32, height: i32) -> Result<ImageData> {
+        let rect = RectI::new(vec2i(x, y), vec2i(width, height));
+        let image_data = self
+            .state
+            .borrow()
+            .context
+            .get_image_data(rect)
+            .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+        let data = color::color_slice_to_u8_slice(&image_data.data).to_vec();
+        Ok(ImageData {
+            width: width as u32,
+            height: height as u32,
+            data: Uint8ClampedArray::new(data),
+        })
+    }
+
+    #[napi]
+    pub fn put_image_data(&self, image_data: ImageData, dx: f64, dy: f64) -> Result<()> {
+        let size = vec2i(image_data.width as i32, image_data.height as i32);
+        if image_data.data.len() != size.area() as usize * 4 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "The image data doesn't match its size".to_owned(),
+            ));
+        }
+        let data = image_data
+            .data
+            .chunks(4)
+            .map(|pixel| ColorU::new(pixel[0], pixel[1], pixel[2], pixel[3]))
+            .collect();
+        let image_data = CanvasImageData { data, size };
+        self.context_mut()
+            .put_image_data(image_data, vec2f(dx as f32, dy as f32));
+        Ok(())
+    }
+
+    // The canvas state
+
+    #[napi]
+    pub fn save(&self) {
+        self.context_mut().save();
+    }
+
+    #[napi]
+    pub fn restore(&self) {
+        self.context_mut().restore();
+    }
+
+    fn context_mut(&self) -> RefMut<PFContext> {
+        RefMut::map(self.state.borrow_mut(), |state| &mut state.context)
+    }
+}
+
+/// A gradient for use as a fill or stroke style.
+#[napi]
+pub struct CanvasGradient {
+    gradient: Gradient,
+}
+
+#[napi]
+impl CanvasGradient {
+    /// Adds a stop with a CSS color string at an offset between 0 and 1.
+    #[napi]
+    pub fn add_color_stop(&mut self, offset: f64, color: String) -> Result<()> {
+        let color = parse_color(&color)?;
+        self.gradient.add_color_stop(color, offset as f32);
+        Ok(())
+    }
+}
+
+/// The dimensions of a piece of text, in pixels.
+#[napi(object)]
+pub struct TextMetrics {
+    pub width: f64,
+    pub actual_bounding_box_left: f64,
+    pub actual_bounding_box_right: f64,
+    pub actual_bounding_box_ascent: f64,
+    pub actual_bounding_box_descent: f64,
+    pub font_bounding_box_ascent: f64,
+    pub font_bounding_box_descent: f64,
+}
+
+/// Non-premultiplied RGBA pixels, as returned by `getImageData()`.
+#[napi(object)]
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    pub data: Uint8ClampedArray,
+}
+
+// A renderer that draws into a headless OpenGL context. It's created on first use and kept for
+// the life of the thread, since creating it compiles shaders.
+struct GPURenderer {
+    context: GLHeadlessContext,
+    renderer: Renderer<GLDevice>,
+}
+
+impl GPURenderer {
+    fn new() -> Result<GPURenderer> {
+        let context = GLHeadlessContext::new(vec2i(1, 1))
+            .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+        let device = context.create_device();
+        let mode = RendererMode::default_for_device(&device);
+        let resource_loader = EmbeddedResourceLoader::new();
+        let renderer = Renderer::new(device, &resource_loader, mode, RendererOptions::default());
+        Ok(GPURenderer { context, renderer })
+    }
+}
+
+fn render(scene: &Scene, size: Vector2I, backend: Backend) -> Result<RgbaImage> {
+    if backend == Backend::CPU {
+        let image = pathfinder_rasterize::rasterize(scene, &RasterizeOptions::new(size));
+        return Ok(image.to_image_buffer());
+    }
+
+    GPU_RENDERER.with(|gpu_renderer| {
+        let mut gpu_renderer = gpu_renderer.borrow_mut();
+        if gpu_renderer.is_none() {
+            *gpu_renderer = Some(GPURenderer::new()?);
+        }
+        let gpu_renderer = gpu_renderer.as_mut().unwrap();
+        gpu_renderer
+            .context
+            .make_current()
+            .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+
+        let mut scene = scene.clone();
+        let image = gpu_renderer
+            .renderer
+            .render_to_image(
+                &mut scene,
+                size,
+                BuildOptions::default(),
+                SequentialExecutor,
+            )
+            .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+        Ok(image.to_image_buffer())
+    })
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    PngEncoder::new(&mut bytes)
+        .encode(image, image.width(), image.height(), ColorType::Rgba8)
+        .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+    Ok(bytes)
+}
+
+fn export_scene(scene: &Scene, format: FileFormat) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    scene
+        .export(&mut bytes, format)
+        .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?;
+    Ok(bytes)
+}
+
+fn to_fill_style(value: Either<String, ClassInstance<CanvasGradient>>) -> Result<FillStyle> {
+    match value {
+        Either::A(color) => Ok(FillStyle::Color(parse_color(&color)?)),
+        Either::B(gradient) => Ok(FillStyle::Gradient(gradient.gradient.clone())),
+    }
+}
+
+fn parse_color(string: &str) -> Result<ColorU> {
+    let css_color = Color::from_str(string).map_err(|_| invalid_value("color", string))?;
+    Ok(ColorU::new(
+        css_color.r,
+        css_color.g,
+        css_color.b,
+        (css_color.a * 255.0).round() as u8,
+    ))
+}
+
+fn parse_fill_rule(string: Option<String>) -> Result<FillRule> {
+    match string.as_deref() {
+        None | Some("nonzero") => Ok(FillRule::Winding),
+        Some("evenodd") => Ok(FillRule::EvenOdd),
+        Some(string) => Err(invalid_value("fill rule", string)),
+    }
+}
+
+fn parse_composite_operation(string: &str) -> Result<CompositeOperation> {
+    Ok(match string {
+        "source-over" => CompositeOperation::SourceOver,
+        "source-in" => CompositeOperation::SourceIn,
+        "source-out" => CompositeOperation::SourceOut,
+        "source-atop" => CompositeOperation::SourceAtop,
+        "destination-over" => CompositeOperation::DestinationOver,
+        "destination-in" => CompositeOperation::DestinationIn,
+        "destination-out" => CompositeOperation::DestinationOut,
+        "destination-atop" => CompositeOperation::DestinationAtop,
+        "lighter" => CompositeOperation::Lighter,
+        "copy" => CompositeOperation::Copy,
+        "xor" => CompositeOperation::Xor,
+        "multiply" => CompositeOperation::Multiply,
+        "screen" => CompositeOperation::Screen,
+        "overlay" => CompositeOperation::Overlay,
+        "darken" => CompositeOperation::Darken,
+        "lighten" => CompositeOperation::Lighten,
+        "color-dodge" => CompositeOperation::ColorDodge,
+        "color-burn" => CompositeOperation::ColorBurn,
+        "hard-light" => CompositeOperation::HardLight,
+        "soft-light" => CompositeOperation::SoftLight,
+        "difference" => CompositeOperation::Difference,
+        "exclusion" => CompositeOperation::Exclusion,
+        "hue" => CompositeOperation::Hue,
+        "saturation" => CompositeOperation::Saturation,
+        "color" => CompositeOperation::Color,
+        "luminosity" => CompositeOperation::Luminosity,
+        _ => return Err(invalid_value("composite operation", string)),
+    })
+}
+
+fn arc_direction(anticlockwise: Option<bool>) -> ArcDirection {
+    if anticlockwise.unwrap_or(false) {
+        ArcDirection::CCW
+    } else {
+        ArcDirection::CW
+    }
+}
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> RectF {
+    RectF::new(
+        vec2f(x as f32, y as f32),
+        vec2f(width as f32, height as f32),
+    )
+}
+
+// Converts the arguments of `transform()` and `setTransform()`, which are in column-major order.
+fn row_major(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Transform2F {
+    Transform2F::row_major(a as f32, c as f32, e as f32, b as f32, d as f32, f as f32)
+}
+
+fn invalid_value(kind: &str, value: &str) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid {}: {:?}", kind, value))
+}
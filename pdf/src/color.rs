@@ -0,0 +1,218 @@
+// pathfinder/pdf/src/color.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Color spaces, converted to sRGB without color management.
+
+use crate::function::Function;
+use crate::object::{self, as_array, as_name, as_number, as_stream, resolve};
+use lopdf::{Dictionary, Document, Object};
+use pathfinder_color::ColorF;
+
+#[derive(Clone, Debug)]
+pub(crate) enum ColorSpace {
+    Gray,
+    RGB,
+    CMYK,
+    // Colors are looked up in a table of colors in the base space.
+    Indexed {
+        base: Box<ColorSpace>,
+        lookup: Vec<u8>,
+    },
+    // `Separation` and `DeviceN` spaces, whose tints are converted to the alternate space.
+    Tint {
+        component_count: usize,
+        alternate: Box<ColorSpace>,
+        tint_transform: Function,
+    },
+    // L*a*b* colors. Only the lightness is used.
+    Lab,
+    // Fills with patterns. Uncolored tiling patterns take their color from the underlying space.
+    Pattern(Option<Box<ColorSpace>>),
+}
+
+impl ColorSpace {
+    /// Parses a color space given by name or array. Names other than the device spaces are looked
+    /// up in the `ColorSpace` dictionary of `resources`.
+    pub(crate) fn from_object(
+        document: &Document,
+        object: &Object,
+        resources: Option<&Dictionary>,
+    ) -> Option<ColorSpace> {
+        let object = resolve(document, object);
+        if let Some(name) = as_name(object) {
+            return match name {
+                b"DeviceGray" | b"G" | b"CalGray" => Some(ColorSpace::Gray),
+                b"DeviceRGB" | b"RGB" | b"CalRGB" => Some(ColorSpace::RGB),
+                b"DeviceCMYK" | b"CMYK" => Some(ColorSpace::CMYK),
+                b"Pattern" => Some(ColorSpace::Pattern(None)),
+                _ => {
+                    let named = object::get_dictionary(document, resources?, b"ColorSpace")?;
+                    let object = object::get(document, named, name)?;
+                    // Guard against a named space that refers to itself by name.
+                    match as_name(object) {
+                        Some(inner_name) if inner_name == name => None,
+                        _ => ColorSpace::from_object(document, object, None),
+                    }
+                }
+            };
+        }
+
+        let array = as_array(object)?;
+        let family = as_name(resolve(document, array.first()?))?;
+        let operand = |index: usize| array.get(index).map(|object| resolve(document, object));
+        match family {
+            b"DeviceGray" | b"CalGray" => Some(ColorSpace::Gray),
+            b"DeviceRGB" | b"CalRGB" => Some(ColorSpace::RGB),
+            b"DeviceCMYK" => Some(ColorSpace::CMYK),
+            b"Lab" => Some(ColorSpace::Lab),
+            b"ICCBased" => {
+                // The profile itself is ignored; the component count picks the device space.
+                let profile = as_stream(operand(1)?)?;
+                if let Some(alternate) = object::get(document, &profile.dict, b"Alternate") {
+                    if let Some(alternate) = ColorSpace::from_object(document, alternate, resources)
+                    {
+                        return Some(alternate);
+                    }
+                }
+                match object::get_number(document, &profile.dict, b"N")? as i32 {
+                    1 => Some(ColorSpace::Gray),
+                    4 => Some(ColorSpace::CMYK),
+                    _ => Some(ColorSpace::RGB),
+                }
+            }
+            b"Indexed" | b"I" => {
+                let base = ColorSpace::from_object(document, operand(1)?, resources)?;
+                let lookup = match *operand(3)? {
+                    Object::String(ref string, _) => string.clone(),
+                    Object::Stream(ref stream) => object::stream_data(stream)?,
+                    _ => return None,
+                };
+                Some(ColorSpace::Indexed {
+                    base: Box::new(base),
+                    lookup,
+                })
+            }
+            b"Separation" | b"DeviceN" => {
+                let component_count = match family {
+                    b"Separation" => 1,
+                    _ => as_array(operand(1)?)?.len(),
+                };
+                let alternate = ColorSpace::from_object(document, operand(2)?, resources)?;
+                let tint_transform = Function::from_object(document, operand(3)?)?;
+                Some(ColorSpace::Tint {
+                    component_count,
+                    alternate: Box::new(alternate),
+                    tint_transform,
+                })
+            }
+            b"Pattern" => {
+                let base = operand(1)
+                    .and_then(|base| ColorSpace::from_object(document, base, resources))
+                    .map(Box::new);
+                Some(ColorSpace::Pattern(base))
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn component_count(&self) -> usize {
+        match *self {
+            ColorSpace::Gray | ColorSpace::Indexed { .. } => 1,
+            ColorSpace::RGB | ColorSpace::Lab => 3,
+            ColorSpace::CMYK => 4,
+            ColorSpace::Tint {
+                component_count, ..
+            } => component_count,
+            ColorSpace::Pattern(Some(ref base)) => base.component_count(),
+            ColorSpace::Pattern(None) => 0,
+        }
+    }
+
+    /// Returns the color that selecting this space sets, which is black for most spaces.
+    pub(crate) fn initial_color(&self) -> Vec<f32> {
+        match *self {
+            ColorSpace::CMYK => vec![0.0, 0.0, 0.0, 1.0],
+            ColorSpace::Tint {
+                component_count, ..
+            } => vec![1.0; component_count],
+            _ => vec![0.0; self.component_count()],
+        }
+    }
+
+    /// Returns the range that image samples are decoded to, for each component.
+    pub(crate) fn default_decode(&self, bits_per_component: u32) -> Vec<f32> {
+        match *self {
+            ColorSpace::Indexed { .. } => vec![0.0, ((1u32 << bits_per_component) - 1) as f32],
+            ColorSpace::Lab => vec![0.0, 100.0, -100.0, 100.0, -100.0, 100.0],
+            _ => (0..self.component_count())
+                .flat_map(|_| vec![0.0, 1.0])
+                .collect(),
+        }
+    }
+
+    pub(crate) fn to_rgb(&self, components: &[f32]) -> ColorF {
+        let component = |index: usize| {
+            components
+                .get(index)
+                .cloned()
+                .unwrap_or(0.0)
+                .max(0.0)
+                .min(1.0)
+        };
+        match *self {
+            ColorSpace::Gray => {
+                let gray = component(0);
+                ColorF::new(gray, gray, gray, 1.0)
+            }
+            ColorSpace::RGB => ColorF::new(component(0), component(1), component(2), 1.0),
+            ColorSpace::CMYK => {
+                let k = 1.0 - component(3);
+                ColorF::new(
+                    (1.0 - component(0)) * k,
+                    (1.0 - component(1)) * k,
+                    (1.0 - component(2)) * k,
+                    1.0,
+                )
+            }
+            ColorSpace::Indexed {
+                ref base,
+                ref lookup,
+            } => {
+                let count = base.component_count();
+                let index = components.first().cloned().unwrap_or(0.0).max(0.0) as usize;
+                let entry: Vec<f32> = (0..count)
+                    .map(|offset| {
+                        let value = lookup.get(index * count + offset).cloned().unwrap_or(0);
+                        value as f32 / 255.0
+                    })
+                    .collect();
+                base.to_rgb(&entry)
+            }
+            ColorSpace::Tint {
+                ref alternate,
+                ref tint_transform,
+                ..
+            } => alternate.to_rgb(&tint_transform.evaluate(components)),
+            ColorSpace::Lab => {
+                let lightness = (components.first().cloned().unwrap_or(0.0) / 100.0)
+                    .max(0.0)
+                    .min(1.0);
+                ColorF::new(lightness, lightness, lightness, 1.0)
+            }
+            ColorSpace::Pattern(Some(ref base)) => base.to_rgb(components),
+            ColorSpace::Pattern(None) => ColorF::black(),
+        }
+    }
+}
+
+/// Reads the numeric operands of a color operator.
+pub(crate) fn components(operands: &[Object]) -> Vec<f32> {
+    operands.iter().filter_map(as_number).collect()
+}
@@ -0,0 +1,703 @@
+// pathfinder/pdf/src/font.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loads fonts and maps character codes to glyph outlines.
+//!
+//! Embedded fonts are loaded with `font-kit`. Fonts that aren't embedded, such as the standard 14
+//! fonts, are replaced with the closest system font. Multibyte encodings are assumed to use
+//! two-byte codes, as `Identity-H` does, and vertical writing is laid out horizontally.
+
+use crate::object::{self, as_array, as_dictionary, as_name, as_number, as_stream, resolve};
+use font_kit::family_name::FamilyName;
+use font_kit::hinting::HintingOptions;
+use font_kit::loader::Loader;
+use font_kit::loaders::default::Font;
+use font_kit::outline::OutlineSink;
+use font_kit::properties::{Properties, Style, Weight};
+use font_kit::source::{Source, SystemSource};
+use lopdf::{Dictionary, Document, Object, Stream};
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem;
+use std::str;
+use std::sync::Arc;
+
+// The `Symbolic` bit of the font descriptor flags.
+const SYMBOLIC_FLAG: i64 = 1 << 2;
+
+// Windows code page 1252, from 0x80 to 0x9f. The rest of `WinAnsiEncoding` matches Latin-1.
+const WIN_ANSI_0X80: &str = "€\u{0}‚ƒ„…†‡ˆ‰Š‹Œ\u{0}Ž\u{0}\u{0}‘’“”•–—˜™š›œ\u{0}žŸ";
+
+// `MacRomanEncoding` from 0x80 to 0xff. The lower half matches ASCII.
+const MAC_ROMAN_0X80: &str = "ÄÅÇÉÑÖÜáàâäãåçéèêëíìîïñóòôöõúùûü†°¢£§•¶ß®©™´¨≠ÆØ∞±≤≥¥µ∂∑∏π∫ªºΩæø¿¡\
+                              ¬√ƒ≈∆«»…\u{A0}ÀÃÕŒœ–—“”‘’÷◊ÿŸ⁄€‹›\u{FB01}\u{FB02}‡·‚„‰ÂÊÁËÈÍÎÏÌÓÔ\
+                              \u{F8FF}ÒÚÛÙıˆ˜¯˘˙˚¸˝˛ˇ";
+
+/// A font resource.
+pub(crate) struct PDFFont<'a> {
+    kind: FontKind<'a>,
+    widths: Widths,
+}
+
+enum FontKind<'a> {
+    Outline {
+        font: Font,
+        units_per_em: f32,
+        mapping: GlyphMapping,
+        outlines: RefCell<HashMap<u32, Option<Arc<Outline>>>>,
+    },
+    Type3 {
+        glyph_names: Vec<Option<Vec<u8>>>,
+        char_procs: &'a Dictionary,
+        resources: Option<&'a Dictionary>,
+        font_matrix: Transform2F,
+    },
+    // No font could be found. Text still advances, but isn't drawn.
+    Missing {
+        two_byte: bool,
+    },
+}
+
+enum GlyphMapping {
+    Simple {
+        glyph_names: Vec<Option<Vec<u8>>>,
+        characters: Vec<Option<char>>,
+        symbolic: bool,
+    },
+    CID {
+        cid_to_gid: Option<Vec<u16>>,
+    },
+}
+
+enum Widths {
+    // Thousandths of a unit of text space, starting at the first character.
+    Simple {
+        first_char: u32,
+        widths: Vec<f32>,
+        missing_width: Option<f32>,
+    },
+    CID {
+        default_width: f32,
+        widths: HashMap<u32, f32>,
+    },
+}
+
+/// A glyph of a Type 3 font, which is drawn by running a content stream.
+pub(crate) struct Type3Glyph<'a> {
+    pub(crate) content: &'a Stream,
+    pub(crate) resources: Option<&'a Dictionary>,
+    pub(crate) font_matrix: Transform2F,
+}
+
+impl<'a> PDFFont<'a> {
+    pub(crate) fn from_dictionary(
+        document: &'a Document,
+        dictionary: &'a Dictionary,
+    ) -> PDFFont<'a> {
+        let subtype = object::get_name(document, dictionary, b"Subtype").unwrap_or(b"Type1");
+        match subtype {
+            b"Type0" => PDFFont::composite(document, dictionary),
+            b"Type3" => PDFFont::type3(document, dictionary),
+            _ => PDFFont::simple(document, dictionary),
+        }
+    }
+
+    fn simple(document: &'a Document, dictionary: &'a Dictionary) -> PDFFont<'a> {
+        let descriptor = object::get_dictionary(document, dictionary, b"FontDescriptor");
+        let symbolic = descriptor
+            .and_then(|descriptor| object::get_number(document, descriptor, b"Flags"))
+            .map_or(false, |flags| flags as i64 & SYMBOLIC_FLAG != 0);
+        let (glyph_names, characters) = simple_encoding(document, dictionary);
+        let widths = simple_widths(document, dictionary, descriptor);
+
+        let font = descriptor
+            .and_then(|descriptor| load_embedded_font(document, descriptor))
+            .or_else(|| load_system_font(document, dictionary));
+        let kind = match font {
+            Some(font) => FontKind::Outline {
+                units_per_em: font.metrics().units_per_em as f32,
+                font,
+                mapping: GlyphMapping::Simple {
+                    glyph_names,
+                    characters,
+                    symbolic,
+                },
+                outlines: RefCell::new(HashMap::new()),
+            },
+            None => FontKind::Missing { two_byte: false },
+        };
+        PDFFont { kind, widths }
+    }
+
+    fn composite(document: &'a Document, dictionary: &'a Dictionary) -> PDFFont<'a> {
+        let descendant = object::get(document, dictionary, b"DescendantFonts")
+            .and_then(as_array)
+            .and_then(|fonts| fonts.first())
+            .and_then(|font| as_dictionary(resolve(document, font)));
+        let descendant = match descendant {
+            Some(descendant) => descendant,
+            None => {
+                return PDFFont {
+                    kind: FontKind::Missing { two_byte: true },
+                    widths: Widths::CID {
+                        default_width: 1000.0,
+                        widths: HashMap::new(),
+                    },
+                }
+            }
+        };
+
+        let widths = cid_widths(document, descendant);
+        let cid_to_gid = match object::get(document, descendant, b"CIDToGIDMap").and_then(as_stream)
+        {
+            Some(stream) => object::stream_data(stream).map(|data| {
+                data.chunks(2)
+                    .map(|pair| (pair[0] as u16) << 8 | *pair.get(1).unwrap_or(&0) as u16)
+                    .collect()
+            }),
+            None => None,
+        };
+
+        let font = object::get_dictionary(document, descendant, b"FontDescriptor")
+            .and_then(|descriptor| load_embedded_font(document, descriptor))
+            .or_else(|| load_system_font(document, dictionary));
+        let kind = match font {
+            Some(font) => FontKind::Outline {
+                units_per_em: font.metrics().units_per_em as f32,
+                font,
+                mapping: GlyphMapping::CID { cid_to_gid },
+                outlines: RefCell::new(HashMap::new()),
+            },
+            None => FontKind::Missing { two_byte: true },
+        };
+        PDFFont { kind, widths }
+    }
+
+    fn type3(document: &'a Document, dictionary: &'a Dictionary) -> PDFFont<'a> {
+        let (glyph_names, _) = simple_encoding(document, dictionary);
+        let widths = simple_widths(document, dictionary, None);
+        let kind = match object::get_dictionary(document, dictionary, b"CharProcs") {
+            Some(char_procs) => FontKind::Type3 {
+                glyph_names,
+                char_procs,
+                resources: object::get_dictionary(document, dictionary, b"Resources"),
+                font_matrix: object::get_matrix(document, dictionary, b"FontMatrix"),
+            },
+            None => FontKind::Missing { two_byte: false },
+        };
+        PDFFont { kind, widths }
+    }
+
+    /// Returns true if no font program could be found, so that the text can't be drawn.
+    pub(crate) fn is_missing(&self) -> bool {
+        match self.kind {
+            FontKind::Missing { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Splits a string into character codes.
+    pub(crate) fn codes(&self, string: &[u8]) -> Vec<u32> {
+        let two_byte = match self.kind {
+            FontKind::Outline {
+                mapping: GlyphMapping::CID { .. },
+                ..
+            }
+            | FontKind::Missing { two_byte: true } => true,
+            _ => false,
+        };
+        if two_byte {
+            string
+                .chunks(2)
+                .map(|pair| (pair[0] as u32) << 8 | *pair.get(1).unwrap_or(&0) as u32)
+                .collect()
+        } else {
+            string.iter().map(|&byte| byte as u32).collect()
+        }
+    }
+
+    /// Returns the horizontal advance of the glyph for a code, in text space units at a font size
+    /// of 1.
+    pub(crate) fn advance(&self, code: u32) -> f32 {
+        let width = match self.widths {
+            Widths::Simple {
+                first_char,
+                ref widths,
+                missing_width,
+            } => code
+                .checked_sub(first_char)
+                .and_then(|index| widths.get(index as usize).cloned())
+                .or(missing_width),
+            Widths::CID {
+                default_width,
+                ref widths,
+            } => Some(*widths.get(&code).unwrap_or(&default_width)),
+        };
+
+        match (width, &self.kind) {
+            (Some(width), &FontKind::Type3 { font_matrix, .. }) => {
+                (font_matrix * vec2f(width, 0.0) - font_matrix * Vector2F::zero()).x()
+            }
+            (Some(width), _) => width / 1000.0,
+            (
+                None,
+                &FontKind::Outline {
+                    ref font,
+                    units_per_em,
+                    ..
+                },
+            ) => match self
+                .glyph_id(code)
+                .and_then(|glyph| font.advance(glyph).ok())
+            {
+                Some(advance) => advance.x() / units_per_em,
+                None => 0.0,
+            },
+            (None, _) => 0.0,
+        }
+    }
+
+    /// Returns the outline of the glyph for a code, in text space at a font size of 1.
+    pub(crate) fn outline(&self, code: u32) -> Option<Arc<Outline>> {
+        let (font, units_per_em, outlines) = match self.kind {
+            FontKind::Outline {
+                ref font,
+                units_per_em,
+                ref outlines,
+                ..
+            } => (font, units_per_em, outlines),
+            _ => return None,
+        };
+        if let Some(outline) = outlines.borrow().get(&code) {
+            return outline.clone();
+        }
+
+        let outline = self.glyph_id(code).and_then(|glyph_id| {
+            let transform = Transform2F::from_scale(1.0 / units_per_em);
+            let mut builder = OutlineBuilder::new(transform);
+            font.outline(glyph_id, HintingOptions::None, &mut builder)
+                .ok()?;
+            Some(Arc::new(builder.build()))
+        });
+        outlines.borrow_mut().insert(code, outline.clone());
+        outline
+    }
+
+    /// Returns the content stream that draws the glyph for a code, if this is a Type 3 font.
+    pub(crate) fn type3_glyph(&self, document: &'a Document, code: u32) -> Option<Type3Glyph<'a>> {
+        match self.kind {
+            FontKind::Type3 {
+                ref glyph_names,
+                char_procs,
+                resources,
+                font_matrix,
+            } => {
+                let name = glyph_names.get(code as usize)?.as_ref()?;
+                let content = object::get(document, char_procs, name).and_then(as_stream)?;
+                Some(Type3Glyph {
+                    content,
+                    resources,
+                    font_matrix,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_type3(&self) -> bool {
+        match self.kind {
+            FontKind::Type3 { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn glyph_id(&self, code: u32) -> Option<u32> {
+        let (font, mapping) = match self.kind {
+            FontKind::Outline {
+                ref font,
+                ref mapping,
+                ..
+            } => (font, mapping),
+            _ => return None,
+        };
+        match *mapping {
+            GlyphMapping::CID { ref cid_to_gid } => match *cid_to_gid {
+                Some(ref cid_to_gid) => cid_to_gid.get(code as usize).map(|&gid| gid as u32),
+                None => Some(code),
+            },
+            GlyphMapping::Simple {
+                ref glyph_names,
+                ref characters,
+                symbolic,
+            } => {
+                let name = glyph_names
+                    .get(code as usize)
+                    .and_then(|name| name.as_ref());
+                if let Some(name) = name {
+                    if let Some(glyph_id) = str::from_utf8(name)
+                        .ok()
+                        .and_then(|name| font.glyph_by_name(name))
+                    {
+                        return Some(glyph_id);
+                    }
+                }
+                let character = name
+                    .and_then(|name| character_for_glyph_name(name))
+                    .or_else(|| characters.get(code as usize).cloned().flatten());
+                if let Some(glyph_id) =
+                    character.and_then(|character| font.glyph_for_char(character))
+                {
+                    return Some(glyph_id);
+                }
+                // Symbolic TrueType fonts often map codes into the private use area, or have no
+                // usable `cmap` at all, in which case codes are glyph IDs.
+                if let Some(glyph_id) = std::char::from_u32(0xf000 + code)
+                    .and_then(|character| font.glyph_for_char(character))
+                {
+                    return Some(glyph_id);
+                }
+                if symbolic && code < font.glyph_count() {
+                    return Some(code);
+                }
+                None
+            }
+        }
+    }
+}
+
+// Returns the glyph name and Unicode character of each code of a simple font.
+fn simple_encoding(
+    document: &Document,
+    dictionary: &Dictionary,
+) -> (Vec<Option<Vec<u8>>>, Vec<Option<char>>) {
+    let encoding = object::get(document, dictionary, b"Encoding");
+    let (base_encoding, differences) = match encoding {
+        Some(&Object::Name(ref name)) => (Some(&name[..]), None),
+        Some(&Object::Dictionary(ref encoding)) => (
+            object::get_name(document, encoding, b"BaseEncoding"),
+            object::get(document, encoding, b"Differences").and_then(as_array),
+        ),
+        _ => (None, None),
+    };
+
+    let characters = (0..256)
+        .map(|code| base_encoding_character(base_encoding, code as u8))
+        .collect();
+
+    // `Differences` lists a code followed by the names of consecutive codes starting there.
+    let mut glyph_names = vec![None; 256];
+    let mut code = 0;
+    for object in differences.unwrap_or(&[]) {
+        let object = resolve(document, object);
+        if let Some(number) = as_number(object) {
+            code = number as usize;
+        } else if let Some(name) = as_name(object) {
+            if let Some(slot) = glyph_names.get_mut(code) {
+                *slot = Some(name.to_vec());
+            }
+            code += 1;
+        }
+    }
+
+    (glyph_names, characters)
+}
+
+fn base_encoding_character(encoding: Option<&[u8]>, code: u8) -> Option<char> {
+    match (encoding, code) {
+        (_, 0..=0x1f) => None,
+        (Some(b"WinAnsiEncoding"), 0x80..=0x9f) => WIN_ANSI_0X80
+            .chars()
+            .nth(code as usize - 0x80)
+            .filter(|&character| character != '\u{0}'),
+        (Some(b"WinAnsiEncoding"), _) => Some(code as char),
+        (Some(b"MacRomanEncoding"), 0x80..=0xff) => {
+            MAC_ROMAN_0X80.chars().nth(code as usize - 0x80)
+        }
+        // `StandardEncoding` has curly quotes in place of the ASCII ones. Its upper half is mostly
+        // accented letters found through glyph names instead.
+        (_, b'\'') => Some('’'),
+        (_, b'`') => Some('‘'),
+        (_, 0x20..=0x7e) => Some(code as char),
+        _ => None,
+    }
+}
+
+// Maps glyph names to characters, for fonts that can't look glyphs up by name. This covers the
+// `uniXXXX` and `uXXXX` forms, single letters and digits, and common punctuation; it isn't the
+// whole Adobe Glyph List.
+fn character_for_glyph_name(name: &[u8]) -> Option<char> {
+    let name = str::from_utf8(name).ok()?;
+    let name = name.split('.').next()?;
+    if name.len() == 1 {
+        return name.chars().next();
+    }
+    if name.starts_with("uni") && name.len() == 7 {
+        return u32::from_str_radix(&name[3..], 16)
+            .ok()
+            .and_then(std::char::from_u32);
+    }
+    if name.starts_with('u') && (5..=7).contains(&name.len()) {
+        if let Ok(value) = u32::from_str_radix(&name[1..], 16) {
+            return std::char::from_u32(value);
+        }
+    }
+    let character = match name {
+        "space" => ' ',
+        "exclam" => '!',
+        "quotedbl" => '"',
+        "numbersign" => '#',
+        "dollar" => '$',
+        "percent" => '%',
+        "ampersand" => '&',
+        "quotesingle" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "asterisk" => '*',
+        "plus" => '+',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "period" => '.',
+        "slash" => '/',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        "colon" => ':',
+        "semicolon" => ';',
+        "less" => '<',
+        "equal" => '=',
+        "greater" => '>',
+        "question" => '?',
+        "at" => '@',
+        "bracketleft" => '[',
+        "backslash" => '\\',
+        "bracketright" => ']',
+        "asciicircum" => '^',
+        "underscore" => '_',
+        "grave" => '`',
+        "braceleft" => '{',
+        "bar" => '|',
+        "braceright" => '}',
+        "asciitilde" => '~',
+        "quoteleft" => '‘',
+        "quoteright" => '’',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "endash" => '–',
+        "emdash" => '—',
+        "bullet" => '•',
+        "ellipsis" => '…',
+        "dagger" => '†',
+        "degree" => '°',
+        "copyright" => '©',
+        "registered" => '®',
+        "trademark" => '™',
+        "fi" => 'ﬁ',
+        "fl" => 'ﬂ',
+        _ => return None,
+    };
+    Some(character)
+}
+
+fn simple_widths(
+    document: &Document,
+    dictionary: &Dictionary,
+    descriptor: Option<&Dictionary>,
+) -> Widths {
+    Widths::Simple {
+        first_char: object::get_number(document, dictionary, b"FirstChar").unwrap_or(0.0) as u32,
+        widths: object::get_numbers(document, dictionary, b"Widths"),
+        missing_width: descriptor
+            .and_then(|descriptor| object::get_number(document, descriptor, b"MissingWidth")),
+    }
+}
+
+// Reads the `W` array of a CIDFont, whose entries are either `c [w1 w2 …]` or `c_first c_last w`.
+fn cid_widths(document: &Document, descendant: &Dictionary) -> Widths {
+    let default_width = object::get_number(document, descendant, b"DW").unwrap_or(1000.0);
+    let mut widths = HashMap::new();
+    let entries = object::get(document, descendant, b"W")
+        .and_then(as_array)
+        .unwrap_or(&[]);
+    let mut index = 0;
+    while index + 1 < entries.len() {
+        let first = match as_number(resolve(document, &entries[index])) {
+            Some(first) => first as u32,
+            None => break,
+        };
+        match *resolve(document, &entries[index + 1]) {
+            Object::Array(ref run) => {
+                for (offset, width) in object::numbers(document, run).into_iter().enumerate() {
+                    widths.insert(first + offset as u32, width);
+                }
+                index += 2;
+            }
+            ref last => {
+                let last = as_number(last).unwrap_or(0.0) as u32;
+                let width = entries
+                    .get(index + 2)
+                    .and_then(|width| as_number(resolve(document, width)))
+                    .unwrap_or(default_width);
+                for code in first..=last.min(first + 0xffff) {
+                    widths.insert(code, width);
+                }
+                index += 3;
+            }
+        }
+    }
+    Widths::CID {
+        default_width,
+        widths,
+    }
+}
+
+fn load_embedded_font(document: &Document, descriptor: &Dictionary) -> Option<Font> {
+    let stream = [&b"FontFile2"[..], b"FontFile3", b"FontFile"]
+        .iter()
+        .filter_map(|key| object::get(document, descriptor, key).and_then(as_stream))
+        .next()?;
+    let data = object::stream_data(stream)?;
+    Font::from_bytes(Arc::new(data), 0).ok()
+}
+
+// Picks a system font resembling the `BaseFont`, such as `Times-Bold` or `ABCDEF+Arial,Italic`.
+fn load_system_font(document: &Document, dictionary: &Dictionary) -> Option<Font> {
+    let base_font = object::get_name(document, dictionary, b"BaseFont").unwrap_or(b"Helvetica");
+    let base_font = String::from_utf8_lossy(base_font).to_lowercase();
+    // Subset fonts have a six-letter tag prefix.
+    let base_font = match base_font.find('+') {
+        Some(6) => &base_font[7..],
+        _ => &base_font[..],
+    };
+
+    let family = if base_font.contains("courier") || base_font.contains("mono") {
+        FamilyName::Monospace
+    } else if base_font.contains("times")
+        || (base_font.contains("serif") && !base_font.contains("sans"))
+    {
+        FamilyName::Serif
+    } else {
+        FamilyName::SansSerif
+    };
+    let mut properties = Properties::new();
+    if base_font.contains("bold") || base_font.contains("black") || base_font.contains("heavy") {
+        properties.weight = Weight::BOLD;
+    }
+    if base_font.contains("italic") || base_font.contains("oblique") {
+        properties.style = Style::Italic;
+    }
+
+    SystemSource::new()
+        .select_best_match(&[family], &properties)
+        .ok()?
+        .load()
+        .ok()
+}
+
+struct OutlineBuilder {
+    outline: Outline,
+    current_contour: Contour,
+    transform: Transform2F,
+}
+
+impl OutlineBuilder {
+    fn new(transform: Transform2F) -> OutlineBuilder {
+        OutlineBuilder {
+            outline: Outline::new(),
+            current_contour: Contour::new(),
+            transform,
+        }
+    }
+
+    fn flush_current_contour(&mut self) {
+        if !self.current_contour.is_empty() {
+            self.outline
+                .push_contour(mem::replace(&mut self.current_contour, Contour::new()));
+        }
+    }
+
+    fn build(mut self) -> Outline {
+        self.flush_current_contour();
+        self.outline
+    }
+}
+
+impl OutlineSink for OutlineBuilder {
+    fn move_to(&mut self, to: Vector2F) {
+        self.flush_current_contour();
+        self.current_contour.push_endpoint(self.transform * to);
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.current_contour.push_endpoint(self.transform * to);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.current_contour
+            .push_quadratic(self.transform * ctrl, self.transform * to);
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.current_contour.push_cubic(
+            self.transform * ctrl.from(),
+            self.transform * ctrl.to(),
+            self.transform * to,
+        );
+    }
+
+    fn close(&mut self) {
+        self.current_contour.close();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base_encoding_character, character_for_glyph_name};
+
+    #[test]
+    fn test_base_encodings() {
+        assert_eq!(
+            base_encoding_character(Some(b"WinAnsiEncoding"), 0x80),
+            Some('€')
+        );
+        assert_eq!(
+            base_encoding_character(Some(b"WinAnsiEncoding"), 0x81),
+            None
+        );
+        assert_eq!(
+            base_encoding_character(Some(b"WinAnsiEncoding"), 0xe9),
+            Some('é')
+        );
+        assert_eq!(
+            base_encoding_character(Some(b"MacRomanEncoding"), 0x8e),
+            Some('é')
+        );
+        assert_eq!(base_encoding_character(None, b'\''), Some('’'));
+    }
+
+    #[test]
+    fn test_glyph_names() {
+        assert_eq!(character_for_glyph_name(b"A"), Some('A'));
+        assert_eq!(character_for_glyph_name(b"uni00E9"), Some('é'));
+        assert_eq!(character_for_glyph_name(b"u1F600"), Some('😀'));
+        assert_eq!(character_for_glyph_name(b"period.sc"), Some('.'));
+        assert_eq!(character_for_glyph_name(b"notaglyph"), None);
+    }
+}
@@ -0,0 +1,552 @@
+// pathfinder/pdf/src/function.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! PDF functions, which shadings and separation color spaces use to compute colors.
+
+use crate::object::{self, as_array, as_dictionary, as_stream, resolve};
+use lopdf::{Document, Object};
+
+// PostScript calculator functions that run longer than this are assumed to be broken.
+const MAX_CALCULATOR_STEPS: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub(crate) enum Function {
+    // Type 0: a table of samples, interpolated linearly along the first input.
+    Sampled {
+        domain: Vec<f32>,
+        range: Vec<f32>,
+        size: Vec<usize>,
+        encode: Vec<f32>,
+        decode: Vec<f32>,
+        samples: Vec<f32>,
+    },
+    // Type 2: `C0 + x^N × (C1 − C0)`.
+    Exponential {
+        domain: Vec<f32>,
+        c0: Vec<f32>,
+        c1: Vec<f32>,
+        exponent: f32,
+    },
+    // Type 3: a sequence of functions, each covering part of the domain.
+    Stitching {
+        domain: Vec<f32>,
+        functions: Vec<Function>,
+        bounds: Vec<f32>,
+        encode: Vec<f32>,
+    },
+    // Type 4: a program in a small subset of PostScript.
+    Calculator {
+        domain: Vec<f32>,
+        range: Vec<f32>,
+        program: Vec<Instruction>,
+    },
+    // An array of single-output functions, one per output.
+    Array(Vec<Function>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Instruction {
+    Number(f32),
+    Operator(Vec<u8>),
+    If(Vec<Instruction>),
+    IfElse(Vec<Instruction>, Vec<Instruction>),
+}
+
+impl Function {
+    /// Parses a function dictionary or stream, or an array of them. Returns `None` for malformed
+    /// functions.
+    pub(crate) fn from_object(document: &Document, object: &Object) -> Option<Function> {
+        let object = resolve(document, object);
+        if let Some(array) = as_array(object) {
+            let functions: Option<Vec<Function>> = array
+                .iter()
+                .map(|function| Function::from_object(document, function))
+                .collect();
+            return functions.map(Function::Array);
+        }
+
+        let dictionary = as_dictionary(object)?;
+        let domain = object::get_numbers(document, dictionary, b"Domain");
+        let range = object::get_numbers(document, dictionary, b"Range");
+        match object::get_number(document, dictionary, b"FunctionType")? as i32 {
+            0 => {
+                let data = object::stream_data(as_stream(object)?)?;
+                let size: Vec<usize> = object::get_numbers(document, dictionary, b"Size")
+                    .into_iter()
+                    .map(|size| size.max(1.0) as usize)
+                    .collect();
+                let bits_per_sample =
+                    object::get_number(document, dictionary, b"BitsPerSample")? as u32;
+                let mut encode = object::get_numbers(document, dictionary, b"Encode");
+                if encode.is_empty() {
+                    encode = size
+                        .iter()
+                        .flat_map(|&size| vec![0.0, (size - 1) as f32])
+                        .collect();
+                }
+                let mut decode = object::get_numbers(document, dictionary, b"Decode");
+                if decode.is_empty() {
+                    decode = range.clone();
+                }
+                let sample_count = size.iter().product::<usize>() * range.len() / 2;
+                let samples = unpack_samples(&data, bits_per_sample, sample_count)?;
+                Some(Function::Sampled {
+                    domain,
+                    range,
+                    size,
+                    encode,
+                    decode,
+                    samples,
+                })
+            }
+            2 => {
+                let mut c0 = object::get_numbers(document, dictionary, b"C0");
+                if c0.is_empty() {
+                    c0 = vec![0.0];
+                }
+                let mut c1 = object::get_numbers(document, dictionary, b"C1");
+                if c1.is_empty() {
+                    c1 = vec![1.0];
+                }
+                let exponent = object::get_number(document, dictionary, b"N")?;
+                Some(Function::Exponential {
+                    domain,
+                    c0,
+                    c1,
+                    exponent,
+                })
+            }
+            3 => {
+                let functions = object::get(document, dictionary, b"Functions")
+                    .and_then(as_array)?
+                    .iter()
+                    .map(|function| Function::from_object(document, function))
+                    .collect::<Option<Vec<Function>>>()?;
+                let bounds = object::get_numbers(document, dictionary, b"Bounds");
+                let encode = object::get_numbers(document, dictionary, b"Encode");
+                if functions.is_empty() || bounds.len() + 1 != functions.len() {
+                    return None;
+                }
+                Some(Function::Stitching {
+                    domain,
+                    functions,
+                    bounds,
+                    encode,
+                })
+            }
+            4 => {
+                let data = object::stream_data(as_stream(object)?)?;
+                let program = parse_calculator_program(&data)?;
+                Some(Function::Calculator {
+                    domain,
+                    range,
+                    program,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluates the function. Inputs are clipped to the domain and outputs to the range.
+    pub(crate) fn evaluate(&self, input: &[f32]) -> Vec<f32> {
+        match *self {
+            Function::Sampled {
+                ref domain,
+                ref range,
+                ref size,
+                ref encode,
+                ref decode,
+                ref samples,
+            } => {
+                let output_count = range.len() / 2;
+                if output_count == 0 || size.is_empty() {
+                    return vec![];
+                }
+
+                // Find the sample position along each input. Only the first input is
+                // interpolated; the others use the nearest sample.
+                let mut index = 0;
+                let mut stride = output_count;
+                let mut fraction = 0.0;
+                let mut next_offset = 0;
+                for (dimension, &size) in size.iter().enumerate() {
+                    let x = clip_to_domain(input.get(dimension).cloned(), domain, dimension);
+                    let (d0, d1) = pair(domain, dimension, (0.0, 1.0));
+                    let (e0, e1) = pair(encode, dimension, (0.0, (size - 1) as f32));
+                    let e = interpolate(x, d0, d1, e0, e1)
+                        .max(0.0)
+                        .min((size - 1) as f32);
+                    if dimension == 0 {
+                        let floor = e.floor();
+                        fraction = e - floor;
+                        index += floor as usize * stride;
+                        next_offset = if (floor as usize) + 1 < size {
+                            stride
+                        } else {
+                            0
+                        };
+                    } else {
+                        index += e.round() as usize * stride;
+                    }
+                    stride *= size;
+                }
+
+                (0..output_count)
+                    .map(|output| {
+                        let sample = |offset: usize| {
+                            samples.get(index + offset + output).cloned().unwrap_or(0.0)
+                        };
+                        let value = sample(0) + (sample(next_offset) - sample(0)) * fraction;
+                        let (d0, d1) = pair(decode, output, (0.0, 1.0));
+                        clip_to_range(d0 + value * (d1 - d0), range, output)
+                    })
+                    .collect()
+            }
+            Function::Exponential {
+                ref domain,
+                ref c0,
+                ref c1,
+                exponent,
+            } => {
+                let x = clip_to_domain(input.first().cloned(), domain, 0);
+                let x_n = x.powf(exponent);
+                c0.iter()
+                    .zip(c1.iter())
+                    .map(|(&c0, &c1)| c0 + x_n * (c1 - c0))
+                    .collect()
+            }
+            Function::Stitching {
+                ref domain,
+                ref functions,
+                ref bounds,
+                ref encode,
+            } => {
+                let x = clip_to_domain(input.first().cloned(), domain, 0);
+                let (d0, d1) = pair(domain, 0, (0.0, 1.0));
+                let index = bounds
+                    .iter()
+                    .position(|&bound| x < bound)
+                    .unwrap_or(bounds.len());
+                let low = if index == 0 { d0 } else { bounds[index - 1] };
+                let high = if index == bounds.len() {
+                    d1
+                } else {
+                    bounds[index]
+                };
+                let (e0, e1) = pair(encode, index, (0.0, 1.0));
+                functions[index].evaluate(&[interpolate(x, low, high, e0, e1)])
+            }
+            Function::Calculator {
+                ref domain,
+                ref range,
+                ref program,
+            } => {
+                let mut stack: Vec<f32> = (0..domain.len() / 2)
+                    .map(|dimension| {
+                        clip_to_domain(input.get(dimension).cloned(), domain, dimension)
+                    })
+                    .collect();
+                let mut steps = 0;
+                if run_calculator(program, &mut stack, &mut steps).is_none() {
+                    return vec![0.0; range.len() / 2];
+                }
+                let output_count = range.len() / 2;
+                let start = stack.len().saturating_sub(output_count);
+                stack[start..]
+                    .iter()
+                    .enumerate()
+                    .map(|(output, &value)| clip_to_range(value, range, output))
+                    .collect()
+            }
+            Function::Array(ref functions) => functions
+                .iter()
+                .filter_map(|function| function.evaluate(input).first().cloned())
+                .collect(),
+        }
+    }
+}
+
+fn pair(values: &[f32], index: usize, default: (f32, f32)) -> (f32, f32) {
+    match (values.get(index * 2), values.get(index * 2 + 1)) {
+        (Some(&low), Some(&high)) => (low, high),
+        _ => default,
+    }
+}
+
+fn clip_to_domain(x: Option<f32>, domain: &[f32], dimension: usize) -> f32 {
+    let (low, high) = pair(domain, dimension, (0.0, 1.0));
+    x.unwrap_or(low).max(low).min(high)
+}
+
+fn clip_to_range(y: f32, range: &[f32], output: usize) -> f32 {
+    let (low, high) = pair(range, output, (f32::NEG_INFINITY, f32::INFINITY));
+    y.max(low).min(high)
+}
+
+fn interpolate(x: f32, x_min: f32, x_max: f32, y_min: f32, y_max: f32) -> f32 {
+    if x_max == x_min {
+        return y_min;
+    }
+    y_min + (x - x_min) * (y_max - y_min) / (x_max - x_min)
+}
+
+// Unpacks big-endian samples of the given bit depth into values in [0, 1].
+pub(crate) fn unpack_samples(data: &[u8], bits: u32, count: usize) -> Option<Vec<f32>> {
+    if bits == 0 || bits > 32 || data.len() * 8 < count * bits as usize {
+        return None;
+    }
+    let max = ((1u64 << bits) - 1) as f32;
+    let mut samples = Vec::with_capacity(count);
+    let mut bit_offset = 0;
+    for _ in 0..count {
+        let mut value = 0u64;
+        for _ in 0..bits {
+            let bit = (data[bit_offset / 8] >> (7 - bit_offset % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            bit_offset += 1;
+        }
+        samples.push(value as f32 / max);
+    }
+    Some(samples)
+}
+
+fn parse_calculator_program(data: &[u8]) -> Option<Vec<Instruction>> {
+    let mut tokens = tokenize(data).into_iter().peekable();
+    // The program is enclosed in braces.
+    match tokens.next() {
+        Some(ref token) if token == b"{" => {}
+        _ => return None,
+    }
+    parse_procedure(&mut tokens)
+}
+
+fn parse_procedure<I>(tokens: &mut std::iter::Peekable<I>) -> Option<Vec<Instruction>>
+where
+    I: Iterator<Item = Vec<u8>>,
+{
+    let mut instructions = vec![];
+    let mut pending_procedures = vec![];
+    loop {
+        let token = tokens.next()?;
+        match &token[..] {
+            b"}" => return Some(instructions),
+            b"{" => pending_procedures.push(parse_procedure(tokens)?),
+            b"if" => {
+                let procedure = pending_procedures.pop()?;
+                instructions.push(Instruction::If(procedure));
+            }
+            b"ifelse" => {
+                let else_procedure = pending_procedures.pop()?;
+                let if_procedure = pending_procedures.pop()?;
+                instructions.push(Instruction::IfElse(if_procedure, else_procedure));
+            }
+            _ => match std::str::from_utf8(&token).ok()?.parse::<f32>() {
+                Ok(number) => instructions.push(Instruction::Number(number)),
+                Err(_) => instructions.push(Instruction::Operator(token)),
+            },
+        }
+    }
+}
+
+fn tokenize(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut tokens = vec![];
+    let mut token = vec![];
+    for &byte in data {
+        match byte {
+            b'{' | b'}' => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::replace(&mut token, vec![]));
+                }
+                tokens.push(vec![byte]);
+            }
+            _ if byte.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::replace(&mut token, vec![]));
+                }
+            }
+            _ => token.push(byte),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+// Runs a calculator procedure. Booleans are represented as 0 and 1. Returns `None` on stack
+// underflow, unknown operators, or runaway programs.
+fn run_calculator(program: &[Instruction], stack: &mut Vec<f32>, steps: &mut usize) -> Option<()> {
+    for instruction in program {
+        *steps += 1;
+        if *steps > MAX_CALCULATOR_STEPS {
+            return None;
+        }
+        match *instruction {
+            Instruction::Number(number) => stack.push(number),
+            Instruction::If(ref procedure) => {
+                if stack.pop()? != 0.0 {
+                    run_calculator(procedure, stack, steps)?;
+                }
+            }
+            Instruction::IfElse(ref if_procedure, ref else_procedure) => {
+                if stack.pop()? != 0.0 {
+                    run_calculator(if_procedure, stack, steps)?;
+                } else {
+                    run_calculator(else_procedure, stack, steps)?;
+                }
+            }
+            Instruction::Operator(ref operator) => run_operator(operator, stack)?,
+        }
+    }
+    Some(())
+}
+
+fn run_operator(operator: &[u8], stack: &mut Vec<f32>) -> Option<()> {
+    let boolean = |value: bool| if value { 1.0 } else { 0.0 };
+    match operator {
+        b"true" => stack.push(1.0),
+        b"false" => stack.push(0.0),
+        b"abs" | b"neg" | b"ceiling" | b"floor" | b"round" | b"truncate" | b"sqrt" | b"sin"
+        | b"cos" | b"exp" | b"ln" | b"log" | b"cvi" | b"cvr" | b"not" => {
+            let x = stack.pop()?;
+            stack.push(match operator {
+                b"abs" => x.abs(),
+                b"neg" => -x,
+                b"ceiling" => x.ceil(),
+                b"floor" => x.floor(),
+                b"round" => (x + 0.5).floor(),
+                b"truncate" | b"cvi" => x.trunc(),
+                b"sqrt" => x.sqrt(),
+                // Angles are in degrees.
+                b"sin" => x.to_radians().sin(),
+                b"cos" => x.to_radians().cos(),
+                b"exp" => x.exp(),
+                b"ln" => x.ln(),
+                b"log" => x.log10(),
+                b"not" => boolean(x == 0.0),
+                _ => x,
+            });
+        }
+        b"add" | b"sub" | b"mul" | b"div" | b"idiv" | b"mod" | b"atan" | b"eq" | b"ne" | b"gt"
+        | b"ge" | b"lt" | b"le" | b"and" | b"or" | b"xor" | b"bitshift" => {
+            let y = stack.pop()?;
+            let x = stack.pop()?;
+            stack.push(match operator {
+                b"add" => x + y,
+                b"sub" => x - y,
+                b"mul" => x * y,
+                b"div" => x / y,
+                b"idiv" if y as i64 != 0 => (x as i64 / y as i64) as f32,
+                b"mod" if y as i64 != 0 => (x as i64 % y as i64) as f32,
+                b"idiv" | b"mod" => return None,
+                b"atan" => {
+                    let angle = x.atan2(y).to_degrees();
+                    if angle < 0.0 {
+                        angle + 360.0
+                    } else {
+                        angle
+                    }
+                }
+                b"eq" => boolean(x == y),
+                b"ne" => boolean(x != y),
+                b"gt" => boolean(x > y),
+                b"ge" => boolean(x >= y),
+                b"lt" => boolean(x < y),
+                b"le" => boolean(x <= y),
+                b"and" => (x as i64 & y as i64) as f32,
+                b"or" => (x as i64 | y as i64) as f32,
+                b"xor" => (x as i64 ^ y as i64) as f32,
+                b"bitshift" if y >= 0.0 => ((x as i64) << y as i64) as f32,
+                b"bitshift" => ((x as i64) >> -y as i64) as f32,
+                _ => return None,
+            });
+        }
+        b"dup" => {
+            let x = *stack.last()?;
+            stack.push(x);
+        }
+        b"pop" => {
+            stack.pop()?;
+        }
+        b"exch" => {
+            let length = stack.len();
+            if length < 2 {
+                return None;
+            }
+            stack.swap(length - 1, length - 2);
+        }
+        b"copy" => {
+            let count = stack.pop()? as usize;
+            let start = stack.len().checked_sub(count)?;
+            let copied = stack[start..].to_vec();
+            stack.extend(copied);
+        }
+        b"index" => {
+            let index = stack.pop()? as usize;
+            let position = stack.len().checked_sub(index + 1)?;
+            let x = stack[position];
+            stack.push(x);
+        }
+        b"roll" => {
+            let shift = stack.pop()? as i64;
+            let count = stack.pop()? as usize;
+            let start = stack.len().checked_sub(count)?;
+            if count > 0 {
+                let shift = shift.rem_euclid(count as i64) as usize;
+                stack[start..].rotate_right(shift);
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_calculator_program, run_calculator, Function};
+
+    #[test]
+    fn test_exponential() {
+        let function = Function::Exponential {
+            domain: vec![0.0, 1.0],
+            c0: vec![0.0, 1.0],
+            c1: vec![1.0, 0.0],
+            exponent: 1.0,
+        };
+        assert_eq!(function.evaluate(&[0.25]), vec![0.25, 0.75]);
+        assert_eq!(function.evaluate(&[2.0]), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_stitching() {
+        let half = |c0, c1| Function::Exponential {
+            domain: vec![0.0, 1.0],
+            c0: vec![c0],
+            c1: vec![c1],
+            exponent: 1.0,
+        };
+        let function = Function::Stitching {
+            domain: vec![0.0, 1.0],
+            functions: vec![half(0.0, 1.0), half(1.0, 0.0)],
+            bounds: vec![0.5],
+            encode: vec![0.0, 1.0, 0.0, 1.0],
+        };
+        assert_eq!(function.evaluate(&[0.25]), vec![0.5]);
+        assert_eq!(function.evaluate(&[0.75]), vec![0.5]);
+    }
+
+    #[test]
+    fn test_calculator() {
+        let program = parse_calculator_program(b"{ dup 0.5 gt { 1 exch sub } if 2 mul }").unwrap();
+        let (mut stack, mut steps) = (vec![0.75], 0);
+        run_calculator(&program, &mut stack, &mut steps).unwrap();
+        assert_eq!(stack, vec![0.5]);
+    }
+}
@@ -0,0 +1,208 @@
+// pathfinder/pdf/src/image.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decodes image XObjects.
+
+use crate::color::ColorSpace;
+use crate::object::{self, as_array, as_name, as_stream, resolve};
+use image::ImageFormat;
+use lopdf::{Dictionary, Document, Stream};
+use pathfinder_color::{self as color, ColorU};
+use pathfinder_content::pattern::Image;
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use std::sync::Arc;
+
+/// Decodes an image, including its soft mask or stencil mask if it has one.
+///
+/// Stencil masks (`/ImageMask true`) are painted with `fill_color`. Returns `None` for malformed
+/// images and for filters other than the general-purpose ones and `DCTDecode`.
+pub(crate) fn decode_image(
+    document: &Document,
+    stream: &Stream,
+    resources: Option<&Dictionary>,
+    fill_color: ColorU,
+) -> Option<Image> {
+    let (size, mut pixels) = decode_pixels(document, stream, resources, fill_color)?;
+
+    let mask = object::get(document, &stream.dict, b"SMask")
+        .and_then(as_stream)
+        .map(|mask| (mask, true))
+        .or_else(|| {
+            object::get(document, &stream.dict, b"Mask")
+                .and_then(as_stream)
+                .map(|mask| (mask, false))
+        });
+    if let Some((mask, is_soft)) = mask {
+        // Soft masks are grayscale images of alpha values; stencil masks are painted where the
+        // image shows.
+        let (mask_size, mask_pixels) = decode_pixels(document, mask, None, ColorU::white())?;
+        for y in 0..size.y() {
+            let mask_y = y * mask_size.y() / size.y();
+            for x in 0..size.x() {
+                let mask_x = x * mask_size.x() / size.x();
+                let mask_pixel = mask_pixels[(mask_y * mask_size.x() + mask_x) as usize];
+                let pixel = &mut pixels[(y * size.x() + x) as usize];
+                pixel.a = if is_soft { mask_pixel.r } else { mask_pixel.a };
+            }
+        }
+    }
+
+    Some(Image::new(size, Arc::new(pixels)))
+}
+
+fn decode_pixels(
+    document: &Document,
+    stream: &Stream,
+    resources: Option<&Dictionary>,
+    fill_color: ColorU,
+) -> Option<(Vector2I, Vec<ColorU>)> {
+    let dictionary = &stream.dict;
+    let width = object::get_number(document, dictionary, b"Width")? as i32;
+    let height = object::get_number(document, dictionary, b"Height")? as i32;
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    let size = vec2i(width, height);
+
+    let filters: Vec<&[u8]> = match object::get(document, dictionary, b"Filter") {
+        Some(filter) => match as_array(filter) {
+            Some(filters) => filters
+                .iter()
+                .filter_map(|filter| as_name(resolve(document, filter)))
+                .collect(),
+            None => as_name(filter).into_iter().collect(),
+        },
+        None => vec![],
+    };
+    match filters.last() {
+        Some(&b"DCTDecode") if filters.len() == 1 => {
+            let image =
+                image::load_from_memory_with_format(&stream.content, ImageFormat::Jpeg).ok()?;
+            let image = image.to_rgba8();
+            if image.dimensions() != (width as u32, height as u32) {
+                return None;
+            }
+            return Some((size, color::u8_vec_to_color_vec(image.into_raw())));
+        }
+        Some(&b"DCTDecode")
+        | Some(&b"JPXDecode")
+        | Some(&b"JBIG2Decode")
+        | Some(&b"CCITTFaxDecode") => return None,
+        _ => {}
+    }
+    let data = object::stream_data(stream)?;
+
+    if object::get_bool(document, dictionary, b"ImageMask").unwrap_or(false) {
+        // Samples of 0 are painted, unless the decode array inverts them.
+        let inverted = object::get_numbers(document, dictionary, b"Decode").first() == Some(&1.0);
+        let samples = SampleReader::new(&data, size, 1, 1)?;
+        let pixels = samples
+            .map(|sample| {
+                if (sample == 0) != inverted {
+                    fill_color
+                } else {
+                    ColorU::transparent_black()
+                }
+            })
+            .collect();
+        return Some((size, pixels));
+    }
+
+    let color_space = ColorSpace::from_object(
+        document,
+        object::get(document, dictionary, b"ColorSpace")?,
+        resources,
+    )?;
+    let bits_per_component = object::get_number(document, dictionary, b"BitsPerComponent")
+        .map(|bits| bits as u32)
+        .unwrap_or(8);
+    let component_count = color_space.component_count();
+    let mut decode = object::get_numbers(document, dictionary, b"Decode");
+    if decode.len() != component_count * 2 {
+        decode = color_space.default_decode(bits_per_component);
+    }
+
+    let max_sample = ((1u64 << bits_per_component) - 1) as f32;
+    let mut samples = SampleReader::new(&data, size, component_count, bits_per_component)?;
+    let mut components = vec![0.0; component_count];
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for _ in 0..(width * height) {
+        for (index, component) in components.iter_mut().enumerate() {
+            let sample = samples.next()? as f32;
+            let (d0, d1) = (decode[index * 2], decode[index * 2 + 1]);
+            *component = d0 + sample * (d1 - d0) / max_sample;
+        }
+        pixels.push(color_space.to_rgb(&components).to_u8());
+    }
+    Some((size, pixels))
+}
+
+// Reads packed samples, whose rows start on byte boundaries.
+struct SampleReader<'a> {
+    data: &'a [u8],
+    row_length: usize,
+    samples_per_row: usize,
+    bits: u32,
+    row: usize,
+    sample_in_row: usize,
+}
+
+impl<'a> SampleReader<'a> {
+    fn new(
+        data: &'a [u8],
+        size: Vector2I,
+        component_count: usize,
+        bits: u32,
+    ) -> Option<SampleReader<'a>> {
+        if ![1, 2, 4, 8, 16].contains(&bits) {
+            return None;
+        }
+        let samples_per_row = size.x() as usize * component_count;
+        let row_length = (samples_per_row * bits as usize + 7) / 8;
+        if data.len() < row_length * size.y() as usize {
+            return None;
+        }
+        Some(SampleReader {
+            data,
+            row_length,
+            samples_per_row,
+            bits,
+            row: 0,
+            sample_in_row: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for SampleReader<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.sample_in_row == self.samples_per_row {
+            self.row += 1;
+            self.sample_in_row = 0;
+        }
+        let row = self.data.get(self.row * self.row_length..)?;
+        let sample = match self.bits {
+            8 => *row.get(self.sample_in_row)? as u32,
+            16 => {
+                let offset = self.sample_in_row * 2;
+                (*row.get(offset)? as u32) << 8 | *row.get(offset + 1)? as u32
+            }
+            bits => {
+                let bit_offset = self.sample_in_row * bits as usize;
+                let byte = *row.get(bit_offset / 8)? as u32;
+                let shift = 8 - bits - (bit_offset % 8) as u32;
+                (byte >> shift) & ((1 << bits) - 1)
+            }
+        };
+        self.sample_in_row += 1;
+        Some(sample)
+    }
+}
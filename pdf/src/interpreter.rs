@@ -0,0 +1,1008 @@
+// pathfinder/pdf/src/interpreter.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Runs content streams, adding what they draw to a scene.
+
+use crate::color::{self, ColorSpace};
+use crate::font::PDFFont;
+use crate::image;
+use crate::object::{self, as_array, as_dictionary, as_name, as_number, as_stream, as_string};
+use crate::object::{matrix, resolve};
+use crate::shading::Shading;
+use crate::BuildResultFlags;
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::dash::OutlineDash;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::{Contour, Outline};
+use pathfinder_content::pattern::{Image, Pattern};
+use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::paint::{Paint, PaintId};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Group, Scene};
+use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
+
+// Form XObjects and Type 3 glyphs nested deeper than this are assumed to be recursive.
+const MAX_NESTING_DEPTH: usize = 32;
+
+pub(crate) struct Interpreter<'a> {
+    document: &'a Document,
+    pub(crate) scene: Scene,
+    pub(crate) result_flags: BuildResultFlags,
+    resources: Option<&'a Dictionary>,
+    state: GraphicsState<'a>,
+    state_stack: Vec<GraphicsState<'a>>,
+    path: PathBuilder,
+    pending_clip: Option<FillRule>,
+    text_matrix: Transform2F,
+    text_line_matrix: Transform2F,
+    // Glyphs drawn with a clipping render mode, in scene space, until the end of the text object.
+    text_clip: Option<Outline>,
+    // Maps the default space of the current content stream to the scene. Pattern matrices are
+    // relative to it rather than to the current transform.
+    pattern_transform: Transform2F,
+    // The visible area of the page, in scene space.
+    page_bounds: RectF,
+    fonts: HashMap<ObjectId, Rc<PDFFont<'a>>>,
+    images: HashMap<ObjectId, Option<Image>>,
+    depth: usize,
+}
+
+#[derive(Clone)]
+struct GraphicsState<'a> {
+    transform: Transform2F,
+    clip_path: Option<ClipPathId>,
+    fill: ColorState,
+    stroke: ColorState,
+    fill_alpha: f32,
+    stroke_alpha: f32,
+    blend_mode: BlendMode,
+    line_width: f32,
+    line_cap: LineCap,
+    line_join: i32,
+    miter_limit: f32,
+    dash_array: Vec<f32>,
+    dash_offset: f32,
+    font: Option<Rc<PDFFont<'a>>>,
+    font_size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horizontal_scaling: f32,
+    leading: f32,
+    rise: f32,
+    render_mode: i32,
+}
+
+#[derive(Clone)]
+struct ColorState {
+    space: Rc<ColorSpace>,
+    source: PaintSource,
+}
+
+#[derive(Clone)]
+enum PaintSource {
+    Color(ColorF),
+    // A shading pattern, already transformed into scene space.
+    Paint(Paint),
+    // An unsupported pattern; nothing is drawn.
+    None,
+}
+
+// Builds the current path in user space.
+struct PathBuilder {
+    outline: Outline,
+    contour: Contour,
+    current_point: Vector2F,
+    subpath_start: Vector2F,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Creates an interpreter that draws into `scene`. `transform` maps the default user space of
+    /// the page to the scene, and `page_bounds` is the visible area in scene space.
+    pub(crate) fn new(
+        document: &'a Document,
+        scene: Scene,
+        transform: Transform2F,
+        page_bounds: RectF,
+    ) -> Interpreter<'a> {
+        Interpreter {
+            document,
+            scene,
+            result_flags: BuildResultFlags::empty(),
+            resources: None,
+            state: GraphicsState::new(transform),
+            state_stack: vec![],
+            path: PathBuilder::new(),
+            pending_clip: None,
+            text_matrix: Transform2F::default(),
+            text_line_matrix: Transform2F::default(),
+            text_clip: None,
+            pattern_transform: transform,
+            page_bounds,
+            fonts: HashMap::new(),
+            images: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    /// Clips everything drawn afterward to the given rectangle, in scene space.
+    pub(crate) fn clip_to_rect(&mut self, rect: RectF) {
+        let mut clip_path = ClipPath::new(Outline::from_rect(rect));
+        clip_path.set_clip_path(self.state.clip_path);
+        self.state.clip_path = Some(self.scene.push_clip_path(clip_path));
+    }
+
+    /// Runs a content stream with the given resources.
+    pub(crate) fn execute(&mut self, data: &[u8], resources: Option<&'a Dictionary>) {
+        let content = match Content::decode(data) {
+            Ok(content) => content,
+            Err(_) => {
+                self.result_flags
+                    .insert(BuildResultFlags::MALFORMED_CONTENT);
+                return;
+            }
+        };
+
+        let old_resources = mem::replace(&mut self.resources, resources);
+        for operation in &content.operations {
+            self.run_operator(&operation.operator, &operation.operands);
+        }
+        self.resources = old_resources;
+    }
+
+    fn run_operator(&mut self, operator: &str, operands: &[Object]) {
+        let number = |index: usize| operands.get(index).and_then(as_number).unwrap_or(0.0);
+        let point = |index: usize| vec2f(number(index), number(index + 1));
+        match operator {
+            // Graphics state
+            "q" => self.state_stack.push(self.state.clone()),
+            "Q" => {
+                if let Some(state) = self.state_stack.pop() {
+                    self.state = state;
+                }
+            }
+            "cm" => {
+                let transform = matrix(
+                    number(0),
+                    number(1),
+                    number(2),
+                    number(3),
+                    number(4),
+                    number(5),
+                );
+                self.state.transform = self.state.transform * transform;
+            }
+            "w" => self.state.line_width = number(0),
+            "J" => self.state.line_cap = line_cap(number(0) as i32),
+            "j" => self.state.line_join = number(0) as i32,
+            "M" => self.state.miter_limit = number(0),
+            "d" => {
+                if let Some(dash_array) = operands.first().and_then(as_array) {
+                    self.state.dash_array = object::numbers(self.document, dash_array);
+                    self.state.dash_offset = number(1);
+                }
+            }
+            "gs" => {
+                if let Some(name) = operands.first().and_then(as_name) {
+                    self.set_external_state(name);
+                }
+            }
+
+            // Path construction
+            "m" => self.path.move_to(point(0)),
+            "l" => self.path.line_to(point(0)),
+            "c" => self.path.cubic_to(point(0), point(2), point(4)),
+            "v" => {
+                let current_point = self.path.current_point;
+                self.path.cubic_to(current_point, point(0), point(2));
+            }
+            "y" => self.path.cubic_to(point(0), point(2), point(2)),
+            "h" => self.path.close(),
+            "re" => self.path.rect(RectF::new(point(0), point(2))),
+
+            // Path painting
+            "S" => self.paint_path(false, None, true),
+            "s" => self.paint_path(true, None, true),
+            "f" | "F" => self.paint_path(false, Some(FillRule::Winding), false),
+            "f*" => self.paint_path(false, Some(FillRule::EvenOdd), false),
+            "B" => self.paint_path(false, Some(FillRule::Winding), true),
+            "B*" => self.paint_path(false, Some(FillRule::EvenOdd), true),
+            "b" => self.paint_path(true, Some(FillRule::Winding), true),
+            "b*" => self.paint_path(true, Some(FillRule::EvenOdd), true),
+            "n" => self.paint_path(false, None, false),
+            "W" => self.pending_clip = Some(FillRule::Winding),
+            "W*" => self.pending_clip = Some(FillRule::EvenOdd),
+
+            // Color
+            "CS" | "cs" => {
+                let space = operands.first().and_then(|space| {
+                    ColorSpace::from_object(self.document, space, self.resources)
+                });
+                let space = space.unwrap_or(ColorSpace::Gray);
+                let components = space.initial_color();
+                self.set_color(operator == "CS", space, &components, None);
+            }
+            "SC" | "SCN" | "sc" | "scn" => {
+                let is_stroke = operator.starts_with('S');
+                let space = if is_stroke {
+                    &self.state.stroke.space
+                } else {
+                    &self.state.fill.space
+                };
+                let space = (**space).clone();
+                let pattern = operands.last().and_then(as_name);
+                self.set_color(is_stroke, space, &color::components(operands), pattern);
+            }
+            "G" | "g" => {
+                let components = color::components(operands);
+                self.set_color(operator == "G", ColorSpace::Gray, &components, None);
+            }
+            "RG" | "rg" => {
+                let components = color::components(operands);
+                self.set_color(operator == "RG", ColorSpace::RGB, &components, None);
+            }
+            "K" | "k" => {
+                let components = color::components(operands);
+                self.set_color(operator == "K", ColorSpace::CMYK, &components, None);
+            }
+            "sh" => {
+                if let Some(name) = operands.first().and_then(as_name) {
+                    self.draw_shading(name);
+                }
+            }
+
+            // Text
+            "BT" => {
+                self.text_matrix = Transform2F::default();
+                self.text_line_matrix = Transform2F::default();
+            }
+            "ET" => self.end_text(),
+            "Tc" => self.state.char_spacing = number(0),
+            "Tw" => self.state.word_spacing = number(0),
+            "Tz" => self.state.horizontal_scaling = number(0) / 100.0,
+            "TL" => self.state.leading = number(0),
+            "Tf" => {
+                let font = operands.first().and_then(as_name);
+                self.state.font = font.and_then(|name| self.font(name));
+                self.state.font_size = number(1);
+            }
+            "Tr" => self.state.render_mode = number(0) as i32,
+            "Ts" => self.state.rise = number(0),
+            "Td" => self.move_text_line(point(0)),
+            "TD" => {
+                self.state.leading = -number(1);
+                self.move_text_line(point(0));
+            }
+            "Tm" => {
+                self.text_line_matrix = matrix(
+                    number(0),
+                    number(1),
+                    number(2),
+                    number(3),
+                    number(4),
+                    number(5),
+                );
+                self.text_matrix = self.text_line_matrix;
+            }
+            "T*" => self.move_text_line(vec2f(0.0, -self.state.leading)),
+            "Tj" => {
+                if let Some(string) = operands.first().and_then(as_string) {
+                    self.show_text(string);
+                }
+            }
+            "'" => {
+                self.move_text_line(vec2f(0.0, -self.state.leading));
+                if let Some(string) = operands.first().and_then(as_string) {
+                    self.show_text(string);
+                }
+            }
+            "\"" => {
+                self.state.word_spacing = number(0);
+                self.state.char_spacing = number(1);
+                self.move_text_line(vec2f(0.0, -self.state.leading));
+                if let Some(string) = operands.get(2).and_then(as_string) {
+                    self.show_text(string);
+                }
+            }
+            "TJ" => {
+                for element in operands.first().and_then(as_array).unwrap_or(&[]) {
+                    match *element {
+                        Object::String(ref string, _) => self.show_text(string),
+                        ref adjustment => {
+                            // Adjustments are in thousandths of a unit of text space, and move
+                            // the next glyph back.
+                            let adjustment = as_number(adjustment).unwrap_or(0.0);
+                            let offset = -adjustment / 1000.0
+                                * self.state.font_size
+                                * self.state.horizontal_scaling;
+                            self.text_matrix = self.text_matrix
+                                * Transform2F::from_translation(vec2f(offset, 0.0));
+                        }
+                    }
+                }
+            }
+
+            // XObjects
+            "Do" => {
+                if let Some(name) = operands.first().and_then(as_name) {
+                    self.draw_xobject(name);
+                }
+            }
+
+            // Rendering intents, flatness, marked content, compatibility sections, Type 3 glyph
+            // metrics, and inline images are ignored.
+            _ => {}
+        }
+    }
+
+    fn set_external_state(&mut self, name: &[u8]) {
+        let dictionary = match self
+            .resource(b"ExtGState", name)
+            .and_then(|(object, _)| as_dictionary(object))
+        {
+            Some(dictionary) => dictionary,
+            None => return,
+        };
+        for (key, value) in dictionary.iter() {
+            let value = resolve(self.document, value);
+            match &key[..] {
+                b"LW" => self.state.line_width = as_number(value).unwrap_or(1.0),
+                b"LC" => self.state.line_cap = line_cap(as_number(value).unwrap_or(0.0) as i32),
+                b"LJ" => self.state.line_join = as_number(value).unwrap_or(0.0) as i32,
+                b"ML" => self.state.miter_limit = as_number(value).unwrap_or(10.0),
+                b"D" => {
+                    if let Some(&[ref dash_array, ref dash_offset]) = as_array(value) {
+                        if let Some(dash_array) = as_array(resolve(self.document, dash_array)) {
+                            self.state.dash_array = object::numbers(self.document, dash_array);
+                            self.state.dash_offset = as_number(dash_offset).unwrap_or(0.0);
+                        }
+                    }
+                }
+                b"CA" => self.state.stroke_alpha = as_number(value).unwrap_or(1.0),
+                b"ca" => self.state.fill_alpha = as_number(value).unwrap_or(1.0),
+                b"BM" => {
+                    // An array lists blend modes in order of preference.
+                    let names = match as_array(value) {
+                        Some(names) => names.iter().filter_map(as_name).collect(),
+                        None => as_name(value).into_iter().collect::<Vec<_>>(),
+                    };
+                    if let Some(blend_mode) = names.into_iter().filter_map(blend_mode).next() {
+                        self.state.blend_mode = blend_mode;
+                    }
+                }
+                b"Font" => {
+                    if let Some(&[ref font, ref size]) = as_array(value) {
+                        if let Some(font) = as_dictionary(resolve(self.document, font)) {
+                            self.state.font =
+                                Some(Rc::new(PDFFont::from_dictionary(self.document, font)));
+                            self.state.font_size = as_number(size).unwrap_or(0.0);
+                        }
+                    }
+                }
+                b"SMask" => {
+                    if as_name(value) != Some(&b"None"[..]) {
+                        self.result_flags
+                            .insert(BuildResultFlags::UNSUPPORTED_SOFT_MASK);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn set_color(
+        &mut self,
+        is_stroke: bool,
+        space: ColorSpace,
+        components: &[f32],
+        pattern_name: Option<&[u8]>,
+    ) {
+        let source = match (&space, pattern_name) {
+            (&ColorSpace::Pattern(ref base), Some(pattern_name)) => {
+                self.pattern_paint(pattern_name, base.as_ref().map(|base| &**base), components)
+            }
+            (&ColorSpace::Pattern(_), None) => PaintSource::None,
+            _ => PaintSource::Color(space.to_rgb(components)),
+        };
+        let color_state = ColorState {
+            space: Rc::new(space),
+            source,
+        };
+        if is_stroke {
+            self.state.stroke = color_state;
+        } else {
+            self.state.fill = color_state;
+        }
+    }
+
+    fn pattern_paint(
+        &mut self,
+        name: &[u8],
+        base: Option<&ColorSpace>,
+        components: &[f32],
+    ) -> PaintSource {
+        let pattern = match self
+            .resource(b"Pattern", name)
+            .and_then(|(object, _)| as_dictionary(object))
+        {
+            Some(pattern) => pattern,
+            None => return PaintSource::None,
+        };
+        match object::get_number(self.document, pattern, b"PatternType").map(|ty| ty as i32) {
+            Some(2) => {
+                let shading = object::get(self.document, pattern, b"Shading").and_then(|shading| {
+                    Shading::from_object(self.document, shading, self.resources)
+                });
+                match shading {
+                    Some(shading) => {
+                        let transform = self.pattern_transform
+                            * object::get_matrix(self.document, pattern, b"Matrix");
+                        let mut paint = Paint::from_gradient(shading.gradient);
+                        paint.apply_transform(&transform);
+                        PaintSource::Paint(paint)
+                    }
+                    None => {
+                        self.result_flags
+                            .insert(BuildResultFlags::UNSUPPORTED_SHADING);
+                        PaintSource::None
+                    }
+                }
+            }
+            _ => {
+                // Tiling patterns aren't supported. Uncolored ones are drawn in their color.
+                self.result_flags
+                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN);
+                match base {
+                    Some(base) => PaintSource::Color(base.to_rgb(components)),
+                    None => PaintSource::None,
+                }
+            }
+        }
+    }
+
+    fn paint_path(&mut self, close: bool, fill_rule: Option<FillRule>, stroke: bool) {
+        if close {
+            self.path.close();
+        }
+        let outline = Arc::new(self.path.take());
+
+        if let Some(fill_rule) = fill_rule {
+            self.fill_outline(outline.clone(), fill_rule, "Fill");
+        }
+        if stroke {
+            self.stroke_outline(&outline);
+        }
+
+        if let Some(fill_rule) = self.pending_clip.take() {
+            let outline = (*outline).clone().transformed(&self.state.transform);
+            self.push_clip_path(outline, fill_rule);
+        }
+    }
+
+    fn fill_outline(&mut self, outline: Arc<Outline>, fill_rule: FillRule, name: &str) {
+        let fill = self.state.fill.source.clone();
+        let paint_id = match self.push_paint(&fill, self.state.fill_alpha) {
+            Some(paint_id) => paint_id,
+            None => return,
+        };
+        let mut path = DrawPath::new(outline, paint_id);
+        path.set_transform(self.state.transform);
+        path.set_fill_rule(fill_rule);
+        path.set_clip_path(self.state.clip_path);
+        path.set_blend_mode(self.state.blend_mode);
+        path.set_name(name.to_owned());
+        self.scene.push_draw_path(path);
+    }
+
+    fn stroke_outline(&mut self, outline: &Outline) {
+        let stroke = self.state.stroke.source.clone();
+        let paint_id = match self.push_paint(&stroke, self.state.stroke_alpha) {
+            Some(paint_id) => paint_id,
+            None => return,
+        };
+
+        let mut outline = outline.clone();
+        let mut dash_array = self.state.dash_array.clone();
+        if dash_array.iter().sum::<f32>() > 0.0 {
+            // Odd-length arrays repeat, swapping dashes and gaps.
+            if dash_array.len() % 2 == 1 {
+                dash_array.extend(dash_array.clone());
+            }
+            let mut dash = OutlineDash::new(&outline, &dash_array, self.state.dash_offset);
+            dash.dash();
+            outline = dash.into_outline();
+        }
+
+        // Strokes are built in user space, so that they scale with the transform.
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, self.stroke_style());
+        stroke_to_fill.offset();
+        let outline = stroke_to_fill.into_outline();
+
+        let mut path = DrawPath::new(outline, paint_id);
+        path.set_transform(self.state.transform);
+        path.set_clip_path(self.state.clip_path);
+        path.set_blend_mode(self.state.blend_mode);
+        path.set_name("Stroke".to_owned());
+        self.scene.push_draw_path(path);
+    }
+
+    fn stroke_style(&self) -> StrokeStyle {
+        // A width of zero asks for the thinnest line the device can draw.
+        let mut line_width = self.state.line_width;
+        if line_width <= 0.0 {
+            let scale = self.state.transform.extract_scale();
+            line_width = 1.0 / f32::max(scale.x().max(scale.y()), f32::EPSILON);
+        }
+        let line_join = match self.state.line_join {
+            1 => LineJoin::Round,
+            2 => LineJoin::Bevel,
+            _ => LineJoin::Miter(self.state.miter_limit),
+        };
+        StrokeStyle {
+            line_width,
+            line_cap: self.state.line_cap,
+            line_join,
+        }
+    }
+
+    fn push_paint(&mut self, source: &PaintSource, alpha: f32) -> Option<PaintId> {
+        let mut paint = match *source {
+            PaintSource::Color(color) => Paint::from_color(color.to_u8()),
+            PaintSource::Paint(ref paint) => paint.clone(),
+            PaintSource::None => return None,
+        };
+        if alpha < 1.0 {
+            let mut base_color = paint.base_color().to_f32();
+            base_color.set_a(base_color.a() * alpha.max(0.0));
+            paint.set_base_color(base_color.to_u8());
+        }
+        Some(self.scene.push_paint(&paint))
+    }
+
+    // Intersects the clip with an outline in scene space.
+    fn push_clip_path(&mut self, outline: Outline, fill_rule: FillRule) {
+        let mut clip_path = ClipPath::new(outline);
+        clip_path.set_clip_path(self.state.clip_path);
+        clip_path.set_fill_rule(fill_rule);
+        self.state.clip_path = Some(self.scene.push_clip_path(clip_path));
+    }
+
+    fn draw_shading(&mut self, name: &[u8]) {
+        let shading = self
+            .resource(b"Shading", name)
+            .and_then(|(object, _)| Shading::from_object(self.document, object, self.resources));
+        let shading = match shading {
+            Some(shading) => shading,
+            None => {
+                self.result_flags
+                    .insert(BuildResultFlags::UNSUPPORTED_SHADING);
+                return;
+            }
+        };
+
+        let mut paint = Paint::from_gradient(shading.gradient);
+        paint.apply_transform(&self.state.transform);
+        let paint_id = match self.push_paint(&PaintSource::Paint(paint), self.state.fill_alpha) {
+            Some(paint_id) => paint_id,
+            None => return,
+        };
+
+        // The shading fills the clip, or its bounding box if it has one.
+        let (outline, transform) = match shading.bbox {
+            Some(bbox) => (Outline::from_rect(bbox), self.state.transform),
+            None => (Outline::from_rect(self.page_bounds), Transform2F::default()),
+        };
+        let mut path = DrawPath::new(outline, paint_id);
+        path.set_transform(transform);
+        path.set_clip_path(self.state.clip_path);
+        path.set_blend_mode(self.state.blend_mode);
+        path.set_name("Shading".to_owned());
+        self.scene.push_draw_path(path);
+    }
+
+    fn font(&mut self, name: &[u8]) -> Option<Rc<PDFFont<'a>>> {
+        let (object, id) = self.resource(b"Font", name)?;
+        if let Some(font) = id.and_then(|id| self.fonts.get(&id)) {
+            return Some(font.clone());
+        }
+        let font = Rc::new(PDFFont::from_dictionary(
+            self.document,
+            as_dictionary(object)?,
+        ));
+        if let Some(id) = id {
+            self.fonts.insert(id, font.clone());
+        }
+        Some(font)
+    }
+
+    fn move_text_line(&mut self, offset: Vector2F) {
+        self.text_line_matrix = self.text_line_matrix * Transform2F::from_translation(offset);
+        self.text_matrix = self.text_line_matrix;
+    }
+
+    fn show_text(&mut self, string: &[u8]) {
+        let font = match self.state.font.clone() {
+            Some(font) => font,
+            None => return,
+        };
+        if font.is_missing() {
+            self.result_flags.insert(BuildResultFlags::UNSUPPORTED_FONT);
+        }
+
+        let state = &self.state;
+        let text_space = Transform2F::row_major(
+            state.font_size * state.horizontal_scaling,
+            0.0,
+            0.0,
+            0.0,
+            state.font_size,
+            state.rise,
+        );
+        let codes = font.codes(string);
+        let single_byte = codes.len() == string.len();
+
+        let mut glyphs = Outline::new();
+        for code in codes {
+            let glyph_transform = self.text_matrix * text_space;
+            if font.is_type3() {
+                self.draw_type3_glyph(&font, code, glyph_transform);
+            } else if let Some(outline) = font.outline(code) {
+                for contour in (*outline)
+                    .clone()
+                    .transformed(&glyph_transform)
+                    .into_contours()
+                {
+                    glyphs.push_contour(contour);
+                }
+            }
+
+            // Word spacing only applies to the single-byte space character.
+            let mut advance = font.advance(code) * self.state.font_size + self.state.char_spacing;
+            if single_byte && code == 32 {
+                advance += self.state.word_spacing;
+            }
+            let offset = vec2f(advance * self.state.horizontal_scaling, 0.0);
+            self.text_matrix = self.text_matrix * Transform2F::from_translation(offset);
+        }
+
+        if !glyphs.is_empty() {
+            self.draw_text(glyphs);
+        }
+    }
+
+    // Draws glyph outlines in user space according to the text rendering mode.
+    fn draw_text(&mut self, glyphs: Outline) {
+        let render_mode = self.state.render_mode;
+        let glyphs = Arc::new(glyphs);
+        if let 0 | 2 | 4 | 6 = render_mode {
+            self.fill_outline(glyphs.clone(), FillRule::Winding, "Text");
+        }
+        if let 1 | 2 | 5 | 6 = render_mode {
+            self.stroke_outline(&glyphs);
+        }
+        if render_mode >= 4 {
+            let text_clip = self.text_clip.get_or_insert_with(Outline::new);
+            for contour in (*glyphs)
+                .clone()
+                .transformed(&self.state.transform)
+                .into_contours()
+            {
+                text_clip.push_contour(contour);
+            }
+        }
+    }
+
+    fn end_text(&mut self) {
+        // Text drawn with a clipping mode is added to the clip at the end of the text object,
+        // even if nothing was drawn.
+        if let Some(text_clip) = self.text_clip.take() {
+            self.push_clip_path(text_clip, FillRule::Winding);
+        } else if self.state.render_mode >= 4 {
+            self.push_clip_path(Outline::new(), FillRule::Winding);
+        }
+    }
+
+    fn draw_type3_glyph(&mut self, font: &PDFFont<'a>, code: u32, glyph_transform: Transform2F) {
+        let glyph = match font.type3_glyph(self.document, code) {
+            Some(glyph) => glyph,
+            None => return,
+        };
+        let data = match object::stream_data(glyph.content) {
+            Some(data) => data,
+            None => return,
+        };
+
+        let text_matrices = (self.text_matrix, self.text_line_matrix);
+        let transform = self.state.transform * glyph_transform * glyph.font_matrix;
+        let resources = glyph.resources.or(self.resources);
+        self.run_nested(&data, resources, transform, |_| {});
+        self.text_matrix = text_matrices.0;
+        self.text_line_matrix = text_matrices.1;
+    }
+
+    fn draw_xobject(&mut self, name: &[u8]) {
+        let (object, id) = match self.resource(b"XObject", name) {
+            Some(xobject) => xobject,
+            None => return,
+        };
+        let stream = match as_stream(object) {
+            Some(stream) => stream,
+            None => return,
+        };
+        match object::get_name(self.document, &stream.dict, b"Subtype") {
+            Some(b"Image") => self.draw_image(stream, id),
+            Some(b"Form") => self.draw_form(stream),
+            _ => {}
+        }
+    }
+
+    fn draw_image(&mut self, stream: &Stream, id: Option<ObjectId>) {
+        // Stencil masks take the fill color, so they aren't cached.
+        let is_stencil =
+            object::get_bool(self.document, &stream.dict, b"ImageMask").unwrap_or(false);
+        let fill_color = match self.state.fill.source {
+            PaintSource::Color(color) => color.to_u8(),
+            _ => ColorU::black(),
+        };
+        let cached = match id {
+            Some(id) if !is_stencil => self.images.get(&id).cloned(),
+            _ => None,
+        };
+        let image = match cached {
+            Some(image) => image,
+            None => {
+                let image = image::decode_image(self.document, stream, self.resources, fill_color);
+                if let Some(id) = id.filter(|_| !is_stencil) {
+                    self.images.insert(id, image.clone());
+                }
+                image
+            }
+        };
+        let image = match image {
+            Some(image) => image,
+            None => {
+                self.result_flags
+                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE);
+                return;
+            }
+        };
+
+        // Images fill the unit square of user space, with their first row at the top.
+        let size = image.size().to_f32();
+        let image_transform =
+            Transform2F::row_major(1.0 / size.x(), 0.0, 0.0, 0.0, -1.0 / size.y(), 1.0);
+        let mut pattern = Pattern::from_image(image);
+        pattern.apply_transform(self.state.transform * image_transform);
+        let paint = PaintSource::Paint(Paint::from_pattern(pattern));
+        let paint_id = match self.push_paint(&paint, self.state.fill_alpha) {
+            Some(paint_id) => paint_id,
+            None => return,
+        };
+
+        let unit_square = RectF::new(Vector2F::zero(), Vector2F::splat(1.0));
+        let mut path = DrawPath::new(Outline::from_rect(unit_square), paint_id);
+        path.set_transform(self.state.transform);
+        path.set_clip_path(self.state.clip_path);
+        path.set_blend_mode(self.state.blend_mode);
+        path.set_name("Image".to_owned());
+        self.scene.push_draw_path(path);
+    }
+
+    fn draw_form(&mut self, stream: &'a Stream) {
+        let data = match object::stream_data(stream) {
+            Some(data) => data,
+            None => return,
+        };
+        let dictionary = &stream.dict;
+        let transform =
+            self.state.transform * object::get_matrix(self.document, dictionary, b"Matrix");
+        let bbox = object::get_rect(self.document, dictionary, b"BBox");
+        let resources =
+            object::get_dictionary(self.document, dictionary, b"Resources").or(self.resources);
+
+        // The opacity and blend mode of a transparency group apply to the group as a whole.
+        let is_group = object::get(self.document, dictionary, b"Group").is_some()
+            && (self.state.fill_alpha < 1.0 || self.state.blend_mode != BlendMode::SrcOver);
+        if is_group {
+            let mut group = Group::new();
+            group.set_opacity(self.state.fill_alpha);
+            group.set_blend_mode(self.state.blend_mode);
+            group.set_name("Form".to_owned());
+            self.scene.push_group(group);
+        }
+
+        self.run_nested(&data, resources, transform, |interpreter| {
+            if let Some(bbox) = bbox {
+                let outline = Outline::from_rect(bbox).transformed(&transform);
+                interpreter.push_clip_path(outline, FillRule::Winding);
+            }
+            if is_group {
+                interpreter.state.fill_alpha = 1.0;
+                interpreter.state.stroke_alpha = 1.0;
+                interpreter.state.blend_mode = BlendMode::SrcOver;
+            }
+        });
+
+        if is_group {
+            self.scene.pop_group();
+        }
+    }
+
+    // Runs a nested content stream with its own graphics state stack, restoring the state after.
+    fn run_nested<F>(
+        &mut self,
+        data: &[u8],
+        resources: Option<&'a Dictionary>,
+        transform: Transform2F,
+        prepare: F,
+    ) where
+        F: FnOnce(&mut Interpreter<'a>),
+    {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return;
+        }
+
+        let old_state = self.state.clone();
+        let old_state_stack = mem::replace(&mut self.state_stack, vec![]);
+        let old_path = mem::replace(&mut self.path, PathBuilder::new());
+        let old_pattern_transform = mem::replace(&mut self.pattern_transform, transform);
+        self.state.transform = transform;
+        prepare(self);
+
+        self.depth += 1;
+        self.execute(data, resources);
+        self.depth -= 1;
+
+        self.state = old_state;
+        self.state_stack = old_state_stack;
+        self.path = old_path;
+        self.pattern_transform = old_pattern_transform;
+    }
+
+    // Looks up a named resource in a category such as `Font`, returning it and its object ID.
+    fn resource(&self, category: &[u8], name: &[u8]) -> Option<(&'a Object, Option<ObjectId>)> {
+        let dictionary = object::get_dictionary(self.document, self.resources?, category)?;
+        let object = object::get(self.document, dictionary, name)?;
+        Some((object, object::get_reference(dictionary, name)))
+    }
+}
+
+impl<'a> GraphicsState<'a> {
+    fn new(transform: Transform2F) -> GraphicsState<'a> {
+        let black = ColorState {
+            space: Rc::new(ColorSpace::Gray),
+            source: PaintSource::Color(ColorF::black()),
+        };
+        GraphicsState {
+            transform,
+            clip_path: None,
+            fill: black.clone(),
+            stroke: black,
+            fill_alpha: 1.0,
+            stroke_alpha: 1.0,
+            blend_mode: BlendMode::SrcOver,
+            line_width: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: 0,
+            miter_limit: 10.0,
+            dash_array: vec![],
+            dash_offset: 0.0,
+            font: None,
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            render_mode: 0,
+        }
+    }
+}
+
+impl PathBuilder {
+    fn new() -> PathBuilder {
+        PathBuilder {
+            outline: Outline::new(),
+            contour: Contour::new(),
+            current_point: Vector2F::zero(),
+            subpath_start: Vector2F::zero(),
+        }
+    }
+
+    fn move_to(&mut self, to: Vector2F) {
+        self.flush_contour();
+        self.contour.push_endpoint(to);
+        self.current_point = to;
+        self.subpath_start = to;
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.start_contour();
+        self.contour.push_endpoint(to);
+        self.current_point = to;
+    }
+
+    fn cubic_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        self.start_contour();
+        self.contour.push_cubic(ctrl0, ctrl1, to);
+        self.current_point = to;
+    }
+
+    fn close(&mut self) {
+        if !self.contour.is_empty() {
+            self.contour.close();
+            self.flush_contour();
+        }
+        self.current_point = self.subpath_start;
+    }
+
+    fn rect(&mut self, rect: RectF) {
+        self.flush_contour();
+        self.outline.push_contour(Contour::from_rect(rect));
+        self.current_point = rect.origin();
+        self.subpath_start = rect.origin();
+    }
+
+    // After a subpath is closed, drawing continues from its start.
+    fn start_contour(&mut self) {
+        if self.contour.is_empty() {
+            self.contour.push_endpoint(self.current_point);
+        }
+    }
+
+    fn flush_contour(&mut self) {
+        if !self.contour.is_empty() {
+            self.outline
+                .push_contour(mem::replace(&mut self.contour, Contour::new()));
+        }
+    }
+
+    fn take(&mut self) -> Outline {
+        self.flush_contour();
+        mem::replace(&mut self.outline, Outline::new())
+    }
+}
+
+fn line_cap(value: i32) -> LineCap {
+    match value {
+        1 => LineCap::Round,
+        2 => LineCap::Square,
+        _ => LineCap::Butt,
+    }
+}
+
+fn blend_mode(name: &[u8]) -> Option<BlendMode> {
+    match name {
+        b"Normal" | b"Compatible" => Some(BlendMode::SrcOver),
+        b"Multiply" => Some(BlendMode::Multiply),
+        b"Screen" => Some(BlendMode::Screen),
+        b"Overlay" => Some(BlendMode::Overlay),
+        b"Darken" => Some(BlendMode::Darken),
+        b"Lighten" => Some(BlendMode::Lighten),
+        b"ColorDodge" => Some(BlendMode::ColorDodge),
+        b"ColorBurn" => Some(BlendMode::ColorBurn),
+        b"HardLight" => Some(BlendMode::HardLight),
+        b"SoftLight" => Some(BlendMode::SoftLight),
+        b"Difference" => Some(BlendMode::Difference),
+        b"Exclusion" => Some(BlendMode::Exclusion),
+        b"Hue" => Some(BlendMode::Hue),
+        b"Saturation" => Some(BlendMode::Saturation),
+        b"Color" => Some(BlendMode::Color),
+        b"Luminosity" => Some(BlendMode::Luminosity),
+        _ => None,
+    }
+}
@@ -0,0 +1,255 @@
+// pathfinder/pdf/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders PDF pages into Pathfinder scenes.
+//!
+//! Page content streams are interpreted directly: paths, strokes, clips, text in embedded and
+//! system fonts, images, axial and radial shadings, transparency, and blend modes are supported.
+//! Anything else is skipped and reported in the `BuildResultFlags` of the result.
+
+#[macro_use]
+extern crate bitflags;
+
+use crate::interpreter::Interpreter;
+use crate::object::{as_dictionary, resolve};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::scene::Scene;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+
+mod color;
+mod font;
+mod function;
+mod image;
+mod interpreter;
+mod object;
+mod shading;
+
+// Pages in the page tree inherit attributes from their ancestors, up to this depth.
+const MAX_PAGE_TREE_DEPTH: usize = 32;
+
+// US Letter, which PDF readers assume when a page has no media box.
+const DEFAULT_PAGE_WIDTH: f32 = 612.0;
+const DEFAULT_PAGE_HEIGHT: f32 = 792.0;
+
+/// A parsed PDF document.
+pub struct PDFDocument {
+    document: Document,
+    pages: Vec<ObjectId>,
+}
+
+/// A page rendered into a scene.
+pub struct PDFScene {
+    pub scene: Scene,
+    pub result_flags: BuildResultFlags,
+}
+
+bitflags! {
+    // NB: If you change this, make sure to update the `Display`
+    // implementation as well.
+    pub struct BuildResultFlags: u16 {
+        const UNSUPPORTED_IMAGE     = 0x0001;
+        const UNSUPPORTED_SHADING   = 0x0002;
+        const UNSUPPORTED_PATTERN   = 0x0004;
+        const UNSUPPORTED_FONT      = 0x0008;
+        const UNSUPPORTED_SOFT_MASK = 0x0010;
+        const MALFORMED_CONTENT     = 0x0020;
+    }
+}
+
+/// Errors that can occur when loading a document or rendering a page.
+#[derive(Debug)]
+pub enum PDFError {
+    /// The document couldn't be parsed.
+    Parse(lopdf::Error),
+    /// The document has no page with the given index.
+    PageNotFound(usize),
+}
+
+impl PDFDocument {
+    /// Parses a PDF document from memory.
+    pub fn from_data(data: &[u8]) -> Result<PDFDocument, PDFError> {
+        Ok(PDFDocument::from_document(Document::load_mem(data)?))
+    }
+
+    /// Wraps a document that has already been loaded with `lopdf`.
+    pub fn from_document(document: Document) -> PDFDocument {
+        let pages = document.get_pages().into_iter().map(|(_, id)| id).collect();
+        PDFDocument { document, pages }
+    }
+
+    /// Returns the underlying `lopdf` document.
+    #[inline]
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns the size of a page in points, after rotation.
+    pub fn page_size(&self, index: usize) -> Result<Vector2F, PDFError> {
+        let page = self.page(index)?;
+        Ok(self.page_geometry(page).1.size())
+    }
+
+    /// Renders a page into a new scene whose view box is the page, in points.
+    #[inline]
+    pub fn render_page(&self, index: usize) -> Result<PDFScene, PDFError> {
+        self.render_page_into_scene(index, Scene::new())
+    }
+
+    /// Renders a page into an existing scene, setting its view box to the page.
+    pub fn render_page_into_scene(&self, index: usize, scene: Scene) -> Result<PDFScene, PDFError> {
+        let page_id = self.page_id(index)?;
+        let page = self.document.get_dictionary(page_id)?;
+        let (transform, view_box) = self.page_geometry(page);
+        let content = self.document.get_page_content(page_id)?;
+        let resources = self.inherited(page, b"Resources").and_then(as_dictionary);
+
+        let mut interpreter = Interpreter::new(&self.document, scene, transform, view_box);
+        interpreter.scene.set_view_box(view_box);
+        interpreter.clip_to_rect(view_box);
+        interpreter.execute(&content, resources);
+
+        Ok(PDFScene {
+            scene: interpreter.scene,
+            result_flags: interpreter.result_flags,
+        })
+    }
+
+    fn page_id(&self, index: usize) -> Result<ObjectId, PDFError> {
+        self.pages
+            .get(index)
+            .cloned()
+            .ok_or(PDFError::PageNotFound(index))
+    }
+
+    fn page(&self, index: usize) -> Result<&Dictionary, PDFError> {
+        Ok(self.document.get_dictionary(self.page_id(index)?)?)
+    }
+
+    // Returns the transform from the default user space of a page to the scene, which has the
+    // origin at the top left of the crop box and Y pointing down, and the rotated page bounds.
+    fn page_geometry(&self, page: &Dictionary) -> (Transform2F, RectF) {
+        let bounds = self
+            .inherited(page, b"CropBox")
+            .and_then(|crop_box| object::rect(&self.document, crop_box))
+            .or_else(|| {
+                self.inherited(page, b"MediaBox")
+                    .and_then(|media_box| object::rect(&self.document, media_box))
+            })
+            .unwrap_or_else(|| {
+                RectF::new(
+                    Vector2F::zero(),
+                    vec2f(DEFAULT_PAGE_WIDTH, DEFAULT_PAGE_HEIGHT),
+                )
+            });
+        let rotation = self
+            .inherited(page, b"Rotate")
+            .and_then(object::as_number)
+            .map(|degrees| (degrees as i32).rem_euclid(360))
+            .unwrap_or(0);
+
+        let (x0, y0, x1, y1) = (
+            bounds.min_x(),
+            bounds.min_y(),
+            bounds.max_x(),
+            bounds.max_y(),
+        );
+        // Pages are rotated clockwise.
+        let (transform, size) = match rotation {
+            90 => (
+                Transform2F::row_major(0.0, 1.0, -y0, 1.0, 0.0, -x0),
+                vec2f(bounds.height(), bounds.width()),
+            ),
+            180 => (
+                Transform2F::row_major(-1.0, 0.0, x1, 0.0, 1.0, -y0),
+                bounds.size(),
+            ),
+            270 => (
+                Transform2F::row_major(0.0, -1.0, y1, -1.0, 0.0, x1),
+                vec2f(bounds.height(), bounds.width()),
+            ),
+            _ => (
+                Transform2F::row_major(1.0, 0.0, -x0, 0.0, -1.0, y1),
+                bounds.size(),
+            ),
+        };
+        (transform, RectF::new(Vector2F::zero(), size))
+    }
+
+    // Looks up a page attribute, falling back to the page's ancestors.
+    fn inherited<'a>(&'a self, mut node: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+        for _ in 0..MAX_PAGE_TREE_DEPTH {
+            if let Some(value) = object::get(&self.document, node, key) {
+                return Some(value);
+            }
+            let parent = node.get(b"Parent").ok()?;
+            node = as_dictionary(resolve(&self.document, parent))?;
+        }
+        None
+    }
+}
+
+impl Display for BuildResultFlags {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let mut first = true;
+        for (bit, name) in NAMES.iter().enumerate() {
+            if (self.bits() >> bit) & 1 == 0 {
+                continue;
+            }
+            if !first {
+                formatter.write_str(", ")?;
+            } else {
+                first = false;
+            }
+            formatter.write_str(name)?;
+        }
+
+        return Ok(());
+
+        // Must match the order in `BuildResultFlags`.
+        static NAMES: &'static [&'static str] = &[
+            "image",
+            "shading",
+            "pattern",
+            "font",
+            "soft mask",
+            "malformed content",
+        ];
+    }
+}
+
+impl Display for PDFError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            PDFError::Parse(ref error) => write!(formatter, "PDF parse error: {}", error),
+            PDFError::PageNotFound(index) => write!(formatter, "page {} not found", index),
+        }
+    }
+}
+
+impl Error for PDFError {}
+
+impl From<lopdf::Error> for PDFError {
+    fn from(error: lopdf::Error) -> PDFError {
+        PDFError::Parse(error)
+    }
+}
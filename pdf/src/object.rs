@@ -0,0 +1,184 @@
+// pathfinder/pdf/src/object.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for reading loosely-typed PDF objects.
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::vec2f;
+
+// Chains of indirect references are legal but rare; this guards against cycles.
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+static NULL: Object = Object::Null;
+
+/// Follows indirect references until a direct object is reached. Dangling references resolve to
+/// `null`, as the spec requires.
+pub(crate) fn resolve<'a>(document: &'a Document, mut object: &'a Object) -> &'a Object {
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        match *object {
+            Object::Reference(id) => match document.get_object(id) {
+                Ok(target) => object = target,
+                Err(_) => return &NULL,
+            },
+            _ => return object,
+        }
+    }
+    &NULL
+}
+
+/// Looks up a dictionary entry and resolves it.
+pub(crate) fn get<'a>(
+    document: &'a Document,
+    dictionary: &'a Dictionary,
+    key: &[u8],
+) -> Option<&'a Object> {
+    match dictionary.get(key) {
+        Ok(object) => match *resolve(document, object) {
+            Object::Null => None,
+            ref object => Some(object),
+        },
+        Err(_) => None,
+    }
+}
+
+/// Returns the ID of the object that a dictionary entry refers to, if it's indirect.
+pub(crate) fn get_reference(dictionary: &Dictionary, key: &[u8]) -> Option<ObjectId> {
+    match dictionary.get(key) {
+        Ok(&Object::Reference(id)) => Some(id),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_number(object: &Object) -> Option<f32> {
+    match *object {
+        Object::Integer(value) => Some(value as f32),
+        Object::Real(value) => Some(value as f32),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_name(object: &Object) -> Option<&[u8]> {
+    match *object {
+        Object::Name(ref name) => Some(name),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_string(object: &Object) -> Option<&[u8]> {
+    match *object {
+        Object::String(ref string, _) => Some(string),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_array(object: &Object) -> Option<&[Object]> {
+    match *object {
+        Object::Array(ref array) => Some(array),
+        _ => None,
+    }
+}
+
+/// Returns the dictionary of a dictionary or stream object.
+pub(crate) fn as_dictionary(object: &Object) -> Option<&Dictionary> {
+    match *object {
+        Object::Dictionary(ref dictionary) => Some(dictionary),
+        Object::Stream(ref stream) => Some(&stream.dict),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_stream(object: &Object) -> Option<&Stream> {
+    match *object {
+        Object::Stream(ref stream) => Some(stream),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_number(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Option<f32> {
+    get(document, dictionary, key).and_then(as_number)
+}
+
+pub(crate) fn get_name<'a>(
+    document: &'a Document,
+    dictionary: &'a Dictionary,
+    key: &[u8],
+) -> Option<&'a [u8]> {
+    get(document, dictionary, key).and_then(as_name)
+}
+
+pub(crate) fn get_dictionary<'a>(
+    document: &'a Document,
+    dictionary: &'a Dictionary,
+    key: &[u8],
+) -> Option<&'a Dictionary> {
+    get(document, dictionary, key).and_then(as_dictionary)
+}
+
+pub(crate) fn get_bool(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Option<bool> {
+    match get(document, dictionary, key) {
+        Some(&Object::Boolean(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Reads an array of numbers, resolving each element. Non-numeric elements are skipped.
+pub(crate) fn get_numbers(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Vec<f32> {
+    match get(document, dictionary, key).and_then(as_array) {
+        Some(array) => numbers(document, array),
+        None => vec![],
+    }
+}
+
+pub(crate) fn numbers(document: &Document, array: &[Object]) -> Vec<f32> {
+    array
+        .iter()
+        .filter_map(|object| as_number(resolve(document, object)))
+        .collect()
+}
+
+pub(crate) fn get_rect(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Option<RectF> {
+    rect(document, get(document, dictionary, key)?)
+}
+
+/// Reads a rectangle given as `[llx lly urx ury]`, in either corner order.
+pub(crate) fn rect(document: &Document, object: &Object) -> Option<RectF> {
+    match numbers(document, as_array(object)?)[..] {
+        [x0, y0, x1, y1] => Some(RectF::from_points(
+            vec2f(x0.min(x1), y0.min(y1)),
+            vec2f(x0.max(x1), y0.max(y1)),
+        )),
+        _ => None,
+    }
+}
+
+/// Reads a matrix given as `[a b c d e f]`, defaulting to the identity.
+pub(crate) fn get_matrix(document: &Document, dictionary: &Dictionary, key: &[u8]) -> Transform2F {
+    match get_numbers(document, dictionary, key)[..] {
+        [a, b, c, d, e, f] => matrix(a, b, c, d, e, f),
+        _ => Transform2F::default(),
+    }
+}
+
+/// Converts a PDF matrix, which maps `(x, y)` to `(ax + cy + e, bx + dy + f)`.
+pub(crate) fn matrix(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Transform2F {
+    Transform2F::row_major(a, c, e, b, d, f)
+}
+
+/// Returns the contents of a stream with its filters undone.
+///
+/// Image-specific filters such as `DCTDecode` aren't handled here; see `image.rs`.
+pub(crate) fn stream_data(stream: &Stream) -> Option<Vec<u8>> {
+    match stream.dict.get(b"Filter") {
+        Ok(_) => stream.decompressed_content().ok(),
+        Err(_) => Some(stream.content.clone()),
+    }
+}
@@ -0,0 +1,80 @@
+// pathfinder/pdf/src/shading.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Converts axial and radial shadings to gradients.
+
+use crate::color::ColorSpace;
+use crate::function::Function;
+use crate::object::{self, as_dictionary, resolve};
+use lopdf::{Dictionary, Document, Object};
+use pathfinder_content::gradient::Gradient;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::vec2f;
+use pathfinder_simd::default::F32x2;
+
+// The number of color stops that shading functions are sampled at.
+const STOP_COUNT: usize = 64;
+
+/// A shading that can be drawn as a gradient, in its own coordinate space.
+#[derive(Clone, Debug)]
+pub(crate) struct Shading {
+    pub(crate) gradient: Gradient,
+    pub(crate) bbox: Option<RectF>,
+}
+
+impl Shading {
+    /// Parses a shading dictionary or stream. Returns `None` for malformed shadings and for types
+    /// other than axial (2) and radial (3), such as function-based shadings and meshes.
+    pub(crate) fn from_object(
+        document: &Document,
+        object: &Object,
+        resources: Option<&Dictionary>,
+    ) -> Option<Shading> {
+        let dictionary = as_dictionary(resolve(document, object))?;
+        let color_space = ColorSpace::from_object(
+            document,
+            object::get(document, dictionary, b"ColorSpace")?,
+            resources,
+        )?;
+        let function =
+            Function::from_object(document, object::get(document, dictionary, b"Function")?)?;
+        let domain = match object::get_numbers(document, dictionary, b"Domain")[..] {
+            [t0, t1] => (t0, t1),
+            _ => (0.0, 1.0),
+        };
+
+        let coords = object::get_numbers(document, dictionary, b"Coords");
+        let mut gradient = match (
+            object::get_number(document, dictionary, b"ShadingType")? as i32,
+            &coords[..],
+        ) {
+            (2, &[x0, y0, x1, y1]) => Gradient::linear_from_points(vec2f(x0, y0), vec2f(x1, y1)),
+            (3, &[x0, y0, r0, x1, y1, r1]) => Gradient::radial(
+                LineSegment2F::new(vec2f(x0, y0), vec2f(x1, y1)),
+                F32x2::new(r0, r1),
+            ),
+            _ => return None,
+        };
+
+        // Gradients are always extended past their ends, whatever `Extend` says.
+        for index in 0..STOP_COUNT {
+            let offset = index as f32 / (STOP_COUNT - 1) as f32;
+            let t = domain.0 + (domain.1 - domain.0) * offset;
+            let color = color_space.to_rgb(&function.evaluate(&[t]));
+            gradient.add_color_stop(color.to_u8(), offset);
+        }
+
+        Some(Shading {
+            gradient,
+            bbox: object::get_rect(document, dictionary, b"BBox"),
+        })
+    }
+}
@@ -0,0 +1,636 @@
+// pathfinder/python/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Python bindings to the Pathfinder canvas API, SVG loading, and export.
+
+use css_color_parser::Color;
+use pathfinder_canvas::{ArcDirection, Canvas, CanvasFontContext, CanvasRenderingContext2D};
+use pathfinder_canvas::{FillRule, FillStyle, LineCap, LineJoin, Path2D, TextAlign, TextBaseline};
+use pathfinder_color::ColorU;
+use pathfinder_content::gradient::Gradient;
+use pathfinder_export::{Export, FileFormat, PageOptions, PdfConformance};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::vec2f;
+use pathfinder_renderer::scene::Scene;
+use pathfinder_simd::default::F32x2;
+use pathfinder_svg::SVGScene;
+use pyo3::exceptions::{PyIOError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use usvg::Options;
+
+/// A 2D drawing context that works like the one of an HTML canvas.
+#[pyclass(name = "CanvasRenderingContext2D", unsendable)]
+pub struct PyCanvasRenderingContext2D {
+    context: CanvasRenderingContext2D,
+    default_path: Path2D,
+}
+
+#[pymethods]
+impl PyCanvasRenderingContext2D {
+    /// Creates a blank canvas of the given size, drawing text with the system fonts.
+    #[new]
+    fn new(width: f32, height: f32) -> Self {
+        let font_context = CanvasFontContext::from_system_source();
+        let context = Canvas::new(vec2f(width, height)).get_context_2d(font_context);
+        PyCanvasRenderingContext2D {
+            context,
+            default_path: Path2D::new(),
+        }
+    }
+
+    #[getter]
+    fn width(&self) -> i32 {
+        self.context.canvas().size().x()
+    }
+
+    #[getter]
+    fn height(&self) -> i32 {
+        self.context.canvas().size().y()
+    }
+
+    /// Erases everything drawn so far, keeping the drawing state.
+    fn clear(&mut self) {
+        self.context.clear();
+    }
+
+    /// Returns a copy of everything drawn so far.
+    fn to_scene(&self) -> PyScene {
+        PyScene {
+            scene: self.context.canvas().scene().clone(),
+        }
+    }
+
+    /// Draws a scene, such as a loaded SVG, with its origin at the given point.
+    #[args(x = "0.0", y = "0.0")]
+    fn draw_scene(&mut self, scene: &PyScene, x: f32, y: f32) {
+        let transform = self.context.transform() * Transform2F::from_translation(vec2f(x, y));
+        let canvas = self.context.canvas_mut();
+        canvas
+            .scene_mut()
+            .append_scene_transformed(&scene.scene, transform);
+    }
+
+    /// Writes everything drawn so far to a file, in the format given by its extension: `.png`,
+    /// `.pdf`, `.ps`, or `.svg`. PNG images are rendered on the CPU at `scale` pixels per unit.
+    #[args(scale = "1.0")]
+    fn write_file(&self, path: &str, scale: f32) -> PyResult<()> {
+        write_scene(self.context.canvas().scene(), path, scale)
+    }
+
+    /// Returns everything drawn so far as the bytes of a `png`, `pdf`, `ps`, or `svg` file.
+    #[args(scale = "1.0")]
+    fn export<'p>(&self, py: Python<'p>, format: &str, scale: f32) -> PyResult<&'p PyBytes> {
+        let bytes = export_scene(self.context.canvas().scene(), format, scale)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    // Drawing rectangles
+
+    fn clear_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.context
+            .clear_rect(RectF::new(vec2f(x, y), vec2f(width, height)));
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.context
+            .fill_rect(RectF::new(vec2f(x, y), vec2f(width, height)));
+    }
+
+    fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.context
+            .stroke_rect(RectF::new(vec2f(x, y), vec2f(width, height)));
+    }
+
+    // Drawing text
+
+    fn fill_text(&mut self, text: &str, x: f32, y: f32) {
+        self.context.fill_text(text, vec2f(x, y));
+    }
+
+    fn stroke_text(&mut self, text: &str, x: f32, y: f32) {
+        self.context.stroke_text(text, vec2f(x, y));
+    }
+
+    fn measure_text(&self, text: &str) -> PyTextMetrics {
+        let metrics = self.context.measure_text(text);
+        PyTextMetrics {
+            width: metrics.width(),
+            actual_bounding_box_left: metrics.actual_bounding_box_left(),
+            actual_bounding_box_right: metrics.actual_bounding_box_right(),
+            actual_bounding_box_ascent: metrics.actual_bounding_box_ascent(),
+            actual_bounding_box_descent: metrics.actual_bounding_box_descent(),
+            font_bounding_box_ascent: metrics.font_bounding_box_ascent(),
+            font_bounding_box_descent: metrics.font_bounding_box_descent(),
+        }
+    }
+
+    // Line styles
+
+    #[getter]
+    fn line_width(&self) -> f32 {
+        self.context.line_width()
+    }
+
+    #[setter]
+    fn set_line_width(&mut self, new_line_width: f32) {
+        self.context.set_line_width(new_line_width);
+    }
+
+    #[getter]
+    fn line_cap(&self) -> &'static str {
+        match self.context.line_cap() {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        }
+    }
+
+    #[setter]
+    fn set_line_cap(&mut self, new_line_cap: &str) -> PyResult<()> {
+        self.context.set_line_cap(match new_line_cap {
+            "butt" => LineCap::Butt,
+            "round" => LineCap::Round,
+            "square" => LineCap::Square,
+            _ => return Err(invalid_value("line cap", new_line_cap)),
+        });
+        Ok(())
+    }
+
+    #[getter]
+    fn line_join(&self) -> &'static str {
+        match self.context.line_join() {
+            LineJoin::Miter => "miter",
+            LineJoin::Bevel => "bevel",
+            LineJoin::Round => "round",
+        }
+    }
+
+    #[setter]
+    fn set_line_join(&mut self, new_line_join: &str) -> PyResult<()> {
+        self.context.set_line_join(match new_line_join {
+            "miter" => LineJoin::Miter,
+            "bevel" => LineJoin::Bevel,
+            "round" => LineJoin::Round,
+            _ => return Err(invalid_value("line join", new_line_join)),
+        });
+        Ok(())
+    }
+
+    #[getter]
+    fn miter_limit(&self) -> f32 {
+        self.context.miter_limit()
+    }
+
+    #[setter]
+    fn set_miter_limit(&mut self, new_miter_limit: f32) {
+        self.context.set_miter_limit(new_miter_limit);
+    }
+
+    fn set_line_dash(&mut self, segments: Vec<f32>) {
+        self.context.set_line_dash(segments);
+    }
+
+    #[getter]
+    fn line_dash_offset(&self) -> f32 {
+        self.context.line_dash_offset()
+    }
+
+    #[setter]
+    fn set_line_dash_offset(&mut self, new_line_dash_offset: f32) {
+        self.context.set_line_dash_offset(new_line_dash_offset);
+    }
+
+    // Text styles
+
+    /// Sets the font from a CSS `font` shorthand like `"bold 14px 'Fira Sans', serif"`.
+    #[setter]
+    fn set_font(&mut self, new_font: &str) {
+        self.context.set_font(new_font);
+    }
+
+    #[getter]
+    fn font_size(&self) -> f32 {
+        self.context.font_size()
+    }
+
+    #[setter]
+    fn set_font_size(&mut self, new_font_size: f32) {
+        self.context.set_font_size(new_font_size);
+    }
+
+    #[setter]
+    fn set_text_align(&mut self, new_text_align: &str) -> PyResult<()> {
+        self.context.set_text_align(match new_text_align {
+            "left" => TextAlign::Left,
+            "right" => TextAlign::Right,
+            "center" => TextAlign::Center,
+            "start" => TextAlign::Start,
+            "end" => TextAlign::End,
+            _ => return Err(invalid_value("text alignment", new_text_align)),
+        });
+        Ok(())
+    }
+
+    #[setter]
+    fn set_text_baseline(&mut self, new_text_baseline: &str) -> PyResult<()> {
+        self.context.set_text_baseline(match new_text_baseline {
+            "alphabetic" => TextBaseline::Alphabetic,
+            "top" => TextBaseline::Top,
+            "hanging" => TextBaseline::Hanging,
+            "middle" => TextBaseline::Middle,
+            "ideographic" => TextBaseline::Ideographic,
+            "bottom" => TextBaseline::Bottom,
+            _ => return Err(invalid_value("text baseline", new_text_baseline)),
+        });
+        Ok(())
+    }
+
+    // Fill and stroke styles
+
+    /// Sets the fill style to a CSS color string or a `CanvasGradient`.
+    #[setter]
+    fn set_fill_style(&mut self, new_fill_style: &PyAny) -> PyResult<()> {
+        let new_fill_style = extract_fill_style(new_fill_style)?;
+        self.context.set_fill_style(new_fill_style);
+        Ok(())
+    }
+
+    /// Sets the stroke style to a CSS color string or a `CanvasGradient`.
+    #[setter]
+    fn set_stroke_style(&mut self, new_stroke_style: &PyAny) -> PyResult<()> {
+        let new_stroke_style = extract_fill_style(new_stroke_style)?;
+        self.context.set_stroke_style(new_stroke_style);
+        Ok(())
+    }
+
+    fn create_linear_gradient(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> PyCanvasGradient {
+        PyCanvasGradient {
+            gradient: Gradient::linear_from_points(vec2f(x0, y0), vec2f(x1, y1)),
+        }
+    }
+
+    fn create_radial_gradient(
+        &self,
+        x0: f32,
+        y0: f32,
+        r0: f32,
+        x1: f32,
+        y1: f32,
+        r1: f32,
+    ) -> PyCanvasGradient {
+        let line = LineSegment2F::new(vec2f(x0, y0), vec2f(x1, y1));
+        PyCanvasGradient {
+            gradient: Gradient::radial(line, F32x2::new(r0, r1)),
+        }
+    }
+
+    fn create_conic_gradient(&self, start_angle: f32, x: f32, y: f32) -> PyCanvasGradient {
+        PyCanvasGradient {
+            gradient: self.context.create_conic_gradient(start_angle, vec2f(x, y)),
+        }
+    }
+
+    // Compositing
+
+    #[getter]
+    fn global_alpha(&self) -> f32 {
+        self.context.global_alpha()
+    }
+
+    #[setter]
+    fn set_global_alpha(&mut self, new_global_alpha: f32) {
+        self.context.set_global_alpha(new_global_alpha);
+    }
+
+    // Paths
+
+    fn begin_path(&mut self) {
+        self.default_path = Path2D::new();
+    }
+
+    fn close_path(&mut self) {
+        self.default_path.close_path();
+    }
+
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.default_path.move_to(vec2f(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.default_path.line_to(vec2f(x, y));
+    }
+
+    fn quadratic_curve_to(&mut self, cpx: f32, cpy: f32, x: f32, y: f32) {
+        self.default_path
+            .quadratic_curve_to(vec2f(cpx, cpy), vec2f(x, y));
+    }
+
+    fn bezier_curve_to(&mut self, cp1x: f32, cp1y: f32, cp2x: f32, cp2y: f32, x: f32, y: f32) {
+        self.default_path
+            .bezier_curve_to(vec2f(cp1x, cp1y), vec2f(cp2x, cp2y), vec2f(x, y));
+    }
+
+    #[args(anticlockwise = "false")]
+    fn arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        self.default_path.arc(
+            vec2f(x, y),
+            radius,
+            start_angle,
+            end_angle,
+            arc_direction(anticlockwise),
+        );
+    }
+
+    fn arc_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) {
+        self.default_path
+            .arc_to(vec2f(x1, y1), vec2f(x2, y2), radius);
+    }
+
+    #[args(start_angle = "0.0", end_angle = "2.0 * PI", anticlockwise = "false")]
+    fn ellipse(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: bool,
+    ) {
+        self.default_path.ellipse(
+            vec2f(x, y),
+            vec2f(radius_x, radius_y),
+            rotation,
+            start_angle,
+            end_angle,
+            arc_direction(anticlockwise),
+        );
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.default_path
+            .rect(RectF::new(vec2f(x, y), vec2f(width, height)));
+    }
+
+    #[args(radius = "0.0")]
+    fn round_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32) {
+        self.default_path
+            .round_rect(RectF::new(vec2f(x, y), vec2f(width, height)), radius);
+    }
+
+    /// Fills the current path with the `"nonzero"` or `"evenodd"` fill rule.
+    #[args(fill_rule = "\"nonzero\"")]
+    fn fill(&mut self, fill_rule: &str) -> PyResult<()> {
+        let fill_rule = parse_fill_rule(fill_rule)?;
+        self.context.fill_path(self.default_path.clone(), fill_rule);
+        Ok(())
+    }
+
+    fn stroke(&mut self) {
+        self.context.stroke_path(self.default_path.clone());
+    }
+
+    /// Intersects the clip with the current path, using the `"nonzero"` or `"evenodd"` fill rule.
+    #[args(fill_rule = "\"nonzero\"")]
+    fn clip(&mut self, fill_rule: &str) -> PyResult<()> {
+        let fill_rule = parse_fill_rule(fill_rule)?;
+        self.context.clip_path(self.default_path.clone(), fill_rule);
+        Ok(())
+    }
+
+    // Transformations
+
+    fn translate(&mut self, x: f32, y: f32) {
+        self.context.translate(vec2f(x, y));
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        self.context.scale(vec2f(x, y));
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        self.context.rotate(angle);
+    }
+
+    fn transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        let new_transform = self.context.transform() * Transform2F::row_major(a, c, e, b, d, f);
+        self.context.set_transform(&new_transform);
+    }
+
+    fn set_transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        self.context
+            .set_transform(&Transform2F::row_major(a, c, e, b, d, f));
+    }
+
+    fn reset_transform(&mut self) {
+        self.context.reset_transform();
+    }
+
+    // The canvas state
+
+    fn save(&mut self) {
+        self.context.save();
+    }
+
+    fn restore(&mut self) {
+        self.context.restore();
+    }
+}
+
+/// A gradient for use as a fill or stroke style.
+#[pyclass(name = "CanvasGradient")]
+#[derive(Clone)]
+pub struct PyCanvasGradient {
+    gradient: Gradient,
+}
+
+#[pymethods]
+impl PyCanvasGradient {
+    /// Adds a stop with a CSS color string at an offset between 0 and 1.
+    fn add_color_stop(&mut self, offset: f32, color: &str) -> PyResult<()> {
+        let color = parse_color(color)?;
+        self.gradient.add_color_stop(color, offset);
+        Ok(())
+    }
+}
+
+/// The dimensions of a piece of text, in pixels.
+#[pyclass(name = "TextMetrics")]
+pub struct PyTextMetrics {
+    #[pyo3(get)]
+    width: f32,
+    #[pyo3(get)]
+    actual_bounding_box_left: f32,
+    #[pyo3(get)]
+    actual_bounding_box_right: f32,
+    #[pyo3(get)]
+    actual_bounding_box_ascent: f32,
+    #[pyo3(get)]
+    actual_bounding_box_descent: f32,
+    #[pyo3(get)]
+    font_bounding_box_ascent: f32,
+    #[pyo3(get)]
+    font_bounding_box_descent: f32,
+}
+
+/// A vector scene, such as a loaded SVG.
+#[pyclass(name = "Scene")]
+pub struct PyScene {
+    scene: Scene,
+}
+
+#[pymethods]
+impl PyScene {
+    /// Parses the contents of an SVG file.
+    #[staticmethod]
+    fn from_svg_data(data: &[u8]) -> PyResult<PyScene> {
+        match SVGScene::from_data(data, &Options::default().to_ref()) {
+            Ok(svg) => Ok(PyScene { scene: svg.scene }),
+            Err(error) => Err(PyValueError::new_err(format!("Invalid SVG: {}", error))),
+        }
+    }
+
+    /// Loads an SVG file.
+    #[staticmethod]
+    fn from_svg_file(path: &str) -> PyResult<PyScene> {
+        let data = fs::read(path).map_err(|error| PyIOError::new_err(error.to_string()))?;
+        PyScene::from_svg_data(&data)
+    }
+
+    /// The visible part of the scene, as a tuple of `(x, y, width, height)`.
+    #[getter]
+    fn view_box(&self) -> (f32, f32, f32, f32) {
+        let view_box = self.scene.view_box();
+        (
+            view_box.origin_x(),
+            view_box.origin_y(),
+            view_box.width(),
+            view_box.height(),
+        )
+    }
+
+    /// Writes the scene to a file, in the format given by its extension: `.png`, `.pdf`, `.ps`,
+    /// or `.svg`. PNG images are rendered on the CPU at `scale` pixels per unit.
+    #[args(scale = "1.0")]
+    fn write_file(&self, path: &str, scale: f32) -> PyResult<()> {
+        write_scene(&self.scene, path, scale)
+    }
+
+    /// Returns the scene as the bytes of a `png`, `pdf`, `ps`, or `svg` file.
+    #[args(scale = "1.0")]
+    fn export<'p>(&self, py: Python<'p>, format: &str, scale: f32) -> PyResult<&'p PyBytes> {
+        let bytes = export_scene(&self.scene, format, scale)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+}
+
+#[pymodule]
+fn pathfinder(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyCanvasRenderingContext2D>()?;
+    module.add_class::<PyCanvasGradient>()?;
+    module.add_class::<PyTextMetrics>()?;
+    module.add_class::<PyScene>()?;
+    Ok(())
+}
+
+fn write_scene(scene: &Scene, path: &str, scale: f32) -> PyResult<()> {
+    let format = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("");
+    let bytes = export_scene(scene, &format.to_lowercase(), scale)?;
+    fs::write(path, bytes).map_err(|error| PyIOError::new_err(error.to_string()))
+}
+
+fn export_scene(scene: &Scene, format: &str, scale: f32) -> PyResult<Vec<u8>> {
+    let format = match format {
+        "png" => FileFormat::PNG {
+            size: (scene.view_box().size() * scale).ceil().to_i32(),
+            background: None,
+        },
+        "pdf" => FileFormat::PDF {
+            compressed: true,
+            page: PageOptions::default(),
+            conformance: PdfConformance::default(),
+        },
+        "ps" => FileFormat::PS {
+            page: PageOptions::default(),
+        },
+        "svg" => FileFormat::SVG,
+        _ => return Err(invalid_value("export format", format)),
+    };
+    export_with_format(scene, format)
+}
+
+fn export_with_format(scene: &Scene, format: FileFormat) -> PyResult<Vec<u8>> {
+    let mut bytes = vec![];
+    scene
+        .export(&mut bytes, format)
+        .map_err(|error| PyIOError::new_err(error.to_string()))?;
+    Ok(bytes)
+}
+
+fn extract_fill_style(value: &PyAny) -> PyResult<FillStyle> {
+    if let Ok(color) = value.extract::<&str>() {
+        return Ok(FillStyle::Color(parse_color(color)?));
+    }
+    if let Ok(gradient) = value.extract::<PyRef<PyCanvasGradient>>() {
+        return Ok(FillStyle::Gradient(gradient.gradient.clone()));
+    }
+    Err(PyTypeError::new_err(
+        "Fill and stroke styles must be CSS color strings or gradients",
+    ))
+}
+
+fn parse_color(string: &str) -> PyResult<ColorU> {
+    let css_color = Color::from_str(string).map_err(|_| invalid_value("color", string))?;
+    Ok(ColorU::new(
+        css_color.r,
+        css_color.g,
+        css_color.b,
+        (css_color.a * 255.0).round() as u8,
+    ))
+}
+
+fn parse_fill_rule(string: &str) -> PyResult<FillRule> {
+    match string {
+        "nonzero" => Ok(FillRule::Winding),
+        "evenodd" => Ok(FillRule::EvenOdd),
+        _ => Err(invalid_value("fill rule", string)),
+    }
+}
+
+fn arc_direction(anticlockwise: bool) -> ArcDirection {
+    if anticlockwise {
+        ArcDirection::CCW
+    } else {
+        ArcDirection::CW
+    }
+}
+
+fn invalid_value(kind: &str, value: &str) -> PyErr {
+    PyValueError::new_err(format!("Invalid {}: {:?}", kind, value))
+}
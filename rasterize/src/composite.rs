@@ -0,0 +1,206 @@
+// pathfinder/rasterize/src/composite.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blends source colors onto destination pixels.
+
+use crate::shade::{premultiply, unpremultiply};
+use pathfinder_color::ColorF;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_simd::default::F32x4;
+
+/// Composites a non-premultiplied source color onto a premultiplied destination pixel, returning
+/// the new premultiplied pixel.
+pub(crate) fn composite(dest: F32x4, src: ColorF, blend_mode: BlendMode) -> F32x4 {
+    let (src_alpha, dest_alpha) = (src.a(), dest[3]);
+
+    // Porter-Duff operators: `src * src_factor + dest * dest_factor`.
+    let (src_factor, dest_factor) = match blend_mode {
+        BlendMode::Clear => (0.0, 0.0),
+        BlendMode::Copy => (1.0, 0.0),
+        BlendMode::SrcIn => (dest_alpha, 0.0),
+        BlendMode::SrcOut => (1.0 - dest_alpha, 0.0),
+        BlendMode::SrcOver => (1.0, 1.0 - src_alpha),
+        BlendMode::SrcAtop => (dest_alpha, 1.0 - src_alpha),
+        BlendMode::DestIn => (0.0, src_alpha),
+        BlendMode::DestOut => (0.0, 1.0 - src_alpha),
+        BlendMode::DestOver => (1.0 - dest_alpha, 1.0),
+        BlendMode::DestAtop => (1.0 - dest_alpha, src_alpha),
+        BlendMode::Xor => (1.0 - dest_alpha, 1.0 - src_alpha),
+        BlendMode::Lighter => (1.0, 1.0),
+        _ => return blend(dest, src, blend_mode),
+    };
+
+    let result = premultiply(src) * F32x4::splat(src_factor) + dest * F32x4::splat(dest_factor);
+    result.min(F32x4::splat(1.0))
+}
+
+/// Composites a non-premultiplied source color source-over onto a premultiplied destination
+/// pixel, with separate coverage for the red, green, and blue channels. The green coverage stands
+/// in for the coverage of the alpha channel.
+pub(crate) fn composite_subpixel(dest: F32x4, src: ColorF, coverage: [f32; 3]) -> F32x4 {
+    let alphas =
+        F32x4::new(coverage[0], coverage[1], coverage[2], coverage[1]) * F32x4::splat(src.a());
+    let src = F32x4::new(src.r(), src.g(), src.b(), 1.0) * alphas;
+    (src + dest * (F32x4::splat(1.0) - alphas)).min(F32x4::splat(1.0))
+}
+
+// Separable and non-separable blend modes, per the W3C Compositing and Blending specification.
+fn blend(dest: F32x4, src: ColorF, blend_mode: BlendMode) -> F32x4 {
+    let (src_alpha, dest_alpha) = (src.a(), dest[3]);
+    let dest_color = unpremultiply(dest);
+    let (cs, cd) = (
+        [src.r(), src.g(), src.b()],
+        [dest_color.r(), dest_color.g(), dest_color.b()],
+    );
+
+    let blended = match blend_mode {
+        BlendMode::Hue => set_lum(set_sat(cs, sat(cd)), lum(cd)),
+        BlendMode::Saturation => set_lum(set_sat(cd, sat(cs)), lum(cd)),
+        BlendMode::Color => set_lum(cs, lum(cd)),
+        BlendMode::Luminosity => set_lum(cd, lum(cs)),
+        _ => [
+            blend_channel(cd[0], cs[0], blend_mode),
+            blend_channel(cd[1], cs[1], blend_mode),
+            blend_channel(cd[2], cs[2], blend_mode),
+        ],
+    };
+
+    let mut result = [0.0; 4];
+    for channel in 0..3 {
+        result[channel] = (1.0 - dest_alpha) * src_alpha * cs[channel]
+            + (1.0 - src_alpha) * dest[channel]
+            + src_alpha * dest_alpha * blended[channel];
+    }
+    result[3] = src_alpha + dest_alpha - src_alpha * dest_alpha;
+    F32x4::new(result[0], result[1], result[2], result[3])
+        .clamp(F32x4::default(), F32x4::splat(1.0))
+}
+
+fn blend_channel(dest: f32, src: f32, blend_mode: BlendMode) -> f32 {
+    match blend_mode {
+        BlendMode::Multiply => dest * src,
+        BlendMode::Screen => screen(dest, src),
+        BlendMode::Overlay => hard_light(src, dest),
+        BlendMode::Darken => dest.min(src),
+        BlendMode::Lighten => dest.max(src),
+        BlendMode::ColorDodge => color_dodge(dest, src),
+        BlendMode::ColorBurn => 1.0 - color_dodge(1.0 - dest, 1.0 - src),
+        BlendMode::HardLight => hard_light(dest, src),
+        BlendMode::SoftLight => {
+            let darkened_dest = if dest <= 0.25 {
+                ((16.0 * dest - 12.0) * dest + 4.0) * dest
+            } else {
+                dest.sqrt()
+            };
+            let factor = if src <= 0.5 {
+                dest * (1.0 - dest)
+            } else {
+                darkened_dest - dest
+            };
+            dest + (src * 2.0 - 1.0) * factor
+        }
+        BlendMode::Difference => (dest - src).abs(),
+        BlendMode::Exclusion => dest + src - 2.0 * dest * src,
+        _ => src,
+    }
+}
+
+fn screen(dest: f32, src: f32) -> f32 {
+    dest + src - dest * src
+}
+
+fn hard_light(dest: f32, src: f32) -> f32 {
+    if src <= 0.5 {
+        dest * 2.0 * src
+    } else {
+        screen(dest, 2.0 * src - 1.0)
+    }
+}
+
+fn color_dodge(dest: f32, src: f32) -> f32 {
+    if dest == 0.0 {
+        0.0
+    } else if src == 1.0 {
+        1.0
+    } else {
+        (dest / (1.0 - src)).min(1.0)
+    }
+}
+
+// The helpers for the non-separable blend modes.
+
+fn lum(color: [f32; 3]) -> f32 {
+    0.3 * color[0] + 0.59 * color[1] + 0.11 * color[2]
+}
+
+fn clip_color(mut color: [f32; 3]) -> [f32; 3] {
+    let l = lum(color);
+    let n = color[0].min(color[1]).min(color[2]);
+    let x = color[0].max(color[1]).max(color[2]);
+    if n < 0.0 {
+        for channel in &mut color {
+            *channel = l + (*channel - l) * l / (l - n);
+        }
+    }
+    if x > 1.0 {
+        for channel in &mut color {
+            *channel = l + (*channel - l) * (1.0 - l) / (x - l);
+        }
+    }
+    color
+}
+
+fn set_lum(color: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(color);
+    clip_color([color[0] + d, color[1] + d, color[2] + d])
+}
+
+fn sat(color: [f32; 3]) -> f32 {
+    color[0].max(color[1]).max(color[2]) - color[0].min(color[1]).min(color[2])
+}
+
+fn set_sat(color: [f32; 3], s: f32) -> [f32; 3] {
+    let (color_min, color_sat) = (color[0].min(color[1]).min(color[2]), sat(color));
+    if color_sat == 0.0 {
+        return [0.0; 3];
+    }
+    [
+        (color[0] - color_min) * s / color_sat,
+        (color[1] - color_min) * s / color_sat,
+        (color[2] - color_min) * s / color_sat,
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::{composite, lum};
+    use pathfinder_color::ColorF;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_simd::default::F32x4;
+
+    #[test]
+    fn non_separable_modes_take_luminosity_from_the_right_side() {
+        let gray = F32x4::new(0.5, 0.5, 0.5, 1.0);
+        let red = ColorF::new(1.0, 0.0, 0.0, 1.0);
+
+        // `Color` keeps the hue and saturation of the source and the luminosity of the
+        // destination. Red is pushed out of gamut and must be clipped back in.
+        let result = composite(gray, red, BlendMode::Color);
+        assert!((lum([result[0], result[1], result[2]]) - 0.5).abs() < 0.001);
+        assert!((result[0] - 1.0).abs() < 0.001);
+        assert!(result[1] == result[2] && result[1] < 0.5);
+
+        // `Luminosity` is the reverse.
+        let result = composite(gray, red, BlendMode::Luminosity);
+        for channel in 0..3 {
+            assert!((result[channel] - 0.3).abs() < 0.001);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+// pathfinder/rasterize/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A software rasterizer that renders Pathfinder scenes entirely on the CPU.
+//!
+//! This is useful on servers, in continuous integration (e.g. for golden image tests), and on
+//! platforms without OpenGL or Metal. It walks the same display list as the GPU renderer, so
+//! render targets, groups, clip paths, blend modes, gradients, patterns, and noise are all
+//! supported. It is much slower than the GPU renderer and makes no attempt to match it bit for
+//! bit.
+//!
+//! Blur and text filters on patterns aren't supported yet; such patterns are drawn unfiltered.
+//! Use `unsupported_filter()` to find out whether a scene contains any.
+
+use crate::mask::Mask;
+use fxhash::FxHashMap;
+use pathfinder_color::ColorF;
+use pathfinder_content::effects::PatternFilter;
+use pathfinder_content::pattern::Image;
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2I};
+use pathfinder_renderer::scene::{ClipPathId, DisplayItem, DrawPath, DrawPathId, Scene};
+use pathfinder_simd::default::F32x4;
+use std::sync::Arc;
+
+mod composite;
+mod mask;
+mod shade;
+
+/// Options that control how a scene is rasterized.
+#[derive(Clone, Debug)]
+pub struct RasterizeOptions {
+    /// The size of the resulting image, in pixels.
+    pub size: Vector2I,
+    /// A transform applied to the whole scene before rasterization.
+    pub transform: Transform2F,
+    /// The color to clear the image to, or `None` for transparent black.
+    pub background_color: Option<ColorF>,
+}
+
+// A buffer of premultiplied pixels that paths are drawn into.
+pub(crate) struct Layer {
+    size: Vector2I,
+    pub(crate) pixels: Vec<F32x4>,
+}
+
+impl RasterizeOptions {
+    /// Returns options that rasterize the scene untransformed onto a transparent image of the
+    /// given size.
+    #[inline]
+    pub fn new(size: Vector2I) -> RasterizeOptions {
+        RasterizeOptions {
+            size,
+            transform: Transform2F::default(),
+            background_color: None,
+        }
+    }
+}
+
+/// Renders a scene to an image with non-premultiplied RGBA pixels.
+pub fn rasterize(scene: &Scene, options: &RasterizeOptions) -> Image {
+    let mut root = Layer::new(options.size);
+    if let Some(background_color) = options.background_color {
+        root.pixels
+            .iter_mut()
+            .for_each(|pixel| *pixel = shade::premultiply(background_color));
+    }
+
+    let mut rasterizer = Rasterizer {
+        scene,
+        transform: options.transform,
+        inverse_transform: options.transform.inverse(),
+        layer_stack: vec![(None, root)],
+        render_targets: FxHashMap::default(),
+        clip_masks: FxHashMap::default(),
+    };
+
+    for display_item in scene.display_list() {
+        match *display_item {
+            DisplayItem::PushRenderTarget(render_target_id) => {
+                let size = scene.get_render_target(render_target_id).size();
+                rasterizer
+                    .layer_stack
+                    .push((Some(render_target_id), Layer::new(size)));
+            }
+            DisplayItem::PopRenderTarget => {
+                if let (Some(render_target_id), layer) = rasterizer.layer_stack.pop().unwrap() {
+                    rasterizer.render_targets.insert(render_target_id, layer);
+                }
+            }
+            DisplayItem::DrawPaths(ref draw_path_id_range) => {
+                for draw_path_index in draw_path_id_range.start.0..draw_path_id_range.end.0 {
+                    let draw_path = scene.get_draw_path(DrawPathId(draw_path_index));
+                    rasterizer.draw_path(draw_path);
+                }
+            }
+        }
+    }
+
+    let (_, root) = rasterizer.layer_stack.swap_remove(0);
+    let pixels = root
+        .pixels
+        .into_iter()
+        .map(|pixel| shade::unpremultiply(pixel).to_u8())
+        .collect();
+    Image::new(options.size, Arc::new(pixels))
+}
+
+/// Returns the first pattern filter drawn by the scene that the rasterizer can't apply, if any.
+///
+/// `rasterize()` draws patterns with such filters unfiltered, so its output won't match the GPU
+/// renderer's.
+pub fn unsupported_filter(scene: &Scene) -> Option<PatternFilter> {
+    (0..scene.draw_path_count()).find_map(|draw_path_index| {
+        let draw_path = scene.get_draw_path(DrawPathId(draw_path_index));
+        match scene.get_paint(draw_path.paint()).pattern()?.filter()? {
+            filter @ PatternFilter::Blur { .. } | filter @ PatternFilter::Text { .. } => {
+                Some(filter)
+            }
+            PatternFilter::ColorMatrix(_) => None,
+        }
+    })
+}
+
+struct Rasterizer<'a> {
+    scene: &'a Scene,
+    transform: Transform2F,
+    inverse_transform: Transform2F,
+    layer_stack: Vec<(Option<RenderTargetId>, Layer)>,
+    render_targets: FxHashMap<RenderTargetId, Layer>,
+    // Clip masks are cached per layer size, since clip paths are often shared by many paths.
+    clip_masks: FxHashMap<(ClipPathId, Vector2I), Mask>,
+}
+
+impl<'a> Rasterizer<'a> {
+    fn draw_path(&mut self, draw_path: &DrawPath) {
+        let layer_rect = RectI::new(Vector2I::zero(), self.layer_stack.last().unwrap().1.size);
+        let transform = self.transform * draw_path.transform();
+        let outline = draw_path.outline().clone().transformed(&transform);
+        let mut mask = Mask::from_outline(&outline, draw_path.fill_rule(), layer_rect);
+        if let Some(clip_path_id) = draw_path.clip_path() {
+            mask.intersect(self.clip_mask(clip_path_id, layer_rect));
+        }
+
+        let paint = self.scene.get_paint(draw_path.paint());
+        let blend_mode = draw_path.blend_mode();
+        let subpixel_coverage = draw_path.subpixel_coverage();
+        let (_, ref mut layer) = *self.layer_stack.last_mut().unwrap();
+        let mut mask_rect = mask.rect();
+        if subpixel_coverage {
+            // Subpixel coverage bleeds into the pixels on either side of the mask.
+            mask_rect = RectI::from_points(
+                mask_rect.origin() - vec2i(1, 0),
+                mask_rect.lower_right() + vec2i(1, 0),
+            )
+            .intersection(layer_rect)
+            .unwrap_or_default();
+        }
+        for y in mask_rect.min_y()..mask_rect.max_y() {
+            for x in mask_rect.min_x()..mask_rect.max_x() {
+                let pixel = Vector2I::new(x, y);
+                let coverage = if subpixel_coverage {
+                    mask.get_subpixels(pixel)
+                } else {
+                    [mask.get(pixel); 3]
+                };
+                if coverage == [0.0; 3] {
+                    continue;
+                }
+
+                let point = self.inverse_transform * (pixel.to_f32() + vec2f(0.5, 0.5));
+                let mut color = shade::shade(paint, point, &self.render_targets);
+
+                let index = y as usize * layer.size.x() as usize + x as usize;
+                layer.pixels[index] = if subpixel_coverage {
+                    composite::composite_subpixel(layer.pixels[index], color, coverage)
+                } else {
+                    color.set_a(color.a() * coverage[0]);
+                    composite::composite(layer.pixels[index], color, blend_mode)
+                };
+            }
+        }
+    }
+
+    fn clip_mask(&mut self, clip_path_id: ClipPathId, layer_rect: RectI) -> &Mask {
+        let key = (clip_path_id, layer_rect.size());
+        if !self.clip_masks.contains_key(&key) {
+            let clip_path = self.scene.get_clip_path(clip_path_id);
+            let outline = clip_path.outline().clone().transformed(&self.transform);
+            let mut mask = Mask::from_outline(&outline, clip_path.fill_rule(), layer_rect);
+            if let Some(nested_clip_path_id) = clip_path.clip_path() {
+                mask.intersect(self.clip_mask(nested_clip_path_id, layer_rect));
+            }
+            self.clip_masks.insert(key, mask);
+        }
+        &self.clip_masks[&key]
+    }
+}
+
+impl Layer {
+    fn new(size: Vector2I) -> Layer {
+        Layer {
+            size,
+            pixels: vec![F32x4::default(); size.x() as usize * size.y() as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{rasterize, RasterizeOptions};
+    use pathfinder_color::{ColorF, ColorU};
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_content::paint_effect::{PaintEffect, PaintEffectKind};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use pathfinder_renderer::paint::Paint;
+    use pathfinder_renderer::scene::{ClipPath, DrawPath, Scene};
+
+    #[test]
+    fn clip_and_blend() {
+        let mut scene = Scene::new();
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let green = scene.push_paint(&Paint::from_color(ColorU::new(0, 255, 0, 255)));
+
+        let rect = |x, y, w, h| Outline::from_rect(RectF::new(vec2f(x, y), vec2f(w, h)));
+        scene.push_draw_path(DrawPath::new(rect(0.0, 0.0, 4.0, 4.0), red));
+        let clip_path = scene.push_clip_path(ClipPath::new(rect(0.0, 0.0, 2.0, 4.0)));
+        let mut draw_path = DrawPath::new(rect(0.0, 0.0, 4.0, 2.0), green);
+        draw_path.set_clip_path(Some(clip_path));
+        draw_path.set_blend_mode(BlendMode::Multiply);
+        scene.push_draw_path(draw_path);
+
+        let mut options = RasterizeOptions::new(vec2i(4, 4));
+        options.background_color = Some(ColorF::white());
+        let image = rasterize(&scene, &options);
+        let pixels = image.pixels();
+        assert_eq!(pixels[0], ColorU::new(0, 0, 0, 255));
+        assert_eq!(pixels[3], ColorU::new(255, 0, 0, 255));
+        assert_eq!(pixels[8], ColorU::new(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn subpixel_coverage_leans_toward_the_covered_side() {
+        let mut scene = Scene::new();
+        let white = scene.push_paint(&Paint::from_color(ColorU::white()));
+        let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.5, 1.0)));
+        let mut draw_path = DrawPath::new(outline, white);
+        draw_path.set_subpixel_coverage(true);
+        scene.push_draw_path(draw_path);
+
+        let mut options = RasterizeOptions::new(vec2i(3, 1));
+        options.background_color = Some(ColorF::black());
+        let image = rasterize(&scene, &options);
+        let pixels = image.pixels();
+        assert_eq!(pixels[1], ColorU::new(170, 128, 85, 255));
+        assert!(pixels[2].r > 0 && pixels[2].g == 0 && pixels[2].b == 0);
+    }
+
+    #[test]
+    fn hatching_masks_the_base_color() {
+        let mut scene = Scene::new();
+        let effect = PaintEffect::new(PaintEffectKind::Hatching {
+            spacing: 4.0,
+            line_width: 2.0,
+        });
+        let red = ColorU::new(255, 0, 0, 255);
+        let hatched = scene.push_paint(&Paint::from_effect(red, effect));
+        let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 4.0)));
+        scene.push_draw_path(DrawPath::new(outline, hatched));
+
+        let mut options = RasterizeOptions::new(vec2i(1, 4));
+        options.background_color = Some(ColorF::white());
+        let image = rasterize(&scene, &options);
+        let pixels = image.pixels();
+        assert_eq!(pixels[0], red);
+        assert_eq!(pixels[1], ColorU::white());
+        assert_eq!(pixels[2], ColorU::white());
+        assert_eq!(pixels[3], red);
+    }
+}
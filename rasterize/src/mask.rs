@@ -0,0 +1,273 @@
+// pathfinder/rasterize/src/mask.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Computes per-pixel coverage of outlines.
+//!
+//! This uses the same signed-area accumulation as the GPU fill shaders: every line segment adds
+//! the area it covers to each pixel, the areas are summed from left to right along each row, and
+//! the fill rule is applied to the resulting winding coverage.
+
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::outline::{ContourIterFlags, Outline};
+use pathfinder_content::segment::Segment;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
+
+const FLATTENING_TOLERANCE: f32 = 0.25;
+
+/// The coverage of a path over a rectangle of pixels, from 0 to 1.
+pub(crate) struct Mask {
+    rect: RectI,
+    coverage: Vec<f32>,
+}
+
+// Accumulates signed areas before the fill rule is applied.
+struct Accumulator {
+    size: Vector2I,
+    stride: usize,
+    areas: Vec<f32>,
+}
+
+impl Mask {
+    /// Computes the coverage of an outline, already in device space, within `clip_rect`.
+    pub(crate) fn from_outline(outline: &Outline, fill_rule: FillRule, clip_rect: RectI) -> Mask {
+        let rect = outline
+            .bounds()
+            .round_out()
+            .to_i32()
+            .intersection(clip_rect)
+            .unwrap_or_default();
+
+        let mut accumulator = Accumulator::new(rect.size());
+        let origin = rect.origin().to_f32();
+        for contour in outline.contours() {
+            let (first_position, last_position) =
+                match (contour.first_position(), contour.last_position()) {
+                    (Some(first_position), Some(last_position)) => (first_position, last_position),
+                    _ => continue,
+                };
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                accumulator.add_segment(&segment, origin);
+            }
+            // Filling implicitly closes every contour.
+            let close_segment = LineSegment2F::new(last_position, first_position);
+            accumulator.add_line(close_segment - origin);
+        }
+
+        Mask {
+            rect,
+            coverage: accumulator.resolve(fill_rule),
+        }
+    }
+
+    /// Returns the rectangle of pixels that this mask covers.
+    #[inline]
+    pub(crate) fn rect(&self) -> RectI {
+        self.rect
+    }
+
+    /// Returns the coverage of the given pixel, which is zero outside the mask's rectangle.
+    #[inline]
+    pub(crate) fn get(&self, pixel: Vector2I) -> f32 {
+        if !self.rect.contains_point(pixel) {
+            return 0.0;
+        }
+        let local = pixel - self.rect.origin();
+        self.coverage[local.y() as usize * self.rect.width() as usize + local.x() as usize]
+    }
+
+    /// Approximates the coverage of the red, green, and blue subpixels of a pixel by interpolating
+    /// a third of the way toward its horizontal neighbors, as the GPU renderer does.
+    pub(crate) fn get_subpixels(&self, pixel: Vector2I) -> [f32; 3] {
+        let center = self.get(pixel);
+        let left = self.get(pixel - vec2i(1, 0));
+        let right = self.get(pixel + vec2i(1, 0));
+        [
+            center + (left - center) / 3.0,
+            center,
+            center + (right - center) / 3.0,
+        ]
+    }
+
+    /// Multiplies this mask by another, as when a path is clipped by a clip path.
+    pub(crate) fn intersect(&mut self, other: &Mask) {
+        let width = self.rect.width();
+        for (index, coverage) in self.coverage.iter_mut().enumerate() {
+            let local = Vector2I::new(index as i32 % width, index as i32 / width);
+            *coverage *= other.get(self.rect.origin() + local);
+        }
+    }
+}
+
+impl Accumulator {
+    fn new(size: Vector2I) -> Accumulator {
+        // Leave room for the areas that land just past the right edge.
+        let stride = size.x() as usize + 2;
+        Accumulator {
+            size,
+            stride,
+            areas: vec![0.0; stride * size.y() as usize],
+        }
+    }
+
+    fn add_segment(&mut self, segment: &Segment, origin: Vector2F) {
+        if segment.is_none() {
+            return;
+        }
+        if segment.is_quadratic() {
+            return self.add_segment(&segment.to_cubic(), origin);
+        }
+        if segment.is_line() || segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE) {
+            return self.add_line(segment.baseline - origin);
+        }
+        let (prev, next) = segment.split(0.5);
+        self.add_segment(&prev, origin);
+        self.add_segment(&next, origin);
+    }
+
+    fn add_line(&mut self, line: LineSegment2F) {
+        // Split the line where it crosses the left and right edges. Afterward, clamping the
+        // pieces horizontally leaves the coverage inside the mask unchanged.
+        let width = self.size.x() as f32;
+        let (from, to) = (line.from(), line.to());
+        let mut splits = [0.0, 1.0, 1.0, 1.0];
+        let mut split_count = 1;
+        for &edge in &[0.0, width] {
+            if (from.x() - edge) * (to.x() - edge) < 0.0 {
+                splits[split_count] = (edge - from.x()) / (to.x() - from.x());
+                split_count += 1;
+            }
+        }
+        splits[1..split_count].sort_by(|a, b| a.partial_cmp(b).unwrap());
+        splits[split_count] = 1.0;
+
+        for index in 0..split_count {
+            let clamp = |point: Vector2F| vec2f(point.x().max(0.0).min(width), point.y());
+            let piece_from = clamp(from.lerp(to, splits[index]));
+            let piece_to = clamp(from.lerp(to, splits[index + 1]));
+            self.add_clamped_line(piece_from, piece_to);
+        }
+    }
+
+    // Adapted from the accumulation rasterizer in `font-rs` by Raph Levien.
+    fn add_clamped_line(&mut self, p0: Vector2F, p1: Vector2F) {
+        if p0.y() == p1.y() {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y() < p1.y() {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+
+        let height = self.size.y() as f32;
+        let dxdy = (p1.x() - p0.x()) / (p1.y() - p0.y());
+        let mut x = p0.x();
+        if p0.y() < 0.0 {
+            x -= p0.y() * dxdy;
+        }
+
+        let y_start = p0.y().max(0.0) as usize;
+        let y_end = p1.y().min(height).ceil().max(0.0) as usize;
+        for y in y_start..y_end {
+            let row = &mut self.areas[(y * self.stride)..((y + 1) * self.stride)];
+            let dy = ((y + 1) as f32).min(p1.y()) - (y as f32).max(p0.y());
+            let x_next = x + dxdy * dy;
+            let d = dy * direction;
+
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0_index = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1_index = x1_ceil as usize;
+
+            if x1_index <= x0_index + 1 {
+                let x_mid = 0.5 * (x + x_next) - x0_floor;
+                row[x0_index] += d - d * x_mid;
+                row[x0_index + 1] += d * x_mid;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0_fract = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0_fract) * (1.0 - x0_fract);
+                let x1_fract = x1 - x1_ceil + 1.0;
+                let a_max = 0.5 * s * x1_fract * x1_fract;
+
+                row[x0_index] += d * a0;
+                if x1_index == x0_index + 2 {
+                    row[x0_index + 1] += d * (1.0 - a0 - a_max);
+                } else {
+                    let a1 = s * (1.5 - x0_fract);
+                    row[x0_index + 1] += d * (a1 - a0);
+                    for area in &mut row[(x0_index + 2)..(x1_index - 1)] {
+                        *area += d * s;
+                    }
+                    let a2 = a1 + (x1_index - x0_index - 3) as f32 * s;
+                    row[x1_index - 1] += d * (1.0 - a2 - a_max);
+                }
+                row[x1_index] += d * a_max;
+            }
+
+            x = x_next;
+        }
+    }
+
+    fn resolve(self, fill_rule: FillRule) -> Vec<f32> {
+        let (width, height) = (self.size.x() as usize, self.size.y() as usize);
+        let mut coverage = Vec::with_capacity(width * height);
+        for row in self.areas.chunks(self.stride) {
+            let mut winding = 0.0;
+            for &area in &row[0..width] {
+                winding += area;
+                coverage.push(match fill_rule {
+                    FillRule::Winding => f32::abs(winding).min(1.0),
+                    FillRule::EvenOdd => 1.0 - f32::abs(1.0 - winding.rem_euclid(2.0)),
+                });
+            }
+        }
+        coverage
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Mask;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+
+    #[test]
+    fn rect_coverage() {
+        let outline = Outline::from_rect(RectF::new(vec2f(1.5, 1.0), vec2f(2.0, 2.0)));
+        let clip_rect = RectI::new(vec2i(0, 0), vec2i(8, 8));
+        let mask = Mask::from_outline(&outline, FillRule::Winding, clip_rect);
+        assert_eq!(mask.get(vec2i(0, 1)), 0.0);
+        assert!((mask.get(vec2i(1, 1)) - 0.5).abs() < 0.0001);
+        assert!((mask.get(vec2i(2, 2)) - 1.0).abs() < 0.0001);
+        assert!((mask.get(vec2i(3, 2)) - 0.5).abs() < 0.0001);
+        assert_eq!(mask.get(vec2i(2, 3)), 0.0);
+    }
+
+    #[test]
+    fn fill_rules() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        outline.push_outline(Outline::from_rect(RectF::new(
+            vec2f(1.0, 1.0),
+            vec2f(2.0, 2.0),
+        )));
+        let clip_rect = RectI::new(vec2i(0, 0), vec2i(4, 4));
+        let winding = Mask::from_outline(&outline, FillRule::Winding, clip_rect);
+        let even_odd = Mask::from_outline(&outline, FillRule::EvenOdd, clip_rect);
+        assert!((winding.get(vec2i(1, 1)) - 1.0).abs() < 0.0001);
+        assert!(even_odd.get(vec2i(1, 1)).abs() < 0.0001);
+        assert!((even_odd.get(vec2i(0, 0)) - 1.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,302 @@
+// pathfinder/rasterize/src/shade.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Evaluates paints at points in the scene.
+//!
+//! These mirror the color sampling and filters in `tile_fragment.inc.glsl`.
+
+use crate::Layer;
+use fxhash::FxHashMap;
+use pathfinder_color::ColorF;
+use pathfinder_content::effects::PatternFilter;
+use pathfinder_content::gradient::{Gradient, GradientGeometry, GradientWrap};
+use pathfinder_content::noise::{Noise, NoiseKind};
+use pathfinder_content::paint_effect::{PaintEffect, PaintEffectKind};
+use pathfinder_content::pattern::{Pattern, PatternSource};
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
+use pathfinder_renderer::paint::{Paint, PaintCompositeOp};
+use pathfinder_simd::default::F32x4;
+use std::f32::consts::PI;
+
+/// Returns the non-premultiplied color of a paint at a point in scene space.
+pub(crate) fn shade(
+    paint: &Paint,
+    point: Vector2F,
+    render_targets: &FxHashMap<RenderTargetId, Layer>,
+) -> ColorF {
+    let base_color = paint.base_color().to_f32();
+    let overlay = match *paint.overlay() {
+        None => return base_color,
+        Some(ref overlay) => overlay,
+    };
+
+    let overlay_color = if let Some(gradient) = paint.gradient() {
+        sample_gradient(gradient, point)
+    } else if let Some(pattern) = paint.pattern() {
+        sample_pattern(pattern, point, render_targets)
+    } else if let Some(noise) = paint.noise() {
+        sample_noise(noise, point)
+    } else if let Some(effect) = paint.effect() {
+        sample_effect(effect, point)
+    } else {
+        return base_color;
+    };
+
+    let alpha = overlay_color.a() * base_color.a();
+    let mut color = match overlay.composite_op() {
+        PaintCompositeOp::SrcIn => overlay_color,
+        PaintCompositeOp::DestIn => base_color,
+    };
+    color.set_a(alpha);
+    color
+}
+
+fn sample_gradient(gradient: &Gradient, point: Vector2F) -> ColorF {
+    let t = match gradient.geometry {
+        GradientGeometry::Linear(line) => {
+            (point - line.from()).dot(line.vector()) / line.square_length()
+        }
+        GradientGeometry::Radial {
+            line,
+            radii,
+            transform,
+        } => {
+            // Solve for the largest circle that passes through the point, as the shader does.
+            let d_p = transform.inverse() * point - line.from();
+            let d_c = line.vector();
+            let d_r = radii.y() - radii.x();
+
+            let a = d_c.dot(d_c) - d_r * d_r;
+            let b = d_p.dot(d_c) + radii.x() * d_r;
+            let c = d_p.dot(d_p) - radii.x() * radii.x();
+            let discrim = b * b - a * c;
+            if discrim == 0.0 {
+                return ColorF::transparent_black();
+            }
+
+            let (t0, t1) = ((b + discrim.sqrt()) / a, (b - discrim.sqrt()) / a);
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            if t0 >= 0.0 {
+                t0
+            } else {
+                t1
+            }
+        }
+        GradientGeometry::Conic { transform, .. } => {
+            gradient.geometry.conic_offset(transform.inverse() * point)
+        }
+    };
+
+    let t = match gradient.wrap {
+        GradientWrap::Clamp => t,
+        GradientWrap::Repeat => t - t.floor(),
+    };
+    gradient.sample(t).to_f32()
+}
+
+fn sample_pattern(
+    pattern: &Pattern,
+    point: Vector2F,
+    render_targets: &FxHashMap<RenderTargetId, Layer>,
+) -> ColorF {
+    let texel_position = pattern.transform().inverse() * point;
+    let size = pattern.size();
+
+    // Fetches a premultiplied texel, wrapping or clamping to transparent black.
+    let fetch = |mut texel: Vector2I| -> F32x4 {
+        if pattern.repeat_x() {
+            texel.set_x(texel.x().rem_euclid(size.x()));
+        }
+        if pattern.repeat_y() {
+            texel.set_y(texel.y().rem_euclid(size.y()));
+        }
+        if texel.x() < 0 || texel.y() < 0 || texel.x() >= size.x() || texel.y() >= size.y() {
+            return F32x4::default();
+        }
+        let index = texel.y() as usize * size.x() as usize + texel.x() as usize;
+        match *pattern.source() {
+            PatternSource::Image(ref image) => premultiply(image.pixels()[index].to_f32()),
+            PatternSource::RenderTarget { id, .. } => match render_targets.get(&id) {
+                Some(layer) => layer.pixels[index],
+                None => F32x4::default(),
+            },
+            // External textures live on the GPU, so there's nothing to sample here.
+            PatternSource::ExternalTexture { .. } => F32x4::default(),
+        }
+    };
+
+    let color = if pattern.smoothing_enabled() {
+        let position = texel_position - vec2f(0.5, 0.5);
+        let origin = position.floor();
+        let t = position - origin;
+        let origin = origin.to_i32();
+        let top = lerp(fetch(origin), fetch(origin + vec2i(1, 0)), t.x());
+        let bottom = lerp(
+            fetch(origin + vec2i(0, 1)),
+            fetch(origin + vec2i(1, 1)),
+            t.x(),
+        );
+        lerp(top, bottom, t.y())
+    } else {
+        fetch(texel_position.floor().to_i32())
+    };
+
+    let color = unpremultiply(color);
+    match pattern.filter() {
+        Some(PatternFilter::ColorMatrix(matrix)) => {
+            let [m0, m1, m2, m3, m4] = matrix.0;
+            let color = m0 * F32x4::splat(color.r())
+                + m1 * F32x4::splat(color.g())
+                + m2 * F32x4::splat(color.b())
+                + m3 * F32x4::splat(color.a())
+                + m4;
+            ColorF(color.clamp(F32x4::default(), F32x4::splat(1.0)))
+        }
+        // TODO: Blurs and text filters.
+        Some(PatternFilter::Blur { .. }) | Some(PatternFilter::Text { .. }) | None => color,
+    }
+}
+
+fn sample_noise(noise: &Noise, point: Vector2F) -> ColorF {
+    let base_frequency = noise.stitched_base_frequency();
+    let stitch_origin = noise
+        .stitch_rect
+        .map_or(Vector2F::zero(), |rect: RectF| rect.origin());
+    let fractal_noise = noise.kind == NoiseKind::FractalNoise;
+    // Match the precision that the GPU gets.
+    let seed = noise.seed & 0x7ff;
+
+    let mut position = (noise.transform.inverse() * point - stitch_origin) * base_frequency;
+    let mut period = noise.stitch_period();
+    let mut sum = [0.0; 4];
+    let mut amplitude = 1.0;
+    for _ in 0..noise.num_octaves {
+        for (channel, sum) in sum.iter_mut().enumerate() {
+            let value = perlin(position, period, channel as i32, seed);
+            *sum += if fractal_noise { value } else { value.abs() } * amplitude;
+        }
+        position *= 2.0;
+        period *= 2.0;
+        amplitude *= 0.5;
+    }
+
+    let finish = |value: f32| {
+        let value = if fractal_noise {
+            value * 0.5 + 0.5
+        } else {
+            value
+        };
+        value.clamp(0.0, 1.0)
+    };
+    ColorF::new(
+        finish(sum[0]),
+        finish(sum[1]),
+        finish(sum[2]),
+        finish(sum[3]),
+    )
+}
+
+fn sample_effect(effect: &PaintEffect, point: Vector2F) -> ColorF {
+    let inverse_transform = effect.transform.inverse();
+    let position = inverse_transform * point;
+    let pixel_size = inverse_transform.matrix.det().abs().sqrt();
+
+    // Antialiased coverage of lines centered on multiples of `spacing`.
+    let lines = |position: f32, spacing: f32, line_width: f32| {
+        let t = position / spacing + 0.5;
+        let dist = (t - t.floor() - 0.5).abs() * spacing;
+        ((line_width * 0.5 - dist) / pixel_size + 0.5).clamp(0.0, 1.0)
+    };
+
+    let alpha = match effect.kind {
+        PaintEffectKind::Hatching {
+            spacing,
+            line_width,
+        } => lines(position.y(), spacing, line_width),
+        PaintEffectKind::CrossHatching {
+            spacing,
+            line_width,
+        } => f32::max(
+            lines(position.y(), spacing, line_width),
+            lines(position.x(), spacing, line_width),
+        ),
+        PaintEffectKind::Halftone {
+            cell_size,
+            coverage,
+        } => {
+            let cell = position * (1.0 / cell_size);
+            let offset = (cell - cell.floor() - vec2f(0.5, 0.5)) * cell_size;
+            let radius = cell_size * (coverage.clamp(0.0, 1.0) / PI).sqrt();
+            ((radius - offset.length()) / pixel_size + 0.5).clamp(0.0, 1.0)
+        }
+    };
+    ColorF::new(1.0, 1.0, 1.0, alpha)
+}
+
+// The "lowbias32" integer hash by Chris Wellons, as in `filterNoiseGradient()`.
+fn noise_gradient(lattice_point: Vector2F, channel: i32, seed: i32) -> Vector2F {
+    let mut hash = (lattice_point.x() as i32 as u32).wrapping_mul(0x8da6b343)
+        ^ (lattice_point.y() as i32 as u32).wrapping_mul(0xd8163841)
+        ^ ((seed * 4 + channel) as u32).wrapping_mul(0xcb1ab31f);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb352d);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846ca68b);
+    hash ^= hash >> 16;
+    let angle = (hash & 0xffff) as f32 * (2.0 * PI / 65536.0);
+    vec2f(angle.cos(), angle.sin())
+}
+
+fn perlin(position: Vector2F, period: Vector2F, channel: i32, seed: i32) -> f32 {
+    let mut lattice0 = position.floor();
+    let mut lattice1 = lattice0 + vec2f(1.0, 1.0);
+    let t = position - lattice0;
+    if period.x() > 0.0 {
+        lattice0.set_x(lattice0.x().rem_euclid(period.x()));
+        lattice1.set_x(lattice1.x().rem_euclid(period.x()));
+    }
+    if period.y() > 0.0 {
+        lattice0.set_y(lattice0.y().rem_euclid(period.y()));
+        lattice1.set_y(lattice1.y().rem_euclid(period.y()));
+    }
+
+    let n00 = noise_gradient(lattice0, channel, seed).dot(t);
+    let n10 =
+        noise_gradient(vec2f(lattice1.x(), lattice0.y()), channel, seed).dot(t - vec2f(1.0, 0.0));
+    let n01 =
+        noise_gradient(vec2f(lattice0.x(), lattice1.y()), channel, seed).dot(t - vec2f(0.0, 1.0));
+    let n11 = noise_gradient(lattice1, channel, seed).dot(t - vec2f(1.0, 1.0));
+
+    let s = t * t * (vec2f(3.0, 3.0) - t * 2.0);
+    let top = n00 + (n10 - n00) * s.x();
+    let bottom = n01 + (n11 - n01) * s.x();
+    top + (bottom - top) * s.y()
+}
+
+#[inline]
+fn lerp(a: F32x4, b: F32x4, t: f32) -> F32x4 {
+    a + (b - a) * F32x4::splat(t)
+}
+
+#[inline]
+pub(crate) fn premultiply(color: ColorF) -> F32x4 {
+    color.0 * F32x4::new(color.a(), color.a(), color.a(), 1.0)
+}
+
+#[inline]
+pub(crate) fn unpremultiply(color: F32x4) -> ColorF {
+    let alpha = color[3];
+    if alpha <= 0.0 {
+        return ColorF::transparent_black();
+    }
+    ColorF(color * F32x4::new(1.0 / alpha, 1.0 / alpha, 1.0 / alpha, 1.0))
+}
@@ -13,15 +13,17 @@
 use crate::concurrent::executor::Executor;
 use crate::gpu::blend::BlendModeExt;
 use crate::gpu::options::RendererLevel;
+use crate::gpu_data::PropagateMetadataD3D11;
 use crate::gpu_data::{AlphaTileId, BackdropInfoD3D11, Clip, ClippedPathInfo, DiceMetadataD3D11};
 use crate::gpu_data::{DrawTileBatch, DrawTileBatchD3D11, DrawTileBatchD3D9, Fill, GlobalPathId};
-use crate::gpu_data::{PathBatchIndex, PathSource, PrepareTilesInfoD3D11, PropagateMetadataD3D11};
+use crate::gpu_data::{PathBatchIndex, PathDepthPlane, PathSource, PrepareTilesInfoD3D11};
 use crate::gpu_data::{RenderCommand, SegmentIndicesD3D11, SegmentsD3D11, TileBatchDataD3D11};
 use crate::gpu_data::{TileBatchId, TileBatchTexture, TileObjectPrimitive, TilePathInfoD3D11};
 use crate::options::{PrepareMode, PreparedBuildOptions, PreparedRenderTransform};
 use crate::paint::{PaintId, PaintInfo, PaintMetadata};
 use crate::scene::{ClipPathId, DisplayItem, DrawPath, DrawPathId, LastSceneInfo, PathId};
 use crate::scene::{Scene, SceneSink};
+use crate::tile_cache::{CachedDrawPath, TileCacheKey};
 use crate::tile_map::DenseTileMap;
 use crate::tiler::Tiler;
 use crate::tiles::{self, DrawTilingPathInfo, TilingPathInfo, TILE_HEIGHT, TILE_WIDTH};
@@ -33,7 +35,7 @@ use pathfinder_content::outline::{Outline, PointFlags};
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
 use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::F32x4;
 use std::borrow::Cow;
@@ -52,10 +54,21 @@ const MAX_CLIP_BATCHES: u32 = 32;
 pub(crate) struct SceneBuilder<'a, 'b, 'c, 'd> {
     pub(crate) scene: &'a mut Scene,
     built_options: &'b PreparedBuildOptions,
-    next_alpha_tile_indices: [AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
+    view_boxes: PathViewBoxes,
+    pub(crate) next_alpha_tile_indices: [AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
     pub(crate) sink: &'c mut SceneSink<'d>,
 }
 
+// The rectangles that paths are tiled against.
+//
+// Paths drawn to the output are tiled against the view box, while paths drawn to a render target
+// are tiled against the bounds of that render target. A clip path is tiled against all the
+// rectangles of the paths it clips.
+struct PathViewBoxes {
+    draw: Vec<RectF>,
+    clip: Vec<RectF>,
+}
+
 #[derive(Debug)]
 pub(crate) struct ObjectBuilder {
     pub built_path: BuiltPath,
@@ -69,6 +82,7 @@ struct BuiltDrawPath {
     path: BuiltPath,
     clip_path_id: Option<ClipPathId>,
     blend_mode: BlendMode,
+    subpixel_coverage: bool,
     filter: Filter,
     color_texture: Option<TileBatchTexture>,
     sampling_flags_1: TextureSamplingFlags,
@@ -82,7 +96,7 @@ impl BuiltDrawPath {
         path_object: &DrawPath,
         paint_metadata: &PaintMetadata,
     ) -> BuiltDrawPath {
-        let blend_mode = path_object.blend_mode();
+        let blend_mode = path_object.effective_blend_mode();
         let occludes = paint_metadata.is_opaque && blend_mode.occludes_backdrop();
         BuiltDrawPath {
             path: built_path,
@@ -92,6 +106,7 @@ impl BuiltDrawPath {
             sampling_flags_1: TextureSamplingFlags::empty(),
             mask_0_fill_rule: path_object.fill_rule(),
             blend_mode,
+            subpixel_coverage: path_object.subpixel_coverage(),
             occludes,
         }
     }
@@ -121,7 +136,8 @@ pub(crate) struct BuiltPathBinCPUData {
     /// for tile columns above the viewport.
     pub backdrops: Vec<i32>,
     pub tiles: DenseTileMap<TileObjectPrimitive>,
-    pub clip_tiles: Option<DenseTileMap<Clip>>,
+    /// Jobs to combine clip masks into the masks of this path's tiles, if it's clipped.
+    pub clip_tiles: Option<Vec<Clip>>,
 }
 
 #[derive(Clone, Debug)]
@@ -141,9 +157,11 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         built_options: &'b PreparedBuildOptions,
         sink: &'c mut SceneSink<'d>,
     ) -> SceneBuilder<'a, 'b, 'c, 'd> {
+        let view_boxes = PathViewBoxes::new(scene, built_options);
         SceneBuilder {
             scene,
             built_options,
+            view_boxes,
             next_alpha_tile_indices: [AtomicUsize::new(0), AtomicUsize::new(0)],
             sink,
         }
@@ -153,6 +171,7 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
     where
         E: Executor,
     {
+        trace_scope!("build_scene", draw_paths = self.scene.draw_paths().len());
         let start_time = Instant::now();
 
         // Send the start rendering command.
@@ -163,11 +182,15 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         let total_path_count = clip_path_count + draw_path_count;
 
         let needs_readable_framebuffer = self.needs_readable_framebuffer();
+        let path_depth_planes = self.build_path_depth_planes();
+        let uses_path_depth = !path_depth_planes.is_empty();
 
         self.sink.listener.send(RenderCommand::Start {
             bounding_quad,
             path_count: total_path_count,
             needs_readable_framebuffer,
+            dirty_rect: self.built_options.dirty_rect,
+            path_depth_planes,
         });
 
         let prepare_mode = self.built_options.to_prepare_mode(self.sink.renderer_level);
@@ -188,13 +211,21 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             self.sink.listener.send(render_command);
         }
 
-        let built_paths = match prepare_mode {
+        let mut built_paths = match prepare_mode {
             PrepareMode::CPU | PrepareMode::TransformCPUBinGPU => {
                 Some(self.build_paths_on_cpu(executor, &paint_metadata, &prepare_mode))
             }
             PrepareMode::GPU { .. } => None,
         };
 
+        // Occlusion culling assumes that later paths are in front of earlier ones, which isn't
+        // true when the depth buffer orders paths.
+        if let (true, Some(ref mut built_paths)) = (uses_path_depth, &mut built_paths) {
+            for built_draw_path in &mut built_paths.draw {
+                built_draw_path.occludes = false;
+            }
+        }
+
         // TODO(pcwalton): Do this earlier?
         let scene_is_dirty = match (&prepare_mode, &self.sink.last_scene) {
             (&PrepareMode::GPU { .. }, &None) => true,
@@ -240,14 +271,20 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
     where
         E: Executor,
     {
+        trace_scope!("tile_paths");
         let clip_path_count = self.scene.clip_paths().len();
         let draw_path_count = self.scene.draw_paths().len();
         let effective_view_box = self.scene.effective_view_box(self.built_options);
 
+        if let Some(ref mut tile_cache) = self.sink.tile_cache {
+            let key = TileCacheKey::new(self.scene.id(), effective_view_box, self.built_options);
+            tile_cache.prepare(key, draw_path_count);
+        }
+
         let built_clip_paths = executor.build_vector(clip_path_count, |path_index| {
             self.build_clip_path_on_cpu(PathBuildParams {
                 path_id: PathId(path_index as u32),
-                view_box: effective_view_box,
+                view_box: self.view_boxes.clip[path_index],
                 prepare_mode: *prepare_mode,
                 built_options: &self.built_options,
                 scene: &self.scene,
@@ -258,7 +295,7 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             self.build_draw_path_on_cpu(DrawPathBuildParams {
                 path_build_params: PathBuildParams {
                     path_id: PathId(path_index as u32),
-                    view_box: effective_view_box,
+                    view_box: self.view_boxes.draw[path_index],
                     prepare_mode: *prepare_mode,
                     built_options: &self.built_options,
                     scene: &self.scene,
@@ -268,9 +305,19 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             })
         });
 
-        BuiltPaths {
-            draw: built_draw_paths,
+        let mut draw = Vec::with_capacity(built_draw_paths.len());
+        for (path_index, (built_draw_path, cached_draw_path)) in
+            built_draw_paths.into_iter().enumerate()
+        {
+            if let (Some(tile_cache), Some(cached_draw_path)) =
+                (self.sink.tile_cache.as_mut(), cached_draw_path)
+            {
+                tile_cache.insert(DrawPathId(path_index as u32), cached_draw_path);
+            }
+            draw.push(built_draw_path);
         }
+
+        BuiltPaths { draw }
     }
 
     fn build_clip_path_on_cpu(&self, params: PathBuildParams) -> BuiltPath {
@@ -282,7 +329,12 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             prepare_mode,
         } = params;
         let path_object = &scene.get_clip_path(path_id.to_clip_path_id());
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
+        let outline = scene.apply_render_options(
+            path_object.outline(),
+            &Transform2F::default(),
+            0.0,
+            built_options,
+        );
 
         let mut tiler = Tiler::new(
             self,
@@ -301,7 +353,11 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         tiler.object_builder.built_path
     }
 
-    fn build_draw_path_on_cpu(&self, params: DrawPathBuildParams) -> BuiltDrawPath {
+    // Returns the built path, plus the tiling results to cache if they weren't already cached.
+    fn build_draw_path_on_cpu(
+        &self,
+        params: DrawPathBuildParams,
+    ) -> (BuiltDrawPath, Option<CachedDrawPath>) {
         let DrawPathBuildParams {
             path_build_params:
                 PathBuildParams {
@@ -315,12 +371,37 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             built_clip_paths,
         } = params;
 
-        let path_object = scene.get_draw_path(path_id.to_draw_path_id());
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
-
+        let draw_path_id = path_id.to_draw_path_id();
+        let path_object = scene.get_draw_path(draw_path_id);
         let paint_id = path_object.paint();
         let paint_metadata = &paint_metadata[paint_id.0 as usize];
 
+        // Clipped paths depend on their clip paths' tiles, so they're never cached.
+        let tile_cache = match self.sink.tile_cache {
+            Some(ref tile_cache) if tile_cache.is_active() && path_object.clip_path().is_none() => {
+                Some(tile_cache)
+            }
+            _ => None,
+        };
+        let epoch = scene.draw_path_epoch(draw_path_id);
+
+        if let Some(cached_draw_path) = tile_cache.and_then(|cache| cache.get(draw_path_id, epoch))
+        {
+            let (built_path, fills) = cached_draw_path.instantiate(&self.next_alpha_tile_indices);
+            self.send_fills(fills);
+            return (
+                BuiltDrawPath::new(built_path, path_object, paint_metadata),
+                None,
+            );
+        }
+
+        let outline = scene.apply_render_options(
+            path_object.outline(),
+            &path_object.transform(),
+            path_object.depth(),
+            built_options,
+        );
+
         let mut tiler = Tiler::new(
             self,
             path_id,
@@ -332,15 +413,23 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             &built_clip_paths,
             TilingPathInfo::Draw(DrawTilingPathInfo {
                 paint_id,
-                blend_mode: path_object.blend_mode(),
+                blend_mode: path_object.effective_blend_mode(),
                 fill_rule: path_object.fill_rule(),
+                subpixel_coverage: path_object.subpixel_coverage(),
             }),
         );
 
         tiler.generate_tiles();
-        self.send_fills(tiler.object_builder.fills);
 
-        BuiltDrawPath::new(tiler.object_builder.built_path, path_object, paint_metadata)
+        let ObjectBuilder {
+            built_path, fills, ..
+        } = tiler.object_builder;
+        let cached_draw_path =
+            tile_cache.and_then(|_| CachedDrawPath::new(epoch, &built_path, &fills));
+        self.send_fills(fills);
+
+        let built_draw_path = BuiltDrawPath::new(built_path, path_object, paint_metadata);
+        (built_draw_path, cached_draw_path)
     }
 
     fn send_fills(&self, fills: Vec<Fill>) {
@@ -355,22 +444,32 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         prepare_mode: &PrepareMode,
         built_paths: Option<BuiltPaths>,
     ) {
+        trace_scope!("build_tile_batches");
         let mut tile_batch_builder = TileBatchBuilder::new(built_paths);
 
         // Prepare display items.
         for display_item in self.scene.display_list() {
             match *display_item {
-                DisplayItem::PushRenderTarget(render_target_id) => tile_batch_builder
-                    .draw_commands
-                    .push(RenderCommand::PushRenderTarget(render_target_id)),
-                DisplayItem::PopRenderTarget => tile_batch_builder
-                    .draw_commands
-                    .push(RenderCommand::PopRenderTarget),
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    let size = self.scene.get_render_target(render_target_id).size();
+                    tile_batch_builder
+                        .render_target_rects
+                        .push(RectF::new(Vector2F::zero(), size.to_f32()));
+                    tile_batch_builder
+                        .draw_commands
+                        .push(RenderCommand::PushRenderTarget(render_target_id))
+                }
+                DisplayItem::PopRenderTarget => {
+                    tile_batch_builder.render_target_rects.pop();
+                    tile_batch_builder
+                        .draw_commands
+                        .push(RenderCommand::PopRenderTarget)
+                }
                 DisplayItem::DrawPaths(ref path_id_range) => {
                     tile_batch_builder.build_tile_batches_for_draw_path_display_item(
                         &self.scene,
                         &self.sink,
-                        self.built_options,
+                        &self.view_boxes,
                         path_id_range.start..path_id_range.end,
                         paint_metadata,
                         prepare_mode,
@@ -397,6 +496,36 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         self.build_tile_batches(paint_metadata, prepare_mode, built_paths);
     }
 
+    // Paths are only ordered by depth at the D3D9 level, and only if some path is off the scene
+    // plane. Otherwise, this returns an empty list, and paths are drawn in painter's order.
+    fn build_path_depth_planes(&self) -> Vec<PathDepthPlane> {
+        if self.sink.renderer_level != RendererLevel::D3D9
+            || self
+                .scene
+                .draw_paths()
+                .iter()
+                .all(|draw_path| draw_path.depth() == 0.0)
+        {
+            return vec![];
+        }
+
+        let mut last_plane: Option<(f32, PathDepthPlane)> = None;
+        let mut planes = Vec::with_capacity(self.scene.draw_paths().len());
+        for draw_path in self.scene.draw_paths() {
+            let depth = draw_path.depth();
+            let plane = match last_plane {
+                Some((last_depth, plane)) if last_depth == depth => plane,
+                _ => match self.built_options.transform.depth_plane_at_depth(depth) {
+                    None => return vec![],
+                    Some(plane) => plane,
+                },
+            };
+            last_plane = Some((depth, plane));
+            planes.push(plane);
+        }
+        planes
+    }
+
     fn needs_readable_framebuffer(&self) -> bool {
         let mut framebuffer_nesting = 0;
         for display_item in self.scene.display_list() {
@@ -409,7 +538,10 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                     }
                     for draw_path_id in draw_path_id_range.start.0..draw_path_id_range.end.0 {
                         let draw_path_id = DrawPathId(draw_path_id);
-                        let blend_mode = self.scene.get_draw_path(draw_path_id).blend_mode();
+                        let blend_mode = self
+                            .scene
+                            .get_draw_path(draw_path_id)
+                            .effective_blend_mode();
                         if blend_mode.needs_readable_framebuffer() {
                             return true;
                         }
@@ -439,6 +571,57 @@ struct DrawPathBuildParams<'a> {
     built_clip_paths: &'a [BuiltPath],
 }
 
+impl PathViewBoxes {
+    fn new(scene: &Scene, built_options: &PreparedBuildOptions) -> PathViewBoxes {
+        let effective_view_box = scene.effective_view_box(built_options);
+        let mut draw = vec![effective_view_box; scene.draw_paths().len()];
+        let mut render_target_stack = vec![];
+        for display_item in scene.display_list() {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    let size = scene.get_render_target(render_target_id).size().to_f32();
+                    let view_box = RectF::new(Vector2F::zero(), size);
+                    render_target_stack.push(if built_options.subpixel_aa_enabled {
+                        view_box * vec2f(3.0, 1.0)
+                    } else {
+                        view_box
+                    });
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths(ref draw_path_id_range) => {
+                    if let Some(&view_box) = render_target_stack.last() {
+                        for draw_path_id in draw_path_id_range.start.0..draw_path_id_range.end.0 {
+                            draw[draw_path_id as usize] = view_box;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Clip paths that no draw path uses are tiled against the view box.
+        let mut clip: Vec<Option<RectF>> = vec![None; scene.clip_paths().len()];
+        for (draw_path, &view_box) in scene.draw_paths().iter().zip(draw.iter()) {
+            let mut clip_path_id = draw_path.clip_path();
+            while let Some(id) = clip_path_id {
+                let clip_view_box = &mut clip[id.0 as usize];
+                *clip_view_box = Some(match *clip_view_box {
+                    None => view_box,
+                    Some(clip_view_box) => clip_view_box.union_rect(view_box),
+                });
+                clip_path_id = scene.get_clip_path(id).clip_path();
+            }
+        }
+        let clip = clip
+            .into_iter()
+            .map(|view_box| view_box.unwrap_or(effective_view_box))
+            .collect();
+
+        PathViewBoxes { draw, clip }
+    }
+}
+
 impl BuiltPath {
     fn new(
         path_id: PathId,
@@ -480,17 +663,7 @@ impl BuiltPath {
                     tile_bounds,
                 ),
                 clip_tiles: match *tiling_path_info {
-                    TilingPathInfo::Draw(_) if clip_path_id.is_some() => {
-                        Some(DenseTileMap::from_builder(
-                            |_| Clip {
-                                dest_tile_id: AlphaTileId(!0),
-                                dest_backdrop: 0,
-                                src_tile_id: AlphaTileId(!0),
-                                src_backdrop: 0,
-                            },
-                            tile_bounds,
-                        ))
-                    }
+                    TilingPathInfo::Draw(_) if clip_path_id.is_some() => Some(vec![]),
                     _ => None,
                 },
             }),
@@ -800,7 +973,7 @@ impl TileBatchDataD3D11 {
                 }) => src_tiles,
                 _ => panic!("Clip tiles weren't computed on CPU!"),
             };
-            dest_clips.extend_from_slice(&src_tiles.data);
+            dest_clips.extend_from_slice(src_tiles);
         }
 
         batch_path_index
@@ -830,6 +1003,7 @@ struct BuiltSegments {
 
 impl BuiltSegments {
     fn from_scene(scene: &Scene) -> BuiltSegments {
+        trace_scope!("build_segments");
         let mut built_segments = BuiltSegments {
             draw_segments: SegmentsD3D11::new(),
             clip_segments: SegmentsD3D11::new(),
@@ -838,11 +1012,15 @@ impl BuiltSegments {
         };
 
         for clip_path in scene.clip_paths() {
-            let range = built_segments.clip_segments.add_path(clip_path.outline());
+            let range = built_segments
+                .clip_segments
+                .add_path(clip_path.outline(), &Transform2F::default());
             built_segments.clip_segment_ranges.push(range);
         }
         for draw_path in scene.draw_paths() {
-            let range = built_segments.draw_segments.add_path(draw_path.outline());
+            let range = built_segments
+                .draw_segments
+                .add_path(draw_path.outline(), &draw_path.transform());
             built_segments.draw_segment_ranges.push(range);
         }
 
@@ -858,7 +1036,7 @@ impl SegmentsD3D11 {
         }
     }
 
-    fn add_path(&mut self, outline: &Outline) -> Range<u32> {
+    fn add_path(&mut self, outline: &Outline, transform: &Transform2F) -> Range<u32> {
         let first_segment_index = self.indices.len() as u32;
         for contour in outline.contours() {
             let point_count = contour.len() as u32;
@@ -892,7 +1070,8 @@ impl SegmentsD3D11 {
                     });
                 }
 
-                self.points.push(contour.position_of(point_index));
+                self.points
+                    .push(*transform * contour.position_of(point_index));
             }
 
             self.points.push(contour.position_of(0));
@@ -909,6 +1088,8 @@ struct TileBatchBuilder {
     clip_batches_d3d11: Option<ClipBatchesD3D11>,
     next_batch_id: TileBatchId,
     level: TileBatchBuilderLevel,
+    // The bounds of each render target on the stack.
+    render_target_rects: Vec<RectF>,
 }
 
 enum TileBatchBuilderLevel {
@@ -933,6 +1114,7 @@ impl TileBatchBuilder {
                 None => TileBatchBuilderLevel::D3D11,
                 Some(built_paths) => TileBatchBuilderLevel::D3D9 { built_paths },
             },
+            render_target_rects: vec![],
         }
     }
 
@@ -940,7 +1122,7 @@ impl TileBatchBuilder {
         &mut self,
         scene: &Scene,
         sink: &SceneSink,
-        built_options: &PreparedBuildOptions,
+        view_boxes: &PathViewBoxes,
         draw_path_id_range: Range<DrawPathId>,
         paint_metadata: &[PaintMetadata],
         prepare_mode: &PrepareMode,
@@ -952,7 +1134,7 @@ impl TileBatchBuilder {
                 TileBatchBuilderLevel::D3D11 { .. } => {
                     match self.prepare_draw_path_for_gpu_binning(
                         scene,
-                        built_options,
+                        view_boxes,
                         draw_path_id,
                         prepare_mode,
                         paint_metadata,
@@ -975,10 +1157,12 @@ impl TileBatchBuilder {
                     )
                 }
                 Some(DrawTileBatch::D3D9(ref mut existing_batch)) => {
-                    !fixup_batch_for_new_path_if_possible(
-                        &mut existing_batch.color_texture,
-                        &draw_path,
-                    )
+                    // Subpixel coverage needs a different blend state, so it gets its own batch.
+                    existing_batch.subpixel_coverage != draw_path.subpixel_coverage
+                        || !fixup_batch_for_new_path_if_possible(
+                            &mut existing_batch.color_texture,
+                            &draw_path,
+                        )
                 }
                 None => false,
             };
@@ -1002,7 +1186,11 @@ impl TileBatchBuilder {
             if draw_tile_batch.is_none() {
                 draw_tile_batch = match self.level {
                     TileBatchBuilderLevel::D3D9 { .. } => {
-                        let tile_bounds = tiles::round_rect_out_to_tile_bounds(scene.view_box());
+                        let target_rect = match self.render_target_rects.last() {
+                            Some(&render_target_rect) => render_target_rect,
+                            None => scene.view_box(),
+                        };
+                        let tile_bounds = tiles::round_rect_out_to_tile_bounds(target_rect);
                         Some(DrawTileBatch::D3D9(DrawTileBatchD3D9 {
                             tiles: vec![],
                             clips: vec![],
@@ -1010,6 +1198,7 @@ impl TileBatchBuilder {
                             color_texture: draw_path.color_texture,
                             filter: draw_path.filter,
                             blend_mode: draw_path.blend_mode,
+                            subpixel_coverage: draw_path.subpixel_coverage,
                         }))
                     }
                     TileBatchBuilderLevel::D3D11 { .. } => {
@@ -1032,7 +1221,7 @@ impl TileBatchBuilder {
                 Some(ref mut clip_batches_d3d11) => add_clip_path_to_batch(
                     scene,
                     sink,
-                    built_options,
+                    view_boxes,
                     draw_path.clip_path_id,
                     prepare_mode,
                     0,
@@ -1087,13 +1276,7 @@ impl TileBatchBuilder {
                         None => continue,
                         Some(ref clip_tiles) => clip_tiles,
                     };
-                    for clip_tile in &clip_tiles.data {
-                        if clip_tile.dest_tile_id != AlphaTileId(!0)
-                            && clip_tile.src_tile_id != AlphaTileId(!0)
-                        {
-                            draw_tile_batch.clips.push(*clip_tile);
-                        }
-                    }
+                    draw_tile_batch.clips.extend_from_slice(clip_tiles);
                 }
             }
         }
@@ -1114,7 +1297,7 @@ impl TileBatchBuilder {
     fn prepare_draw_path_for_gpu_binning(
         &self,
         scene: &Scene,
-        built_options: &PreparedBuildOptions,
+        view_boxes: &PathViewBoxes,
         draw_path_id: DrawPathId,
         prepare_mode: &PrepareMode,
         paint_metadata: &[PaintMetadata],
@@ -1126,11 +1309,11 @@ impl TileBatchBuilder {
             }
         };
 
-        let effective_view_box = scene.effective_view_box(built_options);
+        let view_box = view_boxes.draw[draw_path_id.0 as usize];
         let draw_path = scene.get_draw_path(draw_path_id);
 
-        let mut path_bounds = transform * draw_path.outline().bounds();
-        match path_bounds.intersection(effective_view_box) {
+        let mut path_bounds = transform * draw_path.bounds();
+        match path_bounds.intersection(view_box) {
             Some(intersection) => path_bounds = intersection,
             None => return None,
         }
@@ -1140,14 +1323,15 @@ impl TileBatchBuilder {
         let built_path = BuiltPath::new(
             draw_path_id.to_path_id(),
             path_bounds,
-            effective_view_box,
+            view_box,
             draw_path.fill_rule(),
             &prepare_mode,
             draw_path.clip_path(),
             &TilingPathInfo::Draw(DrawTilingPathInfo {
                 paint_id,
-                blend_mode: draw_path.blend_mode(),
+                blend_mode: draw_path.effective_blend_mode(),
                 fill_rule: draw_path.fill_rule(),
+                subpixel_coverage: draw_path.subpixel_coverage(),
             }),
         );
         Some(BuiltDrawPath::new(built_path, draw_path, paint_metadata))
@@ -1181,7 +1365,7 @@ struct ClipBatchesD3D11 {
 fn add_clip_path_to_batch(
     scene: &Scene,
     sink: &SceneSink,
-    built_options: &PreparedBuildOptions,
+    view_boxes: &PathViewBoxes,
     clip_path_id: Option<ClipPathId>,
     prepare_mode: &PrepareMode,
     clip_level: usize,
@@ -1205,7 +1389,7 @@ fn add_clip_path_to_batch(
                     } = prepare_clip_path_for_gpu_binning(
                         scene,
                         sink,
-                        built_options,
+                        view_boxes,
                         clip_path_id,
                         prepare_mode,
                         clip_level,
@@ -1246,7 +1430,7 @@ fn add_clip_path_to_batch(
 fn prepare_clip_path_for_gpu_binning(
     scene: &Scene,
     sink: &SceneSink,
-    built_options: &PreparedBuildOptions,
+    view_boxes: &PathViewBoxes,
     clip_path_id: ClipPathId,
     prepare_mode: &PrepareMode,
     clip_level: usize,
@@ -1258,14 +1442,13 @@ fn prepare_clip_path_for_gpu_binning(
             panic!("`prepare_clip_path_for_gpu_binning()` requires a GPU prepare mode!")
         }
     };
-    let effective_view_box = scene.effective_view_box(built_options);
     let clip_path = scene.get_clip_path(clip_path_id);
 
     // Add subclip path if necessary.
     let subclip_id = add_clip_path_to_batch(
         scene,
         sink,
-        built_options,
+        view_boxes,
         clip_path.clip_path(),
         prepare_mode,
         clip_level + 1,
@@ -1279,7 +1462,7 @@ fn prepare_clip_path_for_gpu_binning(
     let built_path = BuiltPath::new(
         clip_path_id.to_path_id(),
         path_bounds,
-        effective_view_box,
+        view_boxes.clip[clip_path_id.0 as usize],
         clip_path.fill_rule(),
         &prepare_mode,
         clip_path.clip_path(),
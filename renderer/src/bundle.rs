@@ -0,0 +1,1501 @@
+// pathfinder/renderer/src/bundle.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scene bundles: built scenes saved to disk, so that they can be rendered again without being
+//! rebuilt.
+//!
+//! A bundle holds the render commands of one frame, as produced by `SceneProxy::finish_build()`:
+//! the fills, tiles, and clips of every path, along with texture uploads and paint metadata. A
+//! viewer that shows the same huge document on every launch can save a build with
+//! `BuiltFrame::write_bundle()` and load it next time with `BuiltFrame::read_bundle()`, skipping
+//! tiling entirely:
+//!
+//! ```norun
+//! let key = SceneBundleKey::new(document_hash, view_box, &build_options, renderer_level);
+//! let path = cache_dir.join(key.file_name());
+//! let frame = match File::open(&path).map_err(BundleError::from) {
+//!     Ok(file) => BuiltFrame::read_bundle(&key, file)?,
+//!     Err(_) => {
+//!         let handle = scene_proxy.begin_bundle_build(build_options);
+//!         let frame = scene_proxy.finish_build(handle);
+//!         frame.write_bundle(&key, File::create(&path)?)?;
+//!         frame
+//!     }
+//! };
+//! frame.render(&mut renderer);
+//! ```
+//!
+//! Ordinary builds only upload the textures and scene data that changed since the previous frame,
+//! so a bundle must come from `SceneProxy::begin_bundle_build()`, which builds the frame as if it
+//! were the first.
+//!
+//! Bundles are meant as a local cache. The format changes between versions of Pathfinder, in
+//! which case reading fails with `BundleError::UnsupportedFormat` and the scene must be rebuilt.
+
+use crate::gpu::options::RendererLevel;
+use crate::gpu_data::{AlphaTileId, BackdropInfoD3D11, Clip, ClippedPathInfo, ColorCombineMode};
+use crate::gpu_data::{DiceMetadataD3D11, DrawTileBatchD3D11, DrawTileBatchD3D9, Fill, PathSource};
+use crate::gpu_data::{PathBatchIndex, PathDepthPlane, PrepareTilesInfoD3D11};
+use crate::gpu_data::{PropagateMetadataD3D11, RenderCommand, SegmentIndicesD3D11, SegmentsD3D11};
+use crate::gpu_data::{TextureLocation, TextureMetadataEntry, TexturePageDescriptor};
+use crate::gpu_data::{TexturePageId, TileBatchDataD3D11, TileBatchId, TileBatchTexture};
+use crate::gpu_data::{TileObjectPrimitive, TilePathInfoD3D11};
+use crate::options::{BuildOptions, RenderTransform};
+use crate::paint::PaintCompositeOp;
+use crate::scene::PathId;
+use crate::tile_map::DenseTileMap;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use fxhash::FxHasher64;
+use instant::Instant;
+use pathfinder_color::matrix::ColorMatrix;
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::PatternFilter;
+use pathfinder_content::effects::{BlendMode, BlurDirection, DefringingKernel, Filter};
+use pathfinder_content::noise::{Noise, NoiseKind};
+use pathfinder_content::paint_effect::{PaintEffect, PaintEffectKind};
+use pathfinder_content::pattern::ExternalTextureId;
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F};
+use pathfinder_gpu::TextureSamplingFlags;
+use pathfinder_simd::default::{F32x2, F32x4, I32x2, I32x4};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::hash::Hasher;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAGIC: [u8; 4] = *b"PFSB";
+
+// Increment this whenever the encoding of any render command changes.
+const VERSION: u32 = 1;
+
+// Lengths read from a corrupt bundle could be huge, so vectors grow as elements are actually
+// read, beyond this many.
+const MAX_PREALLOCATED_LENGTH: usize = 65536;
+
+/// Identifies a built scene, so that a bundle is only loaded for the scene and view it was built
+/// for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SceneBundleKey {
+    scene_hash: u64,
+    options_hash: u64,
+}
+
+/// Errors that can occur when reading or writing a scene bundle.
+#[derive(Debug)]
+pub enum BundleError {
+    /// The bundle couldn't be read or written.
+    Io(io::Error),
+    /// The data isn't a scene bundle, or it was written by an incompatible version of Pathfinder.
+    UnsupportedFormat,
+    /// The bundle was built from a different scene, or with different options.
+    KeyMismatch,
+    /// The bundle is corrupt.
+    Malformed,
+    /// The frame depends on textures uploaded by earlier frames, so it can't be replayed on its
+    /// own. Build it with `SceneProxy::begin_bundle_build()` instead.
+    NotSelfContained,
+}
+
+impl SceneBundleKey {
+    /// Creates the key for a scene built with the given options.
+    ///
+    /// `scene_hash` identifies the contents of the scene. Pathfinder doesn't compute it, so that a
+    /// viewer can look up a bundle before it has even loaded the scene: a hash of the document
+    /// that the scene comes from is a good choice. Everything else that affects the built scene,
+    /// including the zoom level and the renderer level, is taken from the other arguments.
+    pub fn new(
+        scene_hash: u64,
+        view_box: RectF,
+        options: &BuildOptions,
+        renderer_level: RendererLevel,
+    ) -> SceneBundleKey {
+        let mut hasher = FxHasher64::default();
+        hash_f32s(&mut hasher, &[view_box.min_x(), view_box.min_y()]);
+        hash_f32s(&mut hasher, &[view_box.max_x(), view_box.max_y()]);
+        match options.transform {
+            RenderTransform::Transform2D(transform) => {
+                hasher.write_u8(0);
+                hash_f32s(
+                    &mut hasher,
+                    &[transform.m11(), transform.m12(), transform.m13()],
+                );
+                hash_f32s(
+                    &mut hasher,
+                    &[transform.m21(), transform.m22(), transform.m23()],
+                );
+            }
+            RenderTransform::Perspective(ref perspective) => {
+                hasher.write_u8(1);
+                let transform = &perspective.transform;
+                for column in &[transform.c0, transform.c1, transform.c2, transform.c3] {
+                    hash_f32s(&mut hasher, &[column[0], column[1], column[2], column[3]]);
+                }
+                hasher.write_i32(perspective.window_size.x());
+                hasher.write_i32(perspective.window_size.y());
+            }
+        }
+        hash_f32s(&mut hasher, &[options.dilation.x(), options.dilation.y()]);
+        hasher.write_u8(options.subpixel_aa_enabled as u8);
+        match options.dirty_rect {
+            None => hasher.write_u8(0),
+            Some(rect) => {
+                hasher.write_u8(1);
+                hash_f32s(
+                    &mut hasher,
+                    &[rect.min_x(), rect.min_y(), rect.max_x(), rect.max_y()],
+                );
+            }
+        }
+        hasher.write_u8(match renderer_level {
+            RendererLevel::D3D9 => 0,
+            RendererLevel::D3D11 => 1,
+        });
+
+        SceneBundleKey {
+            scene_hash,
+            options_hash: hasher.finish(),
+        }
+    }
+
+    /// Returns a file name that's unique to this key, for storing bundles in a cache directory.
+    #[inline]
+    pub fn file_name(&self) -> String {
+        format!(
+            "{:016x}-{:016x}.pfbundle",
+            self.scene_hash, self.options_hash
+        )
+    }
+}
+
+fn hash_f32s(hasher: &mut FxHasher64, values: &[f32]) {
+    for value in values {
+        hasher.write_u32(value.to_bits());
+    }
+}
+
+pub(crate) fn write_commands<W>(
+    key: &SceneBundleKey,
+    commands: &[RenderCommand],
+    writer: W,
+) -> Result<(), BundleError>
+where
+    W: Write,
+{
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(&MAGIC)?;
+    VERSION.encode(&mut writer)?;
+    key.scene_hash.encode(&mut writer)?;
+    key.options_hash.encode(&mut writer)?;
+    commands.len().encode(&mut writer)?;
+    for command in commands {
+        command.encode(&mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn read_commands<R>(
+    key: &SceneBundleKey,
+    reader: R,
+) -> Result<Vec<RenderCommand>, BundleError>
+where
+    R: Read,
+{
+    let start_time = Instant::now();
+    let mut reader = BufReader::new(reader);
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC || u32::decode(&mut reader)? != VERSION {
+        return Err(BundleError::UnsupportedFormat);
+    }
+    let scene_hash = u64::decode(&mut reader)?;
+    let options_hash = u64::decode(&mut reader)?;
+    if scene_hash != key.scene_hash || options_hash != key.options_hash {
+        return Err(BundleError::KeyMismatch);
+    }
+
+    let mut commands: Vec<RenderCommand> = Decode::decode(&mut reader)?;
+
+    // Report the time it took to load the frame as its build time, for the debug UI.
+    if let Some(RenderCommand::Finish {
+        ref mut cpu_build_time,
+    }) = commands.last_mut()
+    {
+        *cpu_build_time = start_time.elapsed();
+    }
+
+    Ok(commands)
+}
+
+trait Encode {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write;
+}
+
+trait Decode: Sized {
+    fn decode<R>(reader: &mut R) -> Result<Self, BundleError>
+    where
+        R: Read;
+}
+
+macro_rules! impl_codec_for_number {
+    ($ty:ty, $write:ident, $read:ident) => {
+        impl Encode for $ty {
+            #[inline]
+            fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+            where
+                W: Write,
+            {
+                writer.$write::<LittleEndian>(*self)
+            }
+        }
+
+        impl Decode for $ty {
+            #[inline]
+            fn decode<R>(reader: &mut R) -> Result<$ty, BundleError>
+            where
+                R: Read,
+            {
+                Ok(reader.$read::<LittleEndian>()?)
+            }
+        }
+    };
+}
+
+impl_codec_for_number!(u16, write_u16, read_u16);
+impl_codec_for_number!(i16, write_i16, read_i16);
+impl_codec_for_number!(u32, write_u32, read_u32);
+impl_codec_for_number!(i32, write_i32, read_i32);
+impl_codec_for_number!(u64, write_u64, read_u64);
+impl_codec_for_number!(f32, write_f32, read_f32);
+
+// Encodes a struct as its fields, in order.
+macro_rules! impl_codec_for_struct {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl Encode for $name {
+            fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+            where
+                W: Write,
+            {
+                $(self.$field.encode(writer)?;)*
+                Ok(())
+            }
+        }
+
+        impl Decode for $name {
+            fn decode<R>(reader: &mut R) -> Result<$name, BundleError>
+            where
+                R: Read,
+            {
+                Ok($name {
+                    $($field: Decode::decode(reader)?,)*
+                })
+            }
+        }
+    };
+}
+
+// Encodes a tuple struct with one field as that field.
+macro_rules! impl_codec_for_newtype {
+    ($name:ident) => {
+        impl Encode for $name {
+            #[inline]
+            fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+            where
+                W: Write,
+            {
+                self.0.encode(writer)
+            }
+        }
+
+        impl Decode for $name {
+            #[inline]
+            fn decode<R>(reader: &mut R) -> Result<$name, BundleError>
+            where
+                R: Read,
+            {
+                Ok($name(Decode::decode(reader)?))
+            }
+        }
+    };
+}
+
+// Encodes an enum without fields as the index of its variant.
+macro_rules! impl_codec_for_fieldless_enum {
+    ($name:ident { $($variant:ident),* $(,)? }) => {
+        impl Encode for $name {
+            fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+            where
+                W: Write,
+            {
+                const VARIANTS: &[$name] = &[$($name::$variant),*];
+                let index = VARIANTS
+                    .iter()
+                    .position(|variant| mem::discriminant(variant) == mem::discriminant(self))
+                    .unwrap();
+                writer.write_u8(index as u8)
+            }
+        }
+
+        impl Decode for $name {
+            fn decode<R>(reader: &mut R) -> Result<$name, BundleError>
+            where
+                R: Read,
+            {
+                const VARIANTS: &[$name] = &[$($name::$variant),*];
+                VARIANTS
+                    .get(reader.read_u8()? as usize)
+                    .cloned()
+                    .ok_or(BundleError::Malformed)
+            }
+        }
+    };
+}
+
+impl Encode for u8 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_u8(*self)
+    }
+}
+
+impl Decode for u8 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<u8, BundleError>
+    where
+        R: Read,
+    {
+        Ok(reader.read_u8()?)
+    }
+}
+
+impl Encode for i8 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_i8(*self)
+    }
+}
+
+impl Decode for i8 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<i8, BundleError>
+    where
+        R: Read,
+    {
+        Ok(reader.read_i8()?)
+    }
+}
+
+impl Encode for bool {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_u8(*self as u8)
+    }
+}
+
+impl Decode for bool {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<bool, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl Encode for usize {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        (*self as u64).encode(writer)
+    }
+}
+
+impl Decode for usize {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<usize, BundleError>
+    where
+        R: Read,
+    {
+        let value = u64::decode(reader)?;
+        if value > usize::MAX as u64 {
+            return Err(BundleError::Malformed);
+        }
+        Ok(value as usize)
+    }
+}
+
+impl Encode for Duration {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.as_secs().encode(writer)?;
+        self.subsec_nanos().encode(writer)
+    }
+}
+
+impl Decode for Duration {
+    fn decode<R>(reader: &mut R) -> Result<Duration, BundleError>
+    where
+        R: Read,
+    {
+        let secs = u64::decode(reader)?;
+        let nanos = u32::decode(reader)?;
+        Ok(Duration::new(secs, nanos))
+    }
+}
+
+impl<T> Encode for Option<T>
+where
+    T: Encode,
+{
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match *self {
+            None => writer.write_u8(0),
+            Some(ref value) => {
+                writer.write_u8(1)?;
+                value.encode(writer)
+            }
+        }
+    }
+}
+
+impl<T> Decode for Option<T>
+where
+    T: Decode,
+{
+    fn decode<R>(reader: &mut R) -> Result<Option<T>, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(reader)?)),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl<T> Encode for Vec<T>
+where
+    T: Encode,
+{
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.len().encode(writer)?;
+        for element in self {
+            element.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for Vec<T>
+where
+    T: Decode,
+{
+    fn decode<R>(reader: &mut R) -> Result<Vec<T>, BundleError>
+    where
+        R: Read,
+    {
+        let length = usize::decode(reader)?;
+        let mut elements = Vec::with_capacity(length.min(MAX_PREALLOCATED_LENGTH));
+        for _ in 0..length {
+            elements.push(T::decode(reader)?);
+        }
+        Ok(elements)
+    }
+}
+
+impl<T> Encode for Arc<T>
+where
+    T: Encode,
+{
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        (**self).encode(writer)
+    }
+}
+
+impl<T> Decode for Arc<T>
+where
+    T: Decode,
+{
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<Arc<T>, BundleError>
+    where
+        R: Read,
+    {
+        Ok(Arc::new(T::decode(reader)?))
+    }
+}
+
+impl<T> Encode for [T; 4]
+where
+    T: Encode,
+{
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        for element in self {
+            element.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for [T; 4]
+where
+    T: Decode,
+{
+    fn decode<R>(reader: &mut R) -> Result<[T; 4], BundleError>
+    where
+        R: Read,
+    {
+        Ok([
+            T::decode(reader)?,
+            T::decode(reader)?,
+            T::decode(reader)?,
+            T::decode(reader)?,
+        ])
+    }
+}
+
+impl<T> Encode for [T; 5]
+where
+    T: Encode,
+{
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        for element in self {
+            element.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for [T; 5]
+where
+    T: Decode,
+{
+    fn decode<R>(reader: &mut R) -> Result<[T; 5], BundleError>
+    where
+        R: Read,
+    {
+        Ok([
+            T::decode(reader)?,
+            T::decode(reader)?,
+            T::decode(reader)?,
+            T::decode(reader)?,
+            T::decode(reader)?,
+        ])
+    }
+}
+
+impl Encode for F32x2 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self[0].encode(writer)?;
+        self[1].encode(writer)
+    }
+}
+
+impl Decode for F32x2 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<F32x2, BundleError>
+    where
+        R: Read,
+    {
+        Ok(F32x2::new(f32::decode(reader)?, f32::decode(reader)?))
+    }
+}
+
+impl Encode for F32x4 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        [self[0], self[1], self[2], self[3]].encode(writer)
+    }
+}
+
+impl Decode for F32x4 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<F32x4, BundleError>
+    where
+        R: Read,
+    {
+        let [a, b, c, d] = <[f32; 4]>::decode(reader)?;
+        Ok(F32x4::new(a, b, c, d))
+    }
+}
+
+impl Encode for I32x2 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self[0].encode(writer)?;
+        self[1].encode(writer)
+    }
+}
+
+impl Decode for I32x2 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<I32x2, BundleError>
+    where
+        R: Read,
+    {
+        Ok(I32x2::new(i32::decode(reader)?, i32::decode(reader)?))
+    }
+}
+
+impl Encode for I32x4 {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        [self[0], self[1], self[2], self[3]].encode(writer)
+    }
+}
+
+impl Decode for I32x4 {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<I32x4, BundleError>
+    where
+        R: Read,
+    {
+        let [a, b, c, d] = <[i32; 4]>::decode(reader)?;
+        Ok(I32x4::new(a, b, c, d))
+    }
+}
+
+impl Encode for TextureSamplingFlags {
+    #[inline]
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_u8(self.bits())
+    }
+}
+
+impl Decode for TextureSamplingFlags {
+    #[inline]
+    fn decode<R>(reader: &mut R) -> Result<TextureSamplingFlags, BundleError>
+    where
+        R: Read,
+    {
+        TextureSamplingFlags::from_bits(reader.read_u8()?).ok_or(BundleError::Malformed)
+    }
+}
+
+impl Encode for DenseTileMap<i32> {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.data.encode(writer)?;
+        self.rect.encode(writer)
+    }
+}
+
+impl Decode for DenseTileMap<i32> {
+    fn decode<R>(reader: &mut R) -> Result<DenseTileMap<i32>, BundleError>
+    where
+        R: Read,
+    {
+        let data: Vec<i32> = Decode::decode(reader)?;
+        let rect = RectI::decode(reader)?;
+        if data.len() != rect.area() as usize {
+            return Err(BundleError::Malformed);
+        }
+        Ok(DenseTileMap { data, rect })
+    }
+}
+
+impl_codec_for_newtype!(AlphaTileId);
+impl_codec_for_newtype!(ColorF);
+impl_codec_for_newtype!(ColorMatrix);
+impl_codec_for_newtype!(DefringingKernel);
+impl_codec_for_newtype!(ExternalTextureId);
+impl_codec_for_newtype!(LineSegment2F);
+impl_codec_for_newtype!(Matrix2x2F);
+impl_codec_for_newtype!(PathBatchIndex);
+impl_codec_for_newtype!(PathId);
+impl_codec_for_newtype!(RectF);
+impl_codec_for_newtype!(RectI);
+impl_codec_for_newtype!(TexturePageId);
+impl_codec_for_newtype!(TileBatchId);
+impl_codec_for_newtype!(Vector2F);
+impl_codec_for_newtype!(Vector2I);
+impl_codec_for_newtype!(Vector4F);
+
+impl_codec_for_struct!(BackdropInfoD3D11 {
+    initial_backdrop,
+    tile_x_offset,
+    path_index,
+});
+impl_codec_for_struct!(Clip {
+    dest_tile_id,
+    dest_backdrop,
+    dest_ctrl,
+    pad0,
+    src_tile_id,
+    src_backdrop,
+    src_ctrl,
+    pad1,
+});
+impl_codec_for_struct!(ClippedPathInfo {
+    clip_batch_id,
+    clipped_path_count,
+    max_clipped_tile_count,
+    clips,
+});
+impl_codec_for_struct!(ColorU { r, g, b, a });
+impl_codec_for_struct!(DiceMetadataD3D11 {
+    global_path_id,
+    first_global_segment_index,
+    first_batch_segment_index,
+    pad,
+});
+impl_codec_for_struct!(DrawTileBatchD3D9 {
+    tiles,
+    clips,
+    z_buffer_data,
+    color_texture,
+    filter,
+    blend_mode,
+    subpixel_coverage,
+});
+impl_codec_for_struct!(DrawTileBatchD3D11 {
+    tile_batch_data,
+    color_texture,
+});
+impl_codec_for_struct!(Fill { line_segment, link });
+impl_codec_for_struct!(LineSegmentU16 {
+    from_x,
+    from_y,
+    to_x,
+    to_y,
+});
+impl_codec_for_struct!(Noise {
+    kind,
+    base_frequency,
+    num_octaves,
+    seed,
+    stitch_rect,
+    transform,
+});
+impl_codec_for_struct!(PaintEffect { kind, transform });
+impl_codec_for_struct!(PathDepthPlane {
+    x_coefficient,
+    y_coefficient,
+    constant,
+    pad,
+});
+impl_codec_for_struct!(PrepareTilesInfoD3D11 {
+    backdrops,
+    propagate_metadata,
+    dice_metadata,
+    tile_path_info,
+    transform,
+});
+impl_codec_for_struct!(PropagateMetadataD3D11 {
+    tile_rect,
+    tile_offset,
+    path_index,
+    z_write,
+    clip_path_index,
+    backdrop_offset,
+    pad0,
+    pad1,
+    pad2,
+});
+impl_codec_for_struct!(RenderTargetId {
+    scene,
+    render_target,
+});
+impl_codec_for_struct!(SegmentIndicesD3D11 {
+    first_point_index,
+    flags,
+});
+impl_codec_for_struct!(SegmentsD3D11 { points, indices });
+impl_codec_for_struct!(TextureLocation { page, rect });
+impl_codec_for_struct!(TextureMetadataEntry {
+    color_0_transform,
+    color_0_combine_mode,
+    base_color,
+    filter,
+    blend_mode,
+});
+impl_codec_for_struct!(TexturePageDescriptor { size });
+impl_codec_for_struct!(TileBatchDataD3D11 {
+    batch_id,
+    path_count,
+    tile_count,
+    segment_count,
+    prepare_info,
+    path_source,
+    clipped_path_info,
+});
+impl_codec_for_struct!(TileBatchTexture {
+    page,
+    sampling_flags,
+    composite_op,
+});
+impl_codec_for_struct!(TileObjectPrimitive {
+    tile_x,
+    tile_y,
+    alpha_tile_id,
+    path_id,
+    color,
+    ctrl,
+    backdrop,
+});
+impl_codec_for_struct!(TilePathInfoD3D11 {
+    tile_min_x,
+    tile_min_y,
+    tile_max_x,
+    tile_max_y,
+    first_tile_index,
+    color,
+    ctrl,
+    backdrop,
+});
+impl_codec_for_struct!(Transform2F { matrix, vector });
+
+impl_codec_for_fieldless_enum!(BlendMode {
+    Clear,
+    Copy,
+    SrcIn,
+    SrcOut,
+    SrcOver,
+    SrcAtop,
+    DestIn,
+    DestOut,
+    DestOver,
+    DestAtop,
+    Xor,
+    Lighter,
+    Darken,
+    Lighten,
+    Multiply,
+    Screen,
+    HardLight,
+    Overlay,
+    ColorDodge,
+    ColorBurn,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+});
+impl_codec_for_fieldless_enum!(BlurDirection { X, Y });
+impl_codec_for_fieldless_enum!(ColorCombineMode {
+    None,
+    SrcIn,
+    DestIn,
+});
+impl_codec_for_fieldless_enum!(NoiseKind {
+    FractalNoise,
+    Turbulence,
+});
+impl_codec_for_fieldless_enum!(PaintCompositeOp { SrcIn, DestIn });
+impl_codec_for_fieldless_enum!(PathSource { Draw, Clip });
+
+impl Encode for Filter {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match *self {
+            Filter::None => writer.write_u8(0),
+            Filter::RadialGradient {
+                line,
+                radii,
+                uv_origin,
+            } => {
+                writer.write_u8(1)?;
+                line.encode(writer)?;
+                radii.encode(writer)?;
+                uv_origin.encode(writer)
+            }
+            Filter::ConicGradient {
+                center,
+                angle,
+                uv_origin,
+            } => {
+                writer.write_u8(2)?;
+                center.encode(writer)?;
+                angle.encode(writer)?;
+                uv_origin.encode(writer)
+            }
+            Filter::Noise(ref noise) => {
+                writer.write_u8(3)?;
+                noise.encode(writer)
+            }
+            Filter::PaintEffect {
+                ref effect,
+                pixel_size,
+            } => {
+                writer.write_u8(4)?;
+                effect.encode(writer)?;
+                pixel_size.encode(writer)
+            }
+            Filter::PatternFilter(ref pattern_filter) => {
+                writer.write_u8(5)?;
+                pattern_filter.encode(writer)
+            }
+        }
+    }
+}
+
+impl Decode for Filter {
+    fn decode<R>(reader: &mut R) -> Result<Filter, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(Filter::None),
+            1 => Ok(Filter::RadialGradient {
+                line: Decode::decode(reader)?,
+                radii: Decode::decode(reader)?,
+                uv_origin: Decode::decode(reader)?,
+            }),
+            2 => Ok(Filter::ConicGradient {
+                center: Decode::decode(reader)?,
+                angle: Decode::decode(reader)?,
+                uv_origin: Decode::decode(reader)?,
+            }),
+            3 => Ok(Filter::Noise(Decode::decode(reader)?)),
+            4 => Ok(Filter::PaintEffect {
+                effect: Decode::decode(reader)?,
+                pixel_size: Decode::decode(reader)?,
+            }),
+            5 => Ok(Filter::PatternFilter(Decode::decode(reader)?)),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl Encode for PatternFilter {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match *self {
+            PatternFilter::Text {
+                fg_color,
+                bg_color,
+                defringing_kernel,
+                gamma_correction,
+            } => {
+                writer.write_u8(0)?;
+                fg_color.encode(writer)?;
+                bg_color.encode(writer)?;
+                defringing_kernel.encode(writer)?;
+                gamma_correction.encode(writer)
+            }
+            PatternFilter::Blur { direction, sigma } => {
+                writer.write_u8(1)?;
+                direction.encode(writer)?;
+                sigma.encode(writer)
+            }
+            PatternFilter::ColorMatrix(ref matrix) => {
+                writer.write_u8(2)?;
+                matrix.encode(writer)
+            }
+        }
+    }
+}
+
+impl Decode for PatternFilter {
+    fn decode<R>(reader: &mut R) -> Result<PatternFilter, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(PatternFilter::Text {
+                fg_color: Decode::decode(reader)?,
+                bg_color: Decode::decode(reader)?,
+                defringing_kernel: Decode::decode(reader)?,
+                gamma_correction: Decode::decode(reader)?,
+            }),
+            1 => Ok(PatternFilter::Blur {
+                direction: Decode::decode(reader)?,
+                sigma: Decode::decode(reader)?,
+            }),
+            2 => Ok(PatternFilter::ColorMatrix(Decode::decode(reader)?)),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl Encode for PaintEffectKind {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match *self {
+            PaintEffectKind::Hatching {
+                spacing,
+                line_width,
+            } => {
+                writer.write_u8(0)?;
+                spacing.encode(writer)?;
+                line_width.encode(writer)
+            }
+            PaintEffectKind::CrossHatching {
+                spacing,
+                line_width,
+            } => {
+                writer.write_u8(1)?;
+                spacing.encode(writer)?;
+                line_width.encode(writer)
+            }
+            PaintEffectKind::Halftone {
+                cell_size,
+                coverage,
+            } => {
+                writer.write_u8(2)?;
+                cell_size.encode(writer)?;
+                coverage.encode(writer)
+            }
+        }
+    }
+}
+
+impl Decode for PaintEffectKind {
+    fn decode<R>(reader: &mut R) -> Result<PaintEffectKind, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(PaintEffectKind::Hatching {
+                spacing: Decode::decode(reader)?,
+                line_width: Decode::decode(reader)?,
+            }),
+            1 => Ok(PaintEffectKind::CrossHatching {
+                spacing: Decode::decode(reader)?,
+                line_width: Decode::decode(reader)?,
+            }),
+            2 => Ok(PaintEffectKind::Halftone {
+                cell_size: Decode::decode(reader)?,
+                coverage: Decode::decode(reader)?,
+            }),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl Encode for RenderCommand {
+    fn encode<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        match *self {
+            RenderCommand::Start {
+                path_count,
+                ref bounding_quad,
+                needs_readable_framebuffer,
+                dirty_rect,
+                ref path_depth_planes,
+            } => {
+                writer.write_u8(0)?;
+                path_count.encode(writer)?;
+                bounding_quad.encode(writer)?;
+                needs_readable_framebuffer.encode(writer)?;
+                dirty_rect.encode(writer)?;
+                path_depth_planes.encode(writer)
+            }
+            RenderCommand::AllocateTexturePage {
+                page_id,
+                descriptor,
+            } => {
+                writer.write_u8(1)?;
+                page_id.encode(writer)?;
+                descriptor.encode(writer)
+            }
+            RenderCommand::DeclareExternalTexturePage {
+                page_id,
+                texture_id,
+            } => {
+                writer.write_u8(2)?;
+                page_id.encode(writer)?;
+                texture_id.encode(writer)
+            }
+            RenderCommand::UploadTexelData {
+                ref texels,
+                location,
+            } => {
+                writer.write_u8(3)?;
+                texels.encode(writer)?;
+                location.encode(writer)
+            }
+            RenderCommand::DeclareRenderTarget { id, location } => {
+                writer.write_u8(4)?;
+                id.encode(writer)?;
+                location.encode(writer)
+            }
+            RenderCommand::UploadTextureMetadata(ref metadata) => {
+                writer.write_u8(5)?;
+                metadata.encode(writer)
+            }
+            RenderCommand::AddFillsD3D9(ref fills) => {
+                writer.write_u8(6)?;
+                fills.encode(writer)
+            }
+            RenderCommand::FlushFillsD3D9 => writer.write_u8(7),
+            RenderCommand::UploadSceneD3D11 {
+                ref draw_segments,
+                ref clip_segments,
+            } => {
+                writer.write_u8(8)?;
+                draw_segments.encode(writer)?;
+                clip_segments.encode(writer)
+            }
+            RenderCommand::PushRenderTarget(render_target_id) => {
+                writer.write_u8(9)?;
+                render_target_id.encode(writer)
+            }
+            RenderCommand::PopRenderTarget => writer.write_u8(10),
+            RenderCommand::PrepareClipTilesD3D11(ref batch) => {
+                writer.write_u8(11)?;
+                batch.encode(writer)
+            }
+            RenderCommand::DrawTilesD3D9(ref batch) => {
+                writer.write_u8(12)?;
+                batch.encode(writer)
+            }
+            RenderCommand::DrawTilesD3D11(ref batch) => {
+                writer.write_u8(13)?;
+                batch.encode(writer)
+            }
+            RenderCommand::Finish { cpu_build_time } => {
+                writer.write_u8(14)?;
+                cpu_build_time.encode(writer)
+            }
+        }
+    }
+}
+
+impl Decode for RenderCommand {
+    fn decode<R>(reader: &mut R) -> Result<RenderCommand, BundleError>
+    where
+        R: Read,
+    {
+        match reader.read_u8()? {
+            0 => Ok(RenderCommand::Start {
+                path_count: Decode::decode(reader)?,
+                bounding_quad: Decode::decode(reader)?,
+                needs_readable_framebuffer: Decode::decode(reader)?,
+                dirty_rect: Decode::decode(reader)?,
+                path_depth_planes: Decode::decode(reader)?,
+            }),
+            1 => Ok(RenderCommand::AllocateTexturePage {
+                page_id: Decode::decode(reader)?,
+                descriptor: Decode::decode(reader)?,
+            }),
+            2 => Ok(RenderCommand::DeclareExternalTexturePage {
+                page_id: Decode::decode(reader)?,
+                texture_id: Decode::decode(reader)?,
+            }),
+            3 => Ok(RenderCommand::UploadTexelData {
+                texels: Decode::decode(reader)?,
+                location: Decode::decode(reader)?,
+            }),
+            4 => Ok(RenderCommand::DeclareRenderTarget {
+                id: Decode::decode(reader)?,
+                location: Decode::decode(reader)?,
+            }),
+            5 => Ok(RenderCommand::UploadTextureMetadata(Decode::decode(
+                reader,
+            )?)),
+            6 => Ok(RenderCommand::AddFillsD3D9(Decode::decode(reader)?)),
+            7 => Ok(RenderCommand::FlushFillsD3D9),
+            8 => Ok(RenderCommand::UploadSceneD3D11 {
+                draw_segments: Decode::decode(reader)?,
+                clip_segments: Decode::decode(reader)?,
+            }),
+            9 => Ok(RenderCommand::PushRenderTarget(Decode::decode(reader)?)),
+            10 => Ok(RenderCommand::PopRenderTarget),
+            11 => Ok(RenderCommand::PrepareClipTilesD3D11(Decode::decode(
+                reader,
+            )?)),
+            12 => Ok(RenderCommand::DrawTilesD3D9(Decode::decode(reader)?)),
+            13 => Ok(RenderCommand::DrawTilesD3D11(Decode::decode(reader)?)),
+            14 => Ok(RenderCommand::Finish {
+                cpu_build_time: Decode::decode(reader)?,
+            }),
+            _ => Err(BundleError::Malformed),
+        }
+    }
+}
+
+impl Display for BundleError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            BundleError::Io(ref error) => write!(formatter, "scene bundle I/O error: {}", error),
+            BundleError::UnsupportedFormat => {
+                write!(
+                    formatter,
+                    "not a scene bundle for this version of Pathfinder"
+                )
+            }
+            BundleError::KeyMismatch => {
+                write!(
+                    formatter,
+                    "scene bundle was built for a different scene or view"
+                )
+            }
+            BundleError::Malformed => write!(formatter, "scene bundle is corrupt"),
+            BundleError::NotSelfContained => {
+                write!(
+                    formatter,
+                    "frame depends on earlier frames and can't be saved as a scene bundle"
+                )
+            }
+        }
+    }
+}
+
+impl Error for BundleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BundleError::Io(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BundleError {
+    fn from(error: io::Error) -> BundleError {
+        BundleError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_commands, write_commands, BundleError, SceneBundleKey};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::concurrent::scene_proxy::{BuiltFrame, SceneProxy};
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::{AlphaTileId, Clip, TexturePageId};
+    use crate::gpu_data::{DrawTileBatchD3D9, Fill, RenderCommand, TileObjectPrimitive};
+    use crate::options::BuildOptions;
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, PathId, Scene};
+    use crate::tile_map::DenseTileMap;
+    use pathfinder_content::effects::{BlendMode, Filter};
+    use pathfinder_content::outline::Outline;
+    use pathfinder_content::pattern::{ExternalTextureId, Pattern};
+    use pathfinder_geometry::line_segment::LineSegmentU16;
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{vec2f, vec2i, Vector4F};
+    use std::time::Duration;
+
+    fn test_key(scene_hash: u64) -> SceneBundleKey {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(640.0, 480.0));
+        SceneBundleKey::new(
+            scene_hash,
+            view_box,
+            &BuildOptions::default(),
+            RendererLevel::D3D9,
+        )
+    }
+
+    fn test_commands() -> Vec<RenderCommand> {
+        let tile_rect = RectI::new(vec2i(0, 0), vec2i(2, 1));
+        vec![
+            RenderCommand::Start {
+                path_count: 1,
+                bounding_quad: [Vector4F::new(1.0, 2.0, 3.0, 4.0); 4],
+                needs_readable_framebuffer: false,
+                dirty_rect: Some(RectI::new(vec2i(16, 16), vec2i(32, 32))),
+                path_depth_planes: vec![],
+            },
+            RenderCommand::AddFillsD3D9(vec![Fill {
+                line_segment: LineSegmentU16 {
+                    from_x: 1,
+                    from_y: 2,
+                    to_x: 3,
+                    to_y: 4,
+                },
+                link: 5,
+            }]),
+            RenderCommand::FlushFillsD3D9,
+            RenderCommand::DrawTilesD3D9(DrawTileBatchD3D9 {
+                tiles: vec![TileObjectPrimitive {
+                    tile_x: -1,
+                    tile_y: 2,
+                    alpha_tile_id: AlphaTileId(7),
+                    path_id: PathId(0),
+                    color: 3,
+                    ctrl: 1,
+                    backdrop: -1,
+                }],
+                clips: vec![Clip::default()],
+                z_buffer_data: DenseTileMap::from_builder(|tile| tile.x(), tile_rect),
+                color_texture: None,
+                filter: Filter::None,
+                blend_mode: BlendMode::Multiply,
+                subpixel_coverage: true,
+            }),
+            RenderCommand::Finish {
+                cpu_build_time: Duration::from_millis(250),
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trip_preserves_commands() {
+        let key = test_key(0x1234);
+        let commands = test_commands();
+        let mut bundle = vec![];
+        write_commands(&key, &commands, &mut bundle).unwrap();
+
+        let read = read_commands(&key, &bundle[..]).unwrap();
+        assert_eq!(read.len(), commands.len());
+
+        // The build time is replaced with the time it took to load the bundle, so compare
+        // everything before it.
+        let mut expected = vec![];
+        write_commands(&key, &commands[..(commands.len() - 1)], &mut expected).unwrap();
+        let mut actual = vec![];
+        write_commands(&key, &read[..(read.len() - 1)], &mut actual).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_other_keys_and_corrupt_data() {
+        let key = test_key(0x1234);
+        let mut bundle = vec![];
+        write_commands(&key, &test_commands(), &mut bundle).unwrap();
+
+        match read_commands(&test_key(0x5678), &bundle[..]) {
+            Err(BundleError::KeyMismatch) => {}
+            result => panic!("expected a key mismatch, got {:?}", result.map(|_| ())),
+        }
+        match read_commands(&key, &b"PDF-1.7"[..]) {
+            Err(BundleError::UnsupportedFormat) => {}
+            result => panic!(
+                "expected an unsupported format, got {:?}",
+                result.map(|_| ())
+            ),
+        }
+        assert!(read_commands(&key, &bundle[..(bundle.len() / 2)]).is_err());
+    }
+
+    // Checks that every texture page the commands use is allocated by the commands themselves, as
+    // it must be for them to render on a renderer that has never seen an earlier frame.
+    fn replay_on_fresh_renderer(commands: &[RenderCommand]) -> Result<(), TexturePageId> {
+        let mut allocated_pages = vec![];
+        for command in commands {
+            let used_page = match *command {
+                RenderCommand::AllocateTexturePage { page_id, .. }
+                | RenderCommand::DeclareExternalTexturePage { page_id, .. } => {
+                    allocated_pages.push(page_id);
+                    None
+                }
+                RenderCommand::UploadTexelData { location, .. }
+                | RenderCommand::DeclareRenderTarget { location, .. } => Some(location.page),
+                RenderCommand::DrawTilesD3D9(ref batch) => {
+                    batch.color_texture.map(|texture| texture.page)
+                }
+                _ => None,
+            };
+            match used_page {
+                Some(page) if !allocated_pages.contains(&page) => return Err(page),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn later_frames_bundle_every_texture_they_use() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(640.0, 480.0)));
+        let pattern = Pattern::from_external_texture(ExternalTextureId(1), vec2i(16, 16));
+        let paint = scene.push_paint(&Paint::from_pattern(pattern));
+        let rect = RectF::new(vec2f(10.0, 10.0), vec2f(100.0, 50.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+        let mut scene_proxy =
+            SceneProxy::from_scene(scene, RendererLevel::D3D9, SequentialExecutor);
+
+        let handle = scene_proxy.begin_build(BuildOptions::default());
+        let first_frame = scene_proxy.finish_build(handle);
+        assert!(replay_on_fresh_renderer(first_frame.commands()).is_ok());
+
+        // The second frame reuses the texture page declared by the first.
+        let key = test_key(0x1234);
+        let handle = scene_proxy.begin_build(BuildOptions::default());
+        let second_frame = scene_proxy.finish_build(handle);
+        assert!(replay_on_fresh_renderer(second_frame.commands()).is_err());
+        match second_frame.write_bundle(&key, vec![]) {
+            Err(BundleError::NotSelfContained) => {}
+            result => panic!("expected an incomplete frame, got {:?}", result),
+        }
+
+        let handle = scene_proxy.begin_bundle_build(BuildOptions::default());
+        let mut bundle = vec![];
+        scene_proxy
+            .finish_build(handle)
+            .write_bundle(&key, &mut bundle)
+            .unwrap();
+        let frame = BuiltFrame::read_bundle(&key, &bundle[..]).unwrap();
+        assert_eq!(replay_on_fresh_renderer(frame.commands()), Ok(()));
+    }
+}
@@ -10,6 +10,8 @@
 
 //! An abstraction over threading and parallelism systems such as Rayon.
 
+use std::sync::Arc;
+
 /// An abstraction over threading and parallelism systems such as Rayon.
 pub trait Executor {
     /// Like the Rayon snippet:
@@ -23,6 +25,21 @@ pub trait Executor {
         F: Fn(usize) -> T + Send + Sync;
 }
 
+/// Lets an executor owned elsewhere in the application be shared with Pathfinder.
+impl<E> Executor for Arc<E>
+where
+    E: Executor + ?Sized,
+{
+    #[inline]
+    fn build_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize) -> T + Send + Sync,
+    {
+        (**self).build_vector(length, builder)
+    }
+}
+
 /// An executor that simply executes tasks sequentially in the same thread.
 pub struct SequentialExecutor;
 
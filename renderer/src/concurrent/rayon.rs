@@ -8,10 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! An executor that parallelizes tasks across all CPUs using the Rayon library.
+//! Executors that parallelize tasks using the Rayon library.
 
 use crate::concurrent::executor::Executor;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::ThreadPool;
+use std::sync::Arc;
 
 /// An executor that parallelizes tasks across all CPUs using the Rayon library.
 pub struct RayonExecutor;
@@ -25,3 +27,35 @@ impl Executor for RayonExecutor {
         (0..length).into_par_iter().map(builder).collect()
     }
 }
+
+/// An executor that parallelizes tasks across the threads of a Rayon thread pool owned by the
+/// application, instead of Rayon's global pool.
+#[derive(Clone)]
+pub struct RayonPoolExecutor {
+    pool: Arc<ThreadPool>,
+}
+
+impl RayonPoolExecutor {
+    /// Creates an executor that runs tasks on the given thread pool.
+    #[inline]
+    pub fn new(pool: Arc<ThreadPool>) -> RayonPoolExecutor {
+        RayonPoolExecutor { pool }
+    }
+
+    /// Returns the thread pool that this executor runs tasks on.
+    #[inline]
+    pub fn pool(&self) -> &Arc<ThreadPool> {
+        &self.pool
+    }
+}
+
+impl Executor for RayonPoolExecutor {
+    fn build_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize) -> T + Send + Sync,
+    {
+        self.pool
+            .install(|| (0..length).into_par_iter().map(builder).collect())
+    }
+}
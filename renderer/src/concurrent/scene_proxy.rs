@@ -19,15 +19,17 @@
 //!
 //! You don't need to use this API to use Pathfinder; it's only a convenience.
 
+use crate::bundle::{self, BundleError, SceneBundleKey};
 use crate::concurrent::executor::Executor;
 use crate::gpu::options::RendererLevel;
-use crate::gpu::renderer::Renderer;
+use crate::gpu::renderer::{RenderError, Renderer};
 use crate::gpu_data::RenderCommand;
 use crate::options::{BuildOptions, RenderCommandListener};
 use crate::scene::{Scene, SceneSink};
 use crossbeam_channel::{self, Receiver, Sender};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_gpu::Device;
+use std::io::{Read, Write};
 use std::thread;
 
 const MAX_MESSAGES_IN_FLIGHT: usize = 1024;
@@ -36,6 +38,26 @@ const MAX_MESSAGES_IN_FLIGHT: usize = 1024;
 pub struct SceneProxy {
     sender: Sender<MainToWorkerMsg>,
     receiver: Receiver<RenderCommand>,
+    // The sequence numbers of the next build to be started and finished, respectively.
+    next_build_to_begin: u64,
+    next_build_to_finish: u64,
+}
+
+/// A build started with `SceneProxy::begin_build()` whose commands haven't been collected yet.
+///
+/// Pass this to `SceneProxy::finish_build()` to wait for the build to complete.
+#[must_use]
+#[derive(Debug)]
+pub struct BuildHandle {
+    sequence_number: u64,
+    self_contained: bool,
+}
+
+/// The render commands for one fully-built frame, ready to be submitted to a renderer.
+pub struct BuiltFrame {
+    commands: Vec<RenderCommand>,
+    // True if the frame doesn't depend on textures uploaded by earlier frames.
+    self_contained: bool,
 }
 
 impl SceneProxy {
@@ -61,6 +83,28 @@ impl SceneProxy {
     pub fn from_scene<E>(scene: Scene, renderer_level: RendererLevel, executor: E) -> SceneProxy
     where
         E: Executor + Send + 'static,
+    {
+        SceneProxy::from_scene_with_spawner(scene, renderer_level, executor, |task| {
+            thread::spawn(task);
+        })
+    }
+
+    /// Wraps an existing scene in a scene proxy, using `spawner` to start the task that owns the
+    /// scene instead of spawning a dedicated thread for it.
+    ///
+    /// This lets the scene live in the application's own job system. For example, with a Rayon
+    /// pool shared through a `RayonPoolExecutor`, pass `|task| pool.spawn(task)`. The task runs
+    /// until the proxy is dropped, blocking whichever thread it's running on while it waits for
+    /// messages, so the pool needs at least one thread to spare for it.
+    pub fn from_scene_with_spawner<E, S>(
+        scene: Scene,
+        renderer_level: RendererLevel,
+        executor: E,
+        spawner: S,
+    ) -> SceneProxy
+    where
+        E: Executor + Send + 'static,
+        S: FnOnce(Box<dyn FnOnce() + Send>),
     {
         let (main_to_worker_sender, main_to_worker_receiver) =
             crossbeam_channel::bounded(MAX_MESSAGES_IN_FLIGHT);
@@ -70,10 +114,14 @@ impl SceneProxy {
             drop(worker_to_main_sender.send(command))
         }));
         let sink = SceneSink::new(listener, renderer_level);
-        thread::spawn(move || scene_thread(scene, executor, sink, main_to_worker_receiver));
+        spawner(Box::new(move || {
+            scene_thread(scene, executor, sink, main_to_worker_receiver)
+        }));
         SceneProxy {
             sender: main_to_worker_sender,
             receiver: worker_to_main_receiver,
+            next_build_to_begin: 0,
+            next_build_to_finish: 0,
         }
     }
 
@@ -93,27 +141,171 @@ impl SceneProxy {
             .unwrap();
     }
 
+    /// Enables or disables caching of tiles across frames.
+    ///
+    /// See `SceneSink::set_tile_caching_enabled()` for details.
+    #[inline]
+    pub fn set_tile_caching_enabled(&self, enabled: bool) {
+        self.sender
+            .send(MainToWorkerMsg::SetTileCachingEnabled(enabled))
+            .unwrap();
+    }
+
     /// Constructs a scene and queues up the commands needed to render it.
     #[inline]
     pub fn build(&self, options: BuildOptions) {
         self.sender.send(MainToWorkerMsg::Build(options)).unwrap();
     }
 
+    /// Starts building the scene on the worker thread and returns immediately.
+    ///
+    /// This lets the next frame be built while the current one is being submitted to the GPU:
+    ///
+    /// ```norun
+    /// let mut pending = scene_proxy.begin_build(BuildOptions::default());
+    /// loop {
+    ///     let frame = scene_proxy.finish_build(pending);
+    ///     scene_proxy.replace_scene(next_scene());
+    ///     pending = scene_proxy.begin_build(BuildOptions::default());
+    ///     frame.render(&mut renderer);
+    /// }
+    /// ```
+    ///
+    /// Builds finish in the order they were begun, and each handle must be passed to
+    /// `finish_build()` in that same order. Don't mix this with `render()` while a build is
+    /// pending, as `render()` would consume the pending build's commands.
+    pub fn begin_build(&mut self, options: BuildOptions) -> BuildHandle {
+        self.build(options);
+        self.next_build_handle(false)
+    }
+
+    /// Like `begin_build()`, but builds a frame that can be saved as a scene bundle.
+    ///
+    /// The frame is built as if it were the first: every texture page is allocated and uploaded
+    /// again, and no tiles are reused from earlier frames. Render it like any other frame, because
+    /// later frames build on the textures it uploads.
+    pub fn begin_bundle_build(&mut self, options: BuildOptions) -> BuildHandle {
+        self.sender
+            .send(MainToWorkerMsg::BuildSelfContained(options))
+            .unwrap();
+        self.next_build_handle(true)
+    }
+
+    fn next_build_handle(&mut self, self_contained: bool) -> BuildHandle {
+        let sequence_number = self.next_build_to_begin;
+        self.next_build_to_begin += 1;
+        BuildHandle {
+            sequence_number,
+            self_contained,
+        }
+    }
+
+    /// Waits for a build started with `begin_build()` to complete and returns its commands.
+    pub fn finish_build(&mut self, handle: BuildHandle) -> BuiltFrame {
+        assert_eq!(
+            handle.sequence_number, self.next_build_to_finish,
+            "Builds must be finished in the order they were begun!"
+        );
+        self.next_build_to_finish += 1;
+        BuiltFrame {
+            commands: self.receive_commands(),
+            self_contained: handle.self_contained,
+        }
+    }
+
     /// Sends all queued commands to the given renderer to render the wrapped scene.
     #[inline]
     pub fn render<D>(&mut self, renderer: &mut Renderer<D>)
     where
         D: Device,
     {
+        self.try_render(renderer)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `render()`, but returns an error instead of panicking if the renderer can't draw the
+    /// scene. See `Renderer::try_render_command()`.
+    ///
+    /// The scene's remaining commands are discarded after an error and the scene is ended, so
+    /// both the proxy and the renderer are ready for the next frame.
+    pub fn try_render<D>(&mut self, renderer: &mut Renderer<D>) -> Result<(), RenderError>
+    where
+        D: Device,
+    {
+        trace_scope!("render_scene");
+        let mut result = Ok(());
         renderer.begin_scene();
         while let Ok(command) = self.receiver.recv() {
-            renderer.render_command(&command);
+            if result.is_ok() {
+                result = renderer.try_render_command(&command);
+            }
             match command {
                 RenderCommand::Finish { .. } => break,
                 _ => {}
             }
         }
         renderer.end_scene();
+        result
+    }
+
+    /// Sends all queued commands from several scene proxies to the given renderer, compositing
+    /// their scenes into a single frame.
+    ///
+    /// Scenes are drawn in order, so later scenes appear on top of earlier ones: for example, a
+    /// map base layer, then labels, then a UI overlay. Each proxy must have had `build()` called
+    /// for this frame. Each scene is rendered into the renderer layer matching its index in the
+    /// slice (see `Renderer::begin_layer()`), so pass the proxies in the same order every frame.
+    pub fn render_layers<D>(scene_proxies: &[&SceneProxy], renderer: &mut Renderer<D>)
+    where
+        D: Device,
+    {
+        trace_scope!("render_layers", layer_count = scene_proxies.len());
+
+        // Receive everything up front so that the renderer knows whether any scene needs to read
+        // back the framebuffer before drawing anything.
+        let mut layers: Vec<Vec<RenderCommand>> = scene_proxies
+            .iter()
+            .map(|scene_proxy| scene_proxy.receive_commands())
+            .collect();
+        let mut any_needs_readable_framebuffer = false;
+        for command in layers.iter().flatten() {
+            if let RenderCommand::Start {
+                needs_readable_framebuffer,
+                ..
+            } = *command
+            {
+                any_needs_readable_framebuffer |= needs_readable_framebuffer;
+            }
+        }
+        if let Some(RenderCommand::Start {
+            ref mut needs_readable_framebuffer,
+            ..
+        }) = layers.iter_mut().flatten().next()
+        {
+            *needs_readable_framebuffer = any_needs_readable_framebuffer;
+        }
+
+        renderer.begin_scene();
+        for (layer_index, commands) in layers.iter().enumerate() {
+            renderer.begin_layer(layer_index);
+            for command in commands {
+                renderer.render_command(command);
+            }
+        }
+        renderer.end_scene();
+    }
+
+    fn receive_commands(&self) -> Vec<RenderCommand> {
+        trace_scope!("receive_commands");
+        let mut commands = vec![];
+        while let Ok(command) = self.receiver.recv() {
+            let is_finish = matches!(command, RenderCommand::Finish { .. });
+            commands.push(command);
+            if is_finish {
+                break;
+            }
+        }
+        commands
     }
 
     /// A convenience method to build a scene and send the resulting commands
@@ -146,6 +338,57 @@ impl SceneProxy {
     }
 }
 
+impl BuiltFrame {
+    /// Returns the render commands that make up this frame.
+    #[inline]
+    pub fn commands(&self) -> &[RenderCommand] {
+        &self.commands
+    }
+
+    /// Saves this frame as a scene bundle, so that it can be loaded with `read_bundle()` instead
+    /// of being built again. See the `bundle` module.
+    ///
+    /// Fails with `BundleError::NotSelfContained` unless the frame was built with
+    /// `SceneProxy::begin_bundle_build()`.
+    pub fn write_bundle<W>(&self, key: &SceneBundleKey, writer: W) -> Result<(), BundleError>
+    where
+        W: Write,
+    {
+        if !self.self_contained {
+            return Err(BundleError::NotSelfContained);
+        }
+        bundle::write_commands(key, &self.commands, writer)
+    }
+
+    /// Loads a frame saved with `write_bundle()`.
+    ///
+    /// Fails with `BundleError::KeyMismatch` if the bundle was saved with a different key, in
+    /// which case the scene must be built again.
+    #[inline]
+    pub fn read_bundle<R>(key: &SceneBundleKey, reader: R) -> Result<BuiltFrame, BundleError>
+    where
+        R: Read,
+    {
+        Ok(BuiltFrame {
+            commands: bundle::read_commands(key, reader)?,
+            self_contained: true,
+        })
+    }
+
+    /// Sends the commands for this frame to the given renderer.
+    pub fn render<D>(&self, renderer: &mut Renderer<D>)
+    where
+        D: Device,
+    {
+        trace_scope!("render_built_frame", command_count = self.commands.len());
+        renderer.begin_scene();
+        for command in &self.commands {
+            renderer.render_command(command);
+        }
+        renderer.end_scene();
+    }
+}
+
 fn scene_thread<E>(
     mut scene: Scene,
     executor: E,
@@ -159,7 +402,14 @@ fn scene_thread<E>(
             MainToWorkerMsg::ReplaceScene(new_scene) => scene = new_scene,
             MainToWorkerMsg::CopyScene(sender) => sender.send(scene.clone()).unwrap(),
             MainToWorkerMsg::SetViewBox(new_view_box) => scene.set_view_box(new_view_box),
+            MainToWorkerMsg::SetTileCachingEnabled(enabled) => {
+                sink.set_tile_caching_enabled(enabled)
+            }
             MainToWorkerMsg::Build(options) => scene.build(options, &mut sink, &executor),
+            MainToWorkerMsg::BuildSelfContained(options) => {
+                sink.forget_previous_frames();
+                scene.build(options, &mut sink, &executor)
+            }
         }
     }
 }
@@ -168,5 +418,7 @@ enum MainToWorkerMsg {
     ReplaceScene(Scene),
     CopyScene(Sender<Scene>),
     SetViewBox(RectF),
+    SetTileCachingEnabled(bool),
     Build(BuildOptions),
+    BuildSelfContained(BuildOptions),
 }
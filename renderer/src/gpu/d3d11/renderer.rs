@@ -14,8 +14,9 @@
 
 use crate::gpu::d3d11::shaders::{ProgramsD3D11, PROPAGATE_WORKGROUP_SIZE, SORT_WORKGROUP_SIZE};
 use crate::gpu::d3d11::shaders::{BOUND_WORKGROUP_SIZE, DICE_WORKGROUP_SIZE};
+use crate::gpu::options::FillMode;
 use crate::gpu::perf::TimeCategory;
-use crate::gpu::renderer::{FramebufferFlags, RendererCore};
+use crate::gpu::renderer::{FramebufferFlags, RenderError, RendererCore};
 use crate::gpu_data::{AlphaTileD3D11, BackdropInfoD3D11, DiceMetadataD3D11, DrawTileBatchD3D11};
 use crate::gpu_data::{Fill, FirstTileD3D11, MicrolineD3D11, PathSource, PropagateMetadataD3D11};
 use crate::gpu_data::{SegmentIndicesD3D11, SegmentsD3D11, TileBatchDataD3D11, TileD3D11};
@@ -25,7 +26,7 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_gpu::allocator::{BufferTag, GPUMemoryAllocator, GeneralBufferID};
 use pathfinder_gpu::{BufferTarget, ComputeDimensions, ComputeState, Device, ImageAccess};
-use pathfinder_gpu::{RenderTarget, UniformData};
+use pathfinder_gpu::{DeviceError, RenderTarget, UniformData};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x4, I32x2};
 use std::ops::Range;
@@ -61,15 +62,15 @@ where
     pub(crate) fn new(
         core: &mut RendererCore<D>,
         resources: &dyn ResourceLoader,
-    ) -> RendererD3D11<D> {
-        let programs = ProgramsD3D11::new(&core.device, resources);
-        RendererD3D11 {
+    ) -> Result<RendererD3D11<D>, DeviceError> {
+        let programs = ProgramsD3D11::new(&core.device, resources)?;
+        Ok(RendererD3D11 {
             programs,
             allocated_fill_count: INITIAL_ALLOCATED_FILL_COUNT,
             allocated_microline_count: INITIAL_ALLOCATED_MICROLINE_COUNT,
             scene_buffers: SceneBuffers::new(),
             tile_batch_info: VecMap::<TileBatchInfoD3D11>::new(),
-        }
+        })
     }
 
     fn bound(
@@ -79,6 +80,7 @@ where
         tile_count: u32,
         tile_path_info: &[TilePathInfoD3D11],
     ) {
+        trace_scope!("bound_tiles", tile_count);
         let bound_program = &self.programs.bound_program;
 
         let path_info_buffer_id = core.allocator.allocate_general_buffer::<TilePathInfoD3D11>(
@@ -131,12 +133,12 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Tile);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
             .unwrap()
-            .push_query(TimeCategory::Other, timer_query);
+            .push_query(TimeCategory::Tile, timer_query);
 
         core.allocator.free_general_buffer(path_info_buffer_id);
     }
@@ -195,6 +197,7 @@ where
         tiles_d3d11_buffer_id: GeneralBufferID,
         z_buffer_id: GeneralBufferID,
     ) -> Option<FillBufferInfoD3D11> {
+        trace_scope!("bin_segments");
         let bin_program = &self.programs.bin_program;
 
         let fill_vertex_buffer_id = core.allocator.allocate_general_buffer::<Fill>(
@@ -268,7 +271,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Bin);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -301,6 +304,7 @@ where
         draw_segments: &SegmentsD3D11,
         clip_segments: &SegmentsD3D11,
     ) {
+        trace_scope!("upload_scene");
         self.scene_buffers.upload(
             &mut core.allocator,
             &core.device,
@@ -325,6 +329,7 @@ where
         path_source: PathSource,
         transform: Transform2F,
     ) -> Option<MicrolinesBufferIDsD3D11> {
+        trace_scope!("dice_segments", segment_count = batch_segment_count);
         let dice_program = &self.programs.dice_program;
 
         let microlines_buffer_id = core.allocator.allocate_general_buffer::<MicrolineD3D11>(
@@ -440,7 +445,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Dice);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -480,6 +485,7 @@ where
         alpha_tiles_buffer_id: GeneralBufferID,
         propagate_tiles_info: &PropagateTilesInfoD3D11,
     ) {
+        trace_scope!("draw_fills");
         let &FillBufferInfoD3D11 {
             fill_vertex_buffer_id,
         } = fill_storage_info;
@@ -525,13 +531,19 @@ where
                     image_texture,
                     ImageAccess::ReadWrite,
                 )],
-                uniforms: &[(
-                    &fill_program.alpha_tile_range_uniform,
-                    UniformData::IVec2(I32x2::new(
-                        alpha_tile_range.start as i32,
-                        alpha_tile_range.end as i32,
-                    )),
-                )],
+                uniforms: &[
+                    (
+                        &fill_program.alpha_tile_range_uniform,
+                        UniformData::IVec2(I32x2::new(
+                            alpha_tile_range.start as i32,
+                            alpha_tile_range.end as i32,
+                        )),
+                    ),
+                    (
+                        &fill_program.supersample_uniform,
+                        UniformData::Int((core.options.fill_mode == FillMode::Supersample) as i32),
+                    ),
+                ],
                 storage_buffers: &[
                     (&fill_program.fills_storage_buffer, fill_vertex_buffer),
                     (&fill_program.tiles_storage_buffer, tiles_d3d11_buffer),
@@ -543,7 +555,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Fill);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -558,20 +570,31 @@ where
         &mut self,
         core: &mut RendererCore<D>,
         batch: &DrawTileBatchD3D11,
-    ) {
+    ) -> Result<(), RenderError> {
         let tile_batch_id = batch.tile_batch_data.batch_id;
-        self.prepare_tiles(core, &batch.tile_batch_data);
+        self.prepare_tiles(core, &batch.tile_batch_data)?;
         let batch_info = self.tile_batch_info[tile_batch_id.0 as usize].clone();
+        core.device.push_debug_group("Draw tiles");
         self.draw_tiles(
             core,
             batch_info.tiles_d3d11_buffer_id,
             batch_info.first_tile_map_buffer_id,
             batch.color_texture,
         );
+        core.device.pop_debug_group();
+        Ok(())
     }
 
     // Computes backdrops, performs clipping, and populates Z buffers on GPU.
-    pub(crate) fn prepare_tiles(&mut self, core: &mut RendererCore<D>, batch: &TileBatchDataD3D11) {
+    //
+    // If the scene is too large for the storage buffers even after growing them, the batch's
+    // buffers are freed and `RenderError::SceneTooLarge` is returned.
+    pub(crate) fn prepare_tiles(
+        &mut self,
+        core: &mut RendererCore<D>,
+        batch: &TileBatchDataD3D11,
+    ) -> Result<(), RenderError> {
+        trace_scope!("prepare_tiles", tile_count = batch.tile_count);
         core.stats.total_tile_count += batch.tile_count as usize;
 
         // Upload tiles to GPU or allocate them as appropriate.
@@ -608,6 +631,7 @@ where
 
         // Dice (flatten) segments into microlines. We might have to do this twice if our
         // first attempt runs out of space in the storage buffer.
+        core.device.push_debug_group("Dice segments");
         let mut microlines_storage = None;
         for _ in 0..2 {
             microlines_storage = self.dice_segments(
@@ -621,11 +645,26 @@ where
                 break;
             }
         }
-        let microlines_storage =
-            microlines_storage.expect("Ran out of space for microlines when dicing!");
+        core.device.pop_debug_group();
+        let batch_buffer_ids = [
+            tiles_d3d11_buffer_id,
+            z_buffer_id,
+            first_tile_map_buffer_id,
+            propagate_metadata_buffer_ids.propagate_metadata,
+            propagate_metadata_buffer_ids.backdrops,
+        ];
+        let microlines_storage = match microlines_storage {
+            Some(microlines_storage) => microlines_storage,
+            None => {
+                // Ran out of space for microlines when dicing.
+                free_general_buffers(&mut core.allocator, &batch_buffer_ids);
+                return Err(RenderError::SceneTooLarge);
+            }
+        };
 
         // Initialize tiles, and bin segments. We might have to do this twice if our first
         // attempt runs out of space in the fill buffer.
+        core.device.push_debug_group("Bin segments");
         let mut fill_buffer_info = None;
         for _ in 0..2 {
             self.bound(
@@ -652,16 +691,26 @@ where
                 break;
             }
         }
-        let fill_buffer_info = fill_buffer_info.expect("Ran out of space for fills when binning!");
+        core.device.pop_debug_group();
 
         core.allocator
             .free_general_buffer(microlines_storage.buffer_id);
 
+        let fill_buffer_info = match fill_buffer_info {
+            Some(fill_buffer_info) => fill_buffer_info,
+            None => {
+                // Ran out of space for fills when binning.
+                free_general_buffers(&mut core.allocator, &batch_buffer_ids);
+                return Err(RenderError::SceneTooLarge);
+            }
+        };
+
         // TODO(pcwalton): If we run out of space for alpha tile indices, propagate
         // multiple times.
 
         let alpha_tiles_buffer_id = self.allocate_alpha_tile_info(core, batch.tile_count);
 
+        core.device.push_debug_group("Propagate tiles");
         let propagate_tiles_info = self.propagate_tiles(
             core,
             batch.prepare_info.backdrops.len() as u32,
@@ -672,12 +721,27 @@ where
             &propagate_metadata_buffer_ids,
             clip_buffer_ids.as_ref(),
         );
+        core.device.pop_debug_group();
 
         core.allocator
             .free_general_buffer(propagate_metadata_buffer_ids.backdrops);
 
         // FIXME(pcwalton): Don't unconditionally pass true for copying here.
-        core.reallocate_alpha_tile_pages_if_necessary(true);
+        if let Err(error) = core.reallocate_alpha_tile_pages_if_necessary(true) {
+            free_general_buffers(
+                &mut core.allocator,
+                &[
+                    fill_buffer_info.fill_vertex_buffer_id,
+                    alpha_tiles_buffer_id,
+                    tiles_d3d11_buffer_id,
+                    z_buffer_id,
+                    first_tile_map_buffer_id,
+                    propagate_metadata_buffer_ids.propagate_metadata,
+                ],
+            );
+            return Err(error);
+        }
+        core.device.push_debug_group("Fills");
         self.draw_fills(
             core,
             &fill_buffer_info,
@@ -685,18 +749,21 @@ where
             alpha_tiles_buffer_id,
             &propagate_tiles_info,
         );
+        core.device.pop_debug_group();
 
         core.allocator
             .free_general_buffer(fill_buffer_info.fill_vertex_buffer_id);
         core.allocator.free_general_buffer(alpha_tiles_buffer_id);
 
         // FIXME(pcwalton): This seems like the wrong place to do this...
+        core.device.push_debug_group("Sort tiles");
         self.sort_tiles(
             core,
             tiles_d3d11_buffer_id,
             first_tile_map_buffer_id,
             z_buffer_id,
         );
+        core.device.pop_debug_group();
 
         // Record tile batch info.
         self.tile_batch_info.insert(
@@ -709,6 +776,7 @@ where
                 first_tile_map_buffer_id,
             },
         );
+        Ok(())
     }
 
     fn propagate_tiles(
@@ -722,6 +790,7 @@ where
         propagate_metadata_buffer_ids: &PropagateMetadataBufferIDsD3D11,
         clip_buffer_ids: Option<&ClipBufferIDs>,
     ) -> PropagateTilesInfoD3D11 {
+        trace_scope!("propagate_tiles", column_count);
         let propagate_program = &self.programs.propagate_program;
 
         let tiles_d3d11_buffer = core.allocator.get_general_buffer(tiles_d3d11_buffer_id);
@@ -838,12 +907,12 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Tile);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
             .unwrap()
-            .push_query(TimeCategory::Other, timer_query);
+            .push_query(TimeCategory::Tile, timer_query);
 
         let fill_indirect_draw_params_receiver =
             core.device
@@ -872,6 +941,7 @@ where
         first_tile_map_buffer_id: GeneralBufferID,
         z_buffer_id: GeneralBufferID,
     ) {
+        trace_scope!("sort_tiles");
         let sort_program = &self.programs.sort_program;
 
         let tiles_d3d11_buffer = core.allocator.get_general_buffer(tiles_d3d11_buffer_id);
@@ -910,12 +980,12 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Tile);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
             .unwrap()
-            .push_query(TimeCategory::Other, timer_query);
+            .push_query(TimeCategory::Tile, timer_query);
     }
 
     fn allocate_first_tile_map(&mut self, core: &mut RendererCore<D>) -> GeneralBufferID {
@@ -953,6 +1023,7 @@ where
         first_tile_map_buffer_id: GeneralBufferID,
         color_texture_0: Option<TileBatchTexture>,
     ) {
+        trace_scope!("draw_tiles");
         let timer_query = core
             .timer_query_cache
             .start_timing_draw_call(&core.device, &core.options);
@@ -1037,7 +1108,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Composite);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -1051,7 +1122,7 @@ where
         self.free_tile_batch_buffers(core);
     }
 
-    fn free_tile_batch_buffers(&mut self, core: &mut RendererCore<D>) {
+    pub(crate) fn free_tile_batch_buffers(&mut self, core: &mut RendererCore<D>) {
         for (_, tile_batch_info) in self.tile_batch_info.drain() {
             core.allocator
                 .free_general_buffer(tile_batch_info.z_buffer_id);
@@ -1191,3 +1262,12 @@ impl SceneSourceBuffers {
         self.point_indices_count = segments.indices.len() as u32;
     }
 }
+
+fn free_general_buffers<D>(allocator: &mut GPUMemoryAllocator<D>, buffer_ids: &[GeneralBufferID])
+where
+    D: Device,
+{
+    for &buffer_id in buffer_ids {
+        allocator.free_general_buffer(buffer_id);
+    }
+}
@@ -12,7 +12,7 @@
 
 use crate::gpu::shaders::TileProgramCommon;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
-use pathfinder_gpu::{ComputeDimensions, Device};
+use pathfinder_gpu::{ComputeDimensions, Device, DeviceError};
 use pathfinder_resources::ResourceLoader;
 
 pub(crate) const BOUND_WORKGROUP_SIZE: u32 = 64;
@@ -38,16 +38,19 @@ impl<D> ProgramsD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsD3D11<D> {
-        ProgramsD3D11 {
-            bound_program: BoundProgramD3D11::new(device, resources),
-            dice_program: DiceProgramD3D11::new(device, resources),
-            bin_program: BinProgramD3D11::new(device, resources),
-            propagate_program: PropagateProgramD3D11::new(device, resources),
-            sort_program: SortProgramD3D11::new(device, resources),
-            fill_program: FillProgramD3D11::new(device, resources),
-            tile_program: TileProgramD3D11::new(device, resources),
-        }
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ProgramsD3D11<D>, DeviceError> {
+        Ok(ProgramsD3D11 {
+            bound_program: BoundProgramD3D11::new(device, resources)?,
+            dice_program: DiceProgramD3D11::new(device, resources)?,
+            bin_program: BinProgramD3D11::new(device, resources)?,
+            propagate_program: PropagateProgramD3D11::new(device, resources)?,
+            sort_program: SortProgramD3D11::new(device, resources)?,
+            fill_program: FillProgramD3D11::new(device, resources)?,
+            tile_program: TileProgramD3D11::new(device, resources)?,
+        })
     }
 }
 
@@ -73,8 +76,11 @@ impl<D> PropagateProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> PropagateProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/propagate");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<PropagateProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/propagate")?;
         let local_size = ComputeDimensions {
             x: PROPAGATE_WORKGROUP_SIZE,
             y: 1,
@@ -94,7 +100,7 @@ where
         let first_tile_map_storage_buffer = device.get_storage_buffer(&program, "FirstTileMap", 6);
         let alpha_tiles_storage_buffer = device.get_storage_buffer(&program, "AlphaTiles", 7);
 
-        PropagateProgramD3D11 {
+        Ok(PropagateProgramD3D11 {
             program,
             framebuffer_tile_size_uniform,
             column_count_uniform,
@@ -107,7 +113,7 @@ where
             z_buffer_storage_buffer,
             first_tile_map_storage_buffer,
             alpha_tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -118,6 +124,7 @@ where
     pub(crate) program: D::Program,
     pub(crate) dest_image: D::ImageParameter,
     pub(crate) area_lut_texture: D::TextureParameter,
+    pub(crate) supersample_uniform: D::Uniform,
     pub(crate) alpha_tile_range_uniform: D::Uniform,
     pub(crate) fills_storage_buffer: D::StorageBuffer,
     pub(crate) tiles_storage_buffer: D::StorageBuffer,
@@ -128,8 +135,11 @@ impl<D> FillProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> FillProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/fill");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<FillProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/fill")?;
         let local_size = ComputeDimensions {
             x: TILE_WIDTH,
             y: TILE_HEIGHT / 4,
@@ -139,20 +149,22 @@ where
 
         let dest_image = device.get_image_parameter(&program, "Dest");
         let area_lut_texture = device.get_texture_parameter(&program, "AreaLUT");
+        let supersample_uniform = device.get_uniform(&program, "Supersample");
         let alpha_tile_range_uniform = device.get_uniform(&program, "AlphaTileRange");
         let fills_storage_buffer = device.get_storage_buffer(&program, "Fills", 0);
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 1);
         let alpha_tiles_storage_buffer = device.get_storage_buffer(&program, "AlphaTiles", 2);
 
-        FillProgramD3D11 {
+        Ok(FillProgramD3D11 {
             program,
             dest_image,
             area_lut_texture,
+            supersample_uniform,
             alpha_tile_range_uniform,
             fills_storage_buffer,
             tiles_storage_buffer,
             alpha_tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -173,8 +185,8 @@ impl<D> TileProgramD3D11<D>
 where
     D: Device,
 {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> TileProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/tile");
+    fn new(device: &D, resources: &dyn ResourceLoader) -> Result<TileProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/tile")?;
         device
             .set_compute_program_local_size(&mut program, ComputeDimensions { x: 16, y: 4, z: 1 });
 
@@ -186,7 +198,7 @@ where
         let first_tile_map_storage_buffer = device.get_storage_buffer(&program, "FirstTileMap", 1);
 
         let common = TileProgramCommon::new(device, program);
-        TileProgramD3D11 {
+        Ok(TileProgramD3D11 {
             common,
             load_action_uniform,
             clear_color_uniform,
@@ -194,7 +206,7 @@ where
             dest_image,
             tiles_storage_buffer,
             first_tile_map_storage_buffer,
-        }
+        })
     }
 }
 
@@ -217,8 +229,11 @@ impl<D> BinProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> BinProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/bin");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<BinProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/bin")?;
         let dimensions = ComputeDimensions {
             x: BIN_WORKGROUP_SIZE,
             y: 1,
@@ -237,7 +252,7 @@ where
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 4);
         let backdrops_storage_buffer = device.get_storage_buffer(&program, "Backdrops", 5);
 
-        BinProgramD3D11 {
+        Ok(BinProgramD3D11 {
             program,
             microline_count_uniform,
             max_fill_count_uniform,
@@ -247,7 +262,7 @@ where
             tiles_storage_buffer,
             microlines_storage_buffer,
             backdrops_storage_buffer,
-        }
+        })
     }
 }
 
@@ -272,8 +287,11 @@ impl<D> DiceProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> DiceProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/dice");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<DiceProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/dice")?;
         let dimensions = ComputeDimensions {
             x: DICE_WORKGROUP_SIZE,
             y: 1,
@@ -295,7 +313,7 @@ where
         let input_indices_storage_buffer = device.get_storage_buffer(&program, "InputIndices", 3);
         let microlines_storage_buffer = device.get_storage_buffer(&program, "Microlines", 4);
 
-        DiceProgramD3D11 {
+        Ok(DiceProgramD3D11 {
             program,
             transform_uniform,
             translation_uniform,
@@ -307,7 +325,7 @@ where
             points_storage_buffer,
             input_indices_storage_buffer,
             microlines_storage_buffer,
-        }
+        })
     }
 }
 
@@ -326,8 +344,11 @@ impl<D> BoundProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> BoundProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/bound");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<BoundProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/bound")?;
         let dimensions = ComputeDimensions {
             x: BOUND_WORKGROUP_SIZE,
             y: 1,
@@ -341,13 +362,13 @@ where
         let tile_path_info_storage_buffer = device.get_storage_buffer(&program, "TilePathInfo", 0);
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 1);
 
-        BoundProgramD3D11 {
+        Ok(BoundProgramD3D11 {
             program,
             path_count_uniform,
             tile_count_uniform,
             tile_path_info_storage_buffer,
             tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -366,8 +387,11 @@ impl<D> SortProgramD3D11<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> SortProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/sort");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<SortProgramD3D11<D>, DeviceError> {
+        let mut program = device.try_create_compute_program(resources, "d3d11/sort")?;
         let dimensions = ComputeDimensions {
             x: SORT_WORKGROUP_SIZE,
             y: 1,
@@ -380,12 +404,12 @@ where
         let first_tile_map_storage_buffer = device.get_storage_buffer(&program, "FirstTileMap", 1);
         let z_buffer_storage_buffer = device.get_storage_buffer(&program, "ZBuffer", 2);
 
-        SortProgramD3D11 {
+        Ok(SortProgramD3D11 {
             program,
             tile_count_uniform,
             tiles_storage_buffer,
             first_tile_map_storage_buffer,
             z_buffer_storage_buffer,
-        }
+        })
     }
 }
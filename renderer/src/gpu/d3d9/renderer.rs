@@ -10,20 +10,26 @@
 
 //! A hybrid CPU-GPU renderer that only relies on functionality available in Direct3D 9.
 //!
-//! This renderer supports OpenGL at least 3.0, OpenGL ES at least 3.0, Metal of any version, and
-//! WebGL at least 2.0.
+//! This renderer supports OpenGL at least 3.0, OpenGL ES at least 2.0, Metal of any version, and
+//! WebGL of any version. On OpenGL ES 2.0 and WebGL 1, subpixel coverage isn't available, and radial
+//! gradients are the only filter: paints with any other filter are drawn unfiltered, and a warning
+//! is logged the first time that happens.
 
 use crate::gpu::blend::{BlendModeExt, ToBlendState};
 use crate::gpu::d3d9::shaders::{ClipTileCombineVertexArrayD3D9, ClipTileCopyVertexArrayD3D9};
 use crate::gpu::d3d9::shaders::{CopyTileVertexArray, FillVertexArrayD3D9};
 use crate::gpu::d3d9::shaders::{ProgramsD3D9, TileVertexArrayD3D9};
+use crate::gpu::options::FillMode;
 use crate::gpu::perf::TimeCategory;
 use crate::gpu::renderer::{FramebufferFlags, MASK_FRAMEBUFFER_HEIGHT, MASK_FRAMEBUFFER_WIDTH};
-use crate::gpu::renderer::{RendererCore, RendererFlags};
-use crate::gpu_data::{Clip, DrawTileBatchD3D9, Fill, TileBatchTexture, TileObjectPrimitive};
+use crate::gpu::renderer::{RenderError, RendererCore, RendererFlags};
+use crate::gpu_data::TileBatchTexture;
+use crate::gpu_data::TileObjectPrimitive;
+use crate::gpu_data::{AlphaTileId, Clip, DrawTileBatchD3D9, Fill, PathDepthPlane};
 use crate::tile_map::DenseTileMap;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
 use byte_slice_cast::AsByteSlice;
+use fxhash::FxHashMap;
 use pathfinder_color::ColorF;
 use pathfinder_content::effects::BlendMode;
 use pathfinder_geometry::rect::RectI;
@@ -31,15 +37,19 @@ use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{vec2i, Vector2I, Vector4F};
 use pathfinder_gpu::allocator::{BufferTag, FramebufferID, FramebufferTag, GeneralBufferID};
 use pathfinder_gpu::allocator::{IndexBufferID, TextureID, TextureTag};
-use pathfinder_gpu::{BlendFactor, BlendState, BufferTarget, ClearOps, Device, Primitive};
+use pathfinder_gpu::{BlendFactor, BlendState, BufferTarget, ClearOps, DepthFunc, DepthState};
+use pathfinder_gpu::{Device, FeatureLevel, Primitive};
 use pathfinder_gpu::{RenderOptions, RenderState, RenderTarget, StencilFunc, StencilState};
 use pathfinder_gpu::{TextureDataRef, TextureFormat, UniformData};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x2;
+use std::mem;
 use std::u32;
 
 const MAX_FILLS_PER_BATCH: usize = 0x10000;
 
+const PATH_DEPTH_PLANES_TEXTURE_WIDTH: i32 = 1024;
+
 pub(crate) struct RendererD3D9<D>
 where
     D: Device,
@@ -52,6 +62,14 @@ where
     // Fills.
     buffered_fills: Vec<Fill>,
     pending_fills: Vec<Fill>,
+    // When the mask framebuffer size is limited, every fill in the scene is kept here, so that
+    // masks can be rasterized again if they don't all fit at once.
+    retained_fills: Vec<Fill>,
+    retained_fills_sorted: bool,
+
+    // The depth plane of each draw path in the current scene. All zero if paths are drawn in
+    // painter's order.
+    path_depth_planes_texture_id: Option<TextureID>,
 
     // Temporary framebuffers
     dest_blend_framebuffer_id: FramebufferID,
@@ -64,56 +82,284 @@ where
     pub(crate) fn new(
         core: &mut RendererCore<D>,
         resources: &dyn ResourceLoader,
-    ) -> RendererD3D9<D> {
-        let programs = ProgramsD3D9::new(&core.device, resources);
+    ) -> Result<RendererD3D9<D>, RenderError> {
+        let programs = ProgramsD3D9::new(&core.device, resources)?;
 
         let window_size = core.options.dest.window_size(&core.device);
-        let dest_blend_framebuffer_id = core.allocator.allocate_framebuffer(
+        let dest_blend_framebuffer_id = core.allocator.try_allocate_framebuffer(
             &core.device,
             window_size,
-            TextureFormat::RGBA8,
+            core.options.dest.format(&core.device),
             FramebufferTag("DestBlendD3D9"),
-        );
+        )?;
 
-        RendererD3D9 {
+        Ok(RendererD3D9 {
             programs,
             quads_vertex_indices_buffer_id: None,
             quads_vertex_indices_length: 0,
 
             buffered_fills: vec![],
             pending_fills: vec![],
+            retained_fills: vec![],
+            retained_fills_sorted: true,
+
+            path_depth_planes_texture_id: None,
 
             dest_blend_framebuffer_id,
-        }
+        })
     }
 
     pub(crate) fn upload_and_draw_tiles(
         &mut self,
         core: &mut RendererCore<D>,
         batch: &DrawTileBatchD3D9,
-    ) {
-        if !batch.clips.is_empty() {
-            let clip_buffer_info = self.upload_clip_tiles(core, &batch.clips);
-            self.clip_tiles(core, &clip_buffer_info);
-            core.allocator
-                .free_general_buffer(clip_buffer_info.clip_buffer_id);
+    ) -> Result<(), RenderError> {
+        if self.mask_is_spilled(core) {
+            return self.draw_spilled_tiles(core, batch);
         }
 
-        let tile_buffer = self.upload_tiles(core, &batch.tiles);
-        let z_buffer_texture_id = self.upload_z_buffer(core, &batch.z_buffer_data);
+        self.upload_and_clip_tiles(core, &batch.clips)?;
 
-        self.draw_tiles(
+        let z_buffer_texture_id = self.upload_z_buffer(core, &batch.z_buffer_data)?;
+        let result = self.upload_and_draw_tile_chunks(
             core,
-            batch.tiles.len() as u32,
-            tile_buffer.tile_vertex_buffer_id,
+            &batch.tiles,
             batch.color_texture,
             batch.blend_mode,
+            batch.subpixel_coverage,
             z_buffer_texture_id,
         );
+        core.allocator.free_texture(z_buffer_texture_id);
+        result
+    }
+
+    // Draws tiles in several draw calls if they don't fit in a single tile vertex buffer.
+    fn upload_and_draw_tile_chunks(
+        &mut self,
+        core: &mut RendererCore<D>,
+        tiles: &[TileObjectPrimitive],
+        color_texture: Option<TileBatchTexture>,
+        blend_mode: BlendMode,
+        subpixel_coverage: bool,
+        z_buffer_texture_id: TextureID,
+    ) -> Result<(), RenderError> {
+        // The tile shader for devices at the D3D9 feature level has no subpixel coverage, so it
+        // falls back to grayscale.
+        let subpixel_coverage =
+            subpixel_coverage && core.device.feature_level() != FeatureLevel::D3D9;
+
+        let coverage_passes: &[CoveragePass] = if !subpixel_coverage {
+            &[CoveragePass::Grayscale]
+        } else if self.programs.tile_dual_source_program.is_some() {
+            &[CoveragePass::DualSource]
+        } else {
+            &[CoveragePass::FallbackCoverage, CoveragePass::FallbackColor]
+        };
+
+        let max_tiles_per_chunk = match core.options.memory_budget.max_tile_vertex_buffer_bytes {
+            Some(max_bytes) => {
+                let tile_size = mem::size_of::<TileObjectPrimitive>() as u64;
+                ((max_bytes / tile_size) as usize).max(1)
+            }
+            None => tiles.len().max(1),
+        };
+
+        for tiles in tiles.chunks(max_tiles_per_chunk) {
+            // The two-pass fallback has to finish each path before starting the next, or paths
+            // that overlap would be blended against each other's partial results.
+            let runs = if coverage_passes.len() > 1 {
+                split_tiles_by_path(tiles)
+            } else {
+                vec![tiles]
+            };
+
+            for tiles in runs {
+                let tile_buffer = self.upload_tiles(core, tiles);
+                let result = coverage_passes.iter().try_for_each(|&coverage_pass| {
+                    self.draw_tiles(
+                        core,
+                        tiles.len() as u32,
+                        tile_buffer.tile_vertex_buffer_id,
+                        color_texture,
+                        blend_mode,
+                        coverage_pass,
+                        z_buffer_texture_id,
+                    )
+                });
+                core.allocator
+                    .free_general_buffer(tile_buffer.tile_vertex_buffer_id);
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns true if the scene has more alpha tiles than the mask framebuffer can hold at once.
+    fn mask_is_spilled(&self, core: &RendererCore<D>) -> bool {
+        match core.max_mask_page_count() {
+            Some(max_page_count) => core.alpha_tile_count as u64 > (max_page_count as u64) << 16,
+            None => false,
+        }
+    }
 
+    // Draws a batch of tiles whose masks don't all fit in the mask framebuffer.
+    //
+    // The tiles are drawn in order, in as many passes as necessary. For each pass, the masks that
+    // its tiles need are assigned slots in the mask framebuffer and rasterized again from the
+    // retained fills.
+    fn draw_spilled_tiles(
+        &mut self,
+        core: &mut RendererCore<D>,
+        batch: &DrawTileBatchD3D9,
+    ) -> Result<(), RenderError> {
+        if !self.retained_fills_sorted {
+            self.retained_fills.sort_by_key(|fill| fill.link);
+            self.retained_fills_sorted = true;
+        }
+
+        let mut clips: FxHashMap<u32, Vec<Clip>> = FxHashMap::default();
+        for clip in &batch.clips {
+            clips.entry(clip.dest_tile_id.0).or_default().push(*clip);
+        }
+
+        let z_buffer_texture_id = self.upload_z_buffer(core, &batch.z_buffer_data)?;
+        let result = self.draw_spilled_passes(core, batch, &clips, z_buffer_texture_id);
         core.allocator.free_texture(z_buffer_texture_id);
-        core.allocator
-            .free_general_buffer(tile_buffer.tile_vertex_buffer_id);
+        result
+    }
+
+    fn draw_spilled_passes(
+        &mut self,
+        core: &mut RendererCore<D>,
+        batch: &DrawTileBatchD3D9,
+        clips: &FxHashMap<u32, Vec<Clip>>,
+        z_buffer_texture_id: TextureID,
+    ) -> Result<(), RenderError> {
+        let max_slot_count = (core.max_mask_page_count().unwrap() as usize) << 16;
+        let mut pass = SpilledPass::default();
+        for tile in &batch.tiles {
+            let tile_clips = clips
+                .get(&tile.alpha_tile_id.0)
+                .map_or(&[][..], |clips| clips);
+            let mut new_slot_count = 0;
+            if tile.alpha_tile_id.is_valid() && !pass.slots.contains_key(&tile.alpha_tile_id.0) {
+                new_slot_count += 1;
+            }
+            for clip in tile_clips {
+                if !pass.slots.contains_key(&clip.src_tile_id.0) {
+                    new_slot_count += 1;
+                }
+            }
+            if pass.slots.len() + new_slot_count > max_slot_count {
+                self.draw_spilled_pass(core, &mut pass, batch, z_buffer_texture_id)?;
+            }
+
+            let mut tile = *tile;
+            if tile.alpha_tile_id.is_valid() {
+                tile.alpha_tile_id = pass.slot(tile.alpha_tile_id);
+            }
+            for clip in tile_clips {
+                let src_tile_id = pass.slot(clip.src_tile_id);
+                pass.clips.push(Clip {
+                    dest_tile_id: tile.alpha_tile_id,
+                    src_tile_id,
+                    ..*clip
+                });
+            }
+            pass.tiles.push(tile);
+        }
+
+        self.draw_spilled_pass(core, &mut pass, batch, z_buffer_texture_id)
+    }
+
+    fn draw_spilled_pass(
+        &mut self,
+        core: &mut RendererCore<D>,
+        pass: &mut SpilledPass,
+        batch: &DrawTileBatchD3D9,
+        z_buffer_texture_id: TextureID,
+    ) -> Result<(), RenderError> {
+        if pass.tiles.is_empty() {
+            return Ok(());
+        }
+
+        // Rasterize this pass's masks into a freshly-cleared mask framebuffer.
+        core.framebuffer_flags
+            .remove(FramebufferFlags::MASK_FRAMEBUFFER_IS_DIRTY);
+        let retained_fills = mem::take(&mut self.retained_fills);
+        for (&alpha_tile_id, &slot) in &pass.slots {
+            let start = retained_fills.partition_point(|fill| fill.link < alpha_tile_id);
+            for fill in retained_fills[start..]
+                .iter()
+                .take_while(|fill| fill.link == alpha_tile_id)
+            {
+                if self.buffered_fills.len() == MAX_FILLS_PER_BATCH {
+                    self.rasterize_buffered_fills(core);
+                }
+                self.buffered_fills.push(Fill {
+                    link: slot,
+                    ..*fill
+                });
+            }
+        }
+        self.retained_fills = retained_fills;
+        self.rasterize_buffered_fills(core);
+
+        self.upload_and_clip_tiles(core, &pass.clips)?;
+
+        self.upload_and_draw_tile_chunks(
+            core,
+            &pass.tiles,
+            batch.color_texture,
+            batch.blend_mode,
+            batch.subpixel_coverage,
+            z_buffer_texture_id,
+        )?;
+
+        pass.slots.clear();
+        pass.tiles.clear();
+        pass.clips.clear();
+        Ok(())
+    }
+
+    pub(crate) fn clear_retained_fills(&mut self) {
+        self.retained_fills.clear();
+        self.retained_fills_sorted = true;
+    }
+
+    pub(crate) fn upload_path_depth_planes(
+        &mut self,
+        core: &mut RendererCore<D>,
+        path_depth_planes: &[PathDepthPlane],
+    ) -> Result<(), RenderError> {
+        if let Some(texture_id) = self.path_depth_planes_texture_id.take() {
+            core.allocator.free_texture(texture_id);
+        }
+
+        let width = PATH_DEPTH_PLANES_TEXTURE_WIDTH;
+        let height = (path_depth_planes.len() as i32 + width - 1) / width;
+        let size = vec2i(width, i32::max(height, 1));
+        let mut texels = Vec::with_capacity(size.area() as usize * 4);
+        for plane in path_depth_planes {
+            texels.extend_from_slice(&[plane.x_coefficient, plane.y_coefficient, plane.constant]);
+            texels.push(plane.pad);
+        }
+        texels.resize(size.area() as usize * 4, 0.0);
+
+        let texture_id = core.allocator.try_allocate_texture(
+            &core.device,
+            size,
+            TextureFormat::RGBA32F,
+            TextureTag("PathDepthPlanesD3D9"),
+        )?;
+        let texture = core.allocator.get_texture(texture_id);
+        core.device.upload_to_texture(
+            texture,
+            RectI::new(Vector2I::default(), size),
+            TextureDataRef::F32(&texels),
+        );
+        self.path_depth_planes_texture_id = Some(texture_id);
+        Ok(())
     }
 
     fn upload_tiles(
@@ -179,15 +425,24 @@ where
         self.quads_vertex_indices_length = length;
     }
 
-    pub(crate) fn add_fills(&mut self, core: &mut RendererCore<D>, fill_batch: &[Fill]) {
+    pub(crate) fn add_fills(
+        &mut self,
+        core: &mut RendererCore<D>,
+        fill_batch: &[Fill],
+    ) -> Result<(), RenderError> {
         if fill_batch.is_empty() {
-            return;
+            return Ok(());
         }
 
         core.stats.fill_count += fill_batch.len();
 
         let preserve_alpha_mask_contents = core.alpha_tile_count > 0;
 
+        if core.max_mask_page_count().is_some() {
+            self.retained_fills.extend_from_slice(fill_batch);
+            self.retained_fills_sorted = false;
+        }
+
         self.pending_fills.reserve(fill_batch.len());
         for fill in fill_batch {
             core.alpha_tile_count = core.alpha_tile_count.max(fill.link + 1);
@@ -196,20 +451,38 @@ where
 
         core.stats.alpha_tile_count = core.alpha_tile_count as usize;
 
-        core.reallocate_alpha_tile_pages_if_necessary(preserve_alpha_mask_contents);
+        core.reallocate_alpha_tile_pages_if_necessary(preserve_alpha_mask_contents)?;
+
+        // If the masks no longer fit, they'll be rasterized from the retained fills as tiles are
+        // drawn instead.
+        if self.mask_is_spilled(core) {
+            self.pending_fills.clear();
+            self.buffered_fills.clear();
+            return Ok(());
+        }
 
         if self.buffered_fills.len() + self.pending_fills.len() > MAX_FILLS_PER_BATCH {
             self.draw_buffered_fills(core);
         }
 
         self.buffered_fills.extend(self.pending_fills.drain(..));
+        Ok(())
     }
 
     pub(crate) fn draw_buffered_fills(&mut self, core: &mut RendererCore<D>) {
+        if self.mask_is_spilled(core) {
+            self.buffered_fills.clear();
+            return;
+        }
+        self.rasterize_buffered_fills(core);
+    }
+
+    fn rasterize_buffered_fills(&mut self, core: &mut RendererCore<D>) {
         if self.buffered_fills.is_empty() {
             return;
         }
 
+        trace_scope!("draw_fills", fill_count = self.buffered_fills.len());
         let fill_storage_info = self.upload_buffered_fills(core);
         self.draw_fills(
             core,
@@ -307,6 +580,10 @@ where
                         &fill_raster_program.tile_size_uniform,
                         UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32)),
                     ),
+                    (
+                        &fill_raster_program.supersample_uniform,
+                        UniformData::Int((core.options.fill_mode == FillMode::Supersample) as i32),
+                    ),
                 ],
                 images: &[],
                 storage_buffers: &[],
@@ -328,7 +605,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Fill);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -339,14 +616,49 @@ where
             .insert(FramebufferFlags::MASK_FRAMEBUFFER_IS_DIRTY);
     }
 
-    fn clip_tiles(&mut self, core: &mut RendererCore<D>, clip_buffer_info: &ClipBufferInfo) {
+    // Combines clip masks into the masks of the tiles they clip.
+    //
+    // Each round of combining reads the masks from a copy made before the round starts, so jobs
+    // that combine into the same tile run in separate rounds, in order.
+    fn upload_and_clip_tiles(
+        &mut self,
+        core: &mut RendererCore<D>,
+        clips: &[Clip],
+    ) -> Result<(), RenderError> {
+        trace_scope!("clip_tiles", clip_count = clips.len());
+        let mut rounds: Vec<Vec<Clip>> = vec![];
+        let mut next_rounds: FxHashMap<u32, usize> = FxHashMap::default();
+        for clip in clips {
+            let round = next_rounds.entry(clip.dest_tile_id.0).or_insert(0);
+            if *round == rounds.len() {
+                rounds.push(vec![]);
+            }
+            rounds[*round].push(*clip);
+            *round += 1;
+        }
+
+        for round in rounds {
+            let clip_buffer_info = self.upload_clip_tiles(core, &round);
+            let result = self.clip_tiles(core, &clip_buffer_info);
+            core.allocator
+                .free_general_buffer(clip_buffer_info.clip_buffer_id);
+            result?;
+        }
+        Ok(())
+    }
+
+    fn clip_tiles(
+        &mut self,
+        core: &mut RendererCore<D>,
+        clip_buffer_info: &ClipBufferInfo,
+    ) -> Result<(), RenderError> {
         // Allocate temp mask framebuffer.
-        let mask_temp_framebuffer_id = core.allocator.allocate_framebuffer(
+        let mask_temp_framebuffer_id = core.allocator.try_allocate_framebuffer(
             &core.device,
             self.mask_viewport(core).size(),
             core.mask_texture_format(),
             FramebufferTag("TempClipMaskD3D9"),
-        );
+        )?;
         let mask_temp_framebuffer = core.allocator.get_framebuffer(mask_temp_framebuffer_id);
 
         let mask_storage = core
@@ -416,7 +728,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Other);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -453,7 +765,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Other);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -461,19 +773,20 @@ where
             .push_query(TimeCategory::Other, timer_query);
 
         core.allocator.free_framebuffer(mask_temp_framebuffer_id);
+        Ok(())
     }
 
     fn upload_z_buffer(
         &mut self,
         core: &mut RendererCore<D>,
         z_buffer_map: &DenseTileMap<i32>,
-    ) -> TextureID {
-        let z_buffer_texture_id = core.allocator.allocate_texture(
+    ) -> Result<TextureID, RenderError> {
+        let z_buffer_texture_id = core.allocator.try_allocate_texture(
             &core.device,
             z_buffer_map.rect.size(),
             TextureFormat::RGBA8,
             TextureTag("ZBufferD3D9"),
-        );
+        )?;
         let z_buffer_texture = core.allocator.get_texture(z_buffer_texture_id);
         debug_assert_eq!(z_buffer_map.rect.origin(), Vector2I::default());
         let z_data: &[u8] = z_buffer_map.data.as_byte_slice();
@@ -482,7 +795,7 @@ where
             z_buffer_map.rect,
             TextureDataRef::U8(&z_data),
         );
-        z_buffer_texture_id
+        Ok(z_buffer_texture_id)
     }
 
     // Uploads clip tiles from CPU to GPU.
@@ -508,19 +821,24 @@ where
         tile_vertex_buffer_id: GeneralBufferID,
         color_texture_0: Option<TileBatchTexture>,
         blend_mode: BlendMode,
+        coverage_pass: CoveragePass,
         z_buffer_texture_id: TextureID,
-    ) {
+    ) -> Result<(), RenderError> {
         // TODO(pcwalton): Disable blend for solid tiles.
 
         if tile_count == 0 {
-            return;
+            return Ok(());
         }
 
-        core.stats.total_tile_count += tile_count as usize;
+        trace_scope!("draw_tiles", tile_count);
+
+        if coverage_pass != CoveragePass::FallbackCoverage {
+            core.stats.total_tile_count += tile_count as usize;
+        }
 
         let needs_readable_framebuffer = blend_mode.needs_readable_framebuffer();
         if needs_readable_framebuffer {
-            self.copy_alpha_tiles_to_dest_blend_texture(core, tile_count, tile_vertex_buffer_id);
+            self.copy_alpha_tiles_to_dest_blend_texture(core, tile_count, tile_vertex_buffer_id)?;
         }
 
         let clear_color = core.clear_color_for_draw_operation();
@@ -530,7 +848,10 @@ where
             .timer_query_cache
             .start_timing_draw_call(&core.device, &core.options);
 
-        let tile_raster_program = &self.programs.tile_program;
+        let tile_raster_program = match coverage_pass {
+            CoveragePass::DualSource => self.programs.tile_dual_source_program.as_ref().unwrap(),
+            _ => &self.programs.tile_program,
+        };
 
         let tile_vertex_buffer = core.allocator.get_general_buffer(tile_vertex_buffer_id);
         let quad_vertex_positions_buffer = core
@@ -556,6 +877,10 @@ where
             &tile_raster_program.transform_uniform,
             UniformData::Mat4(self.tile_transform(core).to_columns()),
         ));
+        uniforms.push((
+            &tile_raster_program.coverage_only_uniform,
+            UniformData::Int((coverage_pass == CoveragePass::FallbackCoverage) as i32),
+        ));
         textures.push((
             &tile_raster_program.dest_texture,
             core.device.framebuffer_texture(dest_blend_framebuffer),
@@ -571,9 +896,26 @@ where
             UniformData::IVec2(core.device.texture_size(z_buffer_texture).0),
         ));
 
+        let path_depth_planes_texture = core.allocator.get_texture(
+            self.path_depth_planes_texture_id
+                .expect("Path depth planes weren't uploaded!"),
+        );
+        textures.push((
+            &tile_raster_program.path_depth_planes_texture,
+            path_depth_planes_texture,
+        ));
+        uniforms.push((
+            &tile_raster_program.path_depth_planes_size_uniform,
+            UniformData::IVec2(core.device.texture_size(path_depth_planes_texture).0),
+        ));
+        uniforms.push((
+            &tile_raster_program.path_depth_enabled_uniform,
+            UniformData::Int(core.renderer_flags.contains(RendererFlags::USE_PATH_DEPTH) as i32),
+        ));
+
         let tile_vertex_array = TileVertexArrayD3D9::new(
             &core.device,
-            &self.programs.tile_program,
+            tile_raster_program,
             tile_vertex_buffer,
             quad_vertex_positions_buffer,
             quad_vertex_indices_buffer,
@@ -593,7 +935,8 @@ where
                 uniforms: &uniforms,
                 viewport: draw_viewport,
                 options: RenderOptions {
-                    blend: blend_mode.to_blend_state(),
+                    blend: coverage_pass.blend_state(blend_mode),
+                    depth: self.depth_state(core),
                     stencil: self.stencil_state(core),
                     clear_ops: ClearOps {
                         color: clear_color,
@@ -604,7 +947,7 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Composite);
         core.finish_timing_draw_call(&timer_query);
         core.current_timer
             .as_mut()
@@ -612,6 +955,7 @@ where
             .push_query(TimeCategory::Composite, timer_query);
 
         core.preserve_draw_framebuffer();
+        Ok(())
     }
 
     fn copy_alpha_tiles_to_dest_blend_texture(
@@ -619,7 +963,7 @@ where
         core: &mut RendererCore<D>,
         tile_count: u32,
         vertex_buffer_id: GeneralBufferID,
-    ) {
+    ) -> Result<(), RenderError> {
         let draw_viewport = core.draw_viewport();
 
         let mut textures = vec![];
@@ -634,6 +978,25 @@ where
             ),
         ];
 
+        // Keep the copy at the precision of the destination, in case it has changed. Copies from
+        // 8-bit render target pages lose nothing by going through a wider texture.
+        let dest_format = core.options.dest.format(&core.device);
+        let dest_blend_texture = core.device.framebuffer_texture(
+            core.allocator
+                .get_framebuffer(self.dest_blend_framebuffer_id),
+        );
+        if core.device.texture_format(dest_blend_texture) != dest_format {
+            let size = core.device.texture_size(dest_blend_texture);
+            core.allocator
+                .free_framebuffer(self.dest_blend_framebuffer_id);
+            self.dest_blend_framebuffer_id = core.allocator.try_allocate_framebuffer(
+                &core.device,
+                size,
+                dest_format,
+                FramebufferTag("DestBlendD3D9"),
+            )?;
+        }
+
         let draw_framebuffer = match core.draw_render_target() {
             RenderTarget::Framebuffer(framebuffer) => framebuffer,
             RenderTarget::Default => panic!("Can't copy alpha tiles from default framebuffer!"),
@@ -687,11 +1050,26 @@ where
             },
         );
 
-        core.stats.drawcall_count += 1;
+        core.stats.count_drawcall(TimeCategory::Other);
+        Ok(())
+    }
+
+    fn depth_state(&self, core: &RendererCore<D>) -> Option<DepthState> {
+        if !core.renderer_flags.contains(RendererFlags::USE_PATH_DEPTH) {
+            return None;
+        }
+
+        // Equal depths pass, so that paths on the same plane stay in painter's order.
+        Some(DepthState {
+            func: DepthFunc::LessEqual,
+            write: true,
+        })
     }
 
     fn stencil_state(&self, core: &RendererCore<D>) -> Option<StencilState> {
-        if !core.renderer_flags.contains(RendererFlags::USE_DEPTH) {
+        if !core.renderer_flags.contains(RendererFlags::USE_DEPTH)
+            || core.renderer_flags.contains(RendererFlags::USE_PATH_DEPTH)
+        {
             return None;
         }
 
@@ -740,3 +1118,77 @@ struct ClipBufferInfo {
     clip_buffer_id: GeneralBufferID,
     clip_count: u32,
 }
+
+// The tiles drawn in one pass when the masks don't all fit in the mask framebuffer.
+#[derive(Default)]
+struct SpilledPass {
+    // Maps alpha tile IDs to the mask framebuffer slots that their masks are rasterized into.
+    slots: FxHashMap<u32, u32>,
+    tiles: Vec<TileObjectPrimitive>,
+    clips: Vec<Clip>,
+}
+
+impl SpilledPass {
+    fn slot(&mut self, alpha_tile_id: AlphaTileId) -> AlphaTileId {
+        let next_slot = self.slots.len() as u32;
+        AlphaTileId(*self.slots.entry(alpha_tile_id.0).or_insert(next_slot))
+    }
+}
+
+// How a draw of tiles handles per-channel coverage.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CoveragePass {
+    // Coverage is the same in every channel, and tiles are composited with their blend mode.
+    Grayscale,
+    // Subpixel coverage, composited in one pass with dual-source blending.
+    DualSource,
+    // The first pass of the fallback for subpixel coverage, which scales the destination by one
+    // minus the alpha of each channel.
+    FallbackCoverage,
+    // The second pass of the fallback, which adds the premultiplied color.
+    FallbackColor,
+}
+
+impl CoveragePass {
+    fn blend_state(self, blend_mode: BlendMode) -> Option<BlendState> {
+        match self {
+            CoveragePass::Grayscale => blend_mode.to_blend_state(),
+            CoveragePass::DualSource => Some(BlendState {
+                src_rgb_factor: BlendFactor::One,
+                dest_rgb_factor: BlendFactor::OneMinusSrc1Color,
+                src_alpha_factor: BlendFactor::One,
+                dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+                ..BlendState::default()
+            }),
+            CoveragePass::FallbackCoverage => Some(BlendState {
+                src_rgb_factor: BlendFactor::Zero,
+                dest_rgb_factor: BlendFactor::OneMinusSrcColor,
+                src_alpha_factor: BlendFactor::Zero,
+                dest_alpha_factor: BlendFactor::OneMinusSrcAlpha,
+                ..BlendState::default()
+            }),
+            CoveragePass::FallbackColor => Some(BlendState {
+                src_rgb_factor: BlendFactor::One,
+                dest_rgb_factor: BlendFactor::One,
+                src_alpha_factor: BlendFactor::One,
+                dest_alpha_factor: BlendFactor::One,
+                ..BlendState::default()
+            }),
+        }
+    }
+}
+
+// Splits tiles into runs that each belong to a single path.
+fn split_tiles_by_path(mut tiles: &[TileObjectPrimitive]) -> Vec<&[TileObjectPrimitive]> {
+    let mut runs = vec![];
+    while let Some(first_tile) = tiles.first() {
+        let run_length = tiles
+            .iter()
+            .position(|tile| tile.path_id != first_tile.path_id)
+            .unwrap_or(tiles.len());
+        let (run, rest) = tiles.split_at(run_length);
+        runs.push(run);
+        tiles = rest;
+    }
+    runs
+}
@@ -11,11 +11,12 @@
 //! Shaders and vertex specifications for the Direct3D 9-level renderer.
 
 use crate::gpu::shaders::{TileProgramCommon, TILE_INSTANCE_SIZE};
-use pathfinder_gpu::{BufferTarget, Device, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::{BufferTarget, Device, ProgramKind, VertexAttrClass, VertexAttrDescriptor};
+use pathfinder_gpu::{DeviceError, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 
 const FILL_INSTANCE_SIZE: usize = 12;
-const CLIP_TILE_INSTANCE_SIZE: usize = 16;
+const CLIP_TILE_INSTANCE_SIZE: usize = 32;
 
 pub(crate) struct FillVertexArrayD3D9<D>
 where
@@ -333,12 +334,18 @@ where
         let dest_backdrop_attr = device
             .get_vertex_attr(&clip_tile_combine_program.program, "DestBackdrop")
             .unwrap();
+        let dest_ctrl_attr = device
+            .get_vertex_attr(&clip_tile_combine_program.program, "DestCtrl")
+            .unwrap();
         let src_tile_index_attr = device
             .get_vertex_attr(&clip_tile_combine_program.program, "SrcTileIndex")
             .unwrap();
         let src_backdrop_attr = device
             .get_vertex_attr(&clip_tile_combine_program.program, "SrcBackdrop")
             .unwrap();
+        let src_ctrl_attr = device
+            .get_vertex_attr(&clip_tile_combine_program.program, "SrcCtrl")
+            .unwrap();
 
         device.bind_buffer(
             &vertex_array,
@@ -387,7 +394,7 @@ where
         );
         device.configure_vertex_attr(
             &vertex_array,
-            &src_tile_index_attr,
+            &dest_ctrl_attr,
             &VertexAttrDescriptor {
                 size: 1,
                 class: VertexAttrClass::Int,
@@ -398,6 +405,19 @@ where
                 buffer_index: 1,
             },
         );
+        device.configure_vertex_attr(
+            &vertex_array,
+            &src_tile_index_attr,
+            &VertexAttrDescriptor {
+                size: 1,
+                class: VertexAttrClass::Int,
+                attr_type: VertexAttrType::I32,
+                stride: CLIP_TILE_INSTANCE_SIZE,
+                offset: 16,
+                divisor: 1,
+                buffer_index: 1,
+            },
+        );
         device.configure_vertex_attr(
             &vertex_array,
             &src_backdrop_attr,
@@ -406,7 +426,20 @@ where
                 class: VertexAttrClass::Int,
                 attr_type: VertexAttrType::I32,
                 stride: CLIP_TILE_INSTANCE_SIZE,
-                offset: 12,
+                offset: 20,
+                divisor: 1,
+                buffer_index: 1,
+            },
+        );
+        device.configure_vertex_attr(
+            &vertex_array,
+            &src_ctrl_attr,
+            &VertexAttrDescriptor {
+                size: 1,
+                class: VertexAttrClass::Int,
+                attr_type: VertexAttrType::I32,
+                stride: CLIP_TILE_INSTANCE_SIZE,
+                offset: 24,
                 divisor: 1,
                 buffer_index: 1,
             },
@@ -476,23 +509,26 @@ where
     pub(crate) framebuffer_size_uniform: D::Uniform,
     pub(crate) tile_size_uniform: D::Uniform,
     pub(crate) area_lut_texture: D::TextureParameter,
+    pub(crate) supersample_uniform: D::Uniform,
 }
 
 impl<D> FillProgramD3D9<D>
 where
     D: Device,
 {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> FillProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/fill");
+    fn new(device: &D, resources: &dyn ResourceLoader) -> Result<FillProgramD3D9<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "d3d9/fill")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let area_lut_texture = device.get_texture_parameter(&program, "AreaLUT");
-        FillProgramD3D9 {
+        let supersample_uniform = device.get_uniform(&program, "Supersample");
+        Ok(FillProgramD3D9 {
             program,
             framebuffer_size_uniform,
             tile_size_uniform,
             area_lut_texture,
-        }
+            supersample_uniform,
+        })
     }
 }
 
@@ -503,22 +539,45 @@ where
     pub(crate) common: TileProgramCommon<D>,
     pub(crate) dest_texture: D::TextureParameter,
     pub(crate) transform_uniform: D::Uniform,
+    pub(crate) coverage_only_uniform: D::Uniform,
+    pub(crate) path_depth_planes_texture: D::TextureParameter,
+    pub(crate) path_depth_planes_size_uniform: D::Uniform,
+    pub(crate) path_depth_enabled_uniform: D::Uniform,
 }
 
 impl<D> TileProgramD3D9<D>
 where
     D: Device,
 {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> TileProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile");
+    fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+        fragment_name: &str,
+    ) -> Result<TileProgramD3D9<D>, DeviceError> {
+        let program = device.try_create_program_from_shader_names(
+            resources,
+            fragment_name,
+            ProgramKind::Raster {
+                vertex: "d3d9/tile",
+                fragment: fragment_name,
+            },
+        )?;
         let dest_texture = device.get_texture_parameter(&program, "DestTexture");
         let transform_uniform = device.get_uniform(&program, "Transform");
+        let coverage_only_uniform = device.get_uniform(&program, "CoverageOnly");
+        let path_depth_planes_texture = device.get_texture_parameter(&program, "PathDepthPlanes");
+        let path_depth_planes_size_uniform = device.get_uniform(&program, "PathDepthPlanesSize");
+        let path_depth_enabled_uniform = device.get_uniform(&program, "PathDepthEnabled");
         let common = TileProgramCommon::new(device, program);
-        TileProgramD3D9 {
+        Ok(TileProgramD3D9 {
             common,
             dest_texture,
             transform_uniform,
-        }
+            coverage_only_uniform,
+            path_depth_planes_texture,
+            path_depth_planes_size_uniform,
+            path_depth_enabled_uniform,
+        })
     }
 }
 
@@ -535,15 +594,18 @@ impl<D> ClipTileCombineProgramD3D9<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileCombineProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_clip_combine");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ClipTileCombineProgramD3D9<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "d3d9/tile_clip_combine")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
-        ClipTileCombineProgramD3D9 {
+        Ok(ClipTileCombineProgramD3D9 {
             program,
             src_texture,
             framebuffer_size_uniform,
-        }
+        })
     }
 }
 
@@ -560,15 +622,18 @@ impl<D> ClipTileCopyProgramD3D9<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileCopyProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_clip_copy");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ClipTileCopyProgramD3D9<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "d3d9/tile_clip_copy")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
-        ClipTileCopyProgramD3D9 {
+        Ok(ClipTileCopyProgramD3D9 {
             program,
             src_texture,
             framebuffer_size_uniform,
-        }
+        })
     }
 }
 
@@ -587,19 +652,22 @@ impl<D> CopyTileProgram<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> CopyTileProgram<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_copy");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<CopyTileProgram<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "d3d9/tile_copy")?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let src_texture = device.get_texture_parameter(&program, "Src");
-        CopyTileProgram {
+        Ok(CopyTileProgram {
             program,
             transform_uniform,
             tile_size_uniform,
             framebuffer_size_uniform,
             src_texture,
-        }
+        })
     }
 }
 
@@ -609,6 +677,9 @@ where
 {
     pub(crate) fill_program: FillProgramD3D9<D>,
     pub(crate) tile_program: TileProgramD3D9<D>,
+    // Draws tiles with subpixel coverage in one pass. Only present if the device supports
+    // dual-source blending.
+    pub(crate) tile_dual_source_program: Option<TileProgramD3D9<D>>,
     pub(crate) tile_clip_copy_program: ClipTileCopyProgramD3D9<D>,
     pub(crate) tile_clip_combine_program: ClipTileCombineProgramD3D9<D>,
     pub(crate) tile_copy_program: CopyTileProgram<D>,
@@ -618,13 +689,25 @@ impl<D> ProgramsD3D9<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsD3D9<D> {
-        ProgramsD3D9 {
-            fill_program: FillProgramD3D9::new(device, resources),
-            tile_program: TileProgramD3D9::new(device, resources),
-            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources),
-            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources),
-            tile_copy_program: CopyTileProgram::new(device, resources),
-        }
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ProgramsD3D9<D>, DeviceError> {
+        Ok(ProgramsD3D9 {
+            fill_program: FillProgramD3D9::new(device, resources)?,
+            tile_program: TileProgramD3D9::new(device, resources, "d3d9/tile")?,
+            tile_dual_source_program: if device.capabilities().dual_source_blending {
+                Some(TileProgramD3D9::new(
+                    device,
+                    resources,
+                    "d3d9/tile_dual_source",
+                )?)
+            } else {
+                None
+            },
+            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources)?,
+            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources)?,
+            tile_copy_program: CopyTileProgram::new(device, resources)?,
+        })
     }
 }
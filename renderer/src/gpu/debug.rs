@@ -34,7 +34,7 @@ const STATS_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
 
 const PERFORMANCE_WINDOW_WIDTH: i32 = 400;
 const PERFORMANCE_WINDOW_HEIGHT_D3D9: i32 = LINE_HEIGHT * 8 + PADDING + 2;
-const PERFORMANCE_WINDOW_HEIGHT_D3D11: i32 = LINE_HEIGHT * 10 + PADDING + 2;
+const PERFORMANCE_WINDOW_HEIGHT_D3D11: i32 = LINE_HEIGHT * 11 + PADDING + 2;
 
 const INFO_WINDOW_WIDTH: i32 = 425;
 const INFO_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 2 + PADDING + 2;
@@ -278,6 +278,17 @@ where
                     false,
                 );
                 current_y += LINE_HEIGHT;
+                self.ui_presenter.draw_text(
+                    device,
+                    allocator,
+                    &format!(
+                        "GPU Tile: {:.3} ms",
+                        duration_to_ms(mean_gpu_sample.tile_time)
+                    ),
+                    origin + vec2i(0, current_y),
+                    false,
+                );
+                current_y += LINE_HEIGHT;
             }
             RendererLevel::D3D9 => {}
         }
@@ -328,6 +339,7 @@ where
         wallclock_time += duration_to_ms(mean_gpu_sample.composite_time)
             + duration_to_ms(mean_gpu_sample.dice_time)
             + duration_to_ms(mean_gpu_sample.bin_time)
+            + duration_to_ms(mean_gpu_sample.tile_time)
             + duration_to_ms(mean_gpu_sample.other_time);
         self.ui_presenter.draw_text(
             device,
@@ -18,4 +18,8 @@ pub mod perf;
 pub mod renderer;
 
 pub(crate) mod blend;
+pub(crate) mod offscreen;
 pub(crate) mod shaders;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,59 @@
+// pathfinder/renderer/src/gpu/offscreen.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A pool of off-screen framebuffers that scenes can be rendered into.
+
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::{Device, TextureFormat};
+
+// The maximum number of idle framebuffers to keep around for reuse.
+const MAX_POOLED_FRAMEBUFFERS: usize = 4;
+
+pub(crate) struct OffscreenTargetPool<D>
+where
+    D: Device,
+{
+    framebuffers: Vec<D::Framebuffer>,
+}
+
+impl<D> OffscreenTargetPool<D>
+where
+    D: Device,
+{
+    #[inline]
+    pub(crate) fn new() -> OffscreenTargetPool<D> {
+        OffscreenTargetPool {
+            framebuffers: vec![],
+        }
+    }
+
+    /// Returns an idle framebuffer of the given size, creating one if necessary.
+    pub(crate) fn acquire(&mut self, device: &D, size: Vector2I) -> D::Framebuffer {
+        let index = self.framebuffers.iter().position(|framebuffer| {
+            device.texture_size(device.framebuffer_texture(framebuffer)) == size
+        });
+        match index {
+            Some(index) => self.framebuffers.remove(index),
+            None => {
+                let texture = device.create_texture(TextureFormat::RGBA8, size);
+                device.create_framebuffer(texture)
+            }
+        }
+    }
+
+    /// Returns a framebuffer to the pool, evicting the least recently used one if the pool is
+    /// full.
+    pub(crate) fn release(&mut self, device: &D, framebuffer: D::Framebuffer) {
+        if self.framebuffers.len() == MAX_POOLED_FRAMEBUFFERS {
+            device.destroy_framebuffer(self.framebuffers.remove(0));
+        }
+        self.framebuffers.push(framebuffer);
+    }
+}
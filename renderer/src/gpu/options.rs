@@ -13,7 +13,7 @@
 use pathfinder_color::ColorF;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
-use pathfinder_gpu::{Device, FeatureLevel};
+use pathfinder_gpu::{Device, FeatureLevel, RenderTarget, TextureFormat, UniformData};
 
 /// Renderer options that can't be changed after the renderer is created.
 pub struct RendererMode {
@@ -33,6 +33,77 @@ where
     pub background_color: Option<ColorF>,
     /// Whether to display the debug UI.
     pub show_debug_ui: bool,
+    /// How the fill shaders compute the coverage of each pixel.
+    pub fill_mode: FillMode,
+    /// Limits on the sizes of the GPU resources that the renderer allocates.
+    pub memory_budget: MemoryBudget,
+    /// Whether to apply an ordered dither to the output, which hides banding in large, soft
+    /// gradients. This only has an effect when the destination has 8 bits per channel, and only
+    /// applies to what's drawn to the destination: render targets aren't dithered, and nothing
+    /// is when there are post-processing filters.
+    pub dither: bool,
+    /// Full-frame filters to run, in order, over the composited frame before it reaches the
+    /// destination. Each filter costs an extra pass over every pixel of the viewport.
+    pub post_process: Vec<PostProcessFilter<D>>,
+}
+
+/// A full-frame filter that runs over the composited frame before it's presented.
+pub enum PostProcessFilter<D>
+where
+    D: Device,
+{
+    /// Remaps colors through a 3D color lookup table.
+    ///
+    /// The texture is a strip of N slices of N×N texels each, laid side by side for a total size
+    /// of N²×N. Within a slice, red increases to the right and green increases downward; blue
+    /// selects the slice. The texture should use linear filtering.
+    ColorLut(D::Texture),
+    /// Fades the edges of the frame toward a color.
+    Vignette {
+        /// The color to fade toward. Its alpha is the strength of the effect.
+        color: ColorF,
+        /// The distance from the center at which the fade begins, where the corners are at 1.0.
+        radius: f32,
+        /// The distance over which the fade ramps up to full strength.
+        softness: f32,
+    },
+    /// Raises each color channel to the power `1 / gamma`.
+    Gamma(f32),
+    /// Runs an application-supplied shader.
+    Custom(CustomPostProcessFilter<D>),
+}
+
+/// A post-processing pass that runs an application-supplied shader program.
+pub struct CustomPostProcessFilter<D>
+where
+    D: Device,
+{
+    pub(crate) program: D::Program,
+    pub(crate) dest_rect_uniform: D::Uniform,
+    pub(crate) framebuffer_size_uniform: D::Uniform,
+    pub(crate) src_texture: D::TextureParameter,
+    /// Additional uniforms to set when running the pass.
+    pub uniforms: Vec<(D::Uniform, UniformData)>,
+    /// Additional textures to bind when running the pass.
+    pub textures: Vec<(D::TextureParameter, D::Texture)>,
+}
+
+/// Limits on the sizes of GPU resources, for devices with little video memory.
+///
+/// When a scene needs more than these allow, the renderer splits the work into several smaller
+/// passes. This is slower, but it lets very complex scenes render instead of failing to allocate.
+///
+/// Currently, only the D3D9 level renderer honors these limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryBudget {
+    /// The maximum size of the alpha mask framebuffer in bytes, or `None` for no limit.
+    ///
+    /// The mask framebuffer grows in pages of 65,536 tiles each (32 MB at the D3D9 level), so this
+    /// is rounded down to a whole number of pages, with a minimum of one. Note that clipping
+    /// temporarily allocates a second framebuffer of the same size.
+    pub max_mask_framebuffer_bytes: Option<u64>,
+    /// The maximum size of a single tile vertex buffer in bytes, or `None` for no limit.
+    pub max_tile_vertex_buffer_bytes: Option<u64>,
 }
 
 /// The GPU API level that Pathfinder will use.
@@ -43,11 +114,32 @@ where
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum RendererLevel {
     /// Direct3D 9/OpenGL 3.0/WebGL 2.0 compatibility. Bin on CPU, fill and composite on GPU.
+    ///
+    /// This level also runs on OpenGL ES 2.0 and WebGL 1-class devices, at reduced quality:
+    /// subpixel coverage falls back to grayscale, and only radial gradient filters are
+    /// supported.
     D3D9,
     /// Direct3D 11/OpenGL 4.3/Metal/Vulkan/WebGPU compatibility. Bin, fill, and composite on GPU.
     D3D11,
 }
 
+/// How the fill shaders compute pixel coverage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillMode {
+    /// Compute exact coverage analytically with the help of an area lookup table. This is the
+    /// default and gives the best quality.
+    Analytic,
+    /// Approximate coverage by supersampling: the fill shader tests each edge against 8 points
+    /// per pixel, laid out in the standard 8x multisampling pattern, and counts the points below
+    /// it.
+    ///
+    /// This happens entirely in the fill shader. No multisampled render targets or stencil
+    /// buffers are involved, so it works on any device. It avoids the area lookup table, which
+    /// makes it an escape hatch for drivers that miscompile or mis-sample in the analytic fill
+    /// shader.
+    Supersample,
+}
+
 impl RendererMode {
     /// Creates a new `RendererMode` with a suitable API level for the given GPU device.
     #[inline]
@@ -71,10 +163,50 @@ where
             dest: DestFramebuffer::default(),
             background_color: None,
             show_debug_ui: false,
+            fill_mode: FillMode::default(),
+            memory_budget: MemoryBudget::default(),
+            dither: false,
+            post_process: vec![],
         }
     }
 }
 
+impl Default for FillMode {
+    #[inline]
+    fn default() -> FillMode {
+        FillMode::Analytic
+    }
+}
+
+impl<D> CustomPostProcessFilter<D>
+where
+    D: Device,
+{
+    /// Wraps a program created with `Device::create_post_process_program()`.
+    ///
+    /// The renderer binds the frame to the program's `uSrc` texture. Use `program()` to look up
+    /// any additional uniforms and textures.
+    pub fn new(device: &D, program: D::Program) -> CustomPostProcessFilter<D> {
+        let dest_rect_uniform = device.get_uniform(&program, "DestRect");
+        let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let src_texture = device.get_texture_parameter(&program, "Src");
+        CustomPostProcessFilter {
+            program,
+            dest_rect_uniform,
+            framebuffer_size_uniform,
+            src_texture,
+            uniforms: vec![],
+            textures: vec![],
+        }
+    }
+
+    /// Returns the shader program that this pass runs.
+    #[inline]
+    pub fn program(&self) -> &D::Program {
+        &self.program
+    }
+}
+
 impl RendererLevel {
     /// Returns a suitable renderer level for the given device.
     pub fn default_for_device<D>(device: &D) -> RendererLevel
@@ -82,13 +214,18 @@ impl RendererLevel {
         D: Device,
     {
         match device.feature_level() {
-            FeatureLevel::D3D10 => RendererLevel::D3D9,
-            FeatureLevel::D3D11 => RendererLevel::D3D11,
+            FeatureLevel::D3D9 | FeatureLevel::D3D10 => RendererLevel::D3D9,
+            FeatureLevel::D3D11 if device.capabilities().compute_shaders => RendererLevel::D3D11,
+            FeatureLevel::D3D11 => RendererLevel::D3D9,
         }
     }
 }
 
 /// Where the rendered content should go.
+///
+/// To render extended-range colors, e.g. for HDR displays, use a destination with the `RGBA16F`
+/// format. The D3D9 level renders to such destinations at full precision. The D3D11 level renders
+/// through an 8-bit intermediate framebuffer, so colors are clamped to 1.0.
 #[derive(Clone)]
 pub enum DestFramebuffer<D>
 where
@@ -100,9 +237,24 @@ where
         viewport: RectI,
         /// The total size of the window in device pixels.
         window_size: Vector2I,
+        /// The format of the default framebuffer. This is `RGBA8` unless the window was created
+        /// with a floating-point framebuffer.
+        format: TextureFormat,
     },
     /// The rendered content should go to a non-default framebuffer (off-screen, typically).
     Other(D::Framebuffer),
+    /// The rendered content should go to a region of a framebuffer that the application owns,
+    /// such as one in an engine's render graph, so that it can be composited without a blit.
+    ///
+    /// Native objects can be wrapped without taking ownership: use `GLDevice::wrap_framebuffer()`
+    /// with OpenGL, or pass a texture from the device's `wrap_texture()` method to
+    /// `Device::create_framebuffer()` with the other backends.
+    External {
+        /// The wrapped framebuffer.
+        framebuffer: D::Framebuffer,
+        /// The rectangle within the framebuffer to draw in, in device pixels.
+        viewport: RectI,
+    },
 }
 
 impl<D> Default for DestFramebuffer<D>
@@ -114,6 +266,7 @@ where
         DestFramebuffer::Default {
             viewport: RectI::default(),
             window_size: Vector2I::default(),
+            format: TextureFormat::RGBA8,
         }
     }
 }
@@ -132,6 +285,7 @@ where
         DestFramebuffer::Default {
             viewport,
             window_size,
+            format: TextureFormat::RGBA8,
         }
     }
 
@@ -140,9 +294,34 @@ where
     pub fn window_size(&self, device: &D) -> Vector2I {
         match *self {
             DestFramebuffer::Default { window_size, .. } => window_size,
-            DestFramebuffer::Other(ref framebuffer) => {
-                device.texture_size(device.framebuffer_texture(framebuffer))
-            }
+            DestFramebuffer::Other(ref framebuffer)
+            | DestFramebuffer::External {
+                ref framebuffer, ..
+            } => device.texture_size(device.framebuffer_texture(framebuffer)),
+        }
+    }
+
+    /// Returns the pixel format of the destination buffer.
+    #[inline]
+    pub fn format(&self, device: &D) -> TextureFormat {
+        match *self {
+            DestFramebuffer::Default { format, .. } => format,
+            DestFramebuffer::Other(ref framebuffer)
+            | DestFramebuffer::External {
+                ref framebuffer, ..
+            } => device.texture_format(device.framebuffer_texture(framebuffer)),
+        }
+    }
+
+    /// Returns the render target that draws to the destination buffer.
+    #[inline]
+    pub fn render_target(&self) -> RenderTarget<'_, D> {
+        match *self {
+            DestFramebuffer::Default { .. } => RenderTarget::Default,
+            DestFramebuffer::Other(ref framebuffer)
+            | DestFramebuffer::External {
+                ref framebuffer, ..
+            } => RenderTarget::Framebuffer(framebuffer),
         }
     }
 }
@@ -11,6 +11,7 @@
 //! Performance monitoring infrastructure.
 
 use crate::gpu::options::RendererOptions;
+use instant::Instant;
 use pathfinder_gpu::Device;
 use std::mem;
 use std::ops::{Add, Div};
@@ -34,6 +35,8 @@ pub struct RenderStats {
     pub cpu_build_time: Duration,
     /// The number of GPU API draw calls it took to render the scene.
     pub drawcall_count: u32,
+    /// The number of GPU API draw calls it took to render the scene, broken up into stages.
+    pub drawcall_counts: DrawcallCounts,
     /// The number of bytes of VRAM Pathfinder has allocated.
     ///
     /// This may be higher than `gpu_bytes_committed` because Pathfinder caches some data for
@@ -53,6 +56,7 @@ impl Add<RenderStats> for RenderStats {
             fill_count: self.fill_count + other.fill_count,
             cpu_build_time: self.cpu_build_time + other.cpu_build_time,
             drawcall_count: self.drawcall_count + other.drawcall_count,
+            drawcall_counts: self.drawcall_counts + other.drawcall_counts,
             gpu_bytes_allocated: self.gpu_bytes_allocated + other.gpu_bytes_allocated,
             gpu_bytes_committed: self.gpu_bytes_committed + other.gpu_bytes_committed,
         }
@@ -69,17 +73,111 @@ impl Div<usize> for RenderStats {
             fill_count: self.fill_count / divisor,
             cpu_build_time: self.cpu_build_time / divisor as u32,
             drawcall_count: self.drawcall_count / divisor as u32,
+            drawcall_counts: self.drawcall_counts / divisor,
             gpu_bytes_allocated: self.gpu_bytes_allocated / divisor as u64,
             gpu_bytes_committed: self.gpu_bytes_committed / divisor as u64,
         }
     }
 }
 
+impl RenderStats {
+    #[inline]
+    pub(crate) fn count_drawcall(&mut self, time_category: TimeCategory) {
+        self.drawcall_count += 1;
+        let drawcall_count = match time_category {
+            TimeCategory::Dice => &mut self.drawcall_counts.dice,
+            TimeCategory::Bin => &mut self.drawcall_counts.bin,
+            TimeCategory::Tile => &mut self.drawcall_counts.tile,
+            TimeCategory::Fill => &mut self.drawcall_counts.fill,
+            TimeCategory::Composite => &mut self.drawcall_counts.composite,
+            TimeCategory::Other => &mut self.drawcall_counts.other,
+        };
+        *drawcall_count += 1;
+    }
+}
+
+/// The number of GPU API draw calls issued for each stage of rendering.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DrawcallCounts {
+    /// The number of dicing dispatches. Always zero in the D3D9-level backend.
+    pub dice: u32,
+    /// The number of binning dispatches. Always zero in the D3D9-level backend.
+    pub bin: u32,
+    /// The number of dispatches that bound, propagate, and sort tiles. Always zero in the
+    /// D3D9-level backend.
+    pub tile: u32,
+    /// The number of draw calls or dispatches that rendered fills to masks.
+    pub fill: u32,
+    /// The number of draw calls or dispatches that composited tiles to the output.
+    pub composite: u32,
+    /// The number of miscellaneous draw calls, such as clears, blits, and clip operations.
+    pub other: u32,
+}
+
+impl Add<DrawcallCounts> for DrawcallCounts {
+    type Output = DrawcallCounts;
+
+    #[inline]
+    fn add(self, other: DrawcallCounts) -> DrawcallCounts {
+        DrawcallCounts {
+            dice: self.dice + other.dice,
+            bin: self.bin + other.bin,
+            tile: self.tile + other.tile,
+            fill: self.fill + other.fill,
+            composite: self.composite + other.composite,
+            other: self.other + other.other,
+        }
+    }
+}
+
+impl Div<usize> for DrawcallCounts {
+    type Output = DrawcallCounts;
+
+    #[inline]
+    fn div(self, divisor: usize) -> DrawcallCounts {
+        let divisor = divisor as u32;
+        DrawcallCounts {
+            dice: self.dice / divisor,
+            bin: self.bin / divisor,
+            tile: self.tile / divisor,
+            fill: self.fill / divisor,
+            composite: self.composite / divisor,
+            other: self.other / divisor,
+        }
+    }
+}
+
+/// The statistics and GPU timings for a single rendered frame.
+///
+/// GPU timings arrive a frame or two after the frame was rendered. To line them up with CPU
+/// profiler scopes, match `frame_index` against `Renderer::frame_index()` as it was when the frame
+/// was rendered, or place the GPU work after `cpu_end_time` on the profiler's timeline.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameStats {
+    /// The index of the frame, as returned by `Renderer::frame_index()` while it was rendered.
+    pub frame_index: u64,
+    /// When `Renderer::begin_scene()` was called for the frame.
+    pub cpu_start_time: Instant,
+    /// When the renderer finished encoding the frame's GPU commands, in `Renderer::end_scene()`.
+    ///
+    /// The frame's GPU work can't start before this, except on devices that submit commands as
+    /// they're encoded, such as OpenGL.
+    pub cpu_end_time: Instant,
+    /// Statistics about the frame, gathered on the CPU as it was rendered.
+    pub stats: RenderStats,
+    /// How much GPU time each stage of rendering the frame took.
+    ///
+    /// This is all zero unless GPU timing was enabled while the frame was rendered, either via
+    /// `Renderer::set_gpu_timing_enabled()` or by showing the debug UI.
+    pub time: RenderTime,
+}
+
 pub(crate) struct TimerQueryCache<D>
 where
     D: Device,
 {
     free_queries: Vec<D::TimerQuery>,
+    pub(crate) enabled: bool,
 }
 
 pub(crate) struct PendingTimer<D>
@@ -88,9 +186,15 @@ where
 {
     pub(crate) dice_times: Vec<TimerFuture<D>>,
     pub(crate) bin_times: Vec<TimerFuture<D>>,
+    pub(crate) tile_times: Vec<TimerFuture<D>>,
     pub(crate) fill_times: Vec<TimerFuture<D>>,
     pub(crate) composite_times: Vec<TimerFuture<D>>,
     pub(crate) other_times: Vec<TimerFuture<D>>,
+    // The statistics for the frame that these timers measure, filled in when the frame ends.
+    pub(crate) stats: RenderStats,
+    pub(crate) frame_index: u64,
+    pub(crate) cpu_start_time: Instant,
+    pub(crate) cpu_end_time: Instant,
 }
 
 pub(crate) enum TimerFuture<D>
@@ -105,6 +209,7 @@ where
 pub(crate) enum TimeCategory {
     Dice,
     Bin,
+    Tile,
     Fill,
     Composite,
     Other,
@@ -117,6 +222,7 @@ where
     pub(crate) fn new() -> TimerQueryCache<D> {
         TimerQueryCache {
             free_queries: vec![],
+            enabled: false,
         }
     }
 
@@ -135,7 +241,7 @@ where
         device: &D,
         options: &RendererOptions<D>,
     ) -> Option<D::TimerQuery> {
-        if !options.show_debug_ui {
+        if !self.enabled && !options.show_debug_ui {
             return None;
         }
 
@@ -149,13 +255,19 @@ impl<D> PendingTimer<D>
 where
     D: Device,
 {
-    pub(crate) fn new() -> PendingTimer<D> {
+    pub(crate) fn new(frame_index: u64) -> PendingTimer<D> {
+        let now = Instant::now();
         PendingTimer {
             dice_times: vec![],
             bin_times: vec![],
+            tile_times: vec![],
             fill_times: vec![],
             composite_times: vec![],
             other_times: vec![],
+            stats: RenderStats::default(),
+            frame_index,
+            cpu_start_time: now,
+            cpu_end_time: now,
         }
     }
 
@@ -165,6 +277,7 @@ where
             .dice_times
             .iter_mut()
             .chain(self.bin_times.iter_mut())
+            .chain(self.tile_times.iter_mut())
             .chain(self.fill_times.iter_mut())
             .chain(self.composite_times.iter_mut())
             .chain(self.other_times.iter_mut())
@@ -179,19 +292,29 @@ where
     pub(crate) fn total_time(&self) -> Option<RenderTime> {
         let dice_time = total_time_of_timer_futures(&self.dice_times);
         let bin_time = total_time_of_timer_futures(&self.bin_times);
+        let tile_time = total_time_of_timer_futures(&self.tile_times);
         let fill_time = total_time_of_timer_futures(&self.fill_times);
         let composite_time = total_time_of_timer_futures(&self.composite_times);
         let other_time = total_time_of_timer_futures(&self.other_times);
-        match (dice_time, bin_time, fill_time, composite_time, other_time) {
+        match (
+            dice_time,
+            bin_time,
+            tile_time,
+            fill_time,
+            composite_time,
+            other_time,
+        ) {
             (
                 Some(dice_time),
                 Some(bin_time),
+                Some(tile_time),
                 Some(fill_time),
                 Some(composite_time),
                 Some(other_time),
             ) => Some(RenderTime {
                 dice_time,
                 bin_time,
+                tile_time,
                 fill_time,
                 composite_time,
                 other_time,
@@ -212,6 +335,7 @@ where
         match time_category {
             TimeCategory::Dice => self.dice_times.push(timer_future),
             TimeCategory::Bin => self.bin_times.push(timer_future),
+            TimeCategory::Tile => self.tile_times.push(timer_future),
             TimeCategory::Fill => self.fill_times.push(timer_future),
             TimeCategory::Composite => self.composite_times.push(timer_future),
             TimeCategory::Other => self.other_times.push(timer_future),
@@ -267,6 +391,10 @@ pub struct RenderTime {
     ///
     /// This will be zero in the D3D9-level backend, since in that backend binning is done on CPU.
     pub bin_time: Duration,
+    /// How much GPU time it took to compute tile bounds, propagate backdrops, and sort tiles.
+    ///
+    /// This will be zero in the D3D9-level backend, since in that backend tiling is done on CPU.
+    pub tile_time: Duration,
     /// How much GPU time it took to draw fills (i.e. render edges) to masks.
     pub fill_time: Duration,
     /// How much GPU time it took to draw the contents of the tiles to the output.
@@ -280,7 +408,12 @@ impl RenderTime {
     /// The total GPU time it took to render the scene.
     #[inline]
     pub fn total_time(&self) -> Duration {
-        self.dice_time + self.bin_time + self.fill_time + self.composite_time + self.other_time
+        self.dice_time
+            + self.bin_time
+            + self.tile_time
+            + self.fill_time
+            + self.composite_time
+            + self.other_time
     }
 }
 
@@ -290,6 +423,7 @@ impl Default for RenderTime {
         RenderTime {
             dice_time: Duration::new(0, 0),
             bin_time: Duration::new(0, 0),
+            tile_time: Duration::new(0, 0),
             fill_time: Duration::new(0, 0),
             composite_time: Duration::new(0, 0),
             other_time: Duration::new(0, 0),
@@ -305,6 +439,7 @@ impl Add<RenderTime> for RenderTime {
         RenderTime {
             dice_time: self.dice_time + other.dice_time,
             bin_time: self.bin_time + other.bin_time,
+            tile_time: self.tile_time + other.tile_time,
             fill_time: self.fill_time + other.fill_time,
             composite_time: self.composite_time + other.composite_time,
             other_time: self.other_time + other.other_time,
@@ -321,6 +456,7 @@ impl Div<usize> for RenderTime {
         RenderTime {
             dice_time: self.dice_time / divisor,
             bin_time: self.bin_time / divisor,
+            tile_time: self.tile_time / divisor,
             fill_time: self.fill_time / divisor,
             composite_time: self.composite_time / divisor,
             other_time: self.other_time / divisor,
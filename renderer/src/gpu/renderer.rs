@@ -10,40 +10,56 @@
 
 //! The GPU renderer that processes commands necessary to render a scene.
 
+use crate::concurrent::executor::Executor;
 use crate::gpu::blend::{ToBlendState, ToCompositeCtrl};
 use crate::gpu::d3d11::renderer::RendererD3D11;
 use crate::gpu::d3d9::renderer::RendererD3D9;
 use crate::gpu::debug::DebugUIPresenter;
-use crate::gpu::options::{DestFramebuffer, RendererLevel, RendererMode, RendererOptions};
-use crate::gpu::perf::{PendingTimer, RenderStats, RenderTime, TimeCategory, TimerQueryCache};
+use crate::gpu::offscreen::OffscreenTargetPool;
+use crate::gpu::options::RendererOptions;
+use crate::gpu::options::{DestFramebuffer, PostProcessFilter, RendererLevel, RendererMode};
+use crate::gpu::perf::TimerQueryCache;
+use crate::gpu::perf::{FrameStats, PendingTimer, RenderStats, RenderTime, TimeCategory};
+use crate::gpu::shaders::ReprojectionVertexArray;
 use crate::gpu::shaders::{BlitProgram, BlitVertexArray, ClearProgram, ClearVertexArray};
-use crate::gpu::shaders::{ProgramsCore, ReprojectionProgram, ReprojectionVertexArray};
+use crate::gpu::shaders::{PostProcessProgram, ProgramsCore, ReprojectionProgram};
 use crate::gpu::shaders::{
     StencilProgram, StencilVertexArray, TileProgramCommon, VertexArraysCore,
 };
 use crate::gpu_data::{ColorCombineMode, RenderCommand, TextureLocation, TextureMetadataEntry};
-use crate::gpu_data::{TexturePageDescriptor, TexturePageId, TileBatchTexture};
-use crate::options::BoundingQuad;
+use crate::gpu_data::{PathDepthPlane, TexturePageDescriptor, TexturePageId, TileBatchTexture};
+use crate::options::{BoundingQuad, BuildOptions};
+use crate::scene::Scene;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
 use half::f16;
+use instant::Instant;
 use pathfinder_color::{self as color, ColorF, ColorU};
 use pathfinder_content::effects::{BlendMode, BlurDirection, Filter, PatternFilter};
+use pathfinder_content::noise::NoiseKind;
+use pathfinder_content::paint_effect::PaintEffectKind;
+use pathfinder_content::pattern::{ExternalTextureId, Image, Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::util;
 use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I, Vector4F};
-use pathfinder_gpu::allocator::{BufferTag, FramebufferID, FramebufferTag, GeneralBufferID};
-use pathfinder_gpu::allocator::{GPUMemoryAllocator, IndexBufferID, TextureID, TextureTag};
-use pathfinder_gpu::{
-    BufferData, BufferTarget, ClearOps, DepthFunc, DepthState, Device, Primitive,
-};
+use pathfinder_gpu::allocator::GeneralBufferID;
+use pathfinder_gpu::allocator::{AllocationError, BufferTag, FramebufferID, FramebufferTag};
+use pathfinder_gpu::allocator::{GPUMemoryAllocator, GPUMemoryUsage, IndexBufferID};
+use pathfinder_gpu::allocator::{TextureID, TextureTag};
+use pathfinder_gpu::UniformData;
+use pathfinder_gpu::{BufferData, BufferTarget, ClearOps, DepthFunc, DepthState, Device};
+use pathfinder_gpu::{DeviceError, FeatureLevel, Primitive};
 use pathfinder_gpu::{RenderOptions, RenderState, RenderTarget, StencilFunc, StencilState};
-use pathfinder_gpu::{TextureBinding, TextureDataRef, TextureFormat, UniformBinding, UniformData};
+use pathfinder_gpu::{TextureBinding, TextureData, TextureDataRef, TextureFormat, UniformBinding};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
 use std::f32;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::mem;
+use std::sync::Arc;
 use std::time::Duration;
 use std::u32;
 
@@ -68,6 +84,19 @@ const COMBINER_CTRL_FILTER_RADIAL_GRADIENT: i32 = 0x1;
 const COMBINER_CTRL_FILTER_TEXT: i32 = 0x2;
 const COMBINER_CTRL_FILTER_BLUR: i32 = 0x3;
 const COMBINER_CTRL_FILTER_COLOR_MATRIX: i32 = 0x4;
+const COMBINER_CTRL_FILTER_NOISE: i32 = 0x5;
+const COMBINER_CTRL_FILTER_PAINT_EFFECT: i32 = 0x6;
+const COMBINER_CTRL_FILTER_CONIC_GRADIENT: i32 = 0x7;
+
+const PAINT_EFFECT_HATCHING: f32 = 0.0;
+const PAINT_EFFECT_CROSS_HATCHING: f32 = 1.0;
+const PAINT_EFFECT_HALFTONE: f32 = 2.0;
+
+const POST_PROCESS_FILTER_COLOR_LUT: i32 = 0;
+const POST_PROCESS_FILTER_VIGNETTE: i32 = 1;
+const POST_PROCESS_FILTER_GAMMA: i32 = 2;
+// Not a public filter: `render_to_pattern()` runs it over off-screen renders.
+const POST_PROCESS_FILTER_UNPREMULTIPLY: i32 = 3;
 
 const COMBINER_CTRL_COLOR_FILTER_SHIFT: i32 = 4;
 const COMBINER_CTRL_COLOR_COMBINE_SHIFT: i32 = 8;
@@ -85,18 +114,48 @@ where
     // Shaders
     blit_program: BlitProgram<D>,
     clear_program: ClearProgram<D>,
+    post_process_program: PostProcessProgram<D>,
     stencil_program: StencilProgram<D>,
     reprojection_program: ReprojectionProgram<D>,
 
     // Frames
     frame: Frame<D>,
+    offscreen_target_pool: OffscreenTargetPool<D>,
+    // The external textures that hold patterns made by `render_to_pattern()`.
+    rendered_pattern_textures: HashSet<ExternalTextureId>,
+
+    // Layers
+    current_layer: usize,
+    // The pattern texture pages of every layer but the current one, whose pages live in the core.
+    layer_texture_pages: Vec<Vec<Option<PatternTexturePage>>>,
+
+    // Windows
+    current_window: WindowId,
+    // The state of every window but the current one, whose state lives in the renderer.
+    windows: HashMap<WindowId, WindowState<D>>,
+    next_window_id: u32,
 
     // Debug
     current_cpu_build_time: Option<Duration>,
     pending_timers: VecDeque<PendingTimer<D>>,
+    frame_index: u64,
     debug_ui_presenter: Option<DebugUIPresenter<D>>,
-    last_stats: VecDeque<RenderStats>,
-    last_rendering_time: Option<RenderTime>,
+    last_frame_stats: Option<FrameStats>,
+    warned_of_unsupported_filters: bool,
+}
+
+// Everything that belongs to a single window. Programs, vertex arrays, mask storage, and the
+// memory allocator are shared among all windows.
+struct WindowState<D>
+where
+    D: Device,
+{
+    dest: DestFramebuffer<D>,
+    intermediate_dest_framebuffer_id: FramebufferID,
+    intermediate_dest_framebuffer_size: Vector2I,
+    current_layer: usize,
+    pattern_texture_pages: Vec<Option<PatternTexturePage>>,
+    layer_texture_pages: Vec<Vec<Option<PatternTexturePage>>>,
 }
 
 enum RendererLevelImpl<D>
@@ -142,9 +201,13 @@ where
     render_targets: Vec<RenderTargetInfo>,
     pub(crate) render_target_stack: Vec<RenderTargetId>,
     pub(crate) pattern_texture_pages: Vec<Option<PatternTexturePage>>,
+    external_textures: HashMap<ExternalTextureId, D::Texture>,
+    next_external_texture_id: u32,
     pub(crate) mask_storage: Option<MaskStorage>,
     pub(crate) alpha_tile_count: u32,
     pub(crate) framebuffer_flags: FramebufferFlags,
+    // The part of the main viewport that this frame redraws, or `None` if it redraws all of it.
+    dest_dirty_rect: Option<RectI>,
 }
 
 // TODO(pcwalton): Remove this.
@@ -154,6 +217,7 @@ where
 {
     blit_vertex_array: BlitVertexArray<D>,
     clear_vertex_array: ClearVertexArray<D>,
+    post_process_vertex_array: BlitVertexArray<D>,
     stencil_vertex_array: StencilVertexArray<D>,
     reprojection_vertex_array: ReprojectionVertexArray<D>,
 }
@@ -190,6 +254,21 @@ where
         mode: RendererMode,
         options: RendererOptions<D>,
     ) -> Renderer<D> {
+        Renderer::try_new(device, resources, mode, options)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `new()`, but returns an error instead of panicking if the shaders or lookup tables
+    /// can't be loaded, or if the destination is larger than the device supports.
+    pub fn try_new(
+        device: D,
+        resources: &dyn ResourceLoader,
+        mode: RendererMode,
+        options: RendererOptions<D>,
+    ) -> Result<Renderer<D>, RenderError> {
+        let window_size = options.dest.window_size(&device);
+        check_framebuffer_size(&device, window_size)?;
+
         let mut allocator = GPUMemoryAllocator::new();
 
         device.begin_commands();
@@ -217,51 +296,50 @@ where
             BufferTarget::Index,
         );
 
-        let area_lut_texture_id = allocator.allocate_texture(
+        let area_lut_texture_id = allocator.try_allocate_texture(
             &device,
             Vector2I::splat(256),
             TextureFormat::RGBA8,
             TextureTag("AreaLUT"),
-        );
-        let gamma_lut_texture_id = allocator.allocate_texture(
+        )?;
+        let gamma_lut_texture_id = allocator.try_allocate_texture(
             &device,
             vec2i(256, 8),
             TextureFormat::R8,
             TextureTag("GammaLUT"),
-        );
-        device.upload_png_to_texture(
+        )?;
+        device.try_upload_png_to_texture(
             resources,
             "area-lut",
             allocator.get_texture(area_lut_texture_id),
             TextureFormat::RGBA8,
-        );
-        device.upload_png_to_texture(
+        )?;
+        device.try_upload_png_to_texture(
             resources,
             "gamma-lut",
             allocator.get_texture(gamma_lut_texture_id),
             TextureFormat::R8,
-        );
+        )?;
 
-        let window_size = options.dest.window_size(&device);
-        let intermediate_dest_framebuffer_id = allocator.allocate_framebuffer(
+        let intermediate_dest_framebuffer_id = allocator.try_allocate_framebuffer(
             &device,
             window_size,
-            TextureFormat::RGBA8,
+            intermediate_dest_format(mode.level, options.dest.format(&device)),
             FramebufferTag("IntermediateDest"),
-        );
+        )?;
 
         let texture_metadata_texture_size = vec2i(
             TEXTURE_METADATA_TEXTURE_WIDTH,
             TEXTURE_METADATA_TEXTURE_HEIGHT,
         );
-        let texture_metadata_texture_id = allocator.allocate_texture(
+        let texture_metadata_texture_id = allocator.try_allocate_texture(
             &device,
             texture_metadata_texture_size,
             TextureFormat::RGBA16F,
             TextureTag("TextureMetadata"),
-        );
+        )?;
 
-        let core_programs = ProgramsCore::new(&device, resources);
+        let core_programs = ProgramsCore::new(&device, resources)?;
         let core_vertex_arrays = VertexArraysCore::new(
             &device,
             &core_programs,
@@ -294,22 +372,28 @@ where
             render_targets: vec![],
             render_target_stack: vec![],
             pattern_texture_pages: vec![],
+            external_textures: HashMap::new(),
+            next_external_texture_id: 0,
             mask_storage: None,
             alpha_tile_count: 0,
             framebuffer_flags: FramebufferFlags::empty(),
+            dest_dirty_rect: None,
         };
 
         let level_impl = match core.mode.level {
-            RendererLevel::D3D9 => RendererLevelImpl::D3D9(RendererD3D9::new(&mut core, resources)),
+            RendererLevel::D3D9 => {
+                RendererLevelImpl::D3D9(RendererD3D9::new(&mut core, resources)?)
+            }
             RendererLevel::D3D11 => {
-                RendererLevelImpl::D3D11(RendererD3D11::new(&mut core, resources))
+                RendererLevelImpl::D3D11(RendererD3D11::new(&mut core, resources)?)
             }
         };
 
-        let blit_program = BlitProgram::new(&core.device, resources);
-        let clear_program = ClearProgram::new(&core.device, resources);
-        let stencil_program = StencilProgram::new(&core.device, resources);
-        let reprojection_program = ReprojectionProgram::new(&core.device, resources);
+        let blit_program = BlitProgram::new(&core.device, resources)?;
+        let clear_program = ClearProgram::new(&core.device, resources)?;
+        let post_process_program = PostProcessProgram::new(&core.device, resources)?;
+        let stencil_program = StencilProgram::new(&core.device, resources)?;
+        let reprojection_program = ReprojectionProgram::new(&core.device, resources)?;
 
         let debug_ui_presenter = if core.options.show_debug_ui {
             Some(DebugUIPresenter::new(
@@ -327,6 +411,7 @@ where
             &mut core.allocator,
             &blit_program,
             &clear_program,
+            &post_process_program,
             &reprojection_program,
             &stencil_program,
             quad_vertex_positions_buffer_id,
@@ -335,24 +420,35 @@ where
 
         core.device.end_commands();
 
-        Renderer {
+        Ok(Renderer {
             core,
             level_impl,
 
             blit_program,
             clear_program,
+            post_process_program,
 
             frame,
+            offscreen_target_pool: OffscreenTargetPool::new(),
+            rendered_pattern_textures: HashSet::new(),
+
+            current_layer: 0,
+            layer_texture_pages: vec![],
+
+            current_window: WindowId::MAIN,
+            windows: HashMap::new(),
+            next_window_id: 1,
 
             stencil_program,
             reprojection_program,
 
             current_cpu_build_time: None,
             pending_timers: VecDeque::new(),
+            frame_index: 0,
             debug_ui_presenter,
-            last_stats: VecDeque::new(),
-            last_rendering_time: None,
-        }
+            last_frame_stats: None,
+            warned_of_unsupported_filters: false,
+        })
     }
 
     /// Destroys this renderer and returns the embedded GPU device.
@@ -364,34 +460,214 @@ where
     ///
     /// This must be called before `render_command()`.
     pub fn begin_scene(&mut self) {
+        self.begin_scene_on_current_layer();
+        self.begin_layer(0);
+    }
+
+    fn begin_scene_on_current_layer(&mut self) {
+        self.frame_index += 1;
+        trace_scope!("begin_scene", frame_index = self.frame_index);
         self.core.framebuffer_flags = FramebufferFlags::empty();
 
         self.core.device.begin_commands();
-        self.core.current_timer = Some(PendingTimer::new());
+        self.core.device.push_debug_group("Pathfinder scene");
+        self.core.current_timer = Some(PendingTimer::new(self.frame_index));
         self.core.stats = RenderStats::default();
 
         self.core.alpha_tile_count = 0;
     }
 
+    /// Switches to the given layer, in order to render multiple scenes into a single frame.
+    ///
+    /// Each scene (more precisely, each `SceneSink`) keeps track of which pattern textures it has
+    /// uploaded to the GPU from frame to frame, so scenes that are rendered together must not
+    /// share textures. Layers keep them separate: call this before issuing the commands for each
+    /// scene, giving each scene its own layer index, and keep those indices stable across frames.
+    /// Scenes are composited in the order their commands are issued, with later scenes drawn on
+    /// top of earlier ones.
+    ///
+    /// `begin_scene()` switches back to layer 0, so renderers that only draw one scene per frame
+    /// never need to call this. `SceneProxy::render_layers()` is a convenient way to use this
+    /// feature.
+    pub fn begin_layer(&mut self, layer_index: usize) {
+        if layer_index == self.current_layer {
+            return;
+        }
+
+        let layer_count = usize::max(layer_index, self.current_layer) + 1;
+        if self.layer_texture_pages.len() < layer_count {
+            self.layer_texture_pages.resize_with(layer_count, Vec::new);
+        }
+
+        mem::swap(
+            &mut self.core.pattern_texture_pages,
+            &mut self.layer_texture_pages[self.current_layer],
+        );
+        mem::swap(
+            &mut self.core.pattern_texture_pages,
+            &mut self.layer_texture_pages[layer_index],
+        );
+        self.current_layer = layer_index;
+    }
+
+    /// Adds another window to render into, sharing this renderer's shaders, mask textures, and
+    /// GPU memory pool with the existing windows.
+    ///
+    /// `dest` describes the window's output, just like `RendererOptions::dest` does for the
+    /// window the renderer was created with, which is `WindowId::MAIN`. Each window keeps its own
+    /// pattern texture pages and layers, so every window needs its own `SceneProxy`.
+    pub fn add_window(&mut self, dest: DestFramebuffer<D>) -> WindowId {
+        self.try_add_window(dest)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `add_window()`, but returns an error instead of panicking if the window is larger
+    /// than the device supports.
+    pub fn try_add_window(&mut self, dest: DestFramebuffer<D>) -> Result<WindowId, RenderError> {
+        let window_size = dest.window_size(&self.core.device);
+        let intermediate_dest_framebuffer_id = self.core.allocator.try_allocate_framebuffer(
+            &self.core.device,
+            window_size,
+            intermediate_dest_format(self.core.mode.level, dest.format(&self.core.device)),
+            FramebufferTag("IntermediateDest"),
+        )?;
+
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        self.windows.insert(
+            id,
+            WindowState {
+                dest,
+                intermediate_dest_framebuffer_id,
+                intermediate_dest_framebuffer_size: window_size,
+                current_layer: 0,
+                pattern_texture_pages: vec![],
+                layer_texture_pages: vec![],
+            },
+        );
+        Ok(id)
+    }
+
+    /// Switches the window that subsequent scenes render into.
+    ///
+    /// Call this between `end_scene()` and `begin_scene()`. The renderer only switches its own
+    /// state, so the device must also be pointed at the window's surface if the window uses
+    /// `DestFramebuffer::Default`: for example, with `GLDevice::set_default_framebuffer()` or
+    /// the `swap_texture()` method of the other backends.
+    pub fn set_current_window(&mut self, id: WindowId) {
+        if id == self.current_window {
+            return;
+        }
+
+        let mut window = self.windows.remove(&id).expect("No such window!");
+        self.swap_window_state(&mut window);
+        self.windows.insert(self.current_window, window);
+        self.current_window = id;
+
+        self.dest_framebuffer_size_changed();
+    }
+
+    /// Removes a window, freeing its GPU resources, and returns its destination. Returns `None`
+    /// if there's no such window, or if it's the only one left.
+    ///
+    /// Removing the current window switches to the remaining window with the lowest ID, as if by
+    /// `set_current_window()`, so the device must then be pointed at that window's surface.
+    pub fn remove_window(&mut self, id: WindowId) -> Option<DestFramebuffer<D>> {
+        if id == self.current_window {
+            let next_window = self.windows.keys().min_by_key(|window| window.0).copied()?;
+            self.set_current_window(next_window);
+        }
+        let window = self.windows.remove(&id)?;
+
+        self.core
+            .allocator
+            .free_framebuffer(window.intermediate_dest_framebuffer_id);
+        let layer_pages = window.layer_texture_pages.into_iter().flatten();
+        self.free_pattern_texture_pages(
+            window.pattern_texture_pages.into_iter().chain(layer_pages),
+        );
+
+        Some(window.dest)
+    }
+
+    /// Returns the window that scenes are currently rendered into.
+    #[inline]
+    pub fn current_window(&self) -> WindowId {
+        self.current_window
+    }
+
+    fn swap_window_state(&mut self, window: &mut WindowState<D>) {
+        mem::swap(&mut self.core.options.dest, &mut window.dest);
+        mem::swap(
+            &mut self.core.intermediate_dest_framebuffer_id,
+            &mut window.intermediate_dest_framebuffer_id,
+        );
+        mem::swap(
+            &mut self.core.intermediate_dest_framebuffer_size,
+            &mut window.intermediate_dest_framebuffer_size,
+        );
+        mem::swap(&mut self.current_layer, &mut window.current_layer);
+        mem::swap(
+            &mut self.core.pattern_texture_pages,
+            &mut window.pattern_texture_pages,
+        );
+        mem::swap(
+            &mut self.layer_texture_pages,
+            &mut window.layer_texture_pages,
+        );
+    }
+
     /// Issues a rendering command to the renderer.
     ///
     /// These commands are generated from methods like `Scene::build()`.
     ///
     /// `begin_scene()` must have been called first.
     pub fn render_command(&mut self, command: &RenderCommand) {
+        self.try_render_command(command)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `render_command()`, but returns an error instead of panicking if the frame or the
+    /// scene is too large for the device.
+    ///
+    /// After an error, skip the scene's remaining commands, but still call `end_scene()`. The
+    /// renderer stays usable, so the application can recover, for example by rendering a smaller
+    /// frame or a simpler scene.
+    pub fn try_render_command(&mut self, command: &RenderCommand) -> Result<(), RenderError> {
         debug!("render command: {:?}", command);
         match *command {
             RenderCommand::Start {
                 bounding_quad,
                 path_count,
                 needs_readable_framebuffer,
+                dirty_rect,
+                ref path_depth_planes,
             } => {
-                self.start_rendering(bounding_quad, path_count, needs_readable_framebuffer);
+                let viewport_size = self.core.main_viewport().size();
+                if let Err(error) = check_framebuffer_size(&self.core.device, viewport_size) {
+                    // Keep `end_scene()` from reallocating the intermediate framebuffer at this
+                    // size.
+                    self.core
+                        .renderer_flags
+                        .remove(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
+                    return Err(error);
+                }
+                self.start_rendering(
+                    bounding_quad,
+                    path_count,
+                    needs_readable_framebuffer,
+                    dirty_rect,
+                    path_depth_planes,
+                )?;
             }
             RenderCommand::AllocateTexturePage {
                 page_id,
                 ref descriptor,
-            } => self.allocate_pattern_texture_page(page_id, descriptor),
+            } => self.allocate_pattern_texture_page(page_id, descriptor)?,
+            RenderCommand::DeclareExternalTexturePage {
+                page_id,
+                texture_id,
+            } => self.declare_external_texture_page(page_id, texture_id),
             RenderCommand::UploadTexelData {
                 ref texels,
                 location,
@@ -405,11 +681,13 @@ where
             RenderCommand::AddFillsD3D9(ref fills) => self
                 .level_impl
                 .require_d3d9()
-                .add_fills(&mut self.core, fills),
+                .add_fills(&mut self.core, fills)?,
             RenderCommand::FlushFillsD3D9 => {
+                self.core.device.push_debug_group("Fills");
                 self.level_impl
                     .require_d3d9()
                     .draw_buffered_fills(&mut self.core);
+                self.core.device.pop_debug_group();
             }
             RenderCommand::UploadSceneD3D11 {
                 ref draw_segments,
@@ -423,22 +701,41 @@ where
                 self.push_render_target(render_target_id)
             }
             RenderCommand::PopRenderTarget => self.pop_render_target(),
-            RenderCommand::PrepareClipTilesD3D11(ref batch) => self
-                .level_impl
-                .require_d3d11()
-                .prepare_tiles(&mut self.core, batch),
-            RenderCommand::DrawTilesD3D9(ref batch) => self
-                .level_impl
-                .require_d3d9()
-                .upload_and_draw_tiles(&mut self.core, batch),
-            RenderCommand::DrawTilesD3D11(ref batch) => self
-                .level_impl
-                .require_d3d11()
-                .prepare_and_draw_tiles(&mut self.core, batch),
+            RenderCommand::PrepareClipTilesD3D11(ref batch) => {
+                let group_name = format!("Clip tile batch {}", batch.batch_id.0);
+                self.core.device.push_debug_group(&group_name);
+                let result = self
+                    .level_impl
+                    .require_d3d11()
+                    .prepare_tiles(&mut self.core, batch);
+                self.core.device.pop_debug_group();
+                result?;
+            }
+            RenderCommand::DrawTilesD3D9(ref batch) => {
+                let group_name = format!("Tile batch ({} tiles)", batch.tiles.len());
+                self.core.device.push_debug_group(&group_name);
+                let result = self
+                    .level_impl
+                    .require_d3d9()
+                    .upload_and_draw_tiles(&mut self.core, batch);
+                self.core.device.pop_debug_group();
+                result?;
+            }
+            RenderCommand::DrawTilesD3D11(ref batch) => {
+                let group_name = format!("Tile batch {}", batch.tile_batch_data.batch_id.0);
+                self.core.device.push_debug_group(&group_name);
+                let result = self
+                    .level_impl
+                    .require_d3d11()
+                    .prepare_and_draw_tiles(&mut self.core, batch);
+                self.core.device.pop_debug_group();
+                result?;
+            }
             RenderCommand::Finish { cpu_build_time } => {
-                self.core.stats.cpu_build_time = cpu_build_time;
+                self.core.stats.cpu_build_time += cpu_build_time;
             }
         }
+        Ok(())
     }
 
     /// Finishes rendering a scene.
@@ -449,8 +746,25 @@ where
     /// Note that, after calling this method, you might need to flush the output to the screen via
     /// `swap_buffers()`, `present()`, or a similar method that your windowing library offers.
     pub fn end_scene(&mut self) {
-        self.clear_dest_framebuffer_if_necessary();
-        self.blit_intermediate_dest_framebuffer_if_necessary();
+        self.try_end_scene()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `end_scene()`, but returns an error instead of panicking if the framebuffers needed
+    /// to composite the frame can't be allocated.
+    ///
+    /// The scene is finished either way, so the next call must be `begin_scene()`.
+    pub fn try_end_scene(&mut self) -> Result<(), RenderError> {
+        trace_scope!("end_scene");
+
+        let result = {
+            trace_scope!("composite");
+            self.core.device.push_debug_group("Composite");
+            self.clear_dest_framebuffer_if_necessary();
+            let result = self.blit_intermediate_dest_framebuffer_if_necessary();
+            self.core.device.pop_debug_group();
+            result
+        };
 
         self.core.stats.gpu_bytes_allocated = self.core.allocator.bytes_allocated();
         self.core.stats.gpu_bytes_committed = self.core.allocator.bytes_committed();
@@ -462,19 +776,29 @@ where
             }
         }
 
-        if let Some(timer) = self.core.current_timer.take() {
+        if let Some(mut timer) = self.core.current_timer.take() {
+            timer.stats = self.core.stats;
+            timer.cpu_end_time = Instant::now();
             self.pending_timers.push_back(timer);
         }
         self.current_cpu_build_time = None;
 
         self.update_debug_ui();
         if self.core.options.show_debug_ui {
+            self.core.device.push_debug_group("Debug UI");
             self.draw_debug_ui();
+            self.core.device.pop_debug_group();
         }
 
         self.core.allocator.purge_if_needed();
 
+        self.core.device.pop_debug_group();
+
+        // Backends that record command buffers submit them here.
+        trace_scope!("submit");
         self.core.device.end_commands();
+
+        result
     }
 
     fn start_rendering(
@@ -482,9 +806,23 @@ where
         bounding_quad: BoundingQuad,
         path_count: usize,
         needs_readable_framebuffer: bool,
-    ) {
+        dirty_rect: Option<RectI>,
+        path_depth_planes: &[PathDepthPlane],
+    ) -> Result<(), RenderError> {
+        trace_scope!("start_rendering", path_count);
+        let dest_is_8_bit =
+            self.core.options.dest.format(&self.core.device) == TextureFormat::RGBA8;
         match (&self.core.options.dest, self.core.mode.level) {
-            (&DestFramebuffer::Other(_), _) => {
+            (&DestFramebuffer::Other(_), RendererLevel::D3D11)
+            | (&DestFramebuffer::External { .. }, RendererLevel::D3D11)
+                if !dest_is_8_bit =>
+            {
+                // The D3D11 tile shader can only write to 8-bit images.
+                self.core
+                    .renderer_flags
+                    .insert(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
+            }
+            (&DestFramebuffer::Other(_), _) | (&DestFramebuffer::External { .. }, _) => {
                 self.core
                     .renderer_flags
                     .remove(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
@@ -494,6 +832,14 @@ where
                     .renderer_flags
                     .insert(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
             }
+            _ if self
+                .core
+                .framebuffer_flags
+                .contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY) =>
+            {
+                // An earlier scene in this frame has already drawn to the destination, so it's too
+                // late to switch framebuffers.
+            }
             _ => {
                 self.core.renderer_flags.set(
                     RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED,
@@ -502,28 +848,84 @@ where
             }
         }
 
-        if self.core.renderer_flags.contains(RendererFlags::USE_DEPTH) {
+        if !self.core.options.post_process.is_empty() {
+            // The filters read the composited frame back.
+            self.core
+                .renderer_flags
+                .insert(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
+        }
+
+        // Scenes don't share masks or tile batches, so start afresh if an earlier scene in this
+        // frame left some behind.
+        self.core
+            .framebuffer_flags
+            .remove(FramebufferFlags::MASK_FRAMEBUFFER_IS_DIRTY);
+        match self.level_impl {
+            RendererLevelImpl::D3D9(ref mut d3d9_renderer) => d3d9_renderer.clear_retained_fills(),
+            RendererLevelImpl::D3D11(ref mut d3d11_renderer) => {
+                d3d11_renderer.free_tile_batch_buffers(&mut self.core)
+            }
+        }
+
+        // Per-path depth planes replace the stencil, which would otherwise fill the depth buffer
+        // with the scene plane.
+        let use_path_depth = self.core.renderer_flags.contains(RendererFlags::USE_DEPTH)
+            && !path_depth_planes.is_empty();
+        self.core
+            .renderer_flags
+            .set(RendererFlags::USE_PATH_DEPTH, use_path_depth);
+        if let RendererLevelImpl::D3D9(ref mut d3d9_renderer) = self.level_impl {
+            let path_depth_planes = if use_path_depth {
+                path_depth_planes
+            } else {
+                &[]
+            };
+            d3d9_renderer.upload_path_depth_planes(&mut self.core, path_depth_planes)?;
+        }
+
+        if self.core.renderer_flags.contains(RendererFlags::USE_DEPTH) && !use_path_depth {
             self.draw_stencil(&bounding_quad);
         }
 
-        self.core.stats.path_count = path_count;
+        // Later scenes in the frame draw on top of earlier ones, so the frame redraws the union of
+        // their dirty rectangles.
+        if !self
+            .core
+            .framebuffer_flags
+            .contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY)
+        {
+            self.core.dest_dirty_rect = dirty_rect;
+        } else {
+            self.core.dest_dirty_rect = match (self.core.dest_dirty_rect, dirty_rect) {
+                (Some(old_rect), Some(new_rect)) => Some(RectI::from_points(
+                    old_rect.origin().min(new_rect.origin()),
+                    old_rect.lower_right().max(new_rect.lower_right()),
+                )),
+                _ => None,
+            };
+        }
+        if let Some(dirty_rect) = dirty_rect {
+            self.clear_dest_rect(dirty_rect);
+        }
+
+        self.core.stats.path_count += path_count;
 
         self.core.render_targets.clear();
+        Ok(())
     }
 
     fn update_debug_ui(&mut self) {
-        self.last_stats.push_back(self.core.stats);
         self.shift_rendering_time();
 
         if !self.core.options.show_debug_ui || self.debug_ui_presenter.is_none() {
             return;
         }
 
-        if let Some(last_rendering_time) = self.last_rendering_time {
+        if let Some(last_frame_stats) = self.last_frame_stats {
             self.debug_ui_presenter
                 .as_mut()
                 .unwrap()
-                .add_sample(self.last_stats.pop_front().unwrap(), last_rendering_time);
+                .add_sample(last_frame_stats.stats, last_frame_stats.time);
         }
     }
 
@@ -541,17 +943,64 @@ where
                 self.core.timer_query_cache.free(old_query);
             }
             if let Some(render_time) = pending_timer.total_time() {
-                self.last_rendering_time = Some(render_time);
+                self.last_frame_stats = Some(FrameStats {
+                    frame_index: pending_timer.frame_index,
+                    cpu_start_time: pending_timer.cpu_start_time,
+                    cpu_end_time: pending_timer.cpu_end_time,
+                    stats: pending_timer.stats,
+                    time: render_time,
+                });
                 return;
             }
             self.pending_timers.push_front(pending_timer);
         }
-        self.last_rendering_time = None;
+        self.last_frame_stats = None;
     }
 
     /// Returns GPU timing information for the last frame, if present.
     pub fn last_rendering_time(&self) -> Option<RenderTime> {
-        self.last_rendering_time
+        self.last_frame_stats.map(|frame_stats| frame_stats.time)
+    }
+
+    /// Returns the statistics and GPU timings of the most recent frame whose GPU timings have
+    /// become available, if any.
+    ///
+    /// GPU timer queries complete asynchronously, so the frame these describe usually lags a
+    /// frame or two behind the one that was just rendered. The statistics are always those of the
+    /// same frame as the timings, which makes this suitable for logging performance over time.
+    #[inline]
+    pub fn last_frame_stats(&self) -> Option<FrameStats> {
+        self.last_frame_stats
+    }
+
+    /// Returns the index of the frame most recently begun with `begin_scene()`.
+    ///
+    /// Frames are numbered from 1, so this is 0 before the first frame. Profiler integrations can
+    /// record this alongside their CPU scopes and match it to `FrameStats::frame_index` once the
+    /// frame's GPU timings become available.
+    #[inline]
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Enables or disables GPU timer queries.
+    ///
+    /// Timer queries are always issued when the debug UI is shown. Otherwise, they're disabled by
+    /// default, and the GPU times reported by `last_frame_stats()` will be zero.
+    #[inline]
+    pub fn set_gpu_timing_enabled(&mut self, enabled: bool) {
+        self.core.timer_query_cache.enabled = enabled;
+    }
+
+    /// Returns the GPU memory that the renderer currently holds, broken down by resource type and
+    /// by tag.
+    ///
+    /// Comparing this across scenes is a cheap way to catch leaks, for example of mask textures.
+    /// Textures registered with `register_external_texture()` and the offscreen targets that
+    /// `render_to_pattern()` keeps aren't included.
+    #[inline]
+    pub fn gpu_memory_usage(&self) -> GPUMemoryUsage {
+        self.core.allocator.memory_usage()
     }
 
     /// Returns a reference to the GPU device.
@@ -646,6 +1095,264 @@ where
         &self.core.stats
     }
 
+    /// Renders a scene off-screen and returns the result as a pattern that can be used to paint
+    /// paths in other scenes rendered with this renderer.
+    ///
+    /// The scene is rendered at the given size, on a transparent background, straight into a
+    /// texture that stays on the GPU. The texture is registered as an external texture; once no
+    /// scene paints with the pattern anymore, hand it back with `release_pattern()` so that later
+    /// off-screen renders can reuse it.
+    pub fn render_to_pattern<E>(
+        &mut self,
+        scene: &mut Scene,
+        size: Vector2I,
+        build_options: BuildOptions,
+        executor: E,
+    ) -> Result<Pattern, RenderError>
+    where
+        E: Executor,
+    {
+        let framebuffer = self.render_offscreen(scene, size, build_options, executor)?;
+        let texture = self.core.device.destroy_framebuffer(framebuffer);
+        let texture_id = self.register_external_texture(texture);
+        self.rendered_pattern_textures.insert(texture_id);
+        Ok(Pattern::from_external_texture(texture_id, size))
+    }
+
+    /// Returns the texture of a pattern made by `render_to_pattern()` to the pool of off-screen
+    /// targets, unregistering it. Scenes that still paint with the pattern must not be rendered
+    /// afterward.
+    ///
+    /// Does nothing if the pattern wasn't made by `render_to_pattern()` or was already released.
+    pub fn release_pattern(&mut self, pattern: &Pattern) {
+        let texture_id = match *pattern.source() {
+            PatternSource::ExternalTexture { id, .. } => id,
+            PatternSource::Image(_) | PatternSource::RenderTarget { .. } => return,
+        };
+        if !self.rendered_pattern_textures.remove(&texture_id) {
+            return;
+        }
+        if let Some(texture) = self.core.external_textures.remove(&texture_id) {
+            let framebuffer = self.core.device.create_framebuffer(texture);
+            self.offscreen_target_pool
+                .release(&self.core.device, framebuffer);
+        }
+    }
+
+    /// Renders a scene off-screen like `render_to_pattern()`, but reads the pixels back into an
+    /// image, which is independent of this renderer.
+    ///
+    /// Note that reading back pixels waits for the GPU to finish rendering.
+    pub fn render_to_image<E>(
+        &mut self,
+        scene: &mut Scene,
+        size: Vector2I,
+        build_options: BuildOptions,
+        executor: E,
+    ) -> Result<Image, RenderError>
+    where
+        E: Executor,
+    {
+        let framebuffer = self.render_offscreen(scene, size, build_options, executor)?;
+        let viewport = RectI::new(Vector2I::zero(), size);
+        let receiver = self
+            .core
+            .device
+            .read_pixels(&RenderTarget::Framebuffer(&framebuffer), viewport);
+        let texture_data = self.core.device.recv_texture_data(&receiver);
+        self.offscreen_target_pool
+            .release(&self.core.device, framebuffer);
+
+        match texture_data {
+            TextureData::U8(pixels) => Ok(Image::new(
+                size,
+                Arc::new(color::u8_vec_to_color_vec(pixels)),
+            )),
+            TextureData::U16(_) | TextureData::F16(_) | TextureData::F32(_) => {
+                Err(RenderError::UnsupportedPixelFormat)
+            }
+        }
+    }
+
+    // Renders a scene into a framebuffer from the off-screen pool, with alpha unpremultiplied as
+    // patterns expect.
+    fn render_offscreen<E>(
+        &mut self,
+        scene: &mut Scene,
+        size: Vector2I,
+        build_options: BuildOptions,
+        executor: E,
+    ) -> Result<D::Framebuffer, RenderError>
+    where
+        E: Executor,
+    {
+        check_framebuffer_size(&self.core.device, size)?;
+
+        let commands = scene.build_into_vector(self, build_options, executor);
+
+        let framebuffer = self.offscreen_target_pool.acquire(&self.core.device, size);
+        let old_dest = mem::replace(
+            &mut self.core.options.dest,
+            DestFramebuffer::Other(framebuffer),
+        );
+        let old_background_color = self
+            .core
+            .options
+            .background_color
+            .replace(ColorF::transparent_black());
+        let old_show_debug_ui = mem::replace(&mut self.core.options.show_debug_ui, false);
+
+        // The scene was built with a sink of its own, whose pattern texture pages would otherwise
+        // replace those of the current layer, which its scene still expects to find next frame.
+        let layer_texture_pages = mem::take(&mut self.core.pattern_texture_pages);
+        self.begin_scene_on_current_layer();
+        let result = commands
+            .iter()
+            .try_for_each(|command| self.try_render_command(command));
+        let result = result.and(self.try_end_scene());
+        let offscreen_texture_pages =
+            mem::replace(&mut self.core.pattern_texture_pages, layer_texture_pages);
+        self.free_pattern_texture_pages(offscreen_texture_pages);
+
+        self.core.options.show_debug_ui = old_show_debug_ui;
+        self.core.options.background_color = old_background_color;
+        let framebuffer = match mem::replace(&mut self.core.options.dest, old_dest) {
+            DestFramebuffer::Other(framebuffer) => framebuffer,
+            DestFramebuffer::Default { .. } | DestFramebuffer::External { .. } => unreachable!(),
+        };
+        if let Err(error) = result {
+            self.offscreen_target_pool
+                .release(&self.core.device, framebuffer);
+            return Err(error);
+        }
+
+        // The rendered pixels are premultiplied, so convert them into a second framebuffer.
+        let pattern_framebuffer = self.offscreen_target_pool.acquire(&self.core.device, size);
+        let program = &self.post_process_program;
+        let src_texture = self.core.device.framebuffer_texture(&framebuffer);
+        self.core.device.draw_elements(
+            6,
+            &RenderState {
+                target: &RenderTarget::Framebuffer(&pattern_framebuffer),
+                program: &program.program,
+                vertex_array: &self.frame.post_process_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[
+                    (&program.src_texture, src_texture),
+                    (&program.lut_texture, src_texture),
+                ],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &[
+                    (
+                        &program.dest_rect_uniform,
+                        UniformData::Vec4(RectF::new(Vector2F::zero(), size.to_f32()).0),
+                    ),
+                    (
+                        &program.framebuffer_size_uniform,
+                        UniformData::Vec2(size.to_f32().0),
+                    ),
+                    (
+                        &program.filter_kind_uniform,
+                        UniformData::Int(POST_PROCESS_FILTER_UNPREMULTIPLY),
+                    ),
+                    (
+                        &program.filter_params_0_uniform,
+                        UniformData::Vec4(F32x4::default()),
+                    ),
+                    (
+                        &program.filter_params_1_uniform,
+                        UniformData::Vec4(F32x4::default()),
+                    ),
+                ],
+                viewport: RectI::new(Vector2I::zero(), size),
+                options: RenderOptions::default(),
+            },
+        );
+        self.core.stats.count_drawcall(TimeCategory::Other);
+
+        self.offscreen_target_pool
+            .release(&self.core.device, framebuffer);
+        Ok(pattern_framebuffer)
+    }
+
+    /// Starts reading back the last frame rendered to the destination framebuffer, without
+    /// waiting for the GPU to finish it.
+    ///
+    /// Poll the returned readback with `try_recv_frame()` on later frames, so that screenshot and
+    /// video capture code doesn't stall the render thread.
+    pub fn read_frame_async(&self) -> FrameReadback<D> {
+        let viewport = self.core.main_viewport();
+        let render_target = self.core.options.dest.render_target();
+        FrameReadback {
+            receiver: self.core.device.read_pixels(&render_target, viewport),
+            size: viewport.size(),
+        }
+    }
+
+    /// Returns the pixels of a frame readback if the GPU has finished it, or `None` otherwise.
+    #[inline]
+    pub fn try_recv_frame(&self, readback: &FrameReadback<D>) -> Option<TextureData> {
+        self.core.device.try_recv_texture_data(&readback.receiver)
+    }
+
+    /// Waits for a frame readback to finish and returns its pixels.
+    #[inline]
+    pub fn recv_frame(&self, readback: &FrameReadback<D>) -> TextureData {
+        self.core.device.recv_texture_data(&readback.receiver)
+    }
+
+    /// Reads back the last frame rendered to the destination framebuffer as 8-bit RGBA, waiting
+    /// for the GPU to finish it.
+    ///
+    /// This works whether the destination is the default framebuffer or an off-screen one, which
+    /// makes it suitable for comparing output against golden images in tests. Waiting stalls the
+    /// render thread, so interactive applications should call `read_frame_async()` and poll
+    /// `try_recv_frame_image()` on later frames instead.
+    pub fn read_frame(&self) -> FrameImage {
+        let readback = self.read_frame_async();
+        FrameImage::from_texture_data(readback.size, self.recv_frame(&readback))
+    }
+
+    /// Like `try_recv_frame()`, but converts the pixels to 8-bit RGBA.
+    pub fn try_recv_frame_image(&self, readback: &FrameReadback<D>) -> Option<FrameImage> {
+        self.try_recv_frame(readback)
+            .map(|texture_data| FrameImage::from_texture_data(readback.size, texture_data))
+    }
+
+    /// Registers a texture created outside Pathfinder so that it can be painted with
+    /// `Pattern::from_external_texture()`.
+    ///
+    /// The texture is sampled in place, without a CPU copy, which makes this suitable for video
+    /// frames and camera feeds. Use the device's `wrap_texture()` method to turn a native texture
+    /// into a `D::Texture`. Like image patterns, the texture holds RGBA with non-premultiplied
+    /// alpha.
+    pub fn register_external_texture(&mut self, texture: D::Texture) -> ExternalTextureId {
+        let id = ExternalTextureId(self.core.next_external_texture_id);
+        self.core.next_external_texture_id += 1;
+        self.core.external_textures.insert(id, texture);
+        id
+    }
+
+    /// Replaces the texture behind an external texture ID, returning the old one.
+    ///
+    /// This is the cheapest way to show a new video frame: scenes that refer to the ID don't need
+    /// to be rebuilt, as long as the new texture has the same size as the old one.
+    pub fn replace_external_texture(
+        &mut self,
+        id: ExternalTextureId,
+        texture: D::Texture,
+    ) -> Option<D::Texture> {
+        self.core.external_textures.insert(id, texture)
+    }
+
+    /// Unregisters an external texture and hands it back.
+    ///
+    /// Scenes that still paint with the texture must not be rendered afterward.
+    pub fn unregister_external_texture(&mut self, id: ExternalTextureId) -> Option<D::Texture> {
+        self.core.external_textures.remove(&id)
+    }
+
     /// Returns a GPU-side vertex buffer containing 2D vertices of a unit square.
     ///
     /// This can be handy for custom rendering.
@@ -671,39 +1378,80 @@ where
         &mut self,
         page_id: TexturePageId,
         descriptor: &TexturePageDescriptor,
-    ) {
-        // Fill in IDs up to the requested page ID.
-        let page_index = page_id.0 as usize;
-        while self.core.pattern_texture_pages.len() < page_index + 1 {
-            self.core.pattern_texture_pages.push(None);
-        }
-
-        // Clear out any existing texture.
-        if let Some(old_texture_page) = self.core.pattern_texture_pages[page_index].take() {
-            self.core
-                .allocator
-                .free_framebuffer(old_texture_page.framebuffer_id);
-        }
+    ) -> Result<(), RenderError> {
+        self.free_pattern_texture_page(page_id);
 
         // Allocate texture.
+        let page_index = page_id.0 as usize;
         let texture_size = descriptor.size;
-        let framebuffer_id = self.core.allocator.allocate_framebuffer(
+        let framebuffer_id = self.core.allocator.try_allocate_framebuffer(
             &self.core.device,
             texture_size,
             TextureFormat::RGBA8,
             FramebufferTag("PatternPage"),
-        );
+        )?;
         self.core.pattern_texture_pages[page_index] = Some(PatternTexturePage {
-            framebuffer_id,
+            backing: PatternTextureBacking::Framebuffer(framebuffer_id),
             must_preserve_contents: false,
         });
+        Ok(())
+    }
+
+    fn declare_external_texture_page(
+        &mut self,
+        page_id: TexturePageId,
+        texture_id: ExternalTextureId,
+    ) {
+        self.free_pattern_texture_page(page_id);
+
+        // External textures hold content supplied by the application, so they're never cleared.
+        self.core.pattern_texture_pages[page_id.0 as usize] = Some(PatternTexturePage {
+            backing: PatternTextureBacking::External(texture_id),
+            must_preserve_contents: true,
+        });
+    }
+
+    // Makes room for the given page ID and clears out any texture already there.
+    fn free_pattern_texture_pages<I>(&mut self, pages: I)
+    where
+        I: IntoIterator<Item = Option<PatternTexturePage>>,
+    {
+        for page in pages {
+            if let Some(PatternTexturePage {
+                backing: PatternTextureBacking::Framebuffer(framebuffer_id),
+                ..
+            }) = page
+            {
+                self.core.allocator.free_framebuffer(framebuffer_id);
+            }
+        }
+    }
+
+    fn free_pattern_texture_page(&mut self, page_id: TexturePageId) {
+        // Fill in IDs up to the requested page ID.
+        let page_index = page_id.0 as usize;
+        while self.core.pattern_texture_pages.len() < page_index + 1 {
+            self.core.pattern_texture_pages.push(None);
+        }
+
+        if let Some(old_texture_page) = self.core.pattern_texture_pages[page_index].take() {
+            if let PatternTextureBacking::Framebuffer(framebuffer_id) = old_texture_page.backing {
+                self.core.allocator.free_framebuffer(framebuffer_id);
+            }
+        }
     }
 
     fn upload_texel_data(&mut self, texels: &[ColorU], location: TextureLocation) {
+        trace_scope!("upload_texel_data", texel_count = texels.len());
         let texture_page = self.core.pattern_texture_pages[location.page.0 as usize]
             .as_mut()
             .expect("Texture page not allocated yet!");
-        let framebuffer_id = texture_page.framebuffer_id;
+        let framebuffer_id = match texture_page.backing {
+            PatternTextureBacking::Framebuffer(framebuffer_id) => framebuffer_id,
+            PatternTextureBacking::External(_) => {
+                panic!("Can't upload texels to an external texture!")
+            }
+        };
         let framebuffer = self.core.allocator.get_framebuffer(framebuffer_id);
         let texture = self.core.device.framebuffer_texture(framebuffer);
         let texels = color::color_slice_to_u8_slice(texels);
@@ -739,12 +1487,10 @@ where
                 * 4) as usize;
         let mut texels = Vec::with_capacity(padded_texel_size);
         for entry in metadata {
-            let base_color = entry.base_color.to_f32();
-            let filter_params = self.compute_filter_params(
-                &entry.filter,
-                entry.blend_mode,
-                entry.color_0_combine_mode,
-            );
+            let base_color = entry.base_color;
+            let filter = self.supported_filter(&entry.filter);
+            let filter_params =
+                self.compute_filter_params(filter, entry.blend_mode, entry.color_0_combine_mode);
             texels.extend_from_slice(&[
                 // 0
                 f16::from_f32(entry.color_0_transform.m11()),
@@ -865,7 +1611,7 @@ where
             },
         );
 
-        self.core.stats.drawcall_count += 1;
+        self.core.stats.count_drawcall(TimeCategory::Other);
     }
 
     /// Draws a texture that was originally drawn with `old_transform` with `new_transform` by
@@ -915,12 +1661,14 @@ where
             },
         );
 
-        self.core.stats.drawcall_count += 1;
+        self.core.stats.count_drawcall(TimeCategory::Other);
 
         self.core.preserve_draw_framebuffer();
     }
 
     fn push_render_target(&mut self, render_target_id: RenderTargetId) {
+        let group_name = format!("Render target {}", render_target_id.render_target);
+        self.core.device.push_debug_group(&group_name);
         self.core.render_target_stack.push(render_target_id);
     }
 
@@ -929,6 +1677,56 @@ where
             .render_target_stack
             .pop()
             .expect("Render target stack underflow!");
+        self.core.device.pop_debug_group();
+    }
+
+    // Clears only the dirty rectangle, so that the rest of the destination is preserved.
+    fn clear_dest_rect(&mut self, dirty_rect: RectI) {
+        if self
+            .core
+            .framebuffer_flags
+            .contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY)
+        {
+            return;
+        }
+
+        let background_color = self.core.options.background_color.unwrap_or_default();
+        let main_viewport = self.core.main_viewport();
+        let uniforms = [
+            (
+                &self.clear_program.rect_uniform,
+                UniformData::Vec4(dirty_rect.to_f32().0),
+            ),
+            (
+                &self.clear_program.framebuffer_size_uniform,
+                UniformData::Vec2(main_viewport.size().to_f32().0),
+            ),
+            (
+                &self.clear_program.color_uniform,
+                UniformData::Vec4(background_color.0),
+            ),
+        ];
+
+        self.core.device.draw_elements(
+            6,
+            &RenderState {
+                target: &self.core.draw_render_target(),
+                program: &self.clear_program.program,
+                vertex_array: &self.frame.clear_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &uniforms[..],
+                viewport: main_viewport,
+                options: RenderOptions::default(),
+            },
+        );
+
+        self.core.stats.count_drawcall(TimeCategory::Other);
+        self.core
+            .framebuffer_flags
+            .insert(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY);
     }
 
     fn clear_dest_framebuffer_if_necessary(&mut self) {
@@ -977,79 +1775,370 @@ where
             },
         );
 
-        self.core.stats.drawcall_count += 1;
+        self.core.stats.count_drawcall(TimeCategory::Other);
     }
 
-    fn blit_intermediate_dest_framebuffer_if_necessary(&mut self) {
+    fn blit_intermediate_dest_framebuffer_if_necessary(&mut self) -> Result<(), RenderError> {
         if !self
             .core
             .renderer_flags
             .contains(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED)
         {
-            return;
+            return Ok(());
         }
 
         let main_viewport = self.core.main_viewport();
+        let intermediate_dest_format = intermediate_dest_format(
+            self.core.mode.level,
+            self.core.options.dest.format(&self.core.device),
+        );
+        let old_intermediate_dest_format = self.core.device.texture_format(
+            self.core.device.framebuffer_texture(
+                self.core
+                    .allocator
+                    .get_framebuffer(self.core.intermediate_dest_framebuffer_id),
+            ),
+        );
 
-        if self.core.intermediate_dest_framebuffer_size != main_viewport.size() {
+        if self.core.intermediate_dest_framebuffer_size != main_viewport.size()
+            || old_intermediate_dest_format != intermediate_dest_format
+        {
             self.core
                 .allocator
                 .free_framebuffer(self.core.intermediate_dest_framebuffer_id);
-            self.core.intermediate_dest_framebuffer_id = self.core.allocator.allocate_framebuffer(
-                &self.core.device,
-                main_viewport.size(),
-                TextureFormat::RGBA8,
-                FramebufferTag("IntermediateDest"),
-            );
+            self.core.intermediate_dest_framebuffer_id =
+                self.core.allocator.try_allocate_framebuffer(
+                    &self.core.device,
+                    main_viewport.size(),
+                    intermediate_dest_format,
+                    FramebufferTag("IntermediateDest"),
+                )?;
             self.core.intermediate_dest_framebuffer_size = main_viewport.size();
         }
 
-        let intermediate_dest_framebuffer = self
-            .core
-            .allocator
-            .get_framebuffer(self.core.intermediate_dest_framebuffer_id);
+        if !self.core.options.post_process.is_empty() {
+            return self.run_post_process_filters(intermediate_dest_format);
+        }
 
-        let textures = [(
-            &self.blit_program.src_texture,
-            self.core
-                .device
-                .framebuffer_texture(intermediate_dest_framebuffer),
-        )];
+        let intermediate_dest_framebuffer_id = self.core.intermediate_dest_framebuffer_id;
+        self.blit_frame_to_dest(intermediate_dest_framebuffer_id, intermediate_dest_format)
+    }
+
+    // Copies the frame in the given framebuffer to the destination. If the frame has a dirty
+    // rectangle, only that part of the destination is written.
+    //
+    // The blit shader always maps the whole source texture to its destination rectangle, so the
+    // dirty part of the frame is first copied out to a framebuffer of its own.
+    fn blit_frame_to_dest(
+        &mut self,
+        src_framebuffer_id: FramebufferID,
+        format: TextureFormat,
+    ) -> Result<(), RenderError> {
+        let main_viewport = self.core.main_viewport();
+        let frame_size = main_viewport.size();
+        let frame_rect = RectI::new(Vector2I::zero(), frame_size);
+        let dirty_rect = match self.core.dest_dirty_rect {
+            None => {
+                let src_framebuffer = self.core.allocator.get_framebuffer(src_framebuffer_id);
+                self.blit(
+                    self.core.device.framebuffer_texture(src_framebuffer),
+                    &self.core.options.dest.render_target(),
+                    main_viewport,
+                    frame_rect.to_f32(),
+                    ClearOps {
+                        color: Some(ColorF::new(0.0, 0.0, 0.0, 1.0)),
+                        ..ClearOps::default()
+                    },
+                );
+                self.core.stats.count_drawcall(TimeCategory::Other);
+                return Ok(());
+            }
+            Some(dirty_rect) => match dirty_rect.intersection(frame_rect) {
+                None => return Ok(()),
+                Some(dirty_rect) => dirty_rect,
+            },
+        };
+
+        // Dirty rectangles are measured from the top of the frame, like the rest of the scene, and
+        // the blit shader's destination rectangles from the bottom.
+        let dirty_rect = RectI::new(
+            vec2i(dirty_rect.origin_x(), frame_size.y() - dirty_rect.max_y()),
+            dirty_rect.size(),
+        );
+
+        let dirty_framebuffer_id = self.core.allocator.try_allocate_framebuffer(
+            &self.core.device,
+            dirty_rect.size(),
+            format,
+            FramebufferTag("DirtyRect"),
+        )?;
+        let src_framebuffer = self.core.allocator.get_framebuffer(src_framebuffer_id);
+        let dirty_framebuffer = self.core.allocator.get_framebuffer(dirty_framebuffer_id);
+        self.blit(
+            self.core.device.framebuffer_texture(src_framebuffer),
+            &RenderTarget::Framebuffer(dirty_framebuffer),
+            RectI::new(Vector2I::zero(), dirty_rect.size()),
+            RectI::new(-dirty_rect.origin(), frame_size).to_f32(),
+            ClearOps::default(),
+        );
+        self.blit(
+            self.core.device.framebuffer_texture(dirty_framebuffer),
+            &self.core.options.dest.render_target(),
+            main_viewport,
+            dirty_rect.to_f32(),
+            ClearOps::default(),
+        );
+        self.core.stats.count_drawcall(TimeCategory::Other);
+        self.core.stats.count_drawcall(TimeCategory::Other);
+        self.core.allocator.free_framebuffer(dirty_framebuffer_id);
+        Ok(())
+    }
 
+    // Draws the given texture to the destination rectangle, given in the coordinates of the
+    // viewport.
+    fn blit(
+        &self,
+        src_texture: &D::Texture,
+        target: &RenderTarget<D>,
+        viewport: RectI,
+        dest_rect: RectF,
+        clear_ops: ClearOps,
+    ) {
         self.core.device.draw_elements(
             6,
             &RenderState {
-                target: &RenderTarget::Default,
+                target,
                 program: &self.blit_program.program,
                 vertex_array: &self.frame.blit_vertex_array.vertex_array,
                 primitive: Primitive::Triangles,
-                textures: &textures[..],
+                textures: &[(&self.blit_program.src_texture, src_texture)],
                 images: &[],
                 storage_buffers: &[],
                 uniforms: &[
                     (
                         &self.blit_program.framebuffer_size_uniform,
-                        UniformData::Vec2(main_viewport.size().to_f32().0),
+                        UniformData::Vec2(viewport.size().to_f32().0),
                     ),
                     (
                         &self.blit_program.dest_rect_uniform,
-                        UniformData::Vec4(
-                            RectF::new(Vector2F::zero(), main_viewport.size().to_f32()).0,
-                        ),
+                        UniformData::Vec4(dest_rect.0),
                     ),
                 ],
-                viewport: main_viewport,
+                viewport,
                 options: RenderOptions {
-                    clear_ops: ClearOps {
-                        color: Some(ColorF::new(0.0, 0.0, 0.0, 1.0)),
-                        ..ClearOps::default()
-                    },
+                    clear_ops,
                     ..RenderOptions::default()
                 },
             },
         );
+    }
+
+    // Runs the post-processing filters over the intermediate destination framebuffer. The filters
+    // ping-pong between two scratch framebuffers, and the last one writes to the destination.
+    //
+    // Filters may sample neighboring pixels, so they always run over the whole frame. If only a
+    // dirty rectangle of the destination is to be written, the last filter writes to a scratch
+    // framebuffer as well, and the dirty part is copied from there. The intermediate framebuffer
+    // itself is never written, since outside the dirty rectangle of a later frame it must still
+    // hold the unfiltered frame.
+    fn run_post_process_filters(&mut self, format: TextureFormat) -> Result<(), RenderError> {
+        let main_viewport = self.core.main_viewport();
+        let frame_size = main_viewport.size();
+        let filter_count = self.core.options.post_process.len();
+        let scratch_pass_count = match self.core.dest_dirty_rect {
+            None => filter_count - 1,
+            Some(_) => filter_count,
+        };
+
+        let mut scratch_framebuffer_ids = vec![];
+        for _ in 0..usize::min(scratch_pass_count, 2) {
+            let scratch_framebuffer_id = self.core.allocator.try_allocate_framebuffer(
+                &self.core.device,
+                frame_size,
+                format,
+                FramebufferTag("PostProcessScratch"),
+            );
+            match scratch_framebuffer_id {
+                Ok(scratch_framebuffer_id) => scratch_framebuffer_ids.push(scratch_framebuffer_id),
+                Err(error) => {
+                    for scratch_framebuffer_id in scratch_framebuffer_ids {
+                        self.core.allocator.free_framebuffer(scratch_framebuffer_id);
+                    }
+                    return Err(error.into());
+                }
+            }
+        }
 
-        self.core.stats.drawcall_count += 1;
+        let dest_rect_data = UniformData::Vec4(RectF::new(Vector2F::zero(), frame_size.to_f32()).0);
+        let framebuffer_size_data = UniformData::Vec2(frame_size.to_f32().0);
+
+        let mut src_framebuffer_id = self.core.intermediate_dest_framebuffer_id;
+        for (filter_index, filter) in self.core.options.post_process.iter().enumerate() {
+            let dest_framebuffer_id = if filter_index < scratch_pass_count {
+                Some(scratch_framebuffer_ids[filter_index % 2])
+            } else {
+                None
+            };
+
+            let (target, viewport, clear_ops) = match dest_framebuffer_id {
+                Some(dest_framebuffer_id) => (
+                    RenderTarget::Framebuffer(
+                        self.core.allocator.get_framebuffer(dest_framebuffer_id),
+                    ),
+                    RectI::new(Vector2I::zero(), frame_size),
+                    ClearOps::default(),
+                ),
+                None => {
+                    let target = self.core.options.dest.render_target();
+                    let clear_ops = ClearOps {
+                        color: Some(ColorF::new(0.0, 0.0, 0.0, 1.0)),
+                        ..ClearOps::default()
+                    };
+                    (target, main_viewport, clear_ops)
+                }
+            };
+
+            let src_texture = self
+                .core
+                .device
+                .framebuffer_texture(self.core.allocator.get_framebuffer(src_framebuffer_id));
+            let options = RenderOptions {
+                clear_ops,
+                ..RenderOptions::default()
+            };
+
+            match *filter {
+                PostProcessFilter::Custom(ref custom) => {
+                    let vertex_array = BlitVertexArray::new(
+                        &self.core.device,
+                        &custom.program,
+                        self.core
+                            .allocator
+                            .get_general_buffer(self.core.quad_vertex_positions_buffer_id),
+                        self.core
+                            .allocator
+                            .get_index_buffer(self.core.quad_vertex_indices_buffer_id),
+                    );
+
+                    let mut textures = vec![(&custom.src_texture, src_texture)];
+                    textures.extend(
+                        custom
+                            .textures
+                            .iter()
+                            .map(|(param, texture)| (param, texture)),
+                    );
+                    let mut uniforms = vec![
+                        (&custom.dest_rect_uniform, dest_rect_data),
+                        (&custom.framebuffer_size_uniform, framebuffer_size_data),
+                    ];
+                    uniforms.extend(
+                        custom
+                            .uniforms
+                            .iter()
+                            .map(|(uniform, data)| (uniform, *data)),
+                    );
+
+                    self.core.device.draw_elements(
+                        6,
+                        &RenderState {
+                            target: &target,
+                            program: &custom.program,
+                            vertex_array: &vertex_array.vertex_array,
+                            primitive: Primitive::Triangles,
+                            textures: &textures,
+                            images: &[],
+                            storage_buffers: &[],
+                            uniforms: &uniforms,
+                            viewport,
+                            options,
+                        },
+                    );
+                }
+                _ => {
+                    let program = &self.post_process_program;
+                    // Bind the frame in place of the LUT when there isn't one, so that every
+                    // sampler has a texture.
+                    let (filter_kind, params_0, params_1, lut_texture) = match *filter {
+                        PostProcessFilter::ColorLut(ref lut_texture) => {
+                            let lut_size = self.core.device.texture_size(lut_texture).y();
+                            let params_0 = F32x4::new(lut_size as f32, 0.0, 0.0, 0.0);
+                            (
+                                POST_PROCESS_FILTER_COLOR_LUT,
+                                params_0,
+                                F32x4::default(),
+                                lut_texture,
+                            )
+                        }
+                        PostProcessFilter::Vignette {
+                            color,
+                            radius,
+                            softness,
+                        } => {
+                            let params_1 = F32x4::new(radius, softness, 0.0, 0.0);
+                            (POST_PROCESS_FILTER_VIGNETTE, color.0, params_1, src_texture)
+                        }
+                        PostProcessFilter::Gamma(gamma) => {
+                            let params_0 = F32x4::new(1.0 / gamma, 0.0, 0.0, 0.0);
+                            (
+                                POST_PROCESS_FILTER_GAMMA,
+                                params_0,
+                                F32x4::default(),
+                                src_texture,
+                            )
+                        }
+                        PostProcessFilter::Custom(_) => unreachable!(),
+                    };
+
+                    self.core.device.draw_elements(
+                        6,
+                        &RenderState {
+                            target: &target,
+                            program: &program.program,
+                            vertex_array: &self.frame.post_process_vertex_array.vertex_array,
+                            primitive: Primitive::Triangles,
+                            textures: &[
+                                (&program.src_texture, src_texture),
+                                (&program.lut_texture, lut_texture),
+                            ],
+                            images: &[],
+                            storage_buffers: &[],
+                            uniforms: &[
+                                (&program.dest_rect_uniform, dest_rect_data),
+                                (&program.framebuffer_size_uniform, framebuffer_size_data),
+                                (&program.filter_kind_uniform, UniformData::Int(filter_kind)),
+                                (
+                                    &program.filter_params_0_uniform,
+                                    UniformData::Vec4(params_0),
+                                ),
+                                (
+                                    &program.filter_params_1_uniform,
+                                    UniformData::Vec4(params_1),
+                                ),
+                            ],
+                            viewport,
+                            options,
+                        },
+                    );
+                }
+            }
+
+            self.core.stats.count_drawcall(TimeCategory::Other);
+
+            if let Some(dest_framebuffer_id) = dest_framebuffer_id {
+                src_framebuffer_id = dest_framebuffer_id;
+            }
+        }
+
+        let result = if scratch_pass_count == filter_count {
+            self.blit_frame_to_dest(src_framebuffer_id, format)
+        } else {
+            Ok(())
+        };
+
+        for scratch_framebuffer_id in scratch_framebuffer_ids {
+            self.core.allocator.free_framebuffer(scratch_framebuffer_id);
+        }
+        result
     }
 
     /// Returns the output viewport in the destination framebuffer, as specified in the render
@@ -1065,6 +2154,25 @@ where
         self.core.draw_render_target()
     }
 
+    // The tile shader for devices at the D3D9 feature level (OpenGL ES 2.0 and WebGL 1) only
+    // implements radial gradients, so every other filter is dropped there, and the paint is drawn
+    // unfiltered. This is logged the first time it happens.
+    fn supported_filter<'a>(&mut self, filter: &'a Filter) -> &'a Filter {
+        if self.core.device.feature_level() != FeatureLevel::D3D9
+            || matches!(*filter, Filter::None | Filter::RadialGradient { .. })
+        {
+            return filter;
+        }
+        if !self.warned_of_unsupported_filters {
+            warn!(
+                "{:?} isn't supported at the D3D9 feature level; drawing without it",
+                filter
+            );
+            self.warned_of_unsupported_filters = true;
+        }
+        &Filter::None
+    }
+
     fn compute_filter_params(
         &self,
         filter: &Filter,
@@ -1089,6 +2197,68 @@ where
                 ctrl: ctrl
                     | (COMBINER_CTRL_FILTER_RADIAL_GRADIENT << COMBINER_CTRL_COLOR_FILTER_SHIFT),
             },
+            Filter::ConicGradient {
+                center,
+                angle,
+                uv_origin,
+            } => FilterParams {
+                p0: F32x4::new(center.x(), center.y(), angle, 0.0),
+                p1: F32x2::default().concat_xy_xy(uv_origin.0),
+                p2: F32x4::default(),
+                p3: F32x4::default(),
+                p4: F32x4::default(),
+                ctrl: ctrl
+                    | (COMBINER_CTRL_FILTER_CONIC_GRADIENT << COMBINER_CTRL_COLOR_FILTER_SHIFT),
+            },
+            Filter::Noise(noise) => {
+                let base_frequency = noise.stitched_base_frequency();
+                let stitch_origin = match noise.stitch_rect {
+                    None => Vector2F::zero(),
+                    Some(rect) => rect.origin(),
+                };
+                let fractal_noise = match noise.kind {
+                    NoiseKind::FractalNoise => 1.0,
+                    NoiseKind::Turbulence => 0.0,
+                };
+
+                FilterParams {
+                    p0: base_frequency.0.concat_xy_xy(F32x2::new(
+                        noise.num_octaves as f32,
+                        // Seeds must survive the trip through a half float.
+                        (noise.seed & 0x7ff) as f32,
+                    )),
+                    p1: stitch_origin.0.concat_xy_xy(noise.stitch_period().0),
+                    p2: F32x4::new(fractal_noise, 0.0, 0.0, 0.0),
+                    p3: F32x4::default(),
+                    p4: F32x4::default(),
+                    ctrl: ctrl | (COMBINER_CTRL_FILTER_NOISE << COMBINER_CTRL_COLOR_FILTER_SHIFT),
+                }
+            }
+            Filter::PaintEffect { effect, pixel_size } => {
+                let (kind, size, amount) = match effect.kind {
+                    PaintEffectKind::Hatching {
+                        spacing,
+                        line_width,
+                    } => (PAINT_EFFECT_HATCHING, spacing, line_width),
+                    PaintEffectKind::CrossHatching {
+                        spacing,
+                        line_width,
+                    } => (PAINT_EFFECT_CROSS_HATCHING, spacing, line_width),
+                    PaintEffectKind::Halftone {
+                        cell_size,
+                        coverage,
+                    } => (PAINT_EFFECT_HALFTONE, cell_size, coverage),
+                };
+                FilterParams {
+                    p0: F32x4::new(kind, size, amount, pixel_size),
+                    p1: F32x4::default(),
+                    p2: F32x4::default(),
+                    p3: F32x4::default(),
+                    p4: F32x4::default(),
+                    ctrl: ctrl
+                        | (COMBINER_CTRL_FILTER_PAINT_EFFECT << COMBINER_CTRL_COLOR_FILTER_SHIFT),
+                }
+            }
             Filter::PatternFilter(PatternFilter::Blur { sigma, direction }) => {
                 let sigma_inv = 1.0 / sigma;
                 let gauss_coeff_x = SQRT_2_PI_INV * sigma_inv;
@@ -1167,11 +2337,30 @@ where
         }
     }
 
-    pub(crate) fn reallocate_alpha_tile_pages_if_necessary(&mut self, copy_existing: bool) {
-        let alpha_tile_pages_needed = ((self.alpha_tile_count + 0xffff) >> 16) as u32;
+    /// Returns the maximum number of mask pages that the memory budget allows, if any.
+    pub(crate) fn max_mask_page_count(&self) -> Option<u32> {
+        // The D3D11 renderer can't split its work into several passes yet.
+        if self.mode.level != RendererLevel::D3D9 {
+            return None;
+        }
+        let page_size = MASK_FRAMEBUFFER_WIDTH as u64
+            * MASK_FRAMEBUFFER_HEIGHT as u64
+            * self.mask_texture_format().bytes_per_pixel() as u64;
+        let max_bytes = self.options.memory_budget.max_mask_framebuffer_bytes?;
+        Some(((max_bytes / page_size) as u32).max(1))
+    }
+
+    pub(crate) fn reallocate_alpha_tile_pages_if_necessary(
+        &mut self,
+        copy_existing: bool,
+    ) -> Result<(), RenderError> {
+        let mut alpha_tile_pages_needed = (self.alpha_tile_count + 0xffff) >> 16;
+        if let Some(max_mask_page_count) = self.max_mask_page_count() {
+            alpha_tile_pages_needed = alpha_tile_pages_needed.min(max_mask_page_count);
+        }
         if let Some(ref mask_storage) = self.mask_storage {
             if alpha_tile_pages_needed <= mask_storage.allocated_page_count {
-                return;
+                return Ok(());
             }
         }
 
@@ -1180,12 +2369,12 @@ where
             MASK_FRAMEBUFFER_HEIGHT * alpha_tile_pages_needed as i32,
         );
         let format = self.mask_texture_format();
-        let mask_framebuffer_id = self.allocator.allocate_framebuffer(
+        let mask_framebuffer_id = self.allocator.try_allocate_framebuffer(
             &self.device,
             new_size,
             format,
             FramebufferTag("TileAlphaMask"),
-        );
+        )?;
         let mask_framebuffer = self.allocator.get_framebuffer(mask_framebuffer_id);
         let old_mask_storage = self.mask_storage.take();
         self.mask_storage = Some(MaskStorage {
@@ -1196,7 +2385,7 @@ where
         // Copy over existing content if needed.
         let old_mask_framebuffer_id = match old_mask_storage {
             Some(old_storage) if copy_existing => old_storage.framebuffer_id,
-            Some(_) | None => return,
+            Some(_) | None => return Ok(()),
         };
         let old_mask_framebuffer = self.allocator.get_framebuffer(old_mask_framebuffer_id);
         let old_mask_texture = self.device.framebuffer_texture(old_mask_framebuffer);
@@ -1237,12 +2426,13 @@ where
             },
         );
 
-        self.stats.drawcall_count += 1;
+        self.stats.count_drawcall(TimeCategory::Other);
         self.finish_timing_draw_call(&timer_query);
         self.current_timer
             .as_mut()
             .unwrap()
             .push_query(TimeCategory::Other, timer_query);
+        Ok(())
     }
 
     pub(crate) fn set_uniforms_for_drawing_tiles<'a>(
@@ -1279,6 +2469,16 @@ where
                 TEXTURE_METADATA_TEXTURE_HEIGHT,
             )),
         ));
+        // Only dither the pass that produces the final frame. Render targets are read back and
+        // composited, and post-processing filters would spread the noise.
+        let dither = self.options.dither
+            && self.render_target_stack.is_empty()
+            && self.options.post_process.is_empty()
+            && self.options.dest.format(&self.device) == TextureFormat::RGBA8;
+        uniforms.push((
+            &tile_program.dither_uniform,
+            UniformData::Int(dither as i32),
+        ));
 
         if let Some(ref mask_storage) = self.mask_storage {
             let mask_framebuffer_id = mask_storage.framebuffer_id;
@@ -1317,16 +2517,32 @@ where
     // Pattern textures
 
     fn texture_page(&self, id: TexturePageId) -> &D::Texture {
-        self.device
-            .framebuffer_texture(&self.texture_page_framebuffer(id))
+        let texture_page = self.pattern_texture_pages[id.0 as usize]
+            .as_ref()
+            .expect("Texture page not allocated!");
+        match texture_page.backing {
+            PatternTextureBacking::Framebuffer(framebuffer_id) => self
+                .device
+                .framebuffer_texture(self.allocator.get_framebuffer(framebuffer_id)),
+            PatternTextureBacking::External(texture_id) => self
+                .external_textures
+                .get(&texture_id)
+                .expect("External texture not registered!"),
+        }
     }
 
     fn texture_page_framebuffer(&self, id: TexturePageId) -> &D::Framebuffer {
-        let framebuffer_id = self.pattern_texture_pages[id.0 as usize]
+        let texture_page = self.pattern_texture_pages[id.0 as usize]
             .as_ref()
-            .expect("Texture page not allocated!")
-            .framebuffer_id;
-        self.allocator.get_framebuffer(framebuffer_id)
+            .expect("Texture page not allocated!");
+        match texture_page.backing {
+            PatternTextureBacking::Framebuffer(framebuffer_id) => {
+                self.allocator.get_framebuffer(framebuffer_id)
+            }
+            PatternTextureBacking::External(_) => {
+                panic!("External textures can't be rendered to!")
+            }
+        }
     }
 
     pub(crate) fn clear_color_for_draw_operation(&self) -> Option<ColorF> {
@@ -1368,7 +2584,8 @@ where
 
     fn main_viewport(&self) -> RectI {
         match self.options.dest {
-            DestFramebuffer::Default { viewport, .. } => viewport,
+            DestFramebuffer::Default { viewport, .. }
+            | DestFramebuffer::External { viewport, .. } => viewport,
             DestFramebuffer::Other(ref framebuffer) => {
                 let texture = self.device.framebuffer_texture(framebuffer);
                 let size = self.device.texture_size(texture);
@@ -1401,12 +2618,7 @@ where
                         .get_framebuffer(self.intermediate_dest_framebuffer_id);
                     RenderTarget::Framebuffer(intermediate_dest_framebuffer)
                 } else {
-                    match self.options.dest {
-                        DestFramebuffer::Default { .. } => RenderTarget::Default,
-                        DestFramebuffer::Other(ref framebuffer) => {
-                            RenderTarget::Framebuffer(framebuffer)
-                        }
-                    }
+                    self.options.dest.render_target()
                 }
             }
         }
@@ -1449,6 +2661,7 @@ where
         allocator: &mut GPUMemoryAllocator<D>,
         blit_program: &BlitProgram<D>,
         clear_program: &ClearProgram<D>,
+        post_process_program: &PostProcessProgram<D>,
         reprojection_program: &ReprojectionProgram<D>,
         stencil_program: &StencilProgram<D>,
         quad_vertex_positions_buffer_id: GeneralBufferID,
@@ -1460,7 +2673,7 @@ where
 
         let blit_vertex_array = BlitVertexArray::new(
             device,
-            &blit_program,
+            &blit_program.program,
             &quad_vertex_positions_buffer,
             &quad_vertex_indices_buffer,
         );
@@ -1470,6 +2683,12 @@ where
             &quad_vertex_positions_buffer,
             &quad_vertex_indices_buffer,
         );
+        let post_process_vertex_array = BlitVertexArray::new(
+            device,
+            &post_process_program.program,
+            quad_vertex_positions_buffer,
+            quad_vertex_indices_buffer,
+        );
         let reprojection_vertex_array = ReprojectionVertexArray::new(
             device,
             &reprojection_program,
@@ -1481,6 +2700,7 @@ where
         Frame {
             blit_vertex_array,
             clear_vertex_array,
+            post_process_vertex_array,
             reprojection_vertex_array,
             stencil_vertex_array,
         }
@@ -1534,6 +2754,18 @@ bitflags! {
         // This will be true if any exotic blend modes are used at the top level (not inside a
         // render target), *and* the output framebuffer is the default framebuffer.
         const INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED = 0x02;
+        // Whether draw paths are ordered by the depth buffer, each on its own plane, instead of
+        // in painter's order. Only the D3D9 level supports this.
+        const USE_PATH_DEPTH = 0x04;
+    }
+}
+
+// The D3D11 tile shader writes to the intermediate framebuffer as an 8-bit image, so only the D3D9
+// level can keep extended-range colors.
+fn intermediate_dest_format(level: RendererLevel, dest_format: TextureFormat) -> TextureFormat {
+    match level {
+        RendererLevel::D3D9 => dest_format,
+        RendererLevel::D3D11 => TextureFormat::RGBA8,
     }
 }
 
@@ -1544,6 +2776,17 @@ fn pixel_size_to_tile_size(pixel_size: Vector2I) -> Vector2I {
     vec2i(size.x() / TILE_WIDTH as i32, size.y() / TILE_HEIGHT as i32)
 }
 
+fn check_framebuffer_size<D>(device: &D, size: Vector2I) -> Result<(), RenderError>
+where
+    D: Device,
+{
+    let max_size = device.capabilities().max_texture_size;
+    if size.x() as u32 > max_size || size.y() as u32 > max_size {
+        return Err(RenderError::FramebufferTooLarge { size, max_size });
+    }
+    Ok(())
+}
+
 struct FilterParams {
     p0: F32x4,
     p1: F32x4,
@@ -1554,10 +2797,86 @@ struct FilterParams {
 }
 
 pub(crate) struct PatternTexturePage {
-    pub(crate) framebuffer_id: FramebufferID,
+    pub(crate) backing: PatternTextureBacking,
     pub(crate) must_preserve_contents: bool,
 }
 
+pub(crate) enum PatternTextureBacking {
+    // A framebuffer owned by the renderer.
+    Framebuffer(FramebufferID),
+    // A texture the application registered with `Renderer::register_external_texture()`.
+    External(ExternalTextureId),
+}
+
+/// Identifies one of the windows that a renderer draws into.
+///
+/// See `Renderer::add_window()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowId(pub u32);
+
+impl WindowId {
+    /// The window that the renderer was created with.
+    pub const MAIN: WindowId = WindowId(0);
+}
+
+/// A pending read of a rendered frame, started with `Renderer::read_frame_async()`.
+pub struct FrameReadback<D>
+where
+    D: Device,
+{
+    receiver: D::TextureDataReceiver,
+    size: Vector2I,
+}
+
+impl<D> FrameReadback<D>
+where
+    D: Device,
+{
+    /// The size of the frame in pixels.
+    #[inline]
+    pub fn size(&self) -> Vector2I {
+        self.size
+    }
+}
+
+/// A frame read back from the destination framebuffer, as returned by `Renderer::read_frame()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameImage {
+    /// The size of the frame in pixels.
+    pub size: Vector2I,
+    /// The pixels, four bytes each in RGBA order, with the top row first on every backend.
+    ///
+    /// Like the framebuffer, colors have premultiplied alpha. Floating-point framebuffers are
+    /// clamped to the 0 to 1 range.
+    pub pixels: Vec<u8>,
+}
+
+impl FrameImage {
+    fn from_texture_data(size: Vector2I, texture_data: TextureData) -> FrameImage {
+        let pixels: Vec<u8> = match texture_data {
+            TextureData::U8(pixels) => pixels,
+            TextureData::U16(pixels) => {
+                pixels.into_iter().map(|value| (value >> 8) as u8).collect()
+            }
+            TextureData::F16(pixels) => pixels
+                .into_iter()
+                .map(|value| unit_float_to_u8(value.to_f32()))
+                .collect(),
+            TextureData::F32(pixels) => pixels.into_iter().map(unit_float_to_u8).collect(),
+        };
+        debug_assert_eq!(pixels.len(), size.area() as usize * 4);
+        FrameImage { size, pixels }
+    }
+
+    /// Returns the pixel at the given position, where (0, 0) is the top left corner.
+    #[inline]
+    pub fn pixel(&self, position: Vector2I) -> ColorU {
+        let index = (position.y() * self.size.x() + position.x()) as usize * 4;
+        let pixel = &self.pixels[index..(index + 4)];
+        ColorU::new(pixel[0], pixel[1], pixel[2], pixel[3])
+    }
+}
+
 /// A mutable reference to the debug UI presenter.
 ///
 /// You can use this structure to draw custom debug widgets on screen, as the demo does.
@@ -1572,3 +2891,72 @@ where
     /// The debug UI presenter, useful for drawing custom debug widgets on screen.
     pub debug_ui_presenter: &'a mut DebugUIPresenter<D>,
 }
+
+/// Errors that the renderer reports instead of panicking.
+#[derive(Debug)]
+pub enum RenderError {
+    /// A shader or lookup table couldn't be loaded, or a shader failed to compile or link.
+    Device(DeviceError),
+    /// The destination framebuffer is larger than the largest texture that the device supports.
+    FramebufferTooLarge {
+        /// The size of the destination framebuffer, in device pixels.
+        size: Vector2I,
+        /// The largest width or height that the device supports for a texture, in pixels.
+        max_size: u32,
+    },
+    /// A texture or framebuffer that the renderer needed couldn't be allocated.
+    Allocation(AllocationError),
+    /// The scene has more segments or fills than fit in GPU storage buffers, even after growing
+    /// them. Only the D3D11 renderer level reports this.
+    SceneTooLarge,
+    /// The device read back an off-screen render in a format other than 8-bit RGBA.
+    UnsupportedPixelFormat,
+}
+
+impl Display for RenderError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        match *self {
+            RenderError::Device(ref error) => error.fmt(formatter),
+            RenderError::Allocation(ref error) => error.fmt(formatter),
+            RenderError::FramebufferTooLarge { size, max_size } => write!(
+                formatter,
+                "framebuffer size {}x{} exceeds the device limit of {}",
+                size.x(),
+                size.y(),
+                max_size
+            ),
+            RenderError::SceneTooLarge => {
+                formatter.write_str("scene is too large for the GPU storage buffers")
+            }
+            RenderError::UnsupportedPixelFormat => {
+                formatter.write_str("off-screen render was read back in an unsupported format")
+            }
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            RenderError::Device(ref error) => Some(error),
+            RenderError::Allocation(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DeviceError> for RenderError {
+    fn from(error: DeviceError) -> RenderError {
+        RenderError::Device(error)
+    }
+}
+
+impl From<AllocationError> for RenderError {
+    fn from(error: AllocationError) -> RenderError {
+        RenderError::Allocation(error)
+    }
+}
+
+fn unit_float_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -8,8 +8,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use pathfinder_gpu::{BufferTarget, BufferUploadMode, Device, VertexAttrClass};
-use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::{BufferTarget, BufferUploadMode, Device, ProgramKind, VertexAttrClass};
+use pathfinder_gpu::{DeviceError, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 
 // TODO(pcwalton): Replace with `mem::size_of` calls?
@@ -28,14 +28,12 @@ where
 {
     pub(crate) fn new(
         device: &D,
-        blit_program: &BlitProgram<D>,
+        program: &D::Program,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
     ) -> BlitVertexArray<D> {
         let vertex_array = device.create_vertex_array();
-        let position_attr = device
-            .get_vertex_attr(&blit_program.program, "Position")
-            .unwrap();
+        let position_attr = device.get_vertex_attr(program, "Position").unwrap();
 
         device.bind_buffer(
             &vertex_array,
@@ -85,7 +83,7 @@ where
         VertexArraysCore {
             blit_vertex_array: BlitVertexArray::new(
                 device,
-                &programs.blit_program,
+                &programs.blit_program.program,
                 quad_vertex_positions_buffer,
                 quad_vertex_indices_buffer,
             ),
@@ -157,17 +155,70 @@ impl<D> BlitProgram<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> BlitProgram<D> {
-        let program = device.create_raster_program(resources, "blit");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<BlitProgram<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "blit")?;
         let dest_rect_uniform = device.get_uniform(&program, "DestRect");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let src_texture = device.get_texture_parameter(&program, "Src");
-        BlitProgram {
+        Ok(BlitProgram {
             program,
             dest_rect_uniform,
             framebuffer_size_uniform,
             src_texture,
-        }
+        })
+    }
+}
+
+pub(crate) struct PostProcessProgram<D>
+where
+    D: Device,
+{
+    pub(crate) program: D::Program,
+    pub(crate) dest_rect_uniform: D::Uniform,
+    pub(crate) framebuffer_size_uniform: D::Uniform,
+    pub(crate) filter_kind_uniform: D::Uniform,
+    pub(crate) filter_params_0_uniform: D::Uniform,
+    pub(crate) filter_params_1_uniform: D::Uniform,
+    pub(crate) src_texture: D::TextureParameter,
+    pub(crate) lut_texture: D::TextureParameter,
+}
+
+impl<D> PostProcessProgram<D>
+where
+    D: Device,
+{
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<PostProcessProgram<D>, DeviceError> {
+        let program = device.try_create_program_from_shader_names(
+            resources,
+            "post_process",
+            ProgramKind::Raster {
+                vertex: "blit",
+                fragment: "post_process",
+            },
+        )?;
+        let dest_rect_uniform = device.get_uniform(&program, "DestRect");
+        let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let filter_kind_uniform = device.get_uniform(&program, "FilterKind");
+        let filter_params_0_uniform = device.get_uniform(&program, "FilterParams0");
+        let filter_params_1_uniform = device.get_uniform(&program, "FilterParams1");
+        let src_texture = device.get_texture_parameter(&program, "Src");
+        let lut_texture = device.get_texture_parameter(&program, "LUT");
+        Ok(PostProcessProgram {
+            program,
+            dest_rect_uniform,
+            framebuffer_size_uniform,
+            filter_kind_uniform,
+            filter_params_0_uniform,
+            filter_params_1_uniform,
+            src_texture,
+            lut_texture,
+        })
     }
 }
 
@@ -182,10 +233,13 @@ impl<D> ProgramsCore<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsCore<D> {
-        ProgramsCore {
-            blit_program: BlitProgram::new(device, resources),
-        }
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ProgramsCore<D>, DeviceError> {
+        Ok(ProgramsCore {
+            blit_program: BlitProgram::new(device, resources)?,
+        })
     }
 }
 
@@ -203,17 +257,20 @@ impl<D> ClearProgram<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ClearProgram<D> {
-        let program = device.create_raster_program(resources, "clear");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ClearProgram<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "clear")?;
         let rect_uniform = device.get_uniform(&program, "Rect");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let color_uniform = device.get_uniform(&program, "Color");
-        ClearProgram {
+        Ok(ClearProgram {
             program,
             rect_uniform,
             framebuffer_size_uniform,
             color_uniform,
-        }
+        })
     }
 }
 
@@ -233,6 +290,7 @@ where
     pub(crate) mask_texture_size_0_uniform: D::Uniform,
     pub(crate) gamma_lut_texture: D::TextureParameter,
     pub(crate) framebuffer_size_uniform: D::Uniform,
+    pub(crate) dither_uniform: D::Uniform,
 }
 
 impl<D> TileProgramCommon<D>
@@ -251,6 +309,7 @@ where
         let mask_texture_size_0_uniform = device.get_uniform(&program, "MaskTextureSize0");
         let gamma_lut_texture = device.get_texture_parameter(&program, "GammaLUT");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let dither_uniform = device.get_uniform(&program, "Dither");
 
         TileProgramCommon {
             program,
@@ -265,6 +324,7 @@ where
             mask_texture_size_0_uniform,
             gamma_lut_texture,
             framebuffer_size_uniform,
+            dither_uniform,
         }
     }
 }
@@ -280,9 +340,12 @@ impl<D> StencilProgram<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> StencilProgram<D> {
-        let program = device.create_raster_program(resources, "stencil");
-        StencilProgram { program }
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<StencilProgram<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "stencil")?;
+        Ok(StencilProgram { program })
     }
 }
 
@@ -346,17 +409,20 @@ impl<D> ReprojectionProgram<D>
 where
     D: Device,
 {
-    pub(crate) fn new(device: &D, resources: &dyn ResourceLoader) -> ReprojectionProgram<D> {
-        let program = device.create_raster_program(resources, "reproject");
+    pub(crate) fn new(
+        device: &D,
+        resources: &dyn ResourceLoader,
+    ) -> Result<ReprojectionProgram<D>, DeviceError> {
+        let program = device.try_create_raster_program(resources, "reproject")?;
         let old_transform_uniform = device.get_uniform(&program, "OldTransform");
         let new_transform_uniform = device.get_uniform(&program, "NewTransform");
         let texture = device.get_texture_parameter(&program, "Texture");
-        ReprojectionProgram {
+        Ok(ReprojectionProgram {
             program,
             old_transform_uniform,
             new_transform_uniform,
             texture,
-        }
+        })
     }
 }
 
@@ -0,0 +1,278 @@
+// pathfinder/renderer/src/gpu/tests.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tests that render with a real device, using a headless OpenGL context.
+//!
+//! Each test is skipped if no EGL display is available.
+
+use crate::concurrent::executor::SequentialExecutor;
+use crate::gpu::options::RendererOptions;
+use crate::gpu::options::{DestFramebuffer, PostProcessFilter, RendererLevel, RendererMode};
+use crate::gpu::renderer::{FrameImage, Renderer, WindowId};
+use crate::gpu_data::RenderCommand;
+use crate::options::{BuildOptions, RenderCommandListener};
+use crate::paint::Paint;
+use crate::scene::{DrawPath, Scene, SceneSink};
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::{Image, Pattern, PatternSource};
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{vec2f, vec2i};
+use pathfinder_gl::headless::GLHeadlessContext;
+use pathfinder_gl::GLDevice;
+use pathfinder_gpu::{Device, TextureDataRef, TextureFormat};
+use pathfinder_resources::embedded::EmbeddedResourceLoader;
+use std::sync::{Arc, Mutex};
+
+const WINDOW_SIZE: i32 = 64;
+
+// The renderer comes first so that it's dropped while the context is still current.
+fn create_renderer() -> Option<(Renderer<GLDevice>, GLHeadlessContext)> {
+    let context = match GLHeadlessContext::new(vec2i(WINDOW_SIZE, WINDOW_SIZE)) {
+        Ok(context) => context,
+        Err(error) => {
+            eprintln!("skipping GPU test: {}", error);
+            return None;
+        }
+    };
+    let device = context.create_device();
+    let mode = RendererMode {
+        level: RendererLevel::D3D9,
+    };
+    let options = RendererOptions {
+        dest: DestFramebuffer::full_window(context.size()),
+        background_color: Some(ColorF::white()),
+        ..RendererOptions::default()
+    };
+    let renderer = Renderer::new(device, &EmbeddedResourceLoader::new(), mode, options);
+    Some((renderer, context))
+}
+
+// Builds scenes with one sink across frames, as an application that renders every frame does,
+// so that state the sink keeps between frames is exercised.
+struct FrameSink {
+    sink: SceneSink<'static>,
+    commands: Arc<Mutex<Vec<RenderCommand>>>,
+}
+
+impl FrameSink {
+    fn new() -> FrameSink {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let commands_for_listener = commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            commands_for_listener.lock().unwrap().push(command)
+        }));
+        let sink = SceneSink::new(listener, RendererLevel::D3D9);
+        FrameSink { sink, commands }
+    }
+
+    fn render(
+        &mut self,
+        renderer: &mut Renderer<GLDevice>,
+        scene: &mut Scene,
+        options: BuildOptions,
+    ) -> FrameImage {
+        scene.build(options, &mut self.sink, &SequentialExecutor);
+        let commands: Vec<_> = self.commands.lock().unwrap().drain(..).collect();
+        renderer.begin_scene();
+        for command in &commands {
+            renderer.render_command(command);
+        }
+        renderer.end_scene();
+        renderer.read_frame()
+    }
+}
+
+fn image_pattern(color: ColorU) -> Pattern {
+    let size = vec2i(16, 16);
+    let pixels = vec![color; size.x() as usize * size.y() as usize];
+    Pattern::from_image(Image::new(size, Arc::new(pixels)))
+}
+
+fn external_texture_pattern(renderer: &mut Renderer<GLDevice>, color: ColorU) -> Pattern {
+    let size = vec2i(16, 16);
+    let texels: Vec<u8> = (0..size.x() * size.y())
+        .flat_map(|_| vec![color.r, color.g, color.b, color.a])
+        .collect();
+    let texture = renderer.device().create_texture_from_data(
+        TextureFormat::RGBA8,
+        size,
+        TextureDataRef::U8(&texels),
+    );
+    let texture_id = renderer.register_external_texture(texture);
+    Pattern::from_external_texture(texture_id, size)
+}
+
+fn pattern_scene(pattern: Pattern) -> Scene {
+    let mut scene = Scene::new();
+    let size = WINDOW_SIZE as f32;
+    scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(size, size)));
+    let paint = scene.push_paint(&Paint::from_pattern(pattern));
+    let rect = RectF::new(vec2f(8.0, 8.0), vec2f(16.0, 16.0));
+    scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+    scene
+}
+
+fn color_scene(color: ColorU) -> Scene {
+    let mut scene = Scene::new();
+    let size = WINDOW_SIZE as f32;
+    let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(size, size));
+    scene.set_view_box(view_box);
+    let paint = scene.push_paint(&Paint::from_color(color));
+    scene.push_draw_path(DrawPath::new(Outline::from_rect(view_box), paint));
+    scene
+}
+
+#[test]
+fn offscreen_render_keeps_the_pattern_pages_of_the_current_scene() {
+    let (mut renderer, _context) = match create_renderer() {
+        Some(renderer_and_context) => renderer_and_context,
+        None => return,
+    };
+    let mut frame_sink = FrameSink::new();
+    let red_pattern = external_texture_pattern(&mut renderer, ColorU::new(255, 0, 0, 255));
+    let mut main_scene = pattern_scene(red_pattern);
+
+    let first_frame = frame_sink.render(&mut renderer, &mut main_scene, BuildOptions::default());
+    assert_eq!(
+        first_frame.pixel(vec2i(16, 16)),
+        ColorU::new(255, 0, 0, 255)
+    );
+
+    let mut offscreen_scene = pattern_scene(image_pattern(ColorU::new(0, 0, 255, 255)));
+    let image = renderer
+        .render_to_image(
+            &mut offscreen_scene,
+            vec2i(WINDOW_SIZE, WINDOW_SIZE),
+            BuildOptions::default(),
+            SequentialExecutor,
+        )
+        .unwrap();
+    assert_eq!(
+        image.pixels()[16 * WINDOW_SIZE as usize + 16],
+        ColorU::new(0, 0, 255, 255)
+    );
+
+    // The sink only declares the page holding the external texture once, so the renderer must
+    // still have it on the next frame.
+    let second_frame = frame_sink.render(&mut renderer, &mut main_scene, BuildOptions::default());
+    assert_eq!(second_frame.pixels, first_frame.pixels);
+}
+
+#[test]
+fn released_patterns_are_reused_for_later_offscreen_renders() {
+    let (mut renderer, _context) = match create_renderer() {
+        Some(renderer_and_context) => renderer_and_context,
+        None => return,
+    };
+    let size = vec2i(WINDOW_SIZE, WINDOW_SIZE);
+    let mut scene = pattern_scene(image_pattern(ColorU::new(0, 255, 0, 255)));
+
+    let pattern = renderer
+        .render_to_pattern(
+            &mut scene,
+            size,
+            BuildOptions::default(),
+            SequentialExecutor,
+        )
+        .unwrap();
+    renderer.release_pattern(&pattern);
+    let texture_id = match *pattern.source() {
+        PatternSource::ExternalTexture { id, .. } => id,
+        _ => panic!("expected an external texture pattern"),
+    };
+    assert!(renderer.unregister_external_texture(texture_id).is_none());
+
+    // Releasing twice is harmless, and the pooled target is reused rather than leaked.
+    renderer.release_pattern(&pattern);
+    let pattern = renderer
+        .render_to_pattern(
+            &mut scene,
+            size,
+            BuildOptions::default(),
+            SequentialExecutor,
+        )
+        .unwrap();
+    renderer.release_pattern(&pattern);
+}
+
+#[test]
+fn dirty_rect_limits_post_processed_frames() {
+    let (mut renderer, _context) = match create_renderer() {
+        Some(renderer_and_context) => renderer_and_context,
+        None => return,
+    };
+    let mut frame_sink = FrameSink::new();
+    let red = ColorU::new(255, 0, 0, 255);
+    let blue = ColorU::new(0, 0, 255, 255);
+    frame_sink.render(
+        &mut renderer,
+        &mut color_scene(red),
+        BuildOptions::default(),
+    );
+
+    // The filter makes the frame go through the intermediate framebuffer, which has never held
+    // the red frame, so nothing outside the dirty rectangle may be copied from it.
+    renderer.options_mut().post_process = vec![PostProcessFilter::Gamma(1.0)];
+    let options = BuildOptions {
+        dirty_rect: Some(RectF::new(vec2f(0.0, 0.0), vec2f(16.0, 16.0))),
+        ..BuildOptions::default()
+    };
+    let frame = frame_sink.render(&mut renderer, &mut color_scene(blue), options);
+    assert_eq!(frame.pixel(vec2i(8, 8)), blue);
+    assert_eq!(frame.pixel(vec2i(40, 40)), red);
+    assert_eq!(frame.pixel(vec2i(8, 40)), red);
+}
+
+#[test]
+fn removing_the_current_window_switches_to_another() {
+    let (mut renderer, _context) = match create_renderer() {
+        Some(renderer_and_context) => renderer_and_context,
+        None => return,
+    };
+    let red = ColorU::new(255, 0, 0, 255);
+    let blue = ColorU::new(0, 0, 255, 255);
+
+    let small_size = vec2i(WINDOW_SIZE / 2, WINDOW_SIZE / 2);
+    let texture = renderer
+        .device()
+        .create_texture(TextureFormat::RGBA8, small_size);
+    let framebuffer = renderer.device().create_framebuffer(texture);
+    let small_window = renderer.add_window(DestFramebuffer::Other(framebuffer));
+    renderer.set_current_window(small_window);
+    let frame = FrameSink::new().render(
+        &mut renderer,
+        &mut color_scene(blue),
+        BuildOptions::default(),
+    );
+    assert_eq!(frame.size, small_size);
+    assert_eq!(frame.pixel(vec2i(4, 4)), blue);
+
+    match renderer.remove_window(small_window) {
+        Some(DestFramebuffer::Other(_)) => {}
+        _ => panic!("expected the small window's framebuffer back"),
+    }
+    assert_eq!(renderer.current_window(), WindowId::MAIN);
+
+    // The main window's framebuffer and viewport are back in use.
+    let frame = FrameSink::new().render(
+        &mut renderer,
+        &mut color_scene(red),
+        BuildOptions::default(),
+    );
+    assert_eq!(frame.size, vec2i(WINDOW_SIZE, WINDOW_SIZE));
+    assert_eq!(frame.pixel(vec2i(4, 4)), red);
+    assert_eq!(frame.pixel(vec2i(WINDOW_SIZE - 4, WINDOW_SIZE - 4)), red);
+
+    // The last window can't be removed.
+    assert!(renderer.remove_window(WindowId::MAIN).is_none());
+    assert!(renderer.remove_window(small_window).is_none());
+    assert_eq!(renderer.current_window(), WindowId::MAIN);
+}
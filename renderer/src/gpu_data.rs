@@ -15,8 +15,9 @@ use crate::options::BoundingQuad;
 use crate::paint::PaintCompositeOp;
 use crate::scene::PathId;
 use crate::tile_map::DenseTileMap;
-use pathfinder_color::ColorU;
+use pathfinder_color::{ColorF, ColorU};
 use pathfinder_content::effects::{BlendMode, Filter};
+use pathfinder_content::pattern::ExternalTextureId;
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
 use pathfinder_geometry::rect::RectI;
@@ -34,6 +35,8 @@ pub const TILE_CTRL_MASK_EVEN_ODD: i32 = 0x2;
 
 pub const TILE_CTRL_MASK_0_SHIFT: i32 = 0;
 
+pub const TILE_CTRL_SUBPIXEL_COVERAGE: i32 = 0x4;
+
 pub enum RenderCommand {
     // Starts rendering a frame.
     Start {
@@ -48,6 +51,17 @@ pub enum RenderCommand {
         /// This is needed if a path that renders directly to the output framebuffer (i.e. not to a
         /// render target) uses one of the more exotic blend modes.
         needs_readable_framebuffer: bool,
+
+        /// The only region of the destination framebuffer to redraw, if the whole framebuffer
+        /// isn't to be redrawn.
+        dirty_rect: Option<RectI>,
+
+        /// The depth plane of each draw path, indexed by path ID, if paths are to be ordered by
+        /// the depth buffer instead of in painter's order.
+        ///
+        /// This is only sent at the D3D9 level, when the scene has a perspective transform and
+        /// some paths have a nonzero depth. It's empty otherwise.
+        path_depth_planes: Vec<PathDepthPlane>,
     },
 
     // Allocates a texture page.
@@ -56,6 +70,12 @@ pub enum RenderCommand {
         descriptor: TexturePageDescriptor,
     },
 
+    // Backs a texture page with an external texture registered with the renderer.
+    DeclareExternalTexturePage {
+        page_id: TexturePageId,
+        texture_id: ExternalTextureId,
+    },
+
     // Uploads data to a texture page.
     UploadTexelData {
         texels: Arc<Vec<ColorU>>,
@@ -237,6 +257,8 @@ pub struct DrawTileBatchD3D9 {
     pub filter: Filter,
     /// The blend mode to composite these tiles with.
     pub blend_mode: BlendMode,
+    /// True if these tiles have per-channel coverage for LCD subpixel antialiasing.
+    pub subpixel_coverage: bool,
 }
 
 /// Information needed to draw a batch of tiles in D3D11.
@@ -336,12 +358,25 @@ pub struct DiceMetadataD3D11 {
     pub pad: u32,
 }
 
+/// The plane, in device pixels, that a path lies on after a perspective transform.
+///
+/// The normalized device Z coordinate of the path at device pixel (x, y) is
+/// `x_coefficient * x + y_coefficient * y + constant`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct PathDepthPlane {
+    pub x_coefficient: f32,
+    pub y_coefficient: f32,
+    pub constant: f32,
+    pub pad: f32,
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct TextureMetadataEntry {
     pub color_0_transform: Transform2F,
     pub color_0_combine_mode: ColorCombineMode,
-    pub base_color: ColorU,
+    pub base_color: ColorF,
     pub filter: Filter,
     pub blend_mode: BlendMode,
 }
@@ -381,8 +416,16 @@ pub struct ClipMetadata {
 pub struct Clip {
     pub dest_tile_id: AlphaTileId,
     pub dest_backdrop: i32,
+    /// The fill rule of the destination mask, as `TILE_CTRL_MASK_WINDING` or
+    /// `TILE_CTRL_MASK_EVEN_ODD`.
+    pub dest_ctrl: i32,
+    pub pad0: i32,
     pub src_tile_id: AlphaTileId,
     pub src_backdrop: i32,
+    /// The fill rule of the source mask, as `TILE_CTRL_MASK_WINDING` or
+    /// `TILE_CTRL_MASK_EVEN_ODD`.
+    pub src_ctrl: i32,
+    pub pad1: i32,
 }
 
 impl Default for Clip {
@@ -391,8 +434,12 @@ impl Default for Clip {
         Clip {
             dest_tile_id: AlphaTileId(!0),
             dest_backdrop: 0,
+            dest_ctrl: TILE_CTRL_MASK_WINDING,
+            pad0: 0,
             src_tile_id: AlphaTileId(!0),
             src_backdrop: 0,
+            src_ctrl: TILE_CTRL_MASK_WINDING,
+            pad1: 0,
         }
     }
 }
@@ -489,6 +536,16 @@ impl Debug for RenderCommand {
             } => {
                 write!(formatter, "AllocateTexturePage({})", page_id.0)
             }
+            RenderCommand::DeclareExternalTexturePage {
+                page_id,
+                texture_id,
+            } => {
+                write!(
+                    formatter,
+                    "DeclareExternalTexturePage({}, {:?})",
+                    page_id.0, texture_id
+                )
+            }
             RenderCommand::UploadTexelData {
                 ref texels,
                 location,
@@ -9,6 +9,22 @@
 // except according to those terms.
 
 //! Pathfinder's renderer and associated objects.
+//!
+//! Scene building and rendering can be profiled by enabling any of these features, which open a
+//! scope around tiling, batching, each GPU pass, and command submission:
+//!
+//! * `pf-tracing` emits `tracing` spans, which any `tracing` subscriber can record.
+//!
+//! * `pf-puffin` records `puffin` scopes. The application must turn them on with
+//!   `puffin::set_scopes_on()` and mark frames with `puffin::GlobalProfiler::lock().new_frame()`.
+//!
+//! * `pf-tracy` records Tracy zones. The application marks frames with
+//!   `tracy_client::finish_continuous_frame!()`.
+//!
+//! Scopes on the render thread measure the CPU time spent issuing GPU work, not the time the GPU
+//! takes to run it. For that, enable GPU timing with `Renderer::set_gpu_timing_enabled()` and read
+//! `Renderer::last_frame_stats()`, whose frame index and CPU timestamps tie the GPU timings back
+//! to the frame's CPU scopes.
 
 #![warn(missing_docs)]
 
@@ -17,6 +33,21 @@ extern crate bitflags;
 #[macro_use]
 extern crate log;
 
+// Opens a profiling scope that lasts until the end of the enclosing block in each enabled
+// profiler. Only `tracing` records the fields. With no profiler enabled, this expands to nothing,
+// and field values aren't evaluated.
+macro_rules! trace_scope {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "pf-tracing")]
+        let _tracing_scope = tracing::info_span!($name $(, $($field)*)?).entered();
+        #[cfg(feature = "pf-puffin")]
+        puffin::profile_scope!($name);
+        #[cfg(feature = "pf-tracy")]
+        let _tracy_scope = tracy_client::span!($name, 0);
+    };
+}
+
+pub mod bundle;
 pub mod concurrent;
 pub mod gpu;
 pub mod options;
@@ -26,6 +57,8 @@ pub mod scene;
 mod allocator;
 mod builder;
 mod gpu_data;
+mod path_index;
+mod tile_cache;
 mod tile_map;
 mod tiler;
 mod tiles;
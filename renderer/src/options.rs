@@ -11,11 +11,12 @@
 //! Options that control how rendering is to be performed.
 
 use crate::gpu::options::RendererLevel;
-use crate::gpu_data::RenderCommand;
-use pathfinder_geometry::rect::RectF;
+use crate::gpu_data::{PathDepthPlane, RenderCommand};
+use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH};
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::transform3d::Perspective;
-use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use pathfinder_geometry::transform3d::{Perspective, Transform4F};
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector3F, Vector4F};
 
 #[allow(deprecated)]
 use pathfinder_content::clip::PolygonClipper3D;
@@ -58,14 +59,24 @@ pub struct BuildOptions {
     pub dilation: Vector2F,
     /// True if subpixel antialiasing for LCD screens is to be performed.
     pub subpixel_aa_enabled: bool,
+    /// If present, only this rectangle of the destination framebuffer, in device pixels, is
+    /// redrawn. The rectangle is rounded out to tile boundaries.
+    ///
+    /// Everything outside the rectangle keeps whatever it contained before, so this is only
+    /// useful if the destination framebuffer retains its contents from frame to frame.
+    pub dirty_rect: Option<RectF>,
 }
 
 impl BuildOptions {
     pub(crate) fn prepare(self, bounds: RectF) -> PreparedBuildOptions {
+        let tile_size = vec2i(TILE_WIDTH as i32, TILE_HEIGHT as i32);
         PreparedBuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            dirty_rect: self
+                .dirty_rect
+                .map(|rect| tiles::round_rect_out_to_tile_bounds(rect).scale_xy(tile_size)),
         }
     }
 }
@@ -99,51 +110,62 @@ impl RenderTransform {
             RenderTransform::Perspective(ref perspective) => *perspective,
         };
 
-        let mut points = vec![
-            bounds.origin().to_4d(),
-            bounds.upper_right().to_4d(),
-            bounds.lower_right().to_4d(),
-            bounds.lower_left().to_4d(),
-        ];
-        debug!("-----");
-        debug!("bounds={:?} ORIGINAL quad={:?}", bounds, points);
-        for point in &mut points {
-            *point = perspective.transform * *point;
-        }
-        debug!("... PERSPECTIVE quad={:?}", points);
-
-        // Compute depth.
-        let quad = [
-            points[0].to_3d().to_4d(),
-            points[1].to_3d().to_4d(),
-            points[2].to_3d().to_4d(),
-            points[3].to_3d().to_4d(),
-        ];
-        debug!("... PERSPECTIVE-DIVIDED points = {:?}", quad);
-
-        points = PolygonClipper3D::new(points).clip();
-        debug!("... CLIPPED quad={:?}", points);
-        for point in &mut points {
-            *point = point.to_3d().to_4d()
-        }
-
-        let inverse_transform = perspective.transform.inverse();
-        let clip_polygon = points
-            .into_iter()
-            .map(|point| (inverse_transform * point).to_2d())
-            .collect();
+        let (clip_polygon, quad) = compute_clip_polygon(&perspective, bounds);
         return PreparedRenderTransform::Perspective {
             perspective,
             clip_polygon,
             quad,
+            bounds,
         };
     }
 }
 
+// Clips the scene bounds against the view frustum. Returns the clip polygon in scene space and the
+// perspective-divided bounding quad.
+#[allow(deprecated)]
+fn compute_clip_polygon(perspective: &Perspective, bounds: RectF) -> (Vec<Vector2F>, BoundingQuad) {
+    let mut points = vec![
+        bounds.origin().to_4d(),
+        bounds.upper_right().to_4d(),
+        bounds.lower_right().to_4d(),
+        bounds.lower_left().to_4d(),
+    ];
+    debug!("-----");
+    debug!("bounds={:?} ORIGINAL quad={:?}", bounds, points);
+    for point in &mut points {
+        *point = perspective.transform * *point;
+    }
+    debug!("... PERSPECTIVE quad={:?}", points);
+
+    // Compute depth.
+    let quad = [
+        points[0].to_3d().to_4d(),
+        points[1].to_3d().to_4d(),
+        points[2].to_3d().to_4d(),
+        points[3].to_3d().to_4d(),
+    ];
+    debug!("... PERSPECTIVE-DIVIDED points = {:?}", quad);
+
+    points = PolygonClipper3D::new(points).clip();
+    debug!("... CLIPPED quad={:?}", points);
+    for point in &mut points {
+        *point = point.to_3d().to_4d()
+    }
+
+    let inverse_transform = perspective.transform.inverse();
+    let clip_polygon = points
+        .into_iter()
+        .map(|point| (inverse_transform * point).to_2d())
+        .collect();
+    (clip_polygon, quad)
+}
+
 pub(crate) struct PreparedBuildOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    // The dirty rectangle, rounded out to tile boundaries.
+    pub(crate) dirty_rect: Option<RectI>,
 }
 
 #[derive(Clone, Copy)]
@@ -186,6 +208,7 @@ pub(crate) enum PreparedRenderTransform {
         perspective: Perspective,
         clip_polygon: Vec<Vector2F>,
         quad: [Vector4F; 4],
+        bounds: RectF,
     },
 }
 
@@ -197,4 +220,57 @@ impl PreparedRenderTransform {
             _ => false,
         }
     }
+
+    /// Returns the perspective transform for a path pushed `depth` units along the Z axis, along
+    /// with its clip polygon, or `None` if this isn't a perspective transform.
+    pub(crate) fn perspective_at_depth(&self, depth: f32) -> Option<(Perspective, Vec<Vector2F>)> {
+        match *self {
+            PreparedRenderTransform::Perspective {
+                perspective,
+                bounds,
+                ..
+            } => {
+                let perspective = perspective
+                    * Transform4F::from_translation(Vector4F::new(0.0, 0.0, depth, 1.0));
+                let (clip_polygon, _) = compute_clip_polygon(&perspective, bounds);
+                Some((perspective, clip_polygon))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the plane, in device pixels, that a path pushed `depth` units along the Z axis
+    /// lies on, or `None` if this isn't a perspective transform.
+    pub(crate) fn depth_plane_at_depth(&self, depth: f32) -> Option<PathDepthPlane> {
+        let (perspective, clip_polygon) = self.perspective_at_depth(depth)?;
+
+        // Pick points inside the view frustum, so that none of them are behind the camera.
+        if clip_polygon.len() < 3 {
+            return Some(PathDepthPlane::default());
+        }
+        let window_size = perspective.window_size.to_f32();
+        let points: Vec<Vector3F> = clip_polygon[0..3]
+            .iter()
+            .map(|&point| {
+                let point = (perspective.transform * point.to_4d()).to_3d();
+                let device_point = (vec2f(point.x(), -point.y()) + 1.0) * window_size * 0.5;
+                Vector3F::new(device_point.x(), device_point.y(), point.z())
+            })
+            .collect();
+
+        let normal = (points[1] - points[0]).cross(points[2] - points[0]);
+        if normal.z().abs() < f32::EPSILON {
+            return Some(PathDepthPlane {
+                constant: points[0].z(),
+                ..PathDepthPlane::default()
+            });
+        }
+        let (x_coefficient, y_coefficient) = (-normal.x() / normal.z(), -normal.y() / normal.z());
+        Some(PathDepthPlane {
+            x_coefficient,
+            y_coefficient,
+            constant: points[0].z() - x_coefficient * points[0].x() - y_coefficient * points[0].y(),
+            pad: 0.0,
+        })
+    }
 }
@@ -15,10 +15,12 @@ use crate::gpu_data::{ColorCombineMode, RenderCommand, TextureLocation, TextureM
 use crate::gpu_data::{TexturePageDescriptor, TexturePageId, TileBatchTexture};
 use crate::scene::{RenderTarget, SceneId};
 use hashbrown::{HashMap, HashSet};
-use pathfinder_color::ColorU;
+use pathfinder_color::{ColorF, ColorU};
 use pathfinder_content::effects::{BlendMode, Filter, PatternFilter};
 use pathfinder_content::gradient::{Gradient, GradientGeometry, GradientWrap};
-use pathfinder_content::pattern::{ImageHash, Pattern, PatternSource};
+use pathfinder_content::noise::Noise;
+use pathfinder_content::paint_effect::PaintEffect;
+use pathfinder_content::pattern::{ExternalTextureId, ImageHash, Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
@@ -28,12 +30,21 @@ use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::{F32x2, F32x4};
 use std::f32;
 use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-// The size of a gradient tile.
+// The number of gradients stored in each gradient tile.
 //
 // TODO(pcwalton): Choose this size dynamically!
-const GRADIENT_TILE_LENGTH: u32 = 256;
+const GRADIENT_TILE_HEIGHT: u32 = 256;
+
+// The default number of texels in each gradient ramp.
+const DEFAULT_GRADIENT_RAMP_RESOLUTION: u32 = 256;
+
+// The largest number of texels in each gradient ramp. Ramps are baked before the scene meets a
+// device, so this is the smallest maximum texture size of the supported APIs: OpenGL ES 3.0 and
+// WebGL 2 only guarantee 2048.
+const MAX_GRADIENT_RAMP_RESOLUTION: u32 = 2048;
 
 // Stores all paints in a scene.
 #[derive(Clone)]
@@ -42,38 +53,71 @@ pub(crate) struct Palette {
     render_targets: Vec<RenderTarget>,
     cache: HashMap<Paint, PaintId>,
     scene_id: SceneId,
+    pub(crate) gradient_ramp_options: GradientRampOptions,
+}
+
+/// Controls how gradients are baked into the ramp textures that the GPU samples.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GradientRampOptions {
+    /// The number of texels that each gradient is sampled into. The default is 256.
+    ///
+    /// Long, subtle gradients may show banding at low resolutions. Values are clamped between one
+    /// and 2048, which every supported device can hold in a single texture row.
+    pub resolution: u32,
+    /// How the ramp texture is filtered when it's sampled.
+    pub filter: GradientRampFilter,
+}
+
+/// How gradient ramp textures are filtered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GradientRampFilter {
+    /// Colors are interpolated between neighboring texels. This is the default.
+    Linear,
+    /// The nearest texel is used, which keeps hard stops crisp.
+    Nearest,
 }
 
 // Caches texture images from scene to scene.
 pub(crate) struct PaintTextureManager {
     allocator: TextureAllocator,
     cached_images: HashMap<ImageHash, TextureLocation>,
+    cached_external_textures: HashMap<ExternalTextureId, TextureLocation>,
 }
 
-/// Defines how a path is to be filled: with a solid color, gradient, or pattern.
+/// Defines how a path is to be filled: with a solid color, gradient, pattern, noise, or a
+/// stylized effect.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Paint {
     base_color: ColorU,
+    intensity: Intensity,
     overlay: Option<PaintOverlay>,
 }
 
+// A multiplier for the RGB channels of a base color, wrapped so that paints can be hashed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Intensity(f32);
+
 /// What is to be overlaid on top of a base color.
 ///
-/// An overlay is a gradient or a pattern, plus a composite operation which determines how the
-/// gradient or pattern is to be combined with the base color.
+/// An overlay is a gradient, a pattern, noise, or a stylized effect, plus a composite operation
+/// which determines how the overlay is to be combined with the base color.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct PaintOverlay {
     composite_op: PaintCompositeOp,
     contents: PaintContents,
 }
 
-/// The contents of an overlay: either a gradient or a pattern.
+/// The contents of an overlay: a gradient, a pattern, procedural noise, or a stylized effect.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub(crate) enum PaintContents {
     /// A gradient, either linear or radial.
     Gradient(Gradient),
     /// A raster image pattern.
     Pattern(Pattern),
+    /// Procedural noise, evaluated in the shader.
+    Noise(Noise),
+    /// A stylized coverage pattern, evaluated in the shader.
+    Effect(PaintEffect),
 }
 
 /// The ID of a paint, unique to a scene.
@@ -93,11 +137,25 @@ pub enum PaintCompositeOp {
     DestIn,
 }
 
+impl Eq for Intensity {}
+
+impl Hash for Intensity {
+    #[inline]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.0.to_bits().hash(state)
+    }
+}
+
 impl Debug for PaintContents {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         match *self {
             PaintContents::Gradient(ref gradient) => gradient.fmt(formatter),
             PaintContents::Pattern(ref pattern) => pattern.fmt(formatter),
+            PaintContents::Noise(ref noise) => noise.fmt(formatter),
+            PaintContents::Effect(ref effect) => effect.fmt(formatter),
         }
     }
 }
@@ -110,16 +168,35 @@ impl Palette {
             render_targets: vec![],
             cache: HashMap::new(),
             scene_id,
+            gradient_ramp_options: GradientRampOptions::default(),
+        }
+    }
+}
+
+impl Default for GradientRampOptions {
+    #[inline]
+    fn default() -> GradientRampOptions {
+        GradientRampOptions {
+            resolution: DEFAULT_GRADIENT_RAMP_RESOLUTION,
+            filter: GradientRampFilter::Linear,
         }
     }
 }
 
+impl Default for GradientRampFilter {
+    #[inline]
+    fn default() -> GradientRampFilter {
+        GradientRampFilter::Linear
+    }
+}
+
 impl Paint {
     /// Creates a simple paint from a single base color.
     #[inline]
     pub fn from_color(color: ColorU) -> Paint {
         Paint {
             base_color: color,
+            intensity: Intensity(1.0),
             overlay: None,
         }
     }
@@ -129,6 +206,7 @@ impl Paint {
     pub fn from_gradient(gradient: Gradient) -> Paint {
         Paint {
             base_color: ColorU::white(),
+            intensity: Intensity(1.0),
             overlay: Some(PaintOverlay {
                 composite_op: PaintCompositeOp::SrcIn,
                 contents: PaintContents::Gradient(gradient),
@@ -141,6 +219,7 @@ impl Paint {
     pub fn from_pattern(pattern: Pattern) -> Paint {
         Paint {
             base_color: ColorU::white(),
+            intensity: Intensity(1.0),
             overlay: Some(PaintOverlay {
                 composite_op: PaintCompositeOp::SrcIn,
                 contents: PaintContents::Pattern(pattern),
@@ -148,6 +227,34 @@ impl Paint {
         }
     }
 
+    /// Creates a paint from procedural noise.
+    #[inline]
+    pub fn from_noise(noise: Noise) -> Paint {
+        Paint {
+            base_color: ColorU::white(),
+            intensity: Intensity(1.0),
+            overlay: Some(PaintOverlay {
+                composite_op: PaintCompositeOp::SrcIn,
+                contents: PaintContents::Noise(noise),
+            }),
+        }
+    }
+
+    /// Creates a paint that draws a stylized effect in the given color.
+    ///
+    /// The effect masks the base color, so changing the base color later recolors the effect.
+    #[inline]
+    pub fn from_effect(color: ColorU, effect: PaintEffect) -> Paint {
+        Paint {
+            base_color: color,
+            intensity: Intensity(1.0),
+            overlay: Some(PaintOverlay {
+                composite_op: PaintCompositeOp::DestIn,
+                contents: PaintContents::Effect(effect),
+            }),
+        }
+    }
+
     /// A convenience function to create a solid black paint.
     #[inline]
     pub fn black() -> Paint {
@@ -179,6 +286,7 @@ impl Paint {
             Some(ref overlay) => match overlay.contents {
                 PaintContents::Gradient(ref gradient) => gradient.is_opaque(),
                 PaintContents::Pattern(ref pattern) => pattern.is_opaque(),
+                PaintContents::Noise(_) | PaintContents::Effect(_) => false,
             },
         }
     }
@@ -195,7 +303,9 @@ impl Paint {
             None => true,
             Some(ref overlay) => match overlay.contents {
                 PaintContents::Gradient(ref gradient) => gradient.is_fully_transparent(),
-                PaintContents::Pattern(_) => false,
+                PaintContents::Pattern(_) | PaintContents::Noise(_) | PaintContents::Effect(_) => {
+                    false
+                }
             },
         }
     }
@@ -218,6 +328,8 @@ impl Paint {
             match overlay.contents {
                 PaintContents::Gradient(ref mut gradient) => gradient.apply_transform(*transform),
                 PaintContents::Pattern(ref mut pattern) => pattern.apply_transform(*transform),
+                PaintContents::Noise(ref mut noise) => noise.apply_transform(*transform),
+                PaintContents::Effect(ref mut effect) => effect.apply_transform(*transform),
             }
         }
     }
@@ -238,6 +350,37 @@ impl Paint {
         self.base_color = new_base_color;
     }
 
+    /// Returns the multiplier applied to the red, green, and blue channels of the base color.
+    ///
+    /// The default is 1.0.
+    #[inline]
+    pub fn intensity(&self) -> f32 {
+        self.intensity.0
+    }
+
+    /// Changes the multiplier applied to the red, green, and blue channels of the base color.
+    ///
+    /// Values above 1.0 produce extended-range colors for HDR output. These are only preserved
+    /// when rendering to a floating-point destination; 8-bit destinations clamp them to 1.0. The
+    /// multiplier doesn't affect gradients or patterns, which are stored with 8 bits per channel.
+    #[inline]
+    pub fn set_intensity(&mut self, new_intensity: f32) {
+        self.intensity = Intensity(new_intensity);
+    }
+
+    /// Returns the base color with the intensity applied, as a floating-point color.
+    #[inline]
+    pub fn extended_base_color(&self) -> ColorF {
+        let color = self.base_color.to_f32();
+        let intensity = self.intensity.0;
+        ColorF::new(
+            color.r() * intensity,
+            color.g() * intensity,
+            color.b() * intensity,
+            color.a(),
+        )
+    }
+
     /// Returns the paint overlay, which is the portion of the paint on top of the base color.
     #[inline]
     pub fn overlay(&self) -> &Option<PaintOverlay> {
@@ -286,6 +429,30 @@ impl Paint {
             },
         }
     }
+
+    /// Returns the noise function, if this paint represents one.
+    #[inline]
+    pub fn noise(&self) -> Option<&Noise> {
+        match self.overlay {
+            None => None,
+            Some(ref overlay) => match overlay.contents {
+                PaintContents::Noise(ref noise) => Some(noise),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns the stylized effect, if this paint represents one.
+    #[inline]
+    pub fn effect(&self) -> Option<&PaintEffect> {
+        match self.overlay {
+            None => None,
+            Some(ref overlay) => match overlay.contents {
+                PaintContents::Effect(ref effect) => Some(effect),
+                _ => None,
+            },
+        }
+    }
 }
 
 impl PaintOverlay {
@@ -323,7 +490,7 @@ pub(crate) struct PaintMetadata {
     /// Metadata associated with the color texture, if applicable.
     pub(crate) color_texture_metadata: Option<PaintColorTextureMetadata>,
     /// The base color that the color texture gets mixed into.
-    pub(crate) base_color: ColorU,
+    pub(crate) base_color: ColorF,
     pub(crate) blend_mode: BlendMode,
     /// True if this paint is fully opaque.
     pub(crate) is_opaque: bool,
@@ -372,6 +539,14 @@ pub(crate) enum PaintFilter {
         /// The radii of the two circles.
         radii: F32x2,
     },
+    ConicGradient {
+        /// The point that the gradient sweeps around.
+        center: Vector2F,
+        /// The angle at which the gradient starts, in radians.
+        angle: f32,
+    },
+    Noise(Noise),
+    Effect(PaintEffect),
     PatternFilter(PatternFilter),
 }
 
@@ -388,6 +563,11 @@ impl Palette {
         paint_id
     }
 
+    #[inline]
+    pub(crate) fn get_render_target(&self, render_target_id: RenderTargetId) -> &RenderTarget {
+        &self.render_targets[render_target_id.render_target as usize]
+    }
+
     pub(crate) fn push_render_target(&mut self, render_target: RenderTarget) -> RenderTargetId {
         let id = self.render_targets.len() as u32;
         self.render_targets.push(render_target);
@@ -402,6 +582,8 @@ impl Palette {
         texture_manager: &mut PaintTextureManager,
         render_transform: Transform2F,
     ) -> PaintInfo {
+        trace_scope!("build_paint_info");
+
         // Assign render target locations.
         let mut transient_paint_locations = vec![];
         let render_target_metadata =
@@ -413,6 +595,7 @@ impl Palette {
             gradient_tile_builder,
             image_texel_info,
             used_image_hashes,
+            used_external_texture_ids,
         } = self.assign_paint_locations(
             &render_target_metadata,
             texture_manager,
@@ -440,6 +623,7 @@ impl Palette {
         // Free transient locations and unused images, now that they're no longer needed.
         self.free_transient_locations(texture_manager, transient_paint_locations);
         self.free_unused_images(texture_manager, used_image_hashes);
+        self.free_unused_external_textures(texture_manager, used_external_texture_ids);
 
         PaintInfo {
             render_commands,
@@ -470,9 +654,11 @@ impl Palette {
         transient_paint_locations: &mut Vec<TextureLocation>,
     ) -> PaintLocationsInfo {
         let mut paint_metadata = vec![];
-        let mut gradient_tile_builder = GradientTileBuilder::new();
+        let mut gradient_tile_builder =
+            GradientTileBuilder::new(self.gradient_ramp_options.resolution);
         let mut image_texel_info = vec![];
         let mut used_image_hashes = HashSet::new();
+        let mut used_external_texture_ids = HashSet::new();
         for paint in &self.paints {
             let allocator = &mut texture_manager.allocator;
             let color_texture_metadata = match paint.overlay {
@@ -487,6 +673,12 @@ impl Palette {
                                 }
                                 GradientWrap::Clamp => {}
                             }
+                            if self.gradient_ramp_options.filter == GradientRampFilter::Nearest {
+                                sampling_flags.insert(
+                                    TextureSamplingFlags::NEAREST_MIN
+                                        | TextureSamplingFlags::NEAREST_MAG,
+                                );
+                            }
 
                             // FIXME(pcwalton): The gradient size might not be big enough. Detect
                             // this.
@@ -504,6 +696,9 @@ impl Palette {
                                     GradientGeometry::Radial { line, radii, .. } => {
                                         PaintFilter::RadialGradient { line, radii }
                                     }
+                                    GradientGeometry::Conic { center, angle, .. } => {
+                                        PaintFilter::ConicGradient { center, angle }
+                                    }
                                 },
                                 transform: Transform2F::default(),
                                 composite_op: overlay.composite_op(),
@@ -511,10 +706,15 @@ impl Palette {
                             })
                         }
                         PaintContents::Pattern(ref pattern) => {
-                            let border = vec2i(
-                                if pattern.repeat_x() { 0 } else { 1 },
-                                if pattern.repeat_y() { 0 } else { 1 },
-                            );
+                            // External textures are sampled in place, so there's no room for a
+                            // border around them.
+                            let border = match *pattern.source() {
+                                PatternSource::ExternalTexture { .. } => Vector2I::zero(),
+                                _ => vec2i(
+                                    if pattern.repeat_x() { 0 } else { 1 },
+                                    if pattern.repeat_y() { 0 } else { 1 },
+                                ),
+                            };
 
                             let location;
                             match *pattern.source() {
@@ -554,6 +754,30 @@ impl Palette {
                                         texels: (*image.pixels()).clone(),
                                     });
                                 }
+                                PatternSource::ExternalTexture { id, size } => {
+                                    // Each external texture gets a page of its own, which the
+                                    // renderer backs with the registered texture itself.
+                                    let cached_location =
+                                        texture_manager.cached_external_textures.get(&id).cloned();
+                                    location = match cached_location {
+                                        Some(cached_location)
+                                            if cached_location.rect.size() == size =>
+                                        {
+                                            cached_location
+                                        }
+                                        _ => {
+                                            if let Some(old_location) = cached_location {
+                                                allocator.free(old_location);
+                                            }
+                                            let location = allocator.allocate_image(size);
+                                            texture_manager
+                                                .cached_external_textures
+                                                .insert(id, location);
+                                            location
+                                        }
+                                    };
+                                    used_external_texture_ids.insert(id);
+                                }
                             }
 
                             let mut sampling_flags = TextureSamplingFlags::empty();
@@ -585,6 +809,35 @@ impl Palette {
                                 border,
                             })
                         }
+                        PaintContents::Noise(noise) => {
+                            // Noise doesn't sample the color texture, but color combining needs
+                            // one bound, so reserve a single texel in the atlas.
+                            let location = allocator.allocate(vec2i(1, 1), AllocationMode::Atlas);
+                            transient_paint_locations.push(location);
+                            Some(PaintColorTextureMetadata {
+                                location,
+                                page_scale: allocator.page_scale(location.page),
+                                sampling_flags: TextureSamplingFlags::empty(),
+                                filter: PaintFilter::Noise(noise),
+                                transform: Transform2F::default(),
+                                composite_op: overlay.composite_op(),
+                                border: Vector2I::zero(),
+                            })
+                        }
+                        PaintContents::Effect(effect) => {
+                            // Like noise, effects need a color texture bound for color combining.
+                            let location = allocator.allocate(vec2i(1, 1), AllocationMode::Atlas);
+                            transient_paint_locations.push(location);
+                            Some(PaintColorTextureMetadata {
+                                location,
+                                page_scale: allocator.page_scale(location.page),
+                                sampling_flags: TextureSamplingFlags::empty(),
+                                filter: PaintFilter::Effect(effect),
+                                transform: Transform2F::default(),
+                                composite_op: overlay.composite_op(),
+                                border: Vector2I::zero(),
+                            })
+                        }
                     }
                 }
             };
@@ -592,7 +845,7 @@ impl Palette {
             paint_metadata.push(PaintMetadata {
                 color_texture_metadata,
                 is_opaque: paint.is_opaque(),
-                base_color: paint.base_color(),
+                base_color: paint.extended_base_color(),
                 // FIXME(pcwalton)
                 blend_mode: BlendMode::SrcOver,
             });
@@ -603,6 +856,7 @@ impl Palette {
             gradient_tile_builder,
             image_texel_info,
             used_image_hashes,
+            used_external_texture_ids,
         }
     }
 
@@ -646,9 +900,15 @@ impl Palette {
                     geometry: GradientGeometry::Radial { ref transform, .. },
                     ..
                 }) => transform.inverse(),
+                PaintContents::Gradient(Gradient {
+                    geometry: GradientGeometry::Conic { ref transform, .. },
+                    ..
+                }) => transform.inverse(),
+                PaintContents::Noise(ref noise) => noise.transform.inverse(),
+                PaintContents::Effect(ref effect) => effect.transform.inverse(),
                 PaintContents::Pattern(ref pattern) => {
                     match pattern.source() {
-                        PatternSource::Image(_) => {
+                        PatternSource::Image(_) | PatternSource::ExternalTexture { .. } => {
                             let texture_origin_uv =
                                 rect_to_uv(texture_rect, texture_scale).origin();
                             Transform2F::from_scale(texture_scale).translate(texture_origin_uv)
@@ -680,10 +940,12 @@ impl Palette {
                     None => Transform2F::default(),
                     Some(ref color_texture_metadata) => color_texture_metadata.transform,
                 },
-                color_0_combine_mode: if paint_metadata.color_texture_metadata.is_some() {
-                    ColorCombineMode::SrcIn
-                } else {
-                    ColorCombineMode::None
+                color_0_combine_mode: match paint_metadata.color_texture_metadata {
+                    None => ColorCombineMode::None,
+                    Some(ref color_texture_metadata) => match color_texture_metadata.composite_op {
+                        PaintCompositeOp::SrcIn => ColorCombineMode::SrcIn,
+                        PaintCompositeOp::DestIn => ColorCombineMode::DestIn,
+                    },
                 },
                 base_color: paint_metadata.base_color,
                 filter: paint_metadata.filter(),
@@ -698,14 +960,31 @@ impl Palette {
         texture_manager: &mut PaintTextureManager,
     ) {
         for page_id in texture_manager.allocator.page_ids() {
-            let page_size = texture_manager.allocator.page_size(page_id);
-            let descriptor = TexturePageDescriptor { size: page_size };
-
-            if texture_manager.allocator.page_is_new(page_id) {
-                render_commands.push(RenderCommand::AllocateTexturePage {
-                    page_id,
-                    descriptor,
-                });
+            if !texture_manager.allocator.page_is_new(page_id) {
+                continue;
+            }
+
+            // Pages holding external textures are backed by those textures instead of fresh ones.
+            let external_texture_id = texture_manager
+                .cached_external_textures
+                .iter()
+                .find(|(_, location)| location.page == page_id)
+                .map(|(texture_id, _)| *texture_id);
+            match external_texture_id {
+                Some(texture_id) => {
+                    render_commands.push(RenderCommand::DeclareExternalTexturePage {
+                        page_id,
+                        texture_id,
+                    });
+                }
+                None => {
+                    let page_size = texture_manager.allocator.page_size(page_id);
+                    let descriptor = TexturePageDescriptor { size: page_size };
+                    render_commands.push(RenderCommand::AllocateTexturePage {
+                        page_id,
+                        descriptor,
+                    });
+                }
             }
         }
         texture_manager.allocator.mark_all_pages_as_allocated();
@@ -764,6 +1043,23 @@ impl Palette {
         });
     }
 
+    // Frees the pages of external textures that aren't used this frame.
+    fn free_unused_external_textures(
+        &self,
+        texture_manager: &mut PaintTextureManager,
+        used_external_texture_ids: HashSet<ExternalTextureId>,
+    ) {
+        let cached_external_textures = &mut texture_manager.cached_external_textures;
+        let allocator = &mut texture_manager.allocator;
+        cached_external_textures.retain(|texture_id, location| {
+            let keep = used_external_texture_ids.contains(texture_id);
+            if !keep {
+                allocator.free(*location);
+            }
+            keep
+        });
+    }
+
     pub(crate) fn append_palette(&mut self, palette: Palette) -> MergedPaletteInfo {
         // Merge render targets.
         let mut render_target_mapping = HashMap::new();
@@ -790,8 +1086,9 @@ impl Palette {
                             id: old_render_target_id,
                             size,
                         } => {
+                            let new_render_target_id = render_target_mapping[old_render_target_id];
                             let mut new_pattern =
-                                Pattern::from_render_target(*old_render_target_id, *size);
+                                Pattern::from_render_target(new_render_target_id, *size);
                             new_pattern.set_filter(pattern.filter());
                             new_pattern.apply_transform(pattern.transform());
                             new_pattern.set_repeat_x(pattern.repeat_x());
@@ -819,6 +1116,7 @@ impl PaintTextureManager {
         PaintTextureManager {
             allocator: TextureAllocator::new(),
             cached_images: HashMap::new(),
+            cached_external_textures: HashMap::new(),
         }
     }
 }
@@ -844,6 +1142,21 @@ impl PaintMetadata {
                         uv_origin: uv_rect.origin(),
                     }
                 }
+                PaintFilter::ConicGradient { center, angle } => {
+                    let uv_rect =
+                        rect_to_uv(color_metadata.location.rect, color_metadata.page_scale)
+                            .contract(vec2f(0.0, color_metadata.page_scale.y() * 0.5));
+                    Filter::ConicGradient {
+                        center,
+                        angle,
+                        uv_origin: uv_rect.origin(),
+                    }
+                }
+                PaintFilter::Noise(noise) => Filter::Noise(noise),
+                PaintFilter::Effect(effect) => Filter::PaintEffect {
+                    effect,
+                    pixel_size: color_metadata.transform.matrix.det().abs().sqrt(),
+                },
                 PaintFilter::PatternFilter(pattern_filter) => Filter::PatternFilter(pattern_filter),
             },
         }
@@ -864,6 +1177,8 @@ fn rect_to_uv(rect: RectI, texture_scale: Vector2F) -> RectF {
 
 struct GradientTileBuilder {
     tiles: Vec<GradientTile>,
+    // The number of texels in each gradient.
+    resolution: u32,
 }
 
 struct GradientTile {
@@ -873,8 +1188,16 @@ struct GradientTile {
 }
 
 impl GradientTileBuilder {
-    fn new() -> GradientTileBuilder {
-        GradientTileBuilder { tiles: vec![] }
+    fn new(resolution: u32) -> GradientTileBuilder {
+        GradientTileBuilder {
+            tiles: vec![],
+            resolution: resolution.clamp(1, MAX_GRADIENT_RAMP_RESOLUTION),
+        }
+    }
+
+    #[inline]
+    fn tile_size(&self) -> Vector2I {
+        vec2i(self.resolution as i32, GRADIENT_TILE_HEIGHT as i32)
     }
 
     fn allocate(
@@ -883,8 +1206,8 @@ impl GradientTileBuilder {
         transient_paint_locations: &mut Vec<TextureLocation>,
         gradient: &Gradient,
     ) -> TextureLocation {
-        if self.tiles.is_empty() || self.tiles.last().unwrap().next_index == GRADIENT_TILE_LENGTH {
-            let size = Vector2I::splat(GRADIENT_TILE_LENGTH as i32);
+        if self.tiles.is_empty() || self.tiles.last().unwrap().next_index == GRADIENT_TILE_HEIGHT {
+            let size = self.tile_size();
             let area = size.x() as usize * size.y() as usize;
             let page_location = allocator.allocate(size, AllocationMode::OwnPage);
             transient_paint_locations.push(page_location);
@@ -895,12 +1218,13 @@ impl GradientTileBuilder {
             })
         }
 
+        let resolution = self.resolution;
         let mut data = self.tiles.last_mut().unwrap();
         let location = TextureLocation {
             page: data.page,
             rect: RectI::new(
                 vec2i(0, data.next_index as i32),
-                vec2i(GRADIENT_TILE_LENGTH as i32, 1),
+                vec2i(resolution as i32, 1),
             ),
         };
         data.next_index += 1;
@@ -909,9 +1233,9 @@ impl GradientTileBuilder {
         // TODO(pcwalton): Optimize this:
         // 1. Calculate ∇t up front and use differencing in the inner loop.
         // 2. Go four pixels at a time with SIMD.
-        let first_address = location.rect.origin_y() as usize * GRADIENT_TILE_LENGTH as usize;
-        for x in 0..(GRADIENT_TILE_LENGTH as i32) {
-            let t = (x as f32 + 0.5) / GRADIENT_TILE_LENGTH as f32;
+        let first_address = location.rect.origin_y() as usize * resolution as usize;
+        for x in 0..(resolution as i32) {
+            let t = (x as f32 + 0.5) / resolution as f32;
             data.texels[first_address + x as usize] = gradient.sample(t);
         }
 
@@ -919,11 +1243,12 @@ impl GradientTileBuilder {
     }
 
     fn create_render_commands(self, render_commands: &mut Vec<RenderCommand>) {
+        let tile_size = self.tile_size();
         for tile in self.tiles {
             render_commands.push(RenderCommand::UploadTexelData {
                 texels: Arc::new(tile.texels),
                 location: TextureLocation {
-                    rect: RectI::new(vec2i(0, 0), Vector2I::splat(GRADIENT_TILE_LENGTH as i32)),
+                    rect: RectI::new(vec2i(0, 0), tile_size),
                     page: tile.page,
                 },
             });
@@ -936,6 +1261,7 @@ struct PaintLocationsInfo {
     gradient_tile_builder: GradientTileBuilder,
     image_texel_info: Vec<ImageTexelInfo>,
     used_image_hashes: HashSet<ImageHash>,
+    used_external_texture_ids: HashSet<ExternalTextureId>,
 }
 
 struct ImageTexelInfo {
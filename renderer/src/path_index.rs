@@ -0,0 +1,262 @@
+// pathfinder/renderer/src/path_index.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A bounding volume hierarchy over the bounds of draw paths.
+//!
+//! This is a dynamic AABB tree in the style of Box2D's broadphase: paths are inserted next to the
+//! sibling that enlarges the tree the least, and can be removed or moved individually without
+//! rebuilding anything.
+
+use crate::scene::DrawPathId;
+use pathfinder_geometry::rect::RectF;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PathIndex {
+    nodes: Vec<Node>,
+    root: Option<u32>,
+    // Indices of nodes that are free to reuse.
+    free_nodes: Vec<u32>,
+    // The leaf node for each draw path, indexed by draw path ID.
+    leaves: Vec<Option<u32>>,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: RectF,
+    parent: Option<u32>,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Leaf(DrawPathId),
+    Branch([u32; 2]),
+}
+
+impl PathIndex {
+    #[inline]
+    pub(crate) fn new() -> PathIndex {
+        PathIndex::default()
+    }
+
+    /// Sets the bounds of a draw path, or removes it from the index if `bounds` is `None`.
+    pub(crate) fn update(&mut self, draw_path_id: DrawPathId, bounds: Option<RectF>) {
+        self.remove(draw_path_id);
+        if let Some(bounds) = bounds {
+            self.insert(draw_path_id, bounds);
+        }
+    }
+
+    /// Returns the IDs of all paths whose bounds touch `rect`, in ascending order.
+    pub(crate) fn query(&self, rect: RectF) -> Vec<DrawPathId> {
+        let mut draw_path_ids = vec![];
+        let mut stack: Vec<u32> = self.root.into_iter().collect();
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index as usize];
+            if !overlaps(node.bounds, rect) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf(draw_path_id) => draw_path_ids.push(draw_path_id),
+                NodeKind::Branch(children) => stack.extend_from_slice(&children),
+            }
+        }
+        draw_path_ids.sort_by_key(|draw_path_id| draw_path_id.0);
+        draw_path_ids
+    }
+
+    fn insert(&mut self, draw_path_id: DrawPathId, bounds: RectF) {
+        let leaf = self.allocate_node(Node {
+            bounds,
+            parent: None,
+            kind: NodeKind::Leaf(draw_path_id),
+        });
+        let index = draw_path_id.0 as usize;
+        if self.leaves.len() <= index {
+            self.leaves.resize(index + 1, None);
+        }
+        self.leaves[index] = Some(leaf);
+
+        let mut sibling = match self.root {
+            None => {
+                self.root = Some(leaf);
+                return;
+            }
+            Some(root) => root,
+        };
+
+        // Descend toward the child whose bounds grow the least.
+        while let NodeKind::Branch(children) = self.nodes[sibling as usize].kind {
+            let cost = |child: u32| {
+                let child_bounds = self.nodes[child as usize].bounds;
+                area(child_bounds.union_rect(bounds)) - area(child_bounds)
+            };
+            sibling = if cost(children[0]) <= cost(children[1]) {
+                children[0]
+            } else {
+                children[1]
+            };
+        }
+
+        // Replace the sibling with a new branch containing both it and the new leaf.
+        let old_parent = self.nodes[sibling as usize].parent;
+        let branch = self.allocate_node(Node {
+            bounds: self.nodes[sibling as usize].bounds.union_rect(bounds),
+            parent: old_parent,
+            kind: NodeKind::Branch([sibling, leaf]),
+        });
+        self.nodes[sibling as usize].parent = Some(branch);
+        self.nodes[leaf as usize].parent = Some(branch);
+        match old_parent {
+            None => self.root = Some(branch),
+            Some(old_parent) => {
+                self.replace_child(old_parent, sibling, branch);
+                self.refit(old_parent);
+            }
+        }
+    }
+
+    fn remove(&mut self, draw_path_id: DrawPathId) {
+        let leaf = match self.leaves.get_mut(draw_path_id.0 as usize) {
+            Some(leaf) => match leaf.take() {
+                Some(leaf) => leaf,
+                None => return,
+            },
+            None => return,
+        };
+        self.free_nodes.push(leaf);
+
+        let parent = match self.nodes[leaf as usize].parent {
+            None => {
+                self.root = None;
+                return;
+            }
+            Some(parent) => parent,
+        };
+
+        // Replace the parent with the leaf's sibling.
+        let sibling = match self.nodes[parent as usize].kind {
+            NodeKind::Branch([first, second]) if first == leaf => second,
+            NodeKind::Branch([first, _]) => first,
+            NodeKind::Leaf(_) => unreachable!(),
+        };
+        let grandparent = self.nodes[parent as usize].parent;
+        self.nodes[sibling as usize].parent = grandparent;
+        self.free_nodes.push(parent);
+        match grandparent {
+            None => self.root = Some(sibling),
+            Some(grandparent) => {
+                self.replace_child(grandparent, parent, sibling);
+                self.refit(grandparent);
+            }
+        }
+    }
+
+    // Recomputes the bounds of a branch and all of its ancestors.
+    fn refit(&mut self, mut node_index: u32) {
+        loop {
+            if let NodeKind::Branch([first, second]) = self.nodes[node_index as usize].kind {
+                let bounds = self.nodes[first as usize]
+                    .bounds
+                    .union_rect(self.nodes[second as usize].bounds);
+                self.nodes[node_index as usize].bounds = bounds;
+            }
+            match self.nodes[node_index as usize].parent {
+                None => break,
+                Some(parent) => node_index = parent,
+            }
+        }
+    }
+
+    fn replace_child(&mut self, parent: u32, old_child: u32, new_child: u32) {
+        if let NodeKind::Branch(ref mut children) = self.nodes[parent as usize].kind {
+            for child in children.iter_mut() {
+                if *child == old_child {
+                    *child = new_child;
+                }
+            }
+        }
+    }
+
+    fn allocate_node(&mut self, node: Node) -> u32 {
+        match self.free_nodes.pop() {
+            Some(node_index) => {
+                self.nodes[node_index as usize] = node;
+                node_index
+            }
+            None => {
+                self.nodes.push(node);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+}
+
+#[inline]
+fn area(rect: RectF) -> f32 {
+    rect.width() * rect.height()
+}
+
+// Like `RectF::intersects()`, but counts touching edges, so that zero-sized bounds can be found.
+#[inline]
+fn overlaps(a: RectF, b: RectF) -> bool {
+    a.min_x() <= b.max_x()
+        && b.min_x() <= a.max_x()
+        && a.min_y() <= b.max_y()
+        && b.min_y() <= a.max_y()
+}
+
+#[cfg(test)]
+mod test {
+    use super::PathIndex;
+    use crate::scene::DrawPathId;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn query_matches_linear_scan() {
+        let mut index = PathIndex::new();
+        let mut all_bounds = vec![];
+        let mut seed = 1u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as f32 / 65536.0 * 1000.0
+        };
+        for path_index in 0..200 {
+            let bounds = RectF::new(vec2f(random(), random()), vec2f(random(), random()) * 0.1);
+            index.update(DrawPathId(path_index), Some(bounds));
+            all_bounds.push(Some(bounds));
+        }
+
+        // Move some paths and remove others.
+        for path_index in (0..200).step_by(3) {
+            let bounds = RectF::new(vec2f(random(), random()), vec2f(10.0, 10.0));
+            index.update(DrawPathId(path_index), Some(bounds));
+            all_bounds[path_index as usize] = Some(bounds);
+        }
+        for path_index in (0..200).step_by(7) {
+            index.update(DrawPathId(path_index), None);
+            all_bounds[path_index as usize] = None;
+        }
+
+        for _ in 0..50 {
+            let rect = RectF::new(vec2f(random(), random()), vec2f(random(), random()) * 0.3);
+            let expected: Vec<_> = all_bounds
+                .iter()
+                .enumerate()
+                .filter(
+                    |(_, bounds)| matches!(bounds, Some(bounds) if super::overlaps(*bounds, rect)),
+                )
+                .map(|(path_index, _)| DrawPathId(path_index as u32))
+                .collect();
+            assert_eq!(index.query(rect), expected);
+        }
+    }
+}
@@ -17,15 +17,22 @@ use crate::gpu::renderer::Renderer;
 use crate::gpu_data::RenderCommand;
 use crate::options::{BuildOptions, PreparedBuildOptions};
 use crate::options::{PreparedRenderTransform, RenderCommandListener};
-use crate::paint::{MergedPaletteInfo, Paint, PaintId, PaintInfo, PaintTextureManager, Palette};
+use crate::paint::{GradientRampOptions, MergedPaletteInfo, Paint, PaintId, PaintInfo};
+use crate::paint::{PaintTextureManager, Palette};
+use crate::path_index::PathIndex;
+use crate::tile_cache::TileCache;
+use pathfinder_color::ColorU;
 use pathfinder_content::effects::BlendMode;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::{Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{vec2f, Vector2I};
+use pathfinder_geometry::vector::{vec2f, Vector2F, Vector2I};
 use pathfinder_gpu::Device;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::mem;
 use std::ops::Range;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -35,12 +42,21 @@ use std::u64;
 static NEXT_SCENE_ID: AtomicUsize = AtomicUsize::new(0);
 
 /// The vector scene to be rendered.
-#[derive(Clone)]
 pub struct Scene {
     display_list: Vec<DisplayItem>,
     draw_paths: Vec<DrawPath>,
+    // The epoch at which each draw path was last modified.
+    draw_path_epochs: Vec<SceneEpoch>,
     clip_paths: Vec<ClipPath>,
+    glyph_runs: Vec<GlyphRun>,
+    // A bounding volume hierarchy over the bounds of the draw paths.
+    path_index: PathIndex,
     palette: Palette,
+    // The groups that have been pushed but not yet popped. `None` entries are groups that draw
+    // their children directly, without an intermediate render target.
+    group_stack: Vec<Option<PendingGroup>>,
+    // The upper left corners, in scene coordinates, of the render targets of groups.
+    group_origins: HashMap<RenderTargetId, Vector2F>,
     bounds: RectF,
     view_box: RectF,
     id: SceneId,
@@ -55,12 +71,17 @@ impl Scene {
     /// Creates a new empty scene.
     #[inline]
     pub fn new() -> Scene {
-        let scene_id = SceneId(NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed) as u32);
+        let scene_id = SceneId::next();
         Scene {
             display_list: vec![],
             draw_paths: vec![],
+            draw_path_epochs: vec![],
             clip_paths: vec![],
+            glyph_runs: vec![],
+            path_index: PathIndex::new(),
             palette: Palette::new(scene_id),
+            group_stack: vec![],
+            group_origins: HashMap::new(),
             bounds: RectF::default(),
             view_box: RectF::default(),
             id: scene_id,
@@ -73,8 +94,14 @@ impl Scene {
     /// If a render target is on the stack (see `push_render_target()`), the path goes to the
     /// render target. Otherwise, it goes to the main output.
     ///
-    /// Returns an ID which can later be used to retrieve the path via `get_draw_path()`.
-    pub fn push_draw_path(&mut self, draw_path: DrawPath) -> DrawPathId {
+    /// Returns an ID which can later be used to retrieve the path via `get_draw_path()`, or to
+    /// modify it via `replace_draw_path()`, `remove_draw_path()`, or `set_paint()`.
+    pub fn push_draw_path(&mut self, mut draw_path: DrawPath) -> DrawPathId {
+        if let Some(pending_group) = self.group_stack.iter().rev().flatten().next() {
+            let origin = pending_group.origin;
+            draw_path = self.translate_into_group(draw_path, origin);
+        }
+
         let draw_path_index = DrawPathId(self.draw_paths.len() as u32);
         self.draw_paths.push(draw_path);
         self.push_draw_path_with_index(draw_path_index);
@@ -82,8 +109,9 @@ impl Scene {
     }
 
     fn push_draw_path_with_index(&mut self, draw_path_id: DrawPathId) {
-        let new_path_bounds = self.draw_paths[draw_path_id.0 as usize].outline.bounds();
+        let new_path_bounds = self.draw_paths[draw_path_id.0 as usize].bounds();
         self.bounds = self.bounds.union_rect(new_path_bounds);
+        self.update_path_index(draw_path_id);
 
         let end_path_id = DrawPathId(draw_path_id.0 + 1);
         match self.display_list.last_mut() {
@@ -93,7 +121,113 @@ impl Scene {
                 .push(DisplayItem::DrawPaths(draw_path_id..end_path_id)),
         }
 
+        self.touch_draw_path(draw_path_id);
+    }
+
+    // Bumps the epoch and records that the given draw path changed in it.
+    fn touch_draw_path(&mut self, draw_path_id: DrawPathId) {
         self.epoch.next();
+        let index = draw_path_id.0 as usize;
+        if self.draw_path_epochs.len() <= index {
+            self.draw_path_epochs.resize(index + 1, self.epoch);
+        }
+        self.draw_path_epochs[index] = self.epoch;
+    }
+
+    /// Replaces the draw path with the given ID, keeping its place in the display list.
+    ///
+    /// This allows a retained scene to be updated in place instead of being rebuilt from scratch.
+    /// Returns the draw path that was replaced.
+    ///
+    /// If the path belongs to a group with an intermediate render target, the new path is given in
+    /// scene coordinates and translated into the render target, as `push_draw_path()` does.
+    pub fn replace_draw_path(
+        &mut self,
+        draw_path_id: DrawPathId,
+        mut new_draw_path: DrawPath,
+    ) -> DrawPath {
+        if let Some(origin) = self.group_origin(draw_path_id) {
+            new_draw_path = self.translate_into_group(new_draw_path, origin);
+        }
+        self.replace_draw_path_in_place(draw_path_id, new_draw_path)
+    }
+
+    // Replaces a draw path with one already in the coordinate space of its render target.
+    fn replace_draw_path_in_place(
+        &mut self,
+        draw_path_id: DrawPathId,
+        new_draw_path: DrawPath,
+    ) -> DrawPath {
+        if !new_draw_path.outline.is_empty() {
+            self.bounds = self.bounds.union_rect(new_draw_path.bounds());
+        }
+        let old_draw_path =
+            mem::replace(&mut self.draw_paths[draw_path_id.0 as usize], new_draw_path);
+
+        // The new path may not be the glyph that a glyph run recorded.
+        for glyph_run in &mut self.glyph_runs {
+            glyph_run
+                .glyphs
+                .retain(|glyph| glyph.draw_path != draw_path_id);
+        }
+
+        self.update_path_index(draw_path_id);
+        self.touch_draw_path(draw_path_id);
+        old_draw_path
+    }
+
+    fn update_path_index(&mut self, draw_path_id: DrawPathId) {
+        let draw_path = &self.draw_paths[draw_path_id.0 as usize];
+        let bounds = if draw_path.outline.is_empty() {
+            None
+        } else {
+            Some(draw_path.bounds())
+        };
+        self.path_index.update(draw_path_id, bounds);
+    }
+
+    /// Removes the draw path with the given ID, so that it no longer draws anything.
+    ///
+    /// Draw path IDs are stable: the IDs of other paths are unaffected, and the removed path's
+    /// slot is left in place with an empty outline rather than being reused. Returns the draw path
+    /// that was removed.
+    pub fn remove_draw_path(&mut self, draw_path_id: DrawPathId) -> DrawPath {
+        let paint = self.draw_paths[draw_path_id.0 as usize].paint;
+        self.replace_draw_path_in_place(draw_path_id, DrawPath::new(Outline::new(), paint))
+    }
+
+    /// Changes the transform that's applied to the outline of the draw path with the given ID.
+    ///
+    /// This is much cheaper than replacing the path with a transformed copy of its outline, which
+    /// makes it suitable for animating individual objects.
+    pub fn set_draw_path_transform(
+        &mut self,
+        draw_path_id: DrawPathId,
+        new_transform: Transform2F,
+    ) {
+        let draw_path = &mut self.draw_paths[draw_path_id.0 as usize];
+        draw_path.transform = new_transform;
+        self.bounds = self.bounds.union_rect(draw_path.bounds());
+        self.update_path_index(draw_path_id);
+        self.touch_draw_path(draw_path_id);
+    }
+
+    /// Changes the depth of the draw path with the given ID. See `DrawPath::set_depth()`.
+    pub fn set_draw_path_depth(&mut self, draw_path_id: DrawPathId, new_depth: f32) {
+        self.draw_paths[draw_path_id.0 as usize].depth = new_depth;
+        self.touch_draw_path(draw_path_id);
+    }
+
+    /// Changes the paint of the draw path with the given ID.
+    ///
+    /// As with `replace_draw_path()`, the paint is translated into the render target of the
+    /// group that the path belongs to, if any.
+    pub fn set_paint(&mut self, draw_path_id: DrawPathId, mut new_paint: PaintId) {
+        if let Some(origin) = self.group_origin(draw_path_id) {
+            new_paint = self.translate_paint_into_group(new_paint, origin);
+        }
+        self.draw_paths[draw_path_id.0 as usize].paint = new_paint;
+        self.touch_draw_path(draw_path_id);
     }
 
     /// Defines a clip path. Returns an ID that can be used to later clip draw paths.
@@ -125,6 +259,151 @@ impl Scene {
         self.display_list.push(DisplayItem::PopRenderTarget);
     }
 
+    /// Begins a group of paths that are composited together onto the content underneath.
+    ///
+    /// All draw paths pushed until the matching `pop_group()` call belong to the group. If the
+    /// group has an opacity less than 1, a blend mode other than source-over, or is isolated, its
+    /// children are rendered to an intermediate render target covering the view box, which is
+    /// then composited as a whole. Otherwise, the children are drawn directly.
+    ///
+    /// The intermediate target's origin is the upper left corner of the view box, so children
+    /// drawn to it are translated by the opposite of that corner as they're pushed: the transform,
+    /// paint, and clip path of such a draw path refer to the render target, not to the scene.
+    ///
+    /// Groups may be nested. Because the intermediate target is sized to the view box, the view
+    /// box should be set before any groups are pushed.
+    pub fn push_group(&mut self, group: Group) {
+        if !group.needs_intermediate_target() {
+            self.group_stack.push(None);
+            return;
+        }
+
+        // Keep the origin on a pixel boundary so that compositing doesn't resample the target.
+        let origin = self.view_box.origin().floor();
+        let size = (self.view_box.lower_right().ceil() - origin).to_i32();
+        let render_target = RenderTarget::new(size, group.name.clone());
+        let render_target_id = self.push_render_target(render_target);
+        self.group_origins.insert(render_target_id, origin);
+        self.group_stack.push(Some(PendingGroup {
+            group,
+            render_target_id,
+            origin,
+            size,
+            translated_clip_paths: HashMap::new(),
+        }));
+    }
+
+    /// Ends the most-recently-pushed group, compositing its contents if necessary.
+    pub fn pop_group(&mut self) {
+        let PendingGroup {
+            group,
+            render_target_id,
+            origin,
+            size,
+            ..
+        } = match self.group_stack.pop() {
+            Some(Some(pending_group)) => pending_group,
+            Some(None) | None => return,
+        };
+
+        self.pop_render_target();
+
+        let alpha = (group.opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let mut pattern = Pattern::from_render_target(render_target_id, size);
+        pattern.apply_transform(Transform2F::from_translation(origin));
+        let mut paint = Paint::from_pattern(pattern);
+        paint.set_base_color(ColorU::new(255, 255, 255, alpha));
+        let paint_id = self.push_paint(&paint);
+
+        let outline = Outline::from_rect(RectF::new(origin, size.to_f32()));
+        let mut draw_path = DrawPath::new(outline, paint_id);
+        draw_path.set_blend_mode(group.blend_mode);
+        draw_path.set_name(group.name);
+        self.push_draw_path(draw_path);
+    }
+
+    // Moves a draw path from scene coordinates into those of the render target of a group whose
+    // upper left corner is at `origin`.
+    fn translate_into_group(&mut self, mut draw_path: DrawPath, origin: Vector2F) -> DrawPath {
+        draw_path.transform = Transform2F::from_translation(-origin) * draw_path.transform;
+        draw_path.paint = self.translate_paint_into_group(draw_path.paint, origin);
+        draw_path.clip_path = draw_path
+            .clip_path
+            .map(|clip_path_id| self.translate_clip_path_into_group(clip_path_id, origin));
+        draw_path
+    }
+
+    fn translate_paint_into_group(&mut self, paint_id: PaintId, origin: Vector2F) -> PaintId {
+        if self.get_paint(paint_id).is_color() {
+            return paint_id;
+        }
+        let mut paint = self.get_paint(paint_id).clone();
+        paint.apply_transform(&Transform2F::from_translation(-origin));
+        self.push_paint(&paint)
+    }
+
+    // Translated clip paths are shared among the paths of the innermost pending group; paths of
+    // other groups, which can only be reached by replacing them later, get fresh copies unless
+    // their render targets have the same origin.
+    fn translate_clip_path_into_group(
+        &mut self,
+        clip_path_id: ClipPathId,
+        origin: Vector2F,
+    ) -> ClipPathId {
+        let translated_clip_path_id = self
+            .group_stack
+            .iter()
+            .rev()
+            .flatten()
+            .next()
+            .filter(|pending_group| pending_group.origin == origin)
+            .and_then(|pending_group| pending_group.translated_clip_paths.get(&clip_path_id));
+        if let Some(&translated_clip_path_id) = translated_clip_path_id {
+            return translated_clip_path_id;
+        }
+
+        let mut clip_path = self.clip_paths[clip_path_id.0 as usize].clone();
+        clip_path
+            .outline
+            .transform(&Transform2F::from_translation(-origin));
+        clip_path.clip_path = clip_path
+            .clip_path
+            .map(|clip_path_id| self.translate_clip_path_into_group(clip_path_id, origin));
+        let translated_clip_path_id = self.push_clip_path(clip_path);
+
+        if let Some(pending_group) = self.group_stack.iter_mut().rev().flatten().next() {
+            if pending_group.origin == origin {
+                pending_group
+                    .translated_clip_paths
+                    .insert(clip_path_id, translated_clip_path_id);
+            }
+        }
+        translated_clip_path_id
+    }
+
+    // Returns the origin of the group render target that the given draw path is drawn to, if any.
+    fn group_origin(&self, draw_path_id: DrawPathId) -> Option<Vector2F> {
+        let mut render_target_stack = vec![];
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push(render_target_id)
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths(ref range)
+                    if range.start.0 <= draw_path_id.0 && draw_path_id.0 < range.end.0 =>
+                {
+                    let render_target_id = render_target_stack.last()?;
+                    return self.group_origins.get(render_target_id).cloned();
+                }
+                DisplayItem::DrawPaths(_) => {}
+            }
+        }
+        None
+    }
+
     /// Adds all elements in a scene to this one.
     ///
     /// This includes draw paths, clip paths, render targets, and paints.
@@ -134,10 +413,15 @@ impl Scene {
             paint_mapping,
         } = self.palette.append_palette(scene.palette);
 
-        // Merge clip paths.
-        let mut clip_path_mapping = Vec::with_capacity(scene.clip_paths.len());
-        for clip_path in scene.clip_paths {
-            clip_path_mapping.push(self.clip_paths.len());
+        // Merge clip paths. Clip paths can be nested in any other clip path of the scene, so the
+        // mapping is built before any of them are pushed.
+        let clip_path_mapping: Vec<usize> = (0..scene.clip_paths.len())
+            .map(|clip_path_index| self.clip_paths.len() + clip_path_index)
+            .collect();
+        for mut clip_path in scene.clip_paths {
+            clip_path.clip_path = clip_path
+                .clip_path
+                .map(|clip_path_id| ClipPathId(clip_path_mapping[clip_path_id.0 as usize] as u32));
             self.clip_paths.push(clip_path);
         }
 
@@ -153,10 +437,27 @@ impl Scene {
                 }),
                 fill_rule: draw_path.fill_rule,
                 blend_mode: draw_path.blend_mode,
+                transform: draw_path.transform,
+                subpixel_coverage: draw_path.subpixel_coverage,
+                depth: draw_path.depth,
                 name: draw_path.name,
             });
         }
 
+        // Merge group origins.
+        for (old_render_target_id, origin) in scene.group_origins {
+            let new_render_target_id = render_target_mapping[&old_render_target_id];
+            self.group_origins.insert(new_render_target_id, origin);
+        }
+
+        // Merge glyph runs.
+        for mut glyph_run in scene.glyph_runs {
+            for glyph in &mut glyph_run.glyphs {
+                glyph.draw_path = DrawPathId(draw_path_mapping[glyph.draw_path.0 as usize]);
+            }
+            self.glyph_runs.push(glyph_run);
+        }
+
         // Merge display items.
         for display_item in scene.display_list {
             match display_item {
@@ -181,6 +482,94 @@ impl Scene {
         self.epoch.next();
     }
 
+    /// Adds all elements in a scene to this one, transformed by the given transform.
+    ///
+    /// This is useful for placing reusable vector assets, such as icons or symbols, many times
+    /// within one scene. Paths drawn to the main output are transformed, along with their paints
+    /// and clip paths. Paths drawn to render targets (including those of groups) stay as they are,
+    /// since they reach the output through transformed patterns.
+    pub fn append_scene_transformed(&mut self, scene: &Scene, transform: Transform2F) {
+        let mut scene = scene.clone();
+        scene.transform_output(&transform);
+        self.append_scene(scene);
+    }
+
+    // Transforms the paths drawn to the main output, as well as their paints and clip paths.
+    //
+    // Paints and clip paths can also be used inside render targets, where they must stay
+    // untransformed, so transformed copies are made rather than modifying them in place.
+    fn transform_output(&mut self, transform: &Transform2F) {
+        if transform.is_identity() {
+            return;
+        }
+
+        let mut paint_mapping: Vec<Option<PaintId>> = vec![None; self.palette.paints.len()];
+        let mut clip_path_mapping: Vec<Option<ClipPathId>> = vec![None; self.clip_paths.len()];
+        let mut render_target_depth = 0;
+        for display_item_index in 0..self.display_list.len() {
+            let draw_path_id_range = match self.display_list[display_item_index] {
+                DisplayItem::PushRenderTarget(_) => {
+                    render_target_depth += 1;
+                    continue;
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_depth -= 1;
+                    continue;
+                }
+                DisplayItem::DrawPaths(_) if render_target_depth > 0 => continue,
+                DisplayItem::DrawPaths(ref draw_path_id_range) => draw_path_id_range.clone(),
+            };
+
+            for draw_path_index in draw_path_id_range.start.0..draw_path_id_range.end.0 {
+                let draw_path_index = draw_path_index as usize;
+                let old_paint_id = self.draw_paths[draw_path_index].paint;
+                let new_paint_id = match paint_mapping[old_paint_id.0 as usize] {
+                    Some(new_paint_id) => new_paint_id,
+                    None => {
+                        let mut paint = self.palette.paints[old_paint_id.0 as usize].clone();
+                        paint.apply_transform(transform);
+                        let new_paint_id = self.palette.push_paint(&paint);
+                        paint_mapping[old_paint_id.0 as usize] = Some(new_paint_id);
+                        new_paint_id
+                    }
+                };
+
+                let clip_path_id = self.draw_paths[draw_path_index].clip_path;
+                let clip_path_id = clip_path_id.map(|clip_path_id| {
+                    self.transformed_clip_path(clip_path_id, transform, &mut clip_path_mapping)
+                });
+
+                let draw_path = &mut self.draw_paths[draw_path_index];
+                draw_path.transform = *transform * draw_path.transform;
+                draw_path.paint = new_paint_id;
+                draw_path.clip_path = clip_path_id;
+            }
+        }
+    }
+
+    // Returns a transformed copy of the given clip path and the clip paths that it's nested in.
+    fn transformed_clip_path(
+        &mut self,
+        clip_path_id: ClipPathId,
+        transform: &Transform2F,
+        clip_path_mapping: &mut Vec<Option<ClipPathId>>,
+    ) -> ClipPathId {
+        if let Some(new_clip_path_id) = clip_path_mapping[clip_path_id.0 as usize] {
+            return new_clip_path_id;
+        }
+
+        let mut clip_path = self.clip_paths[clip_path_id.0 as usize].clone();
+        clip_path.outline.transform(transform);
+        clip_path.clip_path = clip_path.clip_path.map(|nested_clip_path_id| {
+            self.transformed_clip_path(nested_clip_path_id, transform, clip_path_mapping)
+        });
+
+        let new_clip_path_id = ClipPathId(self.clip_paths.len() as u32);
+        self.clip_paths.push(clip_path);
+        clip_path_mapping[clip_path_id.0 as usize] = Some(new_clip_path_id);
+        new_clip_path_id
+    }
+
     #[inline]
     pub(crate) fn build_paint_info(
         &mut self,
@@ -191,6 +580,21 @@ impl Scene {
             .build_paint_info(texture_manager, render_transform)
     }
 
+    /// Records that the given draw paths are glyphs of a font.
+    ///
+    /// This doesn't draw anything: the glyphs must already have been pushed as draw paths. It lets
+    /// exporters that support text, such as PDF, write the glyphs as text instead of as outlines,
+    /// which makes the text selectable and the output smaller.
+    pub fn push_glyph_run(&mut self, glyph_run: GlyphRun) {
+        self.glyph_runs.push(glyph_run);
+    }
+
+    /// Returns the glyph runs that have been recorded with `push_glyph_run()`.
+    #[inline]
+    pub fn glyph_runs(&self) -> &[GlyphRun] {
+        &self.glyph_runs
+    }
+
     /// Defines a new paint, which specifies how paths are to be filled or stroked. Returns a paint
     /// ID that can be later specified alongside draw paths.
     #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -200,6 +604,135 @@ impl Scene {
         paint_id
     }
 
+    /// Returns the options that control how gradients are baked into ramp textures.
+    #[inline]
+    pub fn gradient_ramp_options(&self) -> GradientRampOptions {
+        self.palette.gradient_ramp_options
+    }
+
+    /// Changes the resolution and filtering of the textures that gradients are baked into.
+    ///
+    /// Use `GradientRampFilter::Nearest` to keep hard stops from blurring, and a higher resolution
+    /// to reduce banding in long gradients.
+    #[inline]
+    pub fn set_gradient_ramp_options(&mut self, new_options: GradientRampOptions) {
+        self.palette.gradient_ramp_options = new_options;
+        self.epoch.next();
+    }
+
+    /// Returns the IDs of the draw paths whose fills contain the given point, topmost first.
+    ///
+    /// The point is in scene coordinates; to pick at a position in the rendered output, transform
+    /// it by the inverse of the render transform first. Each path is tested with its own fill rule
+    /// and must also lie inside its clip path, if any. Paints are ignored, so transparent paths can
+    /// still be picked.
+    ///
+    /// Paths drawn to render targets are found through the paths that fill with those render
+    /// targets as patterns, such as the path that composites a group (see `push_group()`). The
+    /// point is moved into the render target by the inverse of the pattern transform, and the
+    /// paths inside the render target that contain it are listed just before the path that
+    /// fills with it.
+    pub fn pick(&self, point: Vector2F) -> Vec<DrawPathId> {
+        // Find the display items that draw to each render target.
+        let mut render_target_contents = HashMap::new();
+        let mut render_target_stack = vec![];
+        for (display_item_index, display_item) in self.display_list.iter().enumerate() {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push((render_target_id, display_item_index + 1))
+                }
+                DisplayItem::PopRenderTarget => {
+                    if let Some((render_target_id, start)) = render_target_stack.pop() {
+                        render_target_contents.insert(render_target_id, start..display_item_index);
+                    }
+                }
+                DisplayItem::DrawPaths(_) => {}
+            }
+        }
+
+        let mut draw_path_ids = vec![];
+        self.pick_in_display_items(
+            0..self.display_list.len(),
+            point,
+            &render_target_contents,
+            &mut draw_path_ids,
+        );
+        draw_path_ids.reverse();
+        draw_path_ids
+    }
+
+    // Appends the paths at the given point, bottommost first, skipping nested render targets.
+    fn pick_in_display_items(
+        &self,
+        display_item_range: Range<usize>,
+        point: Vector2F,
+        render_target_contents: &HashMap<RenderTargetId, Range<usize>>,
+        draw_path_ids: &mut Vec<DrawPathId>,
+    ) {
+        let mut render_target_depth = 0;
+        for display_item in &self.display_list[display_item_range] {
+            match *display_item {
+                DisplayItem::PushRenderTarget(_) => render_target_depth += 1,
+                DisplayItem::PopRenderTarget => render_target_depth -= 1,
+                DisplayItem::DrawPaths(_) if render_target_depth > 0 => {}
+                DisplayItem::DrawPaths(ref draw_path_id_range) => {
+                    for draw_path_index in draw_path_id_range.start.0..draw_path_id_range.end.0 {
+                        let draw_path = &self.draw_paths[draw_path_index as usize];
+                        let local_point = draw_path.transform.inverse() * point;
+                        if !draw_path
+                            .outline
+                            .contains_point(local_point, draw_path.fill_rule)
+                            || !self.clip_path_contains_point(draw_path.clip_path, point)
+                        {
+                            continue;
+                        }
+
+                        draw_path_ids.push(DrawPathId(draw_path_index));
+
+                        // Look inside the render target that this path is filled with, if any.
+                        let pattern = match self.get_paint(draw_path.paint).pattern() {
+                            Some(pattern) => pattern,
+                            None => continue,
+                        };
+                        if let PatternSource::RenderTarget { id, .. } = *pattern.source() {
+                            if let Some(contents) = render_target_contents.get(&id) {
+                                self.pick_in_display_items(
+                                    contents.clone(),
+                                    pattern.transform().inverse() * point,
+                                    render_target_contents,
+                                    draw_path_ids,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the IDs of the draw paths whose bounding boxes touch the given rectangle, in
+    /// ascending order.
+    ///
+    /// This is a quick conservative test, suitable for culling paths to a viewport or for
+    /// rubber-band selection; it uses a spatial index, so it doesn't scan every path. Clip paths
+    /// aren't taken into account. Paths drawn to render targets are included, and their bounds
+    /// are in the coordinate space of the render target.
+    #[inline]
+    pub fn paths_in_rect(&self, rect: RectF) -> Vec<DrawPathId> {
+        self.path_index.query(rect)
+    }
+
+    fn clip_path_contains_point(&self, clip_path_id: Option<ClipPathId>, point: Vector2F) -> bool {
+        match clip_path_id {
+            None => true,
+            Some(clip_path_id) => {
+                let clip_path = &self.clip_paths[clip_path_id.0 as usize];
+                clip_path.outline.contains_point(point, clip_path.fill_rule)
+                    && self.clip_path_contains_point(clip_path.clip_path, point)
+            }
+        }
+    }
+
     /// Returns a rectangle that should enclose all objects in the scene.
     ///
     /// FIXME(pcwalton): Is this really needed?
@@ -234,8 +767,12 @@ impl Scene {
     pub(crate) fn apply_render_options(
         &self,
         original_outline: &Outline,
+        path_transform: &Transform2F,
+        path_depth: f32,
         options: &PreparedBuildOptions,
     ) -> Outline {
+        // The path transform is folded into the copy that's made below anyway, so transformed
+        // paths cost no more to build than untransformed ones.
         let mut outline;
         match options.transform {
             PreparedRenderTransform::Perspective {
@@ -243,13 +780,28 @@ impl Scene {
                 ref clip_polygon,
                 ..
             } => {
-                if original_outline.is_outside_polygon(clip_polygon) {
+                // Paths off the scene plane get their own near-plane clip.
+                let (perspective, clip_polygon) = if path_depth == 0.0 {
+                    (*perspective, Cow::Borrowed(clip_polygon))
+                } else {
+                    let (perspective, clip_polygon) =
+                        options.transform.perspective_at_depth(path_depth).unwrap();
+                    (perspective, Cow::Owned(clip_polygon))
+                };
+
+                let scene_outline = if path_transform.is_identity() {
+                    Cow::Borrowed(original_outline)
+                } else {
+                    Cow::Owned(original_outline.clone().transformed(path_transform))
+                };
+
+                if scene_outline.is_outside_polygon(&clip_polygon) {
                     outline = Outline::new();
                 } else {
-                    outline = (*original_outline).clone();
+                    outline = scene_outline.into_owned();
                     outline.close_all_contours();
-                    outline.clip_against_polygon(clip_polygon);
-                    outline.apply_perspective(perspective);
+                    outline.clip_against_polygon(&clip_polygon);
+                    outline.apply_perspective(&perspective);
 
                     // TODO(pcwalton): Support subpixel AA in 3D.
                 }
@@ -258,8 +810,9 @@ impl Scene {
                 // TODO(pcwalton): Short circuit.
                 outline = (*original_outline).clone();
                 outline.close_all_contours();
+                let mut transform = Transform2F::default();
                 if options.transform.is_2d() || options.subpixel_aa_enabled {
-                    let mut transform = match options.transform {
+                    transform = match options.transform {
                         PreparedRenderTransform::Transform2D(transform) => transform,
                         PreparedRenderTransform::None => Transform2F::default(),
                         PreparedRenderTransform::Perspective { .. } => unreachable!(),
@@ -267,8 +820,8 @@ impl Scene {
                     if options.subpixel_aa_enabled {
                         transform *= Transform2F::from_scale(vec2f(3.0, 1.0))
                     }
-                    outline.transform(&transform);
                 }
+                outline.transform(&(transform * *path_transform));
             }
         }
 
@@ -281,10 +834,17 @@ impl Scene {
 
     #[inline]
     pub(crate) fn effective_view_box(&self, render_options: &PreparedBuildOptions) -> RectF {
+        let view_box = match render_options.dirty_rect {
+            None => self.view_box,
+            Some(dirty_rect) => self
+                .view_box
+                .intersection(dirty_rect.to_f32())
+                .unwrap_or_default(),
+        };
         if render_options.subpixel_aa_enabled {
-            self.view_box * vec2f(3.0, 1.0)
+            view_box * vec2f(3.0, 1.0)
         } else {
-            self.view_box
+            view_box
         }
     }
 
@@ -306,8 +866,10 @@ impl Scene {
         SceneBuilder::new(self, &prepared_options, sink).build(executor)
     }
 
+    /// Returns the display list, which specifies the order in which paths are drawn and the
+    /// render targets they are drawn to.
     #[inline]
-    pub(crate) fn display_list(&self) -> &[DisplayItem] {
+    pub fn display_list(&self) -> &[DisplayItem] {
         &self.display_list
     }
 
@@ -327,6 +889,18 @@ impl Scene {
         self.draw_paths.len() as u32
     }
 
+    /// Returns the number of clip paths in this scene.
+    #[inline]
+    pub fn clip_path_count(&self) -> u32 {
+        self.clip_paths.len() as u32
+    }
+
+    /// Returns the number of paints in this scene.
+    #[inline]
+    pub fn paint_count(&self) -> u16 {
+        self.palette.paints.len() as u16
+    }
+
     /// Returns the draw path with the given ID.
     #[inline]
     pub fn get_draw_path(&self, draw_path_id: DrawPathId) -> &DrawPath {
@@ -339,6 +913,12 @@ impl Scene {
         &self.clip_paths[clip_path_id.0 as usize]
     }
 
+    /// Returns the render target with the given ID.
+    #[inline]
+    pub fn get_render_target(&self, render_target_id: RenderTargetId) -> &RenderTarget {
+        self.palette.get_render_target(render_target_id)
+    }
+
     /// Returns the paint with the given ID.
     #[inline]
     pub fn get_paint(&self, paint_id: PaintId) -> &Paint {
@@ -359,6 +939,11 @@ impl Scene {
         self.epoch
     }
 
+    #[inline]
+    pub(crate) fn draw_path_epoch(&self, draw_path_id: DrawPathId) -> SceneEpoch {
+        self.draw_path_epochs[draw_path_id.0 as usize]
+    }
+
     /// A convenience method to build a scene and accumulate commands into a vector.
     pub fn build_into_vector<D, E>(
         &mut self,
@@ -401,6 +986,37 @@ impl Scene {
     }
 }
 
+impl Clone for Scene {
+    /// Returns a copy of this scene with a fresh ID.
+    ///
+    /// The copy can be modified independently of the original, so the two must not share tiles
+    /// cached by a `SceneSink` or scene data already uploaded to the GPU.
+    fn clone(&self) -> Scene {
+        Scene {
+            display_list: self.display_list.clone(),
+            draw_paths: self.draw_paths.clone(),
+            draw_path_epochs: self.draw_path_epochs.clone(),
+            clip_paths: self.clip_paths.clone(),
+            glyph_runs: self.glyph_runs.clone(),
+            path_index: self.path_index.clone(),
+            palette: self.palette.clone(),
+            group_stack: self.group_stack.clone(),
+            group_origins: self.group_origins.clone(),
+            bounds: self.bounds,
+            view_box: self.view_box,
+            id: SceneId::next(),
+            epoch: self.epoch,
+        }
+    }
+}
+
+impl SceneId {
+    #[inline]
+    fn next() -> SceneId {
+        SceneId(NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed) as u32)
+    }
+}
+
 /// Receives render commands and delivers them to a `RenderCommandListener`.
 ///
 /// Scene sinks wrap render command listeners with cached information about the previous scene.
@@ -409,6 +1025,7 @@ pub struct SceneSink<'a> {
     pub(crate) renderer_level: RendererLevel,
     pub(crate) last_scene: Option<LastSceneInfo>,
     pub(crate) paint_texture_manager: PaintTextureManager,
+    pub(crate) tile_cache: Option<TileCache>,
 }
 
 pub(crate) struct LastSceneInfo {
@@ -463,6 +1080,33 @@ impl<'a> SceneSink<'a> {
             renderer_level,
             last_scene: None,
             paint_texture_manager: PaintTextureManager::new(),
+            tile_cache: None,
+        }
+    }
+
+    /// Enables or disables caching of tiles across frames.
+    ///
+    /// When enabled, draw paths that haven't been modified since the last build reuse the tiles
+    /// from that build instead of being tiled again, provided that the scene, view box, and
+    /// transform are the same. This only has an effect when tiling is done on CPU, which is the
+    /// case at the D3D9 level.
+    pub fn set_tile_caching_enabled(&mut self, enabled: bool) {
+        if enabled != self.tile_cache.is_some() {
+            self.tile_cache = if enabled {
+                Some(TileCache::new())
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Discards everything remembered about earlier builds, so that the next build allocates and
+    /// uploads all of its textures and tiles all of its paths, as if it were the first.
+    pub(crate) fn forget_previous_frames(&mut self) {
+        self.last_scene = None;
+        self.paint_texture_manager = PaintTextureManager::new();
+        if self.tile_cache.is_some() {
+            self.tile_cache = Some(TileCache::new());
         }
     }
 }
@@ -471,7 +1115,9 @@ impl<'a> SceneSink<'a> {
 #[derive(Clone, Debug)]
 pub struct DrawPath {
     /// The actual vector path outline.
-    pub outline: Outline,
+    ///
+    /// This may be shared with other draw paths, which place it with their own `transform`.
+    pub outline: Arc<Outline>,
     /// The ID of the paint that specifies how to fill the interior of this outline.
     pub paint: PaintId,
     /// The ID of an optional clip path that will be used to clip this path.
@@ -480,6 +1126,20 @@ pub struct DrawPath {
     pub fill_rule: FillRule,
     /// How to blend this path with everything below it.
     pub blend_mode: BlendMode,
+    /// A transform applied to the outline when the scene is built.
+    ///
+    /// The stored outline is left untouched, so animating this is cheap. The paint and the clip
+    /// path are not affected by this transform.
+    pub transform: Transform2F,
+    /// Whether this path is antialiased separately in each color channel, for LCD subpixel text.
+    ///
+    /// Such paths always composite with source-over, regardless of the blend mode.
+    pub subpixel_coverage: bool,
+    /// How far this path is offset along the Z axis of the scene.
+    ///
+    /// This only matters when rendering with `RenderTransform::Perspective` and the depth buffer
+    /// enabled. See `set_depth()`.
+    pub depth: f32,
     /// The name of this path, for debugging.
     ///
     /// Pass the empty string (which does not allocate) if debugging is not needed.
@@ -503,6 +1163,38 @@ pub struct ClipPath {
     pub name: String,
 }
 
+/// Glyphs of one font that have been drawn as paths, recorded so that exporters can write them as
+/// text.
+///
+/// In glyph space, the origin is at the glyph's position on the baseline, y points down, and one
+/// unit is the font size.
+#[derive(Clone, Debug)]
+pub struct GlyphRun {
+    /// The contents of the font file.
+    pub font_data: Arc<Vec<u8>>,
+    /// The index of the font within the file, for font collections.
+    pub font_index: u32,
+    /// The size of the font, in the units of `transform`.
+    pub font_size: f32,
+    /// The transform from the space that the glyph positions are in to the scene.
+    ///
+    /// This doesn't include the transforms of the glyphs' draw paths, which apply on top.
+    pub transform: Transform2F,
+    /// The glyphs, in the order they were drawn.
+    pub glyphs: Vec<RunGlyph>,
+}
+
+/// A glyph in a glyph run.
+#[derive(Clone, Copy, Debug)]
+pub struct RunGlyph {
+    /// The ID of the glyph within its font.
+    pub glyph_id: u32,
+    /// The position of the glyph's origin, before the run's transform is applied.
+    pub position: Vector2F,
+    /// The draw path that draws the glyph.
+    pub draw_path: DrawPathId,
+}
+
 /// The ID of a draw path, unique to a single scene.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct DrawPathId(pub u32);
@@ -526,6 +1218,28 @@ pub struct RenderTarget {
     name: String,
 }
 
+/// A group of paths that are composited together, with collective opacity and blending.
+///
+/// Groups are pushed with `Scene::push_group()` and popped with `Scene::pop_group()`.
+#[derive(Clone, Debug)]
+pub struct Group {
+    opacity: f32,
+    blend_mode: BlendMode,
+    isolated: bool,
+    name: String,
+}
+
+#[derive(Clone, Debug)]
+struct PendingGroup {
+    group: Group,
+    render_target_id: RenderTargetId,
+    // The upper left corner of the render target, in scene coordinates.
+    origin: Vector2F,
+    size: Vector2I,
+    // Copies of clip paths, moved into the coordinate space of the render target.
+    translated_clip_paths: HashMap<ClipPathId, ClipPathId>,
+}
+
 /// High-level drawing commands.
 #[derive(Clone, Debug)]
 pub enum DisplayItem {
@@ -544,14 +1258,24 @@ impl DrawPath {
     ///
     /// Initially, there is no clip path, the fill rule is set to winding, the blend mode is set to
     /// source-over, and the path has no name.
+    ///
+    /// The outline can be an `Arc<Outline>` shared among many paths, such as the instances of a
+    /// symbol, in which case each path places it with `set_transform()`. The geometry is then
+    /// stored only once.
     #[inline]
-    pub fn new(outline: Outline, paint: PaintId) -> DrawPath {
+    pub fn new<O>(outline: O, paint: PaintId) -> DrawPath
+    where
+        O: Into<Arc<Outline>>,
+    {
         DrawPath {
-            outline,
+            outline: outline.into(),
             paint,
             clip_path: None,
             fill_rule: FillRule::Winding,
             blend_mode: BlendMode::SrcOver,
+            transform: Transform2F::default(),
+            subpixel_coverage: false,
+            depth: 0.0,
             name: String::new(),
         }
     }
@@ -562,8 +1286,9 @@ impl DrawPath {
         &self.outline
     }
 
+    /// Returns the ID of the clip path that limits the filled region of this path, if any.
     #[inline]
-    pub(crate) fn clip_path(&self) -> Option<ClipPathId> {
+    pub fn clip_path(&self) -> Option<ClipPathId> {
         self.clip_path
     }
 
@@ -576,13 +1301,15 @@ impl DrawPath {
         self.clip_path = new_clip_path
     }
 
+    /// Returns the ID of the paint that this path is filled with.
     #[inline]
-    pub(crate) fn paint(&self) -> PaintId {
+    pub fn paint(&self) -> PaintId {
         self.paint
     }
 
+    /// Returns the fill rule: even-odd or winding.
     #[inline]
-    pub(crate) fn fill_rule(&self) -> FillRule {
+    pub fn fill_rule(&self) -> FillRule {
         self.fill_rule
     }
 
@@ -592,8 +1319,10 @@ impl DrawPath {
         self.fill_rule = new_fill_rule
     }
 
+    /// Returns the blend mode, which specifies how this path is composited with content underneath
+    /// it.
     #[inline]
-    pub(crate) fn blend_mode(&self) -> BlendMode {
+    pub fn blend_mode(&self) -> BlendMode {
         self.blend_mode
     }
 
@@ -604,6 +1333,77 @@ impl DrawPath {
         self.blend_mode = new_blend_mode
     }
 
+    /// Returns the transform applied to the outline of this path at build time.
+    #[inline]
+    pub fn transform(&self) -> Transform2F {
+        self.transform
+    }
+
+    /// Sets the transform applied to the outline of this path at build time.
+    ///
+    /// To change the transform of a path that's already in a scene, use
+    /// `Scene::set_draw_path_transform()`.
+    #[inline]
+    pub fn set_transform(&mut self, new_transform: Transform2F) {
+        self.transform = new_transform
+    }
+
+    /// Returns true if this path is antialiased separately in each color channel.
+    #[inline]
+    pub fn subpixel_coverage(&self) -> bool {
+        self.subpixel_coverage
+    }
+
+    /// Sets whether this path is antialiased separately in each color channel.
+    ///
+    /// This is meant for text on LCD screens whose subpixels are laid out horizontally in RGB
+    /// order. The path is composited source-over with per-channel alpha, using dual-source
+    /// blending where the device supports it and two passes otherwise.
+    #[inline]
+    pub fn set_subpixel_coverage(&mut self, new_subpixel_coverage: bool) {
+        self.subpixel_coverage = new_subpixel_coverage
+    }
+
+    /// Returns how far this path is offset along the Z axis of the scene.
+    #[inline]
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
+
+    /// Sets how far this path is offset along the Z axis of the scene.
+    ///
+    /// When the scene is rendered with `RenderTransform::Perspective` and the depth buffer is
+    /// enabled (see `Renderer::enable_depth()`), each path is placed on its own plane at this
+    /// depth, and paths are ordered against one another and against other 3D content by the depth
+    /// buffer instead of in painter's order. Paths at the same depth still draw in painter's
+    /// order. Translucent paths should be drawn back to front, as usual for depth-tested content.
+    ///
+    /// This is only honored at the D3D9 renderer level. At the D3D11 level, the depth is ignored.
+    #[inline]
+    pub fn set_depth(&mut self, new_depth: f32) {
+        self.depth = new_depth
+    }
+
+    // Returns the blend mode this path is actually composited with.
+    #[inline]
+    pub(crate) fn effective_blend_mode(&self) -> BlendMode {
+        if self.subpixel_coverage {
+            BlendMode::SrcOver
+        } else {
+            self.blend_mode
+        }
+    }
+
+    // Returns the bounds of the outline after this path's transform is applied.
+    #[inline]
+    pub(crate) fn bounds(&self) -> RectF {
+        if self.transform.is_identity() {
+            self.outline.bounds()
+        } else {
+            self.transform * self.outline.bounds()
+        }
+    }
+
     /// Assigns a name to this path, for debugging.
     #[inline]
     pub fn set_name(&mut self, new_name: String) {
@@ -632,8 +1432,9 @@ impl ClipPath {
         &self.outline
     }
 
+    /// Returns the ID of the nested clip path, if any.
     #[inline]
-    pub(crate) fn clip_path(&self) -> Option<ClipPathId> {
+    pub fn clip_path(&self) -> Option<ClipPathId> {
         self.clip_path
     }
 
@@ -646,8 +1447,9 @@ impl ClipPath {
         self.clip_path = new_clip_path
     }
 
+    /// Returns the fill rule: even-odd or winding.
     #[inline]
-    pub(crate) fn fill_rule(&self) -> FillRule {
+    pub fn fill_rule(&self) -> FillRule {
         self.fill_rule
     }
 
@@ -664,6 +1466,61 @@ impl ClipPath {
     }
 }
 
+impl Group {
+    /// Creates a new group.
+    ///
+    /// Initially, the group is fully opaque, is composited with source-over, is not isolated, and
+    /// has no name.
+    #[inline]
+    pub fn new() -> Group {
+        Group {
+            opacity: 1.0,
+            blend_mode: BlendMode::SrcOver,
+            isolated: false,
+            name: String::new(),
+        }
+    }
+
+    /// Sets the opacity that the group's contents are composited with, from 0 to 1.
+    #[inline]
+    pub fn set_opacity(&mut self, new_opacity: f32) {
+        self.opacity = new_opacity
+    }
+
+    /// Sets the blend mode, which specifies how the group's contents will be composited with
+    /// content underneath it.
+    #[inline]
+    pub fn set_blend_mode(&mut self, new_blend_mode: BlendMode) {
+        self.blend_mode = new_blend_mode
+    }
+
+    /// Sets whether the group is isolated.
+    ///
+    /// The children of an isolated group blend only with each other, never with the content
+    /// underneath the group.
+    #[inline]
+    pub fn set_isolated(&mut self, new_isolated: bool) {
+        self.isolated = new_isolated
+    }
+
+    /// Assigns a name to this group, for debugging.
+    #[inline]
+    pub fn set_name(&mut self, new_name: String) {
+        self.name = new_name
+    }
+
+    fn needs_intermediate_target(&self) -> bool {
+        self.isolated || self.opacity < 1.0 || self.blend_mode != BlendMode::SrcOver
+    }
+}
+
+impl Default for Group {
+    #[inline]
+    fn default() -> Group {
+        Group::new()
+    }
+}
+
 impl RenderTarget {
     /// Declares a new render target of the given device pixel size and with the given name for
     /// debugging.
@@ -706,3 +1563,320 @@ impl PathId {
         DrawPathId(self.0)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ClipPath, ClipPathId, DisplayItem, DrawPath, DrawPathId, Group, Scene, SceneSink};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::RenderCommandListener;
+    use crate::options::{BuildOptions, PreparedBuildOptions, PreparedRenderTransform};
+    use crate::paint::{GradientRampOptions, Paint, PaintId};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F};
+    use std::sync::{Arc, Mutex};
+
+    struct TestSink {
+        sink: SceneSink<'static>,
+        commands: Arc<Mutex<Vec<RenderCommand>>>,
+    }
+
+    impl TestSink {
+        fn new(tile_caching_enabled: bool) -> TestSink {
+            let commands = Arc::new(Mutex::new(vec![]));
+            let commands_for_listener = commands.clone();
+            let listener = RenderCommandListener::new(Box::new(move |command| {
+                commands_for_listener.lock().unwrap().push(command)
+            }));
+            let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+            sink.set_tile_caching_enabled(tile_caching_enabled);
+            TestSink { sink, commands }
+        }
+
+        // Builds the scene and returns the coordinates of every tile drawn, in order.
+        fn build(&mut self, scene: &mut Scene) -> Vec<(i16, i16)> {
+            scene.build(BuildOptions::default(), &mut self.sink, &SequentialExecutor);
+            let mut tiles = vec![];
+            for command in self.commands.lock().unwrap().drain(..) {
+                if let RenderCommand::DrawTilesD3D9(batch) = command {
+                    tiles.extend(batch.tiles.iter().map(|tile| (tile.tile_x, tile.tile_y)));
+                }
+            }
+            tiles
+        }
+    }
+
+    fn square_path(origin: f32, size: f32, paint: PaintId) -> DrawPath {
+        let rect = RectF::new(vec2f(origin, origin), vec2f(size, size));
+        DrawPath::new(Outline::from_rect(rect), paint)
+    }
+
+    #[test]
+    fn cloned_scenes_do_not_share_cached_tiles() {
+        let mut sink = TestSink::new(true);
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::black());
+        scene.push_draw_path(square_path(8.0, 24.0, paint));
+        sink.build(&mut scene);
+
+        // Edit the original and the clone once each, so that their epochs match.
+        let mut clone = scene.clone();
+        assert_ne!(clone.id(), scene.id());
+        scene.replace_draw_path(DrawPathId(0), square_path(8.0, 40.0, paint));
+        clone.replace_draw_path(DrawPathId(0), square_path(72.0, 120.0, paint));
+
+        sink.build(&mut scene);
+        let cached_tiles = sink.build(&mut clone);
+        let expected_tiles = TestSink::new(false).build(&mut clone);
+        assert!(!expected_tiles.is_empty());
+        assert_eq!(cached_tiles, expected_tiles);
+    }
+
+    #[test]
+    fn gradient_ramp_resolution_is_clamped() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        scene.set_gradient_ramp_options(GradientRampOptions {
+            resolution: u32::MAX,
+            ..GradientRampOptions::default()
+        });
+        let mut gradient = Gradient::linear_from_points(vec2f(0.0, 0.0), vec2f(64.0, 0.0));
+        gradient.add_color_stop(ColorU::black(), 0.0);
+        gradient.add_color_stop(ColorU::white(), 1.0);
+        let paint = scene.push_paint(&Paint::from_gradient(gradient));
+        scene.push_draw_path(square_path(0.0, 64.0, paint));
+
+        let mut sink = TestSink::new(false);
+        scene.build(BuildOptions::default(), &mut sink.sink, &SequentialExecutor);
+        let page_widths: Vec<i32> = sink
+            .commands
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|command| match *command {
+                RenderCommand::AllocateTexturePage { ref descriptor, .. } => {
+                    Some(descriptor.size.x())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(page_widths, vec![2048]);
+    }
+
+    #[test]
+    fn path_transforms_apply_before_the_render_transform() {
+        let scene = Scene::new();
+        let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let path_transform = Transform2F::from_translation(vec2f(10.0, 0.0));
+        let options = PreparedBuildOptions {
+            transform: PreparedRenderTransform::Transform2D(Transform2F::from_scale(2.0)),
+            dilation: Vector2F::zero(),
+            subpixel_aa_enabled: false,
+            dirty_rect: None,
+        };
+
+        let transformed = scene.apply_render_options(&outline, &path_transform, 0.0, &options);
+        assert_eq!(
+            transformed.bounds(),
+            RectF::new(vec2f(20.0, 0.0), vec2f(8.0, 8.0))
+        );
+
+        // The original outline is left alone.
+        assert_eq!(
+            outline.bounds(),
+            RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn group_render_targets_cover_only_the_view_box() {
+        let mut scene = Scene::new();
+        let view_box = RectF::new(vec2f(1000.0, 2000.0), vec2f(64.0, 32.0));
+        scene.set_view_box(view_box);
+        let paint = scene.push_paint(&Paint::black());
+        let mut group = Group::new();
+        group.set_opacity(0.5);
+        scene.push_group(group);
+        let rect = RectF::new(vec2f(1040.0, 2010.0), vec2f(8.0, 8.0));
+        let child = scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+        scene.pop_group();
+
+        let render_target_id = match scene.display_list()[0] {
+            DisplayItem::PushRenderTarget(render_target_id) => render_target_id,
+            ref display_item => panic!("expected a render target, got {:?}", display_item),
+        };
+        assert_eq!(
+            scene.get_render_target(render_target_id).size(),
+            vec2i(64, 32)
+        );
+
+        // The child is drawn relative to the render target, and the composite covers the view box.
+        let child_bounds = scene.get_draw_path(child).bounds();
+        assert_eq!(child_bounds, RectF::new(vec2f(40.0, 10.0), vec2f(8.0, 8.0)));
+        let composite = scene.get_draw_path(DrawPathId(child.0 + 1));
+        assert_eq!(composite.bounds(), view_box);
+
+        // The child's tiles land in the render target, and the composite's in the view box.
+        let tiles = TestSink::new(false).build(&mut scene);
+        assert!(tiles.contains(&(2, 0)));
+        assert!(tiles.contains(&(62, 125)));
+    }
+
+    // A scene with a path clipped by a clip path that is itself clipped, all covering 0..10.
+    fn nested_clip_scene() -> Scene {
+        let mut scene = Scene::new();
+        let rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let outer_clip_path = scene.push_clip_path(ClipPath::new(Outline::from_rect(rect)));
+        let mut inner_clip_path = ClipPath::new(Outline::from_rect(rect));
+        inner_clip_path.set_clip_path(Some(outer_clip_path));
+        let inner_clip_path = scene.push_clip_path(inner_clip_path);
+        let paint = scene.push_paint(&Paint::black());
+        let mut draw_path = square_path(0.0, 10.0, paint);
+        draw_path.set_clip_path(Some(inner_clip_path));
+        scene.push_draw_path(draw_path);
+        scene
+    }
+
+    // A scene whose own clip path lies elsewhere, so that appended clip paths get new IDs.
+    fn scene_with_distant_clip_path() -> Scene {
+        let mut scene = Scene::new();
+        let rect = RectF::new(vec2f(500.0, 500.0), vec2f(1.0, 1.0));
+        scene.push_clip_path(ClipPath::new(Outline::from_rect(rect)));
+        scene
+    }
+
+    #[test]
+    fn append_scene_remaps_nested_clip_paths() {
+        let mut scene = scene_with_distant_clip_path();
+        scene.append_scene(nested_clip_scene());
+        assert!(scene.bounds().contains_point(vec2f(5.0, 5.0)));
+        assert_eq!(scene.pick(vec2f(5.0, 5.0)), vec![DrawPathId(0)]);
+        assert_eq!(scene.clip_paths[2].clip_path, Some(ClipPathId(1)));
+    }
+
+    #[test]
+    fn append_scene_transformed_remaps_nested_clip_paths() {
+        let mut scene = scene_with_distant_clip_path();
+        let transform = Transform2F::from_translation(vec2f(100.0, 100.0));
+        scene.append_scene_transformed(&nested_clip_scene(), transform);
+        assert_eq!(scene.pick(vec2f(105.0, 105.0)), vec![DrawPathId(0)]);
+        assert!(scene.pick(vec2f(5.0, 5.0)).is_empty());
+    }
+
+    #[test]
+    fn pick_finds_paths_inside_groups() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(100.0, 100.0), vec2f(200.0, 200.0)));
+        let paint = scene.push_paint(&Paint::black());
+        let background = scene.push_draw_path(square_path(100.0, 200.0, paint));
+        let mut group = Group::new();
+        group.set_opacity(0.5);
+        scene.push_group(group);
+        let outer = scene.push_draw_path(square_path(120.0, 100.0, paint));
+        let inner = scene.push_draw_path(square_path(150.0, 20.0, paint));
+        let overflowing = scene.push_draw_path(square_path(250.0, 100.0, paint));
+        scene.pop_group();
+        let composite = DrawPathId(overflowing.0 + 1);
+
+        assert_eq!(
+            scene.pick(vec2f(160.0, 160.0)),
+            vec![inner, outer, composite, background]
+        );
+        assert_eq!(
+            scene.pick(vec2f(130.0, 130.0)),
+            vec![outer, composite, background]
+        );
+        assert_eq!(scene.pick(vec2f(230.0, 230.0)), vec![composite, background]);
+        assert_eq!(
+            scene.pick(vec2f(260.0, 260.0)),
+            vec![overflowing, composite, background]
+        );
+
+        // The group only shows what falls inside its render target.
+        assert!(scene.pick(vec2f(320.0, 320.0)).is_empty());
+    }
+
+    // A scene with a view box at 100, 100 and a group, drawn to a render target, holding one path.
+    fn scene_with_group_path() -> (Scene, PaintId) {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(100.0, 100.0), vec2f(200.0, 200.0)));
+        let paint = scene.push_paint(&Paint::black());
+        let mut group = Group::new();
+        group.set_opacity(0.5);
+        scene.push_group(group);
+        scene.push_draw_path(square_path(120.0, 20.0, paint));
+        scene.pop_group();
+        (scene, paint)
+    }
+
+    #[test]
+    fn replace_draw_path_translates_into_groups() {
+        let (mut scene, paint) = scene_with_group_path();
+        let mut sink = TestSink::new(true);
+        sink.build(&mut scene);
+
+        let path = DrawPathId(0);
+        scene.replace_draw_path(path, square_path(150.0, 20.0, paint));
+        let expected_bounds = RectF::new(vec2f(50.0, 50.0), vec2f(20.0, 20.0));
+        assert_eq!(scene.get_draw_path(path).bounds(), expected_bounds);
+        assert!(scene.pick(vec2f(160.0, 160.0)).contains(&path));
+        assert!(!scene.pick(vec2f(130.0, 130.0)).contains(&path));
+
+        // The replacement bumps the path's epoch, so cached tiles aren't reused.
+        assert_eq!(scene.draw_path_epochs[0], scene.epoch);
+        assert_eq!(
+            sink.build(&mut scene),
+            TestSink::new(false).build(&mut scene)
+        );
+    }
+
+    #[test]
+    fn set_paint_translates_into_groups() {
+        let (mut scene, _) = scene_with_group_path();
+        let mut gradient = Gradient::linear_from_points(vec2f(100.0, 0.0), vec2f(300.0, 0.0));
+        gradient.add_color_stop(ColorU::black(), 0.0);
+        gradient.add_color_stop(ColorU::white(), 1.0);
+        let gradient_paint = scene.push_paint(&Paint::from_gradient(gradient));
+        scene.set_paint(DrawPathId(0), gradient_paint);
+
+        // The paint is the same as that of a path pushed into the group with it.
+        let (mut expected_scene, _) = scene_with_group_path();
+        let gradient_paint = expected_scene.push_paint(scene.get_paint(gradient_paint));
+        let mut group = Group::new();
+        group.set_opacity(0.5);
+        expected_scene.push_group(group);
+        let expected_path = expected_scene.push_draw_path(square_path(120.0, 20.0, gradient_paint));
+        expected_scene.pop_group();
+
+        let paint = scene.get_draw_path(DrawPathId(0)).paint;
+        let expected_paint = expected_scene.get_draw_path(expected_path).paint;
+        assert_eq!(
+            scene.get_paint(paint),
+            expected_scene.get_paint(expected_paint)
+        );
+    }
+
+    #[test]
+    fn remove_draw_path_inside_group() {
+        let (mut scene, _) = scene_with_group_path();
+        let mut sink = TestSink::new(true);
+        sink.build(&mut scene);
+        let bounds = scene.bounds();
+
+        let path = DrawPathId(0);
+        scene.remove_draw_path(path);
+        assert_eq!(scene.bounds(), bounds);
+        assert!(!scene.pick(vec2f(130.0, 130.0)).contains(&path));
+        assert_eq!(scene.draw_path_epochs[0], scene.epoch);
+        assert_eq!(
+            sink.build(&mut scene),
+            TestSink::new(false).build(&mut scene)
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// pathfinder/renderer/src/tile_cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Caches the results of CPU tiling from frame to frame.
+//!
+//! When a scene is rebuilt with the same transform and view box, draw paths that haven't changed
+//! since the last frame reuse their tiles, backdrops, and fills instead of being flattened and
+//! tiled again. Fills are still submitted every frame, because the mask framebuffer doesn't
+//! persist across frames, but alpha tile IDs are reassigned so that cached and freshly-tiled
+//! paths can be mixed freely.
+
+use crate::builder::{BuiltPath, BuiltPathData, ALPHA_TILE_LEVEL_COUNT};
+use crate::gpu_data::{AlphaTileId, Fill};
+use crate::options::{PreparedBuildOptions, PreparedRenderTransform};
+use crate::scene::{DrawPathId, SceneEpoch, SceneId};
+use fxhash::FxHashMap;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use std::sync::atomic::AtomicUsize;
+
+pub(crate) struct TileCache {
+    key: Option<TileCacheKey>,
+    draw_paths: Vec<Option<CachedDrawPath>>,
+}
+
+// Everything outside the draw path itself that affects how it's tiled.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct TileCacheKey {
+    scene_id: SceneId,
+    transform: Transform2F,
+    view_box: RectF,
+    dilation: Vector2F,
+    subpixel_aa_enabled: bool,
+}
+
+pub(crate) struct CachedDrawPath {
+    epoch: SceneEpoch,
+    path: BuiltPath,
+    // The `link` field of each fill is the local index of its tile, not an alpha tile ID.
+    fills: Vec<Fill>,
+}
+
+impl TileCache {
+    #[inline]
+    pub(crate) fn new() -> TileCache {
+        TileCache {
+            key: None,
+            draw_paths: vec![],
+        }
+    }
+
+    /// Discards all cached paths if anything other than the paths themselves changed since the
+    /// last frame.
+    pub(crate) fn prepare(&mut self, key: Option<TileCacheKey>, draw_path_count: usize) {
+        if key.is_none() || self.key != key {
+            self.draw_paths.clear();
+        }
+        self.key = key;
+        self.draw_paths.resize_with(draw_path_count, || None);
+    }
+
+    #[inline]
+    pub(crate) fn is_active(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub(crate) fn get(
+        &self,
+        draw_path_id: DrawPathId,
+        epoch: SceneEpoch,
+    ) -> Option<&CachedDrawPath> {
+        match self.draw_paths.get(draw_path_id.0 as usize) {
+            Some(Some(cached_draw_path)) if cached_draw_path.epoch == epoch => {
+                Some(cached_draw_path)
+            }
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn insert(&mut self, draw_path_id: DrawPathId, cached_draw_path: CachedDrawPath) {
+        self.draw_paths[draw_path_id.0 as usize] = Some(cached_draw_path);
+    }
+}
+
+impl TileCacheKey {
+    /// Returns the cache key for the given options, or `None` if the results of tiling can't be
+    /// cached (for example, because a perspective transform is in use).
+    pub(crate) fn new(
+        scene_id: SceneId,
+        view_box: RectF,
+        options: &PreparedBuildOptions,
+    ) -> Option<TileCacheKey> {
+        let transform = match options.transform {
+            PreparedRenderTransform::None => Transform2F::default(),
+            PreparedRenderTransform::Transform2D(transform) => transform,
+            PreparedRenderTransform::Perspective { .. } => return None,
+        };
+        Some(TileCacheKey {
+            scene_id,
+            transform,
+            view_box,
+            dilation: options.dilation,
+            subpixel_aa_enabled: options.subpixel_aa_enabled,
+        })
+    }
+}
+
+impl CachedDrawPath {
+    /// Captures the tiling results for a path. Returns `None` if the path isn't tiled on CPU.
+    pub(crate) fn new(
+        epoch: SceneEpoch,
+        path: &BuiltPath,
+        fills: &[Fill],
+    ) -> Option<CachedDrawPath> {
+        let tiles = match path.data {
+            BuiltPathData::CPU(ref data) => &data.tiles,
+            BuiltPathData::TransformCPUBinGPU(_) | BuiltPathData::GPU => return None,
+        };
+
+        let mut local_tile_indices = FxHashMap::default();
+        for (local_tile_index, tile) in tiles.data.iter().enumerate() {
+            if tile.alpha_tile_id.is_valid() {
+                local_tile_indices.insert(tile.alpha_tile_id.0, local_tile_index as u32);
+            }
+        }
+
+        let fills = fills
+            .iter()
+            .map(|fill| Fill {
+                line_segment: fill.line_segment,
+                link: local_tile_indices[&fill.link],
+            })
+            .collect();
+
+        Some(CachedDrawPath {
+            epoch,
+            path: (*path).clone(),
+            fills,
+        })
+    }
+
+    /// Returns a copy of the cached path and its fills with freshly-allocated alpha tiles.
+    pub(crate) fn instantiate(
+        &self,
+        next_alpha_tile_indices: &[AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
+    ) -> (BuiltPath, Vec<Fill>) {
+        let mut path = self.path.clone();
+        let tiles = match path.data {
+            BuiltPathData::CPU(ref mut data) => &mut data.tiles,
+            BuiltPathData::TransformCPUBinGPU(_) | BuiltPathData::GPU => unreachable!(),
+        };
+
+        for tile in &mut tiles.data {
+            if tile.alpha_tile_id.is_valid() {
+                tile.alpha_tile_id = AlphaTileId::new(next_alpha_tile_indices, 0);
+            }
+        }
+
+        let fills = self
+            .fills
+            .iter()
+            .map(|fill| Fill {
+                line_segment: fill.line_segment,
+                link: tiles.data[fill.link as usize].alpha_tile_id.0,
+            })
+            .collect();
+
+        (path, fills)
+    }
+}
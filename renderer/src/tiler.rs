@@ -13,7 +13,8 @@
 
 use crate::builder::{BuiltPath, BuiltPathBinCPUData, BuiltPathData, ObjectBuilder, SceneBuilder};
 use crate::gpu::options::RendererLevel;
-use crate::gpu_data::AlphaTileId;
+use crate::gpu_data::{AlphaTileId, Clip, TileObjectPrimitive, TILE_CTRL_MASK_0_SHIFT};
+use crate::gpu_data::{TILE_CTRL_MASK_EVEN_ODD, TILE_CTRL_MASK_WINDING};
 use crate::options::PrepareMode;
 use crate::scene::{ClipPathId, PathId};
 use crate::tiles::{TilingPathInfo, TILE_HEIGHT, TILE_WIDTH};
@@ -29,11 +30,16 @@ use std::f32::NEG_INFINITY;
 
 const FLATTENING_TOLERANCE: f32 = 0.25;
 
+const TILE_CTRL_MASK_0_BITS: u8 =
+    ((TILE_CTRL_MASK_WINDING | TILE_CTRL_MASK_EVEN_ODD) << TILE_CTRL_MASK_0_SHIFT) as u8;
+
 pub(crate) struct Tiler<'a, 'b, 'c, 'd> {
     scene_builder: &'a SceneBuilder<'b, 'a, 'c, 'd>,
     pub(crate) object_builder: ObjectBuilder,
     outline: &'a Outline,
-    clip_path: Option<&'a BuiltPath>,
+    view_box: RectF,
+    // The clip path, followed by the clip path that clips it, and so on.
+    clip_paths: Vec<&'a BuiltPath>,
 }
 
 impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
@@ -53,10 +59,19 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
             .intersection(view_box)
             .unwrap_or(RectF::default());
 
-        let clip_path = match clip_path_id {
-            Some(clip_path_id) => Some(&built_clip_paths[clip_path_id.0 as usize]),
-            _ => None,
-        };
+        // Clip paths are tiled without their own clips, which are applied to the paths that they
+        // clip instead, so `built_clip_paths` is empty when tiling a clip path.
+        let mut clip_paths = vec![];
+        let mut next_clip_path_id = clip_path_id;
+        while let Some(clip_path_id) = next_clip_path_id {
+            match built_clip_paths.get(clip_path_id.0 as usize) {
+                Some(built_clip_path) => {
+                    clip_paths.push(built_clip_path);
+                    next_clip_path_id = built_clip_path.clip_path_id;
+                }
+                None => break,
+            }
+        }
 
         let object_builder = ObjectBuilder::new(
             path_id,
@@ -72,7 +87,8 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
             scene_builder,
             object_builder,
             outline,
-            clip_path,
+            view_box,
+            clip_paths,
         }
     }
 
@@ -96,12 +112,19 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
 
         for contour in self.outline.contours() {
             for segment in contour.iter(ContourIterFlags::empty()) {
-                process_segment(&segment, self.scene_builder, &mut self.object_builder);
+                process_segment(
+                    &segment,
+                    self.view_box,
+                    self.scene_builder,
+                    &mut self.object_builder,
+                );
             }
         }
     }
 
     fn prepare_tiles(&mut self) {
+        let fill_rule = self.object_builder.built_path.fill_rule;
+
         // Don't do this here if the GPU will do it.
         let (backdrops, tiles, clips) = match self.object_builder.built_path.data {
             BuiltPathData::CPU(ref mut tiled_data) => (
@@ -117,90 +140,152 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
         // Propagate backdrops.
         let tiles_across = tiles.rect.width() as usize;
         for (draw_tile_index, draw_tile) in tiles.data.iter_mut().enumerate() {
-            let tile_coords = vec2i(draw_tile.tile_x as i32, draw_tile.tile_y as i32);
             let column = draw_tile_index % tiles_across;
             let delta = draw_tile.backdrop as i32;
+            draw_tile.backdrop = backdrops[column] as i8;
+            backdrops[column] += delta;
+        }
 
-            let mut draw_alpha_tile_id = draw_tile.alpha_tile_id;
-            let mut draw_tile_backdrop = backdrops[column] as i8;
-
-            if let Some(built_clip_path) = self.clip_path {
-                let clip_tiles = match built_clip_path.data {
-                    BuiltPathData::CPU(BuiltPathBinCPUData { ref tiles, .. }) => tiles,
-                    _ => unreachable!(),
-                };
-                match clip_tiles.get(tile_coords) {
-                    Some(clip_tile) => {
-                        if clip_tile.alpha_tile_id != AlphaTileId(!0)
-                            && draw_alpha_tile_id != AlphaTileId(!0)
-                        {
-                            // Hard case: We have an alpha tile and a clip tile with masks. Add a
-                            // job to combine the two masks. Because the mask combining step
-                            // applies the backdrops, zero out the backdrop in the draw tile itself
-                            // so that we don't double-count it.
-                            let clip = clips
-                                .as_mut()
-                                .expect("Where are the clips?")
-                                .get_mut(tile_coords)
-                                .unwrap();
-                            clip.dest_tile_id = draw_tile.alpha_tile_id;
-                            clip.dest_backdrop = draw_tile_backdrop as i32;
-                            clip.src_tile_id = clip_tile.alpha_tile_id;
-                            clip.src_backdrop = clip_tile.backdrop as i32;
-                            draw_tile_backdrop = 0;
-                        } else if clip_tile.alpha_tile_id != AlphaTileId(!0)
-                            && draw_alpha_tile_id == AlphaTileId(!0)
-                            && draw_tile_backdrop != 0
-                        {
-                            // This is a solid draw tile, but there's a clip applied. Replace it
-                            // with an alpha tile pointing directly to the clip mask.
-                            draw_alpha_tile_id = clip_tile.alpha_tile_id;
-                            draw_tile_backdrop = clip_tile.backdrop;
-                        } else if clip_tile.alpha_tile_id == AlphaTileId(!0)
-                            && clip_tile.backdrop == 0
-                        {
-                            // This is a blank clip tile. Cull the draw tile entirely.
-                            draw_alpha_tile_id = AlphaTileId(!0);
-                            draw_tile_backdrop = 0;
-                        }
-                    }
-                    None => {
-                        // This draw tile is outside the clip path rect. Cull the tile.
-                        draw_alpha_tile_id = AlphaTileId(!0);
-                        draw_tile_backdrop = 0;
-                    }
-                }
+        if self.clip_paths.is_empty() {
+            return;
+        }
+
+        let clips = clips.as_mut().expect("Where are the clips?");
+        let mut clip_tiles = vec![];
+        for draw_tile in &mut tiles.data {
+            clip_draw_tile(
+                draw_tile,
+                fill_rule,
+                &self.clip_paths,
+                &mut clip_tiles,
+                clips,
+                self.scene_builder,
+            );
+        }
+    }
+}
+
+// Applies the clip paths to a draw tile whose backdrop has been propagated.
+//
+// Draw tiles that are clipped out entirely are culled. Where the draw tile and clip tiles have
+// masks, jobs are added to combine the clip masks into the draw tile's mask, one per clip path, in
+// order.
+fn clip_draw_tile<'a>(
+    draw_tile: &mut TileObjectPrimitive,
+    fill_rule: FillRule,
+    clip_paths: &[&'a BuiltPath],
+    clip_tiles: &mut Vec<(&'a TileObjectPrimitive, FillRule)>,
+    clips: &mut Vec<Clip>,
+    scene_builder: &SceneBuilder,
+) {
+    let tile_coords = vec2i(draw_tile.tile_x as i32, draw_tile.tile_y as i32);
+
+    // Collect the clip tiles with masks. Clip tiles without masks are either solid, in which case
+    // they don't affect the draw tile, or blank.
+    clip_tiles.clear();
+    for clip_path in clip_paths {
+        let tiles = match clip_path.data {
+            BuiltPathData::CPU(BuiltPathBinCPUData { ref tiles, .. }) => tiles,
+            _ => unreachable!(),
+        };
+        match tiles.get(tile_coords) {
+            Some(clip_tile) if clip_tile.alpha_tile_id != AlphaTileId(!0) => {
+                clip_tiles.push((clip_tile, clip_path.fill_rule))
+            }
+            Some(clip_tile) if backdrop_is_solid(clip_tile.backdrop, clip_path.fill_rule) => {}
+            _ => {
+                // This is a blank clip tile, or the draw tile is outside the clip path rect. Cull
+                // the draw tile entirely.
+                draw_tile.alpha_tile_id = AlphaTileId(!0);
+                draw_tile.backdrop = 0;
+                return;
             }
+        }
+    }
 
-            draw_tile.alpha_tile_id = draw_alpha_tile_id;
-            draw_tile.backdrop = draw_tile_backdrop;
+    if clip_tiles.is_empty() {
+        return;
+    }
 
-            backdrops[column] += delta;
+    if draw_tile.alpha_tile_id == AlphaTileId(!0) {
+        if !backdrop_is_solid(draw_tile.backdrop, fill_rule) {
+            return;
+        }
+
+        if let [(clip_tile, clip_fill_rule)] = **clip_tiles {
+            // This is a solid draw tile, but there's a clip applied. Replace it with an alpha tile
+            // pointing directly to the clip mask.
+            draw_tile.alpha_tile_id = clip_tile.alpha_tile_id;
+            draw_tile.backdrop = clip_tile.backdrop;
+            draw_tile.ctrl = (draw_tile.ctrl & !TILE_CTRL_MASK_0_BITS)
+                | (mask_fill_rule(clip_fill_rule) << TILE_CTRL_MASK_0_SHIFT) as u8;
+            return;
         }
+
+        // This is a solid draw tile with several clip masks applied. Give it an empty mask of its
+        // own for the masks to be combined into, since the clip masks may be shared.
+        draw_tile.alpha_tile_id = AlphaTileId::new(&scene_builder.next_alpha_tile_indices, 0);
+    }
+
+    // Hard case: We have an alpha tile and clip tiles with masks. Add jobs to combine the masks.
+    // Because the mask combining step applies the backdrops and fill rules, zero out the backdrop
+    // in the draw tile itself so that we don't double-count it. The first job leaves coverage
+    // between 0 and 1 in the draw tile's mask, which the later ones treat as a nonzero mask.
+    let mut dest_backdrop = draw_tile.backdrop as i32;
+    let mut dest_ctrl = mask_fill_rule(fill_rule);
+    for &(clip_tile, clip_fill_rule) in clip_tiles.iter() {
+        clips.push(Clip {
+            dest_tile_id: draw_tile.alpha_tile_id,
+            dest_backdrop,
+            dest_ctrl,
+            src_tile_id: clip_tile.alpha_tile_id,
+            src_backdrop: clip_tile.backdrop as i32,
+            src_ctrl: mask_fill_rule(clip_fill_rule),
+            ..Clip::default()
+        });
+        dest_backdrop = 0;
+        dest_ctrl = TILE_CTRL_MASK_WINDING;
+    }
+    draw_tile.backdrop = 0;
+}
+
+// Returns true if a tile without a mask is filled, given its backdrop.
+fn backdrop_is_solid(backdrop: i8, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::Winding => backdrop != 0,
+        FillRule::EvenOdd => backdrop % 2 != 0,
+    }
+}
+
+fn mask_fill_rule(fill_rule: FillRule) -> i32 {
+    match fill_rule {
+        FillRule::Winding => TILE_CTRL_MASK_WINDING,
+        FillRule::EvenOdd => TILE_CTRL_MASK_EVEN_ODD,
     }
 }
 
 fn process_segment(
     segment: &Segment,
+    view_box: RectF,
     scene_builder: &SceneBuilder,
     object_builder: &mut ObjectBuilder,
 ) {
     // TODO(pcwalton): Stop degree elevating.
     if segment.is_quadratic() {
         let cubic = segment.to_cubic();
-        return process_segment(&cubic, scene_builder, object_builder);
+        return process_segment(&cubic, view_box, scene_builder, object_builder);
     }
 
     if segment.is_line()
         || (segment.is_cubic() && segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE))
     {
-        return process_line_segment(segment.baseline, scene_builder, object_builder);
+        return process_line_segment(segment.baseline, view_box, scene_builder, object_builder);
     }
 
     // TODO(pcwalton): Use a smarter flattening algorithm.
     let (prev, next) = segment.split(0.5);
-    process_segment(&prev, scene_builder, object_builder);
-    process_segment(&next, scene_builder, object_builder);
+    process_segment(&prev, view_box, scene_builder, object_builder);
+    process_segment(&next, view_box, scene_builder, object_builder);
 }
 
 // This is the meat of the technique. It implements the fast lattice-clipping algorithm from
@@ -210,10 +295,10 @@ fn process_segment(
 // Ray Tracing" 1987: http://www.cse.yorku.ca/~amana/research/grid.pdf
 fn process_line_segment(
     line_segment: LineSegment2F,
+    view_box: RectF,
     scene_builder: &SceneBuilder,
     object_builder: &mut ObjectBuilder,
 ) {
-    let view_box = scene_builder.scene.view_box();
     let clip_box = RectF::from_points(
         vec2f(view_box.min_x(), NEG_INFINITY),
         view_box.lower_right(),
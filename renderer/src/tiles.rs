@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::gpu_data::TILE_CTRL_SUBPIXEL_COVERAGE;
 use crate::gpu_data::{TileObjectPrimitive, TILE_CTRL_MASK_WINDING};
 use crate::gpu_data::{TILE_CTRL_MASK_0_SHIFT, TILE_CTRL_MASK_EVEN_ODD};
 use crate::paint::PaintId;
@@ -30,6 +31,7 @@ pub(crate) struct DrawTilingPathInfo {
     pub(crate) paint_id: PaintId,
     pub(crate) blend_mode: BlendMode,
     pub(crate) fill_rule: FillRule,
+    pub(crate) subpixel_coverage: bool,
 }
 
 impl TilingPathInfo {
@@ -54,6 +56,9 @@ impl TilingPathInfo {
                         ctrl |= (TILE_CTRL_MASK_WINDING << TILE_CTRL_MASK_0_SHIFT) as u8
                     }
                 }
+                if draw_tiling_path_info.subpixel_coverage {
+                    ctrl |= TILE_CTRL_SUBPIXEL_COVERAGE as u8
+                }
             }
             TilingPathInfo::Clip => {}
         }
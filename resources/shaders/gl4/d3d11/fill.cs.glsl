@@ -50,6 +50,32 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT){
 }
 
 
+
+
+vec4 computeCoverageSupersample(vec2 from, vec2 to){
+    const vec2 samplePositions[8]= vec2[8](vec2(1.0, - 3.0), vec2(- 1.0, 3.0),
+                                            vec2(5.0, 1.0), vec2(- 3.0, - 5.0),
+                                            vec2(- 5.0, 5.0), vec2(- 7.0, - 1.0),
+                                            vec2(3.0, 7.0), vec2(7.0, - 7.0));
+
+    vec2 left = from . x < to . x ? from : to, right = from . x < to . x ? to : from;
+
+
+    vec4 coverages = vec4(0.0);
+    for(int sampleIndex = 0;sampleIndex < 8;sampleIndex ++){
+        vec2 samplePosition = samplePositions[sampleIndex]/ 16.0;
+        if(samplePosition . x < left . x || samplePosition . x >= right . x)
+            continue;
+        float t =(samplePosition . x - left . x)/(right . x - left . x);
+        float y = mix(left . y, right . y, t);
+        coverages += step(vec4(y), vec4(samplePosition . y)+ vec4(0.0, 1.0, 2.0, 3.0));
+    }
+
+    float winding = from . x < to . x ? - 1.0 : 1.0;
+    return coverages * winding / 8.0;
+}
+
+
 layout(local_size_x = 16, local_size_y = 4)in;
 
 
@@ -59,6 +85,8 @@ layout(local_size_x = 16, local_size_y = 4)in;
 
 layout(rgba8)uniform image2D uDest;
 uniform sampler2D uAreaLUT;
+
+uniform int uSupersample;
 uniform ivec2 uAlphaTileRange;
 
 layout(std430, binding = 0)buffer bFills {
@@ -100,7 +128,10 @@ vec4 accumulateCoverageForFillList(int fillIndex, ivec2 tileSubCoord){
         vec4 lineSegment = vec4(fillFrom & 0xffff, fillFrom >> 16,
                                 fillTo & 0xffff, fillTo >> 16)/ 256.0;
         lineSegment -= tileFragCoord . xyxy;
-        coverages += computeCoverage(lineSegment . xy, lineSegment . zw, uAreaLUT);
+        if(uSupersample != 0)
+            coverages += computeCoverageSupersample(lineSegment . xy, lineSegment . zw);
+        else
+            coverages += computeCoverage(lineSegment . xy, lineSegment . zw, uAreaLUT);
         fillIndex = int(iFills[fillIndex * 3 + 2]);
         iteration ++;
     } while(fillIndex >= 0 && iteration < 1024);
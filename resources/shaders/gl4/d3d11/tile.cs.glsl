@@ -93,6 +93,9 @@ layout(local_size_x = 16, local_size_y = 4)in;
 
 
 
+
+
+
 
 
 
@@ -331,6 +334,23 @@ vec4 filterRadialGradient(vec2 colorTexCoord,
 
 
 
+vec4 filterConicGradient(vec2 colorTexCoord,
+                         sampler2D colorTexture,
+                         vec4 filterParams0,
+                         vec4 filterParams1){
+    vec2 center = filterParams0 . xy, uvOrigin = filterParams1 . zw;
+    float angle = filterParams0 . z;
+
+    vec2 d = colorTexCoord - center;
+    float t = fract((atan(d . y, d . x)- angle)*(1.0 / 6.28318530718));
+    return texture(colorTexture, uvOrigin + vec2(t, 0.0));
+}
+
+
+
+
+
+
 vec4 filterBlur(vec2 colorTexCoord,
                 sampler2D colorTexture,
                 vec2 colorTextureSize,
@@ -383,6 +403,121 @@ vec4 filterColorMatrix(vec2 colorTexCoord,
     return colorMatrix * srcColor + filterParams4;
 }
 
+
+
+
+
+
+vec2 filterNoiseGradient(vec2 latticePoint, int channel, int seed){
+    uint hash = uint(int(latticePoint . x))* 0x8da6b343u ^
+        uint(int(latticePoint . y))* 0xd8163841u ^
+        uint(seed * 4 + channel)* 0xcb1ab31fu;
+    hash ^= hash >> 16;
+    hash *= 0x7feb352du;
+    hash ^= hash >> 15;
+    hash *= 0x846ca68bu;
+    hash ^= hash >> 16;
+    float angle = float(hash & 0xffffu)*(6.283185307179586 / 65536.0);
+    return vec2(cos(angle), sin(angle));
+}
+
+
+
+float filterNoisePerlin(vec2 position, vec2 period, int channel, int seed){
+    vec2 lattice0 = floor(position), lattice1 = lattice0 + vec2(1.0);
+    vec2 t = position - lattice0;
+    if(period . x > 0.0){
+        lattice0 . x = mod(lattice0 . x, period . x);
+        lattice1 . x = mod(lattice1 . x, period . x);
+    }
+    if(period . y > 0.0){
+        lattice0 . y = mod(lattice0 . y, period . y);
+        lattice1 . y = mod(lattice1 . y, period . y);
+    }
+
+    float n00 = dot(filterNoiseGradient(lattice0, channel, seed), t);
+    float n10 = dot(filterNoiseGradient(vec2(lattice1 . x, lattice0 . y), channel, seed),
+                    t - vec2(1.0, 0.0));
+    float n01 = dot(filterNoiseGradient(vec2(lattice0 . x, lattice1 . y), channel, seed),
+                    t - vec2(0.0, 1.0));
+    float n11 = dot(filterNoiseGradient(lattice1, channel, seed), t - vec2(1.0));
+
+    vec2 s = t * t *(vec2(3.0)- 2.0 * t);
+    return mix(mix(n00, n10, s . x), mix(n01, n11, s . x), s . y);
+}
+
+
+
+
+
+
+
+
+
+vec4 filterNoise(vec2 colorTexCoord, vec4 filterParams0, vec4 filterParams1, vec4 filterParams2){
+
+    vec2 baseFrequency = filterParams0 . xy;
+    int numOctaves = int(filterParams0 . z), seed = int(filterParams0 . w);
+    vec2 stitchOrigin = filterParams1 . xy, period = filterParams1 . zw;
+    bool fractalNoise = filterParams2 . x != 0.0;
+
+    vec2 position =(colorTexCoord - stitchOrigin)* baseFrequency;
+    vec4 sum = vec4(0.0);
+    float amplitude = 1.0;
+    for(int octave = 0;octave < numOctaves;octave ++){
+        vec4 octaveNoise = vec4(filterNoisePerlin(position, period, 0, seed),
+                                filterNoisePerlin(position, period, 1, seed),
+                                filterNoisePerlin(position, period, 2, seed),
+                                filterNoisePerlin(position, period, 3, seed));
+        sum +=(fractalNoise ? octaveNoise : abs(octaveNoise))* amplitude;
+        position *= 2.0;
+        period *= 2.0;
+        amplitude *= 0.5;
+    }
+
+
+    if(fractalNoise)
+        sum = sum * 0.5 + vec4(0.5);
+    return clamp(sum, vec4(0.0), vec4(1.0));
+}
+
+
+
+
+
+
+
+
+
+float filterPaintEffectLines(float position, float spacing, float lineWidth, float pixelSize){
+    float dist = abs(fract(position / spacing + 0.5)- 0.5)* spacing;
+    return clamp((lineWidth * 0.5 - dist)/ pixelSize + 0.5, 0.0, 1.0);
+}
+
+
+
+
+
+
+
+
+vec4 filterPaintEffect(vec2 colorTexCoord, vec4 filterParams0){
+    int kind = int(filterParams0 . x);
+    float size = filterParams0 . y, amount = filterParams0 . z, pixelSize = filterParams0 . w;
+
+    float alpha;
+    if(kind == 2){
+        vec2 offset =(fract(colorTexCoord / size)- vec2(0.5))* size;
+        float radius = size * sqrt(clamp(amount, 0.0, 1.0)/ 3.14159265);
+        alpha = clamp((radius - length(offset))/ pixelSize + 0.5, 0.0, 1.0);
+    } else {
+        alpha = filterPaintEffectLines(colorTexCoord . y, size, amount, pixelSize);
+        if(kind == 1)
+            alpha = max(alpha, filterPaintEffectLines(colorTexCoord . x, size, amount, pixelSize));
+    }
+    return vec4(vec3(1.0), alpha);
+}
+
 vec4 filterNone(vec2 colorTexCoord, sampler2D colorTexture){
     return sampleColor(colorTexture, colorTexCoord);
 }
@@ -430,6 +565,12 @@ vec4 filterColor(vec2 colorTexCoord,
                           filterParams2,
                           filterParams3,
                           filterParams4);
+    case 0x5 :
+        return filterNoise(colorTexCoord, filterParams0, filterParams1, filterParams2);
+    case 0x6 :
+        return filterPaintEffect(colorTexCoord, filterParams0);
+    case 0x7 :
+        return filterConicGradient(colorTexCoord, colorTexture, filterParams0, filterParams1);
     }
     return filterNone(colorTexCoord, colorTexture);
 }
@@ -442,34 +583,44 @@ vec3 compositeSelect(bvec3 cond, vec3 ifTrue, vec3 ifFalse){
                 cond . z ? ifTrue . z : ifFalse . z);
 }
 
-float compositeDivide(float num, float denom){
-    return denom != 0.0 ? num / denom : 0.0;
-}
-
 vec3 compositeColorDodge(vec3 destColor, vec3 srcColor){
     bvec3 destZero = equal(destColor, vec3(0.0)), srcOne = equal(srcColor, vec3(1.0));
     return compositeSelect(destZero,
                            vec3(0.0),
-                           compositeSelect(srcOne, vec3(1.0), destColor /(vec3(1.0)- srcColor)));
+                           compositeSelect(srcOne,
+                                           vec3(1.0),
+                                           min(destColor /(vec3(1.0)- srcColor), vec3(1.0))));
 }
 
 
-vec3 compositeHSLToRGB(vec3 hsl){
-    float a = hsl . y * min(hsl . z, 1.0 - hsl . z);
-    vec3 ks = mod(vec3(0.0, 8.0, 4.0)+ vec3(hsl . x * 1.9098593171027443), 12.0);
-    return hsl . zzz - clamp(min(ks - vec3(3.0), vec3(9.0)- ks), - 1.0, 1.0)* a;
+
+
+float compositeLum(vec3 color){
+    return dot(color, vec3(0.3, 0.59, 0.11));
+}
+
+vec3 compositeClipColor(vec3 color){
+    float l = compositeLum(color);
+    float n = min(min(color . r, color . g), color . b), x = max(max(color . r, color . g), color . b);
+    if(n < 0.0)
+        color = vec3(l)+(color - vec3(l))* l /(l - n);
+    if(x > 1.0)
+        color = vec3(l)+(color - vec3(l))*(1.0 - l)/(x - l);
+    return color;
 }
 
+vec3 compositeSetLum(vec3 color, float l){
+    return compositeClipColor(color + vec3(l - compositeLum(color)));
+}
+
+float compositeSat(vec3 color){
+    return max(max(color . r, color . g), color . b)- min(min(color . r, color . g), color . b);
+}
 
-vec3 compositeRGBToHSL(vec3 rgb){
-    float v = max(max(rgb . r, rgb . g), rgb . b), xMin = min(min(rgb . r, rgb . g), rgb . b);
-    float c = v - xMin, l = mix(xMin, v, 0.5);
-    vec3 terms = rgb . r == v ? vec3(0.0, rgb . gb):
-                 rgb . g == v ? vec3(2.0, rgb . br):
-                              vec3(4.0, rgb . rg);
-    float h = 1.0471975511965976 * compositeDivide(terms . x * c + terms . y - terms . z, c);
-    float s = compositeDivide(c, v);
-    return vec3(h, s, l);
+vec3 compositeSetSat(vec3 color, float s){
+    float colorMin = min(min(color . r, color . g), color . b);
+    float colorSat = compositeSat(color);
+    return colorSat > 0.0 ?(color - vec3(colorMin))* s / colorSat : vec3(0.0);
 }
 
 vec3 compositeScreen(vec3 destColor, vec3 srcColor){
@@ -493,19 +644,6 @@ vec3 compositeSoftLight(vec3 destColor, vec3 srcColor){
     return destColor +(srcColor * 2.0 - 1.0)* factor;
 }
 
-vec3 compositeHSL(vec3 destColor, vec3 srcColor, int op){
-    switch(op){
-    case 0xc :
-        return vec3(srcColor . x, destColor . y, destColor . z);
-    case 0xd :
-        return vec3(destColor . x, srcColor . y, destColor . z);
-    case 0xe :
-        return vec3(srcColor . x, srcColor . y, destColor . z);
-    default :
-        return vec3(destColor . x, destColor . y, srcColor . z);
-    }
-}
-
 vec3 compositeRGB(vec3 destColor, vec3 srcColor, int op){
     switch(op){
     case 0x1 :
@@ -531,16 +669,31 @@ vec3 compositeRGB(vec3 destColor, vec3 srcColor, int op){
     case 0xb :
         return destColor + srcColor - vec3(2.0)* destColor * srcColor;
     case 0xc :
+        return compositeSetLum(compositeSetSat(srcColor, compositeSat(destColor)),
+                               compositeLum(destColor));
     case 0xd :
+        return compositeSetLum(compositeSetSat(destColor, compositeSat(srcColor)),
+                               compositeLum(destColor));
     case 0xe :
+        return compositeSetLum(srcColor, compositeLum(destColor));
     case 0xf :
-        return compositeHSLToRGB(compositeHSL(compositeRGBToHSL(destColor),
-                                              compositeRGBToHSL(srcColor),
-                                              op));
+        return compositeSetLum(destColor, compositeLum(srcColor));
     }
     return srcColor;
 }
 
+
+
+vec4 compositeColors(vec4 srcColor, vec4 destColor, int op){
+    vec3 destRGB = destColor . a > 0.0 ? destColor . rgb / destColor . a : vec3(0.0);
+    vec3 blendedRGB = compositeRGB(destRGB, srcColor . rgb, op);
+    vec3 resultRGB = srcColor . a *(1.0 - destColor . a)* srcColor . rgb +
+                     srcColor . a * destColor . a * blendedRGB +
+                     (1.0 - srcColor . a)* destColor . rgb;
+    float resultAlpha = srcColor . a + destColor . a *(1.0 - srcColor . a);
+    return resultAlpha > 0.0 ? vec4(resultRGB / resultAlpha, resultAlpha): vec4(0.0);
+}
+
 vec4 composite(vec4 srcColor,
                sampler2D destTexture,
                vec2 destTextureSize,
@@ -549,18 +702,26 @@ vec4 composite(vec4 srcColor,
     if(op == 0x0)
         return srcColor;
 
-
     vec2 destTexCoord = fragCoord / destTextureSize;
     vec4 destColor = texture(destTexture, destTexCoord);
-    vec3 blendedRGB = compositeRGB(destColor . rgb, srcColor . rgb, op);
-    return vec4(srcColor . a *(1.0 - destColor . a)* srcColor . rgb +
-                srcColor . a * destColor . a * blendedRGB +
-                (1.0 - srcColor . a)* destColor . rgb,
-                1.0);
+    return compositeColors(srcColor, destColor, op);
 }
 
 
 
+float fetchMaskCoverage(sampler2D maskTexture,
+                        vec2 maskTextureSize,
+                        ivec2 maskTexCoordI,
+                        float backdrop,
+                        int maskCtrl){
+    vec4 texel = texture(maskTexture,(vec2(maskTexCoordI / ivec2(1, 4))+ 0.5)/ maskTextureSize);
+    float coverage = texel[maskTexCoordI . y % 4]+ backdrop;
+
+    if((maskCtrl & 0x1)!= 0)
+        return abs(coverage);
+    return 1.0 - abs(1.0 - mod(coverage, 2.0));
+}
+
 float sampleMask(float maskAlpha,
                  sampler2D maskTexture,
                  vec2 maskTextureSize,
@@ -570,18 +731,63 @@ float sampleMask(float maskAlpha,
         return maskAlpha;
 
     ivec2 maskTexCoordI = ivec2(floor(maskTexCoord . xy));
-    vec4 texel = texture(maskTexture,(vec2(maskTexCoordI / ivec2(1, 4))+ 0.5)/ maskTextureSize);
-    float coverage = texel[maskTexCoordI . y % 4]+ maskTexCoord . z;
-
-    if((maskCtrl & 0x1)!= 0)
-        coverage = abs(coverage);
-    else
-        coverage = 1.0 - abs(1.0 - mod(coverage, 2.0));
+    float coverage = fetchMaskCoverage(maskTexture,
+                                       maskTextureSize,
+                                       maskTexCoordI,
+                                       maskTexCoord . z,
+                                       maskCtrl);
     return min(maskAlpha, coverage);
 }
 
 
 
+
+vec3 sampleSubpixelMask(sampler2D maskTexture,
+                        vec2 maskTextureSize,
+                        vec3 maskTexCoord,
+                        int maskCtrl){
+    if(maskCtrl == 0)
+        return vec3(1.0);
+
+    ivec2 maskTexCoordI = ivec2(floor(maskTexCoord . xy));
+    int tileX = maskTexCoordI . x % 16;
+    ivec2 leftTexCoordI = maskTexCoordI - ivec2(tileX > 0 ? 1 : 0, 0);
+    ivec2 rightTexCoordI = maskTexCoordI + ivec2(tileX < 16 - 1 ? 1 : 0, 0);
+
+    float left = fetchMaskCoverage(maskTexture,
+                                   maskTextureSize,
+                                   leftTexCoordI,
+                                   maskTexCoord . z,
+                                   maskCtrl);
+    float center = fetchMaskCoverage(maskTexture,
+                                     maskTextureSize,
+                                     maskTexCoordI,
+                                     maskTexCoord . z,
+                                     maskCtrl);
+    float right = fetchMaskCoverage(maskTexture,
+                                    maskTextureSize,
+                                    rightTexCoordI,
+                                    maskTexCoord . z,
+                                    maskCtrl);
+    vec3 coverage = vec3(mix(center, left, 1.0 / 3.0), center, mix(center, right, 1.0 / 3.0));
+    return min(vec3(1.0), coverage);
+}
+
+
+
+
+
+vec4 ditherColor(vec4 color, vec2 fragCoord){
+    ivec2 position = ivec2(fragCoord)& ivec2(3);
+    int diagonal = position . x ^ position . y;
+    int index =((diagonal & 1)<< 3)|((position . y & 1)<< 2)|(diagonal & 2)|
+        ((position . y >> 1)& 1);
+    float threshold =(float(index)+ 0.5)/ 16.0 - 0.5;
+    return vec4(color . rgb + vec3(threshold * color . a / 255.0), color . a);
+}
+
+
+
 vec4 calculateColor(vec2 fragCoord,
                     sampler2D colorTexture0,
                     sampler2D maskTexture0,
@@ -599,11 +805,19 @@ vec4 calculateColor(vec2 fragCoord,
                     vec3 maskTexCoord0,
                     vec2 colorTexCoord0,
                     vec4 baseColor,
-                    int tileCtrl){
+                    int tileCtrl,
+                    out vec3 channelAlphas){
 
     int maskCtrl0 =(tileCtrl >> 0)& 0x3;
-    float maskAlpha = 1.0;
-    maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
+    bool subpixelCoverage =(tileCtrl & 0x4)!= 0;
+    vec3 maskAlphas;
+    if(subpixelCoverage){
+        maskAlphas = sampleSubpixelMask(maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
+    } else {
+        float maskAlpha = 1.0;
+        maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
+        maskAlphas = vec3(maskAlpha);
+    }
 
 
     vec4 color = baseColor;
@@ -627,14 +841,18 @@ vec4 calculateColor(vec2 fragCoord,
     }
 
 
-    color . a *= maskAlpha;
+    channelAlphas = color . a * maskAlphas;
+    color . a = channelAlphas . g;
 
 
-    int compositeOp =(ctrl >> 10)& 0xf;
-    color = composite(color, destTexture, framebufferSize, fragCoord, compositeOp);
+    if(! subpixelCoverage){
+        int compositeOp =(ctrl >> 10)& 0xf;
+        color = composite(color, destTexture, framebufferSize, fragCoord, compositeOp);
+        channelAlphas = vec3(color . a);
+    }
 
 
-    color . rgb *= color . a;
+    color . rgb *= channelAlphas;
     return color;
 }
 
@@ -709,6 +927,7 @@ uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
 uniform vec2 uFramebufferSize;
 uniform ivec2 uFramebufferTileSize;
+uniform int uDither;
 layout(rgba8)uniform image2D uDestImage;
 
 layout(std430, binding = 0)buffer bTiles {
@@ -796,7 +1015,16 @@ void main(){
 
 
 
+            int compositeOp =(ctrl >> 10)&
+                                           0xf;
+            ctrl &= ~(0xf << 10);
+            if((tileCtrl & 0x4)!= 0)
+                compositeOp = 0x0;
+
 
+
+
+            vec3 channelAlphas;
             vec4 srcColor = calculateColor(fragCoord,
                                            uColorTexture0,
                                            uMaskTexture0,
@@ -814,15 +1042,32 @@ void main(){
                                            maskTexCoord0,
                                            colorTexCoord0,
                                            baseColor,
-                                           tileCtrl);
+                                           tileCtrl,
+                                           channelAlphas);
 
-            destColors[subY]= destColors[subY]*(1.0 - srcColor . a)+ srcColor;
+            if(compositeOp == 0x0){
+
+                destColors[subY]= destColors[subY]*(vec4(1.0)- vec4(channelAlphas, srcColor . a))+
+                    srcColor;
+            } else {
+                vec4 unpremultipliedSrcColor =
+                    srcColor . a > 0.0 ? vec4(srcColor . rgb / srcColor . a, srcColor . a): vec4(0.0);
+                vec4 blendedColor = compositeColors(unpremultipliedSrcColor,
+                                                    destColors[subY],
+                                                    compositeOp);
+                destColors[subY]= vec4(blendedColor . rgb * blendedColor . a, blendedColor . a);
+            }
         }
 
         tileIndex = int(iTiles[tileIndex * 4 + 0]);
     }
 
-    for(int subY = 0;subY < 4;subY ++)
-        imageStore(uDestImage, toImageCoords(firstFragCoord + ivec2(0, subY)), destColors[subY]);
+    for(int subY = 0;subY < 4;subY ++){
+        ivec2 destCoord = firstFragCoord + ivec2(0, subY);
+        vec4 destColor = destColors[subY];
+        if(uDither != 0)
+            destColor = ditherColor(destColor, vec2(destCoord));
+        imageStore(uDestImage, toImageCoords(destCoord), destColor);
+    }
 }
 
@@ -50,14 +50,45 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT){
 }
 
 
+
+
+vec4 computeCoverageSupersample(vec2 from, vec2 to){
+    const vec2 samplePositions[8]= vec2[8](vec2(1.0, - 3.0), vec2(- 1.0, 3.0),
+                                            vec2(5.0, 1.0), vec2(- 3.0, - 5.0),
+                                            vec2(- 5.0, 5.0), vec2(- 7.0, - 1.0),
+                                            vec2(3.0, 7.0), vec2(7.0, - 7.0));
+
+    vec2 left = from . x < to . x ? from : to, right = from . x < to . x ? to : from;
+
+
+    vec4 coverages = vec4(0.0);
+    for(int sampleIndex = 0;sampleIndex < 8;sampleIndex ++){
+        vec2 samplePosition = samplePositions[sampleIndex]/ 16.0;
+        if(samplePosition . x < left . x || samplePosition . x >= right . x)
+            continue;
+        float t =(samplePosition . x - left . x)/(right . x - left . x);
+        float y = mix(left . y, right . y, t);
+        coverages += step(vec4(y), vec4(samplePosition . y)+ vec4(0.0, 1.0, 2.0, 3.0));
+    }
+
+    float winding = from . x < to . x ? - 1.0 : 1.0;
+    return coverages * winding / 8.0;
+}
+
+
 uniform sampler2D uAreaLUT;
 
+uniform int uSupersample;
+
 in vec2 vFrom;
 in vec2 vTo;
 
 out vec4 oFragColor;
 
 void main(){
-    oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    if(uSupersample != 0)
+        oFragColor = computeCoverageSupersample(vFrom, vTo);
+    else
+        oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
 }
 
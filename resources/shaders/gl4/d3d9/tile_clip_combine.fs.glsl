@@ -22,13 +22,23 @@ uniform sampler2D uSrc;
 
 in vec2 vTexCoord0;
 in float vBackdrop0;
+in float vEvenOdd0;
 in vec2 vTexCoord1;
 in float vBackdrop1;
+in float vEvenOdd1;
 
 out vec4 oFragColor;
 
+
+
+vec4 resolveCoverage(vec4 winding, float evenOdd){
+    vec4 nonzeroCoverage = min(abs(winding), vec4(1.0));
+    vec4 evenOddCoverage = vec4(1.0)- abs(vec4(1.0)- mod(winding, 2.0));
+    return mix(nonzeroCoverage, evenOddCoverage, evenOdd);
+}
+
 void main(){
-    oFragColor = min(abs(texture(uSrc, vTexCoord0)+ vBackdrop0),
-                     abs(texture(uSrc, vTexCoord1)+ vBackdrop1));
+    oFragColor = min(resolveCoverage(texture(uSrc, vTexCoord0)+ vBackdrop0, vEvenOdd0),
+                     resolveCoverage(texture(uSrc, vTexCoord1)+ vBackdrop1, vEvenOdd1));
 }
 
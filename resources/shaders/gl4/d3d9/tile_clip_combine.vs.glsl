@@ -18,18 +18,24 @@ precision highp float;
 
 
 
+
+
 uniform vec2 uFramebufferSize;
 
 in ivec2 aTileOffset;
 in int aDestTileIndex;
 in int aDestBackdrop;
+in int aDestCtrl;
 in int aSrcTileIndex;
 in int aSrcBackdrop;
+in int aSrcCtrl;
 
 out vec2 vTexCoord0;
 out float vBackdrop0;
+out float vEvenOdd0;
 out vec2 vTexCoord1;
 out float vBackdrop1;
+out float vEvenOdd1;
 
 void main(){
     vec2 destPosition = vec2(ivec2(aDestTileIndex % 256, aDestTileIndex / 256)+ aTileOffset);
@@ -43,6 +49,9 @@ void main(){
     vBackdrop0 = float(aDestBackdrop);
     vBackdrop1 = float(aSrcBackdrop);
 
+    vEvenOdd0 = aDestCtrl == 0x2 ? 1.0 : 0.0;
+    vEvenOdd1 = aSrcCtrl == 0x2 ? 1.0 : 0.0;
+
     if(aDestTileIndex < 0)
         destPosition = vec2(0.0);
 
@@ -0,0 +1,892 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+vec4 sampleColor(sampler2D colorTexture, vec2 colorTexCoord){
+    return texture(colorTexture, colorTexCoord);
+}
+
+
+
+vec4 combineColor0(vec4 destColor, vec4 srcColor, int op){
+    switch(op){
+    case 0x1 :
+        return vec4(srcColor . rgb, srcColor . a * destColor . a);
+    case 0x2 :
+        return vec4(destColor . rgb, srcColor . a * destColor . a);
+    }
+    return destColor;
+}
+
+
+
+float filterTextSample1Tap(float offset, sampler2D colorTexture, vec2 colorTexCoord){
+    return texture(colorTexture, colorTexCoord + vec2(offset, 0.0)). r;
+}
+
+
+void filterTextSample9Tap(out vec4 outAlphaLeft,
+                          out float outAlphaCenter,
+                          out vec4 outAlphaRight,
+                          sampler2D colorTexture,
+                          vec2 colorTexCoord,
+                          vec4 kernel,
+                          float onePixel){
+    bool wide = kernel . x > 0.0;
+    outAlphaLeft =
+        vec4(wide ? filterTextSample1Tap(- 4.0 * onePixel, colorTexture, colorTexCoord): 0.0,
+             filterTextSample1Tap(- 3.0 * onePixel, colorTexture, colorTexCoord),
+             filterTextSample1Tap(- 2.0 * onePixel, colorTexture, colorTexCoord),
+             filterTextSample1Tap(- 1.0 * onePixel, colorTexture, colorTexCoord));
+    outAlphaCenter = filterTextSample1Tap(0.0, colorTexture, colorTexCoord);
+    outAlphaRight =
+        vec4(filterTextSample1Tap(1.0 * onePixel, colorTexture, colorTexCoord),
+             filterTextSample1Tap(2.0 * onePixel, colorTexture, colorTexCoord),
+             filterTextSample1Tap(3.0 * onePixel, colorTexture, colorTexCoord),
+             wide ? filterTextSample1Tap(4.0 * onePixel, colorTexture, colorTexCoord): 0.0);
+}
+
+float filterTextConvolve7Tap(vec4 alpha0, vec3 alpha1, vec4 kernel){
+    return dot(alpha0, kernel)+ dot(alpha1, kernel . zyx);
+}
+
+float filterTextGammaCorrectChannel(float bgColor, float fgColor, sampler2D gammaLUT){
+    return texture(gammaLUT, vec2(fgColor, 1.0 - bgColor)). r;
+}
+
+
+vec3 filterTextGammaCorrect(vec3 bgColor, vec3 fgColor, sampler2D gammaLUT){
+    return vec3(filterTextGammaCorrectChannel(bgColor . r, fgColor . r, gammaLUT),
+                filterTextGammaCorrectChannel(bgColor . g, fgColor . g, gammaLUT),
+                filterTextGammaCorrectChannel(bgColor . b, fgColor . b, gammaLUT));
+}
+
+
+
+
+
+
+vec4 filterText(vec2 colorTexCoord,
+                sampler2D colorTexture,
+                sampler2D gammaLUT,
+                vec2 colorTextureSize,
+                vec4 filterParams0,
+                vec4 filterParams1,
+                vec4 filterParams2){
+
+    vec4 kernel = filterParams0;
+    vec3 bgColor = filterParams1 . rgb;
+    vec3 fgColor = filterParams2 . rgb;
+    bool gammaCorrectionEnabled = filterParams2 . a != 0.0;
+
+
+    vec3 alpha;
+    if(kernel . w == 0.0){
+        alpha = texture(colorTexture, colorTexCoord). rrr;
+    } else {
+        vec4 alphaLeft, alphaRight;
+        float alphaCenter;
+        filterTextSample9Tap(alphaLeft,
+                             alphaCenter,
+                             alphaRight,
+                             colorTexture,
+                             colorTexCoord,
+                             kernel,
+                             1.0 / colorTextureSize . x);
+
+        float r = filterTextConvolve7Tap(alphaLeft, vec3(alphaCenter, alphaRight . xy), kernel);
+        float g = filterTextConvolve7Tap(vec4(alphaLeft . yzw, alphaCenter), alphaRight . xyz, kernel);
+        float b = filterTextConvolve7Tap(vec4(alphaLeft . zw, alphaCenter, alphaRight . x),
+                                         alphaRight . yzw,
+                                         kernel);
+
+        alpha = vec3(r, g, b);
+    }
+
+
+    if(gammaCorrectionEnabled)
+        alpha = filterTextGammaCorrect(bgColor, alpha, gammaLUT);
+
+
+    return vec4(mix(bgColor, fgColor, alpha), 1.0);
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+vec4 filterRadialGradient(vec2 colorTexCoord,
+                          sampler2D colorTexture,
+                          vec2 colorTextureSize,
+                          vec2 fragCoord,
+                          vec2 framebufferSize,
+                          vec4 filterParams0,
+                          vec4 filterParams1){
+    vec2 lineFrom = filterParams0 . xy, lineVector = filterParams0 . zw;
+    vec2 radii = filterParams1 . xy, uvOrigin = filterParams1 . zw;
+
+    vec2 dP = colorTexCoord - lineFrom, dC = lineVector;
+    float dR = radii . y - radii . x;
+
+    float a = dot(dC, dC)- dR * dR;
+    float b = dot(dP, dC)+ radii . x * dR;
+    float c = dot(dP, dP)- radii . x * radii . x;
+    float discrim = b * b - a * c;
+
+    vec4 color = vec4(0.0);
+    if(discrim != 0.0){
+        vec2 ts = vec2(sqrt(discrim)* vec2(1.0, - 1.0)+ vec2(b))/ vec2(a);
+        if(ts . x > ts . y)
+            ts = ts . yx;
+        float t = ts . x >= 0.0 ? ts . x : ts . y;
+        color = texture(colorTexture, uvOrigin + vec2(t, 0.0));
+    }
+
+    return color;
+}
+
+
+
+
+
+
+vec4 filterBlur(vec2 colorTexCoord,
+                sampler2D colorTexture,
+                vec2 colorTextureSize,
+                vec4 filterParams0,
+                vec4 filterParams1){
+
+    vec2 srcOffsetScale = filterParams0 . xy / colorTextureSize;
+    int support = int(filterParams0 . z);
+    vec3 gaussCoeff = filterParams1 . xyz;
+
+
+    float gaussSum = gaussCoeff . x;
+    vec4 color = texture(colorTexture, colorTexCoord)* gaussCoeff . x;
+    gaussCoeff . xy *= gaussCoeff . yz;
+
+
+
+
+
+
+
+
+
+    for(int i = 1;i <= support;i += 2){
+        float gaussPartialSum = gaussCoeff . x;
+        gaussCoeff . xy *= gaussCoeff . yz;
+        gaussPartialSum += gaussCoeff . x;
+
+        vec2 srcOffset = srcOffsetScale *(float(i)+ gaussCoeff . x / gaussPartialSum);
+        color +=(texture(colorTexture, colorTexCoord - srcOffset)+
+                  texture(colorTexture, colorTexCoord + srcOffset))* gaussPartialSum;
+
+        gaussSum += 2.0 * gaussPartialSum;
+        gaussCoeff . xy *= gaussCoeff . yz;
+    }
+
+
+    return color / gaussSum;
+}
+
+vec4 filterColorMatrix(vec2 colorTexCoord,
+                       sampler2D colorTexture,
+                       vec4 filterParams0,
+                       vec4 filterParams1,
+                       vec4 filterParams2,
+                       vec4 filterParams3,
+                       vec4 filterParams4){
+    vec4 srcColor = texture(colorTexture, colorTexCoord);
+    mat4 colorMatrix = mat4(filterParams0, filterParams1, filterParams2, filterParams3);
+    return colorMatrix * srcColor + filterParams4;
+}
+
+
+
+
+
+
+vec2 filterNoiseGradient(vec2 latticePoint, int channel, int seed){
+    uint hash = uint(int(latticePoint . x))* 0x8da6b343u ^
+        uint(int(latticePoint . y))* 0xd8163841u ^
+        uint(seed * 4 + channel)* 0xcb1ab31fu;
+    hash ^= hash >> 16;
+    hash *= 0x7feb352du;
+    hash ^= hash >> 15;
+    hash *= 0x846ca68bu;
+    hash ^= hash >> 16;
+    float angle = float(hash & 0xffffu)*(6.283185307179586 / 65536.0);
+    return vec2(cos(angle), sin(angle));
+}
+
+
+
+float filterNoisePerlin(vec2 position, vec2 period, int channel, int seed){
+    vec2 lattice0 = floor(position), lattice1 = lattice0 + vec2(1.0);
+    vec2 t = position - lattice0;
+    if(period . x > 0.0){
+        lattice0 . x = mod(lattice0 . x, period . x);
+        lattice1 . x = mod(lattice1 . x, period . x);
+    }
+    if(period . y > 0.0){
+        lattice0 . y = mod(lattice0 . y, period . y);
+        lattice1 . y = mod(lattice1 . y, period . y);
+    }
+
+    float n00 = dot(filterNoiseGradient(lattice0, channel, seed), t);
+    float n10 = dot(filterNoiseGradient(vec2(lattice1 . x, lattice0 . y), channel, seed),
+                    t - vec2(1.0, 0.0));
+    float n01 = dot(filterNoiseGradient(vec2(lattice0 . x, lattice1 . y), channel, seed),
+                    t - vec2(0.0, 1.0));
+    float n11 = dot(filterNoiseGradient(lattice1, channel, seed), t - vec2(1.0));
+
+    vec2 s = t * t *(vec2(3.0)- 2.0 * t);
+    return mix(mix(n00, n10, s . x), mix(n01, n11, s . x), s . y);
+}
+
+
+
+
+
+
+
+
+
+vec4 filterNoise(vec2 colorTexCoord, vec4 filterParams0, vec4 filterParams1, vec4 filterParams2){
+
+    vec2 baseFrequency = filterParams0 . xy;
+    int numOctaves = int(filterParams0 . z), seed = int(filterParams0 . w);
+    vec2 stitchOrigin = filterParams1 . xy, period = filterParams1 . zw;
+    bool fractalNoise = filterParams2 . x != 0.0;
+
+    vec2 position =(colorTexCoord - stitchOrigin)* baseFrequency;
+    vec4 sum = vec4(0.0);
+    float amplitude = 1.0;
+    for(int octave = 0;octave < numOctaves;octave ++){
+        vec4 octaveNoise = vec4(filterNoisePerlin(position, period, 0, seed),
+                                filterNoisePerlin(position, period, 1, seed),
+                                filterNoisePerlin(position, period, 2, seed),
+                                filterNoisePerlin(position, period, 3, seed));
+        sum +=(fractalNoise ? octaveNoise : abs(octaveNoise))* amplitude;
+        position *= 2.0;
+        period *= 2.0;
+        amplitude *= 0.5;
+    }
+
+
+    if(fractalNoise)
+        sum = sum * 0.5 + vec4(0.5);
+    return clamp(sum, vec4(0.0), vec4(1.0));
+}
+
+
+
+
+
+
+
+
+
+float filterPaintEffectLines(float position, float spacing, float lineWidth, float pixelSize){
+    float dist = abs(fract(position / spacing + 0.5)- 0.5)* spacing;
+    return clamp((lineWidth * 0.5 - dist)/ pixelSize + 0.5, 0.0, 1.0);
+}
+
+
+
+
+
+
+
+
+vec4 filterPaintEffect(vec2 colorTexCoord, vec4 filterParams0){
+    int kind = int(filterParams0 . x);
+    float size = filterParams0 . y, amount = filterParams0 . z, pixelSize = filterParams0 . w;
+
+    float alpha;
+    if(kind == 2){
+        vec2 offset =(fract(colorTexCoord / size)- vec2(0.5))* size;
+        float radius = size * sqrt(clamp(amount, 0.0, 1.0)/ 3.14159265);
+        alpha = clamp((radius - length(offset))/ pixelSize + 0.5, 0.0, 1.0);
+    } else {
+        alpha = filterPaintEffectLines(colorTexCoord . y, size, amount, pixelSize);
+        if(kind == 1)
+            alpha = max(alpha, filterPaintEffectLines(colorTexCoord . x, size, amount, pixelSize));
+    }
+    return vec4(vec3(1.0), alpha);
+}
+
+vec4 filterNone(vec2 colorTexCoord, sampler2D colorTexture){
+    return sampleColor(colorTexture, colorTexCoord);
+}
+
+vec4 filterColor(vec2 colorTexCoord,
+                 sampler2D colorTexture,
+                 sampler2D gammaLUT,
+                 vec2 colorTextureSize,
+                 vec2 fragCoord,
+                 vec2 framebufferSize,
+                 vec4 filterParams0,
+                 vec4 filterParams1,
+                 vec4 filterParams2,
+                 vec4 filterParams3,
+                 vec4 filterParams4,
+                 int colorFilter){
+    switch(colorFilter){
+    case 0x1 :
+        return filterRadialGradient(colorTexCoord,
+                                    colorTexture,
+                                    colorTextureSize,
+                                    fragCoord,
+                                    framebufferSize,
+                                    filterParams0,
+                                    filterParams1);
+    case 0x3 :
+        return filterBlur(colorTexCoord,
+                          colorTexture,
+                          colorTextureSize,
+                          filterParams0,
+                          filterParams1);
+    case 0x2 :
+        return filterText(colorTexCoord,
+                          colorTexture,
+                          gammaLUT,
+                          colorTextureSize,
+                          filterParams0,
+                          filterParams1,
+                          filterParams2);
+    case 0x4 :
+        return filterColorMatrix(colorTexCoord,
+                          colorTexture,
+                          filterParams0,
+                          filterParams1,
+                          filterParams2,
+                          filterParams3,
+                          filterParams4);
+    case 0x5 :
+        return filterNoise(colorTexCoord, filterParams0, filterParams1, filterParams2);
+    case 0x6 :
+        return filterPaintEffect(colorTexCoord, filterParams0);
+    }
+    return filterNone(colorTexCoord, colorTexture);
+}
+
+
+
+vec3 compositeSelect(bvec3 cond, vec3 ifTrue, vec3 ifFalse){
+    return vec3(cond . x ? ifTrue . x : ifFalse . x,
+                cond . y ? ifTrue . y : ifFalse . y,
+                cond . z ? ifTrue . z : ifFalse . z);
+}
+
+vec3 compositeColorDodge(vec3 destColor, vec3 srcColor){
+    bvec3 destZero = equal(destColor, vec3(0.0)), srcOne = equal(srcColor, vec3(1.0));
+    return compositeSelect(destZero,
+                           vec3(0.0),
+                           compositeSelect(srcOne,
+                                           vec3(1.0),
+                                           min(destColor /(vec3(1.0)- srcColor), vec3(1.0))));
+}
+
+
+
+
+float compositeLum(vec3 color){
+    return dot(color, vec3(0.3, 0.59, 0.11));
+}
+
+vec3 compositeClipColor(vec3 color){
+    float l = compositeLum(color);
+    float n = min(min(color . r, color . g), color . b), x = max(max(color . r, color . g), color . b);
+    if(n < 0.0)
+        color = vec3(l)+(color - vec3(l))* l /(l - n);
+    if(x > 1.0)
+        color = vec3(l)+(color - vec3(l))*(1.0 - l)/(x - l);
+    return color;
+}
+
+vec3 compositeSetLum(vec3 color, float l){
+    return compositeClipColor(color + vec3(l - compositeLum(color)));
+}
+
+float compositeSat(vec3 color){
+    return max(max(color . r, color . g), color . b)- min(min(color . r, color . g), color . b);
+}
+
+vec3 compositeSetSat(vec3 color, float s){
+    float colorMin = min(min(color . r, color . g), color . b);
+    float colorSat = compositeSat(color);
+    return colorSat > 0.0 ?(color - vec3(colorMin))* s / colorSat : vec3(0.0);
+}
+
+vec3 compositeScreen(vec3 destColor, vec3 srcColor){
+    return destColor + srcColor - destColor * srcColor;
+}
+
+vec3 compositeHardLight(vec3 destColor, vec3 srcColor){
+    return compositeSelect(lessThanEqual(srcColor, vec3(0.5)),
+                           destColor * vec3(2.0)* srcColor,
+                           compositeScreen(destColor, vec3(2.0)* srcColor - vec3(1.0)));
+}
+
+vec3 compositeSoftLight(vec3 destColor, vec3 srcColor){
+    vec3 darkenedDestColor =
+        compositeSelect(lessThanEqual(destColor, vec3(0.25)),
+                        ((vec3(16.0)* destColor - 12.0)* destColor + 4.0)* destColor,
+                        sqrt(destColor));
+    vec3 factor = compositeSelect(lessThanEqual(srcColor, vec3(0.5)),
+                                  destColor *(vec3(1.0)- destColor),
+                                  darkenedDestColor - destColor);
+    return destColor +(srcColor * 2.0 - 1.0)* factor;
+}
+
+vec3 compositeRGB(vec3 destColor, vec3 srcColor, int op){
+    switch(op){
+    case 0x1 :
+        return destColor * srcColor;
+    case 0x2 :
+        return compositeScreen(destColor, srcColor);
+    case 0x3 :
+        return compositeHardLight(srcColor, destColor);
+    case 0x4 :
+        return min(destColor, srcColor);
+    case 0x5 :
+        return max(destColor, srcColor);
+    case 0x6 :
+        return compositeColorDodge(destColor, srcColor);
+    case 0x7 :
+        return vec3(1.0)- compositeColorDodge(vec3(1.0)- destColor, vec3(1.0)- srcColor);
+    case 0x8 :
+        return compositeHardLight(destColor, srcColor);
+    case 0x9 :
+        return compositeSoftLight(destColor, srcColor);
+    case 0xa :
+        return abs(destColor - srcColor);
+    case 0xb :
+        return destColor + srcColor - vec3(2.0)* destColor * srcColor;
+    case 0xc :
+        return compositeSetLum(compositeSetSat(srcColor, compositeSat(destColor)),
+                               compositeLum(destColor));
+    case 0xd :
+        return compositeSetLum(compositeSetSat(destColor, compositeSat(srcColor)),
+                               compositeLum(destColor));
+    case 0xe :
+        return compositeSetLum(srcColor, compositeLum(destColor));
+    case 0xf :
+        return compositeSetLum(destColor, compositeLum(srcColor));
+    }
+    return srcColor;
+}
+
+
+
+vec4 compositeColors(vec4 srcColor, vec4 destColor, int op){
+    vec3 destRGB = destColor . a > 0.0 ? destColor . rgb / destColor . a : vec3(0.0);
+    vec3 blendedRGB = compositeRGB(destRGB, srcColor . rgb, op);
+    vec3 resultRGB = srcColor . a *(1.0 - destColor . a)* srcColor . rgb +
+                     srcColor . a * destColor . a * blendedRGB +
+                     (1.0 - srcColor . a)* destColor . rgb;
+    float resultAlpha = srcColor . a + destColor . a *(1.0 - srcColor . a);
+    return resultAlpha > 0.0 ? vec4(resultRGB / resultAlpha, resultAlpha): vec4(0.0);
+}
+
+vec4 composite(vec4 srcColor,
+               sampler2D destTexture,
+               vec2 destTextureSize,
+               vec2 fragCoord,
+               int op){
+    if(op == 0x0)
+        return srcColor;
+
+    vec2 destTexCoord = fragCoord / destTextureSize;
+    vec4 destColor = texture(destTexture, destTexCoord);
+    return compositeColors(srcColor, destColor, op);
+}
+
+
+
+float fetchMaskCoverage(sampler2D maskTexture,
+                        vec2 maskTextureSize,
+                        ivec2 maskTexCoordI,
+                        float backdrop,
+                        int maskCtrl){
+    vec4 texel = texture(maskTexture,(vec2(maskTexCoordI / ivec2(1, 4))+ 0.5)/ maskTextureSize);
+    float coverage = texel[maskTexCoordI . y % 4]+ backdrop;
+
+    if((maskCtrl & 0x1)!= 0)
+        return abs(coverage);
+    return 1.0 - abs(1.0 - mod(coverage, 2.0));
+}
+
+float sampleMask(float maskAlpha,
+                 sampler2D maskTexture,
+                 vec2 maskTextureSize,
+                 vec3 maskTexCoord,
+                 int maskCtrl){
+    if(maskCtrl == 0)
+        return maskAlpha;
+
+    ivec2 maskTexCoordI = ivec2(floor(maskTexCoord . xy));
+    float coverage = fetchMaskCoverage(maskTexture,
+                                       maskTextureSize,
+                                       maskTexCoordI,
+                                       maskTexCoord . z,
+                                       maskCtrl);
+    return min(maskAlpha, coverage);
+}
+
+
+
+
+vec3 sampleSubpixelMask(sampler2D maskTexture,
+                        vec2 maskTextureSize,
+                        vec3 maskTexCoord,
+                        int maskCtrl){
+    if(maskCtrl == 0)
+        return vec3(1.0);
+
+    ivec2 maskTexCoordI = ivec2(floor(maskTexCoord . xy));
+    int tileX = maskTexCoordI . x % 16;
+    ivec2 leftTexCoordI = maskTexCoordI - ivec2(tileX > 0 ? 1 : 0, 0);
+    ivec2 rightTexCoordI = maskTexCoordI + ivec2(tileX < 16 - 1 ? 1 : 0, 0);
+
+    float left = fetchMaskCoverage(maskTexture,
+                                   maskTextureSize,
+                                   leftTexCoordI,
+                                   maskTexCoord . z,
+                                   maskCtrl);
+    float center = fetchMaskCoverage(maskTexture,
+                                     maskTextureSize,
+                                     maskTexCoordI,
+                                     maskTexCoord . z,
+                                     maskCtrl);
+    float right = fetchMaskCoverage(maskTexture,
+                                    maskTextureSize,
+                                    rightTexCoordI,
+                                    maskTexCoord . z,
+                                    maskCtrl);
+    vec3 coverage = vec3(mix(center, left, 1.0 / 3.0), center, mix(center, right, 1.0 / 3.0));
+    return min(vec3(1.0), coverage);
+}
+
+
+
+
+
+vec4 ditherColor(vec4 color, vec2 fragCoord){
+    ivec2 position = ivec2(fragCoord)& ivec2(3);
+    int diagonal = position . x ^ position . y;
+    int index =((diagonal & 1)<< 3)|((position . y & 1)<< 2)|(diagonal & 2)|
+        ((position . y >> 1)& 1);
+    float threshold =(float(index)+ 0.5)/ 16.0 - 0.5;
+    return vec4(color . rgb + vec3(threshold * color . a / 255.0), color . a);
+}
+
+
+
+vec4 calculateColor(vec2 fragCoord,
+                    sampler2D colorTexture0,
+                    sampler2D maskTexture0,
+                    sampler2D destTexture,
+                    sampler2D gammaLUT,
+                    vec2 colorTextureSize0,
+                    vec2 maskTextureSize0,
+                    vec4 filterParams0,
+                    vec4 filterParams1,
+                    vec4 filterParams2,
+                    vec4 filterParams3,
+                    vec4 filterParams4,
+                    vec2 framebufferSize,
+                    int ctrl,
+                    vec3 maskTexCoord0,
+                    vec2 colorTexCoord0,
+                    vec4 baseColor,
+                    int tileCtrl,
+                    out vec3 channelAlphas){
+
+    int maskCtrl0 =(tileCtrl >> 0)& 0x3;
+    bool subpixelCoverage =(tileCtrl & 0x4)!= 0;
+    vec3 maskAlphas;
+    if(subpixelCoverage){
+        maskAlphas = sampleSubpixelMask(maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
+    } else {
+        float maskAlpha = 1.0;
+        maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
+        maskAlphas = vec3(maskAlpha);
+    }
+
+
+    vec4 color = baseColor;
+    int color0Combine =(ctrl >> 8)&
+                                       0x3;
+    if(color0Combine != 0){
+        int color0Filter =(ctrl >> 4)& 0xf;
+        vec4 color0 = filterColor(colorTexCoord0,
+                                  colorTexture0,
+                                  gammaLUT,
+                                  colorTextureSize0,
+                                  fragCoord,
+                                  framebufferSize,
+                                  filterParams0,
+                                  filterParams1,
+                                  filterParams2,
+                                  filterParams3,
+                                  filterParams4,
+                                  color0Filter);
+        color = combineColor0(color, color0, color0Combine);
+    }
+
+
+    channelAlphas = color . a * maskAlphas;
+    color . a = channelAlphas . g;
+
+
+    if(! subpixelCoverage){
+        int compositeOp =(ctrl >> 10)& 0xf;
+        color = composite(color, destTexture, framebufferSize, fragCoord, compositeOp);
+        channelAlphas = vec3(color . a);
+    }
+
+
+    color . rgb *= channelAlphas;
+    return color;
+}
+
+
+uniform sampler2D uColorTexture0;
+uniform sampler2D uMaskTexture0;
+uniform sampler2D uDestTexture;
+uniform sampler2D uGammaLUT;
+uniform vec2 uColorTextureSize0;
+uniform vec2 uMaskTextureSize0;
+uniform vec2 uFramebufferSize;
+uniform int uDither;
+
+in vec3 vMaskTexCoord0;
+in vec2 vColorTexCoord0;
+in vec4 vBaseColor;
+in float vTileCtrl;
+in vec4 vFilterParams0;
+in vec4 vFilterParams1;
+in vec4 vFilterParams2;
+in vec4 vFilterParams3;
+in vec4 vFilterParams4;
+in float vCtrl;
+
+layout(location = 0, index = 0)out vec4 oFragColor;
+
+layout(location = 0, index = 1)out vec4 oFragBlend;
+
+void main(){
+    vec3 channelAlphas;
+    vec4 color = calculateColor(gl_FragCoord . xy,
+                                uColorTexture0,
+                                uMaskTexture0,
+                                uDestTexture,
+                                uGammaLUT,
+                                uColorTextureSize0,
+                                uMaskTextureSize0,
+                                vFilterParams0,
+                                vFilterParams1,
+                                vFilterParams2,
+                                vFilterParams3,
+                                vFilterParams4,
+                                uFramebufferSize,
+                                int(vCtrl),
+                                vMaskTexCoord0,
+                                vColorTexCoord0,
+                                vBaseColor,
+                                int(vTileCtrl),
+                                channelAlphas);
+    if(uDither != 0)
+        color = ditherColor(color, gl_FragCoord . xy);
+    oFragColor = color;
+    oFragBlend = vec4(channelAlphas, color . a);
+}
+
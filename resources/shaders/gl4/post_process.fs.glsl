@@ -0,0 +1,77 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+
+
+
+
+
+
+
+
+
+
+uniform sampler2D uSrc;
+uniform sampler2D uLUT;
+
+
+
+uniform vec4 uFilterParams0;
+
+uniform vec4 uFilterParams1;
+uniform int uFilterKind;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+vec3 unpremultiply(vec4 color){
+    return color . a == 0.0 ? vec3(0.0): color . rgb / color . a;
+}
+
+
+
+vec3 sampleColorLUT(vec3 color, float size){
+    vec3 scaled = clamp(color, 0.0, 1.0)*(size - 1.0);
+    float sliceLo = floor(scaled . b);
+    float sliceHi = min(sliceLo + 1.0, size - 1.0);
+    vec2 texCoord =(scaled . rg + 0.5)/ vec2(size * size, size);
+    vec3 lo = texture(uLUT, texCoord + vec2(sliceLo / size, 0.0)). rgb;
+    vec3 hi = texture(uLUT, texCoord + vec2(sliceHi / size, 0.0)). rgb;
+    return mix(lo, hi, scaled . b - sliceLo);
+}
+
+void main(){
+    vec4 color = texture(uSrc, vTexCoord);
+
+    if(uFilterKind == 0){
+        color . rgb = sampleColorLUT(unpremultiply(color), uFilterParams0 . x)* color . a;
+    } else if(uFilterKind == 1){
+
+        float dist = length(vTexCoord - 0.5)* sqrt(2.0);
+        float amount = smoothstep(uFilterParams1 . x, uFilterParams1 . x + uFilterParams1 . y, dist);
+        color . rgb = mix(color . rgb, uFilterParams0 . rgb * color . a, amount * uFilterParams0 . a);
+    } else if(uFilterKind == 2){
+        color . rgb = pow(unpremultiply(color), vec3(uFilterParams0 . x))* color . a;
+    } else {
+
+        color . rgb = unpremultiply(color);
+    }
+
+    oFragColor = color;
+}
+
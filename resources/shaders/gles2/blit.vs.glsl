@@ -0,0 +1,30 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+uniform vec4 uDestRect;
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aPosition;
+
+varying vec2 vTexCoord;
+
+void main(){
+    vec2 position = mix(uDestRect . xy, uDestRect . zw, aPosition)/ uFramebufferSize;
+    vTexCoord = aPosition;
+    gl_Position = vec4(mix(vec2(- 1.0), vec2(1.0), position), 0.0, 1.0);
+}
+
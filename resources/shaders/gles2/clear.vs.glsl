@@ -0,0 +1,27 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+uniform vec4 uRect;
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aPosition;
+
+void main(){
+    vec2 position = mix(uRect . xy, uRect . zw, aPosition)/ uFramebufferSize * 2.0 - 1.0;
+    gl_Position = vec4(position . x, - position . y, 0.0, 1.0);
+}
+
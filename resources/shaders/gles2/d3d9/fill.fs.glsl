@@ -0,0 +1,86 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform sampler2D uAreaLUT;
+
+uniform int uSupersample;
+
+varying vec2 vFrom;
+varying vec2 vTo;
+
+vec4 computeCoverage(vec2 from, vec2 to){
+    vec2 left = from . x < to . x ? from : to, right = from . x < to . x ? to : from;
+
+    vec2 window = clamp(vec2(from . x, to . x), - 0.5, 0.5);
+    float offset = mix(window . x, window . y, 0.5)- left . x;
+    float t = offset /(right . x - left . x);
+
+    float y = mix(left . y, right . y, t);
+    float d =(right . y - left . y)/(right . x - left . x);
+
+    float dX = window . x - window . y;
+    return texture2D(uAreaLUT, vec2(y + 8.0, abs(d * dX))/ 16.0)* dX;
+}
+
+
+vec2 computeSamplePosition(int sampleIndex){
+    if(sampleIndex == 0)
+        return vec2(1.0, - 3.0);
+    if(sampleIndex == 1)
+        return vec2(- 1.0, 3.0);
+    if(sampleIndex == 2)
+        return vec2(5.0, 1.0);
+    if(sampleIndex == 3)
+        return vec2(- 3.0, - 5.0);
+    if(sampleIndex == 4)
+        return vec2(- 5.0, 5.0);
+    if(sampleIndex == 5)
+        return vec2(- 7.0, - 1.0);
+    if(sampleIndex == 6)
+        return vec2(3.0, 7.0);
+    return vec2(7.0, - 7.0);
+}
+
+vec4 computeCoverageSupersample(vec2 from, vec2 to){
+    vec2 left = from . x < to . x ? from : to, right = from . x < to . x ? to : from;
+
+    vec4 coverages = vec4(0.0);
+    for(int sampleIndex = 0;sampleIndex < 8;sampleIndex ++){
+        vec2 samplePosition = computeSamplePosition(sampleIndex)/ 16.0;
+        if(samplePosition . x < left . x || samplePosition . x >= right . x)
+            continue;
+        float t =(samplePosition . x - left . x)/(right . x - left . x);
+        float y = mix(left . y, right . y, t);
+        coverages += step(vec4(y), vec4(samplePosition . y)+ vec4(0.0, 1.0, 2.0, 3.0));
+    }
+
+    float winding = from . x < to . x ? - 1.0 : 1.0;
+    return coverages * winding / 8.0;
+}
+
+void main(){
+    if(uSupersample != 0)
+        gl_FragColor = computeCoverageSupersample(vFrom, vTo);
+    else
+        gl_FragColor = computeCoverage(vFrom, vTo);
+}
+
@@ -0,0 +1,87 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+float decodeInt32(vec2 halves){
+    float low = halves . x < 0.0 ? halves . x + 65536.0 : halves . x;
+    return halves . y * 65536.0 + low;
+}
+
+
+vec2 computeTileCoord(float tileIndex){
+    float row = floor((tileIndex + 0.5)/ 256.0);
+    return vec2(tileIndex - row * 256.0, row);
+}
+
+
+uniform vec2 uFramebufferSize;
+uniform vec2 uTileSize;
+
+attribute vec2 aTessCoord;
+attribute vec4 aLineSegment;
+attribute vec2 aTileIndex;
+
+varying vec2 vFrom;
+varying vec2 vTo;
+
+vec2 computeTileOffset(float tileIndex, float stencilTextureWidth, vec2 tileSize){
+    float tilesPerRow = floor(stencilTextureWidth / tileSize . x);
+    float row = floor((tileIndex + 0.5)/ tilesPerRow);
+    vec2 tileOffset = vec2(tileIndex - row * tilesPerRow, row);
+    return tileOffset * tileSize * vec2(1.0, 0.25);
+}
+
+void main(){
+    vec2 tileOrigin = computeTileOffset(decodeInt32(aTileIndex), uFramebufferSize . x, uTileSize);
+
+    vec4 lineSegment = aLineSegment / 256.0;
+    vec2 from = lineSegment . xy, to = lineSegment . zw;
+
+    vec2 position;
+    if(aTessCoord . x == 0.0)
+        position . x = floor(min(from . x, to . x));
+    else
+        position . x = ceil(max(from . x, to . x));
+    if(aTessCoord . y == 0.0)
+        position . y = floor(min(from . y, to . y));
+    else
+        position . y = uTileSize . y;
+    position . y = floor(position . y * 0.25);
+
+
+    vec2 offset = vec2(0.0, 1.5)- position * vec2(1.0, 4.0);
+    vFrom = from + offset;
+    vTo = to + offset;
+
+    vec2 globalPosition =(tileOrigin + position)/ uFramebufferSize * 2.0 - 1.0;
+    gl_Position = vec4(globalPosition, 0.0, 1.0);
+}
+
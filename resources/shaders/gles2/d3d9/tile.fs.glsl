@@ -0,0 +1,251 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+uniform sampler2D uColorTexture0;
+uniform sampler2D uMaskTexture0;
+uniform sampler2D uDestTexture;
+uniform vec2 uColorTextureSize0;
+uniform vec2 uMaskTextureSize0;
+uniform vec2 uFramebufferSize;
+
+varying vec3 vMaskTexCoord0;
+varying vec2 vColorTexCoord0;
+varying vec4 vBaseColor;
+varying float vTileCtrl;
+varying vec4 vFilterParams0;
+varying vec4 vFilterParams1;
+varying float vCtrl;
+varying float vCompositeOp;
+
+vec4 filterRadialGradient(vec2 colorTexCoord, vec4 filterParams0, vec4 filterParams1){
+    vec2 lineFrom = filterParams0 . xy, lineVector = filterParams0 . zw;
+    vec2 radii = filterParams1 . xy, uvOrigin = filterParams1 . zw;
+
+    vec2 dP = colorTexCoord - lineFrom, dC = lineVector;
+    float dR = radii . y - radii . x;
+
+    float a = dot(dC, dC)- dR * dR;
+    float b = dot(dP, dC)+ radii . x * dR;
+    float c = dot(dP, dP)- radii . x * radii . x;
+    float discrim = b * b - a * c;
+
+    vec4 color = vec4(0.0);
+    if(discrim != 0.0){
+        vec2 ts = vec2(sqrt(discrim)* vec2(1.0, - 1.0)+ vec2(b))/ vec2(a);
+        if(ts . x > ts . y)
+            ts = ts . yx;
+        float t = ts . x >= 0.0 ? ts . x : ts . y;
+        color = texture2D(uColorTexture0, uvOrigin + vec2(t, 0.0));
+    }
+
+    return color;
+}
+
+
+
+vec3 compositeSelect(bvec3 cond, vec3 ifTrue, vec3 ifFalse){
+    return vec3(cond . x ? ifTrue . x : ifFalse . x,
+                cond . y ? ifTrue . y : ifFalse . y,
+                cond . z ? ifTrue . z : ifFalse . z);
+}
+
+vec3 compositeColorDodge(vec3 destColor, vec3 srcColor){
+    bvec3 destZero = equal(destColor, vec3(0.0)), srcOne = equal(srcColor, vec3(1.0));
+    return compositeSelect(destZero,
+                           vec3(0.0),
+                           compositeSelect(srcOne,
+                                           vec3(1.0),
+                                           min(destColor /(vec3(1.0)- srcColor), vec3(1.0))));
+}
+
+float compositeLum(vec3 color){
+    return dot(color, vec3(0.3, 0.59, 0.11));
+}
+
+vec3 compositeClipColor(vec3 color){
+    float l = compositeLum(color);
+    float n = min(min(color . r, color . g), color . b), x = max(max(color . r, color . g), color . b);
+    if(n < 0.0)
+        color = vec3(l)+(color - vec3(l))* l /(l - n);
+    if(x > 1.0)
+        color = vec3(l)+(color - vec3(l))*(1.0 - l)/(x - l);
+    return color;
+}
+
+vec3 compositeSetLum(vec3 color, float l){
+    return compositeClipColor(color + vec3(l - compositeLum(color)));
+}
+
+float compositeSat(vec3 color){
+    return max(max(color . r, color . g), color . b)- min(min(color . r, color . g), color . b);
+}
+
+vec3 compositeSetSat(vec3 color, float s){
+    float colorMin = min(min(color . r, color . g), color . b);
+    float colorSat = compositeSat(color);
+    return colorSat > 0.0 ?(color - vec3(colorMin))* s / colorSat : vec3(0.0);
+}
+
+vec3 compositeScreen(vec3 destColor, vec3 srcColor){
+    return destColor + srcColor - destColor * srcColor;
+}
+
+vec3 compositeHardLight(vec3 destColor, vec3 srcColor){
+    return compositeSelect(lessThanEqual(srcColor, vec3(0.5)),
+                           destColor * vec3(2.0)* srcColor,
+                           compositeScreen(destColor, vec3(2.0)* srcColor - vec3(1.0)));
+}
+
+vec3 compositeSoftLight(vec3 destColor, vec3 srcColor){
+    vec3 darkenedDestColor =
+        compositeSelect(lessThanEqual(destColor, vec3(0.25)),
+                        ((vec3(16.0)* destColor - 12.0)* destColor + 4.0)* destColor,
+                        sqrt(destColor));
+    vec3 factor = compositeSelect(lessThanEqual(srcColor, vec3(0.5)),
+                                  destColor *(vec3(1.0)- destColor),
+                                  darkenedDestColor - destColor);
+    return destColor +(srcColor * 2.0 - 1.0)* factor;
+}
+
+
+vec3 compositeRGB(vec3 destColor, vec3 srcColor, float op){
+    if(op == 1.0)
+        return destColor * srcColor;
+    if(op == 2.0)
+        return compositeScreen(destColor, srcColor);
+    if(op == 3.0)
+        return compositeHardLight(srcColor, destColor);
+    if(op == 4.0)
+        return min(destColor, srcColor);
+    if(op == 5.0)
+        return max(destColor, srcColor);
+    if(op == 6.0)
+        return compositeColorDodge(destColor, srcColor);
+    if(op == 7.0)
+        return vec3(1.0)- compositeColorDodge(vec3(1.0)- destColor, vec3(1.0)- srcColor);
+    if(op == 8.0)
+        return compositeHardLight(destColor, srcColor);
+    if(op == 9.0)
+        return compositeSoftLight(destColor, srcColor);
+    if(op == 10.0)
+        return abs(destColor - srcColor);
+    if(op == 11.0)
+        return destColor + srcColor - vec3(2.0)* destColor * srcColor;
+    if(op == 12.0)
+        return compositeSetLum(compositeSetSat(srcColor, compositeSat(destColor)),
+                               compositeLum(destColor));
+    if(op == 13.0)
+        return compositeSetLum(compositeSetSat(destColor, compositeSat(srcColor)),
+                               compositeLum(destColor));
+    if(op == 14.0)
+        return compositeSetLum(srcColor, compositeLum(destColor));
+    if(op == 15.0)
+        return compositeSetLum(destColor, compositeLum(srcColor));
+    return srcColor;
+}
+
+
+
+vec4 composite(vec4 srcColor, float op){
+    if(op == 0.0)
+        return srcColor;
+
+    vec4 destColor = texture2D(uDestTexture, gl_FragCoord . xy / uFramebufferSize);
+    vec3 destRGB = destColor . a > 0.0 ? destColor . rgb / destColor . a : vec3(0.0);
+    vec3 blendedRGB = compositeRGB(destRGB, srcColor . rgb, op);
+    vec3 resultRGB = srcColor . a *(1.0 - destColor . a)* srcColor . rgb +
+                     srcColor . a * destColor . a * blendedRGB +
+                     (1.0 - srcColor . a)* destColor . rgb;
+    float resultAlpha = srcColor . a + destColor . a *(1.0 - srcColor . a);
+    return resultAlpha > 0.0 ? vec4(resultRGB / resultAlpha, resultAlpha): vec4(0.0);
+}
+
+
+float sampleMask(vec3 maskTexCoord, float maskCtrl){
+    if(maskCtrl == 0.0)
+        return 1.0;
+
+    vec2 maskTexelCoord = floor(maskTexCoord . xy);
+    vec2 texCoord = vec2(maskTexelCoord . x, floor(maskTexelCoord . y * 0.25))+ 0.5;
+    vec4 texel = texture2D(uMaskTexture0, texCoord / uMaskTextureSize0);
+    float row = maskTexelCoord . y - floor(maskTexelCoord . y * 0.25)* 4.0;
+    float coverage = dot(texel, vec4(equal(vec4(row), vec4(0.0, 1.0, 2.0, 3.0))))+
+        maskTexCoord . z;
+
+    if(mod(maskCtrl, 2.0)== 1.0)
+        return min(1.0, abs(coverage));
+    return min(1.0, 1.0 - abs(1.0 - mod(coverage, 2.0)));
+}
+
+void main(){
+    float maskAlpha = sampleMask(vMaskTexCoord0, floor(vTileCtrl + 0.5));
+
+    float ctrl = floor(vCtrl + 0.5);
+    vec4 color = vBaseColor;
+    float color0Combine = mod(floor(ctrl / 256.0), 4.0);
+    if(color0Combine != 0.0){
+        float color0Filter = mod(floor(ctrl / 16.0), 16.0);
+        vec4 color0;
+        if(color0Filter == 1.0)
+            color0 = filterRadialGradient(vColorTexCoord0, vFilterParams0, vFilterParams1);
+        else
+            color0 = texture2D(uColorTexture0, vColorTexCoord0);
+
+        if(color0Combine == 1.0)
+            color = vec4(color0 . rgb, color0 . a * color . a);
+        else if(color0Combine == 2.0)
+            color = vec4(color . rgb, color0 . a * color . a);
+    }
+
+    color . a *= maskAlpha;
+    color = composite(color, floor(vCompositeOp + 0.5));
+    color . rgb *= color . a;
+    gl_FragColor = color;
+}
+
@@ -0,0 +1,44 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform sampler2D uSrc;
+
+varying vec2 vTexCoord0;
+varying float vBackdrop0;
+varying float vEvenOdd0;
+varying vec2 vTexCoord1;
+varying float vBackdrop1;
+varying float vEvenOdd1;
+
+
+
+vec4 resolveCoverage(vec4 winding, float evenOdd){
+    vec4 nonzeroCoverage = min(abs(winding), vec4(1.0));
+    vec4 evenOddCoverage = vec4(1.0)- abs(vec4(1.0)- mod(winding, 2.0));
+    return mix(nonzeroCoverage, evenOddCoverage, evenOdd);
+}
+
+void main(){
+    gl_FragColor = min(resolveCoverage(texture2D(uSrc, vTexCoord0)+ vBackdrop0, vEvenOdd0),
+                       resolveCoverage(texture2D(uSrc, vTexCoord1)+ vBackdrop1, vEvenOdd1));
+}
+
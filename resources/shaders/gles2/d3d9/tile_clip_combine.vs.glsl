@@ -0,0 +1,86 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+float decodeInt32(vec2 halves){
+    float low = halves . x < 0.0 ? halves . x + 65536.0 : halves . x;
+    return halves . y * 65536.0 + low;
+}
+
+
+vec2 computeTileCoord(float tileIndex){
+    float row = floor((tileIndex + 0.5)/ 256.0);
+    return vec2(tileIndex - row * 256.0, row);
+}
+
+
+
+
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aTileOffset;
+attribute vec2 aDestTileIndex;
+attribute vec2 aDestBackdrop;
+attribute vec2 aDestCtrl;
+attribute vec2 aSrcTileIndex;
+attribute vec2 aSrcBackdrop;
+attribute vec2 aSrcCtrl;
+
+varying vec2 vTexCoord0;
+varying float vBackdrop0;
+varying float vEvenOdd0;
+varying vec2 vTexCoord1;
+varying float vBackdrop1;
+varying float vEvenOdd1;
+
+void main(){
+    float destTileIndex = decodeInt32(aDestTileIndex);
+    float srcTileIndex = decodeInt32(aSrcTileIndex);
+    vec2 destPosition = computeTileCoord(destTileIndex)+ aTileOffset;
+    vec2 srcPosition = computeTileCoord(srcTileIndex)+ aTileOffset;
+    destPosition *= vec2(16.0, 4.0)/ uFramebufferSize;
+    srcPosition *= vec2(16.0, 4.0)/ uFramebufferSize;
+
+    vTexCoord0 = destPosition;
+    vTexCoord1 = srcPosition;
+
+    vBackdrop0 = decodeInt32(aDestBackdrop);
+    vBackdrop1 = decodeInt32(aSrcBackdrop);
+
+    vEvenOdd0 = decodeInt32(aDestCtrl)== 2.0 ? 1.0 : 0.0;
+    vEvenOdd1 = decodeInt32(aSrcCtrl)== 2.0 ? 1.0 : 0.0;
+
+    if(destTileIndex < 0.0)
+        destPosition = vec2(0.0);
+
+    gl_Position = vec4(mix(vec2(- 1.0), vec2(1.0), destPosition), 0.0, 1.0);
+}
+
@@ -0,0 +1,30 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform sampler2D uSrc;
+
+varying vec2 vTexCoord;
+
+void main(){
+    gl_FragColor = texture2D(uSrc, vTexCoord);
+}
+
@@ -0,0 +1,64 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+float decodeInt32(vec2 halves){
+    float low = halves . x < 0.0 ? halves . x + 65536.0 : halves . x;
+    return halves . y * 65536.0 + low;
+}
+
+
+vec2 computeTileCoord(float tileIndex){
+    float row = floor((tileIndex + 0.5)/ 256.0);
+    return vec2(tileIndex - row * 256.0, row);
+}
+
+
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aTileOffset;
+attribute vec2 aTileIndex;
+
+varying vec2 vTexCoord;
+
+void main(){
+    float tileIndex = decodeInt32(aTileIndex);
+    vec2 position = computeTileCoord(tileIndex)+ aTileOffset;
+    position *= vec2(16.0, 4.0)/ uFramebufferSize;
+
+    vTexCoord = position;
+
+    if(tileIndex < 0.0)
+        position = vec2(0.0);
+
+    gl_Position = vec4(mix(vec2(- 1.0), vec2(1.0), position), 0.0, 1.0);
+}
+
@@ -0,0 +1,30 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform vec2 uFramebufferSize;
+uniform sampler2D uSrc;
+
+void main(){
+    vec2 texCoord = gl_FragCoord . xy / uFramebufferSize;
+    gl_FragColor = texture2D(uSrc, texCoord);
+}
+
@@ -0,0 +1,27 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+uniform mat4 uTransform;
+uniform vec2 uTileSize;
+
+attribute vec2 aTilePosition;
+
+void main(){
+    vec2 position = aTilePosition * uTileSize;
+    gl_Position = uTransform * vec4(position, 0.0, 1.0);
+}
+
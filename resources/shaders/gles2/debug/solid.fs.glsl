@@ -0,0 +1,28 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform vec4 uColor;
+
+void main(){
+    gl_FragColor = vec4(uColor . rgb, 1.0)* uColor . a;
+}
+
@@ -0,0 +1,32 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform sampler2D uTexture;
+uniform vec4 uColor;
+
+varying vec2 vTexCoord;
+
+void main(){
+    float alpha = texture2D(uTexture, vTexCoord). r * uColor . a;
+    gl_FragColor = alpha * vec4(uColor . rgb, 1.0);
+}
+
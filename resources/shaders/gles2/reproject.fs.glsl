@@ -0,0 +1,33 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+
+
+
+
+uniform mat4 uOldTransform;
+uniform sampler2D uTexture;
+
+varying vec2 vTexCoord;
+
+void main(){
+    vec4 normTexCoord = uOldTransform * vec4(vTexCoord, 0.0, 1.0);
+    vec2 texCoord =((normTexCoord . xy / normTexCoord . w)+ 1.0)* 0.5;
+    gl_FragColor = texture2D(uTexture, texCoord);
+}
+
@@ -0,0 +1,28 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+uniform mat4 uNewTransform;
+
+attribute vec2 aPosition;
+
+varying vec2 vTexCoord;
+
+void main(){
+    vTexCoord = aPosition;
+    gl_Position = uNewTransform * vec4(aPosition, 0.0, 1.0);
+}
+
@@ -0,0 +1,23 @@
+#version {{version}}
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+
+
+
+
+
+
+
+
+
+
+
+
+precision highp float;
+precision highp sampler2D;
+
+attribute vec3 aPosition;
+
+void main(){
+    gl_Position = vec4(aPosition, 1.0);
+}
+
@@ -0,0 +1,31 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+Texture2D<float4> uSrc : register(t0);
+SamplerState _uSrc_sampler : register(s0);
+
+static float2 vTexCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    float4 color = uSrc.Sample(_uSrc_sampler, vTexCoord);
+    oFragColor = color;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
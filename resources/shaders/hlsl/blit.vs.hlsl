@@ -0,0 +1,40 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float4 _13_uDestRect : packoffset(c0);
+    float2 _13_uFramebufferSize : packoffset(c1);
+};
+
+
+static float4 gl_Position;
+static int2 aPosition;
+static float2 vTexCoord;
+
+struct SPIRV_Cross_Input
+{
+    int2 aPosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord : TEXCOORD0;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 position = lerp(_13_uDestRect.xy, _13_uDestRect.zw, float2(aPosition)) / _13_uFramebufferSize;
+    float2 texCoord = float2(aPosition);
+    vTexCoord = texCoord;
+    gl_Position = float4(lerp((-1.0f).xx, 1.0f.xx, position), 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord = vTexCoord;
+    return stage_output;
+}
@@ -0,0 +1,26 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float4 _12_uColor : packoffset(c0);
+};
+
+
+static float4 oFragColor;
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    oFragColor = float4(_12_uColor.xyz, 1.0f) * _12_uColor.w;
+}
+
+SPIRV_Cross_Output main()
+{
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
@@ -0,0 +1,268 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(64u, 1u, 1u);
+
+RWByteAddressBuffer _155 : register(u2);
+RWByteAddressBuffer _165 : register(u4);
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b6)
+{
+    int _178_uMicrolineCount : packoffset(c0);
+    int _178_uMaxFillCount : packoffset(c0.y);
+};
+
+RWByteAddressBuffer _189 : register(u3);
+RWByteAddressBuffer _254 : register(u5);
+ByteAddressBuffer _349 : register(t0);
+ByteAddressBuffer _363 : register(t1);
+
+static uint3 gl_GlobalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_GlobalInvocationID : SV_DispatchThreadID;
+};
+
+float4 unpackMicroline(uint4 packedMicroline, out uint outPathIndex)
+{
+    outPathIndex = packedMicroline.w;
+    int4 signedMicroline = int4(packedMicroline);
+    return float4(float((signedMicroline.x << 16) >> 16), float(signedMicroline.x >> 16), float((signedMicroline.y << 16) >> 16), float(signedMicroline.y >> 16)) + (float4(float(signedMicroline.z & 255), float((signedMicroline.z >> 8) & 255), float((signedMicroline.z >> 16) & 255), float((signedMicroline.z >> 24) & 255)) / 256.0f.xxxx);
+}
+
+uint computeTileIndexNoCheck(int2 tileCoords, int4 pathTileRect, uint pathTileOffset)
+{
+    int2 offsetCoords = tileCoords - pathTileRect.xy;
+    return (pathTileOffset + uint(offsetCoords.x)) + uint(offsetCoords.y * (pathTileRect.z - pathTileRect.x));
+}
+
+bool4 computeTileOutcodes(int2 tileCoords, int4 pathTileRect)
+{
+    return bool4(bool2(tileCoords.x < pathTileRect.xy.x, tileCoords.y < pathTileRect.xy.y), bool2(tileCoords.x >= pathTileRect.zw.x, tileCoords.y >= pathTileRect.zw.y));
+}
+
+bool computeTileIndex(int2 tileCoords, int4 pathTileRect, uint pathTileOffset, out uint outTileIndex)
+{
+    int2 param = tileCoords;
+    int4 param_1 = pathTileRect;
+    uint param_2 = pathTileOffset;
+    outTileIndex = computeTileIndexNoCheck(param, param_1, param_2);
+    int2 param_3 = tileCoords;
+    int4 param_4 = pathTileRect;
+    return !any(computeTileOutcodes(param_3, param_4));
+}
+
+void addFill(float4 lineSegment, int2 tileCoords, int4 pathTileRect, uint pathTileOffset)
+{
+    int2 param = tileCoords;
+    int4 param_1 = pathTileRect;
+    uint param_2 = pathTileOffset;
+    uint param_3;
+    bool _124 = computeTileIndex(param, param_1, param_2, param_3);
+    uint tileIndex = param_3;
+    if (!_124)
+    {
+        return;
+    }
+    uint4 scaledLocalLine = uint4((lineSegment - float4(tileCoords.xyxy * int4(16, 16, 16, 16))) * 256.0f.xxxx);
+    if (scaledLocalLine.x == scaledLocalLine.z)
+    {
+        return;
+    }
+    uint _160;
+    _155.InterlockedAdd(4, 1u, _160);
+    uint fillIndex = _160;
+    uint _174;
+    _165.InterlockedExchange(((tileIndex * 4u) + 1u) * 4 + 0, uint(int(fillIndex)), _174);
+    uint fillLink = _174;
+    if (fillIndex < uint(_178_uMaxFillCount))
+    {
+        _189.Store(((fillIndex * 3u) + 0u) * 4 + 0, scaledLocalLine.x | (scaledLocalLine.y << uint(16)));
+        _189.Store(((fillIndex * 3u) + 1u) * 4 + 0, scaledLocalLine.z | (scaledLocalLine.w << uint(16)));
+        _189.Store(((fillIndex * 3u) + 2u) * 4 + 0, fillLink);
+    }
+}
+
+void adjustBackdrop(int backdropDelta, int2 tileCoords, int4 pathTileRect, uint pathTileOffset, uint pathBackdropOffset)
+{
+    int2 param = tileCoords;
+    int4 param_1 = pathTileRect;
+    bool4 outcodes = computeTileOutcodes(param, param_1);
+    if (any(outcodes))
+    {
+        bool _233 = (!outcodes.x) && outcodes.y;
+        bool _239;
+        if (_233)
+        {
+            _239 = !outcodes.z;
+        }
+        else
+        {
+            _239 = _233;
+        }
+        if (_239)
+        {
+            uint backdropIndex = pathBackdropOffset + uint(tileCoords.x - pathTileRect.x);
+            uint _260;
+            _254.InterlockedAdd((backdropIndex * 3u) * 4 + 0, uint(backdropDelta), _260);
+        }
+    }
+    else
+    {
+        int2 param_2 = tileCoords;
+        int4 param_3 = pathTileRect;
+        uint param_4 = pathTileOffset;
+        uint tileIndex = computeTileIndexNoCheck(param_2, param_3, param_4);
+        uint _278;
+        _165.InterlockedAdd(((tileIndex * 4u) + 2u) * 4 + 0, uint(backdropDelta) << uint(24), _278);
+    }
+}
+
+void comp_main()
+{
+    uint segmentIndex = gl_GlobalInvocationID.x;
+    if (segmentIndex >= uint(_178_uMicrolineCount))
+    {
+        return;
+    }
+    uint4 param = _349.Load4(segmentIndex * 16 + 0);
+    uint param_1;
+    float4 _357 = unpackMicroline(param, param_1);
+    uint pathIndex = param_1;
+    float4 lineSegment = _357;
+    int4 pathTileRect = int4(_363.Load4(((pathIndex * 3u) + 0u) * 16 + 0));
+    uint pathTileOffset = uint(int(_363.Load(((pathIndex * 3u) + 1u) * 16 + 0)));
+    uint pathBackdropOffset = uint(int(_363.Load(((pathIndex * 3u) + 2u) * 16 + 0)));
+    int2 tileSize = int2(16, 16);
+    int4 tileLineSegment = int4(floor(lineSegment / float4(tileSize.xyxy)));
+    int2 fromTileCoords = tileLineSegment.xy;
+    int2 toTileCoords = tileLineSegment.zw;
+    float2 _vector = lineSegment.zw - lineSegment.xy;
+    float2 vectorIsNegative = float2((_vector.x < 0.0f) ? (-1.0f) : 0.0f, (_vector.y < 0.0f) ? (-1.0f) : 0.0f);
+    int2 tileStep = int2((_vector.x < 0.0f) ? (-1) : 1, (_vector.y < 0.0f) ? (-1) : 1);
+    float2 firstTileCrossing = float2((fromTileCoords + int2(int(_vector.x >= 0.0f), int(_vector.y >= 0.0f))) * tileSize);
+    float2 tMax = (firstTileCrossing - lineSegment.xy) / _vector;
+    float2 tDelta = abs(float2(tileSize) / _vector);
+    float2 currentPosition = lineSegment.xy;
+    int2 tileCoords = fromTileCoords;
+    int lastStepDirection = 0;
+    uint iteration = 0u;
+    int nextStepDirection;
+    float _503;
+    float4 auxiliarySegment;
+    while (iteration < 1024u)
+    {
+        if (tMax.x < tMax.y)
+        {
+            nextStepDirection = 1;
+        }
+        else
+        {
+            if (tMax.x > tMax.y)
+            {
+                nextStepDirection = 2;
+            }
+            else
+            {
+                if (float(tileStep.x) > 0.0f)
+                {
+                    nextStepDirection = 1;
+                }
+                else
+                {
+                    nextStepDirection = 2;
+                }
+            }
+        }
+        if (nextStepDirection == 1)
+        {
+            _503 = tMax.x;
+        }
+        else
+        {
+            _503 = tMax.y;
+        }
+        float nextT = min(_503, 1.0f);
+        if (all(bool2(tileCoords.x == toTileCoords.x, tileCoords.y == toTileCoords.y)))
+        {
+            nextStepDirection = 0;
+        }
+        float2 nextPosition = lerp(lineSegment.xy, lineSegment.zw, nextT.xx);
+        float4 clippedLineSegment = float4(currentPosition, nextPosition);
+        float4 param_2 = clippedLineSegment;
+        int2 param_3 = tileCoords;
+        int4 param_4 = pathTileRect;
+        uint param_5 = pathTileOffset;
+        addFill(param_2, param_3, param_4, param_5);
+        bool haveAuxiliarySegment = false;
+        if ((tileStep.y < 0) && (nextStepDirection == 2))
+        {
+            auxiliarySegment = float4(clippedLineSegment.zw, float2(tileCoords * tileSize));
+            haveAuxiliarySegment = true;
+        }
+        else
+        {
+            if ((tileStep.y > 0) && (lastStepDirection == 2))
+            {
+                auxiliarySegment = float4(float2(tileCoords * tileSize), clippedLineSegment.xy);
+                haveAuxiliarySegment = true;
+            }
+        }
+        if (haveAuxiliarySegment)
+        {
+            float4 param_6 = auxiliarySegment;
+            int2 param_7 = tileCoords;
+            int4 param_8 = pathTileRect;
+            uint param_9 = pathTileOffset;
+            addFill(param_6, param_7, param_8, param_9);
+        }
+        if ((tileStep.x < 0) && (lastStepDirection == 1))
+        {
+            int param_10 = 1;
+            int2 param_11 = tileCoords;
+            int4 param_12 = pathTileRect;
+            uint param_13 = pathTileOffset;
+            uint param_14 = pathBackdropOffset;
+            adjustBackdrop(param_10, param_11, param_12, param_13, param_14);
+        }
+        else
+        {
+            if ((tileStep.x > 0) && (nextStepDirection == 1))
+            {
+                int param_15 = -1;
+                int2 param_16 = tileCoords;
+                int4 param_17 = pathTileRect;
+                uint param_18 = pathTileOffset;
+                uint param_19 = pathBackdropOffset;
+                adjustBackdrop(param_15, param_16, param_17, param_18, param_19);
+            }
+        }
+        if (nextStepDirection == 1)
+        {
+            tMax.x += tDelta.x;
+            tileCoords.x += tileStep.x;
+        }
+        else
+        {
+            if (nextStepDirection == 2)
+            {
+                tMax.y += tDelta.y;
+                tileCoords.y += tileStep.y;
+            }
+            else
+            {
+                if (nextStepDirection == 0)
+                {
+                    break;
+                }
+            }
+        }
+        currentPosition = nextPosition;
+        lastStepDirection = nextStepDirection;
+        iteration++;
+    }
+}
+
+[numthreads(64, 1, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_GlobalInvocationID = stage_input.gl_GlobalInvocationID;
+    comp_main();
+}
@@ -0,0 +1,83 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(64u, 1u, 1u);
+
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b2)
+{
+    int _20_uPathCount : packoffset(c0);
+    int _20_uTileCount : packoffset(c0.y);
+};
+
+ByteAddressBuffer _68 : register(t0);
+RWByteAddressBuffer _151 : register(u1);
+
+static uint3 gl_GlobalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_GlobalInvocationID : SV_DispatchThreadID;
+};
+
+void comp_main()
+{
+    uint tileIndex = gl_GlobalInvocationID.x;
+    if (tileIndex >= uint(_20_uTileCount))
+    {
+        return;
+    }
+    uint lowPathIndex = 0u;
+    uint highPathIndex = uint(_20_uPathCount);
+    int iteration = 0;
+    for (;;)
+    {
+        bool _46 = iteration < 1024;
+        bool _54;
+        if (_46)
+        {
+            _54 = (lowPathIndex + 1u) < highPathIndex;
+        }
+        else
+        {
+            _54 = _46;
+        }
+        if (_54)
+        {
+            uint midPathIndex = lowPathIndex + ((highPathIndex - lowPathIndex) / 2u);
+            uint midTileIndex = _68.Load(midPathIndex * 16 + 8);
+            if (tileIndex < midTileIndex)
+            {
+                highPathIndex = midPathIndex;
+            }
+            else
+            {
+                lowPathIndex = midPathIndex;
+                if (tileIndex == midTileIndex)
+                {
+                    break;
+                }
+            }
+            iteration++;
+            continue;
+        }
+        else
+        {
+            break;
+        }
+    }
+    uint pathIndex = lowPathIndex;
+    uint4 pathInfo = _68.Load4(pathIndex * 16 + 0);
+    int2 packedTileRect = int2(pathInfo.xy);
+    int4 tileRect = int4((packedTileRect.x << 16) >> 16, packedTileRect.x >> 16, (packedTileRect.y << 16) >> 16, packedTileRect.y >> 16);
+    uint tileOffset = tileIndex - pathInfo.z;
+    uint tileWidth = uint(tileRect.z - tileRect.x);
+    int2 tileCoords = tileRect.xy + int2(int(tileOffset % tileWidth), int(tileOffset / tileWidth));
+    _151.Store(((tileIndex * 4u) + 0u) * 4 + 0, 4294967295u);
+    _151.Store(((tileIndex * 4u) + 1u) * 4 + 0, 4294967295u);
+    _151.Store(((tileIndex * 4u) + 2u) * 4 + 0, 16777215u);
+    _151.Store(((tileIndex * 4u) + 3u) * 4 + 0, pathInfo.w);
+}
+
+[numthreads(64, 1, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_GlobalInvocationID = stage_input.gl_GlobalInvocationID;
+    comp_main();
+}
@@ -0,0 +1,195 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(64u, 1u, 1u);
+
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b5)
+{
+    row_major float2x2 _39_uTransform : packoffset(c0);
+    float2 _39_uTranslation : packoffset(c2);
+    int _39_uPathCount : packoffset(c2.z);
+    int _39_uLastBatchSegmentIndex : packoffset(c2.w);
+    int _39_uMaxMicrolineCount : packoffset(c3);
+};
+
+RWByteAddressBuffer _81 : register(u4);
+ByteAddressBuffer _198 : register(t2);
+ByteAddressBuffer _259 : register(t1);
+ByteAddressBuffer _305 : register(t3);
+RWByteAddressBuffer _444 : register(u0);
+
+static uint3 gl_GlobalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_GlobalInvocationID : SV_DispatchThreadID;
+};
+
+float2 getPoint(uint pointIndex)
+{
+    return mul(asfloat(_198.Load2(pointIndex * 8 + 0)), _39_uTransform) + _39_uTranslation;
+}
+
+float2 sampleCurve(float4 baseline, float4 ctrl, float t)
+{
+    float2 p0 = baseline.xy;
+    float2 p1 = ctrl.xy;
+    float2 p2 = ctrl.zw;
+    float2 p3 = baseline.zw;
+    float2 p0p1 = lerp(p0, p1, t.xx);
+    float2 p1p2 = lerp(p1, p2, t.xx);
+    float2 p2p3 = lerp(p2, p3, t.xx);
+    float2 p0p1p2 = lerp(p0p1, p1p2, t.xx);
+    float2 p1p2p3 = lerp(p1p2, p2p3, t.xx);
+    return lerp(p0p1p2, p1p2p3, t.xx);
+}
+
+float2 sampleLine(float4 _line, float t)
+{
+    return lerp(_line.xy, _line.zw, t.xx);
+}
+
+void emitMicroline(float4 microlineSegment, uint pathIndex, uint outputMicrolineIndex)
+{
+    if (outputMicrolineIndex >= uint(_39_uMaxMicrolineCount))
+    {
+        return;
+    }
+    int4 microlineSubpixels = int4(round(clamp(microlineSegment, (-32768.0f).xxxx, 32767.0f.xxxx) * 256.0f));
+    int4 microlinePixels = int4(floor(float4(microlineSubpixels) / 256.0f.xxxx));
+    int4 microlineFractPixels = microlineSubpixels - (microlinePixels * int4(256, 256, 256, 256));
+    _81.Store4(outputMicrolineIndex * 16 + 0, uint4((uint(microlinePixels.x) & 65535u) | (uint(microlinePixels.y) << uint(16)), (uint(microlinePixels.z) & 65535u) | (uint(microlinePixels.w) << uint(16)), ((uint(microlineFractPixels.x) | (uint(microlineFractPixels.y) << uint(8))) | (uint(microlineFractPixels.z) << uint(16))) | (uint(microlineFractPixels.w) << uint(24)), pathIndex));
+}
+
+void comp_main()
+{
+    uint batchSegmentIndex = gl_GlobalInvocationID.x;
+    if (batchSegmentIndex >= uint(_39_uLastBatchSegmentIndex))
+    {
+        return;
+    }
+    uint lowPathIndex = 0u;
+    uint highPathIndex = uint(_39_uPathCount);
+    int iteration = 0;
+    for (;;)
+    {
+        bool _240 = iteration < 1024;
+        bool _247;
+        if (_240)
+        {
+            _247 = (lowPathIndex + 1u) < highPathIndex;
+        }
+        else
+        {
+            _247 = _240;
+        }
+        if (_247)
+        {
+            uint midPathIndex = lowPathIndex + ((highPathIndex - lowPathIndex) / 2u);
+            uint midBatchSegmentIndex = _259.Load(midPathIndex * 16 + 8);
+            if (batchSegmentIndex < midBatchSegmentIndex)
+            {
+                highPathIndex = midPathIndex;
+            }
+            else
+            {
+                lowPathIndex = midPathIndex;
+                if (batchSegmentIndex == midBatchSegmentIndex)
+                {
+                    break;
+                }
+            }
+            iteration++;
+            continue;
+        }
+        else
+        {
+            break;
+        }
+    }
+    uint batchPathIndex = lowPathIndex;
+    uint4 diceMetadata = _259.Load4(batchPathIndex * 16 + 0);
+    uint firstGlobalSegmentIndexInPath = diceMetadata.y;
+    uint firstBatchSegmentIndexInPath = diceMetadata.z;
+    uint globalSegmentIndex = (batchSegmentIndex - firstBatchSegmentIndexInPath) + firstGlobalSegmentIndexInPath;
+    uint2 inputIndices = _305.Load2(globalSegmentIndex * 8 + 0);
+    uint fromPointIndex = inputIndices.x;
+    uint flagsPathIndex = inputIndices.y;
+    uint toPointIndex = fromPointIndex;
+    if ((flagsPathIndex & 1073741824u) != 0u)
+    {
+        toPointIndex += 3u;
+    }
+    else
+    {
+        if ((flagsPathIndex & 2147483648u) != 0u)
+        {
+            toPointIndex += 2u;
+        }
+        else
+        {
+            toPointIndex++;
+        }
+    }
+    uint param = fromPointIndex;
+    uint param_1 = toPointIndex;
+    float4 baseline = float4(getPoint(param), getPoint(param_1));
+    float4 ctrl = 0.0f.xxxx;
+    bool isCurve = (flagsPathIndex & 3221225472u) != 0u;
+    float segmentCountF;
+    if (isCurve)
+    {
+        uint param_2 = fromPointIndex + 1u;
+        float2 ctrl0 = getPoint(param_2);
+        if ((flagsPathIndex & 2147483648u) != 0u)
+        {
+            float2 ctrl0_2 = ctrl0 * 2.0f.xx;
+            ctrl = (baseline + (ctrl0 * 2.0f.xx).xyxy) * 0.3333333432674407958984375f.xxxx;
+        }
+        else
+        {
+            uint param_3 = fromPointIndex + 2u;
+            ctrl = float4(ctrl0, getPoint(param_3));
+        }
+        float2 bound = 6.0f.xx * max(abs((ctrl.zw - (ctrl.xy * 2.0f)) + baseline.xy), abs((baseline.zw - (ctrl.zw * 2.0f)) + ctrl.xy));
+        segmentCountF = sqrt(length(bound) / 2.0f);
+    }
+    else
+    {
+        segmentCountF = length(baseline.zw - baseline.xy) / 16.0f;
+    }
+    int segmentCount = max(int(ceil(segmentCountF)), 1);
+    uint _448;
+    _444.InterlockedAdd(12, uint(segmentCount), _448);
+    uint firstOutputMicrolineIndex = _448;
+    float prevT = 0.0f;
+    float2 prevPoint = baseline.xy;
+    float2 nextPoint;
+    for (int segmentIndex = 0; segmentIndex < segmentCount; segmentIndex++)
+    {
+        float nextT = float(segmentIndex + 1) / float(segmentCount);
+        if (isCurve)
+        {
+            float4 param_4 = baseline;
+            float4 param_5 = ctrl;
+            float param_6 = nextT;
+            nextPoint = sampleCurve(param_4, param_5, param_6);
+        }
+        else
+        {
+            float4 param_7 = baseline;
+            float param_8 = nextT;
+            nextPoint = sampleLine(param_7, param_8);
+        }
+        float4 param_9 = float4(prevPoint, nextPoint);
+        uint param_10 = batchPathIndex;
+        uint param_11 = firstOutputMicrolineIndex + uint(segmentIndex);
+        emitMicroline(param_9, param_10, param_11);
+        prevT = nextT;
+        prevPoint = nextPoint;
+    }
+}
+
+[numthreads(64, 1, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_GlobalInvocationID = stage_input.gl_GlobalInvocationID;
+    comp_main();
+}
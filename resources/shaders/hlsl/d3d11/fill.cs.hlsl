@@ -0,0 +1,148 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(16u, 4u, 1u);
+
+static const float2 _187[8] = { float2(1.0f, -3.0f), float2(-1.0f, 3.0f), float2(5.0f, 1.0f), float2(-3.0f, -5.0f), float2(-5.0f, 5.0f), float2(-7.0f, -1.0f), float2(3.0f, 7.0f), float2(7.0f, -7.0f) };
+
+ByteAddressBuffer _274 : register(t0);
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b5)
+{
+    int _313_uSupersample : packoffset(c0);
+    int2 _313_uAlphaTileRange : packoffset(c0.z);
+};
+
+ByteAddressBuffer _424 : register(t2);
+RWByteAddressBuffer _434 : register(u1);
+Texture2D<float4> uAreaLUT : register(t4);
+SamplerState _uAreaLUT_sampler : register(s4);
+RWTexture2D<unorm float4> uDest : register(u3);
+
+static uint3 gl_WorkGroupID;
+static uint3 gl_LocalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_WorkGroupID : SV_GroupID;
+    uint3 gl_LocalInvocationID : SV_GroupThreadID;
+};
+
+float4 computeCoverageSupersample(float2 from, float2 to)
+{
+    bool2 _143 = (from.x < to.x).xx;
+    float2 left = float2(_143.x ? from.x : to.x, _143.y ? from.y : to.y);
+    bool2 _153 = (from.x < to.x).xx;
+    float2 right = float2(_153.x ? to.x : from.x, _153.y ? to.y : from.y);
+    float4 coverages = 0.0f.xxxx;
+    for (int sampleIndex = 0; sampleIndex < 8; sampleIndex++)
+    {
+        float2 samplePosition = _187[sampleIndex] / 16.0f.xx;
+        bool _199 = samplePosition.x < left.x;
+        bool _208;
+        if (!_199)
+        {
+            _208 = samplePosition.x >= right.x;
+        }
+        else
+        {
+            _208 = _199;
+        }
+        if (_208)
+        {
+            continue;
+        }
+        float t = (samplePosition.x - left.x) / (right.x - left.x);
+        float y = lerp(left.y, right.y, t);
+        coverages += step(y.xxxx, samplePosition.y.xxxx + float4(0.0f, 1.0f, 2.0f, 3.0f));
+    }
+    float winding = (from.x < to.x) ? (-1.0f) : 1.0f;
+    return (coverages * winding) / 8.0f.xxxx;
+}
+
+float4 computeCoverage(float2 from, float2 to, Texture2D<float4> areaLUT, SamplerState _areaLUT_sampler)
+{
+    bool2 _51 = (from.x < to.x).xx;
+    float2 left = float2(_51.x ? from.x : to.x, _51.y ? from.y : to.y);
+    bool2 _61 = (from.x < to.x).xx;
+    float2 right = float2(_61.x ? to.x : from.x, _61.y ? to.y : from.y);
+    float2 window = clamp(float2(from.x, to.x), (-0.5f).xx, 0.5f.xx);
+    float offset = lerp(window.x, window.y, 0.5f) - left.x;
+    float t = offset / (right.x - left.x);
+    float y = lerp(left.y, right.y, t);
+    float d = (right.y - left.y) / (right.x - left.x);
+    float dX = window.x - window.y;
+    return areaLUT.SampleLevel(_areaLUT_sampler, float2(y + 8.0f, abs(d * dX)) / 16.0f.xx, 0.0f) * dX;
+}
+
+float4 accumulateCoverageForFillList(inout int fillIndex, int2 tileSubCoord)
+{
+    float2 tileFragCoord = float2(tileSubCoord) + 0.5f.xx;
+    float4 coverages = 0.0f.xxxx;
+    int iteration = 0;
+    do
+    {
+        uint fillFrom = _274.Load(((fillIndex * 3) + 0) * 4 + 0);
+        uint fillTo = _274.Load(((fillIndex * 3) + 1) * 4 + 0);
+        float4 lineSegment = float4(float(fillFrom & 65535u), float(fillFrom >> uint(16)), float(fillTo & 65535u), float(fillTo >> uint(16))) / 256.0f.xxxx;
+        lineSegment -= tileFragCoord.xyxy;
+        if (_313_uSupersample != 0)
+        {
+            float2 param = lineSegment.xy;
+            float2 param_1 = lineSegment.zw;
+            coverages += computeCoverageSupersample(param, param_1);
+        }
+        else
+        {
+            float2 param_2 = lineSegment.xy;
+            float2 param_3 = lineSegment.zw;
+            coverages += computeCoverage(param_2, param_3, uAreaLUT, _uAreaLUT_sampler);
+        }
+        fillIndex = int(_274.Load(((fillIndex * 3) + 2) * 4 + 0));
+        iteration++;
+    } while ((fillIndex >= 0) && (iteration < 1024));
+    return coverages;
+}
+
+int2 computeTileCoord(uint alphaTileIndex)
+{
+    uint x = alphaTileIndex & 255u;
+    uint y = (alphaTileIndex >> 8u) & (255u + (((alphaTileIndex >> 16u) & 255u) << 8u));
+    return (int2(16, 4) * int2(int(x), int(y))) + int2(gl_LocalInvocationID.xy);
+}
+
+void comp_main()
+{
+    int2 tileSubCoord = int2(gl_LocalInvocationID.xy) * int2(1, 4);
+    uint batchAlphaTileIndex = gl_WorkGroupID.x | (gl_WorkGroupID.y << uint(15));
+    uint alphaTileIndex = batchAlphaTileIndex + uint(_313_uAlphaTileRange.x);
+    if (alphaTileIndex >= uint(_313_uAlphaTileRange.y))
+    {
+        return;
+    }
+    uint tileIndex = _424.Load(((batchAlphaTileIndex * 2u) + 0u) * 4 + 0);
+    if ((int(_434.Load(((tileIndex * 4u) + 2u) * 4 + 0) << uint(8)) >> 8) < 0)
+    {
+        return;
+    }
+    int fillIndex = int(_434.Load(((tileIndex * 4u) + 1u) * 4 + 0));
+    int backdrop = int(_434.Load(((tileIndex * 4u) + 3u) * 4 + 0)) >> 24;
+    float4 coverages = float(backdrop).xxxx;
+    int param = fillIndex;
+    int2 param_1 = tileSubCoord;
+    float4 _473 = accumulateCoverageForFillList(param, param_1);
+    coverages += _473;
+    coverages = clamp(abs(coverages), 0.0f.xxxx, 1.0f.xxxx);
+    int clipTileIndex = int(_424.Load(((batchAlphaTileIndex * 2u) + 1u) * 4 + 0));
+    if (clipTileIndex >= 0)
+    {
+        uint param_2 = uint(clipTileIndex);
+        coverages = min(coverages, uDest[computeTileCoord(param_2)]);
+    }
+    uint param_3 = alphaTileIndex;
+    uDest[computeTileCoord(param_3)] = coverages;
+}
+
+[numthreads(16, 4, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_WorkGroupID = stage_input.gl_WorkGroupID;
+    gl_LocalInvocationID = stage_input.gl_LocalInvocationID;
+    comp_main();
+}
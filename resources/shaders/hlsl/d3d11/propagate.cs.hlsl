@@ -0,0 +1,156 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(64u, 1u, 1u);
+
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b8)
+{
+    int2 _49_uFramebufferTileSize : packoffset(c0);
+    int _49_uColumnCount : packoffset(c0.z);
+    int _49_uFirstAlphaTileIndex : packoffset(c0.w);
+};
+
+ByteAddressBuffer _64 : register(t2);
+ByteAddressBuffer _89 : register(t0);
+ByteAddressBuffer _130 : register(t1);
+RWByteAddressBuffer _178 : register(u3);
+RWByteAddressBuffer _255 : register(u4);
+RWByteAddressBuffer _305 : register(u5);
+RWByteAddressBuffer _313 : register(u7);
+RWByteAddressBuffer _395 : register(u6);
+
+static uint3 gl_GlobalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_GlobalInvocationID : SV_DispatchThreadID;
+};
+
+uint calculateTileIndex(uint bufferOffset, uint4 tileRect, uint2 tileCoord)
+{
+    return (bufferOffset + (tileCoord.y * (tileRect.z - tileRect.x))) + tileCoord.x;
+}
+
+void comp_main()
+{
+    uint columnIndex = gl_GlobalInvocationID.x;
+    if (int(columnIndex) >= _49_uColumnCount)
+    {
+        return;
+    }
+    int currentBackdrop = int(_64.Load(((columnIndex * 3u) + 0u) * 4 + 0));
+    int tileX = int(_64.Load(((columnIndex * 3u) + 1u) * 4 + 0));
+    uint drawPathIndex = uint(int(_64.Load(((columnIndex * 3u) + 2u) * 4 + 0)));
+    uint4 drawTileRect = _89.Load4(((drawPathIndex * 3u) + 0u) * 16 + 0);
+    uint4 drawOffsets = _89.Load4(((drawPathIndex * 3u) + 1u) * 16 + 0);
+    uint2 drawTileSize = drawTileRect.zw - drawTileRect.xy;
+    uint drawTileBufferOffset = drawOffsets.x;
+    bool zWrite = drawOffsets.z != 0u;
+    int clipPathIndex = int(drawOffsets.w);
+    uint4 clipTileRect = uint4(0u, 0u, 0u, 0u);
+    uint4 clipOffsets = uint4(0u, 0u, 0u, 0u);
+    if (clipPathIndex >= 0)
+    {
+        clipTileRect = _130.Load4(((clipPathIndex * 2) + 0) * 16 + 0);
+        clipOffsets = _130.Load4(((clipPathIndex * 2) + 1) * 16 + 0);
+    }
+    uint clipTileBufferOffset = clipOffsets.x;
+    uint clipBackdropOffset = clipOffsets.y;
+    for (uint tileY = 0u; tileY < drawTileSize.y; tileY++)
+    {
+        uint2 drawTileCoord = uint2(uint(tileX), tileY);
+        uint param = drawTileBufferOffset;
+        uint4 param_1 = drawTileRect;
+        uint2 param_2 = drawTileCoord;
+        uint drawTileIndex = calculateTileIndex(param, param_1, param_2);
+        int drawAlphaTileIndex = -1;
+        int clipAlphaTileIndex = -1;
+        int drawFirstFillIndex = int(_178.Load(((drawTileIndex * 4u) + 1u) * 4 + 0));
+        int drawBackdropDelta = int(_178.Load(((drawTileIndex * 4u) + 2u) * 4 + 0)) >> 24;
+        uint drawTileWord = _178.Load(((drawTileIndex * 4u) + 3u) * 4 + 0) & 16777215u;
+        int drawTileBackdrop = currentBackdrop;
+        bool haveDrawAlphaMask = drawFirstFillIndex >= 0;
+        bool needNewAlphaTile = haveDrawAlphaMask;
+        if (clipPathIndex >= 0)
+        {
+            uint2 tileCoord = drawTileCoord + drawTileRect.xy;
+            if (all(bool4(bool2(tileCoord.x >= clipTileRect.xy.x, tileCoord.y >= clipTileRect.xy.y), bool2(tileCoord.x < clipTileRect.zw.x, tileCoord.y < clipTileRect.zw.y))))
+            {
+                uint2 clipTileCoord = tileCoord - clipTileRect.xy;
+                uint param_3 = clipTileBufferOffset;
+                uint4 param_4 = clipTileRect;
+                uint2 param_5 = clipTileCoord;
+                uint clipTileIndex = calculateTileIndex(param_3, param_4, param_5);
+                int thisClipAlphaTileIndex = int(_255.Load(((clipTileIndex * 4u) + 2u) * 4 + 0) << uint(8)) >> 8;
+                uint clipTileWord = _255.Load(((clipTileIndex * 4u) + 3u) * 4 + 0);
+                int clipTileBackdrop = int(clipTileWord) >> 24;
+                if (thisClipAlphaTileIndex >= 0)
+                {
+                    if (haveDrawAlphaMask)
+                    {
+                        clipAlphaTileIndex = thisClipAlphaTileIndex;
+                        needNewAlphaTile = true;
+                    }
+                    else
+                    {
+                        if (drawTileBackdrop != 0)
+                        {
+                            drawAlphaTileIndex = thisClipAlphaTileIndex;
+                            clipAlphaTileIndex = -1;
+                            needNewAlphaTile = false;
+                        }
+                        else
+                        {
+                            drawAlphaTileIndex = -1;
+                            clipAlphaTileIndex = -1;
+                            needNewAlphaTile = false;
+                        }
+                    }
+                }
+                else
+                {
+                    if (clipTileBackdrop == 0)
+                    {
+                        drawTileBackdrop = 0;
+                        needNewAlphaTile = false;
+                    }
+                }
+            }
+            else
+            {
+                drawTileBackdrop = 0;
+                needNewAlphaTile = false;
+            }
+        }
+        if (needNewAlphaTile)
+        {
+            int _308;
+            _305.InterlockedAdd(16, 1, _308);
+            uint drawBatchAlphaTileIndex = uint(int(_308));
+            _313.Store(((drawBatchAlphaTileIndex * 2u) + 0u) * 4 + 0, drawTileIndex);
+            _313.Store(((drawBatchAlphaTileIndex * 2u) + 1u) * 4 + 0, uint(clipAlphaTileIndex));
+            drawAlphaTileIndex = int(drawBatchAlphaTileIndex) + _49_uFirstAlphaTileIndex;
+        }
+        _178.Store(((drawTileIndex * 4u) + 2u) * 4 + 0, (uint(drawAlphaTileIndex) & 16777215u) | (uint(drawBackdropDelta) << uint(24)));
+        _178.Store(((drawTileIndex * 4u) + 3u) * 4 + 0, drawTileWord | (uint(drawTileBackdrop) << uint(24)));
+        int2 tileCoord_1 = int2(tileX, int(tileY)) + int2(drawTileRect.xy);
+        int tileMapIndex = (tileCoord_1.y * _49_uFramebufferTileSize.x) + tileCoord_1.x;
+        if ((zWrite && (drawTileBackdrop != 0)) && (drawAlphaTileIndex < 0))
+        {
+            int _383;
+            _305.InterlockedMax((tileMapIndex + 8) * 4 + 0, int(drawTileIndex), _383);
+        }
+        if ((drawTileBackdrop != 0) || (drawAlphaTileIndex >= 0))
+        {
+            int _400;
+            _395.InterlockedExchange(tileMapIndex * 4 + 0, int(drawTileIndex), _400);
+            int nextTileIndex = int(_400);
+            _178.Store(((drawTileIndex * 4u) + 0u) * 4 + 0, uint(nextTileIndex));
+        }
+        currentBackdrop += drawBackdropDelta;
+    }
+}
+
+[numthreads(64, 1, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_GlobalInvocationID = stage_input.gl_GlobalInvocationID;
+    comp_main();
+}
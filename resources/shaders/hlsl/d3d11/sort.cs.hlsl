@@ -0,0 +1,90 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const uint3 gl_WorkGroupSize = uint3(64u, 1u, 1u);
+
+RWByteAddressBuffer _26 : register(u1);
+RWByteAddressBuffer _37 : register(u0);
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b3)
+{
+    int _65_uTileCount : packoffset(c0);
+};
+
+ByteAddressBuffer _78 : register(t2);
+
+static uint3 gl_GlobalInvocationID;
+struct SPIRV_Cross_Input
+{
+    uint3 gl_GlobalInvocationID : SV_DispatchThreadID;
+};
+
+int getFirst(uint globalTileIndex)
+{
+    return int(_26.Load(globalTileIndex * 4 + 0));
+}
+
+int getNextTile(int tileIndex)
+{
+    return int(_37.Load(((tileIndex * 4) + 0) * 4 + 0));
+}
+
+void setNextTile(int tileIndex, int newNextTileIndex)
+{
+    _37.Store(((tileIndex * 4) + 0) * 4 + 0, uint(newNextTileIndex));
+}
+
+void comp_main()
+{
+    uint globalTileIndex = gl_GlobalInvocationID.x;
+    if (globalTileIndex >= uint(_65_uTileCount))
+    {
+        return;
+    }
+    int zValue = int(_78.Load((8u + globalTileIndex) * 4 + 0));
+    uint param = globalTileIndex;
+    int unsortedFirstTileIndex = getFirst(param);
+    int sortedFirstTileIndex = -1;
+    while (unsortedFirstTileIndex >= 0)
+    {
+        int currentTileIndex = unsortedFirstTileIndex;
+        int param_1 = currentTileIndex;
+        unsortedFirstTileIndex = getNextTile(param_1);
+        if (currentTileIndex >= zValue)
+        {
+            int prevTrialTileIndex = -1;
+            int trialTileIndex = sortedFirstTileIndex;
+            while (true)
+            {
+                if ((trialTileIndex < 0) || (currentTileIndex < trialTileIndex))
+                {
+                    if (prevTrialTileIndex < 0)
+                    {
+                        int param_2 = currentTileIndex;
+                        int param_3 = sortedFirstTileIndex;
+                        setNextTile(param_2, param_3);
+                        sortedFirstTileIndex = currentTileIndex;
+                    }
+                    else
+                    {
+                        int param_4 = currentTileIndex;
+                        int param_5 = trialTileIndex;
+                        setNextTile(param_4, param_5);
+                        int param_6 = prevTrialTileIndex;
+                        int param_7 = currentTileIndex;
+                        setNextTile(param_6, param_7);
+                    }
+                    break;
+                }
+                prevTrialTileIndex = trialTileIndex;
+                int param_8 = trialTileIndex;
+                trialTileIndex = getNextTile(param_8);
+            }
+        }
+    }
+    _26.Store(globalTileIndex * 4 + 0, uint(sortedFirstTileIndex));
+}
+
+[numthreads(64, 1, 1)]
+void main(SPIRV_Cross_Input stage_input)
+{
+    gl_GlobalInvocationID = stage_input.gl_GlobalInvocationID;
+    comp_main();
+}
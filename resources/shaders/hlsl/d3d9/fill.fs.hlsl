@@ -0,0 +1,98 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static const float2 _175[8] = { float2(1.0f, -3.0f), float2(-1.0f, 3.0f), float2(5.0f, 1.0f), float2(-3.0f, -5.0f), float2(-5.0f, 5.0f), float2(-7.0f, -1.0f), float2(3.0f, 7.0f), float2(7.0f, -7.0f) };
+
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b1)
+{
+    int _249_uSupersample : packoffset(c0);
+};
+
+Texture2D<float4> uAreaLUT : register(t0);
+SamplerState _uAreaLUT_sampler : register(s0);
+
+static float4 oFragColor;
+static float2 vFrom;
+static float2 vTo;
+
+struct SPIRV_Cross_Input
+{
+    float2 vFrom : TEXCOORD0;
+    float2 vTo : TEXCOORD1;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+float4 computeCoverageSupersample(float2 from, float2 to)
+{
+    bool2 _128 = (from.x < to.x).xx;
+    float2 left = float2(_128.x ? from.x : to.x, _128.y ? from.y : to.y);
+    bool2 _138 = (from.x < to.x).xx;
+    float2 right = float2(_138.x ? to.x : from.x, _138.y ? to.y : from.y);
+    float4 coverages = 0.0f.xxxx;
+    for (int sampleIndex = 0; sampleIndex < 8; sampleIndex++)
+    {
+        float2 samplePosition = _175[sampleIndex] / 16.0f.xx;
+        bool _187 = samplePosition.x < left.x;
+        bool _196;
+        if (!_187)
+        {
+            _196 = samplePosition.x >= right.x;
+        }
+        else
+        {
+            _196 = _187;
+        }
+        if (_196)
+        {
+            continue;
+        }
+        float t = (samplePosition.x - left.x) / (right.x - left.x);
+        float y = lerp(left.y, right.y, t);
+        coverages += step(y.xxxx, samplePosition.y.xxxx + float4(0.0f, 1.0f, 2.0f, 3.0f));
+    }
+    float winding = (from.x < to.x) ? (-1.0f) : 1.0f;
+    return (coverages * winding) / 8.0f.xxxx;
+}
+
+float4 computeCoverage(float2 from, float2 to, Texture2D<float4> areaLUT, SamplerState _areaLUT_sampler)
+{
+    bool2 _37 = (from.x < to.x).xx;
+    float2 left = float2(_37.x ? from.x : to.x, _37.y ? from.y : to.y);
+    bool2 _47 = (from.x < to.x).xx;
+    float2 right = float2(_47.x ? to.x : from.x, _47.y ? to.y : from.y);
+    float2 window = clamp(float2(from.x, to.x), (-0.5f).xx, 0.5f.xx);
+    float offset = lerp(window.x, window.y, 0.5f) - left.x;
+    float t = offset / (right.x - left.x);
+    float y = lerp(left.y, right.y, t);
+    float d = (right.y - left.y) / (right.x - left.x);
+    float dX = window.x - window.y;
+    return areaLUT.Sample(_areaLUT_sampler, float2(y + 8.0f, abs(d * dX)) / 16.0f.xx) * dX;
+}
+
+void frag_main()
+{
+    if (_249_uSupersample != 0)
+    {
+        float2 param = vFrom;
+        float2 param_1 = vTo;
+        oFragColor = computeCoverageSupersample(param, param_1);
+    }
+    else
+    {
+        float2 param_2 = vFrom;
+        float2 param_3 = vTo;
+        oFragColor = computeCoverage(param_2, param_3, uAreaLUT, _uAreaLUT_sampler);
+    }
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vFrom = stage_input.vFrom;
+    vTo = stage_input.vTo;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
@@ -0,0 +1,98 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _175_uFramebufferSize : packoffset(c0);
+    float2 _175_uTileSize : packoffset(c0.z);
+};
+
+
+static float4 gl_Position;
+static int aTileIndex;
+static uint2 aTessCoord;
+static uint4 aLineSegment;
+static float2 vFrom;
+static float2 vTo;
+
+struct SPIRV_Cross_Input
+{
+    uint2 aTessCoord : TEXCOORD0;
+    uint4 aLineSegment : TEXCOORD1;
+    int aTileIndex : TEXCOORD2;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vFrom : TEXCOORD0;
+    float2 vTo : TEXCOORD1;
+    float4 gl_Position : SV_Position;
+};
+
+float2 computeTileOffset(uint tileIndex, float stencilTextureWidth, float2 tileSize)
+{
+    uint tilesPerRow = uint(stencilTextureWidth / tileSize.x);
+    uint2 tileOffset = uint2(tileIndex % tilesPerRow, tileIndex / tilesPerRow);
+    return (float2(tileOffset) * tileSize) * float2(1.0f, 0.25f);
+}
+
+float4 computeVertexPosition(uint tileIndex, uint2 tessCoord, uint4 packedLineSegment, float2 tileSize, float2 framebufferSize, out float2 outFrom, out float2 outTo)
+{
+    uint param = tileIndex;
+    float param_1 = framebufferSize.x;
+    float2 param_2 = tileSize;
+    float2 tileOrigin = computeTileOffset(param, param_1, param_2);
+    float4 lineSegment = float4(packedLineSegment) / 256.0f.xxxx;
+    float2 from = lineSegment.xy;
+    float2 to = lineSegment.zw;
+    float2 position;
+    if (tessCoord.x == 0u)
+    {
+        position.x = floor(min(from.x, to.x));
+    }
+    else
+    {
+        position.x = ceil(max(from.x, to.x));
+    }
+    if (tessCoord.y == 0u)
+    {
+        position.y = floor(min(from.y, to.y));
+    }
+    else
+    {
+        position.y = tileSize.y;
+    }
+    position.y = floor(position.y * 0.25f);
+    float2 offset = float2(0.0f, 1.5f) - (position * float2(1.0f, 4.0f));
+    outFrom = from + offset;
+    outTo = to + offset;
+    float2 globalPosition = (((tileOrigin + position) / framebufferSize) * 2.0f) - 1.0f.xx;
+    globalPosition.y = -globalPosition.y;
+    return float4(globalPosition, 0.0f, 1.0f);
+}
+
+void vert_main()
+{
+    uint param = uint(aTileIndex);
+    uint2 param_1 = aTessCoord;
+    uint4 param_2 = aLineSegment;
+    float2 param_3 = _175_uTileSize;
+    float2 param_4 = _175_uFramebufferSize;
+    float2 param_5;
+    float2 param_6;
+    float4 _194 = computeVertexPosition(param, param_1, param_2, param_3, param_4, param_5, param_6);
+    vFrom = param_5;
+    vTo = param_6;
+    gl_Position = _194;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aTileIndex = stage_input.aTileIndex;
+    aTessCoord = stage_input.aTessCoord;
+    aLineSegment = stage_input.aLineSegment;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vFrom = vFrom;
+    stage_output.vTo = vTo;
+    return stage_output;
+}
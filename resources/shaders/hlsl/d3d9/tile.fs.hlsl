@@ -0,0 +1,943 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b4)
+{
+    float2 _2129_uColorTextureSize0 : packoffset(c0);
+    float2 _2129_uMaskTextureSize0 : packoffset(c0.z);
+    float2 _2129_uFramebufferSize : packoffset(c1);
+    int _2129_uDither : packoffset(c1.z);
+    int _2129_uCoverageOnly : packoffset(c1.w);
+};
+
+Texture2D<float4> uColorTexture0 : register(t0);
+SamplerState _uColorTexture0_sampler : register(s0);
+Texture2D<float4> uMaskTexture0 : register(t1);
+SamplerState _uMaskTexture0_sampler : register(s1);
+Texture2D<float4> uDestTexture : register(t2);
+SamplerState _uDestTexture_sampler : register(s2);
+Texture2D<float4> uGammaLUT : register(t3);
+SamplerState _uGammaLUT_sampler : register(s3);
+
+static float4 gl_FragCoord;
+static float4 vFilterParams0;
+static float4 vFilterParams1;
+static float4 vFilterParams2;
+static float4 vFilterParams3;
+static float4 vFilterParams4;
+static float vCtrl;
+static float3 vMaskTexCoord0;
+static float2 vColorTexCoord0;
+static float4 vBaseColor;
+static float vTileCtrl;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float3 vMaskTexCoord0 : TEXCOORD0;
+    float2 vColorTexCoord0 : TEXCOORD1;
+    float4 vBaseColor : TEXCOORD2;
+    float vTileCtrl : TEXCOORD3;
+    float4 vFilterParams0 : TEXCOORD4;
+    float4 vFilterParams1 : TEXCOORD5;
+    float4 vFilterParams2 : TEXCOORD6;
+    float4 vFilterParams3 : TEXCOORD7;
+    float4 vFilterParams4 : TEXCOORD8;
+    float vCtrl : TEXCOORD9;
+    float4 gl_FragCoord : SV_Position;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+float mod(float x, float y)
+{
+    return x - y * floor(x / y);
+}
+
+float2 mod(float2 x, float2 y)
+{
+    return x - y * floor(x / y);
+}
+
+float3 mod(float3 x, float3 y)
+{
+    return x - y * floor(x / y);
+}
+
+float4 mod(float4 x, float4 y)
+{
+    return x - y * floor(x / y);
+}
+
+float fetchMaskCoverage(Texture2D<float4> maskTexture, SamplerState _maskTexture_sampler, float2 maskTextureSize, int2 maskTexCoordI, float backdrop, int maskCtrl)
+{
+    float4 texel = maskTexture.Sample(_maskTexture_sampler, (float2(maskTexCoordI / int2(1, 4)) + 0.5f.xx) / maskTextureSize);
+    float coverage = texel[maskTexCoordI.y % 4] + backdrop;
+    if ((maskCtrl & 1) != 0)
+    {
+        return abs(coverage);
+    }
+    return 1.0f - abs(1.0f - mod(coverage, 2.0f));
+}
+
+float3 sampleSubpixelMask(Texture2D<float4> maskTexture, SamplerState _maskTexture_sampler, float2 maskTextureSize, float3 maskTexCoord, int maskCtrl)
+{
+    if (maskCtrl == 0)
+    {
+        return 1.0f.xxx;
+    }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    int tileX = maskTexCoordI.x % 16;
+    int2 leftTexCoordI = maskTexCoordI - int2(int(tileX > 0), 0);
+    int2 rightTexCoordI = maskTexCoordI + int2(int(tileX < 15), 0);
+    float2 param = maskTextureSize;
+    int2 param_1 = leftTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float left = fetchMaskCoverage(maskTexture, _maskTexture_sampler, param, param_1, param_2, param_3);
+    float2 param_4 = maskTextureSize;
+    int2 param_5 = maskTexCoordI;
+    float param_6 = maskTexCoord.z;
+    int param_7 = maskCtrl;
+    float center = fetchMaskCoverage(maskTexture, _maskTexture_sampler, param_4, param_5, param_6, param_7);
+    float2 param_8 = maskTextureSize;
+    int2 param_9 = rightTexCoordI;
+    float param_10 = maskTexCoord.z;
+    int param_11 = maskCtrl;
+    float right = fetchMaskCoverage(maskTexture, _maskTexture_sampler, param_8, param_9, param_10, param_11);
+    float3 coverage = float3(lerp(center, left, 0.3333333432674407958984375f), center, lerp(center, right, 0.3333333432674407958984375f));
+    return min(1.0f.xxx, coverage);
+}
+
+float sampleMask(float maskAlpha, Texture2D<float4> maskTexture, SamplerState _maskTexture_sampler, float2 maskTextureSize, float3 maskTexCoord, int maskCtrl)
+{
+    if (maskCtrl == 0)
+    {
+        return maskAlpha;
+    }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    float2 param = maskTextureSize;
+    int2 param_1 = maskTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float coverage = fetchMaskCoverage(maskTexture, _maskTexture_sampler, param, param_1, param_2, param_3);
+    return min(maskAlpha, coverage);
+}
+
+float4 filterRadialGradient(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float2 colorTextureSize, float2 fragCoord, float2 framebufferSize, float4 filterParams0, float4 filterParams1)
+{
+    float2 lineFrom = filterParams0.xy;
+    float2 lineVector = filterParams0.zw;
+    float2 radii = filterParams1.xy;
+    float2 uvOrigin = filterParams1.zw;
+    float2 dP = colorTexCoord - lineFrom;
+    float2 dC = lineVector;
+    float dR = radii.y - radii.x;
+    float a = dot(dC, dC) - (dR * dR);
+    float b = dot(dP, dC) + (radii.x * dR);
+    float c = dot(dP, dP) - (radii.x * radii.x);
+    float discrim = (b * b) - (a * c);
+    float4 color = 0.0f.xxxx;
+    if (discrim != 0.0f)
+    {
+        float2 ts = float2((float2(1.0f, -1.0f) * sqrt(discrim)) + b.xx) / a.xx;
+        if (ts.x > ts.y)
+        {
+            ts = ts.yx;
+        }
+        float _651;
+        if (ts.x >= 0.0f)
+        {
+            _651 = ts.x;
+        }
+        else
+        {
+            _651 = ts.y;
+        }
+        float t = _651;
+        color = colorTexture.Sample(_colorTexture_sampler, uvOrigin + float2(t, 0.0f));
+    }
+    return color;
+}
+
+float4 filterBlur(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float2 colorTextureSize, float4 filterParams0, float4 filterParams1)
+{
+    float2 srcOffsetScale = filterParams0.xy / colorTextureSize;
+    int support = int(filterParams0.z);
+    float3 gaussCoeff = filterParams1.xyz;
+    float gaussSum = gaussCoeff.x;
+    float4 color = colorTexture.Sample(_colorTexture_sampler, colorTexCoord) * gaussCoeff.x;
+    float2 _727 = gaussCoeff.xy * gaussCoeff.yz;
+    gaussCoeff = float3(_727.x, _727.y, gaussCoeff.z);
+    for (int i = 1; i <= support; i += 2)
+    {
+        float gaussPartialSum = gaussCoeff.x;
+        float2 _747 = gaussCoeff.xy * gaussCoeff.yz;
+        gaussCoeff = float3(_747.x, _747.y, gaussCoeff.z);
+        gaussPartialSum += gaussCoeff.x;
+        float2 srcOffset = srcOffsetScale * (float(i) + (gaussCoeff.x / gaussPartialSum));
+        color += ((colorTexture.Sample(_colorTexture_sampler, colorTexCoord - srcOffset) + colorTexture.Sample(_colorTexture_sampler, colorTexCoord + srcOffset)) * gaussPartialSum);
+        gaussSum += (2.0f * gaussPartialSum);
+        float2 _787 = gaussCoeff.xy * gaussCoeff.yz;
+        gaussCoeff = float3(_787.x, _787.y, gaussCoeff.z);
+    }
+    return color / gaussSum.xxxx;
+}
+
+float filterTextSample1Tap(float offset, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float2 colorTexCoord)
+{
+    return colorTexture.Sample(_colorTexture_sampler, colorTexCoord + float2(offset, 0.0f)).x;
+}
+
+void filterTextSample9Tap(out float4 outAlphaLeft, out float outAlphaCenter, out float4 outAlphaRight, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float2 colorTexCoord, float4 kernel, float onePixel)
+{
+    bool wide = kernel.x > 0.0f;
+    float _334;
+    if (wide)
+    {
+        float param = (-4.0f) * onePixel;
+        float2 param_1 = colorTexCoord;
+        _334 = filterTextSample1Tap(param, colorTexture, _colorTexture_sampler, param_1);
+    }
+    else
+    {
+        _334 = 0.0f;
+    }
+    float param_2 = (-3.0f) * onePixel;
+    float2 param_3 = colorTexCoord;
+    float param_4 = (-2.0f) * onePixel;
+    float2 param_5 = colorTexCoord;
+    float param_6 = (-1.0f) * onePixel;
+    float2 param_7 = colorTexCoord;
+    outAlphaLeft = float4(_334, filterTextSample1Tap(param_2, colorTexture, _colorTexture_sampler, param_3), filterTextSample1Tap(param_4, colorTexture, _colorTexture_sampler, param_5), filterTextSample1Tap(param_6, colorTexture, _colorTexture_sampler, param_7));
+    float param_8 = 0.0f;
+    float2 param_9 = colorTexCoord;
+    outAlphaCenter = filterTextSample1Tap(param_8, colorTexture, _colorTexture_sampler, param_9);
+    float param_10 = 1.0f * onePixel;
+    float2 param_11 = colorTexCoord;
+    float param_12 = 2.0f * onePixel;
+    float2 param_13 = colorTexCoord;
+    float param_14 = 3.0f * onePixel;
+    float2 param_15 = colorTexCoord;
+    float _394;
+    if (wide)
+    {
+        float param_16 = 4.0f * onePixel;
+        float2 param_17 = colorTexCoord;
+        _394 = filterTextSample1Tap(param_16, colorTexture, _colorTexture_sampler, param_17);
+    }
+    else
+    {
+        _394 = 0.0f;
+    }
+    outAlphaRight = float4(filterTextSample1Tap(param_10, colorTexture, _colorTexture_sampler, param_11), filterTextSample1Tap(param_12, colorTexture, _colorTexture_sampler, param_13), filterTextSample1Tap(param_14, colorTexture, _colorTexture_sampler, param_15), _394);
+}
+
+float filterTextConvolve7Tap(float4 alpha0, float3 alpha1, float4 kernel)
+{
+    return dot(alpha0, kernel) + dot(alpha1, kernel.zyx);
+}
+
+float filterTextGammaCorrectChannel(float bgColor, float fgColor, Texture2D<float4> gammaLUT, SamplerState _gammaLUT_sampler)
+{
+    return gammaLUT.Sample(_gammaLUT_sampler, float2(fgColor, 1.0f - bgColor)).x;
+}
+
+float3 filterTextGammaCorrect(float3 bgColor, float3 fgColor, Texture2D<float4> gammaLUT, SamplerState _gammaLUT_sampler)
+{
+    float param = bgColor.x;
+    float param_1 = fgColor.x;
+    float param_2 = bgColor.y;
+    float param_3 = fgColor.y;
+    float param_4 = bgColor.z;
+    float param_5 = fgColor.z;
+    return float3(filterTextGammaCorrectChannel(param, param_1, gammaLUT, _gammaLUT_sampler), filterTextGammaCorrectChannel(param_2, param_3, gammaLUT, _gammaLUT_sampler), filterTextGammaCorrectChannel(param_4, param_5, gammaLUT, _gammaLUT_sampler));
+}
+
+float4 filterText(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, Texture2D<float4> gammaLUT, SamplerState _gammaLUT_sampler, float2 colorTextureSize, float4 filterParams0, float4 filterParams1, float4 filterParams2)
+{
+    float4 kernel = filterParams0;
+    float3 bgColor = filterParams1.xyz;
+    float3 fgColor = filterParams2.xyz;
+    bool gammaCorrectionEnabled = filterParams2.w != 0.0f;
+    float3 alpha;
+    if (kernel.w == 0.0f)
+    {
+        alpha = colorTexture.Sample(_colorTexture_sampler, colorTexCoord).xxx;
+    }
+    else
+    {
+        float2 param_3 = colorTexCoord;
+        float4 param_4 = kernel;
+        float param_5 = 1.0f / colorTextureSize.x;
+        float4 param;
+        float param_1;
+        float4 param_2;
+        filterTextSample9Tap(param, param_1, param_2, colorTexture, _colorTexture_sampler, param_3, param_4, param_5);
+        float4 alphaLeft = param;
+        float alphaCenter = param_1;
+        float4 alphaRight = param_2;
+        float4 param_6 = alphaLeft;
+        float3 param_7 = float3(alphaCenter, alphaRight.xy);
+        float4 param_8 = kernel;
+        float r = filterTextConvolve7Tap(param_6, param_7, param_8);
+        float4 param_9 = float4(alphaLeft.yzw, alphaCenter);
+        float3 param_10 = alphaRight.xyz;
+        float4 param_11 = kernel;
+        float g = filterTextConvolve7Tap(param_9, param_10, param_11);
+        float4 param_12 = float4(alphaLeft.zw, alphaCenter, alphaRight.x);
+        float3 param_13 = alphaRight.yzw;
+        float4 param_14 = kernel;
+        float b = filterTextConvolve7Tap(param_12, param_13, param_14);
+        alpha = float3(r, g, b);
+    }
+    if (gammaCorrectionEnabled)
+    {
+        float3 param_15 = bgColor;
+        float3 param_16 = alpha;
+        alpha = filterTextGammaCorrect(param_15, param_16, gammaLUT, _gammaLUT_sampler);
+    }
+    return float4(lerp(bgColor, fgColor, alpha), 1.0f);
+}
+
+float4 filterColorMatrix(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float4 filterParams0, float4 filterParams1, float4 filterParams2, float4 filterParams3, float4 filterParams4)
+{
+    float4 srcColor = colorTexture.Sample(_colorTexture_sampler, colorTexCoord);
+    float4x4 colorMatrix = float4x4(float4(filterParams0), float4(filterParams1), float4(filterParams2), float4(filterParams3));
+    return mul(srcColor, colorMatrix) + filterParams4;
+}
+
+float2 filterNoiseGradient(float2 latticePoint, int channel, int seed)
+{
+    uint hash = ((uint(int(latticePoint.x)) * 2376512323u) ^ (uint(int(latticePoint.y)) * 3625334849u)) ^ (uint((seed * 4) + channel) * 3407524639u);
+    hash ^= (hash >> uint(16));
+    hash *= 2146121005u;
+    hash ^= (hash >> uint(15));
+    hash *= 2221713035u;
+    hash ^= (hash >> uint(16));
+    float angle = float(hash & 65535u) * 9.5873801910784095525741577148438e-05f;
+    return float2(cos(angle), sin(angle));
+}
+
+float filterNoisePerlin(float2 position, float2 period, int channel, int seed)
+{
+    float2 lattice0 = floor(position);
+    float2 lattice1 = lattice0 + 1.0f.xx;
+    float2 t = position - lattice0;
+    if (period.x > 0.0f)
+    {
+        lattice0.x = mod(lattice0.x, period.x);
+        lattice1.x = mod(lattice1.x, period.x);
+    }
+    if (period.y > 0.0f)
+    {
+        lattice0.y = mod(lattice0.y, period.y);
+        lattice1.y = mod(lattice1.y, period.y);
+    }
+    float2 param = lattice0;
+    int param_1 = channel;
+    int param_2 = seed;
+    float n00 = dot(filterNoiseGradient(param, param_1, param_2), t);
+    float2 param_3 = float2(lattice1.x, lattice0.y);
+    int param_4 = channel;
+    int param_5 = seed;
+    float n10 = dot(filterNoiseGradient(param_3, param_4, param_5), t - float2(1.0f, 0.0f));
+    float2 param_6 = float2(lattice0.x, lattice1.y);
+    int param_7 = channel;
+    int param_8 = seed;
+    float n01 = dot(filterNoiseGradient(param_6, param_7, param_8), t - float2(0.0f, 1.0f));
+    float2 param_9 = lattice1;
+    int param_10 = channel;
+    int param_11 = seed;
+    float n11 = dot(filterNoiseGradient(param_9, param_10, param_11), t - 1.0f.xx);
+    float2 s = (t * t) * (3.0f.xx - (t * 2.0f));
+    return lerp(lerp(n00, n10, s.x), lerp(n01, n11, s.x), s.y);
+}
+
+float4 filterNoise(float2 colorTexCoord, float4 filterParams0, float4 filterParams1, float4 filterParams2)
+{
+    float2 baseFrequency = filterParams0.xy;
+    int numOctaves = int(filterParams0.z);
+    int seed = int(filterParams0.w);
+    float2 stitchOrigin = filterParams1.xy;
+    float2 period = filterParams1.zw;
+    bool fractalNoise = filterParams2.x != 0.0f;
+    float2 position = (colorTexCoord - stitchOrigin) * baseFrequency;
+    float4 sum = 0.0f.xxxx;
+    float amplitude = 1.0f;
+    float4 _1093;
+    for (int octave = 0; octave < numOctaves; octave++)
+    {
+        float2 param = position;
+        float2 param_1 = period;
+        int param_2 = 0;
+        int param_3 = seed;
+        float2 param_4 = position;
+        float2 param_5 = period;
+        int param_6 = 1;
+        int param_7 = seed;
+        float2 param_8 = position;
+        float2 param_9 = period;
+        int param_10 = 2;
+        int param_11 = seed;
+        float2 param_12 = position;
+        float2 param_13 = period;
+        int param_14 = 3;
+        int param_15 = seed;
+        float4 octaveNoise = float4(filterNoisePerlin(param, param_1, param_2, param_3), filterNoisePerlin(param_4, param_5, param_6, param_7), filterNoisePerlin(param_8, param_9, param_10, param_11), filterNoisePerlin(param_12, param_13, param_14, param_15));
+        if (fractalNoise)
+        {
+            _1093 = octaveNoise;
+        }
+        else
+        {
+            _1093 = abs(octaveNoise);
+        }
+        sum += (_1093 * amplitude);
+        position *= 2.0f;
+        period *= 2.0f;
+        amplitude *= 0.5f;
+    }
+    if (fractalNoise)
+    {
+        sum = (sum * 0.5f) + 0.5f.xxxx;
+    }
+    return clamp(sum, 0.0f.xxxx, 1.0f.xxxx);
+}
+
+float filterPaintEffectLines(float position, float spacing, float lineWidth, float pixelSize)
+{
+    float dist = abs(frac((position / spacing) + 0.5f) - 0.5f) * spacing;
+    return clamp((((lineWidth * 0.5f) - dist) / pixelSize) + 0.5f, 0.0f, 1.0f);
+}
+
+float4 filterPaintEffect(float2 colorTexCoord, float4 filterParams0)
+{
+    int kind = int(filterParams0.x);
+    float size = filterParams0.y;
+    float amount = filterParams0.z;
+    float pixelSize = filterParams0.w;
+    float alpha;
+    if (kind == 2)
+    {
+        float2 offset = (frac(colorTexCoord / size.xx) - 0.5f.xx) * size;
+        float radius = size * sqrt(clamp(amount, 0.0f, 1.0f) / 3.1415927410125732421875f);
+        alpha = clamp(((radius - length(offset)) / pixelSize) + 0.5f, 0.0f, 1.0f);
+    }
+    else
+    {
+        float param = colorTexCoord.y;
+        float param_1 = size;
+        float param_2 = amount;
+        float param_3 = pixelSize;
+        alpha = filterPaintEffectLines(param, param_1, param_2, param_3);
+        if (kind == 1)
+        {
+            float param_4 = colorTexCoord.x;
+            float param_5 = size;
+            float param_6 = amount;
+            float param_7 = pixelSize;
+            alpha = max(alpha, filterPaintEffectLines(param_4, param_5, param_6, param_7));
+        }
+    }
+    return float4(1.0f, 1.0f, 1.0f, alpha);
+}
+
+float4 filterConicGradient(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float4 filterParams0, float4 filterParams1)
+{
+    float2 center = filterParams0.xy;
+    float2 uvOrigin = filterParams1.zw;
+    float angle = filterParams0.z;
+    float2 d = colorTexCoord - center;
+    float t = frac((atan2(d.y, d.x) - angle) * 0.15915493667125701904296875f);
+    return colorTexture.Sample(_colorTexture_sampler, uvOrigin + float2(t, 0.0f));
+}
+
+float4 sampleColor(Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, float2 colorTexCoord)
+{
+    return colorTexture.Sample(_colorTexture_sampler, colorTexCoord);
+}
+
+float4 filterNone(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler)
+{
+    float2 param = colorTexCoord;
+    return sampleColor(colorTexture, _colorTexture_sampler, param);
+}
+
+float4 filterColor(float2 colorTexCoord, Texture2D<float4> colorTexture, SamplerState _colorTexture_sampler, Texture2D<float4> gammaLUT, SamplerState _gammaLUT_sampler, float2 colorTextureSize, float2 fragCoord, float2 framebufferSize, float4 filterParams0, float4 filterParams1, float4 filterParams2, float4 filterParams3, float4 filterParams4, int colorFilter)
+{
+    switch (colorFilter)
+    {
+        case 1:
+        {
+            float2 param = colorTexCoord;
+            float2 param_1 = colorTextureSize;
+            float2 param_2 = fragCoord;
+            float2 param_3 = framebufferSize;
+            float4 param_4 = filterParams0;
+            float4 param_5 = filterParams1;
+            return filterRadialGradient(param, colorTexture, _colorTexture_sampler, param_1, param_2, param_3, param_4, param_5);
+        }
+        case 3:
+        {
+            float2 param_6 = colorTexCoord;
+            float2 param_7 = colorTextureSize;
+            float4 param_8 = filterParams0;
+            float4 param_9 = filterParams1;
+            return filterBlur(param_6, colorTexture, _colorTexture_sampler, param_7, param_8, param_9);
+        }
+        case 2:
+        {
+            float2 param_10 = colorTexCoord;
+            float2 param_11 = colorTextureSize;
+            float4 param_12 = filterParams0;
+            float4 param_13 = filterParams1;
+            float4 param_14 = filterParams2;
+            return filterText(param_10, colorTexture, _colorTexture_sampler, gammaLUT, _gammaLUT_sampler, param_11, param_12, param_13, param_14);
+        }
+        case 4:
+        {
+            float2 param_15 = colorTexCoord;
+            float4 param_16 = filterParams0;
+            float4 param_17 = filterParams1;
+            float4 param_18 = filterParams2;
+            float4 param_19 = filterParams3;
+            float4 param_20 = filterParams4;
+            return filterColorMatrix(param_15, colorTexture, _colorTexture_sampler, param_16, param_17, param_18, param_19, param_20);
+        }
+        case 5:
+        {
+            float2 param_21 = colorTexCoord;
+            float4 param_22 = filterParams0;
+            float4 param_23 = filterParams1;
+            float4 param_24 = filterParams2;
+            return filterNoise(param_21, param_22, param_23, param_24);
+        }
+        case 6:
+        {
+            float2 param_25 = colorTexCoord;
+            float4 param_26 = filterParams0;
+            return filterPaintEffect(param_25, param_26);
+        }
+        case 7:
+        {
+            float2 param_27 = colorTexCoord;
+            float4 param_28 = filterParams0;
+            float4 param_29 = filterParams1;
+            return filterConicGradient(param_27, colorTexture, _colorTexture_sampler, param_28, param_29);
+        }
+    }
+    float2 param_30 = colorTexCoord;
+    return filterNone(param_30, colorTexture, _colorTexture_sampler);
+}
+
+float4 combineColor0(float4 destColor, float4 srcColor, int op)
+{
+    switch (op)
+    {
+        case 1:
+        {
+            return float4(srcColor.xyz, srcColor.w * destColor.w);
+        }
+        case 2:
+        {
+            return float4(destColor.xyz, srcColor.w * destColor.w);
+        }
+    }
+    return destColor;
+}
+
+float3 compositeScreen(float3 destColor, float3 srcColor)
+{
+    return (destColor + srcColor) - (destColor * srcColor);
+}
+
+float3 compositeSelect(bool3 cond, float3 ifTrue, float3 ifFalse)
+{
+    float _1321;
+    if (cond.x)
+    {
+        _1321 = ifTrue.x;
+    }
+    else
+    {
+        _1321 = ifFalse.x;
+    }
+    float _1332;
+    if (cond.y)
+    {
+        _1332 = ifTrue.y;
+    }
+    else
+    {
+        _1332 = ifFalse.y;
+    }
+    float _1343;
+    if (cond.z)
+    {
+        _1343 = ifTrue.z;
+    }
+    else
+    {
+        _1343 = ifFalse.z;
+    }
+    return float3(_1321, _1332, _1343);
+}
+
+float3 compositeHardLight(float3 destColor, float3 srcColor)
+{
+    float3 param = destColor;
+    float3 param_1 = (2.0f.xxx * srcColor) - 1.0f.xxx;
+    bool3 param_2 = bool3(srcColor.x <= 0.5f.xxx.x, srcColor.y <= 0.5f.xxx.y, srcColor.z <= 0.5f.xxx.z);
+    float3 param_3 = (destColor * 2.0f.xxx) * srcColor;
+    float3 param_4 = compositeScreen(param, param_1);
+    return compositeSelect(param_2, param_3, param_4);
+}
+
+float3 compositeColorDodge(float3 destColor, float3 srcColor)
+{
+    bool3 destZero = bool3(destColor.x == 0.0f.xxx.x, destColor.y == 0.0f.xxx.y, destColor.z == 0.0f.xxx.z);
+    bool3 srcOne = bool3(srcColor.x == 1.0f.xxx.x, srcColor.y == 1.0f.xxx.y, srcColor.z == 1.0f.xxx.z);
+    bool3 param = srcOne;
+    float3 param_1 = 1.0f.xxx;
+    float3 param_2 = min(destColor / (1.0f.xxx - srcColor), 1.0f.xxx);
+    bool3 param_3 = destZero;
+    float3 param_4 = 0.0f.xxx;
+    float3 param_5 = compositeSelect(param, param_1, param_2);
+    return compositeSelect(param_3, param_4, param_5);
+}
+
+float3 compositeSoftLight(float3 destColor, float3 srcColor)
+{
+    bool3 param = bool3(destColor.x <= 0.25f.xxx.x, destColor.y <= 0.25f.xxx.y, destColor.z <= 0.25f.xxx.z);
+    float3 param_1 = ((((16.0f.xxx * destColor) - 12.0f.xxx) * destColor) + 4.0f.xxx) * destColor;
+    float3 param_2 = sqrt(destColor);
+    float3 darkenedDestColor = compositeSelect(param, param_1, param_2);
+    bool3 param_3 = bool3(srcColor.x <= 0.5f.xxx.x, srcColor.y <= 0.5f.xxx.y, srcColor.z <= 0.5f.xxx.z);
+    float3 param_4 = destColor * (1.0f.xxx - destColor);
+    float3 param_5 = darkenedDestColor - destColor;
+    float3 factor = compositeSelect(param_3, param_4, param_5);
+    return destColor + (((srcColor * 2.0f) - 1.0f.xxx) * factor);
+}
+
+float compositeSat(float3 color)
+{
+    return max(max(color.x, color.y), color.z) - min(min(color.x, color.y), color.z);
+}
+
+float3 compositeSetSat(float3 color, float s)
+{
+    float colorMin = min(min(color.x, color.y), color.z);
+    float3 param = color;
+    float colorSat = compositeSat(param);
+    float3 _1495;
+    if (colorSat > 0.0f)
+    {
+        _1495 = ((color - colorMin.xxx) * s) / colorSat.xxx;
+    }
+    else
+    {
+        _1495 = 0.0f.xxx;
+    }
+    return _1495;
+}
+
+float compositeLum(float3 color)
+{
+    return dot(color, float3(0.300000011920928955078125f, 0.589999973773956298828125f, 0.10999999940395355224609375f));
+}
+
+float3 compositeClipColor(inout float3 color)
+{
+    float3 param = color;
+    float l = compositeLum(param);
+    float n = min(min(color.x, color.y), color.z);
+    float x = max(max(color.x, color.y), color.z);
+    if (n < 0.0f)
+    {
+        color = l.xxx + (((color - l.xxx) * l) / (l - n).xxx);
+    }
+    if (x > 1.0f)
+    {
+        color = l.xxx + (((color - l.xxx) * (1.0f - l)) / (x - l).xxx);
+    }
+    return color;
+}
+
+float3 compositeSetLum(float3 color, float l)
+{
+    float3 param = color;
+    float3 param_1 = color + (l - compositeLum(param)).xxx;
+    float3 _1458 = compositeClipColor(param_1);
+    return _1458;
+}
+
+float3 compositeRGB(float3 destColor, float3 srcColor, int op)
+{
+    switch (op)
+    {
+        case 1:
+        {
+            return destColor * srcColor;
+        }
+        case 2:
+        {
+            float3 param = destColor;
+            float3 param_1 = srcColor;
+            return compositeScreen(param, param_1);
+        }
+        case 3:
+        {
+            float3 param_2 = srcColor;
+            float3 param_3 = destColor;
+            return compositeHardLight(param_2, param_3);
+        }
+        case 4:
+        {
+            return min(destColor, srcColor);
+        }
+        case 5:
+        {
+            return max(destColor, srcColor);
+        }
+        case 6:
+        {
+            float3 param_4 = destColor;
+            float3 param_5 = srcColor;
+            return compositeColorDodge(param_4, param_5);
+        }
+        case 7:
+        {
+            float3 param_6 = 1.0f.xxx - destColor;
+            float3 param_7 = 1.0f.xxx - srcColor;
+            return 1.0f.xxx - compositeColorDodge(param_6, param_7);
+        }
+        case 8:
+        {
+            float3 param_8 = destColor;
+            float3 param_9 = srcColor;
+            return compositeHardLight(param_8, param_9);
+        }
+        case 9:
+        {
+            float3 param_10 = destColor;
+            float3 param_11 = srcColor;
+            return compositeSoftLight(param_10, param_11);
+        }
+        case 10:
+        {
+            return abs(destColor - srcColor);
+        }
+        case 11:
+        {
+            return (destColor + srcColor) - ((2.0f.xxx * destColor) * srcColor);
+        }
+        case 12:
+        {
+            float3 param_12 = destColor;
+            float3 param_13 = srcColor;
+            float param_14 = compositeSat(param_12);
+            float3 param_15 = destColor;
+            float3 param_16 = compositeSetSat(param_13, param_14);
+            float param_17 = compositeLum(param_15);
+            return compositeSetLum(param_16, param_17);
+        }
+        case 13:
+        {
+            float3 param_18 = srcColor;
+            float3 param_19 = destColor;
+            float param_20 = compositeSat(param_18);
+            float3 param_21 = destColor;
+            float3 param_22 = compositeSetSat(param_19, param_20);
+            float param_23 = compositeLum(param_21);
+            return compositeSetLum(param_22, param_23);
+        }
+        case 14:
+        {
+            float3 param_24 = destColor;
+            float3 param_25 = srcColor;
+            float param_26 = compositeLum(param_24);
+            return compositeSetLum(param_25, param_26);
+        }
+        case 15:
+        {
+            float3 param_27 = srcColor;
+            float3 param_28 = destColor;
+            float param_29 = compositeLum(param_27);
+            return compositeSetLum(param_28, param_29);
+        }
+    }
+    return srcColor;
+}
+
+float4 compositeColors(float4 srcColor, float4 destColor, int op)
+{
+    float3 _1723;
+    if (destColor.w > 0.0f)
+    {
+        _1723 = destColor.xyz / destColor.w.xxx;
+    }
+    else
+    {
+        _1723 = 0.0f.xxx;
+    }
+    float3 destRGB = _1723;
+    float3 param = destRGB;
+    float3 param_1 = srcColor.xyz;
+    int param_2 = op;
+    float3 blendedRGB = compositeRGB(param, param_1, param_2);
+    float3 resultRGB = ((srcColor.xyz * (srcColor.w * (1.0f - destColor.w))) + (blendedRGB * (srcColor.w * destColor.w))) + (destColor.xyz * (1.0f - srcColor.w));
+    float resultAlpha = srcColor.w + (destColor.w * (1.0f - srcColor.w));
+    float4 _1780;
+    if (resultAlpha > 0.0f)
+    {
+        _1780 = float4(resultRGB / resultAlpha.xxx, resultAlpha);
+    }
+    else
+    {
+        _1780 = 0.0f.xxxx;
+    }
+    return _1780;
+}
+
+float4 composite(float4 srcColor, Texture2D<float4> destTexture, SamplerState _destTexture_sampler, float2 destTextureSize, float2 fragCoord, int op)
+{
+    if (op == 0)
+    {
+        return srcColor;
+    }
+    float2 destTexCoord = fragCoord / destTextureSize;
+    float4 destColor = destTexture.Sample(_destTexture_sampler, destTexCoord);
+    float4 param = srcColor;
+    float4 param_1 = destColor;
+    int param_2 = op;
+    return compositeColors(param, param_1, param_2);
+}
+
+float4 calculateColor(float2 fragCoord, Texture2D<float4> colorTexture0, SamplerState _colorTexture0_sampler, Texture2D<float4> maskTexture0, SamplerState _maskTexture0_sampler, Texture2D<float4> destTexture, SamplerState _destTexture_sampler, Texture2D<float4> gammaLUT, SamplerState _gammaLUT_sampler, float2 colorTextureSize0, float2 maskTextureSize0, float4 filterParams0, float4 filterParams1, float4 filterParams2, float4 filterParams3, float4 filterParams4, float2 framebufferSize, int ctrl, float3 maskTexCoord0, float2 colorTexCoord0, float4 baseColor, int tileCtrl, inout float3 channelAlphas)
+{
+    int maskCtrl0 = (tileCtrl >> 0) & 3;
+    bool subpixelCoverage = (tileCtrl & 4) != 0;
+    float3 maskAlphas;
+    if (subpixelCoverage)
+    {
+        float2 param = maskTextureSize0;
+        float3 param_1 = maskTexCoord0;
+        int param_2 = maskCtrl0;
+        maskAlphas = sampleSubpixelMask(maskTexture0, _maskTexture0_sampler, param, param_1, param_2);
+    }
+    else
+    {
+        float maskAlpha = 1.0f;
+        float param_3 = maskAlpha;
+        float2 param_4 = maskTextureSize0;
+        float3 param_5 = maskTexCoord0;
+        int param_6 = maskCtrl0;
+        maskAlpha = sampleMask(param_3, maskTexture0, _maskTexture0_sampler, param_4, param_5, param_6);
+        maskAlphas = maskAlpha.xxx;
+    }
+    float4 color = baseColor;
+    int color0Combine = (ctrl >> 8) & 3;
+    if (color0Combine != 0)
+    {
+        int color0Filter = (ctrl >> 4) & 15;
+        float2 param_7 = colorTexCoord0;
+        float2 param_8 = colorTextureSize0;
+        float2 param_9 = fragCoord;
+        float2 param_10 = framebufferSize;
+        float4 param_11 = filterParams0;
+        float4 param_12 = filterParams1;
+        float4 param_13 = filterParams2;
+        float4 param_14 = filterParams3;
+        float4 param_15 = filterParams4;
+        int param_16 = color0Filter;
+        float4 color0 = filterColor(param_7, colorTexture0, _colorTexture0_sampler, gammaLUT, _gammaLUT_sampler, param_8, param_9, param_10, param_11, param_12, param_13, param_14, param_15, param_16);
+        float4 param_17 = color;
+        float4 param_18 = color0;
+        int param_19 = color0Combine;
+        color = combineColor0(param_17, param_18, param_19);
+    }
+    channelAlphas = maskAlphas * color.w;
+    color.w = channelAlphas.y;
+    if (!subpixelCoverage)
+    {
+        int compositeOp = (ctrl >> 10) & 15;
+        float4 param_20 = color;
+        float2 param_21 = framebufferSize;
+        float2 param_22 = fragCoord;
+        int param_23 = compositeOp;
+        color = composite(param_20, destTexture, _destTexture_sampler, param_21, param_22, param_23);
+        channelAlphas = color.w.xxx;
+    }
+    float3 _2114 = color.xyz * channelAlphas;
+    color = float4(_2114.x, _2114.y, _2114.z, color.w);
+    return color;
+}
+
+float4 ditherColor(float4 color, float2 fragCoord)
+{
+    int2 position = int2(fragCoord) & int2(3, 3);
+    int diagonal = position.x ^ position.y;
+    int index = ((((diagonal & 1) << 3) | ((position.y & 1) << 2)) | (diagonal & 2)) | ((position.y >> 1) & 1);
+    float threshold = ((float(index) + 0.5f) / 16.0f) - 0.5f;
+    return float4(color.xyz + ((threshold * color.w) / 255.0f).xxx, color.w);
+}
+
+void frag_main()
+{
+    float2 param = gl_FragCoord.xy;
+    float2 param_1 = _2129_uColorTextureSize0;
+    float2 param_2 = _2129_uMaskTextureSize0;
+    float4 param_3 = vFilterParams0;
+    float4 param_4 = vFilterParams1;
+    float4 param_5 = vFilterParams2;
+    float4 param_6 = vFilterParams3;
+    float4 param_7 = vFilterParams4;
+    float2 param_8 = _2129_uFramebufferSize;
+    int param_9 = int(vCtrl);
+    float3 param_10 = vMaskTexCoord0;
+    float2 param_11 = vColorTexCoord0;
+    float4 param_12 = vBaseColor;
+    int param_13 = int(vTileCtrl);
+    float3 param_14;
+    float4 _2180 = calculateColor(param, uColorTexture0, _uColorTexture0_sampler, uMaskTexture0, _uMaskTexture0_sampler, uDestTexture, _uDestTexture_sampler, uGammaLUT, _uGammaLUT_sampler, param_1, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11, param_12, param_13, param_14);
+    float3 channelAlphas = param_14;
+    float4 color = _2180;
+    if (_2129_uDither != 0)
+    {
+        float4 param_15 = color;
+        float2 param_16 = gl_FragCoord.xy;
+        color = ditherColor(param_15, param_16);
+    }
+    float4 _2199;
+    if (_2129_uCoverageOnly != 0)
+    {
+        _2199 = float4(channelAlphas, color.w);
+    }
+    else
+    {
+        _2199 = color;
+    }
+    oFragColor = _2199;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    gl_FragCoord = stage_input.gl_FragCoord;
+    gl_FragCoord.w = 1.0 / gl_FragCoord.w;
+    vFilterParams0 = stage_input.vFilterParams0;
+    vFilterParams1 = stage_input.vFilterParams1;
+    vFilterParams2 = stage_input.vFilterParams2;
+    vFilterParams3 = stage_input.vFilterParams3;
+    vFilterParams4 = stage_input.vFilterParams4;
+    vCtrl = stage_input.vCtrl;
+    vMaskTexCoord0 = stage_input.vMaskTexCoord0;
+    vColorTexCoord0 = stage_input.vColorTexCoord0;
+    vBaseColor = stage_input.vBaseColor;
+    vTileCtrl = stage_input.vTileCtrl;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
@@ -0,0 +1,200 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    row_major float4x4 _186_uTransform : packoffset(c0);
+    float2 _186_uTileSize : packoffset(c4);
+    int2 _186_uTextureMetadataSize : packoffset(c4.z);
+    int2 _186_uZBufferSize : packoffset(c5);
+    int2 _186_uPathDepthPlanesSize : packoffset(c5.z);
+    int _186_uPathDepthEnabled : packoffset(c6);
+};
+
+Texture2D<float4> uZBuffer : register(t2);
+SamplerState _uZBuffer_sampler : register(s2);
+Texture2D<float4> uTextureMetadata : register(t1);
+SamplerState _uTextureMetadata_sampler : register(s1);
+Texture2D<float4> uPathDepthPlanes : register(t3);
+SamplerState _uPathDepthPlanes_sampler : register(s3);
+
+static float4 gl_Position;
+static int2 aTileOrigin;
+static int2 aTileOffset;
+static int aPathIndex;
+static uint4 aMaskTexCoord0;
+static int2 aCtrlBackdrop;
+static int aColor;
+static float2 vColorTexCoord0;
+static float4 vBaseColor;
+static float4 vFilterParams0;
+static float4 vFilterParams1;
+static float4 vFilterParams2;
+static float4 vFilterParams3;
+static float4 vFilterParams4;
+static float vTileCtrl;
+static float vCtrl;
+static float3 vMaskTexCoord0;
+
+struct SPIRV_Cross_Input
+{
+    int2 aTileOffset : TEXCOORD0;
+    int2 aTileOrigin : TEXCOORD1;
+    uint4 aMaskTexCoord0 : TEXCOORD2;
+    int2 aCtrlBackdrop : TEXCOORD3;
+    int aPathIndex : TEXCOORD4;
+    int aColor : TEXCOORD5;
+};
+
+struct SPIRV_Cross_Output
+{
+    float3 vMaskTexCoord0 : TEXCOORD0;
+    float2 vColorTexCoord0 : TEXCOORD1;
+    float4 vBaseColor : TEXCOORD2;
+    float vTileCtrl : TEXCOORD3;
+    float4 vFilterParams0 : TEXCOORD4;
+    float4 vFilterParams1 : TEXCOORD5;
+    float4 vFilterParams2 : TEXCOORD6;
+    float4 vFilterParams3 : TEXCOORD7;
+    float4 vFilterParams4 : TEXCOORD8;
+    float vCtrl : TEXCOORD9;
+    float4 gl_Position : SV_Position;
+};
+
+float4 fetchUnscaled(Texture2D<float4> srcTexture, SamplerState _srcTexture_sampler, float2 scale, float2 originCoord, int entry)
+{
+    return srcTexture.SampleLevel(_srcTexture_sampler, ((originCoord + 0.5f.xx) + float2(float(entry), 0.0f)) * scale, 0.0f);
+}
+
+void computeTileVaryings(float2 position, int colorEntry, Texture2D<float4> textureMetadata, SamplerState _textureMetadata_sampler, int2 textureMetadataSize, out float2 outColorTexCoord0, out float4 outBaseColor, out float4 outFilterParams0, out float4 outFilterParams1, out float4 outFilterParams2, out float4 outFilterParams3, out float4 outFilterParams4, out int outCtrl)
+{
+    float2 metadataScale = 1.0f.xx / float2(textureMetadataSize);
+    float2 metadataEntryCoord = float2(float((colorEntry % 128) * 10), float(colorEntry / 128));
+    float2 param = metadataScale;
+    float2 param_1 = metadataEntryCoord;
+    int param_2 = 0;
+    float4 colorTexMatrix0 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param, param_1, param_2);
+    float2 param_3 = metadataScale;
+    float2 param_4 = metadataEntryCoord;
+    int param_5 = 1;
+    float4 colorTexOffsets = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_3, param_4, param_5);
+    float2 param_6 = metadataScale;
+    float2 param_7 = metadataEntryCoord;
+    int param_8 = 2;
+    float4 baseColor = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_6, param_7, param_8);
+    float2 param_9 = metadataScale;
+    float2 param_10 = metadataEntryCoord;
+    int param_11 = 3;
+    float4 filterParams0 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_9, param_10, param_11);
+    float2 param_12 = metadataScale;
+    float2 param_13 = metadataEntryCoord;
+    int param_14 = 4;
+    float4 filterParams1 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_12, param_13, param_14);
+    float2 param_15 = metadataScale;
+    float2 param_16 = metadataEntryCoord;
+    int param_17 = 5;
+    float4 filterParams2 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_15, param_16, param_17);
+    float2 param_18 = metadataScale;
+    float2 param_19 = metadataEntryCoord;
+    int param_20 = 6;
+    float4 filterParams3 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_18, param_19, param_20);
+    float2 param_21 = metadataScale;
+    float2 param_22 = metadataEntryCoord;
+    int param_23 = 7;
+    float4 filterParams4 = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_21, param_22, param_23);
+    float2 param_24 = metadataScale;
+    float2 param_25 = metadataEntryCoord;
+    int param_26 = 8;
+    float4 extra = fetchUnscaled(textureMetadata, _textureMetadata_sampler, param_24, param_25, param_26);
+    outColorTexCoord0 = mul(position, float2x2(float2(colorTexMatrix0.xy), float2(colorTexMatrix0.zw))) + colorTexOffsets.xy;
+    outBaseColor = baseColor;
+    outFilterParams0 = filterParams0;
+    outFilterParams1 = filterParams1;
+    outFilterParams2 = filterParams2;
+    outFilterParams3 = filterParams3;
+    outFilterParams4 = filterParams4;
+    outCtrl = int(extra.x);
+}
+
+void vert_main()
+{
+    float2 tileOrigin = float2(aTileOrigin);
+    float2 tileOffset = float2(aTileOffset);
+    float2 position = (tileOrigin + tileOffset) * _186_uTileSize;
+    int4 zValue = int4(uZBuffer.SampleLevel(_uZBuffer_sampler, (tileOrigin + 0.5f.xx) / float2(_186_uZBufferSize), 0.0f) * 255.0f);
+    if (aPathIndex < (((zValue.x | (zValue.y << 8)) | (zValue.z << 16)) | (zValue.w << 24)))
+    {
+        gl_Position = 0.0f.xxxx;
+        return;
+    }
+    uint2 maskTileCoord = uint2(aMaskTexCoord0.x, aMaskTexCoord0.y + (256u * aMaskTexCoord0.z));
+    float2 maskTexCoord0 = (float2(maskTileCoord) + tileOffset) * _186_uTileSize;
+    bool _269 = aCtrlBackdrop.y == 0;
+    bool _275;
+    if (_269)
+    {
+        _275 = aMaskTexCoord0.w != 0u;
+    }
+    else
+    {
+        _275 = _269;
+    }
+    if (_275)
+    {
+        gl_Position = 0.0f.xxxx;
+        return;
+    }
+    float2 param = position;
+    int param_1 = aColor;
+    int2 param_2 = _186_uTextureMetadataSize;
+    float2 param_3;
+    float4 param_4;
+    float4 param_5;
+    float4 param_6;
+    float4 param_7;
+    float4 param_8;
+    float4 param_9;
+    int param_10;
+    computeTileVaryings(param, param_1, uTextureMetadata, _uTextureMetadata_sampler, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10);
+    vColorTexCoord0 = param_3;
+    vBaseColor = param_4;
+    vFilterParams0 = param_5;
+    vFilterParams1 = param_6;
+    vFilterParams2 = param_7;
+    vFilterParams3 = param_8;
+    vFilterParams4 = param_9;
+    int ctrl = param_10;
+    vTileCtrl = float(aCtrlBackdrop.x);
+    vCtrl = float(ctrl);
+    vMaskTexCoord0 = float3(maskTexCoord0, float(aCtrlBackdrop.y));
+    gl_Position = mul(float4(position, 0.0f, 1.0f), _186_uTransform);
+    if (_186_uPathDepthEnabled != 0)
+    {
+        float2 planeCoord = float2(float(aPathIndex % _186_uPathDepthPlanesSize.x), float(aPathIndex / _186_uPathDepthPlanesSize.x));
+        float2 planeTexCoord = (planeCoord + 0.5f.xx) / float2(_186_uPathDepthPlanesSize);
+        float4 plane = uPathDepthPlanes.SampleLevel(_uPathDepthPlanes_sampler, planeTexCoord, 0.0f);
+        gl_Position.z = dot(plane.xyz, float3(position, 1.0f));
+    }
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aTileOrigin = stage_input.aTileOrigin;
+    aTileOffset = stage_input.aTileOffset;
+    aPathIndex = stage_input.aPathIndex;
+    aMaskTexCoord0 = stage_input.aMaskTexCoord0;
+    aCtrlBackdrop = stage_input.aCtrlBackdrop;
+    aColor = stage_input.aColor;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vColorTexCoord0 = vColorTexCoord0;
+    stage_output.vBaseColor = vBaseColor;
+    stage_output.vFilterParams0 = vFilterParams0;
+    stage_output.vFilterParams1 = vFilterParams1;
+    stage_output.vFilterParams2 = vFilterParams2;
+    stage_output.vFilterParams3 = vFilterParams3;
+    stage_output.vFilterParams4 = vFilterParams4;
+    stage_output.vTileCtrl = vTileCtrl;
+    stage_output.vCtrl = vCtrl;
+    stage_output.vMaskTexCoord0 = vMaskTexCoord0;
+    return stage_output;
+}
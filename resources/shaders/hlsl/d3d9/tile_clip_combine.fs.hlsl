@@ -0,0 +1,76 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+Texture2D<float4> uSrc : register(t0);
+SamplerState _uSrc_sampler : register(s0);
+
+static float4 oFragColor;
+static float2 vTexCoord0;
+static float vBackdrop0;
+static float vEvenOdd0;
+static float2 vTexCoord1;
+static float vBackdrop1;
+static float vEvenOdd1;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord0 : TEXCOORD0;
+    float vBackdrop0 : TEXCOORD1;
+    float vEvenOdd0 : TEXCOORD2;
+    float2 vTexCoord1 : TEXCOORD3;
+    float vBackdrop1 : TEXCOORD4;
+    float vEvenOdd1 : TEXCOORD5;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+float mod(float x, float y)
+{
+    return x - y * floor(x / y);
+}
+
+float2 mod(float2 x, float2 y)
+{
+    return x - y * floor(x / y);
+}
+
+float3 mod(float3 x, float3 y)
+{
+    return x - y * floor(x / y);
+}
+
+float4 mod(float4 x, float4 y)
+{
+    return x - y * floor(x / y);
+}
+
+float4 resolveCoverage(float4 winding, float evenOdd)
+{
+    float4 nonzeroCoverage = min(abs(winding), 1.0f.xxxx);
+    float4 evenOddCoverage = 1.0f.xxxx - abs(1.0f.xxxx - mod(winding, 2.0f.xxxx));
+    return lerp(nonzeroCoverage, evenOddCoverage, evenOdd.xxxx);
+}
+
+void frag_main()
+{
+    float4 param = uSrc.Sample(_uSrc_sampler, vTexCoord0) + vBackdrop0.xxxx;
+    float param_1 = vEvenOdd0;
+    float4 param_2 = uSrc.Sample(_uSrc_sampler, vTexCoord1) + vBackdrop1.xxxx;
+    float param_3 = vEvenOdd1;
+    oFragColor = min(resolveCoverage(param, param_1), resolveCoverage(param_2, param_3));
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord0 = stage_input.vTexCoord0;
+    vBackdrop0 = stage_input.vBackdrop0;
+    vEvenOdd0 = stage_input.vEvenOdd0;
+    vTexCoord1 = stage_input.vTexCoord1;
+    vBackdrop1 = stage_input.vBackdrop1;
+    vEvenOdd1 = stage_input.vEvenOdd1;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
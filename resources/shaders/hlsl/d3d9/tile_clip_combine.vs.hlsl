@@ -0,0 +1,84 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _40_uFramebufferSize : packoffset(c0);
+};
+
+
+static float4 gl_Position;
+static int aDestTileIndex;
+static int2 aTileOffset;
+static int aSrcTileIndex;
+static float2 vTexCoord0;
+static float2 vTexCoord1;
+static float vBackdrop0;
+static int aDestBackdrop;
+static float vBackdrop1;
+static int aSrcBackdrop;
+static float vEvenOdd0;
+static int aDestCtrl;
+static float vEvenOdd1;
+static int aSrcCtrl;
+
+struct SPIRV_Cross_Input
+{
+    int2 aTileOffset : TEXCOORD0;
+    int aDestTileIndex : TEXCOORD1;
+    int aDestBackdrop : TEXCOORD2;
+    int aDestCtrl : TEXCOORD3;
+    int aSrcTileIndex : TEXCOORD4;
+    int aSrcBackdrop : TEXCOORD5;
+    int aSrcCtrl : TEXCOORD6;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord0 : TEXCOORD0;
+    float vBackdrop0 : TEXCOORD1;
+    float vEvenOdd0 : TEXCOORD2;
+    float2 vTexCoord1 : TEXCOORD3;
+    float vBackdrop1 : TEXCOORD4;
+    float vEvenOdd1 : TEXCOORD5;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 destPosition = float2(int2(aDestTileIndex % 256, aDestTileIndex / 256) + aTileOffset);
+    float2 srcPosition = float2(int2(aSrcTileIndex % 256, aSrcTileIndex / 256) + aTileOffset);
+    destPosition *= (float2(16.0f, 4.0f) / _40_uFramebufferSize);
+    srcPosition *= (float2(16.0f, 4.0f) / _40_uFramebufferSize);
+    vTexCoord0 = destPosition;
+    vTexCoord1 = srcPosition;
+    vBackdrop0 = float(aDestBackdrop);
+    vBackdrop1 = float(aSrcBackdrop);
+    vEvenOdd0 = float(aDestCtrl == 2);
+    vEvenOdd1 = float(aSrcCtrl == 2);
+    if (aDestTileIndex < 0)
+    {
+        destPosition = 0.0f.xx;
+    }
+    destPosition.y = 1.0f - destPosition.y;
+    gl_Position = float4(lerp((-1.0f).xx, 1.0f.xx, destPosition), 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aDestTileIndex = stage_input.aDestTileIndex;
+    aTileOffset = stage_input.aTileOffset;
+    aSrcTileIndex = stage_input.aSrcTileIndex;
+    aDestBackdrop = stage_input.aDestBackdrop;
+    aSrcBackdrop = stage_input.aSrcBackdrop;
+    aDestCtrl = stage_input.aDestCtrl;
+    aSrcCtrl = stage_input.aSrcCtrl;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord0 = vTexCoord0;
+    stage_output.vTexCoord1 = vTexCoord1;
+    stage_output.vBackdrop0 = vBackdrop0;
+    stage_output.vBackdrop1 = vBackdrop1;
+    stage_output.vEvenOdd0 = vEvenOdd0;
+    stage_output.vEvenOdd1 = vEvenOdd1;
+    return stage_output;
+}
@@ -0,0 +1,30 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+Texture2D<float4> uSrc : register(t0);
+SamplerState _uSrc_sampler : register(s0);
+
+static float4 oFragColor;
+static float2 vTexCoord;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    oFragColor = uSrc.Sample(_uSrc_sampler, vTexCoord);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
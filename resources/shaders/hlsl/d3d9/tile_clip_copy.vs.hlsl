@@ -0,0 +1,47 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _30_uFramebufferSize : packoffset(c0);
+};
+
+
+static float4 gl_Position;
+static int aTileIndex;
+static int2 aTileOffset;
+static float2 vTexCoord;
+
+struct SPIRV_Cross_Input
+{
+    int2 aTileOffset : TEXCOORD0;
+    int aTileIndex : TEXCOORD1;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord : TEXCOORD0;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 position = float2(int2(aTileIndex % 256, aTileIndex / 256) + aTileOffset);
+    position *= (float2(16.0f, 4.0f) / _30_uFramebufferSize);
+    vTexCoord = position;
+    if (aTileIndex < 0)
+    {
+        position = 0.0f.xx;
+    }
+    position.y = 1.0f - position.y;
+    gl_Position = float4(lerp((-1.0f).xx, 1.0f.xx, position), 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aTileIndex = stage_input.aTileIndex;
+    aTileOffset = stage_input.aTileOffset;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord = vTexCoord;
+    return stage_output;
+}
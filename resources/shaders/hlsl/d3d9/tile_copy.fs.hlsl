@@ -0,0 +1,37 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _17_uFramebufferSize : packoffset(c0);
+};
+
+Texture2D<float4> uSrc : register(t1);
+SamplerState _uSrc_sampler : register(s1);
+
+static float4 gl_FragCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float4 gl_FragCoord : SV_Position;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    float2 texCoord = gl_FragCoord.xy / _17_uFramebufferSize;
+    oFragColor = uSrc.Sample(_uSrc_sampler, texCoord);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    gl_FragCoord = stage_input.gl_FragCoord;
+    gl_FragCoord.w = 1.0 / gl_FragCoord.w;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
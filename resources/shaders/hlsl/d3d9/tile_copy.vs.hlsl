@@ -0,0 +1,35 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    row_major float4x4 _20_uTransform : packoffset(c0);
+    float2 _20_uTileSize : packoffset(c4);
+};
+
+
+static float4 gl_Position;
+static int2 aTilePosition;
+
+struct SPIRV_Cross_Input
+{
+    int2 aTilePosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 position = float2(aTilePosition) * _20_uTileSize;
+    gl_Position = mul(float4(position, 0.0f, 1.0f), _20_uTransform);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aTilePosition = stage_input.aTilePosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    return stage_output;
+}
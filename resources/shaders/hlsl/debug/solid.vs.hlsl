@@ -0,0 +1,34 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _18_uFramebufferSize : packoffset(c0);
+};
+
+
+static float4 gl_Position;
+static int2 aPosition;
+
+struct SPIRV_Cross_Input
+{
+    int2 aPosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 position = ((float2(aPosition) / _18_uFramebufferSize) * 2.0f) - 1.0f.xx;
+    gl_Position = float4(position.x, -position.y, 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    return stage_output;
+}
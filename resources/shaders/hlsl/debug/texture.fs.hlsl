@@ -0,0 +1,36 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b1)
+{
+    float4 _25_uColor : packoffset(c0);
+};
+
+Texture2D<float4> uTexture : register(t0);
+SamplerState _uTexture_sampler : register(s0);
+
+static float2 vTexCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    float alpha = uTexture.Sample(_uTexture_sampler, vTexCoord).x * _25_uColor.w;
+    oFragColor = float4(_25_uColor.xyz, 1.0f) * alpha;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
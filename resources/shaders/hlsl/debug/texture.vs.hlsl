@@ -0,0 +1,42 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float2 _18_uFramebufferSize : packoffset(c0);
+    float2 _18_uTextureSize : packoffset(c0.z);
+};
+
+
+static float4 gl_Position;
+static float2 vTexCoord;
+static int2 aTexCoord;
+static int2 aPosition;
+
+struct SPIRV_Cross_Input
+{
+    int2 aPosition : TEXCOORD0;
+    int2 aTexCoord : TEXCOORD1;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord : TEXCOORD0;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    vTexCoord = float2(aTexCoord) / _18_uTextureSize;
+    float2 position = ((float2(aPosition) / _18_uFramebufferSize) * 2.0f) - 1.0f.xx;
+    gl_Position = float4(position.x, -position.y, 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aTexCoord = stage_input.aTexCoord;
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord = vTexCoord;
+    return stage_output;
+}
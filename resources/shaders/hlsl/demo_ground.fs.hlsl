@@ -0,0 +1,44 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    float4 _33_uGroundColor : packoffset(c0);
+    float4 _33_uGridlineColor : packoffset(c1);
+};
+
+
+static float2 vTexCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    float2 texCoordPx = frac(vTexCoord) / fwidth(vTexCoord);
+    float4 _28;
+    if (any(bool2(texCoordPx.x <= 1.0f.xx.x, texCoordPx.y <= 1.0f.xx.y)))
+    {
+        _28 = _33_uGridlineColor;
+    }
+    else
+    {
+        _28 = _33_uGroundColor;
+    }
+    oFragColor = _28;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
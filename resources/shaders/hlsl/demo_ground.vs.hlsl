@@ -0,0 +1,38 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    row_major float4x4 _19_uTransform : packoffset(c0);
+    int _19_uGridlineCount : packoffset(c4);
+};
+
+
+static float4 gl_Position;
+static float2 vTexCoord;
+static int2 aPosition;
+
+struct SPIRV_Cross_Input
+{
+    int2 aPosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord : TEXCOORD0;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    vTexCoord = float2(aPosition * _19_uGridlineCount.xx);
+    gl_Position = mul(float4(int4(aPosition.x, 0, aPosition.y, 1)), _19_uTransform);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord = vTexCoord;
+    return stage_output;
+}
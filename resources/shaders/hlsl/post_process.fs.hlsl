@@ -0,0 +1,98 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b2)
+{
+    float4 _120_uFilterParams0 : packoffset(c0);
+    float4 _120_uFilterParams1 : packoffset(c1);
+    int _120_uFilterKind : packoffset(c2);
+};
+
+Texture2D<float4> uLUT : register(t1);
+SamplerState _uLUT_sampler : register(s1);
+Texture2D<float4> uSrc : register(t0);
+SamplerState _uSrc_sampler : register(s0);
+
+static float2 vTexCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+float3 unpremultiply(float4 color)
+{
+    float3 _28;
+    if (color.w == 0.0f)
+    {
+        _28 = 0.0f.xxx;
+    }
+    else
+    {
+        _28 = color.xyz / color.w.xxx;
+    }
+    return _28;
+}
+
+float3 sampleColorLUT(float3 color, float size)
+{
+    float3 scaled = clamp(color, 0.0f.xxx, 1.0f.xxx) * (size - 1.0f);
+    float sliceLo = floor(scaled.z);
+    float sliceHi = min(sliceLo + 1.0f, size - 1.0f);
+    float2 texCoord = (scaled.xy + 0.5f.xx) / float2(size * size, size);
+    float3 lo = uLUT.Sample(_uLUT_sampler, texCoord + float2(sliceLo / size, 0.0f)).xyz;
+    float3 hi = uLUT.Sample(_uLUT_sampler, texCoord + float2(sliceHi / size, 0.0f)).xyz;
+    return lerp(lo, hi, (scaled.z - sliceLo).xxx);
+}
+
+void frag_main()
+{
+    float4 color = uSrc.Sample(_uSrc_sampler, vTexCoord);
+    if (_120_uFilterKind == 0)
+    {
+        float4 param = color;
+        float3 param_1 = unpremultiply(param);
+        float param_2 = _120_uFilterParams0.x;
+        float3 _141 = sampleColorLUT(param_1, param_2) * color.w;
+        color = float4(_141.x, _141.y, _141.z, color.w);
+    }
+    else
+    {
+        if (_120_uFilterKind == 1)
+        {
+            float dist = length(vTexCoord - 0.5f.xx) * 1.41421353816986083984375f;
+            float amount = smoothstep(_120_uFilterParams1.x, _120_uFilterParams1.x + _120_uFilterParams1.y, dist);
+            float3 _183 = lerp(color.xyz, _120_uFilterParams0.xyz * color.w, (amount * _120_uFilterParams0.w).xxx);
+            color = float4(_183.x, _183.y, _183.z, color.w);
+        }
+        else
+        {
+            if (_120_uFilterKind == 2)
+            {
+                float4 param_3 = color;
+                float3 _201 = pow(unpremultiply(param_3), _120_uFilterParams0.x.xxx) * color.w;
+                color = float4(_201.x, _201.y, _201.z, color.w);
+            }
+            else
+            {
+                float4 param_4 = color;
+                float3 _207 = unpremultiply(param_4);
+                color = float4(_207.x, _207.y, _207.z, color.w);
+            }
+        }
+    }
+    oFragColor = color;
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
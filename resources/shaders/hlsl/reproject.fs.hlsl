@@ -0,0 +1,37 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    row_major float4x4 _13_uOldTransform : packoffset(c0);
+};
+
+Texture2D<float4> uTexture : register(t1);
+SamplerState _uTexture_sampler : register(s1);
+
+static float2 vTexCoord;
+static float4 oFragColor;
+
+struct SPIRV_Cross_Input
+{
+    float2 vTexCoord : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    float4 normTexCoord = mul(float4(vTexCoord, 0.0f, 1.0f), _13_uOldTransform);
+    float2 texCoord = ((normTexCoord.xy / normTexCoord.w.xx) + 1.0f.xx) * 0.5f;
+    oFragColor = uTexture.Sample(_uTexture_sampler, texCoord);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    vTexCoord = stage_input.vTexCoord;
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
@@ -0,0 +1,39 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+cbuffer _RESERVED_IDENTIFIER_FIXUP_gl_DefaultUniformBlock : register(b0)
+{
+    row_major float4x4 _36_uNewTransform : packoffset(c0);
+};
+
+
+static float4 gl_Position;
+static int2 aPosition;
+static float2 vTexCoord;
+
+struct SPIRV_Cross_Input
+{
+    int2 aPosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float2 vTexCoord : TEXCOORD0;
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    float2 position = float2(aPosition);
+    vTexCoord = position;
+    position.y = 1.0f - position.y;
+    gl_Position = mul(float4(position, 0.0f, 1.0f), _36_uNewTransform);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    stage_output.vTexCoord = vTexCoord;
+    return stage_output;
+}
@@ -0,0 +1,20 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static float4 oFragColor;
+
+struct SPIRV_Cross_Output
+{
+    float4 oFragColor : SV_Target0;
+};
+
+void frag_main()
+{
+    oFragColor = float4(1.0f, 0.0f, 0.0f, 1.0f);
+}
+
+SPIRV_Cross_Output main()
+{
+    frag_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.oFragColor = oFragColor;
+    return stage_output;
+}
@@ -0,0 +1,27 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+static float4 gl_Position;
+static float3 aPosition;
+
+struct SPIRV_Cross_Input
+{
+    float3 aPosition : TEXCOORD0;
+};
+
+struct SPIRV_Cross_Output
+{
+    float4 gl_Position : SV_Position;
+};
+
+void vert_main()
+{
+    gl_Position = float4(aPosition, 1.0f);
+}
+
+SPIRV_Cross_Output main(SPIRV_Cross_Input stage_input)
+{
+    aPosition = stage_input.aPosition;
+    vert_main();
+    SPIRV_Cross_Output stage_output;
+    stage_output.gl_Position = gl_Position;
+    return stage_output;
+}
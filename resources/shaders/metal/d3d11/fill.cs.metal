@@ -37,8 +37,31 @@ float4 computeCoverage(thread const float2& from, thread const float2& to, threa
     return areaLUT.sample(areaLUTSmplr, (float2(y + 8.0, abs(d * dX)) / float2(16.0)), level(0.0)) * dX;
 }
 
+constant float2 _samplePositions[8] = { float2(1.0, -3.0), float2(-1.0, 3.0), float2(5.0, 1.0), float2(-3.0, -5.0), float2(-5.0, 5.0), float2(-7.0, -1.0), float2(3.0, 7.0), float2(7.0, -7.0) };
+
+static inline __attribute__((always_inline))
+float4 computeCoverageSupersample(thread const float2& from, thread const float2& to)
+{
+    float2 left = select(to, from, bool2(from.x < to.x));
+    float2 right = select(from, to, bool2(from.x < to.x));
+    float4 coverages = float4(0.0);
+    for (int sampleIndex = 0; sampleIndex < 8; sampleIndex++)
+    {
+        float2 samplePosition = _samplePositions[sampleIndex] / float2(16.0);
+        if ((samplePosition.x < left.x) || (samplePosition.x >= right.x))
+        {
+            continue;
+        }
+        float t = (samplePosition.x - left.x) / (right.x - left.x);
+        float y = mix(left.y, right.y, t);
+        coverages += step(float4(y), float4(samplePosition.y) + float4(0.0, 1.0, 2.0, 3.0));
+    }
+    float winding = (from.x < to.x) ? (-1.0) : 1.0;
+    return (coverages * winding) / float4(8.0);
+}
+
 static inline __attribute__((always_inline))
-float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& tileSubCoord, const device bFills& v_148, thread texture2d<float> uAreaLUT, thread const sampler uAreaLUTSmplr)
+float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& tileSubCoord, const device bFills& v_148, constant int& uSupersample, thread texture2d<float> uAreaLUT, thread const sampler uAreaLUTSmplr)
 {
     float2 tileFragCoord = float2(tileSubCoord) + float2(0.5);
     float4 coverages = float4(0.0);
@@ -51,7 +74,14 @@ float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& t
         lineSegment -= tileFragCoord.xyxy;
         float2 param = lineSegment.xy;
         float2 param_1 = lineSegment.zw;
-        coverages += computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+        if (uSupersample != 0)
+        {
+            coverages += computeCoverageSupersample(param, param_1);
+        }
+        else
+        {
+            coverages += computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+        }
         fillIndex = int(v_148.iFills[(fillIndex * 3) + 2]);
         iteration++;
     } while ((fillIndex >= 0) && (iteration < 1024));
@@ -66,7 +96,7 @@ int2 computeTileCoord(thread const uint& alphaTileIndex, thread uint3& gl_LocalI
     return (int2(16, 4) * int2(int(x), int(y))) + int2(gl_LocalInvocationID.xy);
 }
 
-kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], const device bFills& v_148 [[buffer(0)]], const device bAlphaTiles& _284 [[buffer(2)]], device bTiles& _294 [[buffer(3)]], texture2d<float> uAreaLUT [[texture(0)]], texture2d<float, access::read_write> uDest [[texture(1)]], sampler uAreaLUTSmplr [[sampler(0)]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]])
+kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], const device bFills& v_148 [[buffer(0)]], const device bAlphaTiles& _284 [[buffer(2)]], device bTiles& _294 [[buffer(3)]], constant int& uSupersample [[buffer(4)]], texture2d<float> uAreaLUT [[texture(0)]], texture2d<float, access::read_write> uDest [[texture(1)]], sampler uAreaLUTSmplr [[sampler(0)]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]])
 {
     int2 tileSubCoord = int2(gl_LocalInvocationID.xy) * int2(1, 4);
     uint batchAlphaTileIndex = gl_WorkGroupID.x | (gl_WorkGroupID.y << uint(15));
@@ -85,7 +115,7 @@ kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], const device bFi
     float4 coverages = float4(float(backdrop));
     int param = fillIndex;
     int2 param_1 = tileSubCoord;
-    float4 _334 = accumulateCoverageForFillList(param, param_1, v_148, uAreaLUT, uAreaLUTSmplr);
+    float4 _334 = accumulateCoverageForFillList(param, param_1, v_148, uSupersample, uAreaLUT, uAreaLUTSmplr);
     coverages += _334;
     coverages = fast::clamp(abs(coverages), float4(0.0), float4(1.0));
     int clipTileIndex = int(_284.iAlphaTiles[(batchAlphaTileIndex * 2u) + 1u]);
@@ -90,6 +90,18 @@ void computeTileVaryings(thread const float2& position, thread const int& colorE
     outCtrl = int(extra.x);
 }
 
+static inline __attribute__((always_inline))
+float fetchMaskCoverage(thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const int2& maskTexCoordI, thread const float& backdrop, thread const int& maskCtrl)
+{
+    float4 texel = maskTexture.sample(maskTextureSmplr, ((float2(maskTexCoordI / int2(1, 4)) + float2(0.5)) / maskTextureSize), level(0.0));
+    float coverage = texel[maskTexCoordI.y % 4] + backdrop;
+    if ((maskCtrl & 1) != 0)
+    {
+        return abs(coverage);
+    }
+    return 1.0 - abs(1.0 - mod(coverage, 2.0));
+}
+
 static inline __attribute__((always_inline))
 float sampleMask(thread const float& maskAlpha, thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl)
 {
@@ -97,6 +109,44 @@ float sampleMask(thread const float& maskAlpha, thread const texture2d<float> ma
     {
         return maskAlpha;
     }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    float2 param = maskTextureSize;
+    int2 param_1 = maskTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float coverage = fetchMaskCoverage(maskTexture, maskTextureSmplr, param, param_1, param_2, param_3);
+    return fast::min(maskAlpha, coverage);
+}
+
+static inline __attribute__((always_inline))
+float3 sampleSubpixelMask(thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl)
+{
+    if (maskCtrl == 0)
+    {
+        return float3(1.0);
+    }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    int tileX = maskTexCoordI.x % 16;
+    int2 leftTexCoordI = maskTexCoordI - int2((tileX > 0) ? 1 : 0, 0);
+    int2 rightTexCoordI = maskTexCoordI + int2((tileX < 15) ? 1 : 0, 0);
+    float2 param = maskTextureSize;
+    int2 param_1 = leftTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float left = fetchMaskCoverage(maskTexture, maskTextureSmplr, param, param_1, param_2, param_3);
+    float2 param_4 = maskTextureSize;
+    int2 param_5 = maskTexCoordI;
+    float param_6 = maskTexCoord.z;
+    int param_7 = maskCtrl;
+    float center = fetchMaskCoverage(maskTexture, maskTextureSmplr, param_4, param_5, param_6, param_7);
+    float2 param_8 = maskTextureSize;
+    int2 param_9 = rightTexCoordI;
+    float param_10 = maskTexCoord.z;
+    int param_11 = maskCtrl;
+    float right = fetchMaskCoverage(maskTexture, maskTextureSmplr, param_8, param_9, param_10, param_11);
+    float3 coverage = float3(mix(center, left, 0.3333333432674407958984375), center, mix(center, right, 0.3333333432674407958984375));
+    return fast::min(float3(1.0), coverage);
+}
     int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
     float4 texel = maskTexture.sample(maskTextureSmplr, ((float2(maskTexCoordI / int2(1, 4)) + float2(0.5)) / maskTextureSize), level(0.0));
     float coverage = texel[maskTexCoordI.y % 4] + maskTexCoord.z;
@@ -148,6 +198,17 @@ float4 filterRadialGradient(thread const float2& colorTexCoord, thread const tex
     return color;
 }
 
+static inline __attribute__((always_inline))
+float4 filterConicGradient(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float4& filterParams0, thread const float4& filterParams1)
+{
+    float2 center = filterParams0.xy;
+    float2 uvOrigin = filterParams1.zw;
+    float angle = filterParams0.z;
+    float2 d = colorTexCoord - center;
+    float t = fract((atan2(d.y, d.x) - angle) * 0.15915493667125701904296875);
+    return colorTexture.sample(colorTextureSmplr, (uvOrigin + float2(t, 0.0)));
+}
+
 static inline __attribute__((always_inline))
 float4 filterBlur(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0, thread const float4& filterParams1)
 {
@@ -303,6 +364,147 @@ float4 filterColorMatrix(thread const float2& colorTexCoord, thread const textur
     return (colorMatrix * srcColor) + filterParams4;
 }
 
+static inline __attribute__((always_inline))
+float2 filterNoiseGradient(thread const float2& latticePoint, thread const int& channel, thread const int& seed)
+{
+    uint hash = ((uint(int(latticePoint.x)) * 2376512323u) ^ (uint(int(latticePoint.y)) * 3625334849u)) ^ (uint((seed * 4) + channel) * 3407524639u);
+    hash ^= (hash >> uint(16));
+    hash *= 2146121005u;
+    hash ^= (hash >> uint(15));
+    hash *= 2221713035u;
+    hash ^= (hash >> uint(16));
+    float angle = float(hash & 65535u) * 9.58738019107840955257415771484375e-05;
+    return float2(cos(angle), sin(angle));
+}
+
+static inline __attribute__((always_inline))
+float filterNoisePerlin(thread const float2& position, thread const float2& period, thread const int& channel, thread const int& seed)
+{
+    float2 lattice0 = floor(position);
+    float2 lattice1 = lattice0 + float2(1.0);
+    float2 t = position - lattice0;
+    if (period.x > 0.0)
+    {
+        lattice0.x = mod(lattice0.x, period.x);
+        lattice1.x = mod(lattice1.x, period.x);
+    }
+    if (period.y > 0.0)
+    {
+        lattice0.y = mod(lattice0.y, period.y);
+        lattice1.y = mod(lattice1.y, period.y);
+    }
+    float2 param = lattice0;
+    int param_1 = channel;
+    int param_2 = seed;
+    float n00 = dot(filterNoiseGradient(param, param_1, param_2), t);
+    float2 param_3 = float2(lattice1.x, lattice0.y);
+    int param_4 = channel;
+    int param_5 = seed;
+    float n10 = dot(filterNoiseGradient(param_3, param_4, param_5), t - float2(1.0, 0.0));
+    float2 param_6 = float2(lattice0.x, lattice1.y);
+    int param_7 = channel;
+    int param_8 = seed;
+    float n01 = dot(filterNoiseGradient(param_6, param_7, param_8), t - float2(0.0, 1.0));
+    float2 param_9 = lattice1;
+    int param_10 = channel;
+    int param_11 = seed;
+    float n11 = dot(filterNoiseGradient(param_9, param_10, param_11), t - float2(1.0));
+    float2 s = (t * t) * (float2(3.0) - (t * 2.0));
+    return mix(mix(n00, n10, s.x), mix(n01, n11, s.x), s.y);
+}
+
+static inline __attribute__((always_inline))
+float4 filterNoise(thread const float2& colorTexCoord, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+{
+    float2 baseFrequency = filterParams0.xy;
+    int numOctaves = int(filterParams0.z);
+    int seed = int(filterParams0.w);
+    float2 stitchOrigin = filterParams1.xy;
+    float2 period = filterParams1.zw;
+    bool fractalNoise = filterParams2.x != 0.0;
+    float2 position = (colorTexCoord - stitchOrigin) * baseFrequency;
+    float4 sum = float4(0.0);
+    float amplitude = 1.0;
+    for (int octave = 0; octave < numOctaves; octave++)
+    {
+        float2 param = position;
+        float2 param_1 = period;
+        int param_2 = 0;
+        int param_3 = seed;
+        float2 param_4 = position;
+        float2 param_5 = period;
+        int param_6 = 1;
+        int param_7 = seed;
+        float2 param_8 = position;
+        float2 param_9 = period;
+        int param_10 = 2;
+        int param_11 = seed;
+        float2 param_12 = position;
+        float2 param_13 = period;
+        int param_14 = 3;
+        int param_15 = seed;
+        float4 octaveNoise = float4(filterNoisePerlin(param, param_1, param_2, param_3), filterNoisePerlin(param_4, param_5, param_6, param_7), filterNoisePerlin(param_8, param_9, param_10, param_11), filterNoisePerlin(param_12, param_13, param_14, param_15));
+        float4 _2048;
+        if (fractalNoise)
+        {
+            _2048 = octaveNoise;
+        }
+        else
+        {
+            _2048 = abs(octaveNoise);
+        }
+        sum += (_2048 * amplitude);
+        position *= 2.0;
+        period *= 2.0;
+        amplitude *= 0.5;
+    }
+    if (fractalNoise)
+    {
+        sum = (sum * 0.5) + float4(0.5);
+    }
+    return fast::clamp(sum, float4(0.0), float4(1.0));
+}
+
+static inline __attribute__((always_inline))
+float filterPaintEffectLines(thread const float& position, thread const float& spacing, thread const float& lineWidth, thread const float& pixelSize)
+{
+    float dist = abs(fract((position / spacing) + 0.5) - 0.5) * spacing;
+    return fast::clamp((((lineWidth * 0.5) - dist) / pixelSize) + 0.5, 0.0, 1.0);
+}
+
+static inline __attribute__((always_inline))
+float4 filterPaintEffect(thread const float2& colorTexCoord, thread const float4& filterParams0)
+{
+    int kind = int(filterParams0.x);
+    float size = filterParams0.y;
+    float amount = filterParams0.z;
+    float pixelSize = filterParams0.w;
+    float alpha;
+    if (kind == 2)
+    {
+        float2 offset = (fract(colorTexCoord / float2(size)) - float2(0.5)) * size;
+        float radius = size * sqrt(fast::clamp(amount, 0.0, 1.0) / 3.1415927410125732421875);
+        alpha = fast::clamp(((radius - length(offset)) / pixelSize) + 0.5, 0.0, 1.0);
+    }
+    else
+    {
+        float param = colorTexCoord.y;
+        float param_1 = size;
+        float param_2 = amount;
+        float param_3 = pixelSize;
+        alpha = filterPaintEffectLines(param, param_1, param_2, param_3);
+        if (kind == 1)
+        {
+            float param_4 = colorTexCoord.x;
+            float param_5 = size;
+            float param_6 = amount;
+            float param_7 = pixelSize;
+            alpha = fast::max(alpha, filterPaintEffectLines(param_4, param_5, param_6, param_7));
+        }
+    }
+    return float4(float3(1.0), alpha);
+}
+
 static inline __attribute__((always_inline))
 float4 sampleColor(thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
 {
@@ -358,9 +560,30 @@ float4 filterColor(thread const float2& colorTexCoord, thread const texture2d<fl
             float4 param_20 = filterParams4;
             return filterColorMatrix(param_15, colorTexture, colorTextureSmplr, param_16, param_17, param_18, param_19, param_20);
         }
+        case 5:
+        {
+            float2 param_21 = colorTexCoord;
+            float4 param_22 = filterParams0;
+            float4 param_23 = filterParams1;
+            float4 param_24 = filterParams2;
+            return filterNoise(param_21, param_22, param_23, param_24);
+        }
+        case 6:
+        {
+            float2 param_26 = colorTexCoord;
+            float4 param_27 = filterParams0;
+            return filterPaintEffect(param_26, param_27);
+        }
+        case 7:
+        {
+            float2 param_28 = colorTexCoord;
+            float4 param_29 = filterParams0;
+            float4 param_30 = filterParams1;
+            return filterConicGradient(param_28, colorTexture, colorTextureSmplr, param_29, param_30);
+        }
     }
-    float2 param_21 = colorTexCoord;
-    return filterNone(param_21, colorTexture, colorTextureSmplr);
+    float2 param_25 = colorTexCoord;
+    return filterNone(param_25, colorTexture, colorTextureSmplr);
 }
 
 static inline __attribute__((always_inline))
@@ -437,7 +660,7 @@ float3 compositeColorDodge(thread const float3& destColor, thread const float3&
     bool3 srcOne = srcColor == float3(1.0);
     bool3 param = srcOne;
     float3 param_1 = float3(1.0);
-    float3 param_2 = destColor / (float3(1.0) - srcColor);
+    float3 param_2 = fast::min(destColor / (float3(1.0) - srcColor), float3(1.0));
     bool3 param_3 = destZero;
     float3 param_4 = float3(0.0);
     float3 param_5 = compositeSelect(param, param_1, param_2);
@@ -459,85 +682,60 @@ float3 compositeSoftLight(thread const float3& destColor, thread const float3& s
 }
 
 static inline __attribute__((always_inline))
-float compositeDivide(thread const float& num, thread const float& denom)
+float compositeLum(thread const float3& color)
 {
-    float _869;
-    if (denom != 0.0)
-    {
-        _869 = num / denom;
-    }
-    else
-    {
-        _869 = 0.0;
-    }
-    return _869;
+    return dot(color, float3(0.300000011920928955078125, 0.589999973773956298828125, 0.10999999940395355224609375));
 }
 
 static inline __attribute__((always_inline))
-float3 compositeRGBToHSL(thread const float3& rgb)
+float3 compositeClipColor(thread float3& color)
 {
-    float v = fast::max(fast::max(rgb.x, rgb.y), rgb.z);
-    float xMin = fast::min(fast::min(rgb.x, rgb.y), rgb.z);
-    float c = v - xMin;
-    float l = mix(xMin, v, 0.5);
-    float3 _975;
-    if (rgb.x == v)
+    float3 param = color;
+    float l = compositeLum(param);
+    float n = fast::min(fast::min(color.x, color.y), color.z);
+    float x = fast::max(fast::max(color.x, color.y), color.z);
+    if (n < 0.0)
     {
-        _975 = float3(0.0, rgb.yz);
+        color = float3(l) + (((color - float3(l)) * l) / float3(l - n));
     }
-    else
+    if (x > 1.0)
     {
-        float3 _988;
-        if (rgb.y == v)
-        {
-            _988 = float3(2.0, rgb.zx);
-        }
-        else
-        {
-            _988 = float3(4.0, rgb.xy);
-        }
-        _975 = _988;
+        color = float3(l) + (((color - float3(l)) * (1.0 - l)) / float3(x - l));
     }
-    float3 terms = _975;
-    float param = ((terms.x * c) + terms.y) - terms.z;
-    float param_1 = c;
-    float h = 1.0471975803375244140625 * compositeDivide(param, param_1);
-    float param_2 = c;
-    float param_3 = v;
-    float s = compositeDivide(param_2, param_3);
-    return float3(h, s, l);
+    return color;
 }
 
 static inline __attribute__((always_inline))
-float3 compositeHSL(thread const float3& destColor, thread const float3& srcColor, thread const int& op)
+float3 compositeSetLum(thread const float3& color, thread const float& l)
 {
-    switch (op)
-    {
-        case 12:
-        {
-            return float3(srcColor.x, destColor.y, destColor.z);
-        }
-        case 13:
-        {
-            return float3(destColor.x, srcColor.y, destColor.z);
-        }
-        case 14:
-        {
-            return float3(srcColor.x, srcColor.y, destColor.z);
-        }
-        default:
-        {
-            return float3(destColor.x, destColor.y, srcColor.z);
-        }
-    }
+    float3 param = color;
+    float3 param_1 = color + float3(l - compositeLum(param));
+    float3 _clipped = compositeClipColor(param_1);
+    return _clipped;
 }
 
 static inline __attribute__((always_inline))
-float3 compositeHSLToRGB(thread const float3& hsl)
+float compositeSat(thread const float3& color)
 {
-    float a = hsl.y * fast::min(hsl.z, 1.0 - hsl.z);
-    float3 ks = mod(float3(0.0, 8.0, 4.0) + float3(hsl.x * 1.90985929965972900390625), float3(12.0));
-    return hsl.zzz - (fast::clamp(fast::min(ks - float3(3.0), float3(9.0) - ks), float3(-1.0), float3(1.0)) * a);
+    return fast::max(fast::max(color.x, color.y), color.z) - fast::min(fast::min(color.x, color.y), color.z);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeSetSat(thread const float3& color, thread const float& s)
+{
+    float colorMin = fast::min(fast::min(color.x, color.y), color.z);
+    float3 param = color;
+    float colorSat = compositeSat(param);
+    float3 _saturated;
+    if (colorSat > 0.0)
+    {
+        _saturated = ((color - float3(colorMin)) * s) / float3(colorSat);
+    }
+    else
+    {
+        _saturated = float3(0.0);
+    }
+    return _saturated;
 }
 
 static inline __attribute__((always_inline))
@@ -602,22 +800,71 @@ float3 compositeRGB(thread const float3& destColor, thread const float3& srcColo
             return (destColor + srcColor) - ((float3(2.0) * destColor) * srcColor);
         }
         case 12:
+        {
+            float3 param_12 = destColor;
+            float3 param_13 = srcColor;
+            float param_14 = compositeSat(param_12);
+            float3 param_15 = compositeSetSat(param_13, param_14);
+            float param_16 = compositeLum(param_12);
+            return compositeSetLum(param_15, param_16);
+        }
         case 13:
+        {
+            float3 param_17 = srcColor;
+            float3 param_18 = destColor;
+            float param_19 = compositeSat(param_17);
+            float3 param_20 = compositeSetSat(param_18, param_19);
+            float param_21 = compositeLum(param_18);
+            return compositeSetLum(param_20, param_21);
+        }
         case 14:
+        {
+            float3 param_22 = destColor;
+            float3 param_23 = srcColor;
+            float param_24 = compositeLum(param_22);
+            return compositeSetLum(param_23, param_24);
+        }
         case 15:
         {
-            float3 param_12 = destColor;
-            float3 param_13 = srcColor;
-            float3 param_14 = compositeRGBToHSL(param_12);
-            float3 param_15 = compositeRGBToHSL(param_13);
-            int param_16 = op;
-            float3 param_17 = compositeHSL(param_14, param_15, param_16);
-            return compositeHSLToRGB(param_17);
+            float3 param_25 = srcColor;
+            float3 param_26 = destColor;
+            float param_27 = compositeLum(param_25);
+            return compositeSetLum(param_26, param_27);
         }
     }
     return srcColor;
 }
 
+static inline __attribute__((always_inline))
+float4 compositeColors(thread const float4& srcColor, thread const float4& destColor, thread const int& op)
+{
+    float3 destRGB;
+    if (destColor.w > 0.0)
+    {
+        destRGB = destColor.xyz / float3(destColor.w);
+    }
+    else
+    {
+        destRGB = float3(0.0);
+    }
+    float3 param = destRGB;
+    float3 param_1 = srcColor.xyz;
+    int param_2 = op;
+    float3 blendedRGB = compositeRGB(param, param_1, param_2);
+    float3 resultRGB = ((srcColor.xyz * (srcColor.w * (1.0 - destColor.w))) + (blendedRGB * (srcColor.w * destColor.w))) + (destColor.xyz * (1.0 - srcColor.w));
+    float resultAlpha = srcColor.w + (destColor.w * (1.0 - srcColor.w));
+    float4 _result;
+    if (resultAlpha > 0.0)
+    {
+        _result = float4(resultRGB / float3(resultAlpha), resultAlpha);
+    }
+    else
+    {
+        _result = float4(0.0);
+    }
+    return _result;
+}
+
 static inline __attribute__((always_inline))
 float4 composite(thread const float4& srcColor, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const float2& destTextureSize, thread const float2& fragCoord, thread const int& op)
 {
@@ -626,58 +873,86 @@ float4 composite(thread const float4& srcColor, thread const texture2d<float> de
         return srcColor;
     }
     float2 destTexCoord = fragCoord / destTextureSize;
-    float4 destColor = destTexture.sample(destTextureSmplr, destTexCoord, level(0.0));
-    float3 param = destColor.xyz;
-    float3 param_1 = srcColor.xyz;
+    float4 destColor = destTexture.sample(destTextureSmplr, destTexCoord);
+    float4 param = srcColor;
+    float4 param_1 = destColor;
     int param_2 = op;
-    float3 blendedRGB = compositeRGB(param, param_1, param_2);
-    return float4(((srcColor.xyz * (srcColor.w * (1.0 - destColor.w))) + (blendedRGB * (srcColor.w * destColor.w))) + (destColor.xyz * (1.0 - srcColor.w)), 1.0);
+    return compositeColors(param, param_1, param_2);
 }
 
 static inline __attribute__((always_inline))
-float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float4& filterParams3, thread const float4& filterParams4, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& baseColor, thread const int& tileCtrl)
+float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float4& filterParams3, thread const float4& filterParams4, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& baseColor, thread const int& tileCtrl, thread float3& channelAlphas)
 {
     int maskCtrl0 = (tileCtrl >> 0) & 3;
-    float maskAlpha = 1.0;
-    float param = maskAlpha;
-    float2 param_1 = maskTextureSize0;
-    float3 param_2 = maskTexCoord0;
-    int param_3 = maskCtrl0;
-    maskAlpha = sampleMask(param, maskTexture0, maskTexture0Smplr, param_1, param_2, param_3);
+    bool subpixelCoverage = (tileCtrl & 4) != 0;
+    float3 maskAlphas;
+    if (subpixelCoverage)
+    {
+        float2 param = maskTextureSize0;
+        float3 param_1 = maskTexCoord0;
+        int param_2 = maskCtrl0;
+        maskAlphas = sampleSubpixelMask(maskTexture0, maskTexture0Smplr, param, param_1, param_2);
+    }
+    else
+    {
+        float maskAlpha = 1.0;
+        float param_3 = maskAlpha;
+        float2 param_4 = maskTextureSize0;
+        float3 param_5 = maskTexCoord0;
+        int param_6 = maskCtrl0;
+        maskAlpha = sampleMask(param_3, maskTexture0, maskTexture0Smplr, param_4, param_5, param_6);
+        maskAlphas = float3(maskAlpha);
+    }
     float4 color = baseColor;
     int color0Combine = (ctrl >> 8) & 3;
     if (color0Combine != 0)
     {
         int color0Filter = (ctrl >> 4) & 15;
-        float2 param_4 = colorTexCoord0;
-        float2 param_5 = colorTextureSize0;
-        float2 param_6 = fragCoord;
-        float2 param_7 = framebufferSize;
-        float4 param_8 = filterParams0;
-        float4 param_9 = filterParams1;
-        float4 param_10 = filterParams2;
-        float4 param_11 = filterParams3;
-        float4 param_12 = filterParams4;
-        int param_13 = color0Filter;
-        float4 color0 = filterColor(param_4, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_5, param_6, param_7, param_8, param_9, param_10, param_11, param_12, param_13);
-        float4 param_14 = color;
-        float4 param_15 = color0;
-        int param_16 = color0Combine;
-        color = combineColor0(param_14, param_15, param_16);
-    }
-    color.w *= maskAlpha;
-    int compositeOp = (ctrl >> 10) & 15;
-    float4 param_17 = color;
-    float2 param_18 = framebufferSize;
-    float2 param_19 = fragCoord;
-    int param_20 = compositeOp;
-    color = composite(param_17, destTexture, destTextureSmplr, param_18, param_19, param_20);
-    float3 _1435 = color.xyz * color.w;
+        float2 param_7 = colorTexCoord0;
+        float2 param_8 = colorTextureSize0;
+        float2 param_9 = fragCoord;
+        float2 param_10 = framebufferSize;
+        float4 param_11 = filterParams0;
+        float4 param_12 = filterParams1;
+        float4 param_13 = filterParams2;
+        float4 param_14 = filterParams3;
+        float4 param_15 = filterParams4;
+        int param_16 = color0Filter;
+        float4 color0 = filterColor(param_7, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_8, param_9, param_10, param_11, param_12, param_13, param_14, param_15, param_16);
+        float4 param_17 = color;
+        float4 param_18 = color0;
+        int param_19 = color0Combine;
+        color = combineColor0(param_17, param_18, param_19);
+    }
+    channelAlphas = maskAlphas * color.w;
+    color.w = channelAlphas.y;
+    if (!subpixelCoverage)
+    {
+        int compositeOp = (ctrl >> 10) & 15;
+        float4 param_20 = color;
+        float2 param_21 = framebufferSize;
+        float2 param_22 = fragCoord;
+        int param_23 = compositeOp;
+        color = composite(param_20, destTexture, destTextureSmplr, param_21, param_22, param_23);
+        channelAlphas = float3(color.w);
+    }
+    float3 _1435 = color.xyz * channelAlphas;
     color = float4(_1435.x, _1435.y, _1435.z, color.w);
     return color;
 }
 
-kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant int& uLoadAction [[buffer(4)]], constant int2& uTextureMetadataSize [[buffer(7)]], constant float2& uFramebufferSize [[buffer(0)]], constant float2& uTileSize [[buffer(1)]], constant float4& uClearColor [[buffer(5)]], constant float2& uColorTextureSize0 [[buffer(8)]], constant float2& uMaskTextureSize0 [[buffer(9)]], const device bFirstTileMap& _1599 [[buffer(2)]], const device bTiles& _1690 [[buffer(6)]], texture2d<float, access::read_write> uDestImage [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], texture2d<float> uColorTexture0 [[texture(2)]], texture2d<float> uMaskTexture0 [[texture(3)]], texture2d<float> uGammaLUT [[texture(4)]], sampler uTextureMetadataSmplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uMaskTexture0Smplr [[sampler(2)]], sampler uGammaLUTSmplr [[sampler(3)]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]])
+static inline __attribute__((always_inline))
+float4 ditherColor(thread const float4& color, thread const float2& fragCoord)
+{
+    int2 position = int2(fragCoord) & int2(3);
+    int diagonal = position.x ^ position.y;
+    int index = ((((diagonal & 1) << 3) | ((position.y & 1) << 2)) | (diagonal & 2)) | ((position.y >> 1) & 1);
+    float threshold = ((float(index) + 0.5) / 16.0) - 0.5;
+    float3 _dither = color.xyz + float3((threshold * color.w) / 255.0);
+    return float4(_dither, color.w);
+}
+
+kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant int& uLoadAction [[buffer(4)]], constant int2& uTextureMetadataSize [[buffer(7)]], constant float2& uFramebufferSize [[buffer(0)]], constant float2& uTileSize [[buffer(1)]], constant float4& uClearColor [[buffer(5)]], constant float2& uColorTextureSize0 [[buffer(8)]], constant float2& uMaskTextureSize0 [[buffer(9)]], constant int& uDither [[buffer(10)]], const device bFirstTileMap& _1599 [[buffer(2)]], const device bTiles& _1690 [[buffer(6)]], texture2d<float, access::read_write> uDestImage [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], texture2d<float> uColorTexture0 [[texture(2)]], texture2d<float> uMaskTexture0 [[texture(3)]], texture2d<float> uGammaLUT [[texture(4)]], sampler uTextureMetadataSmplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uMaskTexture0Smplr [[sampler(2)]], sampler uGammaLUTSmplr [[sampler(3)]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]])
 {
     int2 tileCoord = int2(gl_WorkGroupID.xy);
     int2 firstTileSubCoord = int2(gl_LocalInvocationID.xy) * int2(1, 4);
@@ -745,6 +1020,12 @@ kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant in
             float4 filterParams3 = param_9;
             float4 filterParams4 = param_10;
             int ctrl = param_11;
+            int compositeOp = (ctrl >> 10) & 15;
+            ctrl &= (-15361);
+            if ((tileCtrl & 4) != 0)
+            {
+                compositeOp = 0;
+            }
             float2 param_12 = fragCoord;
             float2 param_13 = uColorTextureSize0;
             float2 param_14 = uMaskTextureSize0;
@@ -759,15 +1040,47 @@ kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant in
             float2 param_23 = colorTexCoord0;
             float4 param_24 = baseColor;
             int param_25 = tileCtrl;
-            float4 srcColor = calculateColor(param_12, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, param_13, param_14, param_15, param_16, param_17, param_18, param_19, param_20, param_21, param_22, param_23, param_24, param_25);
-            destColors[subY_1] = (destColors[subY_1] * (1.0 - srcColor.w)) + srcColor;
+            float3 param_26;
+            float4 _1871 = calculateColor(param_12, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uColorTexture0, uColorTexture0Smplr, uGammaLUT, uGammaLUTSmplr, param_13, param_14, param_15, param_16, param_17, param_18, param_19, param_20, param_21, param_22, param_23, param_24, param_25, param_26);
+            float3 channelAlphas = param_26;
+            float4 srcColor = _1871;
+            if (compositeOp == 0)
+            {
+                destColors[subY_1] = (destColors[subY_1] * (float4(1.0) - float4(channelAlphas, srcColor.w))) + srcColor;
+            }
+            else
+            {
+                float4 _unpremultiplied;
+                if (srcColor.w > 0.0)
+                {
+                    _unpremultiplied = float4(srcColor.xyz / float3(srcColor.w), srcColor.w);
+                }
+                else
+                {
+                    _unpremultiplied = float4(0.0);
+                }
+                float4 unpremultipliedSrcColor = _unpremultiplied;
+                float4 param_27 = unpremultipliedSrcColor;
+                float4 param_28 = destColors[subY_1];
+                int param_29 = compositeOp;
+                float4 blendedColor = compositeColors(param_27, param_28, param_29);
+                destColors[subY_1] = float4(blendedColor.xyz * blendedColor.w, blendedColor.w);
+            }
         }
         tileIndex = int(_1690.iTiles[(tileIndex * 4) + 0]);
     }
     for (int subY_2 = 0; subY_2 < 4; subY_2++)
     {
-        int2 param_26 = firstFragCoord + int2(0, subY_2);
-        uDestImage.write(destColors[subY_2], uint2(toImageCoords(param_26, uFramebufferSize)));
+        int2 destCoord = firstFragCoord + int2(0, subY_2);
+        float4 destColor = destColors[subY_2];
+        if (uDither != 0)
+        {
+            float4 param_30 = destColor;
+            float2 param_31 = float2(destCoord);
+            destColor = ditherColor(param_30, param_31);
+        }
+        int2 param_32 = destCoord;
+        uDestImage.write(destColor, uint2(toImageCoords(param_32, uFramebufferSize)));
     }
 }
 
@@ -31,12 +31,42 @@ float4 computeCoverage(thread const float2& from, thread const float2& to, threa
     return areaLUT.sample(areaLUTSmplr, (float2(y + 8.0, abs(d * dX)) / float2(16.0))) * dX;
 }
 
-fragment main0_out main0(main0_in in [[stage_in]], texture2d<float> uAreaLUT [[texture(0)]], sampler uAreaLUTSmplr [[sampler(0)]])
+constant float2 _samplePositions[8] = { float2(1.0, -3.0), float2(-1.0, 3.0), float2(5.0, 1.0), float2(-3.0, -5.0), float2(-5.0, 5.0), float2(-7.0, -1.0), float2(3.0, 7.0), float2(7.0, -7.0) };
+
+static inline __attribute__((always_inline))
+float4 computeCoverageSupersample(thread const float2& from, thread const float2& to)
+{
+    float2 left = select(to, from, bool2(from.x < to.x));
+    float2 right = select(from, to, bool2(from.x < to.x));
+    float4 coverages = float4(0.0);
+    for (int sampleIndex = 0; sampleIndex < 8; sampleIndex++)
+    {
+        float2 samplePosition = _samplePositions[sampleIndex] / float2(16.0);
+        if ((samplePosition.x < left.x) || (samplePosition.x >= right.x))
+        {
+            continue;
+        }
+        float t = (samplePosition.x - left.x) / (right.x - left.x);
+        float y = mix(left.y, right.y, t);
+        coverages += step(float4(y), float4(samplePosition.y) + float4(0.0, 1.0, 2.0, 3.0));
+    }
+    float winding = (from.x < to.x) ? (-1.0) : 1.0;
+    return (coverages * winding) / float4(8.0);
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant int& uSupersample [[buffer(0)]], texture2d<float> uAreaLUT [[texture(0)]], sampler uAreaLUTSmplr [[sampler(0)]])
 {
     main0_out out = {};
     float2 param = in.vFrom;
     float2 param_1 = in.vTo;
-    out.oFragColor = computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+    if (uSupersample != 0)
+    {
+        out.oFragColor = computeCoverageSupersample(param, param_1);
+    }
+    else
+    {
+        out.oFragColor = computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+    }
     return out;
 }
 
@@ -88,7 +88,7 @@ void computeTileVaryings(thread const float2& position, thread const int& colorE
     outCtrl = int(extra.x);
 }
 
-vertex main0_out main0(main0_in in [[stage_in]], constant int2& uZBufferSize [[buffer(1)]], constant int2& uTextureMetadataSize [[buffer(2)]], constant float2& uTileSize [[buffer(0)]], constant float4x4& uTransform [[buffer(3)]], texture2d<float> uZBuffer [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], sampler uZBufferSmplr [[sampler(0)]], sampler uTextureMetadataSmplr [[sampler(1)]])
+vertex main0_out main0(main0_in in [[stage_in]], constant int2& uZBufferSize [[buffer(1)]], constant int2& uTextureMetadataSize [[buffer(2)]], constant float2& uTileSize [[buffer(0)]], constant float4x4& uTransform [[buffer(3)]], constant int& uPathDepthEnabled [[buffer(4)]], constant int2& uPathDepthPlanesSize [[buffer(5)]], texture2d<float> uZBuffer [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], texture2d<float> uPathDepthPlanes [[texture(2)]], sampler uZBufferSmplr [[sampler(0)]], sampler uTextureMetadataSmplr [[sampler(1)]], sampler uPathDepthPlanesSmplr [[sampler(2)]])
 {
     main0_out out = {};
     float2 tileOrigin = float2(in.aTileOrigin);
@@ -141,6 +141,13 @@ vertex main0_out main0(main0_in in [[stage_in]], constant int2& uZBufferSize [[b
     out.vCtrl = float(ctrl);
     out.vMaskTexCoord0 = float3(maskTexCoord0, float(in.aCtrlBackdrop.y));
     out.gl_Position = uTransform * float4(position, 0.0, 1.0);
+    if (uPathDepthEnabled != 0)
+    {
+        float2 planeCoord = float2(float(in.aPathIndex % uPathDepthPlanesSize.x), float(in.aPathIndex / uPathDepthPlanesSize.x));
+        float2 planeTexCoord = (planeCoord + float2(0.5)) / float2(uPathDepthPlanesSize);
+        float4 plane = uPathDepthPlanes.sample(uPathDepthPlanesSmplr, planeTexCoord, level(0.0));
+        out.gl_Position.z = dot(plane.xyz, float3(position, 1.0));
+    }
     return out;
 }
 
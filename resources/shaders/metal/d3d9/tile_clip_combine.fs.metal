@@ -1,4 +1,6 @@
 // Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
 #include <metal_stdlib>
 #include <simd/simd.h>
 
@@ -13,14 +15,35 @@ struct main0_in
 {
     float2 vTexCoord0 [[user(locn0)]];
     float vBackdrop0 [[user(locn1)]];
-    float2 vTexCoord1 [[user(locn2)]];
-    float vBackdrop1 [[user(locn3)]];
+    float vEvenOdd0 [[user(locn2)]];
+    float2 vTexCoord1 [[user(locn3)]];
+    float vBackdrop1 [[user(locn4)]];
+    float vEvenOdd1 [[user(locn5)]];
 };
 
+// Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
+template<typename Tx, typename Ty>
+inline Tx mod(Tx x, Ty y)
+{
+    return x - y * floor(x / y);
+}
+
+static inline __attribute__((always_inline))
+float4 resolveCoverage(thread const float4& winding, thread const float& evenOdd)
+{
+    float4 nonzeroCoverage = fast::min(abs(winding), float4(1.0));
+    float4 evenOddCoverage = float4(1.0) - abs(float4(1.0) - mod(winding, float4(2.0)));
+    return mix(nonzeroCoverage, evenOddCoverage, float4(evenOdd));
+}
+
 fragment main0_out main0(main0_in in [[stage_in]], texture2d<float> uSrc [[texture(0)]], sampler uSrcSmplr [[sampler(0)]])
 {
     main0_out out = {};
-    out.oFragColor = fast::min(abs(uSrc.sample(uSrcSmplr, in.vTexCoord0) + float4(in.vBackdrop0)), abs(uSrc.sample(uSrcSmplr, in.vTexCoord1) + float4(in.vBackdrop1)));
+    float4 param = uSrc.sample(uSrcSmplr, in.vTexCoord0) + float4(in.vBackdrop0);
+    float param_1 = in.vEvenOdd0;
+    float4 param_2 = uSrc.sample(uSrcSmplr, in.vTexCoord1) + float4(in.vBackdrop1);
+    float param_3 = in.vEvenOdd1;
+    out.oFragColor = fast::min(resolveCoverage(param, param_1), resolveCoverage(param_2, param_3));
     return out;
 }
 
@@ -8,8 +8,10 @@ struct main0_out
 {
     float2 vTexCoord0 [[user(locn0)]];
     float vBackdrop0 [[user(locn1)]];
-    float2 vTexCoord1 [[user(locn2)]];
-    float vBackdrop1 [[user(locn3)]];
+    float vEvenOdd0 [[user(locn2)]];
+    float2 vTexCoord1 [[user(locn3)]];
+    float vBackdrop1 [[user(locn4)]];
+    float vEvenOdd1 [[user(locn5)]];
     float4 gl_Position [[position]];
 };
 
@@ -18,8 +20,10 @@ struct main0_in
     int2 aTileOffset [[attribute(0)]];
     int aDestTileIndex [[attribute(1)]];
     int aDestBackdrop [[attribute(2)]];
-    int aSrcTileIndex [[attribute(3)]];
-    int aSrcBackdrop [[attribute(4)]];
+    int aDestCtrl [[attribute(3)]];
+    int aSrcTileIndex [[attribute(4)]];
+    int aSrcBackdrop [[attribute(5)]];
+    int aSrcCtrl [[attribute(6)]];
 };
 
 vertex main0_out main0(main0_in in [[stage_in]], constant float2& uFramebufferSize [[buffer(0)]])
@@ -33,6 +37,8 @@ vertex main0_out main0(main0_in in [[stage_in]], constant float2& uFramebufferSi
     out.vTexCoord1 = srcPosition;
     out.vBackdrop0 = float(in.aDestBackdrop);
     out.vBackdrop1 = float(in.aSrcBackdrop);
+    out.vEvenOdd0 = (in.aDestCtrl == 2) ? 1.0 : 0.0;
+    out.vEvenOdd1 = (in.aSrcCtrl == 2) ? 1.0 : 0.0;
     if (in.aDestTileIndex < 0)
     {
         destPosition = float2(0.0);
@@ -0,0 +1,913 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#pragma clang diagnostic ignored "-Wmissing-prototypes"
+
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+constant float3 _1121 = {};
+
+struct main0_out
+{
+    float4 oFragColor [[color(0), index(0)]];
+    float4 oFragBlend [[color(0), index(1)]];
+};
+
+struct main0_in
+{
+    float3 vMaskTexCoord0 [[user(locn0)]];
+    float2 vColorTexCoord0 [[user(locn1)]];
+    float4 vBaseColor [[user(locn2)]];
+    float vTileCtrl [[user(locn3)]];
+    float4 vFilterParams0 [[user(locn4)]];
+    float4 vFilterParams1 [[user(locn5)]];
+    float4 vFilterParams2 [[user(locn6)]];
+    float4 vFilterParams3 [[user(locn7)]];
+    float4 vFilterParams4 [[user(locn8)]];
+    float vCtrl [[user(locn9)]];
+};
+
+// Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
+template<typename Tx, typename Ty>
+inline Tx mod(Tx x, Ty y)
+{
+    return x - y * floor(x / y);
+}
+
+static inline __attribute__((always_inline))
+float fetchMaskCoverage(thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const int2& maskTexCoordI, thread const float& backdrop, thread const int& maskCtrl)
+{
+    float4 texel = maskTexture.sample(maskTextureSmplr, ((float2(maskTexCoordI / int2(1, 4)) + float2(0.5)) / maskTextureSize));
+    float coverage = texel[maskTexCoordI.y % 4] + backdrop;
+    if ((maskCtrl & 1) != 0)
+    {
+        return abs(coverage);
+    }
+    return 1.0 - abs(1.0 - mod(coverage, 2.0));
+}
+
+static inline __attribute__((always_inline))
+float sampleMask(thread const float& maskAlpha, thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl)
+{
+    if (maskCtrl == 0)
+    {
+        return maskAlpha;
+    }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    float2 param = maskTextureSize;
+    int2 param_1 = maskTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float coverage = fetchMaskCoverage(maskTexture, maskTextureSmplr, param, param_1, param_2, param_3);
+    return fast::min(maskAlpha, coverage);
+}
+
+static inline __attribute__((always_inline))
+float3 sampleSubpixelMask(thread const texture2d<float> maskTexture, thread const sampler maskTextureSmplr, thread const float2& maskTextureSize, thread const float3& maskTexCoord, thread const int& maskCtrl)
+{
+    if (maskCtrl == 0)
+    {
+        return float3(1.0);
+    }
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    int tileX = maskTexCoordI.x % 16;
+    int2 leftTexCoordI = maskTexCoordI - int2((tileX > 0) ? 1 : 0, 0);
+    int2 rightTexCoordI = maskTexCoordI + int2((tileX < 15) ? 1 : 0, 0);
+    float2 param = maskTextureSize;
+    int2 param_1 = leftTexCoordI;
+    float param_2 = maskTexCoord.z;
+    int param_3 = maskCtrl;
+    float left = fetchMaskCoverage(maskTexture, maskTextureSmplr, param, param_1, param_2, param_3);
+    float2 param_4 = maskTextureSize;
+    int2 param_5 = maskTexCoordI;
+    float param_6 = maskTexCoord.z;
+    int param_7 = maskCtrl;
+    float center = fetchMaskCoverage(maskTexture, maskTextureSmplr, param_4, param_5, param_6, param_7);
+    float2 param_8 = maskTextureSize;
+    int2 param_9 = rightTexCoordI;
+    float param_10 = maskTexCoord.z;
+    int param_11 = maskCtrl;
+    float right = fetchMaskCoverage(maskTexture, maskTextureSmplr, param_8, param_9, param_10, param_11);
+    float3 coverage = float3(mix(center, left, 0.3333333432674407958984375), center, mix(center, right, 0.3333333432674407958984375));
+    return fast::min(float3(1.0), coverage);
+}
+    int2 maskTexCoordI = int2(floor(maskTexCoord.xy));
+    float4 texel = maskTexture.sample(maskTextureSmplr, ((float2(maskTexCoordI / int2(1, 4)) + float2(0.5)) / maskTextureSize));
+    float coverage = texel[maskTexCoordI.y % 4] + maskTexCoord.z;
+    if ((maskCtrl & 1) != 0)
+    {
+        coverage = abs(coverage);
+    }
+    else
+    {
+        coverage = 1.0 - abs(1.0 - mod(coverage, 2.0));
+    }
+    return fast::min(maskAlpha, coverage);
+}
+
+static inline __attribute__((always_inline))
+float4 filterRadialGradient(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTextureSize, thread const float2& fragCoord, thread const float2& framebufferSize, thread const float4& filterParams0, thread const float4& filterParams1)
+{
+    float2 lineFrom = filterParams0.xy;
+    float2 lineVector = filterParams0.zw;
+    float2 radii = filterParams1.xy;
+    float2 uvOrigin = filterParams1.zw;
+    float2 dP = colorTexCoord - lineFrom;
+    float2 dC = lineVector;
+    float dR = radii.y - radii.x;
+    float a = dot(dC, dC) - (dR * dR);
+    float b = dot(dP, dC) + (radii.x * dR);
+    float c = dot(dP, dP) - (radii.x * radii.x);
+    float discrim = (b * b) - (a * c);
+    float4 color = float4(0.0);
+    if (discrim != 0.0)
+    {
+        float2 ts = float2((float2(1.0, -1.0) * sqrt(discrim)) + float2(b)) / float2(a);
+        if (ts.x > ts.y)
+        {
+            ts = ts.yx;
+        }
+        float _581;
+        if (ts.x >= 0.0)
+        {
+            _581 = ts.x;
+        }
+        else
+        {
+            _581 = ts.y;
+        }
+        float t = _581;
+        color = colorTexture.sample(colorTextureSmplr, (uvOrigin + float2(t, 0.0)));
+    }
+    return color;
+}
+
+static inline __attribute__((always_inline))
+float4 filterBlur(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0, thread const float4& filterParams1)
+{
+    float2 srcOffsetScale = filterParams0.xy / colorTextureSize;
+    int support = int(filterParams0.z);
+    float3 gaussCoeff = filterParams1.xyz;
+    float gaussSum = gaussCoeff.x;
+    float4 color = colorTexture.sample(colorTextureSmplr, colorTexCoord) * gaussCoeff.x;
+    float2 _625 = gaussCoeff.xy * gaussCoeff.yz;
+    gaussCoeff = float3(_625.x, _625.y, gaussCoeff.z);
+    for (int i = 1; i <= support; i += 2)
+    {
+        float gaussPartialSum = gaussCoeff.x;
+        float2 _645 = gaussCoeff.xy * gaussCoeff.yz;
+        gaussCoeff = float3(_645.x, _645.y, gaussCoeff.z);
+        gaussPartialSum += gaussCoeff.x;
+        float2 srcOffset = srcOffsetScale * (float(i) + (gaussCoeff.x / gaussPartialSum));
+        color += ((colorTexture.sample(colorTextureSmplr, (colorTexCoord - srcOffset)) + colorTexture.sample(colorTextureSmplr, (colorTexCoord + srcOffset))) * gaussPartialSum);
+        gaussSum += (2.0 * gaussPartialSum);
+        float2 _685 = gaussCoeff.xy * gaussCoeff.yz;
+        gaussCoeff = float3(_685.x, _685.y, gaussCoeff.z);
+    }
+    return color / float4(gaussSum);
+}
+
+static inline __attribute__((always_inline))
+float filterTextSample1Tap(thread const float& offset, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
+{
+    return colorTexture.sample(colorTextureSmplr, (colorTexCoord + float2(offset, 0.0))).x;
+}
+
+static inline __attribute__((always_inline))
+void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCenter, thread float4& outAlphaRight, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord, thread const float4& kernel0, thread const float& onePixel)
+{
+    bool wide = kernel0.x > 0.0;
+    float _264;
+    if (wide)
+    {
+        float param = (-4.0) * onePixel;
+        float2 param_1 = colorTexCoord;
+        _264 = filterTextSample1Tap(param, colorTexture, colorTextureSmplr, param_1);
+    }
+    else
+    {
+        _264 = 0.0;
+    }
+    float param_2 = (-3.0) * onePixel;
+    float2 param_3 = colorTexCoord;
+    float param_4 = (-2.0) * onePixel;
+    float2 param_5 = colorTexCoord;
+    float param_6 = (-1.0) * onePixel;
+    float2 param_7 = colorTexCoord;
+    outAlphaLeft = float4(_264, filterTextSample1Tap(param_2, colorTexture, colorTextureSmplr, param_3), filterTextSample1Tap(param_4, colorTexture, colorTextureSmplr, param_5), filterTextSample1Tap(param_6, colorTexture, colorTextureSmplr, param_7));
+    float param_8 = 0.0;
+    float2 param_9 = colorTexCoord;
+    outAlphaCenter = filterTextSample1Tap(param_8, colorTexture, colorTextureSmplr, param_9);
+    float param_10 = 1.0 * onePixel;
+    float2 param_11 = colorTexCoord;
+    float param_12 = 2.0 * onePixel;
+    float2 param_13 = colorTexCoord;
+    float param_14 = 3.0 * onePixel;
+    float2 param_15 = colorTexCoord;
+    float _324;
+    if (wide)
+    {
+        float param_16 = 4.0 * onePixel;
+        float2 param_17 = colorTexCoord;
+        _324 = filterTextSample1Tap(param_16, colorTexture, colorTextureSmplr, param_17);
+    }
+    else
+    {
+        _324 = 0.0;
+    }
+    outAlphaRight = float4(filterTextSample1Tap(param_10, colorTexture, colorTextureSmplr, param_11), filterTextSample1Tap(param_12, colorTexture, colorTextureSmplr, param_13), filterTextSample1Tap(param_14, colorTexture, colorTextureSmplr, param_15), _324);
+}
+
+static inline __attribute__((always_inline))
+float filterTextConvolve7Tap(thread const float4& alpha0, thread const float3& alpha1, thread const float4& kernel0)
+{
+    return dot(alpha0, kernel0) + dot(alpha1, kernel0.zyx);
+}
+
+static inline __attribute__((always_inline))
+float filterTextGammaCorrectChannel(thread const float& bgColor, thread const float& fgColor, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr)
+{
+    return gammaLUT.sample(gammaLUTSmplr, float2(fgColor, 1.0 - bgColor)).x;
+}
+
+static inline __attribute__((always_inline))
+float3 filterTextGammaCorrect(thread const float3& bgColor, thread const float3& fgColor, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr)
+{
+    float param = bgColor.x;
+    float param_1 = fgColor.x;
+    float param_2 = bgColor.y;
+    float param_3 = fgColor.y;
+    float param_4 = bgColor.z;
+    float param_5 = fgColor.z;
+    return float3(filterTextGammaCorrectChannel(param, param_1, gammaLUT, gammaLUTSmplr), filterTextGammaCorrectChannel(param_2, param_3, gammaLUT, gammaLUTSmplr), filterTextGammaCorrectChannel(param_4, param_5, gammaLUT, gammaLUTSmplr));
+}
+
+static inline __attribute__((always_inline))
+float4 filterText(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+{
+    float4 kernel0 = filterParams0;
+    float3 bgColor = filterParams1.xyz;
+    float3 fgColor = filterParams2.xyz;
+    bool gammaCorrectionEnabled = filterParams2.w != 0.0;
+    float3 alpha;
+    if (kernel0.w == 0.0)
+    {
+        alpha = colorTexture.sample(colorTextureSmplr, colorTexCoord).xxx;
+    }
+    else
+    {
+        float2 param_3 = colorTexCoord;
+        float4 param_4 = kernel0;
+        float param_5 = 1.0 / colorTextureSize.x;
+        float4 param;
+        float param_1;
+        float4 param_2;
+        filterTextSample9Tap(param, param_1, param_2, colorTexture, colorTextureSmplr, param_3, param_4, param_5);
+        float4 alphaLeft = param;
+        float alphaCenter = param_1;
+        float4 alphaRight = param_2;
+        float4 param_6 = alphaLeft;
+        float3 param_7 = float3(alphaCenter, alphaRight.xy);
+        float4 param_8 = kernel0;
+        float r = filterTextConvolve7Tap(param_6, param_7, param_8);
+        float4 param_9 = float4(alphaLeft.yzw, alphaCenter);
+        float3 param_10 = alphaRight.xyz;
+        float4 param_11 = kernel0;
+        float g = filterTextConvolve7Tap(param_9, param_10, param_11);
+        float4 param_12 = float4(alphaLeft.zw, alphaCenter, alphaRight.x);
+        float3 param_13 = alphaRight.yzw;
+        float4 param_14 = kernel0;
+        float b = filterTextConvolve7Tap(param_12, param_13, param_14);
+        alpha = float3(r, g, b);
+    }
+    if (gammaCorrectionEnabled)
+    {
+        float3 param_15 = bgColor;
+        float3 param_16 = alpha;
+        alpha = filterTextGammaCorrect(param_15, param_16, gammaLUT, gammaLUTSmplr);
+    }
+    return float4(mix(bgColor, fgColor, alpha), 1.0);
+}
+
+static inline __attribute__((always_inline))
+float4 filterColorMatrix(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float4& filterParams3, thread const float4& filterParams4)
+{
+    float4 srcColor = colorTexture.sample(colorTextureSmplr, colorTexCoord);
+    float4x4 colorMatrix = float4x4(float4(filterParams0), float4(filterParams1), float4(filterParams2), float4(filterParams3));
+    return (colorMatrix * srcColor) + filterParams4;
+}
+
+static inline __attribute__((always_inline))
+float2 filterNoiseGradient(thread const float2& latticePoint, thread const int& channel, thread const int& seed)
+{
+    uint hash = ((uint(int(latticePoint.x)) * 2376512323u) ^ (uint(int(latticePoint.y)) * 3625334849u)) ^ (uint((seed * 4) + channel) * 3407524639u);
+    hash ^= (hash >> uint(16));
+    hash *= 2146121005u;
+    hash ^= (hash >> uint(15));
+    hash *= 2221713035u;
+    hash ^= (hash >> uint(16));
+    float angle = float(hash & 65535u) * 9.58738019107840955257415771484375e-05;
+    return float2(cos(angle), sin(angle));
+}
+
+static inline __attribute__((always_inline))
+float filterNoisePerlin(thread const float2& position, thread const float2& period, thread const int& channel, thread const int& seed)
+{
+    float2 lattice0 = floor(position);
+    float2 lattice1 = lattice0 + float2(1.0);
+    float2 t = position - lattice0;
+    if (period.x > 0.0)
+    {
+        lattice0.x = mod(lattice0.x, period.x);
+        lattice1.x = mod(lattice1.x, period.x);
+    }
+    if (period.y > 0.0)
+    {
+        lattice0.y = mod(lattice0.y, period.y);
+        lattice1.y = mod(lattice1.y, period.y);
+    }
+    float2 param = lattice0;
+    int param_1 = channel;
+    int param_2 = seed;
+    float n00 = dot(filterNoiseGradient(param, param_1, param_2), t);
+    float2 param_3 = float2(lattice1.x, lattice0.y);
+    int param_4 = channel;
+    int param_5 = seed;
+    float n10 = dot(filterNoiseGradient(param_3, param_4, param_5), t - float2(1.0, 0.0));
+    float2 param_6 = float2(lattice0.x, lattice1.y);
+    int param_7 = channel;
+    int param_8 = seed;
+    float n01 = dot(filterNoiseGradient(param_6, param_7, param_8), t - float2(0.0, 1.0));
+    float2 param_9 = lattice1;
+    int param_10 = channel;
+    int param_11 = seed;
+    float n11 = dot(filterNoiseGradient(param_9, param_10, param_11), t - float2(1.0));
+    float2 s = (t * t) * (float2(3.0) - (t * 2.0));
+    return mix(mix(n00, n10, s.x), mix(n01, n11, s.x), s.y);
+}
+
+static inline __attribute__((always_inline))
+float4 filterNoise(thread const float2& colorTexCoord, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2)
+{
+    float2 baseFrequency = filterParams0.xy;
+    int numOctaves = int(filterParams0.z);
+    int seed = int(filterParams0.w);
+    float2 stitchOrigin = filterParams1.xy;
+    float2 period = filterParams1.zw;
+    bool fractalNoise = filterParams2.x != 0.0;
+    float2 position = (colorTexCoord - stitchOrigin) * baseFrequency;
+    float4 sum = float4(0.0);
+    float amplitude = 1.0;
+    for (int octave = 0; octave < numOctaves; octave++)
+    {
+        float2 param = position;
+        float2 param_1 = period;
+        int param_2 = 0;
+        int param_3 = seed;
+        float2 param_4 = position;
+        float2 param_5 = period;
+        int param_6 = 1;
+        int param_7 = seed;
+        float2 param_8 = position;
+        float2 param_9 = period;
+        int param_10 = 2;
+        int param_11 = seed;
+        float2 param_12 = position;
+        float2 param_13 = period;
+        int param_14 = 3;
+        int param_15 = seed;
+        float4 octaveNoise = float4(filterNoisePerlin(param, param_1, param_2, param_3), filterNoisePerlin(param_4, param_5, param_6, param_7), filterNoisePerlin(param_8, param_9, param_10, param_11), filterNoisePerlin(param_12, param_13, param_14, param_15));
+        float4 _2048;
+        if (fractalNoise)
+        {
+            _2048 = octaveNoise;
+        }
+        else
+        {
+            _2048 = abs(octaveNoise);
+        }
+        sum += (_2048 * amplitude);
+        position *= 2.0;
+        period *= 2.0;
+        amplitude *= 0.5;
+    }
+    if (fractalNoise)
+    {
+        sum = (sum * 0.5) + float4(0.5);
+    }
+    return fast::clamp(sum, float4(0.0), float4(1.0));
+}
+
+static inline __attribute__((always_inline))
+float filterPaintEffectLines(thread const float& position, thread const float& spacing, thread const float& lineWidth, thread const float& pixelSize)
+{
+    float dist = abs(fract((position / spacing) + 0.5) - 0.5) * spacing;
+    return fast::clamp((((lineWidth * 0.5) - dist) / pixelSize) + 0.5, 0.0, 1.0);
+}
+
+static inline __attribute__((always_inline))
+float4 filterPaintEffect(thread const float2& colorTexCoord, thread const float4& filterParams0)
+{
+    int kind = int(filterParams0.x);
+    float size = filterParams0.y;
+    float amount = filterParams0.z;
+    float pixelSize = filterParams0.w;
+    float alpha;
+    if (kind == 2)
+    {
+        float2 offset = (fract(colorTexCoord / float2(size)) - float2(0.5)) * size;
+        float radius = size * sqrt(fast::clamp(amount, 0.0, 1.0) / 3.1415927410125732421875);
+        alpha = fast::clamp(((radius - length(offset)) / pixelSize) + 0.5, 0.0, 1.0);
+    }
+    else
+    {
+        float param = colorTexCoord.y;
+        float param_1 = size;
+        float param_2 = amount;
+        float param_3 = pixelSize;
+        alpha = filterPaintEffectLines(param, param_1, param_2, param_3);
+        if (kind == 1)
+        {
+            float param_4 = colorTexCoord.x;
+            float param_5 = size;
+            float param_6 = amount;
+            float param_7 = pixelSize;
+            alpha = fast::max(alpha, filterPaintEffectLines(param_4, param_5, param_6, param_7));
+        }
+    }
+    return float4(float3(1.0), alpha);
+}
+
+static inline __attribute__((always_inline))
+float4 sampleColor(thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
+{
+    return colorTexture.sample(colorTextureSmplr, colorTexCoord);
+}
+
+static inline __attribute__((always_inline))
+float4 filterNone(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr)
+{
+    float2 param = colorTexCoord;
+    return sampleColor(colorTexture, colorTextureSmplr, param);
+}
+
+static inline __attribute__((always_inline))
+float4 filterColor(thread const float2& colorTexCoord, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize, thread const float2& fragCoord, thread const float2& framebufferSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float4& filterParams3, thread const float4& filterParams4, thread const int& colorFilter)
+{
+    switch (colorFilter)
+    {
+        case 1:
+        {
+            float2 param = colorTexCoord;
+            float2 param_1 = colorTextureSize;
+            float2 param_2 = fragCoord;
+            float2 param_3 = framebufferSize;
+            float4 param_4 = filterParams0;
+            float4 param_5 = filterParams1;
+            return filterRadialGradient(param, colorTexture, colorTextureSmplr, param_1, param_2, param_3, param_4, param_5);
+        }
+        case 3:
+        {
+            float2 param_6 = colorTexCoord;
+            float2 param_7 = colorTextureSize;
+            float4 param_8 = filterParams0;
+            float4 param_9 = filterParams1;
+            return filterBlur(param_6, colorTexture, colorTextureSmplr, param_7, param_8, param_9);
+        }
+        case 2:
+        {
+            float2 param_10 = colorTexCoord;
+            float2 param_11 = colorTextureSize;
+            float4 param_12 = filterParams0;
+            float4 param_13 = filterParams1;
+            float4 param_14 = filterParams2;
+            return filterText(param_10, colorTexture, colorTextureSmplr, gammaLUT, gammaLUTSmplr, param_11, param_12, param_13, param_14);
+        }
+        case 4:
+        {
+            float2 param_15 = colorTexCoord;
+            float4 param_16 = filterParams0;
+            float4 param_17 = filterParams1;
+            float4 param_18 = filterParams2;
+            float4 param_19 = filterParams3;
+            float4 param_20 = filterParams4;
+            return filterColorMatrix(param_15, colorTexture, colorTextureSmplr, param_16, param_17, param_18, param_19, param_20);
+        }
+        case 5:
+        {
+            float2 param_21 = colorTexCoord;
+            float4 param_22 = filterParams0;
+            float4 param_23 = filterParams1;
+            float4 param_24 = filterParams2;
+            return filterNoise(param_21, param_22, param_23, param_24);
+        }
+        case 6:
+        {
+            float2 param_26 = colorTexCoord;
+            float4 param_27 = filterParams0;
+            return filterPaintEffect(param_26, param_27);
+        }
+    }
+    float2 param_25 = colorTexCoord;
+    return filterNone(param_25, colorTexture, colorTextureSmplr);
+}
+
+static inline __attribute__((always_inline))
+float4 combineColor0(thread const float4& destColor, thread const float4& srcColor, thread const int& op)
+{
+    switch (op)
+    {
+        case 1:
+        {
+            return float4(srcColor.xyz, srcColor.w * destColor.w);
+        }
+        case 2:
+        {
+            return float4(destColor.xyz, srcColor.w * destColor.w);
+        }
+    }
+    return destColor;
+}
+
+static inline __attribute__((always_inline))
+float3 compositeScreen(thread const float3& destColor, thread const float3& srcColor)
+{
+    return (destColor + srcColor) - (destColor * srcColor);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeSelect(thread const bool3& cond, thread const float3& ifTrue, thread const float3& ifFalse)
+{
+    float _805;
+    if (cond.x)
+    {
+        _805 = ifTrue.x;
+    }
+    else
+    {
+        _805 = ifFalse.x;
+    }
+    float _816;
+    if (cond.y)
+    {
+        _816 = ifTrue.y;
+    }
+    else
+    {
+        _816 = ifFalse.y;
+    }
+    float _827;
+    if (cond.z)
+    {
+        _827 = ifTrue.z;
+    }
+    else
+    {
+        _827 = ifFalse.z;
+    }
+    return float3(_805, _816, _827);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeHardLight(thread const float3& destColor, thread const float3& srcColor)
+{
+    float3 param = destColor;
+    float3 param_1 = (float3(2.0) * srcColor) - float3(1.0);
+    bool3 param_2 = srcColor <= float3(0.5);
+    float3 param_3 = (destColor * float3(2.0)) * srcColor;
+    float3 param_4 = compositeScreen(param, param_1);
+    return compositeSelect(param_2, param_3, param_4);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeColorDodge(thread const float3& destColor, thread const float3& srcColor)
+{
+    bool3 destZero = destColor == float3(0.0);
+    bool3 srcOne = srcColor == float3(1.0);
+    bool3 param = srcOne;
+    float3 param_1 = float3(1.0);
+    float3 param_2 = fast::min(destColor / (float3(1.0) - srcColor), float3(1.0));
+    bool3 param_3 = destZero;
+    float3 param_4 = float3(0.0);
+    float3 param_5 = compositeSelect(param, param_1, param_2);
+    return compositeSelect(param_3, param_4, param_5);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeSoftLight(thread const float3& destColor, thread const float3& srcColor)
+{
+    bool3 param = destColor <= float3(0.25);
+    float3 param_1 = ((((float3(16.0) * destColor) - float3(12.0)) * destColor) + float3(4.0)) * destColor;
+    float3 param_2 = sqrt(destColor);
+    float3 darkenedDestColor = compositeSelect(param, param_1, param_2);
+    bool3 param_3 = srcColor <= float3(0.5);
+    float3 param_4 = destColor * (float3(1.0) - destColor);
+    float3 param_5 = darkenedDestColor - destColor;
+    float3 factor = compositeSelect(param_3, param_4, param_5);
+    return destColor + (((srcColor * 2.0) - float3(1.0)) * factor);
+}
+
+static inline __attribute__((always_inline))
+float compositeLum(thread const float3& color)
+{
+    return dot(color, float3(0.300000011920928955078125, 0.589999973773956298828125, 0.10999999940395355224609375));
+}
+
+static inline __attribute__((always_inline))
+float3 compositeClipColor(thread float3& color)
+{
+    float3 param = color;
+    float l = compositeLum(param);
+    float n = fast::min(fast::min(color.x, color.y), color.z);
+    float x = fast::max(fast::max(color.x, color.y), color.z);
+    if (n < 0.0)
+    {
+        color = float3(l) + (((color - float3(l)) * l) / float3(l - n));
+    }
+    if (x > 1.0)
+    {
+        color = float3(l) + (((color - float3(l)) * (1.0 - l)) / float3(x - l));
+    }
+    return color;
+}
+
+static inline __attribute__((always_inline))
+float3 compositeSetLum(thread const float3& color, thread const float& l)
+{
+    float3 param = color;
+    float3 param_1 = color + float3(l - compositeLum(param));
+    float3 _clipped = compositeClipColor(param_1);
+    return _clipped;
+}
+
+static inline __attribute__((always_inline))
+float compositeSat(thread const float3& color)
+{
+    return fast::max(fast::max(color.x, color.y), color.z) - fast::min(fast::min(color.x, color.y), color.z);
+}
+
+static inline __attribute__((always_inline))
+float3 compositeSetSat(thread const float3& color, thread const float& s)
+{
+    float colorMin = fast::min(fast::min(color.x, color.y), color.z);
+    float3 param = color;
+    float colorSat = compositeSat(param);
+    float3 _saturated;
+    if (colorSat > 0.0)
+    {
+        _saturated = ((color - float3(colorMin)) * s) / float3(colorSat);
+    }
+    else
+    {
+        _saturated = float3(0.0);
+    }
+    return _saturated;
+}
+
+static inline __attribute__((always_inline))
+float3 compositeRGB(thread const float3& destColor, thread const float3& srcColor, thread const int& op)
+{
+    switch (op)
+    {
+        case 1:
+        {
+            return destColor * srcColor;
+        }
+        case 2:
+        {
+            float3 param = destColor;
+            float3 param_1 = srcColor;
+            return compositeScreen(param, param_1);
+        }
+        case 3:
+        {
+            float3 param_2 = srcColor;
+            float3 param_3 = destColor;
+            return compositeHardLight(param_2, param_3);
+        }
+        case 4:
+        {
+            return fast::min(destColor, srcColor);
+        }
+        case 5:
+        {
+            return fast::max(destColor, srcColor);
+        }
+        case 6:
+        {
+            float3 param_4 = destColor;
+            float3 param_5 = srcColor;
+            return compositeColorDodge(param_4, param_5);
+        }
+        case 7:
+        {
+            float3 param_6 = float3(1.0) - destColor;
+            float3 param_7 = float3(1.0) - srcColor;
+            return float3(1.0) - compositeColorDodge(param_6, param_7);
+        }
+        case 8:
+        {
+            float3 param_8 = destColor;
+            float3 param_9 = srcColor;
+            return compositeHardLight(param_8, param_9);
+        }
+        case 9:
+        {
+            float3 param_10 = destColor;
+            float3 param_11 = srcColor;
+            return compositeSoftLight(param_10, param_11);
+        }
+        case 10:
+        {
+            return abs(destColor - srcColor);
+        }
+        case 11:
+        {
+            return (destColor + srcColor) - ((float3(2.0) * destColor) * srcColor);
+        }
+        case 12:
+        {
+            float3 param_12 = destColor;
+            float3 param_13 = srcColor;
+            float param_14 = compositeSat(param_12);
+            float3 param_15 = compositeSetSat(param_13, param_14);
+            float param_16 = compositeLum(param_12);
+            return compositeSetLum(param_15, param_16);
+        }
+        case 13:
+        {
+            float3 param_17 = srcColor;
+            float3 param_18 = destColor;
+            float param_19 = compositeSat(param_17);
+            float3 param_20 = compositeSetSat(param_18, param_19);
+            float param_21 = compositeLum(param_18);
+            return compositeSetLum(param_20, param_21);
+        }
+        case 14:
+        {
+            float3 param_22 = destColor;
+            float3 param_23 = srcColor;
+            float param_24 = compositeLum(param_22);
+            return compositeSetLum(param_23, param_24);
+        }
+        case 15:
+        {
+            float3 param_25 = srcColor;
+            float3 param_26 = destColor;
+            float param_27 = compositeLum(param_25);
+            return compositeSetLum(param_26, param_27);
+        }
+    }
+    return srcColor;
+}
+
+static inline __attribute__((always_inline))
+float4 compositeColors(thread const float4& srcColor, thread const float4& destColor, thread const int& op)
+{
+    float3 destRGB;
+    if (destColor.w > 0.0)
+    {
+        destRGB = destColor.xyz / float3(destColor.w);
+    }
+    else
+    {
+        destRGB = float3(0.0);
+    }
+    float3 param = destRGB;
+    float3 param_1 = srcColor.xyz;
+    int param_2 = op;
+    float3 blendedRGB = compositeRGB(param, param_1, param_2);
+    float3 resultRGB = ((srcColor.xyz * (srcColor.w * (1.0 - destColor.w))) + (blendedRGB * (srcColor.w * destColor.w))) + (destColor.xyz * (1.0 - srcColor.w));
+    float resultAlpha = srcColor.w + (destColor.w * (1.0 - srcColor.w));
+    float4 _result;
+    if (resultAlpha > 0.0)
+    {
+        _result = float4(resultRGB / float3(resultAlpha), resultAlpha);
+    }
+    else
+    {
+        _result = float4(0.0);
+    }
+    return _result;
+}
+
+static inline __attribute__((always_inline))
+float4 composite(thread const float4& srcColor, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const float2& destTextureSize, thread const float2& fragCoord, thread const int& op)
+{
+    if (op == 0)
+    {
+        return srcColor;
+    }
+    float2 destTexCoord = fragCoord / destTextureSize;
+    float4 destColor = destTexture.sample(destTextureSmplr, destTexCoord);
+    float4 param = srcColor;
+    float4 param_1 = destColor;
+    int param_2 = op;
+    return compositeColors(param, param_1, param_2);
+}
+
+static inline __attribute__((always_inline))
+float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float4& filterParams3, thread const float4& filterParams4, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& baseColor, thread const int& tileCtrl, thread float3& channelAlphas)
+{
+    int maskCtrl0 = (tileCtrl >> 0) & 3;
+    bool subpixelCoverage = (tileCtrl & 4) != 0;
+    float3 maskAlphas;
+    if (subpixelCoverage)
+    {
+        float2 param = maskTextureSize0;
+        float3 param_1 = maskTexCoord0;
+        int param_2 = maskCtrl0;
+        maskAlphas = sampleSubpixelMask(maskTexture0, maskTexture0Smplr, param, param_1, param_2);
+    }
+    else
+    {
+        float maskAlpha = 1.0;
+        float param_3 = maskAlpha;
+        float2 param_4 = maskTextureSize0;
+        float3 param_5 = maskTexCoord0;
+        int param_6 = maskCtrl0;
+        maskAlpha = sampleMask(param_3, maskTexture0, maskTexture0Smplr, param_4, param_5, param_6);
+        maskAlphas = float3(maskAlpha);
+    }
+    float4 color = baseColor;
+    int color0Combine = (ctrl >> 8) & 3;
+    if (color0Combine != 0)
+    {
+        int color0Filter = (ctrl >> 4) & 15;
+        float2 param_7 = colorTexCoord0;
+        float2 param_8 = colorTextureSize0;
+        float2 param_9 = fragCoord;
+        float2 param_10 = framebufferSize;
+        float4 param_11 = filterParams0;
+        float4 param_12 = filterParams1;
+        float4 param_13 = filterParams2;
+        float4 param_14 = filterParams3;
+        float4 param_15 = filterParams4;
+        int param_16 = color0Filter;
+        float4 color0 = filterColor(param_7, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_8, param_9, param_10, param_11, param_12, param_13, param_14, param_15, param_16);
+        float4 param_17 = color;
+        float4 param_18 = color0;
+        int param_19 = color0Combine;
+        color = combineColor0(param_17, param_18, param_19);
+    }
+    channelAlphas = maskAlphas * color.w;
+    color.w = channelAlphas.y;
+    if (!subpixelCoverage)
+    {
+        int compositeOp = (ctrl >> 10) & 15;
+        float4 param_20 = color;
+        float2 param_21 = framebufferSize;
+        float2 param_22 = fragCoord;
+        int param_23 = compositeOp;
+        color = composite(param_20, destTexture, destTextureSmplr, param_21, param_22, param_23);
+        channelAlphas = float3(color.w);
+    }
+    float3 _1409 = color.xyz * channelAlphas;
+    color = float4(_1409.x, _1409.y, _1409.z, color.w);
+    return color;
+}
+
+static inline __attribute__((always_inline))
+float4 ditherColor(thread const float4& color, thread const float2& fragCoord)
+{
+    int2 position = int2(fragCoord) & int2(3);
+    int diagonal = position.x ^ position.y;
+    int index = ((((diagonal & 1) << 3) | ((position.y & 1) << 2)) | (diagonal & 2)) | ((position.y >> 1) & 1);
+    float threshold = ((float(index) + 0.5) / 16.0) - 0.5;
+    float3 _dither = color.xyz + float3((threshold * color.w) / 255.0);
+    return float4(_dither, color.w);
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant float2& uColorTextureSize0 [[buffer(0)]], constant float2& uMaskTextureSize0 [[buffer(1)]], constant float2& uFramebufferSize [[buffer(2)]], constant int& uDither [[buffer(3)]], texture2d<float> uColorTexture0 [[texture(0)]], texture2d<float> uMaskTexture0 [[texture(1)]], texture2d<float> uDestTexture [[texture(2)]], texture2d<float> uGammaLUT [[texture(3)]], sampler uColorTexture0Smplr [[sampler(0)]], sampler uMaskTexture0Smplr [[sampler(1)]], sampler uDestTextureSmplr [[sampler(2)]], sampler uGammaLUTSmplr [[sampler(3)]], float4 gl_FragCoord [[position]])
+{
+    main0_out out = {};
+    float2 param = gl_FragCoord.xy;
+    float2 param_1 = uColorTextureSize0;
+    float2 param_2 = uMaskTextureSize0;
+    float4 param_3 = in.vFilterParams0;
+    float4 param_4 = in.vFilterParams1;
+    float4 param_5 = in.vFilterParams2;
+    float4 param_6 = in.vFilterParams3;
+    float4 param_7 = in.vFilterParams4;
+    float2 param_8 = uFramebufferSize;
+    int param_9 = int(in.vCtrl);
+    float3 param_10 = in.vMaskTexCoord0;
+    float2 param_11 = in.vColorTexCoord0;
+    float4 param_12 = in.vBaseColor;
+    int param_13 = int(in.vTileCtrl);
+    float3 param_14;
+    float4 _1495 = calculateColor(param, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uDestTexture, uDestTextureSmplr, uGammaLUT, uGammaLUTSmplr, param_1, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11, param_12, param_13, param_14);
+    float3 channelAlphas = param_14;
+    float4 color = _1495;
+    if (uDither != 0)
+    {
+        float4 param_15 = color;
+        float2 param_16 = gl_FragCoord.xy;
+        color = ditherColor(param_15, param_16);
+    }
+    out.oFragColor = color;
+    out.oFragBlend = float4(channelAlphas, color.w);
+    return out;
+}
+
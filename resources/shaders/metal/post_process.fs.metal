@@ -0,0 +1,84 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+#include <metal_stdlib>
+#include <simd/simd.h>
+
+using namespace metal;
+
+struct main0_out
+{
+    float4 oFragColor [[color(0)]];
+};
+
+struct main0_in
+{
+    float2 vTexCoord [[user(locn0)]];
+};
+
+static inline __attribute__((always_inline))
+float3 unpremultiply(thread const float4& color)
+{
+    float3 _20;
+    if (color.w == 0.0)
+    {
+        _20 = float3(0.0);
+    }
+    else
+    {
+        _20 = color.xyz / float3(color.w);
+    }
+    return _20;
+}
+
+static inline __attribute__((always_inline))
+float3 sampleColorLUT(thread const float3& color, thread const float& size, thread texture2d<float> uLUT, thread const sampler uLUTSmplr)
+{
+    float3 scaled = fast::clamp(color, float3(0.0), float3(1.0)) * (size - 1.0);
+    float sliceLo = floor(scaled.z);
+    float sliceHi = fast::min(sliceLo + 1.0, size - 1.0);
+    float2 texCoord = (scaled.xy + float2(0.5)) / float2(size * size, size);
+    float3 lo = uLUT.sample(uLUTSmplr, (texCoord + float2(sliceLo / size, 0.0))).xyz;
+    float3 hi = uLUT.sample(uLUTSmplr, (texCoord + float2(sliceHi / size, 0.0))).xyz;
+    return mix(lo, hi, float3(scaled.z - sliceLo));
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant int& uFilterKind [[buffer(0)]], constant float4& uFilterParams0 [[buffer(1)]], constant float4& uFilterParams1 [[buffer(2)]], texture2d<float> uSrc [[texture(0)]], texture2d<float> uLUT [[texture(1)]], sampler uSrcSmplr [[sampler(0)]], sampler uLUTSmplr [[sampler(1)]])
+{
+    main0_out out = {};
+    float4 color = uSrc.sample(uSrcSmplr, in.vTexCoord);
+    if (uFilterKind == 0)
+    {
+        float4 param = color;
+        float3 param_1 = unpremultiply(param);
+        float param_2 = uFilterParams0.x;
+        float3 _117 = sampleColorLUT(param_1, param_2, uLUT, uLUTSmplr) * color.w;
+        color = float4(_117.x, _117.y, _117.z, color.w);
+    }
+    else
+    {
+        if (uFilterKind == 1)
+        {
+            float dist = length(in.vTexCoord - float2(0.5)) * sqrt(2.0);
+            float amount = smoothstep(uFilterParams1.x, uFilterParams1.x + uFilterParams1.y, dist);
+            float3 _150 = mix(color.xyz, uFilterParams0.xyz * color.w, float3(amount * uFilterParams0.w));
+            color = float4(_150.x, _150.y, _150.z, color.w);
+        }
+        else
+        {
+            if (uFilterKind == 2)
+            {
+                float4 param_3 = color;
+                float3 _165 = powr(unpremultiply(param_3), float3(uFilterParams0.x)) * color.w;
+                color = float4(_165.x, _165.y, _165.z, color.w);
+            }
+            else
+            {
+                float4 param_4 = color;
+                float3 _171 = unpremultiply(param_4);
+                color = float4(_171.x, _171.y, _171.z, color.w);
+            }
+        }
+    }
+    out.oFragColor = color;
+    return out;
+}
+
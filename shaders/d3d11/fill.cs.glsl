@@ -29,6 +29,8 @@ layout(local_size_x = 16, local_size_y = 4) in;
 
 layout(rgba8) uniform image2D uDest;
 uniform sampler2D uAreaLUT;
+// 1 to supersample coverage instead of using the area LUT.
+uniform int uSupersample;
 uniform ivec2 uAlphaTileRange;
 
 layout(std430, binding = 0) buffer bFills {
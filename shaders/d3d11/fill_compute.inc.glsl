@@ -17,7 +17,10 @@ vec4 accumulateCoverageForFillList(int fillIndex, ivec2 tileSubCoord) {
         vec4 lineSegment = vec4(fillFrom & 0xffff, fillFrom >> 16,
                                 fillTo   & 0xffff, fillTo   >> 16) / 256.0;
         lineSegment -= tileFragCoord.xyxy;
-        coverages += computeCoverage(lineSegment.xy, lineSegment.zw, uAreaLUT);
+        if (uSupersample != 0)
+            coverages += computeCoverageSupersample(lineSegment.xy, lineSegment.zw);
+        else
+            coverages += computeCoverage(lineSegment.xy, lineSegment.zw, uAreaLUT);
         fillIndex = int(iFills[fillIndex * 3 + 2]);
         iteration++;
     } while (fillIndex >= 0 && iteration < 1024);
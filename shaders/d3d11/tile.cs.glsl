@@ -45,6 +45,7 @@ uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
 uniform vec2 uFramebufferSize;
 uniform ivec2 uFramebufferTileSize;
+uniform int uDither;
 layout(rgba8) uniform image2D uDestImage;
 
 layout(std430, binding = 0) buffer bTiles {
@@ -130,9 +131,18 @@ void main() {
                                 filterParams4,
                                 ctrl);
 
-            // FIXME(pcwalton): The `uColorTexture0` below is a placeholder and needs to be
-            // replaced!
+            // The destination is already in `destColors`, so composite against that directly
+            // instead of sampling a copy of the framebuffer.
+            int compositeOp = (ctrl >> COMBINER_CTRL_COMPOSITE_SHIFT) &
+                COMBINER_CTRL_COMPOSITE_MASK;
+            ctrl &= ~(COMBINER_CTRL_COMPOSITE_MASK << COMBINER_CTRL_COMPOSITE_SHIFT);
+            if ((tileCtrl & TILE_CTRL_SUBPIXEL_COVERAGE) != 0)
+                compositeOp = COMBINER_CTRL_COMPOSITE_NORMAL;
 
+            // With the composite op cleared, `calculateColor()` never samples its destination
+            // texture, so `uColorTexture0` just fills that slot.
+
+            vec3 channelAlphas;
             vec4 srcColor = calculateColor(fragCoord,
                                            uColorTexture0,
                                            uMaskTexture0,
@@ -150,14 +160,31 @@ void main() {
                                            maskTexCoord0,
                                            colorTexCoord0,
                                            baseColor,
-                                           tileCtrl);
+                                           tileCtrl,
+                                           channelAlphas);
 
-            destColors[subY] = destColors[subY] * (1.0 - srcColor.a) + srcColor;
+            if (compositeOp == COMBINER_CTRL_COMPOSITE_NORMAL) {
+                // The channel alphas only differ from the source alpha for subpixel coverage.
+                destColors[subY] = destColors[subY] * (vec4(1.0) - vec4(channelAlphas, srcColor.a)) +
+                    srcColor;
+            } else {
+                vec4 unpremultipliedSrcColor =
+                    srcColor.a > 0.0 ? vec4(srcColor.rgb / srcColor.a, srcColor.a) : vec4(0.0);
+                vec4 blendedColor = compositeColors(unpremultipliedSrcColor,
+                                                    destColors[subY],
+                                                    compositeOp);
+                destColors[subY] = vec4(blendedColor.rgb * blendedColor.a, blendedColor.a);
+            }
         }
 
         tileIndex = int(iTiles[tileIndex * 4 + TILE_FIELD_NEXT_TILE_ID]);
     }
 
-    for (int subY = 0; subY < 4; subY++)
-        imageStore(uDestImage, toImageCoords(firstFragCoord + ivec2(0, subY)), destColors[subY]);
+    for (int subY = 0; subY < 4; subY++) {
+        ivec2 destCoord = firstFragCoord + ivec2(0, subY);
+        vec4 destColor = destColors[subY];
+        if (uDither != 0)
+            destColor = ditherColor(destColor, vec2(destCoord));
+        imageStore(uDestImage, toImageCoords(destCoord), destColor);
+    }
 }
@@ -21,6 +21,8 @@ precision highp sampler2D;
 #include "fill_area.inc.glsl"
 
 uniform sampler2D uAreaLUT;
+// 1 to supersample coverage instead of using the area LUT.
+uniform int uSupersample;
 
 in vec2 vFrom;
 in vec2 vTo;
@@ -28,5 +30,8 @@ in vec2 vTo;
 out vec4 oFragColor;
 
 void main() {
-    oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    if (uSupersample != 0)
+        oFragColor = computeCoverageSupersample(vFrom, vTo);
+    else
+        oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
 }
@@ -27,6 +27,8 @@ uniform sampler2D uGammaLUT;
 uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
 uniform vec2 uFramebufferSize;
+uniform int uDither;
+uniform int uCoverageOnly;
 
 in vec3 vMaskTexCoord0;
 in vec2 vColorTexCoord0;
@@ -46,7 +48,8 @@ out vec4 oFragColor;
 // TODO(pcwalton): Generate this dynamically.
 
 void main() {
-    oFragColor = calculateColor(gl_FragCoord.xy,
+    vec3 channelAlphas;
+    vec4 color = calculateColor(gl_FragCoord.xy,
                                 uColorTexture0,
                                 uMaskTexture0,
                                 uDestTexture,
@@ -63,5 +66,12 @@ void main() {
                                 vMaskTexCoord0,
                                 vColorTexCoord0,
                                 vBaseColor,
-                                int(vTileCtrl));
+                                int(vTileCtrl),
+                                channelAlphas);
+    if (uDither != 0)
+        color = ditherColor(color, gl_FragCoord.xy);
+
+    // Without dual-source blending, subpixel coverage is drawn in two passes, the first of which
+    // only outputs the per-channel alpha.
+    oFragColor = uCoverageOnly != 0 ? vec4(channelAlphas, color.a) : color;
 }
@@ -26,6 +26,9 @@ uniform sampler2D uTextureMetadata;
 uniform ivec2 uTextureMetadataSize;
 uniform sampler2D uZBuffer;
 uniform ivec2 uZBufferSize;
+uniform sampler2D uPathDepthPlanes;
+uniform ivec2 uPathDepthPlanesSize;
+uniform int uPathDepthEnabled;
 
 in ivec2 aTileOffset;
 in ivec2 aTileOrigin;
@@ -80,4 +83,13 @@ void main() {
     vCtrl = float(ctrl);
     vMaskTexCoord0 = vec3(maskTexCoord0, float(aCtrlBackdrop.y));
     gl_Position = uTransform * vec4(position, 0.0, 1.0);
+
+    // Place the tile on the depth plane of its path, so the depth buffer orders paths.
+    if (uPathDepthEnabled != 0) {
+        vec2 planeCoord = vec2(aPathIndex % uPathDepthPlanesSize.x,
+                               aPathIndex / uPathDepthPlanesSize.x);
+        vec2 planeTexCoord = (planeCoord + vec2(0.5)) / vec2(uPathDepthPlanesSize);
+        vec4 plane = texture(uPathDepthPlanes, planeTexCoord);
+        gl_Position.z = dot(plane.xyz, vec3(position, 1.0));
+    }
 }
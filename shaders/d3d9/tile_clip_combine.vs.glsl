@@ -16,18 +16,24 @@ precision highp float;
 precision highp sampler2D;
 #endif
 
+#define TILE_CTRL_MASK_EVEN_ODD     0x2
+
 uniform vec2 uFramebufferSize;
 
 in ivec2 aTileOffset;
 in int aDestTileIndex;
 in int aDestBackdrop;
+in int aDestCtrl;
 in int aSrcTileIndex;
 in int aSrcBackdrop;
+in int aSrcCtrl;
 
 out vec2 vTexCoord0;
 out float vBackdrop0;
+out float vEvenOdd0;
 out vec2 vTexCoord1;
 out float vBackdrop1;
+out float vEvenOdd1;
 
 void main() {
     vec2 destPosition = vec2(ivec2(aDestTileIndex % 256, aDestTileIndex / 256) + aTileOffset);
@@ -41,6 +47,9 @@ void main() {
     vBackdrop0 = float(aDestBackdrop);
     vBackdrop1 = float(aSrcBackdrop);
 
+    vEvenOdd0 = aDestCtrl == TILE_CTRL_MASK_EVEN_ODD ? 1.0 : 0.0;
+    vEvenOdd1 = aSrcCtrl  == TILE_CTRL_MASK_EVEN_ODD ? 1.0 : 0.0;
+
     if (aDestTileIndex < 0)
         destPosition = vec2(0.0);
 
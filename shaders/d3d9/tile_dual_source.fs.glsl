@@ -0,0 +1,75 @@
+#version 330
+
+// pathfinder/shaders/tile_dual_source.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A variant of `tile.fs.glsl` for tiles with subpixel coverage, which are blended with
+// `dest * (1 - oFragBlend) + oFragColor`. Only used if the device supports dual-source blending.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+
+#ifdef GL_ES
+precision highp sampler2D;
+#endif
+
+#include "tile_fragment.inc.glsl"
+
+uniform sampler2D uColorTexture0;
+uniform sampler2D uMaskTexture0;
+uniform sampler2D uDestTexture;
+uniform sampler2D uGammaLUT;
+uniform vec2 uColorTextureSize0;
+uniform vec2 uMaskTextureSize0;
+uniform vec2 uFramebufferSize;
+uniform int uDither;
+
+in vec3 vMaskTexCoord0;
+in vec2 vColorTexCoord0;
+in vec4 vBaseColor;
+in float vTileCtrl;
+in vec4 vFilterParams0;
+in vec4 vFilterParams1;
+in vec4 vFilterParams2;
+in vec4 vFilterParams3;
+in vec4 vFilterParams4;
+in float vCtrl;
+
+layout(location = 0, index = 0) out vec4 oFragColor;
+// The per-channel alpha of `oFragColor`, for dual-source blending.
+layout(location = 0, index = 1) out vec4 oFragBlend;
+
+void main() {
+    vec3 channelAlphas;
+    vec4 color = calculateColor(gl_FragCoord.xy,
+                                uColorTexture0,
+                                uMaskTexture0,
+                                uDestTexture,
+                                uGammaLUT,
+                                uColorTextureSize0,
+                                uMaskTextureSize0,
+                                vFilterParams0,
+                                vFilterParams1,
+                                vFilterParams2,
+                                vFilterParams3,
+                                vFilterParams4,
+                                uFramebufferSize,
+                                int(vCtrl),
+                                vMaskTexCoord0,
+                                vColorTexCoord0,
+                                vBaseColor,
+                                int(vTileCtrl),
+                                channelAlphas);
+    if (uDither != 0)
+        color = ditherColor(color, gl_FragCoord.xy);
+    oFragColor = color;
+    oFragBlend = vec4(channelAlphas, color.a);
+}
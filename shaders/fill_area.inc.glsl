@@ -25,3 +25,30 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT) {
     float dX = window.x - window.y;
     return texture(areaLUT, vec2(y + 8.0, abs(d * dX)) / 16.0) * dX;
 }
+
+// Computes the same coverage as `computeCoverage()` by supersampling instead of consulting the
+// area LUT: the line is tested against 8 points per pixel, in the standard 8x multisampling
+// pattern, and the points below it are counted. This is less precise, but it's simple enough to
+// be a useful fallback on drivers that mishandle the analytic path.
+vec4 computeCoverageSupersample(vec2 from, vec2 to) {
+    const vec2 samplePositions[8] = vec2[8](vec2( 1.0, -3.0), vec2(-1.0,  3.0),
+                                            vec2( 5.0,  1.0), vec2(-3.0, -5.0),
+                                            vec2(-5.0,  5.0), vec2(-7.0, -1.0),
+                                            vec2( 3.0,  7.0), vec2( 7.0, -7.0));
+
+    vec2 left = from.x < to.x ? from : to, right = from.x < to.x ? to : from;
+
+    // Count the samples in each of the 4 pixels that lie below the line.
+    vec4 coverages = vec4(0.0);
+    for (int sampleIndex = 0; sampleIndex < 8; sampleIndex++) {
+        vec2 samplePosition = samplePositions[sampleIndex] / 16.0;
+        if (samplePosition.x < left.x || samplePosition.x >= right.x)
+            continue;
+        float t = (samplePosition.x - left.x) / (right.x - left.x);
+        float y = mix(left.y, right.y, t);
+        coverages += step(vec4(y), vec4(samplePosition.y) + vec4(0.0, 1.0, 2.0, 3.0));
+    }
+
+    float winding = from.x < to.x ? -1.0 : 1.0;
+    return coverages * winding / 8.0;
+}
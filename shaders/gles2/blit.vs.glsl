@@ -0,0 +1,27 @@
+#version 100
+
+// pathfinder/shaders/gles2/blit.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+precision highp float;
+precision highp sampler2D;
+
+uniform vec4 uDestRect;
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aPosition;
+
+varying vec2 vTexCoord;
+
+void main() {
+    vec2 position = mix(uDestRect.xy, uDestRect.zw, aPosition) / uFramebufferSize;
+    vTexCoord = aPosition;
+    gl_Position = vec4(mix(vec2(-1.0), vec2(1.0), position), 0.0, 1.0);
+}
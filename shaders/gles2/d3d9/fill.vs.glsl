@@ -0,0 +1,61 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/fill.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+#include "gles2/int32.inc.glsl"
+
+uniform vec2 uFramebufferSize;
+uniform vec2 uTileSize;
+
+attribute vec2 aTessCoord;
+attribute vec4 aLineSegment;
+attribute vec2 aTileIndex;
+
+varying vec2 vFrom;
+varying vec2 vTo;
+
+vec2 computeTileOffset(float tileIndex, float stencilTextureWidth, vec2 tileSize) {
+    float tilesPerRow = floor(stencilTextureWidth / tileSize.x);
+    float row = floor((tileIndex + 0.5) / tilesPerRow);
+    vec2 tileOffset = vec2(tileIndex - row * tilesPerRow, row);
+    return tileOffset * tileSize * vec2(1.0, 0.25);
+}
+
+void main() {
+    vec2 tileOrigin = computeTileOffset(decodeInt32(aTileIndex), uFramebufferSize.x, uTileSize);
+
+    vec4 lineSegment = aLineSegment / 256.0;
+    vec2 from = lineSegment.xy, to = lineSegment.zw;
+
+    vec2 position;
+    if (aTessCoord.x == 0.0)
+        position.x = floor(min(from.x, to.x));
+    else
+        position.x = ceil(max(from.x, to.x));
+    if (aTessCoord.y == 0.0)
+        position.y = floor(min(from.y, to.y));
+    else
+        position.y = uTileSize.y;
+    position.y = floor(position.y * 0.25);
+
+    // See `d3d9/fill.vs.glsl` for the explanation of this offset.
+    vec2 offset = vec2(0.0, 1.5) - position * vec2(1.0, 4.0);
+    vFrom = from + offset;
+    vTo = to + offset;
+
+    vec2 globalPosition = (tileOrigin + position) / uFramebufferSize * 2.0 - 1.0;
+    gl_Position = vec4(globalPosition, 0.0, 1.0);
+}
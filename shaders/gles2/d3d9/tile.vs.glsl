@@ -0,0 +1,95 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+#include "gles2/int32.inc.glsl"
+
+uniform mat4 uTransform;
+uniform vec2 uTileSize;
+uniform sampler2D uTextureMetadata;
+uniform ivec2 uTextureMetadataSize;
+uniform sampler2D uZBuffer;
+uniform ivec2 uZBufferSize;
+uniform sampler2D uPathDepthPlanes;
+uniform ivec2 uPathDepthPlanesSize;
+uniform int uPathDepthEnabled;
+
+attribute vec2 aTileOffset;
+attribute vec2 aTileOrigin;
+attribute vec4 aMaskTexCoord0;
+attribute vec2 aCtrlBackdrop;
+attribute vec2 aPathIndex;
+attribute float aColor;
+
+varying vec3 vMaskTexCoord0;
+varying vec2 vColorTexCoord0;
+varying vec4 vBaseColor;
+varying float vTileCtrl;
+varying vec4 vFilterParams0;
+varying vec4 vFilterParams1;
+varying float vCtrl;
+varying float vCompositeOp;
+
+vec4 fetchMetadata(vec2 entryCoord, float entry) {
+    vec2 scale = vec2(1.0) / vec2(uTextureMetadataSize);
+    return texture2D(uTextureMetadata, (entryCoord + vec2(0.5 + entry, 0.5)) * scale);
+}
+
+void main() {
+    vec2 position = (aTileOrigin + aTileOffset) * uTileSize;
+    float pathIndex = decodeInt32(aPathIndex);
+
+    vec2 zBufferTexCoord = (aTileOrigin + vec2(0.5)) / vec2(uZBufferSize);
+    vec4 zValue = floor(texture2D(uZBuffer, zBufferTexCoord) * 255.0 + 0.5);
+    if (pathIndex < dot(zValue, vec4(1.0, 256.0, 65536.0, 16777216.0))) {
+        gl_Position = vec4(0.0);
+        return;
+    }
+
+    vec2 maskTileCoord = vec2(aMaskTexCoord0.x, aMaskTexCoord0.y + 256.0 * aMaskTexCoord0.z);
+    vec2 maskTexCoord0 = (maskTileCoord + aTileOffset) * uTileSize;
+    if (aCtrlBackdrop.y == 0.0 && aMaskTexCoord0.w != 0.0) {
+        gl_Position = vec4(0.0);
+        return;
+    }
+
+    float colorRow = floor((aColor + 0.5) / 128.0);
+    vec2 metadataEntryCoord = vec2((aColor - colorRow * 128.0) * 10.0, colorRow);
+    vec4 colorTexMatrix0 = fetchMetadata(metadataEntryCoord, 0.0);
+    vec4 colorTexOffsets = fetchMetadata(metadataEntryCoord, 1.0);
+    vBaseColor           = fetchMetadata(metadataEntryCoord, 2.0);
+    vFilterParams0       = fetchMetadata(metadataEntryCoord, 3.0);
+    vFilterParams1       = fetchMetadata(metadataEntryCoord, 4.0);
+    vec4 extra           = fetchMetadata(metadataEntryCoord, 8.0);
+    vColorTexCoord0 = mat2(colorTexMatrix0.xy, colorTexMatrix0.zw) * position + colorTexOffsets.xy;
+
+    // The controls are split up so that each part stays exact even if the fragment shader only
+    // has medium precision.
+    float ctrl = floor(extra.x + 0.5);
+    vTileCtrl = mod(aCtrlBackdrop.x, 4.0);
+    vCtrl = mod(ctrl, 1024.0);
+    vCompositeOp = floor(ctrl / 1024.0);
+    vMaskTexCoord0 = vec3(maskTexCoord0, aCtrlBackdrop.y);
+    gl_Position = uTransform * vec4(position, 0.0, 1.0);
+
+    if (uPathDepthEnabled != 0) {
+        vec2 planesSize = vec2(uPathDepthPlanesSize);
+        float planeRow = floor((pathIndex + 0.5) / planesSize.x);
+        vec2 planeCoord = vec2(pathIndex - planeRow * planesSize.x, planeRow);
+        vec4 plane = texture2D(uPathDepthPlanes, (planeCoord + vec2(0.5)) / planesSize);
+        gl_Position.z = dot(plane.xyz, vec3(position, 1.0));
+    }
+}
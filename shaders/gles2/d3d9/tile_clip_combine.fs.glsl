@@ -0,0 +1,41 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile_clip_combine.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+precision highp sampler2D;
+#else
+precision mediump float;
+precision mediump sampler2D;
+#endif
+
+uniform sampler2D uSrc;
+
+varying vec2 vTexCoord0;
+varying float vBackdrop0;
+varying float vEvenOdd0;
+varying vec2 vTexCoord1;
+varying float vBackdrop1;
+varying float vEvenOdd1;
+
+// Applies a mask's fill rule, so that the result is coverage from 0 to 1 that either fill rule
+// leaves alone when the combined mask is drawn or combined again.
+vec4 resolveCoverage(vec4 winding, float evenOdd) {
+    vec4 nonzeroCoverage = min(abs(winding), vec4(1.0));
+    vec4 evenOddCoverage = vec4(1.0) - abs(vec4(1.0) - mod(winding, 2.0));
+    return mix(nonzeroCoverage, evenOddCoverage, evenOdd);
+}
+
+void main() {
+    gl_FragColor = min(resolveCoverage(texture2D(uSrc, vTexCoord0) + vBackdrop0, vEvenOdd0),
+                       resolveCoverage(texture2D(uSrc, vTexCoord1) + vBackdrop1, vEvenOdd1));
+}
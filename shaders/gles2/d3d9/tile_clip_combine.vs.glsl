@@ -0,0 +1,60 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile_clip_combine.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+#include "gles2/int32.inc.glsl"
+
+#define TILE_CTRL_MASK_EVEN_ODD     2.0
+
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aTileOffset;
+attribute vec2 aDestTileIndex;
+attribute vec2 aDestBackdrop;
+attribute vec2 aDestCtrl;
+attribute vec2 aSrcTileIndex;
+attribute vec2 aSrcBackdrop;
+attribute vec2 aSrcCtrl;
+
+varying vec2 vTexCoord0;
+varying float vBackdrop0;
+varying float vEvenOdd0;
+varying vec2 vTexCoord1;
+varying float vBackdrop1;
+varying float vEvenOdd1;
+
+void main() {
+    float destTileIndex = decodeInt32(aDestTileIndex);
+    float srcTileIndex = decodeInt32(aSrcTileIndex);
+    vec2 destPosition = computeTileCoord(destTileIndex) + aTileOffset;
+    vec2 srcPosition  = computeTileCoord(srcTileIndex)  + aTileOffset;
+    destPosition *= vec2(16.0, 4.0) / uFramebufferSize;
+    srcPosition  *= vec2(16.0, 4.0) / uFramebufferSize;
+
+    vTexCoord0 = destPosition;
+    vTexCoord1 = srcPosition;
+
+    vBackdrop0 = decodeInt32(aDestBackdrop);
+    vBackdrop1 = decodeInt32(aSrcBackdrop);
+
+    vEvenOdd0 = decodeInt32(aDestCtrl) == TILE_CTRL_MASK_EVEN_ODD ? 1.0 : 0.0;
+    vEvenOdd1 = decodeInt32(aSrcCtrl)  == TILE_CTRL_MASK_EVEN_ODD ? 1.0 : 0.0;
+
+    if (destTileIndex < 0.0)
+        destPosition = vec2(0.0);
+
+    gl_Position = vec4(mix(vec2(-1.0), vec2(1.0), destPosition), 0.0, 1.0);
+}
@@ -0,0 +1,27 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile_clip_copy.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+precision highp sampler2D;
+#else
+precision mediump float;
+precision mediump sampler2D;
+#endif
+
+uniform sampler2D uSrc;
+
+varying vec2 vTexCoord;
+
+void main() {
+    gl_FragColor = texture2D(uSrc, vTexCoord);
+}
@@ -0,0 +1,38 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile_clip_copy.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#extension GL_GOOGLE_include_directive : enable
+
+precision highp float;
+precision highp sampler2D;
+
+#include "gles2/int32.inc.glsl"
+
+uniform vec2 uFramebufferSize;
+
+attribute vec2 aTileOffset;
+attribute vec2 aTileIndex;
+
+varying vec2 vTexCoord;
+
+void main() {
+    float tileIndex = decodeInt32(aTileIndex);
+    vec2 position = computeTileCoord(tileIndex) + aTileOffset;
+    position *= vec2(16.0, 4.0) / uFramebufferSize;
+
+    vTexCoord = position;
+
+    if (tileIndex < 0.0)
+        position = vec2(0.0);
+
+    gl_Position = vec4(mix(vec2(-1.0), vec2(1.0), position), 0.0, 1.0);
+}
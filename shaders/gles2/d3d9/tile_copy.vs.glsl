@@ -0,0 +1,24 @@
+#version 100
+
+// pathfinder/shaders/gles2/d3d9/tile_copy.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+precision highp float;
+precision highp sampler2D;
+
+uniform mat4 uTransform;
+uniform vec2 uTileSize;
+
+attribute vec2 aTilePosition;
+
+void main() {
+    vec2 position = aTilePosition * uTileSize;
+    gl_Position = uTransform * vec4(position, 0.0, 1.0);
+}
@@ -0,0 +1,22 @@
+// pathfinder/shaders/gles2/int32.inc.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Reassembles a 32-bit integer vertex attribute that was split into its low and high signed 16-bit
+// halves, as `VertexAttrDescriptor::to_float_compatible()` does.
+float decodeInt32(vec2 halves) {
+    float low = halves.x < 0.0 ? halves.x + 65536.0 : halves.x;
+    return halves.y * 65536.0 + low;
+}
+
+// Returns the column and row of a tile in a 256-tile-wide texture.
+vec2 computeTileCoord(float tileIndex) {
+    float row = floor((tileIndex + 0.5) / 256.0);
+    return vec2(tileIndex - row * 256.0, row);
+}
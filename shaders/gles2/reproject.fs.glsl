@@ -0,0 +1,30 @@
+#version 100
+
+// pathfinder/shaders/gles2/reproject.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+precision highp float;
+precision highp sampler2D;
+#else
+precision mediump float;
+precision mediump sampler2D;
+#endif
+
+uniform mat4 uOldTransform;
+uniform sampler2D uTexture;
+
+varying vec2 vTexCoord;
+
+void main() {
+    vec4 normTexCoord = uOldTransform * vec4(vTexCoord, 0.0, 1.0);
+    vec2 texCoord = ((normTexCoord.xy / normTexCoord.w) + 1.0) * 0.5;
+    gl_FragColor = texture2D(uTexture, texCoord);
+}
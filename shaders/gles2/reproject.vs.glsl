@@ -0,0 +1,25 @@
+#version 100
+
+// pathfinder/shaders/gles2/reproject.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+precision highp float;
+precision highp sampler2D;
+
+uniform mat4 uNewTransform;
+
+attribute vec2 aPosition;
+
+varying vec2 vTexCoord;
+
+void main() {
+    vTexCoord = aPosition;
+    gl_Position = uNewTransform * vec4(aPosition, 0.0, 1.0);
+}
@@ -0,0 +1,20 @@
+#version 100
+
+// pathfinder/shaders/gles2/stencil.vs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+precision highp float;
+precision highp sampler2D;
+
+attribute vec3 aPosition;
+
+void main() {
+    gl_Position = vec4(aPosition, 1.0);
+}
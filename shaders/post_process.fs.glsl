@@ -0,0 +1,74 @@
+#version 330
+
+// pathfinder/shaders/post_process.fs.glsl
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The built-in full-frame filters, run over the composited frame before it's presented.
+
+precision highp float;
+
+#ifdef GL_ES
+precision highp sampler2D;
+#endif
+
+#define POST_PROCESS_FILTER_COLOR_LUT       0
+#define POST_PROCESS_FILTER_VIGNETTE        1
+#define POST_PROCESS_FILTER_GAMMA           2
+#define POST_PROCESS_FILTER_UNPREMULTIPLY   3
+
+uniform sampler2D uSrc;
+uniform sampler2D uLUT;
+// Color LUT: x = LUT size.
+// Vignette: color to fade toward, with the strength in alpha.
+// Gamma: x = 1 / gamma.
+uniform vec4 uFilterParams0;
+// Vignette: x = inner radius, y = softness.
+uniform vec4 uFilterParams1;
+uniform int uFilterKind;
+
+in vec2 vTexCoord;
+
+out vec4 oFragColor;
+
+vec3 unpremultiply(vec4 color) {
+    return color.a == 0.0 ? vec3(0.0) : color.rgb / color.a;
+}
+
+// The LUT is laid out as `size` slices of `size`×`size` texels side by side, with red increasing
+// to the right within a slice, green increasing downward, and blue selecting the slice.
+vec3 sampleColorLUT(vec3 color, float size) {
+    vec3 scaled = clamp(color, 0.0, 1.0) * (size - 1.0);
+    float sliceLo = floor(scaled.b);
+    float sliceHi = min(sliceLo + 1.0, size - 1.0);
+    vec2 texCoord = (scaled.rg + 0.5) / vec2(size * size, size);
+    vec3 lo = texture(uLUT, texCoord + vec2(sliceLo / size, 0.0)).rgb;
+    vec3 hi = texture(uLUT, texCoord + vec2(sliceHi / size, 0.0)).rgb;
+    return mix(lo, hi, scaled.b - sliceLo);
+}
+
+void main() {
+    vec4 color = texture(uSrc, vTexCoord);
+
+    if (uFilterKind == POST_PROCESS_FILTER_COLOR_LUT) {
+        color.rgb = sampleColorLUT(unpremultiply(color), uFilterParams0.x) * color.a;
+    } else if (uFilterKind == POST_PROCESS_FILTER_VIGNETTE) {
+        // Distance from the center, normalized so that the corners are at 1.
+        float dist = length(vTexCoord - 0.5) * sqrt(2.0);
+        float amount = smoothstep(uFilterParams1.x, uFilterParams1.x + uFilterParams1.y, dist);
+        color.rgb = mix(color.rgb, uFilterParams0.rgb * color.a, amount * uFilterParams0.a);
+    } else if (uFilterKind == POST_PROCESS_FILTER_GAMMA) {
+        color.rgb = pow(unpremultiply(color), vec3(uFilterParams0.x)) * color.a;
+    } else {
+        // Off-screen renders become patterns this way, and patterns hold unpremultiplied colors.
+        color.rgb = unpremultiply(color);
+    }
+
+    oFragColor = color;
+}
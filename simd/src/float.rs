@@ -0,0 +1,126 @@
+// pathfinder/simd/src/float.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Floating-point math that works without `std`.
+//!
+//! Without `std`, `f32` lacks methods such as `sqrt()` that need the platform's math library.
+//! Importing `F32Ext` supplies them, implemented with `libm`. When `std` is available, its
+//! inherent methods take precedence, and the trait forwards to them.
+
+/// The `f32` methods that Pathfinder uses which aren't in `core`.
+pub trait F32Ext {
+    fn abs(self) -> f32;
+    fn acos(self) -> f32;
+    fn atan2(self, other: f32) -> f32;
+    fn cbrt(self) -> f32;
+    fn ceil(self) -> f32;
+    fn cos(self) -> f32;
+    fn floor(self) -> f32;
+    fn round(self) -> f32;
+    fn sin(self) -> f32;
+    fn sqrt(self) -> f32;
+    fn tan(self) -> f32;
+}
+
+#[cfg(feature = "std")]
+impl F32Ext for f32 {
+    #[inline]
+    fn abs(self) -> f32 {
+        f32::abs(self)
+    }
+    #[inline]
+    fn acos(self) -> f32 {
+        f32::acos(self)
+    }
+    #[inline]
+    fn atan2(self, other: f32) -> f32 {
+        f32::atan2(self, other)
+    }
+    #[inline]
+    fn cbrt(self) -> f32 {
+        f32::cbrt(self)
+    }
+    #[inline]
+    fn ceil(self) -> f32 {
+        f32::ceil(self)
+    }
+    #[inline]
+    fn cos(self) -> f32 {
+        f32::cos(self)
+    }
+    #[inline]
+    fn floor(self) -> f32 {
+        f32::floor(self)
+    }
+    #[inline]
+    fn round(self) -> f32 {
+        f32::round(self)
+    }
+    #[inline]
+    fn sin(self) -> f32 {
+        f32::sin(self)
+    }
+    #[inline]
+    fn sqrt(self) -> f32 {
+        f32::sqrt(self)
+    }
+    #[inline]
+    fn tan(self) -> f32 {
+        f32::tan(self)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl F32Ext for f32 {
+    #[inline]
+    fn abs(self) -> f32 {
+        libm::fabsf(self)
+    }
+    #[inline]
+    fn acos(self) -> f32 {
+        libm::acosf(self)
+    }
+    #[inline]
+    fn atan2(self, other: f32) -> f32 {
+        libm::atan2f(self, other)
+    }
+    #[inline]
+    fn cbrt(self) -> f32 {
+        libm::cbrtf(self)
+    }
+    #[inline]
+    fn ceil(self) -> f32 {
+        libm::ceilf(self)
+    }
+    #[inline]
+    fn cos(self) -> f32 {
+        libm::cosf(self)
+    }
+    #[inline]
+    fn floor(self) -> f32 {
+        libm::floorf(self)
+    }
+    #[inline]
+    fn round(self) -> f32 {
+        libm::roundf(self)
+    }
+    #[inline]
+    fn sin(self) -> f32 {
+        libm::sinf(self)
+    }
+    #[inline]
+    fn sqrt(self) -> f32 {
+        libm::sqrtf(self)
+    }
+    #[inline]
+    fn tan(self) -> f32 {
+        libm::tanf(self)
+    }
+}
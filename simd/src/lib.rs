@@ -10,8 +10,12 @@
 
 #![cfg_attr(pf_rustc_nightly, feature(link_llvm_intrinsics, platform_intrinsics))]
 #![cfg_attr(pf_rustc_nightly, feature(simd_ffi, stdsimd))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! A minimal SIMD abstraction, usable outside of Pathfinder.
+//!
+//! The `std` feature is on by default. Without it, this crate is `no_std`, and the `float` module
+//! provides the `f32` math functions that `core` lacks.
 
 #[cfg(all(not(feature = "pf-no-simd"), pf_rustc_nightly, target_arch = "aarch64"))]
 pub use crate::arm as default;
@@ -33,6 +37,7 @@ pub use crate::x86 as default;
 #[cfg(all(pf_rustc_nightly, target_arch = "aarch64"))]
 pub mod arm;
 mod extras;
+pub mod float;
 pub mod scalar;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub mod x86;
@@ -0,0 +1,737 @@
+// pathfinder/svg/src/animation.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Samples SMIL-animated SVGs at a point in time.
+//!
+//! `usvg` discards animation elements, so they're read from the source document when it's
+//! loaded. If only `transform`, `opacity`, and `display` are animated, the document is parsed
+//! once, with each animated element wrapped in a named group, and each sample sets the
+//! transforms and opacities of those groups. Animating any other attribute changes what `usvg`
+//! builds, so in that case each sample writes a static copy of the document with the animated
+//! attribute values and parses that instead.
+//!
+//! Only clock-value `begin` times are supported; event-based timing, `<animateMotion>`,
+//! `calcMode="spline"`, and CSS animations are ignored.
+
+use crate::compositing::{self, Compositing};
+use crate::xml::{self, ElementEdits, SVG_NAMESPACE, XLINK_NAMESPACE};
+use crate::SVGScene;
+use hashbrown::HashMap;
+use pathfinder_renderer::scene::Scene;
+use roxmltree::{Document, Node};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use usvg::Tree;
+use usvg::{Node as UsvgNode, NodeExt, NodeKind, Opacity, OptionsRef, Transform as UsvgTransform};
+
+const WRAPPER_ID_PREFIX: &str = "__pathfinder_animation_";
+
+/// An SVG document with SMIL animations that can be sampled at any time.
+///
+/// The parsed document is cached between samples, so this can't be shared between threads.
+pub struct SVGAnimation {
+    source: String,
+    animations: Vec<Animation>,
+    // The static document to apply the animated values to, if they can all be applied to groups.
+    wrapped_document: Option<WrappedDocument>,
+    // `wrapped_document`, once the first sample has parsed it.
+    tree: RefCell<Option<WrappedTree>>,
+}
+
+/// The reasons why an animated SVG couldn't be loaded or sampled.
+#[derive(Debug)]
+pub enum AnimationError {
+    /// The document isn't UTF-8.
+    Utf8,
+    /// The document isn't well-formed XML.
+    Xml(roxmltree::Error),
+    /// `usvg` rejected the sampled document.
+    Svg(usvg::Error),
+}
+
+impl SVGAnimation {
+    /// Loads an SVG document, keeping the animation elements that `usvg` would discard.
+    pub fn from_data(data: &[u8]) -> Result<SVGAnimation, AnimationError> {
+        let source = String::from_utf8(data.to_vec()).map_err(|_| AnimationError::Utf8)?;
+        let (animations, wrapped_document) = {
+            let document = Document::parse(&source).map_err(AnimationError::Xml)?;
+            let animations = animations(&document);
+            let wrapped_document = WrappedDocument::new(&document, &animations);
+            (animations, wrapped_document)
+        };
+        Ok(SVGAnimation {
+            source,
+            animations,
+            wrapped_document,
+            tree: RefCell::new(None),
+        })
+    }
+
+    /// Returns the time, in seconds, at which the last animation ends, or `None` if some
+    /// animation repeats indefinitely.
+    pub fn duration(&self) -> Option<f32> {
+        let mut duration = 0.0;
+        for animation in &self.animations {
+            duration = f32::max(duration, animation.end()?);
+        }
+        Some(duration)
+    }
+
+    /// Returns a static SVG document showing the animation at the given time, in seconds.
+    pub fn sample_source(&self, time: f32) -> String {
+        let document = Document::parse(&self.source).unwrap();
+
+        let mut edits: HashMap<usize, ElementEdits> = HashMap::new();
+        for (key, attribute, value) in self.values_at(time) {
+            edits
+                .entry(key)
+                .or_insert_with(ElementEdits::default)
+                .attributes
+                .push((attribute.to_owned(), value));
+        }
+        for node in document
+            .descendants()
+            .filter(|&node| is_animation_element(node))
+        {
+            edits
+                .entry(xml::element_key(node))
+                .or_insert_with(ElementEdits::default)
+                .removed = true;
+        }
+
+        xml::write_document(&document, &edits)
+    }
+
+    /// Builds the scene showing the animation at the given time, in seconds.
+    ///
+    /// When the document doesn't have to be rewritten, the first sample parses it with the given
+    /// options and later ones reuse that, so pass the same options each time.
+    pub fn sample(&self, time: f32, options: &OptionsRef) -> Result<SVGScene, AnimationError> {
+        let wrapped_document = match self.wrapped_document {
+            Some(ref wrapped_document) => wrapped_document,
+            None => {
+                return SVGScene::from_data(self.sample_source(time).as_bytes(), options)
+                    .map_err(AnimationError::Svg)
+            }
+        };
+
+        let mut tree = self.tree.borrow_mut();
+        if tree.is_none() {
+            *tree = Some(wrapped_document.parse(options)?);
+        }
+        let tree = tree.as_ref().unwrap();
+
+        let values: HashMap<(usize, &str), String> = self
+            .values_at(time)
+            .into_iter()
+            .map(|(key, attribute, value)| ((key, attribute), value))
+            .collect();
+        for (element, wrappers) in wrapped_document.elements.iter().zip(&tree.wrappers) {
+            element.apply(&values, wrappers);
+        }
+
+        Ok(SVGScene::from_tree_and_scene_with_size(
+            &tree.tree,
+            Scene::new(),
+            None,
+            wrapped_document.compositing.clone(),
+        ))
+    }
+
+    // Returns the key of each animated element, the animated attribute, and its value at the
+    // given time. Later values for the same attribute take precedence over earlier ones.
+    fn values_at(&self, time: f32) -> Vec<(usize, &str, String)> {
+        self.animations
+            .iter()
+            .filter_map(|animation| {
+                let value = animation.value_at(time)?;
+                Some((animation.target, animation.attribute.as_str(), value))
+            })
+            .collect()
+    }
+}
+
+// The source document with each animated element wrapped in a named group, which takes over the
+// animated attributes.
+struct WrappedDocument {
+    source: String,
+    compositing: HashMap<String, Compositing>,
+    elements: Vec<WrappedElement>,
+}
+
+struct WrappedElement {
+    key: usize,
+    wrapper_id: String,
+    // The animated attributes, with their values in the source document.
+    attributes: Vec<(String, Option<String>)>,
+}
+
+struct WrappedTree {
+    tree: Tree,
+    // The groups wrapping each element, with their transforms as parsed. An element reused by
+    // `<use>` has one for each use, and one that `usvg` removed as invisible has none.
+    wrappers: Vec<Vec<(UsvgNode, UsvgTransform)>>,
+}
+
+impl WrappedDocument {
+    // Returns `None` if some animation can't be applied to a wrapper group.
+    fn new(document: &Document, animations: &[Animation]) -> Option<WrappedDocument> {
+        let mut elements: Vec<WrappedElement> = vec![];
+        for animation in animations {
+            match animation.attribute.as_str() {
+                "transform" | "opacity" | "display" if animation.target_can_wrap => {}
+                _ => return None,
+            }
+
+            let index = match elements
+                .iter()
+                .position(|element| element.key == animation.target)
+            {
+                Some(index) => index,
+                None => {
+                    elements.push(WrappedElement {
+                        key: animation.target,
+                        wrapper_id: format!("{}{}", WRAPPER_ID_PREFIX, elements.len()),
+                        attributes: vec![],
+                    });
+                    elements.len() - 1
+                }
+            };
+            let attributes = &mut elements[index].attributes;
+            if attributes
+                .iter()
+                .all(|&(ref attribute, _)| *attribute != animation.attribute)
+            {
+                attributes.push((animation.attribute.clone(), animation.base_value.clone()));
+            }
+        }
+
+        // Composited elements get their own wrappers, outside the animated ones, since blending
+        // applies to the transformed, translucent element.
+        let (mut edits, compositing) = compositing::composited_element_edits(document);
+        for element in &elements {
+            let element_edits = edits
+                .entry(element.key)
+                .or_insert_with(ElementEdits::default);
+            element_edits.wrapper_ids.push(element.wrapper_id.clone());
+            for &(ref attribute, _) in &element.attributes {
+                let neutral_value = match &**attribute {
+                    "transform" => "matrix(1 0 0 1 0 0)",
+                    "opacity" => "1",
+                    _ => "inline",
+                };
+                element_edits
+                    .attributes
+                    .push((attribute.clone(), neutral_value.to_owned()));
+            }
+        }
+        for node in document
+            .descendants()
+            .filter(|&node| is_animation_element(node))
+        {
+            edits
+                .entry(xml::element_key(node))
+                .or_insert_with(ElementEdits::default)
+                .removed = true;
+        }
+
+        Some(WrappedDocument {
+            source: xml::write_document(document, &edits),
+            compositing,
+            elements,
+        })
+    }
+
+    fn parse(&self, options: &OptionsRef) -> Result<WrappedTree, AnimationError> {
+        // The wrapper groups are named, and `usvg` removes them otherwise.
+        let mut options = options.clone();
+        options.keep_named_groups = true;
+        let tree = Tree::from_str(&self.source, &options).map_err(AnimationError::Svg)?;
+
+        let indices: HashMap<&str, usize> = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (element.wrapper_id.as_str(), index))
+            .collect();
+        let mut wrappers = vec![vec![]; self.elements.len()];
+        for node in tree.root().descendants() {
+            let index = match *node.borrow() {
+                NodeKind::Group(ref group) => indices.get(group.id.as_str()).cloned(),
+                _ => None,
+            };
+            if let Some(index) = index {
+                let transform = node.transform();
+                wrappers[index].push((node, transform));
+            }
+        }
+
+        Ok(WrappedTree { tree, wrappers })
+    }
+}
+
+impl WrappedElement {
+    fn apply(
+        &self,
+        values: &HashMap<(usize, &str), String>,
+        wrappers: &[(UsvgNode, UsvgTransform)],
+    ) {
+        let (mut transform, mut opacity, mut displayed) = (UsvgTransform::default(), 1.0, true);
+        for &(ref attribute, ref base_value) in &self.attributes {
+            let value = match values.get(&(self.key, attribute.as_str())) {
+                Some(value) => value.as_str(),
+                None => match *base_value {
+                    Some(ref base_value) => base_value.as_str(),
+                    None => continue,
+                },
+            };
+            match &**attribute {
+                "transform" => {
+                    // An invalid transform is ignored, as if the attribute weren't there.
+                    transform = svgtypes::Transform::from_str(value)
+                        .map(UsvgTransform::from)
+                        .unwrap_or_default();
+                }
+                "opacity" => {
+                    opacity = f64::from_str(value.trim())
+                        .ok()
+                        .filter(|opacity| opacity.is_finite())
+                        .map_or(1.0, |opacity| opacity.clamp(0.0, 1.0));
+                }
+                _ => displayed = value.trim() != "none",
+            }
+        }
+        if !displayed {
+            opacity = 0.0;
+        }
+
+        for &(ref node, ref parsed_transform) in wrappers {
+            // `rctree` only hands out mutable borrows through mutable handles.
+            let mut node = node.clone();
+            let mut node_kind = node.borrow_mut();
+            if let NodeKind::Group(ref mut group) = *node_kind {
+                group.transform = *parsed_transform;
+                group.transform.append(&transform);
+                group.opacity = Opacity::new(opacity);
+            }
+        }
+    }
+}
+
+struct Animation {
+    // The key of the animated element; see `xml::element_key()`.
+    target: usize,
+    // Whether the animated element can be wrapped in a group; see `compositing::can_wrap()`.
+    target_can_wrap: bool,
+    attribute: String,
+    // The animated attribute's value in the source document.
+    base_value: Option<String>,
+    kind: AnimationKind,
+    values: Vec<String>,
+    key_times: Option<Vec<f32>>,
+    discrete: bool,
+    begin: f32,
+    duration: f32,
+    repeat_count: Option<f32>,
+    freeze: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AnimationKind {
+    Set,
+    Animate,
+    // `<animateTransform>`, with whether the result is appended to the base transform.
+    Transform { additive: bool },
+}
+
+impl Animation {
+    fn from_node(node: Node) -> Option<Animation> {
+        let kind = match node.tag_name().name() {
+            "set" => AnimationKind::Set,
+            "animate" => AnimationKind::Animate,
+            "animateTransform" => AnimationKind::Transform {
+                additive: node.attribute("additive") == Some("sum"),
+            },
+            _ => return None,
+        };
+
+        let target = match node
+            .attribute((XLINK_NAMESPACE, "href"))
+            .or(node.attribute("href"))
+        {
+            Some(href) => {
+                let id = href.strip_prefix('#')?;
+                node.document()
+                    .descendants()
+                    .find(|node| node.attribute("id") == Some(id))?
+            }
+            None => node.parent_element()?,
+        };
+
+        let attribute = match kind {
+            AnimationKind::Transform { .. } => "transform".to_owned(),
+            _ => node.attribute("attributeName")?.to_owned(),
+        };
+
+        let transform_type = node.attribute("type").unwrap_or("translate");
+        let mut values: Vec<String> = match node.attribute("values") {
+            Some(values) => values
+                .split(';')
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+                .collect(),
+            None => {
+                let to = node.attribute("to")?.to_owned();
+                match node.attribute("from") {
+                    Some(from) => vec![from.to_owned(), to],
+                    // Without `from`, the animation starts at the attribute's base value.
+                    None if kind == AnimationKind::Animate => match target.attribute(&*attribute) {
+                        Some(from) => vec![from.to_owned(), to],
+                        None => vec![to],
+                    },
+                    None => vec![to],
+                }
+            }
+        };
+        if values.is_empty() {
+            return None;
+        }
+        if let AnimationKind::Transform { .. } = kind {
+            for value in &mut values {
+                *value = format!("{}({})", transform_type, value);
+            }
+        }
+
+        let key_times = node.attribute("keyTimes").and_then(|key_times| {
+            key_times
+                .split(';')
+                .map(|key_time| key_time.trim().parse().ok())
+                .collect::<Option<Vec<f32>>>()
+                .filter(|key_times| key_times.len() == values.len())
+        });
+
+        let begin = match node.attribute("begin") {
+            None => 0.0,
+            Some(begin) => parse_clock_value(begin.split(';').next().unwrap_or(""))?,
+        };
+        let duration = node
+            .attribute("dur")
+            .and_then(parse_clock_value)
+            .unwrap_or(std::f32::INFINITY);
+        let repeat_count = match node.attribute("repeatCount") {
+            Some("indefinite") => None,
+            Some(repeat_count) => Some(repeat_count.parse().unwrap_or(1.0)),
+            None if node.attribute("repeatDur") == Some("indefinite") => None,
+            None => Some(1.0),
+        };
+
+        Some(Animation {
+            target: xml::element_key(target),
+            target_can_wrap: compositing::can_wrap(target),
+            base_value: target.attribute(&*attribute).map(str::to_owned),
+            attribute,
+            kind,
+            values,
+            key_times,
+            discrete: kind == AnimationKind::Set || node.attribute("calcMode") == Some("discrete"),
+            begin,
+            duration,
+            repeat_count,
+            freeze: node.attribute("fill") == Some("freeze"),
+        })
+    }
+
+    fn end(&self) -> Option<f32> {
+        Some(self.begin + self.duration * self.repeat_count?)
+    }
+
+    fn value_at(&self, time: f32) -> Option<String> {
+        if time < self.begin {
+            return None;
+        }
+
+        let progress = match self.end() {
+            Some(end) if time >= end => {
+                if !self.freeze {
+                    return None;
+                }
+                // Frozen animations keep the value from the end of their last iteration.
+                let fraction = self.repeat_count.unwrap_or(1.0).fract();
+                if fraction == 0.0 {
+                    1.0
+                } else {
+                    fraction
+                }
+            }
+            _ if self.duration.is_infinite() => 0.0,
+            _ => ((time - self.begin) / self.duration).fract(),
+        };
+
+        let value = self.interpolate(progress);
+        match self.kind {
+            AnimationKind::Transform { additive: true } => match self.base_value {
+                Some(ref base_value) => Some(format!("{} {}", base_value, value)),
+                None => Some(value),
+            },
+            _ => Some(value),
+        }
+    }
+
+    fn interpolate(&self, progress: f32) -> String {
+        let count = self.values.len();
+        if count == 1 {
+            return self.values[0].clone();
+        }
+
+        let key_times: Vec<f32> = match self.key_times {
+            Some(ref key_times) => key_times.clone(),
+            None if self.discrete => (0..count).map(|i| i as f32 / count as f32).collect(),
+            None => (0..count).map(|i| i as f32 / (count - 1) as f32).collect(),
+        };
+        let index = key_times
+            .iter()
+            .rposition(|&key_time| key_time <= progress)
+            .unwrap_or(0);
+        if self.discrete || index + 1 >= count {
+            return self.values[index].clone();
+        }
+
+        let span = key_times[index + 1] - key_times[index];
+        let local_progress = if span > 0.0 {
+            (progress - key_times[index]) / span
+        } else {
+            0.0
+        };
+        interpolate_values(&self.values[index], &self.values[index + 1], local_progress)
+    }
+}
+
+fn animations(document: &Document) -> Vec<Animation> {
+    document
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().namespace() == Some(SVG_NAMESPACE))
+        .filter_map(Animation::from_node)
+        .collect()
+}
+
+// Interpolates the numbers in two values that otherwise match, such as `"10 20"` and `"30 40"` or
+// `"rotate(0)"` and `"rotate(90)"`. Colors in hex notation are interpolated by channel. Anything
+// else switches halfway through, as `calcMode="discrete"` would.
+fn interpolate_values(from: &str, to: &str, progress: f32) -> String {
+    if let (Some(from), Some(to)) = (parse_hex_color(from), parse_hex_color(to)) {
+        let channel =
+            |i: usize| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * progress).round() as u8;
+        return format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2));
+    }
+
+    let (from_text, from_numbers) = split_numbers(from);
+    let (to_text, to_numbers) = split_numbers(to);
+    if from_text != to_text || from_numbers.len() != to_numbers.len() {
+        return if progress < 0.5 { from } else { to }.to_owned();
+    }
+
+    let mut output = String::new();
+    for (index, text) in from_text.iter().enumerate() {
+        output.push_str(text);
+        if let (Some(from), Some(to)) = (from_numbers.get(index), to_numbers.get(index)) {
+            output.push_str(&(from + (to - from) * progress).to_string());
+        }
+    }
+    output
+}
+
+// Splits a value into the text around its numbers and the numbers themselves.
+fn split_numbers(value: &str) -> (Vec<&str>, Vec<f32>) {
+    let (mut text, mut numbers) = (vec![], vec![]);
+    let bytes = value.as_bytes();
+    let (mut text_start, mut index) = (0, 0);
+    while index < bytes.len() {
+        let starts_number = bytes[index].is_ascii_digit()
+            || ((bytes[index] == b'-' || bytes[index] == b'.')
+                && bytes
+                    .get(index + 1)
+                    .map_or(false, |byte| byte.is_ascii_digit()));
+        if !starts_number {
+            index += 1;
+            continue;
+        }
+
+        let mut end = index + 1;
+        while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+            end += 1;
+        }
+        if let Ok(number) = f32::from_str(&value[index..end]) {
+            text.push(&value[text_start..index]);
+            numbers.push(number);
+            text_start = end;
+        }
+        index = end;
+    }
+    text.push(&value[text_start..]);
+    (text, numbers)
+}
+
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let digits = value.trim().strip_prefix('#')?;
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(digits.get(range)?, 16).ok();
+    match digits.len() {
+        3 => Some([
+            channel(0..1)? * 17,
+            channel(1..2)? * 17,
+            channel(2..3)? * 17,
+        ]),
+        6 => Some([channel(0..2)?, channel(2..4)?, channel(4..6)?]),
+        _ => None,
+    }
+}
+
+// Parses a SMIL clock value, such as `2s`, `500ms`, `1.5min`, or `00:01:30.5`, into seconds.
+fn parse_clock_value(value: &str) -> Option<f32> {
+    let value = value.trim();
+    if value.contains(':') {
+        return value.split(':').try_fold(0.0, |total, part| {
+            Some(total * 60.0 + part.parse::<f32>().ok()?)
+        });
+    }
+
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix("min") {
+        (number, 60.0)
+    } else if let Some(number) = value.strip_suffix('h') {
+        (number, 3600.0)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else {
+        (value, 1.0)
+    };
+    number
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .map(|number| number * scale)
+}
+
+fn is_animation_element(node: Node) -> bool {
+    match node.tag_name().name() {
+        "set" | "animate" | "animateTransform" | "animateMotion" | "animateColor" => {
+            node.tag_name().namespace() == Some(SVG_NAMESPACE)
+        }
+        _ => false,
+    }
+}
+
+impl Display for AnimationError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            AnimationError::Utf8 => write!(formatter, "the SVG document isn't valid UTF-8"),
+            AnimationError::Xml(ref error) => write!(formatter, "XML error: {}", error),
+            AnimationError::Svg(ref error) => write!(formatter, "SVG error: {}", error),
+        }
+    }
+}
+
+impl Error for AnimationError {}
+
+#[cfg(test)]
+mod test {
+    use super::SVGAnimation;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use pathfinder_renderer::scene::{DrawPath, DrawPathId, Scene};
+    use usvg::Options;
+
+    fn animation(content: &str) -> SVGAnimation {
+        let source = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"100\">{}</svg>",
+            content
+        );
+        SVGAnimation::from_data(source.as_bytes()).unwrap()
+    }
+
+    fn sample(animation: &SVGAnimation, time: f32) -> Scene {
+        let options = Options::default();
+        animation.sample(time, &options.to_ref()).unwrap().scene
+    }
+
+    fn last_path(scene: &Scene) -> &DrawPath {
+        scene.get_draw_path(DrawPathId(scene.draw_path_count() - 1))
+    }
+
+    fn last_path_bounds(scene: &Scene) -> RectF {
+        let draw_path = last_path(scene);
+        draw_path.transform() * draw_path.outline().bounds()
+    }
+
+    fn last_path_alpha(scene: &Scene) -> u8 {
+        scene.get_paint(last_path(scene).paint()).base_color().a
+    }
+
+    #[test]
+    fn animate_transform_is_applied_to_the_parsed_tree() {
+        let animation = animation(
+            "<rect width=\"10\" height=\"10\" transform=\"translate(0 5)\">\
+                <animateTransform attributeName=\"transform\" type=\"translate\" from=\"0 0\" \
+                    to=\"40 0\" dur=\"2s\" fill=\"freeze\"/>\
+            </rect>",
+        );
+        assert!(animation.wrapped_document.is_some());
+
+        let bounds_at = |time| last_path_bounds(&sample(&animation, time));
+        assert_eq!(
+            bounds_at(1.0),
+            RectF::new(vec2f(20.0, 0.0), vec2f(10.0, 10.0))
+        );
+        assert_eq!(
+            bounds_at(0.5),
+            RectF::new(vec2f(10.0, 0.0), vec2f(10.0, 10.0))
+        );
+        assert_eq!(
+            bounds_at(3.0),
+            RectF::new(vec2f(40.0, 0.0), vec2f(10.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn animate_opacity_is_applied_to_the_parsed_tree() {
+        let animation = animation(
+            "<rect width=\"10\" height=\"10\">\
+                <animate attributeName=\"opacity\" from=\"1\" to=\"0\" dur=\"2s\"/>\
+            </rect>",
+        );
+        assert!(animation.wrapped_document.is_some());
+
+        assert_eq!(last_path_alpha(&sample(&animation, 1.0)), 128);
+        assert_eq!(last_path_alpha(&sample(&animation, 1.5)), 64);
+
+        // Once the animation ends, the rectangle is opaque again.
+        let scene = sample(&animation, 2.5);
+        assert_eq!(scene.draw_path_count(), 1);
+        assert_eq!(last_path_alpha(&scene), 255);
+    }
+
+    #[test]
+    fn other_animated_attributes_rewrite_the_document() {
+        let animation = animation(
+            "<rect width=\"10\" height=\"10\">\
+                <animate attributeName=\"width\" from=\"10\" to=\"30\" dur=\"2s\"/>\
+            </rect>",
+        );
+        assert!(animation.wrapped_document.is_none());
+
+        let scene = sample(&animation, 1.0);
+        assert_eq!(
+            last_path_bounds(&scene),
+            RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 10.0))
+        );
+    }
+}
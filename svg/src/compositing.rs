@@ -0,0 +1,146 @@
+// pathfinder/svg/src/compositing.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for the `mix-blend-mode` and `isolation` properties.
+//!
+//! `usvg` drops both properties, so each element that sets them is wrapped in a `<g>` with a
+//! generated ID before the document is parsed. `usvg` keeps named groups when asked to, and the
+//! wrapper group is then composited with the element's blend mode and isolation.
+
+use crate::xml::{self, ElementEdits, SVG_NAMESPACE};
+use hashbrown::HashMap;
+use pathfinder_content::effects::BlendMode;
+use roxmltree::{Document, Node};
+
+const WRAPPER_ID_PREFIX: &str = "__pathfinder_compositing_";
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Compositing {
+    pub(crate) blend_mode: BlendMode,
+    pub(crate) isolated: bool,
+}
+
+/// Rewrites the document so that `usvg` keeps a group for each element with a blend mode or
+/// isolation. Returns the rewritten source and the compositing of each of those groups, by ID.
+pub(crate) fn wrap_composited_elements(
+    document: &Document,
+) -> (String, HashMap<String, Compositing>) {
+    let (edits, compositing) = composited_element_edits(document);
+    (xml::write_document(document, &edits), compositing)
+}
+
+/// Returns the edits that wrap each element with a blend mode or isolation in a group, and the
+/// compositing of each of those groups, by ID.
+pub(crate) fn composited_element_edits(
+    document: &Document,
+) -> (HashMap<usize, ElementEdits>, HashMap<String, Compositing>) {
+    let mut edits = HashMap::new();
+    let mut compositing = HashMap::new();
+    for node in document.descendants() {
+        if !can_wrap(node) {
+            continue;
+        }
+        let node_compositing = match Compositing::from_node(node) {
+            Some(node_compositing) => node_compositing,
+            None => continue,
+        };
+
+        let wrapper_id = format!("{}{}", WRAPPER_ID_PREFIX, compositing.len());
+        compositing.insert(wrapper_id.clone(), node_compositing);
+        edits.insert(
+            xml::element_key(node),
+            ElementEdits {
+                wrapper_ids: vec![wrapper_id],
+                ..ElementEdits::default()
+            },
+        );
+    }
+
+    (edits, compositing)
+}
+
+impl Compositing {
+    fn from_node(node: Node) -> Option<Compositing> {
+        // Style declarations override presentation attributes.
+        let mut blend_mode = node.attribute("mix-blend-mode");
+        let mut isolation = node.attribute("isolation");
+        for declaration in node.attribute("style").unwrap_or("").split(';') {
+            let mut parts = declaration.splitn(2, ':');
+            let (property, value) = match (parts.next(), parts.next()) {
+                (Some(property), Some(value)) => (property.trim(), value.trim()),
+                _ => continue,
+            };
+            match property {
+                "mix-blend-mode" => blend_mode = Some(value),
+                "isolation" => isolation = Some(value),
+                _ => {}
+            }
+        }
+
+        let compositing = Compositing {
+            blend_mode: blend_mode
+                .and_then(|blend_mode| blend_mode_from_css(blend_mode.trim()))
+                .unwrap_or(BlendMode::SrcOver),
+            isolated: isolation.map(str::trim) == Some("isolate"),
+        };
+        if compositing.blend_mode == BlendMode::SrcOver && !compositing.isolated {
+            None
+        } else {
+            Some(compositing)
+        }
+    }
+}
+
+// Whether wrapping the element in a group is valid and affects rendering.
+//
+// The root element has nothing underneath it to blend with, and content inside clip paths and
+// masks contributes only its shape. Wrapping the children of a `<switch>` would change which one
+// is chosen.
+pub(crate) fn can_wrap(node: Node) -> bool {
+    if !node.is_element() || node.tag_name().namespace() != Some(SVG_NAMESPACE) {
+        return false;
+    }
+    match node.tag_name().name() {
+        "g" | "path" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon"
+        | "image" | "use" | "text" | "svg" | "switch" => {}
+        _ => return false,
+    }
+    match node.parent_element() {
+        None => return false,
+        Some(parent) if parent.tag_name().name() == "switch" => return false,
+        Some(_) => {}
+    }
+    !node
+        .ancestors()
+        .any(|ancestor| matches!(ancestor.tag_name().name(), "clipPath" | "mask"))
+}
+
+fn blend_mode_from_css(name: &str) -> Option<BlendMode> {
+    match name {
+        "normal" => Some(BlendMode::SrcOver),
+        "multiply" => Some(BlendMode::Multiply),
+        "screen" => Some(BlendMode::Screen),
+        "overlay" => Some(BlendMode::Overlay),
+        "darken" => Some(BlendMode::Darken),
+        "lighten" => Some(BlendMode::Lighten),
+        "color-dodge" => Some(BlendMode::ColorDodge),
+        "color-burn" => Some(BlendMode::ColorBurn),
+        "hard-light" => Some(BlendMode::HardLight),
+        "soft-light" => Some(BlendMode::SoftLight),
+        "difference" => Some(BlendMode::Difference),
+        "exclusion" => Some(BlendMode::Exclusion),
+        "hue" => Some(BlendMode::Hue),
+        "saturation" => Some(BlendMode::Saturation),
+        "color" => Some(BlendMode::Color),
+        "luminosity" => Some(BlendMode::Luminosity),
+        "plus-lighter" => Some(BlendMode::Lighter),
+        _ => None,
+    }
+}
@@ -0,0 +1,327 @@
+// pathfinder/svg/src/filter.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Maps SVG filter effects onto render targets and pattern filters.
+//!
+//! Every intermediate result is a render target the size of the view box, like the ones groups
+//! use, so results can be sampled without any transform. Primitives are evaluated in device space
+//! and in sRGB, regardless of `color-interpolation-filters`, and primitive subregions are ignored.
+
+use crate::{usvg_rect_to_euclid_rect, BuildResultFlags, ColorUExt, SVGScene, State};
+use hashbrown::HashMap;
+use pathfinder_color::matrix::ColorMatrix;
+use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::{BlurDirection, PatternFilter};
+use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::Pattern;
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F, Vector2I};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{DrawPath, RenderTarget};
+use usvg::{FeColorMatrixKind, Filter, FilterInput, FilterKind, Units};
+
+/// The render targets that filter primitives can read from.
+struct FilterInputs {
+    source_graphic: RenderTargetId,
+    source_alpha: Option<RenderTargetId>,
+    results: HashMap<String, RenderTargetId>,
+    previous: RenderTargetId,
+}
+
+impl SVGScene {
+    /// Begins capturing the content of a filtered group into a render target.
+    ///
+    /// Returns the bounds of the content drawn so far, which must be passed back to
+    /// `pop_filtered_group()`.
+    pub(crate) fn push_filtered_group(&mut self) -> (RenderTargetId, Option<RectF>) {
+        let size = self.filter_target_size();
+        let render_target = RenderTarget::new(size, String::new());
+        let render_target_id = self.scene.push_render_target(render_target);
+        (render_target_id, self.source_bounds.take())
+    }
+
+    /// Finishes capturing the content of a filtered group, runs the filters over it, and draws
+    /// the result.
+    pub(crate) fn pop_filtered_group(
+        &mut self,
+        source_graphic: RenderTargetId,
+        outer_bounds: Option<RectF>,
+        filters: &[Filter],
+        state: &State,
+    ) {
+        self.scene.pop_render_target();
+
+        let size = self.filter_target_size();
+        let view_box = RectF::new(Vector2F::zero(), size.to_f32());
+        let mut result = source_graphic;
+        let mut bounds = self.source_bounds.unwrap_or_default();
+        for filter in filters {
+            bounds = filter_region(filter, bounds, &state.transform)
+                .round_out()
+                .intersection(view_box)
+                .unwrap_or_default();
+            result = self.apply_filter(filter, result, bounds, &state.transform);
+        }
+
+        let paint = Paint::from_pattern(Pattern::from_render_target(result, size));
+        let paint_id = self.scene.push_paint(&paint);
+        let mut path = DrawPath::new(Outline::from_rect(bounds), paint_id);
+        path.set_clip_path(state.clip_path);
+        path.set_name(format!("Filter({})", filters[0].id));
+        self.scene.push_draw_path(path);
+
+        self.source_bounds = match outer_bounds {
+            Some(outer_bounds) => Some(outer_bounds.union_rect(bounds)),
+            None => Some(bounds),
+        };
+    }
+
+    fn apply_filter(
+        &mut self,
+        filter: &Filter,
+        source_graphic: RenderTargetId,
+        region: RectF,
+        transform: &Transform2F,
+    ) -> RenderTargetId {
+        // TODO(pcwalton): Scale lengths by the bounding box if `primitiveUnits` is
+        // `objectBoundingBox`.
+        let mut inputs = FilterInputs {
+            source_graphic,
+            source_alpha: None,
+            results: HashMap::new(),
+            previous: source_graphic,
+        };
+
+        for primitive in &filter.children {
+            let result = match primitive.kind {
+                FilterKind::FeGaussianBlur(ref blur) => {
+                    let input = self.filter_input(&blur.input, &mut inputs, region);
+                    let std_dev =
+                        vec2f(blur.std_dev_x.value() as f32, blur.std_dev_y.value() as f32);
+                    self.blur(input, transform.extract_scale() * std_dev, region)
+                }
+                FilterKind::FeOffset(ref offset) => {
+                    let input = self.filter_input(&offset.input, &mut inputs, region);
+                    let offset = transform.matrix * vec2f(offset.dx as f32, offset.dy as f32);
+                    let paint = self.render_target_paint(input, offset, None);
+                    self.draw_filter_layers(&[paint], region)
+                }
+                FilterKind::FeFlood(ref flood) => {
+                    let mut color = ColorU::from_svg_color(flood.color);
+                    color.a = (flood.opacity.value() * 255.0).round() as u8;
+                    self.draw_filter_layers(&[Paint::from_color(color)], region)
+                }
+                FilterKind::FeColorMatrix(ref color_matrix) => {
+                    let input = self.filter_input(&color_matrix.input, &mut inputs, region);
+                    let filter = PatternFilter::ColorMatrix(
+                        ColorMatrix::from_usvg_color_matrix_kind(&color_matrix.kind),
+                    );
+                    let paint = self.render_target_paint(input, Vector2F::zero(), Some(filter));
+                    self.draw_filter_layers(&[paint], region)
+                }
+                FilterKind::FeMerge(ref merge) => {
+                    let mut paints = vec![];
+                    for input in &merge.inputs {
+                        let input = self.filter_input(input, &mut inputs, region);
+                        paints.push(self.render_target_paint(input, Vector2F::zero(), None));
+                    }
+                    self.draw_filter_layers(&paints, region)
+                }
+                FilterKind::FeDropShadow(ref drop_shadow) => {
+                    let input = self.filter_input(&drop_shadow.input, &mut inputs, region);
+                    let std_dev = vec2f(
+                        drop_shadow.std_dev_x.value() as f32,
+                        drop_shadow.std_dev_y.value() as f32,
+                    );
+                    let blurred = self.blur(input, transform.extract_scale() * std_dev, region);
+
+                    let mut color = ColorU::from_svg_color(drop_shadow.color).to_f32();
+                    color.set_a(drop_shadow.opacity.value() as f32);
+                    let offset = vec2f(drop_shadow.dx as f32, drop_shadow.dy as f32);
+                    let shadow_filter = PatternFilter::ColorMatrix(ColorMatrix::shadow(color));
+                    let shadow_paint = self.render_target_paint(
+                        blurred,
+                        transform.matrix * offset,
+                        Some(shadow_filter),
+                    );
+                    let input_paint = self.render_target_paint(input, Vector2F::zero(), None);
+                    self.draw_filter_layers(&[shadow_paint, input_paint], region)
+                }
+                _ => {
+                    // TODO(pcwalton): Support the rest of the filter primitives.
+                    self.result_flags
+                        .insert(BuildResultFlags::UNSUPPORTED_FILTER_PRIMITIVE);
+                    inputs.previous
+                }
+            };
+
+            if !primitive.result.is_empty() {
+                inputs.results.insert(primitive.result.clone(), result);
+            }
+            inputs.previous = result;
+        }
+
+        inputs.previous
+    }
+
+    fn filter_input(
+        &mut self,
+        input: &FilterInput,
+        inputs: &mut FilterInputs,
+        region: RectF,
+    ) -> RenderTargetId {
+        match *input {
+            FilterInput::SourceGraphic => inputs.source_graphic,
+            FilterInput::SourceAlpha => {
+                if let Some(source_alpha) = inputs.source_alpha {
+                    return source_alpha;
+                }
+                let filter = PatternFilter::ColorMatrix(ColorMatrix::alpha_only());
+                let paint =
+                    self.render_target_paint(inputs.source_graphic, Vector2F::zero(), Some(filter));
+                let source_alpha = self.draw_filter_layers(&[paint], region);
+                inputs.source_alpha = Some(source_alpha);
+                source_alpha
+            }
+            FilterInput::Reference(ref name) => match inputs.results.get(name) {
+                Some(&result) => result,
+                None => inputs.previous,
+            },
+            _ => {
+                // TODO(pcwalton): Support background and paint inputs.
+                self.result_flags
+                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_PRIMITIVE);
+                inputs.source_graphic
+            }
+        }
+    }
+
+    /// Performs a two-pass Gaussian blur, the same way the canvas blurs shadows.
+    fn blur(&mut self, input: RenderTargetId, sigma: Vector2F, region: RectF) -> RenderTargetId {
+        let mut result = input;
+        if sigma.x() > 0.0 {
+            let filter = PatternFilter::Blur {
+                direction: BlurDirection::X,
+                sigma: sigma.x(),
+            };
+            let paint = self.render_target_paint(result, Vector2F::zero(), Some(filter));
+            result = self.draw_filter_layers(&[paint], region);
+        }
+        if sigma.y() > 0.0 {
+            let filter = PatternFilter::Blur {
+                direction: BlurDirection::Y,
+                sigma: sigma.y(),
+            };
+            let paint = self.render_target_paint(result, Vector2F::zero(), Some(filter));
+            result = self.draw_filter_layers(&[paint], region);
+        }
+        result
+    }
+
+    /// Draws the given paints, in order, over the filter region of a new render target.
+    fn draw_filter_layers(&mut self, paints: &[Paint], region: RectF) -> RenderTargetId {
+        let size = self.filter_target_size();
+        let render_target_id = self
+            .scene
+            .push_render_target(RenderTarget::new(size, String::new()));
+        for paint in paints {
+            let paint_id = self.scene.push_paint(paint);
+            let path = DrawPath::new(Outline::from_rect(region), paint_id);
+            self.scene.push_draw_path(path);
+        }
+        self.scene.pop_render_target();
+        render_target_id
+    }
+
+    fn render_target_paint(
+        &self,
+        render_target_id: RenderTargetId,
+        offset: Vector2F,
+        filter: Option<PatternFilter>,
+    ) -> Paint {
+        let mut pattern = Pattern::from_render_target(render_target_id, self.filter_target_size());
+        pattern.apply_transform(Transform2F::from_translation(offset));
+        pattern.set_filter(filter);
+        Paint::from_pattern(pattern)
+    }
+
+    #[inline]
+    fn filter_target_size(&self) -> Vector2I {
+        self.scene.view_box().lower_right().ceil().to_i32()
+    }
+}
+
+/// Returns the filter region in device space.
+///
+/// `bounds` is the device-space bounding box of the filtered content, so regions in
+/// `objectBoundingBox` units are only exact for axis-aligned transforms.
+fn filter_region(filter: &Filter, bounds: RectF, transform: &Transform2F) -> RectF {
+    let rect = usvg_rect_to_euclid_rect(&filter.rect);
+    match filter.units {
+        Units::UserSpaceOnUse => *transform * rect,
+        Units::ObjectBoundingBox => RectF::new(
+            bounds.origin() + rect.origin() * bounds.size(),
+            rect.size() * bounds.size(),
+        ),
+    }
+}
+
+trait ColorMatrixExt {
+    fn from_usvg_color_matrix_kind(kind: &FeColorMatrixKind) -> Self;
+    fn alpha_only() -> Self;
+    fn shadow(color: ColorF) -> Self;
+}
+
+impl ColorMatrixExt for ColorMatrix {
+    fn from_usvg_color_matrix_kind(kind: &FeColorMatrixKind) -> ColorMatrix {
+        match *kind {
+            FeColorMatrixKind::Matrix(ref values) => {
+                let mut rows = [[0.0; 5]; 4];
+                for (index, &value) in values.iter().take(20).enumerate() {
+                    rows[index / 5][index % 5] = value as f32;
+                }
+                ColorMatrix::from_rows(rows)
+            }
+            FeColorMatrixKind::Saturate(saturation) => {
+                ColorMatrix::saturate(saturation.value() as f32)
+            }
+            FeColorMatrixKind::HueRotate(angle) => {
+                ColorMatrix::hue_rotate((angle as f32).to_radians())
+            }
+            FeColorMatrixKind::LuminanceToAlpha => ColorMatrix::luminance_to_alpha(),
+        }
+    }
+
+    /// Keeps the alpha channel and makes the color black, like the `SourceAlpha` input.
+    fn alpha_only() -> ColorMatrix {
+        ColorMatrix::from_rows([
+            [0.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+        ])
+    }
+
+    /// Replaces the color with `color`, scaling by the source alpha.
+    ///
+    /// Render targets hold premultiplied colors, so the color channels come from the alpha column
+    /// rather than the constant one.
+    fn shadow(color: ColorF) -> ColorMatrix {
+        let a = color.a();
+        ColorMatrix::from_rows([
+            [0.0, 0.0, 0.0, color.r() * a, 0.0],
+            [0.0, 0.0, 0.0, color.g() * a, 0.0],
+            [0.0, 0.0, 0.0, color.b() * a, 0.0],
+            [0.0, 0.0, 0.0, a, 0.0],
+        ])
+    }
+}
@@ -13,12 +13,16 @@
 #[macro_use]
 extern crate bitflags;
 
+use crate::compositing::Compositing;
 use hashbrown::HashMap;
+use image::ImageFormat;
 use pathfinder_color::ColorU;
 use pathfinder_content::dash::OutlineDash;
 use pathfinder_content::fill::FillRule;
-use pathfinder_content::gradient::{ColorStop, Gradient, GradientWrap};
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientGeometry, GradientWrap};
+use pathfinder_content::orientation::Orientation;
 use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::{Image, Pattern};
 use pathfinder_content::segment::{Segment, SegmentFlags};
 use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
 use pathfinder_content::transform::Transform2FPathIter;
@@ -27,13 +31,26 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{vec2f, Vector2F};
 use pathfinder_renderer::paint::Paint;
-use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Scene};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Group, Scene};
 use pathfinder_simd::default::F32x2;
 use std::fmt::{Display, Formatter, Result as FormatResult};
-use usvg::{BaseGradient, Color as SvgColor, FillRule as UsvgFillRule, LineCap as UsvgLineCap};
-use usvg::{LineJoin as UsvgLineJoin, Node, NodeExt, NodeKind, Opacity, Paint as UsvgPaint};
-use usvg::{PathSegment as UsvgPathSegment, Rect as UsvgRect, SpreadMethod, Stop};
-use usvg::{Transform as UsvgTransform, Tree, Visibility};
+use std::rc::Rc;
+use std::str;
+use std::sync::Arc;
+use usvg::{Align, AspectRatio, BaseGradient, Color as SvgColor, FillRule as UsvgFillRule, Filter};
+use usvg::{Image as UsvgImage, ImageKind, ImageRendering, LineCap as UsvgLineCap};
+use usvg::{LineJoin as UsvgLineJoin, LinearGradient, Node, NodeExt, NodeKind, Opacity};
+use usvg::{OptionsRef, RadialGradient};
+use usvg::{Paint as UsvgPaint, PathData, PathSegment as UsvgPathSegment, Rect as UsvgRect};
+use usvg::{SpreadMethod, Stop, Stroke, Transform as UsvgTransform, Tree, Units, Visibility};
+
+pub mod animation;
+mod compositing;
+mod filter;
+pub mod stream;
+#[cfg(feature = "system-fonts")]
+pub mod text;
+mod xml;
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 
@@ -41,7 +58,14 @@ pub struct SVGScene {
     pub scene: Scene,
     pub result_flags: BuildResultFlags,
     pub clip_paths: HashMap<String, Outline>,
+    clip_path_info: HashMap<String, ClipPathInfo>,
     gradients: HashMap<String, GradientInfo>,
+    filters: HashMap<String, Filter>,
+    outlines: HashMap<OutlineKey, Arc<Outline>>,
+    // The blend modes and isolation of the groups that `from_data()` wrapped elements in, by ID.
+    compositing: HashMap<String, Compositing>,
+    // The device-space bounds of everything drawn so far, used for filter regions.
+    source_bounds: Option<RectF>,
 }
 
 bitflags! {
@@ -56,94 +80,202 @@ bitflags! {
         const UNSUPPORTED_LINK_PAINT             = 0x0020;
         const UNSUPPORTED_FILTER_ATTR            = 0x0040;
         const UNSUPPORTED_MASK_ATTR              = 0x0080;
+        const UNSUPPORTED_FILTER_PRIMITIVE       = 0x0100;
     }
 }
 
 impl SVGScene {
-    // TODO(pcwalton): Allow a global transform to be set.
+    /// Parses SVG data and builds a scene at the intrinsic size of the SVG.
+    ///
+    /// Unlike parsing the data with `usvg` and calling `from_tree()`, this honors the
+    /// `mix-blend-mode` and `isolation` properties, which `usvg` discards. Compressed SVGZ data
+    /// isn't supported.
+    pub fn from_data(data: &[u8], options: &OptionsRef) -> Result<SVGScene, usvg::Error> {
+        let source = str::from_utf8(data).map_err(|_| usvg::Error::NotAnUtf8Str)?;
+        let document = roxmltree::Document::parse(source)?;
+        let (source, compositing) = compositing::wrap_composited_elements(&document);
+
+        // The wrapper groups are named, and `usvg` removes them otherwise.
+        let mut options = options.clone();
+        options.keep_named_groups = true;
+        let tree = Tree::from_str(&source, &options)?;
+        Ok(SVGScene::from_tree_and_scene_with_size(
+            &tree,
+            Scene::new(),
+            None,
+            compositing,
+        ))
+    }
+
+    /// Builds a scene at the intrinsic size of the SVG, given by its `width` and `height`.
     #[inline]
     pub fn from_tree(tree: &Tree) -> SVGScene {
         SVGScene::from_tree_and_scene(tree, Scene::new())
     }
 
-    // TODO(pcwalton): Allow a global transform to be set.
+    /// Builds a scene whose view box has the given size.
+    ///
+    /// The SVG's view box is fitted into it according to `preserveAspectRatio`, as a browser
+    /// would for an `<img>` of that size.
+    #[inline]
+    pub fn from_tree_with_size(tree: &Tree, size: Vector2F) -> SVGScene {
+        SVGScene::from_tree_and_scene_with_size(tree, Scene::new(), Some(size), HashMap::new())
+    }
+
+    /// Appends the SVG to an existing scene, at the intrinsic size of the SVG.
+    #[inline]
     pub fn from_tree_and_scene(tree: &Tree, scene: Scene) -> SVGScene {
+        SVGScene::from_tree_and_scene_with_size(tree, scene, None, HashMap::new())
+    }
+
+    fn from_tree_and_scene_with_size(
+        tree: &Tree,
+        scene: Scene,
+        size: Option<Vector2F>,
+        compositing: HashMap<String, Compositing>,
+    ) -> SVGScene {
+        let mut built_svg = SVGScene::new(scene, compositing);
+        built_svg.process_tree(tree, size);
+        built_svg
+    }
+
+    fn new(scene: Scene, compositing: HashMap<String, Compositing>) -> SVGScene {
         // TODO(pcwalton): Maybe have a `SVGBuilder` type to hold the clip path IDs and other
         // transient data separate from `SVGScene`?
-        let mut built_svg = SVGScene {
+        SVGScene {
             scene,
             result_flags: BuildResultFlags::empty(),
             clip_paths: HashMap::new(),
+            clip_path_info: HashMap::new(),
             gradients: HashMap::new(),
-        };
+            filters: HashMap::new(),
+            outlines: HashMap::new(),
+            compositing,
+            source_bounds: None,
+        }
+    }
 
+    // Sets the view box from the root `<svg>` element and adds the contents of the tree.
+    fn process_tree(&mut self, tree: &Tree, size: Option<Vector2F>) {
         let root = &tree.root();
         match *root.borrow() {
             NodeKind::Svg(ref svg) => {
-                built_svg
-                    .scene
-                    .set_view_box(usvg_rect_to_euclid_rect(&svg.view_box.rect));
+                // Nested `<svg>` elements have already been turned into clipped, transformed
+                // groups by `usvg`, so only the outermost viewport needs handling here.
+                let size = size
+                    .unwrap_or_else(|| vec2f(svg.size.width() as f32, svg.size.height() as f32));
+                self.scene.set_view_box(RectF::new(Vector2F::zero(), size));
+
+                let mut state = State::new();
+                state.transform = view_box_transform(
+                    usvg_rect_to_euclid_rect(&svg.view_box.rect),
+                    svg.view_box.aspect,
+                    size,
+                );
                 for kid in root.children() {
-                    built_svg.process_node(&kid, &State::new(), &mut None);
+                    self.process_node(&kid, &state, &mut vec![]);
                 }
             }
             _ => unreachable!(),
         }
-
-        built_svg
     }
 
-    fn process_node(&mut self, node: &Node, state: &State, clip_outline: &mut Option<Outline>) {
+    fn process_node(&mut self, node: &Node, state: &State, clip_shapes: &mut Vec<ClipShape>) {
         let mut state = (*state).clone();
         let node_transform = usvg_transform_to_transform_2d(&node.transform());
         state.transform = state.transform * node_transform;
         match *node.borrow() {
             NodeKind::Group(ref group) => {
-                if !group.filter.is_empty() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                let mut filters = vec![];
+                for filter_name in &group.filter {
+                    match self.filters.get(filter_name) {
+                        Some(filter) => filters.push(filter.clone()),
+                        None => {
+                            self.result_flags
+                                .insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                        }
+                    }
                 }
                 if group.mask.is_some() {
                     self.result_flags
                         .insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
                 }
+                // Clip paths ignore opacity and clipping, so only draw groups need to be
+                // composited.
+                let is_drawn = state.path_destination == PathDestination::Draw;
                 if let Some(ref clip_path_name) = group.clip_path {
-                    if let Some(clip_outline) = self.clip_paths.get(clip_path_name) {
-                        let transformed_outline =
-                            clip_outline.clone().transformed(&state.transform);
-                        let mut clip_path = ClipPath::new(transformed_outline);
-                        clip_path.set_clip_path(state.clip_path);
-                        clip_path.set_name(format!("ClipPath({})", clip_path_name));
-                        let clip_path_id = self.scene.push_clip_path(clip_path);
-                        state.clip_path = Some(clip_path_id);
+                    if is_drawn {
+                        if let Some(clip_path_id) =
+                            self.push_clip_path(clip_path_name, node, &state)
+                        {
+                            state.clip_path = Some(clip_path_id);
+                        }
                     }
                 }
 
-                for kid in node.children() {
-                    self.process_node(&kid, &state, clip_outline)
+                if is_drawn {
+                    let mut scene_group = Group::new();
+                    scene_group.set_opacity(group.opacity.value() as f32);
+                    if let Some(compositing) = self.compositing.get(&group.id) {
+                        scene_group.set_blend_mode(compositing.blend_mode);
+                        scene_group.set_isolated(compositing.isolated);
+                    }
+                    scene_group.set_name(group.id.clone());
+                    self.scene.push_group(scene_group);
+                }
+
+                if is_drawn && !filters.is_empty() {
+                    // Filters apply before clipping, so the group's clip path is applied to the
+                    // filter output instead of to its content.
+                    let (source_graphic, outer_bounds) = self.push_filtered_group();
+                    let mut source_state = state.clone();
+                    source_state.clip_path = None;
+                    for kid in node.children() {
+                        self.process_node(&kid, &source_state, clip_shapes)
+                    }
+                    self.pop_filtered_group(source_graphic, outer_bounds, &filters, &state);
+                } else {
+                    for kid in node.children() {
+                        self.process_node(&kid, &state, clip_shapes)
+                    }
+                }
+
+                if is_drawn {
+                    self.scene.pop_group();
                 }
             }
-            NodeKind::Path(ref path) if state.path_destination == PathDestination::Clip => {
-                // TODO(pcwalton): Multiple clip paths.
+            NodeKind::Path(ref path)
+                if state.path_destination == PathDestination::Clip
+                    && path.visibility == Visibility::Visible =>
+            {
+                // `usvg` stores the `clip-rule` of clip path children as their fill rule.
+                let fill_rule = match path.fill {
+                    Some(ref fill) => fill.rule,
+                    None => UsvgFillRule::NonZero,
+                };
                 let path = UsvgPathToSegments::new(path.data.iter().cloned());
                 let path = Transform2FPathIter::new(path, &state.transform);
-                if clip_outline.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS);
-                }
-                *clip_outline = Some(Outline::from_segments(path));
+                clip_shapes.push(ClipShape {
+                    outline: Outline::from_segments(path),
+                    fill_rule: FillRule::from_usvg_fill_rule(fill_rule),
+                });
             }
             NodeKind::Path(ref path)
                 if state.path_destination == PathDestination::Draw
                     && path.visibility == Visibility::Visible =>
             {
-                if let Some(ref fill) = path.fill {
-                    let path = UsvgPathToSegments::new(path.data.iter().cloned());
-                    let outline = Outline::from_segments(path);
+                // Gradients in `objectBoundingBox` units are relative to the fill geometry, even
+                // when they paint the stroke.
+                let fill_outline = self.shared_outline(OutlineKey::new(&path.data, None), || {
+                    Outline::from_segments(UsvgPathToSegments::new(path.data.iter().cloned()))
+                });
+                let bounding_box = fill_outline.bounds();
 
+                if let Some(ref fill) = path.fill {
                     let name = format!("Fill({})", node.id());
                     self.push_draw_path(
-                        outline,
+                        fill_outline,
+                        bounding_box,
                         name,
                         &state,
                         &fill.paint,
@@ -153,32 +285,41 @@ impl SVGScene {
                 }
 
                 if let Some(ref stroke) = path.stroke {
-                    let stroke_style = StrokeStyle {
-                        line_width: f32::max(stroke.width.value() as f32, HAIRLINE_STROKE_WIDTH),
-                        line_cap: LineCap::from_usvg_line_cap(stroke.linecap),
-                        line_join: LineJoin::from_usvg_line_join(
-                            stroke.linejoin,
-                            stroke.miterlimit.value() as f32,
-                        ),
-                    };
-
-                    let path = UsvgPathToSegments::new(path.data.iter().cloned());
-                    let mut outline = Outline::from_segments(path);
-
-                    if let Some(ref dash_array) = stroke.dasharray {
-                        let dash_array: Vec<f32> = dash_array.iter().map(|&x| x as f32).collect();
-                        let mut dash = OutlineDash::new(&outline, &dash_array, stroke.dashoffset);
-                        dash.dash();
-                        outline = dash.into_outline();
-                    }
+                    let key = OutlineKey::new(&path.data, Some(stroke));
+                    let outline = self.shared_outline(key, || {
+                        let stroke_style = StrokeStyle {
+                            line_width: f32::max(
+                                stroke.width.value() as f32,
+                                HAIRLINE_STROKE_WIDTH,
+                            ),
+                            line_cap: LineCap::from_usvg_line_cap(stroke.linecap),
+                            line_join: LineJoin::from_usvg_line_join(
+                                stroke.linejoin,
+                                stroke.miterlimit.value() as f32,
+                            ),
+                        };
 
-                    let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
-                    stroke_to_fill.offset();
-                    let outline = stroke_to_fill.into_outline();
+                        let path = UsvgPathToSegments::new(path.data.iter().cloned());
+                        let mut outline = Outline::from_segments(path);
+
+                        if let Some(ref dash_array) = stroke.dasharray {
+                            let dash_array: Vec<f32> =
+                                dash_array.iter().map(|&x| x as f32).collect();
+                            let mut dash =
+                                OutlineDash::new(&outline, &dash_array, stroke.dashoffset);
+                            dash.dash();
+                            outline = dash.into_outline();
+                        }
+
+                        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+                        stroke_to_fill.offset();
+                        stroke_to_fill.into_outline()
+                    });
 
                     let name = format!("Stroke({})", node.id());
                     self.push_draw_path(
                         outline,
+                        bounding_box,
                         name,
                         &state,
                         &stroke.paint,
@@ -188,21 +329,37 @@ impl SVGScene {
                 }
             }
             NodeKind::Path(..) => {}
-            NodeKind::ClipPath(_) => {
-                let mut clip_outline = None;
+            // Documents loaded a batch at a time repeat definitions in each batch that uses
+            // them, but they only need converting once.
+            NodeKind::ClipPath(..) if self.clip_paths.contains_key(&*node.id()) => {}
+            NodeKind::LinearGradient(LinearGradient { ref id, .. })
+            | NodeKind::RadialGradient(RadialGradient { ref id, .. })
+                if self.gradients.contains_key(id) => {}
+            NodeKind::Filter(ref filter) if self.filters.contains_key(&filter.id) => {}
+            NodeKind::ClipPath(ref clip_path) => {
+                let mut clip_shapes = vec![];
                 state.path_destination = PathDestination::Clip;
                 for kid in node.children() {
-                    self.process_node(&kid, &state, &mut clip_outline);
+                    self.process_node(&kid, &state, &mut clip_shapes);
                 }
 
+                let clip_shape = ClipShape::union(clip_shapes);
                 self.clip_paths
-                    .insert(node.id().to_owned(), clip_outline.unwrap());
+                    .insert(node.id().to_owned(), clip_shape.outline);
+                self.clip_path_info.insert(
+                    node.id().to_owned(),
+                    ClipPathInfo {
+                        fill_rule: clip_shape.fill_rule,
+                        units: clip_path.units,
+                        clip_path: clip_path.clip_path.clone(),
+                    },
+                );
             }
             NodeKind::Defs => {
                 // FIXME(pcwalton): This is wrong.
                 state.path_destination = PathDestination::Defs;
                 for kid in node.children() {
-                    self.process_node(&kid, &state, clip_outline);
+                    self.process_node(&kid, &state, clip_shapes);
                 }
             }
             NodeKind::LinearGradient(ref svg_linear_gradient) => {
@@ -226,14 +383,16 @@ impl SVGScene {
                     &svg_radial_gradient.base,
                 )
             }
-            NodeKind::Filter(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
+            NodeKind::Filter(ref filter) => {
+                self.filters.insert(filter.id.clone(), filter.clone());
             }
-            NodeKind::Image(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+            NodeKind::Image(ref image)
+                if state.path_destination == PathDestination::Draw
+                    && image.visibility == Visibility::Visible =>
+            {
+                self.push_image(image, &state);
             }
+            NodeKind::Image(..) => {}
             NodeKind::Mask(..) => {
                 self.result_flags
                     .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
@@ -260,15 +419,29 @@ impl SVGScene {
             gradient.add(stop);
         }
 
-        // Reflect gradient if necessary.
+        // Reflect gradient if necessary. The stops are mirrored into the second half of a period
+        // twice as long as the original gradient, which then repeats.
         if usvg_base_gradient.spread_method == SpreadMethod::Reflect {
             for stop in &usvg_base_gradient.stops {
                 let mut stop = ColorStop::from_usvg_stop(stop);
-                if usvg_base_gradient.spread_method == SpreadMethod::Reflect {
-                    stop.offset = 1.0 - stop.offset * 0.5;
-                }
+                stop.offset = 1.0 - stop.offset * 0.5;
                 gradient.add(stop);
             }
+
+            match gradient.geometry {
+                GradientGeometry::Linear(ref mut line) => {
+                    *line = LineSegment2F::new(line.from(), line.from() + line.vector() * 2.0);
+                }
+                GradientGeometry::Radial {
+                    ref mut line,
+                    ref mut radii,
+                    ..
+                } => {
+                    *line = LineSegment2F::new(line.from(), line.from() + line.vector() * 2.0);
+                    *radii = *radii * F32x2::splat(2.0);
+                }
+                GradientGeometry::Conic { .. } => unreachable!(),
+            }
         }
 
         match usvg_base_gradient.spread_method {
@@ -278,29 +451,142 @@ impl SVGScene {
 
         let transform = usvg_transform_to_transform_2d(&usvg_base_gradient.transform);
 
-        // TODO(pcwalton): What should we do with `gradientUnits`?
         self.gradients.insert(
             id,
             GradientInfo {
                 gradient,
                 transform,
+                units: usvg_base_gradient.units,
             },
         );
     }
 
+    // Pushes the named clip path, and any clip path applied to it, for the content of `node`.
+    fn push_clip_path(&mut self, name: &str, node: &Node, state: &State) -> Option<ClipPathId> {
+        let info = self.clip_path_info.get(name)?.clone();
+
+        let mut transform = state.transform;
+        if info.units == Units::ObjectBoundingBox {
+            // Without a bounding box there's nothing to clip against, so everything is clipped.
+            let bounding_box = self.bounding_box(node).unwrap_or_default();
+            transform = transform
+                * Transform2F::from_translation(bounding_box.origin())
+                * Transform2F::from_scale(bounding_box.size());
+        }
+
+        let parent_clip_path = match info.clip_path {
+            Some(ref nested_name) => self
+                .push_clip_path(nested_name, node, state)
+                .or(state.clip_path),
+            None => state.clip_path,
+        };
+
+        let outline = self.clip_paths[name].clone().transformed(&transform);
+        let mut clip_path = ClipPath::new(outline);
+        clip_path.set_clip_path(parent_clip_path);
+        clip_path.set_fill_rule(info.fill_rule);
+        clip_path.set_name(format!("ClipPath({})", name));
+        Some(self.scene.push_clip_path(clip_path))
+    }
+
+    // Returns the bounding box of the fill geometry of the children of `node`, in the user space
+    // of `node`.
+    fn bounding_box(&mut self, node: &Node) -> Option<RectF> {
+        let mut bounding_box: Option<RectF> = None;
+        for kid in node.children() {
+            let kid_transform = usvg_transform_to_transform_2d(&kid.transform());
+            let kid_bounding_box = match *kid.borrow() {
+                NodeKind::Path(ref path) => {
+                    let outline = self.shared_outline(OutlineKey::new(&path.data, None), || {
+                        Outline::from_segments(UsvgPathToSegments::new(path.data.iter().cloned()))
+                    });
+                    Some(outline.bounds())
+                }
+                NodeKind::Group(_) => self.bounding_box(&kid),
+                _ => None,
+            };
+            if let Some(kid_bounding_box) = kid_bounding_box {
+                let kid_bounding_box = kid_transform * kid_bounding_box;
+                bounding_box = Some(match bounding_box {
+                    Some(bounding_box) => bounding_box.union_rect(kid_bounding_box),
+                    None => kid_bounding_box,
+                });
+            }
+        }
+        bounding_box
+    }
+
+    // Draws an `<image>` element, fitted into its viewport according to `preserveAspectRatio`.
+    fn push_image(&mut self, image: &UsvgImage, state: &State) {
+        let pattern_image = match Image::from_usvg_image_kind(&image.kind) {
+            Some(pattern_image) => pattern_image,
+            None => {
+                self.result_flags
+                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+                return;
+            }
+        };
+
+        let viewport = usvg_rect_to_euclid_rect(&image.view_box.rect);
+        let image_rect = RectF::new(Vector2F::zero(), pattern_image.size().to_f32());
+        let placement = Transform2F::from_translation(viewport.origin())
+            * view_box_transform(image_rect, image.view_box.aspect, viewport.size());
+        // With `slice`, the image overflows the viewport and is cut off at its edges.
+        let bounds = match (placement * image_rect).intersection(viewport) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        self.add_source_bounds(state.transform * bounds);
+
+        let mut pattern = Pattern::from_image(pattern_image);
+        pattern.apply_transform(state.transform * placement);
+        if image.rendering_mode == ImageRendering::OptimizeSpeed {
+            pattern.set_smoothing_enabled(false);
+        }
+        let paint_id = self.scene.push_paint(&Paint::from_pattern(pattern));
+
+        let mut path = DrawPath::new(Outline::from_rect(bounds), paint_id);
+        path.set_clip_path(state.clip_path);
+        path.set_transform(state.transform);
+        path.set_name(format!("Image({})", image.id));
+        self.scene.push_draw_path(path);
+    }
+
+    fn add_source_bounds(&mut self, bounds: RectF) {
+        self.source_bounds = match self.source_bounds {
+            Some(source_bounds) => Some(source_bounds.union_rect(bounds)),
+            None => Some(bounds),
+        };
+    }
+
+    // Returns the outline for the given key, building it only the first time it's seen.
+    fn shared_outline<F>(&mut self, key: OutlineKey, build: F) -> Arc<Outline>
+    where
+        F: FnOnce() -> Outline,
+    {
+        self.outlines
+            .entry(key)
+            .or_insert_with(|| Arc::new(build()))
+            .clone()
+    }
+
+    // The outline is in the path's user space; it's placed with a draw path transform so that
+    // instances of a symbol can share it.
     fn push_draw_path(
         &mut self,
-        mut outline: Outline,
+        outline: Arc<Outline>,
+        bounding_box: RectF,
         name: String,
         state: &State,
         paint: &UsvgPaint,
         opacity: Opacity,
         fill_rule: UsvgFillRule,
     ) {
-        outline.transform(&state.transform);
+        self.add_source_bounds(state.transform * outline.bounds());
         let paint = Paint::from_svg_paint(
             paint,
             &state.transform,
+            bounding_box,
             opacity,
             &self.gradients,
             &mut self.result_flags,
@@ -310,6 +596,7 @@ impl SVGScene {
         let mut path = DrawPath::new(outline, style);
         path.set_clip_path(state.clip_path);
         path.set_fill_rule(fill_rule);
+        path.set_transform(state.transform);
         path.set_name(name);
         self.scene.push_draw_path(path);
     }
@@ -346,6 +633,7 @@ impl Display for BuildResultFlags {
             "non-color paint",
             "filter attribute",
             "mask attribute",
+            "filter primitive",
         ];
     }
 }
@@ -354,6 +642,7 @@ trait PaintExt {
     fn from_svg_paint(
         svg_paint: &UsvgPaint,
         transform: &Transform2F,
+        bounding_box: RectF,
         opacity: Opacity,
         gradients: &HashMap<String, GradientInfo>,
         result_flags: &mut BuildResultFlags,
@@ -365,6 +654,7 @@ impl PaintExt for Paint {
     fn from_svg_paint(
         svg_paint: &UsvgPaint,
         transform: &Transform2F,
+        bounding_box: RectF,
         opacity: Opacity,
         gradients: &HashMap<String, GradientInfo>,
         result_flags: &mut BuildResultFlags,
@@ -374,10 +664,27 @@ impl PaintExt for Paint {
             UsvgPaint::Color(color) => paint = Paint::from_color(ColorU::from_svg_color(color)),
             UsvgPaint::Link(ref id) => {
                 match gradients.get(id) {
-                    Some(ref gradient_info) => {
-                        paint = Paint::from_gradient(gradient_info.gradient.clone());
-                        paint.apply_transform(&(*transform * gradient_info.transform));
-                    }
+                    Some(ref gradient_info) => match gradient_info.units {
+                        Units::UserSpaceOnUse => {
+                            paint = Paint::from_gradient(gradient_info.gradient.clone());
+                            paint.apply_transform(&(*transform * gradient_info.transform));
+                        }
+                        // A bounding box without area disables the paint, per the SVG spec.
+                        Units::ObjectBoundingBox
+                            if bounding_box.width() == 0.0 || bounding_box.height() == 0.0 =>
+                        {
+                            paint = Paint::transparent_black();
+                        }
+                        Units::ObjectBoundingBox => {
+                            let bounding_box_transform =
+                                Transform2F::from_translation(bounding_box.origin())
+                                    * Transform2F::from_scale(bounding_box.size());
+                            paint = Paint::from_gradient(gradient_info.gradient.clone());
+                            paint.apply_transform(
+                                &(*transform * bounding_box_transform * gradient_info.transform),
+                            );
+                        }
+                    },
                     None => {
                         // TODO(pcwalton)
                         result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
@@ -402,6 +709,42 @@ fn usvg_rect_to_euclid_rect(rect: &UsvgRect) -> RectF {
     )
 }
 
+// Maps a view box onto a viewport of the given size, per the `preserveAspectRatio` attribute.
+fn view_box_transform(view_box: RectF, aspect: AspectRatio, size: Vector2F) -> Transform2F {
+    if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+        return Transform2F::default();
+    }
+
+    let scale = size / view_box.size();
+    let (scale, origin) = match aspect.align {
+        Align::None => (scale, Vector2F::zero()),
+        align => {
+            let scale = if aspect.slice {
+                scale.x().max(scale.y())
+            } else {
+                scale.x().min(scale.y())
+            };
+            let slack = size - view_box.size() * scale;
+            let alignment = match align {
+                Align::XMinYMin => vec2f(0.0, 0.0),
+                Align::XMidYMin => vec2f(0.5, 0.0),
+                Align::XMaxYMin => vec2f(1.0, 0.0),
+                Align::XMinYMid => vec2f(0.0, 0.5),
+                Align::XMidYMid | Align::None => vec2f(0.5, 0.5),
+                Align::XMaxYMid => vec2f(1.0, 0.5),
+                Align::XMinYMax => vec2f(0.0, 1.0),
+                Align::XMidYMax => vec2f(0.5, 1.0),
+                Align::XMaxYMax => vec2f(1.0, 1.0),
+            };
+            (Vector2F::splat(scale), slack * alignment)
+        }
+    };
+
+    Transform2F::from_translation(origin)
+        * Transform2F::from_scale(scale)
+        * Transform2F::from_translation(-view_box.origin())
+}
+
 fn usvg_transform_to_transform_2d(transform: &UsvgTransform) -> Transform2F {
     Transform2F::row_major(
         transform.a as f32,
@@ -498,6 +841,25 @@ where
     }
 }
 
+trait ImageExt: Sized {
+    fn from_usvg_image_kind(kind: &ImageKind) -> Option<Self>;
+}
+
+impl ImageExt for Image {
+    // Decodes embedded PNG and JPEG data. `usvg` has already decoded data URIs and loaded external
+    // files relative to `Options::resources_dir`, so only the raw image data is left to decode.
+    fn from_usvg_image_kind(kind: &ImageKind) -> Option<Image> {
+        let (data, format) = match *kind {
+            ImageKind::PNG(ref data) => (&data[..], ImageFormat::Png),
+            ImageKind::JPEG(ref data) => (&data[..], ImageFormat::Jpeg),
+            // TODO: Nested SVG images.
+            _ => return None,
+        };
+        let image = image::load_from_memory_with_format(data, format).ok()?;
+        Some(Image::from_image_buffer(image.to_rgba8()))
+    }
+}
+
 trait ColorUExt {
     fn from_svg_color(svg_color: SvgColor) -> Self;
 }
@@ -597,7 +959,99 @@ enum PathDestination {
     Clip,
 }
 
+// One child of a `<clipPath>`, in the clip path's coordinate system.
+struct ClipShape {
+    outline: Outline,
+    fill_rule: FillRule,
+}
+
+impl ClipShape {
+    // Combines the children of a `<clipPath>` into a single outline covering all of them.
+    fn union(mut shapes: Vec<ClipShape>) -> ClipShape {
+        if shapes.len() == 1 {
+            return shapes.pop().unwrap();
+        }
+
+        // Concatenated contours fill their union under the winding rule as long as every shape
+        // contributes positive winding, so rewind each one to be outermost-clockwise first.
+        let mut outline = Outline::new();
+        for mut shape in shapes {
+            match shape.fill_rule {
+                FillRule::EvenOdd => shape.outline.normalize_orientations(FillRule::EvenOdd),
+                FillRule::Winding => {
+                    if Orientation::from_outline(&shape.outline) == Orientation::Ccw {
+                        shape.outline = reversed_outline(shape.outline);
+                    }
+                }
+            }
+            outline.push_outline(shape.outline);
+        }
+
+        ClipShape {
+            outline,
+            fill_rule: FillRule::Winding,
+        }
+    }
+}
+
+fn reversed_outline(outline: Outline) -> Outline {
+    let mut reversed = Outline::new();
+    for mut contour in outline.into_contours() {
+        contour.reverse();
+        reversed.push_contour(contour);
+    }
+    reversed
+}
+
+#[derive(Clone)]
+struct ClipPathInfo {
+    fill_rule: FillRule,
+    units: Units,
+    // A clip path applied to this clip path.
+    clip_path: Option<String>,
+}
+
+// Identifies an outline built from `usvg` path data.
+//
+// `usvg` expands `<use>` elements by cloning the referenced nodes, but the clones share their path
+// data, so its address is the same for every instance of a symbol.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct OutlineKey {
+    path_data: usize,
+    stroke: Option<StrokeKey>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct StrokeKey {
+    width: u64,
+    line_cap: u8,
+    line_join: u8,
+    miter_limit: u64,
+    dash_array: Option<Vec<u64>>,
+    dash_offset: u32,
+}
+
+impl OutlineKey {
+    fn new(path_data: &Rc<PathData>, stroke: Option<&Stroke>) -> OutlineKey {
+        OutlineKey {
+            path_data: Rc::as_ptr(path_data) as usize,
+            stroke: stroke.map(|stroke| StrokeKey {
+                width: stroke.width.value().to_bits(),
+                line_cap: stroke.linecap as u8,
+                line_join: stroke.linejoin as u8,
+                miter_limit: stroke.miterlimit.value().to_bits(),
+                dash_array: stroke
+                    .dasharray
+                    .as_ref()
+                    .map(|dash_array| dash_array.iter().map(|&x| x.to_bits()).collect()),
+                dash_offset: stroke.dashoffset.to_bits(),
+            }),
+        }
+    }
+}
+
 struct GradientInfo {
     gradient: Gradient,
     transform: Transform2F,
+    units: Units,
 }
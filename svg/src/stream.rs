@@ -0,0 +1,691 @@
+// pathfinder/svg/src/stream.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loads large SVG documents incrementally.
+//!
+//! `usvg` needs the whole document in memory, and its tree takes several times the size of the
+//! source, which is too much for machine-generated files hundreds of megabytes in size. Instead,
+//! the content of the root `<svg>` element is read a batch at a time, and each batch is parsed
+//! as a document of its own, with the root element and the definitions that it refers to, and
+//! then added to the scene. Only one batch is in memory at a time.
+//!
+//! Groups are split between batches where that doesn't change how they render: a batch that ends
+//! inside a group closes it, and the next batch opens it again with the same attributes. Groups
+//! with opacity, filters, masks, or blend modes are composited as a whole, so they're kept in a
+//! single batch.
+//!
+//! Definitions are kept aside and added only to the batches that refer to them, so that each
+//! batch parses just the gradients, clip paths, and so on that it uses. Definitions must come
+//! before their first use, and `<use>` elements can only refer to definitions, not to other
+//! content.
+
+use crate::SVGScene;
+use hashbrown::HashMap;
+use pathfinder_renderer::scene::Scene;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead};
+use std::iter;
+use std::mem;
+use usvg::{OptionsRef, Tree};
+
+// How much content to collect before parsing it.
+const BATCH_SIZE: usize = 1024 * 1024;
+
+// Elements that don't render by themselves, and so are kept aside for the batches that use them.
+const DEFINITION_ELEMENTS: &[&[u8]] = &[
+    b"linearGradient",
+    b"radialGradient",
+    b"clipPath",
+    b"mask",
+    b"pattern",
+    b"filter",
+    b"marker",
+    b"symbol",
+];
+
+// Elements whose children can be split between batches, unless `GROUP_EFFECTS` apply to them.
+const CONTAINER_ELEMENTS: &[&[u8]] = &[b"g", b"a", b"svg"];
+
+// Attributes and properties that apply to the rendered contents of a group as a whole.
+const GROUP_EFFECTS: &[&[u8]] = &[
+    b"opacity",
+    b"filter",
+    b"mask",
+    b"mix-blend-mode",
+    b"isolation",
+];
+
+/// The reasons why an SVG couldn't be loaded from a reader.
+#[derive(Debug)]
+pub enum ReaderError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The document has no root element.
+    NoRootElement,
+    /// `usvg` rejected a batch of the document.
+    Svg(usvg::Error),
+}
+
+impl SVGScene {
+    /// Loads an SVG document from a reader, building the scene a batch of elements at a time.
+    ///
+    /// This bounds memory use for very large documents, at the cost of some generality; see the
+    /// `stream` module for the restrictions on the document.
+    pub fn from_reader<R>(reader: R, options: &OptionsRef) -> Result<SVGScene, ReaderError>
+    where
+        R: BufRead,
+    {
+        let mut tokenizer = Tokenizer { reader };
+        let mut built_svg = SVGScene::new(Scene::new(), Default::default());
+
+        // Everything up to the root element is repeated in each batch, so that entities declared
+        // in a `DOCTYPE` remain available.
+        let mut prolog = vec![];
+        let root_start_tag = loop {
+            let mut markup = vec![];
+            match tokenizer.next(&mut markup)? {
+                None => return Err(ReaderError::NoRootElement),
+                Some(Token::StartTag { self_closing }) => break (markup, self_closing),
+                Some(_) => prolog.extend_from_slice(&markup),
+            }
+        };
+        let (root_start_tag, root_self_closing) = root_start_tag;
+
+        let mut batch = Batch::new(prolog, root_start_tag);
+        if root_self_closing {
+            batch.add_to_scene(&mut built_svg, options)?;
+            return Ok(built_svg);
+        }
+
+        // Split the root's contents into elements, descending into groups that can be split.
+        let mut element = vec![];
+        let mut depth = 0;
+        let mut in_defs = false;
+        loop {
+            let mut markup = vec![];
+            let token = match tokenizer.next(&mut markup)? {
+                None => break,
+                Some(token) => token,
+            };
+
+            if depth == 0 {
+                match token {
+                    Token::StartTag { self_closing: true } => batch.push_element(&markup, in_defs),
+                    Token::StartTag {
+                        self_closing: false,
+                    } if !in_defs && element_name(&markup) == b"defs" => in_defs = true,
+                    Token::StartTag {
+                        self_closing: false,
+                    } if !in_defs && can_split(&markup) => batch.open_container(markup),
+                    Token::StartTag {
+                        self_closing: false,
+                    } => {
+                        element = markup;
+                        depth = 1;
+                    }
+                    Token::EndTag if in_defs => in_defs = false,
+                    Token::EndTag if batch.open_containers.is_empty() => break,
+                    Token::EndTag => batch.close_container(&markup),
+                    // Text and comments directly inside groups don't render.
+                    Token::Text | Token::Other => {}
+                }
+            } else {
+                element.extend_from_slice(&markup);
+                match token {
+                    Token::StartTag {
+                        self_closing: false,
+                    } => depth += 1,
+                    Token::EndTag => depth -= 1,
+                    Token::StartTag { self_closing: true } | Token::Text | Token::Other => {}
+                }
+                if depth == 0 {
+                    batch.push_element(&mem::replace(&mut element, vec![]), in_defs);
+                }
+            }
+
+            if batch.contents.len() >= BATCH_SIZE {
+                batch.add_to_scene(&mut built_svg, options)?;
+            }
+        }
+
+        batch.add_to_scene(&mut built_svg, options)?;
+        Ok(built_svg)
+    }
+}
+
+// A run of the document's content, parsed together.
+struct Batch {
+    prolog: Vec<u8>,
+    root_start_tag: Vec<u8>,
+    // Style sheets, which may apply to any element and so go in every batch.
+    styles: Vec<u8>,
+    definitions: Vec<Definition>,
+    // The index of the definition of each ID.
+    definition_ids: HashMap<Vec<u8>, usize>,
+    // The start tags of the groups that the next element is in, outermost first.
+    open_containers: Vec<Vec<u8>>,
+    contents: Vec<u8>,
+}
+
+// An element that only renders where it's referred to, such as a gradient.
+struct Definition {
+    markup: Vec<u8>,
+    references: Vec<Vec<u8>>,
+}
+
+impl Batch {
+    fn new(prolog: Vec<u8>, root_start_tag: Vec<u8>) -> Batch {
+        Batch {
+            prolog,
+            root_start_tag,
+            styles: vec![],
+            definitions: vec![],
+            definition_ids: HashMap::new(),
+            open_containers: vec![],
+            contents: vec![],
+        }
+    }
+
+    // Adds a whole element. Every child of a `<defs>` is a definition, whatever its name.
+    fn push_element(&mut self, element: &[u8], in_defs: bool) {
+        let name = element_name(element);
+        if name == b"style" {
+            self.styles.extend_from_slice(element);
+        } else if in_defs || DEFINITION_ELEMENTS.contains(&name) {
+            let index = self.definitions.len();
+            for id in ids(element) {
+                self.definition_ids.insert(id.to_vec(), index);
+            }
+            self.definitions.push(Definition {
+                markup: element.to_vec(),
+                references: references(element).map(|id| id.to_vec()).collect(),
+            });
+        } else {
+            self.contents.extend_from_slice(element);
+        }
+    }
+
+    fn open_container(&mut self, start_tag: Vec<u8>) {
+        self.contents.extend_from_slice(&start_tag);
+        self.open_containers.push(start_tag);
+    }
+
+    fn close_container(&mut self, end_tag: &[u8]) {
+        self.contents.extend_from_slice(end_tag);
+        self.open_containers.pop();
+    }
+
+    fn add_to_scene(
+        &mut self,
+        built_svg: &mut SVGScene,
+        options: &OptionsRef,
+    ) -> Result<(), ReaderError> {
+        let mut document = Vec::with_capacity(
+            self.prolog.len() + self.root_start_tag.len() + self.styles.len() + self.contents.len(),
+        );
+        document.extend_from_slice(&self.prolog);
+        document.extend_from_slice(&self.root_start_tag);
+        if document.ends_with(b"/>") {
+            document.truncate(document.len() - 2);
+            document.push(b'>');
+        }
+        document.extend_from_slice(&self.styles);
+        self.write_definitions(&mut document);
+        document.extend_from_slice(&self.contents);
+        for start_tag in self.open_containers.iter().rev() {
+            write_end_tag(start_tag, &mut document);
+        }
+        write_end_tag(&self.root_start_tag, &mut document);
+
+        // The next batch starts inside the same groups.
+        self.contents.clear();
+        for start_tag in &self.open_containers {
+            self.contents.extend_from_slice(start_tag);
+        }
+
+        let tree = Tree::from_data(&document, options).map_err(ReaderError::Svg)?;
+        drop(document);
+
+        // Outlines are shared by the address of their path data, which can be reused by the next
+        // batch once this tree is dropped.
+        built_svg.outlines.clear();
+        built_svg.process_tree(&tree, None);
+        Ok(())
+    }
+
+    // Writes the definitions that the styles and contents refer to, directly or through other
+    // definitions, inside a `<defs>` so that none of them render by themselves.
+    fn write_definitions(&self, document: &mut Vec<u8>) {
+        let mut included = vec![false; self.definitions.len()];
+        let mut pending: Vec<&[u8]> = references(&self.styles)
+            .chain(references(&self.contents))
+            .collect();
+        while let Some(id) = pending.pop() {
+            if let Some(&index) = self.definition_ids.get(id) {
+                if !included[index] {
+                    included[index] = true;
+                    pending.extend(self.definitions[index].references.iter().map(|id| &id[..]));
+                }
+            }
+        }
+        if !included.contains(&true) {
+            return;
+        }
+
+        let prefix = prefix(qualified_name(&self.root_start_tag));
+        document.push(b'<');
+        document.extend_from_slice(prefix);
+        document.extend_from_slice(b"defs>");
+        for (definition, _) in self
+            .definitions
+            .iter()
+            .zip(included)
+            .filter(|&(_, included)| included)
+        {
+            document.extend_from_slice(&definition.markup);
+        }
+        document.extend_from_slice(b"</");
+        document.extend_from_slice(prefix);
+        document.extend_from_slice(b"defs>");
+    }
+}
+
+// Returns true if the children of the element with the given start tag can be split between
+// batches without changing how they render.
+fn can_split(start_tag: &[u8]) -> bool {
+    CONTAINER_ELEMENTS.contains(&element_name(start_tag))
+        && attributes(start_tag).all(|(name, value)| {
+            if name == b"style" {
+                !value.split(|&byte| byte == b';').any(|declaration| {
+                    let property = declaration.split(|&byte| byte == b':').next().unwrap();
+                    let property = trim(property);
+                    GROUP_EFFECTS.contains(&property)
+                })
+            } else {
+                !GROUP_EFFECTS.contains(&name)
+            }
+        })
+}
+
+// Removes leading and trailing whitespace.
+fn trim(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+fn write_end_tag(start_tag: &[u8], output: &mut Vec<u8>) {
+    output.extend_from_slice(b"</");
+    output.extend_from_slice(qualified_name(start_tag));
+    output.push(b'>');
+}
+
+// Returns the name of the element whose markup starts with the given start tag, including any
+// namespace prefix.
+fn qualified_name(element: &[u8]) -> &[u8] {
+    let name = &element[1..];
+    let end = name
+        .iter()
+        .position(|&byte| byte.is_ascii_whitespace() || byte == b'>' || byte == b'/')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+// Returns the namespace prefix of a qualified name, with its colon, or nothing.
+fn prefix(qualified_name: &[u8]) -> &[u8] {
+    match qualified_name.iter().position(|&byte| byte == b':') {
+        Some(colon) => &qualified_name[..colon + 1],
+        None => &[],
+    }
+}
+
+// Returns the name of the element whose markup starts with the given start tag, without any
+// namespace prefix.
+fn element_name(element: &[u8]) -> &[u8] {
+    let name = qualified_name(element);
+    &name[prefix(name).len()..]
+}
+
+// Returns the names and values, still escaped, of the attributes in a start tag.
+fn attributes(start_tag: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = &start_tag[1 + qualified_name(start_tag).len()..];
+    iter::from_fn(move || {
+        let name_start = rest.iter().position(|&byte| !byte.is_ascii_whitespace())?;
+        rest = &rest[name_start..];
+        let name_end = rest
+            .iter()
+            .position(|&byte| byte == b'=' || byte.is_ascii_whitespace())?;
+        let name = &rest[..name_end];
+        let quote_start = rest
+            .iter()
+            .position(|&byte| byte == b'"' || byte == b'\'')?;
+        let quote = rest[quote_start];
+        rest = &rest[quote_start + 1..];
+        let value_end = rest.iter().position(|&byte| byte == quote)?;
+        let value = &rest[..value_end];
+        rest = &rest[value_end + 1..];
+        Some((name, value))
+    })
+}
+
+// Returns the IDs given to the element and its descendants.
+fn ids(markup: &[u8]) -> impl Iterator<Item = &[u8]> {
+    names_after(markup, &[b"id=\"", b"id='"])
+        .filter(move |&(index, _)| index > 0 && markup[index - 1].is_ascii_whitespace())
+        .map(|(_, id)| id)
+}
+
+// Returns the IDs that the markup refers to, through `url(#id)` or `href="#id"`.
+fn references(markup: &[u8]) -> impl Iterator<Item = &[u8]> {
+    names_after(
+        markup,
+        &[b"url(#", b"url('#", b"url(\"#", b"href=\"#", b"href='#"],
+    )
+    .map(|(_, id)| id)
+}
+
+// Returns the names that follow any of the given prefixes in the markup, with the index at which
+// the prefix starts.
+fn names_after<'a>(
+    markup: &'a [u8],
+    prefixes: &'static [&'static [u8]],
+) -> impl Iterator<Item = (usize, &'a [u8])> {
+    (0..markup.len()).filter_map(move |index| {
+        let prefix = prefixes
+            .iter()
+            .find(|prefix| markup[index..].starts_with(prefix))?;
+        let rest = &markup[index + prefix.len()..];
+        let end = rest
+            .iter()
+            .position(|&byte| {
+                byte == b'"' || byte == b'\'' || byte == b')' || byte.is_ascii_whitespace()
+            })
+            .unwrap_or(rest.len());
+        Some((index, &rest[..end]))
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Token {
+    StartTag { self_closing: bool },
+    EndTag,
+    Text,
+    // Comments, processing instructions, CDATA sections, and `DOCTYPE`s.
+    Other,
+}
+
+// Splits XML into markup and text, just far enough to find where elements start and end.
+struct Tokenizer<R> {
+    reader: R,
+}
+
+impl<R> Tokenizer<R>
+where
+    R: BufRead,
+{
+    // Appends the next token to `output`, returning its kind, or `None` at the end of the input.
+    fn next(&mut self, output: &mut Vec<u8>) -> io::Result<Option<Token>> {
+        let first = match self.peek()? {
+            None => return Ok(None),
+            Some(first) => first,
+        };
+        if first != b'<' {
+            self.read_until_before(b'<', output)?;
+            return Ok(Some(Token::Text));
+        }
+
+        self.read_byte(output)?;
+        match self.peek()? {
+            Some(b'/') => {
+                self.read_until_after(b">", output)?;
+                Ok(Some(Token::EndTag))
+            }
+            Some(b'?') => {
+                self.read_until_after(b"?>", output)?;
+                Ok(Some(Token::Other))
+            }
+            Some(b'!') => {
+                self.read_byte(output)?;
+                self.read_declaration(output)?;
+                Ok(Some(Token::Other))
+            }
+            _ => {
+                self.read_start_tag(output)?;
+                Ok(Some(Token::StartTag {
+                    self_closing: output.ends_with(b"/>"),
+                }))
+            }
+        }
+    }
+
+    // Reads the rest of a comment, CDATA section, or `DOCTYPE`, after the `<!`.
+    fn read_declaration(&mut self, output: &mut Vec<u8>) -> io::Result<()> {
+        let start = output.len();
+        while output.len() - start < 7 {
+            match self.read_byte(output)? {
+                None => return Ok(()),
+                Some(b'>') => return Ok(()),
+                Some(_) => {}
+            }
+            if output[start..].starts_with(b"--") {
+                return self.read_until_after(b"-->", output);
+            }
+            if output[start..].starts_with(b"[CDATA[") {
+                return self.read_until_after(b"]]>", output);
+            }
+        }
+
+        // A `DOCTYPE`, whose internal subset may contain `>`.
+        let mut in_subset = false;
+        loop {
+            match self.read_byte(output)? {
+                None => return Ok(()),
+                Some(b'[') => in_subset = true,
+                Some(b']') => in_subset = false,
+                Some(b'>') if !in_subset => return Ok(()),
+                Some(_) => {}
+            }
+        }
+    }
+
+    // Reads the rest of a start tag, whose attribute values may contain `>`.
+    fn read_start_tag(&mut self, output: &mut Vec<u8>) -> io::Result<()> {
+        let mut quote = None;
+        loop {
+            match (self.read_byte(output)?, quote) {
+                (None, _) => return Ok(()),
+                (Some(b'>'), None) => return Ok(()),
+                (Some(byte), None) if byte == b'"' || byte == b'\'' => quote = Some(byte),
+                (Some(byte), Some(open)) if byte == open => quote = None,
+                _ => {}
+            }
+        }
+    }
+
+    fn read_until_before(&mut self, delimiter: u8, output: &mut Vec<u8>) -> io::Result<()> {
+        loop {
+            let (consumed, done) = {
+                let buffer = self.reader.fill_buf()?;
+                if buffer.is_empty() {
+                    return Ok(());
+                }
+                match buffer.iter().position(|&byte| byte == delimiter) {
+                    Some(index) => {
+                        output.extend_from_slice(&buffer[..index]);
+                        (index, true)
+                    }
+                    None => {
+                        output.extend_from_slice(buffer);
+                        (buffer.len(), false)
+                    }
+                }
+            };
+            self.reader.consume(consumed);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_until_after(&mut self, delimiter: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        let last = delimiter[delimiter.len() - 1];
+        loop {
+            self.read_until_before(last, output)?;
+            if self.read_byte(output)?.is_none() || output.ends_with(delimiter) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_byte(&mut self, output: &mut Vec<u8>) -> io::Result<Option<u8>> {
+        let byte = self.peek()?;
+        if let Some(byte) = byte {
+            output.push(byte);
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().cloned())
+    }
+}
+
+impl From<io::Error> for ReaderError {
+    fn from(error: io::Error) -> ReaderError {
+        ReaderError::Io(error)
+    }
+}
+
+impl Display for ReaderError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            ReaderError::Io(ref error) => write!(formatter, "I/O error: {}", error),
+            ReaderError::NoRootElement => write!(formatter, "the document has no root element"),
+            ReaderError::Svg(ref error) => write!(formatter, "SVG error: {}", error),
+        }
+    }
+}
+
+impl Error for ReaderError {}
+
+#[cfg(test)]
+mod test {
+    use super::{can_split, references, Token, Tokenizer};
+
+    // Splits the markup into tokens, checking that nothing is lost.
+    fn tokenize(markup: &str) -> Vec<(Token, &str)> {
+        let mut tokenizer = Tokenizer {
+            reader: markup.as_bytes(),
+        };
+        let (mut tokens, mut start) = (vec![], 0);
+        let mut output = vec![];
+        while let Some(token) = tokenizer.next(&mut output).unwrap() {
+            tokens.push((token, &markup[start..output.len()]));
+            start = output.len();
+        }
+        assert_eq!(output, markup.as_bytes());
+        tokens
+    }
+
+    const START_TAG: Token = Token::StartTag {
+        self_closing: false,
+    };
+    const EMPTY_TAG: Token = Token::StartTag { self_closing: true };
+
+    #[test]
+    fn cdata_sections_are_not_markup() {
+        assert_eq!(
+            tokenize("<style><![CDATA[ a > b { fill: red } <g> ]]></style>"),
+            [
+                (START_TAG, "<style>"),
+                (Token::Other, "<![CDATA[ a > b { fill: red } <g> ]]>"),
+                (Token::EndTag, "</style>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_may_contain_tags() {
+        assert_eq!(
+            tokenize("<g><!-- <rect/> -> --><!----></g>"),
+            [
+                (START_TAG, "<g>"),
+                (Token::Other, "<!-- <rect/> -> -->"),
+                (Token::Other, "<!---->"),
+                (Token::EndTag, "</g>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn entities_pass_through() {
+        assert_eq!(
+            tokenize(
+                "<!DOCTYPE svg [<!ENTITY s \"<rect/>\">]><svg>&s;&lt;&#62;<text>&amp;</text></svg>"
+            ),
+            [
+                (Token::Other, "<!DOCTYPE svg [<!ENTITY s \"<rect/>\">]>"),
+                (START_TAG, "<svg>"),
+                (Token::Text, "&s;&lt;&#62;"),
+                (START_TAG, "<text>"),
+                (Token::Text, "&amp;"),
+                (Token::EndTag, "</text>"),
+                (Token::EndTag, "</svg>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_angle_brackets_do_not_end_tags() {
+        assert_eq!(
+            tokenize("<a title=\"x > y\" alt='/>'/><b title='\"' />"),
+            [
+                (EMPTY_TAG, "<a title=\"x > y\" alt='/>'/>"),
+                (EMPTY_TAG, "<b title='\"' />"),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_with_effects_are_not_split() {
+        assert!(can_split(
+            b"<g transform=\"scale(2)\" fill-opacity=\"0.5\">"
+        ));
+        assert!(can_split(
+            b"<svg:g style=\"fill: red; stroke-opacity: 0.5\">"
+        ));
+        assert!(!can_split(b"<g opacity=\"0.5\">"));
+        assert!(!can_split(
+            b"<g style=\"fill:red;mix-blend-mode : multiply\">"
+        ));
+        assert!(!can_split(b"<switch>"));
+    }
+
+    #[test]
+    fn references_are_found_in_urls_and_links() {
+        let markup = b"<path fill=\"url(#a)\" clip-path=\"url('#b')\"/><use xlink:href=\"#c\"/>";
+        assert_eq!(
+            references(markup).collect::<Vec<_>>(),
+            [&b"a"[..], &b"b"[..], &b"c"[..]]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+// pathfinder/svg/src/text.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Font setup for SVG text.
+//!
+//! `usvg` lays out `<text>` elements and converts them to paths while it parses, honoring
+//! `text-anchor`, `dx`/`dy`, and `<tspan>`s, so by the time a `Tree` reaches `SVGScene` all text
+//! is ordinary path nodes. It can only do that for fonts in its font database, which is empty in
+//! the default `Options`, so text silently disappears unless fonts are loaded first.
+
+use font_kit::family_name::FamilyName;
+use font_kit::handle::Handle;
+use font_kit::properties::Properties;
+use font_kit::source::{Source, SystemSource};
+use usvg::fontdb::Database;
+use usvg::Options;
+
+/// Returns `usvg` options whose font database holds the system fonts.
+///
+/// Pass the result to `Tree::from_data()` to have `<text>` elements rendered.
+pub fn options_with_system_fonts() -> Options {
+    let mut options = Options::default();
+    load_system_fonts(&mut options.fontdb);
+    options
+}
+
+/// Adds every font that `font-kit` can find on the system to `fontdb`.
+///
+/// The generic families (`serif`, `sans-serif`, and so on) are resolved through `font-kit` as
+/// well, so they match what the canvas API picks.
+pub fn load_system_fonts(fontdb: &mut Database) {
+    let source = SystemSource::new();
+    if let Ok(handles) = source.all_fonts() {
+        for handle in handles {
+            match handle {
+                Handle::Path { path, .. } => {
+                    // Fonts that fail to load are skipped; `usvg` falls back to other families.
+                    let _ = fontdb.load_font_file(path);
+                }
+                Handle::Memory { bytes, .. } => fontdb.load_font_data((*bytes).clone()),
+            }
+        }
+    }
+
+    if let Some(family) = generic_family_name(&source, FamilyName::Serif) {
+        fontdb.set_serif_family(family);
+    }
+    if let Some(family) = generic_family_name(&source, FamilyName::SansSerif) {
+        fontdb.set_sans_serif_family(family);
+    }
+    if let Some(family) = generic_family_name(&source, FamilyName::Monospace) {
+        fontdb.set_monospace_family(family);
+    }
+    if let Some(family) = generic_family_name(&source, FamilyName::Cursive) {
+        fontdb.set_cursive_family(family);
+    }
+    if let Some(family) = generic_family_name(&source, FamilyName::Fantasy) {
+        fontdb.set_fantasy_family(family);
+    }
+}
+
+fn generic_family_name(source: &SystemSource, family: FamilyName) -> Option<String> {
+    let handle = source
+        .select_best_match(&[family], &Properties::new())
+        .ok()?;
+    Some(handle.load().ok()?.family_name())
+}
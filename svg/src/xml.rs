@@ -0,0 +1,142 @@
+// pathfinder/svg/src/xml.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rewrites SVG source documents before `usvg` sees them.
+//!
+//! This is how features that `usvg` discards, such as animations and blend modes, are carried
+//! through to the scene: the document is edited into a form that `usvg` keeps.
+
+use hashbrown::HashMap;
+use roxmltree::{Document, Node};
+
+pub(crate) const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub(crate) const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Changes to make to an element when its document is written back out.
+///
+/// Edits are keyed by the element's byte offset in the source; see `element_key()`.
+#[derive(Default)]
+pub(crate) struct ElementEdits {
+    /// Attribute values that replace the element's own. Later entries for the same attribute
+    /// take precedence over earlier ones.
+    pub(crate) attributes: Vec<(String, String)>,
+    /// The IDs of `<g>` elements to wrap the element in, from the outermost inward.
+    pub(crate) wrapper_ids: Vec<String>,
+    /// Whether the element and its descendants are left out entirely.
+    pub(crate) removed: bool,
+}
+
+pub(crate) fn element_key(node: Node) -> usize {
+    node.range().start
+}
+
+/// Writes the document back out as XML, applying the given edits.
+///
+/// Comments and processing instructions are dropped.
+pub(crate) fn write_document(document: &Document, edits: &HashMap<usize, ElementEdits>) -> String {
+    let mut output = String::new();
+    write_element(document.root_element(), edits, true, &mut output);
+    output
+}
+
+fn write_element(
+    node: Node,
+    edits: &HashMap<usize, ElementEdits>,
+    is_root: bool,
+    output: &mut String,
+) {
+    let element_edits = edits.get(&element_key(node));
+    if element_edits.map_or(false, |element_edits| element_edits.removed) {
+        return;
+    }
+    let wrapper_ids = element_edits.map_or(&[][..], |element_edits| &element_edits.wrapper_ids[..]);
+    for wrapper_id in wrapper_ids {
+        output.push_str("<g");
+        write_attribute("id", wrapper_id, output);
+        output.push('>');
+    }
+
+    let name = node.tag_name().name();
+    output.push('<');
+    output.push_str(name);
+    if is_root {
+        write_attribute("xmlns", SVG_NAMESPACE, output);
+        write_attribute("xmlns:xlink", XLINK_NAMESPACE, output);
+    }
+
+    let new_attributes =
+        element_edits.map_or(&[][..], |element_edits| &element_edits.attributes[..]);
+    for attribute in node.attributes() {
+        let prefix = match attribute.namespace() {
+            None => "",
+            Some(XLINK_NAMESPACE) => "xlink:",
+            Some(XML_NAMESPACE) => "xml:",
+            Some(_) => continue,
+        };
+        let replaced = prefix.is_empty()
+            && new_attributes
+                .iter()
+                .any(|&(ref name, _)| name == attribute.name());
+        if !replaced {
+            write_attribute(
+                &format!("{}{}", prefix, attribute.name()),
+                attribute.value(),
+                output,
+            );
+        }
+    }
+
+    // Write each replaced attribute once, with its last value.
+    for (index, &(ref name, ref value)) in new_attributes.iter().enumerate() {
+        if new_attributes[index + 1..]
+            .iter()
+            .all(|&(ref later_name, _)| later_name != name)
+        {
+            write_attribute(name, value, output);
+        }
+    }
+    output.push('>');
+
+    for child in node.children() {
+        if child.is_element() {
+            write_element(child, edits, false, output);
+        } else if child.is_text() {
+            escape(child.text().unwrap_or(""), false, output);
+        }
+    }
+
+    output.push_str("</");
+    output.push_str(name);
+    output.push('>');
+    for _ in wrapper_ids {
+        output.push_str("</g>");
+    }
+}
+
+fn write_attribute(name: &str, value: &str, output: &mut String) {
+    output.push(' ');
+    output.push_str(name);
+    output.push_str("=\"");
+    escape(value, true, output);
+    output.push('"');
+}
+
+fn escape(text: &str, in_attribute: bool, output: &mut String) {
+    for character in text.chars() {
+        match character {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' if in_attribute => output.push_str("&quot;"),
+            _ => output.push(character),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// pathfinder/text/src/baseline.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Baselines from the OpenType `BASE` table.
+
+#[cfg(feature = "pf-font-kit")]
+use font_kit::loader::Loader;
+
+const BASE_TABLE_TAG: u32 = 0x4241_5345; // 'BASE'
+const DFLT_SCRIPT_TAG: u32 = 0x4446_4c54; // 'DFLT'
+const LATN_SCRIPT_TAG: u32 = 0x6c61_746e; // 'latn'
+const HANG_BASELINE_TAG: u32 = 0x6861_6e67; // 'hang'
+const IDEO_BASELINE_TAG: u32 = 0x6964_656f; // 'ideo'
+const ROMN_BASELINE_TAG: u32 = 0x726f_6d6e; // 'romn'
+
+/// The baselines of a font's horizontal text, in font units above the alphabetic baseline.
+///
+/// A baseline is `None` if the font doesn't say where it is.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Baselines {
+    /// The hanging baseline, which Devanagari and similar scripts hang from.
+    pub hanging: Option<f32>,
+    /// The ideographic baseline, at the bottom of the em box of CJK ideographs.
+    pub ideographic: Option<f32>,
+}
+
+impl Baselines {
+    /// Reads the baselines from the font's `BASE` table, using the default script's values or,
+    /// failing that, the Latin script's.
+    #[cfg(feature = "pf-font-kit")]
+    pub fn from_font<F>(font: &F) -> Baselines
+    where
+        F: Loader,
+    {
+        font.load_font_table(BASE_TABLE_TAG)
+            .and_then(|table| parse_base_table(&table))
+            .unwrap_or_default()
+    }
+
+    /// Reads the baselines from the `BASE` table of a font loaded with `ttf-parser`, like
+    /// `from_font()`.
+    #[cfg(feature = "pf-rustybuzz")]
+    pub fn from_face(face: &ttf_parser::Face) -> Baselines {
+        face.table_data(ttf_parser::Tag(BASE_TABLE_TAG))
+            .and_then(parse_base_table)
+            .unwrap_or_default()
+    }
+}
+
+fn parse_base_table(table: &[u8]) -> Option<Baselines> {
+    let axis = offset_from(table, 0, 4)?;
+    let tag_list = offset_from(table, axis, 0)?;
+    let script_list = offset_from(table, axis, 2)?;
+
+    let tag_count = read_u16(table, tag_list)? as usize;
+    let script_count = read_u16(table, script_list)? as usize;
+    let script_tags = (0..script_count)
+        .map(|index| read_u32(table, script_list + 2 + index * 6))
+        .collect::<Option<Vec<_>>>()?;
+    let script_index = script_tags
+        .iter()
+        .position(|&tag| tag == DFLT_SCRIPT_TAG)
+        .or_else(|| script_tags.iter().position(|&tag| tag == LATN_SCRIPT_TAG))
+        .unwrap_or(0);
+    if script_index >= script_count {
+        return None;
+    }
+    let base_script = offset_from(table, script_list, 2 + script_index * 6 + 4)?;
+    let base_values = offset_from(table, base_script, 0)?;
+
+    // Coordinates are listed in the same order as the baseline tags. Every coordinate format
+    // begins with the format and the coordinate.
+    let coord_count = (read_u16(table, base_values + 2)? as usize).min(tag_count);
+    let (mut baselines, mut roman) = (Baselines::default(), 0.0);
+    for index in 0..coord_count {
+        let tag = read_u32(table, tag_list + 2 + index * 4)?;
+        let base_coord = offset_from(table, base_values, 4 + index * 2)?;
+        let coordinate = read_u16(table, base_coord + 2)? as i16 as f32;
+        match tag {
+            HANG_BASELINE_TAG => baselines.hanging = Some(coordinate),
+            IDEO_BASELINE_TAG => baselines.ideographic = Some(coordinate),
+            ROMN_BASELINE_TAG => roman = coordinate,
+            _ => {}
+        }
+    }
+
+    // The alphabetic baseline is usually at zero, but the font may put it elsewhere.
+    baselines.hanging = baselines.hanging.map(|hanging| hanging - roman);
+    baselines.ideographic = baselines.ideographic.map(|ideographic| ideographic - roman);
+    Some(baselines)
+}
+
+// Reads a 16-bit offset at `base + position` and returns the position that it points to,
+// relative to `base`, or `None` if it's null or out of range.
+fn offset_from(table: &[u8], base: usize, position: usize) -> Option<usize> {
+    match read_u16(table, base + position)? {
+        0 => None,
+        offset => Some(base + offset as usize),
+    }
+}
+
+fn read_u16(table: &[u8], position: usize) -> Option<u16> {
+    let bytes = table.get(position..(position + 2))?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(table: &[u8], position: usize) -> Option<u32> {
+    let bytes = table.get(position..(position + 4))?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -8,25 +8,59 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! Text rendering for Pathfinder scenes.
+//!
+//! By default, fonts are loaded with `font-kit` and laid out with `skribo`. With the
+//! `pf-rustybuzz` feature, the `ttf` module instead loads fonts with `ttf-parser` and shapes text
+//! with `rustybuzz`, neither of which uses the platform font backends. Disabling the default
+//! `pf-font-kit` feature removes the `font-kit` and `skribo` dependencies.
+
+#[cfg(feature = "pf-font-kit")]
 use font_kit::error::GlyphLoadingError;
+#[cfg(feature = "pf-font-kit")]
+use font_kit::handle::Handle;
+#[cfg(feature = "pf-font-kit")]
 use font_kit::hinting::HintingOptions;
+#[cfg(feature = "pf-font-kit")]
 use font_kit::loader::Loader;
+#[cfg(feature = "pf-font-kit")]
 use font_kit::loaders::default::Font as DefaultLoader;
+#[cfg(feature = "pf-font-kit")]
 use font_kit::metrics::Metrics;
+#[cfg(feature = "pf-font-kit")]
 use font_kit::outline::OutlineSink;
 use pathfinder_content::effects::BlendMode;
+#[cfg(any(feature = "pf-font-kit", feature = "pf-rustybuzz"))]
 use pathfinder_content::outline::{Contour, Outline};
-use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
+#[cfg(feature = "pf-font-kit")]
+use pathfinder_content::stroke::OutlineStrokeToFill;
+use pathfinder_content::stroke::StrokeStyle;
+#[cfg(feature = "pf-font-kit")]
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{vec2f, Vector2F};
+#[cfg(feature = "pf-font-kit")]
+use pathfinder_geometry::vector::vec2f;
+use pathfinder_geometry::vector::Vector2F;
 use pathfinder_renderer::paint::PaintId;
-use pathfinder_renderer::scene::{ClipPathId, DrawPath, Scene};
+use pathfinder_renderer::scene::ClipPathId;
+#[cfg(feature = "pf-font-kit")]
+use pathfinder_renderer::scene::{DrawPath, DrawPathId, GlyphRun, RunGlyph, Scene};
+#[cfg(feature = "pf-font-kit")]
 use skribo::{FontCollection, Layout, TextStyle};
+#[cfg(feature = "pf-font-kit")]
 use std::collections::HashMap;
+#[cfg(any(feature = "pf-font-kit", feature = "pf-rustybuzz"))]
 use std::mem;
+#[cfg(feature = "pf-font-kit")]
 use std::sync::Arc;
 
+pub use crate::baseline::Baselines;
+
+mod baseline;
+#[cfg(feature = "pf-rustybuzz")]
+pub mod ttf;
+
+#[cfg(feature = "pf-font-kit")]
 #[derive(Clone)]
 pub struct FontContext<F>
 where
@@ -35,6 +69,7 @@ where
     font_info: HashMap<String, FontInfo<F>>,
 }
 
+#[cfg(feature = "pf-font-kit")]
 #[derive(Clone)]
 struct FontInfo<F>
 where
@@ -49,10 +84,15 @@ where
 pub struct FontRenderOptions {
     pub transform: Transform2F,
     pub render_mode: TextRenderMode,
+    /// How to hint glyph outlines. Fonts loaded with `ttf-parser` are never hinted.
+    #[cfg(feature = "pf-font-kit")]
     pub hinting_options: HintingOptions,
     pub clip_path: Option<ClipPathId>,
     pub blend_mode: BlendMode,
     pub paint_id: PaintId,
+    /// Antialiases glyphs separately in each color channel, for LCD screens with horizontal RGB
+    /// subpixels. Glyphs drawn this way are always composited source-over.
+    pub subpixel_coverage: bool,
 }
 
 impl Default for FontRenderOptions {
@@ -61,14 +101,17 @@ impl Default for FontRenderOptions {
         FontRenderOptions {
             transform: Transform2F::default(),
             render_mode: TextRenderMode::Fill,
+            #[cfg(feature = "pf-font-kit")]
             hinting_options: HintingOptions::None,
             clip_path: None,
             blend_mode: BlendMode::SrcOver,
             paint_id: PaintId(0),
+            subpixel_coverage: false,
         }
     }
 }
 
+#[cfg(feature = "pf-font-kit")]
 enum FontInfoRefMut<'a, F>
 where
     F: Loader,
@@ -80,6 +123,7 @@ where
 #[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
 pub struct GlyphId(pub u32);
 
+#[cfg(feature = "pf-font-kit")]
 impl<F> FontContext<F>
 where
     F: Loader,
@@ -100,7 +144,7 @@ where
         glyph_offset: Vector2F,
         font_size: f32,
         render_options: &FontRenderOptions,
-    ) -> Result<(), GlyphLoadingError> {
+    ) -> Result<DrawPathId, GlyphLoadingError> {
         // Insert the font into the cache if needed.
         let mut font_info = match font_key {
             Some(font_key) => {
@@ -171,9 +215,9 @@ where
         let mut path = DrawPath::new(outline, render_options.paint_id);
         path.set_clip_path(render_options.clip_path);
         path.set_blend_mode(render_options.blend_mode);
+        path.set_subpixel_coverage(render_options.subpixel_coverage);
 
-        scene.push_draw_path(path);
-        Ok(())
+        Ok(scene.push_draw_path(path))
     }
 
     /// Attempts to look up a font in the font cache.
@@ -185,6 +229,7 @@ where
     }
 }
 
+#[cfg(feature = "pf-font-kit")]
 impl FontContext<DefaultLoader> {
     pub fn push_layout(
         &mut self,
@@ -194,6 +239,7 @@ impl FontContext<DefaultLoader> {
         render_options: &FontRenderOptions,
     ) -> Result<(), GlyphLoadingError> {
         let mut cached_font_key: Option<CachedFontKey<DefaultLoader>> = None;
+        let mut glyph_run = None;
         for glyph in &layout.glyphs {
             match cached_font_key {
                 Some(ref cached_font_key)
@@ -203,10 +249,14 @@ impl FontContext<DefaultLoader> {
                         font: glyph.font.font.clone(),
                         key: glyph.font.font.postscript_name(),
                     });
+                    if let Some(glyph_run) = glyph_run.take() {
+                        scene.push_glyph_run(glyph_run);
+                    }
+                    glyph_run = new_glyph_run(&*glyph.font.font, style.size, render_options);
                 }
             }
             let cached_font_key = cached_font_key.as_ref().unwrap();
-            self.push_glyph(
+            let draw_path = self.push_glyph(
                 scene,
                 &*cached_font_key.font,
                 cached_font_key.key.as_ref().map(|key| &**key),
@@ -215,6 +265,16 @@ impl FontContext<DefaultLoader> {
                 style.size,
                 &render_options,
             )?;
+            if let Some(ref mut glyph_run) = glyph_run {
+                glyph_run.glyphs.push(RunGlyph {
+                    glyph_id: glyph.glyph_id,
+                    position: glyph.offset,
+                    draw_path,
+                });
+            }
+        }
+        if let Some(glyph_run) = glyph_run {
+            scene.push_glyph_run(glyph_run);
         }
         Ok(())
     }
@@ -233,6 +293,72 @@ impl FontContext<DefaultLoader> {
     }
 }
 
+/// Moves the glyphs of a layout apart by `letter_spacing` pixels after every glyph, plus
+/// `word_spacing` pixels after every space, like the CSS `letter-spacing` and `word-spacing`
+/// properties.
+///
+/// Either may be negative to bring the glyphs closer together.
+#[cfg(feature = "pf-font-kit")]
+pub fn apply_spacing(layout: &mut Layout, letter_spacing: f32, word_spacing: f32) {
+    let mut space_glyph: Option<(Arc<DefaultLoader>, Option<u32>)> = None;
+    let mut shift = 0.0;
+    for glyph in &mut layout.glyphs {
+        glyph.offset += vec2f(shift, 0.0);
+        shift += letter_spacing;
+        if word_spacing == 0.0 {
+            continue;
+        }
+
+        // Look up the space glyph once per run of glyphs in the same font.
+        let font = &glyph.font.font;
+        let space_glyph_id = match space_glyph {
+            Some((ref space_font, space_glyph_id)) if Arc::ptr_eq(space_font, font) => {
+                space_glyph_id
+            }
+            _ => {
+                let space_glyph_id = font.glyph_for_char(' ');
+                space_glyph = Some((font.clone(), space_glyph_id));
+                space_glyph_id
+            }
+        };
+        if space_glyph_id == Some(glyph.glyph_id) {
+            shift += word_spacing;
+        }
+    }
+}
+
+// Starts recording the glyphs drawn with a font, so that exporters can write them as text.
+//
+// Stroked text has no equivalent in most formats, so it's only recorded when filled.
+#[cfg(feature = "pf-font-kit")]
+fn new_glyph_run<F>(
+    font: &F,
+    font_size: f32,
+    render_options: &FontRenderOptions,
+) -> Option<GlyphRun>
+where
+    F: Loader,
+{
+    if render_options.render_mode != TextRenderMode::Fill {
+        return None;
+    }
+    let font_data = font.copy_font_data()?;
+    let font_index = match font.handle() {
+        Some(Handle::Memory { font_index, .. }) | Some(Handle::Path { font_index, .. }) => {
+            font_index
+        }
+        None => 0,
+    };
+    Some(GlyphRun {
+        font_data,
+        font_index,
+        font_size,
+        transform: render_options.transform,
+        glyphs: vec![],
+    })
+}
+
+#[cfg(feature = "pf-font-kit")]
 struct CachedFontKey<F>
 where
     F: Loader,
@@ -241,6 +367,7 @@ where
     key: Option<String>,
 }
 
+#[cfg(feature = "pf-font-kit")]
 impl<F> FontInfo<F>
 where
     F: Loader,
@@ -255,6 +382,7 @@ where
     }
 }
 
+#[cfg(feature = "pf-font-kit")]
 impl<'a, F> FontInfoRefMut<'a, F>
 where
     F: Loader,
@@ -273,12 +401,14 @@ pub enum TextRenderMode {
     Stroke(StrokeStyle),
 }
 
+#[cfg(any(feature = "pf-font-kit", feature = "pf-rustybuzz"))]
 struct OutlinePathBuilder {
     outline: Outline,
     current_contour: Contour,
     transform: Transform2F,
 }
 
+#[cfg(any(feature = "pf-font-kit", feature = "pf-rustybuzz"))]
 impl OutlinePathBuilder {
     fn new(transform: &Transform2F) -> OutlinePathBuilder {
         OutlinePathBuilder {
@@ -301,6 +431,7 @@ impl OutlinePathBuilder {
     }
 }
 
+#[cfg(feature = "pf-font-kit")]
 impl OutlineSink for OutlinePathBuilder {
     fn move_to(&mut self, to: Vector2F) {
         self.flush_current_contour();
@@ -0,0 +1,315 @@
+// pathfinder/text/src/ttf.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Text rendering with fonts loaded by `ttf-parser` and shaped by `rustybuzz`.
+//!
+//! Unlike the `font-kit` backend, this never touches the platform's fonts, so it works on WASM and
+//! embedded targets. Applications supply the font data themselves, and there's no fallback to
+//! other fonts for characters that a font lacks.
+
+use crate::{FontRenderOptions, GlyphId, OutlinePathBuilder, TextRenderMode};
+use pathfinder_content::outline::Outline;
+use pathfinder_content::stroke::OutlineStrokeToFill;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::scene::{DrawPath, DrawPathId, GlyphRun, RunGlyph, Scene};
+use rustybuzz::UnicodeBuffer;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::sync::Arc;
+
+/// A font loaded from an OpenType or TrueType file in memory.
+#[derive(Clone)]
+pub struct Font {
+    data: Arc<Vec<u8>>,
+    index: u32,
+    units_per_em: f32,
+}
+
+/// The error returned when font data can't be parsed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontParseError;
+
+/// Text shaped with a single font.
+#[derive(Clone)]
+pub struct Layout {
+    pub font: Font,
+    pub size: f32,
+    pub glyphs: Vec<LayoutGlyph>,
+    /// The position of the pen after the last glyph.
+    pub advance: Vector2F,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LayoutGlyph {
+    pub glyph_id: u32,
+    /// The position of the glyph's origin, relative to the start of the baseline, with y pointing
+    /// down.
+    pub offset: Vector2F,
+    /// The byte index in the text of the first character that the glyph was shaped from.
+    pub cluster: u32,
+}
+
+/// Caches glyph outlines for fonts loaded with `ttf-parser`.
+#[derive(Clone, Default)]
+pub struct FontContext {
+    font_info: HashMap<FontKey, FontInfo>,
+}
+
+// Fonts are identified by the address of their data, which `FontInfo` keeps alive.
+type FontKey = (usize, u32);
+
+#[derive(Clone)]
+struct FontInfo {
+    _data: Arc<Vec<u8>>,
+    // Outlines in font units, with y pointing up.
+    outline_cache: HashMap<GlyphId, Outline>,
+}
+
+impl Font {
+    /// Loads the font with the given index from a font file or collection.
+    pub fn from_bytes(data: Arc<Vec<u8>>, index: u32) -> Result<Font, FontParseError> {
+        let units_per_em = {
+            let face = ttf_parser::Face::from_slice(&data, index).map_err(|_| FontParseError)?;
+            face.units_per_em() as f32
+        };
+        if units_per_em <= 0.0 {
+            return Err(FontParseError);
+        }
+        Ok(Font {
+            data,
+            index,
+            units_per_em,
+        })
+    }
+
+    /// Returns the parsed font, for access to its tables.
+    ///
+    /// Parsing is cheap, since `ttf-parser` reads tables lazily, but callers doing many lookups
+    /// should keep the face around.
+    #[inline]
+    pub fn face(&self) -> rustybuzz::Face {
+        rustybuzz::Face::from_slice(&self.data, self.index).unwrap()
+    }
+
+    #[inline]
+    pub fn data(&self) -> &Arc<Vec<u8>> {
+        &self.data
+    }
+
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn units_per_em(&self) -> f32 {
+        self.units_per_em
+    }
+
+    /// Returns the ID of the glyph that the font maps a character to, if any.
+    #[inline]
+    pub fn glyph_for_char(&self, character: char) -> Option<u32> {
+        self.face()
+            .glyph_index(character)
+            .map(|glyph_id| glyph_id.0 as u32)
+    }
+
+    fn key(&self) -> FontKey {
+        (Arc::as_ptr(&self.data) as usize, self.index)
+    }
+}
+
+/// Shapes a line of text at the given font size, in pixels.
+///
+/// The direction and script are guessed from the text.
+pub fn layout(font: &Font, size: f32, text: &str) -> Layout {
+    let face = font.face();
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    let glyph_buffer = rustybuzz::shape(&face, &[], buffer);
+
+    let scale = size / font.units_per_em;
+    let mut pen = Vector2F::zero();
+    let mut glyphs = vec![];
+    for (info, position) in glyph_buffer
+        .glyph_infos()
+        .iter()
+        .zip(glyph_buffer.glyph_positions())
+    {
+        let offset = vec2f(position.x_offset as f32, position.y_offset as f32);
+        glyphs.push(LayoutGlyph {
+            glyph_id: info.glyph_id,
+            offset: flip_y(pen + offset * scale),
+            cluster: info.cluster,
+        });
+        pen += vec2f(position.x_advance as f32, position.y_advance as f32) * scale;
+    }
+
+    Layout {
+        font: font.clone(),
+        size,
+        glyphs,
+        advance: flip_y(pen),
+    }
+}
+
+impl FontContext {
+    #[inline]
+    pub fn new() -> FontContext {
+        FontContext::default()
+    }
+
+    /// Adds the glyphs of a layout to the scene.
+    pub fn push_layout(
+        &mut self,
+        scene: &mut Scene,
+        layout: &Layout,
+        render_options: &FontRenderOptions,
+    ) {
+        // Stroked text has no equivalent in most formats, so it's only recorded when filled.
+        let mut glyph_run = match render_options.render_mode {
+            TextRenderMode::Fill => Some(GlyphRun {
+                font_data: layout.font.data.clone(),
+                font_index: layout.font.index,
+                font_size: layout.size,
+                transform: render_options.transform,
+                glyphs: vec![],
+            }),
+            TextRenderMode::Stroke(_) => None,
+        };
+
+        for glyph in &layout.glyphs {
+            let draw_path = self.push_glyph(
+                scene,
+                &layout.font,
+                GlyphId(glyph.glyph_id),
+                glyph.offset,
+                layout.size,
+                render_options,
+            );
+            if let Some(ref mut glyph_run) = glyph_run {
+                glyph_run.glyphs.push(RunGlyph {
+                    glyph_id: glyph.glyph_id,
+                    position: glyph.offset,
+                    draw_path,
+                });
+            }
+        }
+
+        if let Some(glyph_run) = glyph_run {
+            scene.push_glyph_run(glyph_run);
+        }
+    }
+
+    /// Shapes a line of text and adds it to the scene, starting at the origin of
+    /// `render_options.transform`.
+    #[inline]
+    pub fn push_text(
+        &mut self,
+        scene: &mut Scene,
+        text: &str,
+        font: &Font,
+        size: f32,
+        render_options: &FontRenderOptions,
+    ) {
+        let layout = layout(font, size, text);
+        self.push_layout(scene, &layout, render_options)
+    }
+
+    fn push_glyph(
+        &mut self,
+        scene: &mut Scene,
+        font: &Font,
+        glyph_id: GlyphId,
+        glyph_offset: Vector2F,
+        font_size: f32,
+        render_options: &FontRenderOptions,
+    ) -> DrawPathId {
+        let font_info = self
+            .font_info
+            .entry(font.key())
+            .or_insert_with(|| FontInfo {
+                _data: font.data.clone(),
+                outline_cache: HashMap::new(),
+            });
+
+        // Glyphs without outlines, such as spaces, get empty paths so that glyph runs can refer
+        // to them.
+        let outline = font_info.outline_cache.entry(glyph_id).or_insert_with(|| {
+            let mut outline_builder = OutlinePathBuilder::new(&Transform2F::default());
+            font.face()
+                .outline_glyph(ttf_parser::GlyphId(glyph_id.0 as u16), &mut outline_builder);
+            outline_builder.build()
+        });
+
+        let font_scale = font_size / font.units_per_em;
+        let render_transform = render_options.transform
+            * Transform2F::from_scale(vec2f(font_scale, -font_scale)).translate(glyph_offset);
+        let mut outline = outline.clone().transformed(&render_transform);
+
+        if let TextRenderMode::Stroke(stroke_style) = render_options.render_mode {
+            let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
+            stroke_to_fill.offset();
+            outline = stroke_to_fill.into_outline();
+        }
+
+        let mut path = DrawPath::new(outline, render_options.paint_id);
+        path.set_clip_path(render_options.clip_path);
+        path.set_blend_mode(render_options.blend_mode);
+        path.set_subpixel_coverage(render_options.subpixel_coverage);
+        scene.push_draw_path(path)
+    }
+}
+
+impl Display for FontParseError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        formatter.write_str("the font data couldn't be parsed")
+    }
+}
+
+impl Error for FontParseError {}
+
+impl ttf_parser::OutlineBuilder for OutlinePathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.flush_current_contour();
+        self.current_contour
+            .push_endpoint(self.transform * vec2f(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.current_contour
+            .push_endpoint(self.transform * vec2f(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.current_contour
+            .push_quadratic(self.transform * vec2f(x1, y1), self.transform * vec2f(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.current_contour.push_cubic(
+            self.transform * vec2f(x1, y1),
+            self.transform * vec2f(x2, y2),
+            self.transform * vec2f(x, y),
+        );
+    }
+
+    fn close(&mut self) {
+        self.current_contour.close();
+    }
+}
+
+// Converts from font coordinates, where y points up, to scene coordinates.
+fn flip_y(vector: Vector2F) -> Vector2F {
+    vec2f(vector.x(), -vector.y())
+}
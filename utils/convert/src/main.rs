@@ -1,26 +1,69 @@
-use pathfinder_export::{Export, FileFormat};
+use pathfinder_color::ColorU;
+use pathfinder_export::{
+    Export, FileFormat, Orientation, PageFit, PageOptions, PageSize, PdfConformance,
+};
+use pathfinder_pdf::PDFDocument;
+use pathfinder_svg::text;
 use pathfinder_svg::SVGScene;
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufWriter, Read};
 use std::path::PathBuf;
-use usvg::{Options, Tree};
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut args = std::env::args_os().skip(1);
     let input = PathBuf::from(args.next().expect("no input given"));
     let output = PathBuf::from(args.next().expect("no output given"));
 
+    // PDF and PostScript pages are the size of the view box unless a paper size is given, in which
+    // case the scene is scaled to fit the page.
+    let mut page = PageOptions::default();
+    if let Some(paper) = args.next() {
+        let paper = paper.to_string_lossy().to_lowercase();
+        let (size, orientation) = match paper.split_at(paper.find('-').unwrap_or(paper.len())) {
+            (size, "") | (size, "-portrait") => (size, Orientation::Portrait),
+            (size, "-landscape") => (size, Orientation::Landscape),
+            _ => return Err(format!("unknown paper size: {}", paper).into()),
+        };
+        page.size = match size {
+            "a4" => PageSize::A4,
+            "letter" => PageSize::Letter,
+            _ => return Err(format!("unknown paper size: {}", paper).into()),
+        };
+        page.orientation = orientation;
+        page.fit = PageFit::Contain;
+    }
+
     let mut data = Vec::new();
     File::open(input)?.read_to_end(&mut data)?;
-    let svg = SVGScene::from_tree(&Tree::from_data(&data, &Options::default().to_ref()).unwrap());
 
-    let scene = &svg.scene;
+    // Only the first page of PDF input is converted. PDF pages are opaque, so they get a white
+    // background in PNG output.
+    let (scene, background) = if data.starts_with(b"%PDF") {
+        let pdf = PDFDocument::from_data(&data)?.render_page(0)?;
+        if !pdf.result_flags.is_empty() {
+            eprintln!("warning: unsupported PDF features: {}", pdf.result_flags);
+        }
+        (pdf.scene, Some(ColorU::white()))
+    } else {
+        let svg = SVGScene::from_data(&data, &text::options_with_system_fonts().to_ref())?;
+        (svg.scene, None)
+    };
+
     let mut writer = BufWriter::new(File::create(&output)?);
     let format = match output.extension().and_then(|s| s.to_str()) {
-        Some("pdf") => FileFormat::PDF,
-        Some("ps") => FileFormat::PS,
-        _ => return Err("output filename must have .ps or .pdf extension".into()),
+        Some("pdf") => FileFormat::PDF {
+            compressed: true,
+            page,
+            conformance: PdfConformance::Standard,
+        },
+        Some("ps") => FileFormat::PS { page },
+        Some("json") => FileFormat::JSON,
+        Some("png") => FileFormat::PNG {
+            size: scene.view_box().size().ceil().to_i32(),
+            background,
+        },
+        _ => return Err("output filename must have .ps, .pdf, .png, or .json extension".into()),
     };
     scene.export(&mut writer, format).unwrap();
     Ok(())
@@ -8,9 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+//! A WebAssembly package that draws on an HTML canvas element with Pathfinder, through the API of
+//! a 2D canvas context.
+
 use css_color_parser::Color;
-use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, FillRule, FillStyle};
-use pathfinder_canvas::{LineCap, Path2D};
+use pathfinder_canvas::{ArcDirection, Canvas, CanvasFontContext, CanvasRenderingContext2D};
+use pathfinder_canvas::{CompositeOperation, FillRule, FillStyle, LineCap, LineJoin, Path2D};
 use pathfinder_color::ColorU;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
@@ -43,14 +46,17 @@ struct WebCanvasState {
     stroke_style_string: Arc<String>,
 }
 
-#[wasm_bindgen(js_name = "createContext")]
-pub fn create_context(html_canvas: HtmlCanvasElement) -> PFCanvasRenderingContext2D {
+/// Creates a 2D context that draws on the canvas element with Pathfinder, using WebGL 2.
+///
+/// Drawing commands are recorded until `pfFlush()` renders them to the canvas.
+#[wasm_bindgen(js_name = "createPathfinderContext")]
+pub fn create_pathfinder_context(
+    html_canvas: HtmlCanvasElement,
+) -> Result<PFCanvasRenderingContext2D, JsValue> {
     let context = html_canvas
-        .get_context("webgl2")
-        .unwrap()
-        .unwrap()
-        .dyn_into::<WebGl2RenderingContext>()
-        .unwrap();
+        .get_context("webgl2")?
+        .and_then(|context| context.dyn_into::<WebGl2RenderingContext>().ok())
+        .ok_or_else(|| JsValue::from_str("WebGL 2 isn't available on this canvas"))?;
 
     // Get the real size of the window, taking HiDPI into account.
     let framebuffer_size = vec2i(html_canvas.width() as i32, html_canvas.height() as i32);
@@ -72,7 +78,7 @@ pub fn create_context(html_canvas: HtmlCanvasElement) -> PFCanvasRenderingContex
     let font_context = CanvasFontContext::from_system_source();
     let context = Canvas::new(framebuffer_size.to_f32()).get_context_2d(font_context);
 
-    PFCanvasRenderingContext2D {
+    Ok(PFCanvasRenderingContext2D {
         html_canvas,
         context,
         renderer,
@@ -82,7 +88,15 @@ pub fn create_context(html_canvas: HtmlCanvasElement) -> PFCanvasRenderingContex
             stroke_style_string: Arc::new("black".to_owned()),
         },
         saved_states: vec![],
-    }
+    })
+}
+
+/// The former name of `createPathfinderContext()`.
+#[wasm_bindgen(js_name = "createContext")]
+pub fn create_context(
+    html_canvas: HtmlCanvasElement,
+) -> Result<PFCanvasRenderingContext2D, JsValue> {
+    create_pathfinder_context(html_canvas)
 }
 
 #[wasm_bindgen]
@@ -177,6 +191,70 @@ impl PFCanvasRenderingContext2D {
         }
     }
 
+    #[wasm_bindgen(js_name = "lineJoin")]
+    #[wasm_bindgen(getter)]
+    pub fn line_join(&self) -> String {
+        match self.context.line_join() {
+            LineJoin::Miter => "miter".to_owned(),
+            LineJoin::Bevel => "bevel".to_owned(),
+            LineJoin::Round => "round".to_owned(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = "lineJoin")]
+    #[wasm_bindgen(setter)]
+    pub fn set_line_join(&mut self, new_line_join: &str) {
+        if new_line_join == "miter" {
+            self.context.set_line_join(LineJoin::Miter)
+        } else if new_line_join == "bevel" {
+            self.context.set_line_join(LineJoin::Bevel)
+        } else if new_line_join == "round" {
+            self.context.set_line_join(LineJoin::Round)
+        }
+    }
+
+    #[wasm_bindgen(js_name = "miterLimit")]
+    #[wasm_bindgen(getter)]
+    pub fn miter_limit(&self) -> f32 {
+        self.context.miter_limit()
+    }
+
+    #[wasm_bindgen(js_name = "miterLimit")]
+    #[wasm_bindgen(setter)]
+    pub fn set_miter_limit(&mut self, new_miter_limit: f32) {
+        self.context.set_miter_limit(new_miter_limit);
+    }
+
+    #[wasm_bindgen(js_name = "getLineDash")]
+    pub fn line_dash(&mut self) -> Vec<f32> {
+        self.context.line_dash().to_vec()
+    }
+
+    #[wasm_bindgen(js_name = "setLineDash")]
+    pub fn set_line_dash(&mut self, segments: Vec<f32>) {
+        self.context.set_line_dash(segments);
+    }
+
+    #[wasm_bindgen(js_name = "lineDashOffset")]
+    #[wasm_bindgen(getter)]
+    pub fn line_dash_offset(&self) -> f32 {
+        self.context.line_dash_offset()
+    }
+
+    #[wasm_bindgen(js_name = "lineDashOffset")]
+    #[wasm_bindgen(setter)]
+    pub fn set_line_dash_offset(&mut self, new_line_dash_offset: f32) {
+        self.context.set_line_dash_offset(new_line_dash_offset);
+    }
+
+    // Fill and stroke styles
+
+    #[wasm_bindgen(js_name = "fillStyle")]
+    #[wasm_bindgen(getter)]
+    pub fn fill_style(&self) -> String {
+        (*self.current_state.fill_style_string).clone()
+    }
+
     #[wasm_bindgen(js_name = "fillStyle")]
     #[wasm_bindgen(setter)]
     pub fn set_fill_style(&mut self, new_style_string: &str) {
@@ -195,6 +273,87 @@ impl PFCanvasRenderingContext2D {
         }
     }
 
+    #[wasm_bindgen(js_name = "strokeStyle")]
+    #[wasm_bindgen(getter)]
+    pub fn stroke_style(&self) -> String {
+        (*self.current_state.stroke_style_string).clone()
+    }
+
+    // Shadows
+
+    #[wasm_bindgen(js_name = "shadowBlur")]
+    #[wasm_bindgen(getter)]
+    pub fn shadow_blur(&self) -> f32 {
+        self.context.shadow_blur()
+    }
+
+    #[wasm_bindgen(js_name = "shadowBlur")]
+    #[wasm_bindgen(setter)]
+    pub fn set_shadow_blur(&mut self, new_shadow_blur: f32) {
+        self.context.set_shadow_blur(new_shadow_blur);
+    }
+
+    #[wasm_bindgen(js_name = "shadowColor")]
+    #[wasm_bindgen(setter)]
+    pub fn set_shadow_color(&mut self, new_shadow_color: &str) {
+        if let Some(FillStyle::Color(color)) = parse_fill_or_stroke_style(new_shadow_color) {
+            self.context.set_shadow_color(color);
+        }
+    }
+
+    #[wasm_bindgen(js_name = "shadowOffsetX")]
+    #[wasm_bindgen(getter)]
+    pub fn shadow_offset_x(&self) -> f32 {
+        self.context.shadow_offset().x()
+    }
+
+    #[wasm_bindgen(js_name = "shadowOffsetX")]
+    #[wasm_bindgen(setter)]
+    pub fn set_shadow_offset_x(&mut self, new_shadow_offset_x: f32) {
+        let offset = self.context.shadow_offset();
+        self.context
+            .set_shadow_offset(vec2f(new_shadow_offset_x, offset.y()));
+    }
+
+    #[wasm_bindgen(js_name = "shadowOffsetY")]
+    #[wasm_bindgen(getter)]
+    pub fn shadow_offset_y(&self) -> f32 {
+        self.context.shadow_offset().y()
+    }
+
+    #[wasm_bindgen(js_name = "shadowOffsetY")]
+    #[wasm_bindgen(setter)]
+    pub fn set_shadow_offset_y(&mut self, new_shadow_offset_y: f32) {
+        let offset = self.context.shadow_offset();
+        self.context
+            .set_shadow_offset(vec2f(offset.x(), new_shadow_offset_y));
+    }
+
+    // Compositing
+
+    #[wasm_bindgen(js_name = "globalAlpha")]
+    #[wasm_bindgen(getter)]
+    pub fn global_alpha(&self) -> f32 {
+        self.context.global_alpha()
+    }
+
+    #[wasm_bindgen(js_name = "globalAlpha")]
+    #[wasm_bindgen(setter)]
+    pub fn set_global_alpha(&mut self, new_global_alpha: f32) {
+        self.context.set_global_alpha(new_global_alpha);
+    }
+
+    #[wasm_bindgen(js_name = "globalCompositeOperation")]
+    #[wasm_bindgen(setter)]
+    pub fn set_global_composite_operation(&mut self, new_composite_operation: &str) {
+        if let Some(new_composite_operation) = parse_composite_operation(new_composite_operation) {
+            self.context
+                .set_global_composite_operation(new_composite_operation);
+        }
+    }
+
+    // Transformations
+
     pub fn transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
         let new_transform = self.context.transform() * Transform2F::row_major(a, c, e, b, d, f);
         self.context.set_transform(&new_transform)
@@ -212,6 +371,19 @@ impl PFCanvasRenderingContext2D {
         self.context.rotate(angle)
     }
 
+    #[wasm_bindgen(js_name = "setTransform")]
+    pub fn set_transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        self.context
+            .set_transform(&Transform2F::row_major(a, c, e, b, d, f))
+    }
+
+    #[wasm_bindgen(js_name = "resetTransform")]
+    pub fn reset_transform(&mut self) {
+        self.context.reset_transform()
+    }
+
+    // Paths
+
     #[wasm_bindgen(js_name = "beginPath")]
     pub fn begin_path(&mut self) {
         self.default_path = Path2D::new();
@@ -239,14 +411,73 @@ impl PFCanvasRenderingContext2D {
             .quadratic_curve_to(vec2f(cpx, cpy), vec2f(x, y))
     }
 
+    pub fn arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: Option<bool>,
+    ) {
+        self.default_path.arc(
+            vec2f(x, y),
+            radius,
+            start_angle,
+            end_angle,
+            arc_direction(anticlockwise),
+        )
+    }
+
+    #[wasm_bindgen(js_name = "arcTo")]
+    pub fn arc_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) {
+        self.default_path
+            .arc_to(vec2f(x1, y1), vec2f(x2, y2), radius)
+    }
+
+    pub fn ellipse(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        start_angle: f32,
+        end_angle: f32,
+        anticlockwise: Option<bool>,
+    ) {
+        self.default_path.ellipse(
+            vec2f(x, y),
+            vec2f(radius_x, radius_y),
+            rotation,
+            start_angle,
+            end_angle,
+            arc_direction(anticlockwise),
+        )
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.default_path
+            .rect(RectF::new(vec2f(x, y), vec2f(width, height)))
+    }
+
+    #[wasm_bindgen(js_name = "roundRect")]
+    pub fn round_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: Option<f32>) {
+        self.default_path.round_rect(
+            RectF::new(vec2f(x, y), vec2f(width, height)),
+            radius.unwrap_or(0.0),
+        )
+    }
+
     #[wasm_bindgen(js_name = "closePath")]
     pub fn close_path(&mut self) {
         self.default_path.close_path();
     }
 
-    pub fn fill(&mut self) {
+    /// Fills the current path with the `"nonzero"` (the default) or `"evenodd"` fill rule.
+    pub fn fill(&mut self, fill_rule: Option<String>) {
         let path = self.default_path.clone();
-        self.context.fill_path(path, FillRule::Winding);
+        self.context.fill_path(path, parse_fill_rule(fill_rule));
     }
 
     pub fn stroke(&mut self) {
@@ -254,6 +485,25 @@ impl PFCanvasRenderingContext2D {
         self.context.stroke_path(path);
     }
 
+    pub fn clip(&mut self, fill_rule: Option<String>) {
+        let path = self.default_path.clone();
+        self.context.clip_path(path, parse_fill_rule(fill_rule));
+    }
+
+    #[wasm_bindgen(js_name = "isPointInPath")]
+    pub fn is_point_in_path(&self, x: f32, y: f32, fill_rule: Option<String>) -> bool {
+        self.context
+            .is_point_in_path(&self.default_path, vec2f(x, y), parse_fill_rule(fill_rule))
+    }
+
+    #[wasm_bindgen(js_name = "isPointInStroke")]
+    pub fn is_point_in_stroke(&self, x: f32, y: f32) -> bool {
+        self.context
+            .is_point_in_stroke(&self.default_path, vec2f(x, y))
+    }
+
+    // The canvas state
+
     pub fn save(&mut self) {
         self.context.save();
         self.saved_states.push(self.current_state.clone());
@@ -280,3 +530,50 @@ fn parse_fill_or_stroke_style(string: &str) -> Option<FillStyle> {
     );
     Some(FillStyle::Color(color))
 }
+
+fn parse_fill_rule(string: Option<String>) -> FillRule {
+    match string.as_deref() {
+        Some("evenodd") => FillRule::EvenOdd,
+        _ => FillRule::Winding,
+    }
+}
+
+fn parse_composite_operation(string: &str) -> Option<CompositeOperation> {
+    match string {
+        "source-over" => Some(CompositeOperation::SourceOver),
+        "source-in" => Some(CompositeOperation::SourceIn),
+        "source-out" => Some(CompositeOperation::SourceOut),
+        "source-atop" => Some(CompositeOperation::SourceAtop),
+        "destination-over" => Some(CompositeOperation::DestinationOver),
+        "destination-in" => Some(CompositeOperation::DestinationIn),
+        "destination-out" => Some(CompositeOperation::DestinationOut),
+        "destination-atop" => Some(CompositeOperation::DestinationAtop),
+        "lighter" => Some(CompositeOperation::Lighter),
+        "copy" => Some(CompositeOperation::Copy),
+        "xor" => Some(CompositeOperation::Xor),
+        "multiply" => Some(CompositeOperation::Multiply),
+        "screen" => Some(CompositeOperation::Screen),
+        "overlay" => Some(CompositeOperation::Overlay),
+        "darken" => Some(CompositeOperation::Darken),
+        "lighten" => Some(CompositeOperation::Lighten),
+        "color-dodge" => Some(CompositeOperation::ColorDodge),
+        "color-burn" => Some(CompositeOperation::ColorBurn),
+        "hard-light" => Some(CompositeOperation::HardLight),
+        "soft-light" => Some(CompositeOperation::SoftLight),
+        "difference" => Some(CompositeOperation::Difference),
+        "exclusion" => Some(CompositeOperation::Exclusion),
+        "hue" => Some(CompositeOperation::Hue),
+        "saturation" => Some(CompositeOperation::Saturation),
+        "color" => Some(CompositeOperation::Color),
+        "luminosity" => Some(CompositeOperation::Luminosity),
+        _ => None,
+    }
+}
+
+fn arc_direction(anticlockwise: Option<bool>) -> ArcDirection {
+    if anticlockwise.unwrap_or(false) {
+        ArcDirection::CCW
+    } else {
+        ArcDirection::CW
+    }
+}
@@ -9,6 +9,10 @@
 // except according to those terms.
 
 //! A WebGL implementation of the device abstraction.
+//!
+//! WebGL 2 contexts run at the D3D10 feature level. WebGL 1 contexts run at the D3D9 level, with
+//! the OpenGL ES 2.0 shaders and extensions standing in for the WebGL 2 features that the renderer
+//! needs.
 
 #[macro_use]
 extern crate log;
@@ -16,29 +20,102 @@ extern crate log;
 use js_sys::{Float32Array, Object, Uint16Array, Uint8Array};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::DeviceError;
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
-use pathfinder_gpu::{ImageBinding, Primitive, ProgramKind, RenderOptions, RenderState};
-use pathfinder_gpu::{RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
-use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceCapabilities};
+use pathfinder_gpu::{FeatureLevel, ImageBinding, Primitive, ProgramKind, RenderOptions};
+use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, TextureBinding};
+use pathfinder_gpu::{
+    TextureData, TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData,
+};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::mem;
 use std::ops::Range;
 use std::str;
 use std::time::Duration;
 use wasm_bindgen::JsCast;
 use web_sys::WebGl2RenderingContext as WebGl;
+use web_sys::{AngleInstancedArrays, OesVertexArrayObject, WebGlVertexArrayObject};
+
+// Enums from `EXT_disjoint_timer_query_webgl2`, which `web-sys` doesn't define.
+const TIME_ELAPSED_EXT: u32 = 0x88bf;
+const GPU_DISJOINT_EXT: u32 = 0x8fbb;
+
+// Enums from WebGL 1 and its extensions that differ from their WebGL 2 equivalents.
+const LUMINANCE: u32 = 0x1909;
+const HALF_FLOAT_OES: u32 = 0x8d61;
+
+// The extensions that a WebGL 1 context needs, besides the ones that `WebGl1Extensions` holds.
+const WEBGL1_REQUIRED_EXTENSIONS: &[&str] = &[
+    "OES_element_index_uint",
+    "OES_texture_float",
+    "OES_texture_half_float",
+    "EXT_color_buffer_half_float",
+];
 
 pub struct WebGlDevice {
+    // A WebGL 1 context is kept as a WebGL 2 one. `web-sys` looks methods up on the object when
+    // they're called, so the methods that both versions share work on either.
     context: web_sys::WebGl2RenderingContext,
+    // The extensions that stand in for WebGL 2 features, if this is a WebGL 1 context.
+    webgl1: Option<WebGl1Extensions>,
+    timer_queries_supported: bool,
+}
+
+#[derive(Clone)]
+struct WebGl1Extensions {
+    vertex_array_object: OesVertexArrayObject,
+    instanced_arrays: AngleInstancedArrays,
 }
 
+/// A WebGL extension that the device needs but the context doesn't offer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MissingExtension(pub &'static str);
+
 impl WebGlDevice {
     pub fn new(context: web_sys::WebGl2RenderingContext) -> Self {
         context.get_extension("EXT_color_buffer_float").unwrap();
-        WebGlDevice { context }
+        // Browsers may hide this extension to mitigate timing attacks. Without it, timer queries
+        // report zero.
+        let timer_query_extension = context.get_extension("EXT_disjoint_timer_query_webgl2");
+        let timer_queries_supported = matches!(timer_query_extension, Ok(Some(_)));
+        WebGlDevice {
+            context,
+            webgl1: None,
+            timer_queries_supported,
+        }
+    }
+
+    /// Wraps a WebGL 1 context, which renders at the D3D9 feature level: subpixel coverage and
+    /// most filters aren't available.
+    ///
+    /// The context must offer the `OES_vertex_array_object`, `ANGLE_instanced_arrays`,
+    /// `OES_element_index_uint`, `OES_texture_float`, `OES_texture_half_float`, and
+    /// `EXT_color_buffer_half_float` extensions, and vertex shaders must be able to sample
+    /// textures. Timer queries always report zero.
+    pub fn new_webgl1(
+        context: web_sys::WebGlRenderingContext,
+    ) -> Result<WebGlDevice, MissingExtension> {
+        let extension = |name| match context.get_extension(name) {
+            Ok(Some(extension)) => Ok(extension),
+            Ok(None) | Err(_) => Err(MissingExtension(name)),
+        };
+        let webgl1 = WebGl1Extensions {
+            vertex_array_object: extension("OES_vertex_array_object")?.unchecked_into(),
+            instanced_arrays: extension("ANGLE_instanced_arrays")?.unchecked_into(),
+        };
+        for &name in WEBGL1_REQUIRED_EXTENSIONS {
+            extension(name)?;
+        }
+        Ok(WebGlDevice {
+            context: context.unchecked_into(),
+            webgl1: Some(webgl1),
+            timer_queries_supported: false,
+        })
     }
 
     // Error checking
@@ -97,16 +174,25 @@ impl WebGlDevice {
 
     #[inline]
     fn bind_vertex_array(&self, vertex_array: &WebGlVertexArray) {
-        self.context
-            .bind_vertex_array(Some(&vertex_array.gl_vertex_array));
+        self.bind_gl_vertex_array(Some(&vertex_array.gl_vertex_array));
         self.ck();
     }
 
     #[inline]
     fn unbind_vertex_array(&self) {
-        self.context.bind_vertex_array(None);
+        self.bind_gl_vertex_array(None);
         self.ck();
     }
+
+    #[inline]
+    fn bind_gl_vertex_array(&self, gl_vertex_array: Option<&WebGlVertexArrayObject>) {
+        match self.webgl1 {
+            Some(ref webgl1) => webgl1
+                .vertex_array_object
+                .bind_vertex_array_oes(gl_vertex_array),
+            None => self.context.bind_vertex_array(gl_vertex_array),
+        }
+    }
     #[inline]
     fn set_uniform(&self, uniform: &WebGlUniform, data: &UniformData) {
         let location = uniform.location.as_ref();
@@ -188,8 +274,7 @@ impl WebGlDevice {
 
         self.context
             .use_program(Some(&render_state.program.gl_program));
-        self.context
-            .bind_vertex_array(Some(&render_state.vertex_array.gl_vertex_array));
+        self.bind_gl_vertex_array(Some(&render_state.vertex_array.gl_vertex_array));
 
         self.bind_textures_and_images(
             &render_state.program,
@@ -246,6 +331,10 @@ impl WebGlDevice {
                     BlendFactor::DestAlpha => WebGl::DST_ALPHA,
                     BlendFactor::OneMinusDestAlpha => WebGl::ONE_MINUS_DST_ALPHA,
                     BlendFactor::DestColor => WebGl::DST_COLOR,
+                    BlendFactor::OneMinusSrcColor => WebGl::ONE_MINUS_SRC_COLOR,
+                    BlendFactor::OneMinusSrc1Color => {
+                        panic!("WebGL doesn't support dual-source blending!")
+                    }
                 };
 
                 self.context.blend_func_separate(
@@ -360,6 +449,55 @@ impl WebGlDevice {
         }
     }
 
+    fn compile_shader_from_source(
+        &self,
+        name: &str,
+        source: &[u8],
+        kind: ShaderKind,
+    ) -> Result<WebGlShader, DeviceError> {
+        let glsl_version_spec = match self.webgl1 {
+            Some(_) => "100",
+            None => "300 es",
+        };
+
+        let source = self.preprocess(source, glsl_version_spec);
+
+        let gl_shader_kind = match kind {
+            ShaderKind::Vertex => WebGl::VERTEX_SHADER,
+            ShaderKind::Fragment => WebGl::FRAGMENT_SHADER,
+            ShaderKind::Compute => {
+                return Err(DeviceError::UnsupportedShader {
+                    name: name.to_owned(),
+                    kind,
+                })
+            }
+        };
+
+        let gl_shader = self
+            .context
+            .create_shader(gl_shader_kind)
+            .expect("could not create shader");
+        self.context.shader_source(&gl_shader, &source);
+        self.context.compile_shader(&gl_shader);
+        let compile_status = self
+            .context
+            .get_shader_parameter(&gl_shader, WebGl::COMPILE_STATUS);
+        if !compile_status.as_bool().unwrap_or(false) {
+            let log = self
+                .context
+                .get_shader_info_log(&gl_shader)
+                .unwrap_or_default();
+            self.context.delete_shader(Some(&gl_shader));
+            return Err(DeviceError::ShaderCompile {
+                name: name.to_owned(),
+                kind,
+                log,
+            });
+        }
+
+        Ok(WebGlShader { gl_shader })
+    }
+
     fn preprocess(&self, source: &[u8], version: &str) -> String {
         let source = std::str::from_utf8(source).unwrap();
         let mut output = String::new();
@@ -456,7 +594,28 @@ impl Device for WebGlDevice {
 
     #[inline]
     fn feature_level(&self) -> FeatureLevel {
-        FeatureLevel::D3D10
+        match self.webgl1 {
+            Some(_) => FeatureLevel::D3D9,
+            None => FeatureLevel::D3D10,
+        }
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let get_integer = |parameter| match self.context.get_parameter(parameter) {
+            Ok(value) => value.as_f64().unwrap_or(0.0) as u32,
+            Err(_) => 0,
+        };
+        // WebGL 1 has no multisampled renderbuffers.
+        let max_msaa_samples = match self.webgl1 {
+            Some(_) => 1,
+            None => u32::max(get_integer(WebGl::MAX_SAMPLES), 1),
+        };
+        DeviceCapabilities {
+            max_texture_size: get_integer(WebGl::MAX_TEXTURE_SIZE),
+            compute_shaders: false,
+            dual_source_blending: false,
+            max_msaa_samples,
+        }
     }
 
     fn create_texture(&self, format: TextureFormat, size: Vector2I) -> WebGlTexture {
@@ -472,12 +631,12 @@ impl Device for WebGlDevice {
             .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
                 WebGl::TEXTURE_2D,
                 0,
-                format.gl_internal_format() as i32,
+                format.gl_internal_format(self.webgl1.is_some()) as i32,
                 size.x(),
                 size.y(),
                 0,
-                format.gl_format(),
-                format.gl_type(),
+                format.gl_format(self.webgl1.is_some()),
+                format.gl_type(self.webgl1.is_some()),
                 None,
             )
             .unwrap();
@@ -507,12 +666,12 @@ impl Device for WebGlDevice {
             .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
                 WebGl::TEXTURE_2D,
                 0,
-                format.gl_internal_format() as i32,
+                format.gl_internal_format(self.webgl1.is_some()) as i32,
                 size.x(),
                 size.y(),
                 0,
-                format.gl_format(),
-                format.gl_type(),
+                format.gl_format(self.webgl1.is_some()),
+                format.gl_type(self.webgl1.is_some()),
                 Some(&data),
             )
             .unwrap();
@@ -532,41 +691,26 @@ impl Device for WebGlDevice {
         source: &[u8],
         kind: ShaderKind,
     ) -> WebGlShader {
-        let glsl_version_spec = "300 es";
-
-        let source = self.preprocess(source, glsl_version_spec);
-
-        let gl_shader_kind = match kind {
-            ShaderKind::Vertex => WebGl::VERTEX_SHADER,
-            ShaderKind::Fragment => WebGl::FRAGMENT_SHADER,
-            ShaderKind::Compute => panic!("Compute shaders are unsupported in WebGL!"),
-        };
-
-        let gl_shader = self
-            .context
-            .create_shader(gl_shader_kind)
-            .expect("could not create shader");
-        self.context.shader_source(&gl_shader, &source);
-        self.context.compile_shader(&gl_shader);
-        let compile_status = self
-            .context
-            .get_shader_parameter(&gl_shader, WebGl::COMPILE_STATUS);
-        if !compile_status.as_bool().unwrap_or(false) {
-            if let Some(info_log) = self.context.get_shader_info_log(&gl_shader) {
-                info!("Shader info log:\n{}", info_log);
-            }
-            panic!("{:?} shader '{}' compilation failed", kind, name);
-        }
-
-        WebGlShader { gl_shader }
+        self.compile_shader_from_source(name, source, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 
     fn create_program_from_shaders(
         &self,
-        _resources: &dyn ResourceLoader,
+        resources: &dyn ResourceLoader,
         name: &str,
         shaders: ProgramKind<WebGlShader>,
     ) -> WebGlProgram {
+        self.try_create_program_from_shaders(resources, name, shaders)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_program_from_shaders(
+        &self,
+        _resources: &dyn ResourceLoader,
+        name: &str,
+        shaders: ProgramKind<WebGlShader>,
+    ) -> Result<WebGlProgram, DeviceError> {
         let gl_program = self
             .context
             .create_program()
@@ -590,19 +734,24 @@ impl Device for WebGlDevice {
             .as_bool()
             .unwrap_or(false)
         {
-            if let Some(info_log) = self.context.get_program_info_log(&gl_program) {
-                info!("Program info log for {}:\n{}", name, info_log);
-            }
-            panic!("Program {:?} linking failed", name);
+            let log = self
+                .context
+                .get_program_info_log(&gl_program)
+                .unwrap_or_default();
+            self.context.delete_program(Some(&gl_program));
+            return Err(DeviceError::ProgramLink {
+                name: name.to_owned(),
+                log,
+            });
         }
 
         let parameters = WebGlProgramParameters { textures: vec![] };
 
-        WebGlProgram {
+        Ok(WebGlProgram {
             context: self.context.clone(),
             gl_program,
             parameters: RefCell::new(parameters),
-        }
+        })
     }
 
     #[inline]
@@ -612,9 +761,17 @@ impl Device for WebGlDevice {
 
     #[inline]
     fn create_vertex_array(&self) -> WebGlVertexArray {
+        let gl_vertex_array = match self.webgl1 {
+            Some(ref webgl1) => webgl1.vertex_array_object.create_vertex_array_oes(),
+            None => self.context.create_vertex_array(),
+        };
         WebGlVertexArray {
             context: self.context.clone(),
-            gl_vertex_array: self.context.create_vertex_array().unwrap(),
+            vertex_array_object: self
+                .webgl1
+                .as_ref()
+                .map(|webgl1| webgl1.vertex_array_object.clone()),
+            gl_vertex_array: gl_vertex_array.unwrap(),
         }
     }
 
@@ -669,8 +826,13 @@ impl Device for WebGlDevice {
     ) {
         debug_assert_ne!(descriptor.stride, 0);
 
-        self.context
-            .bind_vertex_array(Some(&vertex_array.gl_vertex_array));
+        // WebGL 1 has no integer attributes.
+        let descriptor = match self.webgl1 {
+            Some(_) => descriptor.to_float_compatible(),
+            None => *descriptor,
+        };
+
+        self.bind_gl_vertex_array(Some(&vertex_array.gl_vertex_array));
 
         let attr_type = descriptor.attr_type.to_gl_type();
         match descriptor.class {
@@ -696,10 +858,16 @@ impl Device for WebGlDevice {
             }
         }
 
-        self.context
-            .vertex_attrib_divisor(attr.attr, descriptor.divisor);
+        match self.webgl1 {
+            Some(ref webgl1) => webgl1
+                .instanced_arrays
+                .vertex_attrib_divisor_angle(attr.attr, descriptor.divisor),
+            None => self
+                .context
+                .vertex_attrib_divisor(attr.attr, descriptor.divisor),
+        }
         self.context.enable_vertex_attrib_array(attr.attr);
-        self.context.bind_vertex_array(None);
+        self.bind_gl_vertex_array(None);
     }
 
     fn create_framebuffer(&self, texture: WebGlTexture) -> WebGlFramebuffer {
@@ -858,12 +1026,12 @@ impl Device for WebGlDevice {
                 .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
                     WebGl::TEXTURE_2D,
                     0,
-                    texture.format.gl_internal_format() as i32,
+                    texture.format.gl_internal_format(self.webgl1.is_some()) as i32,
                     rect.width(),
                     rect.height(),
                     0,
-                    texture.format.gl_format(),
-                    texture.format.gl_type(),
+                    texture.format.gl_format(self.webgl1.is_some()),
+                    texture.format.gl_type(self.webgl1.is_some()),
                     Some(&data),
                 )
                 .unwrap();
@@ -876,8 +1044,8 @@ impl Device for WebGlDevice {
                     rect.origin().y(),
                     rect.width(),
                     rect.height(),
-                    texture.format.gl_format(),
-                    texture.format.gl_type(),
+                    texture.format.gl_format(self.webgl1.is_some()),
+                    texture.format.gl_type(self.webgl1.is_some()),
                     Some(&data),
                 )
                 .unwrap();
@@ -927,13 +1095,24 @@ impl Device for WebGlDevice {
         render_state: &RenderState<Self>,
     ) {
         self.set_render_state(render_state);
-        self.context.draw_elements_instanced_with_i32(
-            render_state.primitive.to_gl_primitive(),
-            index_count as i32,
-            WebGl::UNSIGNED_INT,
-            0,
-            instance_count as i32,
-        );
+        match self.webgl1 {
+            Some(ref webgl1) => webgl1
+                .instanced_arrays
+                .draw_elements_instanced_angle_with_i32(
+                    render_state.primitive.to_gl_primitive(),
+                    index_count as i32,
+                    WebGl::UNSIGNED_INT,
+                    0,
+                    instance_count as i32,
+                ),
+            None => self.context.draw_elements_instanced_with_i32(
+                render_state.primitive.to_gl_primitive(),
+                index_count as i32,
+                WebGl::UNSIGNED_INT,
+                0,
+                instance_count as i32,
+            ),
+        }
         self.reset_render_state(render_state);
     }
 
@@ -943,28 +1122,64 @@ impl Device for WebGlDevice {
 
     #[inline]
     fn create_timer_query(&self) -> WebGlTimerQuery {
-        // FIXME use performance timers
-        WebGlTimerQuery {}
+        let gl_query = if self.timer_queries_supported {
+            self.context.create_query()
+        } else {
+            None
+        };
+        WebGlTimerQuery {
+            context: self.context.clone(),
+            gl_query,
+        }
     }
 
     #[inline]
-    fn begin_timer_query(&self, _query: &Self::TimerQuery) {
-        // FIXME use performance timers
+    fn begin_timer_query(&self, query: &Self::TimerQuery) {
+        if let Some(ref gl_query) = query.gl_query {
+            self.context.begin_query(TIME_ELAPSED_EXT, gl_query);
+            self.ck();
+        }
     }
 
     #[inline]
-    fn end_timer_query(&self, _: &Self::TimerQuery) {
-        // FIXME use performance timers
+    fn end_timer_query(&self, query: &Self::TimerQuery) {
+        if query.gl_query.is_some() {
+            self.context.end_query(TIME_ELAPSED_EXT);
+            self.ck();
+        }
     }
 
-    #[inline]
-    fn try_recv_timer_query(&self, _query: &WebGlTimerQuery) -> Option<Duration> {
-        None
+    fn try_recv_timer_query(&self, query: &WebGlTimerQuery) -> Option<Duration> {
+        let gl_query = match query.gl_query {
+            None => return Some(Duration::default()),
+            Some(ref gl_query) => gl_query,
+        };
+        let available = self
+            .context
+            .get_query_parameter(gl_query, WebGl::QUERY_RESULT_AVAILABLE);
+        if available.as_bool() == Some(true) {
+            Some(self.recv_timer_query(query))
+        } else {
+            None
+        }
     }
 
-    #[inline]
-    fn recv_timer_query(&self, _query: &WebGlTimerQuery) -> Duration {
-        Duration::from_millis(0)
+    fn recv_timer_query(&self, query: &WebGlTimerQuery) -> Duration {
+        let gl_query = match query.gl_query {
+            None => return Duration::default(),
+            Some(ref gl_query) => gl_query,
+        };
+        let elapsed = self
+            .context
+            .get_query_parameter(gl_query, WebGl::QUERY_RESULT);
+
+        // If the GPU was disjoint (e.g. its clock changed) while the query ran, the result is
+        // meaningless, so report nothing rather than a bogus time.
+        let disjoint = self.context.get_parameter(GPU_DISJOINT_EXT);
+        if let Ok(Some(true)) = disjoint.map(|disjoint| disjoint.as_bool()) {
+            return Duration::default();
+        }
+        Duration::from_nanos(elapsed.as_f64().unwrap_or(0.0) as u64)
     }
 
     fn try_recv_buffer(&self, _: &()) -> Option<Vec<u8>> {
@@ -1007,13 +1222,30 @@ impl Device for WebGlDevice {
         name: &str,
         kind: ShaderKind,
     ) -> Self::Shader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<Self::Shader, DeviceError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
-        let path = format!("shaders/gl3/{}.{}s.glsl", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let directory = match self.webgl1 {
+            Some(_) => "gles2",
+            None => "gl3",
+        };
+        let path = format!("shaders/{}/{}.{}s.glsl", directory, name, suffix);
+        match resources.slurp(&path) {
+            Ok(source) => self.compile_shader_from_source(name, &source, kind),
+            Err(error) => Err(DeviceError::ResourceLoad { path, error }),
+        }
     }
 
     fn add_fence(&self) -> Self::Fence {
@@ -1027,14 +1259,22 @@ impl Device for WebGlDevice {
 
 pub struct WebGlVertexArray {
     context: web_sys::WebGl2RenderingContext,
+    // The extension that created the vertex array, on WebGL 1.
+    vertex_array_object: Option<OesVertexArrayObject>,
     pub gl_vertex_array: web_sys::WebGlVertexArrayObject,
 }
 
 impl Drop for WebGlVertexArray {
     #[inline]
     fn drop(&mut self) {
-        self.context
-            .delete_vertex_array(Some(&self.gl_vertex_array));
+        match self.vertex_array_object {
+            Some(ref vertex_array_object) => {
+                vertex_array_object.delete_vertex_array_oes(Some(&self.gl_vertex_array))
+            }
+            None => self
+                .context
+                .delete_vertex_array(Some(&self.gl_vertex_array)),
+        }
     }
 }
 
@@ -1103,7 +1343,31 @@ impl Drop for WebGlTexture {
     }
 }
 
-pub struct WebGlTimerQuery {}
+pub struct WebGlTimerQuery {
+    context: web_sys::WebGl2RenderingContext,
+    gl_query: Option<web_sys::WebGlQuery>,
+}
+
+impl Drop for WebGlTimerQuery {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(ref gl_query) = self.gl_query {
+            self.context.delete_query(Some(gl_query));
+        }
+    }
+}
+
+impl Display for MissingExtension {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "the WebGL extension `{}` isn't available",
+            self.0
+        )
+    }
+}
+
+impl Error for MissingExtension {}
 
 trait BufferTargetExt {
     fn to_gl_target(self) -> u32;
@@ -1140,6 +1404,7 @@ impl DepthFuncExt for DepthFunc {
     fn to_gl_depth_func(self) -> u32 {
         match self {
             DepthFunc::Less => WebGl::LESS,
+            DepthFunc::LessEqual => WebGl::LEQUAL,
             DepthFunc::Always => WebGl::ALWAYS,
         }
     }
@@ -1172,13 +1437,17 @@ impl StencilFuncExt for StencilFunc {
 }
 
 trait TextureFormatExt {
-    fn gl_internal_format(self) -> u32;
-    fn gl_format(self) -> u32;
-    fn gl_type(self) -> u32;
+    fn gl_internal_format(self, webgl1: bool) -> u32;
+    fn gl_format(self, webgl1: bool) -> u32;
+    fn gl_type(self, webgl1: bool) -> u32;
 }
 
 impl TextureFormatExt for TextureFormat {
-    fn gl_internal_format(self) -> u32 {
+    fn gl_internal_format(self, webgl1: bool) -> u32 {
+        // WebGL 1 has no sized internal formats.
+        if webgl1 {
+            return self.gl_format(webgl1);
+        }
         match self {
             TextureFormat::R8 => WebGl::R8,
             TextureFormat::R16F => WebGl::R16F,
@@ -1188,16 +1457,18 @@ impl TextureFormatExt for TextureFormat {
         }
     }
 
-    fn gl_format(self) -> u32 {
+    fn gl_format(self, webgl1: bool) -> u32 {
         match self {
+            TextureFormat::R8 | TextureFormat::R16F if webgl1 => LUMINANCE,
             TextureFormat::R8 | TextureFormat::R16F => WebGl::RED,
             TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => WebGl::RGBA,
         }
     }
 
-    fn gl_type(self) -> u32 {
+    fn gl_type(self, webgl1: bool) -> u32 {
         match self {
             TextureFormat::R8 | TextureFormat::RGBA8 => WebGl::UNSIGNED_BYTE,
+            TextureFormat::R16F | TextureFormat::RGBA16F if webgl1 => HALF_FLOAT_OES,
             TextureFormat::R16F | TextureFormat::RGBA16F => WebGl::HALF_FLOAT,
             TextureFormat::RGBA32F => WebGl::FLOAT,
         }
@@ -0,0 +1,1950 @@
+// pathfinder/webgpu/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A WebGPU implementation of the device abstraction, for the browser.
+//!
+//! Shaders are loaded as SPIR-V from `shaders/spirv`, reflected with naga, and translated to WGSL
+//! at load time.
+//!
+//! The WebGPU bindings in `web-sys` are unstable, so build with
+//! `RUSTFLAGS=--cfg=web_sys_unstable_apis`.
+
+#[macro_use]
+extern crate log;
+
+use half::f16;
+use js_sys::{Array, Promise, Uint8Array};
+use naga::front::spv::{self as spv_frontend};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::{AddressSpace, Binding, ImageClass, ResourceBinding, ScalarKind, StorageAccess};
+use naga::{StorageFormat, TypeInner};
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_gpu::{BlendFactor, BlendOp, BlendState, BufferData, BufferTarget};
+use pathfinder_gpu::{BufferUploadMode, ComputeDimensions, ComputeState, DepthFunc, Device};
+use pathfinder_gpu::{DeviceCapabilities, DeviceError};
+use pathfinder_gpu::{FeatureLevel, ImageBinding, Primitive, ProgramKind, RenderState};
+use pathfinder_gpu::{RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
+use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformBinding};
+use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_resources::ResourceLoader;
+use pathfinder_simd::default::{F32x2, F32x4, I32x2};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+use std::slice;
+use std::time::Duration;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{gpu_buffer_usage, gpu_color_write, gpu_map_mode, gpu_shader_stage};
+use web_sys::{gpu_texture_usage, GpuDevice, GpuTexture, GpuTextureFormat};
+
+const DEPTH_STENCIL_FORMAT: GpuTextureFormat = GpuTextureFormat::Depth24plusStencil8;
+
+// Vertex and compute shaders use the first bind group; fragment shaders use the second.
+const VERTEX_BIND_GROUP: u32 = 0;
+const FRAGMENT_BIND_GROUP: u32 = 1;
+
+const BUFFER_USAGES: u32 = gpu_buffer_usage::VERTEX
+    | gpu_buffer_usage::INDEX
+    | gpu_buffer_usage::STORAGE
+    | gpu_buffer_usage::COPY_SRC
+    | gpu_buffer_usage::COPY_DST;
+
+// WebGPU requires buffer copies and writes to be aligned to 4 bytes, and rows copied out of a
+// texture to be aligned to 256 bytes.
+const COPY_BUFFER_ALIGNMENT: usize = 4;
+const COPY_BYTES_PER_ROW_ALIGNMENT: usize = 256;
+
+pub struct WebGpuDevice {
+    device: GpuDevice,
+    queue: web_sys::GpuQueue,
+    float32_filterable: bool,
+    main_color_texture: WebGpuTexture,
+    main_depth_stencil_texture: WebGpuTexture,
+    samplers: Vec<web_sys::GpuSampler>,
+    dummy_texture: WebGpuTexture,
+    command_encoder: RefCell<Option<web_sys::GpuCommandEncoder>>,
+    debug_groups: RefCell<Vec<String>>,
+}
+
+pub struct WebGpuProgram {
+    shaders: ProgramKind<WebGpuShader>,
+    bind_group_layouts: Vec<web_sys::GpuBindGroupLayout>,
+    pipeline_layout: web_sys::GpuPipelineLayout,
+    compute_pipeline: Option<web_sys::GpuComputePipeline>,
+    // The `web_sys` enums in the keys can't be hashed, so pipelines are found by linear search;
+    // a program only ever has a handful.
+    render_pipelines: RefCell<Vec<(RenderPipelineKey, web_sys::GpuRenderPipeline)>>,
+}
+
+pub struct WebGpuShader {
+    module: web_sys::GpuShaderModule,
+    reflection: ShaderReflection,
+}
+
+pub struct WebGpuTexture {
+    texture: GpuTexture,
+    view: web_sys::GpuTextureView,
+    format: GpuTextureFormat,
+    size: Vector2I,
+    sampling_flags: Cell<TextureSamplingFlags>,
+}
+
+pub struct WebGpuFramebuffer(WebGpuTexture);
+
+#[derive(Clone)]
+pub struct WebGpuBuffer {
+    allocation: Rc<RefCell<Option<web_sys::GpuBuffer>>>,
+}
+
+pub struct WebGpuVertexArray {
+    vertex_buffers: RefCell<Vec<WebGpuBuffer>>,
+    index_buffer: RefCell<Option<WebGpuBuffer>>,
+    attributes: RefCell<Vec<WebGpuVertexAttrInfo>>,
+}
+
+pub struct WebGpuVertexAttr {
+    location: u32,
+}
+
+pub struct WebGpuUniform {
+    name: String,
+}
+
+pub struct WebGpuTextureParameter {
+    name: String,
+}
+
+pub struct WebGpuImageParameter {
+    name: String,
+}
+
+pub struct WebGpuStorageBuffer {
+    name: String,
+}
+
+pub struct WebGpuFence(Rc<Cell<bool>>);
+
+pub struct WebGpuTimerQuery(Rc<RefCell<WebGpuTimerQueryTimes>>);
+
+pub struct WebGpuTextureDataReceiver {
+    buffer: web_sys::GpuBuffer,
+    mapped: Rc<Cell<bool>>,
+    format: TextureFormat,
+    swizzle: bool,
+    size: Vector2I,
+    padded_row_size: usize,
+    data: RefCell<Option<TextureData>>,
+}
+
+pub struct WebGpuBufferDataReceiver {
+    buffer: web_sys::GpuBuffer,
+    mapped: Rc<Cell<bool>>,
+    size: usize,
+    data: RefCell<Option<Vec<u8>>>,
+}
+
+// Times in milliseconds, as returned by `Date.now()`.
+#[derive(Default)]
+struct WebGpuTimerQueryTimes {
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+}
+
+struct WebGpuVertexAttrInfo {
+    buffer_index: u32,
+    stride: u32,
+    instanced: bool,
+    attribute: VertexAttributeKey,
+}
+
+#[derive(Default)]
+struct ShaderReflection {
+    layout_entries: Vec<web_sys::GpuBindGroupLayoutEntry>,
+    uniform_block: Option<UniformBlockInfo>,
+    textures: HashMap<String, TextureInfo>,
+    images: HashMap<String, u32>,
+    storage_buffers: HashMap<String, u32>,
+    vertex_inputs: HashMap<String, u32>,
+}
+
+struct UniformBlockInfo {
+    binding: u32,
+    size: usize,
+    offsets: HashMap<String, usize>,
+}
+
+struct TextureInfo {
+    binding: u32,
+    sampler_binding: Option<u32>,
+}
+
+#[derive(Clone, PartialEq)]
+struct RenderPipelineKey {
+    vertex_buffers: Vec<VertexBufferLayoutKey>,
+    topology: web_sys::GpuPrimitiveTopology,
+    color_format: GpuTextureFormat,
+    blend: Option<BlendStateKey>,
+    color_mask: bool,
+    depth: Option<(web_sys::GpuCompareFunction, bool)>,
+    stencil: Option<(web_sys::GpuCompareFunction, u32, bool)>,
+    has_depth_stencil_attachment: bool,
+}
+
+#[derive(Clone, PartialEq)]
+struct VertexBufferLayoutKey {
+    stride: u32,
+    instanced: bool,
+    attributes: Vec<VertexAttributeKey>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct VertexAttributeKey {
+    format: web_sys::GpuVertexFormat,
+    offset: u32,
+    location: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct BlendStateKey {
+    src_rgb_factor: web_sys::GpuBlendFactor,
+    dest_rgb_factor: web_sys::GpuBlendFactor,
+    src_alpha_factor: web_sys::GpuBlendFactor,
+    dest_alpha_factor: web_sys::GpuBlendFactor,
+    op: web_sys::GpuBlendOperation,
+}
+
+impl WebGpuDevice {
+    /// Creates a device that renders to the given texture by default.
+    ///
+    /// To render to a canvas, configure its `GPUCanvasContext` with `RENDER_ATTACHMENT` and
+    /// `COPY_SRC` usage, pass its current texture here, and call `swap_texture()` with the new
+    /// current texture at the start of every frame.
+    ///
+    /// Pathfinder takes advantage of the `float32-filterable` feature if the device was created
+    /// with it.
+    pub fn new(device: GpuDevice, texture: GpuTexture) -> WebGpuDevice {
+        let queue = device.queue();
+
+        // Without filterable floating-point textures, the bind group layouts have to declare all
+        // textures as unfilterable, so fall back to nearest-neighbor sampling everywhere.
+        let float32_filterable = device.features().has("float32-filterable");
+        if !float32_filterable {
+            warn!("Float textures aren't filterable on this device; using nearest sampling.");
+        }
+
+        let samplers = (0..16)
+            .map(|sampling_flags_value| {
+                let sampling_flags = TextureSamplingFlags::from_bits(sampling_flags_value).unwrap();
+                let address_mode = |repeat| {
+                    if repeat {
+                        web_sys::GpuAddressMode::Repeat
+                    } else {
+                        web_sys::GpuAddressMode::ClampToEdge
+                    }
+                };
+                let filter_mode = |nearest| {
+                    if !float32_filterable || nearest {
+                        web_sys::GpuFilterMode::Nearest
+                    } else {
+                        web_sys::GpuFilterMode::Linear
+                    }
+                };
+                let mut descriptor = web_sys::GpuSamplerDescriptor::new();
+                descriptor
+                    .address_mode_u(address_mode(
+                        sampling_flags.contains(TextureSamplingFlags::REPEAT_U),
+                    ))
+                    .address_mode_v(address_mode(
+                        sampling_flags.contains(TextureSamplingFlags::REPEAT_V),
+                    ))
+                    .mag_filter(filter_mode(
+                        sampling_flags.contains(TextureSamplingFlags::NEAREST_MAG),
+                    ))
+                    .min_filter(filter_mode(
+                        sampling_flags.contains(TextureSamplingFlags::NEAREST_MIN),
+                    ));
+                device.create_sampler_with_descriptor(&descriptor)
+            })
+            .collect();
+
+        let main_color_texture = WebGpuTexture::new(texture);
+        let main_depth_stencil_texture =
+            create_depth_stencil_texture(&device, main_color_texture.size);
+        let dummy_texture =
+            WebGpuTexture::new(device.create_texture(&web_sys::GpuTextureDescriptor::new(
+                GpuTextureFormat::Rgba8unorm,
+                &extent_from_size(vec2i(1, 1)),
+                gpu_texture_usage::TEXTURE_BINDING,
+            )));
+
+        WebGpuDevice {
+            device,
+            queue,
+            float32_filterable,
+            main_color_texture,
+            main_depth_stencil_texture,
+            samplers,
+            dummy_texture,
+            command_encoder: RefCell::new(None),
+            debug_groups: RefCell::new(vec![]),
+        }
+    }
+
+    #[inline]
+    pub fn main_texture(&self) -> &GpuTexture {
+        &self.main_color_texture.texture
+    }
+
+    /// Replaces the texture that the device renders to by default, returning the old one.
+    pub fn swap_texture(&mut self, new_texture: GpuTexture) -> GpuTexture {
+        self.flush();
+        let new_texture = WebGpuTexture::new(new_texture);
+        if new_texture.size != self.main_color_texture.size {
+            self.main_depth_stencil_texture =
+                create_depth_stencil_texture(&self.device, new_texture.size);
+        }
+        mem::replace(&mut self.main_color_texture, new_texture).texture
+    }
+
+    #[inline]
+    pub fn gpu_device(&self) -> &GpuDevice {
+        &self.device
+    }
+
+    fn with_command_encoder<F, R>(&self, callback: F) -> R
+    where
+        F: FnOnce(&web_sys::GpuCommandEncoder) -> R,
+    {
+        let mut command_encoder = self.command_encoder.borrow_mut();
+        let command_encoder = command_encoder.get_or_insert_with(|| {
+            let command_encoder = self.device.create_command_encoder();
+            // Debug groups can't span command encoders, so reopen the ones that are still open.
+            for name in self.debug_groups.borrow().iter() {
+                command_encoder.push_debug_group(name);
+            }
+            command_encoder
+        });
+        callback(command_encoder)
+    }
+
+    // Submits all recorded commands. This must happen before anything is written through the
+    // queue so that the write lands after the commands that were recorded before it.
+    fn flush(&self) {
+        if let Some(command_encoder) = self.command_encoder.borrow_mut().take() {
+            for _ in 0..self.debug_groups.borrow().len() {
+                command_encoder.pop_debug_group();
+            }
+            let command_buffers: Array = [command_encoder.finish()].iter().collect();
+            self.queue.submit(&command_buffers);
+        }
+    }
+
+    fn render_target_color_texture<'a>(
+        &'a self,
+        target: &RenderTarget<'a, WebGpuDevice>,
+    ) -> &'a WebGpuTexture {
+        match *target {
+            RenderTarget::Default => &self.main_color_texture,
+            RenderTarget::Framebuffer(framebuffer) => &framebuffer.0,
+        }
+    }
+
+    fn render_target_depth_stencil_view<'a>(
+        &'a self,
+        target: &RenderTarget<'a, WebGpuDevice>,
+    ) -> Option<&'a web_sys::GpuTextureView> {
+        match *target {
+            RenderTarget::Default => Some(&self.main_depth_stencil_texture.view),
+            RenderTarget::Framebuffer(_) => None,
+        }
+    }
+
+    fn create_shader_from_wgsl(
+        &self,
+        name: &str,
+        wgsl: &str,
+        reflection: ShaderReflection,
+    ) -> WebGpuShader {
+        let mut descriptor = web_sys::GpuShaderModuleDescriptor::new(wgsl);
+        descriptor.label(name);
+        let module = self.device.create_shader_module(&descriptor);
+        WebGpuShader { module, reflection }
+    }
+
+    fn create_bind_group_layout(
+        &self,
+        name: &str,
+        shader: &WebGpuShader,
+    ) -> web_sys::GpuBindGroupLayout {
+        let entries: Array = shader.reflection.layout_entries.iter().collect();
+        let mut descriptor = web_sys::GpuBindGroupLayoutDescriptor::new(&entries);
+        descriptor.label(name);
+        self.device.create_bind_group_layout(&descriptor)
+    }
+
+    fn render_pipeline(
+        &self,
+        render_state: &RenderState<WebGpuDevice>,
+    ) -> web_sys::GpuRenderPipeline {
+        let program = render_state.program;
+        let (vertex_shader, fragment_shader) = match program.shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => (vertex, fragment),
+            ProgramKind::Compute(_) => panic!("Can't draw with a compute program!"),
+        };
+
+        let options = &render_state.options;
+        let key = RenderPipelineKey {
+            vertex_buffers: render_state.vertex_array.buffer_layouts(),
+            topology: render_state.primitive.to_gpu_primitive_topology(),
+            color_format: self.render_target_color_texture(render_state.target).format,
+            blend: options.blend.map(|blend| blend.to_blend_state_key()),
+            color_mask: options.color_mask,
+            depth: options
+                .depth
+                .map(|depth| (depth.func.to_gpu_compare_function(), depth.write)),
+            stencil: options.stencil.map(|stencil| {
+                (
+                    stencil.func.to_gpu_compare_function(),
+                    stencil.mask,
+                    stencil.write,
+                )
+            }),
+            has_depth_stencil_attachment: self
+                .render_target_depth_stencil_view(render_state.target)
+                .is_some(),
+        };
+
+        if let Some((_, pipeline)) = program
+            .render_pipelines
+            .borrow()
+            .iter()
+            .find(|(existing_key, _)| *existing_key == key)
+        {
+            return pipeline.clone();
+        }
+
+        let vertex_buffers: Array = key
+            .vertex_buffers
+            .iter()
+            .map(|layout| {
+                let attributes: Array = layout
+                    .attributes
+                    .iter()
+                    .map(|attribute| {
+                        web_sys::GpuVertexAttribute::new(
+                            attribute.format,
+                            attribute.offset as f64,
+                            attribute.location,
+                        )
+                    })
+                    .collect();
+                let mut buffer_layout =
+                    web_sys::GpuVertexBufferLayout::new(layout.stride as f64, &attributes);
+                if layout.instanced {
+                    buffer_layout.step_mode(web_sys::GpuVertexStepMode::Instance);
+                }
+                buffer_layout
+            })
+            .collect();
+
+        let mut vertex_state = web_sys::GpuVertexState::new(&vertex_shader.module);
+        vertex_state.entry_point("main").buffers(&vertex_buffers);
+
+        let mut color_target = web_sys::GpuColorTargetState::new(key.color_format);
+        color_target.write_mask(if key.color_mask {
+            gpu_color_write::ALL
+        } else {
+            0
+        });
+        if let Some(blend) = key.blend {
+            color_target.blend(&blend.to_gpu_blend_state());
+        }
+        let color_targets: Array = [color_target].iter().collect();
+        let mut fragment_state =
+            web_sys::GpuFragmentState::new(&fragment_shader.module, &color_targets);
+        fragment_state.entry_point("main");
+
+        let mut primitive_state = web_sys::GpuPrimitiveState::new();
+        primitive_state.topology(key.topology);
+
+        let mut descriptor =
+            web_sys::GpuRenderPipelineDescriptor::new(&program.pipeline_layout, &vertex_state);
+        descriptor
+            .fragment(&fragment_state)
+            .primitive(&primitive_state);
+
+        if key.has_depth_stencil_attachment {
+            let (depth_compare, depth_write_enabled) = match key.depth {
+                Some((func, write)) => (func, write),
+                None => (web_sys::GpuCompareFunction::Always, false),
+            };
+            let mut depth_stencil = web_sys::GpuDepthStencilState::new(DEPTH_STENCIL_FORMAT);
+            depth_stencil
+                .depth_compare(depth_compare)
+                .depth_write_enabled(depth_write_enabled);
+            if let Some((func, mask, write)) = key.stencil {
+                let mut face = web_sys::GpuStencilFaceState::new();
+                face.compare(func)
+                    .fail_op(web_sys::GpuStencilOperation::Keep)
+                    .depth_fail_op(web_sys::GpuStencilOperation::Keep)
+                    .pass_op(if write {
+                        web_sys::GpuStencilOperation::Replace
+                    } else {
+                        web_sys::GpuStencilOperation::Keep
+                    });
+                depth_stencil
+                    .stencil_front(&face)
+                    .stencil_back(&face)
+                    .stencil_read_mask(mask)
+                    .stencil_write_mask(if write { mask } else { 0 });
+            }
+            descriptor.depth_stencil(&depth_stencil);
+        }
+
+        let pipeline = self.device.create_render_pipeline(&descriptor);
+        program
+            .render_pipelines
+            .borrow_mut()
+            .push((key, pipeline.clone()));
+        pipeline
+    }
+
+    fn create_bind_groups(
+        &self,
+        program: &WebGpuProgram,
+        uniforms: &[UniformBinding<WebGpuUniform>],
+        textures: &[TextureBinding<WebGpuTextureParameter, WebGpuTexture>],
+        images: &[ImageBinding<WebGpuImageParameter, WebGpuTexture>],
+        storage_buffers: &[(&WebGpuStorageBuffer, &WebGpuBuffer)],
+    ) -> Vec<web_sys::GpuBindGroup> {
+        program
+            .shaders()
+            .iter()
+            .zip(program.bind_group_layouts.iter())
+            .map(|(shader, bind_group_layout)| {
+                let reflection = &shader.reflection;
+                let entries = Array::new();
+
+                if let Some(ref uniform_block) = reflection.uniform_block {
+                    let uniform_buffer = self.create_uniform_buffer(uniform_block, uniforms);
+                    entries.push(&web_sys::GpuBindGroupEntry::new(
+                        uniform_block.binding,
+                        &web_sys::GpuBufferBinding::new(&uniform_buffer),
+                    ));
+                }
+
+                for (name, texture_info) in &reflection.textures {
+                    // Textures that the renderer doesn't use for this draw are left unbound in
+                    // OpenGL, but WebGPU needs something in every slot.
+                    let texture = textures
+                        .iter()
+                        .find(|(texture_parameter, _)| texture_parameter.name == *name)
+                        .map(|&(_, texture)| texture)
+                        .unwrap_or(&self.dummy_texture);
+                    entries.push(&web_sys::GpuBindGroupEntry::new(
+                        texture_info.binding,
+                        &texture.view,
+                    ));
+                    if let Some(sampler_binding) = texture_info.sampler_binding {
+                        let sampler_index = texture.sampling_flags.get().bits() as usize;
+                        entries.push(&web_sys::GpuBindGroupEntry::new(
+                            sampler_binding,
+                            &self.samplers[sampler_index],
+                        ));
+                    }
+                }
+
+                for (name, &binding) in &reflection.images {
+                    let texture = images
+                        .iter()
+                        .find(|(image_parameter, _, _)| image_parameter.name == *name)
+                        .map(|&(_, texture, _)| texture)
+                        .unwrap_or_else(|| panic!("No image bound to `{}`!", name));
+                    entries.push(&web_sys::GpuBindGroupEntry::new(binding, &texture.view));
+                }
+
+                for (name, &binding) in &reflection.storage_buffers {
+                    let buffer = storage_buffers
+                        .iter()
+                        .find(|(storage_buffer, _)| storage_buffer.name == *name)
+                        .map(|&(_, buffer)| buffer)
+                        .unwrap_or_else(|| panic!("No buffer bound to `{}`!", name));
+                    entries.push(&web_sys::GpuBindGroupEntry::new(
+                        binding,
+                        &web_sys::GpuBufferBinding::new(&buffer.allocated()),
+                    ));
+                }
+
+                self.device
+                    .create_bind_group(&web_sys::GpuBindGroupDescriptor::new(
+                        &entries,
+                        bind_group_layout,
+                    ))
+            })
+            .collect()
+    }
+
+    fn create_uniform_buffer(
+        &self,
+        uniform_block: &UniformBlockInfo,
+        uniforms: &[UniformBinding<WebGpuUniform>],
+    ) -> web_sys::GpuBuffer {
+        let mut data = vec![0; align_buffer_size(uniform_block.size)];
+        for (uniform, uniform_data) in uniforms {
+            let offset = match uniform_block.offsets.get(&uniform.name) {
+                None => continue,
+                Some(&offset) => offset,
+            };
+            let bytes = uniform_data.as_bytes();
+            match *uniform_data {
+                UniformData::Mat2(_) => {
+                    // Under std140 rules, each column of a `mat2` is aligned to 16 bytes.
+                    data[offset..(offset + 8)].copy_from_slice(&bytes[0..8]);
+                    data[(offset + 16)..(offset + 24)].copy_from_slice(&bytes[8..16]);
+                }
+                _ => data[offset..(offset + bytes.len())].copy_from_slice(bytes),
+            }
+        }
+
+        // The buffer is written before the commands that use it are submitted, so there's no need
+        // to flush first.
+        let buffer = self
+            .device
+            .create_buffer(&web_sys::GpuBufferDescriptor::new(
+                data.len() as f64,
+                gpu_buffer_usage::UNIFORM | gpu_buffer_usage::COPY_DST,
+            ));
+        self.queue
+            .write_buffer_with_u32_and_u8_array(&buffer, 0, &data);
+        buffer
+    }
+
+    fn draw<F>(&self, render_state: &RenderState<WebGpuDevice>, draw: F)
+    where
+        F: FnOnce(&web_sys::GpuRenderPassEncoder),
+    {
+        let pipeline = self.render_pipeline(render_state);
+        let bind_groups = self.create_bind_groups(
+            render_state.program,
+            render_state.uniforms,
+            render_state.textures,
+            render_state.images,
+            render_state.storage_buffers,
+        );
+
+        let clear_ops = &render_state.options.clear_ops;
+        let color_view = &self.render_target_color_texture(render_state.target).view;
+        let mut color_attachment = web_sys::GpuRenderPassColorAttachment::new(
+            if clear_ops.color.is_some() {
+                web_sys::GpuLoadOp::Clear
+            } else {
+                web_sys::GpuLoadOp::Load
+            },
+            web_sys::GpuStoreOp::Store,
+            color_view,
+        );
+        if let Some(color) = clear_ops.color {
+            color_attachment.clear_value(&web_sys::GpuColorDict::new(
+                color.a() as f64,
+                color.b() as f64,
+                color.g() as f64,
+                color.r() as f64,
+            ));
+        }
+        let color_attachments: Array = [color_attachment].iter().collect();
+        let mut descriptor = web_sys::GpuRenderPassDescriptor::new(&color_attachments);
+
+        if let Some(view) = self.render_target_depth_stencil_view(render_state.target) {
+            let mut depth_stencil_attachment =
+                web_sys::GpuRenderPassDepthStencilAttachment::new(view);
+            depth_stencil_attachment
+                .depth_load_op(load_op(clear_ops.depth.is_some()))
+                .depth_store_op(web_sys::GpuStoreOp::Store)
+                .stencil_load_op(load_op(clear_ops.stencil.is_some()))
+                .stencil_store_op(web_sys::GpuStoreOp::Store);
+            if let Some(depth) = clear_ops.depth {
+                depth_stencil_attachment.depth_clear_value(depth);
+            }
+            if let Some(stencil) = clear_ops.stencil {
+                depth_stencil_attachment.stencil_clear_value(stencil as u32);
+            }
+            descriptor.depth_stencil_attachment(&depth_stencil_attachment);
+        }
+
+        let vertex_array = render_state.vertex_array;
+        self.with_command_encoder(|command_encoder| {
+            let render_pass = command_encoder.begin_render_pass(&descriptor);
+
+            let viewport = render_state.viewport;
+            render_pass.set_viewport(
+                viewport.origin().x() as f32,
+                viewport.origin().y() as f32,
+                viewport.width() as f32,
+                viewport.height() as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_pipeline(&pipeline);
+            for (group_index, bind_group) in bind_groups.iter().enumerate() {
+                render_pass.set_bind_group(group_index as u32, Some(bind_group));
+            }
+            for (buffer_index, vertex_buffer) in
+                vertex_array.vertex_buffers.borrow().iter().enumerate()
+            {
+                render_pass
+                    .set_vertex_buffer(buffer_index as u32, Some(&vertex_buffer.allocated()));
+            }
+            if let Some(ref index_buffer) = *vertex_array.index_buffer.borrow() {
+                render_pass
+                    .set_index_buffer(&index_buffer.allocated(), web_sys::GpuIndexFormat::Uint32);
+            }
+            if let Some(stencil) = render_state.options.stencil {
+                render_pass.set_stencil_reference(stencil.reference);
+            }
+
+            draw(&render_pass);
+            render_pass.end();
+        });
+    }
+
+    fn create_readback_buffer(&self, size: usize) -> web_sys::GpuBuffer {
+        self.device
+            .create_buffer(&web_sys::GpuBufferDescriptor::new(
+                size as f64,
+                gpu_buffer_usage::COPY_DST | gpu_buffer_usage::MAP_READ,
+            ))
+    }
+}
+
+impl Device for WebGpuDevice {
+    type Buffer = WebGpuBuffer;
+    type BufferDataReceiver = WebGpuBufferDataReceiver;
+    type Fence = WebGpuFence;
+    type Framebuffer = WebGpuFramebuffer;
+    type ImageParameter = WebGpuImageParameter;
+    type Program = WebGpuProgram;
+    type Shader = WebGpuShader;
+    type StorageBuffer = WebGpuStorageBuffer;
+    type Texture = WebGpuTexture;
+    type TextureDataReceiver = WebGpuTextureDataReceiver;
+    type TextureParameter = WebGpuTextureParameter;
+    type TimerQuery = WebGpuTimerQuery;
+    type Uniform = WebGpuUniform;
+    type VertexArray = WebGpuVertexArray;
+    type VertexAttr = WebGpuVertexAttr;
+
+    #[inline]
+    fn backend_name(&self) -> &'static str {
+        "WebGPU"
+    }
+
+    // Browsers don't reveal the adapter name synchronously.
+    #[inline]
+    fn device_name(&self) -> String {
+        String::new()
+    }
+
+    // Compute shaders work, but the D3D11 level reads indirect draw parameters back from the GPU
+    // and blocks until they arrive, which a browser can't do. So report the D3D10 level to make
+    // the renderer pick the raster pipeline by default.
+    #[inline]
+    fn feature_level(&self) -> FeatureLevel {
+        FeatureLevel::D3D10
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        DeviceCapabilities {
+            max_texture_size: self.device.limits().max_texture_dimension_2d(),
+            // Compute shaders exist, but this backend doesn't run the D3D11 level yet.
+            compute_shaders: false,
+            // naga's SPIR-V frontend drops the `Index` decoration on the second blend source, so
+            // subpixel text uses the two-pass fallback.
+            dual_source_blending: false,
+            // WebGPU guarantees 4x multisampling for renderable formats, and no more.
+            max_msaa_samples: 4,
+        }
+    }
+
+    fn push_debug_group(&self, name: &str) {
+        if let Some(ref command_encoder) = *self.command_encoder.borrow() {
+            command_encoder.push_debug_group(name);
+        }
+        self.debug_groups.borrow_mut().push(name.to_owned());
+    }
+
+    fn pop_debug_group(&self) {
+        if self.debug_groups.borrow_mut().pop().is_none() {
+            return;
+        }
+        if let Some(ref command_encoder) = *self.command_encoder.borrow() {
+            command_encoder.pop_debug_group();
+        }
+    }
+
+    fn set_texture_label(&self, texture: &WebGpuTexture, label: &str) {
+        texture.texture.set_label(label);
+    }
+
+    fn set_buffer_label(&self, buffer: &WebGpuBuffer, label: &str) {
+        if let Some(ref allocation) = *buffer.allocation.borrow() {
+            allocation.set_label(label);
+        }
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> WebGpuTexture {
+        let mut usage = gpu_texture_usage::TEXTURE_BINDING
+            | gpu_texture_usage::COPY_SRC
+            | gpu_texture_usage::COPY_DST
+            | gpu_texture_usage::RENDER_ATTACHMENT;
+        match format {
+            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => {
+                usage |= gpu_texture_usage::STORAGE_BINDING
+            }
+            TextureFormat::R8 | TextureFormat::R16F => {}
+        }
+
+        WebGpuTexture::new(
+            self.device
+                .create_texture(&web_sys::GpuTextureDescriptor::new(
+                    format.to_gpu_texture_format(),
+                    &extent_from_size(size),
+                    usage,
+                )),
+        )
+    }
+
+    fn create_texture_from_data(
+        &self,
+        format: TextureFormat,
+        size: Vector2I,
+        data: TextureDataRef,
+    ) -> WebGpuTexture {
+        let texture = self.create_texture(format, size);
+        self.upload_to_texture(&texture, RectI::new(Vector2I::default(), size), data);
+        texture
+    }
+
+    fn create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> WebGpuShader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<WebGpuShader, DeviceError> {
+        let (wgsl, reflection) = load_shader(resources, name, kind, self.float32_filterable)?;
+        Ok(self.create_shader_from_wgsl(name, &wgsl, reflection))
+    }
+
+    fn create_shader_from_source(
+        &self,
+        name: &str,
+        source: &[u8],
+        kind: ShaderKind,
+    ) -> WebGpuShader {
+        let (wgsl, reflection) = translate_shader(name, source, kind, self.float32_filterable)
+            .unwrap_or_else(|error| panic!("{}", error));
+        self.create_shader_from_wgsl(name, &wgsl, reflection)
+    }
+
+    fn create_vertex_array(&self) -> WebGpuVertexArray {
+        WebGpuVertexArray {
+            vertex_buffers: RefCell::new(vec![]),
+            index_buffer: RefCell::new(None),
+            attributes: RefCell::new(vec![]),
+        }
+    }
+
+    fn bind_buffer(
+        &self,
+        vertex_array: &WebGpuVertexArray,
+        buffer: &WebGpuBuffer,
+        target: BufferTarget,
+    ) {
+        match target {
+            BufferTarget::Vertex => vertex_array
+                .vertex_buffers
+                .borrow_mut()
+                .push((*buffer).clone()),
+            BufferTarget::Index => {
+                *vertex_array.index_buffer.borrow_mut() = Some((*buffer).clone())
+            }
+            _ => panic!("Buffers bound to vertex arrays must be vertex or index buffers!"),
+        }
+    }
+
+    fn create_program_from_shaders(
+        &self,
+        _: &dyn ResourceLoader,
+        name: &str,
+        shaders: ProgramKind<WebGpuShader>,
+    ) -> WebGpuProgram {
+        let bind_group_layouts: Vec<_> = match shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => vec![
+                self.create_bind_group_layout(name, vertex),
+                self.create_bind_group_layout(name, fragment),
+            ],
+            ProgramKind::Compute(ref compute) => {
+                vec![self.create_bind_group_layout(name, compute)]
+            }
+        };
+        let bind_group_layout_array: Array = bind_group_layouts.iter().collect();
+        let mut pipeline_layout_descriptor =
+            web_sys::GpuPipelineLayoutDescriptor::new(&bind_group_layout_array);
+        pipeline_layout_descriptor.label(name);
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&pipeline_layout_descriptor);
+
+        let compute_pipeline = match shaders {
+            ProgramKind::Compute(ref compute) => {
+                let mut stage = web_sys::GpuProgrammableStage::new(&compute.module);
+                stage.entry_point("main");
+                let mut descriptor =
+                    web_sys::GpuComputePipelineDescriptor::new(&pipeline_layout, &stage);
+                descriptor.label(name);
+                Some(self.device.create_compute_pipeline(&descriptor))
+            }
+            ProgramKind::Raster { .. } => None,
+        };
+
+        WebGpuProgram {
+            shaders,
+            bind_group_layouts,
+            pipeline_layout,
+            compute_pipeline,
+            render_pipelines: RefCell::new(vec![]),
+        }
+    }
+
+    // The local size is baked into the SPIR-V, so there's nothing to do here.
+    fn set_compute_program_local_size(&self, _: &mut WebGpuProgram, _: ComputeDimensions) {}
+
+    fn get_vertex_attr(&self, program: &WebGpuProgram, name: &str) -> Option<WebGpuVertexAttr> {
+        let vertex_shader = match program.shaders {
+            ProgramKind::Raster { ref vertex, .. } => vertex,
+            ProgramKind::Compute(_) => unreachable!(),
+        };
+        vertex_shader
+            .reflection
+            .vertex_inputs
+            .get(&format!("a{}", name))
+            .map(|&location| WebGpuVertexAttr { location })
+    }
+
+    fn get_uniform(&self, _: &WebGpuProgram, name: &str) -> WebGpuUniform {
+        WebGpuUniform {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_texture_parameter(&self, _: &WebGpuProgram, name: &str) -> WebGpuTextureParameter {
+        WebGpuTextureParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_image_parameter(&self, _: &WebGpuProgram, name: &str) -> WebGpuImageParameter {
+        WebGpuImageParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_storage_buffer(&self, _: &WebGpuProgram, name: &str, _: u32) -> WebGpuStorageBuffer {
+        WebGpuStorageBuffer {
+            name: format!("b{}", name),
+        }
+    }
+
+    fn configure_vertex_attr(
+        &self,
+        vertex_array: &WebGpuVertexArray,
+        attr: &WebGpuVertexAttr,
+        descriptor: &VertexAttrDescriptor,
+    ) {
+        use web_sys::GpuVertexFormat;
+
+        debug_assert_ne!(descriptor.stride, 0);
+        debug_assert!(descriptor.divisor <= 1);
+
+        let format = match (descriptor.class, descriptor.attr_type, descriptor.size) {
+            (VertexAttrClass::Int, VertexAttrType::I8, 2) => GpuVertexFormat::Sint8x2,
+            (VertexAttrClass::Int, VertexAttrType::I8, 4) => GpuVertexFormat::Sint8x4,
+            (VertexAttrClass::Int, VertexAttrType::U8, 2) => GpuVertexFormat::Uint8x2,
+            (VertexAttrClass::Int, VertexAttrType::U8, 4) => GpuVertexFormat::Uint8x4,
+            // WebGPU has no single-component 16-bit formats, so these read the following
+            // component too. The shader only uses the first.
+            (VertexAttrClass::Int, VertexAttrType::I16, 1)
+            | (VertexAttrClass::Int, VertexAttrType::I16, 2) => GpuVertexFormat::Sint16x2,
+            (VertexAttrClass::Int, VertexAttrType::I16, 4) => GpuVertexFormat::Sint16x4,
+            (VertexAttrClass::Int, VertexAttrType::U16, 1)
+            | (VertexAttrClass::Int, VertexAttrType::U16, 2) => GpuVertexFormat::Uint16x2,
+            (VertexAttrClass::Int, VertexAttrType::U16, 4) => GpuVertexFormat::Uint16x4,
+            (VertexAttrClass::Int, VertexAttrType::I32, 1) => GpuVertexFormat::Sint32,
+            (VertexAttrClass::Int, VertexAttrType::I32, 2) => GpuVertexFormat::Sint32x2,
+            (VertexAttrClass::Int, VertexAttrType::I32, 3) => GpuVertexFormat::Sint32x3,
+            (VertexAttrClass::Int, VertexAttrType::I32, 4) => GpuVertexFormat::Sint32x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 2) => GpuVertexFormat::Unorm8x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 4) => GpuVertexFormat::Unorm8x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 2) => GpuVertexFormat::Snorm8x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 4) => GpuVertexFormat::Snorm8x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 2) => GpuVertexFormat::Unorm16x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 4) => GpuVertexFormat::Unorm16x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 2) => GpuVertexFormat::Snorm16x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 4) => GpuVertexFormat::Snorm16x4,
+            (VertexAttrClass::Float, VertexAttrType::F32, 1) => GpuVertexFormat::Float32,
+            (VertexAttrClass::Float, VertexAttrType::F32, 2) => GpuVertexFormat::Float32x2,
+            (VertexAttrClass::Float, VertexAttrType::F32, 3) => GpuVertexFormat::Float32x3,
+            (VertexAttrClass::Float, VertexAttrType::F32, 4) => GpuVertexFormat::Float32x4,
+            _ => panic!("Unsupported vertex class/type/size combination!"),
+        };
+
+        let mut attributes = vertex_array.attributes.borrow_mut();
+        attributes.retain(|info| info.attribute.location != attr.location);
+        attributes.push(WebGpuVertexAttrInfo {
+            buffer_index: descriptor.buffer_index,
+            stride: descriptor.stride as u32,
+            instanced: descriptor.divisor != 0,
+            attribute: VertexAttributeKey {
+                format,
+                offset: descriptor.offset as u32,
+                location: attr.location,
+            },
+        });
+    }
+
+    fn create_framebuffer(&self, texture: WebGpuTexture) -> WebGpuFramebuffer {
+        WebGpuFramebuffer(texture)
+    }
+
+    fn create_buffer(&self, _: BufferUploadMode) -> WebGpuBuffer {
+        WebGpuBuffer {
+            allocation: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn allocate_buffer<T>(&self, buffer: &WebGpuBuffer, data: BufferData<T>, target: BufferTarget) {
+        let byte_size = match data {
+            BufferData::Uninitialized(size) => size * mem::size_of::<T>(),
+            BufferData::Memory(slice) => slice.len() * mem::size_of::<T>(),
+        };
+
+        let new_buffer = self
+            .device
+            .create_buffer(&web_sys::GpuBufferDescriptor::new(
+                align_buffer_size(byte_size.max(1)) as f64,
+                BUFFER_USAGES,
+            ));
+        *buffer.allocation.borrow_mut() = Some(new_buffer);
+
+        if let BufferData::Memory(slice) = data {
+            self.upload_to_buffer(buffer, 0, slice, target);
+        }
+    }
+
+    fn upload_to_buffer<T>(
+        &self,
+        buffer: &WebGpuBuffer,
+        position: usize,
+        data: &[T],
+        _: BufferTarget,
+    ) {
+        let bytes = slice_as_bytes(data);
+        if bytes.is_empty() {
+            return;
+        }
+
+        self.flush();
+        let allocation = buffer.allocated();
+        if bytes.len() % COPY_BUFFER_ALIGNMENT == 0 {
+            self.queue
+                .write_buffer_with_u32_and_u8_array(&allocation, position as u32, bytes);
+        } else {
+            let mut padded_bytes = bytes.to_vec();
+            padded_bytes.resize(align_buffer_size(bytes.len()), 0);
+            self.queue.write_buffer_with_u32_and_u8_array(
+                &allocation,
+                position as u32,
+                &padded_bytes,
+            );
+        }
+    }
+
+    #[inline]
+    fn framebuffer_texture<'f>(&self, framebuffer: &'f WebGpuFramebuffer) -> &'f WebGpuTexture {
+        &framebuffer.0
+    }
+
+    #[inline]
+    fn destroy_framebuffer(&self, framebuffer: WebGpuFramebuffer) -> WebGpuTexture {
+        framebuffer.0
+    }
+
+    fn texture_format(&self, texture: &WebGpuTexture) -> TextureFormat {
+        TextureFormat::from_gpu_texture_format(texture.format).expect("Unexpected texture format!")
+    }
+
+    #[inline]
+    fn texture_size(&self, texture: &WebGpuTexture) -> Vector2I {
+        texture.size
+    }
+
+    #[inline]
+    fn set_texture_sampling_mode(&self, texture: &WebGpuTexture, flags: TextureSamplingFlags) {
+        texture.sampling_flags.set(flags);
+    }
+
+    fn upload_to_texture(&self, texture: &WebGpuTexture, rect: RectI, data: TextureDataRef) {
+        let format = self.texture_format(texture);
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let data_ptr = data.check_and_extract_data_ptr(rect.size(), format);
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                data_ptr as *const u8,
+                rect.area() as usize * bytes_per_pixel,
+            )
+        };
+
+        let mut destination = web_sys::GpuImageCopyTexture::new(&texture.texture);
+        destination.origin(&origin_from_point(rect.origin()));
+        let mut data_layout = web_sys::GpuImageDataLayout::new();
+        data_layout.bytes_per_row((rect.width() as usize * bytes_per_pixel) as u32);
+
+        self.flush();
+        self.queue.write_texture_with_u8_array_and_u32_sequence(
+            &destination,
+            bytes,
+            &data_layout,
+            &extent_from_size(rect.size()),
+        );
+    }
+
+    fn read_pixels(
+        &self,
+        target: &RenderTarget<WebGpuDevice>,
+        viewport: RectI,
+    ) -> WebGpuTextureDataReceiver {
+        let texture = self.render_target_color_texture(target);
+        let format =
+            TextureFormat::from_gpu_texture_format(texture.format).expect("Unexpected format!");
+
+        let row_size = viewport.width() as usize * format.bytes_per_pixel();
+        let padded_row_size = (row_size + COPY_BYTES_PER_ROW_ALIGNMENT - 1)
+            / COPY_BYTES_PER_ROW_ALIGNMENT
+            * COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.create_readback_buffer(padded_row_size * viewport.height() as usize);
+
+        let mut source = web_sys::GpuImageCopyTexture::new(&texture.texture);
+        source.origin(&origin_from_point(viewport.origin()));
+        let mut destination = web_sys::GpuImageCopyBuffer::new(&buffer);
+        destination
+            .bytes_per_row(padded_row_size as u32)
+            .rows_per_image(viewport.height() as u32);
+        self.with_command_encoder(|command_encoder| {
+            command_encoder.copy_texture_to_buffer_with_u32_sequence(
+                &source,
+                &destination,
+                &extent_from_size(viewport.size()),
+            );
+        });
+        self.flush();
+
+        let mapped = resolve_later(buffer.map_async(gpu_map_mode::READ));
+        WebGpuTextureDataReceiver {
+            buffer,
+            mapped,
+            format,
+            swizzle: match texture.format {
+                GpuTextureFormat::Bgra8unorm | GpuTextureFormat::Bgra8unormSrgb => true,
+                _ => false,
+            },
+            size: viewport.size(),
+            padded_row_size,
+            data: RefCell::new(None),
+        }
+    }
+
+    fn read_buffer(
+        &self,
+        buffer: &WebGpuBuffer,
+        _: BufferTarget,
+        range: Range<usize>,
+    ) -> WebGpuBufferDataReceiver {
+        let size = range.end - range.start;
+        let copy_size = align_buffer_size(size.max(1));
+        let readback_buffer = self.create_readback_buffer(copy_size);
+
+        let source_buffer = buffer.allocated();
+        self.with_command_encoder(|command_encoder| {
+            command_encoder.copy_buffer_to_buffer_with_u32_and_u32_and_u32(
+                &source_buffer,
+                range.start as u32,
+                &readback_buffer,
+                0,
+                copy_size as u32,
+            );
+        });
+        self.flush();
+
+        let mapped = resolve_later(readback_buffer.map_async(gpu_map_mode::READ));
+        WebGpuBufferDataReceiver {
+            buffer: readback_buffer,
+            mapped,
+            size,
+            data: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    fn begin_commands(&self) {
+        // Commands are recorded lazily, so nothing to do here.
+    }
+
+    fn end_commands(&self) {
+        self.flush();
+    }
+
+    fn draw_arrays(&self, index_count: u32, render_state: &RenderState<WebGpuDevice>) {
+        self.draw(render_state, |render_pass| render_pass.draw(index_count));
+    }
+
+    fn draw_elements(&self, index_count: u32, render_state: &RenderState<WebGpuDevice>) {
+        self.draw(render_state, |render_pass| {
+            render_pass.draw_indexed(index_count)
+        });
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        render_state: &RenderState<WebGpuDevice>,
+    ) {
+        self.draw(render_state, |render_pass| {
+            render_pass.draw_indexed_with_instance_count(index_count, instance_count)
+        });
+    }
+
+    fn dispatch_compute(
+        &self,
+        dimensions: ComputeDimensions,
+        compute_state: &ComputeState<WebGpuDevice>,
+    ) {
+        let program = compute_state.program;
+        let pipeline = program
+            .compute_pipeline
+            .as_ref()
+            .expect("Can't dispatch a raster program!");
+        let bind_groups = self.create_bind_groups(
+            program,
+            compute_state.uniforms,
+            compute_state.textures,
+            compute_state.images,
+            compute_state.storage_buffers,
+        );
+
+        self.with_command_encoder(|command_encoder| {
+            let compute_pass = command_encoder.begin_compute_pass();
+            compute_pass.set_pipeline(pipeline);
+            for (group_index, bind_group) in bind_groups.iter().enumerate() {
+                compute_pass.set_bind_group(group_index as u32, Some(bind_group));
+            }
+            compute_pass.dispatch_workgroups_with_workgroup_count_y_and_workgroup_count_z(
+                dimensions.x,
+                dimensions.y,
+                dimensions.z,
+            );
+            compute_pass.end();
+        });
+    }
+
+    fn add_fence(&self) -> WebGpuFence {
+        self.flush();
+        WebGpuFence(resolve_later(self.queue.on_submitted_work_done()))
+    }
+
+    // A browser can't block on the GPU. Work submitted to the queue runs in order anyway, so this
+    // only warns if the fence hasn't been reached yet.
+    fn wait_for_fence(&self, fence: &WebGpuFence) {
+        if !fence.0.get() {
+            debug!("Can't wait for a WebGPU fence; continuing without waiting.");
+        }
+    }
+
+    fn create_timer_query(&self) -> WebGpuTimerQuery {
+        WebGpuTimerQuery(Rc::new(RefCell::new(WebGpuTimerQueryTimes::default())))
+    }
+
+    // Timestamp queries aren't available everywhere, so timer queries measure the wall-clock time
+    // between the GPU finishing the work submitted before each end of the query.
+    fn begin_timer_query(&self, query: &WebGpuTimerQuery) {
+        self.flush();
+        let times = query.0.clone();
+        let promise = self.queue.on_submitted_work_done();
+        wasm_bindgen_futures::spawn_local(async move {
+            if JsFuture::from(promise).await.is_ok() {
+                times.borrow_mut().start_time = Some(js_sys::Date::now());
+            }
+        });
+    }
+
+    fn end_timer_query(&self, query: &WebGpuTimerQuery) {
+        self.flush();
+        let times = query.0.clone();
+        let promise = self.queue.on_submitted_work_done();
+        wasm_bindgen_futures::spawn_local(async move {
+            if JsFuture::from(promise).await.is_ok() {
+                times.borrow_mut().end_time = Some(js_sys::Date::now());
+            }
+        });
+    }
+
+    fn try_recv_timer_query(&self, query: &WebGpuTimerQuery) -> Option<Duration> {
+        let times = query.0.borrow();
+        match (times.start_time, times.end_time) {
+            (Some(start_time), Some(end_time)) => Some(Duration::from_secs_f64(
+                (end_time - start_time).max(0.0) / 1000.0,
+            )),
+            _ => None,
+        }
+    }
+
+    fn recv_timer_query(&self, query: &WebGpuTimerQuery) -> Duration {
+        self.try_recv_timer_query(query)
+            .expect("Can't block on a WebGPU timer query; use `try_recv_timer_query()`!")
+    }
+
+    fn try_recv_texture_data(&self, receiver: &WebGpuTextureDataReceiver) -> Option<TextureData> {
+        receiver.data()
+    }
+
+    fn recv_texture_data(&self, receiver: &WebGpuTextureDataReceiver) -> TextureData {
+        receiver
+            .data()
+            .expect("Can't block on WebGPU readback; use `try_recv_texture_data()`!")
+    }
+
+    fn try_recv_buffer(&self, receiver: &WebGpuBufferDataReceiver) -> Option<Vec<u8>> {
+        receiver.data()
+    }
+
+    fn recv_buffer(&self, receiver: &WebGpuBufferDataReceiver) -> Vec<u8> {
+        receiver
+            .data()
+            .expect("Can't block on WebGPU readback; use `try_recv_buffer()`!")
+    }
+}
+
+impl WebGpuProgram {
+    // The shaders in bind group order.
+    fn shaders(&self) -> Vec<&WebGpuShader> {
+        match self.shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => vec![vertex, fragment],
+            ProgramKind::Compute(ref compute) => vec![compute],
+        }
+    }
+}
+
+impl WebGpuTexture {
+    fn new(texture: GpuTexture) -> WebGpuTexture {
+        let view = texture.create_view();
+        let format = texture.format();
+        let size = vec2i(texture.width() as i32, texture.height() as i32);
+        WebGpuTexture {
+            texture,
+            view,
+            format,
+            size,
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+        }
+    }
+
+    #[inline]
+    pub fn gpu_texture(&self) -> &GpuTexture {
+        &self.texture
+    }
+}
+
+impl WebGpuBuffer {
+    fn allocated(&self) -> web_sys::GpuBuffer {
+        (*self.allocation.borrow())
+            .clone()
+            .expect("Buffer hasn't been allocated!")
+    }
+}
+
+impl WebGpuVertexArray {
+    fn buffer_layouts(&self) -> Vec<VertexBufferLayoutKey> {
+        let attributes = self.attributes.borrow();
+        (0..self.vertex_buffers.borrow().len() as u32)
+            .map(|buffer_index| {
+                let mut layout = VertexBufferLayoutKey {
+                    stride: 0,
+                    instanced: false,
+                    attributes: vec![],
+                };
+                for info in attributes
+                    .iter()
+                    .filter(|info| info.buffer_index == buffer_index)
+                {
+                    layout.stride = info.stride;
+                    layout.instanced = info.instanced;
+                    layout.attributes.push(info.attribute);
+                }
+                layout
+            })
+            .collect()
+    }
+}
+
+impl WebGpuTextureDataReceiver {
+    fn data(&self) -> Option<TextureData> {
+        if let Some(ref data) = *self.data.borrow() {
+            return Some((*data).clone());
+        }
+        if !self.mapped.get() {
+            return None;
+        }
+
+        let mapped_bytes = Uint8Array::new(&self.buffer.get_mapped_range()).to_vec();
+        self.buffer.unmap();
+
+        let row_size = self.size.x() as usize * self.format.bytes_per_pixel();
+        let mut bytes = Vec::with_capacity(row_size * self.size.y() as usize);
+        for row in 0..(self.size.y() as usize) {
+            let row_start = row * self.padded_row_size;
+            bytes.extend_from_slice(&mapped_bytes[row_start..(row_start + row_size)]);
+        }
+
+        if self.swizzle {
+            for pixel in bytes.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let data = match self.format {
+            TextureFormat::R8 | TextureFormat::RGBA8 => TextureData::U8(bytes),
+            TextureFormat::R16F | TextureFormat::RGBA16F => TextureData::F16(
+                bytes
+                    .chunks(2)
+                    .map(|half| f16::from_bits(u16::from_ne_bytes([half[0], half[1]])))
+                    .collect(),
+            ),
+            TextureFormat::RGBA32F => TextureData::F32(
+                bytes
+                    .chunks(4)
+                    .map(|float| f32::from_ne_bytes([float[0], float[1], float[2], float[3]]))
+                    .collect(),
+            ),
+        };
+        *self.data.borrow_mut() = Some(data.clone());
+        Some(data)
+    }
+}
+
+impl WebGpuBufferDataReceiver {
+    fn data(&self) -> Option<Vec<u8>> {
+        if let Some(ref data) = *self.data.borrow() {
+            return Some((*data).clone());
+        }
+        if !self.mapped.get() {
+            return None;
+        }
+
+        let mut data = Uint8Array::new(&self.buffer.get_mapped_range()).to_vec();
+        data.truncate(self.size);
+        self.buffer.unmap();
+        *self.data.borrow_mut() = Some(data.clone());
+        Some(data)
+    }
+}
+
+// Loads a shader from `shaders/spirv`, reflects it, and translates it to WGSL.
+fn load_shader(
+    resources: &dyn ResourceLoader,
+    name: &str,
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> Result<(String, ShaderReflection), DeviceError> {
+    let suffix = match kind {
+        ShaderKind::Vertex => 'v',
+        ShaderKind::Fragment => 'f',
+        ShaderKind::Compute => 'c',
+    };
+    let path = format!("shaders/spirv/{}.{}s.spv", name, suffix);
+    match resources.slurp(&path) {
+        Ok(source) => translate_shader(name, &source, kind, float32_filterable),
+        Err(error) => Err(DeviceError::ResourceLoad { path, error }),
+    }
+}
+
+fn translate_shader(
+    name: &str,
+    source: &[u8],
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> Result<(String, ShaderReflection), DeviceError> {
+    let error = |log: String| DeviceError::ShaderCompile {
+        name: name.to_owned(),
+        kind,
+        log,
+    };
+
+    let options = spv_frontend::Options {
+        adjust_coordinate_space: false,
+        strict_capabilities: false,
+        block_ctx_dump_prefix: None,
+    };
+    let mut module = spv_frontend::parse_u8_slice(source, &options)
+        .map_err(|err| error(format!("Failed to parse SPIR-V: {:?}", err)))?;
+
+    let reflection = reflect_module(&mut module, kind, float32_filterable);
+
+    let module_info = Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| error(format!("Validation failed: {:?}", err.into_inner())))?;
+    let wgsl = naga::back::wgsl::write_string(
+        &module,
+        &module_info,
+        naga::back::wgsl::WriterFlags::empty(),
+    )
+    .map_err(|err| error(format!("Failed to translate to WGSL: {:?}", err)))?;
+    Ok((wgsl, reflection))
+}
+
+// Reflects the resources that the shader uses and assigns them bindings in the bind group for its
+// stage.
+fn reflect_module(
+    module: &mut naga::Module,
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> ShaderReflection {
+    let (group, visibility) = match kind {
+        ShaderKind::Vertex => (VERTEX_BIND_GROUP, gpu_shader_stage::VERTEX),
+        ShaderKind::Fragment => (FRAGMENT_BIND_GROUP, gpu_shader_stage::FRAGMENT),
+        ShaderKind::Compute => (VERTEX_BIND_GROUP, gpu_shader_stage::COMPUTE),
+    };
+
+    let mut reflection = ShaderReflection::default();
+
+    // `spirv-opt --split-combined-image-sampler` leaves each texture and its sampler at the same
+    // binding, which is how they're paired up.
+    let mut texture_names_by_binding = HashMap::new();
+    let mut sampler_bindings = vec![];
+
+    let mut next_binding = 0;
+    for (_, global_variable) in module.global_variables.iter_mut() {
+        let original_binding = match global_variable.binding {
+            None => continue,
+            Some(ref binding) => binding.clone(),
+        };
+        let binding = next_binding;
+        next_binding += 1;
+        global_variable.binding = Some(ResourceBinding { group, binding });
+
+        let ty = &module.types[global_variable.ty];
+        let name = global_variable.name.clone().unwrap_or_default();
+        let mut layout_entry = web_sys::GpuBindGroupLayoutEntry::new(binding, visibility);
+        match (global_variable.space, &ty.inner) {
+            (AddressSpace::Uniform, &TypeInner::Struct { ref members, span }) => {
+                reflection.uniform_block = Some(UniformBlockInfo {
+                    binding,
+                    size: span as usize,
+                    offsets: members
+                        .iter()
+                        .filter_map(|member| {
+                            let name = member.name.clone()?;
+                            Some((name, member.offset as usize))
+                        })
+                        .collect(),
+                });
+                let mut buffer_layout = web_sys::GpuBufferBindingLayout::new();
+                buffer_layout.type_(web_sys::GpuBufferBindingType::Uniform);
+                layout_entry.buffer(&buffer_layout);
+            }
+            (AddressSpace::Storage { access }, _) => {
+                // Blocks like `buffer bFills { ... }` have no instance name, so fall back to the
+                // name of the block.
+                let name = match ty.name {
+                    Some(ref type_name) if type_name.starts_with('b') => type_name.clone(),
+                    _ => name,
+                };
+                reflection.storage_buffers.insert(name, binding);
+                let mut buffer_layout = web_sys::GpuBufferBindingLayout::new();
+                buffer_layout.type_(if access.contains(StorageAccess::STORE) {
+                    web_sys::GpuBufferBindingType::Storage
+                } else {
+                    web_sys::GpuBufferBindingType::ReadOnlyStorage
+                });
+                layout_entry.buffer(&buffer_layout);
+            }
+            (
+                AddressSpace::Handle,
+                &TypeInner::Image {
+                    class: ImageClass::Sampled { kind, .. },
+                    ..
+                },
+            ) => {
+                texture_names_by_binding.insert(original_binding, name.clone());
+                reflection.textures.insert(
+                    name,
+                    TextureInfo {
+                        binding,
+                        sampler_binding: None,
+                    },
+                );
+                let mut texture_layout = web_sys::GpuTextureBindingLayout::new();
+                texture_layout
+                    .sample_type(match kind {
+                        ScalarKind::Sint => web_sys::GpuTextureSampleType::Sint,
+                        ScalarKind::Uint => web_sys::GpuTextureSampleType::Uint,
+                        _ if float32_filterable => web_sys::GpuTextureSampleType::Float,
+                        _ => web_sys::GpuTextureSampleType::UnfilterableFloat,
+                    })
+                    .view_dimension(web_sys::GpuTextureViewDimension::N2d);
+                layout_entry.texture(&texture_layout);
+            }
+            (
+                AddressSpace::Handle,
+                &TypeInner::Image {
+                    class: ImageClass::Storage { format, access },
+                    ..
+                },
+            ) => {
+                reflection.images.insert(name, binding);
+                let mut storage_texture_layout = web_sys::GpuStorageTextureBindingLayout::new(
+                    storage_format_to_gpu_texture_format(format),
+                );
+                storage_texture_layout
+                    .access(if !access.contains(StorageAccess::STORE) {
+                        web_sys::GpuStorageTextureAccess::ReadOnly
+                    } else if !access.contains(StorageAccess::LOAD) {
+                        web_sys::GpuStorageTextureAccess::WriteOnly
+                    } else {
+                        web_sys::GpuStorageTextureAccess::ReadWrite
+                    })
+                    .view_dimension(web_sys::GpuTextureViewDimension::N2d);
+                layout_entry.storage_texture(&storage_texture_layout);
+            }
+            (AddressSpace::Handle, &TypeInner::Sampler { .. }) => {
+                sampler_bindings.push((original_binding, binding));
+                let mut sampler_layout = web_sys::GpuSamplerBindingLayout::new();
+                sampler_layout.type_(if float32_filterable {
+                    web_sys::GpuSamplerBindingType::Filtering
+                } else {
+                    web_sys::GpuSamplerBindingType::NonFiltering
+                });
+                layout_entry.sampler(&sampler_layout);
+            }
+            _ => panic!("Unsupported resource `{}` in shader!", name),
+        }
+
+        reflection.layout_entries.push(layout_entry);
+    }
+
+    for (original_binding, sampler_binding) in sampler_bindings {
+        if let Some(texture_name) = texture_names_by_binding.get(&original_binding) {
+            let texture_info = reflection.textures.get_mut(texture_name).unwrap();
+            texture_info.sampler_binding = Some(sampler_binding);
+        }
+    }
+
+    if kind == ShaderKind::Vertex {
+        for entry_point in &module.entry_points {
+            for argument in &entry_point.function.arguments {
+                if let (Some(ref name), Some(Binding::Location { location, .. })) =
+                    (&argument.name, &argument.binding)
+                {
+                    reflection.vertex_inputs.insert(name.clone(), *location);
+                }
+            }
+        }
+    }
+
+    reflection
+}
+
+fn create_depth_stencil_texture(device: &GpuDevice, size: Vector2I) -> WebGpuTexture {
+    let mut descriptor = web_sys::GpuTextureDescriptor::new(
+        DEPTH_STENCIL_FORMAT,
+        &extent_from_size(size),
+        gpu_texture_usage::RENDER_ATTACHMENT,
+    );
+    descriptor.label("depth-stencil");
+    WebGpuTexture::new(device.create_texture(&descriptor))
+}
+
+// Returns a flag that's set once the promise resolves.
+fn resolve_later(promise: Promise) -> Rc<Cell<bool>> {
+    let resolved = Rc::new(Cell::new(false));
+    let captured_resolved = resolved.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        JsFuture::from(promise)
+            .await
+            .expect("WebGPU operation failed!");
+        captured_resolved.set(true);
+    });
+    resolved
+}
+
+#[inline]
+fn load_op(clear: bool) -> web_sys::GpuLoadOp {
+    if clear {
+        web_sys::GpuLoadOp::Clear
+    } else {
+        web_sys::GpuLoadOp::Load
+    }
+}
+
+fn storage_format_to_gpu_texture_format(format: StorageFormat) -> GpuTextureFormat {
+    match format {
+        StorageFormat::R8Unorm => GpuTextureFormat::R8unorm,
+        StorageFormat::R16Float => GpuTextureFormat::R16float,
+        StorageFormat::R32Float => GpuTextureFormat::R32float,
+        StorageFormat::Rgba8Unorm => GpuTextureFormat::Rgba8unorm,
+        StorageFormat::Rgba16Float => GpuTextureFormat::Rgba16float,
+        StorageFormat::Rgba32Float => GpuTextureFormat::Rgba32float,
+        _ => panic!("Unsupported storage image format {:?}!", format),
+    }
+}
+
+#[inline]
+fn align_buffer_size(size: usize) -> usize {
+    (size + COPY_BUFFER_ALIGNMENT - 1) / COPY_BUFFER_ALIGNMENT * COPY_BUFFER_ALIGNMENT
+}
+
+fn extent_from_size(size: Vector2I) -> Array {
+    [size.x() as u32, size.y() as u32, 1]
+        .iter()
+        .map(|&extent| JsValue::from(extent))
+        .collect()
+}
+
+fn origin_from_point(point: Vector2I) -> Array {
+    [point.x() as u32, point.y() as u32, 0]
+        .iter()
+        .map(|&coordinate| JsValue::from(coordinate))
+        .collect()
+}
+
+fn slice_as_bytes<T>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * mem::size_of::<T>()) }
+}
+
+// Extension traits
+
+trait BlendFactorExt {
+    fn to_gpu_blend_factor(self) -> web_sys::GpuBlendFactor;
+}
+
+impl BlendFactorExt for BlendFactor {
+    #[inline]
+    fn to_gpu_blend_factor(self) -> web_sys::GpuBlendFactor {
+        match self {
+            BlendFactor::Zero => web_sys::GpuBlendFactor::Zero,
+            BlendFactor::One => web_sys::GpuBlendFactor::One,
+            BlendFactor::SrcAlpha => web_sys::GpuBlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha => web_sys::GpuBlendFactor::OneMinusSrcAlpha,
+            BlendFactor::DestAlpha => web_sys::GpuBlendFactor::DstAlpha,
+            BlendFactor::OneMinusDestAlpha => web_sys::GpuBlendFactor::OneMinusDstAlpha,
+            BlendFactor::DestColor => web_sys::GpuBlendFactor::Dst,
+            BlendFactor::OneMinusSrcColor => web_sys::GpuBlendFactor::OneMinusSrc,
+            BlendFactor::OneMinusSrc1Color => {
+                panic!("Dual-source blending isn't supported by the WebGPU backend!")
+            }
+        }
+    }
+}
+
+trait BlendOpExt {
+    fn to_gpu_blend_operation(self) -> web_sys::GpuBlendOperation;
+}
+
+impl BlendOpExt for BlendOp {
+    #[inline]
+    fn to_gpu_blend_operation(self) -> web_sys::GpuBlendOperation {
+        match self {
+            BlendOp::Add => web_sys::GpuBlendOperation::Add,
+            BlendOp::Subtract => web_sys::GpuBlendOperation::Subtract,
+            BlendOp::ReverseSubtract => web_sys::GpuBlendOperation::ReverseSubtract,
+            BlendOp::Min => web_sys::GpuBlendOperation::Min,
+            BlendOp::Max => web_sys::GpuBlendOperation::Max,
+        }
+    }
+}
+
+trait BlendStateExt {
+    fn to_blend_state_key(self) -> BlendStateKey;
+}
+
+impl BlendStateExt for BlendState {
+    fn to_blend_state_key(self) -> BlendStateKey {
+        BlendStateKey {
+            src_rgb_factor: self.src_rgb_factor.to_gpu_blend_factor(),
+            dest_rgb_factor: self.dest_rgb_factor.to_gpu_blend_factor(),
+            src_alpha_factor: self.src_alpha_factor.to_gpu_blend_factor(),
+            dest_alpha_factor: self.dest_alpha_factor.to_gpu_blend_factor(),
+            op: self.op.to_gpu_blend_operation(),
+        }
+    }
+}
+
+impl BlendStateKey {
+    fn to_gpu_blend_state(self) -> web_sys::GpuBlendState {
+        let component = |src_factor, dst_factor| {
+            let mut component = web_sys::GpuBlendComponent::new();
+            component.operation(self.op);
+            match self.op {
+                // WebGPU requires the factors of min and max blends to be one.
+                web_sys::GpuBlendOperation::Min | web_sys::GpuBlendOperation::Max => {}
+                _ => {
+                    component.src_factor(src_factor).dst_factor(dst_factor);
+                }
+            }
+            component
+        };
+        web_sys::GpuBlendState::new(
+            &component(self.src_alpha_factor, self.dest_alpha_factor),
+            &component(self.src_rgb_factor, self.dest_rgb_factor),
+        )
+    }
+}
+
+trait CompareFunctionExt {
+    fn to_gpu_compare_function(self) -> web_sys::GpuCompareFunction;
+}
+
+impl CompareFunctionExt for DepthFunc {
+    #[inline]
+    fn to_gpu_compare_function(self) -> web_sys::GpuCompareFunction {
+        match self {
+            DepthFunc::Less => web_sys::GpuCompareFunction::Less,
+            DepthFunc::LessEqual => web_sys::GpuCompareFunction::LessEqual,
+            DepthFunc::Always => web_sys::GpuCompareFunction::Always,
+        }
+    }
+}
+
+impl CompareFunctionExt for StencilFunc {
+    #[inline]
+    fn to_gpu_compare_function(self) -> web_sys::GpuCompareFunction {
+        match self {
+            StencilFunc::Always => web_sys::GpuCompareFunction::Always,
+            StencilFunc::Equal => web_sys::GpuCompareFunction::Equal,
+        }
+    }
+}
+
+trait PrimitiveExt {
+    fn to_gpu_primitive_topology(self) -> web_sys::GpuPrimitiveTopology;
+}
+
+impl PrimitiveExt for Primitive {
+    #[inline]
+    fn to_gpu_primitive_topology(self) -> web_sys::GpuPrimitiveTopology {
+        match self {
+            Primitive::Triangles => web_sys::GpuPrimitiveTopology::TriangleList,
+            Primitive::Lines => web_sys::GpuPrimitiveTopology::LineList,
+        }
+    }
+}
+
+trait TextureFormatExt: Sized {
+    fn to_gpu_texture_format(self) -> GpuTextureFormat;
+    fn from_gpu_texture_format(gpu_format: GpuTextureFormat) -> Option<Self>;
+}
+
+impl TextureFormatExt for TextureFormat {
+    #[inline]
+    fn to_gpu_texture_format(self) -> GpuTextureFormat {
+        match self {
+            TextureFormat::R8 => GpuTextureFormat::R8unorm,
+            TextureFormat::R16F => GpuTextureFormat::R16float,
+            TextureFormat::RGBA8 => GpuTextureFormat::Rgba8unorm,
+            TextureFormat::RGBA16F => GpuTextureFormat::Rgba16float,
+            TextureFormat::RGBA32F => GpuTextureFormat::Rgba32float,
+        }
+    }
+
+    fn from_gpu_texture_format(gpu_format: GpuTextureFormat) -> Option<TextureFormat> {
+        match gpu_format {
+            GpuTextureFormat::R8unorm => Some(TextureFormat::R8),
+            GpuTextureFormat::R16float => Some(TextureFormat::R16F),
+            GpuTextureFormat::Rgba8unorm
+            | GpuTextureFormat::Rgba8unormSrgb
+            | GpuTextureFormat::Bgra8unorm
+            | GpuTextureFormat::Bgra8unormSrgb => Some(TextureFormat::RGBA8),
+            GpuTextureFormat::Rgba16float => Some(TextureFormat::RGBA16F),
+            GpuTextureFormat::Rgba32float => Some(TextureFormat::RGBA32F),
+            _ => None,
+        }
+    }
+}
+
+trait UniformDataExt {
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl UniformDataExt for UniformData {
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            match *self {
+                UniformData::Float(ref data) => {
+                    slice::from_raw_parts(data as *const f32 as *const u8, 4 * 1)
+                }
+                UniformData::IVec2(ref data) => {
+                    slice::from_raw_parts(data as *const I32x2 as *const u8, 4 * 2)
+                }
+                UniformData::IVec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Int(ref data) => {
+                    slice::from_raw_parts(data as *const i32 as *const u8, 4 * 1)
+                }
+                UniformData::Mat2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+                UniformData::Mat4(ref data) => {
+                    slice::from_raw_parts(&data[0] as *const F32x4 as *const u8, 4 * 16)
+                }
+                UniformData::Vec2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x2 as *const u8, 4 * 2)
+                }
+                UniformData::Vec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Vec4(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+            }
+        }
+    }
+}
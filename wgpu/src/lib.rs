@@ -0,0 +1,2131 @@
+// pathfinder/wgpu/src/lib.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A wgpu implementation of the device abstraction, for Vulkan, Direct3D 12, Metal, and WebGPU.
+//!
+//! Shaders are loaded as SPIR-V from `shaders/spirv` and reflected with naga. See
+//! `shaders/README.md` for how to build them.
+
+#[macro_use]
+extern crate log;
+
+use half::f16;
+use naga::front::spv::{self as spv_frontend};
+use naga::{AddressSpace, Binding, ImageClass, ResourceBinding, ScalarKind, StorageAccess};
+use naga::{StorageFormat, TypeInner};
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use pathfinder_gpu::{BlendFactor, BlendOp, BlendState, BufferData, BufferTarget};
+use pathfinder_gpu::{BufferUploadMode, CompressedTextureFormat, ComputeDimensions};
+use pathfinder_gpu::{ComputeState, DepthFunc, Device, DeviceCapabilities, DeviceError};
+use pathfinder_gpu::{FeatureLevel, ImageBinding, Primitive, ProgramKind, RenderState};
+use pathfinder_gpu::{RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
+use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformBinding};
+use pathfinder_gpu::{UniformData, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_resources::ResourceLoader;
+use pathfinder_simd::default::{F32x2, F32x4, I32x2};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wgpu::util::DeviceExt;
+
+const DEPTH_STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+// Vertex and compute shaders use the first bind group; fragment shaders use the second.
+const VERTEX_BIND_GROUP: u32 = 0;
+const FRAGMENT_BIND_GROUP: u32 = 1;
+
+const BUFFER_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::VERTEX
+    .union(wgpu::BufferUsages::INDEX)
+    .union(wgpu::BufferUsages::STORAGE)
+    .union(wgpu::BufferUsages::COPY_SRC)
+    .union(wgpu::BufferUsages::COPY_DST);
+
+pub struct WgpuDevice {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    adapter_info: wgpu::AdapterInfo,
+    float32_filterable: bool,
+    main_color_texture: WgpuTexture,
+    main_depth_stencil_texture: WgpuTexture,
+    samplers: Vec<wgpu::Sampler>,
+    dummy_texture: WgpuTexture,
+    command_encoder: RefCell<Option<wgpu::CommandEncoder>>,
+    debug_groups: RefCell<Vec<String>>,
+}
+
+pub struct WgpuProgram {
+    shaders: ProgramKind<WgpuShader>,
+    bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    pipeline_layout: wgpu::PipelineLayout,
+    compute_pipeline: Option<wgpu::ComputePipeline>,
+    render_pipelines: RefCell<HashMap<RenderPipelineKey, Rc<wgpu::RenderPipeline>>>,
+}
+
+pub struct WgpuShader {
+    module: wgpu::ShaderModule,
+    reflection: ShaderReflection,
+}
+
+pub struct WgpuTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    size: Vector2I,
+    sampling_flags: Cell<TextureSamplingFlags>,
+}
+
+pub struct WgpuFramebuffer(WgpuTexture);
+
+#[derive(Clone)]
+pub struct WgpuBuffer {
+    allocation: Rc<RefCell<Option<Rc<wgpu::Buffer>>>>,
+}
+
+pub struct WgpuVertexArray {
+    vertex_buffers: RefCell<Vec<WgpuBuffer>>,
+    index_buffer: RefCell<Option<WgpuBuffer>>,
+    attributes: RefCell<Vec<WgpuVertexAttrInfo>>,
+}
+
+pub struct WgpuVertexAttr {
+    location: u32,
+}
+
+pub struct WgpuUniform {
+    name: String,
+}
+
+pub struct WgpuTextureParameter {
+    name: String,
+}
+
+pub struct WgpuImageParameter {
+    name: String,
+}
+
+pub struct WgpuStorageBuffer {
+    name: String,
+}
+
+pub struct WgpuFence(wgpu::SubmissionIndex);
+
+pub struct WgpuTimerQuery(Arc<Mutex<WgpuTimerQueryTimes>>);
+
+pub struct WgpuTextureDataReceiver {
+    buffer: wgpu::Buffer,
+    mapped: Arc<AtomicBool>,
+    format: TextureFormat,
+    swizzle: bool,
+    size: Vector2I,
+    padded_row_size: usize,
+    data: RefCell<Option<TextureData>>,
+}
+
+pub struct WgpuBufferDataReceiver {
+    buffer: wgpu::Buffer,
+    mapped: Arc<AtomicBool>,
+    size: usize,
+    data: RefCell<Option<Vec<u8>>>,
+}
+
+#[derive(Default)]
+struct WgpuTimerQueryTimes {
+    start_time: Option<Instant>,
+    end_time: Option<Instant>,
+}
+
+struct WgpuVertexAttrInfo {
+    buffer_index: u32,
+    stride: u64,
+    step_mode: wgpu::VertexStepMode,
+    attribute: wgpu::VertexAttribute,
+}
+
+#[derive(Default)]
+struct ShaderReflection {
+    layout_entries: Vec<wgpu::BindGroupLayoutEntry>,
+    uniform_block: Option<UniformBlockInfo>,
+    textures: HashMap<String, TextureInfo>,
+    images: HashMap<String, u32>,
+    storage_buffers: HashMap<String, u32>,
+    vertex_inputs: HashMap<String, u32>,
+}
+
+struct UniformBlockInfo {
+    binding: u32,
+    size: usize,
+    offsets: HashMap<String, usize>,
+}
+
+struct TextureInfo {
+    binding: u32,
+    sampler_binding: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct RenderPipelineKey {
+    vertex_buffers: Vec<VertexBufferLayoutKey>,
+    topology: wgpu::PrimitiveTopology,
+    color_format: wgpu::TextureFormat,
+    blend: Option<wgpu::BlendState>,
+    color_mask: bool,
+    depth: Option<(wgpu::CompareFunction, bool)>,
+    stencil: Option<(wgpu::CompareFunction, u32, bool)>,
+    has_depth_stencil_attachment: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct VertexBufferLayoutKey {
+    stride: u64,
+    step_mode: wgpu::VertexStepMode,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl WgpuDevice {
+    /// Creates a device that renders to the given texture by default.
+    ///
+    /// The device should be created with as many of the features in `optional_features()` as the
+    /// adapter supports.
+    ///
+    /// To present to a surface, render to a texture with `COPY_SRC` usage and copy it to the
+    /// surface texture after `end_commands()`.
+    pub fn new(
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        texture: wgpu::Texture,
+    ) -> WgpuDevice {
+        let features = device.features();
+
+        // Without filterable floating-point textures, the bind group layouts have to declare all
+        // textures as unfilterable, so fall back to nearest-neighbor sampling everywhere.
+        let float32_filterable = features.contains(wgpu::Features::FLOAT32_FILTERABLE);
+        if !float32_filterable {
+            warn!("Float textures aren't filterable on this device; using nearest sampling.");
+        }
+
+        let samplers = (0..16)
+            .map(|sampling_flags_value| {
+                let sampling_flags = TextureSamplingFlags::from_bits(sampling_flags_value).unwrap();
+                device.create_sampler(&wgpu::SamplerDescriptor {
+                    label: None,
+                    address_mode_u: if sampling_flags.contains(TextureSamplingFlags::REPEAT_U) {
+                        wgpu::AddressMode::Repeat
+                    } else {
+                        wgpu::AddressMode::ClampToEdge
+                    },
+                    address_mode_v: if sampling_flags.contains(TextureSamplingFlags::REPEAT_V) {
+                        wgpu::AddressMode::Repeat
+                    } else {
+                        wgpu::AddressMode::ClampToEdge
+                    },
+                    mag_filter: if !float32_filterable
+                        || sampling_flags.contains(TextureSamplingFlags::NEAREST_MAG)
+                    {
+                        wgpu::FilterMode::Nearest
+                    } else {
+                        wgpu::FilterMode::Linear
+                    },
+                    min_filter: if !float32_filterable
+                        || sampling_flags.contains(TextureSamplingFlags::NEAREST_MIN)
+                    {
+                        wgpu::FilterMode::Nearest
+                    } else {
+                        wgpu::FilterMode::Linear
+                    },
+                    ..wgpu::SamplerDescriptor::default()
+                })
+            })
+            .collect();
+
+        let main_color_texture = WgpuTexture::new(texture);
+        let main_depth_stencil_texture =
+            create_depth_stencil_texture(&device, main_color_texture.size);
+        let dummy_texture = WgpuTexture::new(device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("dummy"),
+            size: extent_from_size(vec2i(1, 1)),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        }));
+
+        WgpuDevice {
+            device,
+            queue,
+            adapter_info: adapter.get_info(),
+            float32_filterable,
+            main_color_texture,
+            main_depth_stencil_texture,
+            samplers,
+            dummy_texture,
+            command_encoder: RefCell::new(None),
+            debug_groups: RefCell::new(vec![]),
+        }
+    }
+
+    fn create_shader_from_module(
+        &self,
+        name: &str,
+        module: naga::Module,
+        reflection: ShaderReflection,
+    ) -> WgpuShader {
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::ShaderSource::Naga(Cow::Owned(module)),
+            });
+        WgpuShader { module, reflection }
+    }
+
+    /// The features that Pathfinder takes advantage of if the adapter supports them.
+    #[inline]
+    pub fn optional_features() -> wgpu::Features {
+        wgpu::Features::FLOAT32_FILTERABLE
+            | wgpu::Features::TEXTURE_COMPRESSION_BC
+            | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+            | wgpu::Features::TEXTURE_COMPRESSION_ASTC
+    }
+
+    #[inline]
+    pub fn main_texture(&self) -> &wgpu::Texture {
+        &self.main_color_texture.texture
+    }
+
+    /// Replaces the texture that the device renders to by default, returning the old one.
+    pub fn swap_texture(&mut self, new_texture: wgpu::Texture) -> wgpu::Texture {
+        self.flush();
+        let new_texture = WgpuTexture::new(new_texture);
+        if new_texture.size != self.main_color_texture.size {
+            self.main_depth_stencil_texture =
+                create_depth_stencil_texture(&self.device, new_texture.size);
+        }
+        mem::replace(&mut self.main_color_texture, new_texture).texture
+    }
+
+    /// Wraps a texture created outside Pathfinder, such as a video frame, so that it can be
+    /// sampled without a copy. The texture needs `TEXTURE_BINDING` usage.
+    pub fn wrap_texture(&self, texture: wgpu::Texture) -> WgpuTexture {
+        WgpuTexture::new(texture)
+    }
+
+    #[inline]
+    pub fn wgpu_device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    #[inline]
+    pub fn wgpu_queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    fn with_command_encoder<F, R>(&self, callback: F) -> R
+    where
+        F: FnOnce(&mut wgpu::CommandEncoder) -> R,
+    {
+        let mut command_encoder = self.command_encoder.borrow_mut();
+        let command_encoder = command_encoder.get_or_insert_with(|| {
+            let mut command_encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            // Debug groups can't span command encoders, so reopen the ones that are still open.
+            for name in self.debug_groups.borrow().iter() {
+                command_encoder.push_debug_group(name);
+            }
+            command_encoder
+        });
+        callback(command_encoder)
+    }
+
+    // Submits all recorded commands. This must happen before anything is written through the
+    // queue so that the write lands after the commands that were recorded before it.
+    fn flush(&self) -> wgpu::SubmissionIndex {
+        let mut command_encoder = self.command_encoder.borrow_mut().take();
+        if let Some(ref mut command_encoder) = command_encoder {
+            for _ in 0..self.debug_groups.borrow().len() {
+                command_encoder.pop_debug_group();
+            }
+        }
+        self.queue
+            .submit(command_encoder.map(|command_encoder| command_encoder.finish()))
+    }
+
+    fn render_target_color_texture<'a>(
+        &'a self,
+        target: &RenderTarget<'a, WgpuDevice>,
+    ) -> &'a WgpuTexture {
+        match *target {
+            RenderTarget::Default => &self.main_color_texture,
+            RenderTarget::Framebuffer(framebuffer) => &framebuffer.0,
+        }
+    }
+
+    fn render_target_depth_stencil_view<'a>(
+        &'a self,
+        target: &RenderTarget<'a, WgpuDevice>,
+    ) -> Option<&'a wgpu::TextureView> {
+        match *target {
+            RenderTarget::Default => Some(&self.main_depth_stencil_texture.view),
+            RenderTarget::Framebuffer(_) => None,
+        }
+    }
+
+    fn create_bind_group_layout(&self, name: &str, shader: &WgpuShader) -> wgpu::BindGroupLayout {
+        self.device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(name),
+                entries: &shader.reflection.layout_entries,
+            })
+    }
+
+    fn render_pipeline(&self, render_state: &RenderState<WgpuDevice>) -> Rc<wgpu::RenderPipeline> {
+        let program = render_state.program;
+        let (vertex_shader, fragment_shader) = match program.shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => (vertex, fragment),
+            ProgramKind::Compute(_) => panic!("Can't draw with a compute program!"),
+        };
+
+        let options = &render_state.options;
+        let key = RenderPipelineKey {
+            vertex_buffers: render_state.vertex_array.buffer_layouts(),
+            topology: render_state.primitive.to_wgpu_primitive_topology(),
+            color_format: self
+                .render_target_color_texture(render_state.target)
+                .texture
+                .format(),
+            blend: options.blend.map(|blend| blend.to_wgpu_blend_state()),
+            color_mask: options.color_mask,
+            depth: options
+                .depth
+                .map(|depth| (depth.func.to_wgpu_compare_function(), depth.write)),
+            stencil: options.stencil.map(|stencil| {
+                let compare = stencil.func.to_wgpu_compare_function();
+                (compare, stencil.mask, stencil.write)
+            }),
+            has_depth_stencil_attachment: self
+                .render_target_depth_stencil_view(render_state.target)
+                .is_some(),
+        };
+
+        if let Some(pipeline) = program.render_pipelines.borrow().get(&key) {
+            return (*pipeline).clone();
+        }
+
+        let vertex_buffers: Vec<_> = key
+            .vertex_buffers
+            .iter()
+            .map(|layout| wgpu::VertexBufferLayout {
+                array_stride: layout.stride,
+                step_mode: layout.step_mode,
+                attributes: &layout.attributes,
+            })
+            .collect();
+
+        let depth_stencil = if key.has_depth_stencil_attachment {
+            let (depth_compare, depth_write_enabled) =
+                key.depth.unwrap_or((wgpu::CompareFunction::Always, false));
+            let stencil = match key.stencil {
+                None => wgpu::StencilState::default(),
+                Some((compare, mask, write)) => {
+                    let face = wgpu::StencilFaceState {
+                        compare,
+                        fail_op: wgpu::StencilOperation::Keep,
+                        depth_fail_op: wgpu::StencilOperation::Keep,
+                        pass_op: if write {
+                            wgpu::StencilOperation::Replace
+                        } else {
+                            wgpu::StencilOperation::Keep
+                        },
+                    };
+                    wgpu::StencilState {
+                        front: face,
+                        back: face,
+                        read_mask: mask,
+                        write_mask: if write { mask } else { 0 },
+                    }
+                }
+            };
+            Some(wgpu::DepthStencilState {
+                format: DEPTH_STENCIL_FORMAT,
+                depth_write_enabled,
+                depth_compare,
+                stencil,
+                bias: wgpu::DepthBiasState::default(),
+            })
+        } else {
+            None
+        };
+
+        let color_target = wgpu::ColorTargetState {
+            format: key.color_format,
+            blend: key.blend,
+            write_mask: if key.color_mask {
+                wgpu::ColorWrites::ALL
+            } else {
+                wgpu::ColorWrites::empty()
+            },
+        };
+
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&program.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &vertex_shader.module,
+                    entry_point: "main",
+                    buffers: &vertex_buffers,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: key.topology,
+                    ..wgpu::PrimitiveState::default()
+                },
+                depth_stencil,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &fragment_shader.module,
+                    entry_point: "main",
+                    targets: &[Some(color_target)],
+                }),
+                multiview: None,
+            });
+
+        let pipeline = Rc::new(pipeline);
+        program
+            .render_pipelines
+            .borrow_mut()
+            .insert(key, pipeline.clone());
+        pipeline
+    }
+
+    fn create_bind_groups(
+        &self,
+        program: &WgpuProgram,
+        uniforms: &[UniformBinding<WgpuUniform>],
+        textures: &[TextureBinding<WgpuTextureParameter, WgpuTexture>],
+        images: &[ImageBinding<WgpuImageParameter, WgpuTexture>],
+        storage_buffers: &[(&WgpuStorageBuffer, &WgpuBuffer)],
+    ) -> Vec<wgpu::BindGroup> {
+        program
+            .shaders()
+            .iter()
+            .zip(program.bind_group_layouts.iter())
+            .map(|(shader, bind_group_layout)| {
+                let reflection = &shader.reflection;
+
+                let uniform_buffer = reflection
+                    .uniform_block
+                    .as_ref()
+                    .map(|uniform_block| self.create_uniform_buffer(uniform_block, uniforms));
+                let storage_buffer_allocations: Vec<_> = reflection
+                    .storage_buffers
+                    .iter()
+                    .map(|(name, &binding)| {
+                        let buffer = storage_buffers
+                            .iter()
+                            .find(|(storage_buffer, _)| storage_buffer.name == *name)
+                            .map(|&(_, buffer)| buffer)
+                            .unwrap_or_else(|| panic!("No buffer bound to `{}`!", name));
+                        (binding, buffer.allocated())
+                    })
+                    .collect();
+
+                let mut entries = vec![];
+                if let (Some(uniform_block), Some(uniform_buffer)) =
+                    (&reflection.uniform_block, &uniform_buffer)
+                {
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: uniform_block.binding,
+                        resource: uniform_buffer.as_entire_binding(),
+                    });
+                }
+
+                for (name, texture_info) in &reflection.textures {
+                    // Textures that the renderer doesn't use for this draw are left unbound in
+                    // OpenGL, but wgpu needs something in every slot.
+                    let texture = textures
+                        .iter()
+                        .find(|(texture_parameter, _)| texture_parameter.name == *name)
+                        .map(|&(_, texture)| texture)
+                        .unwrap_or(&self.dummy_texture);
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: texture_info.binding,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    });
+                    if let Some(sampler_binding) = texture_info.sampler_binding {
+                        let sampler_index = texture.sampling_flags.get().bits() as usize;
+                        entries.push(wgpu::BindGroupEntry {
+                            binding: sampler_binding,
+                            resource: wgpu::BindingResource::Sampler(&self.samplers[sampler_index]),
+                        });
+                    }
+                }
+
+                for (name, &binding) in &reflection.images {
+                    let texture = images
+                        .iter()
+                        .find(|(image_parameter, _, _)| image_parameter.name == *name)
+                        .map(|&(_, texture, _)| texture)
+                        .unwrap_or_else(|| panic!("No image bound to `{}`!", name));
+                    entries.push(wgpu::BindGroupEntry {
+                        binding,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    });
+                }
+
+                for (binding, buffer) in &storage_buffer_allocations {
+                    entries.push(wgpu::BindGroupEntry {
+                        binding: *binding,
+                        resource: buffer.as_entire_binding(),
+                    });
+                }
+
+                self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: bind_group_layout,
+                    entries: &entries,
+                })
+            })
+            .collect()
+    }
+
+    fn create_uniform_buffer(
+        &self,
+        uniform_block: &UniformBlockInfo,
+        uniforms: &[UniformBinding<WgpuUniform>],
+    ) -> wgpu::Buffer {
+        let mut data = vec![0; uniform_block.size];
+        for (uniform, uniform_data) in uniforms {
+            let offset = match uniform_block.offsets.get(&uniform.name) {
+                None => continue,
+                Some(&offset) => offset,
+            };
+            let bytes = uniform_data.as_bytes();
+            match *uniform_data {
+                UniformData::Mat2(_) => {
+                    // Under std140 rules, each column of a `mat2` is aligned to 16 bytes.
+                    data[offset..(offset + 8)].copy_from_slice(&bytes[0..8]);
+                    data[(offset + 16)..(offset + 24)].copy_from_slice(&bytes[8..16]);
+                }
+                _ => data[offset..(offset + bytes.len())].copy_from_slice(bytes),
+            }
+        }
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &data,
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+    }
+
+    fn draw<F>(&self, render_state: &RenderState<WgpuDevice>, draw: F)
+    where
+        F: FnOnce(&mut wgpu::RenderPass),
+    {
+        let pipeline = self.render_pipeline(render_state);
+        let bind_groups = self.create_bind_groups(
+            render_state.program,
+            render_state.uniforms,
+            render_state.textures,
+            render_state.images,
+            render_state.storage_buffers,
+        );
+        let vertex_array = render_state.vertex_array;
+        let vertex_buffers: Vec<_> = vertex_array
+            .vertex_buffers
+            .borrow()
+            .iter()
+            .map(|buffer| buffer.allocated())
+            .collect();
+        let index_buffer = vertex_array
+            .index_buffer
+            .borrow()
+            .as_ref()
+            .map(|buffer| buffer.allocated());
+
+        let clear_ops = &render_state.options.clear_ops;
+        let color_view = &self.render_target_color_texture(render_state.target).view;
+        let color_attachment = wgpu::RenderPassColorAttachment {
+            view: color_view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: match clear_ops.color {
+                    Some(color) => wgpu::LoadOp::Clear(wgpu::Color {
+                        r: color.r() as f64,
+                        g: color.g() as f64,
+                        b: color.b() as f64,
+                        a: color.a() as f64,
+                    }),
+                    None => wgpu::LoadOp::Load,
+                },
+                store: wgpu::StoreOp::Store,
+            },
+        };
+        let depth_stencil_attachment = self
+            .render_target_depth_stencil_view(render_state.target)
+            .map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: match clear_ops.depth {
+                        Some(depth) => wgpu::LoadOp::Clear(depth),
+                        None => wgpu::LoadOp::Load,
+                    },
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: match clear_ops.stencil {
+                        Some(stencil) => wgpu::LoadOp::Clear(stencil as u32),
+                        None => wgpu::LoadOp::Load,
+                    },
+                    store: wgpu::StoreOp::Store,
+                }),
+            });
+
+        self.with_command_encoder(|command_encoder| {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(color_attachment)],
+                depth_stencil_attachment,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            let viewport = render_state.viewport;
+            render_pass.set_viewport(
+                viewport.origin().x() as f32,
+                viewport.origin().y() as f32,
+                viewport.width() as f32,
+                viewport.height() as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_pipeline(&pipeline);
+            for (group_index, bind_group) in bind_groups.iter().enumerate() {
+                render_pass.set_bind_group(group_index as u32, bind_group, &[]);
+            }
+            for (buffer_index, vertex_buffer) in vertex_buffers.iter().enumerate() {
+                render_pass.set_vertex_buffer(buffer_index as u32, vertex_buffer.slice(..));
+            }
+            if let Some(ref index_buffer) = index_buffer {
+                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            }
+            if let Some(stencil) = render_state.options.stencil {
+                render_pass.set_stencil_reference(stencil.reference);
+            }
+
+            draw(&mut render_pass);
+        });
+    }
+
+    fn create_readback_buffer(&self, size: u64) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        })
+    }
+}
+
+impl Device for WgpuDevice {
+    type Buffer = WgpuBuffer;
+    type BufferDataReceiver = WgpuBufferDataReceiver;
+    type Fence = WgpuFence;
+    type Framebuffer = WgpuFramebuffer;
+    type ImageParameter = WgpuImageParameter;
+    type Program = WgpuProgram;
+    type Shader = WgpuShader;
+    type StorageBuffer = WgpuStorageBuffer;
+    type Texture = WgpuTexture;
+    type TextureDataReceiver = WgpuTextureDataReceiver;
+    type TextureParameter = WgpuTextureParameter;
+    type TimerQuery = WgpuTimerQuery;
+    type Uniform = WgpuUniform;
+    type VertexArray = WgpuVertexArray;
+    type VertexAttr = WgpuVertexAttr;
+
+    #[inline]
+    fn backend_name(&self) -> &'static str {
+        "wgpu"
+    }
+
+    #[inline]
+    fn device_name(&self) -> String {
+        self.adapter_info.name.clone()
+    }
+
+    // naga's SPIR-V frontend can't read the atomics in the D3D11-level compute shaders yet, so
+    // only the raster pipeline is available.
+    #[inline]
+    fn feature_level(&self) -> FeatureLevel {
+        FeatureLevel::D3D10
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        DeviceCapabilities {
+            max_texture_size: self.device.limits().max_texture_dimension_2d,
+            compute_shaders: false,
+            // naga's SPIR-V frontend drops the `Index` decoration on the second blend source, so
+            // subpixel text uses the two-pass fallback.
+            dual_source_blending: false,
+            // WebGPU guarantees 4x multisampling for renderable formats, and no more.
+            max_msaa_samples: 4,
+        }
+    }
+
+    fn supports_compressed_texture_format(&self, format: CompressedTextureFormat) -> bool {
+        let feature = match format {
+            CompressedTextureFormat::BC1
+            | CompressedTextureFormat::BC3
+            | CompressedTextureFormat::BC7 => wgpu::Features::TEXTURE_COMPRESSION_BC,
+            CompressedTextureFormat::ETC2RGB8 | CompressedTextureFormat::ETC2RGBA8 => {
+                wgpu::Features::TEXTURE_COMPRESSION_ETC2
+            }
+            CompressedTextureFormat::ASTC4x4 => wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+        };
+        self.device.features().contains(feature)
+    }
+
+    // wgpu additionally requires the size to be a multiple of the block size.
+    fn create_compressed_texture(
+        &self,
+        format: CompressedTextureFormat,
+        size: Vector2I,
+        data: &[u8],
+    ) -> Option<WgpuTexture> {
+        let block_size = format.block_size();
+        if !self.supports_compressed_texture_format(format)
+            || size.x() % block_size.x() != 0
+            || size.y() % block_size.y() != 0
+        {
+            return None;
+        }
+        assert_eq!(data.len(), format.data_size(size));
+
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: extent_from_size(size),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: format.to_wgpu_texture_format(),
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            data,
+        );
+        Some(WgpuTexture::new(texture))
+    }
+
+    fn push_debug_group(&self, name: &str) {
+        if let Some(ref mut command_encoder) = *self.command_encoder.borrow_mut() {
+            command_encoder.push_debug_group(name);
+        }
+        self.debug_groups.borrow_mut().push(name.to_owned());
+    }
+
+    fn pop_debug_group(&self) {
+        if self.debug_groups.borrow_mut().pop().is_none() {
+            return;
+        }
+        if let Some(ref mut command_encoder) = *self.command_encoder.borrow_mut() {
+            command_encoder.pop_debug_group();
+        }
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> WgpuTexture {
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::RENDER_ATTACHMENT;
+        match format {
+            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => {
+                usage |= wgpu::TextureUsages::STORAGE_BINDING
+            }
+            TextureFormat::R8 | TextureFormat::R16F => {}
+        }
+
+        WgpuTexture::new(self.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: extent_from_size(size),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: format.to_wgpu_texture_format(),
+            usage,
+            view_formats: &[],
+        }))
+    }
+
+    fn create_texture_from_data(
+        &self,
+        format: TextureFormat,
+        size: Vector2I,
+        data: TextureDataRef,
+    ) -> WgpuTexture {
+        let texture = self.create_texture(format, size);
+        self.upload_to_texture(&texture, RectI::new(Vector2I::default(), size), data);
+        texture
+    }
+
+    fn create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> WgpuShader {
+        self.try_create_shader(resources, name, kind)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_shader(
+        &self,
+        resources: &dyn ResourceLoader,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<WgpuShader, DeviceError> {
+        let (module, reflection) = load_shader(resources, name, kind, self.float32_filterable)?;
+        Ok(self.create_shader_from_module(name, module, reflection))
+    }
+
+    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> WgpuShader {
+        let (module, reflection) = parse_shader(name, source, kind, self.float32_filterable)
+            .unwrap_or_else(|error| panic!("{}", error));
+        self.create_shader_from_module(name, module, reflection)
+    }
+
+    fn create_vertex_array(&self) -> WgpuVertexArray {
+        WgpuVertexArray {
+            vertex_buffers: RefCell::new(vec![]),
+            index_buffer: RefCell::new(None),
+            attributes: RefCell::new(vec![]),
+        }
+    }
+
+    fn bind_buffer(
+        &self,
+        vertex_array: &WgpuVertexArray,
+        buffer: &WgpuBuffer,
+        target: BufferTarget,
+    ) {
+        match target {
+            BufferTarget::Vertex => vertex_array
+                .vertex_buffers
+                .borrow_mut()
+                .push((*buffer).clone()),
+            BufferTarget::Index => {
+                *vertex_array.index_buffer.borrow_mut() = Some((*buffer).clone())
+            }
+            _ => panic!("Buffers bound to vertex arrays must be vertex or index buffers!"),
+        }
+    }
+
+    fn create_program_from_shaders(
+        &self,
+        _: &dyn ResourceLoader,
+        name: &str,
+        shaders: ProgramKind<WgpuShader>,
+    ) -> WgpuProgram {
+        let bind_group_layouts: Vec<_> = match shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => vec![
+                self.create_bind_group_layout(name, vertex),
+                self.create_bind_group_layout(name, fragment),
+            ],
+            ProgramKind::Compute(ref compute) => {
+                vec![self.create_bind_group_layout(name, compute)]
+            }
+        };
+        let bind_group_layout_refs: Vec<_> = bind_group_layouts.iter().collect();
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(name),
+                bind_group_layouts: &bind_group_layout_refs,
+                push_constant_ranges: &[],
+            });
+
+        let compute_pipeline = match shaders {
+            ProgramKind::Compute(ref compute) => Some(self.device.create_compute_pipeline(
+                &wgpu::ComputePipelineDescriptor {
+                    label: Some(name),
+                    layout: Some(&pipeline_layout),
+                    module: &compute.module,
+                    entry_point: "main",
+                },
+            )),
+            ProgramKind::Raster { .. } => None,
+        };
+
+        WgpuProgram {
+            shaders,
+            bind_group_layouts,
+            pipeline_layout,
+            compute_pipeline,
+            render_pipelines: RefCell::new(HashMap::new()),
+        }
+    }
+
+    // The local size is baked into the SPIR-V, so there's nothing to do here.
+    fn set_compute_program_local_size(&self, _: &mut WgpuProgram, _: ComputeDimensions) {}
+
+    fn get_vertex_attr(&self, program: &WgpuProgram, name: &str) -> Option<WgpuVertexAttr> {
+        let vertex_shader = match program.shaders {
+            ProgramKind::Raster { ref vertex, .. } => vertex,
+            ProgramKind::Compute(_) => unreachable!(),
+        };
+        vertex_shader
+            .reflection
+            .vertex_inputs
+            .get(&format!("a{}", name))
+            .map(|&location| WgpuVertexAttr { location })
+    }
+
+    fn get_uniform(&self, _: &WgpuProgram, name: &str) -> WgpuUniform {
+        WgpuUniform {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_texture_parameter(&self, _: &WgpuProgram, name: &str) -> WgpuTextureParameter {
+        WgpuTextureParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_image_parameter(&self, _: &WgpuProgram, name: &str) -> WgpuImageParameter {
+        WgpuImageParameter {
+            name: format!("u{}", name),
+        }
+    }
+
+    fn get_storage_buffer(&self, _: &WgpuProgram, name: &str, _: u32) -> WgpuStorageBuffer {
+        WgpuStorageBuffer {
+            name: format!("b{}", name),
+        }
+    }
+
+    fn configure_vertex_attr(
+        &self,
+        vertex_array: &WgpuVertexArray,
+        attr: &WgpuVertexAttr,
+        descriptor: &VertexAttrDescriptor,
+    ) {
+        debug_assert_ne!(descriptor.stride, 0);
+        debug_assert!(descriptor.divisor <= 1);
+
+        let format = match (descriptor.class, descriptor.attr_type, descriptor.size) {
+            (VertexAttrClass::Int, VertexAttrType::I8, 2) => wgpu::VertexFormat::Sint8x2,
+            (VertexAttrClass::Int, VertexAttrType::I8, 4) => wgpu::VertexFormat::Sint8x4,
+            (VertexAttrClass::Int, VertexAttrType::U8, 2) => wgpu::VertexFormat::Uint8x2,
+            (VertexAttrClass::Int, VertexAttrType::U8, 4) => wgpu::VertexFormat::Uint8x4,
+            // wgpu has no single-component 16-bit formats, so these read the following component
+            // too. The shader only uses the first.
+            (VertexAttrClass::Int, VertexAttrType::I16, 1)
+            | (VertexAttrClass::Int, VertexAttrType::I16, 2) => wgpu::VertexFormat::Sint16x2,
+            (VertexAttrClass::Int, VertexAttrType::I16, 4) => wgpu::VertexFormat::Sint16x4,
+            (VertexAttrClass::Int, VertexAttrType::U16, 1)
+            | (VertexAttrClass::Int, VertexAttrType::U16, 2) => wgpu::VertexFormat::Uint16x2,
+            (VertexAttrClass::Int, VertexAttrType::U16, 4) => wgpu::VertexFormat::Uint16x4,
+            (VertexAttrClass::Int, VertexAttrType::I32, 1) => wgpu::VertexFormat::Sint32,
+            (VertexAttrClass::Int, VertexAttrType::I32, 2) => wgpu::VertexFormat::Sint32x2,
+            (VertexAttrClass::Int, VertexAttrType::I32, 3) => wgpu::VertexFormat::Sint32x3,
+            (VertexAttrClass::Int, VertexAttrType::I32, 4) => wgpu::VertexFormat::Sint32x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 2) => wgpu::VertexFormat::Unorm8x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U8, 4) => wgpu::VertexFormat::Unorm8x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 2) => wgpu::VertexFormat::Snorm8x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I8, 4) => wgpu::VertexFormat::Snorm8x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 2) => wgpu::VertexFormat::Unorm16x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::U16, 4) => wgpu::VertexFormat::Unorm16x4,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 2) => wgpu::VertexFormat::Snorm16x2,
+            (VertexAttrClass::FloatNorm, VertexAttrType::I16, 4) => wgpu::VertexFormat::Snorm16x4,
+            (VertexAttrClass::Float, VertexAttrType::F32, 1) => wgpu::VertexFormat::Float32,
+            (VertexAttrClass::Float, VertexAttrType::F32, 2) => wgpu::VertexFormat::Float32x2,
+            (VertexAttrClass::Float, VertexAttrType::F32, 3) => wgpu::VertexFormat::Float32x3,
+            (VertexAttrClass::Float, VertexAttrType::F32, 4) => wgpu::VertexFormat::Float32x4,
+            _ => panic!("Unsupported vertex class/type/size combination!"),
+        };
+
+        let step_mode = if descriptor.divisor == 0 {
+            wgpu::VertexStepMode::Vertex
+        } else {
+            wgpu::VertexStepMode::Instance
+        };
+
+        let mut attributes = vertex_array.attributes.borrow_mut();
+        attributes.retain(|info| info.attribute.shader_location != attr.location);
+        attributes.push(WgpuVertexAttrInfo {
+            buffer_index: descriptor.buffer_index,
+            stride: descriptor.stride as u64,
+            step_mode,
+            attribute: wgpu::VertexAttribute {
+                format,
+                offset: descriptor.offset as u64,
+                shader_location: attr.location,
+            },
+        });
+    }
+
+    fn create_framebuffer(&self, texture: WgpuTexture) -> WgpuFramebuffer {
+        WgpuFramebuffer(texture)
+    }
+
+    fn create_buffer(&self, _: BufferUploadMode) -> WgpuBuffer {
+        WgpuBuffer {
+            allocation: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn allocate_buffer<T>(&self, buffer: &WgpuBuffer, data: BufferData<T>, target: BufferTarget) {
+        let byte_size = match data {
+            BufferData::Uninitialized(size) => size * mem::size_of::<T>(),
+            BufferData::Memory(slice) => slice.len() * mem::size_of::<T>(),
+        };
+
+        let new_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: align_buffer_size(byte_size.max(1)) as u64,
+            usage: BUFFER_USAGES,
+            mapped_at_creation: false,
+        });
+        *buffer.allocation.borrow_mut() = Some(Rc::new(new_buffer));
+
+        if let BufferData::Memory(slice) = data {
+            self.upload_to_buffer(buffer, 0, slice, target);
+        }
+    }
+
+    fn upload_to_buffer<T>(
+        &self,
+        buffer: &WgpuBuffer,
+        position: usize,
+        data: &[T],
+        _: BufferTarget,
+    ) {
+        let bytes = slice_as_bytes(data);
+        if bytes.is_empty() {
+            return;
+        }
+
+        self.flush();
+        let allocation = buffer.allocated();
+        if bytes.len() % wgpu::COPY_BUFFER_ALIGNMENT as usize == 0 {
+            self.queue.write_buffer(&allocation, position as u64, bytes);
+        } else {
+            let mut padded_bytes = bytes.to_vec();
+            padded_bytes.resize(align_buffer_size(bytes.len()), 0);
+            self.queue
+                .write_buffer(&allocation, position as u64, &padded_bytes);
+        }
+    }
+
+    #[inline]
+    fn framebuffer_texture<'f>(&self, framebuffer: &'f WgpuFramebuffer) -> &'f WgpuTexture {
+        &framebuffer.0
+    }
+
+    #[inline]
+    fn destroy_framebuffer(&self, framebuffer: WgpuFramebuffer) -> WgpuTexture {
+        framebuffer.0
+    }
+
+    fn texture_format(&self, texture: &WgpuTexture) -> TextureFormat {
+        TextureFormat::from_wgpu_texture_format(texture.texture.format())
+            .expect("Unexpected texture format!")
+    }
+
+    #[inline]
+    fn texture_size(&self, texture: &WgpuTexture) -> Vector2I {
+        texture.size
+    }
+
+    #[inline]
+    fn set_texture_sampling_mode(&self, texture: &WgpuTexture, flags: TextureSamplingFlags) {
+        texture.sampling_flags.set(flags);
+    }
+
+    fn upload_to_texture(&self, texture: &WgpuTexture, rect: RectI, data: TextureDataRef) {
+        let format = self.texture_format(texture);
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let data_ptr = data.check_and_extract_data_ptr(rect.size(), format);
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                data_ptr as *const u8,
+                rect.area() as usize * bytes_per_pixel,
+            )
+        };
+
+        self.flush();
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: origin_from_point(rect.origin()),
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytes,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some((rect.width() as usize * bytes_per_pixel) as u32),
+                rows_per_image: None,
+            },
+            extent_from_size(rect.size()),
+        );
+    }
+
+    fn read_pixels(
+        &self,
+        target: &RenderTarget<WgpuDevice>,
+        viewport: RectI,
+    ) -> WgpuTextureDataReceiver {
+        let texture = self.render_target_color_texture(target);
+        let wgpu_format = texture.texture.format();
+        let format =
+            TextureFormat::from_wgpu_texture_format(wgpu_format).expect("Unexpected format!");
+
+        // Rows copied out of a texture must be aligned to 256 bytes.
+        let row_size = viewport.width() as usize * format.bytes_per_pixel();
+        let row_alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+        let padded_row_size = (row_size + row_alignment - 1) / row_alignment * row_alignment;
+        let buffer =
+            self.create_readback_buffer((padded_row_size * viewport.height() as usize) as u64);
+
+        self.with_command_encoder(|command_encoder| {
+            command_encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture: &texture.texture,
+                    mip_level: 0,
+                    origin: origin_from_point(viewport.origin()),
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_row_size as u32),
+                        rows_per_image: Some(viewport.height() as u32),
+                    },
+                },
+                extent_from_size(viewport.size()),
+            );
+        });
+        self.flush();
+
+        let mapped = map_buffer_for_reading(&buffer);
+        WgpuTextureDataReceiver {
+            buffer,
+            mapped,
+            format,
+            swizzle: match wgpu_format {
+                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+                _ => false,
+            },
+            size: viewport.size(),
+            padded_row_size,
+            data: RefCell::new(None),
+        }
+    }
+
+    fn read_buffer(
+        &self,
+        buffer: &WgpuBuffer,
+        _: BufferTarget,
+        range: Range<usize>,
+    ) -> WgpuBufferDataReceiver {
+        let size = range.end - range.start;
+        let copy_size = align_buffer_size(size.max(1));
+        let readback_buffer = self.create_readback_buffer(copy_size as u64);
+
+        let source_buffer = buffer.allocated();
+        self.with_command_encoder(|command_encoder| {
+            command_encoder.copy_buffer_to_buffer(
+                &source_buffer,
+                range.start as u64,
+                &readback_buffer,
+                0,
+                copy_size as u64,
+            );
+        });
+        self.flush();
+
+        let mapped = map_buffer_for_reading(&readback_buffer);
+        WgpuBufferDataReceiver {
+            buffer: readback_buffer,
+            mapped,
+            size,
+            data: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    fn begin_commands(&self) {
+        // Commands are recorded lazily, so nothing to do here.
+    }
+
+    fn end_commands(&self) {
+        self.flush();
+    }
+
+    fn draw_arrays(&self, index_count: u32, render_state: &RenderState<WgpuDevice>) {
+        self.draw(render_state, |render_pass| {
+            render_pass.draw(0..index_count, 0..1)
+        });
+    }
+
+    fn draw_elements(&self, index_count: u32, render_state: &RenderState<WgpuDevice>) {
+        self.draw(render_state, |render_pass| {
+            render_pass.draw_indexed(0..index_count, 0, 0..1)
+        });
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        render_state: &RenderState<WgpuDevice>,
+    ) {
+        self.draw(render_state, |render_pass| {
+            render_pass.draw_indexed(0..index_count, 0, 0..instance_count)
+        });
+    }
+
+    fn dispatch_compute(
+        &self,
+        dimensions: ComputeDimensions,
+        compute_state: &ComputeState<WgpuDevice>,
+    ) {
+        let program = compute_state.program;
+        let pipeline = program
+            .compute_pipeline
+            .as_ref()
+            .expect("Can't dispatch a raster program!");
+        let bind_groups = self.create_bind_groups(
+            program,
+            compute_state.uniforms,
+            compute_state.textures,
+            compute_state.images,
+            compute_state.storage_buffers,
+        );
+
+        self.with_command_encoder(|command_encoder| {
+            let mut compute_pass =
+                command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: None,
+                    timestamp_writes: None,
+                });
+            compute_pass.set_pipeline(pipeline);
+            for (group_index, bind_group) in bind_groups.iter().enumerate() {
+                compute_pass.set_bind_group(group_index as u32, bind_group, &[]);
+            }
+            compute_pass.dispatch_workgroups(dimensions.x, dimensions.y, dimensions.z);
+        });
+    }
+
+    fn add_fence(&self) -> WgpuFence {
+        WgpuFence(self.flush())
+    }
+
+    fn wait_for_fence(&self, fence: &WgpuFence) {
+        self.device
+            .poll(wgpu::Maintain::WaitForSubmissionIndex(fence.0.clone()));
+    }
+
+    fn create_timer_query(&self) -> WgpuTimerQuery {
+        WgpuTimerQuery(Arc::new(Mutex::new(WgpuTimerQueryTimes::default())))
+    }
+
+    // wgpu timestamp queries aren't available everywhere, so timer queries measure the wall-clock
+    // time between the GPU finishing the work submitted before each end of the query.
+    fn begin_timer_query(&self, query: &WgpuTimerQuery) {
+        self.flush();
+        let times = query.0.clone();
+        self.queue.on_submitted_work_done(move || {
+            times.lock().unwrap().start_time = Some(Instant::now());
+        });
+    }
+
+    fn end_timer_query(&self, query: &WgpuTimerQuery) {
+        self.flush();
+        let times = query.0.clone();
+        self.queue.on_submitted_work_done(move || {
+            times.lock().unwrap().end_time = Some(Instant::now());
+        });
+    }
+
+    fn try_recv_timer_query(&self, query: &WgpuTimerQuery) -> Option<Duration> {
+        self.device.poll(wgpu::Maintain::Poll);
+        let times = query.0.lock().unwrap();
+        match (times.start_time, times.end_time) {
+            (Some(start_time), Some(end_time)) => Some(end_time - start_time),
+            _ => None,
+        }
+    }
+
+    fn recv_timer_query(&self, query: &WgpuTimerQuery) -> Duration {
+        loop {
+            if let Some(duration) = self.try_recv_timer_query(query) {
+                return duration;
+            }
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+
+    fn try_recv_texture_data(&self, receiver: &WgpuTextureDataReceiver) -> Option<TextureData> {
+        self.device.poll(wgpu::Maintain::Poll);
+        receiver.data()
+    }
+
+    fn recv_texture_data(&self, receiver: &WgpuTextureDataReceiver) -> TextureData {
+        loop {
+            if let Some(data) = receiver.data() {
+                return data;
+            }
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+
+    fn try_recv_buffer(&self, receiver: &WgpuBufferDataReceiver) -> Option<Vec<u8>> {
+        self.device.poll(wgpu::Maintain::Poll);
+        receiver.data()
+    }
+
+    fn recv_buffer(&self, receiver: &WgpuBufferDataReceiver) -> Vec<u8> {
+        loop {
+            if let Some(data) = receiver.data() {
+                return data;
+            }
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+}
+
+impl WgpuProgram {
+    // The shaders in bind group order.
+    fn shaders(&self) -> Vec<&WgpuShader> {
+        match self.shaders {
+            ProgramKind::Raster {
+                ref vertex,
+                ref fragment,
+            } => vec![vertex, fragment],
+            ProgramKind::Compute(ref compute) => vec![compute],
+        }
+    }
+}
+
+impl WgpuTexture {
+    fn new(texture: wgpu::Texture) -> WgpuTexture {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let size = vec2i(texture.width() as i32, texture.height() as i32);
+        WgpuTexture {
+            texture,
+            view,
+            size,
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+        }
+    }
+
+    #[inline]
+    pub fn wgpu_texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+}
+
+impl WgpuBuffer {
+    fn allocated(&self) -> Rc<wgpu::Buffer> {
+        (*self.allocation.borrow())
+            .clone()
+            .expect("Buffer hasn't been allocated!")
+    }
+}
+
+impl WgpuVertexArray {
+    fn buffer_layouts(&self) -> Vec<VertexBufferLayoutKey> {
+        let attributes = self.attributes.borrow();
+        (0..self.vertex_buffers.borrow().len() as u32)
+            .map(|buffer_index| {
+                let mut layout = VertexBufferLayoutKey {
+                    stride: 0,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: vec![],
+                };
+                for info in attributes
+                    .iter()
+                    .filter(|info| info.buffer_index == buffer_index)
+                {
+                    layout.stride = info.stride;
+                    layout.step_mode = info.step_mode;
+                    layout.attributes.push(info.attribute);
+                }
+                layout
+            })
+            .collect()
+    }
+}
+
+impl WgpuTextureDataReceiver {
+    fn data(&self) -> Option<TextureData> {
+        if let Some(ref data) = *self.data.borrow() {
+            return Some((*data).clone());
+        }
+        if !self.mapped.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let row_size = self.size.x() as usize * self.format.bytes_per_pixel();
+        let mut bytes = Vec::with_capacity(row_size * self.size.y() as usize);
+        {
+            let mapped_range = self.buffer.slice(..).get_mapped_range();
+            for row in 0..(self.size.y() as usize) {
+                let row_start = row * self.padded_row_size;
+                bytes.extend_from_slice(&mapped_range[row_start..(row_start + row_size)]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.swizzle {
+            for pixel in bytes.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let data = match self.format {
+            TextureFormat::R8 | TextureFormat::RGBA8 => TextureData::U8(bytes),
+            TextureFormat::R16F | TextureFormat::RGBA16F => TextureData::F16(
+                bytes
+                    .chunks(2)
+                    .map(|half| f16::from_bits(u16::from_ne_bytes([half[0], half[1]])))
+                    .collect(),
+            ),
+            TextureFormat::RGBA32F => TextureData::F32(
+                bytes
+                    .chunks(4)
+                    .map(|float| f32::from_ne_bytes([float[0], float[1], float[2], float[3]]))
+                    .collect(),
+            ),
+        };
+        *self.data.borrow_mut() = Some(data.clone());
+        Some(data)
+    }
+}
+
+impl WgpuBufferDataReceiver {
+    fn data(&self) -> Option<Vec<u8>> {
+        if let Some(ref data) = *self.data.borrow() {
+            return Some((*data).clone());
+        }
+        if !self.mapped.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let data = self.buffer.slice(..).get_mapped_range()[0..self.size].to_vec();
+        self.buffer.unmap();
+        *self.data.borrow_mut() = Some(data.clone());
+        Some(data)
+    }
+}
+
+// Loads a shader from `shaders/spirv` and reflects it, ready to be passed to wgpu.
+fn load_shader(
+    resources: &dyn ResourceLoader,
+    name: &str,
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> Result<(naga::Module, ShaderReflection), DeviceError> {
+    let suffix = match kind {
+        ShaderKind::Vertex => 'v',
+        ShaderKind::Fragment => 'f',
+        ShaderKind::Compute => 'c',
+    };
+    let path = format!("shaders/spirv/{}.{}s.spv", name, suffix);
+    match resources.slurp(&path) {
+        Ok(source) => parse_shader(name, &source, kind, float32_filterable),
+        Err(error) => Err(DeviceError::ResourceLoad { path, error }),
+    }
+}
+
+fn parse_shader(
+    name: &str,
+    source: &[u8],
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> Result<(naga::Module, ShaderReflection), DeviceError> {
+    let options = spv_frontend::Options {
+        adjust_coordinate_space: false,
+        strict_capabilities: false,
+        block_ctx_dump_prefix: None,
+    };
+    let mut module = match spv_frontend::parse_u8_slice(source, &options) {
+        Ok(module) => module,
+        Err(error) => {
+            return Err(DeviceError::ShaderCompile {
+                name: name.to_owned(),
+                kind,
+                log: format!("{:?}", error),
+            })
+        }
+    };
+
+    let reflection = reflect_module(&mut module, kind, float32_filterable);
+    Ok((module, reflection))
+}
+
+// Reflects the resources that the shader uses and assigns them bindings in the bind group for its
+// stage.
+fn reflect_module(
+    module: &mut naga::Module,
+    kind: ShaderKind,
+    float32_filterable: bool,
+) -> ShaderReflection {
+    let (group, visibility) = match kind {
+        ShaderKind::Vertex => (VERTEX_BIND_GROUP, wgpu::ShaderStages::VERTEX),
+        ShaderKind::Fragment => (FRAGMENT_BIND_GROUP, wgpu::ShaderStages::FRAGMENT),
+        ShaderKind::Compute => (VERTEX_BIND_GROUP, wgpu::ShaderStages::COMPUTE),
+    };
+
+    let mut reflection = ShaderReflection::default();
+
+    // `spirv-opt --split-combined-image-sampler` leaves each texture and its sampler at the same
+    // binding, which is how they're paired up.
+    let mut texture_names_by_binding = HashMap::new();
+    let mut sampler_bindings = vec![];
+
+    let mut next_binding = 0;
+    for (_, global_variable) in module.global_variables.iter_mut() {
+        let original_binding = match global_variable.binding {
+            None => continue,
+            Some(ref binding) => binding.clone(),
+        };
+        let binding = next_binding;
+        next_binding += 1;
+        global_variable.binding = Some(ResourceBinding { group, binding });
+
+        let ty = &module.types[global_variable.ty];
+        let name = global_variable.name.clone().unwrap_or_default();
+        let binding_type = match (global_variable.space, &ty.inner) {
+            (AddressSpace::Uniform, &TypeInner::Struct { ref members, span }) => {
+                reflection.uniform_block = Some(UniformBlockInfo {
+                    binding,
+                    size: span as usize,
+                    offsets: members
+                        .iter()
+                        .filter_map(|member| {
+                            let name = member.name.clone()?;
+                            Some((name, member.offset as usize))
+                        })
+                        .collect(),
+                });
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                }
+            }
+            (AddressSpace::Storage { access }, _) => {
+                // Blocks like `buffer bFills { ... }` have no instance name, so fall back to the
+                // name of the block.
+                let name = match ty.name {
+                    Some(ref type_name) if type_name.starts_with('b') => type_name.clone(),
+                    _ => name,
+                };
+                reflection.storage_buffers.insert(name, binding);
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage {
+                        read_only: !access.contains(StorageAccess::STORE),
+                    },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                }
+            }
+            (
+                AddressSpace::Handle,
+                &TypeInner::Image {
+                    class: ImageClass::Sampled { kind, .. },
+                    ..
+                },
+            ) => {
+                texture_names_by_binding.insert(original_binding, name.clone());
+                reflection.textures.insert(
+                    name,
+                    TextureInfo {
+                        binding,
+                        sampler_binding: None,
+                    },
+                );
+                wgpu::BindingType::Texture {
+                    sample_type: match kind {
+                        ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                        ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                        _ => wgpu::TextureSampleType::Float {
+                            filterable: float32_filterable,
+                        },
+                    },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                }
+            }
+            (
+                AddressSpace::Handle,
+                &TypeInner::Image {
+                    class: ImageClass::Storage { format, access },
+                    ..
+                },
+            ) => {
+                reflection.images.insert(name, binding);
+                wgpu::BindingType::StorageTexture {
+                    access: if !access.contains(StorageAccess::STORE) {
+                        wgpu::StorageTextureAccess::ReadOnly
+                    } else if !access.contains(StorageAccess::LOAD) {
+                        wgpu::StorageTextureAccess::WriteOnly
+                    } else {
+                        wgpu::StorageTextureAccess::ReadWrite
+                    },
+                    format: storage_format_to_wgpu_texture_format(format),
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                }
+            }
+            (AddressSpace::Handle, &TypeInner::Sampler { .. }) => {
+                sampler_bindings.push((original_binding, binding));
+                wgpu::BindingType::Sampler(if float32_filterable {
+                    wgpu::SamplerBindingType::Filtering
+                } else {
+                    wgpu::SamplerBindingType::NonFiltering
+                })
+            }
+            _ => panic!("Unsupported resource `{}` in shader!", name),
+        };
+
+        reflection.layout_entries.push(wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: binding_type,
+            count: None,
+        });
+    }
+
+    for (original_binding, sampler_binding) in sampler_bindings {
+        if let Some(texture_name) = texture_names_by_binding.get(&original_binding) {
+            let texture_info = reflection.textures.get_mut(texture_name).unwrap();
+            texture_info.sampler_binding = Some(sampler_binding);
+        }
+    }
+
+    if kind == ShaderKind::Vertex {
+        for entry_point in &module.entry_points {
+            for argument in &entry_point.function.arguments {
+                if let (Some(ref name), Some(Binding::Location { location, .. })) =
+                    (&argument.name, &argument.binding)
+                {
+                    reflection.vertex_inputs.insert(name.clone(), *location);
+                }
+            }
+        }
+    }
+
+    reflection
+}
+
+fn create_depth_stencil_texture(device: &wgpu::Device, size: Vector2I) -> WgpuTexture {
+    WgpuTexture::new(device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("depth-stencil"),
+        size: extent_from_size(size),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_STENCIL_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    }))
+}
+
+fn map_buffer_for_reading(buffer: &wgpu::Buffer) -> Arc<AtomicBool> {
+    let mapped = Arc::new(AtomicBool::new(false));
+    let captured_mapped = mapped.clone();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            result.expect("Failed to map buffer for reading!");
+            captured_mapped.store(true, Ordering::Release);
+        });
+    mapped
+}
+
+fn storage_format_to_wgpu_texture_format(format: StorageFormat) -> wgpu::TextureFormat {
+    match format {
+        StorageFormat::R8Unorm => wgpu::TextureFormat::R8Unorm,
+        StorageFormat::R16Float => wgpu::TextureFormat::R16Float,
+        StorageFormat::R32Float => wgpu::TextureFormat::R32Float,
+        StorageFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+        StorageFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+        StorageFormat::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+        _ => panic!("Unsupported storage image format {:?}!", format),
+    }
+}
+
+#[inline]
+fn align_buffer_size(size: usize) -> usize {
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT as usize;
+    (size + alignment - 1) / alignment * alignment
+}
+
+#[inline]
+fn extent_from_size(size: Vector2I) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: size.x() as u32,
+        height: size.y() as u32,
+        depth_or_array_layers: 1,
+    }
+}
+
+#[inline]
+fn origin_from_point(point: Vector2I) -> wgpu::Origin3d {
+    wgpu::Origin3d {
+        x: point.x() as u32,
+        y: point.y() as u32,
+        z: 0,
+    }
+}
+
+fn slice_as_bytes<T>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * mem::size_of::<T>()) }
+}
+
+// Extension traits
+
+trait BlendFactorExt {
+    fn to_wgpu_blend_factor(self) -> wgpu::BlendFactor;
+}
+
+impl BlendFactorExt for BlendFactor {
+    #[inline]
+    fn to_wgpu_blend_factor(self) -> wgpu::BlendFactor {
+        match self {
+            BlendFactor::Zero => wgpu::BlendFactor::Zero,
+            BlendFactor::One => wgpu::BlendFactor::One,
+            BlendFactor::SrcAlpha => wgpu::BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha => wgpu::BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::DestAlpha => wgpu::BlendFactor::DstAlpha,
+            BlendFactor::OneMinusDestAlpha => wgpu::BlendFactor::OneMinusDstAlpha,
+            BlendFactor::DestColor => wgpu::BlendFactor::Dst,
+            BlendFactor::OneMinusSrcColor => wgpu::BlendFactor::OneMinusSrc,
+            BlendFactor::OneMinusSrc1Color => wgpu::BlendFactor::OneMinusSrc1,
+        }
+    }
+}
+
+trait BlendOpExt {
+    fn to_wgpu_blend_operation(self) -> wgpu::BlendOperation;
+}
+
+impl BlendOpExt for BlendOp {
+    #[inline]
+    fn to_wgpu_blend_operation(self) -> wgpu::BlendOperation {
+        match self {
+            BlendOp::Add => wgpu::BlendOperation::Add,
+            BlendOp::Subtract => wgpu::BlendOperation::Subtract,
+            BlendOp::ReverseSubtract => wgpu::BlendOperation::ReverseSubtract,
+            BlendOp::Min => wgpu::BlendOperation::Min,
+            BlendOp::Max => wgpu::BlendOperation::Max,
+        }
+    }
+}
+
+trait BlendStateExt {
+    fn to_wgpu_blend_state(self) -> wgpu::BlendState;
+}
+
+impl BlendStateExt for BlendState {
+    fn to_wgpu_blend_state(self) -> wgpu::BlendState {
+        let operation = self.op.to_wgpu_blend_operation();
+        let component = |src_factor: BlendFactor, dst_factor: BlendFactor| match self.op {
+            // wgpu requires the factors of min and max blends to be one.
+            BlendOp::Min | BlendOp::Max => wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation,
+            },
+            BlendOp::Add | BlendOp::Subtract | BlendOp::ReverseSubtract => wgpu::BlendComponent {
+                src_factor: src_factor.to_wgpu_blend_factor(),
+                dst_factor: dst_factor.to_wgpu_blend_factor(),
+                operation,
+            },
+        };
+        wgpu::BlendState {
+            color: component(self.src_rgb_factor, self.dest_rgb_factor),
+            alpha: component(self.src_alpha_factor, self.dest_alpha_factor),
+        }
+    }
+}
+
+trait CompareFunctionExt {
+    fn to_wgpu_compare_function(self) -> wgpu::CompareFunction;
+}
+
+impl CompareFunctionExt for DepthFunc {
+    #[inline]
+    fn to_wgpu_compare_function(self) -> wgpu::CompareFunction {
+        match self {
+            DepthFunc::Less => wgpu::CompareFunction::Less,
+            DepthFunc::LessEqual => wgpu::CompareFunction::LessEqual,
+            DepthFunc::Always => wgpu::CompareFunction::Always,
+        }
+    }
+}
+
+impl CompareFunctionExt for StencilFunc {
+    #[inline]
+    fn to_wgpu_compare_function(self) -> wgpu::CompareFunction {
+        match self {
+            StencilFunc::Always => wgpu::CompareFunction::Always,
+            StencilFunc::Equal => wgpu::CompareFunction::Equal,
+        }
+    }
+}
+
+trait PrimitiveExt {
+    fn to_wgpu_primitive_topology(self) -> wgpu::PrimitiveTopology;
+}
+
+impl PrimitiveExt for Primitive {
+    #[inline]
+    fn to_wgpu_primitive_topology(self) -> wgpu::PrimitiveTopology {
+        match self {
+            Primitive::Triangles => wgpu::PrimitiveTopology::TriangleList,
+            Primitive::Lines => wgpu::PrimitiveTopology::LineList,
+        }
+    }
+}
+
+trait TextureFormatExt: Sized {
+    fn to_wgpu_texture_format(self) -> wgpu::TextureFormat;
+    fn from_wgpu_texture_format(wgpu_format: wgpu::TextureFormat) -> Option<Self>;
+}
+
+impl TextureFormatExt for TextureFormat {
+    #[inline]
+    fn to_wgpu_texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            TextureFormat::R8 => wgpu::TextureFormat::R8Unorm,
+            TextureFormat::R16F => wgpu::TextureFormat::R16Float,
+            TextureFormat::RGBA8 => wgpu::TextureFormat::Rgba8Unorm,
+            TextureFormat::RGBA16F => wgpu::TextureFormat::Rgba16Float,
+            TextureFormat::RGBA32F => wgpu::TextureFormat::Rgba32Float,
+        }
+    }
+
+    fn from_wgpu_texture_format(wgpu_format: wgpu::TextureFormat) -> Option<TextureFormat> {
+        match wgpu_format {
+            wgpu::TextureFormat::R8Unorm => Some(TextureFormat::R8),
+            wgpu::TextureFormat::R16Float => Some(TextureFormat::R16F),
+            wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb => Some(TextureFormat::RGBA8),
+            wgpu::TextureFormat::Rgba16Float => Some(TextureFormat::RGBA16F),
+            wgpu::TextureFormat::Rgba32Float => Some(TextureFormat::RGBA32F),
+            // Compressed textures sample as RGBA.
+            wgpu::TextureFormat::Bc1RgbaUnorm
+            | wgpu::TextureFormat::Bc3RgbaUnorm
+            | wgpu::TextureFormat::Bc7RgbaUnorm
+            | wgpu::TextureFormat::Etc2Rgb8Unorm
+            | wgpu::TextureFormat::Etc2Rgba8Unorm
+            | wgpu::TextureFormat::Astc {
+                block: wgpu::AstcBlock::B4x4,
+                channel: wgpu::AstcChannel::Unorm,
+            } => Some(TextureFormat::RGBA8),
+            _ => None,
+        }
+    }
+}
+
+trait CompressedTextureFormatExt {
+    fn to_wgpu_texture_format(self) -> wgpu::TextureFormat;
+}
+
+impl CompressedTextureFormatExt for CompressedTextureFormat {
+    fn to_wgpu_texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            CompressedTextureFormat::BC1 => wgpu::TextureFormat::Bc1RgbaUnorm,
+            CompressedTextureFormat::BC3 => wgpu::TextureFormat::Bc3RgbaUnorm,
+            CompressedTextureFormat::BC7 => wgpu::TextureFormat::Bc7RgbaUnorm,
+            CompressedTextureFormat::ETC2RGB8 => wgpu::TextureFormat::Etc2Rgb8Unorm,
+            CompressedTextureFormat::ETC2RGBA8 => wgpu::TextureFormat::Etc2Rgba8Unorm,
+            CompressedTextureFormat::ASTC4x4 => wgpu::TextureFormat::Astc {
+                block: wgpu::AstcBlock::B4x4,
+                channel: wgpu::AstcChannel::Unorm,
+            },
+        }
+    }
+}
+
+trait UniformDataExt {
+    fn as_bytes(&self) -> &[u8];
+}
+
+impl UniformDataExt for UniformData {
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            match *self {
+                UniformData::Float(ref data) => {
+                    slice::from_raw_parts(data as *const f32 as *const u8, 4 * 1)
+                }
+                UniformData::IVec2(ref data) => {
+                    slice::from_raw_parts(data as *const I32x2 as *const u8, 4 * 2)
+                }
+                UniformData::IVec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Int(ref data) => {
+                    slice::from_raw_parts(data as *const i32 as *const u8, 4 * 1)
+                }
+                UniformData::Mat2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+                UniformData::Mat4(ref data) => {
+                    slice::from_raw_parts(&data[0] as *const F32x4 as *const u8, 4 * 16)
+                }
+                UniformData::Vec2(ref data) => {
+                    slice::from_raw_parts(data as *const F32x2 as *const u8, 4 * 2)
+                }
+                UniformData::Vec3(ref data) => {
+                    slice::from_raw_parts(data.as_ptr() as *const u8, 4 * 3)
+                }
+                UniformData::Vec4(ref data) => {
+                    slice::from_raw_parts(data as *const F32x4 as *const u8, 4 * 4)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::load_shader;
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+    use pathfinder_gpu::{ProgramKind, ShaderKind};
+    use pathfinder_resources::embedded::EmbeddedResourceLoader;
+
+    // Every program that the renderer, the debug UI, and the demo create at the D3D10 level.
+    static PROGRAMS: [ProgramKind<&str>; 13] = [
+        ProgramKind::Raster {
+            vertex: "blit",
+            fragment: "blit",
+        },
+        ProgramKind::Raster {
+            vertex: "blit",
+            fragment: "post_process",
+        },
+        ProgramKind::Raster {
+            vertex: "clear",
+            fragment: "clear",
+        },
+        ProgramKind::Raster {
+            vertex: "stencil",
+            fragment: "stencil",
+        },
+        ProgramKind::Raster {
+            vertex: "reproject",
+            fragment: "reproject",
+        },
+        ProgramKind::Raster {
+            vertex: "d3d9/fill",
+            fragment: "d3d9/fill",
+        },
+        ProgramKind::Raster {
+            vertex: "d3d9/tile",
+            fragment: "d3d9/tile",
+        },
+        ProgramKind::Raster {
+            vertex: "d3d9/tile_clip_combine",
+            fragment: "d3d9/tile_clip_combine",
+        },
+        ProgramKind::Raster {
+            vertex: "d3d9/tile_clip_copy",
+            fragment: "d3d9/tile_clip_copy",
+        },
+        ProgramKind::Raster {
+            vertex: "d3d9/tile_copy",
+            fragment: "d3d9/tile_copy",
+        },
+        ProgramKind::Raster {
+            vertex: "debug/solid",
+            fragment: "debug/solid",
+        },
+        ProgramKind::Raster {
+            vertex: "debug/texture",
+            fragment: "debug/texture",
+        },
+        ProgramKind::Raster {
+            vertex: "demo_ground",
+            fragment: "demo_ground",
+        },
+    ];
+
+    #[test]
+    fn test_create_every_program() {
+        let resources = EmbeddedResourceLoader::new();
+        let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+        for program in &PROGRAMS {
+            let shaders = match *program {
+                ProgramKind::Raster { vertex, fragment } => {
+                    vec![
+                        (vertex, ShaderKind::Vertex),
+                        (fragment, ShaderKind::Fragment),
+                    ]
+                }
+                ProgramKind::Compute(compute) => vec![(compute, ShaderKind::Compute)],
+            };
+            for (name, kind) in shaders {
+                let (module, reflection) = load_shader(&resources, name, kind, true)
+                    .unwrap_or_else(|error| panic!("{}", error));
+                if let Err(error) = validator.validate(&module) {
+                    panic!(
+                        "{:?} shader `{}` is invalid: {:?}",
+                        kind,
+                        name,
+                        error.into_inner()
+                    );
+                }
+                for (texture_name, texture_info) in &reflection.textures {
+                    assert!(
+                        texture_info.sampler_binding.is_some(),
+                        "Texture `{}` in {:?} shader `{}` has no sampler!",
+                        texture_name,
+                        kind,
+                        name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_shaders_are_rejected_with_an_error() {
+        let resources = EmbeddedResourceLoader::new();
+        assert!(load_shader(&resources, "d3d11/bin", ShaderKind::Compute, true).is_err());
+    }
+}
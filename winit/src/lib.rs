@@ -0,0 +1,303 @@
+// pathfinder/winit/src/lib.rs
+//
+// Copyright © 2021 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Opens a `winit` window that Pathfinder can render into.
+//!
+//! `PathfinderWindow` creates the window, an OpenGL context and surface for it via `surfman`, a
+//! `Renderer`, and a `SceneProxy`. It keeps the framebuffer in sync with the window's size and
+//! HiDPI factor when it's given the window's events, so an app only has to supply scenes and call
+//! `render()`.
+
+use euclid::default::Size2D;
+use pathfinder_geometry::vector::{vec2f, vec2i, Vector2F, Vector2I};
+use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_renderer::concurrent::rayon::RayonExecutor;
+use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
+use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererMode, RendererOptions};
+use pathfinder_renderer::gpu::renderer::Renderer;
+use pathfinder_renderer::options::BuildOptions;
+use pathfinder_renderer::scene::Scene;
+use pathfinder_resources::ResourceLoader;
+use surfman::{Connection, Context, ContextAttributeFlags, ContextAttributes, Device};
+use surfman::{GLVersion as SurfmanGLVersion, SurfaceAccess, SurfaceType};
+use winit::dpi::LogicalSize;
+use winit::{Event, EventsLoop, Window, WindowBuilder, WindowEvent};
+
+/// Options for creating a `PathfinderWindow`.
+pub struct WindowOptions {
+    /// The title of the window.
+    pub title: String,
+    /// The initial size of the window, in logical pixels.
+    pub size: Vector2I,
+    /// Whether to request the high-performance GPU on a multi-GPU system. By default, the
+    /// low-power integrated GPU is used.
+    pub high_performance_gpu: bool,
+    /// Options for the renderer. The `dest` field is ignored; the renderer always draws to the
+    /// whole window.
+    pub renderer_options: RendererOptions<GLDevice>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> WindowOptions {
+        WindowOptions {
+            title: "Pathfinder".to_owned(),
+            size: vec2i(640, 480),
+            high_performance_gpu: false,
+            renderer_options: RendererOptions::default(),
+        }
+    }
+}
+
+/// A `winit` window with a Pathfinder renderer attached to it.
+pub struct PathfinderWindow {
+    window: Window,
+    #[allow(dead_code)]
+    connection: Connection,
+    device: Device,
+    context: Context,
+    renderer: Renderer<GLDevice>,
+    scene_proxy: SceneProxy,
+    hidpi_factor: f32,
+}
+
+impl PathfinderWindow {
+    /// Opens a window on the given event loop and creates an OpenGL 3.x context and a renderer
+    /// for it.
+    pub fn new(
+        event_loop: &EventsLoop,
+        options: WindowOptions,
+        resource_loader: &dyn ResourceLoader,
+    ) -> PathfinderWindow {
+        let logical_size = LogicalSize::new(options.size.x() as f64, options.size.y() as f64);
+        let window = WindowBuilder::new()
+            .with_title(options.title)
+            .with_dimensions(logical_size)
+            .build(event_loop)
+            .unwrap();
+        window.show();
+
+        let connection = Connection::from_winit_window(&window).unwrap();
+        let native_widget = connection
+            .create_native_widget_from_winit_window(&window)
+            .unwrap();
+        let adapter = if options.high_performance_gpu {
+            connection.create_hardware_adapter().unwrap()
+        } else {
+            connection.create_low_power_adapter().unwrap()
+        };
+        let mut device = connection.create_device(&adapter).unwrap();
+
+        // Request an OpenGL 3.x context. Pathfinder requires this.
+        let context_attributes = ContextAttributes {
+            version: SurfmanGLVersion::new(3, 0),
+            flags: ContextAttributeFlags::ALPHA,
+        };
+        let context_descriptor = device
+            .create_context_descriptor(&context_attributes)
+            .unwrap();
+
+        let surface_type = SurfaceType::Widget { native_widget };
+        let mut context = device.create_context(&context_descriptor).unwrap();
+        let surface = device
+            .create_surface(&context, SurfaceAccess::GPUOnly, surface_type)
+            .unwrap();
+        device
+            .bind_surface_to_context(&mut context, surface)
+            .unwrap();
+        device.make_context_current(&context).unwrap();
+        gl::load_with(|symbol_name| device.get_proc_address(&context, symbol_name));
+
+        let hidpi_factor = window.get_hidpi_factor() as f32;
+        let framebuffer_size = (options.size.to_f32() * hidpi_factor).to_i32();
+
+        let default_framebuffer = default_framebuffer(&device, &context);
+        let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
+        let mode = RendererMode::default_for_device(&pathfinder_device);
+        let renderer_options = RendererOptions {
+            dest: DestFramebuffer::full_window(framebuffer_size),
+            ..options.renderer_options
+        };
+        let renderer = Renderer::new(pathfinder_device, resource_loader, mode, renderer_options);
+
+        let scene_proxy = SceneProxy::new(renderer.mode().level, RayonExecutor);
+
+        PathfinderWindow {
+            window,
+            connection,
+            device,
+            context,
+            renderer,
+            scene_proxy,
+            hidpi_factor,
+        }
+    }
+
+    /// Updates the framebuffer if the event resized the window or changed its HiDPI factor.
+    ///
+    /// Returns true if the window needs to be redrawn in response to the event.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } => {
+                self.resize();
+                true
+            }
+            Event::WindowEvent {
+                event: WindowEvent::HiDpiFactorChanged(hidpi_factor),
+                ..
+            } => {
+                self.hidpi_factor = hidpi_factor as f32;
+                self.resize();
+                true
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Refresh,
+                ..
+            } => true,
+            _ => false,
+        }
+    }
+
+    /// Resizes the surface and the renderer's destination to the window's current size.
+    ///
+    /// `handle_event()` calls this automatically; call it directly only if you resize the window
+    /// yourself and don't forward its events.
+    pub fn resize(&mut self) {
+        let framebuffer_size = (self.logical_size() * self.hidpi_factor).to_i32();
+        if framebuffer_size == self.framebuffer_size() {
+            return;
+        }
+
+        let mut surface = self
+            .device
+            .unbind_surface_from_context(&mut self.context)
+            .unwrap()
+            .unwrap();
+        let surface_size = framebuffer_size.max(vec2i(1, 1));
+        self.device
+            .resize_surface(
+                &self.context,
+                &mut surface,
+                Size2D::new(surface_size.x(), surface_size.y()),
+            )
+            .unwrap();
+        self.device
+            .bind_surface_to_context(&mut self.context, surface)
+            .unwrap();
+
+        let default_framebuffer = default_framebuffer(&self.device, &self.context);
+        self.renderer
+            .device_mut()
+            .set_default_framebuffer(default_framebuffer);
+        self.renderer.options_mut().dest = DestFramebuffer::full_window(framebuffer_size);
+        self.renderer.dest_framebuffer_size_changed();
+    }
+
+    /// Replaces the scene that `render()` draws.
+    ///
+    /// The scene should be in device pixels; multiply logical coordinates by `hidpi_factor()`.
+    #[inline]
+    pub fn set_scene(&mut self, scene: Scene) {
+        self.scene_proxy.replace_scene(scene);
+    }
+
+    /// Builds and renders the current scene, and presents it to the window.
+    pub fn render(&mut self, options: BuildOptions) {
+        self.device.make_context_current(&self.context).unwrap();
+        self.scene_proxy
+            .build_and_render(&mut self.renderer, options);
+        self.present();
+    }
+
+    /// Presents whatever the renderer has drawn to the window.
+    ///
+    /// `render()` calls this automatically. Use it if you drive the renderer yourself through
+    /// `renderer_mut()`.
+    pub fn present(&mut self) {
+        let mut surface = self
+            .device
+            .unbind_surface_from_context(&mut self.context)
+            .unwrap()
+            .unwrap();
+        self.device
+            .present_surface(&mut self.context, &mut surface)
+            .unwrap();
+        self.device
+            .bind_surface_to_context(&mut self.context, surface)
+            .unwrap();
+    }
+
+    /// Returns the size of the window's contents, in logical pixels.
+    pub fn logical_size(&self) -> Vector2F {
+        match self.window.get_inner_size() {
+            Some(size) => vec2f(size.width as f32, size.height as f32),
+            None => self.framebuffer_size().to_f32() * (1.0 / self.hidpi_factor),
+        }
+    }
+
+    /// Returns the size of the framebuffer, in device pixels.
+    #[inline]
+    pub fn framebuffer_size(&self) -> Vector2I {
+        self.renderer
+            .options()
+            .dest
+            .window_size(self.renderer.device())
+    }
+
+    /// Returns the ratio of device pixels to logical pixels.
+    #[inline]
+    pub fn hidpi_factor(&self) -> f32 {
+        self.hidpi_factor
+    }
+
+    /// Returns the underlying `winit` window.
+    #[inline]
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Returns the scene proxy that `render()` builds from.
+    #[inline]
+    pub fn scene_proxy(&self) -> &SceneProxy {
+        &self.scene_proxy
+    }
+
+    #[inline]
+    pub fn scene_proxy_mut(&mut self) -> &mut SceneProxy {
+        &mut self.scene_proxy
+    }
+
+    /// Returns the renderer, for example to change its options.
+    #[inline]
+    pub fn renderer(&self) -> &Renderer<GLDevice> {
+        &self.renderer
+    }
+
+    #[inline]
+    pub fn renderer_mut(&mut self) -> &mut Renderer<GLDevice> {
+        &mut self.renderer
+    }
+}
+
+impl Drop for PathfinderWindow {
+    fn drop(&mut self) {
+        drop(self.device.destroy_context(&mut self.context));
+    }
+}
+
+fn default_framebuffer(device: &Device, context: &Context) -> gl::types::GLuint {
+    device
+        .context_surface_info(context)
+        .unwrap()
+        .unwrap()
+        .framebuffer_object
+}